@@ -0,0 +1,105 @@
+//! Splitting an animation into frames.
+//!
+//! An animation is ANSI art with a marker line in front of each frame:
+//!
+//! ```text
+//! @@ 200
+//! ...the first frame, shown for 200 ms...
+//! @@
+//! ...the second frame, also shown for 200 ms...
+//! @@ 50
+//! ...the third frame, shown for 50 ms...
+//! ```
+//!
+//! A marker is `@@` at the start of a line, then how long to show the frame
+//! in milliseconds. Leave the time out to keep the one before. Anything in
+//! front of the first marker is a frame too, unless it's only blank lines.
+
+/// How long we show a frame, if the file never says
+pub const DEFAULT_DELAY_MS: u32 = 100;
+
+/// What starts a marker line
+const MARKER: &[u8] = b"@@";
+
+/// One picture in the animation
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Frame<'a> {
+    /// The ANSI art, as code page 437 text with escape sequences
+    pub data: &'a [u8],
+    /// How long to show it, in milliseconds
+    pub delay_ms: u32,
+}
+
+/// Goes through the frames in an animation
+#[derive(Debug, Clone)]
+pub struct Frames<'a> {
+    data: &'a [u8],
+    /// Where the next frame, or its marker, starts
+    pos: usize,
+    delay_ms: u32,
+}
+
+impl<'a> Frames<'a> {
+    /// Split up an animation
+    pub fn new(data: &'a [u8]) -> Frames<'a> {
+        Frames {
+            data,
+            pos: 0,
+            delay_ms: DEFAULT_DELAY_MS,
+        }
+    }
+
+    /// Is there a marker line starting here?
+    fn is_marker(&self, pos: usize) -> bool {
+        self.data[pos..].starts_with(MARKER) && (pos == 0 || self.data[pos - 1] == b'\n')
+    }
+
+    /// Read the marker line starting here, and return where the line after
+    /// it starts
+    fn read_marker(&mut self, pos: usize) -> usize {
+        let rest = &self.data[pos + MARKER.len()..];
+        let line_len = rest
+            .iter()
+            .position(|&b| b == b'\n')
+            .map_or(rest.len(), |n| n + 1);
+        let number = rest[0..line_len]
+            .iter()
+            .skip_while(|b| **b == b' ')
+            .take_while(|b| b.is_ascii_digit())
+            .fold(None, |acc: Option<u32>, b| {
+                Some(acc.unwrap_or(0).saturating_mul(10) + u32::from(b - b'0'))
+            });
+        if let Some(delay_ms) = number {
+            self.delay_ms = delay_ms;
+        }
+        pos + MARKER.len() + line_len
+    }
+}
+
+impl<'a> Iterator for Frames<'a> {
+    type Item = Frame<'a>;
+
+    fn next(&mut self) -> Option<Frame<'a>> {
+        while self.pos < self.data.len() {
+            let start = if self.is_marker(self.pos) {
+                self.read_marker(self.pos)
+            } else {
+                self.pos
+            };
+            let end = (start..self.data.len())
+                .find(|&pos| self.is_marker(pos))
+                .unwrap_or(self.data.len());
+            self.pos = end;
+            let data = &self.data[start..end];
+            // Blank lines before the first marker don't count
+            if start == 0 && data.iter().all(|b| b.is_ascii_whitespace()) {
+                continue;
+            }
+            return Some(Frame {
+                data,
+                delay_ms: self.delay_ms,
+            });
+        }
+        None
+    }
+}
@@ -0,0 +1,210 @@
+//! Application logic for the ANSI animation player
+//!
+//! Each frame is sent to the console as it is, starting from the top left
+//! corner and drawing over the one before, like flipping through the pages
+//! of a flip book. Frames that want a clean screen can start with `ESC[2J`.
+//! See [`frames`] for how the frames are marked out in the file.
+
+#![no_std]
+#![deny(missing_docs)]
+#![deny(unsafe_code)]
+
+use neotron_input::{KeyEvent, Keyboard};
+use neotron_sdk::console;
+
+use ansiview::{cp437, sauce::Sauce};
+
+pub mod frames;
+
+use frames::Frames;
+
+/// The biggest file we can play
+const MAX_FILE: usize = 64 * 1024;
+
+/// How often we check the keyboard while a frame is showing
+const TICK_MS: u32 = 10;
+
+/// The speeds we step through with `+` and `-`, as a percentage
+const SPEEDS: [u32; 7] = [25, 50, 75, 100, 150, 200, 400];
+
+/// The speed we start at
+const NORMAL_SPEED: usize = 3;
+
+/// The end of the picture, for files with a SAUCE record
+const END_OF_FILE: u8 = 0x1A;
+
+/// What happened while we were waiting
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Wait {
+    /// The time ran out
+    Done,
+    /// Start again from the first frame
+    Restart,
+    /// Stop
+    Quit,
+}
+
+/// Plays ANSI animations
+pub struct Player {
+    stdout: neotron_sdk::File,
+    keyboard: Keyboard,
+    data: [u8; MAX_FILE],
+    /// How much of `data` is the animation
+    len: usize,
+    /// Which of [`SPEEDS`] we're playing at
+    speed: usize,
+    /// Go back to the start after the last frame?
+    looping: bool,
+    paused: bool,
+}
+
+impl Player {
+    /// Make a new player
+    pub const fn new() -> Player {
+        Player {
+            stdout: neotron_sdk::stdout(),
+            keyboard: Keyboard::new(),
+            data: [0; MAX_FILE],
+            len: 0,
+            speed: NORMAL_SPEED,
+            looping: false,
+            paused: false,
+        }
+    }
+
+    /// Go back to the start after the last frame, or not
+    pub fn set_looping(&mut self, looping: bool) {
+        self.looping = looping;
+    }
+
+    /// Play at this percentage of the speed the file asks for. We pick the
+    /// nearest one that `+` and `-` can step to.
+    pub fn set_speed(&mut self, percent: u32) {
+        self.speed = SPEEDS
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, speed)| speed.abs_diff(percent))
+            .map_or(NORMAL_SPEED, |(idx, _)| idx);
+    }
+
+    /// Load an animation.
+    ///
+    /// Returns how many frames it has.
+    pub fn load_file(&mut self, filename: &str) -> Result<usize, neotron_sdk::Error> {
+        self.len = 0;
+        let path = neotron_sdk::path::Path::new(filename)?;
+        let f = neotron_sdk::File::open(path, neotron_sdk::Flags::empty())?;
+        while self.len < MAX_FILE {
+            let n = f.read(&mut self.data[self.len..])?;
+            if n == 0 {
+                break;
+            }
+            self.len += n;
+        }
+        // Leave out the SAUCE record, and anything after the end marker
+        let data = &self.data[0..self.len];
+        let end = Sauce::find(data).map_or(data.len(), |sauce| sauce.start());
+        self.len = data[0..end]
+            .iter()
+            .position(|&b| b == END_OF_FILE)
+            .unwrap_or(end);
+        Ok(Frames::new(&self.data[0..self.len]).count())
+    }
+
+    /// Play the animation until it ends, or the user quits.
+    ///
+    /// Once the last frame has shown, if we're not looping, it stays on the
+    /// screen until a key is pressed. `r` plays it again.
+    pub fn run(&mut self) {
+        console::cursor_off(&mut self.stdout);
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        console::clear_screen(&mut self.stdout);
+        'again: loop {
+            // Finding each frame afresh is quick enough, and leaves the keys
+            // free to change things between frames
+            let mut index = 0;
+            while let Some(frame) = Frames::new(&self.data[0..self.len]).nth(index) {
+                console::move_cursor(&mut self.stdout, console::Position::origin());
+                write_frame(&self.stdout, frame.data);
+                match self.wait(frame.delay_ms) {
+                    Wait::Done => index += 1,
+                    Wait::Restart => continue 'again,
+                    Wait::Quit => break 'again,
+                }
+            }
+            if !self.looping {
+                match self.keyboard.wait() {
+                    KeyEvent::Char('r' | 'R') => continue 'again,
+                    _ => break 'again,
+                }
+            }
+        }
+        self.close();
+    }
+
+    /// Put the screen back how we found it
+    fn close(&mut self) {
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        console::clear_screen(&mut self.stdout);
+        console::move_cursor(&mut self.stdout, console::Position::origin());
+        console::cursor_on(&mut self.stdout);
+    }
+
+    /// Show a frame for as long as it asks, at the current speed, dealing
+    /// with any keys pressed in the meantime
+    fn wait(&mut self, delay_ms: u32) -> Wait {
+        let mut waited_ms = 0;
+        loop {
+            match self.keyboard.read() {
+                Some(KeyEvent::Char('q' | 'Q') | KeyEvent::Escape) => return Wait::Quit,
+                Some(KeyEvent::Char('r' | 'R')) => return Wait::Restart,
+                Some(KeyEvent::Char(' ')) => self.paused = !self.paused,
+                Some(KeyEvent::Char('l' | 'L')) => self.looping = !self.looping,
+                Some(KeyEvent::Char('+' | '=')) => {
+                    self.speed = (self.speed + 1).min(SPEEDS.len() - 1)
+                }
+                Some(KeyEvent::Char('-' | '_')) => self.speed = self.speed.saturating_sub(1),
+                Some(KeyEvent::Char('.')) if self.paused => return Wait::Done,
+                _ => {}
+            }
+            // Faster speeds mean shorter waits
+            if !self.paused && waited_ms * SPEEDS[self.speed] >= delay_ms * 100 {
+                return Wait::Done;
+            }
+            neotron_sdk::delay(core::time::Duration::from_millis(u64::from(TICK_MS)));
+            if !self.paused {
+                waited_ms += TICK_MS;
+            }
+        }
+    }
+}
+
+impl Default for Player {
+    fn default() -> Self {
+        Player::new()
+    }
+}
+
+/// Send a frame to the console. Escape sequences and line endings go as
+/// they are, and everything else is turned from code page 437 into
+/// Unicode.
+fn write_frame(stdout: &neotron_sdk::File, data: &[u8]) {
+    let mut buffer = [0u8; 256];
+    let mut len = 0;
+    for &byte in data {
+        if len + 4 > buffer.len() {
+            let _ = stdout.write(&buffer[0..len]);
+            len = 0;
+        }
+        match byte {
+            0x1B | b'\r' | b'\n' | b'\t' | 0x08 | 0x20..=0x7E => {
+                buffer[len] = byte;
+                len += 1;
+            }
+            _ => {
+                len += cp437::to_char(byte).encode_utf8(&mut buffer[len..]).len();
+            }
+        }
+    }
+    let _ = stdout.write(&buffer[0..len]);
+}
@@ -0,0 +1,73 @@
+#![cfg_attr(target_os = "none", no_std)]
+#![cfg_attr(target_os = "none", no_main)]
+
+use core::{fmt::Write, ptr::addr_of_mut};
+
+use neotron_args::{Item, Opt};
+
+#[cfg(not(target_os = "none"))]
+fn main() {
+    neotron_sdk::init();
+}
+
+static mut PLAYER: ansianim::Player = ansianim::Player::new();
+
+#[no_mangle]
+extern "C" fn neotron_main() -> i32 {
+    neotron_error::exit_code(real_main())
+}
+
+/// The options we understand
+static SPEC: neotron_args::Spec = neotron_args::Spec {
+    name: "ansianim",
+    usage: "[options] <file.ans>",
+    about: "Plays ANSI art with several frames, one after the other. While \
+            it plays, Space pauses, . steps a frame, + and - change the \
+            speed, l turns looping on and off, r starts again and q quits.",
+    options: &[
+        Opt::flag('l', "loop", "Go back to the start after the last frame"),
+        Opt::value(
+            's',
+            "speed",
+            "percent",
+            "Play faster or slower, from 25% to 400%",
+        ),
+    ],
+};
+
+fn real_main() -> Result<(), neotron_sdk::Error> {
+    let player = unsafe { &mut *addr_of_mut!(PLAYER) };
+    let mut filename = None;
+    let mut args = neotron_args::Parser::new(&SPEC);
+    loop {
+        let item = match args.next_item() {
+            Ok(Some(item)) => item,
+            Ok(None) => break,
+            Err(neotron_args::Error::Help) => return Ok(()),
+            Err(e) => return Err(e.into()),
+        };
+        match item {
+            Item::Flag("loop") => player.set_looping(true),
+            Item::Value("speed", value) => {
+                let text = value.as_str().trim_end_matches('%');
+                let percent: u32 = args.parse("speed", text)?;
+                player.set_speed(percent);
+            }
+            Item::Positional(value) if filename.is_none() => filename = Some(value),
+            Item::Positional(_) => {
+                return Err(args.usage_error("only one animation at a time").into());
+            }
+            _ => {}
+        }
+    }
+    let Some(filename) = filename else {
+        return Err(args.usage_error("no animation to play").into());
+    };
+    if player.load_file(filename.as_str())? == 0 {
+        let mut stdout = neotron_sdk::stdout();
+        let _ = writeln!(stdout, "{}: nothing to play", filename.as_str());
+        return Ok(());
+    }
+    player.run();
+    Ok(())
+}
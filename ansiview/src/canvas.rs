@@ -0,0 +1,382 @@
+//! Where the picture is drawn.
+//!
+//! ANSI art is a stream of characters and escape sequences which moves a
+//! cursor about an 80 column screen. Rather than send it to the console as it
+//! is, we play it onto a canvas which is much taller than the screen. That
+//! way we can scroll back through a long picture, and we only have to
+//! understand the handful of sequences that art packages actually produce.
+
+/// The widest picture we can draw
+pub const MAX_WIDTH: usize = 80;
+
+/// The tallest picture we can draw
+pub const MAX_ROWS: usize = 500;
+
+/// The most parameters we keep from one escape sequence
+const MAX_PARAMS: usize = 8;
+
+/// The byte which marks the end of the picture (Ctrl+Z)
+const END_OF_FILE: u8 = 0x1A;
+
+/// One character of the picture
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Cell {
+    /// The character, in code page 437
+    pub ch: u8,
+    /// The colours
+    pub pen: Pen,
+}
+
+impl Cell {
+    /// An empty cell
+    const BLANK: Cell = Cell {
+        ch: b' ',
+        pen: Pen::DEFAULT,
+    };
+}
+
+/// The colours a character is drawn in.
+///
+/// We pack these into a byte the same way the PC's text mode does, so the
+/// canvas doesn't take up too much memory.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Pen(u8);
+
+impl Pen {
+    /// Light grey on black, which is where every picture starts
+    pub const DEFAULT: Pen = Pen(0x07);
+
+    /// Bright foreground
+    const BOLD: u8 = 0x08;
+
+    /// Blinking (or, with iCE colours, a bright background)
+    const BLINK: u8 = 0x80;
+
+    /// The foreground colour, 0 to 7, in ANSI order
+    pub fn fg(self) -> u8 {
+        self.0 & 0x07
+    }
+
+    /// The background colour, 0 to 7, in ANSI order
+    pub fn bg(self) -> u8 {
+        (self.0 >> 4) & 0x07
+    }
+
+    /// Is the foreground the bright version of the colour?
+    pub fn bold(self) -> bool {
+        self.0 & Self::BOLD != 0
+    }
+
+    /// Should the character blink?
+    pub fn blink(self) -> bool {
+        self.0 & Self::BLINK != 0
+    }
+
+    /// Change the foreground colour
+    fn set_fg(&mut self, fg: u8) {
+        self.0 = (self.0 & !0x07) | (fg & 0x07);
+    }
+
+    /// Change the background colour
+    fn set_bg(&mut self, bg: u8) {
+        self.0 = (self.0 & !0x70) | ((bg & 0x07) << 4);
+    }
+
+    /// Turn a flag on or off
+    fn set_flag(&mut self, flag: u8, on: bool) {
+        if on {
+            self.0 |= flag;
+        } else {
+            self.0 &= !flag;
+        }
+    }
+}
+
+/// What the escape sequence parser is doing
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum State {
+    /// Drawing characters
+    Ground,
+    /// We've seen an ESC
+    Escape,
+    /// We've seen `ESC [` and are collecting parameters
+    Csi,
+}
+
+/// A picture, and the cursor drawing on it
+pub struct Canvas {
+    cells: [[Cell; MAX_WIDTH]; MAX_ROWS],
+    /// Where we wrap lines
+    width: usize,
+    /// How many rows have been drawn on
+    rows: usize,
+    row: usize,
+    col: usize,
+    saved: (usize, usize),
+    pen: Pen,
+    /// Is the pen swapping its colours round?
+    reverse: bool,
+    state: State,
+    params: [u16; MAX_PARAMS],
+    param_count: usize,
+    /// The rows which have changed since anyone last asked
+    dirty: Option<(usize, usize)>,
+    /// Have we seen the end of the picture?
+    finished: bool,
+    /// Did the picture run off the bottom of the canvas?
+    truncated: bool,
+}
+
+impl Canvas {
+    /// Make a new, empty canvas
+    pub const fn new() -> Canvas {
+        Canvas {
+            cells: [[Cell::BLANK; MAX_WIDTH]; MAX_ROWS],
+            width: MAX_WIDTH,
+            rows: 0,
+            row: 0,
+            col: 0,
+            saved: (0, 0),
+            pen: Pen::DEFAULT,
+            reverse: false,
+            state: State::Ground,
+            params: [0; MAX_PARAMS],
+            param_count: 0,
+            dirty: None,
+            finished: false,
+            truncated: false,
+        }
+    }
+
+    /// Wipe the canvas, ready for a new picture of the given width
+    pub fn reset(&mut self, width: usize) {
+        self.clear();
+        self.width = width.clamp(1, MAX_WIDTH);
+        self.saved = (0, 0);
+        self.pen = Pen::DEFAULT;
+        self.reverse = false;
+        self.state = State::Ground;
+        self.dirty = None;
+        self.finished = false;
+        self.truncated = false;
+    }
+
+    /// Blank out everything we have drawn, and home the cursor
+    fn clear(&mut self) {
+        for row in self.cells[0..self.rows].iter_mut() {
+            row.fill(Cell::BLANK);
+        }
+        if self.rows > 0 {
+            self.mark_dirty(0);
+            self.mark_dirty(self.rows - 1);
+        }
+        self.rows = 0;
+        self.row = 0;
+        self.col = 0;
+    }
+
+    /// How many rows have been drawn on
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Get one row of the picture
+    pub fn row(&self, row: usize) -> &[Cell] {
+        &self.cells[row][0..self.width]
+    }
+
+    /// Which row the cursor is on
+    pub fn cursor_row(&self) -> usize {
+        self.row
+    }
+
+    /// Have we seen the end of the picture?
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Did some of the picture fall off the bottom?
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+
+    /// Get the range of rows which have changed since we were last asked
+    pub fn take_dirty(&mut self) -> Option<core::ops::Range<usize>> {
+        self.dirty.take().map(|(first, last)| first..last + 1)
+    }
+
+    /// Process one byte of the picture
+    pub fn feed(&mut self, byte: u8) {
+        if self.finished {
+            return;
+        }
+        match self.state {
+            State::Ground => match byte {
+                0x1B => self.state = State::Escape,
+                END_OF_FILE => self.finished = true,
+                b'\r' => self.col = 0,
+                b'\n' => self.new_line(),
+                b'\t' => {
+                    self.col = ((self.col / 8 + 1) * 8).min(self.width - 1);
+                }
+                _ => self.put(byte),
+            },
+            State::Escape if byte == b'[' => {
+                self.params = [0; MAX_PARAMS];
+                self.param_count = 0;
+                self.state = State::Csi;
+            }
+            State::Escape => {
+                // Nothing else turns up in art, so draw it like ANSI.SYS would
+                self.state = State::Ground;
+                self.put(0x1B);
+                self.feed(byte);
+            }
+            State::Csi => match byte {
+                b'0'..=b'9' => {
+                    let idx = self.param_count.min(MAX_PARAMS - 1);
+                    self.params[idx] = self.params[idx]
+                        .saturating_mul(10)
+                        .saturating_add(u16::from(byte - b'0'));
+                }
+                b';' => self.param_count += 1,
+                b'?' | b'=' | b'>' | b' ' => {
+                    // Private modes - we collect them, then ignore them
+                }
+                0x40..=0x7E => {
+                    self.param_count = (self.param_count + 1).min(MAX_PARAMS);
+                    self.state = State::Ground;
+                    self.command(byte);
+                }
+                _ => {
+                    // Not a valid sequence, so give up on it
+                    self.state = State::Ground;
+                }
+            },
+        }
+    }
+
+    /// Carry out an escape sequence
+    fn command(&mut self, command: u8) {
+        let count = usize::from(self.params[0].max(1));
+        match command {
+            b'A' => self.row = self.row.saturating_sub(count),
+            b'B' => self.move_to(self.row + count, self.col),
+            b'C' => self.col = (self.col + count).min(self.width - 1),
+            b'D' => self.col = self.col.saturating_sub(count),
+            b'H' | b'f' => {
+                let row = usize::from(self.params[0].max(1)) - 1;
+                let col = usize::from(self.params[1].max(1)) - 1;
+                self.move_to(row, col.min(self.width - 1));
+            }
+            // ANSI.SYS homes the cursor too
+            b'J' if self.params[0] == 2 => self.clear(),
+            b'K' => {
+                let range = match self.params[0] {
+                    0 => self.col..self.width,
+                    1 => 0..self.col + 1,
+                    _ => 0..self.width,
+                };
+                let blank = Cell {
+                    ch: b' ',
+                    pen: self.effective_pen(),
+                };
+                self.cells[self.row][range].fill(blank);
+                self.touch();
+            }
+            b'm' => {
+                for idx in 0..self.param_count {
+                    self.set_attribute(self.params[idx]);
+                }
+            }
+            b's' => self.saved = (self.row, self.col),
+            b'u' => self.move_to(self.saved.0, self.saved.1),
+            _ => {
+                // Modes, scrolling regions, etc. Art doesn't need these.
+            }
+        }
+    }
+
+    /// Handle one SGR parameter
+    fn set_attribute(&mut self, attribute: u16) {
+        match attribute {
+            0 => {
+                self.pen = Pen::DEFAULT;
+                self.reverse = false;
+            }
+            1 => self.pen.set_flag(Pen::BOLD, true),
+            5 | 6 => self.pen.set_flag(Pen::BLINK, true),
+            7 => self.reverse = true,
+            22 => self.pen.set_flag(Pen::BOLD, false),
+            25 => self.pen.set_flag(Pen::BLINK, false),
+            27 => self.reverse = false,
+            30..=37 => self.pen.set_fg((attribute - 30) as u8),
+            39 => self.pen.set_fg(Pen::DEFAULT.fg()),
+            40..=47 => self.pen.set_bg((attribute - 40) as u8),
+            49 => self.pen.set_bg(Pen::DEFAULT.bg()),
+            _ => {}
+        }
+    }
+
+    /// The colours we draw in, allowing for reverse video
+    fn effective_pen(&self) -> Pen {
+        let mut pen = self.pen;
+        if self.reverse {
+            pen.set_fg(self.pen.bg());
+            pen.set_bg(self.pen.fg());
+        }
+        pen
+    }
+
+    /// Draw a character at the cursor, and move along
+    fn put(&mut self, ch: u8) {
+        if self.truncated {
+            return;
+        }
+        self.cells[self.row][self.col] = Cell {
+            ch,
+            pen: self.effective_pen(),
+        };
+        self.touch();
+        self.col += 1;
+        if self.col == self.width {
+            self.new_line();
+        }
+    }
+
+    /// Go to the start of the next line
+    fn new_line(&mut self) {
+        self.move_to(self.row + 1, 0);
+    }
+
+    /// Move the cursor, keeping it on the canvas
+    fn move_to(&mut self, row: usize, col: usize) {
+        if row >= MAX_ROWS {
+            self.truncated = true;
+            self.row = MAX_ROWS - 1;
+        } else {
+            self.row = row;
+        }
+        self.col = col;
+    }
+
+    /// Note that the cursor's row has been drawn on
+    fn touch(&mut self) {
+        self.rows = self.rows.max(self.row + 1);
+        self.mark_dirty(self.row);
+    }
+
+    /// Note that a row has changed
+    fn mark_dirty(&mut self, row: usize) {
+        self.dirty = Some(match self.dirty {
+            Some((first, last)) => (first.min(row), last.max(row)),
+            None => (row, row),
+        });
+    }
+}
+
+impl Default for Canvas {
+    fn default() -> Self {
+        Canvas::new()
+    }
+}
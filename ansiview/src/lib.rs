@@ -0,0 +1,601 @@
+//! Application logic for the ANSI art viewer
+
+#![no_std]
+#![deny(missing_docs)]
+#![deny(unsafe_code)]
+
+use core::fmt::Write;
+
+use neotron_input::{KeyEvent, Keyboard};
+use neotron_sdk::console;
+
+pub mod canvas;
+pub mod cp437;
+pub mod sauce;
+
+use canvas::{Canvas, Pen};
+use sauce::Sauce;
+
+/// The biggest file we can show
+const MAX_FILE: usize = 64 * 1024;
+
+/// How long we wait between chunks of the picture when replaying it
+const TICK_MS: u32 = 20;
+
+/// The speeds we step through with `+` and `-`. Zero means as fast as we can.
+const SPEEDS: [u32; 8] = [300, 1200, 2400, 9600, 19200, 38400, 115200, 0];
+
+/// The speed we replay pictures at, if you don't pick one
+pub const DEFAULT_BAUD: u32 = 9600;
+
+/// What the user wants to do after looking at a picture
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Action {
+    /// Show the next file
+    Next,
+    /// Show the previous file
+    Previous,
+    /// Stop
+    Quit,
+}
+
+/// What the help screen says
+const HELP: &[&str] = &[
+    "While a picture is playing:",
+    "  Space, Enter      Skip to the end",
+    "  +, -              Play faster / slower",
+    "",
+    "Once it has finished:",
+    "  Down, Up          Scroll one line",
+    "  PgDn, PgUp        Scroll one screen",
+    "  Home, End         Go to the top / bottom",
+    "  Space             Scroll down, or go to the next file at the bottom",
+    "  r                 Play it again",
+    "  i                 Show the title, artist, and comments",
+    "",
+    "Any time:",
+    "  n, p              Next / previous file",
+    "  q                 Quit",
+];
+
+/// Represents the ANSI art viewer
+pub struct Viewer {
+    width: u8,
+    height: u8,
+    stdout: neotron_sdk::File,
+    keyboard: Keyboard,
+    data: [u8; MAX_FILE],
+    data_len: usize,
+    /// Where the picture stops and the metadata starts
+    content_len: usize,
+    sauce: Option<Sauce>,
+    canvas: Canvas,
+    /// What we call the file in the status bar
+    name: [u8; Self::MAX_NAME],
+    name_len: usize,
+    /// Which file this is, and how many there are
+    position: (usize, usize),
+    /// The canvas row at the top of the screen
+    top: usize,
+    /// How fast we replay the picture, in bits per second
+    baud: u32,
+    /// Are we part way through replaying the picture?
+    playing: bool,
+    /// A message to show in the status bar, instead of the usual info
+    message: Option<&'static str>,
+}
+
+impl Viewer {
+    /// The longest name we show in the status bar
+    const MAX_NAME: usize = 32;
+
+    /// Make a new viewer.
+    ///
+    /// You can give the screen size in characters.
+    pub const fn new(width: u8, height: u8) -> Viewer {
+        Viewer {
+            width,
+            height,
+            stdout: neotron_sdk::stdout(),
+            keyboard: Keyboard::new(),
+            data: [0; MAX_FILE],
+            data_len: 0,
+            content_len: 0,
+            sauce: None,
+            canvas: Canvas::new(),
+            name: [0; Self::MAX_NAME],
+            name_len: 0,
+            position: (1, 1),
+            top: 0,
+            baud: DEFAULT_BAUD,
+            playing: false,
+            message: None,
+        }
+    }
+
+    /// Set how fast we replay pictures, in bits per second. Zero means as
+    /// fast as we can.
+    pub fn set_baud(&mut self, baud: u32) {
+        self.baud = baud;
+    }
+
+    /// Load a picture
+    pub fn load_file(&mut self, filename: &str) -> Result<(), neotron_sdk::Error> {
+        let len = filename.len().min(Self::MAX_NAME);
+        self.name[0..len].copy_from_slice(&filename.as_bytes()[0..len]);
+        self.name_len = len;
+        self.data_len = 0;
+        let path = neotron_sdk::path::Path::new(filename)?;
+        let f = neotron_sdk::File::open(path, neotron_sdk::Flags::empty())?;
+        while self.data_len < MAX_FILE {
+            let n = f.read(&mut self.data[self.data_len..])?;
+            if n == 0 {
+                break;
+            }
+            self.data_len += n;
+        }
+        let data = &self.data[0..self.data_len];
+        self.sauce = Sauce::find(data);
+        self.content_len = self.sauce.as_ref().map_or(data.len(), Sauce::start);
+        self.message = if self.data_len == MAX_FILE {
+            Some("File too big - only showing the start")
+        } else if self.sauce.as_ref().and_then(Sauce::width).unwrap_or(0) > canvas::MAX_WIDTH {
+            Some("This picture is wider than the screen")
+        } else {
+            None
+        };
+        Ok(())
+    }
+
+    /// Say which file this is, out of how many, for the status bar
+    pub fn set_position(&mut self, index: usize, count: usize) {
+        self.position = (index + 1, count);
+    }
+
+    /// Play the picture, then let the user scroll around it
+    pub fn run(&mut self) -> Action {
+        console::cursor_off(&mut self.stdout);
+        loop {
+            if let Some(action) = self.replay() {
+                return action;
+            }
+            if let Some(action) = self.browse() {
+                return action;
+            }
+        }
+    }
+
+    /// Put the screen back how we found it
+    pub fn close(&mut self) {
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        console::clear_screen(&mut self.stdout);
+        console::move_cursor(&mut self.stdout, console::Position::origin());
+        console::cursor_on(&mut self.stdout);
+    }
+
+    /// Feed the picture onto the canvas, as if it was coming down a modem.
+    ///
+    /// Returns early if the user asked for another file.
+    fn replay(&mut self) -> Option<Action> {
+        let width = self
+            .sauce
+            .as_ref()
+            .and_then(Sauce::width)
+            .unwrap_or(canvas::MAX_WIDTH);
+        self.canvas.reset(width);
+        self.top = 0;
+        self.playing = true;
+        self.draw();
+        let mut played = 0;
+        // Thousandths of a byte we are owed, carried between ticks
+        let mut credit: u32 = 0;
+        let mut skip = false;
+        while played < self.content_len && !self.canvas.is_finished() {
+            while let Some(key) = self.keyboard.read() {
+                let action = match key {
+                    KeyEvent::Char(' ') | KeyEvent::Enter => {
+                        skip = true;
+                        None
+                    }
+                    KeyEvent::Char('+' | '=') => {
+                        self.change_speed(true);
+                        None
+                    }
+                    KeyEvent::Char('-' | '_') => {
+                        self.change_speed(false);
+                        None
+                    }
+                    KeyEvent::Char('n' | 'N') => Some(Action::Next),
+                    KeyEvent::Char('p' | 'P') => Some(Action::Previous),
+                    KeyEvent::Char('q' | 'Q') => Some(Action::Quit),
+                    _ => None,
+                };
+                if action.is_some() {
+                    self.playing = false;
+                    return action;
+                }
+            }
+            let count = if skip || self.baud == 0 {
+                self.content_len - played
+            } else {
+                // Ten bits go down the line for each byte
+                credit += self.baud / 10 * TICK_MS;
+                let count = (credit / 1000) as usize;
+                credit %= 1000;
+                count.min(self.content_len - played)
+            };
+            for &byte in &self.data[played..played + count] {
+                self.canvas.feed(byte);
+            }
+            played += count;
+            self.playing = played < self.content_len && !self.canvas.is_finished();
+            if skip {
+                // Show it from the top
+                self.top = 0;
+                self.draw();
+            } else {
+                self.follow_cursor();
+            }
+            if !skip && self.baud != 0 {
+                neotron_sdk::delay(core::time::Duration::from_millis(u64::from(TICK_MS)));
+            }
+        }
+        if self.canvas.is_truncated() {
+            self.message = Some("Picture too long - only showing the start");
+        }
+        None
+    }
+
+    /// Scroll to keep the cursor on screen, and draw whatever has changed
+    fn follow_cursor(&mut self) {
+        let rows = self.picture_rows();
+        let cursor = self.canvas.cursor_row();
+        let top = if cursor >= self.top + rows {
+            cursor + 1 - rows
+        } else if cursor < self.top {
+            cursor
+        } else {
+            self.top
+        };
+        if top != self.top {
+            self.top = top;
+            self.draw();
+            return;
+        }
+        if let Some(dirty) = self.canvas.take_dirty() {
+            let visible = self.top..self.top + rows;
+            for row in dirty.start.max(visible.start)..dirty.end.min(visible.end) {
+                self.draw_row(row);
+            }
+            self.draw_status();
+        }
+    }
+
+    /// Let the user scroll around the finished picture.
+    ///
+    /// Returns `None` if they want to see it played again.
+    fn browse(&mut self) -> Option<Action> {
+        loop {
+            self.draw();
+            let key = self.keyboard.wait();
+            self.message = None;
+            let page = self.picture_rows();
+            match key {
+                KeyEvent::Char('q' | 'Q') => return Some(Action::Quit),
+                KeyEvent::Char('n' | 'N') => return Some(Action::Next),
+                KeyEvent::Char('p' | 'P') => return Some(Action::Previous),
+                KeyEvent::Char('r' | 'R') => return None,
+                KeyEvent::Char(' ') if self.top >= self.last_top() => return Some(Action::Next),
+                KeyEvent::Char(' ' | 'f') | KeyEvent::PageDown => self.top += page - 1,
+                KeyEvent::Char('b') | KeyEvent::PageUp => {
+                    self.top = self.top.saturating_sub(page - 1)
+                }
+                KeyEvent::Char('j') | KeyEvent::Enter | KeyEvent::Down => self.top += 1,
+                KeyEvent::Char('k') | KeyEvent::Up => self.top = self.top.saturating_sub(1),
+                KeyEvent::Char('g' | '<') | KeyEvent::Home => self.top = 0,
+                KeyEvent::Char('G' | '>') | KeyEvent::End => self.top = self.last_top(),
+                KeyEvent::Char('+' | '=') => self.change_speed(true),
+                KeyEvent::Char('-' | '_') => self.change_speed(false),
+                KeyEvent::Char('i' | 'I') => self.show_info(),
+                KeyEvent::Char('h' | 'H' | '?') => self.show_help(),
+                _ => {}
+            }
+            self.top = self.top.min(self.last_top());
+        }
+    }
+
+    /// Step to the next speed up or down the list
+    fn change_speed(&mut self, faster: bool) {
+        let current = SPEEDS
+            .iter()
+            .position(|&speed| speed == self.baud)
+            .unwrap_or(SPEEDS.len() - 1);
+        self.baud = if faster {
+            SPEEDS[(current + 1).min(SPEEDS.len() - 1)]
+        } else {
+            SPEEDS[current.saturating_sub(1)]
+        };
+        self.draw_status();
+    }
+
+    /// How many rows of the picture fit on the screen (leaving room for the
+    /// status bar)
+    fn picture_rows(&self) -> usize {
+        usize::from(self.height).saturating_sub(1).max(1)
+    }
+
+    /// The furthest we can scroll, with the bottom of the picture at the
+    /// bottom of the screen
+    fn last_top(&self) -> usize {
+        self.canvas.rows().saturating_sub(self.picture_rows())
+    }
+
+    /// Draw the whole screen
+    fn draw(&mut self) {
+        // We're drawing everything, so forget what changed
+        let _ = self.canvas.take_dirty();
+        for row in self.top..self.top + self.picture_rows() {
+            self.draw_row(row);
+        }
+        self.draw_status();
+    }
+
+    /// Draw one row of the canvas, in the right place on the screen
+    fn draw_row(&mut self, row: usize) {
+        console::move_cursor(
+            &mut self.stdout,
+            console::Position {
+                row: (row - self.top) as u8,
+                col: 0,
+            },
+        );
+        let mut current = None;
+        let mut written = 0;
+        if row < self.canvas.rows() {
+            for cell in self.canvas.row(row) {
+                if current != Some(cell.pen) {
+                    set_pen(&mut self.stdout, cell.pen);
+                    current = Some(cell.pen);
+                }
+                let _ = self.stdout.write_char(cp437::to_char(cell.ch));
+                written += 1;
+            }
+        }
+        if current != Some(Pen::DEFAULT) {
+            set_pen(&mut self.stdout, Pen::DEFAULT);
+        }
+        for _ in written..usize::from(self.width) {
+            let _ = self.stdout.write_char(' ');
+        }
+    }
+
+    /// Draw the status bar
+    fn draw_status(&mut self) {
+        console::move_cursor(
+            &mut self.stdout,
+            console::Position {
+                row: self.height - 1,
+                col: 0,
+            },
+        );
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reverse]);
+        let mut status = StatusLine::new();
+        let name = core::str::from_utf8(&self.name[0..self.name_len]).unwrap_or("?");
+        let _ = write!(status, "{}", name);
+        if self.position.1 > 1 {
+            let _ = write!(status, " ({}/{})", self.position.0, self.position.1);
+        }
+        if let Some(message) = self.message {
+            let _ = write!(status, "  {}", message);
+        } else if self.playing {
+            let _ = status.write_str("  ");
+            self.write_speed(&mut status);
+            let _ = status.write_str("  Space: skip  +/-: speed  q: quit");
+        } else {
+            if let Some(sauce) = self.sauce.as_ref() {
+                let _ = status.write_str("  ");
+                write_cp437(&mut status, sauce.title());
+                if !sauce.author().is_empty() {
+                    let _ = status.write_str(" by ");
+                    write_cp437(&mut status, sauce.author());
+                }
+            }
+            let bottom = (self.top + self.picture_rows()).min(self.canvas.rows());
+            let _ = write!(
+                status,
+                "  {}-{}/{}",
+                (self.top + 1).min(bottom),
+                bottom,
+                self.canvas.rows()
+            );
+            let _ = status.write_str("  h: help");
+        }
+        let text = status.as_str();
+        let mut len = 0;
+        for ch in text.chars().take(usize::from(self.width) - 1) {
+            let _ = self.stdout.write_char(ch);
+            len += 1;
+        }
+        for _ in len..usize::from(self.width) - 1 {
+            let _ = self.stdout.write_char(' ');
+        }
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+    }
+
+    /// Say how fast we are playing
+    fn write_speed(&self, status: &mut StatusLine) {
+        let _ = match self.baud {
+            0 => status.write_str("Full speed"),
+            baud => write!(status, "{} baud", baud),
+        };
+    }
+
+    /// Show what the SAUCE record says about the picture
+    fn show_info(&mut self) {
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        console::clear_screen(&mut self.stdout);
+        console::move_cursor(&mut self.stdout, console::Position::origin());
+        let name = core::str::from_utf8(&self.name[0..self.name_len]).unwrap_or("?");
+        let _ = writeln!(self.stdout, "File:     {}", name);
+        let _ = writeln!(
+            self.stdout,
+            "Size:     {} bytes, {} lines",
+            self.data_len,
+            self.canvas.rows()
+        );
+        let mut speed = StatusLine::new();
+        self.write_speed(&mut speed);
+        let _ = writeln!(self.stdout, "Speed:    {}", speed.as_str());
+        let _ = writeln!(self.stdout);
+        match self.sauce.as_ref() {
+            Some(sauce) => {
+                for (label, field) in [
+                    ("Title:    ", sauce.title()),
+                    ("Artist:   ", sauce.author()),
+                    ("Group:    ", sauce.group()),
+                ] {
+                    let mut line = StatusLine::new();
+                    let _ = line.write_str(label);
+                    write_cp437(&mut line, field);
+                    let _ = writeln!(self.stdout, "{}", line.as_str());
+                }
+                if let Some((year, month, day)) = sauce.date() {
+                    let _ = writeln!(self.stdout, "Date:     {:04}-{:02}-{:02}", year, month, day);
+                }
+                if let Some(width) = sauce.width() {
+                    let _ = write!(self.stdout, "Canvas:   {} columns", width);
+                    if let Some(lines) = sauce.lines() {
+                        let _ = write!(self.stdout, " by {} lines", lines);
+                    }
+                    let _ = writeln!(self.stdout);
+                }
+                if sauce.ice_colours() {
+                    let _ = writeln!(
+                        self.stdout,
+                        "Uses iCE colours (bright backgrounds, shown here as normal ones)"
+                    );
+                }
+                let mut first = true;
+                let max_lines = usize::from(self.height).saturating_sub(12);
+                let data = &self.data[0..self.data_len];
+                for comment in sauce.comments(data).take(max_lines) {
+                    if first {
+                        let _ = writeln!(self.stdout);
+                        let _ = writeln!(self.stdout, "Comments:");
+                        first = false;
+                    }
+                    let mut line = StatusLine::new();
+                    let _ = line.write_str("  ");
+                    write_cp437(&mut line, comment);
+                    let _ = writeln!(self.stdout, "{}", line.as_str());
+                }
+            }
+            None => {
+                let _ = writeln!(self.stdout, "This file has no SAUCE record.");
+            }
+        }
+        let _ = writeln!(self.stdout);
+        let _ = write!(self.stdout, "Press any key to go back.");
+        self.keyboard.wait();
+    }
+
+    /// Show the list of keys
+    fn show_help(&mut self) {
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        console::clear_screen(&mut self.stdout);
+        console::move_cursor(&mut self.stdout, console::Position::origin());
+        for line in HELP {
+            let _ = writeln!(self.stdout, "{}", line);
+        }
+        let _ = writeln!(self.stdout);
+        let _ = write!(self.stdout, "Press any key to go back.");
+        self.keyboard.wait();
+    }
+}
+
+/// Switch the console to a pen's colours
+fn set_pen(stdout: &mut neotron_sdk::File, pen: Pen) {
+    console::set_sgr(
+        stdout,
+        [
+            console::SgrParam::Reset,
+            foreground(pen.fg()),
+            background(pen.bg()),
+        ],
+    );
+    if pen.bold() {
+        console::set_sgr(stdout, [console::SgrParam::Bold]);
+    }
+}
+
+/// Write some code page 437 text into a status line
+fn write_cp437(out: &mut StatusLine, text: &[u8]) {
+    for &byte in text {
+        let _ = out.write_char(cp437::to_char(byte));
+    }
+}
+
+/// The SGR parameter which selects an ANSI colour for the text
+fn foreground(index: u8) -> console::SgrParam {
+    match index {
+        1 => console::SgrParam::FgRed,
+        2 => console::SgrParam::FgGreen,
+        3 => console::SgrParam::FgYellow,
+        4 => console::SgrParam::FgBlue,
+        5 => console::SgrParam::FgMagenta,
+        6 => console::SgrParam::FgCyan,
+        7 => console::SgrParam::FgWhite,
+        _ => console::SgrParam::FgBlack,
+    }
+}
+
+/// The SGR parameter which selects an ANSI colour for the background
+fn background(index: u8) -> console::SgrParam {
+    match index {
+        1 => console::SgrParam::BgRed,
+        2 => console::SgrParam::BgGreen,
+        3 => console::SgrParam::BgYellow,
+        4 => console::SgrParam::BgBlue,
+        5 => console::SgrParam::BgMagenta,
+        6 => console::SgrParam::BgCyan,
+        7 => console::SgrParam::BgWhite,
+        _ => console::SgrParam::BgBlack,
+    }
+}
+
+/// Builds a line of text, without an allocator
+struct StatusLine {
+    buffer: [u8; 160],
+    len: usize,
+}
+
+impl StatusLine {
+    /// Make an empty status line
+    fn new() -> StatusLine {
+        StatusLine {
+            buffer: [0; 160],
+            len: 0,
+        }
+    }
+
+    /// Get the text
+    fn as_str(&self) -> &str {
+        core::str::from_utf8(&self.buffer[0..self.len]).unwrap_or("?")
+    }
+}
+
+impl core::fmt::Write for StatusLine {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        // Silently cut off anything which doesn't fit
+        for ch in s.chars() {
+            let mut encoded = [0u8; 4];
+            let encoded = ch.encode_utf8(&mut encoded);
+            if self.len + encoded.len() > self.buffer.len() {
+                break;
+            }
+            self.buffer[self.len..self.len + encoded.len()].copy_from_slice(encoded.as_bytes());
+            self.len += encoded.len();
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,55 @@
+#![cfg_attr(target_os = "none", no_std)]
+#![cfg_attr(target_os = "none", no_main)]
+
+use core::{fmt::Write, ptr::addr_of_mut};
+
+#[cfg(not(target_os = "none"))]
+fn main() {
+    neotron_sdk::init();
+}
+
+static mut VIEWER: ansiview::Viewer = ansiview::Viewer::new(80, 25);
+
+#[no_mangle]
+extern "C" fn neotron_main() -> i32 {
+    neotron_error::exit_code(real_main())
+}
+
+fn real_main() -> Result<(), neotron_sdk::Error> {
+    let viewer = unsafe { &mut *addr_of_mut!(VIEWER) };
+    let mut first = 0;
+    if neotron_sdk::arg(0).is_some_and(|arg| arg == "-b") {
+        let baud = neotron_sdk::arg(1).ok_or(neotron_sdk::Error::InvalidArg)?;
+        let baud = baud
+            .parse::<u32>()
+            .map_err(|_| neotron_sdk::Error::InvalidArg)?;
+        viewer.set_baud(baud);
+        first = 2;
+    }
+    let count = (first..).take_while(|&n| neotron_sdk::arg(n).is_some()).count();
+    if count == 0 {
+        let mut stdout = neotron_sdk::stdout();
+        let _ = writeln!(
+            stdout,
+            "Usage: ansiview [-b <baud>] <file.ans>...\n\nUse -b 0 to skip the modem effect."
+        );
+        return Err(neotron_sdk::Error::InvalidArg);
+    }
+
+    let mut idx = 0;
+    let mut result = Ok(());
+    while let Some(filename) = neotron_sdk::arg(first + idx) {
+        if let Err(e) = viewer.load_file(&filename) {
+            result = Err(e);
+            break;
+        }
+        viewer.set_position(idx, count);
+        match viewer.run() {
+            ansiview::Action::Next => idx += 1,
+            ansiview::Action::Previous => idx = idx.saturating_sub(1),
+            ansiview::Action::Quit => break,
+        }
+    }
+    viewer.close();
+    result
+}
@@ -0,0 +1,139 @@
+//! SAUCE metadata.
+//!
+//! Most ANSI art carries a 128 byte SAUCE record on the end, giving the title,
+//! the artist and the size of the picture. There may be a block of comments
+//! just before it. The record is described at
+//! <https://www.acid.org/info/sauce/sauce.htm>.
+
+/// How long the SAUCE record is
+const RECORD_LEN: usize = 128;
+
+/// How long each comment line is
+const COMMENT_LEN: usize = 64;
+
+/// The data type for character-based files (ASCII, ANSI, etc)
+const DATA_TYPE_CHARACTER: u8 = 1;
+
+/// The flag which says the blink bit gives bright backgrounds instead
+const FLAG_ICE_COLOURS: u8 = 0x01;
+
+/// What the SAUCE record tells us about a file
+#[derive(Debug, Clone)]
+pub struct Sauce {
+    title: [u8; 35],
+    author: [u8; 20],
+    group: [u8; 20],
+    date: [u8; 8],
+    width: u16,
+    lines: u16,
+    ice_colours: bool,
+    /// Where the comments (or the record itself, if there are none) start
+    start: usize,
+    comment_count: usize,
+}
+
+impl Sauce {
+    /// Look for a SAUCE record on the end of a file
+    pub fn find(data: &[u8]) -> Option<Sauce> {
+        let record_start = data.len().checked_sub(RECORD_LEN)?;
+        let record = &data[record_start..];
+        if &record[0..7] != b"SAUCE00" {
+            return None;
+        }
+        let is_character = record[94] == DATA_TYPE_CHARACTER;
+        let mut sauce = Sauce {
+            title: [0; 35],
+            author: [0; 20],
+            group: [0; 20],
+            date: [0; 8],
+            width: 0,
+            lines: 0,
+            ice_colours: is_character && record[105] & FLAG_ICE_COLOURS != 0,
+            start: record_start,
+            comment_count: 0,
+        };
+        sauce.title.copy_from_slice(&record[7..42]);
+        sauce.author.copy_from_slice(&record[42..62]);
+        sauce.group.copy_from_slice(&record[62..82]);
+        sauce.date.copy_from_slice(&record[82..90]);
+        if is_character {
+            sauce.width = u16::from_le_bytes([record[96], record[97]]);
+            sauce.lines = u16::from_le_bytes([record[98], record[99]]);
+        }
+        // The comments sit just in front, after a "COMNT" marker
+        let comments = usize::from(record[104]);
+        if let Some(comment_start) = record_start.checked_sub(5 + comments * COMMENT_LEN) {
+            if comments > 0 && &data[comment_start..comment_start + 5] == b"COMNT" {
+                sauce.start = comment_start;
+                sauce.comment_count = comments;
+            }
+        }
+        Some(sauce)
+    }
+
+    /// Where the picture ends, and the metadata begins
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    /// The title of the picture
+    pub fn title(&self) -> &[u8] {
+        trim(&self.title)
+    }
+
+    /// Who drew it
+    pub fn author(&self) -> &[u8] {
+        trim(&self.author)
+    }
+
+    /// The group they drew it for
+    pub fn group(&self) -> &[u8] {
+        trim(&self.group)
+    }
+
+    /// When it was finished, as `(year, month, day)`
+    pub fn date(&self) -> Option<(u16, u8, u8)> {
+        let text = core::str::from_utf8(&self.date).ok()?;
+        let year = text.get(0..4)?.parse().ok()?;
+        let month = text.get(4..6)?.parse().ok()?;
+        let day = text.get(6..8)?.parse().ok()?;
+        Some((year, month, day))
+    }
+
+    /// How wide the picture is, in characters, if the record says
+    pub fn width(&self) -> Option<usize> {
+        match self.width {
+            0 => None,
+            width => Some(usize::from(width)),
+        }
+    }
+
+    /// How tall the picture is, in lines, if the record says
+    pub fn lines(&self) -> Option<usize> {
+        match self.lines {
+            0 => None,
+            lines => Some(usize::from(lines)),
+        }
+    }
+
+    /// Does the blink bit give bright backgrounds?
+    pub fn ice_colours(&self) -> bool {
+        self.ice_colours
+    }
+
+    /// Get the comment lines, from the file the record came from
+    pub fn comments<'a>(&self, data: &'a [u8]) -> impl Iterator<Item = &'a [u8]> {
+        let start = (self.start + 5).min(data.len());
+        let end = (start + self.comment_count * COMMENT_LEN).min(data.len());
+        data[start..end].chunks(COMMENT_LEN).map(trim)
+    }
+}
+
+/// Chop the padding off the end of a field
+fn trim(field: &[u8]) -> &[u8] {
+    let len = field
+        .iter()
+        .rposition(|&b| b != b' ' && b != 0)
+        .map_or(0, |idx| idx + 1);
+    &field[0..len]
+}
@@ -0,0 +1,164 @@
+//! Ball physics, shared by all the games.
+//!
+//! Positions and velocities are in fixed point, with [`ONE`] units per
+//! character cell. Velocities are in units per frame. As long as the ball
+//! moves less than one cell per frame, it can't skip through a paddle or a
+//! brick.
+
+/// One character cell, in fixed point units
+pub const ONE: i32 = 256;
+
+/// The fastest the ball may move along either axis, per frame
+pub const MAX_SPEED: i32 = ONE * 7 / 8;
+
+/// A ball, bouncing around the screen
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Ball {
+    x: i32,
+    y: i32,
+    dx: i32,
+    dy: i32,
+}
+
+impl Ball {
+    /// Make a stationary ball in the top-left corner
+    pub const fn new() -> Ball {
+        Ball {
+            x: 0,
+            y: 0,
+            dx: 0,
+            dy: 0,
+        }
+    }
+
+    /// Put the ball in the middle of the given cell, with the given velocity
+    pub fn serve(&mut self, col: i16, row: i16, dx: i32, dy: i32) {
+        self.x = i32::from(col) * ONE + ONE / 2;
+        self.y = i32::from(row) * ONE + ONE / 2;
+        self.dx = dx.clamp(-MAX_SPEED, MAX_SPEED);
+        self.dy = dy.clamp(-MAX_SPEED, MAX_SPEED);
+    }
+
+    /// Which column the ball is in
+    pub fn col(&self) -> i16 {
+        (self.x.div_euclid(ONE)) as i16
+    }
+
+    /// Which row the ball is in
+    pub fn row(&self) -> i16 {
+        (self.y.div_euclid(ONE)) as i16
+    }
+
+    /// The horizontal velocity
+    pub fn dx(&self) -> i32 {
+        self.dx
+    }
+
+    /// The vertical velocity
+    pub fn dy(&self) -> i32 {
+        self.dy
+    }
+
+    /// Move the ball on by one frame
+    pub fn step(&mut self) {
+        self.x += self.dx;
+        self.y += self.dy;
+    }
+
+    /// Move the ball back by one frame
+    pub fn unstep(&mut self) {
+        self.x -= self.dx;
+        self.y -= self.dy;
+    }
+
+    /// Make the ball travel right (`true`) or left (`false`)
+    pub fn set_direction_x(&mut self, right: bool) {
+        self.dx = if right { self.dx.abs() } else { -self.dx.abs() };
+    }
+
+    /// Make the ball travel down (`true`) or up (`false`)
+    pub fn set_direction_y(&mut self, down: bool) {
+        self.dy = if down { self.dy.abs() } else { -self.dy.abs() };
+    }
+
+    /// Reverse the horizontal direction
+    pub fn bounce_x(&mut self) {
+        self.dx = -self.dx;
+    }
+
+    /// Reverse the vertical direction
+    pub fn bounce_y(&mut self) {
+        self.dy = -self.dy;
+    }
+
+    /// Keep the ball between the given columns, bouncing off the sides.
+    ///
+    /// Returns true if it bounced.
+    pub fn bounce_between_cols(&mut self, left: i16, right: i16) -> bool {
+        if self.col() < left {
+            self.x = i32::from(left) * ONE;
+            self.set_direction_x(true);
+            true
+        } else if self.col() > right {
+            self.x = i32::from(right) * ONE + ONE - 1;
+            self.set_direction_x(false);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Keep the ball between the given rows, bouncing off the top and bottom.
+    ///
+    /// Returns true if it bounced.
+    pub fn bounce_between_rows(&mut self, top: i16, bottom: i16) -> bool {
+        if self.row() < top {
+            self.y = i32::from(top) * ONE;
+            self.set_direction_y(true);
+            true
+        } else if self.row() > bottom {
+            self.y = i32::from(bottom) * ONE + ONE - 1;
+            self.set_direction_y(false);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Set the velocity along a paddle, depending on where the ball hit it.
+    ///
+    /// `offset` is how far from the middle of the paddle the ball hit, and
+    /// `half_length` is the furthest `offset` can be. Hitting the ends sends
+    /// the ball off at `max_speed`. A `vertical` paddle (as in Pong) changes
+    /// the vertical velocity, otherwise the horizontal velocity is changed.
+    pub fn deflect(&mut self, offset: i16, half_length: i16, max_speed: i32, vertical: bool) {
+        let half_length = i32::from(half_length.max(1));
+        let offset = i32::from(offset).clamp(-half_length, half_length);
+        let mut speed = offset * max_speed / half_length;
+        // Don't let it go straight, or it could get stuck
+        if speed == 0 {
+            speed = if self.x & 1 == 0 { ONE / 16 } else { -ONE / 16 };
+        }
+        if vertical {
+            self.dy = speed;
+        } else {
+            self.dx = speed;
+        }
+    }
+
+    /// Speed the ball up along the given axis by a percentage, up to the limit
+    pub fn speed_up(&mut self, percent: i32, horizontal: bool) {
+        let value = if horizontal {
+            &mut self.dx
+        } else {
+            &mut self.dy
+        };
+        *value = (*value * (100 + percent) / 100).clamp(-MAX_SPEED, MAX_SPEED);
+    }
+}
+
+impl Default for Ball {
+    fn default() -> Self {
+        Ball::new()
+    }
+}
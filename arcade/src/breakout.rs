@@ -0,0 +1,335 @@
+//! Breakout.
+//!
+//! Knock out all the bricks with the ball to get to the next level. Move the
+//! paddle with the left and right arrows (or A and D) and launch the ball with
+//! Space.
+
+use core::fmt::Write;
+
+use neotron_input::{KeyEvent, Keyboard};
+use neotron_screen::Colour;
+use neotron_tui::Line;
+
+use crate::ball::{Ball, ONE};
+use crate::{centre, put, reset, show_message, Screen, SCREEN_HEIGHT, SCREEN_WIDTH};
+
+/// How long each frame lasts
+const FRAME_MS: u64 = 20;
+
+/// Rows of bricks
+const BRICK_ROWS: usize = 6;
+
+/// Bricks in each row
+const BRICK_COLS: usize = 13;
+
+/// Width of each brick, including the gap after it
+const BRICK_WIDTH: i16 = 6;
+
+/// The screen row of the top row of bricks
+const BRICK_TOP: i16 = 4;
+
+/// How wide the paddle is
+const PADDLE_WIDTH: i16 = 9;
+
+/// How far the paddle moves for each key press
+const PADDLE_STEP: i16 = 3;
+
+/// How many balls you get
+const START_LIVES: u8 = 3;
+
+/// The fastest the ball can go across the screen
+const MAX_HORIZONTAL_SPEED: i32 = ONE / 2;
+
+/// The brick layouts for each level.
+///
+/// A `#` is an ordinary brick, a `2` is a brick that takes two hits and a
+/// `.` is a gap. After the last level, we go back to the first, but faster.
+const LEVELS: [[&str; BRICK_ROWS]; 3] = [
+    [
+        "#############",
+        "#############",
+        "#############",
+        "#############",
+        "#############",
+        "#############",
+    ],
+    [
+        "2.2.2.2.2.2.2",
+        "#############",
+        ".###########.",
+        "..#########..",
+        "...#######...",
+        "....#####....",
+    ],
+    [
+        "2222222222222",
+        "#...........#",
+        "#.#########.#",
+        "#.#22222222.#",
+        "#.#########.#",
+        "#...........#",
+    ],
+];
+
+/// One game of Breakout
+pub struct Breakout {
+    ball: Ball,
+    /// How many hits each brick needs. Zero means no brick.
+    bricks: [[u8; BRICK_COLS]; BRICK_ROWS],
+    /// Left-hand column of the paddle
+    paddle: i16,
+    /// Is the ball sitting on the paddle, waiting to be launched?
+    stuck: bool,
+    score: u32,
+    lives: u8,
+    level: u8,
+}
+
+impl Breakout {
+    /// Make a new game of Breakout
+    pub const fn new() -> Breakout {
+        Breakout {
+            ball: Ball::new(),
+            bricks: [[0; BRICK_COLS]; BRICK_ROWS],
+            paddle: 0,
+            stuck: true,
+            score: 0,
+            lives: 0,
+            level: 0,
+        }
+    }
+
+    /// Play until all the balls are lost, or the player quits
+    pub fn play(
+        &mut self,
+        screen: &mut Screen,
+        keyboard: &mut Keyboard,
+        stdout: &mut neotron_sdk::File,
+    ) {
+        let width = SCREEN_WIDTH as i16;
+        let height = SCREEN_HEIGHT as i16;
+        let paddle_row = height - 2;
+        self.score = 0;
+        self.lives = START_LIVES;
+        self.level = 0;
+        self.load_level();
+        self.paddle = (width - PADDLE_WIDTH) / 2;
+        self.stuck = true;
+        reset(screen);
+
+        loop {
+            while let Some(key) = keyboard.read() {
+                match key {
+                    KeyEvent::Left | KeyEvent::Char('a' | 'A') => {
+                        self.move_paddle(-PADDLE_STEP, width)
+                    }
+                    KeyEvent::Right | KeyEvent::Char('d' | 'D') => {
+                        self.move_paddle(PADDLE_STEP, width)
+                    }
+                    KeyEvent::Char(' ') if self.stuck => {
+                        self.stuck = false;
+                        let dx = if neotron_sdk::rand() & 1 == 0 {
+                            ONE / 4
+                        } else {
+                            -ONE / 4
+                        };
+                        self.ball.serve(
+                            self.ball.col(),
+                            self.ball.row(),
+                            dx,
+                            -self.vertical_speed(),
+                        );
+                    }
+                    KeyEvent::Char('p' | 'P') => {
+                        show_message(screen, keyboard, stdout, " PAUSED ");
+                    }
+                    KeyEvent::Char('q' | 'Q') => return,
+                    _ => {}
+                }
+            }
+
+            if self.stuck {
+                // The ball rides on the paddle
+                self.ball
+                    .serve(self.paddle + PADDLE_WIDTH / 2, paddle_row - 1, 0, 0);
+            } else {
+                self.move_ball(width, paddle_row);
+                if self.ball.row() > paddle_row {
+                    self.lives -= 1;
+                    self.stuck = true;
+                    if self.lives == 0 {
+                        self.draw(screen);
+                        show_message(screen, keyboard, stdout, " GAME OVER ");
+                        return;
+                    }
+                }
+            }
+
+            if self.bricks.iter().flatten().all(|b| *b == 0) {
+                self.level = self.level.saturating_add(1);
+                self.load_level();
+                self.stuck = true;
+                self.draw(screen);
+                let mut message = Line::new();
+                let _ = write!(message, " Level {} - press a key ", self.level + 1);
+                show_message(screen, keyboard, stdout, message.as_str());
+            }
+
+            self.draw(screen);
+            screen.flush(stdout);
+            neotron_sdk::delay(core::time::Duration::from_millis(FRAME_MS));
+        }
+    }
+
+    /// Set up the bricks for the current level
+    fn load_level(&mut self) {
+        let layout = &LEVELS[usize::from(self.level) % LEVELS.len()];
+        for (row, line) in self.bricks.iter_mut().zip(layout.iter()) {
+            for (brick, ch) in row.iter_mut().zip(line.bytes()) {
+                *brick = match ch {
+                    b'#' => 1,
+                    b'2' => 2,
+                    _ => 0,
+                };
+            }
+        }
+    }
+
+    /// How fast the ball goes up and down. It gets faster on each level.
+    fn vertical_speed(&self) -> i32 {
+        (ONE / 4 + i32::from(self.level) * ONE / 32).min(ONE / 2)
+    }
+
+    /// Move the paddle left or right, keeping it inside the walls
+    fn move_paddle(&mut self, delta: i16, width: i16) {
+        self.paddle = (self.paddle + delta).clamp(1, width - 1 - PADDLE_WIDTH);
+    }
+
+    /// Find the brick (if any) at this screen location
+    fn brick_at(col: i16, row: i16) -> Option<(usize, usize)> {
+        let row = row - BRICK_TOP;
+        let col = col - 1;
+        if row < 0 || col < 0 {
+            return None;
+        }
+        let (row, col) = (row as usize, (col / BRICK_WIDTH) as usize);
+        if row < BRICK_ROWS && col < BRICK_COLS {
+            Some((row, col))
+        } else {
+            None
+        }
+    }
+
+    /// Move the ball, bouncing it off walls, bricks and the paddle
+    fn move_ball(&mut self, width: i16, paddle_row: i16) {
+        let old_row = self.ball.row();
+        self.ball.step();
+        self.ball.bounce_between_cols(1, width - 2);
+        if self.ball.row() < 2 {
+            self.ball.bounce_between_rows(2, paddle_row);
+        }
+
+        if let Some((row, col)) = Self::brick_at(self.ball.col(), self.ball.row()) {
+            if self.bricks[row][col] > 0 {
+                self.bricks[row][col] -= 1;
+                if self.bricks[row][col] == 0 {
+                    // Higher bricks are worth more
+                    self.score += 10 * (BRICK_ROWS - row) as u32;
+                }
+                // If we came in through the top or bottom, bounce up or down,
+                // otherwise we must have hit the side.
+                let new_row = self.ball.row();
+                self.ball.unstep();
+                if new_row != old_row {
+                    self.ball.bounce_y();
+                } else {
+                    self.ball.bounce_x();
+                }
+            }
+        }
+
+        let col = self.ball.col();
+        if self.ball.dy() > 0
+            && self.ball.row() == paddle_row
+            && col >= self.paddle
+            && col < self.paddle + PADDLE_WIDTH
+        {
+            self.ball.unstep();
+            self.ball.set_direction_y(false);
+            let offset = (col - self.paddle) * 2 - (PADDLE_WIDTH - 1);
+            self.ball
+                .deflect(offset, PADDLE_WIDTH - 1, MAX_HORIZONTAL_SPEED, false);
+        }
+    }
+
+    /// Draw the walls, the bricks, the paddle, the ball and the score
+    fn draw(&self, screen: &mut Screen) {
+        let width = SCREEN_WIDTH as i16;
+        let height = SCREEN_HEIGHT as i16;
+        screen.clear();
+
+        let mut status = Line::new();
+        let _ = write!(
+            status,
+            "SCORE {:06}    LEVEL {:<3}    BALLS {}",
+            self.score,
+            self.level + 1,
+            self.lives
+        );
+        centre(screen, 0, status.as_str(), Colour::White);
+
+        // Walls
+        put(screen, 0, 1, '┌', Colour::Blue);
+        put(screen, width - 1, 1, '┐', Colour::Blue);
+        for col in 1..width - 1 {
+            put(screen, col, 1, '─', Colour::Blue);
+        }
+        for row in 2..height {
+            put(screen, 0, row, '│', Colour::Blue);
+            put(screen, width - 1, row, '│', Colour::Blue);
+        }
+
+        // Bricks
+        let colours = [
+            Colour::Red,
+            Colour::Magenta,
+            Colour::Yellow,
+            Colour::Green,
+            Colour::Cyan,
+            Colour::Blue,
+        ];
+        for (row, (bricks, colour)) in self.bricks.iter().zip(colours.iter()).enumerate() {
+            for (col, &brick) in bricks.iter().enumerate() {
+                if brick == 0 {
+                    continue;
+                }
+                let colour = if brick > 1 { Colour::White } else { *colour };
+                let left = 1 + col as i16 * BRICK_WIDTH;
+                for x in left..left + BRICK_WIDTH - 1 {
+                    put(screen, x, BRICK_TOP + row as i16, '█', colour);
+                }
+            }
+        }
+
+        // Paddle and ball
+        for col in self.paddle..self.paddle + PADDLE_WIDTH {
+            put(screen, col, height - 2, '▀', Colour::White);
+        }
+        put(
+            screen,
+            self.ball.col(),
+            self.ball.row(),
+            '■',
+            Colour::Yellow,
+        );
+        if self.stuck {
+            centre(screen, height / 2, "Press Space to launch", Colour::White);
+        }
+    }
+}
+
+impl Default for Breakout {
+    fn default() -> Self {
+        Breakout::new()
+    }
+}
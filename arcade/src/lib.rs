@@ -0,0 +1,165 @@
+//! Game logic for the Neotron arcade - Pong and Breakout
+
+#![no_std]
+#![deny(missing_docs)]
+#![deny(unsafe_code)]
+
+use neotron_input::{KeyEvent, Keyboard};
+use neotron_screen::{Attr, Colour};
+use neotron_sdk::console;
+
+pub mod ball;
+pub mod breakout;
+pub mod pong;
+
+/// How big the screen is
+const SCREEN_WIDTH: usize = 80;
+const SCREEN_HEIGHT: usize = 25;
+
+/// What the games are drawn on
+pub type Screen = neotron_screen::Screen<SCREEN_WIDTH, SCREEN_HEIGHT>;
+
+/// Represents the arcade application
+///
+/// You pick a game from the menu, and can play as many games as you like.
+pub struct App {
+    stdout: neotron_sdk::File,
+    screen: Screen,
+    keyboard: Keyboard,
+    pong: pong::Pong,
+    breakout: breakout::Breakout,
+}
+
+impl App {
+    /// Make a new arcade application.
+    ///
+    /// The games are laid out for an 80x25 screen, so the size is ignored.
+    pub const fn new(_width: u8, _height: u8) -> App {
+        App {
+            stdout: neotron_sdk::stdout(),
+            screen: Screen::new(),
+            keyboard: Keyboard::new(),
+            pong: pong::Pong::new(),
+            breakout: breakout::Breakout::new(),
+        }
+    }
+
+    /// Show the menu and play games until the user quits
+    pub fn play(&mut self) {
+        console::cursor_off(&mut self.stdout);
+        let mut seed: u16 = 0x2b9e;
+
+        loop {
+            reset(&mut self.screen);
+            self.menu();
+            let key = loop {
+                seed = seed.wrapping_add(1);
+                if let Some(key) = self.keyboard.read() {
+                    break key;
+                }
+                neotron_sdk::delay(core::time::Duration::from_millis(10));
+            };
+            neotron_sdk::srand(seed);
+            match key {
+                KeyEvent::Char('1') => {
+                    self.pong
+                        .play(&mut self.screen, &mut self.keyboard, &mut self.stdout, true)
+                }
+                KeyEvent::Char('2') => self.pong.play(
+                    &mut self.screen,
+                    &mut self.keyboard,
+                    &mut self.stdout,
+                    false,
+                ),
+                KeyEvent::Char('3') => {
+                    self.breakout
+                        .play(&mut self.screen, &mut self.keyboard, &mut self.stdout)
+                }
+                KeyEvent::Char('q' | 'Q') => break,
+                _ => {}
+            }
+        }
+
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        console::clear_screen(&mut self.stdout);
+        console::move_cursor(&mut self.stdout, console::Position::origin());
+        console::cursor_on(&mut self.stdout);
+    }
+
+    /// Draw the game menu
+    fn menu(&mut self) {
+        let row = SCREEN_HEIGHT as i16 / 2 - 5;
+        let screen = &mut self.screen;
+        screen.clear();
+        centre(screen, row, "NEOTRON ARCADE", Colour::Yellow);
+        centre(
+            screen,
+            row + 3,
+            "1 - Pong, against the computer",
+            Colour::White,
+        );
+        centre(
+            screen,
+            row + 4,
+            "2 - Pong, two players         ",
+            Colour::White,
+        );
+        centre(
+            screen,
+            row + 5,
+            "3 - Breakout                  ",
+            Colour::White,
+        );
+        centre(
+            screen,
+            row + 7,
+            "Q - Quit                      ",
+            Colour::White,
+        );
+        centre(
+            screen,
+            row + 10,
+            "In game: P pauses, Q returns to this menu",
+            Colour::Cyan,
+        );
+        self.screen.flush(&mut self.stdout);
+    }
+}
+
+/// Show a message in the middle of the screen and wait for a key
+fn show_message(
+    screen: &mut Screen,
+    keyboard: &mut Keyboard,
+    stdout: &mut neotron_sdk::File,
+    message: &str,
+) {
+    let row = SCREEN_HEIGHT as i16 / 2;
+    centre(screen, row, message, Colour::Yellow);
+    screen.flush(stdout);
+    keyboard.wait();
+}
+
+/// Blank the screen and make sure the next flush redraws all of it
+fn reset(screen: &mut Screen) {
+    screen.clear();
+    screen.invalidate();
+}
+
+/// Put a character on the screen. Anything off the screen is ignored.
+fn put(screen: &mut Screen, col: i16, row: i16, ch: char, colour: Colour) {
+    // Negative positions turn into huge ones, which are off the screen too
+    screen.set(
+        row as usize,
+        col as usize,
+        ch,
+        Attr::new(colour, Colour::Black),
+    );
+}
+
+/// Put some text on the screen, centred on the given row
+fn centre(screen: &mut Screen, row: i16, text: &str, colour: Colour) {
+    let col = neotron_text::centre_offset(text, SCREEN_WIDTH) as i16;
+    for (idx, ch) in text.chars().enumerate() {
+        put(screen, col + idx as i16, row, ch, colour);
+    }
+}
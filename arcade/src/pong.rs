@@ -0,0 +1,213 @@
+//! Pong, for one or two players.
+//!
+//! The left bat is moved with W and S. The right bat is moved with the up and
+//! down arrows (or I and K), or by the computer.
+
+use core::fmt::Write;
+
+use neotron_input::{KeyEvent, Keyboard};
+use neotron_screen::Colour;
+use neotron_tui::Line;
+
+use crate::ball::{Ball, ONE};
+use crate::{centre, put, reset, show_message, Screen, SCREEN_HEIGHT, SCREEN_WIDTH};
+
+/// How long each frame lasts
+const FRAME_MS: u64 = 20;
+
+/// How many rows tall each bat is
+const BAT_LENGTH: i16 = 4;
+
+/// How far the bat moves for each key press
+const BAT_STEP: i16 = 2;
+
+/// The first player to get this many points wins
+const WINNING_SCORE: u8 = 11;
+
+/// How fast the ball starts off, across the screen
+const SERVE_SPEED: i32 = ONE / 3;
+
+/// The fastest the ball can go up or down the screen
+const MAX_VERTICAL_SPEED: i32 = ONE / 3;
+
+/// One game of Pong
+pub struct Pong {
+    ball: Ball,
+    /// Top row of each bat - left then right
+    bats: [i16; 2],
+    /// Score for each player - left then right
+    scores: [u8; 2],
+    /// Is the computer playing the right-hand bat?
+    computer: bool,
+}
+
+impl Pong {
+    /// Make a new game of Pong
+    pub const fn new() -> Pong {
+        Pong {
+            ball: Ball::new(),
+            bats: [0; 2],
+            scores: [0; 2],
+            computer: true,
+        }
+    }
+
+    /// Play until someone wins, or the player quits
+    pub fn play(
+        &mut self,
+        screen: &mut Screen,
+        keyboard: &mut Keyboard,
+        stdout: &mut neotron_sdk::File,
+        computer: bool,
+    ) {
+        let width = SCREEN_WIDTH as i16;
+        let height = SCREEN_HEIGHT as i16;
+        self.computer = computer;
+        self.scores = [0; 2];
+        self.bats = [(height - BAT_LENGTH) / 2; 2];
+        self.serve(width, height, neotron_sdk::rand() & 1 == 0);
+        reset(screen);
+
+        let mut frame: u32 = 0;
+        loop {
+            while let Some(key) = keyboard.read() {
+                match key {
+                    KeyEvent::Char('w' | 'W') => self.move_bat(0, -BAT_STEP, height),
+                    KeyEvent::Char('s' | 'S') => self.move_bat(0, BAT_STEP, height),
+                    KeyEvent::Up | KeyEvent::Char('i' | 'I') if !self.computer => {
+                        self.move_bat(1, -BAT_STEP, height)
+                    }
+                    KeyEvent::Down | KeyEvent::Char('k' | 'K') if !self.computer => {
+                        self.move_bat(1, BAT_STEP, height)
+                    }
+                    KeyEvent::Char('p' | 'P') => {
+                        show_message(screen, keyboard, stdout, " PAUSED ");
+                    }
+                    KeyEvent::Char('q' | 'Q') => return,
+                    _ => {}
+                }
+            }
+
+            frame = frame.wrapping_add(1);
+            if self.computer && frame.is_multiple_of(3) {
+                self.computer_move(width, height);
+            }
+
+            self.ball.step();
+            self.ball.bounce_between_rows(2, height - 2);
+            self.check_bats(width);
+
+            if self.ball.col() < 0 {
+                self.scores[1] += 1;
+                self.serve(width, height, false);
+            } else if self.ball.col() >= width {
+                self.scores[0] += 1;
+                self.serve(width, height, true);
+            }
+
+            self.draw(screen);
+            screen.flush(stdout);
+
+            if let Some(winner) = self.scores.iter().position(|s| *s >= WINNING_SCORE) {
+                let message = match (winner, self.computer) {
+                    (0, true) => " You win! ",
+                    (_, true) => " The computer wins! ",
+                    (0, false) => " Left player wins! ",
+                    (_, false) => " Right player wins! ",
+                };
+                show_message(screen, keyboard, stdout, message);
+                return;
+            }
+
+            neotron_sdk::delay(core::time::Duration::from_millis(FRAME_MS));
+        }
+    }
+
+    /// Put the ball in the middle, heading towards one of the players
+    fn serve(&mut self, width: i16, height: i16, towards_left: bool) {
+        let dx = if towards_left {
+            -SERVE_SPEED
+        } else {
+            SERVE_SPEED
+        };
+        let dy = i32::from(neotron_sdk::rand() % 64) - 32;
+        self.ball.serve(width / 2, height / 2, dx, dy);
+    }
+
+    /// Move a bat up or down, keeping it on the screen
+    fn move_bat(&mut self, bat: usize, delta: i16, height: i16) {
+        self.bats[bat] = (self.bats[bat] + delta).clamp(2, height - 1 - BAT_LENGTH);
+    }
+
+    /// Move the computer's bat towards the ball.
+    ///
+    /// It only reacts once the ball is on its half of the screen, and it
+    /// doesn't move very fast, so it can be beaten.
+    fn computer_move(&mut self, width: i16, height: i16) {
+        let target = if self.ball.dx() > 0 && self.ball.col() > width / 3 {
+            self.ball.row() - BAT_LENGTH / 2
+        } else {
+            (height - BAT_LENGTH) / 2
+        };
+        let delta = (target - self.bats[1]).clamp(-1, 1);
+        self.move_bat(1, delta, height);
+    }
+
+    /// See if the ball has hit either of the bats
+    fn check_bats(&mut self, width: i16) {
+        let bat_cols = [2, width - 3];
+        for (idx, &col) in bat_cols.iter().enumerate() {
+            let heading_to_bat = if idx == 0 {
+                self.ball.dx() < 0
+            } else {
+                self.ball.dx() > 0
+            };
+            let top = self.bats[idx];
+            let row = self.ball.row();
+            if heading_to_bat && self.ball.col() == col && row >= top && row < top + BAT_LENGTH {
+                self.ball.unstep();
+                self.ball.set_direction_x(idx == 0);
+                // Gives -3, -1, 1 or 3 for a bat four rows long
+                let offset = (row - top) * 2 - (BAT_LENGTH - 1);
+                self.ball
+                    .deflect(offset, BAT_LENGTH - 1, MAX_VERTICAL_SPEED, true);
+                self.ball.speed_up(5, true);
+            }
+        }
+    }
+
+    /// Draw the court, the bats, the ball and the scores
+    fn draw(&self, screen: &mut Screen) {
+        let width = SCREEN_WIDTH as i16;
+        let height = SCREEN_HEIGHT as i16;
+        screen.clear();
+        let mut scores = Line::new();
+        let _ = write!(scores, "{:2}   {:<2}", self.scores[0], self.scores[1]);
+        centre(screen, 0, scores.as_str(), Colour::Yellow);
+        for col in 0..width {
+            put(screen, col, 1, '▄', Colour::White);
+            put(screen, col, height - 1, '▀', Colour::White);
+        }
+        for row in (2..height - 1).step_by(2) {
+            put(screen, width / 2, row, '│', Colour::Blue);
+        }
+        for (idx, col) in [2, width - 3].iter().enumerate() {
+            for row in self.bats[idx]..self.bats[idx] + BAT_LENGTH {
+                put(screen, *col, row, '█', Colour::White);
+            }
+        }
+        put(
+            screen,
+            self.ball.col(),
+            self.ball.row(),
+            '■',
+            Colour::Yellow,
+        );
+    }
+}
+
+impl Default for Pong {
+    fn default() -> Self {
+        Pong::new()
+    }
+}
@@ -0,0 +1,280 @@
+//! Application logic for the audio tester
+//!
+//! Each test plays something through the audio device and says on the
+//! screen what you should be hearing:
+//!
+//! * `channels` plays a tone in the left speaker, then the right, then both
+//! * `sweep` glides a tone from 20 Hz up to as high as the device goes
+//! * `rate` plays a 440 Hz A for ten seconds and times it, to check the
+//!   device really runs at the rate it says
+
+#![no_std]
+#![deny(missing_docs)]
+#![deny(unsafe_code)]
+
+use core::fmt::Write;
+
+use neotron_input::{KeyEvent, Keyboard};
+
+pub mod sine;
+
+use sine::Sine;
+
+/// The rates we see if the device takes
+pub const PROBE_RATES: [u32; 7] = [8000, 11025, 16000, 22050, 32000, 44100, 48000];
+
+/// How many frames we send at once
+const BUFFER_FRAMES: usize = 256;
+
+/// The tone for the channel and rate tests, which is the A that orchestras
+/// tune to
+const TONE_HZ: u32 = 440;
+
+/// How long each channel's tone plays, in milliseconds
+const CHANNEL_MS: u32 = 1500;
+
+/// Where the sweep starts and stops, in Hz
+const SWEEP_START_HZ: u32 = 20;
+const SWEEP_END_HZ: u32 = 20000;
+
+/// How long the sweep takes to go up an octave, in milliseconds
+const SWEEP_OCTAVE_MS: u32 = 2000;
+
+/// How many times an octave we change the sweep's pitch
+const SWEEP_STEPS: u32 = 64;
+
+/// The sixty-fourth root of two, in 16.16 fixed point, which takes the
+/// sweep up a sixty-fourth of an octave
+const SWEEP_RATIO: u64 = 66250;
+
+/// How long the rate test plays for, in seconds
+const RATE_SECONDS: u32 = 10;
+
+/// The tests we can run
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Test {
+    /// Left, right, then both
+    Channels,
+    /// A sine sweep
+    Sweep,
+    /// Timing a tone against the clock
+    Rate,
+}
+
+impl Test {
+    /// Every test, in the order we run them
+    pub const ALL: [Test; 3] = [Test::Channels, Test::Sweep, Test::Rate];
+
+    /// Find a test by name
+    pub fn parse(text: &str) -> Option<Test> {
+        Self::ALL
+            .into_iter()
+            .find(|test| text.eq_ignore_ascii_case(test.name()))
+    }
+
+    /// What the test is called
+    pub fn name(self) -> &'static str {
+        match self {
+            Test::Channels => "channels",
+            Test::Sweep => "sweep",
+            Test::Rate => "rate",
+        }
+    }
+}
+
+/// How a test ended
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    /// It played to the end
+    Done,
+    /// A key was pressed to skip the rest of it
+    Skipped,
+    /// Q or Escape was pressed
+    Quit,
+}
+
+/// Which rates from [`PROBE_RATES`] the device takes. Each one is tried by
+/// opening the device, so do this before opening it for real.
+pub fn probe_rates() -> [bool; PROBE_RATES.len()] {
+    let mut taken = [false; PROBE_RATES.len()];
+    for (taken, rate) in taken.iter_mut().zip(PROBE_RATES) {
+        *taken = neotron_audio::open(rate).is_ok();
+    }
+    taken
+}
+
+/// Plays the tests through the audio device
+pub struct Tester {
+    device: neotron_audio::Device,
+    keyboard: Keyboard,
+    /// How loud, out of 100
+    volume: i32,
+}
+
+impl Tester {
+    /// Play through `device`, at `volume` percent of full scale
+    pub fn new(device: neotron_audio::Device, volume: u8) -> Tester {
+        Tester {
+            device,
+            keyboard: Keyboard::new(),
+            volume: i32::from(volume.min(100)),
+        }
+    }
+
+    /// Run a test, saying what's happening on `out`
+    pub fn run<W: Write>(&mut self, test: Test, out: &mut W) -> neotron_sdk::Result<Outcome> {
+        match test {
+            Test::Channels => self.channels(out),
+            Test::Sweep => self.sweep(out),
+            Test::Rate => self.rate(out),
+        }
+    }
+
+    /// A tone in each speaker in turn, then both
+    fn channels<W: Write>(&mut self, out: &mut W) -> neotron_sdk::Result<Outcome> {
+        let _ = writeln!(
+            out,
+            "Channels - you should hear a tone on each side in turn"
+        );
+        let rate = self.device.sample_rate();
+        let mut tone = Sine::new();
+        tone.set_frequency(TONE_HZ, rate);
+        for (name, left, right) in [
+            ("Left", true, false),
+            ("Right", false, true),
+            ("Both", true, true),
+        ] {
+            let _ = writeln!(out, "  {}", name);
+            let outcome = self.play(rate * CHANNEL_MS / 1000, || {
+                let sample = tone.next_sample();
+                (
+                    if left { sample } else { 0 },
+                    if right { sample } else { 0 },
+                )
+            })?;
+            if outcome != Outcome::Done {
+                return Ok(outcome);
+            }
+            // A gap, so each one is heard on its own
+            let outcome = self.play(rate / 2, || (0, 0))?;
+            if outcome != Outcome::Done {
+                return Ok(outcome);
+            }
+        }
+        Ok(Outcome::Done)
+    }
+
+    /// A tone that glides up from low to high, an octave every two seconds
+    fn sweep<W: Write>(&mut self, out: &mut W) -> neotron_sdk::Result<Outcome> {
+        let rate = self.device.sample_rate();
+        // A tone can't go higher than half the sample rate
+        let end = SWEEP_END_HZ.min(rate / 2);
+        let _ = writeln!(
+            out,
+            "Sweep - a tone going up from {} Hz to {} Hz. Note where it fades \
+             out, and listen for buzzing.",
+            SWEEP_START_HZ, end
+        );
+        let step_frames = rate * SWEEP_OCTAVE_MS / 1000 / SWEEP_STEPS;
+        let mut tone = Sine::new();
+        // In 16.16 fixed point, so the small steps add up
+        let mut frequency = u64::from(SWEEP_START_HZ) << 16;
+        let mut outcome = Outcome::Done;
+        while frequency >> 16 <= u64::from(end) {
+            let hz = (frequency >> 16) as u32;
+            let _ = write!(out, "\r  {:>5} Hz", hz);
+            tone.set_frequency(hz, rate);
+            outcome = self.play(step_frames, || {
+                let sample = tone.next_sample();
+                (sample, sample)
+            })?;
+            if outcome != Outcome::Done {
+                break;
+            }
+            frequency = (frequency * SWEEP_RATIO) >> 16;
+        }
+        let _ = writeln!(out);
+        Ok(outcome)
+    }
+
+    /// A tone that we time, to see if the device plays as fast as it
+    /// should
+    fn rate<W: Write>(&mut self, out: &mut W) -> neotron_sdk::Result<Outcome> {
+        let rate = self.device.sample_rate();
+        let _ = writeln!(
+            out,
+            "Rate - {} seconds of A at {} Hz. If it's sharp or flat, the \
+             device isn't running at the rate it says.",
+            RATE_SECONDS, TONE_HZ
+        );
+        let mut clock = neotron_frame::Clock::new();
+        clock.start();
+        let mut tone = Sine::new();
+        tone.set_frequency(TONE_HZ, rate);
+        let mut frames = 0;
+        let mut outcome = Outcome::Done;
+        while frames < rate * RATE_SECONDS {
+            outcome = self.play(rate, || {
+                let sample = tone.next_sample();
+                (sample, sample)
+            })?;
+            if outcome != Outcome::Done {
+                break;
+            }
+            frames += rate;
+            // Once a second is plenty
+            clock.sync();
+        }
+        if outcome != Outcome::Done {
+            return Ok(outcome);
+        }
+        let elapsed_ms = clock.now_ms();
+        if !clock.is_accurate() || elapsed_ms == 0 {
+            let _ = writeln!(out, "  There's no real-time clock to time it with.");
+            return Ok(outcome);
+        }
+        // The device holds some sound we've sent but it hasn't played, so
+        // this is a little high
+        let measured = u64::from(frames) * 1000 / elapsed_ms;
+        let _ = writeln!(
+            out,
+            "  It took about {}.{} seconds, so the device runs at about {} Hz",
+            elapsed_ms / 1000,
+            (elapsed_ms % 1000) / 100,
+            measured
+        );
+        Ok(outcome)
+    }
+
+    /// Play some frames, turned down to our volume, stopping early if a
+    /// key is pressed
+    fn play<F>(&mut self, frames: u32, mut next: F) -> neotron_sdk::Result<Outcome>
+    where
+        F: FnMut() -> (i16, i16),
+    {
+        let mut buffer = [0u8; BUFFER_FRAMES * neotron_audio::convert::FRAME_LEN];
+        let mut left = frames as usize;
+        while left > 0 {
+            let count = left.min(BUFFER_FRAMES);
+            let chunk = &mut buffer[0..count * neotron_audio::convert::FRAME_LEN];
+            neotron_audio::convert::fill_stereo(chunk, || {
+                let (l, r) = next();
+                (self.scale(l), self.scale(r))
+            });
+            self.device.write(chunk)?;
+            left -= count;
+            match self.keyboard.read() {
+                None => {}
+                Some(KeyEvent::Escape) => return Ok(Outcome::Quit),
+                Some(key) if key.is_char('q') => return Ok(Outcome::Quit),
+                Some(_) => return Ok(Outcome::Skipped),
+            }
+        }
+        Ok(Outcome::Done)
+    }
+
+    /// Turn a sample down to our volume
+    fn scale(&self, sample: i16) -> i16 {
+        (i32::from(sample) * self.volume / 100) as i16
+    }
+}
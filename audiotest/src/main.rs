@@ -0,0 +1,122 @@
+#![cfg_attr(target_os = "none", no_std)]
+#![cfg_attr(target_os = "none", no_main)]
+
+use core::fmt::Write;
+
+use audiotest::{Outcome, Test};
+use neotron_args::{Item, Opt};
+
+#[cfg(not(target_os = "none"))]
+fn main() {
+    neotron_sdk::init();
+}
+
+#[no_mangle]
+extern "C" fn neotron_main() -> i32 {
+    neotron_error::exit_code_text(real_main())
+}
+
+/// The options we understand
+static SPEC: neotron_args::Spec = neotron_args::Spec {
+    name: "audiotest",
+    usage: "[options]",
+    about: "Says what the audio device can do, and plays test tones through \
+            it. Press a key to skip a test, or Q to quit.",
+    options: &[
+        Opt::value(
+            'r',
+            "rate",
+            "hz",
+            "The sample rate to ask for (default 44100)",
+        ),
+        Opt::value(
+            't',
+            "test",
+            "name",
+            "Only run one test: channels, sweep or rate",
+        ),
+        Opt::value('v', "volume", "percent", "How loud to play (default 50)"),
+    ],
+};
+
+/// How loud we play, unless told otherwise. Test tones at full scale are
+/// hard on the ears, and on small speakers.
+const DEFAULT_VOLUME: u8 = 50;
+
+fn real_main() -> Result<(), neotron_sdk::Error> {
+    let mut stdout = neotron_sdk::stdout();
+    let mut rate = neotron_audio::SAMPLE_RATE;
+    let mut only = None;
+    let mut volume = DEFAULT_VOLUME;
+    let mut args = neotron_args::Parser::new(&SPEC);
+    loop {
+        let item = match args.next_item() {
+            Ok(Some(item)) => item,
+            Ok(None) => break,
+            Err(neotron_args::Error::Help) => return Ok(()),
+            Err(e) => return Err(e.into()),
+        };
+        match item {
+            Item::Value("rate", value) => rate = args.parse("rate", &value)?,
+            Item::Value("test", value) => {
+                only = Some(
+                    Test::parse(value.as_str())
+                        .ok_or_else(|| args.usage_error("use channels, sweep or rate"))?,
+                );
+            }
+            Item::Value("volume", value) => {
+                volume = args.parse("volume", &value)?;
+                if volume > 100 {
+                    return Err(args.usage_error("volume goes up to 100").into());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let _ = write!(stdout, "Rates the device takes:");
+    let mut any = false;
+    for (taken, probe) in audiotest::probe_rates()
+        .into_iter()
+        .zip(audiotest::PROBE_RATES)
+    {
+        if taken {
+            any = true;
+            let _ = write!(stdout, " {}", probe);
+        }
+    }
+    let _ = writeln!(
+        stdout,
+        "{}",
+        if any { "" } else { " none of the usual ones" }
+    );
+
+    let device = match neotron_audio::open_nearest(rate) {
+        Ok(device) => device,
+        Err(e) => {
+            let _ = writeln!(stdout, "Couldn't open the audio device");
+            return Err(e);
+        }
+    };
+    let _ = writeln!(
+        stdout,
+        "Asked for {} Hz, and got {} Hz, 16-bit stereo",
+        rate,
+        device.sample_rate()
+    );
+
+    let mut tester = audiotest::Tester::new(device, volume);
+    for test in Test::ALL {
+        if only.is_some_and(|only| only != test) {
+            continue;
+        }
+        match tester.run(test, &mut stdout)? {
+            Outcome::Done => {}
+            Outcome::Skipped => {
+                let _ = writeln!(stdout, "  Skipped");
+            }
+            Outcome::Quit => break,
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,68 @@
+//! A sine wave, for test tones.
+//!
+//! The oscillators in `neotron-audio` make buzzy waves, which are full of
+//! harmonics. A test tone wants to be one pure frequency, so we look it up
+//! in a table of a quarter of a sine wave.
+
+/// A quarter of a sine wave, from 0 to 90 degrees, with both ends
+const QUARTER: [i16; 65] = [
+    0, 804, 1608, 2410, 3212, 4011, 4808, 5602, //
+    6393, 7179, 7962, 8739, 9512, 10278, 11039, 11793, //
+    12539, 13279, 14010, 14732, 15446, 16151, 16846, 17530, //
+    18204, 18868, 19519, 20159, 20787, 21403, 22005, 22594, //
+    23170, 23731, 24279, 24811, 25329, 25832, 26319, 26790, //
+    27245, 27683, 28105, 28510, 28898, 29268, 29621, 29956, //
+    30273, 30571, 30852, 31113, 31356, 31580, 31785, 31971, //
+    32137, 32285, 32412, 32521, 32609, 32678, 32728, 32757, //
+    32767,
+];
+
+/// Makes a sine wave at a given pitch.
+///
+/// Like [`neotron_audio::Oscillator`], the phase goes once round a `u32`
+/// per cycle. The top two bits say which quarter of the wave we're in, the
+/// next six pick from the table, and the ten after that go between one
+/// entry and the next.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct Sine {
+    phase: u32,
+    step: u32,
+}
+
+impl Sine {
+    /// Make a silent sine wave
+    pub const fn new() -> Sine {
+        Sine { phase: 0, step: 0 }
+    }
+
+    /// Play at this frequency, in Hz. Zero is silence.
+    pub fn set_frequency(&mut self, frequency: u32, sample_rate: u32) {
+        self.step = neotron_audio::Oscillator::step_for(frequency, sample_rate);
+    }
+
+    /// Work out the next sample, at full volume
+    pub fn next_sample(&mut self) -> i16 {
+        if self.step == 0 {
+            return 0;
+        }
+        let phase = self.phase;
+        self.phase = phase.wrapping_add(self.step);
+        let quarter = phase >> 30;
+        let mut position = (phase >> 14) & 0xFFFF;
+        // The second and fourth quarters go back down the table
+        if quarter & 1 == 1 {
+            position = 0x10000 - position;
+        }
+        let idx = (position >> 10) as usize;
+        let fraction = (position & 0x3FF) as i32;
+        let low = i32::from(QUARTER[idx]);
+        let high = i32::from(QUARTER[(idx + 1).min(QUARTER.len() - 1)]);
+        let value = (low + (((high - low) * fraction) >> 10)) as i16;
+        // The second half is the first half upside down
+        if quarter >= 2 {
+            -value
+        } else {
+            value
+        }
+    }
+}
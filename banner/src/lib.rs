@@ -0,0 +1,243 @@
+//! Application logic for the banner printer
+
+#![no_std]
+#![deny(missing_docs)]
+#![deny(unsafe_code)]
+
+use core::fmt::Write;
+
+use neotron_font::Font;
+
+/// What we draw the letters with
+const FONT: Font = neotron_font::ASCII;
+
+/// The most text we can print in one go
+pub const MAX_TEXT: usize = 160;
+
+/// The widest page we can print on, in characters
+pub const MAX_WIDTH: usize = 240;
+
+/// The biggest we can make the letters when they run across the page
+pub const MAX_SCALE: usize = 4;
+
+/// Which way the letters run
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Layout {
+    /// Across the page, wrapping on to more lines if the text doesn't fit
+    Horizontal,
+    /// Down the page, on their sides, like a banner on continuous paper.
+    /// Turn the page a quarter turn anticlockwise to read it.
+    Vertical,
+}
+
+/// How to draw the banner
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Options {
+    /// Which way the letters run
+    pub layout: Layout,
+    /// The character each pixel is drawn with
+    pub fill: u8,
+    /// How wide the page is, in characters
+    pub width: usize,
+    /// How many characters across and down each pixel takes. Only used for
+    /// horizontal banners - vertical ones are sized to fill the page.
+    pub scale: usize,
+    /// End lines with CR LF, as printers expect, instead of just LF
+    pub crlf: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            layout: Layout::Horizontal,
+            fill: b'#',
+            width: 80,
+            scale: 1,
+            crlf: false,
+        }
+    }
+}
+
+/// Some text, waiting to be printed big
+pub struct Banner {
+    text: [u8; MAX_TEXT],
+    len: usize,
+}
+
+impl Banner {
+    /// Make a banner with no text
+    pub const fn new() -> Banner {
+        Banner {
+            text: [0; MAX_TEXT],
+            len: 0,
+        }
+    }
+
+    /// Add a word to the end of the text, with a space before it if it isn't
+    /// the first. Anything which doesn't fit is dropped.
+    pub fn add_word(&mut self, word: &str) {
+        if self.len > 0 {
+            self.push(b' ');
+        }
+        for ch in word.chars() {
+            // The font only has ASCII, so everything else becomes a `?`
+            self.push(if ch.is_ascii() { ch as u8 } else { b'?' });
+        }
+    }
+
+    /// Is there no text?
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Add one character to the text, if there is room
+    fn push(&mut self, byte: u8) {
+        if self.len < MAX_TEXT {
+            self.text[self.len] = byte;
+            self.len += 1;
+        }
+    }
+
+    /// Print the banner
+    pub fn print<W>(&self, out: &mut W, options: &Options) -> core::fmt::Result
+    where
+        W: Write,
+    {
+        let mut page = Page {
+            out,
+            line: [b' '; MAX_WIDTH],
+            width: options.width.clamp(FONT.height(), MAX_WIDTH),
+            crlf: options.crlf,
+        };
+        let text = &self.text[0..self.len];
+        match options.layout {
+            Layout::Horizontal => page.horizontal(text, options.fill, options.scale),
+            Layout::Vertical => page.vertical(text, options.fill),
+        }
+    }
+}
+
+impl Default for Banner {
+    fn default() -> Self {
+        Banner::new()
+    }
+}
+
+/// Somewhere to print to, one line at a time
+struct Page<'a, W> {
+    out: &'a mut W,
+    /// The line we are building up
+    line: [u8; MAX_WIDTH],
+    width: usize,
+    crlf: bool,
+}
+
+impl<W> Page<'_, W>
+where
+    W: Write,
+{
+    /// Print letters side by side, as many to a line as will fit
+    fn horizontal(&mut self, text: &[u8], fill: u8, scale: usize) -> core::fmt::Result {
+        let scale = scale.clamp(1, MAX_SCALE);
+        let cell = (FONT.width() + 1) * scale;
+        // The last letter on a line doesn't need the gap after it
+        let per_line = ((self.width + scale) / cell).max(1);
+        let mut rest = trim_start(text);
+        let mut first = true;
+        while !rest.is_empty() {
+            let (chunk, next) = split_line(rest, per_line);
+            rest = trim_start(next);
+            if !first {
+                self.end_line()?;
+            }
+            first = false;
+            // Only leave room for tails if something has one
+            let rows = if chunk.iter().any(|&b| FONT.has_tail(char::from(b))) {
+                FONT.height()
+            } else {
+                FONT.baseline()
+            };
+            for row in 0..rows {
+                self.clear();
+                for (idx, &byte) in chunk.iter().enumerate() {
+                    let glyph = FONT.glyph(char::from(byte));
+                    for col in 0..FONT.width() {
+                        if glyph.pixel(col, row) {
+                            let start = idx * cell + col * scale;
+                            self.line[start..start + scale].fill(fill);
+                        }
+                    }
+                }
+                for _ in 0..scale {
+                    self.end_line()?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Print letters one after another down the page, on their sides, with
+    /// the tops of the letters on the right
+    fn vertical(&mut self, text: &[u8], fill: u8) -> core::fmt::Result {
+        let across = self.width / FONT.height();
+        // Characters are about twice as tall as they are wide, so this keeps
+        // the pixels roughly square
+        let down = (across / 2).max(1);
+        for &byte in text {
+            let glyph = FONT.glyph(char::from(byte));
+            for col in 0..=FONT.width() {
+                self.clear();
+                // The blank column on the end is the gap between letters
+                if col < FONT.width() {
+                    for row in 0..FONT.height() {
+                        if glyph.pixel(col, row) {
+                            let start = (FONT.height() - 1 - row) * across;
+                            self.line[start..start + across].fill(fill);
+                        }
+                    }
+                }
+                for _ in 0..down {
+                    self.end_line()?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Blank the line we are building
+    fn clear(&mut self) {
+        self.line.fill(b' ');
+    }
+
+    /// Write out the line we built, without any trailing spaces
+    fn end_line(&mut self) -> core::fmt::Result {
+        let used = &self.line[0..self.width];
+        let len = used
+            .iter()
+            .rposition(|&b| b != b' ')
+            .map_or(0, |pos| pos + 1);
+        // Everything we put in the line is ASCII
+        self.out
+            .write_str(core::str::from_utf8(&used[0..len]).unwrap_or(""))?;
+        self.out.write_str(if self.crlf { "\r\n" } else { "\n" })
+    }
+}
+
+/// Skip over any spaces at the start of some text
+fn trim_start(text: &[u8]) -> &[u8] {
+    let start = text.iter().position(|&b| b != b' ').unwrap_or(text.len());
+    &text[start..]
+}
+
+/// Take up to `count` characters from the front of some text, breaking at a
+/// space if we can. Returns the line, and what is left over.
+fn split_line(text: &[u8], count: usize) -> (&[u8], &[u8]) {
+    if text.len() <= count {
+        return (text, &[]);
+    }
+    match text[0..=count].iter().rposition(|&b| b == b' ') {
+        Some(pos) if pos > 0 => (&text[0..pos], &text[pos..]),
+        // One long word, so cut it
+        _ => text.split_at(count),
+    }
+}
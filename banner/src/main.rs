@@ -0,0 +1,98 @@
+#![cfg_attr(target_os = "none", no_std)]
+#![cfg_attr(target_os = "none", no_main)]
+
+use core::ptr::addr_of_mut;
+
+use neotron_args::{Item, Opt};
+
+#[cfg(not(target_os = "none"))]
+fn main() {
+    neotron_sdk::init();
+}
+
+static mut BANNER: banner::Banner = banner::Banner::new();
+
+#[no_mangle]
+extern "C" fn neotron_main() -> i32 {
+    neotron_error::exit_code_text(real_main())
+}
+
+/// The options we understand
+static SPEC: neotron_args::Spec = neotron_args::Spec {
+    name: "banner",
+    usage: "[options] <text>...",
+    about: "Prints text in big letters, on the screen or on a printer",
+    options: &[
+        Opt::flag(
+            'v',
+            "vertical",
+            "Run the letters down the page, on their sides",
+        ),
+        Opt::value('w', "width", "columns", "How wide the page is (default 80)"),
+        Opt::value('s', "scale", "1-4", "Make the letters bigger"),
+        Opt::value(
+            'c',
+            "char",
+            "char",
+            "Draw the letters with this character (default #)",
+        ),
+        Opt::value(
+            'o',
+            "output",
+            "file",
+            "Print to a file or device instead of the screen",
+        ),
+    ],
+};
+
+fn real_main() -> Result<(), neotron_sdk::Error> {
+    let banner = unsafe { &mut *addr_of_mut!(BANNER) };
+    let mut options = banner::Options::default();
+    let mut output = None;
+    let mut args = neotron_args::Parser::new(&SPEC);
+    loop {
+        let item = match args.next_item() {
+            Ok(Some(item)) => item,
+            Ok(None) => break,
+            Err(neotron_args::Error::Help) => return Ok(()),
+            Err(e) => return Err(e.into()),
+        };
+        match item {
+            Item::Flag("vertical") => options.layout = banner::Layout::Vertical,
+            Item::Value("width", value) => options.width = args.parse("width", &value)?,
+            Item::Value("scale", value) => options.scale = args.parse("scale", &value)?,
+            Item::Value("char", value) => match value.as_bytes() {
+                [fill] if fill.is_ascii_graphic() => options.fill = *fill,
+                _ => {
+                    return Err(args
+                        .usage_error("--char needs one printable character")
+                        .into())
+                }
+            },
+            Item::Value("output", value) => output = Some(value),
+            Item::Positional(word) => banner.add_word(&word),
+            _ => {}
+        }
+    }
+    if banner.is_empty() {
+        return Err(args.usage_error("no text to print").into());
+    }
+
+    if let Some(filename) = output {
+        // Files and printers both get CR LF line endings
+        options.crlf = true;
+        let path = neotron_sdk::path::Path::new(&filename)?;
+        let mut file = neotron_sdk::File::open(
+            path,
+            neotron_sdk::Flags::WRITE | neotron_sdk::Flags::CREATE | neotron_sdk::Flags::TRUNCATE,
+        )?;
+        banner
+            .print(&mut file, &options)
+            .map_err(|_| neotron_sdk::Error::DeviceSpecific)
+    } else {
+        let mut stdout = neotron_sdk::stdout();
+        banner
+            .print(&mut stdout, &options)
+            .map_err(|_| neotron_sdk::Error::DeviceSpecific)
+    }
+}
@@ -0,0 +1,323 @@
+//! Connect Four, against the computer.
+//!
+//! The computer looks a few moves ahead, trying every move for both players.
+//! When it can't see the end of the game, it scores the board by counting the
+//! lines of four which each player could still make.
+
+use neotron_input::{KeyEvent, Keyboard};
+use neotron_sdk::console;
+
+use crate::grid::{Glyph, Grid};
+use crate::{show_status, Difficulty, Outcome};
+
+/// How many columns across the board
+const COLS: usize = 7;
+
+/// How many rows up the board
+const ROWS: usize = 6;
+
+/// The order we try moves in. The middle columns are usually best, and
+/// trying the best moves first lets us skip more of the search.
+const ORDER: [usize; COLS] = [3, 2, 4, 1, 5, 0, 6];
+
+/// The score for a win. Quicker wins add a little more.
+const WIN: i32 = 1_000_000;
+
+/// The four directions a line can go in: across, up, and the two diagonals
+const DIRECTIONS: [(i8, i8); 4] = [(1, 0), (0, 1), (1, 1), (1, -1)];
+
+/// What is in a cell
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Disc {
+    Empty,
+    /// The human player
+    Red,
+    /// The computer
+    Yellow,
+}
+
+impl Disc {
+    /// The other player's disc
+    fn other(self) -> Disc {
+        match self {
+            Disc::Red => Disc::Yellow,
+            Disc::Yellow => Disc::Red,
+            Disc::Empty => Disc::Empty,
+        }
+    }
+}
+
+/// A game of Connect Four
+pub struct ConnectFour {
+    /// The cells, a column at a time, from the bottom up
+    cells: [[Disc; ROWS]; COLS],
+    /// How many discs are in each column
+    heights: [usize; COLS],
+    /// Which column the player is about to drop into
+    cursor: usize,
+    /// The line of four which won the game, if there is one
+    winning_line: Option<[(usize, usize); 4]>,
+}
+
+impl ConnectFour {
+    /// Make a new game
+    pub const fn new() -> ConnectFour {
+        ConnectFour {
+            cells: [[Disc::Empty; ROWS]; COLS],
+            heights: [0; COLS],
+            cursor: COLS / 2,
+            winning_line: None,
+        }
+    }
+
+    /// Play one game.
+    ///
+    /// Returns `None` if the player gave up part way through.
+    pub fn play(
+        &mut self,
+        keyboard: &mut Keyboard,
+        stdout: &mut neotron_sdk::File,
+        width: u8,
+        human_first: bool,
+        difficulty: Difficulty,
+    ) -> Option<Outcome> {
+        *self = ConnectFour::new();
+        let grid = Grid::new(COLS as u8, ROWS as u8, width, 4);
+        let mut turn = if human_first { Disc::Red } else { Disc::Yellow };
+        let mut moves = 0;
+        loop {
+            self.draw(stdout, &grid, turn == Disc::Red);
+            let col = if turn == Disc::Yellow {
+                self.best_move(difficulty.depth())
+            } else {
+                loop {
+                    match keyboard.wait() {
+                        KeyEvent::Left if self.cursor > 0 => self.cursor -= 1,
+                        KeyEvent::Right if self.cursor < COLS - 1 => self.cursor += 1,
+                        KeyEvent::Char(digit @ '1'..='7') => {
+                            self.cursor = digit as usize - '1' as usize;
+                            if self.heights[self.cursor] < ROWS {
+                                break;
+                            }
+                        }
+                        KeyEvent::Down | KeyEvent::Char(' ') | KeyEvent::Enter
+                            if self.heights[self.cursor] < ROWS =>
+                        {
+                            break;
+                        }
+                        KeyEvent::Char('q' | 'Q') | KeyEvent::Escape => return None,
+                        _ => {}
+                    }
+                    self.draw(stdout, &grid, true);
+                }
+                self.cursor
+            };
+            self.drop_disc(col, turn);
+            moves += 1;
+            if self.wins_at(col) {
+                self.winning_line = self.find_line(col);
+                self.draw(stdout, &grid, false);
+                return Some(if turn == Disc::Red {
+                    Outcome::Won
+                } else {
+                    Outcome::Lost
+                });
+            }
+            if moves == COLS * ROWS {
+                self.draw(stdout, &grid, false);
+                return Some(Outcome::Drawn);
+            }
+            turn = turn.other();
+        }
+    }
+
+    /// Draw the board
+    fn draw(&self, stdout: &mut neotron_sdk::File, grid: &Grid, your_turn: bool) {
+        let marker = Glyph {
+            text: " ▼ ",
+            colour: console::SgrParam::FgRed,
+            bold: true,
+        };
+        grid.draw_column_marker(stdout, your_turn.then_some(self.cursor as u8), marker);
+        grid.draw(stdout, None, |col, row| {
+            let (col, row) = (usize::from(col), ROWS - 1 - usize::from(row));
+            let winner = self
+                .winning_line
+                .is_some_and(|line| line.contains(&(col, row)));
+            let text = if winner { "███" } else { "▐█▌" };
+            match self.cells[col][row] {
+                Disc::Empty => Glyph::EMPTY,
+                Disc::Red => Glyph {
+                    text,
+                    colour: console::SgrParam::FgRed,
+                    bold: winner,
+                },
+                Disc::Yellow => Glyph {
+                    text,
+                    colour: console::SgrParam::FgYellow,
+                    bold: winner,
+                },
+            }
+        });
+        let status = if your_turn {
+            "You are red. Pick a column with 1-7, or the arrows and Enter."
+        } else if self.winning_line.is_none() {
+            "Thinking..."
+        } else {
+            ""
+        };
+        show_status(stdout, grid.bottom() + 1, status);
+    }
+
+    /// Put a disc on top of a column
+    fn drop_disc(&mut self, col: usize, disc: Disc) {
+        self.cells[col][self.heights[col]] = disc;
+        self.heights[col] += 1;
+    }
+
+    /// Take the top disc off a column
+    fn undo(&mut self, col: usize) {
+        self.heights[col] -= 1;
+        self.cells[col][self.heights[col]] = Disc::Empty;
+    }
+
+    /// Did the disc on top of this column make a line of four?
+    fn wins_at(&self, col: usize) -> bool {
+        self.find_line(col).is_some()
+    }
+
+    /// Find the line of four made by the disc on top of this column
+    fn find_line(&self, col: usize) -> Option<[(usize, usize); 4]> {
+        let row = self.heights[col] - 1;
+        let disc = self.cells[col][row];
+        for (dx, dy) in DIRECTIONS {
+            // Walk back to the start of the run, then count along it
+            let (mut start_col, mut start_row) = (col as i8, row as i8);
+            while self.get(start_col - dx, start_row - dy) == Some(disc) {
+                start_col -= dx;
+                start_row -= dy;
+            }
+            let mut line = [(0, 0); 4];
+            let mut count = 0;
+            let (mut c, mut r) = (start_col, start_row);
+            while count < 4 && self.get(c, r) == Some(disc) {
+                line[count] = (c as usize, r as usize);
+                count += 1;
+                c += dx;
+                r += dy;
+            }
+            if count == 4 {
+                return Some(line);
+            }
+        }
+        None
+    }
+
+    /// Get a cell, if it is on the board
+    fn get(&self, col: i8, row: i8) -> Option<Disc> {
+        if (0..COLS as i8).contains(&col) && (0..ROWS as i8).contains(&row) {
+            Some(self.cells[col as usize][row as usize])
+        } else {
+            None
+        }
+    }
+
+    /// Pick the best column for the computer
+    fn best_move(&mut self, depth: u8) -> usize {
+        let mut best = (i32::MIN, ORDER[0]);
+        for col in ORDER {
+            if self.heights[col] == ROWS {
+                continue;
+            }
+            self.drop_disc(col, Disc::Yellow);
+            let score = if self.wins_at(col) {
+                WIN + i32::from(depth)
+            } else {
+                -self.negamax(Disc::Red, depth.saturating_sub(1), -i32::MAX, i32::MAX)
+            };
+            self.undo(col);
+            if score > best.0 {
+                best = (score, col);
+            }
+        }
+        best.1
+    }
+
+    /// Score the board from the point of view of `player`, who is about to
+    /// move, looking `depth` moves ahead
+    fn negamax(&mut self, player: Disc, depth: u8, mut alpha: i32, beta: i32) -> i32 {
+        if depth == 0 {
+            return self.evaluate(player);
+        }
+        let mut best = None;
+        for col in ORDER {
+            if self.heights[col] == ROWS {
+                continue;
+            }
+            self.drop_disc(col, player);
+            let score = if self.wins_at(col) {
+                WIN + i32::from(depth)
+            } else {
+                -self.negamax(player.other(), depth - 1, -beta, -alpha)
+            };
+            self.undo(col);
+            best = Some(best.map_or(score, |best: i32| best.max(score)));
+            alpha = alpha.max(score);
+            if alpha >= beta {
+                break;
+            }
+        }
+        // A full board is a draw
+        best.unwrap_or(0)
+    }
+
+    /// Guess how good the board is for `player`, without looking ahead
+    fn evaluate(&self, player: Disc) -> i32 {
+        let mut score = 0;
+        // The middle column is part of the most lines
+        for disc in self.cells[COLS / 2].iter() {
+            if *disc == player {
+                score += 3;
+            } else if *disc == player.other() {
+                score -= 3;
+            }
+        }
+        for (dx, dy) in DIRECTIONS {
+            for col in 0..COLS as i8 {
+                for row in 0..ROWS as i8 {
+                    let (mut mine, mut theirs) = (0, 0);
+                    for step in 0..4 {
+                        match self.get(col + dx * step, row + dy * step) {
+                            None => {
+                                mine = -1;
+                                break;
+                            }
+                            Some(disc) if disc == player => mine += 1,
+                            Some(Disc::Empty) => {}
+                            Some(_) => theirs += 1,
+                        }
+                    }
+                    // Lines which run off the board, or which both players
+                    // have a disc in, don't count
+                    if mine < 0 || (mine > 0 && theirs > 0) {
+                        continue;
+                    }
+                    score += match (mine, theirs) {
+                        (3, _) => 5,
+                        (2, _) => 2,
+                        (_, 3) => -4,
+                        (_, 2) => -1,
+                        _ => 0,
+                    };
+                }
+            }
+        }
+        score
+    }
+}
+
+impl Default for ConnectFour {
+    fn default() -> Self {
+        ConnectFour::new()
+    }
+}
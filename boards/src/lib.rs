@@ -0,0 +1,262 @@
+//! Game logic for the board games - Tic-tac-toe and Connect Four
+
+#![no_std]
+#![deny(missing_docs)]
+#![deny(unsafe_code)]
+
+use core::fmt::Write;
+
+use neotron_input::{KeyEvent, Keyboard};
+use neotron_sdk::console;
+use neotron_tui::Line;
+
+pub mod connect4;
+pub mod grid;
+pub mod tictactoe;
+
+/// How a game ended, from the human player's point of view
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    /// The human won
+    Won,
+    /// The computer won
+    Lost,
+    /// Nobody won
+    Drawn,
+}
+
+/// How hard the computer tries at Connect Four
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Difficulty {
+    /// Only looks a couple of moves ahead
+    Easy,
+    /// Looks four moves ahead
+    Normal,
+    /// Looks six moves ahead, which can take a few seconds
+    Hard,
+}
+
+impl Difficulty {
+    /// How many moves ahead the computer looks
+    pub fn depth(self) -> u8 {
+        match self {
+            Difficulty::Easy => 2,
+            Difficulty::Normal => 4,
+            Difficulty::Hard => 6,
+        }
+    }
+
+    /// The next difficulty, going round in a loop
+    fn next(self) -> Difficulty {
+        match self {
+            Difficulty::Easy => Difficulty::Normal,
+            Difficulty::Normal => Difficulty::Hard,
+            Difficulty::Hard => Difficulty::Easy,
+        }
+    }
+
+    /// The name of this difficulty
+    fn name(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Normal => "Normal",
+            Difficulty::Hard => "Hard",
+        }
+    }
+}
+
+/// Which game we are playing
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Game {
+    TicTacToe,
+    ConnectFour,
+}
+
+impl Game {
+    /// The name of the game
+    fn name(self) -> &'static str {
+        match self {
+            Game::TicTacToe => "TIC-TAC-TOE",
+            Game::ConnectFour => "CONNECT FOUR",
+        }
+    }
+}
+
+/// Wins, losses and draws for one game
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct Tally {
+    won: u16,
+    lost: u16,
+    drawn: u16,
+}
+
+impl Tally {
+    /// No games played yet
+    const NEW: Tally = Tally {
+        won: 0,
+        lost: 0,
+        drawn: 0,
+    };
+
+    /// Count a game
+    fn add(&mut self, outcome: Outcome) {
+        let count = match outcome {
+            Outcome::Won => &mut self.won,
+            Outcome::Lost => &mut self.lost,
+            Outcome::Drawn => &mut self.drawn,
+        };
+        *count = count.saturating_add(1);
+    }
+}
+
+/// Represents the board games application
+///
+/// You pick a game from the menu, and can play as many games as you like.
+pub struct App {
+    stdout: neotron_sdk::File,
+    keyboard: Keyboard,
+    width: u8,
+    tictactoe: tictactoe::TicTacToe,
+    connect4: connect4::ConnectFour,
+    difficulty: Difficulty,
+    /// Scores for tic-tac-toe, then Connect Four
+    tallies: [Tally; 2],
+}
+
+impl App {
+    /// Make a new board games application.
+    ///
+    /// You can give the screen size in characters.
+    pub const fn new(width: u8, _height: u8) -> App {
+        App {
+            stdout: neotron_sdk::stdout(),
+            keyboard: Keyboard::new(),
+            width,
+            tictactoe: tictactoe::TicTacToe::new(),
+            connect4: connect4::ConnectFour::new(),
+            difficulty: Difficulty::Normal,
+            tallies: [Tally::NEW; 2],
+        }
+    }
+
+    /// Show the menu and play games until the user quits
+    pub fn play(&mut self) {
+        console::cursor_off(&mut self.stdout);
+        loop {
+            self.menu();
+            match self.keyboard.wait() {
+                KeyEvent::Char('1') => self.play_game(Game::TicTacToe),
+                KeyEvent::Char('2') => self.play_game(Game::ConnectFour),
+                KeyEvent::Char('d' | 'D') => self.difficulty = self.difficulty.next(),
+                KeyEvent::Char('q' | 'Q') => break,
+                _ => {}
+            }
+        }
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        console::clear_screen(&mut self.stdout);
+        console::move_cursor(&mut self.stdout, console::Position::origin());
+        console::cursor_on(&mut self.stdout);
+    }
+
+    /// Draw the game menu
+    fn menu(&mut self) {
+        self.clear();
+        let mut line = Line::new();
+        self.centre(4, "NEOTRON BOARD GAMES", true);
+        self.centre(7, "1 - Tic-tac-toe              ", false);
+        self.centre(8, "2 - Connect Four             ", false);
+        let _ = write!(line, "D - Difficulty: {:<13}", self.difficulty.name());
+        self.centre(10, line.as_str(), false);
+        self.centre(12, "Q - Quit                     ", false);
+        for (idx, game) in [Game::TicTacToe, Game::ConnectFour].iter().enumerate() {
+            let tally = self.tallies[idx];
+            let mut line = Line::new();
+            let _ = write!(
+                line,
+                "{:<13} won {:>3}  lost {:>3}  drawn {:>3}",
+                game.name(),
+                tally.won,
+                tally.lost,
+                tally.drawn
+            );
+            self.centre(15 + idx as u8, line.as_str(), false);
+        }
+        self.centre(19, "In a game, Q goes back to this menu", false);
+    }
+
+    /// Play one game after another, until the user goes back to the menu
+    fn play_game(&mut self, game: Game) {
+        let mut human_first = true;
+        loop {
+            self.clear();
+            self.centre(1, game.name(), true);
+            let outcome = match game {
+                Game::TicTacToe => self.tictactoe.play(
+                    &mut self.keyboard,
+                    &mut self.stdout,
+                    self.width,
+                    human_first,
+                ),
+                Game::ConnectFour => self.connect4.play(
+                    &mut self.keyboard,
+                    &mut self.stdout,
+                    self.width,
+                    human_first,
+                    self.difficulty,
+                ),
+            };
+            let Some(outcome) = outcome else {
+                return;
+            };
+            self.tallies[game as usize].add(outcome);
+            let message = match outcome {
+                Outcome::Won => "You win! Press N for a new game, or Q for the menu.",
+                Outcome::Lost => "I win! Press N for a new game, or Q for the menu.",
+                Outcome::Drawn => "It's a draw. Press N for a new game, or Q for the menu.",
+            };
+            show_status(&mut self.stdout, 22, message);
+            loop {
+                match self.keyboard.wait() {
+                    KeyEvent::Char('n' | 'N') => break,
+                    KeyEvent::Char('q' | 'Q') | KeyEvent::Escape => return,
+                    _ => {}
+                }
+            }
+            // Take turns to go first
+            human_first = !human_first;
+        }
+    }
+
+    /// Clear the screen
+    fn clear(&mut self) {
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        console::clear_screen(&mut self.stdout);
+    }
+
+    /// Write some text in the middle of a row
+    fn centre(&mut self, row: u8, text: &str, title: bool) {
+        let col = (usize::from(self.width).saturating_sub(text.chars().count())) / 2;
+        console::move_cursor(
+            &mut self.stdout,
+            console::Position {
+                row,
+                col: col as u8,
+            },
+        );
+        if title {
+            console::set_sgr(
+                &mut self.stdout,
+                [console::SgrParam::Bold, console::SgrParam::FgYellow],
+            );
+        }
+        let _ = self.stdout.write_str(text);
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+    }
+}
+
+/// Write a line of text in the middle of the screen, clearing whatever was
+/// there before
+fn show_status(stdout: &mut neotron_sdk::File, row: u8, text: &str) {
+    console::move_cursor(stdout, console::Position { row, col: 0 });
+    let _ = write!(stdout, "{:^80}", text);
+}
@@ -0,0 +1,230 @@
+//! Tic-tac-toe, against a computer which never loses.
+//!
+//! The computer searches every possible game from the current position, so
+//! the best you can do is a draw.
+
+use neotron_input::{KeyEvent, Keyboard};
+use neotron_sdk::console;
+
+use crate::grid::{Glyph, Grid};
+use crate::{show_status, Outcome};
+
+/// The eight lines of three
+const LINES: [[usize; 3]; 8] = [
+    [0, 1, 2],
+    [3, 4, 5],
+    [6, 7, 8],
+    [0, 3, 6],
+    [1, 4, 7],
+    [2, 5, 8],
+    [0, 4, 8],
+    [2, 4, 6],
+];
+
+/// What is in a square
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Mark {
+    Empty,
+    /// The human player
+    Cross,
+    /// The computer
+    Nought,
+}
+
+impl Mark {
+    /// The other player's mark
+    fn other(self) -> Mark {
+        match self {
+            Mark::Cross => Mark::Nought,
+            Mark::Nought => Mark::Cross,
+            Mark::Empty => Mark::Empty,
+        }
+    }
+}
+
+/// A game of tic-tac-toe
+pub struct TicTacToe {
+    board: [Mark; 9],
+    /// Which square the cursor is on
+    cursor: usize,
+}
+
+impl TicTacToe {
+    /// Make a new game
+    pub const fn new() -> TicTacToe {
+        TicTacToe {
+            board: [Mark::Empty; 9],
+            cursor: 4,
+        }
+    }
+
+    /// Play one game.
+    ///
+    /// Returns `None` if the player gave up part way through.
+    pub fn play(
+        &mut self,
+        keyboard: &mut Keyboard,
+        stdout: &mut neotron_sdk::File,
+        width: u8,
+        human_first: bool,
+    ) -> Option<Outcome> {
+        self.board = [Mark::Empty; 9];
+        self.cursor = 4;
+        let grid = Grid::new(3, 3, width, 4);
+        let mut turn = if human_first {
+            Mark::Cross
+        } else {
+            Mark::Nought
+        };
+        loop {
+            self.draw(stdout, &grid, turn == Mark::Cross);
+            if let Some(outcome) = self.outcome() {
+                return Some(outcome);
+            }
+            if turn == Mark::Nought {
+                let square = best_move(&mut self.board, Mark::Nought);
+                self.board[square] = Mark::Nought;
+            } else {
+                loop {
+                    match keyboard.wait() {
+                        KeyEvent::Up if self.cursor >= 3 => self.cursor -= 3,
+                        KeyEvent::Down if self.cursor < 6 => self.cursor += 3,
+                        KeyEvent::Left if !self.cursor.is_multiple_of(3) => self.cursor -= 1,
+                        KeyEvent::Right if self.cursor % 3 < 2 => self.cursor += 1,
+                        KeyEvent::Char(digit @ '1'..='9') => {
+                            self.cursor = digit as usize - '1' as usize;
+                            if self.board[self.cursor] == Mark::Empty {
+                                break;
+                            }
+                        }
+                        KeyEvent::Char(' ') | KeyEvent::Enter
+                            if self.board[self.cursor] == Mark::Empty =>
+                        {
+                            break;
+                        }
+                        KeyEvent::Char('q' | 'Q') | KeyEvent::Escape => return None,
+                        _ => {}
+                    }
+                    self.draw(stdout, &grid, true);
+                }
+                self.board[self.cursor] = Mark::Cross;
+            }
+            turn = turn.other();
+        }
+    }
+
+    /// Has the game finished, and if so, how?
+    fn outcome(&self) -> Option<Outcome> {
+        match winner(&self.board) {
+            Mark::Cross => Some(Outcome::Won),
+            Mark::Nought => Some(Outcome::Lost),
+            Mark::Empty if self.board.contains(&Mark::Empty) => None,
+            Mark::Empty => Some(Outcome::Drawn),
+        }
+    }
+
+    /// Draw the board
+    fn draw(&self, stdout: &mut neotron_sdk::File, grid: &Grid, your_turn: bool) {
+        let cursor = if your_turn {
+            Some(((self.cursor % 3) as u8, (self.cursor / 3) as u8))
+        } else {
+            None
+        };
+        grid.draw(stdout, cursor, |col, row| {
+            let square = usize::from(row * 3 + col);
+            match self.board[square] {
+                Mark::Cross => Glyph {
+                    text: " X ",
+                    colour: console::SgrParam::FgCyan,
+                    bold: true,
+                },
+                Mark::Nought => Glyph {
+                    text: " O ",
+                    colour: console::SgrParam::FgYellow,
+                    bold: true,
+                },
+                // Show which number key picks this square
+                Mark::Empty => Glyph {
+                    text: SQUARE_NUMBERS[square],
+                    colour: console::SgrParam::FgBlue,
+                    bold: false,
+                },
+            }
+        });
+        let status = if your_turn {
+            "You are X. Pick a square with 1-9, or the arrows and Enter."
+        } else {
+            "Thinking..."
+        };
+        show_status(stdout, grid.bottom() + 1, status);
+    }
+}
+
+impl Default for TicTacToe {
+    fn default() -> Self {
+        TicTacToe::new()
+    }
+}
+
+/// The labels for the empty squares
+const SQUARE_NUMBERS: [&str; 9] = [
+    " 1 ", " 2 ", " 3 ", " 4 ", " 5 ", " 6 ", " 7 ", " 8 ", " 9 ",
+];
+
+/// Who has three in a row, if anyone?
+fn winner(board: &[Mark; 9]) -> Mark {
+    for line in LINES.iter() {
+        let mark = board[line[0]];
+        if mark != Mark::Empty && board[line[1]] == mark && board[line[2]] == mark {
+            return mark;
+        }
+    }
+    Mark::Empty
+}
+
+/// Find the best square for `player`.
+///
+/// The board must have at least one empty square. We put marks on it while
+/// searching, but put it back how it was before returning.
+fn best_move(board: &mut [Mark; 9], player: Mark) -> usize {
+    let mut best = (i8::MIN, 0);
+    for square in 0..9 {
+        if board[square] != Mark::Empty {
+            continue;
+        }
+        board[square] = player;
+        let score = -negamax(board, player.other(), 1, -i8::MAX, i8::MAX);
+        board[square] = Mark::Empty;
+        if score > best.0 {
+            best = (score, square);
+        }
+    }
+    best.1
+}
+
+/// Score the board from the point of view of `player`, who is about to move.
+///
+/// Winning sooner, or losing later, scores better.
+fn negamax(board: &mut [Mark; 9], player: Mark, depth: i8, mut alpha: i8, beta: i8) -> i8 {
+    match winner(board) {
+        Mark::Empty => {}
+        // The last move was by the other player, so they have won
+        _ => return depth - 10,
+    }
+    let mut best = None;
+    for square in 0..9 {
+        if board[square] != Mark::Empty {
+            continue;
+        }
+        board[square] = player;
+        let score = -negamax(board, player.other(), depth + 1, -beta, -alpha);
+        board[square] = Mark::Empty;
+        best = Some(best.map_or(score, |best: i8| best.max(score)));
+        alpha = alpha.max(score);
+        if alpha >= beta {
+            break;
+        }
+    }
+    // No squares left is a draw
+    best.unwrap_or(0)
+}
@@ -0,0 +1,303 @@
+//! Expression evaluation for the calculator
+//!
+//! This is a simple recursive-descent parser which evaluates as it goes. It
+//! follows the C operator precedence rules, from loosest to tightest:
+//!
+//! * `|`
+//! * `^`
+//! * `&`
+//! * `<<` and `>>`
+//! * `+` and `-`
+//! * `*`, `/` and `%`
+//! * unary `-` and `~`
+//!
+//! Numbers can be given in decimal (`123`), hex (`0x7B`), octal (`0o173`) or
+//! binary (`0b1111011`), with `_` allowed as a digit separator. Previous
+//! results can be referred to as `$0` (the most recent), `$1`, and so on. A
+//! bare `$` is the same as `$0`.
+//!
+//! All arithmetic is performed on 32-bit values and wraps on overflow.
+
+/// Things that can go wrong when evaluating an expression
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// The expression ended before we expected it to
+    UnexpectedEnd,
+    /// We found a character we didn't expect
+    UnexpectedChar(char),
+    /// A number was badly formed, or too large
+    BadNumber,
+    /// You tried to divide by zero
+    DivideByZero,
+    /// You referred to a register that doesn't have a value
+    NoSuchRegister,
+}
+
+impl Error {
+    /// Get a human-readable description of this error
+    pub fn describe(self) -> &'static str {
+        match self {
+            Error::UnexpectedEnd => "Unexpected end of expression",
+            Error::UnexpectedChar(_) => "Unexpected character",
+            Error::BadNumber => "Bad number",
+            Error::DivideByZero => "Divide by zero",
+            Error::NoSuchRegister => "No such register",
+        }
+    }
+}
+
+/// Evaluate an expression.
+///
+/// The `registers` slice holds previous results, most recent first.
+pub fn evaluate(input: &str, registers: &[u32]) -> Result<u32, Error> {
+    let mut parser = Parser {
+        input: input.as_bytes(),
+        pos: 0,
+        registers,
+    };
+    let value = parser.parse_or()?;
+    parser.skip_whitespace();
+    match parser.peek() {
+        None => Ok(value),
+        Some(ch) => Err(Error::UnexpectedChar(char::from(ch))),
+    }
+}
+
+/// Holds the state of our recursive-descent parser
+struct Parser<'a> {
+    input: &'a [u8],
+    pos: usize,
+    registers: &'a [u32],
+}
+
+impl<'a> Parser<'a> {
+    /// Look at the next byte, without consuming it
+    fn peek(&self) -> Option<u8> {
+        self.input.get(self.pos).cloned()
+    }
+
+    /// Look at the byte after next, without consuming anything
+    fn peek_second(&self) -> Option<u8> {
+        self.input.get(self.pos + 1).cloned()
+    }
+
+    /// Step over any spaces
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ' | b'\t') = self.peek() {
+            self.pos += 1;
+        }
+    }
+
+    /// Handle `a | b`
+    fn parse_or(&mut self) -> Result<u32, Error> {
+        let mut value = self.parse_xor()?;
+        loop {
+            self.skip_whitespace();
+            if self.peek() == Some(b'|') {
+                self.pos += 1;
+                value |= self.parse_xor()?;
+            } else {
+                return Ok(value);
+            }
+        }
+    }
+
+    /// Handle `a ^ b`
+    fn parse_xor(&mut self) -> Result<u32, Error> {
+        let mut value = self.parse_and()?;
+        loop {
+            self.skip_whitespace();
+            if self.peek() == Some(b'^') {
+                self.pos += 1;
+                value ^= self.parse_and()?;
+            } else {
+                return Ok(value);
+            }
+        }
+    }
+
+    /// Handle `a & b`
+    fn parse_and(&mut self) -> Result<u32, Error> {
+        let mut value = self.parse_shift()?;
+        loop {
+            self.skip_whitespace();
+            if self.peek() == Some(b'&') {
+                self.pos += 1;
+                value &= self.parse_shift()?;
+            } else {
+                return Ok(value);
+            }
+        }
+    }
+
+    /// Handle `a << b` and `a >> b`
+    fn parse_shift(&mut self) -> Result<u32, Error> {
+        let mut value = self.parse_sum()?;
+        loop {
+            self.skip_whitespace();
+            match (self.peek(), self.peek_second()) {
+                (Some(b'<'), Some(b'<')) => {
+                    self.pos += 2;
+                    let rhs = self.parse_sum()?;
+                    value = value.checked_shl(rhs).unwrap_or(0);
+                }
+                (Some(b'>'), Some(b'>')) => {
+                    self.pos += 2;
+                    let rhs = self.parse_sum()?;
+                    value = value.checked_shr(rhs).unwrap_or(0);
+                }
+                _ => {
+                    return Ok(value);
+                }
+            }
+        }
+    }
+
+    /// Handle `a + b` and `a - b`
+    fn parse_sum(&mut self) -> Result<u32, Error> {
+        let mut value = self.parse_product()?;
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b'+') => {
+                    self.pos += 1;
+                    value = value.wrapping_add(self.parse_product()?);
+                }
+                Some(b'-') => {
+                    self.pos += 1;
+                    value = value.wrapping_sub(self.parse_product()?);
+                }
+                _ => {
+                    return Ok(value);
+                }
+            }
+        }
+    }
+
+    /// Handle `a * b`, `a / b` and `a % b`
+    ///
+    /// Division is signed, as that's usually what people mean when they type
+    /// `-10 / 3`.
+    fn parse_product(&mut self) -> Result<u32, Error> {
+        let mut value = self.parse_unary()?;
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b'*') => {
+                    self.pos += 1;
+                    value = value.wrapping_mul(self.parse_unary()?);
+                }
+                Some(b'/') => {
+                    self.pos += 1;
+                    let rhs = self.parse_unary()? as i32;
+                    if rhs == 0 {
+                        return Err(Error::DivideByZero);
+                    }
+                    value = (value as i32).wrapping_div(rhs) as u32;
+                }
+                Some(b'%') => {
+                    self.pos += 1;
+                    let rhs = self.parse_unary()? as i32;
+                    if rhs == 0 {
+                        return Err(Error::DivideByZero);
+                    }
+                    value = (value as i32).wrapping_rem(rhs) as u32;
+                }
+                _ => {
+                    return Ok(value);
+                }
+            }
+        }
+    }
+
+    /// Handle `-a` and `~a`
+    fn parse_unary(&mut self) -> Result<u32, Error> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(b'-') => {
+                self.pos += 1;
+                Ok(self.parse_unary()?.wrapping_neg())
+            }
+            Some(b'~') => {
+                self.pos += 1;
+                Ok(!self.parse_unary()?)
+            }
+            _ => self.parse_primary(),
+        }
+    }
+
+    /// Handle numbers, registers and bracketed expressions
+    fn parse_primary(&mut self) -> Result<u32, Error> {
+        self.skip_whitespace();
+        match self.peek() {
+            None => Err(Error::UnexpectedEnd),
+            Some(b'(') => {
+                self.pos += 1;
+                let value = self.parse_or()?;
+                self.skip_whitespace();
+                match self.peek() {
+                    Some(b')') => {
+                        self.pos += 1;
+                        Ok(value)
+                    }
+                    Some(ch) => Err(Error::UnexpectedChar(char::from(ch))),
+                    None => Err(Error::UnexpectedEnd),
+                }
+            }
+            Some(b'$') => {
+                self.pos += 1;
+                let index = if matches!(self.peek(), Some(b'0'..=b'9')) {
+                    self.parse_digits(10)? as usize
+                } else {
+                    0
+                };
+                self.registers
+                    .get(index)
+                    .cloned()
+                    .ok_or(Error::NoSuchRegister)
+            }
+            Some(b'0') => {
+                let radix = match self.peek_second() {
+                    Some(b'x' | b'X') => 16,
+                    Some(b'o' | b'O') => 8,
+                    Some(b'b' | b'B') => 2,
+                    _ => 10,
+                };
+                if radix != 10 {
+                    self.pos += 2;
+                }
+                self.parse_digits(radix)
+            }
+            Some(b'1'..=b'9') => self.parse_digits(10),
+            Some(ch) => Err(Error::UnexpectedChar(char::from(ch))),
+        }
+    }
+
+    /// Convert a run of digits in the given radix into a number.
+    ///
+    /// There must be at least one digit.
+    fn parse_digits(&mut self, radix: u32) -> Result<u32, Error> {
+        let mut value: u32 = 0;
+        let mut num_digits = 0;
+        while let Some(ch) = self.peek() {
+            if ch == b'_' {
+                self.pos += 1;
+                continue;
+            }
+            let Some(digit) = char::from(ch).to_digit(radix) else {
+                break;
+            };
+            value = value
+                .checked_mul(radix)
+                .and_then(|v| v.checked_add(digit))
+                .ok_or(Error::BadNumber)?;
+            num_digits += 1;
+            self.pos += 1;
+        }
+        if num_digits == 0 {
+            Err(Error::BadNumber)
+        } else {
+            Ok(value)
+        }
+    }
+}
@@ -0,0 +1,252 @@
+//! Application logic for the Programmer's Calculator
+
+#![no_std]
+#![deny(missing_docs)]
+#![deny(unsafe_code)]
+
+use core::fmt::Write;
+
+use neotron_input::{KeyEvent, Keyboard};
+use neotron_sdk::console;
+
+pub mod expr;
+
+/// Represents the Calculator application
+pub struct App {
+    width: u8,
+    height: u8,
+    stdout: neotron_sdk::File,
+    keyboard: Keyboard,
+    /// Previous results, most recent first
+    registers: [u32; Self::MAX_REGISTERS],
+    /// How many of the registers contain a result
+    num_registers: usize,
+    /// The expression being typed
+    input: [u8; Self::MAX_INPUT],
+    /// How many bytes of `input` are in use
+    input_len: usize,
+}
+
+impl App {
+    /// The most results we remember
+    const MAX_REGISTERS: usize = 16;
+    /// The longest expression we accept
+    const MAX_INPUT: usize = 72;
+    /// The row on which the register history starts
+    const FIRST_REGISTER_ROW: u8 = 3;
+
+    /// Make a new calculator application.
+    ///
+    /// You can give the screen size in characters.
+    pub const fn new(width: u8, height: u8) -> App {
+        App {
+            width,
+            height,
+            stdout: neotron_sdk::stdout(),
+            keyboard: Keyboard::new(),
+            registers: [0; Self::MAX_REGISTERS],
+            num_registers: 0,
+            input: [0; Self::MAX_INPUT],
+            input_len: 0,
+        }
+    }
+
+    /// Run the calculator.
+    ///
+    /// Reads expressions and prints the results, until the user enters `q`.
+    pub fn play(&mut self) {
+        self.clear_screen();
+        self.draw_registers();
+        self.draw_input();
+
+        loop {
+            match self.keyboard.wait() {
+                KeyEvent::Enter => {
+                    let quit = self.enter();
+                    if quit {
+                        break;
+                    }
+                }
+                KeyEvent::Backspace => {
+                    // Backspace or Delete
+                    self.input_len = self.input_len.saturating_sub(1);
+                    self.draw_input();
+                }
+                KeyEvent::Escape => {
+                    // Escape clears the line
+                    self.input_len = 0;
+                    self.draw_input();
+                }
+                KeyEvent::Char(ch @ ' '..='~') if self.input_len < Self::MAX_INPUT => {
+                    self.input[self.input_len] = ch as u8;
+                    self.input_len += 1;
+                    self.draw_input();
+                }
+                _ => {
+                    // ignore
+                }
+            }
+        }
+
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        console::clear_screen(&mut self.stdout);
+        console::move_cursor(&mut self.stdout, console::Position::origin());
+    }
+
+    /// Process the line the user just entered.
+    ///
+    /// Returns `true` if the user asked to quit.
+    fn enter(&mut self) -> bool {
+        let input = core::str::from_utf8(&self.input[0..self.input_len]).unwrap_or("");
+        match input.trim() {
+            "" => {
+                // Nothing to do
+                return false;
+            }
+            "q" | "quit" => {
+                return true;
+            }
+            "c" | "clear" => {
+                self.num_registers = 0;
+                self.input_len = 0;
+                self.draw_registers();
+                self.draw_input();
+                return false;
+            }
+            _ => {}
+        }
+        match expr::evaluate(input, &self.registers[0..self.num_registers]) {
+            Ok(value) => {
+                self.registers.copy_within(0..Self::MAX_REGISTERS - 1, 1);
+                self.registers[0] = value;
+                if self.num_registers < Self::MAX_REGISTERS {
+                    self.num_registers += 1;
+                }
+                self.input_len = 0;
+                self.draw_registers();
+                self.draw_input();
+            }
+            Err(e) => {
+                // Leave the input alone so they can fix it
+                self.draw_error(e);
+            }
+        }
+        false
+    }
+
+    /// Clear the screen and draw the title and help text.
+    fn clear_screen(&mut self) {
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        console::clear_screen(&mut self.stdout);
+        console::cursor_on(&mut self.stdout);
+        console::move_cursor(&mut self.stdout, console::Position::origin());
+        console::set_sgr(
+            &mut self.stdout,
+            [console::SgrParam::Bold, console::SgrParam::FgYellow],
+        );
+        let _ = self.stdout.write_str("Neotron Programmer's Calculator");
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        console::move_cursor(&mut self.stdout, console::Position { row: 1, col: 0 });
+        let _ = self.stdout.write_str(
+            "Operators: | ^ & << >> + - * / % ~ ( )   Numbers: 123 0x7B 0o173 0b1111011",
+        );
+        console::move_cursor(&mut self.stdout, console::Position { row: 2, col: 0 });
+        let _ = self
+            .stdout
+            .write_str("$n is result n, $ is the last result. Enter 'c' to clear, 'q' to quit.");
+    }
+
+    /// How many registers can we fit on the screen?
+    fn visible_registers(&self) -> usize {
+        let available = self.height.saturating_sub(Self::FIRST_REGISTER_ROW + 4);
+        usize::from(available).min(Self::MAX_REGISTERS)
+    }
+
+    /// Draw the register history, most recent at the bottom.
+    fn draw_registers(&mut self) {
+        let visible = self.visible_registers();
+        for slot in 0..visible {
+            let row = Self::FIRST_REGISTER_ROW + slot as u8;
+            // Put $0 on the bottom row, nearest the input line
+            let index = visible - 1 - slot;
+            console::move_cursor(&mut self.stdout, console::Position { row, col: 0 });
+            if index < self.num_registers {
+                let value = self.registers[index];
+                let _ = write!(
+                    self.stdout,
+                    "${:<2} {:>11} {:>10}  0x{:08X}  0b",
+                    index, value as i32, value, value
+                );
+                for byte in value.to_be_bytes().iter() {
+                    let _ = write!(self.stdout, "_{:08b}", byte);
+                }
+            } else {
+                for _ in 0..self.width {
+                    let _ = self.stdout.write_char(' ');
+                }
+            }
+        }
+    }
+
+    /// Draw the line the user is typing into.
+    fn draw_input(&mut self) {
+        let row = self.input_row();
+        console::move_cursor(
+            &mut self.stdout,
+            console::Position {
+                row: row + 1,
+                col: 0,
+            },
+        );
+        for _ in 0..self.width - 1 {
+            let _ = self.stdout.write_char(' ');
+        }
+        console::move_cursor(&mut self.stdout, console::Position { row, col: 0 });
+        let input = core::str::from_utf8(&self.input[0..self.input_len]).unwrap_or("");
+        let _ = write!(self.stdout, "> {}", input);
+        for _ in (self.input_len + 2)..usize::from(self.width - 1) {
+            let _ = self.stdout.write_char(' ');
+        }
+        console::move_cursor(
+            &mut self.stdout,
+            console::Position {
+                row,
+                col: (self.input_len + 2) as u8,
+            },
+        );
+    }
+
+    /// Show an error message under the input line
+    fn draw_error(&mut self, error: expr::Error) {
+        let row = self.input_row();
+        console::move_cursor(
+            &mut self.stdout,
+            console::Position {
+                row: row + 1,
+                col: 0,
+            },
+        );
+        console::set_sgr(&mut self.stdout, [console::SgrParam::FgRed]);
+        match error {
+            expr::Error::UnexpectedChar(ch) => {
+                let _ = write!(self.stdout, "{} {:?}", error.describe(), ch);
+            }
+            _ => {
+                let _ = self.stdout.write_str(error.describe());
+            }
+        }
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        console::move_cursor(
+            &mut self.stdout,
+            console::Position {
+                row,
+                col: (self.input_len + 2) as u8,
+            },
+        );
+    }
+
+    /// Which row is the input line on?
+    fn input_row(&self) -> u8 {
+        Self::FIRST_REGISTER_ROW + self.visible_registers() as u8 + 1
+    }
+}
@@ -0,0 +1,206 @@
+//! Calendar dates, and today's date from the real-time clock.
+//!
+//! The OS exposes the battery-backed clock as the `RTC:` device. Reading it
+//! gives the current time as text, like `2024-03-09 17:45:02`.
+
+/// The names of the days of the week, starting with Monday
+pub const DAYS: [&str; 7] = [
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+    "Sunday",
+];
+
+/// The names of the months
+pub const MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+/// A day in the calendar.
+///
+/// The fields are in order of size, so dates sort the right way.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Date {
+    /// The year, like 2024
+    pub year: u16,
+    /// The month, 1 to 12
+    pub month: u8,
+    /// The day of the month, 1 to 31
+    pub day: u8,
+}
+
+impl Date {
+    /// The date we use if there's no clock to ask
+    pub const DEFAULT: Date = Date {
+        year: 2024,
+        month: 1,
+        day: 1,
+    };
+
+    /// Read today's date from the real-time clock, if there is one
+    pub fn today() -> Option<Date> {
+        let path = neotron_sdk::path::Path::new("RTC:").ok()?;
+        let rtc = neotron_sdk::File::open(path, neotron_sdk::Flags::empty()).ok()?;
+        let mut buffer = [0u8; 32];
+        let n = rtc.read(&mut buffer).ok()?;
+        let text = core::str::from_utf8(&buffer[0..n]).ok()?;
+        Date::parse(text.trim().get(0..10)?)
+    }
+
+    /// Parse text like `2024-03-09`
+    pub fn parse(text: &str) -> Option<Date> {
+        let mut parts = text.split('-');
+        let date = Date {
+            year: parts.next()?.parse().ok()?,
+            month: parts.next()?.parse().ok()?,
+            day: parts.next()?.parse().ok()?,
+        };
+        if parts.next().is_some()
+            || date.month == 0
+            || date.month > 12
+            || date.day == 0
+            || date.day > days_in_month(date.year, date.month)
+        {
+            return None;
+        }
+        Some(date)
+    }
+
+    /// The day of the week, where 0 is Monday
+    pub fn weekday(&self) -> usize {
+        // Sakamoto's method, adjusted so Monday is zero
+        const OFFSETS: [u16; 12] = [0, 3, 2, 5, 0, 3, 5, 1, 4, 6, 2, 4];
+        let year = if self.month < 3 {
+            self.year - 1
+        } else {
+            self.year
+        };
+        let sunday_based = (year + year / 4 - year / 100
+            + year / 400
+            + OFFSETS[usize::from(self.month - 1)]
+            + u16::from(self.day))
+            % 7;
+        usize::from((sunday_based + 6) % 7)
+    }
+
+    /// The day after this one
+    pub fn next(self) -> Date {
+        if self.day < days_in_month(self.year, self.month) {
+            Date {
+                day: self.day + 1,
+                ..self
+            }
+        } else if self.month < 12 {
+            Date {
+                month: self.month + 1,
+                day: 1,
+                ..self
+            }
+        } else {
+            Date {
+                year: self.year + 1,
+                month: 1,
+                day: 1,
+            }
+        }
+    }
+
+    /// The day before this one
+    pub fn previous(self) -> Date {
+        if self.day > 1 {
+            Date {
+                day: self.day - 1,
+                ..self
+            }
+        } else if self.month > 1 {
+            let month = self.month - 1;
+            Date {
+                month,
+                day: days_in_month(self.year, month),
+                ..self
+            }
+        } else {
+            Date {
+                year: self.year - 1,
+                month: 12,
+                day: 31,
+            }
+        }
+    }
+
+    /// Move forwards (or backwards, if negative) by some days
+    pub fn add_days(self, days: i32) -> Date {
+        let mut date = self;
+        for _ in 0..days.unsigned_abs() {
+            date = if days > 0 {
+                date.next()
+            } else {
+                date.previous()
+            };
+        }
+        date
+    }
+
+    /// Move forwards (or backwards, if negative) by some months. If the day
+    /// doesn't exist in the new month, we use the last day of it.
+    pub fn add_months(self, months: i32) -> Date {
+        let index = i32::from(self.year) * 12 + i32::from(self.month) - 1 + months;
+        let year = index.div_euclid(12) as u16;
+        let month = index.rem_euclid(12) as u8 + 1;
+        Date {
+            year,
+            month,
+            day: self.day.min(days_in_month(year, month)),
+        }
+    }
+
+    /// The first day of this date's month
+    pub fn first_of_month(self) -> Date {
+        Date { day: 1, ..self }
+    }
+
+    /// The name of the month this date is in
+    pub fn month_name(&self) -> &'static str {
+        MONTHS[usize::from(self.month - 1)]
+    }
+
+    /// The name of the day of the week
+    pub fn day_name(&self) -> &'static str {
+        DAYS[self.weekday()]
+    }
+}
+
+impl core::fmt::Display for Date {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+/// Is this a leap year?
+pub fn is_leap_year(year: u16) -> bool {
+    (year.is_multiple_of(4) && !year.is_multiple_of(100)) || year.is_multiple_of(400)
+}
+
+/// How many days are there in the given month?
+pub fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
@@ -0,0 +1,546 @@
+//! Application logic for the calendar and todo list
+
+#![no_std]
+#![deny(missing_docs)]
+#![deny(unsafe_code)]
+
+use core::fmt::Write;
+
+use neotron_input::{KeyEvent, Keyboard};
+use neotron_sdk::console;
+use neotron_tui::Line;
+
+pub mod date;
+pub mod notes;
+
+use date::Date;
+use notes::Notes;
+
+/// The row the month's name goes on
+const TITLE_ROW: u8 = 2;
+
+/// The row the first week goes on
+const GRID_ROW: u8 = TITLE_ROW + 2;
+
+/// The column the calendar starts in
+const GRID_COL: u8 = 2;
+
+/// How wide each day is in the calendar
+const CELL_WIDTH: u8 = 5;
+
+/// The column the list of notes starts in
+const LIST_COL: u8 = GRID_COL + 7 * CELL_WIDTH + 5;
+
+/// How many notes we can show at once
+const LIST_ROWS: usize = 12;
+
+/// The row for messages
+const MESSAGE_ROW: u8 = GRID_ROW + 13;
+
+/// How many days ahead the agenda looks
+const AGENDA_DAYS: i32 = 14;
+
+/// Which half of the screen the keys work on
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Focus {
+    /// Moving about the calendar
+    Calendar,
+    /// Picking one of the day's notes
+    Notes,
+}
+
+/// Represents the calendar application
+pub struct App {
+    width: u8,
+    height: u8,
+    stdout: neotron_sdk::File,
+    keyboard: Keyboard,
+    notes: Notes,
+    /// Today, according to the clock
+    today: Date,
+    /// Was there a clock to ask?
+    has_clock: bool,
+    /// The day the cursor is on
+    selected: Date,
+    focus: Focus,
+    /// Which of the selected day's notes the cursor is on
+    item: usize,
+    /// Something to tell the user
+    message: Option<&'static str>,
+}
+
+impl App {
+    /// Make a new calendar application.
+    ///
+    /// You can give the screen size in characters.
+    pub const fn new(width: u8, height: u8) -> App {
+        App {
+            width,
+            height,
+            stdout: neotron_sdk::stdout(),
+            keyboard: Keyboard::new(),
+            notes: Notes::new(),
+            today: Date::DEFAULT,
+            has_clock: false,
+            selected: Date::DEFAULT,
+            focus: Focus::Calendar,
+            item: 0,
+            message: None,
+        }
+    }
+
+    /// Run the calendar, until the user quits
+    pub fn play(&mut self) {
+        self.notes.load();
+        match Date::today() {
+            Some(today) => {
+                self.today = today;
+                self.has_clock = true;
+            }
+            None => {
+                self.message = Some("No real-time clock, so we don't know what today is.");
+            }
+        }
+        self.selected = self.today;
+        console::cursor_off(&mut self.stdout);
+        self.redraw();
+        loop {
+            self.draw();
+            let key = self.keyboard.wait();
+            self.message = None;
+            let keep_going = match self.focus {
+                Focus::Calendar => self.calendar_key(key),
+                Focus::Notes => self.notes_key(key),
+            };
+            if !keep_going {
+                break;
+            }
+        }
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        console::clear_screen(&mut self.stdout);
+        console::move_cursor(&mut self.stdout, console::Position::origin());
+        console::cursor_on(&mut self.stdout);
+    }
+
+    /// Deal with a key while moving about the calendar. Returns false if the
+    /// user wants to quit.
+    fn calendar_key(&mut self, key: KeyEvent) -> bool {
+        match key {
+            KeyEvent::Left => self.selected = self.selected.previous(),
+            KeyEvent::Right => self.selected = self.selected.next(),
+            KeyEvent::Up => self.selected = self.selected.add_days(-7),
+            KeyEvent::Down => self.selected = self.selected.add_days(7),
+            KeyEvent::PageUp | KeyEvent::Char('<' | ',') => {
+                self.selected = self.selected.add_months(-1)
+            }
+            KeyEvent::PageDown | KeyEvent::Char('>' | '.') => {
+                self.selected = self.selected.add_months(1)
+            }
+            KeyEvent::Char('t' | 'T') => self.selected = self.today,
+            KeyEvent::Char('n' | 'N') => self.add_note(),
+            KeyEvent::Tab | KeyEvent::Enter => {
+                if self.notes.on_day(self.selected).is_empty() {
+                    self.message = Some("Nothing to do on that day. Press N to add a note.");
+                } else {
+                    self.focus = Focus::Notes;
+                    self.item = 0;
+                    self.draw_help();
+                }
+            }
+            KeyEvent::Char('a' | 'A') => {
+                self.show_agenda();
+                self.redraw();
+            }
+            KeyEvent::Char('q' | 'Q') | KeyEvent::Escape => return false,
+            _ => {}
+        }
+        true
+    }
+
+    /// Deal with a key while picking one of the day's notes. Returns false if
+    /// the user wants to quit.
+    fn notes_key(&mut self, key: KeyEvent) -> bool {
+        let range = self.notes.on_day(self.selected);
+        let index = range.start + self.item;
+        match key {
+            KeyEvent::Up => self.item = self.item.saturating_sub(1),
+            KeyEvent::Down => self.item = (self.item + 1).min(range.len() - 1),
+            KeyEvent::Char(' ' | 'x' | 'X') => {
+                self.notes.toggle(index);
+                self.save();
+            }
+            KeyEvent::Char('e' | 'E') | KeyEvent::Enter => {
+                let mut input = [0u8; notes::MAX_TEXT];
+                let current = self.notes.get(index).text();
+                input[0..current.len()].copy_from_slice(current.as_bytes());
+                let len = current.len();
+                if let Some(text) = self.prompt("Change it to: ", &mut input, len) {
+                    if !text.trim().is_empty() {
+                        self.notes.edit(index, text.trim());
+                        self.save();
+                    }
+                }
+            }
+            KeyEvent::Char('d' | 'D') | KeyEvent::Backspace => {
+                self.notes.remove(index);
+                self.save();
+                if range.len() == 1 {
+                    self.back_to_calendar();
+                } else {
+                    self.item = self.item.min(range.len() - 2);
+                }
+            }
+            KeyEvent::Char('n' | 'N') => self.add_note(),
+            KeyEvent::Tab | KeyEvent::Escape | KeyEvent::Left | KeyEvent::Right => {
+                self.back_to_calendar()
+            }
+            KeyEvent::Char('q' | 'Q') => return false,
+            _ => {}
+        }
+        true
+    }
+
+    /// Stop picking notes, and go back to moving about the calendar
+    fn back_to_calendar(&mut self) {
+        self.focus = Focus::Calendar;
+        self.item = 0;
+        self.draw_help();
+    }
+
+    /// Ask what the note should say, and add it to the selected day
+    fn add_note(&mut self) {
+        let mut input = [0u8; notes::MAX_TEXT];
+        let Some(text) = self.prompt("To do: ", &mut input, 0) else {
+            return;
+        };
+        let text = text.trim();
+        if text.is_empty() {
+            return;
+        }
+        if self.notes.add(self.selected, text) {
+            self.save();
+            if self.focus == Focus::Notes {
+                self.item = self.notes.on_day(self.selected).len() - 1;
+            }
+        } else {
+            self.message = Some("There's no room for any more notes.");
+        }
+    }
+
+    /// Write the notes to disk, complaining if we can't
+    fn save(&mut self) {
+        if self.notes.save().is_err() {
+            self.message = Some("Couldn't save the notes!");
+        }
+    }
+
+    /// Clear the screen and draw everything
+    fn redraw(&mut self) {
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        console::clear_screen(&mut self.stdout);
+        console::set_sgr(
+            &mut self.stdout,
+            [
+                console::SgrParam::Bold,
+                console::SgrParam::FgWhite,
+                console::SgrParam::BgBlue,
+            ],
+        );
+        self.move_to(0, 0);
+        let _ = write!(self.stdout, "{:^1$}", "CALENDAR", usize::from(self.width));
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        self.draw_help();
+    }
+
+    /// Draw the things which change as keys are pressed
+    fn draw(&mut self) {
+        self.draw_month();
+        self.draw_notes();
+        self.clear_row(MESSAGE_ROW);
+        if let Some(message) = self.message {
+            self.move_to(MESSAGE_ROW, GRID_COL);
+            let _ = self.stdout.write_str(message);
+        }
+    }
+
+    /// Draw the month the selected day is in
+    fn draw_month(&mut self) {
+        let mut title = Line::new();
+        let _ = write!(
+            title,
+            "{} {}",
+            self.selected.month_name(),
+            self.selected.year
+        );
+        self.move_to(TITLE_ROW, GRID_COL);
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Bold]);
+        let _ = write!(
+            self.stdout,
+            "{:^1$}",
+            title.as_str(),
+            usize::from(7 * CELL_WIDTH)
+        );
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        self.move_to(TITLE_ROW + 1, GRID_COL);
+        for name in date::DAYS {
+            let _ = write!(self.stdout, "{:^5}", &name[0..3]);
+        }
+
+        let first = self.selected.first_of_month();
+        let offset = first.weekday();
+        let days = usize::from(date::days_in_month(first.year, first.month));
+        for cell in 0..42 {
+            let row = GRID_ROW + (cell / 7) as u8 * 2;
+            let col = GRID_COL + (cell % 7) as u8 * CELL_WIDTH;
+            self.move_to(row, col);
+            if cell < offset || cell >= offset + days {
+                let _ = self.stdout.write_str("     ");
+                continue;
+            }
+            let day = Date {
+                day: (cell - offset + 1) as u8,
+                ..first
+            };
+            let notes = self.notes.on_day(day);
+            let marker = if notes.is_empty() {
+                ' '
+            } else if notes.clone().all(|index| self.notes.get(index).done) {
+                '·'
+            } else {
+                '*'
+            };
+            if day == self.selected {
+                console::set_sgr(&mut self.stdout, [console::SgrParam::Reverse]);
+            }
+            if day == self.today && self.has_clock {
+                console::set_sgr(
+                    &mut self.stdout,
+                    [console::SgrParam::Bold, console::SgrParam::FgYellow],
+                );
+            } else if cell % 7 >= 5 {
+                console::set_sgr(&mut self.stdout, [console::SgrParam::FgCyan]);
+            }
+            let _ = write!(self.stdout, " {:>2}{} ", day.day, marker);
+            console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        }
+    }
+
+    /// Draw the notes for the selected day
+    fn draw_notes(&mut self) {
+        let width = usize::from(self.width - LIST_COL - 1);
+        let mut title = Line::new();
+        let _ = write!(
+            title,
+            "{} {} {} {}",
+            self.selected.day_name(),
+            self.selected.day,
+            self.selected.month_name(),
+            self.selected.year
+        );
+        self.move_to(TITLE_ROW, LIST_COL);
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Bold]);
+        let _ = write!(self.stdout, "{:<1$}", title.as_str(), width);
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+
+        let range = self.notes.on_day(self.selected);
+        // Scroll so the cursor is on screen
+        let first = self.item.saturating_sub(LIST_ROWS - 1);
+        for row in 0..LIST_ROWS {
+            self.move_to(GRID_ROW + row as u8, LIST_COL);
+            let item = first + row;
+            if range.is_empty() && row == 0 {
+                let _ = write!(self.stdout, "{:<1$}", "Nothing to do.", width);
+                continue;
+            }
+            if item >= range.len() {
+                let _ = write!(self.stdout, "{:1$}", "", width);
+                continue;
+            }
+            let note = *self.notes.get(range.start + item);
+            if self.focus == Focus::Notes && item == self.item {
+                console::set_sgr(&mut self.stdout, [console::SgrParam::Reverse]);
+            } else if note.done {
+                console::set_sgr(&mut self.stdout, [console::SgrParam::FgGreen]);
+            }
+            let _ = write!(self.stdout, "[{}] ", if note.done { 'x' } else { ' ' });
+            let text = cut(note.text(), width - 4);
+            let _ = write!(self.stdout, "{:<1$}", text, width - 4);
+            console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        }
+        self.move_to(GRID_ROW + LIST_ROWS as u8, LIST_COL);
+        let _ = write!(self.stdout, "{:1$}", "", width);
+        if range.len() > first + LIST_ROWS {
+            self.move_to(GRID_ROW + LIST_ROWS as u8, LIST_COL);
+            let _ = write!(
+                self.stdout,
+                "...and {} more",
+                range.len() - first - LIST_ROWS
+            );
+        }
+    }
+
+    /// Draw the list of keys at the bottom
+    fn draw_help(&mut self) {
+        let help = match self.focus {
+            Focus::Calendar => {
+                "Arrows: Day  PgUp/PgDn: Month  T: Today  N: New  Tab: Notes  A: Agenda  Q: Quit"
+            }
+            Focus::Notes => {
+                "Up/Down: Choose  Space: Done  E: Edit  D: Delete  N: New  Tab: Calendar"
+            }
+        };
+        self.clear_row(self.height - 2);
+        self.move_to(self.height - 2, 0);
+        let _ = self.stdout.write_str(help);
+    }
+
+    /// Show the notes which haven't been done yet, and those coming up soon
+    fn show_agenda(&mut self) {
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        console::clear_screen(&mut self.stdout);
+        console::set_sgr(
+            &mut self.stdout,
+            [
+                console::SgrParam::Bold,
+                console::SgrParam::FgWhite,
+                console::SgrParam::BgBlue,
+            ],
+        );
+        self.move_to(0, 0);
+        let _ = write!(self.stdout, "{:^1$}", "AGENDA", usize::from(self.width));
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+
+        let last_row = self.height - 4;
+        let mut row = 2;
+        let mut skipped = 0;
+        let end = self.today.add_days(AGENDA_DAYS);
+        let mut heading = None;
+        for index in 0..self.notes.len() {
+            let note = *self.notes.get(index);
+            let overdue = note.date < self.today && !note.done;
+            if !overdue && (note.date < self.today || note.date >= end) {
+                continue;
+            }
+            let wanted = if overdue { None } else { Some(note.date) };
+            let lines = if heading != Some(wanted) { 2 } else { 1 };
+            if row + lines > last_row {
+                skipped += 1;
+                continue;
+            }
+            if heading != Some(wanted) {
+                // A new day, so it needs a heading
+                heading = Some(wanted);
+                self.move_to(row, 2);
+                match wanted {
+                    None => {
+                        console::set_sgr(
+                            &mut self.stdout,
+                            [console::SgrParam::Bold, console::SgrParam::FgRed],
+                        );
+                        let _ = self.stdout.write_str("Overdue");
+                    }
+                    Some(date) => {
+                        console::set_sgr(&mut self.stdout, [console::SgrParam::Bold]);
+                        let _ = write!(
+                            self.stdout,
+                            "{} {} {}",
+                            date.day_name(),
+                            date.day,
+                            date.month_name()
+                        );
+                        if date == self.today {
+                            let _ = self.stdout.write_str(" (today)");
+                        }
+                    }
+                }
+                console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+                row += 1;
+            }
+            self.move_to(row, 4);
+            if note.done {
+                console::set_sgr(&mut self.stdout, [console::SgrParam::FgGreen]);
+            }
+            let _ = write!(
+                self.stdout,
+                "[{}] {}",
+                if note.done { 'x' } else { ' ' },
+                note.text()
+            );
+            if overdue {
+                let _ = write!(self.stdout, " ({})", note.date);
+            }
+            console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+            row += 1;
+        }
+        self.move_to(row.max(2), 2);
+        if heading.is_none() {
+            let _ = write!(
+                self.stdout,
+                "Nothing to do in the next {} days.",
+                AGENDA_DAYS
+            );
+        } else if skipped > 0 {
+            let _ = write!(self.stdout, "...and {} more", skipped);
+        }
+        self.move_to(self.height - 2, 0);
+        let _ = self.stdout.write_str("Press any key to go back.");
+        self.keyboard.wait();
+    }
+
+    /// Read some text on the row above the list of keys, starting with the
+    /// first `len` bytes of `input`.
+    ///
+    /// Returns `None` if the user pressed Escape.
+    fn prompt<'a>(&mut self, prompt: &str, input: &'a mut [u8], len: usize) -> Option<&'a str> {
+        let row = self.height - 3;
+        self.clear_row(row);
+        self.move_to(row, 0);
+        let _ = self.stdout.write_str(prompt);
+        let _ = self.stdout.write(&input[0..len]);
+        console::cursor_on(&mut self.stdout);
+        let mut len = len;
+        let done = loop {
+            match self.keyboard.wait() {
+                KeyEvent::Enter => break true,
+                KeyEvent::Escape => break false,
+                KeyEvent::Backspace if len > 0 => {
+                    len -= 1;
+                    let _ = self.stdout.write_str("\u{0008} \u{0008}");
+                }
+                KeyEvent::Char(key) if key.is_ascii() && len < input.len() => {
+                    input[len] = key as u8;
+                    len += 1;
+                    let _ = self.stdout.write_char(key);
+                }
+                _ => {}
+            }
+        };
+        console::cursor_off(&mut self.stdout);
+        self.clear_row(row);
+        if done {
+            core::str::from_utf8(&input[0..len]).ok()
+        } else {
+            None
+        }
+    }
+
+    /// Blank out a row
+    fn clear_row(&mut self, row: u8) {
+        self.move_to(row, 0);
+        let _ = write!(self.stdout, "{:1$}", "", usize::from(self.width) - 1);
+    }
+
+    /// Move the cursor
+    fn move_to(&mut self, row: u8, col: u8) {
+        console::move_cursor(&mut self.stdout, console::Position { row, col });
+    }
+}
+
+/// Cut some text short, so it fits in a space
+fn cut(text: &str, width: usize) -> &str {
+    let mut len = text.len().min(width);
+    while !text.is_char_boundary(len) {
+        len -= 1;
+    }
+    &text[0..len]
+}
@@ -0,0 +1,222 @@
+//! The todo notes for each day, and the file we keep them in.
+//!
+//! The file is plain text with one note per line, like
+//! `2024-03-09 [ ] Buy milk`, with an `x` in the brackets once it is done.
+//! You can edit it by hand if you like.
+
+use core::fmt::Write;
+
+use crate::date::Date;
+
+/// Where we keep the notes
+const NOTES_FILE: &str = "CALENDAR.DAT";
+
+/// The most notes we can keep
+pub const MAX_NOTES: usize = 200;
+
+/// The longest note we can keep
+pub const MAX_TEXT: usize = 60;
+
+/// The longest line we read from the file. Anything longer is cut short.
+const MAX_LINE: usize = 11 + 4 + MAX_TEXT;
+
+/// Something to do on a particular day
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Note {
+    /// The day it is for
+    pub date: Date,
+    /// Has it been done?
+    pub done: bool,
+    text: [u8; MAX_TEXT],
+    text_len: usize,
+}
+
+impl Note {
+    /// An unused note
+    const EMPTY: Note = Note {
+        date: Date::DEFAULT,
+        done: false,
+        text: [0; MAX_TEXT],
+        text_len: 0,
+    };
+
+    /// What needs doing
+    pub fn text(&self) -> &str {
+        core::str::from_utf8(&self.text[0..self.text_len]).unwrap_or("?")
+    }
+
+    /// Make a note, cutting the text short if it is too long
+    fn new(date: Date, done: bool, text: &str) -> Note {
+        let mut note = Note {
+            date,
+            done,
+            ..Note::EMPTY
+        };
+        let mut len = text.len().min(MAX_TEXT);
+        // Don't cut a character in half
+        while !text.is_char_boundary(len) {
+            len -= 1;
+        }
+        note.text[0..len].copy_from_slice(&text.as_bytes()[0..len]);
+        note.text_len = len;
+        note
+    }
+}
+
+/// All the notes, in date order
+pub struct Notes {
+    notes: [Note; MAX_NOTES],
+    count: usize,
+}
+
+impl Notes {
+    /// Make an empty set of notes
+    pub const fn new() -> Notes {
+        Notes {
+            notes: [Note::EMPTY; MAX_NOTES],
+            count: 0,
+        }
+    }
+
+    /// How many notes there are
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    /// Are there no notes at all?
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Get a note
+    pub fn get(&self, index: usize) -> &Note {
+        &self.notes[index]
+    }
+
+    /// Get every note, in date order
+    pub fn iter(&self) -> impl Iterator<Item = &Note> {
+        self.notes[0..self.count].iter()
+    }
+
+    /// Get the indices of the notes for a day
+    pub fn on_day(&self, date: Date) -> core::ops::Range<usize> {
+        let start = self.notes[0..self.count].partition_point(|note| note.date < date);
+        let end = self.notes[0..self.count].partition_point(|note| note.date <= date);
+        start..end
+    }
+
+    /// Add a note, after any others on the same day. Returns false if there's
+    /// no room.
+    pub fn add(&mut self, date: Date, text: &str) -> bool {
+        if self.count == MAX_NOTES {
+            return false;
+        }
+        let index = self.on_day(date).end;
+        self.notes.copy_within(index..self.count, index + 1);
+        self.notes[index] = Note::new(date, false, text);
+        self.count += 1;
+        true
+    }
+
+    /// Change what a note says
+    pub fn edit(&mut self, index: usize, text: &str) {
+        let note = &self.notes[index];
+        self.notes[index] = Note::new(note.date, note.done, text);
+    }
+
+    /// Mark a note as done, or not done
+    pub fn toggle(&mut self, index: usize) {
+        self.notes[index].done = !self.notes[index].done;
+    }
+
+    /// Throw a note away
+    pub fn remove(&mut self, index: usize) {
+        self.notes.copy_within(index + 1..self.count, index);
+        self.count -= 1;
+    }
+
+    /// Read the notes from disk. If there's no file, we start with none.
+    pub fn load(&mut self) {
+        self.count = 0;
+        let Ok(path) = neotron_sdk::path::Path::new(NOTES_FILE) else {
+            return;
+        };
+        let Ok(file) = neotron_sdk::File::open(path, neotron_sdk::Flags::empty()) else {
+            return;
+        };
+        let mut buffer = [0u8; 256];
+        let mut line = [0u8; MAX_LINE];
+        let mut line_len = 0;
+        loop {
+            let n = match file.read(&mut buffer) {
+                Ok(0) | Err(_) => break,
+                Ok(n) => n,
+            };
+            for &byte in &buffer[0..n] {
+                if byte == b'\n' {
+                    self.parse_line(&line[0..line_len]);
+                    line_len = 0;
+                } else if line_len < MAX_LINE {
+                    line[line_len] = byte;
+                    line_len += 1;
+                }
+            }
+        }
+        self.parse_line(&line[0..line_len]);
+    }
+
+    /// Add the note from one line of the file, if it makes sense
+    fn parse_line(&mut self, line: &[u8]) {
+        let Ok(line) = core::str::from_utf8(line) else {
+            return;
+        };
+        let line = line.trim_end();
+        let (Some(date), Some(mark), Some(text)) =
+            (line.get(0..10), line.get(10..14), line.get(14..))
+        else {
+            return;
+        };
+        let Some(date) = Date::parse(date) else {
+            return;
+        };
+        let done = match mark {
+            " [ ]" => false,
+            " [x]" | " [X]" => true,
+            _ => return,
+        };
+        let text = text.trim();
+        if text.is_empty() {
+            return;
+        }
+        if self.add(date, text) && done {
+            let index = self.on_day(date).end - 1;
+            self.notes[index].done = true;
+        }
+    }
+
+    /// Write the notes to disk
+    pub fn save(&self) -> Result<(), neotron_sdk::Error> {
+        let path = neotron_sdk::path::Path::new(NOTES_FILE)?;
+        let mut file = neotron_sdk::File::open(
+            path,
+            neotron_sdk::Flags::WRITE | neotron_sdk::Flags::CREATE | neotron_sdk::Flags::TRUNCATE,
+        )?;
+        for note in self.iter() {
+            writeln!(
+                file,
+                "{} [{}] {}",
+                note.date,
+                if note.done { 'x' } else { ' ' },
+                note.text()
+            )
+            .map_err(|_| neotron_sdk::Error::DeviceSpecific)?;
+        }
+        Ok(())
+    }
+}
+
+impl Default for Notes {
+    fn default() -> Self {
+        Notes::new()
+    }
+}
@@ -0,0 +1,86 @@
+//! The player's chips, which are kept on disk between sessions.
+
+use core::fmt::Write;
+
+/// Where we keep the chips
+const BANK_FILE: &str = "CARDS.DAT";
+
+/// How many chips a new player gets, and what we top a broke player up to
+pub const STARTING_CHIPS: u32 = 100;
+
+/// The player's bankroll
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Bank {
+    /// How many chips the player has
+    pub chips: u32,
+    /// The most chips the player has ever had
+    pub best: u32,
+}
+
+impl Bank {
+    /// Make a bankroll for a new player
+    pub const fn new() -> Bank {
+        Bank {
+            chips: STARTING_CHIPS,
+            best: STARTING_CHIPS,
+        }
+    }
+
+    /// Take a stake off the player. Returns false if they can't afford it.
+    pub fn stake(&mut self, amount: u32) -> bool {
+        if amount > self.chips {
+            return false;
+        }
+        self.chips -= amount;
+        true
+    }
+
+    /// Give the player some winnings
+    pub fn pay(&mut self, amount: u32) {
+        self.chips = self.chips.saturating_add(amount);
+        self.best = self.best.max(self.chips);
+    }
+
+    /// Read the bankroll from disk, if we have one
+    pub fn load(&mut self) {
+        let Ok(path) = neotron_sdk::path::Path::new(BANK_FILE) else {
+            return;
+        };
+        let Ok(file) = neotron_sdk::File::open(path, neotron_sdk::Flags::empty()) else {
+            return;
+        };
+        let mut buffer = [0u8; 32];
+        let Ok(n) = file.read(&mut buffer) else {
+            return;
+        };
+        let Ok(text) = core::str::from_utf8(&buffer[0..n]) else {
+            return;
+        };
+        let mut lines = text.lines().map(|line| line.trim().parse::<u32>().ok());
+        if let Some(chips) = lines.next().flatten() {
+            self.chips = chips;
+        }
+        self.best = lines.next().flatten().unwrap_or(0).max(self.chips);
+    }
+
+    /// Write the bankroll to disk, as one number per line
+    pub fn save(&self) {
+        let Ok(path) = neotron_sdk::path::Path::new(BANK_FILE) else {
+            return;
+        };
+        let Ok(mut file) = neotron_sdk::File::open(
+            path,
+            neotron_sdk::Flags::WRITE | neotron_sdk::Flags::CREATE | neotron_sdk::Flags::TRUNCATE,
+        ) else {
+            return;
+        };
+        let _ = writeln!(file, "{}", self.chips);
+        let _ = writeln!(file, "{}", self.best);
+    }
+}
+
+impl Default for Bank {
+    fn default() -> Self {
+        Bank::new()
+    }
+}
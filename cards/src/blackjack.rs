@@ -0,0 +1,241 @@
+//! Blackjack, against the dealer.
+//!
+//! The dealer stands on all 17s and a blackjack pays three to two. You can
+//! double down on your first two cards, but not split.
+
+use core::fmt::Write;
+
+use neotron_input::KeyEvent;
+use neotron_tui::Line;
+
+use crate::deck::Card;
+use crate::{Table, CARD_SPACING, HELP_ROW, STATUS_ROW};
+
+/// Where the dealer's hand goes
+const DEALER_ROW: u8 = 3;
+
+/// Where the player's hand goes
+const PLAYER_ROW: u8 = 11;
+
+/// The column of the first card in each hand
+const FIRST_COL: u8 = 4;
+
+/// Shuffle before a hand if there are fewer cards than this left
+const RESHUFFLE_AT: usize = 15;
+
+/// The most cards a hand can hold without going bust (four aces, four twos
+/// and three threes)
+const MAX_CARDS: usize = 11;
+
+/// The cards in one player's hand
+struct Hand {
+    cards: [Card; MAX_CARDS],
+    len: usize,
+}
+
+impl Hand {
+    /// Make an empty hand
+    fn new() -> Hand {
+        Hand {
+            cards: [Card::from_index(0); MAX_CARDS],
+            len: 0,
+        }
+    }
+
+    /// Add a card to the hand
+    fn push(&mut self, card: Card) {
+        if self.len < MAX_CARDS {
+            self.cards[self.len] = card;
+            self.len += 1;
+        }
+    }
+
+    /// The cards in the hand
+    fn cards(&self) -> &[Card] {
+        &self.cards[0..self.len]
+    }
+
+    /// What the hand is worth.
+    ///
+    /// Aces count as eleven, unless that would take us over 21.
+    fn value(&self) -> u8 {
+        let mut total = 0;
+        let mut aces = false;
+        for card in self.cards() {
+            total += card.rank().min(10);
+            aces |= card.rank() == 1;
+        }
+        if aces && total + 10 <= 21 {
+            total + 10
+        } else {
+            total
+        }
+    }
+
+    /// Is this 21 with two cards?
+    fn is_blackjack(&self) -> bool {
+        self.len == 2 && self.value() == 21
+    }
+
+    /// Has this hand gone over 21?
+    fn is_bust(&self) -> bool {
+        self.value() > 21
+    }
+}
+
+/// One hand of blackjack
+struct Round {
+    dealer: Hand,
+    player: Hand,
+    /// Is the dealer's second card still face down?
+    hole_hidden: bool,
+    bet: u32,
+}
+
+/// Play hands of blackjack until the player leaves the table
+pub fn play(table: &mut Table) {
+    let mut last_bet = 10;
+    loop {
+        table.clear("BLACKJACK");
+        table.draw_chips();
+        let Some(bet) = table.choose_bet(last_bet) else {
+            return;
+        };
+        last_bet = bet;
+        table.bank.stake(bet);
+        table.bank.save();
+        if table.deck.remaining() < RESHUFFLE_AT {
+            table.deck.shuffle();
+        }
+        let message = play_round(table, bet);
+        table.bank.save();
+        table.draw_chips();
+        table.centre(STATUS_ROW, message);
+        table.centre(HELP_ROW, "Press any key to play again");
+        table.keyboard.wait();
+    }
+}
+
+/// Play one hand, which has already been paid for. Returns how it went.
+fn play_round(table: &mut Table, bet: u32) -> &'static str {
+    let mut round = Round {
+        dealer: Hand::new(),
+        player: Hand::new(),
+        hole_hidden: true,
+        bet,
+    };
+    for _ in 0..2 {
+        round.player.push(table.deck.deal());
+        round.dealer.push(table.deck.deal());
+    }
+    draw(table, &round);
+
+    if round.player.is_blackjack() || round.dealer.is_blackjack() {
+        round.hole_hidden = false;
+        draw(table, &round);
+        return if !round.dealer.is_blackjack() {
+            // Three to two, on top of the stake back
+            table.bank.pay(bet * 5 / 2);
+            "Blackjack! You win."
+        } else if round.player.is_blackjack() {
+            table.bank.pay(bet);
+            "You both have blackjack. It's a push."
+        } else {
+            "The dealer has blackjack. You lose."
+        };
+    }
+
+    // The player's turn
+    loop {
+        let can_double = round.player.len == 2 && table.bank.chips >= round.bet;
+        table.centre(STATUS_ROW, "");
+        table.centre(
+            HELP_ROW,
+            if can_double {
+                "H hit, S stand, D double down"
+            } else {
+                "H hit, S stand"
+            },
+        );
+        match table.keyboard.wait() {
+            KeyEvent::Char('h' | 'H') => round.player.push(table.deck.deal()),
+            KeyEvent::Char('s' | 'S') => break,
+            KeyEvent::Char('d' | 'D') if can_double => {
+                table.bank.stake(round.bet);
+                round.bet *= 2;
+                round.player.push(table.deck.deal());
+                draw(table, &round);
+                break;
+            }
+            _ => continue,
+        }
+        draw(table, &round);
+        if round.player.is_bust() {
+            round.hole_hidden = false;
+            draw(table, &round);
+            return "Bust! You lose.";
+        }
+        if round.player.value() == 21 {
+            break;
+        }
+    }
+
+    // The dealer's turn
+    round.hole_hidden = false;
+    draw(table, &round);
+    while round.dealer.value() < 17 {
+        neotron_sdk::delay(core::time::Duration::from_millis(500));
+        round.dealer.push(table.deck.deal());
+        draw(table, &round);
+    }
+
+    let (player, dealer) = (round.player.value(), round.dealer.value());
+    if round.dealer.is_bust() {
+        table.bank.pay(round.bet * 2);
+        "The dealer is bust. You win!"
+    } else if player > dealer {
+        table.bank.pay(round.bet * 2);
+        "You win!"
+    } else if player == dealer {
+        table.bank.pay(round.bet);
+        "It's a push. You get your bet back."
+    } else {
+        "The dealer wins."
+    }
+}
+
+/// Draw both hands
+fn draw(table: &mut Table, round: &Round) {
+    let mut line = Line::new();
+    if round.hole_hidden {
+        let _ = write!(line, "Dealer      ");
+    } else {
+        let _ = write!(line, "Dealer: {:<3} ", round.dealer.value());
+    }
+    table.text(DEALER_ROW, FIRST_COL, line.as_str());
+    for (idx, card) in round.dealer.cards().iter().enumerate() {
+        let face = if idx == 1 && round.hole_hidden {
+            None
+        } else {
+            Some(*card)
+        };
+        table.draw_card(DEALER_ROW + 1, FIRST_COL + idx as u8 * CARD_SPACING, face);
+    }
+
+    let mut line = Line::new();
+    let _ = write!(
+        line,
+        "You: {:<3}  Bet: {:<5}",
+        round.player.value(),
+        round.bet
+    );
+    table.text(PLAYER_ROW, FIRST_COL, line.as_str());
+    for (idx, card) in round.player.cards().iter().enumerate() {
+        table.draw_card(
+            PLAYER_ROW + 1,
+            FIRST_COL + idx as u8 * CARD_SPACING,
+            Some(*card),
+        );
+    }
+    table.draw_chips();
+}
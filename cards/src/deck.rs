@@ -0,0 +1,128 @@
+//! Playing cards, and a deck to deal them from.
+
+/// The four suits
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Suit {
+    /// Clubs (black)
+    Clubs,
+    /// Diamonds (red)
+    Diamonds,
+    /// Hearts (red)
+    Hearts,
+    /// Spades (black)
+    Spades,
+}
+
+impl Suit {
+    /// All the suits
+    pub const ALL: [Suit; 4] = [Suit::Clubs, Suit::Diamonds, Suit::Hearts, Suit::Spades];
+
+    /// The symbol for this suit
+    pub fn symbol(self) -> char {
+        match self {
+            Suit::Clubs => '♣',
+            Suit::Diamonds => '♦',
+            Suit::Hearts => '♥',
+            Suit::Spades => '♠',
+        }
+    }
+
+    /// Is this a red suit?
+    pub fn is_red(self) -> bool {
+        matches!(self, Suit::Diamonds | Suit::Hearts)
+    }
+}
+
+/// A playing card.
+///
+/// Stored as a number from 0 to 51 - the suit times 13, plus the rank less one.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Card(u8);
+
+impl Card {
+    /// Make a card from its position in a new deck, 0 to 51
+    pub const fn from_index(index: u8) -> Card {
+        Card(index % 52)
+    }
+
+    /// The rank, from 1 (Ace) to 13 (King)
+    pub fn rank(self) -> u8 {
+        self.0 % 13 + 1
+    }
+
+    /// The suit
+    pub fn suit(self) -> Suit {
+        Suit::ALL[usize::from(self.0 / 13)]
+    }
+
+    /// Is this a red card?
+    pub fn is_red(self) -> bool {
+        self.suit().is_red()
+    }
+
+    /// The rank as text, like `A` or `10`
+    pub fn rank_str(self) -> &'static str {
+        const RANKS: [&str; 13] = [
+            "A", "2", "3", "4", "5", "6", "7", "8", "9", "10", "J", "Q", "K",
+        ];
+        RANKS[usize::from(self.rank() - 1)]
+    }
+}
+
+/// A deck of 52 cards, which we deal from the top
+pub struct Deck {
+    cards: [Card; 52],
+    /// How many cards have been dealt
+    dealt: u8,
+    /// Shuffles the cards
+    pub rng: neotron_rand::Rng,
+}
+
+impl Deck {
+    /// Make a new deck.
+    ///
+    /// It counts as all dealt, so it gets shuffled before the first card is
+    /// dealt.
+    pub const fn new() -> Deck {
+        let mut cards = [Card(0); 52];
+        let mut idx = 0;
+        while idx < 52 {
+            cards[idx] = Card(idx as u8);
+            idx += 1;
+        }
+        Deck {
+            cards,
+            dealt: 52,
+            rng: neotron_rand::Rng::new(0),
+        }
+    }
+
+    /// Gather up all the cards and shuffle them
+    pub fn shuffle(&mut self) {
+        self.rng.shuffle(&mut self.cards);
+        self.dealt = 0;
+    }
+
+    /// How many cards are left to deal
+    pub fn remaining(&self) -> usize {
+        self.cards.len() - usize::from(self.dealt)
+    }
+
+    /// Deal the top card.
+    ///
+    /// If the deck has run out, we shuffle it first.
+    pub fn deal(&mut self) -> Card {
+        if self.remaining() == 0 {
+            self.shuffle();
+        }
+        let card = self.cards[usize::from(self.dealt)];
+        self.dealt += 1;
+        card
+    }
+}
+
+impl Default for Deck {
+    fn default() -> Self {
+        Deck::new()
+    }
+}
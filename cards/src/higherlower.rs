@@ -0,0 +1,91 @@
+//! Higher or Lower.
+//!
+//! We turn over a card and you guess whether the next one will be higher or
+//! lower. Every right guess doubles your pot, and you can take the pot at any
+//! time. A wrong guess loses it. Aces are high, and a card of the same rank
+//! doesn't count either way.
+
+use core::fmt::Write;
+
+use neotron_input::KeyEvent;
+use neotron_tui::Line;
+
+use crate::deck::Card;
+use crate::{Table, CARD_SPACING, HELP_ROW, STATUS_ROW};
+
+/// Where the row of cards goes
+const CARD_ROW: u8 = 7;
+
+/// The column of the first card
+const FIRST_COL: u8 = 10;
+
+/// How many cards fit across the screen. Filling the row wins the pot.
+const MAX_CARDS: usize = 10;
+
+/// The rank of a card, with aces high
+fn value(card: Card) -> u8 {
+    if card.rank() == 1 {
+        14
+    } else {
+        card.rank()
+    }
+}
+
+/// Play rounds until the player leaves the table
+pub fn play(table: &mut Table) {
+    let mut last_bet = 10;
+    loop {
+        table.clear("HIGHER OR LOWER");
+        table.draw_chips();
+        let Some(bet) = table.choose_bet(last_bet) else {
+            return;
+        };
+        last_bet = bet;
+        table.bank.stake(bet);
+        table.bank.save();
+        table.deck.shuffle();
+        let message = play_round(table, bet);
+        table.bank.save();
+        table.draw_chips();
+        table.centre(STATUS_ROW, message);
+        table.centre(HELP_ROW, "Press any key to play again");
+        table.keyboard.wait();
+    }
+}
+
+/// Play one round, which has already been paid for. Returns how it went.
+fn play_round(table: &mut Table, bet: u32) -> &'static str {
+    let mut pot = bet;
+    let mut card = table.deck.deal();
+    let mut count = 1;
+    table.draw_card(CARD_ROW, FIRST_COL, Some(card));
+    loop {
+        let mut line = Line::new();
+        let _ = write!(line, "The pot is {} chips", pot);
+        table.centre(STATUS_ROW, line.as_str());
+        table.centre(HELP_ROW, "H higher, L lower, T take the pot");
+        let higher = match table.keyboard.wait() {
+            KeyEvent::Char('h' | 'H') => true,
+            KeyEvent::Char('l' | 'L') => false,
+            KeyEvent::Char('t' | 'T') => {
+                table.bank.pay(pot);
+                return "You take the pot.";
+            }
+            _ => continue,
+        };
+        let next = table.deck.deal();
+        table.draw_card(CARD_ROW, FIRST_COL + count as u8 * CARD_SPACING, Some(next));
+        count += 1;
+        if value(next) != value(card) {
+            if (value(next) > value(card)) != higher {
+                return "Wrong! You lose the pot.";
+            }
+            pot = pot.saturating_mul(2);
+        }
+        card = next;
+        if count == MAX_CARDS {
+            table.bank.pay(pot);
+            return "A full row of cards! You take the pot.";
+        }
+    }
+}
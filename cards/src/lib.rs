@@ -0,0 +1,226 @@
+//! Game logic for the card games - Blackjack and Higher or Lower
+
+#![no_std]
+#![deny(missing_docs)]
+#![deny(unsafe_code)]
+
+use core::fmt::Write;
+
+use neotron_input::{KeyEvent, Keyboard};
+use neotron_sdk::console;
+use neotron_tui::Line;
+
+pub mod bank;
+pub mod blackjack;
+pub mod deck;
+pub mod higherlower;
+
+use deck::Card;
+
+/// The smallest bet we take
+const MIN_BET: u32 = 5;
+
+/// The largest bet we take
+const MAX_BET: u32 = 500;
+
+/// How wide a card is on screen
+pub const CARD_WIDTH: u8 = 5;
+
+/// How far apart cards are laid out
+pub const CARD_SPACING: u8 = 6;
+
+/// The row for messages about the game
+pub const STATUS_ROW: u8 = 19;
+
+/// The row for the keys the player can press
+pub const HELP_ROW: u8 = 21;
+
+/// Represents the card games application
+pub struct App {
+    table: Table,
+}
+
+impl App {
+    /// Make a new card games application.
+    ///
+    /// You can give the screen size in characters.
+    pub const fn new(width: u8, _height: u8) -> App {
+        App {
+            table: Table::new(width),
+        }
+    }
+
+    /// Show the menu and play games until the user quits
+    pub fn play(&mut self) {
+        let table = &mut self.table;
+        table.bank.load();
+        console::cursor_off(&mut table.stdout);
+        table.deck.rng = neotron_rand::Rng::from_rtc();
+        loop {
+            table.clear("NEOTRON CARD TABLE");
+            if table.bank.chips < MIN_BET {
+                table.bank.chips = bank::STARTING_CHIPS;
+                table.bank.save();
+                table.centre(
+                    STATUS_ROW,
+                    "You're out of chips, so the house has lent you some more.",
+                );
+            }
+            table.draw_chips();
+            table.centre(7, "1 - Blackjack      ");
+            table.centre(8, "2 - Higher or Lower");
+            table.centre(10, "Q - Quit           ");
+            let mut line = Line::new();
+            let _ = write!(line, "Most chips ever held: {}", table.bank.best);
+            table.centre(14, line.as_str());
+            let key = table.keyboard.wait();
+            if let KeyEvent::Char(ch) = key {
+                table.deck.rng.mix(u32::from(ch));
+            }
+            match key {
+                KeyEvent::Char('1') => blackjack::play(table),
+                KeyEvent::Char('2') => higherlower::play(table),
+                KeyEvent::Char('q' | 'Q') => break,
+                _ => {}
+            }
+        }
+        table.bank.save();
+        console::set_sgr(&mut table.stdout, [console::SgrParam::Reset]);
+        console::clear_screen(&mut table.stdout);
+        console::move_cursor(&mut table.stdout, console::Position::origin());
+        console::cursor_on(&mut table.stdout);
+    }
+}
+
+/// Everything the games share - the screen, the keyboard, the deck and the
+/// player's chips
+pub struct Table {
+    /// Where we draw the game
+    pub stdout: neotron_sdk::File,
+    /// Where the player's keys come from
+    pub keyboard: Keyboard,
+    width: u8,
+    /// The cards we deal from
+    pub deck: deck::Deck,
+    /// The player's chips
+    pub bank: bank::Bank,
+}
+
+impl Table {
+    /// Make a new table
+    const fn new(width: u8) -> Table {
+        Table {
+            stdout: neotron_sdk::stdout(),
+            keyboard: Keyboard::new(),
+            width,
+            deck: deck::Deck::new(),
+            bank: bank::Bank::new(),
+        }
+    }
+
+    /// Clear the screen and put a title at the top
+    pub fn clear(&mut self, title: &str) {
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        console::clear_screen(&mut self.stdout);
+        let col = (usize::from(self.width).saturating_sub(title.chars().count())) / 2;
+        self.move_to(1, col as u8);
+        console::set_sgr(
+            &mut self.stdout,
+            [console::SgrParam::Bold, console::SgrParam::FgYellow],
+        );
+        let _ = self.stdout.write_str(title);
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+    }
+
+    /// Show the player's chips in the top right-hand corner
+    pub fn draw_chips(&mut self) {
+        let mut line = Line::new();
+        let _ = write!(line, "Chips: {:>6}", self.bank.chips);
+        let col = self.width.saturating_sub(line.as_str().len() as u8 + 2);
+        self.move_to(1, col);
+        console::set_sgr(&mut self.stdout, [console::SgrParam::FgGreen]);
+        let _ = self.stdout.write_str(line.as_str());
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+    }
+
+    /// Write some text in the middle of a row, clearing whatever was there
+    pub fn centre(&mut self, row: u8, text: &str) {
+        self.move_to(row, 0);
+        let _ = write!(self.stdout, "{:^1$}", text, usize::from(self.width));
+    }
+
+    /// Write some text at a position
+    pub fn text(&mut self, row: u8, col: u8, text: &str) {
+        self.move_to(row, col);
+        let _ = self.stdout.write_str(text);
+    }
+
+    /// Draw a card with its top-left corner at the given position.
+    ///
+    /// `None` draws the back of a card.
+    pub fn draw_card(&mut self, row: u8, col: u8, card: Option<Card>) {
+        let (colours, lines) = match card {
+            Some(card) => {
+                let fg = if card.is_red() {
+                    console::SgrParam::FgRed
+                } else {
+                    console::SgrParam::FgBlack
+                };
+                let mut top = Line::new();
+                let mut middle = Line::new();
+                let mut bottom = Line::new();
+                let _ = write!(top, "│{:<3}│", card.rank_str());
+                let _ = write!(middle, "│ {} │", card.suit().symbol());
+                let _ = write!(bottom, "│{:>3}│", card.rank_str());
+                ([fg, console::SgrParam::BgWhite], [top, middle, bottom])
+            }
+            None => {
+                let mut back = Line::new();
+                let _ = back.write_str("│▒▒▒│");
+                (
+                    [console::SgrParam::FgCyan, console::SgrParam::BgBlue],
+                    [back.clone(), back.clone(), back],
+                )
+            }
+        };
+        console::set_sgr(&mut self.stdout, colours);
+        self.text(row, col, "┌───┐");
+        for (offset, line) in lines.iter().enumerate() {
+            self.text(row + 1 + offset as u8, col, line.as_str());
+        }
+        self.text(row + 4, col, "└───┘");
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+    }
+
+    /// Ask the player how much to bet.
+    ///
+    /// Returns `None` if they want to leave the table.
+    pub fn choose_bet(&mut self, last_bet: u32) -> Option<u32> {
+        let most = self.bank.chips.min(MAX_BET);
+        let mut bet = last_bet.clamp(MIN_BET, most.max(MIN_BET));
+        loop {
+            if self.bank.chips < MIN_BET {
+                self.centre(STATUS_ROW, "You're out of chips!");
+                self.centre(HELP_ROW, "Press any key to go back to the menu.");
+                self.keyboard.wait();
+                return None;
+            }
+            let mut line = Line::new();
+            let _ = write!(line, "Your bet: {} chips", bet);
+            self.centre(STATUS_ROW, line.as_str());
+            self.centre(HELP_ROW, "+/- change bet, Enter deal, Q leave table");
+            match self.keyboard.wait() {
+                KeyEvent::Char('+' | '=') => bet = (bet + MIN_BET).min(most),
+                KeyEvent::Char('-' | '_') => bet = bet.saturating_sub(MIN_BET).max(MIN_BET),
+                KeyEvent::Enter | KeyEvent::Char(' ') => return Some(bet),
+                KeyEvent::Char('q' | 'Q') | KeyEvent::Escape => return None,
+                _ => {}
+            }
+        }
+    }
+
+    /// Move the cursor
+    fn move_to(&mut self, row: u8, col: u8) {
+        console::move_cursor(&mut self.stdout, console::Position { row, col });
+    }
+}
@@ -0,0 +1,17 @@
+#![cfg_attr(target_os = "none", no_std)]
+#![cfg_attr(target_os = "none", no_main)]
+
+use core::ptr::addr_of_mut;
+
+#[cfg(not(target_os = "none"))]
+fn main() {
+    neotron_sdk::init();
+}
+
+static mut APP: cards::App = cards::App::new(80, 25);
+
+#[no_mangle]
+extern "C" fn neotron_main() -> i32 {
+    unsafe { (*addr_of_mut!(APP)).play() }
+    0
+}
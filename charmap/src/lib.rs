@@ -0,0 +1,366 @@
+//! Application logic for the character chart
+//!
+//! Shows all 256 codes, half at a time: ASCII, and the top half of code page
+//! 437 that the console font draws for codes 128 to 255. Each code is drawn
+//! with the glyph the console font has for it, and the control codes say
+//! which keys send them.
+
+#![no_std]
+#![deny(missing_docs)]
+#![deny(unsafe_code)]
+
+use core::fmt::Write;
+
+use ansiview::cp437;
+use neotron_input::{KeyEvent, Keyboard};
+use neotron_sdk::console;
+use neotron_tui::Line;
+
+/// The row the chart starts on
+const TOP_ROW: u8 = 2;
+
+/// How many codes go in each column
+const ROWS: u8 = 16;
+
+/// How many codes we show at once
+const PAGE: u8 = 128;
+
+/// How wide each column is
+const COLUMN_WIDTH: u8 = 10;
+
+/// The row we describe the selected code on
+const DETAIL_ROW: u8 = TOP_ROW + ROWS + 1;
+
+/// The short names of the control codes
+pub const SHORT_NAMES: [&str; 32] = [
+    "NUL", "SOH", "STX", "ETX", "EOT", "ENQ", "ACK", "BEL", "BS", "HT", "LF", "VT", "FF", "CR",
+    "SO", "SI", "DLE", "DC1", "DC2", "DC3", "DC4", "NAK", "SYN", "ETB", "CAN", "EM", "SUB", "ESC",
+    "FS", "GS", "RS", "US",
+];
+
+/// The full names of the control codes
+pub const LONG_NAMES: [&str; 32] = [
+    "Null",
+    "Start of Heading",
+    "Start of Text",
+    "End of Text",
+    "End of Transmission",
+    "Enquiry",
+    "Acknowledge",
+    "Bell",
+    "Backspace",
+    "Horizontal Tab",
+    "Line Feed",
+    "Vertical Tab",
+    "Form Feed",
+    "Carriage Return",
+    "Shift Out",
+    "Shift In",
+    "Data Link Escape",
+    "Device Control 1 (XON)",
+    "Device Control 2",
+    "Device Control 3 (XOFF)",
+    "Device Control 4",
+    "Negative Acknowledge",
+    "Synchronous Idle",
+    "End of Transmission Block",
+    "Cancel",
+    "End of Medium",
+    "Substitute",
+    "Escape",
+    "File Separator",
+    "Group Separator",
+    "Record Separator",
+    "Unit Separator",
+];
+
+/// Represents the character chart application
+pub struct App {
+    stdout: neotron_sdk::File,
+    keyboard: Keyboard,
+    width: u8,
+    height: u8,
+    /// The code the cursor is on
+    selected: u8,
+}
+
+impl App {
+    /// Make a new character chart application.
+    ///
+    /// You can give the screen size in characters.
+    pub const fn new(width: u8, height: u8) -> App {
+        App {
+            stdout: neotron_sdk::stdout(),
+            keyboard: Keyboard::new(),
+            width,
+            height,
+            selected: b'A',
+        }
+    }
+
+    /// Show the chart until the user quits
+    pub fn run(&mut self) {
+        console::cursor_off(&mut self.stdout);
+        self.clear(
+            "ASCII AND CODE PAGE 437",
+            "Arrows: Move  Tab: Other half  Enter: Find  Type a key to jump to it  Esc: Quit",
+        );
+        let mut page = None;
+        loop {
+            if page != Some(self.selected / PAGE) {
+                page = Some(self.selected / PAGE);
+                let first = self.selected / PAGE * PAGE;
+                for code in first..=first + (PAGE - 1) {
+                    self.draw_code(code, false);
+                }
+            }
+            self.draw_code(self.selected, true);
+            self.draw_detail(self.selected);
+            let key = self.keyboard.wait();
+            self.draw_code(self.selected, false);
+            let selected = self.selected;
+            self.selected = match key {
+                KeyEvent::Up => selected.wrapping_sub(1),
+                KeyEvent::Down => selected.wrapping_add(1),
+                KeyEvent::Left => selected.wrapping_sub(ROWS),
+                KeyEvent::Right => selected.wrapping_add(ROWS),
+                KeyEvent::Home => 0,
+                KeyEvent::End => 0xFF,
+                KeyEvent::Escape => break,
+                KeyEvent::Tab => selected ^ PAGE,
+                KeyEvent::Enter => self.find().unwrap_or(selected),
+                KeyEvent::Ctrl(ch @ 'a'..='z') => ch as u8 - b'a' + 1,
+                KeyEvent::Char(ch) => code_for(ch).unwrap_or(selected),
+                _ => selected,
+            };
+        }
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        console::clear_screen(&mut self.stdout);
+        console::move_cursor(&mut self.stdout, console::Position::origin());
+        console::cursor_on(&mut self.stdout);
+    }
+
+    /// Ask what to find, and find it.
+    ///
+    /// Returns `None` if the user pressed Escape, or we couldn't find it.
+    fn find(&mut self) -> Option<u8> {
+        let mut input = [0u8; 8];
+        let text = self.prompt(
+            "Find a character, code (65, 0x41) or name (ESC, U+00E9): ",
+            &mut input,
+        )?;
+        let found = find(text);
+        if found.is_none() {
+            self.centre(self.height - 3, "No character like that");
+        }
+        found
+    }
+
+    /// Draw one entry in the chart
+    fn draw_code(&mut self, code: u8, highlight: bool) {
+        self.move_to(TOP_ROW + code % ROWS, code % PAGE / ROWS * COLUMN_WIDTH);
+        if highlight {
+            console::set_sgr(&mut self.stdout, [console::SgrParam::Reverse]);
+        } else if code < 0x20 || code == 0x7F {
+            console::set_sgr(&mut self.stdout, [console::SgrParam::FgCyan]);
+        }
+        let mut name = [0u8; 4];
+        let _ = write!(
+            self.stdout,
+            "{:3} {:02X} {:<3}",
+            code,
+            code,
+            short_name(code, &mut name)
+        );
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+    }
+
+    /// Describe the selected code in detail
+    fn draw_detail(&mut self, code: u8) {
+        let mut line = Line::new();
+        let _ = write!(
+            line,
+            "Decimal {}   Hex 0x{:02X}   Octal 0o{:03o}   Binary 0b{:04b}_{:04b}",
+            code,
+            code,
+            code,
+            code >> 4,
+            code & 0x0F
+        );
+        self.centre(DETAIL_ROW, line.as_str());
+        let mut line = Line::new();
+        let ch = char::from(code);
+        let glyph = cp437::to_char(code);
+        let _ = match code {
+            0x00..=0x1F => write!(
+                line,
+                "{} - {} - type Ctrl+{}{} - drawn as {}",
+                SHORT_NAMES[usize::from(code)],
+                LONG_NAMES[usize::from(code)],
+                char::from(code + 0x40),
+                key_name(code),
+                glyph
+            ),
+            b' ' => line.write_str("Space"),
+            0x7F => write!(
+                line,
+                "DEL - Delete - the Backspace key on some terminals - drawn as {}",
+                glyph
+            ),
+            b'0'..=b'9' => write!(line, "'{}' - the digit {}", ch, code - b'0'),
+            b'A'..=b'Z' => write!(
+                line,
+                "'{}' - capital letter, lower case is '{}'",
+                ch,
+                char::from(code + 32)
+            ),
+            b'a'..=b'z' => write!(
+                line,
+                "'{}' - small letter, upper case is '{}'",
+                ch,
+                char::from(code - 32)
+            ),
+            0x80..=0xFF => write!(
+                line,
+                "'{}' - Unicode U+{:04X} - not ASCII, but the console font has it",
+                glyph, glyph as u32
+            ),
+            _ => write!(line, "'{}'", ch),
+        };
+        self.centre(DETAIL_ROW + 2, line.as_str());
+    }
+
+    /// Clear the screen, and put a title bar at the top and a list of keys
+    /// at the bottom
+    fn clear(&mut self, title: &str, help: &str) {
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        console::clear_screen(&mut self.stdout);
+        console::set_sgr(
+            &mut self.stdout,
+            [
+                console::SgrParam::Bold,
+                console::SgrParam::FgWhite,
+                console::SgrParam::BgBlue,
+            ],
+        );
+        self.centre(0, title);
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        self.centre(self.height - 2, help);
+    }
+
+    /// Write some text in the middle of a row, clearing whatever was there
+    fn centre(&mut self, row: u8, text: &str) {
+        self.move_to(row, 0);
+        let _ = write!(
+            self.stdout,
+            "{}",
+            neotron_text::centred(text, usize::from(self.width))
+        );
+    }
+
+    /// Move the cursor
+    fn move_to(&mut self, row: u8, col: u8) {
+        console::move_cursor(&mut self.stdout, console::Position { row, col });
+    }
+
+    /// Blank out a row
+    fn clear_row(&mut self, row: u8) {
+        self.move_to(row, 0);
+        let _ = neotron_text::write_repeated(&mut self.stdout, ' ', usize::from(self.width));
+    }
+
+    /// Read a line of text on the row above the list of keys.
+    ///
+    /// Returns `None` if the user pressed Escape.
+    fn prompt<'a>(&mut self, prompt: &str, input: &'a mut [u8]) -> Option<&'a str> {
+        let row = self.height - 3;
+        self.clear_row(row);
+        self.move_to(row, 2);
+        let _ = self.stdout.write_str(prompt);
+        console::cursor_on(&mut self.stdout);
+        let mut len = 0;
+        let done = loop {
+            match self.keyboard.wait() {
+                KeyEvent::Enter => break true,
+                KeyEvent::Escape => break false,
+                KeyEvent::Backspace if len > 0 => {
+                    len -= 1;
+                    let _ = self.stdout.write_str("\u{0008} \u{0008}");
+                }
+                KeyEvent::Char(key) if key.is_ascii() && len < input.len() => {
+                    input[len] = key as u8;
+                    len += 1;
+                    let _ = self.stdout.write_char(key);
+                }
+                _ => {}
+            }
+        };
+        console::cursor_off(&mut self.stdout);
+        self.clear_row(row);
+        if done {
+            core::str::from_utf8(&input[0..len]).ok()
+        } else {
+            None
+        }
+    }
+}
+
+/// The key that sends a control code, besides Ctrl and a letter, with
+/// `" or "` in front
+fn key_name(code: u8) -> &'static str {
+    match code {
+        0x08 => " or Backspace",
+        0x09 => " or Tab",
+        0x0D => " or Enter",
+        0x1B => " or Esc",
+        _ => "",
+    }
+}
+
+/// The code the console font draws this character for, if it has it
+fn code_for(ch: char) -> Option<u8> {
+    // The printable ASCII characters are themselves, so check those first
+    (0x20..=0x7E)
+        .chain(0x80..=0xFF)
+        .chain(0x00..=0x1F)
+        .chain([0x7F])
+        .find(|&code| cp437::to_char(code) == ch)
+}
+
+/// Find a code from what was typed at the prompt: a character, a number in
+/// decimal or hex, a control code's name, or a Unicode code point that the
+/// console font has
+fn find(text: &str) -> Option<u8> {
+    let text = text.trim();
+    let mut chars = text.chars();
+    if let (Some(ch), None) = (chars.next(), chars.clone().next()) {
+        return u8::try_from(ch).ok();
+    }
+    if let Some(hex) = text.strip_prefix("U+").or_else(|| text.strip_prefix("u+")) {
+        return code_for(char::from_u32(u32::from_str_radix(hex, 16).ok()?)?);
+    }
+    if let Some(hex) = text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        return u8::from_str_radix(hex, 16).ok();
+    }
+    if let Ok(code) = text.parse::<u8>() {
+        return Some(code);
+    }
+    match text {
+        _ if text.eq_ignore_ascii_case("SP") => Some(b' '),
+        _ if text.eq_ignore_ascii_case("DEL") => Some(0x7F),
+        _ => SHORT_NAMES
+            .iter()
+            .position(|name| text.eq_ignore_ascii_case(name))
+            .map(|code| code as u8),
+    }
+}
+
+/// What we show for a code in the chart
+fn short_name(code: u8, buffer: &mut [u8; 4]) -> &str {
+    match code {
+        0x00..=0x1F => SHORT_NAMES[usize::from(code)],
+        b' ' => "SP",
+        0x7F => "DEL",
+        _ => cp437::to_char(code).encode_utf8(buffer),
+    }
+}
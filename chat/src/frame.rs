@@ -0,0 +1,214 @@
+//! The framing protocol we speak over the serial link.
+//!
+//! Every frame looks like:
+//!
+//! ```text
+//! 0x7E <kind> <seq> <len> <payload...> <check>
+//! ```
+//!
+//! where `check` is chosen so all the bytes after the `0x7E` add up to zero
+//! (modulo 256). If a frame gets mangled, the check fails and we throw it
+//! away, then hunt for the next `0x7E`. Messages are acknowledged, and sent
+//! again if the acknowledgement doesn't turn up, so the odd lost frame
+//! doesn't lose any of the conversation.
+
+/// The byte every frame starts with
+const START: u8 = 0x7E;
+
+/// The most payload a frame can carry
+pub const MAX_PAYLOAD: usize = 160;
+
+/// What sort of frame it is
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Kind {
+    /// "I'm here", with our name as the payload. Sent every few seconds.
+    Hello,
+    /// Something the user typed
+    Message,
+    /// "I got your message", with the same sequence number
+    Ack,
+    /// "I'm leaving"
+    Bye,
+}
+
+impl Kind {
+    /// Turn a kind into the byte we send
+    fn to_byte(self) -> u8 {
+        match self {
+            Kind::Hello => b'H',
+            Kind::Message => b'M',
+            Kind::Ack => b'A',
+            Kind::Bye => b'B',
+        }
+    }
+
+    /// Turn a byte we received into a kind, if it is one
+    fn from_byte(byte: u8) -> Option<Kind> {
+        match byte {
+            b'H' => Some(Kind::Hello),
+            b'M' => Some(Kind::Message),
+            b'A' => Some(Kind::Ack),
+            b'B' => Some(Kind::Bye),
+            _ => None,
+        }
+    }
+}
+
+/// One frame, sent or received
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Frame {
+    /// What sort of frame it is
+    pub kind: Kind,
+    /// The sequence number, so we can match up acknowledgements and spot
+    /// repeats
+    pub seq: u8,
+    payload: [u8; MAX_PAYLOAD],
+    len: usize,
+}
+
+impl Frame {
+    /// Make a frame. A payload which is too long is cut short.
+    pub fn new(kind: Kind, seq: u8, payload: &[u8]) -> Frame {
+        let len = payload.len().min(MAX_PAYLOAD);
+        let mut frame = Frame {
+            kind,
+            seq,
+            payload: [0; MAX_PAYLOAD],
+            len,
+        };
+        frame.payload[0..len].copy_from_slice(&payload[0..len]);
+        frame
+    }
+
+    /// Get the payload
+    pub fn payload(&self) -> &[u8] {
+        &self.payload[0..self.len]
+    }
+
+    /// Send the frame down the link
+    pub fn send(&self, link: &neotron_sdk::File) -> Result<(), neotron_sdk::Error> {
+        let header = [self.kind.to_byte(), self.seq, self.len as u8];
+        let sum = header
+            .iter()
+            .chain(self.payload().iter())
+            .fold(0u8, |sum, &b| sum.wrapping_add(b));
+        link.write(&[START])?;
+        link.write(&header)?;
+        link.write(self.payload())?;
+        link.write(&[sum.wrapping_neg()])?;
+        Ok(())
+    }
+}
+
+/// What the decoder is waiting for
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum State {
+    /// Looking for the start of a frame
+    Hunt,
+    /// The kind
+    Kind,
+    /// The sequence number
+    Seq,
+    /// The payload length
+    Len,
+    /// The payload
+    Payload,
+    /// The check byte
+    Check,
+}
+
+/// Pulls frames out of the bytes coming in from the link
+pub struct Decoder {
+    state: State,
+    kind: u8,
+    seq: u8,
+    len: usize,
+    received: usize,
+    payload: [u8; MAX_PAYLOAD],
+    sum: u8,
+    /// How many frames have been thrown away
+    errors: u32,
+}
+
+impl Decoder {
+    /// Make a decoder, waiting for a frame to start
+    pub const fn new() -> Decoder {
+        Decoder {
+            state: State::Hunt,
+            kind: 0,
+            seq: 0,
+            len: 0,
+            received: 0,
+            payload: [0; MAX_PAYLOAD],
+            sum: 0,
+            errors: 0,
+        }
+    }
+
+    /// How many frames we had to throw away because they were mangled
+    pub fn errors(&self) -> u32 {
+        self.errors
+    }
+
+    /// Process one byte from the link. Returns a frame if that byte finished
+    /// one off.
+    pub fn feed(&mut self, byte: u8) -> Option<Frame> {
+        self.sum = self.sum.wrapping_add(byte);
+        match self.state {
+            State::Hunt => {
+                if byte == START {
+                    self.sum = 0;
+                    self.state = State::Kind;
+                }
+            }
+            State::Kind => {
+                self.kind = byte;
+                self.state = State::Seq;
+            }
+            State::Seq => {
+                self.seq = byte;
+                self.state = State::Len;
+            }
+            State::Len if usize::from(byte) > MAX_PAYLOAD => self.reject(),
+            State::Len => {
+                self.len = usize::from(byte);
+                self.received = 0;
+                self.state = if self.len == 0 {
+                    State::Check
+                } else {
+                    State::Payload
+                };
+            }
+            State::Payload => {
+                self.payload[self.received] = byte;
+                self.received += 1;
+                if self.received == self.len {
+                    self.state = State::Check;
+                }
+            }
+            State::Check => {
+                self.state = State::Hunt;
+                let kind = Kind::from_byte(self.kind);
+                match kind {
+                    Some(kind) if self.sum == 0 => {
+                        return Some(Frame::new(kind, self.seq, &self.payload[0..self.len]));
+                    }
+                    _ => self.errors += 1,
+                }
+            }
+        }
+        None
+    }
+
+    /// Give up on the frame we were decoding
+    fn reject(&mut self) {
+        self.errors += 1;
+        self.state = State::Hunt;
+    }
+}
+
+impl Default for Decoder {
+    fn default() -> Self {
+        Decoder::new()
+    }
+}
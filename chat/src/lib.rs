@@ -0,0 +1,734 @@
+//! Application logic for the serial chat
+
+#![no_std]
+#![deny(missing_docs)]
+#![deny(unsafe_code)]
+
+use core::fmt::Write;
+
+use neotron_input::{KeyEvent, Keyboard};
+use neotron_line::{Editor, Outcome};
+use neotron_sdk::console;
+
+pub mod frame;
+
+use frame::{Decoder, Frame, Kind, MAX_PAYLOAD};
+
+/// The ioctl which sets the baud rate of a serial device
+const IOCTL_SET_BAUD: u64 = 1;
+
+/// How long we wait each time round the loop
+const TICK: core::time::Duration = core::time::Duration::from_millis(10);
+
+/// How often we say hello (in ticks)
+const HELLO_TICKS: u32 = 200;
+
+/// How long the other end can be quiet before we think it's gone (in ticks)
+const LOST_TICKS: u32 = 700;
+
+/// How long we wait for a message to be acknowledged (in ticks)
+const RETRY_TICKS: u32 = 150;
+
+/// How many times we send a message before giving up on it
+const MAX_TRIES: u8 = 4;
+
+/// How many messages can be waiting to go
+const MAX_QUEUE: usize = 4;
+
+/// The longest name we keep
+const MAX_NAME: usize = 12;
+
+/// How many lines of conversation we remember
+const HISTORY: usize = 100;
+
+/// The longest line of conversation
+const LINE_LEN: usize = 78;
+
+/// How many rows the box you type in has
+const INPUT_ROWS: u8 = 3;
+
+/// How many bytes of what we've sent we remember, to bring back with Up
+const SENT_HISTORY: usize = 512;
+
+/// What we know about the other end of the link
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Status {
+    /// We haven't heard from anyone yet
+    Waiting,
+    /// They're there, and talking to us
+    Connected,
+    /// They've gone quiet
+    Lost,
+    /// They said goodbye
+    Left,
+}
+
+/// Who said a line of the conversation
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Speaker {
+    /// We did
+    Me,
+    /// The other end did
+    Them,
+    /// It's news about the link
+    System,
+}
+
+/// Has one of our messages got there?
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Delivery {
+    /// Not one of ours, or not the first line of it
+    None,
+    /// Still waiting to hear
+    Pending,
+    /// It got there
+    Delivered,
+    /// We gave up
+    Failed,
+}
+
+/// One line of the conversation
+#[derive(Debug, Copy, Clone)]
+struct Line {
+    text: [u8; LINE_LEN],
+    len: usize,
+    speaker: Speaker,
+    delivery: Delivery,
+    /// The sequence number of the message it came from, if it was ours
+    seq: u8,
+}
+
+impl Line {
+    /// An empty line
+    const EMPTY: Line = Line {
+        text: [0; LINE_LEN],
+        len: 0,
+        speaker: Speaker::System,
+        delivery: Delivery::None,
+        seq: 0,
+    };
+}
+
+/// A message waiting to be acknowledged
+#[derive(Debug, Copy, Clone)]
+struct Outgoing {
+    frame: Frame,
+    tries: u8,
+    /// When we last sent it
+    sent_at: u32,
+}
+
+/// Represents the chat application
+pub struct App {
+    width: u8,
+    height: u8,
+    stdout: neotron_sdk::File,
+    keyboard: Keyboard,
+    link: Option<neotron_sdk::File>,
+    baud: u32,
+    decoder: Decoder,
+    name: [u8; MAX_NAME],
+    name_len: usize,
+    peer: [u8; MAX_NAME],
+    peer_len: usize,
+    status: Status,
+    /// Counts up every time round the loop
+    tick: u32,
+    /// When we last heard anything from the other end
+    last_heard: u32,
+    /// When we last said hello
+    last_hello: Option<u32>,
+    /// The sequence number of our next message
+    tx_seq: u8,
+    /// The sequence number of the last message we got, so we can spot repeats
+    rx_seq: Option<u8>,
+    queue: [Option<Outgoing>; MAX_QUEUE],
+    history: [Line; HISTORY],
+    lines: usize,
+    /// How many lines back from the end we are looking
+    scroll: usize,
+    /// What the user is typing
+    editor: Editor<MAX_PAYLOAD, SENT_HISTORY>,
+    history_changed: bool,
+    status_changed: bool,
+    input_changed: bool,
+}
+
+impl App {
+    /// Make a new chat application.
+    ///
+    /// You can give the screen size in characters.
+    pub const fn new(width: u8, height: u8) -> App {
+        App {
+            width,
+            height,
+            stdout: neotron_sdk::stdout(),
+            keyboard: Keyboard::new(),
+            link: None,
+            baud: 0,
+            decoder: Decoder::new(),
+            name: *b"NEOTRON     ",
+            name_len: 7,
+            peer: [0; MAX_NAME],
+            peer_len: 0,
+            status: Status::Waiting,
+            tick: 0,
+            last_heard: 0,
+            last_hello: None,
+            tx_seq: 0,
+            rx_seq: None,
+            queue: [None; MAX_QUEUE],
+            history: [Line::EMPTY; HISTORY],
+            lines: 0,
+            scroll: 0,
+            editor: Editor::new(),
+            history_changed: true,
+            status_changed: true,
+            input_changed: true,
+        }
+    }
+
+    /// Choose the name the other end sees
+    pub fn set_name(&mut self, name: &str) {
+        let name = name.trim();
+        let len = name.len().min(MAX_NAME);
+        self.name[0..len].copy_from_slice(&name.as_bytes()[0..len]);
+        sanitise(&mut self.name[0..len]);
+        self.name_len = len;
+    }
+
+    /// Open the serial device, and set its baud rate
+    pub fn open(&mut self, device: &str, baud: u32) -> Result<(), neotron_sdk::Error> {
+        let path = neotron_sdk::path::Path::new(device)?;
+        let link = neotron_sdk::File::open(path, neotron_sdk::Flags::empty())?;
+        link.ioctl(IOCTL_SET_BAUD, u64::from(baud))?;
+        self.link = Some(link);
+        self.baud = baud;
+        Ok(())
+    }
+
+    /// Run the chat, until the user leaves
+    pub fn run(&mut self) {
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        console::clear_screen(&mut self.stdout);
+        self.draw_title();
+        self.add_system("Waiting for the other Neotron to say hello...");
+        loop {
+            self.receive();
+            if !self.handle_keys() {
+                break;
+            }
+            self.housekeeping();
+            self.draw();
+            neotron_sdk::delay(TICK);
+            self.tick = self.tick.wrapping_add(1);
+        }
+        self.send(&Frame::new(Kind::Bye, 0, &[]));
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        console::clear_screen(&mut self.stdout);
+        console::move_cursor(&mut self.stdout, console::Position::origin());
+        console::cursor_on(&mut self.stdout);
+    }
+
+    /// Deal with anything that has come down the link
+    fn receive(&mut self) {
+        let Some(link) = self.link.as_ref() else {
+            return;
+        };
+        let mut buffer = [0u8; 128];
+        let Ok(n) = link.read(&mut buffer) else {
+            return;
+        };
+        let errors = self.decoder.errors();
+        for &byte in &buffer[0..n] {
+            if let Some(frame) = self.decoder.feed(byte) {
+                self.handle_frame(&frame);
+            }
+        }
+        if self.decoder.errors() != errors {
+            self.status_changed = true;
+        }
+    }
+
+    /// Deal with a frame from the other end
+    fn handle_frame(&mut self, frame: &Frame) {
+        self.last_heard = self.tick;
+        match frame.kind {
+            Kind::Hello => {
+                let len = frame.payload().len().min(MAX_NAME);
+                if self.peer[0..self.peer_len] != frame.payload()[0..len] {
+                    self.peer[0..len].copy_from_slice(&frame.payload()[0..len]);
+                    sanitise(&mut self.peer[0..len]);
+                    self.peer_len = len;
+                    self.status_changed = true;
+                }
+                if frame.seq == 0 {
+                    // They've just started, so their messages start again
+                    // too, and they'll want to know who we are
+                    self.rx_seq = None;
+                    self.say_hello();
+                }
+                self.connected();
+            }
+            Kind::Message => {
+                self.send(&Frame::new(Kind::Ack, frame.seq, &[]));
+                self.connected();
+                if self.rx_seq != Some(frame.seq) {
+                    self.rx_seq = Some(frame.seq);
+                    self.add_message(Speaker::Them, frame.seq, frame.payload());
+                }
+            }
+            Kind::Ack => {
+                self.connected();
+                if let Some(outgoing) = self.queue[0] {
+                    if outgoing.frame.seq == frame.seq {
+                        self.set_delivery(frame.seq, Delivery::Delivered);
+                        self.pop_queue();
+                    }
+                }
+            }
+            Kind::Bye => {
+                if self.status == Status::Connected {
+                    self.status = Status::Left;
+                    self.status_changed = true;
+                    self.add_peer_news(" has left.");
+                }
+            }
+        }
+    }
+
+    /// Note that the other end is talking to us
+    fn connected(&mut self) {
+        if self.status != Status::Connected {
+            self.status = Status::Connected;
+            self.status_changed = true;
+            self.add_peer_news(" is here. Say hello!");
+        }
+    }
+
+    /// Send hellos and messages, and notice if the other end has gone
+    fn housekeeping(&mut self) {
+        let hello_due = match self.last_hello {
+            Some(when) => self.tick.wrapping_sub(when) >= HELLO_TICKS,
+            None => true,
+        };
+        if hello_due {
+            self.say_hello();
+        }
+
+        if self.status == Status::Connected && self.tick.wrapping_sub(self.last_heard) >= LOST_TICKS
+        {
+            self.status = Status::Lost;
+            self.status_changed = true;
+            self.add_peer_news(" has gone quiet.");
+        }
+
+        // We only send one message at a time, so they can't overtake
+        // each other. There's no point sending if nobody's listening.
+        if self.status != Status::Connected {
+            return;
+        }
+        let Some(mut outgoing) = self.queue[0] else {
+            return;
+        };
+        if outgoing.tries > 0 && self.tick.wrapping_sub(outgoing.sent_at) < RETRY_TICKS {
+            // Give the acknowledgement a chance to arrive
+            return;
+        }
+        if outgoing.tries == MAX_TRIES {
+            self.set_delivery(outgoing.frame.seq, Delivery::Failed);
+            self.pop_queue();
+            return;
+        }
+        self.send(&outgoing.frame);
+        outgoing.tries += 1;
+        outgoing.sent_at = self.tick;
+        self.queue[0] = Some(outgoing);
+    }
+
+    /// Tell the other end who we are
+    fn say_hello(&mut self) {
+        // The first hello has sequence number zero, so the other end knows
+        // we have just started
+        let seq = if self.last_hello.is_some() { 1 } else { 0 };
+        self.send(&Frame::new(Kind::Hello, seq, &self.name[0..self.name_len]));
+        self.last_hello = Some(self.tick);
+    }
+
+    /// Send a frame, ignoring errors. If it gets lost, we'll send it again.
+    fn send(&self, frame: &Frame) {
+        if let Some(link) = self.link.as_ref() {
+            let _ = frame.send(link);
+        }
+    }
+
+    /// Take the front message off the queue, now we're done with it
+    fn pop_queue(&mut self) {
+        self.queue.copy_within(1.., 0);
+        self.queue[MAX_QUEUE - 1] = None;
+        self.status_changed = true;
+    }
+
+    /// How many messages are waiting to go
+    fn queued(&self) -> usize {
+        self.queue.iter().filter(|o| o.is_some()).count()
+    }
+
+    /// Deal with whatever the user has typed. Returns false if they want to
+    /// leave.
+    fn handle_keys(&mut self) -> bool {
+        while let Some(key) = self.keyboard.read() {
+            match key {
+                KeyEvent::Escape => return false,
+                KeyEvent::PageUp => {
+                    let page = usize::from(self.history_rows());
+                    let most = self.lines.saturating_sub(page);
+                    self.scroll = (self.scroll + page / 2).min(most);
+                    self.history_changed = true;
+                }
+                KeyEvent::PageDown => {
+                    let page = usize::from(self.history_rows());
+                    self.scroll = self.scroll.saturating_sub(page / 2);
+                    self.history_changed = true;
+                }
+                KeyEvent::Ctrl('l') => {
+                    // Ctrl+L draws everything again
+                    console::clear_screen(&mut self.stdout);
+                    self.draw_title();
+                    self.history_changed = true;
+                    self.status_changed = true;
+                    self.input_changed = true;
+                }
+                // The other end only shows ASCII
+                KeyEvent::Char(ch) if !ch.is_ascii() => {}
+                // We draw the box ourselves, as it's more than one row
+                key => {
+                    match self.editor.handle(&mut NoEcho, key) {
+                        Outcome::Editing => {}
+                        Outcome::Done => self.send_input(),
+                        Outcome::Cancelled => self.editor.start(),
+                    }
+                    self.input_changed = true;
+                }
+            }
+        }
+        true
+    }
+
+    /// Put what the user typed in the queue, and in the conversation
+    fn send_input(&mut self) {
+        let mut input = [0u8; MAX_PAYLOAD];
+        let text = self.editor.as_str().as_bytes();
+        let len = text.len();
+        if len == 0 {
+            return;
+        }
+        input[0..len].copy_from_slice(text);
+        let Some(slot) = self.queue.iter().position(|o| o.is_none()) else {
+            self.add_system("Too many messages waiting to go - hang on a moment.");
+            return;
+        };
+        let seq = self.tx_seq;
+        self.tx_seq = self.tx_seq.wrapping_add(1);
+        let frame = Frame::new(Kind::Message, seq, &input[0..len]);
+        self.queue[slot] = Some(Outgoing {
+            frame,
+            tries: 0,
+            sent_at: 0,
+        });
+        self.add_message(Speaker::Me, seq, &input[0..len]);
+        self.editor.start();
+        self.input_changed = true;
+        self.status_changed = true;
+    }
+
+    /// Add a message to the conversation, with the speaker's name in front,
+    /// wrapping it onto as many lines as it needs
+    fn add_message(&mut self, speaker: Speaker, seq: u8, text: &[u8]) {
+        let mut line = Line::EMPTY;
+        line.speaker = speaker;
+        line.seq = seq;
+        if speaker == Speaker::Me {
+            line.delivery = Delivery::Pending;
+        }
+        let name = match speaker {
+            Speaker::Me => &self.name[0..self.name_len],
+            _ => &self.peer[0..self.peer_len],
+        };
+        line.text[0] = b'<';
+        line.text[1..=name.len()].copy_from_slice(name);
+        line.text[name.len() + 1] = b'>';
+        line.text[name.len() + 2] = b' ';
+        line.len = name.len() + 3;
+        self.add_wrapped(line, text);
+    }
+
+    /// Add some news about the link to the conversation
+    fn add_system(&mut self, text: &str) {
+        self.add_wrapped(Line::EMPTY, text.as_bytes());
+    }
+
+    /// Add some news about the other end, with their name in front
+    fn add_peer_news(&mut self, text: &str) {
+        let mut line = Line::EMPTY;
+        let name = if self.peer_len > 0 {
+            &self.peer[0..self.peer_len]
+        } else {
+            b"The other end"
+        };
+        line.text[0..name.len()].copy_from_slice(name);
+        line.len = name.len();
+        self.add_wrapped(line, text.as_bytes());
+    }
+
+    /// Add some text to the conversation, after whatever `first` already
+    /// holds, wrapping at spaces where we can
+    fn add_wrapped(&mut self, first: Line, text: &[u8]) {
+        let mut line = first;
+        let mut rest = text;
+        loop {
+            let space = LINE_LEN - line.len;
+            let mut take = rest.len().min(space);
+            if take < rest.len() {
+                // Break at the last space, if there is one
+                if let Some(pos) = rest[0..=take].iter().rposition(|&b| b == b' ') {
+                    take = pos.max(1);
+                }
+            }
+            line.text[line.len..line.len + take].copy_from_slice(&rest[0..take]);
+            sanitise(&mut line.text[line.len..line.len + take]);
+            line.len += take;
+            self.push_line(line);
+            rest = &rest[take..];
+            while rest.first() == Some(&b' ') {
+                rest = &rest[1..];
+            }
+            if rest.is_empty() {
+                break;
+            }
+            // The rest is indented, and doesn't get a delivery mark
+            line = Line {
+                len: 2,
+                delivery: Delivery::None,
+                ..Line::EMPTY
+            };
+            line.text[0..2].copy_from_slice(b"  ");
+            line.speaker = first.speaker;
+        }
+    }
+
+    /// Put a line on the end of the conversation, forgetting the oldest if
+    /// we need the room
+    fn push_line(&mut self, line: Line) {
+        if self.lines == HISTORY {
+            self.history.copy_within(1.., 0);
+            self.lines -= 1;
+        }
+        self.history[self.lines] = line;
+        self.lines += 1;
+        if self.scroll > 0 {
+            // Keep looking at the same lines
+            self.scroll = (self.scroll + 1).min(self.lines);
+        }
+        self.history_changed = true;
+    }
+
+    /// Mark one of our messages as delivered (or not)
+    fn set_delivery(&mut self, seq: u8, delivery: Delivery) {
+        if let Some(line) = self.history[0..self.lines].iter_mut().rev().find(|line| {
+            line.speaker == Speaker::Me && line.seq == seq && line.delivery == Delivery::Pending
+        }) {
+            line.delivery = delivery;
+            self.history_changed = true;
+        }
+    }
+
+    /// How many rows of conversation fit on the screen
+    fn history_rows(&self) -> u8 {
+        self.height - INPUT_ROWS - 4
+    }
+
+    /// The row with the status bar on it
+    fn status_row(&self) -> u8 {
+        1 + self.history_rows()
+    }
+
+    /// Draw whatever has changed
+    fn draw(&mut self) {
+        if self.history_changed {
+            self.draw_history();
+            self.history_changed = false;
+            // Drawing moves the cursor
+            self.input_changed = true;
+        }
+        if self.status_changed {
+            self.draw_status();
+            self.status_changed = false;
+            self.input_changed = true;
+        }
+        if self.input_changed {
+            self.draw_input();
+            self.input_changed = false;
+        }
+    }
+
+    /// Draw the title and the help
+    fn draw_title(&mut self) {
+        console::cursor_off(&mut self.stdout);
+        console::set_sgr(
+            &mut self.stdout,
+            [
+                console::SgrParam::Bold,
+                console::SgrParam::FgWhite,
+                console::SgrParam::BgBlue,
+            ],
+        );
+        self.move_to(0, 0);
+        let mut title = neotron_tui::Line::new();
+        let _ = write!(
+            title,
+            "CHAT - You are {} - {} baud",
+            core::str::from_utf8(&self.name[0..self.name_len]).unwrap_or("?"),
+            self.baud
+        );
+        let _ = write!(
+            self.stdout,
+            "{:^1$}",
+            title.as_str(),
+            usize::from(self.width)
+        );
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        self.move_to(self.height - 2, 0);
+        let _ = self
+            .stdout
+            .write_str("Enter: Send  PgUp/PgDn: Scroll  Ctrl+L: Redraw  Esc: Quit");
+    }
+
+    /// Draw the conversation
+    fn draw_history(&mut self) {
+        console::cursor_off(&mut self.stdout);
+        let rows = usize::from(self.history_rows());
+        let end = self.lines - self.scroll.min(self.lines);
+        let start = end.saturating_sub(rows);
+        for row in 0..rows {
+            self.move_to(1 + row as u8, 0);
+            let Some(line) = self.history[start..end].get(row).copied() else {
+                let _ = write!(self.stdout, "{:1$}", "", usize::from(self.width) - 1);
+                continue;
+            };
+            let mark = match line.delivery {
+                Delivery::None | Delivery::Delivered => ' ',
+                Delivery::Pending => '-',
+                Delivery::Failed => '!',
+            };
+            let colour = match (line.speaker, line.delivery) {
+                (_, Delivery::Failed) => console::SgrParam::FgRed,
+                (Speaker::Me, _) => console::SgrParam::FgGreen,
+                (Speaker::Them, _) => console::SgrParam::FgCyan,
+                (Speaker::System, _) => console::SgrParam::FgYellow,
+            };
+            console::set_sgr(&mut self.stdout, [colour]);
+            let text = core::str::from_utf8(&line.text[0..line.len]).unwrap_or("?");
+            let _ = write!(
+                self.stdout,
+                "{} {:<2$}",
+                mark,
+                text,
+                usize::from(self.width) - 3
+            );
+            console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        }
+    }
+
+    /// Draw the status bar between the conversation and the typing
+    fn draw_status(&mut self) {
+        console::cursor_off(&mut self.stdout);
+        let mut left = neotron_tui::Line::new();
+        let peer = core::str::from_utf8(&self.peer[0..self.peer_len]).unwrap_or("?");
+        let colour = match self.status {
+            Status::Waiting => {
+                let _ = write!(left, " Waiting for the other end");
+                console::SgrParam::BgYellow
+            }
+            Status::Connected => {
+                let _ = write!(left, " Connected to {}", peer);
+                console::SgrParam::BgGreen
+            }
+            Status::Lost => {
+                let _ = write!(left, " Lost contact with {}", peer);
+                console::SgrParam::BgRed
+            }
+            Status::Left => {
+                let _ = write!(left, " {} has left", peer);
+                console::SgrParam::BgRed
+            }
+        };
+        let mut right = neotron_tui::Line::new();
+        if self.scroll > 0 {
+            let _ = write!(right, "Scrolled back  ");
+        }
+        let queued = self.queued();
+        if queued > 0 {
+            let _ = write!(right, "{} to send  ", queued);
+        }
+        let _ = write!(right, "{} bad frames ", self.decoder.errors());
+        self.move_to(self.status_row(), 0);
+        console::set_sgr(&mut self.stdout, [console::SgrParam::FgBlack, colour]);
+        let width = usize::from(self.width) - 1;
+        let _ = write!(
+            self.stdout,
+            "{}{:>2$}",
+            left.as_str(),
+            right.as_str(),
+            width.saturating_sub(left.as_str().len())
+        );
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+    }
+
+    /// Draw what the user is typing, and leave the cursor after it
+    fn draw_input(&mut self) {
+        console::cursor_off(&mut self.stdout);
+        let width = usize::from(self.width) - 3;
+        let first_row = self.status_row() + 1;
+        let mut input = [0u8; MAX_PAYLOAD];
+        let len = self.editor.as_str().len();
+        input[0..len].copy_from_slice(self.editor.as_str().as_bytes());
+        let text = core::str::from_utf8(&input[0..len]).unwrap_or("");
+        for row in 0..INPUT_ROWS {
+            let start = (usize::from(row) * width).min(text.len());
+            let end = (start + width).min(text.len());
+            self.move_to(first_row + row, 0);
+            let prompt = if row == 0 { "> " } else { "  " };
+            let _ = write!(self.stdout, "{}{:<2$}", prompt, &text[start..end], width);
+        }
+        let cursor = self.editor.cursor_col();
+        let row = (cursor / width).min(usize::from(INPUT_ROWS) - 1);
+        let col = (cursor - row * width).min(width);
+        self.move_to(first_row + row as u8, 2 + col as u8);
+        console::cursor_on(&mut self.stdout);
+    }
+
+    /// Move the cursor
+    fn move_to(&mut self, row: u8, col: u8) {
+        console::move_cursor(&mut self.stdout, console::Position { row, col });
+    }
+}
+
+/// Somewhere for the line editor to echo to, as we draw what's typed
+/// ourselves
+struct NoEcho;
+
+impl Write for NoEcho {
+    fn write_str(&mut self, _s: &str) -> core::fmt::Result {
+        Ok(())
+    }
+}
+
+/// Replace anything we can't print with a `?`
+fn sanitise(text: &mut [u8]) {
+    for byte in text.iter_mut() {
+        if !(b' '..=b'~').contains(byte) {
+            *byte = b'?';
+        }
+    }
+}
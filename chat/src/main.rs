@@ -0,0 +1,52 @@
+#![cfg_attr(target_os = "none", no_std)]
+#![cfg_attr(target_os = "none", no_main)]
+
+use core::{fmt::Write, ptr::addr_of_mut};
+
+/// The device we open if you don't give one
+const DEFAULT_DEVICE: &str = "SER0:";
+
+/// The baud rate we use if you don't give one
+const DEFAULT_BAUD: u32 = 115200;
+
+#[cfg(not(target_os = "none"))]
+fn main() {
+    neotron_sdk::init();
+}
+
+static mut APP: chat::App = chat::App::new(80, 25);
+
+#[no_mangle]
+extern "C" fn neotron_main() -> i32 {
+    neotron_error::exit_code(real_main())
+}
+
+fn real_main() -> Result<(), neotron_sdk::Error> {
+    let app = unsafe { &mut *addr_of_mut!(APP) };
+    let mut first = 0;
+    if neotron_sdk::arg(0).is_some_and(|arg| arg == "-n") {
+        let Some(name) = neotron_sdk::arg(1) else {
+            let mut stdout = neotron_sdk::stdout();
+            let _ = writeln!(stdout, "Usage: chat [-n <name>] [<device> [<baud>]]");
+            return Err(neotron_sdk::Error::InvalidArg);
+        };
+        app.set_name(&name);
+        first = 2;
+    }
+    let device_name = neotron_sdk::arg(first);
+    let device_name: &str = match &device_name {
+        Some(name) => name,
+        None => DEFAULT_DEVICE,
+    };
+    let baud = match neotron_sdk::arg(first + 1) {
+        Some(baud) => baud.parse().map_err(|_| neotron_sdk::Error::InvalidArg)?,
+        None => DEFAULT_BAUD,
+    };
+    if app.open(device_name, baud).is_err() {
+        let mut stdout = neotron_sdk::stdout();
+        let _ = writeln!(stdout, "Failed to open {} at {} baud", device_name, baud);
+        return Err(neotron_sdk::Error::DeviceSpecific);
+    }
+    app.run();
+    Ok(())
+}
@@ -0,0 +1,60 @@
+//! CRC-32, as used by Zip, PNG and Ethernet.
+
+/// The polynomial, bit-reversed
+const POLYNOMIAL: u32 = 0xEDB8_8320;
+
+/// The CRC of every byte, so we can do a byte at a time
+static TABLE: [u32; 256] = make_table();
+
+/// Work out the table
+const fn make_table() -> [u32; 256] {
+    let mut table = [0u32; 256];
+    let mut byte = 0;
+    while byte < 256 {
+        let mut crc = byte as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ POLYNOMIAL
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[byte] = crc;
+        byte += 1;
+    }
+    table
+}
+
+/// Works out a CRC-32 a piece at a time
+#[derive(Debug, Copy, Clone)]
+pub struct Crc32 {
+    crc: u32,
+}
+
+impl Crc32 {
+    /// Start a new CRC
+    pub const fn new() -> Crc32 {
+        Crc32 { crc: 0xFFFF_FFFF }
+    }
+
+    /// Take in some more data
+    pub fn update(&mut self, data: &[u8]) {
+        for &byte in data {
+            let index = usize::from((self.crc as u8) ^ byte);
+            self.crc = (self.crc >> 8) ^ TABLE[index];
+        }
+    }
+
+    /// The CRC of everything so far, written big-endian like it usually is
+    pub fn finish(self) -> [u8; 4] {
+        (!self.crc).to_be_bytes()
+    }
+}
+
+impl Default for Crc32 {
+    fn default() -> Self {
+        Crc32::new()
+    }
+}
@@ -0,0 +1,296 @@
+//! Application logic for the checksum utility
+//!
+//! Files are read a chunk at a time, so they can be any size. Sums are
+//! written like `sha256sum` and friends write them, so lists made on a PC
+//! can be checked on a Neotron, and the other way round:
+//!
+//! ```text
+//! 9e107d9d372bb6826bd81d3542a419d6  GAME.ELF
+//! ```
+
+#![no_std]
+#![deny(missing_docs)]
+#![deny(unsafe_code)]
+
+use core::fmt::Write;
+
+pub mod crc32;
+pub mod md5;
+pub mod sha256;
+
+/// How much of a file we read at once
+const CHUNK_LEN: usize = 512;
+
+/// The longest line we read from a list of sums
+pub const MAX_LINE: usize = 160;
+
+/// The longest sum, which is SHA-256's
+const MAX_SUM: usize = 32;
+
+/// The ways we can sum a file
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Algorithm {
+    /// CRC-32, as used by Zip
+    Crc32,
+    /// MD5
+    Md5,
+    /// SHA-256
+    Sha256,
+}
+
+impl Algorithm {
+    /// Every algorithm, in the order we mention them
+    pub const ALL: [Algorithm; 3] = [Algorithm::Crc32, Algorithm::Md5, Algorithm::Sha256];
+
+    /// Find an algorithm by name, like `sha256`
+    pub fn parse(text: &str) -> Option<Algorithm> {
+        Self::ALL.into_iter().find(|algorithm| {
+            text.eq_ignore_ascii_case(algorithm.name())
+                || text.eq_ignore_ascii_case(algorithm.long_name())
+        })
+    }
+
+    /// The short name, like `sha256`
+    pub fn name(self) -> &'static str {
+        match self {
+            Algorithm::Crc32 => "crc32",
+            Algorithm::Md5 => "md5",
+            Algorithm::Sha256 => "sha256",
+        }
+    }
+
+    /// The name with punctuation, like `SHA-256`
+    pub fn long_name(self) -> &'static str {
+        match self {
+            Algorithm::Crc32 => "CRC-32",
+            Algorithm::Md5 => "MD5",
+            Algorithm::Sha256 => "SHA-256",
+        }
+    }
+
+    /// How many bytes long its sums are
+    pub fn sum_len(self) -> usize {
+        match self {
+            Algorithm::Crc32 => 4,
+            Algorithm::Md5 => 16,
+            Algorithm::Sha256 => 32,
+        }
+    }
+
+    /// Which algorithm makes sums this many hex digits long
+    fn from_hex_len(digits: usize) -> Option<Algorithm> {
+        Self::ALL
+            .into_iter()
+            .find(|algorithm| algorithm.sum_len() * 2 == digits)
+    }
+}
+
+/// A sum, from any of the algorithms
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Sum {
+    bytes: [u8; MAX_SUM],
+    len: usize,
+}
+
+impl Sum {
+    /// Make a sum from its bytes
+    fn new(bytes: &[u8]) -> Sum {
+        let mut sum = Sum {
+            bytes: [0; MAX_SUM],
+            len: bytes.len(),
+        };
+        sum.bytes[0..bytes.len()].copy_from_slice(bytes);
+        sum
+    }
+
+    /// Read a sum written in hex
+    pub fn from_hex(text: &str) -> Option<Sum> {
+        let text = text.as_bytes();
+        if !text.len().is_multiple_of(2) || text.len() > MAX_SUM * 2 {
+            return None;
+        }
+        let mut sum = Sum {
+            bytes: [0; MAX_SUM],
+            len: text.len() / 2,
+        };
+        for (byte, pair) in sum.bytes.iter_mut().zip(text.chunks_exact(2)) {
+            let pair = core::str::from_utf8(pair).ok()?;
+            *byte = u8::from_str_radix(pair, 16).ok()?;
+        }
+        Some(sum)
+    }
+
+    /// The sum's bytes
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes[0..self.len]
+    }
+}
+
+/// Written in lower-case hex
+impl core::fmt::Display for Sum {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        for byte in self.as_bytes() {
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+/// Works out a sum with any of the algorithms
+#[derive(Debug, Clone)]
+pub enum Hasher {
+    /// CRC-32
+    Crc32(crc32::Crc32),
+    /// MD5
+    Md5(md5::Md5),
+    /// SHA-256
+    Sha256(sha256::Sha256),
+}
+
+impl Hasher {
+    /// Start a new sum
+    pub fn new(algorithm: Algorithm) -> Hasher {
+        match algorithm {
+            Algorithm::Crc32 => Hasher::Crc32(crc32::Crc32::new()),
+            Algorithm::Md5 => Hasher::Md5(md5::Md5::new()),
+            Algorithm::Sha256 => Hasher::Sha256(sha256::Sha256::new()),
+        }
+    }
+
+    /// Take in some more data
+    pub fn update(&mut self, data: &[u8]) {
+        match self {
+            Hasher::Crc32(h) => h.update(data),
+            Hasher::Md5(h) => h.update(data),
+            Hasher::Sha256(h) => h.update(data),
+        }
+    }
+
+    /// The sum of everything so far
+    pub fn finish(self) -> Sum {
+        match self {
+            Hasher::Crc32(h) => Sum::new(&h.finish()),
+            Hasher::Md5(h) => Sum::new(&h.finish()),
+            Hasher::Sha256(h) => Sum::new(&h.finish()),
+        }
+    }
+}
+
+/// Sum a whole file, a chunk at a time
+pub fn sum_file(filename: &str, algorithm: Algorithm) -> Result<Sum, neotron_sdk::Error> {
+    let path = neotron_sdk::path::Path::new(filename)?;
+    let file = neotron_sdk::File::open(path, neotron_sdk::Flags::empty())?;
+    let mut hasher = Hasher::new(algorithm);
+    let mut buffer = [0u8; CHUNK_LEN];
+    loop {
+        let n = file.read(&mut buffer)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buffer[0..n]);
+    }
+    Ok(hasher.finish())
+}
+
+/// What happened when we checked a list of sums
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct Checked {
+    /// Files that matched their sums
+    pub ok: u32,
+    /// Files that didn't
+    pub failed: u32,
+    /// Files we couldn't read
+    pub missing: u32,
+    /// Lines that didn't look like a sum and a file name
+    pub bad_lines: u32,
+}
+
+impl Checked {
+    /// Did every file match?
+    pub fn all_ok(&self) -> bool {
+        self.failed == 0 && self.missing == 0 && self.bad_lines == 0 && self.ok > 0
+    }
+}
+
+/// Split a line from a list into the sum and the file name. We take the
+/// `*` that marks binary files too, and ignore blank lines and `#`
+/// comments by returning `None`.
+pub fn parse_line(line: &str) -> Option<Result<(Sum, &str), ()>> {
+    let line = line.trim_end();
+    if line.trim_start().is_empty() || line.starts_with('#') {
+        return None;
+    }
+    let Some((hex, name)) = line.split_once(' ') else {
+        return Some(Err(()));
+    };
+    let name = name.trim_start_matches(' ');
+    let name = name.strip_prefix('*').unwrap_or(name);
+    match Sum::from_hex(hex) {
+        Some(sum) if !name.is_empty() && Algorithm::from_hex_len(hex.len()).is_some() => {
+            Some(Ok((sum, name)))
+        }
+        _ => Some(Err(())),
+    }
+}
+
+/// Check every file in a list of sums, saying how each one went on `out`.
+/// The length of each sum says which algorithm made it.
+pub fn check_list<W: Write>(list: &str, out: &mut W) -> Result<Checked, neotron_sdk::Error> {
+    let path = neotron_sdk::path::Path::new(list)?;
+    let file = neotron_sdk::File::open(path, neotron_sdk::Flags::empty())?;
+    let mut checked = Checked::default();
+    let mut buffer = [0u8; CHUNK_LEN];
+    let mut line = [0u8; MAX_LINE];
+    let mut line_len = 0;
+    let mut too_long = false;
+    let mut check = |text: &[u8], too_long: bool, checked: &mut Checked| {
+        let parsed = match core::str::from_utf8(text) {
+            Ok(text) if !too_long => parse_line(text),
+            _ => Some(Err(())),
+        };
+        match parsed {
+            None => {}
+            Some(Err(())) => checked.bad_lines += 1,
+            Some(Ok((expected, name))) => {
+                let algorithm =
+                    Algorithm::from_hex_len(expected.as_bytes().len()).unwrap_or(Algorithm::Sha256);
+                match sum_file(name, algorithm) {
+                    Ok(actual) if actual == expected => {
+                        checked.ok += 1;
+                        let _ = writeln!(out, "{}: OK", name);
+                    }
+                    Ok(_) => {
+                        checked.failed += 1;
+                        let _ = writeln!(out, "{}: FAILED", name);
+                    }
+                    Err(e) => {
+                        checked.missing += 1;
+                        let _ = writeln!(out, "{}: can't read ({:?})", name, e);
+                    }
+                }
+            }
+        }
+    };
+    loop {
+        let n = file.read(&mut buffer)?;
+        if n == 0 {
+            break;
+        }
+        for &byte in &buffer[0..n] {
+            if byte == b'\n' {
+                check(&line[0..line_len], too_long, &mut checked);
+                line_len = 0;
+                too_long = false;
+            } else if line_len < MAX_LINE {
+                line[line_len] = byte;
+                line_len += 1;
+            } else {
+                too_long = true;
+            }
+        }
+    }
+    if line_len > 0 {
+        check(&line[0..line_len], too_long, &mut checked);
+    }
+    Ok(checked)
+}
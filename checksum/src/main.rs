@@ -0,0 +1,119 @@
+#![cfg_attr(target_os = "none", no_std)]
+#![cfg_attr(target_os = "none", no_main)]
+
+use core::fmt::Write;
+
+use neotron_args::{Item, Opt};
+
+#[cfg(not(target_os = "none"))]
+fn main() {
+    neotron_sdk::init();
+}
+
+#[no_mangle]
+extern "C" fn neotron_main() -> i32 {
+    match real_main() {
+        Ok(true) => 0,
+        // Something didn't match, or couldn't be read
+        Ok(false) => 1,
+        Err(e) => {
+            let _ = neotron_error::Report::from(e).print(&mut neotron_sdk::stdout());
+            2
+        }
+    }
+}
+
+/// The options we understand
+static SPEC: neotron_args::Spec = neotron_args::Spec {
+    name: "checksum",
+    usage: "[options] <file>...",
+    about: "Works out the checksums of files, or checks them against a list \
+            like the ones sha256sum and md5sum write.",
+    options: &[
+        Opt::value(
+            'a',
+            "algorithm",
+            "name",
+            "crc32, md5 or sha256 (the default)",
+        ),
+        Opt::value(
+            'c',
+            "check",
+            "list",
+            "Check the files in a list of sums, instead",
+        ),
+    ],
+};
+
+/// The most files we sum in one go
+const MAX_FILES: usize = 16;
+
+fn real_main() -> Result<bool, neotron_sdk::Error> {
+    let mut stdout = neotron_sdk::stdout();
+    let mut algorithm = checksum::Algorithm::Sha256;
+    let mut list = None;
+    let mut files: [Option<neotron_args::Arg>; MAX_FILES] = Default::default();
+    let mut count = 0;
+    let mut args = neotron_args::Parser::new(&SPEC);
+    loop {
+        let item = match args.next_item() {
+            Ok(Some(item)) => item,
+            Ok(None) => break,
+            Err(neotron_args::Error::Help) => return Ok(true),
+            Err(e) => return Err(e.into()),
+        };
+        match item {
+            Item::Value("algorithm", value) => {
+                algorithm = checksum::Algorithm::parse(value.as_str())
+                    .ok_or_else(|| args.usage_error("use crc32, md5 or sha256"))?;
+            }
+            Item::Value("check", value) => list = Some(value),
+            Item::Positional(value) if count < MAX_FILES => {
+                files[count] = Some(value);
+                count += 1;
+            }
+            Item::Positional(_) => return Err(args.usage_error("too many files").into()),
+            _ => {}
+        }
+    }
+
+    if let Some(list) = list {
+        if count > 0 {
+            return Err(args
+                .usage_error("--check takes the file names from the list")
+                .into());
+        }
+        let checked = checksum::check_list(list.as_str(), &mut stdout)?;
+        let _ = write!(stdout, "{} OK", checked.ok);
+        for (number, what) in [
+            (checked.failed, "failed"),
+            (checked.missing, "missing"),
+            (checked.bad_lines, "bad lines"),
+        ] {
+            if number > 0 {
+                let _ = write!(stdout, ", {} {}", number, what);
+            }
+        }
+        let _ = writeln!(stdout);
+        return Ok(checked.all_ok());
+    }
+
+    if count == 0 {
+        return Err(args.usage_error("no files to sum").into());
+    }
+    let mut all_ok = true;
+    for name in files.iter().flatten() {
+        match checksum::sum_file(name.as_str(), algorithm) {
+            Ok(sum) => {
+                let _ = writeln!(stdout, "{}  {}", sum, name.as_str());
+            }
+            Err(e) => {
+                all_ok = false;
+                let _ = neotron_error::Report::from(e)
+                    .about(name.as_str())
+                    .print(&mut stdout);
+            }
+        }
+    }
+    Ok(all_ok)
+}
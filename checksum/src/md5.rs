@@ -0,0 +1,112 @@
+//! MD5, from RFC 1321.
+//!
+//! It's no good for security any more, but plenty of download sites still
+//! give MD5 sums, and it's fine for spotting a damaged file.
+
+/// How much data goes in each block
+const BLOCK_LEN: usize = 64;
+
+/// How far each step rotates, four to a round
+const SHIFTS: [u32; 16] = [7, 12, 17, 22, 5, 9, 14, 20, 4, 11, 16, 23, 6, 10, 15, 21];
+
+/// The constants for each step, from the sine function
+const K: [u32; 64] = [
+    0xd76aa478, 0xe8c7b756, 0x242070db, 0xc1bdceee, 0xf57c0faf, 0x4787c62a, 0xa8304613, 0xfd469501,
+    0x698098d8, 0x8b44f7af, 0xffff5bb1, 0x895cd7be, 0x6b901122, 0xfd987193, 0xa679438e, 0x49b40821,
+    0xf61e2562, 0xc040b340, 0x265e5a51, 0xe9b6c7aa, 0xd62f105d, 0x02441453, 0xd8a1e681, 0xe7d3fbc8,
+    0x21e1cde6, 0xc33707d6, 0xf4d50d87, 0x455a14ed, 0xa9e3e905, 0xfcefa3f8, 0x676f02d9, 0x8d2a4c8a,
+    0xfffa3942, 0x8771f681, 0x6d9d6122, 0xfde5380c, 0xa4beea44, 0x4bdecfa9, 0xf6bb4b60, 0xbebfbc70,
+    0x289b7ec6, 0xeaa127fa, 0xd4ef3085, 0x04881d05, 0xd9d4d039, 0xe6db99e5, 0x1fa27cf8, 0xc4ac5665,
+    0xf4292244, 0x432aff97, 0xab9423a7, 0xfc93a039, 0x655b59c3, 0x8f0ccc92, 0xffeff47d, 0x85845dd1,
+    0x6fa87e4f, 0xfe2ce6e0, 0xa3014314, 0x4e0811a1, 0xf7537e82, 0xbd3af235, 0x2ad7d2bb, 0xeb86d391,
+];
+
+/// Works out an MD5 sum a piece at a time
+#[derive(Debug, Copy, Clone)]
+pub struct Md5 {
+    state: [u32; 4],
+    /// Data waiting for a whole block
+    block: [u8; BLOCK_LEN],
+    block_len: usize,
+    /// How many bytes we've taken in
+    total: u64,
+}
+
+impl Md5 {
+    /// Start a new sum
+    pub const fn new() -> Md5 {
+        Md5 {
+            state: [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476],
+            block: [0; BLOCK_LEN],
+            block_len: 0,
+            total: 0,
+        }
+    }
+
+    /// Take in some more data
+    pub fn update(&mut self, mut data: &[u8]) {
+        self.total = self.total.wrapping_add(data.len() as u64);
+        while !data.is_empty() {
+            let n = (BLOCK_LEN - self.block_len).min(data.len());
+            self.block[self.block_len..self.block_len + n].copy_from_slice(&data[0..n]);
+            self.block_len += n;
+            data = &data[n..];
+            if self.block_len == BLOCK_LEN {
+                let block = self.block;
+                self.compress(&block);
+                self.block_len = 0;
+            }
+        }
+    }
+
+    /// The sum of everything so far
+    pub fn finish(mut self) -> [u8; 16] {
+        let bits = self.total.wrapping_mul(8);
+        // A one bit, then zeros up to the last eight bytes of a block,
+        // which hold the length
+        self.update(&[0x80]);
+        while self.block_len != BLOCK_LEN - 8 {
+            self.update(&[0]);
+        }
+        self.update(&bits.to_le_bytes());
+        let mut sum = [0u8; 16];
+        for (out, word) in sum.chunks_exact_mut(4).zip(self.state) {
+            out.copy_from_slice(&word.to_le_bytes());
+        }
+        sum
+    }
+
+    /// Mix a block into the state
+    fn compress(&mut self, block: &[u8; BLOCK_LEN]) {
+        let mut words = [0u32; 16];
+        for (word, bytes) in words.iter_mut().zip(block.chunks_exact(4)) {
+            *word = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        }
+        let [mut a, mut b, mut c, mut d] = self.state;
+        for step in 0..64 {
+            let (f, g) = match step / 16 {
+                0 => ((b & c) | (!b & d), step),
+                1 => ((d & b) | (!d & c), (5 * step + 1) % 16),
+                2 => (b ^ c ^ d, (3 * step + 5) % 16),
+                _ => (c ^ (b | !d), (7 * step) % 16),
+            };
+            let f = f
+                .wrapping_add(a)
+                .wrapping_add(K[step])
+                .wrapping_add(words[g]);
+            a = d;
+            d = c;
+            c = b;
+            b = b.wrapping_add(f.rotate_left(SHIFTS[(step / 16) * 4 + step % 4]));
+        }
+        for (state, value) in self.state.iter_mut().zip([a, b, c, d]) {
+            *state = state.wrapping_add(value);
+        }
+    }
+}
+
+impl Default for Md5 {
+    fn default() -> Self {
+        Md5::new()
+    }
+}
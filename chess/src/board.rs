@@ -0,0 +1,613 @@
+//! The rules of chess - board representation and move generation.
+//!
+//! We use a 10x12 "mailbox" board, where the 8x8 playing area is surrounded by
+//! off-board cells. That means moves which would fall off the edge of the
+//! board land on an off-board cell, rather than needing lots of range checks.
+
+/// Which side a piece belongs to
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Colour {
+    /// The side that moves first
+    White,
+    /// The side that moves second
+    Black,
+}
+
+impl Colour {
+    /// Get the opposing side
+    pub fn other(self) -> Colour {
+        match self {
+            Colour::White => Colour::Black,
+            Colour::Black => Colour::White,
+        }
+    }
+
+    /// Get an array index for this colour
+    fn index(self) -> usize {
+        match self {
+            Colour::White => 0,
+            Colour::Black => 1,
+        }
+    }
+
+    /// Which way do pawns of this colour move, as a mailbox offset?
+    fn forward(self) -> i16 {
+        match self {
+            Colour::White => 10,
+            Colour::Black => -10,
+        }
+    }
+}
+
+/// The different kinds of chess piece
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Kind {
+    /// Moves forward, captures diagonally
+    Pawn,
+    /// Moves in an L shape
+    Knight,
+    /// Slides diagonally
+    Bishop,
+    /// Slides orthogonally
+    Rook,
+    /// Slides in any direction
+    Queen,
+    /// Steps in any direction
+    King,
+}
+
+impl Kind {
+    /// Get the letter used for this piece in algebraic notation
+    pub fn letter(self) -> char {
+        match self {
+            Kind::Pawn => 'P',
+            Kind::Knight => 'N',
+            Kind::Bishop => 'B',
+            Kind::Rook => 'R',
+            Kind::Queen => 'Q',
+            Kind::King => 'K',
+        }
+    }
+
+    /// Convert a letter from algebraic notation into a piece kind
+    pub fn from_letter(letter: char) -> Option<Kind> {
+        match letter.to_ascii_uppercase() {
+            'P' => Some(Kind::Pawn),
+            'N' => Some(Kind::Knight),
+            'B' => Some(Kind::Bishop),
+            'R' => Some(Kind::Rook),
+            'Q' => Some(Kind::Queen),
+            'K' => Some(Kind::King),
+            _ => None,
+        }
+    }
+}
+
+/// A piece on the board
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Piece {
+    /// Whose piece it is
+    pub colour: Colour,
+    /// What sort of piece it is
+    pub kind: Kind,
+}
+
+/// One of the 64 squares on the board.
+///
+/// Holds an index into the 10x12 mailbox.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Square(u8);
+
+impl Square {
+    /// Make a square from a file (0 = a) and a rank (0 = 1).
+    pub const fn new(file: u8, rank: u8) -> Square {
+        Square(21 + file + rank * 10)
+    }
+
+    /// Which file (column) is this square on? 0 is the a-file.
+    pub fn file(self) -> u8 {
+        (self.0 % 10) - 1
+    }
+
+    /// Which rank (row) is this square on? 0 is White's back rank.
+    pub fn rank(self) -> u8 {
+        (self.0 / 10) - 2
+    }
+
+    /// Parse a square like `e4`
+    pub fn parse(text: &str) -> Option<Square> {
+        let mut chars = text.chars();
+        let file = chars.next()?;
+        let rank = chars.next()?;
+        if chars.next().is_some() {
+            return None;
+        }
+        if !('a'..='h').contains(&file) || !('1'..='8').contains(&rank) {
+            return None;
+        }
+        Some(Square::new(file as u8 - b'a', rank as u8 - b'1'))
+    }
+
+    /// Get the letter for this square's file
+    pub fn file_char(self) -> char {
+        char::from(b'a' + self.file())
+    }
+
+    /// Get the digit for this square's rank
+    pub fn rank_char(self) -> char {
+        char::from(b'1' + self.rank())
+    }
+
+    /// Move this square by a mailbox offset.
+    ///
+    /// The result might be off the board.
+    fn offset(self, delta: i16) -> usize {
+        (i16::from(self.0) + delta) as usize
+    }
+}
+
+impl core::fmt::Display for Square {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}{}", self.file_char(), self.rank_char())
+    }
+}
+
+/// A move from one square to another.
+///
+/// Castling is represented as a king moving two squares. En-passant is
+/// represented as a pawn moving diagonally onto an empty square.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Move {
+    /// Where the piece starts
+    pub from: Square,
+    /// Where the piece ends up
+    pub to: Square,
+    /// What a pawn turns into when it reaches the far side
+    pub promotion: Option<Kind>,
+}
+
+/// A fixed-size list of moves
+pub struct MoveList {
+    moves: [Move; Self::CAPACITY],
+    len: usize,
+}
+
+impl MoveList {
+    /// More than the most moves possible in any position
+    const CAPACITY: usize = 256;
+
+    /// Make an empty list
+    pub const fn new() -> MoveList {
+        MoveList {
+            moves: [Move {
+                from: Square(0),
+                to: Square(0),
+                promotion: None,
+            }; Self::CAPACITY],
+            len: 0,
+        }
+    }
+
+    /// Add a move to the list
+    fn push(&mut self, mv: Move) {
+        if self.len < Self::CAPACITY {
+            self.moves[self.len] = mv;
+            self.len += 1;
+        }
+    }
+
+    /// Get the moves as a slice
+    pub fn as_slice(&self) -> &[Move] {
+        &self.moves[0..self.len]
+    }
+
+    /// Get the moves as a mutable slice
+    pub fn as_mut_slice(&mut self) -> &mut [Move] {
+        &mut self.moves[0..self.len]
+    }
+
+    /// Is the list empty?
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl Default for MoveList {
+    fn default() -> Self {
+        MoveList::new()
+    }
+}
+
+/// What is in each cell of the mailbox
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Cell {
+    /// Not part of the 8x8 board
+    Offboard,
+    /// An empty square
+    Empty,
+    /// A square with a piece on it
+    Occupied(Piece),
+}
+
+/// Castling rights flags
+const WHITE_KINGSIDE: u8 = 1 << 0;
+const WHITE_QUEENSIDE: u8 = 1 << 1;
+const BLACK_KINGSIDE: u8 = 1 << 2;
+const BLACK_QUEENSIDE: u8 = 1 << 3;
+
+/// How a knight moves
+const KNIGHT_OFFSETS: [i16; 8] = [-21, -19, -12, -8, 8, 12, 19, 21];
+/// How a rook slides
+const ROOK_OFFSETS: [i16; 4] = [-10, -1, 1, 10];
+/// How a bishop slides
+const BISHOP_OFFSETS: [i16; 4] = [-11, -9, 9, 11];
+/// How a king steps (and a queen slides)
+const KING_OFFSETS: [i16; 8] = [-11, -10, -9, -1, 1, 9, 10, 11];
+
+/// The pieces on a back rank, from the a-file to the h-file
+const BACK_RANK: [Kind; 8] = [
+    Kind::Rook,
+    Kind::Knight,
+    Kind::Bishop,
+    Kind::Queen,
+    Kind::King,
+    Kind::Bishop,
+    Kind::Knight,
+    Kind::Rook,
+];
+
+/// The complete state of a game of chess
+#[derive(Debug, Clone)]
+pub struct Board {
+    cells: [Cell; 120],
+    side_to_move: Colour,
+    castling: u8,
+    en_passant: Option<Square>,
+    kings: [Square; 2],
+    fullmove: u16,
+}
+
+impl Board {
+    /// Set up a board in the standard starting position
+    pub fn new() -> Board {
+        let mut board = Board {
+            cells: [Cell::Offboard; 120],
+            side_to_move: Colour::White,
+            castling: WHITE_KINGSIDE | WHITE_QUEENSIDE | BLACK_KINGSIDE | BLACK_QUEENSIDE,
+            en_passant: None,
+            kings: [Square::new(4, 0), Square::new(4, 7)],
+            fullmove: 1,
+        };
+        for rank in 0..8 {
+            for file in 0..8 {
+                board.cells[Square::new(file, rank).0 as usize] = Cell::Empty;
+            }
+        }
+        for (file, kind) in BACK_RANK.iter().enumerate() {
+            let file = file as u8;
+            board.put(Square::new(file, 0), Colour::White, *kind);
+            board.put(Square::new(file, 1), Colour::White, Kind::Pawn);
+            board.put(Square::new(file, 6), Colour::Black, Kind::Pawn);
+            board.put(Square::new(file, 7), Colour::Black, *kind);
+        }
+        board
+    }
+
+    /// Place a piece on the board
+    fn put(&mut self, square: Square, colour: Colour, kind: Kind) {
+        self.cells[square.0 as usize] = Cell::Occupied(Piece { colour, kind });
+    }
+
+    /// What is on this square?
+    pub fn piece_at(&self, square: Square) -> Option<Piece> {
+        match self.cells[square.0 as usize] {
+            Cell::Occupied(piece) => Some(piece),
+            _ => None,
+        }
+    }
+
+    /// Whose turn is it?
+    pub fn side_to_move(&self) -> Colour {
+        self.side_to_move
+    }
+
+    /// Which move number are we on?
+    pub fn fullmove(&self) -> u16 {
+        self.fullmove
+    }
+
+    /// Is the side to move in check?
+    pub fn in_check(&self) -> bool {
+        let us = self.side_to_move;
+        self.is_attacked(self.kings[us.index()], us.other())
+    }
+
+    /// Can any piece of the given colour attack this square?
+    pub fn is_attacked(&self, square: Square, by: Colour) -> bool {
+        let is = |index: usize, kinds: &[Kind]| -> bool {
+            match self.cells[index] {
+                Cell::Occupied(p) => p.colour == by && kinds.contains(&p.kind),
+                _ => false,
+            }
+        };
+
+        // Pawns attack diagonally forwards, so look diagonally backwards
+        let back = -by.forward();
+        if is(square.offset(back - 1), &[Kind::Pawn]) || is(square.offset(back + 1), &[Kind::Pawn])
+        {
+            return true;
+        }
+
+        for offset in KNIGHT_OFFSETS {
+            if is(square.offset(offset), &[Kind::Knight]) {
+                return true;
+            }
+        }
+
+        for offset in KING_OFFSETS {
+            if is(square.offset(offset), &[Kind::King]) {
+                return true;
+            }
+        }
+
+        let sliders = [
+            (&ROOK_OFFSETS, [Kind::Rook, Kind::Queen]),
+            (&BISHOP_OFFSETS, [Kind::Bishop, Kind::Queen]),
+        ];
+        for (offsets, kinds) in sliders.iter() {
+            for offset in offsets.iter() {
+                let mut index = square.offset(*offset);
+                loop {
+                    match self.cells[index] {
+                        Cell::Empty => {
+                            index = (index as i16 + offset) as usize;
+                        }
+                        Cell::Occupied(_) => {
+                            if is(index, kinds) {
+                                return true;
+                            }
+                            break;
+                        }
+                        Cell::Offboard => {
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+
+        false
+    }
+
+    /// Generate all the legal moves in this position
+    pub fn legal_moves(&self) -> MoveList {
+        let mut pseudo = MoveList::new();
+        self.pseudo_legal_moves(&mut pseudo);
+        let mut legal = MoveList::new();
+        let us = self.side_to_move;
+        for mv in pseudo.as_slice() {
+            let mut next = self.clone();
+            next.make_move(*mv);
+            if !next.is_attacked(next.kings[us.index()], us.other()) {
+                legal.push(*mv);
+            }
+        }
+        legal
+    }
+
+    /// Generate all the moves in this position, including those which leave
+    /// the king in check.
+    fn pseudo_legal_moves(&self, list: &mut MoveList) {
+        let us = self.side_to_move;
+        for rank in 0..8 {
+            for file in 0..8 {
+                let from = Square::new(file, rank);
+                let Some(piece) = self.piece_at(from) else {
+                    continue;
+                };
+                if piece.colour != us {
+                    continue;
+                }
+                match piece.kind {
+                    Kind::Pawn => self.pawn_moves(from, list),
+                    Kind::Knight => self.step_moves(from, &KNIGHT_OFFSETS, list),
+                    Kind::Bishop => self.slide_moves(from, &BISHOP_OFFSETS, list),
+                    Kind::Rook => self.slide_moves(from, &ROOK_OFFSETS, list),
+                    Kind::Queen => self.slide_moves(from, &KING_OFFSETS, list),
+                    Kind::King => {
+                        self.step_moves(from, &KING_OFFSETS, list);
+                        self.castling_moves(from, list);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Can a piece of the side to move land on this cell?
+    ///
+    /// Returns `Some(true)` for a capture, `Some(false)` for an empty square,
+    /// and `None` if the square is off-board or has one of our own pieces.
+    fn landing(&self, index: usize) -> Option<bool> {
+        match self.cells[index] {
+            Cell::Empty => Some(false),
+            Cell::Occupied(p) if p.colour != self.side_to_move => Some(true),
+            _ => None,
+        }
+    }
+
+    /// Generate moves for knights and kings
+    fn step_moves(&self, from: Square, offsets: &[i16], list: &mut MoveList) {
+        for offset in offsets {
+            let index = from.offset(*offset);
+            if self.landing(index).is_some() {
+                list.push(Move {
+                    from,
+                    to: Square(index as u8),
+                    promotion: None,
+                });
+            }
+        }
+    }
+
+    /// Generate moves for bishops, rooks and queens
+    fn slide_moves(&self, from: Square, offsets: &[i16], list: &mut MoveList) {
+        for offset in offsets {
+            let mut index = from.offset(*offset);
+            while let Some(capture) = self.landing(index) {
+                list.push(Move {
+                    from,
+                    to: Square(index as u8),
+                    promotion: None,
+                });
+                if capture {
+                    break;
+                }
+                index = (index as i16 + offset) as usize;
+            }
+        }
+    }
+
+    /// Generate moves for a pawn, including promotions and en-passant
+    fn pawn_moves(&self, from: Square, list: &mut MoveList) {
+        let us = self.side_to_move;
+        let forward = us.forward();
+        let (start_rank, last_rank) = match us {
+            Colour::White => (1, 7),
+            Colour::Black => (6, 0),
+        };
+        let mut push = |to: Square| {
+            if to.rank() == last_rank {
+                for kind in [Kind::Queen, Kind::Rook, Kind::Bishop, Kind::Knight] {
+                    list.push(Move {
+                        from,
+                        to,
+                        promotion: Some(kind),
+                    });
+                }
+            } else {
+                list.push(Move {
+                    from,
+                    to,
+                    promotion: None,
+                });
+            }
+        };
+
+        let one_step = from.offset(forward);
+        if self.cells[one_step] == Cell::Empty {
+            push(Square(one_step as u8));
+            let two_step = from.offset(forward * 2);
+            if from.rank() == start_rank && self.cells[two_step] == Cell::Empty {
+                push(Square(two_step as u8));
+            }
+        }
+
+        for side in [-1, 1] {
+            let index = from.offset(forward + side);
+            let target = Square(index as u8);
+            if self.landing(index) == Some(true) || Some(target) == self.en_passant {
+                push(target);
+            }
+        }
+    }
+
+    /// Generate castling moves for the king on the given square
+    fn castling_moves(&self, from: Square, list: &mut MoveList) {
+        let us = self.side_to_move;
+        let (kingside, queenside, rank) = match us {
+            Colour::White => (WHITE_KINGSIDE, WHITE_QUEENSIDE, 0),
+            Colour::Black => (BLACK_KINGSIDE, BLACK_QUEENSIDE, 7),
+        };
+        if from != Square::new(4, rank) || self.is_attacked(from, us.other()) {
+            return;
+        }
+        let empty = |file: u8| self.cells[Square::new(file, rank).0 as usize] == Cell::Empty;
+        let safe = |file: u8| !self.is_attacked(Square::new(file, rank), us.other());
+        if self.castling & kingside != 0 && empty(5) && empty(6) && safe(5) && safe(6) {
+            list.push(Move {
+                from,
+                to: Square::new(6, rank),
+                promotion: None,
+            });
+        }
+        if self.castling & queenside != 0 && empty(1) && empty(2) && empty(3) && safe(2) && safe(3)
+        {
+            list.push(Move {
+                from,
+                to: Square::new(2, rank),
+                promotion: None,
+            });
+        }
+    }
+
+    /// Is this move a capture (including en-passant)?
+    pub fn is_capture(&self, mv: Move) -> bool {
+        self.piece_at(mv.to).is_some()
+            || (Some(mv.to) == self.en_passant
+                && self.piece_at(mv.from).map(|p| p.kind) == Some(Kind::Pawn))
+    }
+
+    /// Make a move on the board.
+    ///
+    /// The move is not checked for legality - get it from `legal_moves`.
+    pub fn make_move(&mut self, mv: Move) {
+        let Some(piece) = self.piece_at(mv.from) else {
+            return;
+        };
+        let us = piece.colour;
+
+        if piece.kind == Kind::Pawn && Some(mv.to) == self.en_passant {
+            // Remove the pawn we just passed
+            let victim = mv.to.offset(-us.forward());
+            self.cells[victim] = Cell::Empty;
+        }
+
+        self.cells[mv.from.0 as usize] = Cell::Empty;
+        self.put(mv.to, us, mv.promotion.unwrap_or(piece.kind));
+
+        self.en_passant = None;
+        match piece.kind {
+            Kind::King => {
+                self.kings[us.index()] = mv.to;
+                let rank = mv.to.rank();
+                if mv.from.file() == 4 && mv.to.file() == 6 {
+                    // Kingside castle - bring the rook over
+                    self.cells[Square::new(7, rank).0 as usize] = Cell::Empty;
+                    self.put(Square::new(5, rank), us, Kind::Rook);
+                } else if mv.from.file() == 4 && mv.to.file() == 2 {
+                    // Queenside castle - bring the rook over
+                    self.cells[Square::new(0, rank).0 as usize] = Cell::Empty;
+                    self.put(Square::new(3, rank), us, Kind::Rook);
+                }
+            }
+            Kind::Pawn if mv.from.rank().abs_diff(mv.to.rank()) == 2 => {
+                self.en_passant = Some(Square(mv.from.offset(us.forward()) as u8));
+            }
+            _ => {}
+        }
+
+        // Moving from, or capturing on, a corner loses the right to castle
+        for square in [mv.from, mv.to] {
+            self.castling &= !match (square.file(), square.rank()) {
+                (0, 0) => WHITE_QUEENSIDE,
+                (7, 0) => WHITE_KINGSIDE,
+                (4, 0) => WHITE_KINGSIDE | WHITE_QUEENSIDE,
+                (0, 7) => BLACK_QUEENSIDE,
+                (7, 7) => BLACK_KINGSIDE,
+                (4, 7) => BLACK_KINGSIDE | BLACK_QUEENSIDE,
+                _ => 0,
+            };
+        }
+
+        if us == Colour::Black {
+            self.fullmove += 1;
+        }
+        self.side_to_move = us.other();
+    }
+}
+
+impl Default for Board {
+    fn default() -> Self {
+        Board::new()
+    }
+}
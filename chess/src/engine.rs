@@ -0,0 +1,172 @@
+//! A small chess engine.
+//!
+//! Does a fixed-depth negamax search with alpha-beta pruning, followed by a
+//! short capture-only search so it doesn't blunder pieces at the horizon. The
+//! evaluation is material plus a few positional bonuses.
+
+use crate::board::{Board, Colour, Kind, Move, Square};
+
+/// A score bigger than any material balance
+const MATE: i32 = 100_000;
+
+/// How deep the capture-only search can go
+const MAX_QUIESCENCE: u8 = 4;
+
+/// How much each kind of piece is worth, in centipawns
+fn value(kind: Kind) -> i32 {
+    match kind {
+        Kind::Pawn => 100,
+        Kind::Knight => 320,
+        Kind::Bishop => 330,
+        Kind::Rook => 500,
+        Kind::Queen => 900,
+        Kind::King => 0,
+    }
+}
+
+/// A bonus for pieces that are close to the middle of the board
+fn centralisation(square: Square) -> i32 {
+    let file = i32::from(square.file());
+    let rank = i32::from(square.rank());
+    let file_distance = (2 * file - 7).abs();
+    let rank_distance = (2 * rank - 7).abs();
+    14 - (file_distance + rank_distance)
+}
+
+/// Score the position from the point of view of the side to move
+fn evaluate(board: &Board) -> i32 {
+    let mut score = 0;
+    for rank in 0..8 {
+        for file in 0..8 {
+            let square = Square::new(file, rank);
+            let Some(piece) = board.piece_at(square) else {
+                continue;
+            };
+            let mut piece_score = value(piece.kind);
+            piece_score += match piece.kind {
+                Kind::Pawn => {
+                    // Reward advancing pawns
+                    let advance = match piece.colour {
+                        Colour::White => i32::from(rank) - 1,
+                        Colour::Black => 6 - i32::from(rank),
+                    };
+                    advance * 5
+                }
+                Kind::Knight | Kind::Bishop => centralisation(square) * 3,
+                Kind::Queen => centralisation(square),
+                _ => 0,
+            };
+            if piece.colour == board.side_to_move() {
+                score += piece_score;
+            } else {
+                score -= piece_score;
+            }
+        }
+    }
+    score
+}
+
+/// How promising does this move look? Used to search good moves first.
+fn move_order_score(board: &Board, mv: Move) -> i32 {
+    let mut score = 0;
+    if let Some(victim) = board.piece_at(mv.to) {
+        // Most valuable victim, least valuable attacker
+        let attacker = board.piece_at(mv.from).map(|p| value(p.kind)).unwrap_or(0);
+        score += 10 * value(victim.kind) - attacker / 10;
+    }
+    if let Some(kind) = mv.promotion {
+        score += value(kind);
+    }
+    score
+}
+
+/// Sort moves so the most promising are first
+fn order_moves(board: &Board, moves: &mut [Move]) {
+    // Insertion sort - the lists are short and we have no allocator
+    for i in 1..moves.len() {
+        let mut j = i;
+        while j > 0 && move_order_score(board, moves[j]) > move_order_score(board, moves[j - 1]) {
+            moves.swap(j, j - 1);
+            j -= 1;
+        }
+    }
+}
+
+/// Search for the best move for the side to move.
+///
+/// Returns `None` if there are no legal moves.
+pub fn best_move(board: &Board, depth: u8) -> Option<Move> {
+    let mut moves = board.legal_moves();
+    order_moves(board, moves.as_mut_slice());
+    let mut best = None;
+    let mut alpha = -MATE - 1;
+    let beta = MATE + 1;
+    for mv in moves.as_slice() {
+        let mut next = board.clone();
+        next.make_move(*mv);
+        let score = -negamax(&next, depth.saturating_sub(1), -beta, -alpha, 1);
+        if best.is_none() || score > alpha {
+            alpha = score;
+            best = Some(*mv);
+        }
+    }
+    best
+}
+
+/// Score a position by searching `depth` moves ahead.
+fn negamax(board: &Board, depth: u8, mut alpha: i32, beta: i32, ply: i32) -> i32 {
+    if depth == 0 {
+        return quiescence(board, MAX_QUIESCENCE, alpha, beta);
+    }
+    let mut moves = board.legal_moves();
+    if moves.is_empty() {
+        return if board.in_check() {
+            // Prefer quicker mates
+            -MATE + ply
+        } else {
+            // Stalemate
+            0
+        };
+    }
+    order_moves(board, moves.as_mut_slice());
+    for mv in moves.as_slice() {
+        let mut next = board.clone();
+        next.make_move(*mv);
+        let score = -negamax(&next, depth - 1, -beta, -alpha, ply + 1);
+        if score >= beta {
+            return beta;
+        }
+        if score > alpha {
+            alpha = score;
+        }
+    }
+    alpha
+}
+
+/// Keep searching captures until the position is quiet.
+fn quiescence(board: &Board, depth: u8, mut alpha: i32, beta: i32) -> i32 {
+    let stand_pat = evaluate(board);
+    if depth == 0 || stand_pat >= beta {
+        return stand_pat;
+    }
+    if stand_pat > alpha {
+        alpha = stand_pat;
+    }
+    let mut moves = board.legal_moves();
+    order_moves(board, moves.as_mut_slice());
+    for mv in moves.as_slice() {
+        if !board.is_capture(*mv) {
+            continue;
+        }
+        let mut next = board.clone();
+        next.make_move(*mv);
+        let score = -quiescence(&next, depth - 1, -beta, -alpha);
+        if score >= beta {
+            return beta;
+        }
+        if score > alpha {
+            alpha = score;
+        }
+    }
+    alpha
+}
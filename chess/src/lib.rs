@@ -0,0 +1,400 @@
+//! Game logic for Chess
+
+#![no_std]
+#![deny(missing_docs)]
+#![deny(unsafe_code)]
+
+use core::fmt::Write;
+
+use neotron_input::{KeyEvent, Keyboard};
+use neotron_sdk::console;
+
+pub mod board;
+pub mod engine;
+pub mod notation;
+
+use board::{Board, Colour, Kind, Square};
+
+/// Who is playing the game
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Mode {
+    /// The human plays the given colour, the computer plays the other
+    VsComputer(Colour),
+    /// Two humans take turns at the keyboard
+    Hotseat,
+}
+
+/// Represents the Chess application
+///
+/// An application can play multiple games.
+pub struct App {
+    width: u8,
+    height: u8,
+    stdout: neotron_sdk::File,
+    keyboard: Keyboard,
+    /// How many moves ahead the computer looks
+    depth: u8,
+    /// Draw pieces as letters, for consoles without the chess glyphs
+    letters: bool,
+    /// The moves played so far, in SAN
+    history: [notation::San; Self::MAX_HISTORY],
+    /// How many entries of `history` are in use
+    history_len: usize,
+}
+
+impl App {
+    /// How many half-moves we remember
+    const MAX_HISTORY: usize = 400;
+    /// The shallowest search we allow
+    const MIN_DEPTH: u8 = 1;
+    /// The deepest search we allow
+    const MAX_DEPTH: u8 = 5;
+    /// Where the top-left of the board goes
+    const BOARD_ORIGIN: console::Position = console::Position { row: 2, col: 3 };
+    /// Which column the side panel starts in
+    const PANEL_COL: u8 = 32;
+    /// The longest move you can type
+    const MAX_INPUT: usize = 16;
+
+    /// Make a new chess application.
+    ///
+    /// You can give the screen size in characters.
+    pub const fn new(width: u8, height: u8) -> App {
+        App {
+            width,
+            height,
+            stdout: neotron_sdk::stdout(),
+            keyboard: Keyboard::new(),
+            depth: 3,
+            letters: false,
+            history: [notation::San::new(); Self::MAX_HISTORY],
+            history_len: 0,
+        }
+    }
+
+    /// Play multiple games of chess.
+    ///
+    /// Loops showing the title screen and playing games.
+    pub fn play(&mut self) {
+        console::cursor_off(&mut self.stdout);
+        loop {
+            self.title_screen();
+            let mode = match self.keyboard.wait() {
+                KeyEvent::Char('1') => Mode::VsComputer(Colour::White),
+                KeyEvent::Char('2') => Mode::VsComputer(Colour::Black),
+                KeyEvent::Char('3') => Mode::Hotseat,
+                KeyEvent::Char('d' | 'D') => {
+                    self.depth += 1;
+                    if self.depth > Self::MAX_DEPTH {
+                        self.depth = Self::MIN_DEPTH;
+                    }
+                    continue;
+                }
+                KeyEvent::Char('l' | 'L') => {
+                    self.letters = !self.letters;
+                    continue;
+                }
+                KeyEvent::Char('q' | 'Q') => {
+                    break;
+                }
+                _ => {
+                    continue;
+                }
+            };
+            self.play_game(mode);
+        }
+
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        console::clear_screen(&mut self.stdout);
+        console::move_cursor(&mut self.stdout, console::Position::origin());
+        console::cursor_on(&mut self.stdout);
+    }
+
+    /// Show the title screen and the game options
+    fn title_screen(&mut self) {
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        console::clear_screen(&mut self.stdout);
+        let row = self.height / 2 - 4;
+        self.centre(row, "Neotron Chess");
+        self.centre(row + 2, "1 - Play White against the computer");
+        self.centre(row + 3, "2 - Play Black against the computer");
+        self.centre(row + 4, "3 - Two players, one keyboard      ");
+        console::move_cursor(
+            &mut self.stdout,
+            console::Position {
+                row: row + 6,
+                col: (self.width - 36) / 2,
+            },
+        );
+        let _ = write!(
+            self.stdout,
+            "D - Computer depth: {}  L - Pieces: {}",
+            self.depth,
+            if self.letters { "KQ" } else { "♚♛" }
+        );
+        self.centre(row + 8, "Q - Quit");
+    }
+
+    /// Play one game in the given mode
+    fn play_game(&mut self, mode: Mode) {
+        let mut board = Board::new();
+        self.history_len = 0;
+        let mut message: &'static str = "";
+
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        console::clear_screen(&mut self.stdout);
+
+        loop {
+            self.draw_board(&board);
+            self.draw_panel(&board, mode);
+
+            let legal = board.legal_moves();
+            if legal.is_empty() {
+                let result = if !board.in_check() {
+                    "Stalemate - it's a draw!"
+                } else if board.side_to_move() == Colour::White {
+                    "Checkmate - Black wins!"
+                } else {
+                    "Checkmate - White wins!"
+                };
+                self.draw_prompt(result, "Press any key");
+                self.keyboard.wait();
+                return;
+            }
+
+            let computer_to_move = match mode {
+                Mode::VsComputer(human) => board.side_to_move() != human,
+                Mode::Hotseat => false,
+            };
+
+            let mv = if computer_to_move {
+                self.draw_prompt("Thinking...", "");
+                match engine::best_move(&board, self.depth) {
+                    Some(mv) => mv,
+                    None => return,
+                }
+            } else {
+                let prompt = if board.side_to_move() == Colour::White {
+                    "White to move:"
+                } else {
+                    "Black to move:"
+                };
+                self.draw_prompt(prompt, message);
+                let mut input = [0u8; Self::MAX_INPUT];
+                let Some(text) = self.read_line(&mut input) else {
+                    // Escape pressed
+                    return;
+                };
+                match text.trim() {
+                    "quit" | "resign" => return,
+                    "" => continue,
+                    _ => {}
+                }
+                match notation::parse(&board, text) {
+                    Ok(mv) => mv,
+                    Err(e) => {
+                        message = e.describe();
+                        continue;
+                    }
+                }
+            };
+
+            message = "";
+            let san = notation::format(&board, mv);
+            if self.history_len < Self::MAX_HISTORY {
+                self.history[self.history_len] = san;
+                self.history_len += 1;
+            }
+            board.make_move(mv);
+        }
+    }
+
+    /// Draw the board with rank and file labels
+    fn draw_board(&mut self, board: &Board) {
+        let origin = Self::BOARD_ORIGIN;
+        for rank in 0..8u8 {
+            let row = origin.row + (7 - rank);
+            console::move_cursor(
+                &mut self.stdout,
+                console::Position {
+                    row,
+                    col: origin.col - 2,
+                },
+            );
+            console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+            let _ = write!(self.stdout, "{} ", rank + 1);
+            for file in 0..8u8 {
+                let square = Square::new(file, rank);
+                let background = if (file + rank) % 2 == 0 {
+                    console::SgrParam::BgGreen
+                } else {
+                    console::SgrParam::BgYellow
+                };
+                match board.piece_at(square) {
+                    Some(piece) => {
+                        let foreground = match piece.colour {
+                            Colour::White => console::SgrParam::FgWhite,
+                            Colour::Black => console::SgrParam::FgBlack,
+                        };
+                        console::set_sgr(
+                            &mut self.stdout,
+                            [console::SgrParam::Bold, foreground, background],
+                        );
+                        let glyph = if self.letters {
+                            piece.kind.letter()
+                        } else {
+                            Self::glyph(piece.kind)
+                        };
+                        let _ = write!(self.stdout, " {} ", glyph);
+                        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+                    }
+                    None => {
+                        console::set_sgr(&mut self.stdout, [background]);
+                        let _ = self.stdout.write_str("   ");
+                    }
+                }
+            }
+        }
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        console::move_cursor(
+            &mut self.stdout,
+            console::Position {
+                row: origin.row + 8,
+                col: origin.col,
+            },
+        );
+        for file in b'a'..=b'h' {
+            let _ = write!(self.stdout, " {} ", char::from(file));
+        }
+    }
+
+    /// Get the Unicode chess symbol for a piece.
+    ///
+    /// We use the solid symbols for both sides and colour them in.
+    fn glyph(kind: Kind) -> char {
+        match kind {
+            Kind::Pawn => '♟',
+            Kind::Knight => '♞',
+            Kind::Bishop => '♝',
+            Kind::Rook => '♜',
+            Kind::Queen => '♛',
+            Kind::King => '♚',
+        }
+    }
+
+    /// Draw the status and the move history
+    fn draw_panel(&mut self, board: &Board, mode: Mode) {
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        let col = Self::PANEL_COL;
+        console::move_cursor(&mut self.stdout, console::Position { row: 1, col });
+        let _ = write!(
+            self.stdout,
+            "Neotron Chess - {}",
+            match mode {
+                Mode::VsComputer(Colour::White) => "you are White",
+                Mode::VsComputer(Colour::Black) => "you are Black",
+                Mode::Hotseat => "two players  ",
+            }
+        );
+        console::move_cursor(&mut self.stdout, console::Position { row: 2, col });
+        if board.in_check() {
+            console::set_sgr(
+                &mut self.stdout,
+                [console::SgrParam::Bold, console::SgrParam::FgRed],
+            );
+            let _ = self.stdout.write_str("Check!");
+            console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        } else {
+            let _ = self.stdout.write_str("      ");
+        }
+
+        // Show as many of the most recent moves as will fit
+        let first_row = 4;
+        let rows = usize::from(self.height.saturating_sub(first_row + 5));
+        let full_moves = self.history_len.div_ceil(2);
+        let first_move = full_moves.saturating_sub(rows);
+        for line in 0..rows {
+            let row = first_row + line as u8;
+            console::move_cursor(&mut self.stdout, console::Position { row, col });
+            let move_idx = first_move + line;
+            if move_idx < full_moves {
+                let white = self.history[move_idx * 2].as_str();
+                let black = if move_idx * 2 + 1 < self.history_len {
+                    self.history[move_idx * 2 + 1].as_str()
+                } else {
+                    ""
+                };
+                let _ = write!(
+                    self.stdout,
+                    "{:>3}. {:<8} {:<8}",
+                    move_idx + 1,
+                    white,
+                    black
+                );
+            } else {
+                let _ = write!(self.stdout, "{:23}", "");
+            }
+        }
+    }
+
+    /// Show a prompt and a message at the bottom of the screen
+    fn draw_prompt(&mut self, prompt: &str, message: &str) {
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        let row = self.height - 3;
+        console::move_cursor(
+            &mut self.stdout,
+            console::Position {
+                row: row + 1,
+                col: 1,
+            },
+        );
+        console::set_sgr(&mut self.stdout, [console::SgrParam::FgRed]);
+        let _ = write!(self.stdout, "{:<60}", message);
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        console::move_cursor(&mut self.stdout, console::Position { row, col: 1 });
+        let _ = write!(self.stdout, "{} {:20}", prompt, "");
+        console::move_cursor(
+            &mut self.stdout,
+            console::Position {
+                row,
+                col: 2 + prompt.chars().count() as u8,
+            },
+        );
+    }
+
+    /// Read a line of text, with echo and backspace.
+    ///
+    /// Returns `None` if Escape is pressed.
+    fn read_line<'a>(&mut self, buffer: &'a mut [u8]) -> Option<&'a str> {
+        let mut len = 0;
+        console::cursor_on(&mut self.stdout);
+        loop {
+            match self.keyboard.wait() {
+                KeyEvent::Enter => break,
+                KeyEvent::Escape => {
+                    console::cursor_off(&mut self.stdout);
+                    return None;
+                }
+                KeyEvent::Backspace if len > 0 => {
+                    len -= 1;
+                    let _ = self.stdout.write_str("\u{0008} \u{0008}");
+                }
+                KeyEvent::Char(key @ ' '..='~') if len < buffer.len() => {
+                    buffer[len] = key as u8;
+                    len += 1;
+                    let _ = self.stdout.write_char(key);
+                }
+                _ => {}
+            }
+        }
+        console::cursor_off(&mut self.stdout);
+        core::str::from_utf8(&buffer[0..len]).ok()
+    }
+
+    /// Write a message centred on the given row
+    fn centre(&mut self, row: u8, message: &str) {
+        let col = (self.width - message.chars().count() as u8) / 2;
+        console::move_cursor(&mut self.stdout, console::Position { row, col });
+        let _ = self.stdout.write_str(message);
+    }
+}
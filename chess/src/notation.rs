@@ -0,0 +1,263 @@
+//! Reading and writing moves in algebraic notation.
+//!
+//! We accept Standard Algebraic Notation (`Nf3`, `exd5`, `O-O`, `e8=Q`) and
+//! long coordinate notation (`g1f3`, `e7e8q`). We always write SAN.
+
+use core::fmt::Write;
+
+use crate::board::{Board, Kind, Move, Square};
+
+/// Reasons we could not understand a move
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// That isn't a move
+    BadSyntax,
+    /// That move isn't allowed here
+    Illegal,
+    /// More than one piece could make that move
+    Ambiguous,
+}
+
+impl Error {
+    /// Get a human-readable description of this error
+    pub fn describe(self) -> &'static str {
+        match self {
+            Error::BadSyntax => "I don't understand that move",
+            Error::Illegal => "That move is not legal",
+            Error::Ambiguous => "Ambiguous - which piece did you mean?",
+        }
+    }
+}
+
+/// A move written in SAN, stored without an allocator
+#[derive(Debug, Copy, Clone)]
+pub struct San {
+    buffer: [u8; Self::CAPACITY],
+    len: usize,
+}
+
+impl San {
+    /// Longest possible SAN move is something like `Qa1xb2+` or `exd8=Q#`
+    const CAPACITY: usize = 8;
+
+    /// Make an empty string
+    pub const fn new() -> San {
+        San {
+            buffer: [0; Self::CAPACITY],
+            len: 0,
+        }
+    }
+
+    /// Get the move text
+    pub fn as_str(&self) -> &str {
+        core::str::from_utf8(&self.buffer[0..self.len]).unwrap_or("?")
+    }
+}
+
+impl Default for San {
+    fn default() -> Self {
+        San::new()
+    }
+}
+
+impl core::fmt::Write for San {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        let bytes = s.as_bytes();
+        let space = &mut self.buffer[self.len..];
+        if bytes.len() > space.len() {
+            return Err(core::fmt::Error);
+        }
+        space[0..bytes.len()].copy_from_slice(bytes);
+        self.len += bytes.len();
+        Ok(())
+    }
+}
+
+/// Convert some text into a legal move in this position
+pub fn parse(board: &Board, text: &str) -> Result<Move, Error> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Err(Error::BadSyntax);
+    }
+
+    // Strip the decorations, which we don't need
+    let mut buffer = [0u8; 12];
+    let mut len = 0;
+    for ch in text.bytes() {
+        if matches!(ch, b'x' | b'+' | b'#' | b'=' | b'!' | b'?' | b'-') {
+            continue;
+        }
+        if len == buffer.len() {
+            return Err(Error::BadSyntax);
+        }
+        buffer[len] = ch;
+        len += 1;
+    }
+    let text = core::str::from_utf8(&buffer[0..len]).map_err(|_| Error::BadSyntax)?;
+
+    let legal = board.legal_moves();
+
+    // Castling (`-` was removed above)
+    if text == "OO" || text == "00" || text == "OOO" || text == "000" {
+        let to_file = if text.len() == 2 { 6 } else { 2 };
+        return legal
+            .as_slice()
+            .iter()
+            .find(|mv| {
+                board.piece_at(mv.from).map(|p| p.kind) == Some(Kind::King)
+                    && mv.from.file() == 4
+                    && mv.to.file() == to_file
+            })
+            .cloned()
+            .ok_or(Error::Illegal);
+    }
+
+    // Long coordinate notation
+    if len == 4 || len == 5 {
+        if let (Some(from), Some(to)) = (Square::parse(&text[0..2]), Square::parse(&text[2..4])) {
+            let promotion = match text.chars().nth(4) {
+                Some(ch) => Some(Kind::from_letter(ch).ok_or(Error::BadSyntax)?),
+                None => None,
+            };
+            return find_unique(board, legal.as_slice(), |mv, kind| {
+                mv.from == from
+                    && mv.to == to
+                    && mv.promotion == promotion.or(promotion_default(kind, mv))
+            });
+        }
+    }
+
+    // Standard Algebraic Notation
+    let mut chars = text;
+    let kind = match chars.chars().next() {
+        Some(ch @ ('N' | 'B' | 'R' | 'Q' | 'K')) => {
+            chars = &chars[1..];
+            Kind::from_letter(ch).ok_or(Error::BadSyntax)?
+        }
+        _ => Kind::Pawn,
+    };
+    let mut promotion = None;
+    if let Some(last) = chars.chars().last() {
+        if kind == Kind::Pawn && matches!(last, 'N' | 'B' | 'R' | 'Q' | 'n' | 'r' | 'q') {
+            promotion = Kind::from_letter(last);
+            chars = &chars[0..chars.len() - 1];
+        }
+    }
+    if chars.len() < 2 {
+        return Err(Error::BadSyntax);
+    }
+    let to = Square::parse(&chars[chars.len() - 2..]).ok_or(Error::BadSyntax)?;
+    let mut from_file = None;
+    let mut from_rank = None;
+    for ch in chars[0..chars.len() - 2].chars() {
+        match ch {
+            'a'..='h' => from_file = Some(ch as u8 - b'a'),
+            '1'..='8' => from_rank = Some(ch as u8 - b'1'),
+            _ => return Err(Error::BadSyntax),
+        }
+    }
+    find_unique(board, legal.as_slice(), |mv, moving| {
+        moving == kind
+            && mv.to == to
+            && from_file.map(|f| f == mv.from.file()).unwrap_or(true)
+            && from_rank.map(|r| r == mv.from.rank()).unwrap_or(true)
+            && mv.promotion == promotion.or(promotion_default(moving, mv))
+    })
+}
+
+/// If a pawn promotion is not specified, we assume a queen
+fn promotion_default(kind: Kind, mv: &Move) -> Option<Kind> {
+    if kind == Kind::Pawn && (mv.to.rank() == 0 || mv.to.rank() == 7) {
+        Some(Kind::Queen)
+    } else {
+        None
+    }
+}
+
+/// Find the one legal move that matches the predicate.
+///
+/// The predicate is given the move, and the kind of piece being moved.
+fn find_unique<F>(board: &Board, legal: &[Move], predicate: F) -> Result<Move, Error>
+where
+    F: Fn(&Move, Kind) -> bool,
+{
+    let mut found = None;
+    for mv in legal {
+        let Some(piece) = board.piece_at(mv.from) else {
+            continue;
+        };
+        if predicate(mv, piece.kind) {
+            if found.is_some() {
+                return Err(Error::Ambiguous);
+            }
+            found = Some(*mv);
+        }
+    }
+    found.ok_or(Error::Illegal)
+}
+
+/// Write a legal move in Standard Algebraic Notation.
+///
+/// The board is the position before the move is made.
+pub fn format(board: &Board, mv: Move) -> San {
+    let mut san = San::new();
+    let Some(piece) = board.piece_at(mv.from) else {
+        return san;
+    };
+    let capture = board.is_capture(mv);
+
+    if piece.kind == Kind::King && mv.from.file() == 4 && mv.to.file() == 6 {
+        let _ = san.write_str("O-O");
+    } else if piece.kind == Kind::King && mv.from.file() == 4 && mv.to.file() == 2 {
+        let _ = san.write_str("O-O-O");
+    } else if piece.kind == Kind::Pawn {
+        if capture {
+            let _ = write!(san, "{}x", mv.from.file_char());
+        }
+        let _ = write!(san, "{}", mv.to);
+        if let Some(kind) = mv.promotion {
+            let _ = write!(san, "={}", kind.letter());
+        }
+    } else {
+        let _ = san.write_char(piece.kind.letter());
+        // Do we need to say which piece is moving?
+        let mut same_file = false;
+        let mut same_rank = false;
+        let mut ambiguous = false;
+        for other in board.legal_moves().as_slice() {
+            if other.to == mv.to
+                && other.from != mv.from
+                && board.piece_at(other.from) == Some(piece)
+            {
+                ambiguous = true;
+                same_file |= other.from.file() == mv.from.file();
+                same_rank |= other.from.rank() == mv.from.rank();
+            }
+        }
+        if ambiguous {
+            if !same_file {
+                let _ = san.write_char(mv.from.file_char());
+            } else if !same_rank {
+                let _ = san.write_char(mv.from.rank_char());
+            } else {
+                let _ = write!(san, "{}", mv.from);
+            }
+        }
+        if capture {
+            let _ = san.write_char('x');
+        }
+        let _ = write!(san, "{}", mv.to);
+    }
+
+    let mut next = board.clone();
+    next.make_move(mv);
+    if next.in_check() {
+        if next.legal_moves().is_empty() {
+            let _ = san.write_char('#');
+        } else {
+            let _ = san.write_char('+');
+        }
+    }
+
+    san
+}
@@ -0,0 +1,129 @@
+//! Alarm beeps, played through the audio device.
+//!
+//! While the alarm is ringing, the clock calls [`Beeper::tick`] once per
+//! loop and we write out that much audio. If there is no audio device, the
+//! alarm is silent - but still flashes on screen.
+
+/// The sample rate we ask the audio device for
+const SAMPLE_RATE: u32 = 44100;
+
+/// How loud the beep is
+const AMPLITUDE: i16 = 8000;
+
+/// The pitch of the beep, in Hz
+const PITCH: u32 = 880;
+
+/// The beep pattern, in milliseconds: beep, gap, beep, long gap
+const PATTERN_MS: [u32; 4] = [150, 100, 150, 600];
+
+/// Makes beeping noises
+pub struct Beeper {
+    dsp: Option<neotron_sdk::File>,
+    ringing: bool,
+    /// How many samples into the pattern we are
+    position: u32,
+}
+
+impl Beeper {
+    /// Make a silent beeper. Call [`Beeper::open`] to start making noise.
+    pub const fn new() -> Beeper {
+        Beeper {
+            dsp: None,
+            ringing: false,
+            position: 0,
+        }
+    }
+
+    /// Try and open the audio device.
+    ///
+    /// If it isn't there, we just stay silent.
+    pub fn open(&mut self) {
+        let Ok(path) = neotron_sdk::path::Path::new("AUDIO:") else {
+            return;
+        };
+        let Ok(dsp) = neotron_sdk::File::open(path, neotron_sdk::Flags::empty()) else {
+            return;
+        };
+        // Set 16-bit stereo, 44.1 kHz
+        if dsp.ioctl(1, 3 << 60 | u64::from(SAMPLE_RATE)).is_err() {
+            return;
+        }
+        self.dsp = Some(dsp);
+    }
+
+    /// Close the audio device
+    pub fn close(&mut self) {
+        self.dsp = None;
+        self.ringing = false;
+    }
+
+    /// Start beeping
+    pub fn start(&mut self) {
+        self.ringing = true;
+        self.position = 0;
+    }
+
+    /// Stop beeping
+    pub fn stop(&mut self) {
+        self.ringing = false;
+    }
+
+    /// Send the given number of milliseconds of audio to the audio device
+    pub fn tick(&mut self, ms: u32) {
+        let Some(dsp) = self.dsp.as_ref() else {
+            return;
+        };
+        if !self.ringing {
+            return;
+        }
+        let pattern_length: u32 = PATTERN_MS.iter().sum::<u32>() * SAMPLE_RATE / 1000;
+        let half_wave = SAMPLE_RATE / PITCH / 2;
+        let mut samples_left = ms * SAMPLE_RATE / 1000;
+        let mut buffer = [0u8; 1024];
+        while samples_left > 0 {
+            let mut used = 0;
+            for chunk in buffer.chunks_exact_mut(4) {
+                if samples_left == 0 {
+                    break;
+                }
+                let sample = if Self::is_beeping(self.position) {
+                    if (self.position / half_wave).is_multiple_of(2) {
+                        AMPLITUDE
+                    } else {
+                        -AMPLITUDE
+                    }
+                } else {
+                    0
+                };
+                let sample = sample.to_le_bytes();
+                chunk[0] = sample[0];
+                chunk[1] = sample[1];
+                chunk[2] = sample[0];
+                chunk[3] = sample[1];
+                used += 4;
+                samples_left -= 1;
+                self.position = (self.position + 1) % pattern_length;
+            }
+            let _ = dsp.write(&buffer[0..used]);
+        }
+    }
+
+    /// Is this sample in one of the beeps, rather than a gap?
+    fn is_beeping(position: u32) -> bool {
+        let mut start = 0;
+        for (idx, length_ms) in PATTERN_MS.iter().enumerate() {
+            let end = start + length_ms * SAMPLE_RATE / 1000;
+            if position < end {
+                return idx.is_multiple_of(2);
+            }
+            start = end;
+        }
+        false
+    }
+}
+
+impl Default for Beeper {
+    fn default() -> Self {
+        Beeper::new()
+    }
+}
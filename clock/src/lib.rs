@@ -0,0 +1,605 @@
+//! Application logic for the clock
+
+#![no_std]
+#![deny(missing_docs)]
+#![deny(unsafe_code)]
+
+use core::fmt::Write;
+
+use neotron_font::BigText;
+use neotron_input::{KeyEvent, Keyboard};
+use neotron_sdk::console;
+use neotron_tui::Line;
+
+mod beeper;
+pub mod rtc;
+
+use beeper::Beeper;
+use rtc::DateTime;
+
+/// How long we wait each time round the main loop
+const TICK_MS: u32 = 50;
+
+/// How often we re-read the real-time clock, in ticks
+const RTC_POLL_TICKS: u32 = 5;
+
+/// How long the snooze button gives you, in seconds
+const SNOOZE_SECONDS: u32 = 5 * 60;
+
+/// Where the big digits go
+const CLOCK_ROW: u8 = 5;
+
+/// Where the date goes
+const DATE_ROW: u8 = CLOCK_ROW + neotron_font::DIGITS.height() as u8 + 1;
+
+/// Where the "alarm ringing" message goes
+const RINGING_ROW: u8 = DATE_ROW + 2;
+
+/// Where the list of alarms goes
+const ALARM_ROW: u8 = RINGING_ROW + 3;
+
+/// One of the user's alarms
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct Alarm {
+    hour: u8,
+    minute: u8,
+    enabled: bool,
+}
+
+/// Why we are making a noise
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Ringing {
+    /// One of the alarms went off
+    Alarm(u8, u8),
+    /// The countdown timer ran out
+    Timer,
+}
+
+/// Represents the clock application
+pub struct App {
+    width: u8,
+    height: u8,
+    stdout: neotron_sdk::File,
+    keyboard: Keyboard,
+    beeper: Beeper,
+    /// The current date and time
+    now: DateTime,
+    /// Are we reading the time from the real-time clock?
+    has_rtc: bool,
+    /// Without a real-time clock, how far we are into the current second
+    sub_second_ms: u32,
+    /// The time currently drawn on screen
+    drawn: Option<DateTime>,
+    alarms: [Alarm; Self::MAX_ALARMS],
+    num_alarms: usize,
+    /// An alarm which was snoozed, and when it goes off again
+    snoozed: Option<(u8, u8)>,
+    /// The last minute we checked the alarms in
+    checked_minute: Option<(u8, u8)>,
+    ringing: Option<Ringing>,
+    /// Is the stopwatch on screen?
+    stopwatch_shown: bool,
+    stopwatch_running: bool,
+    stopwatch_ms: u32,
+    /// How long is left on the countdown timer, if it is running
+    timer_ms: Option<u32>,
+    /// Counts trips around the main loop
+    ticks: u32,
+}
+
+impl App {
+    /// The most alarms we can remember
+    const MAX_ALARMS: usize = 8;
+
+    /// Make a new clock application.
+    ///
+    /// You can give the screen size in characters.
+    pub const fn new(width: u8, height: u8) -> App {
+        App {
+            width,
+            height,
+            stdout: neotron_sdk::stdout(),
+            keyboard: Keyboard::new(),
+            beeper: Beeper::new(),
+            now: DateTime::DEFAULT,
+            has_rtc: false,
+            sub_second_ms: 0,
+            drawn: None,
+            alarms: [Alarm {
+                hour: 0,
+                minute: 0,
+                enabled: false,
+            }; Self::MAX_ALARMS],
+            num_alarms: 0,
+            snoozed: None,
+            checked_minute: None,
+            ringing: None,
+            stopwatch_shown: false,
+            stopwatch_running: false,
+            stopwatch_ms: 0,
+            timer_ms: None,
+            ticks: 0,
+        }
+    }
+
+    /// Run the clock, until the user quits
+    pub fn play(&mut self) {
+        match rtc::read() {
+            Some(now) => {
+                self.now = now;
+                self.has_rtc = true;
+            }
+            None => {
+                self.has_rtc = false;
+            }
+        }
+        self.beeper.open();
+        self.checked_minute = Some((self.now.hour, self.now.minute));
+        console::cursor_off(&mut self.stdout);
+        self.redraw();
+        if !self.has_rtc {
+            self.show_message("No real-time clock found. Press S to set the time.");
+        }
+
+        loop {
+            if let Some(key) = self.keyboard.read() {
+                if self.ringing.is_some() {
+                    self.dismiss(key);
+                } else if !self.handle_key(key) {
+                    break;
+                }
+            }
+            self.update_time();
+            self.update_timers();
+            self.check_alarms();
+            self.draw_clock();
+            self.draw_overlay();
+            self.draw_ringing();
+            self.beeper.tick(TICK_MS);
+            neotron_sdk::delay(core::time::Duration::from_millis(u64::from(TICK_MS)));
+            self.ticks = self.ticks.wrapping_add(1);
+        }
+
+        self.beeper.close();
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        console::clear_screen(&mut self.stdout);
+        console::move_cursor(&mut self.stdout, console::Position::origin());
+        console::cursor_on(&mut self.stdout);
+    }
+
+    /// Deal with a key press. Returns false if the user wants to quit.
+    fn handle_key(&mut self, key: KeyEvent) -> bool {
+        let KeyEvent::Char(key) = key else {
+            return true;
+        };
+        match key.to_ascii_lowercase() {
+            'q' => return false,
+            'a' => self.add_alarm(),
+            'd' => self.delete_alarm(),
+            digit @ '1'..='8' => {
+                let idx = digit as usize - '1' as usize;
+                if idx < self.num_alarms {
+                    self.alarms[idx].enabled = !self.alarms[idx].enabled;
+                    self.draw_alarms();
+                }
+            }
+            's' => self.set_time(),
+            'w' => {
+                self.stopwatch_shown = !self.stopwatch_shown;
+                self.clear_overlay();
+            }
+            ' ' => {
+                self.stopwatch_shown = true;
+                self.stopwatch_running = !self.stopwatch_running;
+            }
+            'r' => {
+                self.stopwatch_running = false;
+                self.stopwatch_ms = 0;
+            }
+            't' => self.start_timer(),
+            'c' => {
+                self.timer_ms = None;
+                self.clear_overlay();
+            }
+            _ => {}
+        }
+        true
+    }
+
+    /// Stop the alarm ringing. Z snoozes it.
+    fn dismiss(&mut self, key: KeyEvent) {
+        if let Some(Ringing::Alarm(..)) = self.ringing {
+            if key.is_char('z') {
+                let mut wake = self.now;
+                wake.add_seconds(SNOOZE_SECONDS);
+                self.snoozed = Some((wake.hour, wake.minute));
+            }
+        }
+        self.ringing = None;
+        self.beeper.stop();
+        self.clear_row(RINGING_ROW);
+        self.draw_alarms();
+    }
+
+    /// Keep the time up to date
+    fn update_time(&mut self) {
+        if self.has_rtc {
+            if self.ticks.is_multiple_of(RTC_POLL_TICKS) {
+                match rtc::read() {
+                    Some(now) => self.now = now,
+                    None => self.has_rtc = false,
+                }
+            }
+        } else {
+            self.sub_second_ms += TICK_MS;
+            while self.sub_second_ms >= 1000 {
+                self.sub_second_ms -= 1000;
+                self.now.add_seconds(1);
+            }
+        }
+    }
+
+    /// Run the stopwatch and the countdown timer
+    fn update_timers(&mut self) {
+        if self.stopwatch_running {
+            self.stopwatch_ms = self.stopwatch_ms.saturating_add(TICK_MS);
+        }
+        if let Some(remaining) = self.timer_ms {
+            let remaining = remaining.saturating_sub(TICK_MS);
+            if remaining == 0 {
+                self.timer_ms = None;
+                self.clear_overlay();
+                self.ring(Ringing::Timer);
+            } else {
+                self.timer_ms = Some(remaining);
+            }
+        }
+    }
+
+    /// See if any alarms should go off
+    fn check_alarms(&mut self) {
+        let minute = (self.now.hour, self.now.minute);
+        if self.checked_minute == Some(minute) {
+            return;
+        }
+        self.checked_minute = Some(minute);
+        if self.snoozed == Some(minute) {
+            self.snoozed = None;
+            self.ring(Ringing::Alarm(minute.0, minute.1));
+            return;
+        }
+        let due = self.alarms[0..self.num_alarms]
+            .iter()
+            .any(|alarm| alarm.enabled && (alarm.hour, alarm.minute) == minute);
+        if due {
+            self.ring(Ringing::Alarm(minute.0, minute.1));
+        }
+    }
+
+    /// Start the alarm going
+    fn ring(&mut self, reason: Ringing) {
+        self.ringing = Some(reason);
+        self.beeper.start();
+    }
+
+    /// Draw everything from scratch
+    fn redraw(&mut self) {
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        console::clear_screen(&mut self.stdout);
+        self.drawn = None;
+        self.draw_clock();
+        self.draw_alarms();
+        self.draw_help();
+    }
+
+    /// Draw the time and date, if they have changed
+    fn draw_clock(&mut self) {
+        if self.drawn == Some(self.now) {
+            return;
+        }
+        let mut text = Line::new();
+        let _ = write!(
+            text,
+            "{:02}:{:02}:{:02}",
+            self.now.hour, self.now.minute, self.now.second
+        );
+        let big = BigText::new(neotron_font::DIGITS, text.as_str());
+        let col = usize::from(self.width).saturating_sub(big.width()) / 2;
+        console::set_sgr(
+            &mut self.stdout,
+            [console::SgrParam::Bold, console::SgrParam::FgCyan],
+        );
+        for row in 0..big.height() {
+            self.move_to(CLOCK_ROW + row as u8, col as u8);
+            let _ = big.write_row(&mut self.stdout, row);
+        }
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+
+        let date_changed = match self.drawn {
+            Some(drawn) => {
+                (drawn.year, drawn.month, drawn.day)
+                    != (self.now.year, self.now.month, self.now.day)
+            }
+            None => true,
+        };
+        if date_changed {
+            let mut date = Line::new();
+            let _ = write!(
+                date,
+                "{} {} {} {}",
+                rtc::DAYS[self.now.weekday()],
+                self.now.day,
+                rtc::MONTHS[usize::from(self.now.month - 1)],
+                self.now.year
+            );
+            self.clear_row(DATE_ROW);
+            self.centre(DATE_ROW, date.as_str());
+        }
+        self.drawn = Some(self.now);
+    }
+
+    /// Draw the stopwatch and timer, in the top corner
+    fn draw_overlay(&mut self) {
+        let mut text = Line::new();
+        if self.stopwatch_shown {
+            let ms = self.stopwatch_ms;
+            let _ = write!(
+                text,
+                "Stopwatch {:02}:{:02}:{:02}.{}",
+                ms / 3_600_000,
+                (ms / 60_000) % 60,
+                (ms / 1000) % 60,
+                (ms / 100) % 10
+            );
+        }
+        if let Some(ms) = self.timer_ms {
+            // Round up, so we never show 00:00 while still running
+            let seconds = ms.div_ceil(1000);
+            if !text.as_str().is_empty() {
+                let _ = text.write_str("  ");
+            }
+            let _ = write!(text, "Timer {:02}:{:02}", seconds / 60, seconds % 60);
+        }
+        if text.as_str().is_empty() {
+            return;
+        }
+        let col = usize::from(self.width).saturating_sub(text.as_str().len() + 1);
+        self.move_to(0, col as u8);
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reverse]);
+        let _ = self.stdout.write_str(text.as_str());
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+    }
+
+    /// Blank out the top row, where the stopwatch and timer go
+    fn clear_overlay(&mut self) {
+        self.clear_row(0);
+    }
+
+    /// Flash a message while the alarm is ringing
+    fn draw_ringing(&mut self) {
+        let Some(reason) = self.ringing else {
+            return;
+        };
+        let mut text = Line::new();
+        match reason {
+            Ringing::Alarm(hour, minute) => {
+                let _ = write!(
+                    text,
+                    " ALARM {:02}:{:02} - press any key to stop, or Z to snooze ",
+                    hour, minute
+                );
+            }
+            Ringing::Timer => {
+                let _ = write!(text, " TIME'S UP - press any key ");
+            }
+        }
+        // Flash on and off every half a second
+        let on = (self.ticks / (500 / TICK_MS)).is_multiple_of(2);
+        if on {
+            console::set_sgr(
+                &mut self.stdout,
+                [console::SgrParam::Bold, console::SgrParam::Reverse],
+            );
+        }
+        self.centre(RINGING_ROW, text.as_str());
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+    }
+
+    /// Draw the list of alarms
+    fn draw_alarms(&mut self) {
+        for row in 0..3 {
+            self.clear_row(ALARM_ROW + row);
+        }
+        self.move_to(ALARM_ROW, 4);
+        if self.num_alarms == 0 {
+            let _ = self.stdout.write_str("No alarms set. Press A to add one.");
+            return;
+        }
+        let _ = self.stdout.write_str("Alarms:");
+        for idx in 0..self.num_alarms {
+            let alarm = self.alarms[idx];
+            let row = ALARM_ROW + 1 + (idx / 4) as u8;
+            let col = 4 + (idx % 4) as u8 * 18;
+            self.move_to(row, col);
+            let _ = write!(
+                self.stdout,
+                "{}) {:02}:{:02} {}",
+                idx + 1,
+                alarm.hour,
+                alarm.minute,
+                if alarm.enabled { "on" } else { "off" }
+            );
+        }
+    }
+
+    /// Draw the list of keys at the bottom
+    fn draw_help(&mut self) {
+        self.move_to(self.height - 3, 0);
+        let _ = self.stdout.write_str(
+            "A: Add alarm  D: Delete alarm  1-8: Alarm on/off  S: Set the time  Q: Quit",
+        );
+        self.move_to(self.height - 2, 0);
+        let _ = self.stdout.write_str(
+            "W: Stopwatch  Space: Start/stop  R: Reset  T: Start timer  C: Cancel timer",
+        );
+    }
+
+    /// Ask for a time, and add an alarm
+    fn add_alarm(&mut self) {
+        if self.num_alarms == Self::MAX_ALARMS {
+            self.show_message("You can't have any more alarms.");
+            return;
+        }
+        let mut input = [0u8; 5];
+        let Some(text) = self.prompt("Alarm time (HH:MM): ", &mut input) else {
+            return;
+        };
+        match parse_pair(text, 23) {
+            Some((hour, minute)) => {
+                self.alarms[self.num_alarms] = Alarm {
+                    hour,
+                    minute,
+                    enabled: true,
+                };
+                self.num_alarms += 1;
+                self.draw_alarms();
+            }
+            None => self.show_message("Please give a time like 07:30."),
+        }
+    }
+
+    /// Ask which alarm to remove, and remove it
+    fn delete_alarm(&mut self) {
+        let mut input = [0u8; 1];
+        let Some(text) = self.prompt("Delete which alarm? ", &mut input) else {
+            return;
+        };
+        let idx = match text.parse::<usize>() {
+            Ok(n) if (1..=self.num_alarms).contains(&n) => n - 1,
+            _ => return,
+        };
+        self.alarms.copy_within(idx + 1..self.num_alarms, idx);
+        self.num_alarms -= 1;
+        self.draw_alarms();
+    }
+
+    /// Ask for the date and time, and set the clock
+    fn set_time(&mut self) {
+        let mut input = [0u8; 19];
+        let Some(text) = self.prompt("Date and time (YYYY-MM-DD HH:MM:SS): ", &mut input) else {
+            return;
+        };
+        let Some(now) = DateTime::parse(text) else {
+            self.show_message("Please give a time like 2024-03-09 17:45:00.");
+            return;
+        };
+        self.now = now;
+        self.sub_second_ms = 0;
+        self.checked_minute = Some((now.hour, now.minute));
+        if self.has_rtc && rtc::write(&now).is_err() {
+            self.show_message("Failed to set the real-time clock.");
+        }
+    }
+
+    /// Ask how long for, and start the countdown timer
+    fn start_timer(&mut self) {
+        let mut input = [0u8; 5];
+        let Some(text) = self.prompt("Timer length (MM:SS): ", &mut input) else {
+            return;
+        };
+        match parse_pair(text, 99) {
+            Some((minutes, seconds)) if minutes > 0 || seconds > 0 => {
+                let ms = (u32::from(minutes) * 60 + u32::from(seconds)) * 1000;
+                self.timer_ms = Some(ms);
+                self.clear_overlay();
+            }
+            _ => self.show_message("Please give a time like 05:00."),
+        }
+    }
+
+    /// Show a message just above the list of keys
+    fn show_message(&mut self, message: &str) {
+        let row = self.prompt_row();
+        self.clear_row(row);
+        self.move_to(row, 0);
+        let _ = self.stdout.write_str(message);
+    }
+
+    /// The row where we ask questions
+    fn prompt_row(&self) -> u8 {
+        self.height - 5
+    }
+
+    /// Read some text from the user.
+    ///
+    /// Returns `None` if they pressed Escape or typed nothing. Without a
+    /// real-time clock, time stands still while we wait.
+    fn prompt<'a>(&mut self, prompt: &str, input: &'a mut [u8]) -> Option<&'a str> {
+        let row = self.prompt_row();
+        self.clear_row(row);
+        self.move_to(row, 0);
+        let _ = self.stdout.write_str(prompt);
+        console::cursor_on(&mut self.stdout);
+        let mut len = 0;
+        let done = loop {
+            let Some(key) = self.keyboard.read() else {
+                neotron_sdk::delay(core::time::Duration::from_millis(10));
+                continue;
+            };
+            match key {
+                KeyEvent::Enter => break len > 0,
+                KeyEvent::Escape => break false,
+                KeyEvent::Backspace if len > 0 => {
+                    len -= 1;
+                    let _ = self.stdout.write_str("\u{0008} \u{0008}");
+                }
+                KeyEvent::Char(key @ ' '..='~') if len < input.len() => {
+                    input[len] = key as u8;
+                    len += 1;
+                    let _ = self.stdout.write_char(key);
+                }
+                _ => {}
+            }
+        };
+        console::cursor_off(&mut self.stdout);
+        self.clear_row(row);
+        if done {
+            core::str::from_utf8(&input[0..len]).ok()
+        } else {
+            None
+        }
+    }
+
+    /// Write some text in the middle of a row
+    fn centre(&mut self, row: u8, text: &str) {
+        let col = usize::from(self.width).saturating_sub(text.len()) / 2;
+        self.move_to(row, col as u8);
+        let _ = self.stdout.write_str(text);
+    }
+
+    /// Blank out a row
+    fn clear_row(&mut self, row: u8) {
+        self.move_to(row, 0);
+        for _ in 0..self.width - 1 {
+            let _ = self.stdout.write_char(' ');
+        }
+    }
+
+    /// Move the cursor
+    fn move_to(&mut self, row: u8, col: u8) {
+        console::move_cursor(&mut self.stdout, console::Position { row, col });
+    }
+}
+
+/// Parse two numbers separated by a colon, like `07:30`.
+///
+/// The first number can be up to `max_first`, and the second up to 59.
+fn parse_pair(text: &str, max_first: u8) -> Option<(u8, u8)> {
+    let (first, second) = text.trim().split_once(':')?;
+    let first: u8 = first.parse().ok()?;
+    let second: u8 = second.parse().ok()?;
+    if first > max_first || second > 59 {
+        return None;
+    }
+    Some((first, second))
+}
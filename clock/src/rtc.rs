@@ -0,0 +1,185 @@
+//! Dates, times, and the real-time clock.
+//!
+//! The OS exposes the battery-backed clock as the `RTC:` device. Reading it
+//! gives the current time as text, like `2024-03-09 17:45:02`, and writing
+//! text in the same format sets it.
+
+use core::fmt::Write;
+
+/// The names of the days of the week, starting with Monday
+pub const DAYS: [&str; 7] = [
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+    "Sunday",
+];
+
+/// The names of the months
+pub const MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+/// A calendar date and a time of day
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct DateTime {
+    /// The year, like 2024
+    pub year: u16,
+    /// The month, 1 to 12
+    pub month: u8,
+    /// The day of the month, 1 to 31
+    pub day: u8,
+    /// Hours, 0 to 23
+    pub hour: u8,
+    /// Minutes, 0 to 59
+    pub minute: u8,
+    /// Seconds, 0 to 59
+    pub second: u8,
+}
+
+impl DateTime {
+    /// The time we show if nobody tells us otherwise
+    pub const DEFAULT: DateTime = DateTime {
+        year: 2024,
+        month: 1,
+        day: 1,
+        hour: 0,
+        minute: 0,
+        second: 0,
+    };
+
+    /// Parse text like `2024-03-09 17:45:02`.
+    ///
+    /// A `T` between the date and the time is also accepted.
+    pub fn parse(text: &str) -> Option<DateTime> {
+        let text = text.trim().as_bytes();
+        if text.len() != 19 || text[4] != b'-' || text[7] != b'-' {
+            return None;
+        }
+        if !(text[10] == b' ' || text[10] == b'T') || text[13] != b':' || text[16] != b':' {
+            return None;
+        }
+        let number = |range: core::ops::Range<usize>| -> Option<u16> {
+            let mut value = 0u16;
+            for byte in &text[range] {
+                if !byte.is_ascii_digit() {
+                    return None;
+                }
+                value = value * 10 + u16::from(byte - b'0');
+            }
+            Some(value)
+        };
+        let date_time = DateTime {
+            year: number(0..4)?,
+            month: number(5..7)? as u8,
+            day: number(8..10)? as u8,
+            hour: number(11..13)? as u8,
+            minute: number(14..16)? as u8,
+            second: number(17..19)? as u8,
+        };
+        if date_time.month == 0
+            || date_time.month > 12
+            || date_time.day == 0
+            || date_time.day > days_in_month(date_time.year, date_time.month)
+            || date_time.hour > 23
+            || date_time.minute > 59
+            || date_time.second > 59
+        {
+            return None;
+        }
+        Some(date_time)
+    }
+
+    /// The day of the week, where 0 is Monday
+    pub fn weekday(&self) -> usize {
+        // Sakamoto's method, adjusted so Monday is zero
+        const OFFSETS: [u16; 12] = [0, 3, 2, 5, 0, 3, 5, 1, 4, 6, 2, 4];
+        let year = if self.month < 3 {
+            self.year - 1
+        } else {
+            self.year
+        };
+        let sunday_based = (year + year / 4 - year / 100
+            + year / 400
+            + OFFSETS[usize::from(self.month - 1)]
+            + u16::from(self.day))
+            % 7;
+        usize::from((sunday_based + 6) % 7)
+    }
+
+    /// Move the time on by some seconds
+    pub fn add_seconds(&mut self, seconds: u32) {
+        let total = u32::from(self.second) + seconds;
+        self.second = (total % 60) as u8;
+        let total = u32::from(self.minute) + total / 60;
+        self.minute = (total % 60) as u8;
+        let total = u32::from(self.hour) + total / 60;
+        self.hour = (total % 24) as u8;
+        for _ in 0..total / 24 {
+            self.day += 1;
+            if self.day > days_in_month(self.year, self.month) {
+                self.day = 1;
+                self.month += 1;
+                if self.month > 12 {
+                    self.month = 1;
+                    self.year += 1;
+                }
+            }
+        }
+    }
+}
+
+impl core::fmt::Display for DateTime {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+            self.year, self.month, self.day, self.hour, self.minute, self.second
+        )
+    }
+}
+
+/// Is this a leap year?
+pub fn is_leap_year(year: u16) -> bool {
+    (year.is_multiple_of(4) && !year.is_multiple_of(100)) || year.is_multiple_of(400)
+}
+
+/// How many days are there in the given month?
+pub fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Read the real-time clock, if there is one
+pub fn read() -> Option<DateTime> {
+    let path = neotron_sdk::path::Path::new("RTC:").ok()?;
+    let rtc = neotron_sdk::File::open(path, neotron_sdk::Flags::empty()).ok()?;
+    let mut buffer = [0u8; 32];
+    let n = rtc.read(&mut buffer).ok()?;
+    let text = core::str::from_utf8(&buffer[0..n]).ok()?;
+    DateTime::parse(text)
+}
+
+/// Set the real-time clock
+pub fn write(date_time: &DateTime) -> Result<(), neotron_sdk::Error> {
+    let path = neotron_sdk::path::Path::new("RTC:")?;
+    let mut rtc = neotron_sdk::File::open(path, neotron_sdk::Flags::WRITE)?;
+    write!(rtc, "{}", date_time).map_err(|_| neotron_sdk::Error::DeviceSpecific)
+}
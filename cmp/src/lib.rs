@@ -0,0 +1,241 @@
+//! Application logic for the byte-by-byte file comparison
+//!
+//! We go through both files sixteen bytes at a time. Each row with a
+//! difference in it is shown from both files, in hex and as text, with the
+//! bytes that differ marked underneath:
+//!
+//! ```text
+//! 00001230 < 48 65 6c 6c 6f 20 57 6f  72 6c 64 21 0a 00 00 00 |Hello World!....|
+//!          > 48 65 6c 6c 6f 20 77 6f  72 6c 64 21 0a 00 00 00 |Hello world!....|
+//!                              ^^
+//! ```
+
+#![no_std]
+#![deny(missing_docs)]
+#![deny(unsafe_code)]
+
+use core::fmt::Write;
+
+/// How many bytes we show on a row
+const ROW_LEN: usize = 16;
+
+/// How much of each file we read at once
+const CHUNK_LEN: usize = 512;
+
+/// How many differences we show, unless told otherwise
+pub const DEFAULT_COUNT: u64 = 10;
+
+/// What to compare, and how much to say about it
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Options {
+    /// Where to start, in both files
+    pub offset: u64,
+    /// How many bytes to compare, or `None` for the rest of the files
+    pub length: Option<u64>,
+    /// Once we've shown rows with this many differing bytes in them, we
+    /// stop showing rows, and just count. Zero shows them all.
+    pub count: u64,
+    /// Show nothing at all, and just count
+    pub silent: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            offset: 0,
+            length: None,
+            count: DEFAULT_COUNT,
+            silent: false,
+        }
+    }
+}
+
+/// What we found
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct Summary {
+    /// How many bytes we compared
+    pub compared: u64,
+    /// How many of them were different
+    pub differences: u64,
+    /// How many rows with differences we didn't show, because there were
+    /// too many
+    pub hidden_rows: u64,
+    /// If one file ended before the other, which one - `0` for the first,
+    /// `1` for the second - and where
+    pub shorter: Option<(usize, u64)>,
+}
+
+impl Summary {
+    /// Are the files the same, over the part we looked at?
+    pub fn is_same(&self) -> bool {
+        self.differences == 0 && self.shorter.is_none()
+    }
+}
+
+/// Reads a file a chunk at a time, and hands it out a row at a time
+struct Reader<'a> {
+    file: &'a neotron_sdk::File,
+    buffer: [u8; CHUNK_LEN],
+    pos: usize,
+    len: usize,
+}
+
+impl<'a> Reader<'a> {
+    /// Read from wherever the file is now
+    fn new(file: &'a neotron_sdk::File) -> Reader<'a> {
+        Reader {
+            file,
+            buffer: [0; CHUNK_LEN],
+            pos: 0,
+            len: 0,
+        }
+    }
+
+    /// Fill up a row, and say how much of it we filled. It's only short if
+    /// the file has ended.
+    fn read_row(&mut self, row: &mut [u8]) -> Result<usize, neotron_sdk::Error> {
+        let mut filled = 0;
+        while filled < row.len() {
+            if self.pos == self.len {
+                self.len = self.file.read(&mut self.buffer)?;
+                self.pos = 0;
+                if self.len == 0 {
+                    break;
+                }
+            }
+            let n = (row.len() - filled).min(self.len - self.pos);
+            row[filled..filled + n].copy_from_slice(&self.buffer[self.pos..self.pos + n]);
+            filled += n;
+            self.pos += n;
+        }
+        Ok(filled)
+    }
+}
+
+/// Compare two files, from wherever the options say, and show the rows that
+/// differ on `out`
+pub fn compare<W: Write>(
+    first: &neotron_sdk::File,
+    second: &neotron_sdk::File,
+    options: &Options,
+    out: &mut W,
+) -> Result<Summary, neotron_sdk::Error> {
+    first.seek_set(options.offset)?;
+    second.seek_set(options.offset)?;
+    let mut readers = [Reader::new(first), Reader::new(second)];
+    let mut summary = Summary::default();
+    let mut shown = 0;
+    let mut offset = options.offset;
+    loop {
+        let want = match options.length {
+            Some(length) => (length - summary.compared).min(ROW_LEN as u64) as usize,
+            None => ROW_LEN,
+        };
+        if want == 0 {
+            break;
+        }
+        let mut rows = [[0u8; ROW_LEN]; 2];
+        let lens = [
+            readers[0].read_row(&mut rows[0][0..want])?,
+            readers[1].read_row(&mut rows[1][0..want])?,
+        ];
+        let both = lens[0].min(lens[1]);
+        let mut differs = [false; ROW_LEN];
+        let mut row_differences = 0;
+        for (idx, differ) in differs.iter_mut().enumerate().take(both) {
+            *differ = rows[0][idx] != rows[1][idx];
+            if *differ {
+                row_differences += 1;
+            }
+        }
+        summary.compared += both as u64;
+        summary.differences += row_differences;
+        if row_differences > 0 && !options.silent {
+            if options.count == 0 || shown < options.count {
+                shown += row_differences;
+                write_rows(out, offset, &rows, lens, &differs)
+                    .map_err(|_| neotron_sdk::Error::DeviceSpecific)?;
+            } else {
+                summary.hidden_rows += 1;
+            }
+        }
+        if lens[0] != lens[1] {
+            let which = if lens[0] < lens[1] { 0 } else { 1 };
+            summary.shorter = Some((which, offset + both as u64));
+        }
+        if lens[0] < want || lens[1] < want {
+            break;
+        }
+        offset += want as u64;
+    }
+    Ok(summary)
+}
+
+/// Show a row from each file, and mark where they differ
+fn write_rows<W: Write>(
+    out: &mut W,
+    offset: u64,
+    rows: &[[u8; ROW_LEN]; 2],
+    lens: [usize; 2],
+    differs: &[bool; ROW_LEN],
+) -> core::fmt::Result {
+    for (which, (row, len)) in rows.iter().zip(lens).enumerate() {
+        if which == 0 {
+            write!(out, "{:08x} < ", offset)?;
+        } else {
+            out.write_str("         > ")?;
+        }
+        write_hex(out, &row[0..len])?;
+        writeln!(out)?;
+    }
+    out.write_str("           ")?;
+    let last = differs.iter().rposition(|d| *d).unwrap_or(0);
+    for (idx, differ) in differs.iter().enumerate().take(last + 1) {
+        if idx == ROW_LEN / 2 {
+            out.write_char(' ')?;
+        }
+        out.write_str(if *differ { "^^ " } else { "   " })?;
+    }
+    writeln!(out)
+}
+
+/// Write a row of bytes in hex, then as text, like
+/// `48 65 6c 6c 6f 20 57 6f  72 6c 64 21 0a 00 00 00 |Hello World!....|`.
+/// A short row is padded, so the text lines up.
+pub fn write_hex<W: Write>(out: &mut W, row: &[u8]) -> core::fmt::Result {
+    for idx in 0..ROW_LEN {
+        if idx == ROW_LEN / 2 {
+            out.write_char(' ')?;
+        }
+        match row.get(idx) {
+            Some(byte) => write!(out, "{:02x} ", byte)?,
+            None => out.write_str("   ")?,
+        }
+    }
+    out.write_char('|')?;
+    for &byte in row {
+        let ch = if byte.is_ascii_graphic() || byte == b' ' {
+            char::from(byte)
+        } else {
+            '.'
+        };
+        out.write_char(ch)?;
+    }
+    out.write_char('|')
+}
+
+/// Read a number of bytes, in decimal or in hex with `0x` in front, and
+/// maybe a `K` or `M` after for KiB or MiB
+pub fn parse_size(text: &str) -> Option<u64> {
+    let text = text.trim();
+    let (text, scale) = match text.as_bytes().last() {
+        Some(b'k' | b'K') => (&text[0..text.len() - 1], 1024),
+        Some(b'm' | b'M') => (&text[0..text.len() - 1], 1024 * 1024),
+        _ => (text, 1),
+    };
+    let value = match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        Some(hex) => u64::from_str_radix(hex, 16).ok()?,
+        None => text.parse::<u64>().ok()?,
+    };
+    value.checked_mul(scale)
+}
@@ -0,0 +1,131 @@
+#![cfg_attr(target_os = "none", no_std)]
+#![cfg_attr(target_os = "none", no_main)]
+
+use core::fmt::Write;
+
+use neotron_args::{Item, Opt};
+
+#[cfg(not(target_os = "none"))]
+fn main() {
+    neotron_sdk::init();
+}
+
+#[no_mangle]
+extern "C" fn neotron_main() -> i32 {
+    match real_main() {
+        Ok(true) => 0,
+        // Like cmp, say when the files differ
+        Ok(false) => 1,
+        Err(e) => {
+            let _ = neotron_error::Report::from(e).print(&mut neotron_sdk::stdout());
+            2
+        }
+    }
+}
+
+/// The options we understand
+static SPEC: neotron_args::Spec = neotron_args::Spec {
+    name: "cmp",
+    usage: "[options] <first file> <second file>",
+    about: "Compares two files byte by byte, and shows where they differ. \
+            Sizes can be in hex, like 0x8000, and can end in K or M.",
+    options: &[
+        Opt::value(
+            'n',
+            "count",
+            "bytes",
+            "Stop showing differences after this many (default 10, 0 for all)",
+        ),
+        Opt::value('o', "offset", "bytes", "Start this far into both files"),
+        Opt::value('l', "length", "bytes", "Only compare this many bytes"),
+        Opt::flag('s', "silent", "Show nothing, and only set the exit code"),
+    ],
+};
+
+fn real_main() -> Result<bool, neotron_sdk::Error> {
+    let mut stdout = neotron_sdk::stdout();
+    let mut options = cmp::Options::default();
+    let mut names = [None, None];
+    let mut args = neotron_args::Parser::new(&SPEC);
+    loop {
+        let item = match args.next_item() {
+            Ok(Some(item)) => item,
+            Ok(None) => break,
+            Err(neotron_args::Error::Help) => return Ok(true),
+            Err(e) => return Err(e.into()),
+        };
+        let size = |long: &str, value: &neotron_args::Arg| {
+            cmp::parse_size(value.as_str()).ok_or_else(|| {
+                args.usage_error(if long == "count" {
+                    "bad value for --count"
+                } else {
+                    "sizes look like 4096, 0x1000 or 4K"
+                })
+            })
+        };
+        match item {
+            Item::Value("count", value) => options.count = size("count", &value)?,
+            Item::Value("offset", value) => options.offset = size("offset", &value)?,
+            Item::Value("length", value) => options.length = Some(size("length", &value)?),
+            Item::Flag("silent") => options.silent = true,
+            Item::Positional(value) => match names.iter_mut().find(|name| name.is_none()) {
+                Some(slot) => *slot = Some(value),
+                None => return Err(args.usage_error("cmp takes two files").into()),
+            },
+            _ => {}
+        }
+    }
+    let [Some(first), Some(second)] = names else {
+        return Err(args.usage_error("cmp takes two files").into());
+    };
+    let open = |name: &neotron_args::Arg| {
+        neotron_sdk::path::Path::new(name.as_str())
+            .and_then(|path| neotron_sdk::File::open(path, neotron_sdk::Flags::empty()))
+            .map_err(|e| neotron_error::Report::from(e).about(name.as_str()))
+    };
+    let files = match (open(&first), open(&second)) {
+        (Ok(a), Ok(b)) => [a, b],
+        (Err(report), _) | (_, Err(report)) => {
+            if !options.silent {
+                let _ = report.print(&mut stdout);
+            }
+            return Err(neotron_sdk::Error::InvalidPath);
+        }
+    };
+    let summary = cmp::compare(&files[0], &files[1], &options, &mut stdout)?;
+    if options.silent {
+        return Ok(summary.is_same());
+    }
+    if summary.hidden_rows > 0 {
+        let _ = writeln!(
+            stdout,
+            "...and {} more rows with differences",
+            summary.hidden_rows
+        );
+    }
+    if summary.differences > 0 {
+        let _ = writeln!(
+            stdout,
+            "{} of {} bytes differ",
+            summary.differences, summary.compared
+        );
+    }
+    if let Some((which, offset)) = summary.shorter {
+        let names = [first.as_str(), second.as_str()];
+        let _ = writeln!(
+            stdout,
+            "{} ends at 0x{:08x}, before {} does",
+            names[which],
+            offset,
+            names[1 - which]
+        );
+    }
+    if summary.is_same() {
+        let _ = writeln!(
+            stdout,
+            "The files are the same ({} bytes compared)",
+            summary.compared
+        );
+    }
+    Ok(summary.is_same())
+}
@@ -0,0 +1,177 @@
+//! Finding the rows in a CSV file.
+//!
+//! We don't load the file into memory. Instead we read it through once,
+//! noting where each row starts, and how wide each column needs to be. When
+//! we want a row, we seek to it and read it back in.
+
+/// The most rows we can index. Anything after this is not shown.
+pub const MAX_ROWS: usize = 8192;
+
+/// The most columns we can show
+pub const MAX_COLUMNS: usize = 32;
+
+/// The widest we make a column, unless asked
+pub const MAX_AUTO_WIDTH: u8 = 24;
+
+/// The characters we might find between fields, in order of preference
+const DELIMITERS: [u8; 3] = [b',', b';', b'\t'];
+
+/// Where each row of a file starts
+pub struct Index {
+    offsets: [u32; MAX_ROWS],
+    rows: usize,
+    /// Did we run out of room before the end of the file?
+    truncated: bool,
+    delimiter: u8,
+    widths: [u8; MAX_COLUMNS],
+    columns: usize,
+}
+
+impl Index {
+    /// Make an empty index
+    pub const fn new() -> Index {
+        Index {
+            offsets: [0; MAX_ROWS],
+            rows: 0,
+            truncated: false,
+            delimiter: b',',
+            widths: [0; MAX_COLUMNS],
+            columns: 0,
+        }
+    }
+
+    /// Read through a file, and find all the rows
+    pub fn build(&mut self, file: &neotron_sdk::File) -> Result<(), neotron_sdk::Error> {
+        self.rows = 0;
+        self.truncated = false;
+        self.columns = 0;
+        self.widths = [0; MAX_COLUMNS];
+        file.seek_set(0)?;
+
+        let mut buffer = [0u8; 512];
+        let mut offset = 0u32;
+        let mut in_quotes = false;
+        let mut row_start = true;
+        let mut column = 0;
+        let mut width = 0u8;
+        let mut first = true;
+        loop {
+            let n = file.read(&mut buffer)?;
+            if n == 0 {
+                break;
+            }
+            if first {
+                self.delimiter = detect_delimiter(&buffer[0..n]);
+                first = false;
+            }
+            for &byte in &buffer[0..n] {
+                if row_start {
+                    if self.rows == MAX_ROWS {
+                        self.truncated = true;
+                        return Ok(());
+                    }
+                    self.offsets[self.rows] = offset;
+                    self.rows += 1;
+                    row_start = false;
+                    column = 0;
+                    width = 0;
+                }
+                offset += 1;
+                match byte {
+                    b'"' => in_quotes = !in_quotes,
+                    b'\n' if !in_quotes => {
+                        self.end_field(column, width);
+                        row_start = true;
+                    }
+                    b'\r' if !in_quotes => {}
+                    byte if byte == self.delimiter && !in_quotes => {
+                        self.end_field(column, width);
+                        column += 1;
+                        width = 0;
+                    }
+                    // Count characters, not the extra bytes of UTF-8
+                    byte if byte & 0xC0 == 0x80 => {}
+                    _ => width = width.saturating_add(1),
+                }
+            }
+        }
+        if !row_start {
+            self.end_field(column, width);
+        }
+        Ok(())
+    }
+
+    /// Note how wide a field was
+    fn end_field(&mut self, column: usize, width: u8) {
+        if column < MAX_COLUMNS {
+            self.widths[column] = self.widths[column].max(width.min(MAX_AUTO_WIDTH));
+            self.columns = self.columns.max(column + 1);
+        }
+    }
+
+    /// How many rows we found
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Did the file have more rows than we could index?
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+
+    /// Where a row starts in the file
+    pub fn offset(&self, row: usize) -> u32 {
+        self.offsets[row]
+    }
+
+    /// What goes between the fields
+    pub fn delimiter(&self) -> u8 {
+        self.delimiter
+    }
+
+    /// How many columns the widest row has
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+
+    /// How wide a column is
+    pub fn width(&self, column: usize) -> u8 {
+        self.widths[column].max(1)
+    }
+
+    /// Change how wide a column is
+    pub fn set_width(&mut self, column: usize, width: u8) {
+        self.widths[column] = width;
+    }
+}
+
+impl Default for Index {
+    fn default() -> Self {
+        Index::new()
+    }
+}
+
+/// Work out which delimiter the file uses, by counting them on the first line
+fn detect_delimiter(data: &[u8]) -> u8 {
+    let mut counts = [0usize; DELIMITERS.len()];
+    let mut in_quotes = false;
+    for &byte in data {
+        match byte {
+            b'"' => in_quotes = !in_quotes,
+            b'\n' if !in_quotes => break,
+            _ if !in_quotes => {
+                if let Some(idx) = DELIMITERS.iter().position(|d| *d == byte) {
+                    counts[idx] += 1;
+                }
+            }
+            _ => {}
+        }
+    }
+    let mut best = 0;
+    for (idx, count) in counts.iter().enumerate() {
+        if *count > counts[best] {
+            best = idx;
+        }
+    }
+    DELIMITERS[best]
+}
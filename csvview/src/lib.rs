@@ -0,0 +1,594 @@
+//! Application logic for the CSV viewer
+
+#![no_std]
+#![deny(missing_docs)]
+#![deny(unsafe_code)]
+
+use core::{cmp::Ordering, fmt::Write};
+
+use neotron_input::{KeyEvent, Keyboard};
+use neotron_line::Editor;
+use neotron_sdk::console;
+use neotron_tui::Line;
+
+pub mod index;
+pub mod record;
+
+use index::{Index, MAX_ROWS};
+use record::Record;
+
+/// How much room the row numbers take up, including the gap after them
+const ROW_NUMBER_WIDTH: u8 = 7;
+
+/// The widest we let you make a column
+const MAX_WIDTH: u8 = 60;
+
+/// The longest filter you can type
+const MAX_FILTER: usize = 32;
+
+/// How many bytes of old filters we remember, to bring back with Up
+const FILTER_HISTORY: usize = 256;
+
+/// Where the column headings go
+const HEADING_ROW: u8 = 1;
+
+/// Where the first row of data goes
+const FIRST_ROW: u8 = 2;
+
+/// How the rows are sorted
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct Sort {
+    column: usize,
+    descending: bool,
+}
+
+/// Represents the CSV viewer
+pub struct Viewer {
+    width: u8,
+    height: u8,
+    stdout: neotron_sdk::File,
+    keyboard: Keyboard,
+    file: Option<neotron_sdk::File>,
+    name: [u8; Self::MAX_NAME],
+    name_len: usize,
+    index: Index,
+    /// The rows we are showing, in the order we show them
+    order: [u16; MAX_ROWS],
+    /// How many entries of `order` are in use
+    shown: usize,
+    /// What we sort each row by
+    keys: [u64; MAX_ROWS],
+    record: Record,
+    /// Another row, for comparing against
+    other: Record,
+    /// Is the first row a list of column names?
+    header: bool,
+    /// Which entry of `order` is at the top of the screen
+    top: usize,
+    /// Which column is at the left of the screen
+    left: usize,
+    /// Which entry of `order` the cursor is on
+    row: usize,
+    /// Which column the cursor is in
+    column: usize,
+    sort: Option<Sort>,
+    /// Only show rows where this column contains the filter text
+    filter_column: usize,
+    filter: [u8; MAX_FILTER],
+    filter_len: usize,
+    /// Where the filter is typed
+    editor: Editor<MAX_FILTER, FILTER_HISTORY>,
+    message: Option<&'static str>,
+}
+
+impl Viewer {
+    /// The longest file name we show
+    const MAX_NAME: usize = 40;
+
+    /// Make a new CSV viewer.
+    ///
+    /// You can give the screen size in characters.
+    pub const fn new(width: u8, height: u8) -> Viewer {
+        Viewer {
+            width,
+            height,
+            stdout: neotron_sdk::stdout(),
+            keyboard: Keyboard::new(),
+            file: None,
+            name: [0; Self::MAX_NAME],
+            name_len: 0,
+            index: Index::new(),
+            order: [0; MAX_ROWS],
+            shown: 0,
+            keys: [0; MAX_ROWS],
+            record: Record::new(),
+            other: Record::new(),
+            header: true,
+            top: 0,
+            left: 0,
+            row: 0,
+            column: 0,
+            sort: None,
+            filter_column: 0,
+            filter: [0; MAX_FILTER],
+            filter_len: 0,
+            editor: Editor::new(),
+            message: None,
+        }
+    }
+
+    /// Open a CSV file, and find all the rows in it
+    pub fn load_file(&mut self, filename: &str) -> Result<(), neotron_sdk::Error> {
+        let mut len = filename.len().min(Self::MAX_NAME);
+        while !filename.is_char_boundary(len) {
+            len -= 1;
+        }
+        self.name[0..len].copy_from_slice(&filename.as_bytes()[0..len]);
+        self.name_len = len;
+        let path = neotron_sdk::path::Path::new(filename)?;
+        let file = neotron_sdk::File::open(path, neotron_sdk::Flags::empty())?;
+        self.index.build(&file)?;
+        self.file = Some(file);
+        self.sort = None;
+        self.filter_len = 0;
+        self.rebuild();
+        Ok(())
+    }
+
+    /// Run the viewer, until the user quits
+    pub fn run(&mut self) {
+        if self.index.is_truncated() {
+            self.message = Some("File too big - only showing the start");
+        }
+        console::cursor_off(&mut self.stdout);
+        console::clear_screen(&mut self.stdout);
+        loop {
+            self.draw();
+            let key = self.keyboard.wait();
+            self.message = None;
+            let page = usize::from(self.data_rows());
+            let last_row = self.shown.saturating_sub(1);
+            let last_column = self.index.columns().saturating_sub(1);
+            match key {
+                KeyEvent::Char('q' | 'Q') => break,
+                KeyEvent::Up => self.row = self.row.saturating_sub(1),
+                KeyEvent::Down => self.row = (self.row + 1).min(last_row),
+                KeyEvent::PageUp => self.row = self.row.saturating_sub(page),
+                KeyEvent::PageDown => self.row = (self.row + page).min(last_row),
+                KeyEvent::Home => self.row = 0,
+                KeyEvent::End => self.row = last_row,
+                KeyEvent::Left => self.column = self.column.saturating_sub(1),
+                KeyEvent::Right | KeyEvent::Tab => self.column = (self.column + 1).min(last_column),
+                KeyEvent::Char('<') => {
+                    let width = self.index.width(self.column);
+                    self.index
+                        .set_width(self.column, width.saturating_sub(1).max(1));
+                }
+                KeyEvent::Char('>') => {
+                    let width = self.index.width(self.column);
+                    self.index
+                        .set_width(self.column, (width + 1).min(MAX_WIDTH));
+                }
+                KeyEvent::Char('s') => {
+                    // Sort by this column, or swap the direction if we already are
+                    let descending = self.sort
+                        == Some(Sort {
+                            column: self.column,
+                            descending: false,
+                        });
+                    self.sort = Some(Sort {
+                        column: self.column,
+                        descending,
+                    });
+                    self.rebuild();
+                }
+                KeyEvent::Char('u') => {
+                    self.sort = None;
+                    self.rebuild();
+                }
+                KeyEvent::Char('/') => self.ask_filter(),
+                KeyEvent::Char('c') => {
+                    self.filter_len = 0;
+                    self.rebuild();
+                }
+                KeyEvent::Char('h') => {
+                    self.header = !self.header;
+                    self.rebuild();
+                }
+                _ => {}
+            }
+        }
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        console::clear_screen(&mut self.stdout);
+        console::move_cursor(&mut self.stdout, console::Position::origin());
+        console::cursor_on(&mut self.stdout);
+    }
+
+    /// Work out which rows to show, and in what order
+    fn rebuild(&mut self) {
+        if self.filter_len > 0 || self.sort.is_some() {
+            self.status("Working...");
+        }
+        let Some(file) = self.file.as_ref() else {
+            return;
+        };
+        let first = if self.header { 1 } else { 0 };
+        let filter = &self.filter[0..self.filter_len];
+        let delimiter = self.index.delimiter();
+        self.shown = 0;
+        for row in first..self.index.rows() {
+            if !filter.is_empty() {
+                if self
+                    .record
+                    .read(file, self.index.offset(row), delimiter)
+                    .is_err()
+                {
+                    break;
+                }
+                let text = self.record.field(self.filter_column).as_bytes();
+                if !contains_ignore_case(text, filter) {
+                    continue;
+                }
+            }
+            self.order[self.shown] = row as u16;
+            self.shown += 1;
+        }
+        if let Some(sort) = self.sort {
+            self.sort_rows(sort);
+        }
+        self.row = self.row.min(self.shown.saturating_sub(1));
+    }
+
+    /// Sort the rows we are showing.
+    ///
+    /// We make a key for each row from the start of the field, which is
+    /// enough to sort most rows. We only go back to the file to compare rows
+    /// which have the same key.
+    fn sort_rows(&mut self, sort: Sort) {
+        let Some(file) = self.file.as_ref() else {
+            return;
+        };
+        let delimiter = self.index.delimiter();
+        let rows = &self.order[0..self.shown];
+
+        // Is everything in this column a number?
+        let mut numeric = true;
+        for row in rows.iter().map(|row| usize::from(*row)) {
+            if self
+                .record
+                .read(file, self.index.offset(row), delimiter)
+                .is_err()
+            {
+                return;
+            }
+            let text = self.record.field(sort.column).trim();
+            if !text.is_empty() && parse_number(text).is_none() {
+                numeric = false;
+                break;
+            }
+        }
+
+        for row in rows.iter().map(|row| usize::from(*row)) {
+            if self
+                .record
+                .read(file, self.index.offset(row), delimiter)
+                .is_err()
+            {
+                return;
+            }
+            let text = self.record.field(sort.column).trim();
+            self.keys[row] = if numeric {
+                // Flip the sign bit, so negative numbers sort first
+                parse_number(text).unwrap_or(i64::MIN) as u64 ^ 1 << 63
+            } else {
+                let mut key = [0u8; 8];
+                for (dest, src) in key.iter_mut().zip(text.bytes()) {
+                    *dest = src.to_ascii_lowercase();
+                }
+                u64::from_be_bytes(key)
+            };
+        }
+
+        let Viewer {
+            order,
+            shown,
+            keys,
+            index,
+            record,
+            other,
+            ..
+        } = self;
+        order[0..*shown].sort_unstable_by(|a, b| {
+            let (a, b) = (usize::from(*a), usize::from(*b));
+            let mut ordering = keys[a].cmp(&keys[b]);
+            if ordering == Ordering::Equal
+                && !numeric
+                && record.read(file, index.offset(a), delimiter).is_ok()
+                && other.read(file, index.offset(b), delimiter).is_ok()
+            {
+                let first = record.field(sort.column).trim().bytes();
+                let second = other.field(sort.column).trim().bytes();
+                ordering = first
+                    .map(|b| b.to_ascii_lowercase())
+                    .cmp(second.map(|b| b.to_ascii_lowercase()));
+            }
+            if sort.descending {
+                ordering = ordering.reverse();
+            }
+            // Keep equal rows in the order they are in the file
+            ordering.then(a.cmp(&b))
+        });
+    }
+
+    /// Ask what to filter the current column by
+    fn ask_filter(&mut self) {
+        let width = usize::from(self.width);
+        self.move_to(self.height - 2, 0);
+        let _ = write!(self.stdout, "{:width$}", "");
+        self.move_to(self.height - 2, 0);
+        let _ = write!(self.stdout, "Show rows where ");
+        self.write_column_name(self.column);
+        let _ = write!(self.stdout, " contains: ");
+        console::cursor_on(&mut self.stdout);
+        let filter = self.editor.read_line(&mut self.stdout, &mut self.keyboard);
+        console::cursor_off(&mut self.stdout);
+        let Some(filter) = filter else {
+            return;
+        };
+        self.filter[0..filter.len()].copy_from_slice(filter.as_bytes());
+        self.filter_len = filter.len();
+        self.filter_column = self.column;
+        self.rebuild();
+        if self.shown == 0 {
+            self.message = Some("No rows match");
+        }
+    }
+
+    /// Draw the screen
+    fn draw(&mut self) {
+        let page = usize::from(self.data_rows());
+        if self.row < self.top {
+            self.top = self.row;
+        } else if self.row >= self.top + page {
+            self.top = self.row + 1 - page;
+        }
+        self.scroll_to_column();
+
+        // Title bar
+        self.move_to(0, 0);
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reverse]);
+        let mut title = Line::new();
+        let name = core::str::from_utf8(&self.name[0..self.name_len]).unwrap_or("?");
+        let _ = write!(title, " {} - {} rows", name, self.shown);
+        if let Some(sort) = self.sort {
+            let _ = write!(
+                title,
+                ", sorted by column {} ({})",
+                sort.column + 1,
+                if sort.descending { "Z-A" } else { "A-Z" }
+            );
+        }
+        if self.filter_len > 0 {
+            let filter = core::str::from_utf8(&self.filter[0..self.filter_len]).unwrap_or("?");
+            let _ = write!(
+                title,
+                ", column {} contains \"{}\"",
+                self.filter_column + 1,
+                filter
+            );
+        }
+        let width = usize::from(self.width);
+        let _ = write!(self.stdout, "{:<width$.width$}", title.as_str());
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+
+        // Column headings
+        self.move_to(HEADING_ROW, 0);
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Bold]);
+        let _ = write!(
+            self.stdout,
+            "{:>w$}",
+            "Row",
+            w = usize::from(ROW_NUMBER_WIDTH) - 1
+        );
+        if self.header {
+            if let Some(file) = self.file.as_ref() {
+                let _ = self
+                    .record
+                    .read(file, self.index.offset(0), self.index.delimiter());
+            }
+        }
+        self.draw_cells(None);
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+
+        // The data
+        for screen_row in 0..page {
+            let idx = self.top + screen_row;
+            self.move_to(FIRST_ROW + screen_row as u8, 0);
+            if idx >= self.shown {
+                let _ = write!(self.stdout, "{:width$}", "");
+                continue;
+            }
+            let row = usize::from(self.order[idx]);
+            if let Some(file) = self.file.as_ref() {
+                let _ = self
+                    .record
+                    .read(file, self.index.offset(row), self.index.delimiter());
+            }
+            let _ = write!(
+                self.stdout,
+                "{:>w$}",
+                row + 1,
+                w = usize::from(ROW_NUMBER_WIDTH) - 1
+            );
+            self.draw_cells(Some(idx == self.row));
+        }
+
+        // The whole of the current cell, and some help
+        self.move_to(self.height - 2, 0);
+        let mut line = Line::new();
+        if let Some(row) = self.order[0..self.shown].get(self.row) {
+            let row = usize::from(*row);
+            if let Some(file) = self.file.as_ref() {
+                let _ = self
+                    .record
+                    .read(file, self.index.offset(row), self.index.delimiter());
+            }
+            let _ = write!(
+                line,
+                "Row {}, column {}: {}",
+                row + 1,
+                self.column + 1,
+                self.record.field(self.column)
+            );
+        }
+        let text = self.message.unwrap_or(line.as_str());
+        let _ = write!(self.stdout, "{:<width$.width$}", text);
+        self.move_to(self.height - 1, 0);
+        let _ = write!(
+            self.stdout,
+            "{:<width$.width$}",
+            "Arrows: move  s: sort  u: unsort  /: filter  c: clear  < >: width  h: header  q: quit"
+        );
+    }
+
+    /// Draw the cells of the record we have read, from the left-most column
+    /// on the screen.
+    ///
+    /// If `current` is `None`, this is the heading row. Otherwise it says
+    /// whether the cursor is on this row.
+    fn draw_cells(&mut self, current: Option<bool>) {
+        let mut col = ROW_NUMBER_WIDTH;
+        let mut column = self.left;
+        while col < self.width && column < self.index.columns() {
+            let width = usize::from(self.index.width(column));
+            let space = usize::from(self.width - col);
+            let highlight = match current {
+                Some(on_row) => on_row && column == self.column,
+                None => column == self.column,
+            };
+            let _ = self.stdout.write_str("|");
+            if highlight {
+                console::set_sgr(&mut self.stdout, [console::SgrParam::Reverse]);
+            }
+            let shown = width.min(space.saturating_sub(1));
+            if current.is_none() && !self.header {
+                let mut name = Line::new();
+                let _ = write_column_letters(&mut name, column);
+                let _ = write!(self.stdout, "{:<shown$.shown$}", name.as_str());
+            } else {
+                let _ = write!(self.stdout, "{:<shown$.shown$}", self.record.field(column));
+            }
+            if highlight {
+                console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+                if current.is_none() {
+                    console::set_sgr(&mut self.stdout, [console::SgrParam::Bold]);
+                }
+            }
+            col = col.saturating_add(width as u8 + 1);
+            column += 1;
+        }
+        // Clear the rest of the line
+        if col < self.width {
+            let _ = write!(self.stdout, "{:w$}", "", w = usize::from(self.width - col));
+        }
+    }
+
+    /// Scroll sideways, so the cursor's column is on the screen
+    fn scroll_to_column(&mut self) {
+        if self.column < self.left {
+            self.left = self.column;
+        }
+        loop {
+            let mut used = ROW_NUMBER_WIDTH;
+            let mut fits = false;
+            for column in self.left..=self.column {
+                used = used.saturating_add(self.index.width(column) + 1);
+                fits = used <= self.width;
+            }
+            if fits || self.left == self.column {
+                break;
+            }
+            self.left += 1;
+        }
+    }
+
+    /// Write the name of a column
+    fn write_column_name(&mut self, column: usize) {
+        if self.header {
+            if let Some(file) = self.file.as_ref() {
+                if self
+                    .other
+                    .read(file, self.index.offset(0), self.index.delimiter())
+                    .is_ok()
+                {
+                    let _ = write!(self.stdout, "\"{}\"", self.other.field(column));
+                    return;
+                }
+            }
+        }
+        let _ = write!(self.stdout, "column ");
+        let _ = write_column_letters(&mut self.stdout, column);
+    }
+
+    /// Show a message while we do something slow
+    fn status(&mut self, message: &str) {
+        self.move_to(self.height - 2, 0);
+        let width = usize::from(self.width);
+        let _ = write!(self.stdout, "{:<width$.width$}", message);
+    }
+
+    /// How many rows of data fit on the screen
+    fn data_rows(&self) -> u8 {
+        self.height.saturating_sub(FIRST_ROW + 2).max(1)
+    }
+
+    /// Move the cursor
+    fn move_to(&mut self, row: u8, col: u8) {
+        console::move_cursor(&mut self.stdout, console::Position { row, col });
+    }
+}
+
+/// Write a spreadsheet-style column name, like `A`, `Z` or `AB`
+fn write_column_letters<W>(out: &mut W, column: usize) -> core::fmt::Result
+where
+    W: Write,
+{
+    if column >= 26 {
+        out.write_char((b'A' + (column / 26 - 1) as u8) as char)?;
+    }
+    out.write_char((b'A' + (column % 26) as u8) as char)
+}
+
+/// Read a decimal number, in millionths
+fn parse_number(text: &str) -> Option<i64> {
+    let (negative, digits) = match text.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, text.strip_prefix('+').unwrap_or(text)),
+    };
+    let (whole, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+    if (whole.is_empty() && fraction.is_empty())
+        || !whole.bytes().all(|b| b.is_ascii_digit())
+        || !fraction.bytes().all(|b| b.is_ascii_digit())
+    {
+        return None;
+    }
+    let mut value: i64 = 0;
+    for digit in whole.bytes() {
+        value = value
+            .checked_mul(10)?
+            .checked_add(i64::from(digit - b'0'))?;
+    }
+    let mut scale = 1_000_000;
+    value = value.checked_mul(scale)?;
+    for digit in fraction.bytes().take(6) {
+        scale /= 10;
+        value += i64::from(digit - b'0') * scale;
+    }
+    Some(if negative { -value } else { value })
+}
+
+/// Does the text contain the pattern, ignoring case?
+fn contains_ignore_case(text: &[u8], pattern: &[u8]) -> bool {
+    text.windows(pattern.len())
+        .any(|window| window.eq_ignore_ascii_case(pattern))
+}
@@ -0,0 +1,28 @@
+#![cfg_attr(target_os = "none", no_std)]
+#![cfg_attr(target_os = "none", no_main)]
+
+use core::{fmt::Write, ptr::addr_of_mut};
+
+#[cfg(not(target_os = "none"))]
+fn main() {
+    neotron_sdk::init();
+}
+
+static mut VIEWER: csvview::Viewer = csvview::Viewer::new(80, 25);
+
+#[no_mangle]
+extern "C" fn neotron_main() -> i32 {
+    neotron_error::exit_code(real_main())
+}
+
+fn real_main() -> Result<(), neotron_sdk::Error> {
+    let viewer = unsafe { &mut *addr_of_mut!(VIEWER) };
+    let Some(filename) = neotron_sdk::arg(0) else {
+        let mut stdout = neotron_sdk::stdout();
+        let _ = writeln!(stdout, "Usage: csvview <file.csv>");
+        return Err(neotron_sdk::Error::InvalidArg);
+    };
+    viewer.load_file(&filename)?;
+    viewer.run();
+    Ok(())
+}
@@ -0,0 +1,112 @@
+//! Reading one row of a CSV file, and splitting it into fields.
+//!
+//! Fields can be quoted, and a quoted field can contain delimiters, line
+//! breaks, and doubled-up quotes.
+
+use crate::index::MAX_COLUMNS;
+
+/// The longest row we read. Anything more is cut off.
+const MAX_LEN: usize = 1024;
+
+/// One row of the file
+pub struct Record {
+    /// The fields, with the quotes taken out
+    text: [u8; MAX_LEN],
+    /// Where each field starts and ends, in `text`
+    fields: [(u16, u16); MAX_COLUMNS],
+    count: usize,
+}
+
+impl Record {
+    /// Make an empty record
+    pub const fn new() -> Record {
+        Record {
+            text: [0; MAX_LEN],
+            fields: [(0, 0); MAX_COLUMNS],
+            count: 0,
+        }
+    }
+
+    /// Read the row which starts at this offset in the file
+    pub fn read(
+        &mut self,
+        file: &neotron_sdk::File,
+        offset: u32,
+        delimiter: u8,
+    ) -> Result<(), neotron_sdk::Error> {
+        file.seek_set(u64::from(offset))?;
+        let mut len = 0;
+        while len < MAX_LEN {
+            let n = file.read(&mut self.text[len..])?;
+            if n == 0 {
+                break;
+            }
+            len += n;
+        }
+        self.parse(len, delimiter);
+        Ok(())
+    }
+
+    /// Split the text into fields, taking the quotes out as we go.
+    ///
+    /// The text never gets longer, so we can do this in place.
+    fn parse(&mut self, len: usize, delimiter: u8) {
+        self.count = 0;
+        let mut read = 0;
+        let mut write = 0;
+        let mut start = 0;
+        let mut in_quotes = false;
+        while read < len {
+            let byte = self.text[read];
+            read += 1;
+            match byte {
+                b'"' if in_quotes && self.text.get(read) == Some(&b'"') && read < len => {
+                    // A doubled quote is a quote
+                    self.text[write] = b'"';
+                    write += 1;
+                    read += 1;
+                }
+                b'"' => in_quotes = !in_quotes,
+                b'\n' if !in_quotes => break,
+                b'\r' if !in_quotes => {}
+                byte if byte == delimiter && !in_quotes => {
+                    self.push_field(start, write);
+                    start = write;
+                }
+                byte => {
+                    self.text[write] = byte;
+                    write += 1;
+                }
+            }
+        }
+        self.push_field(start, write);
+    }
+
+    /// Note where a field is
+    fn push_field(&mut self, start: usize, end: usize) {
+        if self.count < MAX_COLUMNS {
+            self.fields[self.count] = (start as u16, end as u16);
+            self.count += 1;
+        }
+    }
+
+    /// Get a field. Missing fields are empty.
+    pub fn field(&self, column: usize) -> &str {
+        if column >= self.count {
+            return "";
+        }
+        let (start, end) = self.fields[column];
+        let bytes = &self.text[usize::from(start)..usize::from(end)];
+        match core::str::from_utf8(bytes) {
+            Ok(text) => text,
+            // Probably cut off in the middle of a character
+            Err(e) => core::str::from_utf8(&bytes[0..e.valid_up_to()]).unwrap_or(""),
+        }
+    }
+}
+
+impl Default for Record {
+    fn default() -> Self {
+        Record::new()
+    }
+}
@@ -0,0 +1,633 @@
+//! Application logic for the database
+
+#![no_std]
+#![deny(missing_docs)]
+#![deny(unsafe_code)]
+
+use core::fmt::Write;
+
+use neotron_app::{Hint, Shell};
+use neotron_input::{KeyEvent, Keyboard};
+use neotron_line::Editor;
+use neotron_sdk::console;
+use neotron_tui::Line;
+
+pub mod table;
+
+use table::{cut, Record, Table, MAX_FIELDS, MAX_NAME, MAX_RECORDS, MAX_VALUE};
+
+/// The fields you get if you don't choose any
+const ADDRESS_BOOK: [&str; 6] = ["Name", "Phone", "Email", "Address", "Birthday", "Notes"];
+
+/// The longest file name we keep
+const MAX_FILENAME: usize = 40;
+
+/// The longest thing you can search for
+const MAX_SEARCH: usize = 32;
+
+/// The narrowest a column gets in the list
+const MIN_WIDTH: usize = 4;
+
+/// The widest a column gets in the list
+const MAX_WIDTH: usize = 24;
+
+/// Where the column headings go
+const HEADING_ROW: u8 = 1;
+
+/// Where the first record goes
+const FIRST_ROW: u8 = 2;
+
+/// Where the first field goes, when looking at one record
+const FORM_ROW: u8 = 3;
+
+/// What the keys do, when looking at the list of records
+const LIST_HINTS: [Hint; 8] = [
+    Hint::new("Enter", "Open"),
+    Hint::new("A", "Add"),
+    Hint::new("D", "Delete"),
+    Hint::new("/", "Find"),
+    Hint::new("S", "Sort"),
+    Hint::new("N", "New field"),
+    Hint::new("X", "CSV"),
+    Hint::new("Q", "Quit"),
+];
+
+/// What the keys do, when looking at one record
+const FORM_HINTS: [Hint; 5] = [
+    Hint::new("Enter", "Edit"),
+    Hint::new("Up/Down", "Field"),
+    Hint::new("PgUp/PgDn", "Record"),
+    Hint::new("D", "Delete"),
+    Hint::new("Esc", "Back"),
+];
+
+/// Represents the database application
+pub struct App {
+    width: u8,
+    height: u8,
+    stdout: neotron_sdk::File,
+    /// Draws the title bar, key hints and questions
+    shell: Shell<'static>,
+    /// Reads the answers to questions
+    keyboard: Keyboard,
+    table: Table,
+    filename: [u8; MAX_FILENAME],
+    filename_len: usize,
+    /// The records which match the search, as indices into the table
+    visible: [u8; MAX_RECORDS],
+    shown: usize,
+    /// Which of the visible records the cursor is on
+    row: usize,
+    /// The first visible record on the screen
+    top: usize,
+    /// Which field the cursor is on
+    column: usize,
+    /// The first field on the screen
+    left: usize,
+    search: [u8; MAX_SEARCH],
+    search_len: usize,
+    /// Something to tell the user
+    message: Option<&'static str>,
+}
+
+impl App {
+    /// Make a new database application.
+    ///
+    /// You can give the screen size in characters.
+    pub const fn new(width: u8, height: u8) -> App {
+        App {
+            width,
+            height,
+            stdout: neotron_sdk::stdout(),
+            shell: Shell::new("Database", width, height),
+            keyboard: Keyboard::new(),
+            table: Table::new(),
+            filename: [0; MAX_FILENAME],
+            filename_len: 0,
+            visible: [0; MAX_RECORDS],
+            shown: 0,
+            row: 0,
+            top: 0,
+            column: 0,
+            left: 0,
+            search: [0; MAX_SEARCH],
+            search_len: 0,
+            message: None,
+        }
+    }
+
+    /// Open a database file. If there isn't one, you get a new, empty
+    /// database which is saved under that name.
+    pub fn open(&mut self, filename: &str) -> Result<(), neotron_sdk::Error> {
+        let name = cut(filename, MAX_FILENAME);
+        self.filename[0..name.len()].copy_from_slice(name.as_bytes());
+        self.filename_len = name.len();
+        let path = neotron_sdk::path::Path::new(filename)?;
+        match neotron_sdk::File::open(path, neotron_sdk::Flags::empty()) {
+            Ok(file) => self.table.load(&file)?,
+            Err(_) => {
+                // We make a new one when we run
+            }
+        }
+        Ok(())
+    }
+
+    /// Run the database, until the user quits
+    pub fn run(&mut self) {
+        console::cursor_off(&mut self.stdout);
+        self.shell.theme = neotron_app::preferred_theme();
+        if self.table.field_count() == 0 {
+            self.choose_fields();
+            self.save();
+        }
+        self.rebuild();
+        loop {
+            self.draw_list();
+            let key = self.keyboard.wait();
+            self.message = None;
+            let page = usize::from(self.list_rows());
+            let last_row = self.shown.saturating_sub(1);
+            let last_column = self.table.field_count().saturating_sub(1);
+            match key {
+                KeyEvent::Char('q' | 'Q') => break,
+                KeyEvent::Up => self.row = self.row.saturating_sub(1),
+                KeyEvent::Down => self.row = (self.row + 1).min(last_row),
+                KeyEvent::PageUp => self.row = self.row.saturating_sub(page),
+                KeyEvent::PageDown => self.row = (self.row + page).min(last_row),
+                KeyEvent::Home => self.row = 0,
+                KeyEvent::End => self.row = last_row,
+                KeyEvent::Left => self.column = self.column.saturating_sub(1),
+                KeyEvent::Right | KeyEvent::Tab => self.column = (self.column + 1).min(last_column),
+                KeyEvent::Char('e' | 'E') | KeyEvent::Enter if self.shown > 0 => self.show_record(),
+                KeyEvent::Char('a' | 'A') => self.add_record(),
+                KeyEvent::Char('d' | 'D') | KeyEvent::Backspace if self.shown > 0 => {
+                    let index = self.current();
+                    self.delete_record(index);
+                }
+                KeyEvent::Char('/' | 'f' | 'F') => self.ask_search(),
+                KeyEvent::Escape if self.search_len > 0 => {
+                    self.search_len = 0;
+                    self.rebuild();
+                }
+                KeyEvent::Char('s' | 'S') => {
+                    self.table.sort(self.column);
+                    self.save();
+                    self.rebuild();
+                }
+                KeyEvent::Char('n' | 'N') => self.add_field(),
+                KeyEvent::Char('x' | 'X') => self.export(),
+                _ => {}
+            }
+        }
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        console::clear_screen(&mut self.stdout);
+        console::move_cursor(&mut self.stdout, console::Position::origin());
+        console::cursor_on(&mut self.stdout);
+    }
+
+    /// Ask what fields a new database should have
+    fn choose_fields(&mut self) {
+        self.clear_screen("New database", &[]);
+        self.move_to(2, 2);
+        let _ = self
+            .stdout
+            .write_str("There's no database here yet, so let's make one.");
+        self.move_to(4, 2);
+        let _ = write!(
+            self.stdout,
+            "Type the name of each field (up to {}), pressing Enter after each one.",
+            MAX_FIELDS
+        );
+        self.move_to(5, 2);
+        let _ = self
+            .stdout
+            .write_str("Press Enter on its own when you're done.");
+        self.move_to(6, 2);
+        let _ = self
+            .stdout
+            .write_str("If you don't give any, you'll get an address book.");
+        while self.table.field_count() < MAX_FIELDS {
+            let row = 8 + self.table.field_count() as u8;
+            let mut label = Line::new();
+            let _ = write!(label, "  Field {}: ", self.table.field_count() + 1);
+            let mut input = [0u8; MAX_NAME];
+            match self.prompt(row, label.as_str(), &mut input, 0) {
+                Some(name) if !name.trim().is_empty() => {
+                    self.table.add_field(name);
+                    // The prompt has gone, so leave the name on screen
+                    self.move_to(row, 0);
+                    let _ = write!(self.stdout, "{}{}", label.as_str(), name.trim());
+                }
+                _ => break,
+            }
+        }
+        if self.table.field_count() == 0 {
+            for name in ADDRESS_BOOK {
+                self.table.add_field(name);
+            }
+        }
+    }
+
+    /// Work out which records match the search
+    fn rebuild(&mut self) {
+        let search = core::str::from_utf8(&self.search[0..self.search_len]).unwrap_or("");
+        self.shown = 0;
+        for index in 0..self.table.len() {
+            if self.table.get(index).contains(search) {
+                self.visible[self.shown] = index as u8;
+                self.shown += 1;
+            }
+        }
+        self.row = self.row.min(self.shown.saturating_sub(1));
+    }
+
+    /// The index in the table of the record the cursor is on
+    fn current(&self) -> usize {
+        usize::from(self.visible[self.row])
+    }
+
+    /// Ask what to search for
+    fn ask_search(&mut self) {
+        let mut input = [0u8; MAX_SEARCH];
+        input[0..self.search_len].copy_from_slice(&self.search[0..self.search_len]);
+        let Some(text) = self.prompt(self.height - 3, "Find: ", &mut input, self.search_len) else {
+            return;
+        };
+        let text = text.trim();
+        self.search[0..text.len()].copy_from_slice(text.as_bytes());
+        self.search_len = text.len();
+        self.row = 0;
+        self.rebuild();
+        if self.shown == 0 {
+            self.message = Some("No records match. Press Esc to see them all.");
+        }
+    }
+
+    /// Add a new field to every record
+    fn add_field(&mut self) {
+        if self.table.field_count() == MAX_FIELDS {
+            self.message = Some("There's no room for any more fields.");
+            return;
+        }
+        let mut input = [0u8; MAX_NAME];
+        let Some(name) = self.prompt(self.height - 3, "New field name: ", &mut input, 0) else {
+            return;
+        };
+        if self.table.add_field(name) {
+            self.column = self.table.field_count() - 1;
+            self.save();
+        }
+    }
+
+    /// Ask for the values of a new record, and add it
+    fn add_record(&mut self) {
+        if self.table.len() == MAX_RECORDS {
+            self.message = Some("There's no room for any more records.");
+            return;
+        }
+        let mut record = Record::EMPTY;
+        self.draw_form(&record, None, "New record");
+        for field in 0..self.table.field_count() {
+            if !self.edit_field(&mut record, field) {
+                return;
+            }
+        }
+        if let Some(index) = self.table.add(&record) {
+            self.save();
+            // Show everything, so we can be sure the new record is visible
+            self.search_len = 0;
+            self.rebuild();
+            self.row = self.visible[0..self.shown]
+                .iter()
+                .position(|&i| usize::from(i) == index)
+                .unwrap_or(0);
+        }
+    }
+
+    /// Look at the record the cursor is on, and change it if you like
+    fn show_record(&mut self) {
+        let mut field = 0;
+        loop {
+            let index = self.current();
+            let mut record = *self.table.get(index);
+            let mut title = Line::new();
+            let _ = write!(title, "Record {} of {}", self.row + 1, self.shown);
+            self.draw_form(&record, Some(field), title.as_str());
+            let last_field = self.table.field_count() - 1;
+            match self.keyboard.wait() {
+                KeyEvent::Up => field = field.saturating_sub(1),
+                KeyEvent::Down | KeyEvent::Tab => field = (field + 1).min(last_field),
+                KeyEvent::PageUp | KeyEvent::Left => self.row = self.row.saturating_sub(1),
+                KeyEvent::PageDown | KeyEvent::Right => {
+                    self.row = (self.row + 1).min(self.shown - 1);
+                }
+                KeyEvent::Char('e' | 'E') | KeyEvent::Enter => {
+                    let changed = self.edit_field(&mut record, field);
+                    if changed {
+                        self.table.replace(index, &record);
+                        self.save();
+                        field = (field + 1).min(last_field);
+                    }
+                }
+                KeyEvent::Char('d' | 'D') | KeyEvent::Backspace => {
+                    self.delete_record(index);
+                    if self.shown == 0 {
+                        break;
+                    }
+                }
+                KeyEvent::Char('q' | 'Q') | KeyEvent::Escape => break,
+                _ => {}
+            }
+        }
+        self.message = None;
+    }
+
+    /// Ask for a new value for one field of a record, on the form. Returns
+    /// false if the user pressed Escape.
+    fn edit_field(&mut self, record: &mut Record, field: usize) -> bool {
+        let row = FORM_ROW + field as u8;
+        let mut label = Line::new();
+        let _ = write!(label, "{:>1$}: ", self.table.field_name(field), MAX_NAME);
+        let mut input = [0u8; MAX_VALUE];
+        let current = cut(record.field(field), MAX_VALUE);
+        input[0..current.len()].copy_from_slice(current.as_bytes());
+        let Some(value) = self.prompt(row, label.as_str(), &mut input, current.len()) else {
+            // Put back what was there
+            self.draw_field(record, field, false);
+            return false;
+        };
+        if !record.set_field(field, value.trim()) {
+            self.message = Some("That won't fit.");
+        }
+        self.draw_field(record, field, false);
+        true
+    }
+
+    /// Delete a record, if the user says so
+    fn delete_record(&mut self, index: usize) {
+        let sure = self.shell.confirm(
+            &mut self.stdout,
+            &mut self.keyboard,
+            "Delete",
+            &["Delete this record?"],
+        );
+        // Whatever we go back to draws over the box
+        self.shell.clear_body(&mut self.stdout);
+        if sure {
+            self.table.remove(index);
+            self.save();
+            self.rebuild();
+        }
+    }
+
+    /// Write the table out as a CSV file
+    fn export(&mut self) {
+        let mut input = [0u8; MAX_FILENAME];
+        // Suggest the database's name, with a .CSV extension
+        let filename = &self.filename[0..self.filename_len];
+        let stem = match filename.iter().rposition(|&b| b == b'.') {
+            Some(dot) => &filename[0..dot],
+            None => filename,
+        };
+        let stem = &stem[0..stem.len().min(MAX_FILENAME - 4)];
+        input[0..stem.len()].copy_from_slice(stem);
+        input[stem.len()..stem.len() + 4].copy_from_slice(b".CSV");
+        let len = stem.len() + 4;
+        let Some(filename) = self.prompt(self.height - 3, "Export to: ", &mut input, len) else {
+            return;
+        };
+        self.message = match self.table.export_csv(filename.trim()) {
+            Ok(()) => Some("Exported all the records."),
+            Err(_) => Some("Couldn't write the CSV file!"),
+        };
+    }
+
+    /// Write the table to disk, complaining if we can't
+    fn save(&mut self) {
+        let filename = core::str::from_utf8(&self.filename[0..self.filename_len]).unwrap_or("");
+        if self.table.save(filename).is_err() {
+            self.message = Some("Couldn't save the database!");
+        }
+    }
+
+    /// How many records fit on the screen
+    fn list_rows(&self) -> u8 {
+        self.height - FIRST_ROW - 4
+    }
+
+    /// How wide a column should be in the list
+    fn column_width(&self, field: usize) -> usize {
+        let mut width = self.table.field_name(field).chars().count();
+        for index in 0..self.table.len() {
+            width = width.max(self.table.get(index).field(field).chars().count());
+        }
+        width.clamp(MIN_WIDTH, MAX_WIDTH)
+    }
+
+    /// Draw the list of records
+    fn draw_list(&mut self) {
+        let page = usize::from(self.list_rows());
+        if self.row < self.top {
+            self.top = self.row;
+        } else if self.row >= self.top + page {
+            self.top = self.row + 1 - page;
+        }
+
+        // Work out which columns fit, scrolling so the cursor's is on screen
+        let mut widths = [0usize; MAX_FIELDS];
+        for (field, width) in widths.iter_mut().enumerate().take(self.table.field_count()) {
+            *width = self.column_width(field);
+        }
+        let screen_width = usize::from(self.width) - 1;
+        self.left = self.left.min(self.column);
+        while widths[self.left..=self.column]
+            .iter()
+            .map(|w| w + 2)
+            .sum::<usize>()
+            > screen_width
+            && self.left < self.column
+        {
+            self.left += 1;
+        }
+        let mut right = self.left;
+        let mut used = 0;
+        while right < self.table.field_count() && used + widths[right] + 2 <= screen_width {
+            used += widths[right] + 2;
+            right += 1;
+        }
+        let right = right.max(self.column + 1);
+
+        let mut title = Line::new();
+        let _ = write!(
+            title,
+            "{} - {} records",
+            core::str::from_utf8(&self.filename[0..self.filename_len]).unwrap_or("?"),
+            self.table.len()
+        );
+        if self.search_len > 0 {
+            let _ = write!(
+                title,
+                ", {} matching \"{}\"",
+                self.shown,
+                core::str::from_utf8(&self.search[0..self.search_len]).unwrap_or("?")
+            );
+        }
+        self.shell.draw_title(&mut self.stdout, title.as_str());
+
+        self.move_to(HEADING_ROW, 0);
+        console::set_sgr(
+            &mut self.stdout,
+            [console::SgrParam::Bold, console::SgrParam::FgCyan],
+        );
+        let mut used = 0;
+        for (field, &width) in widths.iter().enumerate().take(right).skip(self.left) {
+            used += width + 2;
+            if field == self.column {
+                console::set_sgr(&mut self.stdout, [console::SgrParam::Reverse]);
+            }
+            let _ = write!(
+                self.stdout,
+                " {:<1$} ",
+                cut(self.table.field_name(field), width),
+                width
+            );
+            if field == self.column {
+                console::set_sgr(
+                    &mut self.stdout,
+                    [
+                        console::SgrParam::Reset,
+                        console::SgrParam::Bold,
+                        console::SgrParam::FgCyan,
+                    ],
+                );
+            }
+        }
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        let _ = write!(self.stdout, "{:1$}", "", screen_width.saturating_sub(used));
+
+        // We draw over the old list, rather than clearing it, so it doesn't
+        // flicker
+        for line in 0..page {
+            let row = self.top + line;
+            if row >= self.shown {
+                self.clear_row(FIRST_ROW + line as u8);
+                continue;
+            }
+            let record = *self.table.get(usize::from(self.visible[row]));
+            self.move_to(FIRST_ROW + line as u8, 0);
+            if row == self.row {
+                console::set_sgr(&mut self.stdout, [console::SgrParam::Reverse]);
+            }
+            for (field, &width) in widths.iter().enumerate().take(right).skip(self.left) {
+                let _ = write!(
+                    self.stdout,
+                    " {:<1$} ",
+                    cut(record.field(field), width),
+                    width
+                );
+            }
+            console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+            let _ = write!(self.stdout, "{:1$}", "", screen_width.saturating_sub(used));
+        }
+        if self.table.is_empty() {
+            self.move_to(FIRST_ROW + 1, 2);
+            let _ = self
+                .stdout
+                .write_str("There are no records yet. Press A to add one.");
+        }
+
+        self.clear_row(self.height - 3);
+        if let Some(message) = self.message {
+            self.move_to(self.height - 3, 0);
+            let _ = self.stdout.write_str(message);
+        }
+        self.clear_row(self.height - 2);
+        self.shell.draw_hints(&mut self.stdout, &LIST_HINTS);
+    }
+
+    /// Draw one record as a form, with a field highlighted if you like
+    fn draw_form(&mut self, record: &Record, selected: Option<usize>, title: &str) {
+        self.clear_screen(title, &FORM_HINTS);
+        for field in 0..self.table.field_count() {
+            self.draw_field(record, field, Some(field) == selected);
+        }
+        if let Some(message) = self.message.take() {
+            self.move_to(self.height - 3, 0);
+            let _ = self.stdout.write_str(message);
+        }
+        self.move_to(self.height - 2, 0);
+        let help = match selected {
+            Some(_) => {
+                "Up/Down: Choose  Enter: Change  PgUp/PgDn: Other records  D: Delete  Esc: Back"
+            }
+            None => "Enter: Next field  Esc: Cancel",
+        };
+        let _ = self.stdout.write_str(help);
+    }
+
+    /// Draw one field of the form
+    fn draw_field(&mut self, record: &Record, field: usize, selected: bool) {
+        let row = FORM_ROW + field as u8;
+        self.clear_row(row);
+        self.move_to(row, 0);
+        console::set_sgr(
+            &mut self.stdout,
+            [console::SgrParam::Bold, console::SgrParam::FgCyan],
+        );
+        let _ = write!(
+            self.stdout,
+            "{:>1$}: ",
+            self.table.field_name(field),
+            MAX_NAME
+        );
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        if selected {
+            console::set_sgr(&mut self.stdout, [console::SgrParam::Reverse]);
+        }
+        let _ = write!(self.stdout, "{:<1$}", record.field(field), MAX_VALUE);
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+    }
+
+    /// Clear the screen, and draw the title bar and key hints
+    fn clear_screen(&mut self, title: &str, hints: &[Hint]) {
+        self.shell.draw(&mut self.stdout, title, hints);
+    }
+
+    /// Read some text on the given row, starting with the first `len` bytes
+    /// of `input`.
+    ///
+    /// Returns `None` if the user pressed Escape.
+    fn prompt<'a, const N: usize>(
+        &mut self,
+        row: u8,
+        prompt: &str,
+        input: &'a mut [u8; N],
+        len: usize,
+    ) -> Option<&'a str> {
+        self.clear_row(row);
+        self.move_to(row, 0);
+        let _ = self.stdout.write_str(prompt);
+        console::cursor_on(&mut self.stdout);
+        let mut editor: Editor<N, 0> = Editor::new();
+        let text = core::str::from_utf8(&input[0..len]).unwrap_or("");
+        let result = editor.edit_line(&mut self.stdout, &mut self.keyboard, text);
+        let len = result.map(|text| {
+            input[0..text.len()].copy_from_slice(text.as_bytes());
+            text.len()
+        });
+        console::cursor_off(&mut self.stdout);
+        self.clear_row(row);
+        core::str::from_utf8(&input[0..len?]).ok()
+    }
+
+    /// Blank out a row
+    fn clear_row(&mut self, row: u8) {
+        self.move_to(row, 0);
+        let _ = write!(self.stdout, "{:1$}", "", usize::from(self.width) - 1);
+    }
+
+    /// Move the cursor
+    fn move_to(&mut self, row: u8, col: u8) {
+        console::move_cursor(&mut self.stdout, console::Position { row, col });
+    }
+}
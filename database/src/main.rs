@@ -0,0 +1,29 @@
+#![cfg_attr(target_os = "none", no_std)]
+#![cfg_attr(target_os = "none", no_main)]
+
+use core::ptr::addr_of_mut;
+
+#[cfg(not(target_os = "none"))]
+fn main() {
+    neotron_sdk::init();
+}
+
+static mut APP: database::App = database::App::new(80, 25);
+
+/// The file we use if you don't give one
+const DEFAULT_FILE: &str = "ADDRESS.DB";
+
+#[no_mangle]
+extern "C" fn neotron_main() -> i32 {
+    neotron_error::exit_code(real_main())
+}
+
+fn real_main() -> Result<(), neotron_sdk::Error> {
+    let app = unsafe { &mut *addr_of_mut!(APP) };
+    match neotron_sdk::arg(0) {
+        Some(filename) => app.open(&filename)?,
+        None => app.open(DEFAULT_FILE)?,
+    }
+    app.run();
+    Ok(())
+}
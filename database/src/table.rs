@@ -0,0 +1,342 @@
+//! The records, and the file we keep them in.
+//!
+//! The file is plain text. The first line holds the field names, and every
+//! line after that is a record. The fields are separated by tabs, so you can
+//! edit the file by hand if you like.
+
+use core::{cmp::Ordering, fmt::Write};
+
+/// The most fields a table can have
+pub const MAX_FIELDS: usize = 8;
+
+/// The longest value a field can hold
+pub const MAX_VALUE: usize = 46;
+
+/// The longest field name
+pub const MAX_NAME: usize = 16;
+
+/// The most records a table can hold
+pub const MAX_RECORDS: usize = 160;
+
+/// How much text a record can hold - enough for every field to be full
+const RECORD_LEN: usize = MAX_FIELDS * (MAX_VALUE + 1);
+
+/// What goes between the fields
+const SEPARATOR: u8 = b'\t';
+
+/// One line of the table
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Record {
+    /// The fields, separated by tabs
+    text: [u8; RECORD_LEN],
+    len: usize,
+}
+
+impl Record {
+    /// A record with every field empty
+    pub const EMPTY: Record = Record {
+        text: [0; RECORD_LEN],
+        len: 0,
+    };
+
+    /// Get a field. Missing fields are empty.
+    pub fn field(&self, index: usize) -> &str {
+        let bytes = self.text[0..self.len]
+            .split(|&b| b == SEPARATOR)
+            .nth(index)
+            .unwrap_or(&[]);
+        match core::str::from_utf8(bytes) {
+            Ok(text) => text,
+            // Probably cut off in the middle of a character
+            Err(e) => core::str::from_utf8(&bytes[0..e.valid_up_to()]).unwrap_or(""),
+        }
+    }
+
+    /// Change a field. Returns false if it won't fit.
+    pub fn set_field(&mut self, index: usize, value: &str) -> bool {
+        if index >= MAX_FIELDS || value.bytes().any(|b| b == SEPARATOR || b == b'\n') {
+            return false;
+        }
+        let mut new = Record::EMPTY;
+        let count = self.field_count().max(index + 1);
+        for i in 0..count {
+            if i > 0 && !new.push(&[SEPARATOR]) {
+                return false;
+            }
+            let text = if i == index { value } else { self.field(i) };
+            if !new.push(text.as_bytes()) {
+                return false;
+            }
+        }
+        *self = new;
+        true
+    }
+
+    /// How many fields the text has in it
+    fn field_count(&self) -> usize {
+        if self.len == 0 {
+            0
+        } else {
+            self.text[0..self.len]
+                .iter()
+                .filter(|&&b| b == SEPARATOR)
+                .count()
+                + 1
+        }
+    }
+
+    /// Does any field contain this text, ignoring case?
+    pub fn contains(&self, pattern: &str) -> bool {
+        let pattern = pattern.as_bytes();
+        pattern.is_empty()
+            || self.text[0..self.len]
+                .windows(pattern.len())
+                .any(|window| window.eq_ignore_ascii_case(pattern))
+    }
+
+    /// Add some bytes on the end. Returns false if they won't fit.
+    fn push(&mut self, bytes: &[u8]) -> bool {
+        let Some(space) = self.text.get_mut(self.len..self.len + bytes.len()) else {
+            return false;
+        };
+        space.copy_from_slice(bytes);
+        self.len += bytes.len();
+        true
+    }
+
+    /// Make a record from a line of the file, cutting it short if it's too long
+    fn from_line(line: &[u8]) -> Record {
+        let mut record = Record::EMPTY;
+        let len = line.len().min(RECORD_LEN);
+        record.text[0..len].copy_from_slice(&line[0..len]);
+        record.len = len;
+        record
+    }
+}
+
+/// A set of records, all with the same fields
+pub struct Table {
+    /// The names of the fields
+    names: Record,
+    field_count: usize,
+    records: [Record; MAX_RECORDS],
+    count: usize,
+}
+
+impl Table {
+    /// Make a table with no fields and no records
+    pub const fn new() -> Table {
+        Table {
+            names: Record::EMPTY,
+            field_count: 0,
+            records: [Record::EMPTY; MAX_RECORDS],
+            count: 0,
+        }
+    }
+
+    /// How many fields each record has
+    pub fn field_count(&self) -> usize {
+        self.field_count
+    }
+
+    /// Get the name of a field
+    pub fn field_name(&self, index: usize) -> &str {
+        self.names.field(index)
+    }
+
+    /// Add a field on the end. Every record gets an empty value for it.
+    ///
+    /// Returns false if there are too many fields already.
+    pub fn add_field(&mut self, name: &str) -> bool {
+        let name = cut(name.trim(), MAX_NAME);
+        if self.field_count == MAX_FIELDS
+            || name.is_empty()
+            || !self.names.set_field(self.field_count, name)
+        {
+            return false;
+        }
+        self.field_count += 1;
+        true
+    }
+
+    /// How many records there are
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    /// Are there no records at all?
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Get a record
+    pub fn get(&self, index: usize) -> &Record {
+        &self.records[index]
+    }
+
+    /// Put a record on the end. Returns its index, or `None` if there's no
+    /// room.
+    pub fn add(&mut self, record: &Record) -> Option<usize> {
+        if self.count == MAX_RECORDS {
+            return None;
+        }
+        self.records[self.count] = *record;
+        self.count += 1;
+        Some(self.count - 1)
+    }
+
+    /// Change a record
+    pub fn replace(&mut self, index: usize, record: &Record) {
+        self.records[index] = *record;
+    }
+
+    /// Throw a record away
+    pub fn remove(&mut self, index: usize) {
+        self.records.copy_within(index + 1..self.count, index);
+        self.count -= 1;
+    }
+
+    /// Put the records in order of one field, ignoring case
+    pub fn sort(&mut self, field: usize) {
+        // An insertion sort, because it's stable and we've no allocator
+        for i in 1..self.count {
+            let mut j = i;
+            while j > 0
+                && compare(&self.records[j - 1], &self.records[j], field) == Ordering::Greater
+            {
+                self.records.swap(j - 1, j);
+                j -= 1;
+            }
+        }
+    }
+
+    /// Read a table from a file
+    pub fn load(&mut self, file: &neotron_sdk::File) -> Result<(), neotron_sdk::Error> {
+        self.names = Record::EMPTY;
+        self.field_count = 0;
+        self.count = 0;
+        let mut buffer = [0u8; 256];
+        let mut line = [0u8; RECORD_LEN];
+        let mut line_len = 0;
+        loop {
+            let n = file.read(&mut buffer)?;
+            if n == 0 {
+                break;
+            }
+            for &byte in &buffer[0..n] {
+                match byte {
+                    b'\n' => {
+                        self.parse_line(&line[0..line_len]);
+                        line_len = 0;
+                    }
+                    b'\r' => {}
+                    _ if line_len < RECORD_LEN => {
+                        line[line_len] = byte;
+                        line_len += 1;
+                    }
+                    _ => {}
+                }
+            }
+        }
+        self.parse_line(&line[0..line_len]);
+        Ok(())
+    }
+
+    /// Deal with one line of the file
+    fn parse_line(&mut self, line: &[u8]) {
+        if line.is_empty() {
+            return;
+        }
+        let record = Record::from_line(line);
+        if self.field_count == 0 {
+            // The first line names the fields
+            for index in 0..record.field_count() {
+                self.add_field(record.field(index));
+            }
+        } else {
+            self.add(&record);
+        }
+    }
+
+    /// Write the table to disk
+    pub fn save(&self, filename: &str) -> Result<(), neotron_sdk::Error> {
+        let path = neotron_sdk::path::Path::new(filename)?;
+        let mut file = neotron_sdk::File::open(
+            path,
+            neotron_sdk::Flags::WRITE | neotron_sdk::Flags::CREATE | neotron_sdk::Flags::TRUNCATE,
+        )?;
+        for record in core::iter::once(&self.names).chain(self.records[0..self.count].iter()) {
+            for field in 0..self.field_count {
+                if field > 0 {
+                    file.write(&[SEPARATOR])?;
+                }
+                file.write(record.field(field).as_bytes())?;
+            }
+            writeln!(file).map_err(|_| neotron_sdk::Error::DeviceSpecific)?;
+        }
+        Ok(())
+    }
+
+    /// Write the table out as comma separated values, with the field names
+    /// as the first row.
+    pub fn export_csv(&self, filename: &str) -> Result<(), neotron_sdk::Error> {
+        let path = neotron_sdk::path::Path::new(filename)?;
+        let mut file = neotron_sdk::File::open(
+            path,
+            neotron_sdk::Flags::WRITE | neotron_sdk::Flags::CREATE | neotron_sdk::Flags::TRUNCATE,
+        )?;
+        for record in core::iter::once(&self.names).chain(self.records[0..self.count].iter()) {
+            for field in 0..self.field_count {
+                if field > 0 {
+                    file.write(b",")?;
+                }
+                write_csv_field(&mut file, record.field(field))
+                    .map_err(|_| neotron_sdk::Error::DeviceSpecific)?;
+            }
+            // CSV wants CRLF line endings
+            file.write(b"\r\n")?;
+        }
+        Ok(())
+    }
+}
+
+impl Default for Table {
+    fn default() -> Self {
+        Table::new()
+    }
+}
+
+/// Write one CSV field, quoting it if it needs it
+fn write_csv_field<W>(out: &mut W, value: &str) -> core::fmt::Result
+where
+    W: core::fmt::Write,
+{
+    if !value.contains([',', '"', '\r', '\n']) && value.trim() == value {
+        return out.write_str(value);
+    }
+    out.write_char('"')?;
+    for (idx, part) in value.split('"').enumerate() {
+        if idx > 0 {
+            // A quote inside a quoted field is doubled up
+            out.write_str("\"\"")?;
+        }
+        out.write_str(part)?;
+    }
+    out.write_char('"')
+}
+
+/// Compare two records by one field, ignoring case
+fn compare(a: &Record, b: &Record, field: usize) -> Ordering {
+    let a = a.field(field).bytes().map(|b| b.to_ascii_lowercase());
+    let b = b.field(field).bytes().map(|b| b.to_ascii_lowercase());
+    a.cmp(b)
+}
+
+/// Cut some text short, so it fits in a space
+pub fn cut(text: &str, width: usize) -> &str {
+    let mut len = text.len().min(width);
+    while !text.is_char_boundary(len) {
+        len -= 1;
+    }
+    &text[0..len]
+}
@@ -0,0 +1,167 @@
+//! Application logic for the demo
+
+#![no_std]
+#![deny(missing_docs)]
+#![deny(unsafe_code)]
+
+use neoplay::{player::Player, sync};
+use neotron_sdk::console;
+
+pub mod scene;
+
+use scene::Scene;
+
+/// How big the screen is
+const SCREEN_WIDTH: usize = 80;
+const SCREEN_HEIGHT: usize = 25;
+
+/// What the demo is drawn on
+pub type Screen = neotron_screen::Screen<SCREEN_WIDTH, SCREEN_HEIGHT>;
+
+/// The sample rate we ask the audio device for
+const SAMPLE_RATE: u32 = 44100;
+
+/// How many frames we show each second. The music player works in ticks of
+/// the same length, so the pictures keep in step with it.
+const FRAME_RATE: u32 = 50;
+
+/// How many audio frames we make for each video frame
+const AUDIO_PER_FRAME: usize = (SAMPLE_RATE / FRAME_RATE) as usize;
+
+/// Represents the demo application
+pub struct App {
+    stdout: neotron_sdk::File,
+    stdin: neotron_sdk::File,
+    screen: Screen,
+    scene: Scene,
+}
+
+impl App {
+    /// Make a new demo.
+    ///
+    /// The scene is laid out for an 80x25 screen, so the size is ignored.
+    pub const fn new(_width: u8, _height: u8) -> App {
+        App {
+            stdout: neotron_sdk::stdout(),
+            stdin: neotron_sdk::stdin(),
+            screen: Screen::new(),
+            scene: Scene::new(),
+        }
+    }
+
+    /// Run the demo until a key is pressed.
+    ///
+    /// If you give us a ProTracker module, we play it (over and over) and
+    /// keep the pictures in time with it. Otherwise it runs in silence.
+    pub fn play(&mut self, music: Option<&[u8]>) {
+        let dsp = if music.is_some() {
+            Self::open_audio()
+        } else {
+            None
+        };
+        let mut player = match (music, dsp.as_ref()) {
+            (Some(data), Some(_)) => Player::new(data, SAMPLE_RATE).ok(),
+            _ => None,
+        };
+        self.scene.message = match (music, dsp.as_ref(), player.as_ref()) {
+            (None, _, _) => None,
+            (Some(_), None, _) => Some("No audio device, so no music"),
+            (Some(_), Some(_), None) => Some("Couldn't understand the music file"),
+            (Some(_), Some(_), Some(_)) => None,
+        };
+
+        console::cursor_off(&mut self.stdout);
+        self.screen.clear();
+        self.screen.invalidate();
+        let mut buffer = [0u8; AUDIO_PER_FRAME * 4];
+        if let Some(dsp) = dsp.as_ref() {
+            // Some silence to give us a head-start
+            for _ in 0..4 {
+                let _ = dsp.write(&buffer);
+            }
+        }
+        let mut frames: u32 = 0;
+        loop {
+            let mut timing = Timing::default();
+            match (player.as_mut(), dsp.as_ref(), music) {
+                (Some(song), Some(dsp), Some(data)) => {
+                    for chunk in buffer.chunks_exact_mut(4) {
+                        let (left, right) = song.next_sample_with(&mut Discard, &mut timing);
+                        let left = left.to_le_bytes();
+                        let right = right.to_le_bytes();
+                        chunk[0] = left[0];
+                        chunk[1] = left[1];
+                        chunk[2] = right[0];
+                        chunk[3] = right[1];
+                    }
+                    // This waits until the audio device has room, which keeps
+                    // us running at the frame rate
+                    let _ = dsp.write(&buffer);
+                    if song.is_finished() {
+                        // Go round again
+                        if let Ok(again) = Player::new(data, SAMPLE_RATE) {
+                            *song = again;
+                        }
+                    }
+                }
+                _ => {
+                    neotron_sdk::delay(core::time::Duration::from_millis(u64::from(
+                        1000 / FRAME_RATE,
+                    )));
+                    // Make up a beat, at 120 beats per minute
+                    timing.beat = frames.is_multiple_of(FRAME_RATE / 2);
+                    timing.new_pattern = frames.is_multiple_of(FRAME_RATE * 8);
+                }
+            }
+            frames = frames.wrapping_add(1);
+            self.scene
+                .frame(&mut self.screen, timing.beat, timing.new_pattern);
+            self.screen.flush(&mut self.stdout);
+            let mut key = [0u8; 1];
+            if let Ok(1) = self.stdin.read(&mut key) {
+                break;
+            }
+        }
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        console::clear_screen(&mut self.stdout);
+        console::move_cursor(&mut self.stdout, console::Position::origin());
+        console::cursor_on(&mut self.stdout);
+    }
+
+    /// Open the audio device, for 16-bit stereo at our sample rate
+    fn open_audio() -> Option<neotron_sdk::File> {
+        let path = neotron_sdk::path::Path::new("AUDIO:").ok()?;
+        let dsp = neotron_sdk::File::open(path, neotron_sdk::Flags::empty()).ok()?;
+        dsp.ioctl(1, 3 << 60 | u64::from(SAMPLE_RATE)).ok()?;
+        Some(dsp)
+    }
+}
+
+/// What the music did during one frame
+#[derive(Debug, Default)]
+struct Timing {
+    /// A beat started
+    beat: bool,
+    /// We moved on to the next pattern. The first one doesn't count, so
+    /// the logo doesn't jump as soon as we start.
+    new_pattern: bool,
+}
+
+impl sync::Hooks for Timing {
+    fn row(&mut self, row: &sync::Row) {
+        self.new_pattern |= row.new_pattern && row.position > 0;
+    }
+
+    fn beat(&mut self, _row: &sync::Row) {
+        self.beat = true;
+    }
+}
+
+/// Throws away the player's trace of each line
+struct Discard;
+
+impl core::fmt::Write for Discard {
+    fn write_str(&mut self, _s: &str) -> core::fmt::Result {
+        Ok(())
+    }
+}
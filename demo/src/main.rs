@@ -0,0 +1,47 @@
+#![cfg_attr(target_os = "none", no_std)]
+#![cfg_attr(target_os = "none", no_main)]
+
+use core::ptr::addr_of_mut;
+
+/// The music we play, if you don't pick any
+const DEFAULT_MUSIC: &str = "DEMO.MOD";
+
+const FILE_BUFFER_LEN: usize = 128 * 1024;
+static mut FILE_BUFFER: [u8; FILE_BUFFER_LEN] = [0u8; FILE_BUFFER_LEN];
+
+#[cfg(not(target_os = "none"))]
+fn main() {
+    neotron_sdk::init();
+}
+
+static mut APP: demo::App = demo::App::new(80, 25);
+
+#[no_mangle]
+extern "C" fn neotron_main() -> i32 {
+    neotron_error::exit_code(real_main())
+}
+
+fn real_main() -> Result<(), neotron_sdk::Error> {
+    let app = unsafe { &mut *addr_of_mut!(APP) };
+    let file_buffer = unsafe { &mut *addr_of_mut!(FILE_BUFFER) };
+    let filename = neotron_sdk::arg(0);
+    let music = match load_music(filename.as_deref().unwrap_or(DEFAULT_MUSIC), file_buffer) {
+        Ok(music) => Some(music),
+        // You asked for this file, so it had better be there
+        Err(e) if filename.is_some() => return Err(e),
+        Err(_) => None,
+    };
+    app.play(music);
+    Ok(())
+}
+
+/// Read a music file into memory
+fn load_music<'a>(
+    filename: &str,
+    file_buffer: &'a mut [u8],
+) -> Result<&'a [u8], neotron_sdk::Error> {
+    let path = neotron_sdk::path::Path::new(filename)?;
+    let f = neotron_sdk::File::open(path, neotron_sdk::Flags::empty())?;
+    let n = f.read(file_buffer)?;
+    Ok(&file_buffer[0..n])
+}
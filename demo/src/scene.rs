@@ -0,0 +1,185 @@
+//! The pictures the demo shows.
+//!
+//! There are raster bars bouncing behind a colour-cycling logo, and a
+//! scroller which waves up and down across the bottom of the screen. The bars
+//! flash on the beat, and the colours move on with each new pattern.
+
+use neotron_screen::{Attr, Colour};
+
+use crate::Screen;
+
+/// One cycle of a sine wave, from -127 to 127
+const SINE: [i8; 64] = [
+    0, 12, 25, 37, 49, 60, 71, 81, 90, 98, 106, 112, 117, 122, 125, 126, 127, 126, 125, 122, 117,
+    112, 106, 98, 90, 81, 71, 60, 49, 37, 25, 12, 0, -12, -25, -37, -49, -60, -71, -81, -90, -98,
+    -106, -112, -117, -122, -125, -126, -127, -126, -125, -122, -117, -112, -106, -98, -90, -81,
+    -71, -60, -49, -37, -25, -12,
+];
+
+/// The logo
+const LOGO: [&str; 4] = [
+    r" _  _ ___ ___ _____ ___  ___  _  _ ",
+    r"| \| | __/ _ \_   _| _ \/ _ \| \| |",
+    r"| .` | _| (_) || | |   / (_) | .` |",
+    r"|_|\_|___\___/ |_| |_|_\\___/|_|\_|",
+];
+
+/// Where the top of the logo goes
+const LOGO_ROW: i16 = 4;
+
+/// The text in the scroller
+const SCROLL_TEXT: &[u8] = b"WELCOME TO THE NEOTRON DEMO ... ALL DONE WITH TEXT \
+    CHARACTERS AND A LITTLE BIT OF MATHS ... THE MUSIC IS A PROTRACKER MODULE, \
+    PLAYED BY THE SAME CODE AS NEOPLAY ... THE BARS FLASH ON THE BEAT ... \
+    GREETINGS TO EVERYONE BUILDING AND HACKING ON NEOTRON ... PRESS ANY KEY \
+    TO LEAVE ...                    ";
+
+/// The middle row of the scroller
+const SCROLL_ROW: i16 = 17;
+
+/// How far the scroller waves up and down
+const SCROLL_HEIGHT: i32 = 4;
+
+/// The shades of a raster bar, top to bottom
+const BAR_SHADES: [char; 5] = ['░', '▒', '█', '▒', '░'];
+
+/// How many raster bars there are
+const BARS: usize = 3;
+
+/// The colours we cycle through
+const PALETTE: [Attr; 12] = [
+    Attr::new(Colour::Red, Colour::Black),
+    Attr::new(Colour::Red, Colour::Black).bold(),
+    Attr::new(Colour::Yellow, Colour::Black),
+    Attr::new(Colour::Yellow, Colour::Black).bold(),
+    Attr::new(Colour::Green, Colour::Black).bold(),
+    Attr::new(Colour::Green, Colour::Black),
+    Attr::new(Colour::Cyan, Colour::Black),
+    Attr::new(Colour::Cyan, Colour::Black).bold(),
+    Attr::new(Colour::Blue, Colour::Black).bold(),
+    Attr::new(Colour::Blue, Colour::Black),
+    Attr::new(Colour::Magenta, Colour::Black),
+    Attr::new(Colour::Magenta, Colour::Black).bold(),
+];
+
+/// How the message on the bottom line looks - only just there
+const MESSAGE: Attr = Attr::new(Colour::Black, Colour::Black).bold();
+
+/// How many frames the bars stay lit after a beat
+const FLASH_FRAMES: u8 = 4;
+
+/// Everything we need to draw the next frame
+pub struct Scene {
+    /// Counts frames
+    time: u32,
+    /// How many more frames the bars stay lit
+    flash: u8,
+    /// Moves on with each new pattern, to change the colours
+    shift: usize,
+    /// How far the scroller has scrolled, in characters
+    scroll: usize,
+    /// Something to show on the bottom line
+    pub message: Option<&'static str>,
+}
+
+impl Scene {
+    /// Make a new scene
+    pub const fn new() -> Scene {
+        Scene {
+            time: 0,
+            flash: 0,
+            shift: 0,
+            scroll: 0,
+            message: None,
+        }
+    }
+
+    /// Draw the next frame.
+    ///
+    /// Set `beat` when the music hits a beat, and `new_pattern` when it moves
+    /// on to a new pattern.
+    pub fn frame(&mut self, screen: &mut Screen, beat: bool, new_pattern: bool) {
+        self.time = self.time.wrapping_add(1);
+        if beat {
+            self.flash = FLASH_FRAMES;
+        } else {
+            self.flash = self.flash.saturating_sub(1);
+        }
+        if new_pattern {
+            self.shift = (self.shift + 1) % PALETTE.len();
+        }
+        if self.time.is_multiple_of(2) {
+            self.scroll = (self.scroll + 1) % SCROLL_TEXT.len();
+        }
+        screen.clear();
+        self.draw_bars(screen);
+        self.draw_logo(screen);
+        self.draw_scroller(screen);
+        let text = self.message.unwrap_or("Press any key to exit");
+        let col = neotron_text::centre_offset(text, screen.width());
+        for (idx, ch) in text.chars().enumerate() {
+            // Let the background show through the gaps
+            if ch != ' ' {
+                screen.set(screen.height() - 1, col + idx, ch, MESSAGE);
+            }
+        }
+    }
+
+    /// Draw the raster bars, bouncing up and down behind the logo
+    fn draw_bars(&self, screen: &mut Screen) {
+        let t = self.time as i32;
+        for bar in 0..BARS {
+            let wave = sine(t * 2 + bar as i32 * 7);
+            let middle = LOGO_ROW + 1 + (wave * 5 / 127) as i16;
+            let colour = PALETTE[(bar * 4 + self.shift) % PALETTE.len()].fg;
+            let attr = if self.flash > 0 {
+                Attr::new(colour, Colour::Black).bold()
+            } else {
+                Attr::new(colour, Colour::Black)
+            };
+            for (idx, shade) in BAR_SHADES.iter().enumerate() {
+                let row = middle - 2 + idx as i16;
+                for col in 0..screen.width() {
+                    screen.set(row as usize, col, *shade, attr);
+                }
+            }
+        }
+    }
+
+    /// Draw the logo, with the colours running across it
+    fn draw_logo(&self, screen: &mut Screen) {
+        let left = neotron_text::centre_offset(LOGO[0], screen.width());
+        let cycle = (self.time / 3) as usize;
+        for (row, line) in LOGO.iter().enumerate() {
+            for (idx, ch) in line.chars().enumerate() {
+                let attr = PALETTE[(idx / 3 + row + cycle) % PALETTE.len()];
+                // The spaces are drawn too, which cuts a hole in the bars so
+                // the logo stands out
+                screen.set(LOGO_ROW as usize + row, left + idx, ch, attr);
+            }
+        }
+    }
+
+    /// Draw the scroller, waving up and down
+    fn draw_scroller(&self, screen: &mut Screen) {
+        let t = self.time as i32;
+        for col in 0..screen.width() {
+            let ch = SCROLL_TEXT[(self.scroll + col) % SCROLL_TEXT.len()];
+            let wave = sine(col as i32 * 2 + t * 3) * SCROLL_HEIGHT / 127;
+            let attr = PALETTE[(col / 4 + self.shift + (self.time / 4) as usize) % PALETTE.len()];
+            let row = SCROLL_ROW + wave as i16;
+            screen.set(row as usize, col, char::from(ch), attr);
+        }
+    }
+}
+
+impl Default for Scene {
+    fn default() -> Self {
+        Scene::new()
+    }
+}
+
+/// Look up a sine wave. A whole cycle is 64 steps.
+fn sine(angle: i32) -> i32 {
+    i32::from(SINE[angle.rem_euclid(64) as usize])
+}
@@ -0,0 +1,194 @@
+//! Working out the differences between two files.
+//!
+//! This is the classic longest-common-subsequence diff. Filling in the whole
+//! table of lengths would take far too much memory, so we only keep two rows
+//! of it, plus one bit per cell saying which way to go when walking back
+//! through it. Lines which are the same at the start and end of both files are
+//! taken off first, so usually only the part which changed needs a table.
+
+use crate::lines::MAX_LINES;
+
+/// How many cells of the table we have room for. That's enough to compare
+/// about 360 lines with 360 other lines.
+pub const MAX_CELLS: usize = 128 * 1024;
+
+/// How many lines of each file we line up at once, if the whole thing won't
+/// fit in the table
+const WINDOW: usize = 360;
+
+/// One step in turning the first file into the second
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Op {
+    /// The next line is in both files
+    Same,
+    /// The next line of the first file isn't in the second
+    Delete,
+    /// The next line of the second file isn't in the first
+    Insert,
+}
+
+/// The differences between two files
+pub struct Diff {
+    ops: [Op; MAX_LINES * 2],
+    len: usize,
+    /// For each cell, is it best to skip a line of the first file?
+    skip_first: [u8; MAX_CELLS / 8],
+    /// Two rows of the length table
+    lengths: [[u16; MAX_LINES + 1]; 2],
+    /// Did we have to line the files up a window at a time?
+    approximate: bool,
+}
+
+impl Diff {
+    /// Make an empty diff
+    pub const fn new() -> Diff {
+        Diff {
+            ops: [Op::Same; MAX_LINES * 2],
+            len: 0,
+            skip_first: [0; MAX_CELLS / 8],
+            lengths: [[0; MAX_LINES + 1]; 2],
+            approximate: false,
+        }
+    }
+
+    /// Compare two files, given the hashes of their lines
+    pub fn compare(&mut self, first: &[u32], second: &[u32]) {
+        self.len = 0;
+        self.approximate = false;
+        let prefix = first
+            .iter()
+            .zip(second.iter())
+            .take_while(|(a, b)| a == b)
+            .count();
+        let suffix = first[prefix..]
+            .iter()
+            .rev()
+            .zip(second[prefix..].iter().rev())
+            .take_while(|(a, b)| a == b)
+            .count();
+        self.push_many(Op::Same, prefix);
+        let first_middle = &first[prefix..first.len() - suffix];
+        let second_middle = &second[prefix..second.len() - suffix];
+        self.lcs_windowed(first_middle, second_middle);
+        self.push_many(Op::Same, suffix);
+    }
+
+    /// Line up two lists of lines, a window at a time if they are too big.
+    ///
+    /// After lining up each pair of windows, we keep everything up to the last
+    /// line they have in common and start the next windows from there. The
+    /// answer might not be the shortest, but it is usually close.
+    fn lcs_windowed(&mut self, first: &[u32], second: &[u32]) {
+        let (mut i, mut j) = (0, 0);
+        while first.len() - i > 0 && second.len() - j > 0 {
+            let first_window = &first[i..first.len().min(i + WINDOW)];
+            let second_window = &second[j..second.len().min(j + WINDOW)];
+            let start = self.len;
+            self.lcs(first_window, second_window);
+            if i + first_window.len() == first.len() && j + second_window.len() == second.len() {
+                return;
+            }
+            self.approximate = true;
+            if let Some(last_same) = self.ops[start..self.len]
+                .iter()
+                .rposition(|op| *op == Op::Same)
+            {
+                self.len = start + last_same + 1;
+            }
+            // If they had nothing in common, we keep all of both windows
+            for op in self.ops[start..self.len].iter() {
+                match op {
+                    Op::Same => {
+                        i += 1;
+                        j += 1;
+                    }
+                    Op::Delete => i += 1,
+                    Op::Insert => j += 1,
+                }
+            }
+        }
+        self.push_many(Op::Delete, first.len() - i);
+        self.push_many(Op::Insert, second.len() - j);
+    }
+
+    /// Line up two lists of lines which differ. They must fit in the table.
+    fn lcs(&mut self, first: &[u32], second: &[u32]) {
+        let n = first.len();
+        let m = second.len();
+        let [even, odd] = &mut self.lengths;
+        odd[0..=m].fill(0);
+        even[0..=m].fill(0);
+        // Work backwards, so we can walk forwards through the answer
+        for i in (0..n).rev() {
+            let (row, next) = if i.is_multiple_of(2) {
+                (&mut *even, &*odd)
+            } else {
+                (&mut *odd, &*even)
+            };
+            row[m] = 0;
+            for j in (0..m).rev() {
+                if first[i] == second[j] {
+                    row[j] = next[j + 1] + 1;
+                    continue;
+                }
+                let cell = i * m + j;
+                let mask = 1 << (cell % 8);
+                if next[j] >= row[j + 1] {
+                    row[j] = next[j];
+                    self.skip_first[cell / 8] |= mask;
+                } else {
+                    row[j] = row[j + 1];
+                    self.skip_first[cell / 8] &= !mask;
+                }
+            }
+        }
+
+        let (mut i, mut j) = (0, 0);
+        while i < n && j < m {
+            let cell = i * m + j;
+            if first[i] == second[j] {
+                self.push(Op::Same);
+                i += 1;
+                j += 1;
+            } else if self.skip_first[cell / 8] & (1 << (cell % 8)) != 0 {
+                self.push(Op::Delete);
+                i += 1;
+            } else {
+                self.push(Op::Insert);
+                j += 1;
+            }
+        }
+        self.push_many(Op::Delete, n - i);
+        self.push_many(Op::Insert, m - j);
+    }
+
+    /// Add a step
+    fn push(&mut self, op: Op) {
+        self.ops[self.len] = op;
+        self.len += 1;
+    }
+
+    /// Add the same step a number of times
+    fn push_many(&mut self, op: Op, count: usize) {
+        for _ in 0..count {
+            self.push(op);
+        }
+    }
+
+    /// Get the steps which turn the first file into the second
+    pub fn ops(&self) -> &[Op] {
+        &self.ops[0..self.len]
+    }
+
+    /// Did we have to line the files up a window at a time? If so, there
+    /// might be a shorter list of changes.
+    pub fn is_approximate(&self) -> bool {
+        self.approximate
+    }
+}
+
+impl Default for Diff {
+    fn default() -> Self {
+        Diff::new()
+    }
+}
@@ -0,0 +1,572 @@
+//! Application logic for the diff viewer
+
+#![no_std]
+#![deny(missing_docs)]
+#![deny(unsafe_code)]
+
+use core::fmt::Write;
+
+use neotron_input::{KeyEvent, Keyboard};
+use neotron_sdk::console;
+use neotron_tui::Line;
+
+pub mod compare;
+pub mod lines;
+
+use compare::{Diff, Op};
+use lines::{Lines, MAX_LINES};
+
+/// The most rows either view can have. Every line of both files, plus some
+/// hunk headings.
+const MAX_ROWS: usize = MAX_LINES * 2 + 100;
+
+/// How many unchanged lines to show either side of a change, in the unified
+/// view
+const CONTEXT: usize = 3;
+
+/// Width of the line numbers in the side-by-side view, including a gap
+const NUMBER_WIDTH: usize = 5;
+
+/// The longest file name we show
+const MAX_NAME: usize = 32;
+
+/// One row on the screen. The numbers are line numbers, counting from zero.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Row {
+    /// A line in both files
+    Same(u16, u16),
+    /// A line only in the first file
+    Delete(u16),
+    /// A line only in the second file
+    Insert(u16),
+    /// A line from the first file, replaced by one from the second. Only in
+    /// the side-by-side view.
+    Change(u16, u16),
+    /// The start of a group of changes, and where it is in each file. Only in
+    /// the unified view.
+    Hunk(u16, u16),
+}
+
+impl Row {
+    /// Is this row part of a change?
+    fn is_change(self) -> bool {
+        matches!(self, Row::Delete(_) | Row::Insert(_) | Row::Change(_, _))
+    }
+
+    /// Which line of the first file is this row near?
+    fn first_line(self) -> Option<u16> {
+        match self {
+            Row::Same(a, _) | Row::Delete(a) | Row::Change(a, _) | Row::Hunk(a, _) => Some(a),
+            Row::Insert(_) => None,
+        }
+    }
+}
+
+/// One of the files we are comparing
+struct Side {
+    file: Option<neotron_sdk::File>,
+    name: [u8; MAX_NAME],
+    name_len: usize,
+    lines: Lines,
+}
+
+impl Side {
+    /// Make an empty side
+    const fn new() -> Side {
+        Side {
+            file: None,
+            name: [0; MAX_NAME],
+            name_len: 0,
+            lines: Lines::new(),
+        }
+    }
+
+    /// Open a file, and find the lines in it
+    fn load(&mut self, filename: &str) -> Result<(), neotron_sdk::Error> {
+        let mut len = filename.len().min(MAX_NAME);
+        while !filename.is_char_boundary(len) {
+            len -= 1;
+        }
+        self.name[0..len].copy_from_slice(&filename.as_bytes()[0..len]);
+        self.name_len = len;
+        let path = neotron_sdk::path::Path::new(filename)?;
+        let file = neotron_sdk::File::open(path, neotron_sdk::Flags::empty())?;
+        self.lines.build(&file)?;
+        self.file = Some(file);
+        Ok(())
+    }
+
+    /// Get the file name
+    fn name(&self) -> &str {
+        core::str::from_utf8(&self.name[0..self.name_len]).unwrap_or("?")
+    }
+}
+
+/// Represents the diff viewer
+pub struct Viewer {
+    width: u8,
+    height: u8,
+    stdout: neotron_sdk::File,
+    keyboard: Keyboard,
+    sides: [Side; 2],
+    diff: Diff,
+    rows: [Row; MAX_ROWS],
+    row_count: usize,
+    /// Is this the unified view, rather than side-by-side?
+    unified: bool,
+    /// Which row is at the top of the screen
+    top: usize,
+    /// How many groups of changes there are
+    changes: usize,
+    /// How many lines are only in the first file
+    deleted: usize,
+    /// How many lines are only in the second file
+    inserted: usize,
+    message: Option<&'static str>,
+}
+
+impl Viewer {
+    /// Make a new diff viewer.
+    ///
+    /// You can give the screen size in characters.
+    pub const fn new(width: u8, height: u8) -> Viewer {
+        Viewer {
+            width,
+            height,
+            stdout: neotron_sdk::stdout(),
+            keyboard: Keyboard::new(),
+            sides: [Side::new(), Side::new()],
+            diff: Diff::new(),
+            rows: [Row::Same(0, 0); MAX_ROWS],
+            row_count: 0,
+            unified: false,
+            top: 0,
+            changes: 0,
+            deleted: 0,
+            inserted: 0,
+            message: None,
+        }
+    }
+
+    /// Load two files, and compare them
+    pub fn load_files(&mut self, first: &str, second: &str) -> Result<(), neotron_sdk::Error> {
+        self.sides[0].load(first)?;
+        self.sides[1].load(second)?;
+        self.diff
+            .compare(self.sides[0].lines.hashes(), self.sides[1].lines.hashes());
+        self.changes = 0;
+        self.deleted = 0;
+        self.inserted = 0;
+        let mut previous = Op::Same;
+        for op in self.diff.ops().iter().copied() {
+            match op {
+                Op::Same => {}
+                Op::Delete => self.deleted += 1,
+                Op::Insert => self.inserted += 1,
+            }
+            if op != Op::Same && previous == Op::Same {
+                self.changes += 1;
+            }
+            previous = op;
+        }
+        self.message = if self.diff.is_approximate() {
+            Some("Big changes, so lined up in pieces - the diff may not be the shortest")
+        } else if self.sides.iter().any(|side| side.lines.is_truncated()) {
+            Some("File too long - only comparing the start")
+        } else {
+            None
+        };
+        self.top = 0;
+        self.build_rows();
+        Ok(())
+    }
+
+    /// Are the files the same? Only makes sense after loading them.
+    pub fn is_same(&self) -> bool {
+        self.changes == 0
+    }
+
+    /// Choose the unified view, rather than side-by-side
+    pub fn set_unified(&mut self, unified: bool) {
+        if unified != self.unified {
+            self.unified = unified;
+            self.build_rows();
+        }
+    }
+
+    /// Show the differences, until the user quits
+    pub fn run(&mut self) {
+        console::cursor_off(&mut self.stdout);
+        console::clear_screen(&mut self.stdout);
+        loop {
+            self.draw();
+            let key = self.keyboard.wait();
+            self.message = None;
+            let page = usize::from(self.page_rows());
+            let last = self.row_count.saturating_sub(page);
+            match key {
+                KeyEvent::Char('q' | 'Q') => break,
+                KeyEvent::Up => self.top = self.top.saturating_sub(1),
+                KeyEvent::Down => self.top = (self.top + 1).min(last),
+                KeyEvent::PageUp => self.top = self.top.saturating_sub(page),
+                KeyEvent::PageDown => self.top = (self.top + page).min(last),
+                KeyEvent::Home => self.top = 0,
+                KeyEvent::End => self.top = last,
+                KeyEvent::Char('n') => self.next_change(),
+                KeyEvent::Char('p') => self.previous_change(),
+                KeyEvent::Char('v') | KeyEvent::Tab => self.set_unified(!self.unified),
+                _ => {}
+            }
+        }
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        console::clear_screen(&mut self.stdout);
+        console::move_cursor(&mut self.stdout, console::Position::origin());
+        console::cursor_on(&mut self.stdout);
+    }
+
+    /// Work out what goes on each row of the current view.
+    ///
+    /// We try to keep the same part of the files on the screen.
+    fn build_rows(&mut self) {
+        let top_line = self.rows[0..self.row_count]
+            .get(self.top)
+            .and_then(|row| row.first_line());
+        self.row_count = 0;
+        if self.unified {
+            self.build_unified();
+        } else {
+            self.build_side_by_side();
+        }
+        self.top = match top_line {
+            Some(line) => self.rows[0..self.row_count]
+                .iter()
+                .position(|row| row.first_line().is_some_and(|first| first >= line))
+                .unwrap_or(0),
+            None => 0,
+        };
+    }
+
+    /// Lay out the unified view.
+    ///
+    /// Each change gets a row of its own, and we only show a few unchanged
+    /// lines around each group of changes.
+    fn build_unified(&mut self) {
+        let len = self.diff.ops().len();
+        let mut next_change = 0;
+        let mut last_change = None;
+        let mut in_hunk = false;
+        let (mut a, mut b) = (0, 0);
+        for k in 0..len {
+            let op = self.diff.ops()[k];
+            next_change = next_change.max(k);
+            while next_change < len && self.diff.ops()[next_change] == Op::Same {
+                next_change += 1;
+            }
+            let near_last = last_change.is_some_and(|change| k - change <= CONTEXT);
+            let near_next = next_change < len && next_change - k <= CONTEXT;
+            let show = op != Op::Same || near_last || near_next;
+            if show && !in_hunk {
+                self.push_row(Row::Hunk(a, b));
+            }
+            in_hunk = show;
+            match op {
+                Op::Same => {
+                    if show {
+                        self.push_row(Row::Same(a, b));
+                    }
+                    a += 1;
+                    b += 1;
+                }
+                Op::Delete => {
+                    self.push_row(Row::Delete(a));
+                    last_change = Some(k);
+                    a += 1;
+                }
+                Op::Insert => {
+                    self.push_row(Row::Insert(b));
+                    last_change = Some(k);
+                    b += 1;
+                }
+            }
+        }
+    }
+
+    /// Lay out the side-by-side view.
+    ///
+    /// Lines which were replaced go next to the lines which replaced them.
+    fn build_side_by_side(&mut self) {
+        let len = self.diff.ops().len();
+        let (mut a, mut b) = (0, 0);
+        let mut k = 0;
+        while k < len {
+            if self.diff.ops()[k] == Op::Same {
+                self.push_row(Row::Same(a, b));
+                a += 1;
+                b += 1;
+                k += 1;
+                continue;
+            }
+            let (mut deleted, mut inserted) = (0, 0);
+            while k < len && self.diff.ops()[k] != Op::Same {
+                if self.diff.ops()[k] == Op::Delete {
+                    deleted += 1;
+                } else {
+                    inserted += 1;
+                }
+                k += 1;
+            }
+            for i in 0..deleted.max(inserted) {
+                self.push_row(match (i < deleted, i < inserted) {
+                    (true, true) => Row::Change(a + i, b + i),
+                    (true, false) => Row::Delete(a + i),
+                    _ => Row::Insert(b + i),
+                });
+            }
+            a += deleted;
+            b += inserted;
+        }
+    }
+
+    /// Add a row to the view, if there is room
+    fn push_row(&mut self, row: Row) {
+        if self.row_count < MAX_ROWS {
+            self.rows[self.row_count] = row;
+            self.row_count += 1;
+        }
+    }
+
+    /// Scroll so the next change is near the top of the screen
+    fn next_change(&mut self) {
+        // Skip the rest of any change at the top of the screen
+        let mut idx = self.top + Self::LEAD_IN;
+        while idx < self.row_count && self.rows[idx].is_change() {
+            idx += 1;
+        }
+        match self.rows[0..self.row_count]
+            .iter()
+            .skip(idx)
+            .position(|row| row.is_change())
+        {
+            Some(offset) => self.top = (idx + offset).saturating_sub(Self::LEAD_IN),
+            None => self.message = Some("No more changes"),
+        }
+    }
+
+    /// Scroll so the previous change is near the top of the screen
+    fn previous_change(&mut self) {
+        let mut idx = (self.top + Self::LEAD_IN).min(self.row_count);
+        // Skip back past any change we are on, and the gap before it
+        while idx > 0 && self.rows[idx - 1].is_change() {
+            idx -= 1;
+        }
+        while idx > 0 && !self.rows[idx - 1].is_change() {
+            idx -= 1;
+        }
+        if idx == 0 {
+            self.message = Some("No earlier changes");
+            return;
+        }
+        // Now find the start of that change
+        while idx > 0 && self.rows[idx - 1].is_change() {
+            idx -= 1;
+        }
+        self.top = idx.saturating_sub(Self::LEAD_IN);
+    }
+
+    /// How many rows to leave above a change we jump to
+    const LEAD_IN: usize = 2;
+
+    /// Draw the screen
+    fn draw(&mut self) {
+        let width = usize::from(self.width);
+
+        // Title bar
+        self.move_to(0, 0);
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reverse]);
+        let mut title = Line::new();
+        let _ = write!(
+            title,
+            " {} vs {} - {} change{}, -{} +{} lines",
+            self.sides[0].name(),
+            self.sides[1].name(),
+            self.changes,
+            if self.changes == 1 { "" } else { "s" },
+            self.deleted,
+            self.inserted
+        );
+        let _ = write!(self.stdout, "{:<width$.width$}", title.as_str());
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+
+        for screen_row in 0..self.page_rows() {
+            self.move_to(1 + screen_row, 0);
+            let idx = self.top + usize::from(screen_row);
+            match self.rows[0..self.row_count].get(idx).copied() {
+                Some(row) if self.unified => self.draw_unified(idx, row),
+                Some(row) => self.draw_side_by_side(row),
+                None => {
+                    let _ = write!(self.stdout, "{:width$}", "");
+                }
+            }
+        }
+
+        self.move_to(self.height - 1, 0);
+        let help = match self.message {
+            Some(message) => message,
+            None if self.unified => {
+                "Arrows: scroll  n/p: next/previous change  Tab: side by side  q: quit"
+            }
+            None => "Arrows: scroll  n/p: next/previous change  Tab: unified  q: quit",
+        };
+        let _ = write!(self.stdout, "{:<width$.width$}", help);
+    }
+
+    /// Draw one row of the unified view
+    fn draw_unified(&mut self, idx: usize, row: Row) {
+        let text_width = usize::from(self.width) - 1;
+        match row {
+            Row::Same(a, _) => {
+                let _ = self.stdout.write_str(" ");
+                self.write_line(0, a, text_width);
+            }
+            Row::Delete(a) | Row::Change(a, _) => {
+                console::set_sgr(&mut self.stdout, [console::SgrParam::FgRed]);
+                let _ = self.stdout.write_str("-");
+                self.write_line(0, a, text_width);
+            }
+            Row::Insert(b) => {
+                console::set_sgr(&mut self.stdout, [console::SgrParam::FgGreen]);
+                let _ = self.stdout.write_str("+");
+                self.write_line(1, b, text_width);
+            }
+            Row::Hunk(a, b) => {
+                // Count the lines in this hunk
+                let (mut a_len, mut b_len) = (0, 0);
+                for row in self.rows[idx + 1..self.row_count].iter() {
+                    match row {
+                        Row::Same(_, _) | Row::Change(_, _) => {
+                            a_len += 1;
+                            b_len += 1;
+                        }
+                        Row::Delete(_) => a_len += 1,
+                        Row::Insert(_) => b_len += 1,
+                        Row::Hunk(_, _) => break,
+                    }
+                }
+                console::set_sgr(&mut self.stdout, [console::SgrParam::FgCyan]);
+                let mut text = Line::new();
+                let _ = write!(text, "@@ -{},{} +{},{} @@", a + 1, a_len, b + 1, b_len);
+                let width = usize::from(self.width);
+                let _ = write!(self.stdout, "{:<width$.width$}", text.as_str());
+            }
+        }
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+    }
+
+    /// Draw one row of the side-by-side view
+    fn draw_side_by_side(&mut self, row: Row) {
+        let half = (usize::from(self.width) - 1) / 2;
+        let (left, right, separator) = match row {
+            Row::Same(a, b) => (Some(a), Some(b), ' '),
+            Row::Delete(a) => (Some(a), None, '<'),
+            Row::Insert(b) => (None, Some(b), '>'),
+            Row::Change(a, b) => (Some(a), Some(b), '|'),
+            Row::Hunk(_, _) => (None, None, ' '),
+        };
+        let changed = row.is_change();
+        for (side, line) in [(0, left), (1, right)] {
+            if side == 1 {
+                let _ = write!(self.stdout, "{}", separator);
+            }
+            let Some(line) = line else {
+                let _ = write!(self.stdout, "{:half$}", "");
+                continue;
+            };
+            if changed {
+                let colour = if side == 0 {
+                    console::SgrParam::FgRed
+                } else {
+                    console::SgrParam::FgGreen
+                };
+                console::set_sgr(&mut self.stdout, [colour]);
+            }
+            let _ = write!(
+                self.stdout,
+                "{:>w$} ",
+                u32::from(line) + 1,
+                w = NUMBER_WIDTH - 1
+            );
+            self.write_line(side, line, half - NUMBER_WIDTH);
+            console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        }
+        // Fill the odd column, if there is one
+        let used = half * 2 + 1;
+        if used < usize::from(self.width) {
+            let _ = self.stdout.write_str(" ");
+        }
+    }
+
+    /// Write a line from one of the files, padded or cut to the given width
+    fn write_line(&mut self, side: usize, line: u16, width: usize) {
+        let mut buffer = [0u8; 256];
+        let mut count = 0;
+        let side = &self.sides[side];
+        if let Some(file) = side.file.as_ref() {
+            let offset = side.lines.offset(usize::from(line));
+            if file.seek_set(u64::from(offset)).is_ok() {
+                count = file.read(&mut buffer).unwrap_or(0);
+            }
+        }
+        // The bytes go out as they are, so they can't go through a `Line`
+        let mut out = [0u8; 256];
+        let mut len = 0;
+        let mut push = |byte: u8| {
+            if len < out.len() {
+                out[len] = byte;
+                len += 1;
+            }
+        };
+        let mut column = 0;
+        for byte in buffer[0..count].iter().copied() {
+            if byte == b'\n' || column == width {
+                break;
+            }
+            match byte {
+                b'\r' => {}
+                b'\t' => {
+                    let stop = ((column / 4) + 1) * 4;
+                    while column < stop.min(width) {
+                        push(b' ');
+                        column += 1;
+                    }
+                }
+                0x00..=0x1F | 0x7F => {
+                    push(b'.');
+                    column += 1;
+                }
+                _ => {
+                    // Only count the first byte of each UTF-8 character
+                    if byte & 0xC0 != 0x80 {
+                        column += 1;
+                    }
+                    push(byte);
+                }
+            }
+        }
+        // Don't leave half a character on the end
+        while len > 0 && core::str::from_utf8(&out[0..len]).is_err() {
+            len -= 1;
+        }
+        let _ = self.stdout.write(&out[0..len]);
+        let _ = write!(self.stdout, "{:w$}", "", w = width - column);
+    }
+
+    /// How many rows of the diff fit on the screen
+    fn page_rows(&self) -> u8 {
+        self.height.saturating_sub(2).max(1)
+    }
+
+    /// Move the cursor
+    fn move_to(&mut self, row: u8, col: u8) {
+        console::move_cursor(&mut self.stdout, console::Position { row, col });
+    }
+}
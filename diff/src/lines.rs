@@ -0,0 +1,115 @@
+//! Finding the lines in a file.
+//!
+//! We don't keep the text of each line in memory, only where it starts and a
+//! hash of what is in it. Comparing hashes is enough to line the files up, and
+//! we go back to the file for the text when we need to show it.
+
+/// The most lines we look at in each file
+pub const MAX_LINES: usize = 1500;
+
+/// FNV-1a hash starting value
+const FNV_OFFSET: u32 = 0x811C_9DC5;
+
+/// FNV-1a hash multiplier
+const FNV_PRIME: u32 = 0x0100_0193;
+
+/// The lines in a file
+pub struct Lines {
+    offsets: [u32; MAX_LINES],
+    hashes: [u32; MAX_LINES],
+    count: usize,
+    truncated: bool,
+}
+
+impl Lines {
+    /// Make an empty list of lines
+    pub const fn new() -> Lines {
+        Lines {
+            offsets: [0; MAX_LINES],
+            hashes: [0; MAX_LINES],
+            count: 0,
+            truncated: false,
+        }
+    }
+
+    /// Read through a file, finding the lines.
+    ///
+    /// Carriage returns are ignored, so DOS and UNIX files compare the same.
+    pub fn build(&mut self, file: &neotron_sdk::File) -> Result<(), neotron_sdk::Error> {
+        self.count = 0;
+        self.truncated = false;
+        file.seek_set(0)?;
+        let mut buffer = [0u8; 512];
+        let mut offset: u32 = 0;
+        let mut start = 0;
+        let mut hash = FNV_OFFSET;
+        let mut in_line = false;
+        loop {
+            let count = file.read(&mut buffer)?;
+            if count == 0 {
+                break;
+            }
+            for byte in buffer[0..count].iter().copied() {
+                if !in_line {
+                    start = offset;
+                    hash = FNV_OFFSET;
+                    in_line = true;
+                }
+                offset += 1;
+                match byte {
+                    b'\n' => {
+                        if !self.push(start, hash) {
+                            return Ok(());
+                        }
+                        in_line = false;
+                    }
+                    b'\r' => {}
+                    _ => hash = (hash ^ u32::from(byte)).wrapping_mul(FNV_PRIME),
+                }
+            }
+        }
+        // The last line might not have a newline on the end
+        if in_line {
+            self.push(start, hash);
+        }
+        Ok(())
+    }
+
+    /// Add a line, if there is room
+    fn push(&mut self, offset: u32, hash: u32) -> bool {
+        if self.count == MAX_LINES {
+            self.truncated = true;
+            return false;
+        }
+        self.offsets[self.count] = offset;
+        self.hashes[self.count] = hash;
+        self.count += 1;
+        true
+    }
+
+    /// How many lines we found
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Did we stop before the end of the file?
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+
+    /// Get the hashes of all the lines
+    pub fn hashes(&self) -> &[u32] {
+        &self.hashes[0..self.count]
+    }
+
+    /// Where a line starts in the file
+    pub fn offset(&self, line: usize) -> u32 {
+        self.offsets[line]
+    }
+}
+
+impl Default for Lines {
+    fn default() -> Self {
+        Lines::new()
+    }
+}
@@ -0,0 +1,42 @@
+#![cfg_attr(target_os = "none", no_std)]
+#![cfg_attr(target_os = "none", no_main)]
+
+use core::{fmt::Write, ptr::addr_of_mut};
+
+#[cfg(not(target_os = "none"))]
+fn main() {
+    neotron_sdk::init();
+}
+
+static mut VIEWER: diff::Viewer = diff::Viewer::new(80, 25);
+
+#[no_mangle]
+extern "C" fn neotron_main() -> i32 {
+    neotron_error::exit_code(real_main())
+}
+
+fn real_main() -> Result<(), neotron_sdk::Error> {
+    let viewer = unsafe { &mut *addr_of_mut!(VIEWER) };
+    let mut stdout = neotron_sdk::stdout();
+    let mut arg_idx = 0;
+    if neotron_sdk::arg(0).is_some_and(|arg| arg == "-u") {
+        viewer.set_unified(true);
+        arg_idx += 1;
+    }
+    let (Some(first), Some(second)) = (neotron_sdk::arg(arg_idx), neotron_sdk::arg(arg_idx + 1))
+    else {
+        let _ = writeln!(stdout, "Usage: diff [-u] <first file> <second file>");
+        let _ = writeln!(
+            stdout,
+            "  -u starts in the unified view, rather than side by side"
+        );
+        return Err(neotron_sdk::Error::InvalidArg);
+    };
+    viewer.load_files(&first, &second)?;
+    if viewer.is_same() {
+        let _ = writeln!(stdout, "The files are the same");
+        return Ok(());
+    }
+    viewer.run();
+    Ok(())
+}
@@ -0,0 +1,44 @@
+//! Timing, using the real-time clock.
+//!
+//! The SDK doesn't give us a fine-grained timer, so we time each test by
+//! running it for a whole number of seconds on the real-time clock. We wait
+//! for the seconds to tick over before we start, so the only error is in
+//! noticing when the last second has ended.
+
+/// How many seconds there are in a day
+const SECONDS_PER_DAY: u32 = 24 * 60 * 60;
+
+/// Read the real-time clock, as the number of seconds since midnight.
+///
+/// The clock gives us text like `2024-01-31 12:34:56`.
+pub fn now() -> Option<u32> {
+    let path = neotron_sdk::path::Path::new("RTC:").ok()?;
+    let rtc = neotron_sdk::File::open(path, neotron_sdk::Flags::empty()).ok()?;
+    let mut buffer = [0u8; 32];
+    let n = rtc.read(&mut buffer).ok()?;
+    let text = core::str::from_utf8(&buffer[0..n]).ok()?;
+    let time = text.trim().rsplit(' ').next()?;
+    let mut parts = time.split(':').map(|part| part.parse::<u32>().ok());
+    let hours = parts.next()??;
+    let minutes = parts.next()??;
+    let seconds = parts.next()??;
+    Some(hours * 3600 + minutes * 60 + seconds)
+}
+
+/// How many seconds have passed between two readings of the clock.
+///
+/// Copes with the clock going past midnight.
+pub fn elapsed(start: u32, end: u32) -> u32 {
+    (end + SECONDS_PER_DAY - start) % SECONDS_PER_DAY
+}
+
+/// Wait until the seconds tick over, and return the new time
+pub fn next_second() -> Option<u32> {
+    let start = now()?;
+    loop {
+        let time = now()?;
+        if time != start {
+            return Some(time);
+        }
+    }
+}
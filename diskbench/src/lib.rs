@@ -0,0 +1,340 @@
+//! Application logic for the disk benchmark
+
+#![no_std]
+#![deny(missing_docs)]
+#![deny(unsafe_code)]
+
+use core::fmt::Write;
+
+use neotron_sdk::Error;
+use neotron_tui::Line;
+
+pub mod clock;
+
+/// How big a scratch file we make, if we aren't told
+pub const DEFAULT_SIZE_KIB: u32 = 512;
+
+/// The name of the scratch file we create, and delete afterwards
+const FILE_NAME: &str = "BENCH.TMP";
+
+/// The smallest scratch file we will use
+const MIN_SIZE_KIB: u32 = 16;
+
+/// The largest scratch file we will use
+const MAX_SIZE_KIB: u32 = 64 * 1024;
+
+/// How big the sequential transfers are
+const BLOCK_LEN: usize = 8192;
+
+/// How big the random transfers are - one SD card sector
+const SECTOR_LEN: usize = 512;
+
+/// How long we run each test for
+const TEST_SECONDS: u32 = 5;
+
+/// How many transfers we do between looking at the clock
+const BATCH: u32 = 8;
+
+/// Mixed in to the test data, so a blank disk doesn't look like good data
+const PATTERN: u32 = 0x5EED_F00D;
+
+/// The tests we run, in the order we run them
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Test {
+    /// Write the file from start to end, in large blocks
+    SequentialWrite,
+    /// Read the file from start to end, in large blocks
+    SequentialRead,
+    /// Write single sectors, all over the file
+    RandomWrite,
+    /// Read single sectors, all over the file
+    RandomRead,
+}
+
+impl Test {
+    /// All the tests
+    pub const ALL: [Test; 4] = [
+        Test::SequentialWrite,
+        Test::SequentialRead,
+        Test::RandomWrite,
+        Test::RandomRead,
+    ];
+
+    /// The name of the test, for the results table
+    pub fn name(self) -> &'static str {
+        match self {
+            Test::SequentialWrite => "Sequential write",
+            Test::SequentialRead => "Sequential read",
+            Test::RandomWrite => "Random write",
+            Test::RandomRead => "Random read",
+        }
+    }
+
+    /// How many bytes each transfer moves
+    pub fn block_len(self) -> usize {
+        match self {
+            Test::SequentialWrite | Test::SequentialRead => BLOCK_LEN,
+            Test::RandomWrite | Test::RandomRead => SECTOR_LEN,
+        }
+    }
+
+    /// Does this test write to the disk?
+    pub fn is_write(self) -> bool {
+        matches!(self, Test::SequentialWrite | Test::RandomWrite)
+    }
+
+    /// Does this test jump about in the file?
+    pub fn is_random(self) -> bool {
+        matches!(self, Test::RandomWrite | Test::RandomRead)
+    }
+}
+
+/// The results of one test
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct Outcome {
+    /// How many transfers we did
+    pub transfers: u32,
+    /// How many bytes we moved
+    pub bytes: u64,
+    /// How long it took
+    pub seconds: u32,
+    /// How many reads came back short, or with the wrong data
+    pub errors: u32,
+}
+
+impl Outcome {
+    /// The throughput, in KiB per second
+    pub fn kib_per_second(&self) -> u64 {
+        (self.bytes / 1024)
+            .checked_div(u64::from(self.seconds))
+            .unwrap_or(0)
+    }
+
+    /// The average time each transfer took, in microseconds
+    pub fn latency_us(&self) -> u64 {
+        (u64::from(self.seconds) * 1_000_000)
+            .checked_div(u64::from(self.transfers))
+            .unwrap_or(0)
+    }
+}
+
+/// Represents the disk benchmark
+pub struct Bench {
+    stdout: neotron_sdk::File,
+    /// Where the scratch file lives
+    path: Line,
+    /// How big the scratch file is, in bytes
+    file_len: u32,
+    /// Where the next sequential transfer goes
+    position: u32,
+    buffer: [u8; BLOCK_LEN],
+}
+
+impl Bench {
+    /// Make a new benchmark
+    pub const fn new() -> Bench {
+        Bench {
+            stdout: neotron_sdk::stdout(),
+            path: Line::new(),
+            file_len: 0,
+            position: 0,
+            buffer: [0; BLOCK_LEN],
+        }
+    }
+
+    /// Run all the tests, using a scratch file in the given directory.
+    ///
+    /// The directory can be a drive, like `0:`, or empty for the current
+    /// directory. The results are printed as we go.
+    pub fn run(&mut self, directory: &str, size_kib: u32) -> Result<(), Error> {
+        if !(MIN_SIZE_KIB..=MAX_SIZE_KIB).contains(&size_kib) {
+            let _ = writeln!(
+                self.stdout,
+                "The file size must be between {} and {} KiB",
+                MIN_SIZE_KIB, MAX_SIZE_KIB
+            );
+            return Err(Error::InvalidArg);
+        }
+        if clock::now().is_none() {
+            let _ = writeln!(self.stdout, "This benchmark needs a real-time clock");
+            return Err(Error::DeviceSpecific);
+        }
+        self.file_len = size_kib * 1024 / BLOCK_LEN as u32 * BLOCK_LEN as u32;
+        self.path = Line::new();
+        let separator = if directory.is_empty() || directory.ends_with('/') {
+            ""
+        } else {
+            "/"
+        };
+        write!(self.path, "{}{}{}", directory, separator, FILE_NAME)
+            .map_err(|_| Error::InvalidPath)?;
+
+        let _ = writeln!(
+            self.stdout,
+            "Testing with {} ({} KiB), {} seconds per test\n",
+            self.path.as_str(),
+            self.file_len / 1024,
+            TEST_SECONDS
+        );
+        let _ = writeln!(
+            self.stdout,
+            "Test              Block  Transfers   KiB/s   Latency  Errors"
+        );
+        let _ = writeln!(
+            self.stdout,
+            "----------------  -----  ---------  ------  --------  ------"
+        );
+        let result = self.run_all();
+        // The SDK can't delete files, so empty the scratch file rather than
+        // leave it taking up room
+        if let Ok(path) = neotron_sdk::path::Path::new(self.path.as_str()) {
+            let flags = neotron_sdk::Flags::WRITE | neotron_sdk::Flags::TRUNCATE;
+            if neotron_sdk::File::open(path, flags).is_ok() {
+                let _ = writeln!(
+                    self.stdout,
+                    "\n{} is now empty, and can be deleted",
+                    self.path.as_str()
+                );
+            }
+        }
+        result
+    }
+
+    /// Run each test in turn, and print the results
+    fn run_all(&mut self) -> Result<(), Error> {
+        let mut errors = 0;
+        for test in Test::ALL {
+            let _ = write!(self.stdout, "{:<16}  {:>5}", test.name(), test.block_len());
+            let outcome = self.run_test(test)?;
+            let latency = outcome.latency_us();
+            let _ = writeln!(
+                self.stdout,
+                "  {:>9}  {:>6}  {:>3}.{:03}ms  {:>6}",
+                outcome.transfers,
+                outcome.kib_per_second(),
+                latency / 1000,
+                latency % 1000,
+                outcome.errors
+            );
+            errors += outcome.errors;
+            if test == Test::SequentialWrite {
+                // The random tests need the whole file to be there
+                self.fill()?;
+            }
+        }
+        if errors != 0 {
+            let _ = writeln!(
+                self.stdout,
+                "\nWarning: {} transfers gave bad data. Check your disk!",
+                errors
+            );
+        }
+        Ok(())
+    }
+
+    /// Run one test for [`TEST_SECONDS`]
+    fn run_test(&mut self, test: Test) -> Result<Outcome, Error> {
+        let flags = match test {
+            Test::SequentialWrite => {
+                neotron_sdk::Flags::WRITE
+                    | neotron_sdk::Flags::CREATE
+                    | neotron_sdk::Flags::TRUNCATE
+            }
+            Test::RandomWrite => neotron_sdk::Flags::WRITE,
+            Test::SequentialRead | Test::RandomRead => neotron_sdk::Flags::empty(),
+        };
+        let path = neotron_sdk::path::Path::new(self.path.as_str())?;
+        let file = neotron_sdk::File::open(path, flags)?;
+        self.position = 0;
+        let mut outcome = Outcome::default();
+        let start = clock::next_second().ok_or(Error::DeviceSpecific)?;
+        loop {
+            for _ in 0..BATCH {
+                if !self.transfer(&file, test)? {
+                    outcome.errors += 1;
+                }
+                outcome.transfers += 1;
+                outcome.bytes += test.block_len() as u64;
+            }
+            let now = clock::now().ok_or(Error::DeviceSpecific)?;
+            outcome.seconds = clock::elapsed(start, now);
+            if outcome.seconds >= TEST_SECONDS {
+                return Ok(outcome);
+            }
+        }
+    }
+
+    /// Do one transfer.
+    ///
+    /// Returns `false` if we read back something other than what we wrote.
+    fn transfer(&mut self, file: &neotron_sdk::File, test: Test) -> Result<bool, Error> {
+        let len = test.block_len();
+        let offset = if test.is_random() {
+            let sectors = self.file_len / SECTOR_LEN as u32;
+            let random = u32::from(neotron_sdk::rand()) << 16 | u32::from(neotron_sdk::rand());
+            let offset = random % sectors * SECTOR_LEN as u32;
+            file.seek_set(u64::from(offset))?;
+            offset
+        } else {
+            if self.position + len as u32 > self.file_len {
+                // Go round again
+                self.position = 0;
+                file.seek_set(0)?;
+            }
+            let offset = self.position;
+            self.position += len as u32;
+            offset
+        };
+        if test.is_write() {
+            self.make_pattern(offset, len);
+            file.write(&self.buffer[0..len])?;
+            Ok(true)
+        } else {
+            let n = file.read(&mut self.buffer[0..len])?;
+            Ok(n == len && self.check_pattern(offset, len))
+        }
+    }
+
+    /// Make sure the scratch file is full size.
+    ///
+    /// A slow disk might not get all the way through the file during the
+    /// sequential write test.
+    fn fill(&mut self) -> Result<(), Error> {
+        let path = neotron_sdk::path::Path::new(self.path.as_str())?;
+        let file = neotron_sdk::File::open(path, neotron_sdk::Flags::WRITE)?;
+        let len = file.seek_end()? as u32;
+        let mut offset = len / BLOCK_LEN as u32 * BLOCK_LEN as u32;
+        file.seek_set(u64::from(offset))?;
+        while offset < self.file_len {
+            self.make_pattern(offset, BLOCK_LEN);
+            file.write(&self.buffer)?;
+            offset += BLOCK_LEN as u32;
+        }
+        Ok(())
+    }
+
+    /// Fill the buffer with the data that belongs at this offset in the file
+    fn make_pattern(&mut self, offset: u32, len: usize) {
+        for (idx, word) in self.buffer[0..len].chunks_exact_mut(4).enumerate() {
+            let value = (offset + idx as u32 * 4) ^ PATTERN;
+            word.copy_from_slice(&value.to_le_bytes());
+        }
+    }
+
+    /// Check the buffer holds the data that belongs at this offset in the file
+    fn check_pattern(&self, offset: u32, len: usize) -> bool {
+        self.buffer[0..len]
+            .chunks_exact(4)
+            .enumerate()
+            .all(|(idx, word)| {
+                let value = (offset + idx as u32 * 4) ^ PATTERN;
+                word == value.to_le_bytes()
+            })
+    }
+}
+
+impl Default for Bench {
+    fn default() -> Self {
+        Bench::new()
+    }
+}
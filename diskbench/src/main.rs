@@ -0,0 +1,28 @@
+#![cfg_attr(target_os = "none", no_std)]
+#![cfg_attr(target_os = "none", no_main)]
+
+use core::ptr::addr_of_mut;
+
+#[cfg(not(target_os = "none"))]
+fn main() {
+    neotron_sdk::init();
+}
+
+static mut BENCH: diskbench::Bench = diskbench::Bench::new();
+
+#[no_mangle]
+extern "C" fn neotron_main() -> i32 {
+    neotron_error::exit_code_text(real_main())
+}
+
+fn real_main() -> Result<(), neotron_sdk::Error> {
+    let bench = unsafe { &mut *addr_of_mut!(BENCH) };
+    let directory = neotron_sdk::arg(0);
+    let size_kib = match neotron_sdk::arg(1) {
+        Some(text) => text
+            .parse::<u32>()
+            .map_err(|_| neotron_sdk::Error::InvalidArg)?,
+        None => diskbench::DEFAULT_SIZE_KIB,
+    };
+    bench.run(directory.as_deref().unwrap_or(""), size_kib)
+}
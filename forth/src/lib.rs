@@ -0,0 +1,724 @@
+//! An interactive Forth environment
+//!
+//! This is a small, mostly-standard Forth with 32-bit cells. Words are kept
+//! in a dictionary of headers, and colon definitions are compiled into a
+//! single block of memory (shared with variables and anything you `ALLOT`)
+//! as a list of execution tokens.
+
+#![no_std]
+#![deny(missing_docs)]
+#![deny(unsafe_code)]
+
+use core::fmt::Write;
+
+use neotron_input::Keyboard;
+use neotron_line::Editor;
+
+mod primitives;
+
+use primitives::Prim;
+
+/// A Forth cell
+type Cell = i32;
+
+/// How many bytes in a cell
+const CELL: usize = 4;
+
+/// Things that can go wrong
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// Not enough values on the data stack
+    StackUnderflow,
+    /// Too many values on the data stack
+    StackOverflow,
+    /// Return stack underflow or overflow
+    ReturnStack,
+    /// A word we haven't heard of, which isn't a number either
+    Undefined,
+    /// Tried to divide by zero
+    DivideByZero,
+    /// Tried to access memory that doesn't exist
+    BadAddress,
+    /// No more room for code and data
+    DictionaryFull,
+    /// No more room for word headers
+    TooManyWords,
+    /// That word only works inside a colon definition
+    CompileOnly,
+    /// Control structures don't match up
+    BadControl,
+    /// We needed a name, but the line ended
+    MissingName,
+    /// Can't nest `INCLUDE`
+    NestedInclude,
+    /// A file could not be read
+    File(neotron_sdk::Error),
+    /// `BYE` was executed
+    Bye,
+    /// `ABORT` was executed
+    Abort,
+}
+
+impl Error {
+    /// Get a human-readable description of this error
+    fn describe(self) -> &'static str {
+        match self {
+            Error::StackUnderflow => "stack underflow",
+            Error::StackOverflow => "stack overflow",
+            Error::ReturnStack => "return stack error",
+            Error::Undefined => "undefined word",
+            Error::DivideByZero => "division by zero",
+            Error::BadAddress => "invalid memory address",
+            Error::DictionaryFull => "dictionary full",
+            Error::TooManyWords => "too many words",
+            Error::CompileOnly => "compile-only word",
+            Error::BadControl => "unbalanced control structure",
+            Error::MissingName => "missing name",
+            Error::NestedInclude => "can't nest INCLUDE",
+            Error::File(_) => "file error",
+            Error::Bye => "bye",
+            Error::Abort => "aborted",
+        }
+    }
+}
+
+impl From<neotron_sdk::Error> for Error {
+    fn from(e: neotron_sdk::Error) -> Error {
+        Error::File(e)
+    }
+}
+
+/// What happens when a word is executed
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Kind {
+    /// Built in to the interpreter
+    Prim(Prim),
+    /// A colon definition, with code at the given address
+    Colon(usize),
+    /// Made by `CREATE` (or `VARIABLE`). Pushes the address of its data, then
+    /// runs the `DOES>` code, if any.
+    Create(usize, Option<usize>),
+    /// Pushes a value
+    Constant(Cell),
+}
+
+/// A dictionary entry
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct Header {
+    name: [u8; Forth::MAX_NAME],
+    name_len: u8,
+    immediate: bool,
+    /// Hidden words can't be found, like a colon definition that isn't
+    /// finished yet
+    hidden: bool,
+    kind: Kind,
+}
+
+impl Header {
+    /// An unused header
+    const EMPTY: Header = Header {
+        name: [0; Forth::MAX_NAME],
+        name_len: 0,
+        immediate: false,
+        hidden: false,
+        kind: Kind::Constant(0),
+    };
+
+    /// Get the name of this word
+    fn name(&self) -> &[u8] {
+        &self.name[0..usize::from(self.name_len)]
+    }
+}
+
+/// Represents the Forth system
+pub struct Forth {
+    stdout: neotron_sdk::File,
+    stdin: neotron_sdk::File,
+    /// Reads keys for the line editor
+    keyboard: Keyboard,
+    /// Edits each line as it's typed, and remembers the old ones
+    editor: Editor<{ Forth::INPUT_SIZE }, { Forth::HISTORY_SIZE }>,
+    /// Code and data space
+    memory: [u8; Self::MEMORY_SIZE],
+    /// Next free byte in `memory`
+    here: usize,
+    /// The dictionary headers, oldest first
+    words: [Header; Self::MAX_WORDS],
+    word_count: usize,
+    data_stack: [Cell; Self::STACK_SIZE],
+    dsp: usize,
+    return_stack: [Cell; Self::STACK_SIZE],
+    rsp: usize,
+    /// Address of the next execution token to run
+    ip: usize,
+    /// Are we compiling?
+    compiling: bool,
+    /// Address of the cell that holds `BASE`
+    base_addr: usize,
+    /// The current line of input
+    input: [u8; Self::INPUT_SIZE],
+    input_len: usize,
+    /// How far through `input` we've got
+    input_pos: usize,
+    /// The file being included
+    file: [u8; Self::FILE_SIZE],
+    /// Are we part way through including a file?
+    including: bool,
+    /// Words with this index and above are user-defined
+    first_user_word: usize,
+}
+
+impl Forth {
+    /// Bytes of code and data space
+    const MEMORY_SIZE: usize = 32 * 1024;
+    /// How many words we can define
+    const MAX_WORDS: usize = 512;
+    /// The longest word name we store. Longer names are truncated.
+    const MAX_NAME: usize = 31;
+    /// Depth of each stack, in cells
+    const STACK_SIZE: usize = 128;
+    /// Longest line of input
+    const INPUT_SIZE: usize = 128;
+    /// Bytes of old lines we remember
+    const HISTORY_SIZE: usize = 1024;
+    /// Largest source file we can include
+    const FILE_SIZE: usize = 16 * 1024;
+
+    /// Make a new Forth system. Call [`Forth::boot`] before using it.
+    pub const fn new() -> Forth {
+        Forth {
+            stdout: neotron_sdk::stdout(),
+            stdin: neotron_sdk::stdin(),
+            keyboard: Keyboard::new(),
+            editor: Editor::new(),
+            memory: [0; Self::MEMORY_SIZE],
+            here: 0,
+            words: [Header::EMPTY; Self::MAX_WORDS],
+            word_count: 0,
+            data_stack: [0; Self::STACK_SIZE],
+            dsp: 0,
+            return_stack: [0; Self::STACK_SIZE],
+            rsp: 0,
+            ip: 0,
+            compiling: false,
+            base_addr: 0,
+            input: [0; Self::INPUT_SIZE],
+            input_len: 0,
+            input_pos: 0,
+            file: [0; Self::FILE_SIZE],
+            including: false,
+            first_user_word: 0,
+        }
+    }
+
+    /// Set up the dictionary with all the built-in words
+    pub fn boot(&mut self) {
+        self.word_count = 0;
+        self.here = 0;
+        for prim in Prim::ALL {
+            let _ = self.add_word(prim.name().as_bytes(), Kind::Prim(*prim));
+            if prim.is_immediate() {
+                self.words[self.word_count - 1].immediate = true;
+            }
+        }
+        // BASE is a variable
+        self.base_addr = self.here;
+        let _ = self.add_word(b"BASE", Kind::Create(self.base_addr, None));
+        let _ = self.comma(10);
+        self.first_user_word = self.word_count;
+        self.reset();
+    }
+
+    /// Run the interactive prompt until the user types `BYE`
+    pub fn repl(&mut self) {
+        let _ = writeln!(
+            self.stdout,
+            "Neotron Forth. {} bytes free. Type WORDS for help, BYE to exit.",
+            Self::MEMORY_SIZE - self.here
+        );
+        loop {
+            let len = self.read_line();
+            self.input_len = len;
+            self.input_pos = 0;
+            match self.interpret() {
+                Ok(()) => {
+                    let _ = writeln!(
+                        self.stdout,
+                        "{}",
+                        if self.compiling { " compiled" } else { " ok" }
+                    );
+                }
+                Err(Error::Bye) => break,
+                Err(e) => self.report(e),
+            }
+        }
+    }
+
+    /// Load and run a source file.
+    ///
+    /// Errors are reported, and stop the file from loading any further.
+    pub fn include(&mut self, filename: &str) {
+        if let Err(e) = self.include_file(filename) {
+            self.report(e);
+        }
+    }
+
+    /// Report an error, and reset the system
+    fn report(&mut self, e: Error) {
+        let _ = write!(self.stdout, "\nError: {}", e.describe());
+        if let Error::File(inner) = e {
+            let _ = write!(self.stdout, " ({:?})", inner);
+        }
+        if e == Error::Undefined {
+            let _ = self.stdout.write_str(" - ");
+            let _ = self.stdout.write(self.last_token());
+        }
+        let _ = writeln!(self.stdout);
+        self.reset();
+    }
+
+    /// Clear the stacks, and go back to interpreting
+    fn reset(&mut self) {
+        self.dsp = 0;
+        self.rsp = 0;
+        if self.compiling {
+            // Throw away the half-finished definition
+            if let Some(header) = self.words[0..self.word_count].last() {
+                if header.hidden {
+                    self.word_count -= 1;
+                }
+            }
+            self.compiling = false;
+        }
+    }
+
+    /// Read a line from the keyboard into the input buffer
+    fn read_line(&mut self) -> usize {
+        let line = self
+            .editor
+            .read_line(&mut self.stdout, &mut self.keyboard)
+            .unwrap_or("");
+        self.input[0..line.len()].copy_from_slice(line.as_bytes());
+        // Keep the output off the text we just typed
+        let _ = self.stdout.write_str(" ");
+        line.len()
+    }
+
+    /// Load a source file and interpret it, one line at a time
+    fn include_file(&mut self, filename: &str) -> Result<(), Error> {
+        if self.including {
+            return Err(Error::NestedInclude);
+        }
+        let path = neotron_sdk::path::Path::new(filename)?;
+        let f = neotron_sdk::File::open(path, neotron_sdk::Flags::empty())?;
+        let n = f.read(&mut self.file)?;
+        drop(f);
+
+        // Save the line we were part way through
+        let saved_input = self.input;
+        let saved_len = self.input_len;
+        let saved_pos = self.input_pos;
+        self.including = true;
+
+        let mut result = Ok(());
+        let mut start = 0;
+        while start < n {
+            let end = self.file[start..n]
+                .iter()
+                .position(|&b| b == b'\n')
+                .map(|p| start + p)
+                .unwrap_or(n);
+            let line_len = (end - start).min(Self::INPUT_SIZE);
+            self.input[0..line_len].copy_from_slice(&self.file[start..start + line_len]);
+            self.input_len = line_len;
+            self.input_pos = 0;
+            result = self.interpret();
+            if result.is_err() {
+                break;
+            }
+            start = end + 1;
+        }
+
+        self.including = false;
+        self.input = saved_input;
+        self.input_len = saved_len;
+        self.input_pos = saved_pos;
+        result
+    }
+
+    // ----------------------------------------------------------------------
+    // The outer interpreter
+    // ----------------------------------------------------------------------
+
+    /// Interpret everything left in the input buffer
+    fn interpret(&mut self) -> Result<(), Error> {
+        loop {
+            let (start, end) = self.next_token();
+            if start == end {
+                return Ok(());
+            }
+            let mut token = [0u8; Self::MAX_NAME];
+            let len = (end - start).min(Self::MAX_NAME);
+            token[0..len].copy_from_slice(&self.input[start..start + len]);
+            let token = &token[0..len];
+
+            if let Some(xt) = self.find(token) {
+                let compile_only = match self.words[xt].kind {
+                    Kind::Prim(prim) => prim.is_compile_only(),
+                    _ => false,
+                };
+                if compile_only && !self.compiling {
+                    return Err(Error::CompileOnly);
+                }
+                if self.compiling && !self.words[xt].immediate {
+                    self.comma(xt as Cell)?;
+                } else {
+                    self.execute(xt)?;
+                }
+            } else if let Some(value) = self.parse_number(token) {
+                if self.compiling {
+                    self.compile_literal(value)?;
+                } else {
+                    self.push(value)?;
+                }
+            } else {
+                return Err(Error::Undefined);
+            }
+        }
+    }
+
+    /// Find the next space-delimited token in the input.
+    ///
+    /// Returns its start and end within the input buffer.
+    fn next_token(&mut self) -> (usize, usize) {
+        while self.input_pos < self.input_len && self.input[self.input_pos].is_ascii_whitespace() {
+            self.input_pos += 1;
+        }
+        let start = self.input_pos;
+        while self.input_pos < self.input_len && !self.input[self.input_pos].is_ascii_whitespace() {
+            self.input_pos += 1;
+        }
+        (start, self.input_pos)
+    }
+
+    /// The token we most recently read, for error messages
+    fn last_token(&self) -> &[u8] {
+        let end = self.input_pos.min(self.input_len);
+        let start = self.input[0..end]
+            .iter()
+            .rposition(|b| b.is_ascii_whitespace())
+            .map(|p| p + 1)
+            .unwrap_or(0);
+        &self.input[start..end]
+    }
+
+    /// Read up to the given delimiter. Returns start and end in the input.
+    fn parse_until(&mut self, delimiter: u8) -> (usize, usize) {
+        // Skip the one space after the word that asked for this
+        if self.input_pos < self.input_len && self.input[self.input_pos] == b' ' {
+            self.input_pos += 1;
+        }
+        let start = self.input_pos;
+        while self.input_pos < self.input_len && self.input[self.input_pos] != delimiter {
+            self.input_pos += 1;
+        }
+        let end = self.input_pos;
+        if self.input_pos < self.input_len {
+            // Skip the delimiter
+            self.input_pos += 1;
+        }
+        (start, end)
+    }
+
+    /// Look up a word, newest first, ignoring case
+    fn find(&self, name: &[u8]) -> Option<usize> {
+        self.words[0..self.word_count]
+            .iter()
+            .rposition(|h| !h.hidden && h.name().eq_ignore_ascii_case(name))
+    }
+
+    /// Try and turn a token into a number, in the current base.
+    ///
+    /// Supports a leading `-`, and `$`, `#` and `%` prefixes for hex, decimal
+    /// and binary.
+    fn parse_number(&self, token: &[u8]) -> Option<Cell> {
+        let mut base = self.base() as u32;
+        let mut digits = token;
+        let mut negative = false;
+        match digits.first() {
+            Some(b'$') => {
+                base = 16;
+                digits = &digits[1..];
+            }
+            Some(b'#') => {
+                base = 10;
+                digits = &digits[1..];
+            }
+            Some(b'%') => {
+                base = 2;
+                digits = &digits[1..];
+            }
+            _ => {}
+        }
+        if let Some(b'-') = digits.first() {
+            negative = true;
+            digits = &digits[1..];
+        }
+        if digits.is_empty() || !(2..=36).contains(&base) {
+            return None;
+        }
+        let mut value: Cell = 0;
+        for &ch in digits {
+            let digit = char::from(ch).to_digit(base)?;
+            value = value.wrapping_mul(base as Cell).wrapping_add(digit as Cell);
+        }
+        Some(if negative {
+            value.wrapping_neg()
+        } else {
+            value
+        })
+    }
+
+    /// Add a new word to the dictionary
+    fn add_word(&mut self, name: &[u8], kind: Kind) -> Result<(), Error> {
+        if self.word_count == Self::MAX_WORDS {
+            return Err(Error::TooManyWords);
+        }
+        let mut header = Header::EMPTY;
+        let len = name.len().min(Self::MAX_NAME);
+        for (dest, src) in header.name.iter_mut().zip(name[0..len].iter()) {
+            *dest = src.to_ascii_uppercase();
+        }
+        header.name_len = len as u8;
+        header.kind = kind;
+        self.words[self.word_count] = header;
+        self.word_count += 1;
+        Ok(())
+    }
+
+    /// Read a name from the input and add a word with that name
+    fn add_named_word(&mut self, kind: Kind) -> Result<(), Error> {
+        let (start, end) = self.next_token();
+        if start == end {
+            return Err(Error::MissingName);
+        }
+        let mut name = [0u8; Self::MAX_NAME];
+        let len = (end - start).min(Self::MAX_NAME);
+        name[0..len].copy_from_slice(&self.input[start..start + len]);
+        self.add_word(&name[0..len], kind)
+    }
+
+    /// Get the current number base
+    fn base(&self) -> Cell {
+        self.fetch(self.base_addr).unwrap_or(10)
+    }
+
+    // ----------------------------------------------------------------------
+    // The inner interpreter
+    // ----------------------------------------------------------------------
+
+    /// Execute a word, and everything it calls, to completion
+    fn execute(&mut self, xt: usize) -> Result<(), Error> {
+        let depth = self.rsp;
+        self.call(xt)?;
+        while self.rsp > depth {
+            let next = self.fetch(self.ip)?;
+            self.ip += CELL;
+            self.call(next as usize)?;
+        }
+        Ok(())
+    }
+
+    /// Start executing a word. Colon definitions just push the return address
+    /// and jump - the loop in [`Forth::execute`] does the rest.
+    fn call(&mut self, xt: usize) -> Result<(), Error> {
+        let Some(header) = self.words[0..self.word_count].get(xt) else {
+            return Err(Error::BadAddress);
+        };
+        match header.kind {
+            Kind::Prim(prim) => self.primitive(prim),
+            Kind::Colon(addr) => {
+                self.rpush(self.ip as Cell)?;
+                self.ip = addr;
+                Ok(())
+            }
+            Kind::Create(addr, does) => {
+                self.push(addr as Cell)?;
+                if let Some(does) = does {
+                    self.rpush(self.ip as Cell)?;
+                    self.ip = does;
+                }
+                Ok(())
+            }
+            Kind::Constant(value) => self.push(value),
+        }
+    }
+
+    // ----------------------------------------------------------------------
+    // Stacks and memory
+    // ----------------------------------------------------------------------
+
+    /// Push on to the data stack
+    fn push(&mut self, value: Cell) -> Result<(), Error> {
+        if self.dsp == Self::STACK_SIZE {
+            return Err(Error::StackOverflow);
+        }
+        self.data_stack[self.dsp] = value;
+        self.dsp += 1;
+        Ok(())
+    }
+
+    /// Pop from the data stack
+    fn pop(&mut self) -> Result<Cell, Error> {
+        if self.dsp == 0 {
+            return Err(Error::StackUnderflow);
+        }
+        self.dsp -= 1;
+        Ok(self.data_stack[self.dsp])
+    }
+
+    /// Look at a value on the data stack, 0 being the top
+    fn peek(&self, depth: usize) -> Result<Cell, Error> {
+        if depth >= self.dsp {
+            return Err(Error::StackUnderflow);
+        }
+        Ok(self.data_stack[self.dsp - 1 - depth])
+    }
+
+    /// Push on to the return stack
+    fn rpush(&mut self, value: Cell) -> Result<(), Error> {
+        if self.rsp == Self::STACK_SIZE {
+            return Err(Error::ReturnStack);
+        }
+        self.return_stack[self.rsp] = value;
+        self.rsp += 1;
+        Ok(())
+    }
+
+    /// Pop from the return stack
+    fn rpop(&mut self) -> Result<Cell, Error> {
+        if self.rsp == 0 {
+            return Err(Error::ReturnStack);
+        }
+        self.rsp -= 1;
+        Ok(self.return_stack[self.rsp])
+    }
+
+    /// Check an address range is inside memory, and turn it into a `usize`
+    fn check_addr(&self, addr: Cell, len: usize) -> Result<usize, Error> {
+        let addr = usize::try_from(addr).map_err(|_| Error::BadAddress)?;
+        if addr + len > Self::MEMORY_SIZE {
+            return Err(Error::BadAddress);
+        }
+        Ok(addr)
+    }
+
+    /// Read a cell from memory
+    fn fetch(&self, addr: usize) -> Result<Cell, Error> {
+        let bytes = self
+            .memory
+            .get(addr..addr + CELL)
+            .ok_or(Error::BadAddress)?;
+        Ok(Cell::from_le_bytes([
+            bytes[0], bytes[1], bytes[2], bytes[3],
+        ]))
+    }
+
+    /// Write a cell to memory
+    fn store(&mut self, addr: usize, value: Cell) -> Result<(), Error> {
+        let bytes = self
+            .memory
+            .get_mut(addr..addr + CELL)
+            .ok_or(Error::BadAddress)?;
+        bytes.copy_from_slice(&value.to_le_bytes());
+        Ok(())
+    }
+
+    /// Reserve some memory at `HERE`
+    fn allot(&mut self, bytes: Cell) -> Result<(), Error> {
+        let new_here = self.here as isize + bytes as isize;
+        if new_here < 0 || new_here as usize > Self::MEMORY_SIZE {
+            return Err(Error::DictionaryFull);
+        }
+        self.here = new_here as usize;
+        Ok(())
+    }
+
+    /// Move `HERE` on to the next cell boundary
+    fn align(&mut self) -> Result<(), Error> {
+        let padding = (CELL - self.here % CELL) % CELL;
+        self.allot(padding as Cell)
+    }
+
+    /// Add a cell at `HERE`
+    fn comma(&mut self, value: Cell) -> Result<(), Error> {
+        let addr = self.here;
+        self.allot(CELL as Cell)?;
+        self.store(addr, value)
+    }
+
+    /// Add a byte at `HERE`
+    fn comma_byte(&mut self, value: u8) -> Result<(), Error> {
+        let addr = self.here;
+        self.allot(1)?;
+        self.memory[addr] = value;
+        Ok(())
+    }
+
+    /// Get the execution token for a primitive
+    fn xt_of(&self, prim: Prim) -> Cell {
+        // Primitives are added first, in order
+        prim as Cell
+    }
+
+    /// Compile code to push a literal value
+    fn compile_literal(&mut self, value: Cell) -> Result<(), Error> {
+        self.comma(self.xt_of(Prim::Lit))?;
+        self.comma(value)
+    }
+
+    /// Compile a counted string, padded to a cell boundary
+    fn compile_string(&mut self, start: usize, end: usize) -> Result<(), Error> {
+        self.comma((end - start) as Cell)?;
+        for idx in start..end {
+            self.comma_byte(self.input[idx])?;
+        }
+        self.align()
+    }
+
+    /// Print a number in the current base
+    fn print_number(&mut self, value: Cell, unsigned: bool) {
+        let base = self.base().clamp(2, 36) as u32;
+        let mut magnitude = if unsigned {
+            value as u32
+        } else {
+            value.unsigned_abs()
+        };
+        let mut digits = [0u8; 33];
+        let mut len = 0;
+        loop {
+            let digit = magnitude % base;
+            digits[len] = char::from_digit(digit, base)
+                .map(|c| c.to_ascii_uppercase() as u8)
+                .unwrap_or(b'?');
+            len += 1;
+            magnitude /= base;
+            if magnitude == 0 {
+                break;
+            }
+        }
+        if !unsigned && value < 0 {
+            let _ = self.stdout.write_str("-");
+        }
+        for idx in (0..len).rev() {
+            let _ = self.stdout.write(&digits[idx..idx + 1]);
+        }
+        let _ = self.stdout.write_str(" ");
+    }
+}
+
+impl Default for Forth {
+    fn default() -> Self {
+        Forth::new()
+    }
+}
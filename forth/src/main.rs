@@ -0,0 +1,23 @@
+#![cfg_attr(target_os = "none", no_std)]
+#![cfg_attr(target_os = "none", no_main)]
+
+use core::ptr::addr_of_mut;
+
+#[cfg(not(target_os = "none"))]
+fn main() {
+    neotron_sdk::init();
+}
+
+static mut FORTH: forth::Forth = forth::Forth::new();
+
+#[no_mangle]
+extern "C" fn neotron_main() -> i32 {
+    let forth = unsafe { &mut *addr_of_mut!(FORTH) };
+    forth.boot();
+    // Any arguments are source files to load before we start
+    for filename in (0..).map_while(neotron_sdk::arg) {
+        forth.include(&filename);
+    }
+    forth.repl();
+    0
+}
@@ -0,0 +1,1312 @@
+//! The built-in words.
+//!
+//! Each primitive has an entry in the dictionary, with an execution token
+//! equal to its position in [`Prim::ALL`].
+
+use core::fmt::Write;
+
+use neotron_sdk::console;
+
+use crate::{Cell, Error, Forth, Kind, CELL};
+
+/// Marks an unresolved forward branch on the control-flow stack
+const TAG_ORIG: Cell = 0x4F52;
+/// Marks a backward branch destination on the control-flow stack
+const TAG_DEST: Cell = 0x4445;
+/// Marks a `DO` loop on the control-flow stack
+const TAG_DO: Cell = 0x444F;
+
+/// The words built in to the interpreter
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum Prim {
+    /// `(LIT)`
+    Lit,
+    /// `(BRANCH)`
+    Branch,
+    /// `(0BRANCH)`
+    ZeroBranch,
+    /// `EXIT`
+    Exit,
+    /// `(DO)`
+    DoRt,
+    /// `(?DO)`
+    QDoRt,
+    /// `(LOOP)`
+    LoopRt,
+    /// `(+LOOP)`
+    PlusLoopRt,
+    /// `(.")`
+    DotQuoteRt,
+    /// `(S")`
+    SQuoteRt,
+    /// `(DOES>)`
+    DoesRt,
+    /// `DUP`
+    Dup,
+    /// `DROP`
+    Drop,
+    /// `SWAP`
+    Swap,
+    /// `OVER`
+    Over,
+    /// `ROT`
+    Rot,
+    /// `-ROT`
+    MinusRot,
+    /// `NIP`
+    Nip,
+    /// `TUCK`
+    Tuck,
+    /// `PICK`
+    Pick,
+    /// `?DUP`
+    QDup,
+    /// `2DUP`
+    TwoDup,
+    /// `2DROP`
+    TwoDrop,
+    /// `2SWAP`
+    TwoSwap,
+    /// `2OVER`
+    TwoOver,
+    /// `DEPTH`
+    Depth,
+    /// `>R`
+    ToR,
+    /// `R>`
+    RFrom,
+    /// `R@`
+    RFetch,
+    /// `I`
+    I,
+    /// `J`
+    J,
+    /// `LEAVE`
+    Leave,
+    /// `UNLOOP`
+    Unloop,
+    /// `+`
+    Add,
+    /// `-`
+    Sub,
+    /// `*`
+    Mul,
+    /// `/`
+    Div,
+    /// `MOD`
+    Mod,
+    /// `/MOD`
+    DivMod,
+    /// `*/`
+    MulDiv,
+    /// `NEGATE`
+    Negate,
+    /// `ABS`
+    Abs,
+    /// `MIN`
+    Min,
+    /// `MAX`
+    Max,
+    /// `1+`
+    OnePlus,
+    /// `1-`
+    OneMinus,
+    /// `2*`
+    TwoMul,
+    /// `2/`
+    TwoDiv,
+    /// `AND`
+    And,
+    /// `OR`
+    Or,
+    /// `XOR`
+    Xor,
+    /// `INVERT`
+    Invert,
+    /// `LSHIFT`
+    LShift,
+    /// `RSHIFT`
+    RShift,
+    /// `=`
+    Equal,
+    /// `<>`
+    NotEqual,
+    /// `<`
+    Less,
+    /// `>`
+    Greater,
+    /// `U<`
+    ULess,
+    /// `0=`
+    ZeroEqual,
+    /// `0<`
+    ZeroLess,
+    /// `0>`
+    ZeroGreater,
+    /// `TRUE`
+    True,
+    /// `FALSE`
+    False,
+    /// `@`
+    Fetch,
+    /// `!`
+    Store,
+    /// `C@`
+    CFetch,
+    /// `C!`
+    CStore,
+    /// `+!`
+    PlusStore,
+    /// `HERE`
+    Here,
+    /// `ALLOT`
+    Allot,
+    /// `,`
+    Comma,
+    /// `C,`
+    CComma,
+    /// `CELLS`
+    Cells,
+    /// `CELL+`
+    CellPlus,
+    /// `ALIGN`
+    Align,
+    /// `FILL`
+    Fill,
+    /// `MOVE`
+    Move,
+    /// `.`
+    Dot,
+    /// `U.`
+    UDot,
+    /// `.S`
+    DotS,
+    /// `EMIT`
+    Emit,
+    /// `CR`
+    Cr,
+    /// `SPACE`
+    Space,
+    /// `SPACES`
+    Spaces,
+    /// `TYPE`
+    Type,
+    /// `KEY`
+    Key,
+    /// `PAGE`
+    Page,
+    /// `AT-XY`
+    AtXy,
+    /// `MS`
+    Ms,
+    /// `DECIMAL`
+    Decimal,
+    /// `HEX`
+    Hex,
+    /// `:`
+    Colon,
+    /// `;`
+    Semicolon,
+    /// `CREATE`
+    Create,
+    /// `VARIABLE`
+    Variable,
+    /// `CONSTANT`
+    Constant,
+    /// `DOES>`
+    Does,
+    /// `IMMEDIATE`
+    Immediate,
+    /// `'`
+    Tick,
+    /// `[']`
+    BracketTick,
+    /// `EXECUTE`
+    Execute,
+    /// `[`
+    LeftBracket,
+    /// `]`
+    RightBracket,
+    /// `LITERAL`
+    Literal,
+    /// `POSTPONE`
+    Postpone,
+    /// `COMPILE,`
+    CompileComma,
+    /// `RECURSE`
+    Recurse,
+    /// `IF`
+    If,
+    /// `ELSE`
+    Else,
+    /// `THEN`
+    Then,
+    /// `BEGIN`
+    Begin,
+    /// `UNTIL`
+    Until,
+    /// `AGAIN`
+    Again,
+    /// `WHILE`
+    While,
+    /// `REPEAT`
+    Repeat,
+    /// `DO`
+    Do,
+    /// `?DO`
+    QDo,
+    /// `LOOP`
+    Loop,
+    /// `+LOOP`
+    PlusLoop,
+    /// `."`
+    DotQuote,
+    /// `S"`
+    SQuote,
+    /// `.(`
+    DotParen,
+    /// `(`
+    Paren,
+    /// `\`
+    Backslash,
+    /// `WORDS`
+    Words,
+    /// `FORGET`
+    Forget,
+    /// `INCLUDE`
+    Include,
+    /// `ABORT`
+    Abort,
+    /// `BYE`
+    Bye,
+}
+
+impl Prim {
+    /// Every primitive, in the same order as the enum, so that the execution
+    /// token of a primitive is its position in this list
+    pub(crate) const ALL: &'static [Prim] = &[
+        Prim::Lit,
+        Prim::Branch,
+        Prim::ZeroBranch,
+        Prim::Exit,
+        Prim::DoRt,
+        Prim::QDoRt,
+        Prim::LoopRt,
+        Prim::PlusLoopRt,
+        Prim::DotQuoteRt,
+        Prim::SQuoteRt,
+        Prim::DoesRt,
+        Prim::Dup,
+        Prim::Drop,
+        Prim::Swap,
+        Prim::Over,
+        Prim::Rot,
+        Prim::MinusRot,
+        Prim::Nip,
+        Prim::Tuck,
+        Prim::Pick,
+        Prim::QDup,
+        Prim::TwoDup,
+        Prim::TwoDrop,
+        Prim::TwoSwap,
+        Prim::TwoOver,
+        Prim::Depth,
+        Prim::ToR,
+        Prim::RFrom,
+        Prim::RFetch,
+        Prim::I,
+        Prim::J,
+        Prim::Leave,
+        Prim::Unloop,
+        Prim::Add,
+        Prim::Sub,
+        Prim::Mul,
+        Prim::Div,
+        Prim::Mod,
+        Prim::DivMod,
+        Prim::MulDiv,
+        Prim::Negate,
+        Prim::Abs,
+        Prim::Min,
+        Prim::Max,
+        Prim::OnePlus,
+        Prim::OneMinus,
+        Prim::TwoMul,
+        Prim::TwoDiv,
+        Prim::And,
+        Prim::Or,
+        Prim::Xor,
+        Prim::Invert,
+        Prim::LShift,
+        Prim::RShift,
+        Prim::Equal,
+        Prim::NotEqual,
+        Prim::Less,
+        Prim::Greater,
+        Prim::ULess,
+        Prim::ZeroEqual,
+        Prim::ZeroLess,
+        Prim::ZeroGreater,
+        Prim::True,
+        Prim::False,
+        Prim::Fetch,
+        Prim::Store,
+        Prim::CFetch,
+        Prim::CStore,
+        Prim::PlusStore,
+        Prim::Here,
+        Prim::Allot,
+        Prim::Comma,
+        Prim::CComma,
+        Prim::Cells,
+        Prim::CellPlus,
+        Prim::Align,
+        Prim::Fill,
+        Prim::Move,
+        Prim::Dot,
+        Prim::UDot,
+        Prim::DotS,
+        Prim::Emit,
+        Prim::Cr,
+        Prim::Space,
+        Prim::Spaces,
+        Prim::Type,
+        Prim::Key,
+        Prim::Page,
+        Prim::AtXy,
+        Prim::Ms,
+        Prim::Decimal,
+        Prim::Hex,
+        Prim::Colon,
+        Prim::Semicolon,
+        Prim::Create,
+        Prim::Variable,
+        Prim::Constant,
+        Prim::Does,
+        Prim::Immediate,
+        Prim::Tick,
+        Prim::BracketTick,
+        Prim::Execute,
+        Prim::LeftBracket,
+        Prim::RightBracket,
+        Prim::Literal,
+        Prim::Postpone,
+        Prim::CompileComma,
+        Prim::Recurse,
+        Prim::If,
+        Prim::Else,
+        Prim::Then,
+        Prim::Begin,
+        Prim::Until,
+        Prim::Again,
+        Prim::While,
+        Prim::Repeat,
+        Prim::Do,
+        Prim::QDo,
+        Prim::Loop,
+        Prim::PlusLoop,
+        Prim::DotQuote,
+        Prim::SQuote,
+        Prim::DotParen,
+        Prim::Paren,
+        Prim::Backslash,
+        Prim::Words,
+        Prim::Forget,
+        Prim::Include,
+        Prim::Abort,
+        Prim::Bye,
+    ];
+
+    /// The name of this word in the dictionary
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Prim::Lit => "(LIT)",
+            Prim::Branch => "(BRANCH)",
+            Prim::ZeroBranch => "(0BRANCH)",
+            Prim::Exit => "EXIT",
+            Prim::DoRt => "(DO)",
+            Prim::QDoRt => "(?DO)",
+            Prim::LoopRt => "(LOOP)",
+            Prim::PlusLoopRt => "(+LOOP)",
+            Prim::DotQuoteRt => "(.\")",
+            Prim::SQuoteRt => "(S\")",
+            Prim::DoesRt => "(DOES>)",
+            Prim::Dup => "DUP",
+            Prim::Drop => "DROP",
+            Prim::Swap => "SWAP",
+            Prim::Over => "OVER",
+            Prim::Rot => "ROT",
+            Prim::MinusRot => "-ROT",
+            Prim::Nip => "NIP",
+            Prim::Tuck => "TUCK",
+            Prim::Pick => "PICK",
+            Prim::QDup => "?DUP",
+            Prim::TwoDup => "2DUP",
+            Prim::TwoDrop => "2DROP",
+            Prim::TwoSwap => "2SWAP",
+            Prim::TwoOver => "2OVER",
+            Prim::Depth => "DEPTH",
+            Prim::ToR => ">R",
+            Prim::RFrom => "R>",
+            Prim::RFetch => "R@",
+            Prim::I => "I",
+            Prim::J => "J",
+            Prim::Leave => "LEAVE",
+            Prim::Unloop => "UNLOOP",
+            Prim::Add => "+",
+            Prim::Sub => "-",
+            Prim::Mul => "*",
+            Prim::Div => "/",
+            Prim::Mod => "MOD",
+            Prim::DivMod => "/MOD",
+            Prim::MulDiv => "*/",
+            Prim::Negate => "NEGATE",
+            Prim::Abs => "ABS",
+            Prim::Min => "MIN",
+            Prim::Max => "MAX",
+            Prim::OnePlus => "1+",
+            Prim::OneMinus => "1-",
+            Prim::TwoMul => "2*",
+            Prim::TwoDiv => "2/",
+            Prim::And => "AND",
+            Prim::Or => "OR",
+            Prim::Xor => "XOR",
+            Prim::Invert => "INVERT",
+            Prim::LShift => "LSHIFT",
+            Prim::RShift => "RSHIFT",
+            Prim::Equal => "=",
+            Prim::NotEqual => "<>",
+            Prim::Less => "<",
+            Prim::Greater => ">",
+            Prim::ULess => "U<",
+            Prim::ZeroEqual => "0=",
+            Prim::ZeroLess => "0<",
+            Prim::ZeroGreater => "0>",
+            Prim::True => "TRUE",
+            Prim::False => "FALSE",
+            Prim::Fetch => "@",
+            Prim::Store => "!",
+            Prim::CFetch => "C@",
+            Prim::CStore => "C!",
+            Prim::PlusStore => "+!",
+            Prim::Here => "HERE",
+            Prim::Allot => "ALLOT",
+            Prim::Comma => ",",
+            Prim::CComma => "C,",
+            Prim::Cells => "CELLS",
+            Prim::CellPlus => "CELL+",
+            Prim::Align => "ALIGN",
+            Prim::Fill => "FILL",
+            Prim::Move => "MOVE",
+            Prim::Dot => ".",
+            Prim::UDot => "U.",
+            Prim::DotS => ".S",
+            Prim::Emit => "EMIT",
+            Prim::Cr => "CR",
+            Prim::Space => "SPACE",
+            Prim::Spaces => "SPACES",
+            Prim::Type => "TYPE",
+            Prim::Key => "KEY",
+            Prim::Page => "PAGE",
+            Prim::AtXy => "AT-XY",
+            Prim::Ms => "MS",
+            Prim::Decimal => "DECIMAL",
+            Prim::Hex => "HEX",
+            Prim::Colon => ":",
+            Prim::Semicolon => ";",
+            Prim::Create => "CREATE",
+            Prim::Variable => "VARIABLE",
+            Prim::Constant => "CONSTANT",
+            Prim::Does => "DOES>",
+            Prim::Immediate => "IMMEDIATE",
+            Prim::Tick => "'",
+            Prim::BracketTick => "[']",
+            Prim::Execute => "EXECUTE",
+            Prim::LeftBracket => "[",
+            Prim::RightBracket => "]",
+            Prim::Literal => "LITERAL",
+            Prim::Postpone => "POSTPONE",
+            Prim::CompileComma => "COMPILE,",
+            Prim::Recurse => "RECURSE",
+            Prim::If => "IF",
+            Prim::Else => "ELSE",
+            Prim::Then => "THEN",
+            Prim::Begin => "BEGIN",
+            Prim::Until => "UNTIL",
+            Prim::Again => "AGAIN",
+            Prim::While => "WHILE",
+            Prim::Repeat => "REPEAT",
+            Prim::Do => "DO",
+            Prim::QDo => "?DO",
+            Prim::Loop => "LOOP",
+            Prim::PlusLoop => "+LOOP",
+            Prim::DotQuote => ".\"",
+            Prim::SQuote => "S\"",
+            Prim::DotParen => ".(",
+            Prim::Paren => "(",
+            Prim::Backslash => "\\",
+            Prim::Words => "WORDS",
+            Prim::Forget => "FORGET",
+            Prim::Include => "INCLUDE",
+            Prim::Abort => "ABORT",
+            Prim::Bye => "BYE",
+        }
+    }
+
+    /// Immediate words run even when we're compiling
+    pub(crate) fn is_immediate(self) -> bool {
+        matches!(
+            self,
+            Prim::Semicolon
+                | Prim::Does
+                | Prim::BracketTick
+                | Prim::LeftBracket
+                | Prim::Literal
+                | Prim::Postpone
+                | Prim::Recurse
+                | Prim::If
+                | Prim::Else
+                | Prim::Then
+                | Prim::Begin
+                | Prim::Until
+                | Prim::Again
+                | Prim::While
+                | Prim::Repeat
+                | Prim::Do
+                | Prim::QDo
+                | Prim::Loop
+                | Prim::PlusLoop
+                | Prim::DotQuote
+                | Prim::SQuote
+                | Prim::DotParen
+                | Prim::Paren
+                | Prim::Backslash
+        )
+    }
+
+    /// Some words only make sense inside a colon definition
+    pub(crate) fn is_compile_only(self) -> bool {
+        matches!(
+            self,
+            Prim::Lit
+                | Prim::Branch
+                | Prim::ZeroBranch
+                | Prim::Exit
+                | Prim::DoRt
+                | Prim::QDoRt
+                | Prim::LoopRt
+                | Prim::PlusLoopRt
+                | Prim::DotQuoteRt
+                | Prim::SQuoteRt
+                | Prim::DoesRt
+                | Prim::ToR
+                | Prim::RFrom
+                | Prim::RFetch
+                | Prim::I
+                | Prim::J
+                | Prim::Leave
+                | Prim::Unloop
+                | Prim::Semicolon
+                | Prim::Does
+                | Prim::BracketTick
+                | Prim::LeftBracket
+                | Prim::Literal
+                | Prim::Postpone
+                | Prim::Recurse
+                | Prim::If
+                | Prim::Else
+                | Prim::Then
+                | Prim::Begin
+                | Prim::Until
+                | Prim::Again
+                | Prim::While
+                | Prim::Repeat
+                | Prim::Do
+                | Prim::QDo
+                | Prim::Loop
+                | Prim::PlusLoop
+                | Prim::SQuote
+        )
+    }
+}
+
+/// Convert a Rust boolean into a Forth flag
+fn flag(value: bool) -> Cell {
+    if value {
+        -1
+    } else {
+        0
+    }
+}
+
+impl Forth {
+    /// Run a primitive
+    pub(crate) fn primitive(&mut self, prim: Prim) -> Result<(), Error> {
+        match prim {
+            // Things the compiler puts in colon definitions
+            Prim::Lit => {
+                let value = self.fetch(self.ip)?;
+                self.ip += CELL;
+                self.push(value)?;
+            }
+            Prim::Branch => {
+                self.ip = self.fetch(self.ip)? as usize;
+            }
+            Prim::ZeroBranch => {
+                if self.pop()? == 0 {
+                    self.ip = self.fetch(self.ip)? as usize;
+                } else {
+                    self.ip += CELL;
+                }
+            }
+            Prim::Exit => {
+                self.ip = self.rpop()? as usize;
+            }
+            Prim::DoRt | Prim::QDoRt => {
+                let exit = self.fetch(self.ip)?;
+                self.ip += CELL;
+                let index = self.pop()?;
+                let limit = self.pop()?;
+                if prim == Prim::QDoRt && index == limit {
+                    self.ip = exit as usize;
+                } else {
+                    self.rpush(exit)?;
+                    self.rpush(limit)?;
+                    self.rpush(index)?;
+                }
+            }
+            Prim::LoopRt | Prim::PlusLoopRt => {
+                let step = if prim == Prim::PlusLoopRt {
+                    self.pop()?
+                } else {
+                    1
+                };
+                let dest = self.fetch(self.ip)?;
+                self.ip += CELL;
+                let index = self.rpop()?;
+                let limit = self.rpop()?;
+                let before = index.wrapping_sub(limit);
+                let after = before.wrapping_add(step);
+                let finished = if step >= 0 {
+                    before < 0 && after >= 0
+                } else {
+                    before >= 0 && after < 0
+                };
+                if finished {
+                    self.rpop()?;
+                } else {
+                    self.rpush(limit)?;
+                    self.rpush(index.wrapping_add(step))?;
+                    self.ip = dest as usize;
+                }
+            }
+            Prim::DotQuoteRt | Prim::SQuoteRt => {
+                let len = self.fetch(self.ip)?;
+                let start = self.check_addr((self.ip + CELL) as Cell, len as usize)?;
+                if prim == Prim::DotQuoteRt {
+                    let _ = self.stdout.write(&self.memory[start..start + len as usize]);
+                } else {
+                    self.push(start as Cell)?;
+                    self.push(len)?;
+                }
+                let end = start + len as usize;
+                self.ip = end + (CELL - end % CELL) % CELL;
+            }
+            Prim::DoesRt => {
+                // Make the word we just created run the code after DOES>,
+                // then return from the defining word
+                let does = self.ip;
+                if let Some(header) = self.words[0..self.word_count].last_mut() {
+                    if let Kind::Create(addr, _) = header.kind {
+                        header.kind = Kind::Create(addr, Some(does));
+                    }
+                }
+                self.ip = self.rpop()? as usize;
+            }
+
+            // Stack manipulation
+            Prim::Dup => self.push(self.peek(0)?)?,
+            Prim::Drop => {
+                self.pop()?;
+            }
+            Prim::Swap => {
+                let b = self.pop()?;
+                let a = self.pop()?;
+                self.push(b)?;
+                self.push(a)?;
+            }
+            Prim::Over => self.push(self.peek(1)?)?,
+            Prim::Rot => {
+                let c = self.pop()?;
+                let b = self.pop()?;
+                let a = self.pop()?;
+                self.push(b)?;
+                self.push(c)?;
+                self.push(a)?;
+            }
+            Prim::MinusRot => {
+                let c = self.pop()?;
+                let b = self.pop()?;
+                let a = self.pop()?;
+                self.push(c)?;
+                self.push(a)?;
+                self.push(b)?;
+            }
+            Prim::Nip => {
+                let b = self.pop()?;
+                self.pop()?;
+                self.push(b)?;
+            }
+            Prim::Tuck => {
+                let b = self.pop()?;
+                let a = self.pop()?;
+                self.push(b)?;
+                self.push(a)?;
+                self.push(b)?;
+            }
+            Prim::Pick => {
+                let depth = self.pop()?;
+                let value =
+                    self.peek(usize::try_from(depth).map_err(|_| Error::StackUnderflow)?)?;
+                self.push(value)?;
+            }
+            Prim::QDup => {
+                let value = self.peek(0)?;
+                if value != 0 {
+                    self.push(value)?;
+                }
+            }
+            Prim::TwoDup => {
+                let b = self.peek(0)?;
+                let a = self.peek(1)?;
+                self.push(a)?;
+                self.push(b)?;
+            }
+            Prim::TwoDrop => {
+                self.pop()?;
+                self.pop()?;
+            }
+            Prim::TwoSwap => {
+                let d = self.pop()?;
+                let c = self.pop()?;
+                let b = self.pop()?;
+                let a = self.pop()?;
+                self.push(c)?;
+                self.push(d)?;
+                self.push(a)?;
+                self.push(b)?;
+            }
+            Prim::TwoOver => {
+                let a = self.peek(3)?;
+                let b = self.peek(2)?;
+                self.push(a)?;
+                self.push(b)?;
+            }
+            Prim::Depth => self.push(self.dsp as Cell)?,
+
+            // Return stack
+            Prim::ToR => {
+                let value = self.pop()?;
+                self.rpush(value)?;
+            }
+            Prim::RFrom => {
+                let value = self.rpop()?;
+                self.push(value)?;
+            }
+            Prim::RFetch | Prim::I => {
+                let value = self.rpeek(0)?;
+                self.push(value)?;
+            }
+            Prim::J => {
+                // Skip the inner loop's index, limit and exit address
+                let value = self.rpeek(3)?;
+                self.push(value)?;
+            }
+            Prim::Leave => {
+                self.rpop()?;
+                self.rpop()?;
+                self.ip = self.rpop()? as usize;
+            }
+            Prim::Unloop => {
+                self.rpop()?;
+                self.rpop()?;
+                self.rpop()?;
+            }
+
+            // Arithmetic and logic
+            Prim::Add => self.binary(|a, b| Ok(a.wrapping_add(b)))?,
+            Prim::Sub => self.binary(|a, b| Ok(a.wrapping_sub(b)))?,
+            Prim::Mul => self.binary(|a, b| Ok(a.wrapping_mul(b)))?,
+            Prim::Div => self.binary(|a, b| floored_div(a, b).map(|(_, q)| q))?,
+            Prim::Mod => self.binary(|a, b| floored_div(a, b).map(|(r, _)| r))?,
+            Prim::DivMod => {
+                let b = self.pop()?;
+                let a = self.pop()?;
+                let (rem, quot) = floored_div(a, b)?;
+                self.push(rem)?;
+                self.push(quot)?;
+            }
+            Prim::MulDiv => {
+                let c = self.pop()?;
+                let b = self.pop()?;
+                let a = self.pop()?;
+                if c == 0 {
+                    return Err(Error::DivideByZero);
+                }
+                // Use a double-width intermediate, as the standard requires
+                let result = (i64::from(a) * i64::from(b)).div_euclid(i64::from(c));
+                self.push(result as Cell)?;
+            }
+            Prim::Negate => self.unary(|a| a.wrapping_neg())?,
+            Prim::Abs => self.unary(|a| a.wrapping_abs())?,
+            Prim::Min => self.binary(|a, b| Ok(a.min(b)))?,
+            Prim::Max => self.binary(|a, b| Ok(a.max(b)))?,
+            Prim::OnePlus => self.unary(|a| a.wrapping_add(1))?,
+            Prim::OneMinus => self.unary(|a| a.wrapping_sub(1))?,
+            Prim::TwoMul => self.unary(|a| a.wrapping_shl(1))?,
+            Prim::TwoDiv => self.unary(|a| a >> 1)?,
+            Prim::And => self.binary(|a, b| Ok(a & b))?,
+            Prim::Or => self.binary(|a, b| Ok(a | b))?,
+            Prim::Xor => self.binary(|a, b| Ok(a ^ b))?,
+            Prim::Invert => self.unary(|a| !a)?,
+            Prim::LShift => {
+                self.binary(|a, b| Ok(if (0..32).contains(&b) { a << b } else { 0 }))?
+            }
+            Prim::RShift => self.binary(|a, b| {
+                Ok(if (0..32).contains(&b) {
+                    ((a as u32) >> b) as Cell
+                } else {
+                    0
+                })
+            })?,
+            Prim::Equal => self.binary(|a, b| Ok(flag(a == b)))?,
+            Prim::NotEqual => self.binary(|a, b| Ok(flag(a != b)))?,
+            Prim::Less => self.binary(|a, b| Ok(flag(a < b)))?,
+            Prim::Greater => self.binary(|a, b| Ok(flag(a > b)))?,
+            Prim::ULess => self.binary(|a, b| Ok(flag((a as u32) < (b as u32))))?,
+            Prim::ZeroEqual => self.unary(|a| flag(a == 0))?,
+            Prim::ZeroLess => self.unary(|a| flag(a < 0))?,
+            Prim::ZeroGreater => self.unary(|a| flag(a > 0))?,
+            Prim::True => self.push(-1)?,
+            Prim::False => self.push(0)?,
+
+            // Memory
+            Prim::Fetch => {
+                let addr = self.pop()?;
+                let addr = self.check_addr(addr, CELL)?;
+                self.push(self.fetch(addr)?)?;
+            }
+            Prim::Store => {
+                let addr = self.pop()?;
+                let value = self.pop()?;
+                let addr = self.check_addr(addr, CELL)?;
+                self.store(addr, value)?;
+            }
+            Prim::CFetch => {
+                let addr = self.pop()?;
+                let addr = self.check_addr(addr, 1)?;
+                self.push(Cell::from(self.memory[addr]))?;
+            }
+            Prim::CStore => {
+                let addr = self.pop()?;
+                let value = self.pop()?;
+                let addr = self.check_addr(addr, 1)?;
+                self.memory[addr] = value as u8;
+            }
+            Prim::PlusStore => {
+                let addr = self.pop()?;
+                let value = self.pop()?;
+                let addr = self.check_addr(addr, CELL)?;
+                let old = self.fetch(addr)?;
+                self.store(addr, old.wrapping_add(value))?;
+            }
+            Prim::Here => self.push(self.here as Cell)?,
+            Prim::Allot => {
+                let bytes = self.pop()?;
+                self.allot(bytes)?;
+            }
+            Prim::Comma => {
+                let value = self.pop()?;
+                self.comma(value)?;
+            }
+            Prim::CComma => {
+                let value = self.pop()?;
+                self.comma_byte(value as u8)?;
+            }
+            Prim::Cells => self.unary(|a| a.wrapping_mul(CELL as Cell))?,
+            Prim::CellPlus => self.unary(|a| a.wrapping_add(CELL as Cell))?,
+            Prim::Align => self.align()?,
+            Prim::Fill => {
+                let value = self.pop()?;
+                let len = self.pop()?.max(0) as usize;
+                let addr = self.pop()?;
+                let addr = self.check_addr(addr, len)?;
+                self.memory[addr..addr + len].fill(value as u8);
+            }
+            Prim::Move => {
+                let len = self.pop()?.max(0) as usize;
+                let dest = self.pop()?;
+                let src = self.pop()?;
+                let dest = self.check_addr(dest, len)?;
+                let src = self.check_addr(src, len)?;
+                self.memory.copy_within(src..src + len, dest);
+            }
+
+            // Input and output
+            Prim::Dot => {
+                let value = self.pop()?;
+                self.print_number(value, false);
+            }
+            Prim::UDot => {
+                let value = self.pop()?;
+                self.print_number(value, true);
+            }
+            Prim::DotS => {
+                let _ = write!(self.stdout, "<{}> ", self.dsp);
+                for idx in 0..self.dsp {
+                    self.print_number(self.data_stack[idx], false);
+                }
+            }
+            Prim::Emit => {
+                let ch = self.pop()?;
+                let _ = self.stdout.write(&[ch as u8]);
+            }
+            Prim::Cr => {
+                let _ = writeln!(self.stdout);
+            }
+            Prim::Space => {
+                let _ = self.stdout.write_str(" ");
+            }
+            Prim::Spaces => {
+                let count = self.pop()?;
+                for _ in 0..count.max(0) {
+                    let _ = self.stdout.write_str(" ");
+                }
+            }
+            Prim::Type => {
+                let len = self.pop()?.max(0) as usize;
+                let addr = self.pop()?;
+                let addr = self.check_addr(addr, len)?;
+                let _ = self.stdout.write(&self.memory[addr..addr + len]);
+            }
+            Prim::Key => {
+                let key = loop {
+                    let mut buffer = [0u8; 1];
+                    if let Ok(1) = self.stdin.read(&mut buffer) {
+                        break buffer[0];
+                    }
+                    neotron_sdk::delay(core::time::Duration::from_millis(10));
+                };
+                self.push(Cell::from(key))?;
+            }
+            Prim::Page => {
+                console::clear_screen(&mut self.stdout);
+                console::move_cursor(&mut self.stdout, console::Position::origin());
+            }
+            Prim::AtXy => {
+                let row = self.pop()?;
+                let col = self.pop()?;
+                console::move_cursor(
+                    &mut self.stdout,
+                    console::Position {
+                        row: row.clamp(0, 255) as u8,
+                        col: col.clamp(0, 255) as u8,
+                    },
+                );
+            }
+            Prim::Ms => {
+                let ms = self.pop()?;
+                neotron_sdk::delay(core::time::Duration::from_millis(ms.max(0) as u64));
+            }
+            Prim::Decimal => self.store(self.base_addr, 10)?,
+            Prim::Hex => self.store(self.base_addr, 16)?,
+
+            // Defining words
+            Prim::Colon => {
+                self.align()?;
+                self.add_named_word(Kind::Colon(self.here))?;
+                self.words[self.word_count - 1].hidden = true;
+                self.compiling = true;
+            }
+            Prim::Semicolon => {
+                self.comma(self.xt_of(Prim::Exit))?;
+                self.words[self.word_count - 1].hidden = false;
+                self.compiling = false;
+            }
+            Prim::Create => {
+                self.align()?;
+                self.add_named_word(Kind::Create(self.here, None))?;
+            }
+            Prim::Variable => {
+                self.align()?;
+                self.add_named_word(Kind::Create(self.here, None))?;
+                self.comma(0)?;
+            }
+            Prim::Constant => {
+                let value = self.pop()?;
+                self.add_named_word(Kind::Constant(value))?;
+            }
+            Prim::Does => {
+                self.comma(self.xt_of(Prim::DoesRt))?;
+            }
+            Prim::Immediate => {
+                if self.word_count > self.first_user_word {
+                    self.words[self.word_count - 1].immediate = true;
+                }
+            }
+            Prim::Tick => {
+                let xt = self.parse_word()?;
+                self.push(xt as Cell)?;
+            }
+            Prim::BracketTick => {
+                let xt = self.parse_word()?;
+                self.compile_literal(xt as Cell)?;
+            }
+            Prim::Execute => {
+                let xt = self.pop()?;
+                self.call(usize::try_from(xt).map_err(|_| Error::BadAddress)?)?;
+            }
+            Prim::LeftBracket => self.compiling = false,
+            Prim::RightBracket => self.compiling = true,
+            Prim::Literal => {
+                let value = self.pop()?;
+                self.compile_literal(value)?;
+            }
+            Prim::Postpone => {
+                let xt = self.parse_word()?;
+                if self.words[xt].immediate {
+                    self.comma(xt as Cell)?;
+                } else {
+                    self.compile_literal(xt as Cell)?;
+                    self.comma(self.xt_of(Prim::CompileComma))?;
+                }
+            }
+            Prim::CompileComma => {
+                let xt = self.pop()?;
+                self.comma(xt)?;
+            }
+            Prim::Recurse => {
+                self.comma((self.word_count - 1) as Cell)?;
+            }
+
+            // Control structures, which use the data stack at compile time
+            Prim::If => {
+                self.comma(self.xt_of(Prim::ZeroBranch))?;
+                self.push_orig()?;
+            }
+            Prim::Else => {
+                self.comma(self.xt_of(Prim::Branch))?;
+                let if_orig = self.pop_control(TAG_ORIG)?;
+                self.push_orig()?;
+                self.store(if_orig, self.here as Cell)?;
+            }
+            Prim::Then => {
+                let orig = self.pop_control(TAG_ORIG)?;
+                self.store(orig, self.here as Cell)?;
+            }
+            Prim::Begin => {
+                self.push(self.here as Cell)?;
+                self.push(TAG_DEST)?;
+            }
+            Prim::Until | Prim::Again => {
+                let dest = self.pop_control(TAG_DEST)?;
+                let branch = if prim == Prim::Until {
+                    Prim::ZeroBranch
+                } else {
+                    Prim::Branch
+                };
+                self.comma(self.xt_of(branch))?;
+                self.comma(dest as Cell)?;
+            }
+            Prim::While => {
+                let dest = self.pop_control(TAG_DEST)?;
+                self.comma(self.xt_of(Prim::ZeroBranch))?;
+                self.push_orig()?;
+                self.push(dest as Cell)?;
+                self.push(TAG_DEST)?;
+            }
+            Prim::Repeat => {
+                let dest = self.pop_control(TAG_DEST)?;
+                let orig = self.pop_control(TAG_ORIG)?;
+                self.comma(self.xt_of(Prim::Branch))?;
+                self.comma(dest as Cell)?;
+                self.store(orig, self.here as Cell)?;
+            }
+            Prim::Do | Prim::QDo => {
+                let runtime = if prim == Prim::Do {
+                    Prim::DoRt
+                } else {
+                    Prim::QDoRt
+                };
+                self.comma(self.xt_of(runtime))?;
+                // The exit address gets filled in by LOOP
+                self.push(self.here as Cell)?;
+                self.comma(0)?;
+                self.push(self.here as Cell)?;
+                self.push(TAG_DO)?;
+            }
+            Prim::Loop | Prim::PlusLoop => {
+                let dest = self.pop_control(TAG_DO)?;
+                let exit = self.pop()?;
+                let runtime = if prim == Prim::Loop {
+                    Prim::LoopRt
+                } else {
+                    Prim::PlusLoopRt
+                };
+                self.comma(self.xt_of(runtime))?;
+                self.comma(dest as Cell)?;
+                let exit = self.check_addr(exit, CELL)?;
+                self.store(exit, self.here as Cell)?;
+            }
+
+            // Strings and comments
+            Prim::DotQuote if !self.compiling => {
+                // Handy at the prompt, so we allow it
+                let (start, end) = self.parse_until(b'"');
+                let _ = self.stdout.write(&self.input[start..end]);
+            }
+            Prim::DotQuote | Prim::SQuote => {
+                let (start, end) = self.parse_until(b'"');
+                let runtime = if prim == Prim::DotQuote {
+                    Prim::DotQuoteRt
+                } else {
+                    Prim::SQuoteRt
+                };
+                self.comma(self.xt_of(runtime))?;
+                self.compile_string(start, end)?;
+            }
+            Prim::DotParen => {
+                let (start, end) = self.parse_until(b')');
+                let _ = self.stdout.write(&self.input[start..end]);
+            }
+            Prim::Paren => {
+                self.parse_until(b')');
+            }
+            Prim::Backslash => {
+                self.input_pos = self.input_len;
+            }
+
+            // The system
+            Prim::Words => self.list_words(),
+            Prim::Forget => {
+                let xt = self.parse_word()?;
+                if xt < self.first_user_word {
+                    return Err(Error::Undefined);
+                }
+                match self.words[xt].kind {
+                    Kind::Colon(addr) | Kind::Create(addr, _) => self.here = addr,
+                    _ => {}
+                }
+                self.word_count = xt;
+            }
+            Prim::Include => {
+                let (start, end) = self.next_token();
+                if start == end {
+                    return Err(Error::MissingName);
+                }
+                let mut name = [0u8; 64];
+                let len = (end - start).min(name.len());
+                name[0..len].copy_from_slice(&self.input[start..start + len]);
+                let filename =
+                    core::str::from_utf8(&name[0..len]).map_err(|_| Error::MissingName)?;
+                self.include_file(filename)?;
+            }
+            Prim::Abort => return Err(Error::Abort),
+            Prim::Bye => return Err(Error::Bye),
+        }
+        Ok(())
+    }
+
+    /// Apply a function to the top of the stack
+    fn unary<F>(&mut self, f: F) -> Result<(), Error>
+    where
+        F: Fn(Cell) -> Cell,
+    {
+        let a = self.pop()?;
+        self.push(f(a))
+    }
+
+    /// Apply a function to the top two items on the stack
+    fn binary<F>(&mut self, f: F) -> Result<(), Error>
+    where
+        F: Fn(Cell, Cell) -> Result<Cell, Error>,
+    {
+        let b = self.pop()?;
+        let a = self.pop()?;
+        self.push(f(a, b)?)
+    }
+
+    /// Look at a value on the return stack, 0 being the top
+    fn rpeek(&self, depth: usize) -> Result<Cell, Error> {
+        if depth >= self.rsp {
+            return Err(Error::ReturnStack);
+        }
+        Ok(self.return_stack[self.rsp - 1 - depth])
+    }
+
+    /// Read a word name from the input and find it
+    fn parse_word(&mut self) -> Result<usize, Error> {
+        let (start, end) = self.next_token();
+        if start == end {
+            return Err(Error::MissingName);
+        }
+        let mut name = [0u8; Forth::MAX_NAME];
+        let len = (end - start).min(Forth::MAX_NAME);
+        name[0..len].copy_from_slice(&self.input[start..start + len]);
+        self.find(&name[0..len]).ok_or(Error::Undefined)
+    }
+
+    /// Compile a forward branch address to be filled in later
+    fn push_orig(&mut self) -> Result<(), Error> {
+        self.push(self.here as Cell)?;
+        self.comma(0)?;
+        self.push(TAG_ORIG)
+    }
+
+    /// Pop an entry from the control-flow stack, checking its tag
+    fn pop_control(&mut self, tag: Cell) -> Result<usize, Error> {
+        if !self.compiling {
+            return Err(Error::CompileOnly);
+        }
+        if self.pop()? != tag {
+            return Err(Error::BadControl);
+        }
+        let addr = self.pop()?;
+        self.check_addr(addr, CELL)
+    }
+
+    /// Print the names of all the words, newest first
+    fn list_words(&mut self) {
+        let mut col = 0;
+        for idx in (0..self.word_count).rev() {
+            let header = self.words[idx];
+            if header.hidden || header.name().first() == Some(&b'(') && header.name_len > 1 {
+                continue;
+            }
+            let len = usize::from(header.name_len);
+            if col + len + 1 > 78 {
+                let _ = writeln!(self.stdout);
+                col = 0;
+            }
+            let _ = self.stdout.write(header.name());
+            let _ = self.stdout.write_str(" ");
+            col += len + 1;
+        }
+    }
+}
+
+/// Floored division, as most Forths do it.
+///
+/// Returns the remainder and the quotient.
+fn floored_div(a: Cell, b: Cell) -> Result<(Cell, Cell), Error> {
+    if b == 0 {
+        return Err(Error::DivideByZero);
+    }
+    let mut quotient = a.wrapping_div(b);
+    let mut remainder = a.wrapping_rem(b);
+    if remainder != 0 && ((remainder < 0) != (b < 0)) {
+        quotient -= 1;
+        remainder += b;
+    }
+    Ok((remainder, quotient))
+}
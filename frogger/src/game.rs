@@ -0,0 +1,410 @@
+//! The frog, the traffic and the logs, and what happens when they meet.
+//!
+//! The field is thirteen rows. The frog starts on the bank at the bottom,
+//! crosses five lanes of road to the bank in the middle, then five lanes of
+//! river to the hedge at the top, which has five bays to hop into. Fill all
+//! five and the next level comes round faster.
+
+use neotron_screen::{Attr, Colour};
+use neotron_sprite::Sprite;
+
+/// How many columns the field is
+pub(crate) const FIELD_WIDTH: i16 = 80;
+
+/// How many rows the field is
+pub(crate) const FIELD_HEIGHT: i16 = 13;
+
+/// The row with the hedge and the bays in
+pub(crate) const HOME_ROW: i16 = 0;
+
+/// The first and last rows of river
+pub(crate) const RIVER_ROWS: (i16, i16) = (1, 5);
+
+/// The bank between the river and the road
+pub(crate) const MIDDLE_ROW: i16 = 6;
+
+/// The first and last rows of road
+pub(crate) const ROAD_ROWS: (i16, i16) = (7, 11);
+
+/// The bank the frog starts on
+pub(crate) const START_ROW: i16 = 12;
+
+/// How many bays there are in the hedge
+pub(crate) const BAYS: usize = 5;
+
+/// Where the first bay starts, and how far apart they are
+const BAY_START: i16 = 6;
+const BAY_SPACING: i16 = 16;
+
+/// How wide each bay is
+pub(crate) const BAY_WIDTH: i16 = 4;
+
+/// How many milliseconds a tick is
+pub(crate) const TICK_MS: u32 = 50;
+
+/// How many ticks in a second
+pub(crate) const TICKS_PER_SECOND: u32 = 1000 / TICK_MS;
+
+/// How long the frog has to get home, in seconds
+pub(crate) const TIME_LIMIT: u32 = 30;
+
+/// How many lives a game starts with
+const LIVES: u8 = 3;
+
+/// How many ticks the frog lies squashed before the next one starts
+const DYING_TICKS: u8 = 20;
+
+/// How far a hop goes sideways, in columns
+const HOP_COLUMNS: i16 = 2;
+
+/// How many levels it takes the traffic to get up to full speed
+const FASTEST_LEVEL: u32 = 8;
+
+/// Things going off one side of the field come back on the other this many
+/// columns later, which leaves room for the longest log to get right off
+const WRAP: i16 = FIELD_WIDTH + 16;
+
+/// How far left things go before they wrap round
+const WRAP_LEFT: i16 = FIELD_WIDTH - WRAP;
+
+/// The most things in one lane
+const MAX_THINGS: usize = 4;
+
+/// The frog
+pub(crate) const FROG: Sprite =
+    Sprite::new(&["@@"], Attr::new(Colour::Green, Colour::Black).bold());
+
+/// What's left of a frog
+pub(crate) const SPLAT: Sprite = Sprite::new(&["><"], Attr::new(Colour::Red, Colour::Black).bold());
+
+const CAR_RED: Sprite = Sprite::new(&["▐██▌"], Attr::new(Colour::Red, Colour::Black).bold());
+const CAR_YELLOW: Sprite = Sprite::new(&["▐██▌"], Attr::new(Colour::Yellow, Colour::Black).bold());
+const CAR_CYAN: Sprite = Sprite::new(&["▐██▌"], Attr::new(Colour::Cyan, Colour::Black));
+const TRUCK: Sprite = Sprite::new(&["▐██████▌"], Attr::new(Colour::White, Colour::Black));
+
+const LOG_SHORT: Sprite = Sprite::new(&["▓▓▓▓▓▓"], Attr::new(Colour::Yellow, Colour::Blue));
+const LOG_MEDIUM: Sprite = Sprite::new(&["▓▓▓▓▓▓▓▓▓"], Attr::new(Colour::Yellow, Colour::Blue));
+const LOG_LONG: Sprite = Sprite::new(&["▓▓▓▓▓▓▓▓▓▓▓▓"], Attr::new(Colour::Yellow, Colour::Blue));
+
+/// What goes along a lane, and how fast
+#[derive(Debug, Copy, Clone)]
+struct LaneSpec {
+    sprite: Sprite,
+    count: u8,
+    /// Hundredths of a column a tick, on the first level. Less than zero
+    /// goes left.
+    speed: i16,
+}
+
+/// The river, top to bottom
+const RIVER: [LaneSpec; 5] = [
+    LaneSpec {
+        sprite: LOG_LONG,
+        count: 3,
+        speed: 20,
+    },
+    LaneSpec {
+        sprite: LOG_SHORT,
+        count: 4,
+        speed: -30,
+    },
+    LaneSpec {
+        sprite: LOG_MEDIUM,
+        count: 3,
+        speed: 15,
+    },
+    LaneSpec {
+        sprite: LOG_SHORT,
+        count: 4,
+        speed: -25,
+    },
+    LaneSpec {
+        sprite: LOG_LONG,
+        count: 3,
+        speed: 35,
+    },
+];
+
+/// The road, top to bottom
+const ROAD: [LaneSpec; 5] = [
+    LaneSpec {
+        sprite: TRUCK,
+        count: 2,
+        speed: -20,
+    },
+    LaneSpec {
+        sprite: CAR_YELLOW,
+        count: 3,
+        speed: 40,
+    },
+    LaneSpec {
+        sprite: CAR_CYAN,
+        count: 4,
+        speed: -25,
+    },
+    LaneSpec {
+        sprite: TRUCK,
+        count: 2,
+        speed: 15,
+    },
+    LaneSpec {
+        sprite: CAR_RED,
+        count: 3,
+        speed: -20,
+    },
+];
+
+/// Something that happened, which the player might want to hear about
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum Event {
+    /// Nothing worth mentioning
+    None,
+    /// The frog hopped
+    Hopped,
+    /// The frog was run over, or hopped into the hedge
+    Squashed,
+    /// The frog fell in the river, or was carried off the edge
+    Drowned,
+    /// The frog ran out of time
+    TimeUp,
+    /// The frog got into a bay
+    Home,
+    /// Every bay is full, and the next level has started
+    Cleared,
+    /// The last frog has gone
+    GameOver,
+}
+
+/// One row of traffic or logs
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Lane {
+    pub(crate) row: i16,
+    pub(crate) sprite: Sprite,
+    count: usize,
+    /// Where the left of each thing is
+    xs: [i16; MAX_THINGS],
+    /// Hundredths of a column a tick
+    speed: i16,
+    /// Hundredths of a column moved since the last whole column
+    progress: i16,
+}
+
+impl Lane {
+    /// An empty lane
+    const EMPTY: Lane = Lane {
+        row: 0,
+        sprite: FROG,
+        count: 0,
+        xs: [0; MAX_THINGS],
+        speed: 0,
+        progress: 0,
+    };
+
+    /// Where each thing is
+    pub(crate) fn xs(&self) -> &[i16] {
+        &self.xs[0..self.count]
+    }
+
+    /// Move along for a tick. Gives how many columns we moved.
+    fn step(&mut self) -> i16 {
+        self.progress += self.speed;
+        let columns = self.progress / 100;
+        self.progress %= 100;
+        if columns != 0 {
+            for x in self.xs[0..self.count].iter_mut() {
+                *x = (*x + columns - WRAP_LEFT).rem_euclid(WRAP) + WRAP_LEFT;
+            }
+        }
+        columns
+    }
+
+    /// Does anything in this lane touch the frog?
+    fn touches(&self, frog_x: i16) -> bool {
+        self.xs()
+            .iter()
+            .any(|x| self.sprite.collides(*x, self.row, &FROG, frog_x, self.row))
+    }
+}
+
+/// A game in progress
+pub(crate) struct Game {
+    pub(crate) lanes: [Lane; 10],
+    /// Where the frog is, as (column, row)
+    pub(crate) frog: (i16, i16),
+    /// The nearest the frog has got to the hedge this life, for scoring
+    furthest: i16,
+    /// Which bays have a frog in
+    pub(crate) bays: [bool; BAYS],
+    pub(crate) score: u32,
+    pub(crate) lives: u8,
+    pub(crate) level: u32,
+    /// Ticks left before the frog runs out of time
+    pub(crate) ticks_left: u32,
+    /// Ticks left of showing a squashed frog, if it's been squashed
+    pub(crate) dying: u8,
+}
+
+impl Game {
+    /// A game that hasn't started
+    pub(crate) const fn new() -> Game {
+        Game {
+            lanes: [Lane::EMPTY; 10],
+            frog: (0, START_ROW),
+            furthest: START_ROW,
+            bays: [false; BAYS],
+            score: 0,
+            lives: 0,
+            level: 0,
+            ticks_left: 0,
+            dying: 0,
+        }
+    }
+
+    /// Start a new game
+    pub(crate) fn start(&mut self, rng: &mut neotron_rand::Rng) {
+        self.score = 0;
+        self.lives = LIVES;
+        self.level = 1;
+        self.start_level(rng);
+    }
+
+    /// Set the lanes going at this level's speed, with the bays empty
+    fn start_level(&mut self, rng: &mut neotron_rand::Rng) {
+        self.bays = [false; BAYS];
+        let faster = self.level.min(FASTEST_LEVEL) as i16 - 1;
+        let specs = RIVER.iter().chain(ROAD.iter());
+        let rows = (RIVER_ROWS.0..=RIVER_ROWS.1).chain(ROAD_ROWS.0..=ROAD_ROWS.1);
+        for ((lane, spec), row) in self.lanes.iter_mut().zip(specs).zip(rows) {
+            let count = usize::from(spec.count).min(MAX_THINGS);
+            let spacing = WRAP / count as i16;
+            let offset = rng.below(spacing as u32) as i16;
+            *lane = Lane {
+                row,
+                sprite: spec.sprite,
+                count,
+                xs: [0; MAX_THINGS],
+                // Each level adds a fifth of the first level's speed
+                speed: spec.speed + spec.speed * faster / 5,
+                progress: 0,
+            };
+            for (idx, x) in lane.xs[0..count].iter_mut().enumerate() {
+                *x = WRAP_LEFT + offset + idx as i16 * spacing;
+            }
+        }
+        self.new_frog();
+    }
+
+    /// Put a fresh frog on the start bank
+    fn new_frog(&mut self) {
+        self.frog = (FIELD_WIDTH / 2, START_ROW);
+        self.furthest = START_ROW;
+        self.ticks_left = TIME_LIMIT * TICKS_PER_SECOND;
+        self.dying = 0;
+    }
+
+    /// Where a bay starts
+    pub(crate) fn bay_x(bay: usize) -> i16 {
+        BAY_START + bay as i16 * BAY_SPACING
+    }
+
+    /// Hop the frog one step, `dx` columns and `dy` rows at a time
+    pub(crate) fn hop(&mut self, dx: i16, dy: i16, rng: &mut neotron_rand::Rng) -> Event {
+        if self.dying > 0 {
+            return Event::None;
+        }
+        let x = (self.frog.0 + dx * HOP_COLUMNS).clamp(0, FIELD_WIDTH - FROG.width());
+        let y = (self.frog.1 + dy).clamp(HOME_ROW, START_ROW);
+        if (x, y) == self.frog {
+            return Event::None;
+        }
+        self.frog = (x, y);
+        if y < self.furthest {
+            self.furthest = y;
+            self.score += 10;
+        }
+        if y == HOME_ROW {
+            return self.reach_hedge(rng);
+        }
+        self.check().unwrap_or(Event::Hopped)
+    }
+
+    /// The frog has hopped up to the hedge. Either it's hopped into an
+    /// empty bay, or it's hit the hedge.
+    fn reach_hedge(&mut self, rng: &mut neotron_rand::Rng) -> Event {
+        let x = self.frog.0;
+        let bay = (0..BAYS).find(|bay| {
+            let left = Game::bay_x(*bay);
+            x >= left && x + FROG.width() <= left + BAY_WIDTH
+        });
+        let Some(bay) = bay.filter(|bay| !self.bays[*bay]) else {
+            return self.die(Event::Squashed);
+        };
+        self.bays[bay] = true;
+        self.score += 50 + 10 * (self.ticks_left / TICKS_PER_SECOND);
+        if self.bays.iter().all(|full| *full) {
+            self.score += 1000;
+            self.level += 1;
+            self.start_level(rng);
+            Event::Cleared
+        } else {
+            self.new_frog();
+            Event::Home
+        }
+    }
+
+    /// Move everything on by a tick
+    pub(crate) fn step(&mut self) -> Event {
+        for lane in self.lanes.iter_mut() {
+            let moved = lane.step();
+            // Logs carry the frog along with them
+            if self.dying == 0 && lane.row == self.frog.1 && is_river(lane.row) {
+                self.frog.0 += moved;
+            }
+        }
+        if self.dying > 0 {
+            self.dying -= 1;
+            if self.dying > 0 {
+                return Event::None;
+            }
+            self.lives = self.lives.saturating_sub(1);
+            if self.lives == 0 {
+                return Event::GameOver;
+            }
+            self.new_frog();
+            return Event::None;
+        }
+        self.ticks_left = self.ticks_left.saturating_sub(1);
+        if self.ticks_left == 0 {
+            return self.die(Event::TimeUp);
+        }
+        self.check().unwrap_or(Event::None)
+    }
+
+    /// See if the frog has come to a sticky end where it is
+    fn check(&mut self) -> Option<Event> {
+        let (x, y) = self.frog;
+        if x < 0 || x + FROG.width() > FIELD_WIDTH {
+            return Some(self.die(Event::Drowned));
+        }
+        let lane = self.lanes.iter().find(|lane| lane.row == y)?;
+        let touching = lane.touches(x);
+        if is_river(y) && !touching {
+            Some(self.die(Event::Drowned))
+        } else if !is_river(y) && touching {
+            Some(self.die(Event::Squashed))
+        } else {
+            None
+        }
+    }
+
+    /// Squash the frog, and say how
+    fn die(&mut self, how: Event) -> Event {
+        self.dying = DYING_TICKS;
+        how
+    }
+}
+
+/// Is this row river?
+pub(crate) fn is_river(row: i16) -> bool {
+    (RIVER_ROWS.0..=RIVER_ROWS.1).contains(&row)
+}
@@ -0,0 +1,421 @@
+//! Game logic for Frogger
+//!
+//! Get the frog across the road, without being run over, and across the
+//! river, hopping from log to log, into one of the bays in the hedge. Fill
+//! all five bays to clear the level, and the traffic and logs speed up.
+//!
+//! Everything is drawn into a [`neotron_screen::Screen`] with
+//! [`neotron_sprite`], so each tick only sends what moved.
+
+#![no_std]
+#![deny(missing_docs)]
+#![deny(unsafe_code)]
+
+use core::fmt::Write;
+
+use neotron_input::{KeyEvent, Keyboard};
+use neotron_screen::{Attr, Cell, Colour};
+use neotron_sdk::console;
+use neotron_sprite::{TileMap, Tileset};
+use neotron_tui::Line;
+
+mod game;
+mod scenes;
+mod sounds;
+
+use game::{Event, Game, FIELD_HEIGHT, FIELD_WIDTH};
+use scenes::Screen;
+
+/// How big the screen is
+const SCREEN_WIDTH: usize = 80;
+const SCREEN_HEIGHT: usize = 25;
+
+/// Where the score and lives go
+const HUD_ROW: u8 = 1;
+
+/// Where the top of the field goes
+const FIELD_TOP: i16 = 4;
+
+/// Where the time left goes
+const TIMER_ROW: u8 = 18;
+
+/// Where messages go, like the level number
+const MESSAGE_ROW: u8 = 20;
+
+/// Where the keys are listed
+const HELP_ROW: u8 = 23;
+
+/// How long a message stays up, in ticks
+const MESSAGE_TICKS: u32 = 2 * game::TICKS_PER_SECOND;
+
+/// Where we keep the best score
+const SCORE_FILE: &str = "FROGGER.DAT";
+
+/// The tiles the field is made of
+const EMPTY: u8 = 0;
+const HEDGE: u8 = 1;
+const WATER: u8 = 2;
+const BANK: u8 = 3;
+
+/// What each tile looks like
+const TILES: Tileset = Tileset::new(&[
+    Cell::BLANK,
+    Cell::new('█', Attr::new(Colour::Green, Colour::Black)),
+    Cell::new('≈', Attr::new(Colour::Cyan, Colour::Blue)),
+    Cell::new('▒', Attr::new(Colour::Magenta, Colour::Black)),
+]);
+
+/// How the titles look
+const TITLE: Attr = Attr::new(Colour::Yellow, Colour::Black).bold();
+
+/// The big letters on the title screen
+const BANNER: [&str; 5] = [
+    "█▀▀ █▀█ █▀█ █▀▀ █▀▀ █▀▀ █▀█",
+    "█▀  █▀▄ █ █ █ █ █ █ █▀  █▀▄",
+    "▀   ▀ ▀ ▀▀▀ ▀▀▀ ▀▀▀ ▀▀▀ ▀ ▀",
+    "",
+    "Get the frog home, five times over",
+];
+
+/// Represents the Frogger application
+///
+/// An application can play multiple games.
+pub struct App {
+    stdout: neotron_sdk::File,
+    screen: neotron_screen::Screen<SCREEN_WIDTH, SCREEN_HEIGHT>,
+    /// The hedge, the river, the banks and the road
+    map: TileMap<{ FIELD_WIDTH as usize }, { FIELD_HEIGHT as usize }>,
+    game: Game,
+    rng: neotron_rand::Rng,
+    sounds: neotron_sfx::Player<'static>,
+    high_score: neotron_save::HighScores<1>,
+    /// What's under the field, and how many more ticks it stays there
+    message: Line,
+    message_ticks: u32,
+}
+
+impl App {
+    /// Make a new Frogger application.
+    ///
+    /// The field is made for a screen of 80 by 25 characters, whatever size
+    /// you give.
+    pub const fn new(_width: u8, _height: u8) -> App {
+        App {
+            stdout: neotron_sdk::stdout(),
+            screen: neotron_screen::Screen::new(),
+            map: TileMap::new(),
+            game: Game::new(),
+            rng: neotron_rand::Rng::new(0),
+            sounds: neotron_sfx::Player::new(sounds::SAMPLE_RATE),
+            high_score: neotron_save::HighScores::new(),
+            message: Line::new(),
+            message_ticks: 0,
+        }
+    }
+
+    /// Play multiple games of Frogger.
+    ///
+    /// Shows the title screen, and plays games until the user quits.
+    pub fn play(&mut self) {
+        console::cursor_off(&mut self.stdout);
+        // No file, or a damaged one, just means starting from scratch
+        self.high_score = neotron_save::HighScores::load(SCORE_FILE).unwrap_or_default();
+        self.rng = neotron_rand::Rng::from_rtc();
+        self.sounds.open();
+        self.build_map();
+
+        let mut keyboard = Keyboard::new();
+        let mut scenes: neotron_scene::Stack<Screen, 4> = neotron_scene::Stack::new();
+        scenes.run(Screen::Title, self, &mut keyboard);
+
+        self.sounds.close();
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        console::clear_screen(&mut self.stdout);
+        console::move_cursor(&mut self.stdout, console::Position::origin());
+        console::cursor_on(&mut self.stdout);
+    }
+
+    /// Lay out the hedge, river, banks and road
+    fn build_map(&mut self) {
+        self.map.clear();
+        for x in 0..FIELD_WIDTH {
+            self.map.set(x, game::HOME_ROW, HEDGE);
+            for row in game::RIVER_ROWS.0..=game::RIVER_ROWS.1 {
+                self.map.set(x, row, WATER);
+            }
+            self.map.set(x, game::MIDDLE_ROW, BANK);
+            self.map.set(x, game::START_ROW, BANK);
+        }
+        for bay in 0..game::BAYS {
+            let left = Game::bay_x(bay);
+            for x in left..left + game::BAY_WIDTH {
+                self.map.set(x, game::HOME_ROW, EMPTY);
+            }
+        }
+    }
+
+    /// Draw the title screen
+    fn title_screen(&mut self) {
+        self.screen.set_attr(Attr::DEFAULT);
+        self.screen.clear();
+        for (row, line) in BANNER.iter().enumerate() {
+            self.centre(4 + row as u8, line, TITLE);
+        }
+        let mut line = Line::new();
+        let _ = write!(line, "Best score: {}", self.high_score.best().unwrap_or(0));
+        self.centre(11, line.as_str(), Attr::DEFAULT);
+        self.centre(14, "Space - Play        ", Attr::DEFAULT);
+        let mut line = Line::new();
+        let sound = if !self.sounds.is_open() {
+            "none"
+        } else if self.sounds.enabled {
+            "on"
+        } else {
+            "off"
+        };
+        let _ = write!(line, "S - Sound: {:<9}", sound);
+        self.centre(15, line.as_str(), Attr::DEFAULT);
+        self.centre(16, "Q - Quit            ", Attr::DEFAULT);
+        self.centre(
+            20,
+            "Hop with the arrow keys. Dodge the traffic, ride the logs,",
+            Attr::DEFAULT,
+        );
+        self.centre(
+            21,
+            "and hop into an empty bay in the hedge before time runs out.",
+            Attr::DEFAULT,
+        );
+        self.screen.flush(&mut self.stdout);
+    }
+
+    /// Turn the sound effects on or off
+    fn toggle_sound(&mut self) {
+        self.sounds.enabled = !self.sounds.enabled;
+        if !self.sounds.enabled {
+            self.sounds.stop();
+        }
+    }
+
+    /// Start a game
+    fn start_game(&mut self) {
+        self.game.start(&mut self.rng);
+        let level = self.game.level;
+        self.show_message(format_args!("Level {}", level));
+        self.redraw_game();
+    }
+
+    /// Draw the game again, after something has been drawn over it
+    fn redraw_game(&mut self) {
+        self.screen.set_attr(Attr::DEFAULT);
+        self.screen.clear();
+        self.centre(HELP_ROW, "Arrows hop   P pauses   Q quits", Attr::DEFAULT);
+        self.draw_game();
+    }
+
+    /// Move the game on a tick. Gives false when the game is over.
+    fn tick(&mut self) -> bool {
+        let event = self.game.step();
+        self.handle(event);
+        self.message_ticks = self.message_ticks.saturating_sub(1);
+        self.draw_game();
+        self.sounds.pump(game::TICK_MS);
+        event != Event::GameOver
+    }
+
+    /// Hop the frog
+    fn hop(&mut self, dx: i16, dy: i16) {
+        let event = self.game.hop(dx, dy, &mut self.rng);
+        self.handle(event);
+        self.draw_game();
+    }
+
+    /// Make the right noise for something that happened, and say so if
+    /// it's worth saying
+    fn handle(&mut self, event: Event) {
+        let sfx = match event {
+            Event::None => return,
+            Event::Hopped => sounds::HOP,
+            Event::Squashed => sounds::SQUASH,
+            Event::Drowned => sounds::SPLASH,
+            Event::TimeUp => {
+                self.show_message(format_args!("Out of time!"));
+                sounds::TIME_UP
+            }
+            Event::Home => sounds::HOME,
+            Event::Cleared => {
+                let level = self.game.level;
+                self.show_message(format_args!("Level {}", level));
+                sounds::CLEARED
+            }
+            Event::GameOver => sounds::GAME_OVER,
+        };
+        self.sounds.play(sfx);
+    }
+
+    /// Put up a message under the field for a while
+    fn show_message(&mut self, message: core::fmt::Arguments) {
+        self.message = Line::new();
+        let _ = self.message.write_fmt(message);
+        self.message_ticks = MESSAGE_TICKS;
+    }
+
+    /// Keep the score from a game. Returns whether it beat our best.
+    fn finish_game(&mut self) -> bool {
+        let score = self.game.score;
+        let new_best = score > self.high_score.best().unwrap_or(0);
+        if self.high_score.insert("", score).is_some() {
+            let _ = self.high_score.save(SCORE_FILE);
+        }
+        // Let the last sound play out
+        self.sounds.pump(1000);
+        new_best
+    }
+
+    /// Draw the field, everything on it, and the score
+    fn draw_game(&mut self) {
+        self.draw_field();
+        self.draw_status();
+        self.screen.flush(&mut self.stdout);
+    }
+
+    /// Draw the field, and everything on it
+    fn draw_field(&mut self) {
+        let game = &self.game;
+        let screen = &mut self.screen;
+        screen.fill(
+            FIELD_TOP as usize,
+            0,
+            FIELD_WIDTH as usize,
+            FIELD_HEIGHT as usize,
+            ' ',
+            Attr::DEFAULT,
+        );
+        self.map.draw(screen, &TILES, 0, FIELD_TOP);
+        for lane in game.lanes.iter() {
+            for x in lane.xs() {
+                lane.sprite.draw(screen, *x, FIELD_TOP + lane.row);
+            }
+        }
+        for (bay, full) in game.bays.iter().enumerate() {
+            if *full {
+                let x = Game::bay_x(bay) + (game::BAY_WIDTH - game::FROG.width()) / 2;
+                game::FROG.draw(screen, x, FIELD_TOP + game::HOME_ROW);
+            }
+        }
+        let frog = if game.dying > 0 {
+            game::SPLAT
+        } else {
+            game::FROG
+        };
+        frog.draw(screen, game.frog.0, FIELD_TOP + game.frog.1);
+    }
+
+    /// Draw the score, the lives, the time left and any message
+    fn draw_status(&mut self) {
+        let game = &self.game;
+        let mut line = Line::new();
+        let _ = write!(
+            line,
+            "SCORE {:>6}    BEST {:>6}    LEVEL {:>2}    LIVES ",
+            game.score,
+            self.high_score.best().unwrap_or(0).max(game.score),
+            game.level
+        );
+        for _ in 0..game.lives {
+            let _ = line.write_str("@ ");
+        }
+        // A bar that gets shorter, a block a second
+        let seconds = game.ticks_left.div_ceil(game::TICKS_PER_SECOND);
+        self.centre(HUD_ROW, line.as_str(), TITLE);
+
+        let colour = if seconds <= 5 {
+            Colour::Red
+        } else {
+            Colour::Green
+        };
+        let left = (SCREEN_WIDTH - game::TIME_LIMIT as usize - 5) / 2;
+        self.screen.fill(
+            usize::from(TIMER_ROW),
+            0,
+            SCREEN_WIDTH,
+            1,
+            ' ',
+            Attr::DEFAULT,
+        );
+        self.screen.move_to(usize::from(TIMER_ROW), left);
+        self.screen.set_attr(TITLE);
+        let _ = self.screen.write_str("TIME ");
+        self.screen.fill(
+            usize::from(TIMER_ROW),
+            left + 5,
+            seconds as usize,
+            1,
+            '█',
+            Attr::new(colour, Colour::Black),
+        );
+
+        if self.message_ticks == 0 {
+            self.message = Line::new();
+        }
+        let message = self.message.clone();
+        self.centre(MESSAGE_ROW, message.as_str(), TITLE);
+    }
+
+    /// Put up a message over the field, like the game being paused
+    fn banner(&mut self, lines: &[&str]) {
+        let width = lines
+            .iter()
+            .map(|line| line.chars().count())
+            .max()
+            .unwrap_or(0)
+            + 4;
+        let top = FIELD_TOP as usize + (FIELD_HEIGHT as usize - lines.len()) / 2 - 1;
+        let left = (SCREEN_WIDTH - width) / 2;
+        let attr = Attr::new(Colour::White, Colour::Blue).bold();
+        self.screen
+            .fill(top, left, width, lines.len() + 2, ' ', attr);
+        for (idx, line) in lines.iter().enumerate() {
+            let col = left + (width - line.chars().count()) / 2;
+            self.screen.move_to(top + 1 + idx, col);
+            self.screen.set_attr(attr);
+            let _ = self.screen.write_str(line);
+        }
+        self.screen.flush(&mut self.stdout);
+    }
+
+    /// Say the game is paused
+    fn paused_message(&mut self) {
+        self.banner(&["PAUSED", "", "Press a key to carry on"]);
+    }
+
+    /// Say how the game went
+    fn game_over_message(&mut self, new_best: bool) {
+        let mut line = Line::new();
+        let _ = write!(line, "You scored {}", self.game.score);
+        let best = if new_best { "A new best!" } else { "" };
+        self.banner(&["GAME OVER", "", line.as_str(), best]);
+    }
+
+    /// Write a whole row, with some text in the middle of it
+    fn centre(&mut self, row: u8, text: &str, attr: Attr) {
+        self.screen.move_to(usize::from(row), 0);
+        self.screen.set_attr(attr);
+        let _ = write!(
+            self.screen,
+            "{}",
+            neotron_text::centred(text, SCREEN_WIDTH - 1)
+        );
+    }
+}
+
+/// Which way a key hops the frog, as (columns, rows)
+fn hop_for(key: KeyEvent) -> Option<(i16, i16)> {
+    match key {
+        KeyEvent::Up => Some((0, -1)),
+        KeyEvent::Down => Some((0, 1)),
+        KeyEvent::Left => Some((-1, 0)),
+        KeyEvent::Right => Some((1, 0)),
+        _ => None,
+    }
+}
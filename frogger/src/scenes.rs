@@ -0,0 +1,125 @@
+//! The screens Frogger shows, and how it gets from one to the next
+
+use neotron_input::KeyEvent;
+use neotron_scene::{Scene, Transition};
+
+use crate::App;
+
+/// Each screen, and anything it needs to remember
+pub(crate) enum Screen {
+    /// The title screen
+    Title,
+    /// A game in progress
+    Playing,
+    /// A game, stopped until a key is pressed
+    Paused,
+    /// A game has finished, and we're saying how it went
+    GameOver {
+        new_best: bool,
+        /// Can a key take us back to the title screen yet?
+        ready: bool,
+    },
+}
+
+impl Screen {
+    /// How long the game over message stays up before a key can clear it,
+    /// so hops pressed as the last frog went don't skip it
+    const GAME_OVER_MS: u32 = 1000;
+}
+
+impl Scene<App> for Screen {
+    fn enter(&mut self, app: &mut App) {
+        match self {
+            Screen::Playing => app.start_game(),
+            Screen::GameOver { new_best, .. } => {
+                *new_best = app.finish_game();
+                app.game_over_message(*new_best);
+            }
+            _ => self.draw(app),
+        }
+    }
+
+    fn draw(&mut self, app: &mut App) {
+        match self {
+            Screen::Title => app.title_screen(),
+            Screen::Playing => app.redraw_game(),
+            Screen::Paused => app.paused_message(),
+            Screen::GameOver { new_best, .. } => {
+                app.redraw_game();
+                app.game_over_message(*new_best);
+            }
+        }
+    }
+
+    fn tick_ms(&self, _app: &App) -> Option<u32> {
+        match self {
+            Screen::Playing => Some(crate::game::TICK_MS),
+            Screen::GameOver { ready: false, .. } => Some(Self::GAME_OVER_MS),
+            _ => None,
+        }
+    }
+
+    fn update(&mut self, app: &mut App) -> Transition<Screen> {
+        match self {
+            Screen::Playing => {
+                if app.tick() {
+                    Transition::Stay
+                } else {
+                    Transition::Replace(Screen::GameOver {
+                        new_best: false,
+                        ready: false,
+                    })
+                }
+            }
+            Screen::GameOver { ready, .. } => {
+                *ready = true;
+                Transition::Stay
+            }
+            _ => Transition::Stay,
+        }
+    }
+
+    fn handle_input(&mut self, app: &mut App, key: KeyEvent) -> Transition<Screen> {
+        match self {
+            Screen::Title => {
+                if let KeyEvent::Char(ch) = key {
+                    app.rng.mix(u32::from(ch));
+                }
+                title_key(app, key)
+            }
+            Screen::Playing => {
+                if let Some((dx, dy)) = crate::hop_for(key) {
+                    app.hop(dx, dy);
+                    Transition::Stay
+                } else if key.is_char('q') {
+                    Transition::Replace(Screen::GameOver {
+                        new_best: false,
+                        ready: false,
+                    })
+                } else if key == KeyEvent::Escape || key.is_char('p') {
+                    Transition::Push(Screen::Paused)
+                } else {
+                    Transition::Stay
+                }
+            }
+            Screen::GameOver { ready: false, .. } => Transition::Stay,
+            Screen::Paused | Screen::GameOver { .. } => Transition::Pop,
+        }
+    }
+}
+
+/// Start a game, change the sound, or quit, from the title screen
+fn title_key(app: &mut App, key: KeyEvent) -> Transition<Screen> {
+    match key {
+        KeyEvent::Char(' ') | KeyEvent::Enter => Transition::Push(Screen::Playing),
+        key if key.is_char('p') => Transition::Push(Screen::Playing),
+        key if key.is_char('s') => {
+            app.toggle_sound();
+            app.title_screen();
+            Transition::Stay
+        }
+        KeyEvent::Escape => Transition::Quit,
+        key if key.is_char('q') => Transition::Quit,
+        _ => Transition::Stay,
+    }
+}
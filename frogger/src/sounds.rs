@@ -0,0 +1,94 @@
+//! The sound effects, as recipes of tones built into the game.
+
+use neotron_sfx::{Sfx, HEADER_LEN, STEP_LEN};
+
+/// The sample rate we play at
+pub(crate) const SAMPLE_RATE: u32 = 11025;
+
+/// The waveforms, as a recipe step numbers them
+const SQUARE: u8 = 0;
+const TRIANGLE: u8 = 1;
+const SAWTOOTH: u8 = 2;
+const NOISE: u8 = 3;
+
+/// A short blip for each hop
+pub(crate) const HOP: Sfx<'static> = Sfx::from_bytes(&HOP_BYTES);
+const HOP_BYTES: [u8; len(1)] = recipe([step(SQUARE, 90, 300, 500, 30)]);
+
+/// A crunch, for being run over
+pub(crate) const SQUASH: Sfx<'static> = Sfx::from_bytes(&SQUASH_BYTES);
+const SQUASH_BYTES: [u8; len(2)] = recipe([
+    step(SAWTOOTH, 200, 400, 60, 250),
+    step(NOISE, 160, 800, 200, 150),
+]);
+
+/// A splash, for falling in the river
+pub(crate) const SPLASH: Sfx<'static> = Sfx::from_bytes(&SPLASH_BYTES);
+const SPLASH_BYTES: [u8; len(1)] = recipe([step(NOISE, 200, 3000, 400, 400)]);
+
+/// A slow slide down, for running out of time
+pub(crate) const TIME_UP: Sfx<'static> = Sfx::from_bytes(&TIME_UP_BYTES);
+const TIME_UP_BYTES: [u8; len(1)] = recipe([step(SQUARE, 160, 440, 220, 400)]);
+
+/// Three notes going up, for getting a frog home
+pub(crate) const HOME: Sfx<'static> = Sfx::from_bytes(&HOME_BYTES);
+const HOME_BYTES: [u8; len(3)] = recipe([
+    step(TRIANGLE, 220, 523, 523, 80),
+    step(TRIANGLE, 220, 659, 659, 80),
+    step(TRIANGLE, 220, 784, 784, 120),
+]);
+
+/// A fanfare, for filling every bay
+pub(crate) const CLEARED: Sfx<'static> = Sfx::from_bytes(&CLEARED_BYTES);
+const CLEARED_BYTES: [u8; len(5)] = recipe([
+    step(SQUARE, 180, 523, 523, 100),
+    step(SQUARE, 180, 659, 659, 100),
+    step(SQUARE, 180, 784, 784, 100),
+    step(SQUARE, 200, 1047, 1047, 250),
+    step(SQUARE, 120, 1047, 1047, 150),
+]);
+
+/// Three notes going down, for the end of the game
+pub(crate) const GAME_OVER: Sfx<'static> = Sfx::from_bytes(&GAME_OVER_BYTES);
+const GAME_OVER_BYTES: [u8; len(3)] = recipe([
+    step(TRIANGLE, 220, 392, 392, 200),
+    step(TRIANGLE, 220, 330, 330, 200),
+    step(TRIANGLE, 220, 262, 220, 400),
+]);
+
+/// How long a recipe with this many steps is
+const fn len(steps: usize) -> usize {
+    HEADER_LEN + steps * STEP_LEN
+}
+
+/// One step of a recipe
+const fn step(waveform: u8, volume: u8, from_hz: u16, to_hz: u16, ms: u16) -> [u8; STEP_LEN] {
+    let [from_lo, from_hi] = from_hz.to_le_bytes();
+    let [to_lo, to_hi] = to_hz.to_le_bytes();
+    let [ms_lo, ms_hi] = ms.to_le_bytes();
+    [
+        waveform, volume, from_lo, from_hi, to_lo, to_hi, ms_lo, ms_hi,
+    ]
+}
+
+/// Put a header on some steps. `LEN` has to be [`len`] of `N`.
+const fn recipe<const N: usize, const LEN: usize>(steps: [[u8; STEP_LEN]; N]) -> [u8; LEN] {
+    assert!(LEN == HEADER_LEN + N * STEP_LEN);
+    let mut bytes = [0; LEN];
+    let header = neotron_sfx::recipe_header(N as u16);
+    let mut idx = 0;
+    while idx < HEADER_LEN {
+        bytes[idx] = header[idx];
+        idx += 1;
+    }
+    let mut step = 0;
+    while step < N {
+        let mut idx = 0;
+        while idx < STEP_LEN {
+            bytes[HEADER_LEN + step * STEP_LEN + idx] = steps[step][idx];
+            idx += 1;
+        }
+        step += 1;
+    }
+    bytes
+}
@@ -0,0 +1,131 @@
+//! Game logic for the Neotron game pack - several games in one application
+//!
+//! Loading an application from disk can be slow, so this one has Snake,
+//! Minesweeper and Life built in, behind a menu. Each game is its own
+//! library, and a [`neotron_scene::Game`], so we start whichever one is
+//! picked and feed it our keys until the player quits it, when we come back
+//! here.
+
+#![no_std]
+#![deny(missing_docs)]
+#![deny(unsafe_code)]
+
+use core::fmt::Write;
+
+use neotron_input::{KeyEvent, Keyboard};
+use neotron_sdk::console;
+use neotron_tui::{Menu, Rect, Theme};
+
+/// What's on the menu
+const MENU_ITEMS: [&str; 4] = ["Snake", "Minesweeper", "Life", "Quit"];
+
+/// The colours for the menu
+const THEME: Theme = Theme::DEFAULT;
+
+/// Represents the game pack application
+///
+/// You pick a game from the menu, and can play as many as you like.
+pub struct App {
+    stdout: neotron_sdk::File,
+    keyboard: Keyboard,
+    width: u8,
+    height: u8,
+    menu: Menu<'static>,
+    snake: snake::App,
+    mines: mines::App,
+    life: life::App,
+}
+
+impl App {
+    /// Make a new game pack application.
+    ///
+    /// You can give the screen size in characters.
+    pub const fn new(width: u8, height: u8) -> App {
+        App {
+            stdout: neotron_sdk::stdout(),
+            keyboard: Keyboard::new(),
+            width,
+            height,
+            menu: Menu::new("Game Pack", &MENU_ITEMS),
+            snake: snake::App::new(width, height),
+            mines: mines::App::new(width, height),
+            life: life::App::new(width, height),
+        }
+    }
+
+    /// Show the menu and play games until the user quits
+    pub fn play(&mut self) {
+        console::cursor_off(&mut self.stdout);
+        self.title_screen();
+        loop {
+            let choice = match self.keyboard.wait() {
+                KeyEvent::Up => {
+                    self.menu.up();
+                    None
+                }
+                KeyEvent::Down => {
+                    self.menu.down();
+                    None
+                }
+                KeyEvent::Enter | KeyEvent::Char(' ') => Some(self.menu.selected()),
+                KeyEvent::Escape => break,
+                key if key.is_char('q') => break,
+                KeyEvent::Char(ch) => {
+                    self.menu.jump_to(ch);
+                    None
+                }
+                _ => None,
+            };
+            let Some(choice) = choice else {
+                self.draw_menu();
+                continue;
+            };
+            match MENU_ITEMS[choice] {
+                "Snake" => neotron_scene::play(&mut self.snake, &mut self.keyboard),
+                "Minesweeper" => neotron_scene::play(&mut self.mines, &mut self.keyboard),
+                "Life" => neotron_scene::play(&mut self.life, &mut self.keyboard),
+                _ => break,
+            }
+            // Don't let keys left over from the game pick something off
+            // the menu
+            self.keyboard.flush();
+            console::cursor_off(&mut self.stdout);
+            self.title_screen();
+        }
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        console::clear_screen(&mut self.stdout);
+        console::move_cursor(&mut self.stdout, console::Position::origin());
+        console::cursor_on(&mut self.stdout);
+    }
+
+    /// Draw the title screen, with the game menu
+    fn title_screen(&mut self) {
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        console::clear_screen(&mut self.stdout);
+        console::set_sgr(
+            &mut self.stdout,
+            [console::SgrParam::Bold, console::SgrParam::FgYellow],
+        );
+        self.centre(3, "NEOTRON GAME PACK");
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        self.draw_menu();
+        self.centre(self.height - 4, "Arrows and Enter to choose");
+        self.centre(self.height - 3, "Quitting a game brings you back here");
+    }
+
+    /// Draw the menu, in the middle
+    fn draw_menu(&mut self) {
+        let (width, height) = self.menu.size();
+        let col = (self.width - width) / 2;
+        self.menu
+            .draw(&mut self.stdout, Rect::new(6, col, width, height), &THEME);
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+    }
+
+    /// Write some text in the middle of a row
+    fn centre(&mut self, row: u8, text: &str) {
+        let col = neotron_text::centre_offset(text, usize::from(self.width)) as u8;
+        neotron_tui::move_to(&mut self.stdout, row, col);
+        let _ = self.stdout.write_str(text);
+    }
+}
@@ -0,0 +1,374 @@
+//! Application logic for the text search utility
+
+#![no_std]
+#![deny(missing_docs)]
+#![deny(unsafe_code)]
+
+use core::fmt::Write;
+
+use neotron_fs::glob;
+
+/// The longest line we keep. Anything past this is not searched or shown.
+pub const LINE_LEN: usize = 256;
+
+/// The most lines of context we show either side of a match
+pub const MAX_CONTEXT: usize = 4;
+
+/// The longest pattern we can search for
+const MAX_PATTERN: usize = 80;
+
+/// What to search for, and how to show it
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct Options {
+    /// Ignore ASCII upper/lower case
+    pub ignore_case: bool,
+    /// Show the lines which don't match
+    pub invert: bool,
+    /// Only show the names of files with a match
+    pub files_only: bool,
+    /// How many lines to show either side of a match
+    pub context: usize,
+    /// Look in sub-directories too
+    pub recurse: bool,
+}
+
+/// One line of a file
+struct Line {
+    text: [u8; LINE_LEN],
+    len: usize,
+    number: u32,
+    /// Was this line too long to keep?
+    cut: bool,
+}
+
+impl Line {
+    /// Make an empty line
+    const fn new() -> Line {
+        Line {
+            text: [0; LINE_LEN],
+            len: 0,
+            number: 0,
+            cut: false,
+        }
+    }
+
+    /// Add a byte to the line, unless it is full
+    fn push(&mut self, byte: u8) {
+        if self.len < LINE_LEN {
+            self.text[self.len] = byte;
+            self.len += 1;
+        } else {
+            self.cut = true;
+        }
+    }
+
+    /// Get the bytes we kept
+    fn as_bytes(&self) -> &[u8] {
+        &self.text[0..self.len]
+    }
+}
+
+/// Searches files for lines matching a pattern
+pub struct Search {
+    stdout: neotron_sdk::File,
+    options: Options,
+    pattern: [u8; MAX_PATTERN],
+    pattern_len: usize,
+    /// The line we are reading
+    line: Line,
+    /// The last few lines which didn't match, in case a match comes along
+    before: [Line; MAX_CONTEXT],
+    /// Which entry of `before` is the oldest. We only use as many entries as
+    /// we show lines of context.
+    before_start: usize,
+    /// How many entries of `before` are in use
+    before_count: usize,
+    /// How many more lines to show after the last match
+    after_left: usize,
+    /// The number of the last line we showed in this file
+    last_shown: Option<u32>,
+    /// Have we shown a line from any file yet?
+    shown_any: bool,
+    /// Does this file have nul bytes in it?
+    binary: bool,
+    /// How many lines matched in this file
+    file_matches: u32,
+    /// How many lines matched in all files
+    matches: u32,
+    /// How many files had a match
+    files_matched: u32,
+    /// How many files we looked in
+    files_searched: u32,
+    /// How many lines were too long to search all of
+    lines_cut: u32,
+}
+
+impl Search {
+    /// Make a new search, with an empty pattern
+    pub const fn new() -> Search {
+        Search {
+            stdout: neotron_sdk::stdout(),
+            options: Options {
+                ignore_case: false,
+                invert: false,
+                files_only: false,
+                context: 0,
+                recurse: false,
+            },
+            pattern: [0; MAX_PATTERN],
+            pattern_len: 0,
+            line: Line::new(),
+            before: [Line::new(), Line::new(), Line::new(), Line::new()],
+            before_start: 0,
+            before_count: 0,
+            after_left: 0,
+            last_shown: None,
+            shown_any: false,
+            binary: false,
+            file_matches: 0,
+            matches: 0,
+            files_matched: 0,
+            files_searched: 0,
+            lines_cut: 0,
+        }
+    }
+
+    /// Set what we are searching for
+    pub fn set_pattern(
+        &mut self,
+        pattern: &str,
+        options: Options,
+    ) -> Result<(), neotron_sdk::Error> {
+        if pattern.is_empty() || pattern.len() > MAX_PATTERN {
+            return Err(neotron_sdk::Error::InvalidArg);
+        }
+        self.pattern[0..pattern.len()].copy_from_slice(pattern.as_bytes());
+        self.pattern_len = pattern.len();
+        self.options = options;
+        self.options.context = options.context.min(MAX_CONTEXT);
+        Ok(())
+    }
+
+    /// Search the files named by a command-line argument.
+    ///
+    /// This is either a single file, or a directory and a file name with
+    /// wildcards in, like `0:/DOCS/*.TXT`. If we're searching
+    /// sub-directories, it can also be a directory, which means every file
+    /// in it.
+    pub fn search_arg(&mut self, arg: &str) -> Result<(), neotron_sdk::Error> {
+        let (directory, name) = if glob::has_wildcards(arg) {
+            neotron_fs::split(arg)
+        } else if self.options.recurse {
+            (arg, "*")
+        } else {
+            return self.search_file(arg);
+        };
+        if glob::has_wildcards(directory) {
+            return Err(neotron_sdk::Error::InvalidPath);
+        }
+        let levels = if self.options.recurse {
+            neotron_fs::MAX_DEPTH
+        } else {
+            0
+        };
+        let mut walk = neotron_fs::Walk::new(directory)?.max_depth(levels);
+        let mut found = false;
+        while let Some(entry) = walk.next() {
+            let entry = entry?;
+            if entry.is_dir() || !entry.matches(name) {
+                continue;
+            }
+            self.search_file(walk.path())?;
+            found = true;
+        }
+        if !found {
+            let _ = writeln!(self.stdout, "{}: no files match", arg);
+        }
+        Ok(())
+    }
+
+    /// Search one file
+    pub fn search_file(&mut self, filename: &str) -> Result<(), neotron_sdk::Error> {
+        let path = neotron_sdk::path::Path::new(filename)?;
+        let file = neotron_sdk::File::open(path, neotron_sdk::Flags::empty())?;
+        self.files_searched += 1;
+        self.line.len = 0;
+        self.line.cut = false;
+        self.line.number = 1;
+        self.before_start = 0;
+        self.before_count = 0;
+        self.after_left = 0;
+        self.last_shown = None;
+        self.binary = false;
+        self.file_matches = 0;
+
+        let mut buffer = [0u8; 512];
+        'file: loop {
+            let count = file.read(&mut buffer)?;
+            if count == 0 {
+                break;
+            }
+            for byte in buffer[0..count].iter().copied() {
+                match byte {
+                    b'\n' => {
+                        if !self.finish_line(filename) {
+                            break 'file;
+                        }
+                    }
+                    b'\r' => {}
+                    0 => {
+                        self.binary = true;
+                        self.line.push(byte);
+                    }
+                    _ => self.line.push(byte),
+                }
+            }
+        }
+        if self.line.len > 0 || self.line.cut {
+            self.finish_line(filename);
+        }
+
+        if self.file_matches > 0 {
+            self.files_matched += 1;
+            if self.binary && !self.options.files_only {
+                let _ = writeln!(self.stdout, "Binary file {} matches", filename);
+            }
+        }
+        Ok(())
+    }
+
+    /// Say how the search went, and whether anything matched
+    pub fn finish(&mut self) -> bool {
+        if !self.options.files_only {
+            let _ = writeln!(
+                self.stdout,
+                "{} matching line{} in {} of {} file{}",
+                self.matches,
+                if self.matches == 1 { "" } else { "s" },
+                self.files_matched,
+                self.files_searched,
+                if self.files_searched == 1 { "" } else { "s" },
+            );
+            if self.lines_cut > 0 {
+                let _ = writeln!(
+                    self.stdout,
+                    "{} line{} longer than {} bytes, so only the start was searched",
+                    self.lines_cut,
+                    if self.lines_cut == 1 {
+                        " was"
+                    } else {
+                        "s were"
+                    },
+                    LINE_LEN
+                );
+            }
+        }
+        self.matches > 0
+    }
+
+    /// Deal with the line we have just read.
+    ///
+    /// Returns `false` if there is no point reading any more of the file.
+    fn finish_line(&mut self, filename: &str) -> bool {
+        if self.line.cut {
+            self.lines_cut += 1;
+        }
+        let pattern = &self.pattern[0..self.pattern_len];
+        let found = glob::matches(
+            pattern,
+            self.line.as_bytes(),
+            self.options.ignore_case,
+            true,
+        );
+        let keep_going = if found != self.options.invert {
+            self.matches += 1;
+            self.file_matches += 1;
+            if self.options.files_only {
+                let _ = writeln!(self.stdout, "{}", filename);
+                false
+            } else if self.binary {
+                // We'll say it matched, but not print the rubbish
+                false
+            } else {
+                self.show_match(filename);
+                true
+            }
+        } else {
+            if self.after_left > 0 {
+                self.after_left -= 1;
+                self.show_line(filename, None, b'-');
+            } else if self.options.context > 0 {
+                self.remember_line();
+            }
+            true
+        };
+        self.line.number += 1;
+        self.line.len = 0;
+        self.line.cut = false;
+        keep_going
+    }
+
+    /// Show the line we just read as a match, with the lines before it
+    fn show_match(&mut self, filename: &str) {
+        let first = self.line.number - self.before_count as u32;
+        let joined = self.last_shown.is_some_and(|last| last + 1 >= first);
+        if self.shown_any && !joined && self.options.context > 0 {
+            let _ = writeln!(self.stdout, "--");
+        }
+        for i in 0..self.before_count {
+            let slot = (self.before_start + i) % self.options.context;
+            self.show_line(filename, Some(slot), b'-');
+        }
+        self.before_start = 0;
+        self.before_count = 0;
+        self.show_line(filename, None, b':');
+        self.after_left = self.options.context;
+    }
+
+    /// Keep a copy of the line we just read, in case the next one matches
+    fn remember_line(&mut self) {
+        let slot = if self.before_count < self.options.context {
+            self.before_count += 1;
+            (self.before_start + self.before_count - 1) % self.options.context
+        } else {
+            // Throw away the oldest line
+            let slot = self.before_start;
+            self.before_start = (self.before_start + 1) % self.options.context;
+            slot
+        };
+        let line = &mut self.before[slot];
+        line.text = self.line.text;
+        line.len = self.line.len;
+        line.number = self.line.number;
+        line.cut = self.line.cut;
+    }
+
+    /// Print a line, from the context buffer or the line we just read.
+    ///
+    /// Matches are marked with `:` and the context around them with `-`.
+    fn show_line(&mut self, filename: &str, slot: Option<usize>, marker: u8) {
+        let line = match slot {
+            Some(slot) => &self.before[slot],
+            None => &self.line,
+        };
+        let marker = marker as char;
+        let _ = write!(
+            self.stdout,
+            "{}{}{}{}",
+            filename, marker, line.number, marker
+        );
+        let _ = self.stdout.write(line.as_bytes());
+        if line.cut {
+            let _ = self.stdout.write_str("...");
+        }
+        let _ = writeln!(self.stdout);
+        self.last_shown = Some(line.number);
+        self.shown_any = true;
+    }
+}
+
+impl Default for Search {
+    fn default() -> Self {
+        Search::new()
+    }
+}
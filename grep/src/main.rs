@@ -0,0 +1,99 @@
+#![cfg_attr(target_os = "none", no_std)]
+#![cfg_attr(target_os = "none", no_main)]
+
+use core::{fmt::Write, ptr::addr_of_mut};
+
+#[cfg(not(target_os = "none"))]
+fn main() {
+    neotron_sdk::init();
+}
+
+static mut SEARCH: grep::Search = grep::Search::new();
+
+#[no_mangle]
+extern "C" fn neotron_main() -> i32 {
+    match real_main() {
+        Ok(true) => 0,
+        // Like grep, say when nothing matched
+        Ok(false) => 1,
+        Err(e) => {
+            let _ = neotron_error::Report::from(e).print(&mut neotron_sdk::stdout());
+            2
+        }
+    }
+}
+
+fn real_main() -> Result<bool, neotron_sdk::Error> {
+    let search = unsafe { &mut *addr_of_mut!(SEARCH) };
+    let mut options = grep::Options::default();
+    let mut arg_idx = 0;
+    // Options come first
+    while let Some(arg) = neotron_sdk::arg(arg_idx) {
+        if !arg.starts_with('-') {
+            break;
+        }
+        arg_idx += 1;
+        match &*arg {
+            "-i" => options.ignore_case = true,
+            "-v" => options.invert = true,
+            "-l" => options.files_only = true,
+            "-r" => options.recurse = true,
+            "-C" => {
+                let count = neotron_sdk::arg(arg_idx).ok_or(neotron_sdk::Error::InvalidArg)?;
+                arg_idx += 1;
+                options.context = count
+                    .parse::<usize>()
+                    .map_err(|_| neotron_sdk::Error::InvalidArg)?;
+            }
+            _ => return usage(),
+        }
+    }
+    let Some(pattern) = neotron_sdk::arg(arg_idx) else {
+        return usage();
+    };
+    arg_idx += 1;
+    if neotron_sdk::arg(arg_idx).is_none() {
+        return usage();
+    }
+    search.set_pattern(&pattern, options)?;
+    while let Some(arg) = neotron_sdk::arg(arg_idx) {
+        if let Err(e) = search.search_arg(&arg) {
+            // Carry on with the other files
+            let mut stdout = neotron_sdk::stdout();
+            let _ = writeln!(stdout, "{}: {:?}", &*arg, e);
+        }
+        arg_idx += 1;
+    }
+    Ok(search.finish())
+}
+
+/// Explain how to use the program
+fn usage() -> Result<bool, neotron_sdk::Error> {
+    let mut stdout = neotron_sdk::stdout();
+    let _ = writeln!(
+        stdout,
+        "Usage: grep [-i] [-v] [-l] [-r] [-C <lines>] <pattern> <file>..."
+    );
+    let _ = writeln!(
+        stdout,
+        "  <pattern> can use * for any text and ? for any character"
+    );
+    let _ = writeln!(
+        stdout,
+        "  <file> can be like 0:/DOCS/*.TXT to search many files"
+    );
+    let _ = writeln!(
+        stdout,
+        "  -i ignores case, -v shows lines that don't match,"
+    );
+    let _ = writeln!(
+        stdout,
+        "  -l only lists files, -C shows up to {} lines either side,",
+        grep::MAX_CONTEXT
+    );
+    let _ = writeln!(
+        stdout,
+        "  -r looks in sub-directories too, so <file> can be a directory"
+    );
+    Err(neotron_sdk::Error::InvalidArg)
+}
@@ -0,0 +1,136 @@
+//! Windows Bitmap (BMP) files.
+//!
+//! We handle uncompressed images with 1, 4, 8, 24 or 32 bits per pixel,
+//! stored either bottom-up (the usual way) or top-down.
+
+use crate::image::{Error, Reader, Rgb, MAX_HEIGHT, MAX_WIDTH};
+
+/// No compression
+const BI_RGB: u32 = 0;
+
+/// Uncompressed, with colour masks. We only accept the standard masks.
+const BI_BITFIELDS: u32 = 3;
+
+/// The longest row we can buffer, in bytes
+pub const MAX_STRIDE: usize = MAX_WIDTH * 4;
+
+/// A bitmap we are reading
+pub struct Bmp {
+    width: usize,
+    height: usize,
+    bits_per_pixel: u16,
+    top_down: bool,
+    data_offset: u32,
+    stride: usize,
+}
+
+impl Bmp {
+    /// Read the headers which follow the 4 bytes of magic we've already had.
+    ///
+    /// Any palette is loaded into `palette`.
+    pub fn parse(reader: &mut Reader, palette: &mut [Rgb; 256]) -> Result<Bmp, Error> {
+        // Rest of the file header - file size (2 bytes left), reserved words
+        reader.skip(6)?;
+        let data_offset = reader.u32_le()?;
+
+        // The info header. We need at least the BITMAPINFOHEADER fields.
+        let header_size = reader.u32_le()?;
+        if header_size < 40 {
+            return Err(Error::Unsupported);
+        }
+        let width = reader.u32_le()? as i32;
+        let height = reader.u32_le()? as i32;
+        let _planes = reader.u16_le()?;
+        let bits_per_pixel = reader.u16_le()?;
+        let compression = reader.u32_le()?;
+        // Image size and resolution
+        reader.skip(12)?;
+        let colours_used = reader.u32_le()?;
+        reader.skip(4)?;
+
+        match (bits_per_pixel, compression) {
+            (1 | 4 | 8 | 24 | 32, BI_RGB) | (32, BI_BITFIELDS) => {}
+            _ => return Err(Error::Unsupported),
+        }
+        if width <= 0 || height == 0 {
+            return Err(Error::Unsupported);
+        }
+        let top_down = height < 0;
+        let width = width as usize;
+        let height = height.unsigned_abs() as usize;
+        if width > MAX_WIDTH || height > MAX_HEIGHT {
+            return Err(Error::TooBig);
+        }
+
+        // The palette follows the info header
+        palette.fill(Rgb::default());
+        if bits_per_pixel <= 8 {
+            reader.skip(header_size as usize - 40)?;
+            let max_colours = 1usize << bits_per_pixel;
+            let count = match colours_used as usize {
+                0 => max_colours,
+                n => n.min(max_colours),
+            };
+            for entry in palette.iter_mut().take(count) {
+                let mut bgrx = [0u8; 4];
+                reader.bytes(&mut bgrx)?;
+                *entry = Rgb::new(bgrx[2], bgrx[1], bgrx[0]);
+            }
+        }
+
+        // Rows are padded to a multiple of four bytes
+        let stride = (width * usize::from(bits_per_pixel)).div_ceil(32) * 4;
+
+        Ok(Bmp {
+            width,
+            height,
+            bits_per_pixel,
+            top_down,
+            data_offset,
+            stride,
+        })
+    }
+
+    /// The width of the image, in pixels
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// The height of the image, in pixels
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Decode a row of the image, counting from the top.
+    ///
+    /// The raw bytes of the row are loaded into `buffer`.
+    pub fn read_row(
+        &self,
+        reader: &mut Reader,
+        row: usize,
+        palette: &[Rgb; 256],
+        buffer: &mut [u8; MAX_STRIDE],
+        out: &mut [Rgb],
+    ) -> Result<(), Error> {
+        let stored_row = if self.top_down {
+            row
+        } else {
+            self.height - 1 - row
+        };
+        let offset = self.data_offset as usize + stored_row * self.stride;
+        reader.seek(offset as u32)?;
+        let bytes = &mut buffer[0..self.stride];
+        reader.bytes(bytes)?;
+
+        for (x, pixel) in out.iter_mut().take(self.width).enumerate() {
+            *pixel = match self.bits_per_pixel {
+                1 => palette[usize::from(bytes[x / 8] >> (7 - (x % 8)) & 1)],
+                4 => palette[usize::from(bytes[x / 2] >> (4 - 4 * (x % 2)) & 0x0F)],
+                8 => palette[usize::from(bytes[x])],
+                24 => Rgb::new(bytes[x * 3 + 2], bytes[x * 3 + 1], bytes[x * 3]),
+                _ => Rgb::new(bytes[x * 4 + 2], bytes[x * 4 + 1], bytes[x * 4]),
+            };
+        }
+        Ok(())
+    }
+}
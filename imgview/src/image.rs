@@ -0,0 +1,258 @@
+//! Loading images from disk.
+//!
+//! Images are decoded a row at a time, straight from the file, so we can
+//! show pictures which are much bigger than the memory we have.
+
+use crate::{bmp, qoi};
+
+/// The widest image we can decode
+pub const MAX_WIDTH: usize = 2048;
+
+/// The tallest image we can decode
+pub const MAX_HEIGHT: usize = 4096;
+
+/// A colour, with 8 bits per channel
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct Rgb {
+    /// Red
+    pub r: u8,
+    /// Green
+    pub g: u8,
+    /// Blue
+    pub b: u8,
+}
+
+impl Rgb {
+    /// Make a new colour
+    pub const fn new(r: u8, g: u8, b: u8) -> Rgb {
+        Rgb { r, g, b }
+    }
+}
+
+/// The ways loading an image can fail
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// The OS reported an error
+    Io(neotron_sdk::Error),
+    /// The file ended early
+    Truncated,
+    /// We don't recognise this kind of file
+    UnknownFormat,
+    /// We recognise the file, but can't decode this variant of it
+    Unsupported,
+    /// The image is too big for us
+    TooBig,
+}
+
+impl Error {
+    /// Get a human-readable description of this error
+    pub fn describe(self) -> &'static str {
+        match self {
+            Error::Io(_) => "Failed to read file",
+            Error::Truncated => "File is truncated",
+            Error::UnknownFormat => "Not a BMP or QOI image",
+            Error::Unsupported => "Unsupported kind of image",
+            Error::TooBig => "Image is too big",
+        }
+    }
+}
+
+impl From<neotron_sdk::Error> for Error {
+    fn from(error: neotron_sdk::Error) -> Error {
+        Error::Io(error)
+    }
+}
+
+/// Reads bytes from a file, a block at a time
+pub struct Reader {
+    file: neotron_sdk::File,
+    buffer: [u8; 512],
+    pos: usize,
+    len: usize,
+}
+
+impl Reader {
+    /// Wrap an open file
+    pub fn new(file: neotron_sdk::File) -> Reader {
+        Reader {
+            file,
+            buffer: [0; 512],
+            pos: 0,
+            len: 0,
+        }
+    }
+
+    /// Get the next byte from the file
+    pub fn byte(&mut self) -> Result<u8, Error> {
+        if self.pos == self.len {
+            self.len = self.file.read(&mut self.buffer)?;
+            self.pos = 0;
+            if self.len == 0 {
+                return Err(Error::Truncated);
+            }
+        }
+        let byte = self.buffer[self.pos];
+        self.pos += 1;
+        Ok(byte)
+    }
+
+    /// Fill the given buffer from the file
+    pub fn bytes(&mut self, out: &mut [u8]) -> Result<(), Error> {
+        for byte in out.iter_mut() {
+            *byte = self.byte()?;
+        }
+        Ok(())
+    }
+
+    /// Skip over some bytes
+    pub fn skip(&mut self, count: usize) -> Result<(), Error> {
+        for _ in 0..count {
+            self.byte()?;
+        }
+        Ok(())
+    }
+
+    /// Read a little-endian 16-bit value
+    pub fn u16_le(&mut self) -> Result<u16, Error> {
+        let mut bytes = [0u8; 2];
+        self.bytes(&mut bytes)?;
+        Ok(u16::from_le_bytes(bytes))
+    }
+
+    /// Read a little-endian 32-bit value
+    pub fn u32_le(&mut self) -> Result<u32, Error> {
+        let mut bytes = [0u8; 4];
+        self.bytes(&mut bytes)?;
+        Ok(u32::from_le_bytes(bytes))
+    }
+
+    /// Read a big-endian 32-bit value
+    pub fn u32_be(&mut self) -> Result<u32, Error> {
+        let mut bytes = [0u8; 4];
+        self.bytes(&mut bytes)?;
+        Ok(u32::from_be_bytes(bytes))
+    }
+
+    /// Move to an absolute position in the file
+    pub fn seek(&mut self, offset: u32) -> Result<(), Error> {
+        self.file.seek_set(u64::from(offset))?;
+        self.pos = 0;
+        self.len = 0;
+        Ok(())
+    }
+}
+
+/// The file formats we understand
+enum Format {
+    /// A Windows bitmap
+    Bmp(bmp::Bmp),
+    /// The Quite OK Image format
+    Qoi(qoi::Qoi),
+}
+
+/// An image file we are reading.
+///
+/// This holds the larger buffers the decoders need, so it can live in a
+/// static rather than on the stack.
+pub struct Image {
+    reader: Option<Reader>,
+    format: Option<Format>,
+    next_row: usize,
+    palette: [Rgb; 256],
+    raw_row: [u8; bmp::MAX_STRIDE],
+    seen: qoi::Seen,
+}
+
+impl Image {
+    /// Make an image reader, with no file open
+    pub const fn new() -> Image {
+        Image {
+            reader: None,
+            format: None,
+            next_row: 0,
+            palette: [Rgb::new(0, 0, 0); 256],
+            raw_row: [0; bmp::MAX_STRIDE],
+            seen: qoi::EMPTY_SEEN,
+        }
+    }
+
+    /// Open an image file and read its header
+    pub fn open(&mut self, filename: &str) -> Result<(), Error> {
+        self.reader = None;
+        self.format = None;
+        self.next_row = 0;
+        let path = neotron_sdk::path::Path::new(filename)?;
+        let file = neotron_sdk::File::open(path, neotron_sdk::Flags::empty())?;
+        let mut reader = Reader::new(file);
+        let mut magic = [0u8; 4];
+        reader.bytes(&mut magic)?;
+        let format = if magic[0..2] == *b"BM" {
+            Format::Bmp(bmp::Bmp::parse(&mut reader, &mut self.palette)?)
+        } else if magic == *b"qoif" {
+            Format::Qoi(qoi::Qoi::parse(&mut reader, &mut self.seen)?)
+        } else {
+            return Err(Error::UnknownFormat);
+        };
+        self.reader = Some(reader);
+        self.format = Some(format);
+        Ok(())
+    }
+
+    /// The width of the image, in pixels
+    pub fn width(&self) -> usize {
+        match &self.format {
+            Some(Format::Bmp(bmp)) => bmp.width(),
+            Some(Format::Qoi(qoi)) => qoi.width(),
+            None => 0,
+        }
+    }
+
+    /// The height of the image, in pixels
+    pub fn height(&self) -> usize {
+        match &self.format {
+            Some(Format::Bmp(bmp)) => bmp.height(),
+            Some(Format::Qoi(qoi)) => qoi.height(),
+            None => 0,
+        }
+    }
+
+    /// Which format is this image in?
+    pub fn format_name(&self) -> &'static str {
+        match &self.format {
+            Some(Format::Bmp(_)) => "BMP",
+            Some(Format::Qoi(_)) => "QOI",
+            None => "no",
+        }
+    }
+
+    /// Decode a row of the image, counting from the top.
+    ///
+    /// Rows must be asked for in order, but you can skip rows you don't want.
+    pub fn read_row(&mut self, row: usize, out: &mut [Rgb]) -> Result<(), Error> {
+        let (Some(reader), Some(format)) = (&mut self.reader, &mut self.format) else {
+            return Err(Error::Truncated);
+        };
+        match format {
+            Format::Bmp(bmp) => {
+                // We can seek straight to any row
+                bmp.read_row(reader, row, &self.palette, &mut self.raw_row, out)?;
+            }
+            Format::Qoi(qoi) => {
+                // We have to decode every row to find the next one
+                while self.next_row < row {
+                    qoi.read_row(reader, &mut self.seen, out)?;
+                    self.next_row += 1;
+                }
+                qoi.read_row(reader, &mut self.seen, out)?;
+            }
+        }
+        self.next_row = row + 1;
+        Ok(())
+    }
+}
+
+impl Default for Image {
+    fn default() -> Self {
+        Image::new()
+    }
+}
@@ -0,0 +1,171 @@
+#![cfg_attr(target_os = "none", no_std)]
+#![cfg_attr(target_os = "none", no_main)]
+
+use core::{fmt::Write, ptr::addr_of_mut};
+
+mod bmp;
+mod image;
+mod palette;
+mod qoi;
+mod screen;
+
+use image::{Image, Rgb};
+use palette::Palette;
+use screen::Screen;
+
+/// Turns rows of an image into rows of palette indices for the screen
+struct Renderer {
+    /// One row from the image
+    source: [Rgb; image::MAX_WIDTH],
+    /// One row for the screen
+    output: [u8; screen::MAX_WIDTH],
+    /// Floyd-Steinberg error terms, in sixteenths, for this row and the next.
+    /// There's an extra entry at each end so we don't need bounds checks.
+    errors: [[[i16; 3]; screen::MAX_WIDTH + 2]; 2],
+    /// Do we dither, or just pick the nearest colour?
+    dither: bool,
+}
+
+static mut IMAGE: Image = Image::new();
+
+static mut RENDERER: Renderer = Renderer {
+    source: [Rgb::new(0, 0, 0); image::MAX_WIDTH],
+    output: [0; screen::MAX_WIDTH],
+    errors: [[[0; 3]; screen::MAX_WIDTH + 2]; 2],
+    dither: true,
+};
+
+#[cfg(not(target_os = "none"))]
+fn main() {
+    neotron_sdk::init();
+}
+
+#[no_mangle]
+extern "C" fn neotron_main() -> i32 {
+    neotron_error::exit_code(real_main())
+}
+
+fn real_main() -> Result<(), neotron_sdk::Error> {
+    let mut stdout = neotron_sdk::stdout();
+    let Some(filename) = neotron_sdk::arg(0) else {
+        let _ = writeln!(stdout, "Usage: imgview <file.bmp|file.qoi> [text]");
+        return Err(neotron_sdk::Error::InvalidArg);
+    };
+    let text_only = neotron_sdk::arg(1).is_some_and(|arg| arg.eq_ignore_ascii_case("text"));
+
+    let image = unsafe { &mut *addr_of_mut!(IMAGE) };
+    let renderer = unsafe { &mut *addr_of_mut!(RENDERER) };
+
+    // Check we can read the file before we change the video mode
+    if let Err(e) = image.open(&filename) {
+        let _ = writeln!(stdout, "{}: {}", &*filename, e.describe());
+        return Err(neotron_sdk::Error::InvalidArg);
+    }
+
+    let mut screen = Screen::open(text_only);
+    let mut result = Ok(());
+    loop {
+        screen.begin();
+        match image
+            .open(&filename)
+            .and_then(|()| renderer.draw(image, &mut screen))
+        {
+            Ok(()) => {}
+            Err(e) => {
+                result = Err(e);
+                break;
+            }
+        }
+        screen.status(format_args!(
+            "{} {}x{} {} image. D: Dithering {}, Q: Quit",
+            &*filename,
+            image.width(),
+            image.height(),
+            image.format_name(),
+            if renderer.dither { "off" } else { "on" }
+        ));
+        match screen.wait_for_key() {
+            b'd' | b'D' => renderer.dither = !renderer.dither,
+            _ => break,
+        }
+    }
+    screen.close();
+
+    if let Err(e) = result {
+        let _ = writeln!(stdout, "{}: {}", &*filename, e.describe());
+        return Err(neotron_sdk::Error::InvalidArg);
+    }
+    Ok(())
+}
+
+impl Renderer {
+    /// Draw an image, scaled down to fit the screen if required, and centred
+    fn draw(&mut self, image: &mut Image, screen: &mut Screen) -> Result<(), image::Error> {
+        let (screen_width, screen_height) = screen.size();
+        let (width, height) = fit(image.width(), image.height(), screen_width, screen_height);
+        let left = (screen_width - width) / 2;
+        let top = (screen_height - height) / 2;
+        let palette = screen.palette();
+
+        self.errors = [[[0; 3]; screen::MAX_WIDTH + 2]; 2];
+        let mut loaded_row = None;
+        for y in 0..screen_height {
+            self.output.fill(0);
+            if (top..top + height).contains(&y) {
+                // Nearest neighbour scaling
+                let row = (y - top) * image.height() / height;
+                if loaded_row != Some(row) {
+                    image.read_row(row, &mut self.source)?;
+                    loaded_row = Some(row);
+                }
+                for x in 0..width {
+                    let colour = self.source[x * image.width() / width];
+                    self.output[left + x] = self.quantise(palette, x, colour);
+                }
+                self.errors.swap(0, 1);
+                self.errors[1] = [[0; 3]; screen::MAX_WIDTH + 2];
+            }
+            screen.draw_row(y, &self.output[0..screen_width]);
+        }
+        Ok(())
+    }
+
+    /// Pick a palette entry for a pixel, spreading the error onto the pixels
+    /// we haven't drawn yet.
+    fn quantise(&mut self, palette: Palette, x: usize, colour: Rgb) -> u8 {
+        if !self.dither {
+            return palette.nearest(colour);
+        }
+        let wanted = [colour.r, colour.g, colour.b];
+        let mut adjusted = [0u8; 3];
+        for channel in 0..3 {
+            let value = i16::from(wanted[channel]) + self.errors[0][x + 1][channel] / 16;
+            adjusted[channel] = value.clamp(0, 255) as u8;
+        }
+        let index = palette.nearest(Rgb::new(adjusted[0], adjusted[1], adjusted[2]));
+        let actual = palette.colour(index);
+        let actual = [actual.r, actual.g, actual.b];
+        for channel in 0..3 {
+            let error = i16::from(adjusted[channel]) - i16::from(actual[channel]);
+            self.errors[0][x + 2][channel] += error * 7;
+            self.errors[1][x][channel] += error * 3;
+            self.errors[1][x + 1][channel] += error * 5;
+            self.errors[1][x + 2][channel] += error;
+        }
+        index
+    }
+}
+
+/// Work out how big to draw an image so it fits on the screen, keeping its
+/// shape. We never make images bigger.
+fn fit(width: usize, height: usize, max_width: usize, max_height: usize) -> (usize, usize) {
+    if width <= max_width && height <= max_height {
+        (width, height)
+    } else if width * max_height > max_width * height {
+        // Limited by the width
+        (max_width, (height * max_width / width).max(1))
+    } else {
+        // Limited by the height
+        ((width * max_height / height).max(1), max_height)
+    }
+}
@@ -0,0 +1,165 @@
+//! The colours each kind of screen can show.
+
+use crate::image::Rgb;
+
+/// The 16 colours of a CGA-style text mode, which the BIOS also uses for
+/// 4-bit graphics modes.
+const CGA: [Rgb; 16] = [
+    Rgb::new(0x00, 0x00, 0x00),
+    Rgb::new(0x00, 0x00, 0xAA),
+    Rgb::new(0x00, 0xAA, 0x00),
+    Rgb::new(0x00, 0xAA, 0xAA),
+    Rgb::new(0xAA, 0x00, 0x00),
+    Rgb::new(0xAA, 0x00, 0xAA),
+    Rgb::new(0xAA, 0x55, 0x00),
+    Rgb::new(0xAA, 0xAA, 0xAA),
+    Rgb::new(0x55, 0x55, 0x55),
+    Rgb::new(0x55, 0x55, 0xFF),
+    Rgb::new(0x55, 0xFF, 0x55),
+    Rgb::new(0x55, 0xFF, 0xFF),
+    Rgb::new(0xFF, 0x55, 0x55),
+    Rgb::new(0xFF, 0x55, 0xFF),
+    Rgb::new(0xFF, 0xFF, 0x55),
+    Rgb::new(0xFF, 0xFF, 0xFF),
+];
+
+/// The eight colours an ANSI terminal gives us, in SGR order
+const ANSI: [Rgb; 8] = [
+    Rgb::new(0x00, 0x00, 0x00),
+    Rgb::new(0xAA, 0x00, 0x00),
+    Rgb::new(0x00, 0xAA, 0x00),
+    Rgb::new(0xAA, 0x55, 0x00),
+    Rgb::new(0x00, 0x00, 0xAA),
+    Rgb::new(0xAA, 0x00, 0xAA),
+    Rgb::new(0x00, 0xAA, 0xAA),
+    Rgb::new(0xAA, 0xAA, 0xAA),
+];
+
+/// The levels used by each channel of the 256 colour palette's colour cube
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// A set of colours we can draw with
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Palette {
+    /// Black and white
+    Mono,
+    /// Four shades of grey
+    Grey4,
+    /// The 16 CGA colours
+    Cga16,
+    /// The 8 ANSI text colours
+    Ansi8,
+    /// The standard 256 colour palette - the CGA colours, a 6x6x6 colour
+    /// cube, then a 24 step greyscale ramp
+    Standard256,
+}
+
+impl Palette {
+    /// Pick the palette for a graphics mode with this many bits per pixel
+    pub fn for_depth(bits_per_pixel: u8) -> Palette {
+        match bits_per_pixel {
+            1 => Palette::Mono,
+            2 => Palette::Grey4,
+            4 => Palette::Cga16,
+            _ => Palette::Standard256,
+        }
+    }
+
+    /// What colour does this palette index show?
+    pub fn colour(self, index: u8) -> Rgb {
+        match self {
+            Palette::Mono => {
+                if index == 0 {
+                    Rgb::new(0, 0, 0)
+                } else {
+                    Rgb::new(255, 255, 255)
+                }
+            }
+            Palette::Grey4 => {
+                let level = (index & 3) * 85;
+                Rgb::new(level, level, level)
+            }
+            Palette::Cga16 => CGA[usize::from(index & 15)],
+            Palette::Ansi8 => ANSI[usize::from(index & 7)],
+            Palette::Standard256 => match index {
+                0..=15 => CGA[usize::from(index)],
+                16..=231 => {
+                    let cube = index - 16;
+                    Rgb::new(
+                        CUBE_LEVELS[usize::from(cube / 36)],
+                        CUBE_LEVELS[usize::from((cube / 6) % 6)],
+                        CUBE_LEVELS[usize::from(cube % 6)],
+                    )
+                }
+                _ => {
+                    let level = 8 + (index - 232) * 10;
+                    Rgb::new(level, level, level)
+                }
+            },
+        }
+    }
+
+    /// Find the palette index closest to the given colour
+    pub fn nearest(self, colour: Rgb) -> u8 {
+        match self {
+            Palette::Mono => {
+                if luma(colour) >= 128 {
+                    1
+                } else {
+                    0
+                }
+            }
+            Palette::Grey4 => ((u16::from(luma(colour)) + 42) / 85) as u8,
+            Palette::Cga16 => nearest_in(&CGA, colour),
+            Palette::Ansi8 => nearest_in(&ANSI, colour),
+            Palette::Standard256 => {
+                // Work out the nearest cube colour and the nearest grey,
+                // rather than searching all 256 entries.
+                let level = |c: u8| {
+                    CUBE_LEVELS
+                        .iter()
+                        .enumerate()
+                        .min_by_key(|(_, l)| (i16::from(**l) - i16::from(c)).unsigned_abs())
+                        .map(|(idx, _)| idx as u8)
+                        .unwrap_or(0)
+                };
+                let cube = 16 + 36 * level(colour.r) + 6 * level(colour.g) + level(colour.b);
+                let grey = 232 + (luma(colour).saturating_sub(3) / 10).min(23);
+                if distance(self.colour(grey), colour) < distance(self.colour(cube), colour) {
+                    grey
+                } else {
+                    cube
+                }
+            }
+        }
+    }
+}
+
+/// The brightness of a colour
+fn luma(colour: Rgb) -> u8 {
+    ((u32::from(colour.r) * 77 + u32::from(colour.g) * 150 + u32::from(colour.b) * 29) >> 8) as u8
+}
+
+/// How different do two colours look?
+///
+/// The eye is more sensitive to green than red, and red than blue.
+fn distance(a: Rgb, b: Rgb) -> u32 {
+    let dr = i32::from(a.r) - i32::from(b.r);
+    let dg = i32::from(a.g) - i32::from(b.g);
+    let db = i32::from(a.b) - i32::from(b.b);
+    (2 * dr * dr + 4 * dg * dg + 3 * db * db) as u32
+}
+
+/// Search a list of colours for the closest match
+fn nearest_in(colours: &[Rgb], colour: Rgb) -> u8 {
+    let mut best = 0;
+    let mut best_distance = u32::MAX;
+    for (idx, candidate) in colours.iter().enumerate() {
+        let d = distance(*candidate, colour);
+        if d < best_distance {
+            best = idx as u8;
+            best_distance = d;
+        }
+    }
+    best
+}
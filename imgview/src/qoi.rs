@@ -0,0 +1,139 @@
+//! The Quite OK Image format.
+//!
+//! See <https://qoiformat.org>. The format is a simple stream of opcodes, so
+//! it decodes nicely a row at a time. Any alpha channel is blended onto black.
+
+use crate::image::{Error, Reader, Rgb, MAX_HEIGHT, MAX_WIDTH};
+
+/// A pixel, with alpha
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Rgba([u8; 4]);
+
+/// The table of recently seen pixels, which the encoder can refer back to
+pub type Seen = [Rgba; 64];
+
+/// An empty table of recently seen pixels
+pub const EMPTY_SEEN: Seen = [Rgba([0; 4]); 64];
+
+impl Rgba {
+    /// Where this pixel goes in the table of recently seen pixels
+    fn hash(self) -> usize {
+        let [r, g, b, a] = self.0;
+        (usize::from(r) * 3 + usize::from(g) * 5 + usize::from(b) * 7 + usize::from(a) * 11) % 64
+    }
+
+    /// Blend this pixel onto a black background
+    fn to_rgb(self) -> Rgb {
+        let [r, g, b, a] = self.0;
+        let blend = |c: u8| ((u16::from(c) * u16::from(a)) / 255) as u8;
+        Rgb::new(blend(r), blend(g), blend(b))
+    }
+}
+
+/// A QOI image we are reading
+pub struct Qoi {
+    width: usize,
+    height: usize,
+    previous: Rgba,
+    run: u8,
+}
+
+impl Qoi {
+    /// Read the header which follows the 4 bytes of magic we've already had.
+    ///
+    /// The table of recently seen pixels is cleared.
+    pub fn parse(reader: &mut Reader, seen: &mut Seen) -> Result<Qoi, Error> {
+        let width = reader.u32_be()? as usize;
+        let height = reader.u32_be()? as usize;
+        let _channels = reader.byte()?;
+        let _colour_space = reader.byte()?;
+        if width == 0 || height == 0 {
+            return Err(Error::Unsupported);
+        }
+        if width > MAX_WIDTH || height > MAX_HEIGHT {
+            return Err(Error::TooBig);
+        }
+        *seen = EMPTY_SEEN;
+        Ok(Qoi {
+            width,
+            height,
+            previous: Rgba([0, 0, 0, 255]),
+            run: 0,
+        })
+    }
+
+    /// The width of the image, in pixels
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// The height of the image, in pixels
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Decode the next row of the image
+    pub fn read_row(
+        &mut self,
+        reader: &mut Reader,
+        seen: &mut Seen,
+        out: &mut [Rgb],
+    ) -> Result<(), Error> {
+        for x in 0..self.width {
+            let pixel = self.next_pixel(reader, seen)?;
+            if let Some(slot) = out.get_mut(x) {
+                *slot = pixel.to_rgb();
+            }
+        }
+        Ok(())
+    }
+
+    /// Decode the next pixel
+    fn next_pixel(&mut self, reader: &mut Reader, seen: &mut Seen) -> Result<Rgba, Error> {
+        if self.run > 0 {
+            self.run -= 1;
+            return Ok(self.previous);
+        }
+        let op = reader.byte()?;
+        let mut pixel = self.previous;
+        match op {
+            0xFE => {
+                // QOI_OP_RGB
+                reader.bytes(&mut pixel.0[0..3])?;
+            }
+            0xFF => {
+                // QOI_OP_RGBA
+                reader.bytes(&mut pixel.0)?;
+            }
+            _ => match op >> 6 {
+                0 => {
+                    // QOI_OP_INDEX
+                    pixel = seen[usize::from(op & 0x3F)];
+                }
+                1 => {
+                    // QOI_OP_DIFF
+                    pixel.0[0] = pixel.0[0].wrapping_add((op >> 4) & 3).wrapping_sub(2);
+                    pixel.0[1] = pixel.0[1].wrapping_add((op >> 2) & 3).wrapping_sub(2);
+                    pixel.0[2] = pixel.0[2].wrapping_add(op & 3).wrapping_sub(2);
+                }
+                2 => {
+                    // QOI_OP_LUMA
+                    let next = reader.byte()?;
+                    let dg = (op & 0x3F).wrapping_sub(32);
+                    let dr_dg = (next >> 4).wrapping_sub(8);
+                    let db_dg = (next & 0x0F).wrapping_sub(8);
+                    pixel.0[0] = pixel.0[0].wrapping_add(dg).wrapping_add(dr_dg);
+                    pixel.0[1] = pixel.0[1].wrapping_add(dg);
+                    pixel.0[2] = pixel.0[2].wrapping_add(dg).wrapping_add(db_dg);
+                }
+                _ => {
+                    // QOI_OP_RUN - this pixel, plus some more
+                    self.run = op & 0x3F;
+                }
+            },
+        }
+        seen[pixel.hash()] = pixel;
+        self.previous = pixel;
+        Ok(pixel)
+    }
+}
@@ -0,0 +1,300 @@
+//! Where we draw the picture.
+//!
+//! If the video device will give us a graphics mode, we write pixels straight
+//! into the framebuffer. Otherwise we draw on the text console using
+//! half-block characters, which gives two square-ish pixels per cell.
+
+use core::fmt::Write;
+
+use neotron_sdk::console;
+
+use crate::palette::Palette;
+
+/// The device which gives us access to the framebuffer
+const VIDEO_DEVICE: &str = "VIDEO:";
+
+/// The ioctl which reports the current video mode
+const IOCTL_GET_MODE: u64 = 0;
+
+/// The ioctl which changes the video mode
+const IOCTL_SET_MODE: u64 = 1;
+
+/// The graphics modes we ask for, best first
+const GRAPHICS_MODES: [Mode; 5] = [
+    Mode::new(320, 240, 8),
+    Mode::new(640, 480, 4),
+    Mode::new(320, 240, 4),
+    Mode::new(640, 480, 2),
+    Mode::new(640, 480, 1),
+];
+
+/// The widest screen we support, in pixels
+pub const MAX_WIDTH: usize = 640;
+
+/// The width of the text console, in characters
+const TEXT_COLS: usize = 80;
+
+/// The height of the text console, in characters. We leave the bottom line for the status bar.
+const TEXT_ROWS: usize = 25;
+
+/// A video mode.
+///
+/// The video device packs these into an ioctl value as `width | height << 16
+/// | bits_per_pixel << 32`. Text modes have zero bits per pixel.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct Mode {
+    width: u16,
+    height: u16,
+    bits_per_pixel: u8,
+}
+
+impl Mode {
+    /// Make a new mode
+    const fn new(width: u16, height: u16, bits_per_pixel: u8) -> Mode {
+        Mode {
+            width,
+            height,
+            bits_per_pixel,
+        }
+    }
+
+    /// Unpack a mode from an ioctl value
+    fn from_raw(raw: u64) -> Mode {
+        Mode {
+            width: raw as u16,
+            height: (raw >> 16) as u16,
+            bits_per_pixel: (raw >> 32) as u8,
+        }
+    }
+
+    /// Pack a mode into an ioctl value
+    fn to_raw(self) -> u64 {
+        u64::from(self.width) | u64::from(self.height) << 16 | u64::from(self.bits_per_pixel) << 32
+    }
+
+    /// Is this a graphics mode we can draw on?
+    fn is_usable(self) -> bool {
+        matches!(self.bits_per_pixel, 1 | 2 | 4 | 8)
+            && self.width > 0
+            && usize::from(self.width) <= MAX_WIDTH
+            && self.height > 0
+    }
+}
+
+/// What we are drawing on
+enum Target {
+    /// A framebuffer
+    Graphics {
+        video: neotron_sdk::File,
+        mode: Mode,
+        original: u64,
+    },
+    /// The text console
+    Text,
+}
+
+/// Something we can draw a picture on
+pub struct Screen {
+    target: Target,
+    stdout: neotron_sdk::File,
+    stdin: neotron_sdk::File,
+    /// In text mode, the top half of the row of cells we are drawing
+    upper: [u8; TEXT_COLS],
+    /// Pixels packed for the framebuffer
+    packed: [u8; MAX_WIDTH],
+}
+
+impl Screen {
+    /// Find the best screen we can.
+    ///
+    /// We only use the text console if `text_only` is set, or no graphics mode
+    /// is available.
+    pub fn open(text_only: bool) -> Screen {
+        let target = if text_only {
+            Target::Text
+        } else {
+            Self::open_graphics().unwrap_or(Target::Text)
+        };
+        Screen {
+            target,
+            stdout: neotron_sdk::stdout(),
+            stdin: neotron_sdk::stdin(),
+            upper: [0; TEXT_COLS],
+            packed: [0; MAX_WIDTH],
+        }
+    }
+
+    /// Try to get a graphics mode from the video device
+    fn open_graphics() -> Option<Target> {
+        let path = neotron_sdk::path::Path::new(VIDEO_DEVICE).ok()?;
+        let video = neotron_sdk::File::open(path, neotron_sdk::Flags::WRITE).ok()?;
+        let original = video.ioctl(IOCTL_GET_MODE, 0).ok()?;
+        if Mode::from_raw(original).is_usable() {
+            // Already in graphics mode - use it as it is
+            return Some(Target::Graphics {
+                video,
+                mode: Mode::from_raw(original),
+                original,
+            });
+        }
+        for mode in GRAPHICS_MODES {
+            if video.ioctl(IOCTL_SET_MODE, mode.to_raw()).is_ok() {
+                return Some(Target::Graphics {
+                    video,
+                    mode,
+                    original,
+                });
+            }
+        }
+        None
+    }
+
+    /// Put the screen back how we found it
+    pub fn close(&mut self) {
+        if let Target::Graphics {
+            video,
+            mode,
+            original,
+        } = &self.target
+        {
+            if mode.to_raw() != *original {
+                let _ = video.ioctl(IOCTL_SET_MODE, *original);
+            }
+        }
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        console::clear_screen(&mut self.stdout);
+        console::move_cursor(&mut self.stdout, console::Position::origin());
+        console::cursor_on(&mut self.stdout);
+    }
+
+    /// Is this the text console?
+    pub fn is_text(&self) -> bool {
+        matches!(self.target, Target::Text)
+    }
+
+    /// The size of the screen, in pixels
+    pub fn size(&self) -> (usize, usize) {
+        match &self.target {
+            Target::Graphics { mode, .. } => (usize::from(mode.width), usize::from(mode.height)),
+            Target::Text => (TEXT_COLS, (TEXT_ROWS - 1) * 2),
+        }
+    }
+
+    /// The colours we can draw with
+    pub fn palette(&self) -> Palette {
+        match &self.target {
+            Target::Graphics { mode, .. } => Palette::for_depth(mode.bits_per_pixel),
+            Target::Text => Palette::Ansi8,
+        }
+    }
+
+    /// Get ready to draw a new picture
+    pub fn begin(&mut self) {
+        console::cursor_off(&mut self.stdout);
+        if self.is_text() {
+            console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+            console::clear_screen(&mut self.stdout);
+        }
+    }
+
+    /// Draw a complete row of pixels, given as palette indices
+    pub fn draw_row(&mut self, y: usize, pixels: &[u8]) {
+        match &self.target {
+            Target::Graphics { video, mode, .. } => {
+                let bits = usize::from(mode.bits_per_pixel);
+                let stride = usize::from(mode.width) * bits / 8;
+                let per_byte = 8 / bits;
+                let packed = &mut self.packed[0..stride];
+                packed.fill(0);
+                for (x, pixel) in pixels.iter().enumerate().take(usize::from(mode.width)) {
+                    // Leftmost pixel goes in the most significant bits
+                    let shift = 8 - bits * (1 + x % per_byte);
+                    packed[x / per_byte] |= pixel << shift;
+                }
+                if video.seek_set((y * stride) as u64).is_ok() {
+                    let _ = video.write(packed);
+                }
+            }
+            Target::Text if y.is_multiple_of(2) => {
+                let len = pixels.len().min(TEXT_COLS);
+                self.upper[0..len].copy_from_slice(&pixels[0..len]);
+            }
+            Target::Text => {
+                console::move_cursor(
+                    &mut self.stdout,
+                    console::Position {
+                        row: (y / 2) as u8,
+                        col: 0,
+                    },
+                );
+                let mut current = None;
+                for (x, lower) in pixels.iter().enumerate().take(TEXT_COLS) {
+                    let colours = (self.upper[x], *lower);
+                    if current != Some(colours) {
+                        console::set_sgr(
+                            &mut self.stdout,
+                            [foreground(colours.0), background(colours.1)],
+                        );
+                        current = Some(colours);
+                    }
+                    let _ = self.stdout.write_char('▀');
+                }
+            }
+        }
+    }
+
+    /// Show a line of text at the bottom of the text console
+    pub fn status(&mut self, message: core::fmt::Arguments) {
+        if !self.is_text() {
+            return;
+        }
+        console::move_cursor(
+            &mut self.stdout,
+            console::Position {
+                row: (TEXT_ROWS - 1) as u8,
+                col: 0,
+            },
+        );
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        let _ = self.stdout.write_fmt(message);
+    }
+
+    /// Wait for a key to be pressed
+    pub fn wait_for_key(&mut self) -> u8 {
+        loop {
+            let mut buffer = [0u8; 1];
+            if let Ok(1) = self.stdin.read(&mut buffer) {
+                return buffer[0];
+            }
+            neotron_sdk::delay(core::time::Duration::from_millis(10));
+        }
+    }
+}
+
+/// The SGR parameter which selects an ANSI colour for the text
+fn foreground(index: u8) -> console::SgrParam {
+    match index {
+        1 => console::SgrParam::FgRed,
+        2 => console::SgrParam::FgGreen,
+        3 => console::SgrParam::FgYellow,
+        4 => console::SgrParam::FgBlue,
+        5 => console::SgrParam::FgMagenta,
+        6 => console::SgrParam::FgCyan,
+        7 => console::SgrParam::FgWhite,
+        _ => console::SgrParam::FgBlack,
+    }
+}
+
+/// The SGR parameter which selects an ANSI colour for the background
+fn background(index: u8) -> console::SgrParam {
+    match index {
+        1 => console::SgrParam::BgRed,
+        2 => console::SgrParam::BgGreen,
+        3 => console::SgrParam::BgYellow,
+        4 => console::SgrParam::BgBlue,
+        5 => console::SgrParam::BgMagenta,
+        6 => console::SgrParam::BgCyan,
+        7 => console::SgrParam::BgWhite,
+        _ => console::SgrParam::BgBlack,
+    }
+}
@@ -0,0 +1,905 @@
+//! Game logic for Invaders
+
+#![no_std]
+#![deny(missing_docs)]
+#![deny(unsafe_code)]
+
+use core::fmt::Write;
+
+use neotron_input::{KeyEvent, Keyboard};
+use neotron_sdk::console;
+
+pub mod sound;
+
+use sound::{Effect, Sound};
+
+/// Represents the Invaders application
+///
+/// An application can play multiple games.
+pub struct App {
+    game: Game,
+    width: u8,
+    height: u8,
+    stdout: neotron_sdk::File,
+    keyboard: Keyboard,
+    sound: Sound,
+    high_score: u32,
+}
+
+impl App {
+    /// Make a new invaders application.
+    ///
+    /// You can give the screen size in characters.
+    pub const fn new(width: u8, height: u8) -> App {
+        App {
+            game: Game::new(width, height),
+            width,
+            height,
+            stdout: neotron_sdk::stdout(),
+            keyboard: Keyboard::new(),
+            sound: Sound::new(),
+            high_score: 0,
+        }
+    }
+
+    /// Play multiple games of invaders.
+    ///
+    /// Loops showing the title screen and playing games.
+    pub fn play(&mut self) {
+        console::cursor_off(&mut self.stdout);
+        self.sound.open();
+
+        let mut seed: u16 = 0x1d37;
+        let mut last_score = None;
+
+        'outer: loop {
+            self.title_screen(last_score);
+            loop {
+                let key = self.keyboard.wait();
+                seed = seed.wrapping_add(1);
+                match key {
+                    KeyEvent::Char('q' | 'Q') => break 'outer,
+                    KeyEvent::Char('p' | 'P' | ' ') => break,
+                    _ => {}
+                }
+            }
+
+            neotron_sdk::srand(seed);
+            let score = self.game.play(
+                &mut self.keyboard,
+                &mut self.stdout,
+                &mut self.sound,
+                self.high_score,
+            );
+            self.high_score = self.high_score.max(score);
+            last_score = Some(score);
+        }
+
+        self.sound.close();
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        console::clear_screen(&mut self.stdout);
+        console::move_cursor(&mut self.stdout, console::Position::origin());
+        console::cursor_on(&mut self.stdout);
+    }
+
+    /// Show the title screen, with the points table
+    fn title_screen(&mut self, last_score: Option<u32>) {
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        console::clear_screen(&mut self.stdout);
+        let row = self.height / 2 - 6;
+        console::set_sgr(
+            &mut self.stdout,
+            [console::SgrParam::Bold, console::SgrParam::FgGreen],
+        );
+        self.centre(row, "N E O T R O N   I N V A D E R S");
+        for (idx, kind) in [Kind::Squid, Kind::Crab, Kind::Octopus].iter().enumerate() {
+            console::set_sgr(&mut self.stdout, [console::SgrParam::Reset, kind.colour()]);
+            console::move_cursor(
+                &mut self.stdout,
+                console::Position {
+                    row: row + 3 + idx as u8 * 2,
+                    col: self.width / 2 - 8,
+                },
+            );
+            let _ = write!(
+                self.stdout,
+                "{} = {:2} POINTS",
+                kind.sprite(false),
+                kind.points()
+            );
+        }
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        if let Some(score) = last_score {
+            console::move_cursor(
+                &mut self.stdout,
+                console::Position {
+                    row: row + 10,
+                    col: (self.width - 28) / 2,
+                },
+            );
+            let _ = write!(
+                self.stdout,
+                "Score: {:06}  High: {:06}",
+                score, self.high_score
+            );
+        }
+        self.centre(row + 12, "Arrows/A/D move, Space fires, P pauses");
+        self.centre(row + 13, "Q to Quit | 'P' to Play");
+    }
+
+    /// Write a message centred on the given row
+    fn centre(&mut self, row: u8, message: &str) {
+        let col = (self.width - message.chars().count() as u8) / 2;
+        console::move_cursor(&mut self.stdout, console::Position { row, col });
+        let _ = self.stdout.write_str(message);
+    }
+}
+
+/// The different kinds of invader
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Kind {
+    /// The top row
+    Squid,
+    /// The middle two rows
+    Crab,
+    /// The bottom two rows
+    Octopus,
+}
+
+impl Kind {
+    /// Which kind of invader lives on each row of the fleet
+    fn for_row(row: usize) -> Kind {
+        match row {
+            0 => Kind::Squid,
+            1 | 2 => Kind::Crab,
+            _ => Kind::Octopus,
+        }
+    }
+
+    /// Get the characters for this invader, in one of two animation frames
+    fn sprite(self, frame: bool) -> &'static str {
+        match (self, frame) {
+            (Kind::Squid, false) => "/O\\",
+            (Kind::Squid, true) => "\\O/",
+            (Kind::Crab, false) => "{@}",
+            (Kind::Crab, true) => "}@{",
+            (Kind::Octopus, false) => "<W>",
+            (Kind::Octopus, true) => ">M<",
+        }
+    }
+
+    /// Get the ANSI colour for this invader
+    fn colour(self) -> console::SgrParam {
+        match self {
+            Kind::Squid => console::SgrParam::FgMagenta,
+            Kind::Crab => console::SgrParam::FgCyan,
+            Kind::Octopus => console::SgrParam::FgGreen,
+        }
+    }
+
+    /// How many points you get for shooting this invader
+    fn points(self) -> u32 {
+        match self {
+            Kind::Squid => 30,
+            Kind::Crab => 20,
+            Kind::Octopus => 10,
+        }
+    }
+}
+
+/// A location on the screen
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct Point {
+    x: u8,
+    y: u8,
+}
+
+/// Represents one game of Invaders
+struct Game {
+    width: u8,
+    height: u8,
+    /// Which invaders are still alive, indexed by row then column
+    alive: [[bool; Self::COLS]; Self::ROWS],
+    /// How many invaders are left
+    remaining: u8,
+    /// Screen column of the left-hand edge of the fleet. Can go negative if
+    /// the left-hand columns have all been shot.
+    fleet_x: i16,
+    /// Screen row of the top of the fleet
+    fleet_y: u8,
+    fleet_right: bool,
+    /// Which animation frame the invaders are showing
+    fleet_frame: bool,
+    /// Frames until the fleet next moves
+    step_timer: u8,
+    /// Which of the four marching notes comes next
+    step_note: u8,
+    /// Column of the left-hand edge of the player's base
+    player_x: u8,
+    /// The player's shot, if one is in the air
+    shot: Option<Point>,
+    /// Bombs dropped by the invaders
+    bombs: [Option<Point>; Self::MAX_BOMBS],
+    /// The health of each shield cell, from 0 (gone) to 4 (intact)
+    shields: [[u8; Self::MAX_WIDTH]; Self::SHIELD_ROWS],
+    score: u32,
+    high_score: u32,
+    lives: u8,
+    level: u8,
+    frame_count: u32,
+}
+
+impl Game {
+    /// Invaders per row
+    const COLS: usize = 11;
+    /// Rows of invaders
+    const ROWS: usize = 5;
+    /// Screen columns between the start of one invader and the next
+    const SPACING: i16 = 6;
+    /// How wide each invader (and the player) is
+    const SPRITE_WIDTH: u8 = 3;
+    /// The widest screen we support
+    const MAX_WIDTH: usize = 128;
+    /// How tall the shields are
+    const SHIELD_ROWS: usize = 2;
+    /// How wide each shield is
+    const SHIELD_WIDTH: u8 = 7;
+    /// How many bombs can be falling at once
+    const MAX_BOMBS: usize = 3;
+    /// How long each frame lasts
+    const FRAME_MS: u32 = 30;
+    /// How many lives you start with
+    const START_LIVES: u8 = 3;
+    /// Shield glyphs, from almost gone to intact
+    const SHIELD_GLYPHS: [char; 4] = ['░', '▒', '▓', '█'];
+
+    /// Make a new game for a screen of the given size
+    const fn new(width: u8, height: u8) -> Game {
+        Game {
+            width: if width as usize > Self::MAX_WIDTH {
+                Self::MAX_WIDTH as u8
+            } else {
+                width
+            },
+            height,
+            alive: [[true; Self::COLS]; Self::ROWS],
+            remaining: 0,
+            fleet_x: 0,
+            fleet_y: 0,
+            fleet_right: true,
+            fleet_frame: false,
+            step_timer: 0,
+            step_note: 0,
+            player_x: 0,
+            shot: None,
+            bombs: [None; Self::MAX_BOMBS],
+            shields: [[0; Self::MAX_WIDTH]; Self::SHIELD_ROWS],
+            score: 0,
+            high_score: 0,
+            lives: 0,
+            level: 0,
+            frame_count: 0,
+        }
+    }
+
+    /// Play one game, and return the score.
+    fn play(
+        &mut self,
+        keyboard: &mut Keyboard,
+        stdout: &mut neotron_sdk::File,
+        sound: &mut Sound,
+        high_score: u32,
+    ) -> u32 {
+        self.score = 0;
+        self.high_score = high_score;
+        self.lives = Self::START_LIVES;
+        self.level = 1;
+        self.start_level();
+        self.draw_all(stdout);
+
+        loop {
+            // Handle all the keys that have been pressed
+            while let Some(key) = keyboard.read() {
+                match key {
+                    KeyEvent::Left | KeyEvent::Char('a' | 'A') if self.player_x > 0 => {
+                        self.player_x -= 1;
+                        self.draw_player(stdout);
+                    }
+                    KeyEvent::Right | KeyEvent::Char('d' | 'D')
+                        if self.player_x + Self::SPRITE_WIDTH < self.width =>
+                    {
+                        self.player_x += 1;
+                        self.draw_player(stdout);
+                    }
+                    KeyEvent::Up | KeyEvent::Char(' ' | 'w' | 'W') if self.shot.is_none() => {
+                        self.shot = Some(Point {
+                            x: self.player_x + 1,
+                            y: self.player_row(),
+                        });
+                        sound.play(Effect::Shoot);
+                    }
+                    KeyEvent::Char('p' | 'P') => {
+                        self.pause(keyboard, stdout);
+                    }
+                    KeyEvent::Char('q' | 'Q') => {
+                        return self.score;
+                    }
+                    _ => {}
+                }
+            }
+
+            self.frame_count = self.frame_count.wrapping_add(1);
+            self.move_shot(stdout, sound);
+            if self.frame_count.is_multiple_of(2) {
+                self.move_bombs(stdout);
+            }
+            if self.step_timer == 0 {
+                self.step_fleet(stdout, sound);
+                self.step_timer = self.step_interval();
+            } else {
+                self.step_timer -= 1;
+            }
+            self.drop_bombs(stdout);
+            self.check_shot(stdout, sound);
+
+            if self.player_is_hit() {
+                self.lose_life(stdout, sound);
+                if self.lives == 0 {
+                    self.game_over(stdout, sound);
+                    return self.score;
+                }
+            }
+            if self.fleet_landed() {
+                self.lives = 0;
+                self.draw_status(stdout);
+                sound.play(Effect::PlayerHit);
+                self.game_over(stdout, sound);
+                return self.score;
+            }
+            if self.remaining == 0 {
+                self.level = self.level.saturating_add(1);
+                self.start_level();
+                self.draw_all(stdout);
+            }
+
+            sound.tick(Self::FRAME_MS);
+            neotron_sdk::delay(core::time::Duration::from_millis(u64::from(Self::FRAME_MS)));
+        }
+    }
+
+    /// Set up the fleet and the shields for the current level
+    fn start_level(&mut self) {
+        self.alive = [[true; Self::COLS]; Self::ROWS];
+        self.remaining = (Self::COLS * Self::ROWS) as u8;
+        let fleet_width = Self::SPACING * (Self::COLS as i16 - 1) + Self::SPRITE_WIDTH as i16;
+        self.fleet_x = (i16::from(self.width) - fleet_width) / 2;
+        // Each level starts a little lower down
+        self.fleet_y = 2 + (self.level - 1).min(4);
+        self.fleet_right = true;
+        self.fleet_frame = false;
+        self.step_timer = 0;
+        self.player_x = (self.width - Self::SPRITE_WIDTH) / 2;
+        self.shot = None;
+        self.bombs = [None; Self::MAX_BOMBS];
+        self.shields = [[0; Self::MAX_WIDTH]; Self::SHIELD_ROWS];
+        for shield in 0..4u8 {
+            let centre = self.width * (2 * shield + 1) / 8;
+            let left = centre - Self::SHIELD_WIDTH / 2;
+            for row in self.shields.iter_mut() {
+                for cell in row
+                    .iter_mut()
+                    .skip(usize::from(left))
+                    .take(usize::from(Self::SHIELD_WIDTH))
+                {
+                    *cell = Self::SHIELD_GLYPHS.len() as u8;
+                }
+            }
+        }
+    }
+
+    /// How many frames between each step of the fleet.
+    ///
+    /// The fewer invaders there are, the faster they go. They also go faster
+    /// on later levels.
+    fn step_interval(&self) -> u8 {
+        (self.remaining / 2).saturating_sub(self.level - 1).max(1)
+    }
+
+    /// The screen row the player's base is on
+    fn player_row(&self) -> u8 {
+        self.height - 2
+    }
+
+    /// The screen row the top of the shields are on
+    fn shield_row(&self) -> u8 {
+        self.height - 6
+    }
+
+    /// Find the invader (if any) at this screen location
+    fn invader_at(&self, point: Point) -> Option<(usize, usize)> {
+        let dy = point.y.checked_sub(self.fleet_y)?;
+        if dy % 2 != 0 || usize::from(dy / 2) >= Self::ROWS {
+            return None;
+        }
+        let dx = i16::from(point.x) - self.fleet_x;
+        if dx < 0 || dx % Self::SPACING >= i16::from(Self::SPRITE_WIDTH) {
+            return None;
+        }
+        let row = usize::from(dy / 2);
+        let col = (dx / Self::SPACING) as usize;
+        if col < Self::COLS && self.alive[row][col] {
+            Some((row, col))
+        } else {
+            None
+        }
+    }
+
+    /// Get a mutable reference to the shield cell at this screen location
+    fn shield_at(&mut self, point: Point) -> Option<&mut u8> {
+        let row = usize::from(point.y.checked_sub(self.shield_row())?);
+        let cell = self.shields.get_mut(row)?.get_mut(usize::from(point.x))?;
+        if *cell > 0 {
+            Some(cell)
+        } else {
+            None
+        }
+    }
+
+    /// Work out what should be shown at this screen location, ignoring shots
+    /// and bombs
+    fn background_at(&self, point: Point) -> (char, console::SgrParam) {
+        if let Some((row, _col)) = self.invader_at(point) {
+            let kind = Kind::for_row(row);
+            let offset = (i16::from(point.x) - self.fleet_x) % Self::SPACING;
+            let ch = kind
+                .sprite(self.fleet_frame)
+                .chars()
+                .nth(offset as usize)
+                .unwrap_or(' ');
+            return (ch, kind.colour());
+        }
+        if let Some(row) = point.y.checked_sub(self.shield_row()) {
+            if let Some(&health) = self
+                .shields
+                .get(usize::from(row))
+                .and_then(|r| r.get(usize::from(point.x)))
+            {
+                if health > 0 {
+                    return (
+                        Self::SHIELD_GLYPHS[usize::from(health - 1)],
+                        console::SgrParam::FgGreen,
+                    );
+                }
+            }
+        }
+        if point.y == self.player_row()
+            && point.x >= self.player_x
+            && point.x < self.player_x + Self::SPRITE_WIDTH
+        {
+            let ch = "▄█▄".chars().nth(usize::from(point.x - self.player_x));
+            return (ch.unwrap_or(' '), console::SgrParam::FgYellow);
+        }
+        (' ', console::SgrParam::FgWhite)
+    }
+
+    /// Redraw whatever should be at this screen location
+    fn draw_background(&self, stdout: &mut neotron_sdk::File, point: Point) {
+        let (ch, colour) = self.background_at(point);
+        console::move_cursor(
+            stdout,
+            console::Position {
+                row: point.y,
+                col: point.x,
+            },
+        );
+        console::set_sgr(stdout, [console::SgrParam::Reset, colour]);
+        let _ = stdout.write_char(ch);
+    }
+
+    /// Draw a shot or a bomb
+    fn draw_missile(
+        stdout: &mut neotron_sdk::File,
+        point: Point,
+        ch: char,
+        colour: console::SgrParam,
+    ) {
+        console::move_cursor(
+            stdout,
+            console::Position {
+                row: point.y,
+                col: point.x,
+            },
+        );
+        console::set_sgr(
+            stdout,
+            [console::SgrParam::Reset, console::SgrParam::Bold, colour],
+        );
+        let _ = stdout.write_char(ch);
+    }
+
+    /// Clear the screen and draw everything
+    fn draw_all(&self, stdout: &mut neotron_sdk::File) {
+        console::set_sgr(stdout, [console::SgrParam::Reset]);
+        console::clear_screen(stdout);
+        self.draw_status(stdout);
+        self.draw_fleet(stdout, self.fleet_y);
+        for row in 0..Self::SHIELD_ROWS as u8 {
+            for x in 0..self.width {
+                self.draw_background(
+                    stdout,
+                    Point {
+                        x,
+                        y: self.shield_row() + row,
+                    },
+                );
+            }
+        }
+        self.draw_player(stdout);
+        // The ground
+        console::set_sgr(
+            stdout,
+            [console::SgrParam::Reset, console::SgrParam::FgGreen],
+        );
+        console::move_cursor(
+            stdout,
+            console::Position {
+                row: self.height - 1,
+                col: 0,
+            },
+        );
+        for _ in 1..self.width {
+            let _ = stdout.write_char('▀');
+        }
+        console::set_sgr(stdout, [console::SgrParam::Reset]);
+    }
+
+    /// Draw the score line at the top of the screen
+    fn draw_status(&self, stdout: &mut neotron_sdk::File) {
+        console::set_sgr(stdout, [console::SgrParam::Reset, console::SgrParam::Bold]);
+        console::move_cursor(stdout, console::Position { row: 0, col: 1 });
+        let _ = write!(
+            stdout,
+            "SCORE {:06}   HI {:06}   LEVEL {:<3}   LIVES ",
+            self.score,
+            self.high_score.max(self.score),
+            self.level
+        );
+        console::set_sgr(
+            stdout,
+            [console::SgrParam::Reset, console::SgrParam::FgYellow],
+        );
+        for life in 0..Self::START_LIVES {
+            let _ = stdout.write_char(if life < self.lives { '▲' } else { ' ' });
+        }
+        console::set_sgr(stdout, [console::SgrParam::Reset]);
+    }
+
+    /// Draw the rows of the fleet, given the row the fleet used to start on.
+    ///
+    /// We also draw one column either side, and any rows the fleet used to be
+    /// on, to rub out where the invaders were before.
+    fn draw_fleet(&self, stdout: &mut neotron_sdk::File, old_y: u8) {
+        let fleet_width = Self::SPACING * (Self::COLS as i16 - 1) + Self::SPRITE_WIDTH as i16;
+        let left = (self.fleet_x - 1).max(0) as u8;
+        let right = (self.fleet_x + fleet_width + 1).min(i16::from(self.width)) as u8;
+        for row in 0..Self::ROWS as u8 {
+            let old_row = old_y + row * 2;
+            let new_row = self.fleet_y + row * 2;
+            if old_row != new_row {
+                self.draw_span(stdout, old_row, left, right);
+            }
+            self.draw_span(stdout, new_row, left, right);
+        }
+        console::set_sgr(stdout, [console::SgrParam::Reset]);
+    }
+
+    /// Redraw part of a row, from `left` up to (but not including) `right`
+    fn draw_span(&self, stdout: &mut neotron_sdk::File, y: u8, left: u8, right: u8) {
+        let mut last_colour = None;
+        console::move_cursor(stdout, console::Position { row: y, col: left });
+        for x in left..right {
+            let (ch, colour) = self.background_at(Point { x, y });
+            if last_colour != Some(colour) {
+                console::set_sgr(stdout, [console::SgrParam::Reset, colour]);
+                last_colour = Some(colour);
+            }
+            let _ = stdout.write_char(ch);
+        }
+    }
+
+    /// Draw the player's base, and the spaces either side of it
+    fn draw_player(&self, stdout: &mut neotron_sdk::File) {
+        let y = self.player_row();
+        for x in self.player_x.saturating_sub(1)
+            ..(self.player_x + Self::SPRITE_WIDTH + 1).min(self.width)
+        {
+            self.draw_background(stdout, Point { x, y });
+        }
+        console::set_sgr(stdout, [console::SgrParam::Reset]);
+    }
+
+    /// Move the player's shot up the screen
+    fn move_shot(&mut self, stdout: &mut neotron_sdk::File, sound: &mut Sound) {
+        let Some(shot) = self.shot else {
+            return;
+        };
+        if shot.y != self.player_row() {
+            self.draw_background(stdout, shot);
+        }
+        if shot.y <= 1 {
+            self.shot = None;
+            return;
+        }
+        let shot = Point {
+            x: shot.x,
+            y: shot.y - 1,
+        };
+        self.shot = Some(shot);
+        // Did we meet a bomb coming the other way?
+        for bomb in self.bombs.iter_mut() {
+            if *bomb == Some(shot) {
+                *bomb = None;
+                self.shot = None;
+                self.draw_background(stdout, shot);
+                sound.play(Effect::Explosion);
+                return;
+            }
+        }
+    }
+
+    /// See if the shot has hit anything, and draw it if it hasn't
+    fn check_shot(&mut self, stdout: &mut neotron_sdk::File, sound: &mut Sound) {
+        let Some(shot) = self.shot else {
+            return;
+        };
+        if let Some((row, col)) = self.invader_at(shot) {
+            self.alive[row][col] = false;
+            self.remaining -= 1;
+            self.score += Kind::for_row(row).points();
+            self.shot = None;
+            sound.play(Effect::Explosion);
+            // Rub out the invader
+            let left = self.fleet_x + col as i16 * Self::SPACING;
+            for x in left..left + i16::from(Self::SPRITE_WIDTH) {
+                self.draw_background(
+                    stdout,
+                    Point {
+                        x: x as u8,
+                        y: shot.y,
+                    },
+                );
+            }
+            self.draw_status(stdout);
+        } else if let Some(cell) = self.shield_at(shot) {
+            *cell -= 1;
+            self.shot = None;
+            self.draw_background(stdout, shot);
+        } else {
+            Self::draw_missile(stdout, shot, '│', console::SgrParam::FgWhite);
+        }
+    }
+
+    /// Move the bombs down the screen
+    fn move_bombs(&mut self, stdout: &mut neotron_sdk::File) {
+        for idx in 0..Self::MAX_BOMBS {
+            let Some(bomb) = self.bombs[idx] else {
+                continue;
+            };
+            self.draw_background(stdout, bomb);
+            if bomb.y + 1 >= self.height - 1 {
+                self.bombs[idx] = None;
+                continue;
+            }
+            let bomb = Point {
+                x: bomb.x,
+                y: bomb.y + 1,
+            };
+            if self.shot == Some(bomb) {
+                // Shot it down
+                self.shot = None;
+                self.bombs[idx] = None;
+                self.draw_background(stdout, bomb);
+            } else if let Some(cell) = self.shield_at(bomb) {
+                *cell -= 1;
+                self.bombs[idx] = None;
+                self.draw_background(stdout, bomb);
+            } else {
+                self.bombs[idx] = Some(bomb);
+                Self::draw_missile(stdout, bomb, '↓', console::SgrParam::FgRed);
+            }
+        }
+    }
+
+    /// Maybe drop a new bomb from a random invader
+    fn drop_bombs(&mut self, stdout: &mut neotron_sdk::File) {
+        // Later levels drop bombs more often
+        let chance = 12u16.saturating_sub(u16::from(self.level)).max(4);
+        if !neotron_sdk::rand().is_multiple_of(chance) {
+            return;
+        }
+        let Some(slot) = self.bombs.iter().position(|b| b.is_none()) else {
+            return;
+        };
+        let col = usize::from(neotron_sdk::rand()) % Self::COLS;
+        // Find the lowest invader in that column
+        let Some(row) = (0..Self::ROWS).rev().find(|&row| self.alive[row][col]) else {
+            return;
+        };
+        let x = self.fleet_x + col as i16 * Self::SPACING + 1;
+        if x < 0 || x >= i16::from(self.width) {
+            return;
+        }
+        let bomb = Point {
+            x: x as u8,
+            y: self.fleet_y + row as u8 * 2 + 1,
+        };
+        self.bombs[slot] = Some(bomb);
+        Self::draw_missile(stdout, bomb, '↓', console::SgrParam::FgRed);
+    }
+
+    /// March the fleet one step sideways, or down if it has reached the edge
+    fn step_fleet(&mut self, stdout: &mut neotron_sdk::File, sound: &mut Sound) {
+        let mut min_col = Self::COLS;
+        let mut max_col = 0;
+        for row in self.alive.iter() {
+            for (col, alive) in row.iter().enumerate() {
+                if *alive {
+                    min_col = min_col.min(col);
+                    max_col = max_col.max(col);
+                }
+            }
+        }
+        if min_col > max_col {
+            return;
+        }
+        let left = self.fleet_x + min_col as i16 * Self::SPACING;
+        let right = self.fleet_x + max_col as i16 * Self::SPACING + i16::from(Self::SPRITE_WIDTH);
+        let old_y = self.fleet_y;
+        if (self.fleet_right && right >= i16::from(self.width)) || (!self.fleet_right && left <= 0)
+        {
+            self.fleet_y += 1;
+            self.fleet_right = !self.fleet_right;
+        } else if self.fleet_right {
+            self.fleet_x += 1;
+        } else {
+            self.fleet_x -= 1;
+        }
+        self.fleet_frame = !self.fleet_frame;
+
+        // Invaders crush any shields they walk over
+        for (row, alive_row) in self.alive.iter().enumerate() {
+            let y = self.fleet_y + row as u8 * 2;
+            let Some(shield_row) = y.checked_sub(self.shield_row()) else {
+                continue;
+            };
+            let Some(cells) = self.shields.get_mut(usize::from(shield_row)) else {
+                continue;
+            };
+            for (col, alive) in alive_row.iter().enumerate() {
+                if !alive {
+                    continue;
+                }
+                let left = self.fleet_x + col as i16 * Self::SPACING;
+                for x in left..left + i16::from(Self::SPRITE_WIDTH) {
+                    if let Some(cell) = cells.get_mut(x as usize) {
+                        *cell = 0;
+                    }
+                }
+            }
+        }
+
+        self.draw_fleet(stdout, old_y);
+        sound.play(Effect::Step(self.step_note));
+        self.step_note = self.step_note.wrapping_add(1);
+    }
+
+    /// Has a bomb hit the player's base?
+    fn player_is_hit(&self) -> bool {
+        self.bombs.iter().flatten().any(|bomb| {
+            bomb.y == self.player_row()
+                && bomb.x >= self.player_x
+                && bomb.x < self.player_x + Self::SPRITE_WIDTH
+        })
+    }
+
+    /// Have the invaders reached the ground?
+    fn fleet_landed(&self) -> bool {
+        let lowest = (0..Self::ROWS)
+            .rev()
+            .find(|&row| self.alive[row].iter().any(|a| *a));
+        match lowest {
+            Some(row) => self.fleet_y + row as u8 * 2 >= self.player_row(),
+            None => false,
+        }
+    }
+
+    /// Blow up the player's base and take away a life
+    fn lose_life(&mut self, stdout: &mut neotron_sdk::File, sound: &mut Sound) {
+        self.lives -= 1;
+        sound.play(Effect::PlayerHit);
+        for idx in 0..Self::MAX_BOMBS {
+            if let Some(point) = self.bombs[idx].take() {
+                self.draw_background(stdout, point);
+            }
+        }
+        self.draw_status(stdout);
+        // Flash the wreckage for a while
+        for frame in 0..20 {
+            console::move_cursor(
+                stdout,
+                console::Position {
+                    row: self.player_row(),
+                    col: self.player_x,
+                },
+            );
+            console::set_sgr(stdout, [console::SgrParam::Reset, console::SgrParam::FgRed]);
+            let _ = stdout.write_str(if frame % 2 == 0 { "*▒*" } else { "▒*▒" });
+            sound.tick(Self::FRAME_MS * 2);
+            neotron_sdk::delay(core::time::Duration::from_millis(u64::from(
+                Self::FRAME_MS * 2,
+            )));
+        }
+        if self.lives > 0 {
+            self.player_x = (self.width - Self::SPRITE_WIDTH) / 2;
+            let y = self.player_row();
+            for x in 0..self.width {
+                self.draw_background(stdout, Point { x, y });
+            }
+        }
+        console::set_sgr(stdout, [console::SgrParam::Reset]);
+    }
+
+    /// Show the game over message and wait a moment
+    fn game_over(&self, stdout: &mut neotron_sdk::File, sound: &mut Sound) {
+        let message = " G A M E   O V E R ";
+        console::move_cursor(
+            stdout,
+            console::Position {
+                row: self.height / 2,
+                col: (self.width - message.len() as u8) / 2,
+            },
+        );
+        console::set_sgr(
+            stdout,
+            [
+                console::SgrParam::Reset,
+                console::SgrParam::Bold,
+                console::SgrParam::FgRed,
+                console::SgrParam::BgBlack,
+            ],
+        );
+        let _ = stdout.write_str(message);
+        console::set_sgr(stdout, [console::SgrParam::Reset]);
+        for _ in 0..50 {
+            sound.tick(Self::FRAME_MS);
+            neotron_sdk::delay(core::time::Duration::from_millis(u64::from(Self::FRAME_MS)));
+        }
+    }
+
+    /// Wait until a key is pressed
+    fn pause(&self, keyboard: &mut Keyboard, stdout: &mut neotron_sdk::File) {
+        let message = " PAUSED - press a key ";
+        let point = Point {
+            x: (self.width - message.len() as u8) / 2,
+            y: self.height / 2,
+        };
+        console::move_cursor(
+            stdout,
+            console::Position {
+                row: point.y,
+                col: point.x,
+            },
+        );
+        console::set_sgr(
+            stdout,
+            [console::SgrParam::Reset, console::SgrParam::Reverse],
+        );
+        let _ = stdout.write_str(message);
+        console::set_sgr(stdout, [console::SgrParam::Reset]);
+        keyboard.wait();
+        for x in point.x..point.x + message.len() as u8 {
+            self.draw_background(stdout, Point { x, y: point.y });
+        }
+        console::set_sgr(stdout, [console::SgrParam::Reset]);
+    }
+}
@@ -0,0 +1,199 @@
+//! Sound effects, played through the audio device.
+//!
+//! We make simple square-wave and noise effects on the fly. The game calls
+//! [`Sound::tick`] once per frame and we write out exactly one frame's worth
+//! of samples, so the audio keeps pace with the game. If there is no audio
+//! device, the game carries on in silence.
+
+/// The sample rate we ask the audio device for
+const SAMPLE_RATE: u32 = 44100;
+
+/// How loud our effects are
+const AMPLITUDE: i32 = 6000;
+
+/// The sound effects we can make
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Effect {
+    /// The player fires a shot
+    Shoot,
+    /// The invaders take a step. There are four notes, which cycle.
+    Step(u8),
+    /// An invader is destroyed
+    Explosion,
+    /// The player's base is destroyed
+    PlayerHit,
+}
+
+impl Effect {
+    /// How long this effect lasts, in milliseconds
+    fn duration_ms(self) -> u32 {
+        match self {
+            Effect::Shoot => 150,
+            Effect::Step(_) => 60,
+            Effect::Explosion => 250,
+            Effect::PlayerHit => 800,
+        }
+    }
+
+    /// More important effects interrupt less important ones
+    fn priority(self) -> u8 {
+        match self {
+            Effect::Step(_) => 0,
+            Effect::Shoot => 1,
+            Effect::Explosion => 2,
+            Effect::PlayerHit => 3,
+        }
+    }
+}
+
+/// Plays sound effects
+pub struct Sound {
+    dsp: Option<neotron_sdk::File>,
+    effect: Option<Effect>,
+    /// Total length of the current effect, in samples
+    length: u32,
+    /// How many samples of the current effect are left
+    remaining: u32,
+    /// Square wave phase accumulator (16.16 fixed point)
+    phase: u32,
+    /// Noise generator state
+    lfsr: u16,
+}
+
+impl Sound {
+    /// Make a silent sound player. Call [`Sound::open`] to start making noise.
+    pub const fn new() -> Sound {
+        Sound {
+            dsp: None,
+            effect: None,
+            length: 0,
+            remaining: 0,
+            phase: 0,
+            lfsr: 0xACE1,
+        }
+    }
+
+    /// Try and open the audio device.
+    ///
+    /// If it isn't there, we just stay silent.
+    pub fn open(&mut self) {
+        let Ok(path) = neotron_sdk::path::Path::new("AUDIO:") else {
+            return;
+        };
+        let Ok(dsp) = neotron_sdk::File::open(path, neotron_sdk::Flags::empty()) else {
+            return;
+        };
+        // Set 16-bit stereo, 44.1 kHz
+        if dsp.ioctl(1, 3 << 60 | u64::from(SAMPLE_RATE)).is_err() {
+            return;
+        }
+        self.dsp = Some(dsp);
+        // Some silence to give us a head-start
+        self.tick(50);
+    }
+
+    /// Close the audio device
+    pub fn close(&mut self) {
+        self.dsp = None;
+        self.effect = None;
+    }
+
+    /// Start playing an effect.
+    ///
+    /// It won't interrupt a more important effect that is still playing.
+    pub fn play(&mut self, effect: Effect) {
+        if let Some(current) = self.effect {
+            if self.remaining > 0 && current.priority() > effect.priority() {
+                return;
+            }
+        }
+        self.effect = Some(effect);
+        self.length = effect.duration_ms() * SAMPLE_RATE / 1000;
+        self.remaining = self.length;
+    }
+
+    /// Send the given number of milliseconds of audio to the audio device
+    pub fn tick(&mut self, ms: u32) {
+        if self.dsp.is_none() {
+            return;
+        }
+        let mut samples_left = ms * SAMPLE_RATE / 1000;
+        let mut buffer = [0u8; 1024];
+        while samples_left > 0 {
+            let mut used = 0;
+            for chunk in buffer.chunks_exact_mut(4) {
+                if samples_left == 0 {
+                    break;
+                }
+                let sample = self.next_sample().to_le_bytes();
+                chunk[0] = sample[0];
+                chunk[1] = sample[1];
+                chunk[2] = sample[0];
+                chunk[3] = sample[1];
+                used += 4;
+                samples_left -= 1;
+            }
+            if let Some(dsp) = self.dsp.as_ref() {
+                let _ = dsp.write(&buffer[0..used]);
+            }
+        }
+    }
+
+    /// Work out the next (mono) sample
+    fn next_sample(&mut self) -> i16 {
+        let Some(effect) = self.effect else {
+            return 0;
+        };
+        if self.remaining == 0 {
+            self.effect = None;
+            return 0;
+        }
+        let elapsed = self.length - self.remaining;
+        self.remaining -= 1;
+        // Fade out over the length of the effect
+        let volume = (self.remaining * 256 / self.length) as i32;
+        let high = match effect {
+            Effect::Shoot => {
+                // A falling whistle
+                let frequency = 1600 - (1200 * elapsed / self.length);
+                self.square(frequency)
+            }
+            Effect::Step(note) => {
+                let frequency = match note % 4 {
+                    0 => 110,
+                    1 => 98,
+                    2 => 87,
+                    _ => 82,
+                };
+                self.square(frequency)
+            }
+            Effect::Explosion => self.noise(elapsed, 4),
+            Effect::PlayerHit => self.noise(elapsed, 12),
+        };
+        let level = if high { AMPLITUDE } else { -AMPLITUDE };
+        (level * volume / 256) as i16
+    }
+
+    /// Step a square wave of the given frequency, and get its current level
+    fn square(&mut self, frequency: u32) -> bool {
+        self.phase = self.phase.wrapping_add((frequency << 16) / SAMPLE_RATE);
+        self.phase & 0x8000 != 0
+    }
+
+    /// Get some noise, which changes every `hold` samples.
+    ///
+    /// Larger values of `hold` make a deeper rumble.
+    fn noise(&mut self, elapsed: u32, hold: u32) -> bool {
+        if elapsed.is_multiple_of(hold) {
+            let bit = (self.lfsr ^ (self.lfsr >> 2) ^ (self.lfsr >> 3) ^ (self.lfsr >> 5)) & 1;
+            self.lfsr = (self.lfsr >> 1) | (bit << 15);
+        }
+        self.lfsr & 1 != 0
+    }
+}
+
+impl Default for Sound {
+    fn default() -> Self {
+        Sound::new()
+    }
+}
@@ -0,0 +1,227 @@
+//! Application logic for the keyboard tester
+//!
+//! We read standard input a byte at a time and feed it through the same
+//! [`Decoder`] every application uses, keeping the bytes that made up each
+//! key and how long they took to arrive. A key that comes out wrong can
+//! then be seen for what the terminal really sent:
+//!
+//! ```text
+//!    time    gap  bytes                    text           key
+//!    1520   +812  1b 5b 31 35 7e           ESC [ 1 5 ~    F5
+//! ```
+
+#![no_std]
+#![deny(missing_docs)]
+#![deny(unsafe_code)]
+
+use core::fmt::Write;
+
+use neotron_input::{Decoder, KeyEvent};
+
+/// The most bytes we keep for one key. Any more are counted, but not shown.
+pub const MAX_BYTES: usize = 8;
+
+/// The bytes that made up one key, and when they arrived
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Press {
+    bytes: [u8; MAX_BYTES],
+    len: usize,
+    /// Bytes that didn't fit in `bytes`
+    extra: usize,
+    /// When the first byte arrived, in milliseconds
+    pub started_ms: u64,
+    /// The longest wait between two of its bytes
+    pub max_gap_ms: u64,
+    /// What the decoder made of it, if anything
+    pub key: Option<KeyEvent>,
+    /// The decoder gave up waiting for the rest of a sequence
+    pub timed_out: bool,
+    /// It started soon after the decoder gave up waiting, so it might be
+    /// the rest of a sequence that arrived too slowly
+    pub maybe_split: bool,
+}
+
+impl Press {
+    /// A press with nothing in it yet
+    const fn new(started_ms: u64) -> Press {
+        Press {
+            bytes: [0; MAX_BYTES],
+            len: 0,
+            extra: 0,
+            started_ms,
+            max_gap_ms: 0,
+            key: None,
+            timed_out: false,
+            maybe_split: false,
+        }
+    }
+
+    /// The bytes we kept
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes[0..self.len]
+    }
+
+    fn push(&mut self, byte: u8) {
+        if self.len < MAX_BYTES {
+            self.bytes[self.len] = byte;
+            self.len += 1;
+        } else {
+            self.extra += 1;
+        }
+    }
+}
+
+/// Feeds bytes to the decoder, and sorts them into key presses
+pub struct Tester {
+    decoder: Decoder,
+    /// The key we're part way through, if any
+    press: Option<Press>,
+    /// When the last byte arrived
+    last_byte_ms: u64,
+    /// When the decoder last gave up waiting
+    timed_out_ms: Option<u64>,
+    /// How long to wait for the rest of a sequence
+    timeout_ms: u32,
+}
+
+impl Tester {
+    /// Make a tester, which waits `timeout_ms` for the rest of a sequence
+    pub const fn new(timeout_ms: u32) -> Tester {
+        Tester {
+            decoder: Decoder::new(),
+            press: None,
+            last_byte_ms: 0,
+            timed_out_ms: None,
+            timeout_ms,
+        }
+    }
+
+    /// How long we wait for the rest of a sequence
+    pub fn timeout_ms(&self) -> u32 {
+        self.timeout_ms
+    }
+
+    /// Take a byte that arrived at `now_ms`. Gives a press if that byte
+    /// finished one off.
+    pub fn feed(&mut self, byte: u8, now_ms: u64) -> Option<Press> {
+        let gap_ms = now_ms - self.last_byte_ms;
+        self.last_byte_ms = now_ms;
+        let key = self.decoder.feed(byte);
+        let Some(mut press) = self.press.take() else {
+            let press = self.start(byte, now_ms);
+            if key.is_none() && self.decoder.is_pending() {
+                self.press = Some(press);
+                return None;
+            }
+            return Some(Press { key, ..press });
+        };
+        if key.is_some() && self.decoder.is_pending() {
+            // This byte finished the last key off, and started another -
+            // like a second Escape
+            self.press = Some(self.start(byte, now_ms));
+        } else {
+            press.push(byte);
+            press.max_gap_ms = press.max_gap_ms.max(gap_ms);
+            if key.is_none() && self.decoder.is_pending() {
+                self.press = Some(press);
+                return None;
+            }
+        }
+        press.key = key;
+        Some(press)
+    }
+
+    /// Call this when no byte has arrived. Gives a press if the decoder has
+    /// waited long enough for the rest of a sequence.
+    pub fn poll(&mut self, now_ms: u64) -> Option<Press> {
+        if !self.decoder.is_pending() || now_ms - self.last_byte_ms < u64::from(self.timeout_ms) {
+            return None;
+        }
+        let mut press = self.press.take()?;
+        press.key = self.decoder.timeout();
+        press.timed_out = true;
+        self.timed_out_ms = Some(now_ms);
+        Some(press)
+    }
+
+    /// Start a new press with its first byte
+    fn start(&mut self, byte: u8, now_ms: u64) -> Press {
+        let mut press = Press::new(now_ms);
+        press.push(byte);
+        // The rest of a sequence wouldn't start with another Escape
+        if let Some(timed_out) = self.timed_out_ms.take() {
+            press.maybe_split = byte != 0x1B && now_ms - timed_out < u64::from(self.timeout_ms);
+        }
+        press
+    }
+}
+
+/// Write bytes in hex, like `1b 5b 41`, padded out to `width` columns
+pub fn write_hex<W: Write>(out: &mut W, press: &Press, width: usize) -> core::fmt::Result {
+    let mut written = 0;
+    for byte in press.bytes() {
+        write!(out, "{:02x} ", byte)?;
+        written += 3;
+    }
+    if press.extra > 0 {
+        write!(out, "+{} ", press.extra)?;
+        written += 2 + count_digits(press.extra);
+    }
+    pad(out, width, written)
+}
+
+/// Write bytes as text, like `ESC [ A`, with control characters named,
+/// padded out to `width` columns
+pub fn write_text<W: Write>(out: &mut W, press: &Press, width: usize) -> core::fmt::Result {
+    let mut written = 0;
+    for (idx, &byte) in press.bytes().iter().enumerate() {
+        if idx > 0 {
+            out.write_char(' ')?;
+            written += 1;
+        }
+        match byte {
+            0x1B => out.write_str("ESC")?,
+            0x7F => out.write_str("DEL")?,
+            0x00..=0x1F => write!(out, "^{}", char::from(byte + b'@'))?,
+            0x20..=0x7E => out.write_char(char::from(byte))?,
+            // Part of a UTF-8 character, which the hex shows better
+            _ => out.write_char('.')?,
+        }
+        written += match byte {
+            0x1B | 0x7F => 3,
+            0x00..=0x1F => 2,
+            _ => 1,
+        };
+    }
+    pad(out, width, written)
+}
+
+/// Write what a key is, like `F5` or `Ctrl+C`
+pub fn write_key<W: Write>(out: &mut W, key: KeyEvent) -> core::fmt::Result {
+    match key {
+        KeyEvent::Char(' ') => out.write_str("Space"),
+        KeyEvent::Char(ch) => write!(out, "'{}'", ch),
+        KeyEvent::F(n) => write!(out, "F{}", n),
+        KeyEvent::Ctrl(ch) => write!(out, "Ctrl+{}", ch.to_ascii_uppercase()),
+        KeyEvent::Alt(ch) => write!(out, "Alt+{}", ch),
+        other => write!(out, "{:?}", other),
+    }
+}
+
+/// Write spaces until we've written `width` columns
+fn pad<W: Write>(out: &mut W, width: usize, written: usize) -> core::fmt::Result {
+    for _ in written..width {
+        out.write_char(' ')?;
+    }
+    Ok(())
+}
+
+/// How many digits a number has
+fn count_digits(mut value: usize) -> usize {
+    let mut digits = 1;
+    while value >= 10 {
+        value /= 10;
+        digits += 1;
+    }
+    digits
+}
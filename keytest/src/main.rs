@@ -0,0 +1,151 @@
+#![cfg_attr(target_os = "none", no_std)]
+#![cfg_attr(target_os = "none", no_main)]
+
+use core::fmt::Write;
+
+use neotron_args::{Item, Opt};
+use neotron_input::KeyEvent;
+
+#[cfg(not(target_os = "none"))]
+fn main() {
+    neotron_sdk::init();
+}
+
+#[no_mangle]
+extern "C" fn neotron_main() -> i32 {
+    neotron_error::exit_code_text(real_main())
+}
+
+/// The options we understand
+static SPEC: neotron_args::Spec = neotron_args::Spec {
+    name: "keytest",
+    usage: "[options]",
+    about: "Shows the bytes each key sends, and what applications will make \
+            of them, for sorting out keyboards and terminals that don't \
+            work as they should.",
+    options: &[Opt::value(
+        't',
+        "timeout",
+        "ms",
+        "How long to wait for the rest of an escape sequence (default 50)",
+    )],
+};
+
+/// How many Escapes in a row make us quit
+const QUIT_ESCAPES: u32 = 3;
+
+/// How often we line our clock up with the real-time clock, in polls
+const SYNC_POLLS: u32 = 250;
+
+/// How wide the columns are
+const HEX_WIDTH: usize = 25;
+const TEXT_WIDTH: usize = 15;
+
+fn real_main() -> Result<(), neotron_sdk::Error> {
+    let mut stdout = neotron_sdk::stdout();
+    let stdin = neotron_sdk::stdin();
+    let mut timeout_ms = neotron_input::ESCAPE_TIMEOUT_MS;
+    let mut args = neotron_args::Parser::new(&SPEC);
+    loop {
+        let item = match args.next_item() {
+            Ok(Some(item)) => item,
+            Ok(None) => break,
+            Err(neotron_args::Error::Help) => return Ok(()),
+            Err(e) => return Err(e.into()),
+        };
+        if let Item::Value("timeout", value) = item {
+            timeout_ms = args.parse("timeout", &value)?;
+        }
+    }
+
+    let mut tester = keytest::Tester::new(timeout_ms);
+    let mut clock = neotron_frame::Clock::new();
+    clock.start();
+    let _ = writeln!(
+        stdout,
+        "Press some keys. Press Escape {} times to quit.",
+        QUIT_ESCAPES
+    );
+    if !clock.is_accurate() {
+        let _ = writeln!(stdout, "There's no real-time clock, so times are rough.");
+    }
+    let _ = writeln!(
+        stdout,
+        "   time    gap  {:<hex$}{:<text$}key",
+        "bytes",
+        "text",
+        hex = HEX_WIDTH,
+        text = TEXT_WIDTH
+    );
+
+    let mut last_ms = 0;
+    let mut escapes = 0;
+    let mut keys = 0;
+    let mut split = 0;
+    let mut polls = 0;
+    while escapes < QUIT_ESCAPES {
+        let mut buffer = [0u8; 1];
+        let press = if let Ok(1) = stdin.read(&mut buffer) {
+            tester.feed(buffer[0], clock.now_ms())
+        } else {
+            clock.sleep_ms(1);
+            polls += 1;
+            if polls % SYNC_POLLS == 0 {
+                clock.sync();
+            }
+            tester.poll(clock.now_ms())
+        };
+        let Some(press) = press else {
+            continue;
+        };
+        let _ = show(&mut stdout, &press, press.started_ms - last_ms, &tester);
+        last_ms = press.started_ms;
+        keys += 1;
+        if press.maybe_split {
+            split += 1;
+        }
+        if press.key == Some(KeyEvent::Escape) {
+            escapes += 1;
+        } else {
+            escapes = 0;
+        }
+    }
+
+    let _ = writeln!(stdout, "{} keys", keys);
+    if split > 0 {
+        let _ = writeln!(
+            stdout,
+            "{} looked like the end of an escape sequence that arrived more \
+             than {}ms after its start. Try a longer --timeout.",
+            split,
+            tester.timeout_ms()
+        );
+    }
+    Ok(())
+}
+
+/// Show a line for one key
+fn show<W: Write>(
+    out: &mut W,
+    press: &keytest::Press,
+    gap_ms: u64,
+    tester: &keytest::Tester,
+) -> core::fmt::Result {
+    write!(out, "{:>7} {:>+6}  ", press.started_ms, gap_ms as i64)?;
+    keytest::write_hex(out, press, HEX_WIDTH)?;
+    keytest::write_text(out, press, TEXT_WIDTH)?;
+    match press.key {
+        Some(key) => keytest::write_key(out, key)?,
+        None => out.write_str("(nothing)")?,
+    }
+    if press.max_gap_ms > 0 {
+        write!(out, " ({}ms apart)", press.max_gap_ms)?;
+    }
+    if press.timed_out {
+        write!(out, " after {}ms", tester.timeout_ms())?;
+    }
+    if press.maybe_split {
+        out.write_str(" - split?")?;
+    }
+    writeln!(out)
+}
@@ -0,0 +1,526 @@
+//! Game logic for Conway's Game of Life
+
+#![no_std]
+#![deny(missing_docs)]
+#![deny(unsafe_code)]
+
+use core::fmt::Write;
+
+use neotron_input::{KeyEvent, Keyboard};
+use neotron_sdk::console;
+
+pub mod pattern;
+
+/// What the keys are for at the moment
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Mode {
+    /// Editing the grid, or watching it run
+    Edit,
+    /// The help screen is up until a key is pressed
+    Help,
+    /// Typing the name of a pattern file to load
+    Load,
+}
+
+/// Represents the Life application
+///
+/// It's a [`neotron_scene::Game`], so it can be run a key at a time.
+pub struct App {
+    width: u8,
+    height: u8,
+    stdout: neotron_sdk::File,
+    mode: Mode,
+    /// Changes with every key pressed, to mix into the random numbers
+    seed: u16,
+    grid: Grid,
+    /// The previous generation, so we only redraw what changed
+    previous: Grid,
+    generation: u32,
+    running: bool,
+    /// Delay between generations when running
+    interval_ms: u16,
+    /// Where the editing cursor is
+    cursor: (u8, u8),
+    /// Somewhere to load pattern files into
+    file_buffer: [u8; Self::FILE_BUFFER_LEN],
+    /// The name of the pattern file being typed in
+    load_name: [u8; Self::MAX_FILENAME],
+    load_len: usize,
+}
+
+impl App {
+    /// The largest pattern file we can load
+    const FILE_BUFFER_LEN: usize = 16 * 1024;
+    /// The longest pattern file name you can type
+    const MAX_FILENAME: usize = 64;
+    /// The fastest we go
+    const MIN_INTERVAL_MS: u16 = 10;
+    /// The slowest we go
+    const MAX_INTERVAL_MS: u16 = 2000;
+
+    /// Make a new Life application.
+    ///
+    /// You can give the screen size in characters. The bottom row is used for
+    /// the status line.
+    pub const fn new(width: u8, height: u8) -> App {
+        let grid_width = if width as usize > Grid::MAX_WIDTH {
+            Grid::MAX_WIDTH as u8
+        } else {
+            width
+        };
+        let grid_height = if height as usize > Grid::MAX_HEIGHT + 1 {
+            Grid::MAX_HEIGHT as u8
+        } else {
+            height - 1
+        };
+        App {
+            width,
+            height,
+            stdout: neotron_sdk::stdout(),
+            mode: Mode::Edit,
+            seed: 0x7a3b,
+            grid: Grid::new(grid_width, grid_height),
+            previous: Grid::new(grid_width, grid_height),
+            generation: 0,
+            running: false,
+            interval_ms: 100,
+            cursor: (grid_width / 2, grid_height / 2),
+            file_buffer: [0; Self::FILE_BUFFER_LEN],
+            load_name: [0; Self::MAX_FILENAME],
+            load_len: 0,
+        }
+    }
+
+    /// Load a `.rle` or `.cells` pattern file, centred on the grid.
+    pub fn load_file(&mut self, filename: &str) -> Result<(), neotron_sdk::Error> {
+        let path = neotron_sdk::path::Path::new(filename)?;
+        let f = neotron_sdk::File::open(path, neotron_sdk::Flags::empty())?;
+        let n = f.read(&mut self.file_buffer)?;
+        drop(f);
+        let Ok(text) = core::str::from_utf8(&self.file_buffer[0..n]) else {
+            return Err(neotron_sdk::Error::InvalidArg);
+        };
+        let Ok((pattern_width, pattern_height)) = pattern::parse(text, |_, _| {}) else {
+            return Err(neotron_sdk::Error::InvalidArg);
+        };
+        let grid = &mut self.grid;
+        grid.clear();
+        let offset_x = u16::from(grid.width).saturating_sub(pattern_width) / 2;
+        let offset_y = u16::from(grid.height).saturating_sub(pattern_height) / 2;
+        let _ = pattern::parse(text, |x, y| {
+            let x = x + offset_x;
+            let y = y + offset_y;
+            if x < u16::from(grid.width) && y < u16::from(grid.height) {
+                grid.set(x as u8, y as u8, true);
+            }
+        });
+        self.generation = 0;
+        Ok(())
+    }
+
+    /// Run the Life explorer until the user quits.
+    pub fn play(&mut self) {
+        neotron_scene::play(self, &mut Keyboard::new());
+    }
+
+    /// Deal with a keypress while editing.
+    ///
+    /// Returns `false` if the user wants to quit.
+    fn edit_key(&mut self, key: KeyEvent) -> bool {
+        let old_cursor = self.cursor;
+        match key {
+            KeyEvent::Up | KeyEvent::Char('w' | 'W') => {
+                self.cursor.1 = self.cursor.1.checked_sub(1).unwrap_or(self.grid.height - 1)
+            }
+            KeyEvent::Down | KeyEvent::Char('s' | 'S') => {
+                self.cursor.1 = (self.cursor.1 + 1) % self.grid.height
+            }
+            KeyEvent::Left | KeyEvent::Char('a' | 'A') => {
+                self.cursor.0 = self.cursor.0.checked_sub(1).unwrap_or(self.grid.width - 1)
+            }
+            KeyEvent::Right | KeyEvent::Char('d' | 'D') => {
+                self.cursor.0 = (self.cursor.0 + 1) % self.grid.width
+            }
+            KeyEvent::Char(' ') => {
+                let (x, y) = self.cursor;
+                let alive = self.grid.get(x, y);
+                self.grid.set(x, y, !alive);
+            }
+            KeyEvent::Enter => {
+                self.running = !self.running;
+            }
+            KeyEvent::Char('.') => {
+                self.running = false;
+                self.step();
+            }
+            KeyEvent::Char('+' | '=') => {
+                self.interval_ms = (self.interval_ms / 2).max(Self::MIN_INTERVAL_MS);
+            }
+            KeyEvent::Char('-' | '_') => {
+                self.interval_ms = (self.interval_ms * 2).min(Self::MAX_INTERVAL_MS);
+            }
+            KeyEvent::Char('e' | 'E') => {
+                self.grid.wrap = !self.grid.wrap;
+            }
+            KeyEvent::Char('c' | 'C') => {
+                self.grid.clear();
+                self.generation = 0;
+                self.running = false;
+            }
+            KeyEvent::Char('r' | 'R') => {
+                neotron_sdk::srand(self.seed);
+                self.grid.randomise();
+                self.generation = 0;
+            }
+            KeyEvent::Char('l' | 'L') => {
+                self.running = false;
+                self.prompt_load();
+                return true;
+            }
+            KeyEvent::Char('?') => {
+                self.running = false;
+                self.help_screen();
+                return true;
+            }
+            KeyEvent::Char('q' | 'Q') => {
+                return false;
+            }
+            _ => {}
+        }
+        self.draw_changes();
+        if old_cursor != self.cursor {
+            self.draw_cell(old_cursor.0, old_cursor.1);
+        }
+        self.draw_cell(self.cursor.0, self.cursor.1);
+        self.draw_status();
+        true
+    }
+
+    /// Show the keys, until one is pressed
+    fn help_screen(&mut self) {
+        const HELP: [&str; 13] = [
+            "   Neotron Life - Keys    ",
+            "",
+            " Arrows/WASD  Move cursor ",
+            " Space        Toggle cell ",
+            " Enter        Run / pause ",
+            " .            Single step ",
+            " + / -        Faster/slower",
+            " E            Wrap edges  ",
+            " R            Randomise   ",
+            " C            Clear       ",
+            " L            Load pattern",
+            " Q            Quit        ",
+            "",
+        ];
+        self.mode = Mode::Help;
+        console::set_sgr(
+            &mut self.stdout,
+            [console::SgrParam::Reset, console::SgrParam::Reverse],
+        );
+        let row = (self.height - HELP.len() as u8) / 2;
+        for (idx, line) in HELP.iter().enumerate() {
+            let col = (self.width - 28) / 2;
+            console::move_cursor(
+                &mut self.stdout,
+                console::Position {
+                    row: row + idx as u8,
+                    col,
+                },
+            );
+            let _ = write!(self.stdout, " {:<26} ", line);
+        }
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+    }
+
+    /// Ask for the name of a pattern file to load
+    fn prompt_load(&mut self) {
+        self.mode = Mode::Load;
+        self.load_len = 0;
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        console::move_cursor(
+            &mut self.stdout,
+            console::Position {
+                row: self.height - 1,
+                col: 0,
+            },
+        );
+        let _ = write!(self.stdout, "{:1$}", "", usize::from(self.width - 1));
+        console::move_cursor(
+            &mut self.stdout,
+            console::Position {
+                row: self.height - 1,
+                col: 0,
+            },
+        );
+        let _ = self.stdout.write_str("Load pattern: ");
+        console::cursor_on(&mut self.stdout);
+    }
+
+    /// Deal with a key while the pattern file name is being typed
+    fn load_key(&mut self, key: KeyEvent) {
+        match key {
+            KeyEvent::Enter => {}
+            KeyEvent::Escape => self.load_len = 0,
+            KeyEvent::Backspace if self.load_len > 0 => {
+                self.load_len -= 1;
+                let _ = self.stdout.write_str("\u{0008} \u{0008}");
+                return;
+            }
+            KeyEvent::Char(key @ ' '..='~') if self.load_len < self.load_name.len() => {
+                self.load_name[self.load_len] = key as u8;
+                self.load_len += 1;
+                let _ = self.stdout.write_char(key);
+                return;
+            }
+            _ => return,
+        }
+        console::cursor_off(&mut self.stdout);
+        if self.load_len > 0 {
+            let name = self.load_name;
+            if let Ok(filename) = core::str::from_utf8(&name[0..self.load_len]) {
+                let _ = self.load_file(filename);
+            }
+        }
+        self.mode = Mode::Edit;
+        self.redraw();
+    }
+
+    /// Work out the next generation and show it
+    fn step(&mut self) {
+        self.previous.copy_from(&self.grid);
+        self.grid.next_generation(&self.previous);
+        self.generation += 1;
+        self.draw_changes();
+        self.draw_cell(self.cursor.0, self.cursor.1);
+        self.draw_status();
+    }
+
+    /// Redraw the whole screen
+    fn redraw(&mut self) {
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        console::clear_screen(&mut self.stdout);
+        for y in 0..self.grid.height {
+            self.draw_row(y);
+        }
+        self.previous.copy_from(&self.grid);
+        self.draw_cell(self.cursor.0, self.cursor.1);
+        self.draw_status();
+    }
+
+    /// Redraw any rows which are different from last time we drew them
+    fn draw_changes(&mut self) {
+        for y in 0..self.grid.height {
+            if self.grid.row(y) != self.previous.row(y) {
+                self.draw_row(y);
+            }
+        }
+        self.previous.copy_from(&self.grid);
+    }
+
+    /// Draw one row of cells
+    fn draw_row(&mut self, y: u8) {
+        console::move_cursor(&mut self.stdout, console::Position { row: y, col: 0 });
+        console::set_sgr(
+            &mut self.stdout,
+            [console::SgrParam::Reset, console::SgrParam::FgGreen],
+        );
+        for x in 0..self.grid.width {
+            let _ = self
+                .stdout
+                .write_char(if self.grid.get(x, y) { '█' } else { ' ' });
+        }
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+    }
+
+    /// Draw one cell, highlighting it if it is under the cursor
+    fn draw_cell(&mut self, x: u8, y: u8) {
+        console::move_cursor(&mut self.stdout, console::Position { row: y, col: x });
+        let alive = self.grid.get(x, y);
+        if (x, y) == self.cursor {
+            console::set_sgr(
+                &mut self.stdout,
+                [console::SgrParam::FgYellow, console::SgrParam::BgBlue],
+            );
+            let _ = self.stdout.write_char(if alive { '█' } else { '+' });
+        } else {
+            console::set_sgr(&mut self.stdout, [console::SgrParam::FgGreen]);
+            let _ = self.stdout.write_char(if alive { '█' } else { ' ' });
+        }
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+    }
+
+    /// Draw the status line
+    fn draw_status(&mut self) {
+        console::set_sgr(
+            &mut self.stdout,
+            [console::SgrParam::Reset, console::SgrParam::Reverse],
+        );
+        console::move_cursor(
+            &mut self.stdout,
+            console::Position {
+                row: self.height - 1,
+                col: 0,
+            },
+        );
+        let _ = write!(
+            self.stdout,
+            " {} Gen {:<6} Pop {:<5} {:>4}ms {} | ? for help {:20}",
+            if self.running { "RUN " } else { "STOP" },
+            self.generation,
+            self.grid.population(),
+            self.interval_ms,
+            if self.grid.wrap { "Wrap" } else { "Edge" },
+            "",
+        );
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+    }
+}
+
+impl neotron_scene::Game for App {
+    fn start(&mut self) {
+        console::cursor_off(&mut self.stdout);
+        self.mode = Mode::Edit;
+        self.redraw();
+    }
+
+    fn tick_ms(&self) -> Option<u32> {
+        (self.running && self.mode == Mode::Edit).then_some(u32::from(self.interval_ms))
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) -> bool {
+        if let KeyEvent::Char(ch) = key {
+            self.seed = self.seed.wrapping_add(ch as u16);
+        }
+        match self.mode {
+            Mode::Edit => return self.edit_key(key),
+            Mode::Help => {
+                self.mode = Mode::Edit;
+                self.redraw();
+            }
+            Mode::Load => self.load_key(key),
+        }
+        true
+    }
+
+    fn tick(&mut self) -> bool {
+        self.step();
+        true
+    }
+
+    fn finish(&mut self) {
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        console::clear_screen(&mut self.stdout);
+        console::move_cursor(&mut self.stdout, console::Position::origin());
+        console::cursor_on(&mut self.stdout);
+    }
+}
+
+/// A grid of cells, each alive or dead
+struct Grid {
+    cells: [[bool; Self::MAX_WIDTH]; Self::MAX_HEIGHT],
+    width: u8,
+    height: u8,
+    /// Do the edges wrap around to the other side?
+    wrap: bool,
+}
+
+impl Grid {
+    /// The widest grid we can handle
+    const MAX_WIDTH: usize = 80;
+    /// The tallest grid we can handle
+    const MAX_HEIGHT: usize = 48;
+
+    /// Make an empty grid
+    const fn new(width: u8, height: u8) -> Grid {
+        Grid {
+            cells: [[false; Self::MAX_WIDTH]; Self::MAX_HEIGHT],
+            width,
+            height,
+            wrap: true,
+        }
+    }
+
+    /// Kill every cell
+    fn clear(&mut self) {
+        self.cells = [[false; Self::MAX_WIDTH]; Self::MAX_HEIGHT];
+    }
+
+    /// Fill the grid with random cells, about one in four alive
+    fn randomise(&mut self) {
+        for y in 0..self.height {
+            for x in 0..self.width {
+                self.set(x, y, neotron_sdk::rand() & 3 == 0);
+            }
+        }
+    }
+
+    /// Copy the cells from another grid
+    fn copy_from(&mut self, other: &Grid) {
+        self.cells = other.cells;
+        self.width = other.width;
+        self.height = other.height;
+        self.wrap = other.wrap;
+    }
+
+    /// Is this cell alive?
+    fn get(&self, x: u8, y: u8) -> bool {
+        self.cells[usize::from(y)][usize::from(x)]
+    }
+
+    /// Set a cell alive or dead
+    fn set(&mut self, x: u8, y: u8, alive: bool) {
+        self.cells[usize::from(y)][usize::from(x)] = alive;
+    }
+
+    /// Get the cells in a row
+    fn row(&self, y: u8) -> &[bool] {
+        &self.cells[usize::from(y)][0..usize::from(self.width)]
+    }
+
+    /// How many cells are alive?
+    fn population(&self) -> u32 {
+        let mut count = 0;
+        for y in 0..self.height {
+            count += self.row(y).iter().filter(|&&alive| alive).count() as u32;
+        }
+        count
+    }
+
+    /// Count the live neighbours of a cell in this grid
+    fn neighbours(&self, x: u8, y: u8) -> u8 {
+        let mut count = 0;
+        for dy in [-1i16, 0, 1] {
+            for dx in [-1i16, 0, 1] {
+                if dx == 0 && dy == 0 {
+                    continue;
+                }
+                let mut nx = i16::from(x) + dx;
+                let mut ny = i16::from(y) + dy;
+                let width = i16::from(self.width);
+                let height = i16::from(self.height);
+                if self.wrap {
+                    nx = (nx + width) % width;
+                    ny = (ny + height) % height;
+                } else if nx < 0 || ny < 0 || nx >= width || ny >= height {
+                    continue;
+                }
+                if self.get(nx as u8, ny as u8) {
+                    count += 1;
+                }
+            }
+        }
+        count
+    }
+
+    /// Apply the rules of Life (B3/S23) to the previous generation
+    fn next_generation(&mut self, previous: &Grid) {
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let alive = previous.get(x, y);
+                let n = previous.neighbours(x, y);
+                self.set(x, y, n == 3 || (alive && n == 2));
+            }
+        }
+    }
+}
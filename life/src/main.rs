@@ -0,0 +1,25 @@
+#![cfg_attr(target_os = "none", no_std)]
+#![cfg_attr(target_os = "none", no_main)]
+
+use core::{fmt::Write, ptr::addr_of_mut};
+
+#[cfg(not(target_os = "none"))]
+fn main() {
+    neotron_sdk::init();
+}
+
+static mut APP: life::App = life::App::new(80, 25);
+
+#[no_mangle]
+extern "C" fn neotron_main() -> i32 {
+    let app = unsafe { &mut *addr_of_mut!(APP) };
+    if let Some(filename) = neotron_sdk::arg(0) {
+        if let Err(e) = app.load_file(&filename) {
+            let mut stdout = neotron_sdk::stdout();
+            let _ = writeln!(stdout, "Error loading {:?}: {:?}", filename, e);
+            return 1;
+        }
+    }
+    app.play();
+    0
+}
@@ -0,0 +1,130 @@
+//! Loaders for Life pattern files.
+//!
+//! We understand two common formats:
+//!
+//! * Plaintext (`.cells`) - `!` starts a comment line, `.` is a dead cell and
+//!   `O` (or `*`) is a live cell.
+//! * Run Length Encoded (`.rle`) - `#` starts a comment line, then a header
+//!   line like `x = 3, y = 3, rule = B3/S23`, then runs of `b` (dead) and `o`
+//!   (alive) cells with an optional repeat count, `$` ending each row and `!`
+//!   ending the pattern.
+
+/// Things that can go wrong loading a pattern
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// The pattern has characters we don't understand
+    BadPattern,
+    /// There are no live cells in the pattern
+    Empty,
+}
+
+/// Parse a pattern in either format.
+///
+/// The function `set_cell` is called with the column and row of each live
+/// cell. Returns the width and height of the pattern.
+pub fn parse<F>(text: &str, set_cell: F) -> Result<(u16, u16), Error>
+where
+    F: FnMut(u16, u16),
+{
+    let is_rle = text
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| line.starts_with('x'))
+        .unwrap_or(false);
+    if is_rle {
+        parse_rle(text, set_cell)
+    } else {
+        parse_plaintext(text, set_cell)
+    }
+}
+
+/// Parse a plaintext (`.cells`) pattern
+fn parse_plaintext<F>(text: &str, mut set_cell: F) -> Result<(u16, u16), Error>
+where
+    F: FnMut(u16, u16),
+{
+    let mut width = 0;
+    let mut height = 0;
+    let mut live = false;
+    for line in text.lines().filter(|line| !line.starts_with('!')) {
+        for (col, ch) in line.trim_end().chars().enumerate() {
+            match ch {
+                '.' | ' ' => {}
+                'O' | 'o' | '*' => {
+                    set_cell(col as u16, height);
+                    live = true;
+                }
+                _ => return Err(Error::BadPattern),
+            }
+            width = width.max(col as u16 + 1);
+        }
+        height += 1;
+    }
+    if live {
+        Ok((width, height))
+    } else {
+        Err(Error::Empty)
+    }
+}
+
+/// Parse a Run Length Encoded (`.rle`) pattern
+fn parse_rle<F>(text: &str, mut set_cell: F) -> Result<(u16, u16), Error>
+where
+    F: FnMut(u16, u16),
+{
+    let mut row: u16 = 0;
+    let mut col: u16 = 0;
+    let mut width = 0;
+    let mut count: u16 = 0;
+    let mut live = false;
+    let mut seen_header = false;
+    'lines: for line in text.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if !seen_header {
+            // We don't need the header - we work out the size ourselves, and
+            // only support the standard rules.
+            seen_header = true;
+            continue;
+        }
+        for ch in line.chars() {
+            match ch {
+                '0'..='9' => {
+                    count = count
+                        .saturating_mul(10)
+                        .saturating_add(ch as u16 - '0' as u16);
+                    continue;
+                }
+                'b' | '.' => {
+                    col = col.saturating_add(count.max(1));
+                }
+                '$' => {
+                    row = row.saturating_add(count.max(1));
+                    col = 0;
+                }
+                '!' => {
+                    break 'lines;
+                }
+                'a'..='z' | 'A'..='Z' => {
+                    // Any other letter is a live cell
+                    for _ in 0..count.max(1) {
+                        set_cell(col, row);
+                        col = col.saturating_add(1);
+                    }
+                    live = true;
+                }
+                ' ' | '\t' => {}
+                _ => return Err(Error::BadPattern),
+            }
+            width = width.max(col);
+            count = 0;
+        }
+    }
+    if live {
+        Ok((width, row + 1))
+    } else {
+        Err(Error::Empty)
+    }
+}
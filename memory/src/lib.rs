@@ -0,0 +1,214 @@
+//! Game logic for the memory games - Simon and Pairs
+
+#![no_std]
+#![deny(missing_docs)]
+#![deny(unsafe_code)]
+
+use core::fmt::Write;
+
+use neotron_input::{KeyEvent, Keyboard};
+use neotron_sdk::console;
+use neotron_tui::Line;
+
+pub mod pairs;
+pub mod simon;
+pub mod speaker;
+
+/// Which game we are playing
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Game {
+    Simon,
+    Pairs,
+}
+
+impl Game {
+    /// The name of the game
+    fn name(self) -> &'static str {
+        match self {
+            Game::Simon => "SIMON",
+            Game::Pairs => "PAIRS",
+        }
+    }
+}
+
+/// Represents the memory games application
+///
+/// You pick a game from the menu, and can play as many games as you like.
+/// We remember your best scores until you quit.
+pub struct App {
+    stdout: neotron_sdk::File,
+    keyboard: Keyboard,
+    speaker: speaker::Speaker,
+    width: u8,
+    simon: simon::Simon,
+    pairs: pairs::Pairs,
+    /// Picks the Simon sequence and shuffles the Pairs cards
+    rng: neotron_rand::Rng,
+    /// The longest Simon sequence anyone has repeated
+    best_sequence: usize,
+    /// The fewest turns anyone has cleared the Pairs table in
+    best_turns: Option<u16>,
+}
+
+impl App {
+    /// Make a new memory games application.
+    ///
+    /// You can give the screen size in characters.
+    pub const fn new(width: u8, _height: u8) -> App {
+        App {
+            stdout: neotron_sdk::stdout(),
+            keyboard: Keyboard::new(),
+            speaker: speaker::Speaker::new(),
+            width,
+            simon: simon::Simon::new(),
+            pairs: pairs::Pairs::new(),
+            rng: neotron_rand::Rng::new(0),
+            best_sequence: 0,
+            best_turns: None,
+        }
+    }
+
+    /// Show the menu and play games until the user quits
+    pub fn play(&mut self) {
+        self.speaker.open();
+        console::cursor_off(&mut self.stdout);
+        self.rng = neotron_rand::Rng::from_rtc();
+        loop {
+            self.menu();
+            let key = self.keyboard.wait();
+            if let KeyEvent::Char(ch) = key {
+                self.rng.mix(u32::from(ch));
+            }
+            match key {
+                KeyEvent::Char('1') => self.play_game(Game::Simon),
+                KeyEvent::Char('2') => self.play_game(Game::Pairs),
+                KeyEvent::Char('s' | 'S') => self.speaker.enabled = !self.speaker.enabled,
+                KeyEvent::Char('q' | 'Q') => break,
+                _ => {}
+            }
+        }
+        self.speaker.close();
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        console::clear_screen(&mut self.stdout);
+        console::move_cursor(&mut self.stdout, console::Position::origin());
+        console::cursor_on(&mut self.stdout);
+    }
+
+    /// Draw the game menu
+    fn menu(&mut self) {
+        self.clear();
+        self.centre(4, "NEOTRON MEMORY GAMES", true);
+        self.centre(7, "1 - Simon (repeat the sequence) ", false);
+        self.centre(8, "2 - Pairs (find the matches)    ", false);
+        let sound = if !self.speaker.is_present() {
+            "No audio device"
+        } else if self.speaker.enabled {
+            "On"
+        } else {
+            "Off"
+        };
+        let mut line = Line::new();
+        let _ = write!(line, "S - Sound: {:<21}", sound);
+        self.centre(10, line.as_str(), false);
+        self.centre(12, "Q - Quit                        ", false);
+        let mut line = Line::new();
+        let _ = write!(line, "Simon - longest sequence: {:<6}", self.best_sequence);
+        self.centre(15, line.as_str(), false);
+        let mut line = Line::new();
+        let _ = match self.best_turns {
+            Some(turns) => write!(line, "Pairs - fewest turns:     {:<6}", turns),
+            None => write!(line, "Pairs - fewest turns:     {:<6}", "-"),
+        };
+        self.centre(16, line.as_str(), false);
+        self.centre(19, "In a game, Q goes back to this menu", false);
+    }
+
+    /// Play one game after another, until the user goes back to the menu
+    fn play_game(&mut self, game: Game) {
+        loop {
+            self.clear();
+            self.centre(0, game.name(), true);
+            let mut message = Line::new();
+            match game {
+                Game::Simon => {
+                    let Some(length) = self.simon.play(
+                        &mut self.keyboard,
+                        &mut self.stdout,
+                        &mut self.speaker,
+                        &mut self.rng,
+                        self.width,
+                    ) else {
+                        return;
+                    };
+                    let _ = if length == simon::MAX_LENGTH {
+                        write!(message, "You remembered all {} - you win!", length)
+                    } else if length > self.best_sequence {
+                        write!(message, "You remembered {} - a new best!", length)
+                    } else {
+                        write!(message, "You remembered {}.", length)
+                    };
+                    self.best_sequence = self.best_sequence.max(length);
+                }
+                Game::Pairs => {
+                    let Some(turns) = self.pairs.play(
+                        &mut self.keyboard,
+                        &mut self.stdout,
+                        &mut self.speaker,
+                        &mut self.rng,
+                        self.width,
+                    ) else {
+                        return;
+                    };
+                    let _ = if self.best_turns.is_none_or(|best| turns < best) {
+                        self.best_turns = Some(turns);
+                        write!(message, "All found in {} turns - a new best!", turns)
+                    } else {
+                        write!(message, "All found in {} turns.", turns)
+                    };
+                }
+            }
+            let _ = message.write_str(" N for a new game, or Q for the menu.");
+            show_status(&mut self.stdout, 23, message.as_str());
+            loop {
+                match self.keyboard.wait() {
+                    KeyEvent::Char('n' | 'N') => break,
+                    KeyEvent::Char('q' | 'Q') | KeyEvent::Escape => return,
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    /// Clear the screen
+    fn clear(&mut self) {
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        console::clear_screen(&mut self.stdout);
+    }
+
+    /// Write some text in the middle of a row
+    fn centre(&mut self, row: u8, text: &str, title: bool) {
+        let col = (usize::from(self.width).saturating_sub(text.chars().count())) / 2;
+        console::move_cursor(
+            &mut self.stdout,
+            console::Position {
+                row,
+                col: col as u8,
+            },
+        );
+        if title {
+            console::set_sgr(
+                &mut self.stdout,
+                [console::SgrParam::Bold, console::SgrParam::FgYellow],
+            );
+        }
+        let _ = self.stdout.write_str(text);
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+    }
+}
+
+/// Write a line of text in the middle of the screen, clearing whatever was
+/// there before
+fn show_status(stdout: &mut neotron_sdk::File, row: u8, text: &str) {
+    console::move_cursor(stdout, console::Position { row, col: 0 });
+    let _ = write!(stdout, "{:^80}", text);
+}
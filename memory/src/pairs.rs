@@ -0,0 +1,221 @@
+//! Pairs, or pelmanism.
+//!
+//! Twelve pairs of cards are dealt face down. Turn over two at a time: if
+//! they match they stay face up, and if not they go back. Clear the table in
+//! as few turns as you can.
+
+use core::fmt::Write;
+
+use neotron_input::{KeyEvent, Keyboard};
+use neotron_sdk::console;
+use neotron_tui::Line;
+
+use crate::{show_status, speaker::Speaker};
+
+/// How many cards across
+const COLUMNS: usize = 6;
+
+/// How many cards down
+const ROWS: usize = 4;
+
+/// How many cards there are
+const CARDS: usize = COLUMNS * ROWS;
+
+/// The screen row of the top of the first row of cards
+const TOP: u8 = 3;
+
+/// How far apart the cards are, across
+const CARD_PITCH: u8 = 9;
+
+/// How far apart the cards are, down
+const ROW_PITCH: u8 = 4;
+
+/// The row we show messages on
+const STATUS_ROW: u8 = TOP + ROW_PITCH * ROWS as u8 + 1;
+
+/// What is on the face of each pair of cards, and what colour it is
+const FACES: [(char, console::SgrParam); CARDS / 2] = [
+    ('♥', console::SgrParam::FgRed),
+    ('♦', console::SgrParam::FgRed),
+    ('♣', console::SgrParam::FgGreen),
+    ('♠', console::SgrParam::FgGreen),
+    ('☺', console::SgrParam::FgYellow),
+    ('☻', console::SgrParam::FgYellow),
+    ('♪', console::SgrParam::FgCyan),
+    ('♫', console::SgrParam::FgCyan),
+    ('▲', console::SgrParam::FgMagenta),
+    ('▼', console::SgrParam::FgMagenta),
+    ('■', console::SgrParam::FgWhite),
+    ('☼', console::SgrParam::FgWhite),
+];
+
+/// Which way up a card is
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum State {
+    /// Face down
+    Hidden,
+    /// Turned over this turn
+    Showing,
+    /// Part of a pair we've found
+    Matched,
+}
+
+/// A game of Pairs
+pub struct Pairs {
+    /// Which face each card has, as an index into `FACES`
+    faces: [u8; CARDS],
+    states: [State; CARDS],
+    /// Which card the cursor is on
+    cursor: usize,
+}
+
+impl Pairs {
+    /// Make a new game
+    pub const fn new() -> Pairs {
+        Pairs {
+            faces: [0; CARDS],
+            states: [State::Hidden; CARDS],
+            cursor: 0,
+        }
+    }
+
+    /// Play one game.
+    ///
+    /// Returns how many turns it took to find every pair, or `None` if the
+    /// player gave up part way through.
+    pub fn play(
+        &mut self,
+        keyboard: &mut Keyboard,
+        stdout: &mut neotron_sdk::File,
+        speaker: &mut Speaker,
+        rng: &mut neotron_rand::Rng,
+        width: u8,
+    ) -> Option<u16> {
+        self.deal(rng);
+        let left = (width - CARD_PITCH * COLUMNS as u8) / 2 + 1;
+        let mut turns: u16 = 0;
+        let mut found = 0;
+        while found < CARDS / 2 {
+            let first = self.pick(keyboard, stdout, left, turns)?;
+            let second = self.pick(keyboard, stdout, left, turns)?;
+            turns = turns.saturating_add(1);
+            if self.faces[first] == self.faces[second] {
+                self.states[first] = State::Matched;
+                self.states[second] = State::Matched;
+                found += 1;
+                self.draw(stdout, left, turns, false);
+                speaker.tone(660, 80);
+                speaker.tone(880, 120);
+            } else {
+                self.draw(stdout, left, turns, false);
+                show_status(stdout, STATUS_ROW, "No match. Press any key to carry on.");
+                speaker.tone(150, 200);
+                // Give them a while to remember the cards, unless they're
+                // in a hurry
+                for _ in 0..300 {
+                    if keyboard.read().is_some() {
+                        break;
+                    }
+                    neotron_sdk::delay(core::time::Duration::from_millis(10));
+                }
+                self.states[first] = State::Hidden;
+                self.states[second] = State::Hidden;
+            }
+        }
+        self.draw(stdout, left, turns, false);
+        Some(turns)
+    }
+
+    /// Shuffle the cards and turn them all face down
+    fn deal(&mut self, rng: &mut neotron_rand::Rng) {
+        for (idx, face) in self.faces.iter_mut().enumerate() {
+            *face = (idx / 2) as u8;
+        }
+        rng.shuffle(&mut self.faces);
+        self.states = [State::Hidden; CARDS];
+        self.cursor = 0;
+    }
+
+    /// Let the player move around and turn a card over.
+    ///
+    /// Returns which card it was, or `None` if they want to stop.
+    fn pick(
+        &mut self,
+        keyboard: &mut Keyboard,
+        stdout: &mut neotron_sdk::File,
+        left: u8,
+        turns: u16,
+    ) -> Option<usize> {
+        loop {
+            self.draw(stdout, left, turns, true);
+            let (col, row) = (self.cursor % COLUMNS, self.cursor / COLUMNS);
+            match keyboard.wait() {
+                KeyEvent::Left if col > 0 => self.cursor -= 1,
+                KeyEvent::Right if col < COLUMNS - 1 => self.cursor += 1,
+                KeyEvent::Up if row > 0 => self.cursor -= COLUMNS,
+                KeyEvent::Down if row < ROWS - 1 => self.cursor += COLUMNS,
+                KeyEvent::Char(' ') | KeyEvent::Enter
+                    if self.states[self.cursor] == State::Hidden =>
+                {
+                    self.states[self.cursor] = State::Showing;
+                    return Some(self.cursor);
+                }
+                KeyEvent::Char('q' | 'Q') | KeyEvent::Escape => return None,
+                _ => {}
+            }
+        }
+    }
+
+    /// Draw the cards, and how many turns it's taken so far
+    fn draw(&self, stdout: &mut neotron_sdk::File, left: u8, turns: u16, choosing: bool) {
+        for idx in 0..CARDS {
+            let col = left + (idx % COLUMNS) as u8 * CARD_PITCH;
+            let top = TOP + (idx / COLUMNS) as u8 * ROW_PITCH;
+            let border = if choosing && idx == self.cursor {
+                [console::SgrParam::Bold, console::SgrParam::FgYellow]
+            } else if self.states[idx] == State::Matched {
+                [console::SgrParam::Reset, console::SgrParam::FgGreen]
+            } else {
+                [console::SgrParam::Reset, console::SgrParam::FgWhite]
+            };
+            console::move_cursor(stdout, console::Position { row: top, col });
+            console::set_sgr(stdout, border);
+            let _ = stdout.write_str("┌─────┐");
+            console::move_cursor(stdout, console::Position { row: top + 1, col });
+            let _ = stdout.write_char('│');
+            if self.states[idx] == State::Hidden {
+                console::set_sgr(
+                    stdout,
+                    [console::SgrParam::Reset, console::SgrParam::FgBlue],
+                );
+                let _ = stdout.write_str("▒▒▒▒▒");
+            } else {
+                let (face, colour) = FACES[usize::from(self.faces[idx])];
+                console::set_sgr(stdout, [console::SgrParam::Bold, colour]);
+                let _ = write!(stdout, "  {}  ", face);
+            }
+            console::set_sgr(stdout, border);
+            let _ = stdout.write_char('│');
+            console::move_cursor(stdout, console::Position { row: top + 2, col });
+            let _ = stdout.write_str("└─────┘");
+        }
+        console::set_sgr(stdout, [console::SgrParam::Reset]);
+        let mut line = Line::new();
+        if choosing {
+            let _ = write!(
+                line,
+                "Turns: {}. Use the arrows, then Enter to turn a card over.",
+                turns
+            );
+        } else {
+            let _ = write!(line, "Turns: {}", turns);
+        }
+        show_status(stdout, STATUS_ROW, line.as_str());
+    }
+}
+
+impl Default for Pairs {
+    fn default() -> Self {
+        Pairs::new()
+    }
+}
@@ -0,0 +1,264 @@
+//! Simon, or repeat the sequence.
+//!
+//! The computer lights up the four pads in a sequence, each with its own
+//! tone, and you play the sequence back with the arrow keys. Every time you
+//! get it right, the sequence gets one step longer - and after a while, it
+//! gets faster too.
+
+use core::fmt::Write;
+
+use neotron_input::{KeyEvent, Keyboard};
+use neotron_sdk::console;
+use neotron_tui::Line;
+
+use crate::{show_status, speaker::Speaker};
+
+/// The longest sequence there is. Get this far and you've won.
+pub const MAX_LENGTH: usize = 31;
+
+/// The screen row of the top of the top pad
+const TOP: u8 = 3;
+
+/// How wide each pad is
+const PAD_WIDTH: u8 = 14;
+
+/// How tall each pad is
+const PAD_HEIGHT: u8 = 5;
+
+/// The row we show messages on
+const STATUS_ROW: u8 = TOP + PAD_HEIGHT * 3 + 2;
+
+/// How long you get to press each key, in milliseconds
+const TIMEOUT_MS: u32 = 3000;
+
+/// The pitch of the noise you get for a mistake, in Hz
+const BUZZ_PITCH: u32 = 42;
+
+/// One of the four coloured pads
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct Pad {
+    /// The key which presses it
+    key: KeyEvent,
+    /// What the key is called
+    label: &'static str,
+    colour: console::SgrParam,
+    /// The pitch of its tone, in Hz
+    pitch: u32,
+    /// Where it goes, in pads across and down from the top left
+    place: (u8, u8),
+}
+
+/// The pads, in a diamond, with the same tones as the original game
+const PADS: [Pad; 4] = [
+    Pad {
+        key: KeyEvent::Up,
+        label: "Up",
+        colour: console::SgrParam::FgGreen,
+        pitch: 392,
+        place: (1, 0),
+    },
+    Pad {
+        key: KeyEvent::Left,
+        label: "Left",
+        colour: console::SgrParam::FgRed,
+        pitch: 330,
+        place: (0, 1),
+    },
+    Pad {
+        key: KeyEvent::Right,
+        label: "Right",
+        colour: console::SgrParam::FgYellow,
+        pitch: 262,
+        place: (2, 1),
+    },
+    Pad {
+        key: KeyEvent::Down,
+        label: "Down",
+        colour: console::SgrParam::FgBlue,
+        pitch: 196,
+        place: (1, 2),
+    },
+];
+
+/// A game of Simon
+pub struct Simon {
+    /// Which pad lights up at each step
+    sequence: [u8; MAX_LENGTH],
+    len: usize,
+}
+
+impl Simon {
+    /// Make a new game
+    pub const fn new() -> Simon {
+        Simon {
+            sequence: [0; MAX_LENGTH],
+            len: 0,
+        }
+    }
+
+    /// Play one game.
+    ///
+    /// Returns how long a sequence you managed to repeat, or `None` if the
+    /// player gave up part way through.
+    pub fn play(
+        &mut self,
+        keyboard: &mut Keyboard,
+        stdout: &mut neotron_sdk::File,
+        speaker: &mut Speaker,
+        rng: &mut neotron_rand::Rng,
+        width: u8,
+    ) -> Option<usize> {
+        self.len = 0;
+        let left = (width - PAD_WIDTH * 3) / 2;
+        for pad in 0..PADS.len() {
+            draw_pad(stdout, left, pad, false);
+        }
+        show_status(stdout, STATUS_ROW, "Press any key to start");
+        if let KeyEvent::Char('q' | 'Q') | KeyEvent::Escape = keyboard.wait() {
+            return None;
+        }
+        loop {
+            self.sequence[self.len] = rng.index(PADS.len()) as u8;
+            self.len += 1;
+            let mut line = Line::new();
+            let _ = write!(line, "Watch... ({} to remember)", self.len);
+            show_status(stdout, STATUS_ROW, line.as_str());
+            speaker.rest(400);
+
+            // The original speeds up after the 5th, 9th and 13th steps
+            let step_ms = match self.len {
+                0..=5 => 420,
+                6..=9 => 320,
+                10..=13 => 250,
+                _ => 200,
+            };
+            for &pad in &self.sequence[0..self.len] {
+                flash(stdout, speaker, left, usize::from(pad), step_ms);
+                speaker.rest(step_ms / 2);
+            }
+
+            show_status(stdout, STATUS_ROW, "Your turn - use the arrow keys");
+            // Anything pressed while we were playing doesn't count
+            while keyboard.read().is_some() {}
+            for idx in 0..self.len {
+                let expected = usize::from(self.sequence[idx]);
+                let pressed = match wait_for_pad(keyboard) {
+                    Press::Pad(pad) => Some(pad),
+                    Press::TimedOut => None,
+                    Press::Quit => return None,
+                };
+                if let Some(pad) = pressed {
+                    flash(stdout, speaker, left, pad, 200);
+                }
+                if pressed != Some(expected) {
+                    let mut line = Line::new();
+                    let _ = if pressed.is_some() {
+                        write!(line, "Wrong! It was {}.", PADS[expected].label)
+                    } else {
+                        write!(line, "Too slow! It was {}.", PADS[expected].label)
+                    };
+                    show_status(stdout, STATUS_ROW, line.as_str());
+                    draw_pad(stdout, left, expected, true);
+                    speaker.tone(BUZZ_PITCH, 1000);
+                    draw_pad(stdout, left, expected, false);
+                    return Some(self.len - 1);
+                }
+            }
+            if self.len == MAX_LENGTH {
+                // Play a little fanfare, going round the pads
+                for _ in 0..3 {
+                    for pad in 0..PADS.len() {
+                        flash(stdout, speaker, left, pad, 80);
+                    }
+                }
+                return Some(self.len);
+            }
+            show_status(stdout, STATUS_ROW, "Well done!");
+            speaker.rest(800);
+        }
+    }
+}
+
+impl Default for Simon {
+    fn default() -> Self {
+        Simon::new()
+    }
+}
+
+/// What the player did
+enum Press {
+    /// Pressed one of the pads
+    Pad(usize),
+    /// Didn't press anything in time
+    TimedOut,
+    /// Wants to stop
+    Quit,
+}
+
+/// Wait for the player to press a pad, but not forever
+fn wait_for_pad(keyboard: &mut Keyboard) -> Press {
+    for _ in 0..TIMEOUT_MS / 10 {
+        match keyboard.read() {
+            Some(KeyEvent::Char('q' | 'Q') | KeyEvent::Escape) => return Press::Quit,
+            Some(key) => {
+                // Other keys don't count
+                if let Some(pad) = PADS.iter().position(|pad| pad.key == key) {
+                    return Press::Pad(pad);
+                }
+            }
+            None => neotron_sdk::delay(core::time::Duration::from_millis(10)),
+        }
+    }
+    Press::TimedOut
+}
+
+/// Light up a pad and play its tone
+fn flash(stdout: &mut neotron_sdk::File, speaker: &mut Speaker, left: u8, pad: usize, ms: u32) {
+    draw_pad(stdout, left, pad, true);
+    speaker.tone(PADS[pad].pitch, ms);
+    draw_pad(stdout, left, pad, false);
+}
+
+/// Draw a pad, lit up or not
+fn draw_pad(stdout: &mut neotron_sdk::File, left: u8, pad: usize, lit: bool) {
+    let pad = &PADS[pad];
+    let col = left + pad.place.0 * PAD_WIDTH;
+    let top = TOP + pad.place.1 * PAD_HEIGHT;
+    if lit {
+        console::set_sgr(stdout, [console::SgrParam::Bold, pad.colour]);
+    } else {
+        console::set_sgr(stdout, [console::SgrParam::Reset, pad.colour]);
+    }
+    let fill = if lit { '█' } else { '░' };
+    for row in 0..PAD_HEIGHT - 1 {
+        console::move_cursor(
+            stdout,
+            console::Position {
+                row: top + row,
+                col: col + 1,
+            },
+        );
+        if row == (PAD_HEIGHT - 1) / 2 {
+            // Put the name of the key in the middle
+            let width = usize::from(PAD_WIDTH - 2);
+            let side = (width - pad.label.len()) / 2;
+            for _ in 0..side {
+                let _ = stdout.write_char(fill);
+            }
+            console::set_sgr(stdout, [console::SgrParam::Reverse]);
+            let _ = stdout.write_str(pad.label);
+            console::set_sgr(stdout, [console::SgrParam::Reset, pad.colour]);
+            if lit {
+                console::set_sgr(stdout, [console::SgrParam::Bold]);
+            }
+            for _ in side + pad.label.len()..width {
+                let _ = stdout.write_char(fill);
+            }
+        } else {
+            for _ in 0..PAD_WIDTH - 2 {
+                let _ = stdout.write_char(fill);
+            }
+        }
+    }
+    console::set_sgr(stdout, [console::SgrParam::Reset]);
+}
@@ -0,0 +1,113 @@
+//! Tones, played through the audio device.
+//!
+//! Playing a tone writes that much audio and waits for it, so the games can
+//! use tones and rests to time what they show on screen. If there is no audio
+//! device, or the sound is turned off, we just wait instead.
+
+/// The sample rate we ask the audio device for
+const SAMPLE_RATE: u32 = 44100;
+
+/// How loud the tones are
+const AMPLITUDE: i16 = 6000;
+
+/// Makes tones
+pub struct Speaker {
+    dsp: Option<neotron_sdk::File>,
+    /// Whether the user wants to hear anything
+    pub enabled: bool,
+}
+
+impl Speaker {
+    /// Make a silent speaker. Call [`Speaker::open`] to start making noise.
+    pub const fn new() -> Speaker {
+        Speaker {
+            dsp: None,
+            enabled: true,
+        }
+    }
+
+    /// Try and open the audio device.
+    ///
+    /// If it isn't there, we just stay silent.
+    pub fn open(&mut self) {
+        let Ok(path) = neotron_sdk::path::Path::new("AUDIO:") else {
+            return;
+        };
+        let Ok(dsp) = neotron_sdk::File::open(path, neotron_sdk::Flags::empty()) else {
+            return;
+        };
+        // Set 16-bit stereo, 44.1 kHz
+        if dsp.ioctl(1, 3 << 60 | u64::from(SAMPLE_RATE)).is_err() {
+            return;
+        }
+        self.dsp = Some(dsp);
+    }
+
+    /// Close the audio device
+    pub fn close(&mut self) {
+        self.dsp = None;
+    }
+
+    /// Did we find an audio device?
+    pub fn is_present(&self) -> bool {
+        self.dsp.is_some()
+    }
+
+    /// Play a square wave at this pitch, in Hz, for this many milliseconds
+    pub fn tone(&mut self, pitch: u32, ms: u32) {
+        self.play(pitch, ms);
+    }
+
+    /// Be quiet for this many milliseconds
+    pub fn rest(&mut self, ms: u32) {
+        self.play(0, ms);
+    }
+
+    /// Write out some audio, or just wait if we can't
+    fn play(&mut self, pitch: u32, ms: u32) {
+        let dsp = match self.dsp.as_ref() {
+            Some(dsp) if self.enabled => dsp,
+            _ => {
+                neotron_sdk::delay(core::time::Duration::from_millis(u64::from(ms)));
+                return;
+            }
+        };
+        // A pitch of zero is silence
+        let half_wave = SAMPLE_RATE
+            .checked_div(pitch)
+            .map_or(0, |wave| (wave / 2).max(1));
+        let mut samples_left = ms * SAMPLE_RATE / 1000;
+        let mut position = 0;
+        let mut buffer = [0u8; 1024];
+        while samples_left > 0 {
+            let mut used = 0;
+            for chunk in buffer.chunks_exact_mut(4) {
+                if samples_left == 0 {
+                    break;
+                }
+                let sample = if half_wave == 0 {
+                    0
+                } else if (position / half_wave).is_multiple_of(2) {
+                    AMPLITUDE
+                } else {
+                    -AMPLITUDE
+                };
+                let sample = sample.to_le_bytes();
+                chunk[0] = sample[0];
+                chunk[1] = sample[1];
+                chunk[2] = sample[0];
+                chunk[3] = sample[1];
+                used += 4;
+                samples_left -= 1;
+                position += 1;
+            }
+            let _ = dsp.write(&buffer[0..used]);
+        }
+    }
+}
+
+impl Default for Speaker {
+    fn default() -> Self {
+        Speaker::new()
+    }
+}
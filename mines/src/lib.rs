@@ -0,0 +1,773 @@
+//! Game logic for Minesweeper
+
+#![no_std]
+#![deny(missing_docs)]
+#![deny(unsafe_code)]
+
+use core::fmt::Write;
+
+use neotron_input::{KeyEvent, Keyboard};
+use neotron_sdk::console;
+
+/// A board size and number of mines
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct Difficulty {
+    /// What we call this setting
+    name: &'static str,
+    /// Board width in cells
+    width: u8,
+    /// Board height in cells
+    height: u8,
+    /// How many mines to hide
+    mines: u16,
+}
+
+/// The standard board sizes. We keep best times for each of these.
+const PRESETS: [Difficulty; 3] = [
+    Difficulty {
+        name: "Beginner",
+        width: 9,
+        height: 9,
+        mines: 10,
+    },
+    Difficulty {
+        name: "Intermediate",
+        width: 16,
+        height: 16,
+        mines: 40,
+    },
+    Difficulty {
+        name: "Expert",
+        width: 30,
+        height: 16,
+        mines: 99,
+    },
+];
+
+/// Where we keep the best times
+const BEST_TIMES_FILE: &str = "MINES.DAT";
+
+/// How a game ended
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Outcome {
+    /// Cleared the board in this many seconds
+    Won(u32),
+    /// Stepped on a mine
+    Lost,
+}
+
+/// What we're showing, and waiting for a key for
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Mode {
+    /// The title screen
+    Title,
+    /// Asking for the size of a custom board
+    Custom(Custom),
+    /// A game in progress
+    Playing(Play),
+    /// A game has finished, and the message stays up until a key is pressed
+    Over,
+}
+
+/// The answers so far, when asking for a custom board
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct Custom {
+    /// The width, height and mines, as they are given
+    answers: [u16; 3],
+    /// How many of `answers` we have
+    count: usize,
+    /// The number being typed
+    value: u16,
+    /// How many digits of it there are
+    digits: u8,
+}
+
+impl Custom {
+    /// The questions we ask
+    const LABELS: [&'static str; 3] = ["Width", "Height", "Mines"];
+
+    /// Start asking
+    const fn new() -> Custom {
+        Custom {
+            answers: [0; 3],
+            count: 0,
+            value: 0,
+            digits: 0,
+        }
+    }
+}
+
+/// A game in progress
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct Play {
+    difficulty: Difficulty,
+    /// Which of the `PRESETS` this is, if any, for the best times
+    preset: Option<usize>,
+    /// Where the top left of the board is drawn
+    origin: console::Position,
+    cursor: (u8, u8),
+    elapsed_ms: u32,
+    /// The time on the screen, in seconds
+    shown_seconds: u32,
+}
+
+/// Represents the Minesweeper application
+///
+/// An application can play multiple games. It's a
+/// [`neotron_scene::Game`], so it can be run a key at a time.
+pub struct App {
+    width: u8,
+    height: u8,
+    stdout: neotron_sdk::File,
+    board: Board,
+    /// Best time in seconds for each of the `PRESETS`
+    best_times: [Option<u32>; PRESETS.len()],
+    mode: Mode,
+    /// Changes with every key pressed on the title screen, to mix into the
+    /// random numbers
+    seed: u16,
+}
+
+impl App {
+    /// How often the timer ticks
+    const TICK_MS: u32 = 20;
+
+    /// Make a new minesweeper application.
+    ///
+    /// You can give the screen size in characters.
+    pub const fn new(width: u8, height: u8) -> App {
+        App {
+            width,
+            height,
+            stdout: neotron_sdk::stdout(),
+            board: Board::new(),
+            best_times: [None; PRESETS.len()],
+            mode: Mode::Title,
+            seed: 0x1d53,
+        }
+    }
+
+    /// Play multiple games of minesweeper.
+    ///
+    /// Loops showing the title screen and playing games.
+    pub fn play(&mut self) {
+        neotron_scene::play(self, &mut Keyboard::new());
+    }
+
+    /// Show the title screen, with the best times
+    fn title_screen(&mut self) {
+        self.mode = Mode::Title;
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        console::clear_screen(&mut self.stdout);
+        let row = self.height / 2 - 5;
+        self.centre(row, "Neotron Minesweeper");
+        for (index, preset) in PRESETS.iter().enumerate() {
+            let row = row + 2 + index as u8;
+            console::move_cursor(&mut self.stdout, console::Position { row, col: 20 });
+            let _ = write!(
+                self.stdout,
+                "{} - {:<12} {:>2}x{:<2} {:>2} mines  ",
+                index + 1,
+                preset.name,
+                preset.width,
+                preset.height,
+                preset.mines
+            );
+            match self.best_times[index] {
+                Some(seconds) => {
+                    let _ = write!(self.stdout, "Best: {}s", seconds);
+                }
+                None => {
+                    let _ = write!(self.stdout, "Best: ---");
+                }
+            }
+        }
+        self.centre(row + 6, "C - Custom board   Q - Quit");
+        self.centre(
+            row + 8,
+            "In game: WASD/arrows to move, Space to reveal, F to flag, Q to give up",
+        );
+    }
+
+    /// Deal with a key on the title screen.
+    ///
+    /// Returns `false` if the user wants to quit.
+    fn title_key(&mut self, key: KeyEvent) -> bool {
+        if let KeyEvent::Char(ch) = key {
+            self.seed = self.seed.wrapping_add(ch as u16);
+        }
+        match key {
+            KeyEvent::Char(digit @ '1'..='3') => {
+                let index = digit as usize - '1' as usize;
+                self.start_game(PRESETS[index], Some(index));
+            }
+            KeyEvent::Char('c' | 'C') => {
+                let custom = Custom::new();
+                self.mode = Mode::Custom(custom);
+                self.prompt_number(&custom);
+            }
+            KeyEvent::Char('q' | 'Q') => return false,
+            _ => {}
+        }
+        true
+    }
+
+    /// The smallest and largest answers we take to the question being
+    /// asked for a custom board
+    fn custom_range(&self, custom: &Custom) -> (u16, u16) {
+        match custom.count {
+            0 => (
+                5,
+                u16::from((self.width / 2 - 2).min(Board::MAX_WIDTH as u8)),
+            ),
+            1 => (5, u16::from((self.height - 6).min(Board::MAX_HEIGHT as u8))),
+            _ => (1, custom.answers[0] * custom.answers[1] - 9),
+        }
+    }
+
+    /// Ask the next question for a custom board
+    fn prompt_number(&mut self, custom: &Custom) {
+        let row = self.height - 3;
+        let (min, max) = self.custom_range(custom);
+        console::move_cursor(&mut self.stdout, console::Position { row, col: 0 });
+        let _ = write!(self.stdout, "{:79}", "");
+        console::move_cursor(&mut self.stdout, console::Position { row, col: 20 });
+        let label = Custom::LABELS[custom.count];
+        let _ = write!(self.stdout, "{} ({}-{}): ", label, min, max);
+    }
+
+    /// Deal with a key while asking for a custom board size. Escape goes
+    /// back to the title screen.
+    fn custom_key(&mut self, mut custom: Custom, key: KeyEvent) {
+        match key {
+            KeyEvent::Char(key @ '0'..='9') if custom.digits < 4 => {
+                custom.value = custom.value * 10 + (key as u16 - '0' as u16);
+                custom.digits += 1;
+                let _ = self.stdout.write_char(key);
+            }
+            KeyEvent::Backspace if custom.digits > 0 => {
+                custom.value /= 10;
+                custom.digits -= 1;
+                let _ = self.stdout.write_str("\u{0008} \u{0008}");
+            }
+            KeyEvent::Enter => {
+                let (min, max) = self.custom_range(&custom);
+                if (min..=max).contains(&custom.value) {
+                    custom.answers[custom.count] = custom.value;
+                    custom.count += 1;
+                }
+                custom.value = 0;
+                custom.digits = 0;
+                if custom.count == custom.answers.len() {
+                    let [width, height, mines] = custom.answers;
+                    let difficulty = Difficulty {
+                        name: "Custom",
+                        width: width as u8,
+                        height: height as u8,
+                        mines,
+                    };
+                    self.start_game(difficulty, None);
+                    return;
+                }
+                // Ask again if the answer was no good
+                self.prompt_number(&custom);
+            }
+            KeyEvent::Escape => {
+                self.title_screen();
+                return;
+            }
+            _ => {}
+        }
+        self.mode = Mode::Custom(custom);
+    }
+
+    /// Start a game at the given difficulty
+    fn start_game(&mut self, difficulty: Difficulty, preset: Option<usize>) {
+        neotron_sdk::srand(self.seed);
+        self.board
+            .reset(difficulty.width, difficulty.height, difficulty.mines);
+        let play = Play {
+            difficulty,
+            preset,
+            origin: console::Position {
+                row: 3,
+                col: (self.width - difficulty.width * 2) / 2,
+            },
+            cursor: (difficulty.width / 2, difficulty.height / 2),
+            elapsed_ms: 0,
+            shown_seconds: 0,
+        };
+        self.mode = Mode::Playing(play);
+
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        console::clear_screen(&mut self.stdout);
+        self.draw_board(play.origin, play.cursor);
+        self.draw_counters(0);
+        self.draw_status("WASD/arrows to move, Space to reveal, F to flag, Q to give up");
+    }
+
+    /// Deal with a key during a game
+    fn game_key(&mut self, mut play: Play, key: KeyEvent) {
+        // Arrow keys do the same as WASD
+        let key = match key {
+            KeyEvent::Up => 'w',
+            KeyEvent::Down => 's',
+            KeyEvent::Right => 'd',
+            KeyEvent::Left => 'a',
+            KeyEvent::Enter => ' ',
+            KeyEvent::Char(ch) => ch.to_ascii_lowercase(),
+            _ => return,
+        };
+
+        let difficulty = play.difficulty;
+        let old_cursor = play.cursor;
+        let cursor = &mut play.cursor;
+        match key {
+            'w' if cursor.1 > 0 => cursor.1 -= 1,
+            's' if cursor.1 < difficulty.height - 1 => cursor.1 += 1,
+            'a' if cursor.0 > 0 => cursor.0 -= 1,
+            'd' if cursor.0 < difficulty.width - 1 => cursor.0 += 1,
+            'f' => {
+                self.board.toggle_flag(cursor.0, cursor.1);
+                self.draw_counters(play.shown_seconds);
+            }
+            ' ' => {
+                let hit_mine = self.board.reveal(cursor.0, cursor.1);
+                self.draw_board(play.origin, play.cursor);
+                if hit_mine {
+                    self.board.reveal_mines();
+                    self.draw_board(play.origin, play.cursor);
+                    self.game_over(&play, Outcome::Lost);
+                    return;
+                }
+                if self.board.is_cleared() {
+                    self.game_over(&play, Outcome::Won(play.elapsed_ms / 1000));
+                    return;
+                }
+            }
+            'q' => {
+                self.title_screen();
+                return;
+            }
+            _ => {}
+        }
+        if old_cursor != play.cursor {
+            self.draw_cell(play.origin, old_cursor, false);
+        }
+        self.draw_cell(play.origin, play.cursor, true);
+        self.mode = Mode::Playing(play);
+    }
+
+    /// Say how the game went, and keep the time if it's a new best
+    fn game_over(&mut self, play: &Play, outcome: Outcome) {
+        let message = match outcome {
+            Outcome::Won(seconds) => {
+                if let Some(index) = play.preset {
+                    if self.best_times[index].map(|t| seconds < t).unwrap_or(true) {
+                        self.best_times[index] = Some(seconds);
+                        self.save_best_times();
+                    }
+                }
+                "You cleared the board! Press any key."
+            }
+            Outcome::Lost => "BOOM! Press any key.",
+        };
+        self.draw_status(message);
+        self.mode = Mode::Over;
+    }
+
+    /// Draw every cell on the board
+    fn draw_board(&mut self, origin: console::Position, cursor: (u8, u8)) {
+        for y in 0..self.board.height {
+            for x in 0..self.board.width {
+                self.draw_cell(origin, (x, y), (x, y) == cursor);
+            }
+        }
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+    }
+
+    /// Draw one cell, highlighted if the cursor is on it
+    fn draw_cell(&mut self, origin: console::Position, (x, y): (u8, u8), highlight: bool) {
+        let cell = self.board.cell(x, y);
+        console::move_cursor(
+            &mut self.stdout,
+            console::Position {
+                row: origin.row + y,
+                col: origin.col + x * 2,
+            },
+        );
+        let (colour, glyph) = if cell.revealed {
+            if cell.mine {
+                (console::SgrParam::FgRed, '*')
+            } else {
+                match cell.adjacent {
+                    0 => (console::SgrParam::FgWhite, ' '),
+                    1 => (console::SgrParam::FgBlue, '1'),
+                    2 => (console::SgrParam::FgGreen, '2'),
+                    3 => (console::SgrParam::FgRed, '3'),
+                    4 => (console::SgrParam::FgMagenta, '4'),
+                    5 => (console::SgrParam::FgYellow, '5'),
+                    6 => (console::SgrParam::FgCyan, '6'),
+                    n => (console::SgrParam::FgWhite, char::from(b'0' + n)),
+                }
+            }
+        } else if cell.flagged {
+            (console::SgrParam::FgRed, 'F')
+        } else {
+            (console::SgrParam::FgWhite, '░')
+        };
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        if highlight {
+            console::set_sgr(
+                &mut self.stdout,
+                [console::SgrParam::Bold, colour, console::SgrParam::BgBlue],
+            );
+        } else {
+            console::set_sgr(&mut self.stdout, [console::SgrParam::Bold, colour]);
+        }
+        let _ = write!(self.stdout, "{} ", glyph);
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+    }
+
+    /// Draw the mine counter and the timer
+    fn draw_counters(&mut self, seconds: u32) {
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        console::move_cursor(&mut self.stdout, console::Position { row: 1, col: 2 });
+        let remaining = i32::from(self.board.mines) - i32::from(self.board.flags);
+        let _ = write!(self.stdout, "Mines: {:<4}  Time: {:<5}", remaining, seconds);
+    }
+
+    /// Show a message on the bottom line
+    fn draw_status(&mut self, message: &str) {
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        console::move_cursor(
+            &mut self.stdout,
+            console::Position {
+                row: self.height - 1,
+                col: 0,
+            },
+        );
+        let _ = write!(self.stdout, "{:<79}", message);
+    }
+
+    /// Read the best times from disk, if we have any
+    fn load_best_times(&mut self) {
+        let Ok(path) = neotron_sdk::path::Path::new(BEST_TIMES_FILE) else {
+            return;
+        };
+        let Ok(file) = neotron_sdk::File::open(path, neotron_sdk::Flags::empty()) else {
+            return;
+        };
+        let mut buffer = [0u8; 64];
+        let Ok(n) = file.read(&mut buffer) else {
+            return;
+        };
+        let Ok(text) = core::str::from_utf8(&buffer[0..n]) else {
+            return;
+        };
+        for (slot, line) in self.best_times.iter_mut().zip(text.lines()) {
+            *slot = line.trim().parse().ok();
+        }
+    }
+
+    /// Write the best times to disk.
+    ///
+    /// One line per preset, with `-` where there is no time yet.
+    fn save_best_times(&mut self) {
+        let Ok(path) = neotron_sdk::path::Path::new(BEST_TIMES_FILE) else {
+            return;
+        };
+        let Ok(mut file) = neotron_sdk::File::open(
+            path,
+            neotron_sdk::Flags::WRITE | neotron_sdk::Flags::CREATE | neotron_sdk::Flags::TRUNCATE,
+        ) else {
+            return;
+        };
+        for time in self.best_times.iter() {
+            let _ = match time {
+                Some(seconds) => writeln!(file, "{}", seconds),
+                None => writeln!(file, "-"),
+            };
+        }
+    }
+
+    /// Write a message centred on the given row
+    fn centre(&mut self, row: u8, message: &str) {
+        let col = (self.width - message.chars().count() as u8) / 2;
+        console::move_cursor(&mut self.stdout, console::Position { row, col });
+        let _ = self.stdout.write_str(message);
+    }
+}
+
+impl neotron_scene::Game for App {
+    fn start(&mut self) {
+        console::cursor_off(&mut self.stdout);
+        self.load_best_times();
+        self.title_screen();
+    }
+
+    fn tick_ms(&self) -> Option<u32> {
+        // The clock starts with the first cell revealed
+        match self.mode {
+            Mode::Playing(_) if self.board.started => Some(Self::TICK_MS),
+            _ => None,
+        }
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) -> bool {
+        match self.mode {
+            Mode::Title => return self.title_key(key),
+            Mode::Custom(custom) => self.custom_key(custom, key),
+            Mode::Playing(play) => self.game_key(play, key),
+            Mode::Over => self.title_screen(),
+        }
+        true
+    }
+
+    fn tick(&mut self) -> bool {
+        if let Mode::Playing(mut play) = self.mode {
+            play.elapsed_ms += Self::TICK_MS;
+            if play.elapsed_ms / 1000 != play.shown_seconds {
+                play.shown_seconds = play.elapsed_ms / 1000;
+                self.draw_counters(play.shown_seconds);
+            }
+            self.mode = Mode::Playing(play);
+        }
+        true
+    }
+
+    fn finish(&mut self) {
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        console::clear_screen(&mut self.stdout);
+        console::move_cursor(&mut self.stdout, console::Position::origin());
+        console::cursor_on(&mut self.stdout);
+    }
+}
+
+/// One square on the board
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct Cell {
+    /// Is there a mine here?
+    mine: bool,
+    /// Has the player uncovered this cell?
+    revealed: bool,
+    /// Has the player marked this cell as a mine?
+    flagged: bool,
+    /// How many of the neighbouring cells have mines
+    adjacent: u8,
+}
+
+impl Cell {
+    /// An empty, covered cell
+    const fn new() -> Cell {
+        Cell {
+            mine: false,
+            revealed: false,
+            flagged: false,
+            adjacent: 0,
+        }
+    }
+}
+
+/// The minefield
+struct Board {
+    cells: [[Cell; Self::MAX_WIDTH]; Self::MAX_HEIGHT],
+    width: u8,
+    height: u8,
+    mines: u16,
+    flags: u16,
+    /// How many safe cells are still covered
+    covered_safe: u16,
+    /// Mines are only placed on the first reveal, so you can't lose on the
+    /// first go.
+    started: bool,
+    /// Cells waiting to be uncovered during a flood fill
+    stack: [(u8, u8); Self::MAX_WIDTH * Self::MAX_HEIGHT],
+}
+
+impl Board {
+    /// The widest board we can handle
+    const MAX_WIDTH: usize = 36;
+    /// The tallest board we can handle
+    const MAX_HEIGHT: usize = 20;
+
+    /// Make an empty board
+    const fn new() -> Board {
+        Board {
+            cells: [[Cell::new(); Self::MAX_WIDTH]; Self::MAX_HEIGHT],
+            width: 0,
+            height: 0,
+            mines: 0,
+            flags: 0,
+            covered_safe: 0,
+            started: false,
+            stack: [(0, 0); Self::MAX_WIDTH * Self::MAX_HEIGHT],
+        }
+    }
+
+    /// Clear the board ready for a new game
+    fn reset(&mut self, width: u8, height: u8, mines: u16) {
+        for row in self.cells.iter_mut() {
+            for cell in row.iter_mut() {
+                *cell = Cell::new();
+            }
+        }
+        self.width = width;
+        self.height = height;
+        self.mines = mines;
+        self.flags = 0;
+        self.covered_safe = u16::from(width) * u16::from(height) - mines;
+        self.started = false;
+    }
+
+    /// Get a cell
+    fn cell(&self, x: u8, y: u8) -> Cell {
+        self.cells[usize::from(y)][usize::from(x)]
+    }
+
+    /// Get a cell, so you can change it
+    fn cell_mut(&mut self, x: u8, y: u8) -> &mut Cell {
+        &mut self.cells[usize::from(y)][usize::from(x)]
+    }
+
+    /// Call a function for each cell next to the given cell
+    fn for_each_neighbour<F>(&self, x: u8, y: u8, mut f: F)
+    where
+        F: FnMut(u8, u8),
+    {
+        for ny in y.saturating_sub(1)..=(y + 1).min(self.height - 1) {
+            for nx in x.saturating_sub(1)..=(x + 1).min(self.width - 1) {
+                if (nx, ny) != (x, y) {
+                    f(nx, ny);
+                }
+            }
+        }
+    }
+
+    /// Hide the mines, keeping the area around the first click clear
+    fn place_mines(&mut self, safe_x: u8, safe_y: u8) {
+        let mut placed = 0;
+        while placed < self.mines {
+            let x = (neotron_sdk::rand() % u16::from(self.width)) as u8;
+            let y = (neotron_sdk::rand() % u16::from(self.height)) as u8;
+            if self.cell(x, y).mine || (x.abs_diff(safe_x) <= 1 && y.abs_diff(safe_y) <= 1) {
+                continue;
+            }
+            self.cell_mut(x, y).mine = true;
+            placed += 1;
+        }
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let mut count = 0;
+                self.for_each_neighbour(x, y, |nx, ny| {
+                    if self.cells[usize::from(ny)][usize::from(nx)].mine {
+                        count += 1;
+                    }
+                });
+                self.cell_mut(x, y).adjacent = count;
+            }
+        }
+        self.started = true;
+    }
+
+    /// Mark or unmark a covered cell as a mine
+    fn toggle_flag(&mut self, x: u8, y: u8) {
+        let cell = self.cell_mut(x, y);
+        if cell.revealed {
+            return;
+        }
+        cell.flagged = !cell.flagged;
+        if cell.flagged {
+            self.flags += 1;
+        } else {
+            self.flags -= 1;
+        }
+    }
+
+    /// Uncover a cell.
+    ///
+    /// Empty areas are cleared with a flood fill. Revealing an uncovered number
+    /// with the right number of flags around it uncovers all its neighbours.
+    /// Returns `true` if a mine was uncovered.
+    fn reveal(&mut self, x: u8, y: u8) -> bool {
+        if !self.started {
+            self.place_mines(x, y);
+        }
+        let cell = self.cell(x, y);
+        if cell.flagged {
+            return false;
+        }
+        let mut stack_len = 0;
+        let mut hit_mine = false;
+        if cell.revealed {
+            // Chord - if they've flagged enough, open up the rest
+            let mut flags = 0;
+            self.for_each_neighbour(x, y, |nx, ny| {
+                if self.cells[usize::from(ny)][usize::from(nx)].flagged {
+                    flags += 1;
+                }
+            });
+            if cell.adjacent == 0 || flags != cell.adjacent {
+                return false;
+            }
+            for ny in y.saturating_sub(1)..=(y + 1).min(self.height - 1) {
+                for nx in x.saturating_sub(1)..=(x + 1).min(self.width - 1) {
+                    hit_mine |= self.uncover(nx, ny, &mut stack_len);
+                }
+            }
+        } else {
+            hit_mine = self.uncover(x, y, &mut stack_len);
+        }
+
+        // Flood fill out from any empty cells we found
+        while stack_len > 0 {
+            stack_len -= 1;
+            let (x, y) = self.stack[stack_len];
+            for ny in y.saturating_sub(1)..=(y + 1).min(self.height - 1) {
+                for nx in x.saturating_sub(1)..=(x + 1).min(self.width - 1) {
+                    self.uncover(nx, ny, &mut stack_len);
+                }
+            }
+        }
+        hit_mine
+    }
+
+    /// Uncover a single cell.
+    ///
+    /// If it has no neighbouring mines, it goes on the flood fill stack. As
+    /// cells are uncovered before they are stacked, no cell is stacked twice.
+    /// Returns `true` if it was a mine.
+    fn uncover(&mut self, x: u8, y: u8, stack_len: &mut usize) -> bool {
+        let cell = self.cell_mut(x, y);
+        if cell.revealed || cell.flagged {
+            return false;
+        }
+        cell.revealed = true;
+        if cell.mine {
+            return true;
+        }
+        let empty = cell.adjacent == 0;
+        self.covered_safe -= 1;
+        if empty {
+            self.stack[*stack_len] = (x, y);
+            *stack_len += 1;
+        }
+        false
+    }
+
+    /// Uncover every mine, for when the game is lost
+    fn reveal_mines(&mut self) {
+        for row in self.cells.iter_mut() {
+            for cell in row.iter_mut() {
+                if cell.mine {
+                    cell.revealed = true;
+                }
+            }
+        }
+    }
+
+    /// Has every safe cell been uncovered?
+    fn is_cleared(&self) -> bool {
+        self.covered_safe == 0
+    }
+}
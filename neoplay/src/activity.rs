@@ -0,0 +1,93 @@
+//! A one-line display of which channels are playing notes.
+//!
+//! Each channel gets one character, which lights up when a note starts and
+//! fades over the next few rows, like `[#+. ]`. The line is redrawn in
+//! place with a carriage return, and only when it changes, so it costs a
+//! handful of bytes a row. That suits a serial console, or any other
+//! terminal too slow or too plain for the pattern data.
+
+use core::fmt::Write;
+
+use crate::sync;
+
+/// What we draw a channel with, from quiet to a note that's just started.
+/// These are plain ASCII so they work on any terminal.
+const LEVELS: [char; 4] = [' ', '.', '+', '#'];
+
+/// The level a channel jumps to when a note starts
+const FULL: u8 = LEVELS.len() as u8 - 1;
+
+/// Lights for `N` channels
+#[derive(Debug, Copy, Clone)]
+pub struct Activity<const N: usize> {
+    /// How lit each channel is
+    levels: [u8; N],
+    /// What the line on the screen shows, if we're part way through one
+    drawn: Option<[u8; N]>,
+}
+
+impl<const N: usize> Activity<N> {
+    /// Make a display with every channel dark
+    pub const fn new() -> Activity<N> {
+        Activity {
+            levels: [0; N],
+            drawn: None,
+        }
+    }
+
+    /// Light up a channel
+    pub fn trigger(&mut self, channel: usize) {
+        if let Some(level) = self.levels.get_mut(channel) {
+            *level = FULL;
+        }
+    }
+
+    /// Fade every channel a step
+    pub fn fade(&mut self) {
+        for level in self.levels.iter_mut() {
+            *level = level.saturating_sub(1);
+        }
+    }
+
+    /// Draw the line, like `[#+. ]`, if it's changed since we last drew it
+    pub fn write<W: Write>(&mut self, out: &mut W) -> core::fmt::Result {
+        if self.drawn == Some(self.levels) {
+            return Ok(());
+        }
+        out.write_str("\r[")?;
+        for level in self.levels.iter() {
+            out.write_char(LEVELS[usize::from(*level)])?;
+        }
+        out.write_char(']')?;
+        self.drawn = Some(self.levels);
+        Ok(())
+    }
+
+    /// Move off the line, so other text doesn't land on top of it. The
+    /// next [`Activity::write`] starts a fresh one.
+    pub fn end_line<W: Write>(&mut self, out: &mut W) -> core::fmt::Result {
+        if self.drawn.take().is_some() {
+            out.write_char('\n')?;
+        }
+        Ok(())
+    }
+}
+
+impl<const N: usize> Default for Activity<N> {
+    fn default() -> Self {
+        Activity::new()
+    }
+}
+
+/// Each row fades the lights a step, then lights the channels with new
+/// notes
+impl<const N: usize> sync::Hooks for Activity<N> {
+    fn row(&mut self, row: &sync::Row) {
+        self.fade();
+        for (channel, trigger) in row.triggers.iter().enumerate() {
+            if trigger.is_some() {
+                self.trigger(channel);
+            }
+        }
+    }
+}
@@ -0,0 +1,87 @@
+//! Writes out a whole module as text.
+//!
+//! This is handy for archiving modules, or working out why one sounds
+//! wrong, without having to listen to it.
+
+use core::fmt::Write;
+
+use crate::player;
+
+/// Why we couldn't dump a module
+#[derive(Debug)]
+pub enum Error {
+    /// The module didn't make sense
+    Module(neotracker::Error),
+    /// We couldn't write the text out
+    Write,
+}
+
+impl From<core::fmt::Error> for Error {
+    fn from(_e: core::fmt::Error) -> Error {
+        Error::Write
+    }
+}
+
+/// Write out the samples, the order list and every pattern in the song.
+///
+/// Each pattern is written once, however many times the song plays it.
+pub fn dump<W: Write>(data: &[u8], out: &mut W) -> Result<(), Error> {
+    let modfile = neotracker::ProTrackerModule::new(data).map_err(Error::Module)?;
+
+    writeln!(out, "Samples:")?;
+    for sample_no in 1..=31 {
+        let Some(sample) = modfile.sample(sample_no) else {
+            continue;
+        };
+        if sample.sample_length_bytes() == 0 {
+            continue;
+        }
+        write!(
+            out,
+            "{:02}: {:6} bytes, volume {:02}",
+            sample_no,
+            sample.sample_length_bytes(),
+            sample.volume()
+        )?;
+        if sample.loops() {
+            write!(
+                out,
+                ", loops from {} for {} bytes",
+                sample.repeat_point_bytes(),
+                sample.repeat_length_bytes()
+            )?;
+        }
+        writeln!(out)?;
+    }
+
+    writeln!(out)?;
+    write!(out, "Order:")?;
+    let mut last_pattern = 0;
+    let mut position = 0;
+    while let Some(pattern_idx) = modfile.song_position(position) {
+        write!(out, " {:02}", pattern_idx)?;
+        last_pattern = last_pattern.max(pattern_idx);
+        position += 1;
+    }
+    writeln!(out)?;
+
+    for pattern_idx in 0..=last_pattern {
+        let Some(pattern) = modfile.pattern(pattern_idx) else {
+            continue;
+        };
+        writeln!(out)?;
+        writeln!(out, "Pattern {:02}:", pattern_idx)?;
+        let mut line_no = 0;
+        while let Some(line) = pattern.line(line_no) {
+            write!(out, "{:02}: ", line_no)?;
+            for note in line.channel.iter() {
+                player::write_note(out, note)?;
+                out.write_char('|')?;
+            }
+            writeln!(out)?;
+            line_no += 1;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,140 @@
+//! Writes each sample in a module out as a WAV file.
+//!
+//! Musicians can then load them into the tracker, or anything else that
+//! reads WAV files. `0:/MUSIC/AIRWOLF.MOD` gives `0:/MUSIC/AIRWOL01.WAV`,
+//! `0:/MUSIC/AIRWOL02.WAV` and so on, numbered like the samples are in the
+//! module. Empty samples are skipped.
+//!
+//! The files are 8-bit mono, at the rate that plays the sample back at the
+//! pitch of a C-3 on an Amiga.
+
+use core::fmt::Write;
+
+/// The sample rate we put in the files. An Amiga plays a C-3 (period 428)
+/// at this rate.
+pub const SAMPLE_RATE: u32 = 8287;
+
+/// How much of the module's name goes in each file's name, leaving room
+/// for the sample number in an 8.3 name
+const STEM_LEN: usize = 6;
+
+/// How long a WAV header is
+const HEADER_LEN: usize = 44;
+
+/// Why we couldn't extract the samples
+#[derive(Debug)]
+pub enum Error {
+    /// The module didn't make sense
+    Module(neotracker::Error),
+    /// We couldn't write a sample file
+    Io(neotron_sdk::Error),
+    /// We couldn't write the text out
+    Write,
+}
+
+impl From<core::fmt::Error> for Error {
+    fn from(_e: core::fmt::Error) -> Error {
+        Error::Write
+    }
+}
+
+impl From<neotron_sdk::Error> for Error {
+    fn from(e: neotron_sdk::Error) -> Error {
+        Error::Io(e)
+    }
+}
+
+/// Write each sample in the module at `module_path` out as a WAV file next
+/// to it, and say what we wrote on `out`.
+///
+/// Returns how many files we wrote.
+pub fn extract<W: Write>(data: &[u8], module_path: &str, out: &mut W) -> Result<usize, Error> {
+    let modfile = neotracker::ProTrackerModule::new(data).map_err(Error::Module)?;
+    let mut count = 0;
+    for sample_no in 1..=31 {
+        let Some(sample) = modfile.sample(sample_no) else {
+            continue;
+        };
+        let bytes = sample.raw_sample_bytes();
+        let bytes = &bytes[0..sample.sample_length_bytes().min(bytes.len())];
+        if bytes.is_empty() {
+            continue;
+        }
+        let path = wav_file(module_path, sample_no)?;
+        write_wav(path.as_str(), bytes)?;
+        writeln!(
+            out,
+            "{:02}: {} ({} bytes)",
+            sample_no,
+            path.as_str(),
+            bytes.len()
+        )?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+/// Work out the name of the file for one sample
+fn wav_file(module_path: &str, sample_no: u8) -> Result<neotron_fs::PathBuf, neotron_sdk::Error> {
+    let (directory, name) = neotron_fs::split(module_path);
+    let stem = match name.rsplit_once('.') {
+        Some((stem, _)) => stem,
+        None => name,
+    };
+    // Names are ASCII, but don't split a character if it isn't
+    let mut end = stem.len().min(STEM_LEN);
+    while !stem.is_char_boundary(end) {
+        end -= 1;
+    }
+    let mut path = neotron_fs::PathBuf::from_path(directory)?;
+    path.push_str(&stem[0..end])?;
+    let digits = [b'0' + sample_no / 10, b'0' + sample_no % 10];
+    path.push_str(core::str::from_utf8(&digits).unwrap_or("00"))?;
+    path.push_str(".WAV")?;
+    Ok(path)
+}
+
+/// The header for an 8-bit mono WAV file with this many bytes of samples
+fn wav_header(len: u32) -> [u8; HEADER_LEN] {
+    // The RIFF chunk has to be an even length, so odd data gets a pad byte
+    let riff_len = 36 + len + (len & 1);
+    let mut header = [0u8; HEADER_LEN];
+    header[0..4].copy_from_slice(b"RIFF");
+    header[4..8].copy_from_slice(&riff_len.to_le_bytes());
+    header[8..12].copy_from_slice(b"WAVE");
+    header[12..16].copy_from_slice(b"fmt ");
+    header[16..20].copy_from_slice(&16u32.to_le_bytes());
+    // PCM, one channel
+    header[20..22].copy_from_slice(&1u16.to_le_bytes());
+    header[22..24].copy_from_slice(&1u16.to_le_bytes());
+    header[24..28].copy_from_slice(&SAMPLE_RATE.to_le_bytes());
+    // One byte a frame, so bytes per second is the sample rate
+    header[28..32].copy_from_slice(&SAMPLE_RATE.to_le_bytes());
+    header[32..34].copy_from_slice(&1u16.to_le_bytes());
+    header[34..36].copy_from_slice(&8u16.to_le_bytes());
+    header[36..40].copy_from_slice(b"data");
+    header[40..44].copy_from_slice(&len.to_le_bytes());
+    header
+}
+
+/// Write one sample out. Amiga samples are signed, but 8-bit WAV files are
+/// unsigned.
+fn write_wav(path: &str, samples: &[u8]) -> Result<(), neotron_sdk::Error> {
+    let path = neotron_sdk::path::Path::new(path)?;
+    let file = neotron_sdk::File::open(
+        path,
+        neotron_sdk::Flags::WRITE | neotron_sdk::Flags::CREATE | neotron_sdk::Flags::TRUNCATE,
+    )?;
+    file.write(&wav_header(samples.len() as u32))?;
+    let mut buffer = [0u8; 256];
+    for chunk in samples.chunks(buffer.len()) {
+        for (out, sample) in buffer.iter_mut().zip(chunk) {
+            *out = sample ^ 0x80;
+        }
+        file.write(&buffer[0..chunk.len()])?;
+    }
+    if !samples.len().is_multiple_of(2) {
+        file.write(&[0])?;
+    }
+    Ok(())
+}
@@ -0,0 +1,17 @@
+//! A 4-channel ProTracker MOD player.
+//!
+//! The player lives in a library so that other applications, like the
+//! tracker editor, can play modules too.
+
+#![no_std]
+
+pub mod activity;
+pub mod dump;
+pub mod extract;
+pub mod player;
+pub mod playlist;
+pub mod sleep;
+pub mod status;
+pub mod sync;
+pub mod tags;
+pub mod vu;
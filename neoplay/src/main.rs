@@ -3,86 +3,643 @@
 
 use core::{fmt::Write, ptr::addr_of_mut};
 
-const FILE_BUFFER_LEN: usize = 192 * 1024;
+use neoplay::{activity, dump, extract, player, playlist, sleep, status, tags, vu};
+use neotron_args::{Item, Opt};
+
+/// How many KiB of module we can load. Set `NEOPLAY_BUFFER_KIB` when you
+/// build to change it.
+const FILE_BUFFER_KIB: usize = match option_env!("NEOPLAY_BUFFER_KIB") {
+    Some(text) => match usize::from_str_radix(text, 10) {
+        Ok(kib) if kib > 0 => kib,
+        _ => panic!("NEOPLAY_BUFFER_KIB should be a number of KiB"),
+    },
+    None => 192,
+};
+
+const FILE_BUFFER_LEN: usize = FILE_BUFFER_KIB * 1024;
 static mut FILE_BUFFER: [u8; FILE_BUFFER_LEN] = [0u8; FILE_BUFFER_LEN];
 
-mod player;
+/// Where we keep the settings
+const CONFIG_FILE: &str = "NEOPLAY.CFG";
+
+/// The sample rate we ask for, unless the settings say otherwise
+const DEFAULT_SAMPLE_RATE: u32 = 44100;
+
+/// How many buffers of silence we play first, unless the settings say
+/// otherwise
+const DEFAULT_PREBUFFER: u32 = 11;
+
+/// How many buffers of silence we play when we stop in a hurry, to be sure
+/// the device has nothing of the song left to repeat
+const SILENCE_BUFFERS: u32 = 16;
+
+/// Where a 4-channel module keeps its tag
+const TAG_OFFSET: usize = 1080;
+
+/// How many characters fit across the screen
+const SCREEN_WIDTH: u8 = 80;
+
+/// How many rows the screen has. The pattern data has a status line along
+/// the bottom one.
+const SCREEN_HEIGHT: u8 = 25;
+
+/// How many rows the limiter has to leave the music alone before we say
+/// it has stopped, so the status doesn't flicker on and off
+const LIMIT_HOLD_ROWS: u32 = 64;
+
+/// What we show while a module plays
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Display {
+    /// Every line of the pattern
+    Patterns,
+    /// Level meters, a line at a time
+    Meters,
+    /// A single line with a light for each channel
+    Channels,
+}
+
+impl Display {
+    /// The next one, going round in a loop
+    fn next(self) -> Display {
+        match self {
+            Display::Patterns => Display::Meters,
+            Display::Meters => Display::Channels,
+            Display::Channels => Display::Patterns,
+        }
+    }
+}
+
+/// The options we understand
+static SPEC: neotron_args::Spec = neotron_args::Spec {
+    name: "neoplay",
+    usage: "[options] [<file.mod>...]",
+    about: "Plays 4-channel ProTracker modules, one after the other. With no \
+            modules given, reads a list of them from stdin, one per line.",
+    options: &[
+        Opt::value(
+            'd',
+            "dump",
+            "file",
+            "Write the samples and patterns out as text, instead of playing",
+        ),
+        Opt::flag(
+            'x',
+            "extract",
+            "Write each sample out as a WAV file next to its module, instead of playing",
+        ),
+        Opt::value(
+            'l',
+            "limit",
+            "dB",
+            "Keep the sound this many dB under full volume, to save your ears",
+        ),
+        Opt::value(
+            'r',
+            "rating",
+            "stars",
+            "Only play modules rated at least this, from 1 to 5",
+        ),
+        Opt::flag('b', "best-first", "Play the best rated modules first"),
+        Opt::flag(
+            'q',
+            "quiet",
+            "Show a light for each channel instead of the pattern data",
+        ),
+        Opt::value(
+            's',
+            "sleep",
+            "minutes",
+            "Fade out and stop after this many minutes",
+        ),
+    ],
+};
 
 #[cfg(not(target_os = "none"))]
 fn main() {
-    neotron_sdk::init();
+    neotron_host::setup();
+    neotron_host::on_panic(emergency_stop);
+    neotron_host::os::init();
 }
 
 #[no_mangle]
 extern "C" fn neotron_main() -> i32 {
-    if let Err(e) = real_main() {
-        let mut stdout = neotron_sdk::stdout();
-        let _ = writeln!(stdout, "Error: {:?}", e);
-        1
-    } else {
-        0
+    let result = real_main();
+    if result.is_err() {
+        emergency_stop();
     }
+    neotron_error::exit_code_text(result)
 }
 
 fn real_main() -> Result<(), neotron_sdk::Error> {
     let mut stdout = neotron_sdk::stdout();
     let stdin = neotron_sdk::stdin();
-    let Some(filename) = neotron_sdk::arg(0) else {
-        return Err(neotron_sdk::Error::InvalidArg);
-    };
-    let _ = writeln!(stdout, "Loading {:?}...", filename);
-    let path = neotron_sdk::path::Path::new(&filename)?;
-    let f = neotron_sdk::File::open(path, neotron_sdk::Flags::empty())?;
-    let file_buffer = unsafe {
-        let file_buffer = &mut *addr_of_mut!(FILE_BUFFER);
-        let n = f.read(file_buffer)?;
-        &file_buffer[0..n]
+    let mut playlist = playlist::Playlist::new();
+    let mut dump_file = None;
+    let mut extract = false;
+    let mut limit_db = None;
+    let mut min_rating = 0;
+    let mut best_first = false;
+    let mut sleep_minutes = None;
+    let mut display = Display::Patterns;
+    let mut args = neotron_args::Parser::new(&SPEC);
+    loop {
+        let item = match args.next_item() {
+            Ok(Some(item)) => item,
+            Ok(None) => break,
+            Err(neotron_args::Error::Help) => return Ok(()),
+            Err(e) => return Err(e.into()),
+        };
+        match item {
+            Item::Value("dump", value) => dump_file = Some(value),
+            Item::Flag("extract") => extract = true,
+            Item::Value("limit", value) => {
+                let db: u8 = args.parse("limit", &value)?;
+                if db > neotron_audio::MAX_CEILING_DB {
+                    return Err(args.usage_error("the limit goes up to 24 dB").into());
+                }
+                limit_db = Some(db);
+            }
+            Item::Value("rating", value) => {
+                min_rating = args.parse("rating", &value)?;
+                if min_rating > tags::MAX_RATING {
+                    return Err(args.usage_error("ratings go up to 5").into());
+                }
+            }
+            Item::Flag("best-first") => best_first = true,
+            Item::Flag("quiet") => display = Display::Channels,
+            Item::Value("sleep", value) => {
+                let minutes: u16 = args.parse("sleep", &value)?;
+                if minutes == 0 || minutes > sleep::MAX_MINUTES {
+                    return Err(args.usage_error("sleep for 1 to 720 minutes").into());
+                }
+                sleep_minutes = Some(minutes);
+            }
+            Item::Positional(value) if !playlist.push(&value) => {
+                return Err(args.usage_error("too many modules").into());
+            }
+            _ => {}
+        }
+    }
+    if playlist.is_empty() && !playlist.read_from(&stdin) {
+        let _ = writeln!(
+            stdout,
+            "Too many modules - only playing the first {}",
+            playlist.len()
+        );
+    }
+    if min_rating > 0 || best_first {
+        playlist.select(min_rating, best_first, tags::rating);
+        if playlist.is_empty() {
+            let _ = writeln!(stdout, "No modules are rated {} or more", min_rating);
+            return Ok(());
+        }
+    }
+    if playlist.is_empty() {
+        return Err(args.usage_error("no module to play").into());
+    }
+    let mut config_buffer = [0u8; 256];
+    let config = match neotron_config::load(CONFIG_FILE, &mut config_buffer) {
+        Ok(config) => config,
+        Err(neotron_config::Error::Syntax(line)) => {
+            let _ = writeln!(stdout, "Ignoring {}: bad line {}", CONFIG_FILE, line);
+            neotron_config::Config::empty()
+        }
+        Err(_) => neotron_config::Config::empty(),
     };
-    drop(f);
-    // Set 16-bit stereo, 44.1 kHz
-    let dsp_path = neotron_sdk::path::Path::new("AUDIO:")?;
-    let dsp = neotron_sdk::File::open(dsp_path, neotron_sdk::Flags::empty())?;
-    if dsp.ioctl(1, 3 << 60 | 44100).is_err() {
-        let _ = writeln!(stdout, "Failed to configure audio");
-        return neotron_sdk::Result::Err(neotron_sdk::Error::DeviceSpecific);
+    let sample_rate = config
+        .get_u32("audio", "sample_rate")
+        .unwrap_or(DEFAULT_SAMPLE_RATE);
+    let prebuffer = config
+        .get_u32("audio", "prebuffer")
+        .unwrap_or(DEFAULT_PREBUFFER);
+    let mut click = config
+        .get("audio", "click")
+        .and_then(|text| player::Click::parse(text.trim()))
+        .unwrap_or(player::Click::Off);
+
+    if let Some(dump_file) = dump_file {
+        let (1, Some(filename)) = (playlist.len(), playlist.iter().next()) else {
+            return Err(args.usage_error("--dump takes one module").into());
+        };
+        let _ = writeln!(stdout, "Loading {:?}...", filename);
+        let f = open_module(&mut stdout, filename).ok_or(neotron_sdk::Error::InvalidArg)?;
+        // Safety: nothing is using the file buffer yet
+        let file_buffer = unsafe { read_module(&f)? };
+        drop(f);
+        let path = neotron_sdk::path::Path::new(&dump_file)?;
+        let mut out = neotron_sdk::File::open(
+            path,
+            neotron_sdk::Flags::WRITE | neotron_sdk::Flags::CREATE | neotron_sdk::Flags::TRUNCATE,
+        )?;
+        return match dump::dump(file_buffer, &mut out) {
+            Ok(()) => {
+                let _ = writeln!(stdout, "Wrote {:?}", dump_file);
+                Ok(())
+            }
+            Err(dump::Error::Module(e)) => {
+                let _ = writeln!(stdout, "Failed to read module: {:?}", e);
+                Err(neotron_sdk::Error::InvalidArg)
+            }
+            Err(dump::Error::Write) => Err(neotron_sdk::Error::DeviceSpecific),
+        };
     }
 
-    let mut player = match player::Player::new(file_buffer, 44100) {
-        Ok(player) => player,
-        Err(e) => {
-            let _ = writeln!(stdout, "Failed to create player: {:?}", e);
-            return Err(neotron_sdk::Error::InvalidArg);
+    if extract {
+        for filename in playlist.iter() {
+            let _ = writeln!(stdout, "Loading {:?}...", filename);
+            let Some(f) = open_module(&mut stdout, filename) else {
+                continue;
+            };
+            // Safety: we've finished with the last module
+            let file_buffer = unsafe { read_module(&f)? };
+            drop(f);
+            match extract::extract(file_buffer, filename, &mut stdout) {
+                Ok(count) => {
+                    let _ = writeln!(stdout, "Wrote {} samples", count);
+                }
+                Err(extract::Error::Module(e)) => {
+                    let _ = writeln!(stdout, "Failed to read module: {:?}", e);
+                }
+                Err(extract::Error::Io(e)) => return Err(e),
+                Err(extract::Error::Write) => return Err(neotron_sdk::Error::DeviceSpecific),
+            }
+        }
+        return Ok(());
+    }
+
+    // Set 16-bit stereo, at the sample rate we want if we can
+    let dsp = match neotron_audio::open_nearest(sample_rate) {
+        Ok(dsp) => dsp,
+        Err(neotron_sdk::Error::DeviceSpecific) => {
+            let _ = writeln!(stdout, "Failed to configure audio");
+            return Err(neotron_sdk::Error::DeviceSpecific);
         }
+        Err(e) => return Err(e),
     };
+    // We mix at the rate we wanted, and convert to the device's rate, so
+    // the pitch comes out right either way
+    let mut resampler = None;
+    if dsp.sample_rate() != sample_rate {
+        let _ = writeln!(
+            stdout,
+            "Audio runs at {} Hz, so converting from {} Hz",
+            dsp.sample_rate(),
+            sample_rate
+        );
+        resampler = Some(neotron_audio::Resampler::new(
+            sample_rate,
+            dsp.sample_rate(),
+        ));
+    }
+
+    // The limiter comes last, so it works at the device's rate
+    let mut limiter = limit_db.map(|db| neotron_audio::Limiter::new(db, dsp.sample_rate()));
+    if let Some(limiter) = limiter.as_ref() {
+        let _ = writeln!(
+            stdout,
+            "Limiter on: nothing louder than -{} dB",
+            limiter.ceiling_db()
+        );
+    }
+
+    let mut sleep_timer = sleep::SleepTimer::new(dsp.sample_rate());
+    sleep_timer.set(sleep_minutes);
+    if let Some(minutes) = sleep_minutes {
+        let _ = writeln!(stdout, "Stopping in {} minutes", minutes);
+    }
 
-    let _ = writeln!(stdout, "Playing {:?}...", filename);
     let mut sample_buffer = [0u8; 1024];
-    // loop some some silence to give us a head-start
-    for _i in 0..11 {
-        let _ = dsp.write(&sample_buffer);
+    // Names typed at the Open prompt, so Up brings them back
+    let mut open_editor = neotron_line::Editor::<64, 256>::new();
+    let mut keyboard = neotron_input::Keyboard::new();
+    let mut meters = vu::Stereo::new();
+    let mut lights = activity::Activity::<4>::new();
+    let mut status_line = status::Status::<4>::new(SCREEN_WIDTH, SCREEN_HEIGHT);
+    if display == Display::Patterns {
+        let _ = status_line.open(&mut stdout);
     }
+    let mut limit_shown = false;
+    let mut quiet_rows: u32 = 0;
+    'songs: for name in playlist.iter() {
+        // The module playing, which can change if another is opened
+        let Ok(mut filename) = neotron_fs::PathBuf::from_path(name) else {
+            let _ = writeln!(stdout, "Name too long: {:?}", name);
+            continue;
+        };
+        let _ = writeln!(stdout, "Loading {:?}...", filename.as_str());
+        let Some(f) = open_module(&mut stdout, filename.as_str()) else {
+            continue;
+        };
+        // Safety: the player for the last song has gone
+        let file_buffer = unsafe { read_module(&f)? };
+        drop(f);
+        let Ok(mut player) = new_player(&mut stdout, file_buffer, sample_rate) else {
+            continue;
+        };
+        player.set_click(click);
 
-    loop {
-        for chunk in sample_buffer.chunks_exact_mut(4) {
-            let (left, right) = player.next_sample(&mut stdout);
-            let left_bytes = left.to_le_bytes();
-            let right_bytes = right.to_le_bytes();
-            chunk[0] = left_bytes[0];
-            chunk[1] = left_bytes[1];
-            chunk[2] = right_bytes[0];
-            chunk[3] = right_bytes[1];
-        }
-        let _ = dsp.write(&sample_buffer);
-        let mut in_buf = [0u8; 1];
-        if player.is_finished() {
-            break;
+        show_playing(&mut stdout, filename.as_str());
+        // loop some some silence to give us a head-start
+        for _i in 0..prebuffer {
+            let _ = dsp.write(&[0u8; 1024]);
         }
-        if stdin.read(&mut in_buf).is_ok() && in_buf[0].to_ascii_lowercase() == b'q' {
-            break;
+
+        let mut last_line = None;
+        loop {
+            neotron_audio::convert::fill_stereo(&mut sample_buffer, || {
+                let mut mix = || match display {
+                    Display::Patterns => player.next_sample(&mut stdout),
+                    Display::Meters => player.next_sample(&mut Quiet),
+                    Display::Channels => player.next_sample_with(&mut Quiet, &mut lights),
+                };
+                let mut frame = match resampler.as_mut() {
+                    Some(resampler) => resampler.next(mix),
+                    None => mix(),
+                };
+                if let Some(limiter) = limiter.as_mut() {
+                    frame = limiter.next(frame);
+                }
+                frame = sleep_timer.next(frame);
+                meters.add(frame);
+                frame
+            });
+            let _ = dsp.write(&sample_buffer);
+            if display == Display::Channels {
+                let _ = lights.write(&mut stdout);
+            }
+            if sleep_timer.is_done() {
+                let _ = lights.end_line(&mut stdout);
+                let _ = writeln!(stdout, "Sleep timer finished");
+                break 'songs;
+            }
+            // The meters show what played since the last line started
+            let line = (player.position(), player.line());
+            if last_line != Some(line) {
+                let limiting = limiter.as_mut().is_some_and(|l| l.was_limiting());
+                quiet_rows = if limiting {
+                    0
+                } else {
+                    quiet_rows.saturating_add(1)
+                };
+                // Whatever the display, say when the limiter starts and
+                // stops turning the music down
+                if limiting != limit_shown && (limiting || quiet_rows >= LIMIT_HOLD_ROWS) {
+                    limit_shown = limiting;
+                    let _ = lights.end_line(&mut stdout);
+                    if limiting {
+                        let _ = writeln!(stdout, "LIMIT: turning the music down");
+                    } else {
+                        let _ = writeln!(stdout, "LIMIT: off");
+                    }
+                }
+                if display == Display::Patterns {
+                    let _ = status_line.write(&mut stdout, player.effects());
+                }
+                if display == Display::Meters {
+                    let _ = write!(stdout, "{:03} {:06}: ", line.0, line.1);
+                    let _ = meters.write(&mut stdout);
+                    if limiting {
+                        let _ = write!(stdout, " LIMIT");
+                    }
+                    let _ = writeln!(stdout);
+                }
+            }
+            last_line = Some(line);
+            if player.is_finished() {
+                let _ = lights.end_line(&mut stdout);
+                let _ = writeln!(stdout, "Finished!");
+                let _ = player.stats().write(&mut stdout);
+                break;
+            }
+            let Some(neotron_input::KeyEvent::Char(key)) = keyboard.read() else {
+                continue;
+            };
+            // Whatever the key says goes on a line of its own
+            let _ = lights.end_line(&mut stdout);
+            match key.to_ascii_lowercase() {
+                'q' => break 'songs,
+                'n' => break,
+                'o' => {
+                    // Nothing gets mixed while we wait for the name, so leave
+                    // the device playing silence rather than the end of the song
+                    for _i in 0..SILENCE_BUFFERS {
+                        let _ = dsp.write(&[0u8; 1024]);
+                    }
+                    let _ = stdout.write_str("Open: ");
+                    let name = open_editor.read_line(&mut stdout, &mut keyboard);
+                    let _ = writeln!(stdout);
+                    let Some(name) = name.map(str::trim).filter(|name| !name.is_empty()) else {
+                        continue;
+                    };
+                    // Make sure the new one looks right before it goes over the
+                    // old one, so we can keep playing the old one if it doesn't
+                    let _ = writeln!(stdout, "Loading {:?}...", name);
+                    let Some(f) = open_module(&mut stdout, name) else {
+                        continue;
+                    };
+                    if !check_module(&mut stdout, &f) {
+                        continue;
+                    }
+                    // Safety: the new module goes where the old one was, but the
+                    // old player is never used again - we replace it just below,
+                    // or move on to the next song
+                    let file_buffer = match unsafe { read_module(&f) } {
+                        Ok(data) => data,
+                        Err(e) => {
+                            let _ = writeln!(stdout, "Can't read {:?}: {:?}", name, e);
+                            break;
+                        }
+                    };
+                    drop(f);
+                    // The old one has gone now, so if this is no good there's
+                    // nothing left to play, and we move on
+                    match new_player(&mut stdout, file_buffer, sample_rate) {
+                        Ok(new) => player = new,
+                        Err(_) => break,
+                    }
+                    player.set_click(click);
+                    if let Ok(new_name) = neotron_fs::PathBuf::from_path(name) {
+                        filename = new_name;
+                    }
+                    show_playing(&mut stdout, filename.as_str());
+                    for _i in 0..prebuffer {
+                        let _ = dsp.write(&[0u8; 1024]);
+                    }
+                }
+                key @ '0'..='5' => {
+                    let rating = key as u8 - b'0';
+                    match tags::set_rating(filename.as_str(), rating) {
+                        Ok(()) if rating == 0 => {
+                            let _ = writeln!(stdout, "Rating cleared");
+                        }
+                        Ok(()) => {
+                            let _ = writeln!(stdout, "Rated {}/{}", rating, tags::MAX_RATING);
+                        }
+                        Err(e) => {
+                            let _ = writeln!(stdout, "Can't save rating: {:?}", e);
+                        }
+                    }
+                }
+                'v' => {
+                    display = display.next();
+                    // Only the pattern data has the status line under it
+                    if display == Display::Patterns {
+                        let _ = status_line.open(&mut stdout);
+                    } else {
+                        let _ = status_line.close(&mut stdout);
+                    }
+                }
+                'c' => {
+                    click = click.next();
+                    player.set_click(click);
+                    let _ = writeln!(stdout, "Click: {}", click.name());
+                }
+                't' => {
+                    sleep_timer.step();
+                    match sleep_timer.minutes_left() {
+                        Some(minutes) => {
+                            let _ = writeln!(stdout, "Stopping in {} minutes", minutes);
+                        }
+                        None => {
+                            let _ = writeln!(stdout, "Sleep timer off");
+                        }
+                    }
+                }
+                _ => {}
+            }
         }
     }
 
+    let _ = status_line.close(&mut stdout);
     let _ = writeln!(stdout, "Bye!");
 
     Ok(())
 }
+
+/// Leave things tidy when something has gone wrong: make sure the speakers
+/// are quiet, and put the console back to normal.
+///
+/// On a PC this runs after a panic too. On a Neotron the SDK handles
+/// panics, so it only runs when we stop with an error.
+fn emergency_stop() {
+    // On a PC, the sound card goes quiet by itself once we let go of it
+    #[cfg(target_os = "none")]
+    if let Ok(dsp) = neotron_audio::open_nearest(DEFAULT_SAMPLE_RATE) {
+        for _i in 0..SILENCE_BUFFERS {
+            let _ = dsp.write(&[0u8; 1024]);
+        }
+    }
+    let mut stdout = neotron_sdk::stdout();
+    // Let every row scroll again, in case the status line was showing
+    let _ = write!(stdout, "\x1b[r\x1b[{};1H", SCREEN_HEIGHT);
+    neotron_sdk::console::set_sgr(&mut stdout, [neotron_sdk::console::SgrParam::Reset]);
+    neotron_sdk::console::cursor_on(&mut stdout);
+    let _ = writeln!(stdout);
+}
+
+/// Somewhere to send text we don't want to see
+struct Quiet;
+
+impl Write for Quiet {
+    fn write_str(&mut self, _s: &str) -> core::fmt::Result {
+        Ok(())
+    }
+}
+
+/// Read a module into the file buffer.
+///
+/// # Safety
+///
+/// This overwrites the file buffer, so nothing else can be using it - in
+/// particular, any player made from the last module must never be used
+/// again.
+unsafe fn read_module(f: &neotron_sdk::File) -> Result<&'static [u8], neotron_sdk::Error> {
+    let file_buffer = &mut *addr_of_mut!(FILE_BUFFER);
+    let n = f.read(file_buffer)?;
+    Ok(&file_buffer[0..n])
+}
+
+/// Make a player for a module, saying why if we can't
+fn new_player(
+    stdout: &mut neotron_sdk::File,
+    data: &'static [u8],
+    sample_rate: u32,
+) -> Result<player::Player<'static>, neotron_sdk::Error> {
+    let player = player::Player::new(data, sample_rate).map_err(|e| {
+        let _ = writeln!(stdout, "Failed to create player: {:?}", e);
+        neotron_sdk::Error::InvalidArg
+    })?;
+    let _ = writeln!(
+        stdout,
+        "Module uses {} of {} KiB, player uses {} bytes",
+        data.len().div_ceil(1024),
+        FILE_BUFFER_KIB,
+        core::mem::size_of::<player::Player>()
+    );
+    Ok(player)
+}
+
+/// Open a module, checking it will fit in the file buffer, and saying why
+/// if we can't.
+///
+/// If we can't tell how big the file is, we assume it fits, and just load
+/// as much as we can.
+fn open_module(stdout: &mut neotron_sdk::File, filename: &str) -> Option<neotron_sdk::File> {
+    let f = match neotron_sdk::path::Path::new(filename)
+        .and_then(|path| neotron_sdk::File::open(path, neotron_sdk::Flags::empty()))
+    {
+        Ok(f) => f,
+        Err(e) => {
+            let _ = writeln!(stdout, "Can't open {:?}: {:?}", filename, e);
+            return None;
+        }
+    };
+    if let Ok(stat) = f.stat() {
+        if stat.file_size > FILE_BUFFER_LEN as u64 {
+            let _ = writeln!(
+                stdout,
+                "That module is {} KiB, but we only have room for {} KiB",
+                stat.file_size.div_ceil(1024),
+                FILE_BUFFER_KIB
+            );
+            return None;
+        }
+    }
+    Some(f)
+}
+
+/// Check a file we've opened is a 4-channel module, saying why if it
+/// isn't. This only reads the start of it, so it doesn't disturb the module
+/// that's playing. The file is left ready to read from the start.
+fn check_module(stdout: &mut neotron_sdk::File, f: &neotron_sdk::File) -> bool {
+    let mut header = [0u8; TAG_OFFSET + 4];
+    let is_module = matches!(f.read(&mut header), Ok(n) if n == header.len())
+        && &header[TAG_OFFSET..] == b"M.K.";
+    if !is_module {
+        let _ = writeln!(stdout, "That isn't a 4-channel M.K. module");
+        return false;
+    }
+    if let Err(e) = f.seek_set(0) {
+        let _ = writeln!(stdout, "Can't read it: {:?}", e);
+        return false;
+    }
+    true
+}
+
+/// Say which module is playing, and how it's rated if it is
+fn show_playing(stdout: &mut neotron_sdk::File, filename: &str) {
+    match tags::rating(filename) {
+        0 => {
+            let _ = writeln!(stdout, "Playing {:?}...", filename);
+        }
+        rating => {
+            let _ = writeln!(
+                stdout,
+                "Playing {:?} ({}/{})...",
+                filename,
+                rating,
+                tags::MAX_RATING
+            );
+        }
+    }
+}
@@ -1,5 +1,159 @@
 //! Plays a MOD file.
 
+use crate::sync;
+
+/// Write a note as its period, sample number and effect code, like
+/// `1ac 01f04`. An empty note is all dashes.
+pub fn write_note<T>(out: &mut T, note: &neotracker::Note) -> core::fmt::Result
+where
+    T: core::fmt::Write,
+{
+    if note.is_empty() {
+        return out.write_str("--- -----");
+    }
+    write!(
+        out,
+        "{:3x} {:02}{:03x}",
+        note.period(),
+        note.sample_no(),
+        note.effect_u16()
+    )
+}
+
+/// A short name for a ProTracker effect, from the effect number (the top
+/// nibble of the three-digit effect code)
+pub fn effect_name(effect: u8) -> &'static str {
+    match effect {
+        0x0 => "Arp",
+        0x1 => "SldUp",
+        0x2 => "SldDn",
+        0x3 => "Port",
+        0x4 => "Vib",
+        0x5 => "PortVS",
+        0x6 => "VibVS",
+        0x7 => "Trem",
+        0x8 => "Pan",
+        0x9 => "Offset",
+        0xA => "VolSld",
+        0xB => "Jump",
+        0xC => "Vol",
+        0xD => "Break",
+        0xE => "Ext",
+        _ => "Speed",
+    }
+}
+
+/// How loud the click track is, out of 256
+const CLICK_VOLUME: u16 = 24;
+
+/// The pitch of a click, in Hz
+const CLICK_HZ: u32 = 880;
+
+/// The pitch of the click at the start of each pattern, in Hz
+const ACCENT_HZ: u32 = 1760;
+
+/// How long a click takes to fade away, in milliseconds
+const CLICK_MS: u32 = 20;
+
+/// When the click track clicks
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Click {
+    /// Never
+    Off,
+    /// On every row
+    Row,
+    /// On every fourth row
+    Beat,
+}
+
+impl Click {
+    /// Read the setting from some text
+    pub fn parse(text: &str) -> Option<Click> {
+        if text.eq_ignore_ascii_case("off") {
+            Some(Click::Off)
+        } else if text.eq_ignore_ascii_case("row") {
+            Some(Click::Row)
+        } else if text.eq_ignore_ascii_case("beat") {
+            Some(Click::Beat)
+        } else {
+            None
+        }
+    }
+
+    /// The name of the setting
+    pub fn name(self) -> &'static str {
+        match self {
+            Click::Off => "off",
+            Click::Row => "row",
+            Click::Beat => "beat",
+        }
+    }
+
+    /// The next setting, going round in a loop
+    pub fn next(self) -> Click {
+        match self {
+            Click::Off => Click::Beat,
+            Click::Beat => Click::Row,
+            Click::Row => Click::Off,
+        }
+    }
+}
+
+/// What happened while a song played
+#[derive(Debug, Default, Clone)]
+pub struct Stats {
+    /// How many patterns we've played, counting repeats
+    pub patterns: u32,
+    /// How many lines we've played
+    pub lines: u32,
+    /// How many times each effect came up, by effect number
+    pub effects: [u32; 16],
+    /// The loudest sample we've sent out, on either side
+    pub peak: u16,
+    /// How many stereo frames were too loud, and got clipped
+    pub clipped: u32,
+}
+
+impl Stats {
+    /// Write out a short report
+    pub fn write<T>(&self, out: &mut T) -> core::fmt::Result
+    where
+        T: core::fmt::Write,
+    {
+        writeln!(
+            out,
+            "Played {} patterns, {} lines",
+            self.patterns, self.lines
+        )?;
+        if self.effects.iter().all(|count| *count == 0) {
+            writeln!(out, "No effects")?;
+        } else {
+            writeln!(out, "Effects:")?;
+            for (effect, count) in self.effects.iter().enumerate() {
+                if *count != 0 {
+                    writeln!(
+                        out,
+                        "  {:x} {:<6} {:6}",
+                        effect,
+                        effect_name(effect as u8),
+                        count
+                    )?;
+                }
+            }
+        }
+        writeln!(
+            out,
+            "Peak level {}%",
+            u32::from(self.peak) * 100 / u32::from(i16::MAX as u16)
+        )?;
+        if self.clipped == 0 {
+            writeln!(out, "No clipping")
+        } else {
+            writeln!(out, "Clipped {} frames", self.clipped)
+        }
+    }
+}
+
 #[derive(Debug, Default)]
 struct Channel {
     sample_data: Option<*const u8>,
@@ -9,11 +163,14 @@ struct Channel {
     repeat_point: usize,
     volume: u8,
     note_period: u16,
-    sample_position: neotracker::Fractional,
-    note_step: neotracker::Fractional,
+    sample_position: neotron_fixed::Fractional,
+    note_step: neotron_fixed::Fractional,
     effect: Option<neotracker::Effect>,
+    /// The effect number on the line playing, if there is one
+    effect_no: Option<u8>,
 }
 
+/// Plays a ProTracker module
 pub struct Player<'a> {
     modfile: neotracker::ProTrackerModule<'a>,
     /// How many samples left in this tick
@@ -23,7 +180,7 @@ pub struct Player<'a> {
     ticks_per_line: u32,
     third_ticks_per_line: u32,
     samples_per_tick: u32,
-    clock_ticks_per_device_sample: neotracker::Fractional,
+    clock_ticks_per_device_sample: neotron_fixed::Fractional,
     position: u8,
     line: u8,
     finished: bool,
@@ -31,14 +188,23 @@ pub struct Player<'a> {
     /// us to jump to a specific row in the next pattern.
     pattern_break: Option<u8>,
     channels: [Channel; 4],
+    sample_rate: u32,
+    /// When to click, to help you follow the rhythm
+    click: Click,
+    click_tone: neotron_audio::Oscillator,
+    click_envelope: neotron_audio::Envelope,
+    stats: Stats,
+    /// The order table entry we last played a line from
+    last_position: Option<u8>,
 }
 
 /// This code is based on https://www.codeslow.com/2019/02/in-this-post-we-will-finally-have-some.html?m=1
 impl<'a> Player<'a> {
     /// Make a new player, at the given sample rate.
-    pub fn new(data: &'static [u8], sample_rate: u32) -> Result<Player<'a>, neotracker::Error> {
-        // We need a 'static reference to this data, and we're not going to free it.
-        // So just leak it.
+    ///
+    /// The channels keep pointers into the module data, so the data must
+    /// outlive the player.
+    pub fn new(data: &'a [u8], sample_rate: u32) -> Result<Player<'a>, neotracker::Error> {
         let modfile = neotracker::ProTrackerModule::new(data)?;
         Ok(Player {
             modfile,
@@ -50,7 +216,7 @@ impl<'a> Player<'a> {
             position: 0,
             line: 0,
             finished: false,
-            clock_ticks_per_device_sample: neotracker::Fractional::new_from_sample_rate(
+            clock_ticks_per_device_sample: neotron_fixed::Fractional::new_from_sample_rate(
                 sample_rate,
             ),
             pattern_break: None,
@@ -60,18 +226,74 @@ impl<'a> Player<'a> {
                 Channel::default(),
                 Channel::default(),
             ],
+            sample_rate,
+            click: Click::Off,
+            click_tone: neotron_audio::Oscillator::new(neotron_audio::Waveform::Square),
+            click_envelope: neotron_audio::Envelope::new(neotron_audio::Adsr::from_ms(
+                0,
+                CLICK_MS,
+                0,
+                0,
+                sample_rate,
+            )),
+            stats: Stats::default(),
+            last_position: None,
         })
     }
 
+    /// What has happened since the song started
+    pub fn stats(&self) -> &Stats {
+        &self.stats
+    }
+
+    /// When the click track clicks
+    pub fn click(&self) -> Click {
+        self.click
+    }
+
+    /// Change when the click track clicks
+    pub fn set_click(&mut self, click: Click) {
+        self.click = click;
+        if click == Click::Off {
+            self.click_envelope.silence();
+        }
+    }
+
     /// Are we finished playing?
     pub fn is_finished(&self) -> bool {
         self.finished
     }
 
+    /// Which entry in the song's order table we are playing
+    pub fn position(&self) -> u8 {
+        self.position
+    }
+
+    /// Which line of the pattern plays next
+    pub fn line(&self) -> u8 {
+        self.line
+    }
+
+    /// The effect number each channel has on the line playing, for
+    /// [`effect_name`]
+    pub fn effects(&self) -> [Option<u8>; 4] {
+        core::array::from_fn(|idx| self.channels[idx].effect_no)
+    }
+
     /// Return a stereo sample pair
     pub fn next_sample<T>(&mut self, out: &mut T) -> (i16, i16)
     where
         T: core::fmt::Write,
+    {
+        self.next_sample_with(out, &mut ())
+    }
+
+    /// Return a stereo sample pair, and tell the hooks if a row or a beat
+    /// starts
+    pub fn next_sample_with<T, H>(&mut self, out: &mut T, hooks: &mut H) -> (i16, i16)
+    where
+        T: core::fmt::Write,
+        H: sync::Hooks,
     {
         if self.ticks_left == 0 && self.samples_left == 0 {
             // It is time for a new line
@@ -91,8 +313,12 @@ impl<'a> Player<'a> {
                     self.finished = true;
                     return (0, 0);
                 };
-                // Grab the pattern
-                let pattern = self.modfile.pattern(pattern_idx).expect("Get pattern");
+                // Grab the pattern. If it isn't there, the module is broken,
+                // so stop rather than panic.
+                let Some(pattern) = self.modfile.pattern(pattern_idx) else {
+                    self.finished = true;
+                    return (0, 0);
+                };
                 // Get the line from the pattern
                 let Some(line) = pattern.line(self.line) else {
                     // Go to start of next pattern
@@ -105,14 +331,53 @@ impl<'a> Player<'a> {
                 break line;
             };
 
+            // A pattern break can land us part way into the next pattern, so
+            // count patterns by when the position changes
+            let new_pattern = self.last_position != Some(self.position);
+            if new_pattern {
+                self.last_position = Some(self.position);
+                self.stats.patterns += 1;
+            }
+            self.stats.lines += 1;
+            let mut row = sync::Row {
+                position: self.position,
+                pattern: self.modfile.song_position(self.position).unwrap_or(0),
+                line: self.line,
+                new_pattern,
+                triggers: [None; 4],
+            };
+
+            let clicks = match self.click {
+                Click::Off => false,
+                Click::Row => true,
+                Click::Beat => row.is_beat(),
+            };
+            if clicks {
+                let hz = if self.line == 0 { ACCENT_HZ } else { CLICK_HZ };
+                self.click_tone.set_frequency(hz, self.sample_rate);
+                self.click_tone.reset();
+                self.click_envelope.trigger();
+            }
+
             // Load four channels with new line data
             let _ = write!(out, "{:03} {:06}: ", self.position, self.line);
-            for (channel_num, ch) in self.channels.iter_mut().enumerate() {
+            for ((channel_num, ch), trigger) in self
+                .channels
+                .iter_mut()
+                .enumerate()
+                .zip(row.triggers.iter_mut())
+            {
                 let note = &line.channel[channel_num];
                 // Do we have a new sample to play?
-                if note.is_empty() {
-                    let _ = write!(out, "--- -----|");
-                } else {
+                let _ = write_note(out, note);
+                let _ = out.write_char('|');
+                // An arpeggio of nothing is no effect at all
+                let effect = note.effect_u16();
+                ch.effect_no = (effect != 0).then_some(((effect >> 8) & 0x0F) as u8);
+                if let Some(effect_no) = ch.effect_no {
+                    self.stats.effects[usize::from(effect_no)] += 1;
+                }
+                if !note.is_empty() {
                     if let Some(sample) = self.modfile.sample(note.sample_no()) {
                         // if the period is zero, keep playing the old note
                         if note.period() != 0 {
@@ -127,15 +392,13 @@ impl<'a> Player<'a> {
                         ch.sample_length = sample.sample_length_bytes();
                         ch.repeat_length = sample.repeat_length_bytes();
                         ch.repeat_point = sample.repeat_point_bytes();
-                        ch.sample_position = neotracker::Fractional::default();
+                        ch.sample_position = neotron_fixed::Fractional::default();
+                        *trigger = Some(sync::Trigger {
+                            sample: note.sample_no(),
+                            period: ch.note_period,
+                            volume: 0,
+                        });
                     }
-                    let _ = write!(
-                        out,
-                        "{:3x} {:02}{:03x}|",
-                        note.period(),
-                        note.sample_no(),
-                        note.effect_u16()
-                    );
                 }
                 ch.effect = None;
                 match note.effect() {
@@ -151,17 +414,15 @@ impl<'a> Player<'a> {
                     Some(neotracker::Effect::SetVolume(value)) => {
                         ch.volume = value;
                     }
-                    Some(neotracker::Effect::SetSpeed(value)) => {
-                        if value <= 31 {
-                            self.ticks_per_line = u32::from(value);
-                            self.third_ticks_per_line = u32::from(value / 3);
-                        } else {
-                            // They are trying to set speed in beats per minute
-                        }
+                    Some(neotracker::Effect::SetSpeed(value)) if value <= 31 => {
+                        // Larger values set the speed in beats per minute,
+                        // which we don't support yet
+                        self.ticks_per_line = u32::from(value);
+                        self.third_ticks_per_line = u32::from(value / 3);
                     }
                     Some(neotracker::Effect::SampleOffset(n)) => {
                         let offset = u32::from(n) * 256;
-                        ch.sample_position = neotracker::Fractional::new(offset);
+                        ch.sample_position = neotron_fixed::Fractional::new(offset);
                     }
                     Some(neotracker::Effect::PatternBreak(row)) => {
                         // Start the next pattern early, at the given row
@@ -174,8 +435,16 @@ impl<'a> Player<'a> {
                         // Do nothing
                     }
                 }
+                // The effect might have changed the volume
+                if let Some(trigger) = trigger.as_mut() {
+                    trigger.volume = ch.volume;
+                }
             }
             let _ = writeln!(out);
+            hooks.row(&row);
+            if row.is_beat() {
+                hooks.beat(&row);
+            }
 
             self.line += 1;
             self.samples_left = self.samples_per_tick - 1;
@@ -242,8 +511,7 @@ impl<'a> Player<'a> {
         }
 
         // Pump existing channels
-        let mut left_sample = 0;
-        let mut right_sample = 0;
+        let mut mixer = neotron_audio::Mixer::new();
         for (ch_idx, ch) in self.channels.iter_mut().enumerate() {
             if ch.note_period == 0 || ch.sample_length == 0 {
                 continue;
@@ -253,33 +521,40 @@ impl<'a> Player<'a> {
             };
             let integer_pos = ch.sample_position.as_index();
             let sample_byte = unsafe { sample_data.add(integer_pos).read() } as i8;
-            let mut channel_value = (sample_byte as i8) as i32;
             // max channel vol (64), sample range [-128,127] scaled to [-32768, 32767]
-            channel_value *= 256;
-            channel_value *= i32::from(ch.volume);
-            channel_value /= 64;
+            let channel_value = neotron_audio::convert::from_i8(sample_byte);
+            let volume = u16::from(ch.volume) * 4;
             // move the sample index by a non-integer amount
             ch.sample_position += ch.note_step;
             // loop sample if required
             if ch.sample_loops {
                 if ch.sample_position.as_index() >= (ch.repeat_point + ch.repeat_length) {
-                    ch.sample_position = neotracker::Fractional::new(ch.repeat_point as u32);
+                    ch.sample_position = neotron_fixed::Fractional::new(ch.repeat_point as u32);
                 }
             } else if ch.sample_position.as_index() >= ch.sample_length {
                 // stop playing sample
                 ch.note_period = 0;
             }
 
-            if ch_idx == 0 || ch_idx == 3 {
-                left_sample += channel_value;
-            } else {
-                right_sample += channel_value;
-            }
+            // Amiga style: channels 0 and 3 on the left, 1 and 2 on the right
+            let pan = if ch_idx == 0 || ch_idx == 3 { 0 } else { 255 };
+            mixer.add_panned(channel_value, volume, pan);
+        }
+
+        if self.click_envelope.is_active() {
+            let click = self.click_envelope.apply(self.click_tone.next_sample());
+            mixer.add(click, CLICK_VOLUME);
         }
 
-        (
-            left_sample.clamp(-32768, 32767) as i16,
-            right_sample.clamp(-32768, 32767) as i16,
-        )
+        if mixer.is_clipping() {
+            self.stats.clipped += 1;
+        }
+        let (left, right) = mixer.finish();
+        self.stats.peak = self
+            .stats
+            .peak
+            .max(left.unsigned_abs())
+            .max(right.unsigned_abs());
+        (left, right)
     }
 }
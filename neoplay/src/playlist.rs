@@ -0,0 +1,130 @@
+//! A list of modules to play, one after the other.
+//!
+//! The names are kept one per line in a fixed buffer, so we don't need to
+//! allocate.
+
+/// How many bytes of names we can hold
+const MAX_TEXT: usize = 2048;
+
+/// How many names [`Playlist::select`] can sort out. The shortest name
+/// takes two bytes, with its new line.
+const MAX_NAMES: usize = MAX_TEXT / 2;
+
+/// The highest score [`Playlist::select`] understands
+pub const MAX_SCORE: u8 = 5;
+
+/// Some modules to play
+pub struct Playlist {
+    text: [u8; MAX_TEXT],
+    len: usize,
+}
+
+impl Playlist {
+    /// Make an empty playlist
+    pub const fn new() -> Playlist {
+        Playlist {
+            text: [0; MAX_TEXT],
+            len: 0,
+        }
+    }
+
+    /// Add a module to the end. Returns `false` if there's no room.
+    pub fn push(&mut self, name: &str) -> bool {
+        let name = name.trim();
+        let end = self.len + name.len() + 1;
+        if end > MAX_TEXT {
+            return false;
+        }
+        self.text[self.len..end - 1].copy_from_slice(name.as_bytes());
+        self.text[end - 1] = b'\n';
+        self.len = end;
+        true
+    }
+
+    /// Add the names in a file, one per line, until it runs out.
+    ///
+    /// Returns `false` if there wasn't room for them all, in which case we
+    /// keep as many whole names as fit.
+    pub fn read_from(&mut self, file: &neotron_sdk::File) -> bool {
+        while self.len < MAX_TEXT {
+            match file.read(&mut self.text[self.len..]) {
+                Ok(0) | Err(_) => return true,
+                Ok(n) => self.len += n,
+            }
+        }
+        // We're full, but if there's more to come, the last name might only
+        // be part of one
+        let mut extra = [0u8; 1];
+        if !matches!(file.read(&mut extra), Ok(1)) {
+            return true;
+        }
+        self.len = self.text[0..self.len]
+            .iter()
+            .rposition(|b| *b == b'\n')
+            .map_or(0, |idx| idx + 1);
+        false
+    }
+
+    /// Go through the modules in order
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.text[0..self.len]
+            .split(|b| *b == b'\n' || *b == b'\r')
+            .filter_map(|line| core::str::from_utf8(line).ok())
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+    }
+
+    /// Keep the modules which `score` gives at least `min` to. If
+    /// `best_first` is set, the highest scores go to the front, and modules
+    /// with the same score stay in the order they were in.
+    ///
+    /// Scores go from 0 to [`MAX_SCORE`].
+    pub fn select<F>(&mut self, min: u8, best_first: bool, mut score: F)
+    where
+        F: FnMut(&str) -> u8,
+    {
+        let mut scores = [0u8; MAX_NAMES];
+        for (idx, name) in self.iter().take(MAX_NAMES).enumerate() {
+            scores[idx] = score(name).min(MAX_SCORE);
+        }
+        let old = Playlist {
+            text: self.text,
+            len: self.len,
+        };
+        self.len = 0;
+        // Going from the best score down, or just once if the order stays
+        let mut level = if best_first { MAX_SCORE } else { min };
+        loop {
+            for (idx, name) in old.iter().take(MAX_NAMES).enumerate() {
+                let keep = if best_first {
+                    scores[idx] == level
+                } else {
+                    scores[idx] >= min
+                };
+                if keep {
+                    self.push(name);
+                }
+            }
+            if level <= min {
+                break;
+            }
+            level -= 1;
+        }
+    }
+
+    /// How many modules are on the list
+    pub fn len(&self) -> usize {
+        self.iter().count()
+    }
+
+    /// Is the list empty?
+    pub fn is_empty(&self) -> bool {
+        self.iter().next().is_none()
+    }
+}
+
+impl Default for Playlist {
+    fn default() -> Self {
+        Playlist::new()
+    }
+}
@@ -0,0 +1,81 @@
+//! A sleep timer, which fades the music out after a while.
+//!
+//! The SDK doesn't give us a fine-grained timer, but the audio device takes
+//! samples at a steady rate, so we count the frames we send it. That's
+//! only music time - if playback stops, like while you type the name of a
+//! module to open, so does the timer.
+
+/// How long the fade at the end takes, in seconds
+const FADE_SECONDS: u32 = 10;
+
+/// The longest you can set the timer for, in minutes
+pub const MAX_MINUTES: u16 = 12 * 60;
+
+/// What the `t` key steps through, in minutes
+const STEPS: [u16; 5] = [15, 30, 45, 60, 90];
+
+/// The fixed-point one, for the gain
+const ONE: u32 = 1 << 16;
+
+/// Counts down, then fades the sound out
+#[derive(Debug, Copy, Clone)]
+pub struct SleepTimer {
+    /// How many frames go by in a minute
+    frames_per_minute: u64,
+    /// How many frames the fade takes
+    fade_frames: u64,
+    /// How many frames are left until it's silent, if the timer is on
+    left: Option<u64>,
+}
+
+impl SleepTimer {
+    /// Make a timer, switched off, for sound at this sample rate
+    pub const fn new(sample_rate: u32) -> SleepTimer {
+        SleepTimer {
+            frames_per_minute: sample_rate as u64 * 60,
+            fade_frames: sample_rate as u64 * FADE_SECONDS as u64,
+            left: None,
+        }
+    }
+
+    /// Go quiet in this many minutes, up to [`MAX_MINUTES`], or never if
+    /// it's `None`. The fade happens at the end of that time.
+    pub fn set(&mut self, minutes: Option<u16>) {
+        self.left = minutes.map(|m| u64::from(m.min(MAX_MINUTES)) * self.frames_per_minute);
+    }
+
+    /// How many minutes are left, rounded up, if the timer is on
+    pub fn minutes_left(&self) -> Option<u16> {
+        self.left
+            .map(|left| left.div_ceil(self.frames_per_minute) as u16)
+    }
+
+    /// Move on to the next step of the `t` key: the next step longer than
+    /// the time that's left, or off after the longest one
+    pub fn step(&mut self) {
+        let next = match self.minutes_left() {
+            None => Some(STEPS[0]),
+            Some(left) => STEPS.iter().copied().find(|step| *step > left),
+        };
+        self.set(next);
+    }
+
+    /// Has the fade finished?
+    pub fn is_done(&self) -> bool {
+        self.left == Some(0)
+    }
+
+    /// Count a frame, and turn it down if we're fading
+    pub fn next(&mut self, (left, right): (i16, i16)) -> (i16, i16) {
+        let Some(frames) = self.left.as_mut() else {
+            return (left, right);
+        };
+        *frames = frames.saturating_sub(1);
+        if *frames >= self.fade_frames {
+            return (left, right);
+        }
+        let gain = (*frames * u64::from(ONE) / self.fade_frames) as i32;
+        let scale = |sample: i16| ((i32::from(sample) * gain) >> 16) as i16;
+        (scale(left), scale(right))
+    }
+}
@@ -0,0 +1,90 @@
+//! A status line along the bottom of the screen, under the pattern data.
+//!
+//! It names the effect each channel has on the line playing, like `Vib`
+//! for vibrato or `VolSld` for a volume slide, with `---` for a channel
+//! with no effect. The rows above it scroll as usual, and it's only redrawn
+//! when it changes.
+
+use core::fmt::Write;
+
+use crate::player;
+
+/// The status line for `N` channels
+#[derive(Debug, Copy, Clone)]
+pub struct Status<const N: usize> {
+    /// How wide the screen is
+    width: u8,
+    /// How many rows the screen has. The status line is the last one.
+    height: u8,
+    /// Whether the bottom row is kept for us
+    open: bool,
+    /// What the status line shows, if we've drawn it
+    drawn: Option<[Option<u8>; N]>,
+}
+
+impl<const N: usize> Status<N> {
+    /// Make a status line for a screen this size
+    pub const fn new(width: u8, height: u8) -> Status<N> {
+        Status {
+            width,
+            height,
+            open: false,
+            drawn: None,
+        }
+    }
+
+    /// Keep the bottom row for the status line, and scroll the rows above
+    /// it. The cursor goes to the start of the last row that scrolls.
+    pub fn open<W: Write>(&mut self, out: &mut W) -> core::fmt::Result {
+        if self.open {
+            return Ok(());
+        }
+        // Scroll everything up a row, so nothing is left on the bottom one.
+        // Setting the region moves the cursor, so put it back afterwards.
+        write!(
+            out,
+            "\x1b[{0};1H\n\x1b[1;{1}r\x1b[{1};1H",
+            self.height,
+            self.height - 1
+        )?;
+        self.open = true;
+        self.drawn = None;
+        Ok(())
+    }
+
+    /// Name the effect on each channel, from the effect numbers, if they've
+    /// changed since we last drew them. The cursor must be at the start of the last row
+    /// that scrolls, and that's where it's left.
+    pub fn write<W: Write>(&mut self, out: &mut W, effects: [Option<u8>; N]) -> core::fmt::Result {
+        if !self.open || self.drawn == Some(effects) {
+            return Ok(());
+        }
+        write!(out, "\x1b[{};1H\x1b[7m", self.height)?;
+        for (idx, effect) in effects.iter().enumerate() {
+            let name = effect.map_or("---", player::effect_name);
+            write!(out, " {}: {:<6} ", idx + 1, name)?;
+        }
+        write!(out, "\x1b[0m\x1b[{};1H", self.height - 1)?;
+        self.drawn = Some(effects);
+        Ok(())
+    }
+
+    /// Let every row scroll again, and blank out the status line. The
+    /// cursor goes to the start of the bottom row.
+    pub fn close<W: Write>(&mut self, out: &mut W) -> core::fmt::Result {
+        if !self.open {
+            return Ok(());
+        }
+        // Leave the cursor at the start of the bottom row, which is blank
+        write!(
+            out,
+            "\x1b[r\x1b[{0};1H{1:2$}\x1b[{0};1H",
+            self.height,
+            "",
+            usize::from(self.width.saturating_sub(1))
+        )?;
+        self.open = false;
+        self.drawn = None;
+        Ok(())
+    }
+}
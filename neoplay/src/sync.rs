@@ -0,0 +1,62 @@
+//! Hooks for keeping pictures in time with the music.
+//!
+//! Give [`Player::next_sample_with`](crate::player::Player::next_sample_with)
+//! something that implements [`Hooks`], and it's told about each row as the
+//! row starts, and about each beat. Visualisers and demos can flash, switch
+//! scenes or light up a channel when a note plays.
+//!
+//! The hooks run when a row is mixed, not when it's heard. If you keep some
+//! audio buffered ahead, the row is heard that much later.
+
+/// How many rows make a beat. Most modules are written with four.
+pub const ROWS_PER_BEAT: u8 = 4;
+
+/// A note starting on one channel
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Trigger {
+    /// Which sample, from 1 to 31
+    pub sample: u8,
+    /// The Amiga period of the note, which is lower for higher notes
+    pub period: u16,
+    /// How loud, from 0 to 64
+    pub volume: u8,
+}
+
+/// A row of the song starting
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Row {
+    /// Which entry in the song's order table we are playing
+    pub position: u8,
+    /// Which pattern that entry plays
+    pub pattern: u8,
+    /// Which row of the pattern this is
+    pub line: u8,
+    /// Is this the first row we've played from this entry in the order
+    /// table? A pattern break can make that a row other than 0.
+    pub new_pattern: bool,
+    /// The notes starting on each channel, if any
+    pub triggers: [Option<Trigger>; 4],
+}
+
+impl Row {
+    /// Does this row start a beat?
+    pub fn is_beat(&self) -> bool {
+        self.line.is_multiple_of(ROWS_PER_BEAT)
+    }
+}
+
+/// Things that want to know where the music is up to.
+///
+/// Both do nothing unless you say otherwise, so you only need to write the
+/// ones you want.
+pub trait Hooks {
+    /// A row has started
+    fn row(&mut self, _row: &Row) {}
+
+    /// A beat has started. This comes just after [`Hooks::row`] for the
+    /// same row.
+    fn beat(&mut self, _row: &Row) {}
+}
+
+/// For when nobody's listening
+impl Hooks for () {}
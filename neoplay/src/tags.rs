@@ -0,0 +1,78 @@
+//! Notes about each module, kept in a file next to it.
+//!
+//! `0:/MUSIC/SONG.MOD` has its notes in `0:/MUSIC/SONG.TAG`, which looks
+//! like:
+//!
+//! ```text
+//! [song]
+//! rating = 4
+//! ```
+//!
+//! Ratings go from 1 to [`MAX_RATING`], and a module with no rating counts
+//! as 0. Anything else in the `[song]` section, like tags you've added
+//! yourself, is kept when we change the rating.
+
+/// The best rating a module can have
+pub const MAX_RATING: u8 = 5;
+
+/// What the notes files end with
+const EXTENSION: &str = "TAG";
+
+/// Where we keep the notes about what's in a `[song]` section
+const SECTION: &str = "song";
+
+/// The biggest notes file we read
+const MAX_FILE: usize = 512;
+
+/// Work out the name of a module's notes file
+pub fn tag_file(module: &str) -> Result<neotron_fs::PathBuf, neotron_sdk::Error> {
+    let (directory, name) = neotron_fs::split(module);
+    let stem = match name.rsplit_once('.') {
+        Some((stem, _)) => stem,
+        None => name,
+    };
+    let mut path = neotron_fs::PathBuf::from_path(directory)?;
+    path.push_str(stem)?;
+    path.push_str(".")?;
+    path.push_str(EXTENSION)?;
+    Ok(path)
+}
+
+/// How a module is rated, or 0 if it isn't
+pub fn rating(module: &str) -> u8 {
+    let Ok(path) = tag_file(module) else {
+        return 0;
+    };
+    let mut buffer = [0u8; MAX_FILE];
+    neotron_config::load(path.as_str(), &mut buffer)
+        .ok()
+        .and_then(|config| config.get_u32(SECTION, "rating"))
+        .map_or(0, |rating| rating.min(u32::from(MAX_RATING)) as u8)
+}
+
+/// Rate a module, from 1 to [`MAX_RATING`], or 0 to forget its rating
+pub fn set_rating(module: &str, rating: u8) -> Result<(), neotron_config::Error> {
+    let path = tag_file(module)?;
+    let mut buffer = [0u8; MAX_FILE];
+    // No notes yet is fine, but we don't want to lose any that are there
+    let old = match neotron_config::load(path.as_str(), &mut buffer) {
+        Ok(config) => config,
+        Err(neotron_config::Error::Io(_)) => neotron_config::Config::empty(),
+        Err(e) => return Err(e),
+    };
+    // The old notes are still in the buffer, so we can write them back out
+    let mut writer = neotron_config::create(path.as_str())?;
+    let write = |writer: &mut neotron_config::Writer<_>| -> core::fmt::Result {
+        writer.section(SECTION)?;
+        if rating > 0 {
+            writer.set("rating", rating.min(MAX_RATING))?;
+        }
+        for (key, value) in old.entries(SECTION) {
+            if !key.eq_ignore_ascii_case("rating") {
+                writer.set_str(key, value)?;
+            }
+        }
+        Ok(())
+    };
+    write(&mut writer).map_err(|_| neotron_config::Error::Io(neotron_sdk::Error::DeviceSpecific))
+}
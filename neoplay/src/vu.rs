@@ -0,0 +1,97 @@
+//! Level meters, drawn with the eighth-block characters.
+//!
+//! Each meter is a row of characters that get taller from left to right, so
+//! the louder it is, the further along the bar lights up. The loudest point
+//! is held for a while after the level drops, and then falls away a step at
+//! a time.
+
+use core::fmt::Write;
+
+/// The characters we draw a meter with, from quietest to loudest
+const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// How many times a meter is drawn before the peak starts to fall
+const HOLD: u8 = 6;
+
+/// A meter for one side
+#[derive(Debug, Default, Copy, Clone)]
+pub struct Meter {
+    /// The loudest sample since we last drew the meter
+    level: u16,
+    /// How many characters the peak marker is along
+    peak: usize,
+    /// How many more times we draw the peak before it falls
+    hold: u8,
+}
+
+impl Meter {
+    /// Make a meter that shows silence
+    pub const fn new() -> Meter {
+        Meter {
+            level: 0,
+            peak: 0,
+            hold: 0,
+        }
+    }
+
+    /// Take a sample into account
+    pub fn add(&mut self, sample: i16) {
+        self.level = self.level.max(sample.unsigned_abs());
+    }
+
+    /// Draw the meter, and start measuring again
+    pub fn write<W: Write>(&mut self, out: &mut W) -> core::fmt::Result {
+        // Anything at all lights the first character
+        let lit = (usize::from(self.level) * BARS.len()).div_ceil(32768);
+        self.level = 0;
+        if lit >= self.peak {
+            self.peak = lit;
+            self.hold = HOLD;
+        } else if self.hold > 0 {
+            self.hold -= 1;
+        } else {
+            self.peak -= 1;
+        }
+        for (idx, bar) in BARS.iter().enumerate() {
+            if idx < lit || idx + 1 == self.peak {
+                out.write_char(*bar)?;
+            } else {
+                out.write_char(' ')?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A pair of meters, for the left and right
+#[derive(Debug, Default, Copy, Clone)]
+pub struct Stereo {
+    /// The left side
+    pub left: Meter,
+    /// The right side
+    pub right: Meter,
+}
+
+impl Stereo {
+    /// Make a pair of meters that show silence
+    pub const fn new() -> Stereo {
+        Stereo {
+            left: Meter::new(),
+            right: Meter::new(),
+        }
+    }
+
+    /// Take a stereo sample into account
+    pub fn add(&mut self, (left, right): (i16, i16)) {
+        self.left.add(left);
+        self.right.add(right);
+    }
+
+    /// Draw both meters, like `L ▁▂▃▄    R ▁▂▃  ▆  `
+    pub fn write<W: Write>(&mut self, out: &mut W) -> core::fmt::Result {
+        out.write_str("L ")?;
+        self.left.write(out)?;
+        out.write_str(" R ")?;
+        self.right.write(out)
+    }
+}
@@ -0,0 +1,91 @@
+//! Check what neoplay mixes, against recordings we know are right
+
+use neotron_golden::{assert_golden, golden, Run};
+
+/// Play it slowly and with no silence first, to keep the recording small
+const SETTINGS: &str = "[audio]\nsample_rate = 8000\nprebuffer = 0\n";
+
+/// Build a tiny four-channel ProTracker module.
+///
+/// There is one instrument, a looping square wave, and one pattern that
+/// plays a chord, changes volume, slides, and then breaks out after four
+/// rows so the song ends.
+fn tiny_module() -> Vec<u8> {
+    // Period, then effect and parameter, for each channel on each row
+    let rows: [[(u16, u8, u8); 4]; 4] = [
+        [
+            (428, 0x0, 0x00),
+            (339, 0xC, 0x20),
+            (285, 0x0, 0x00),
+            (0, 0x0, 0x00),
+        ],
+        [
+            (0, 0x1, 0x04),
+            (0, 0x0, 0x00),
+            (0, 0xA, 0x02),
+            (214, 0x0, 0x00),
+        ],
+        [
+            (0, 0x0, 0x00),
+            (0, 0x2, 0x04),
+            (0, 0x0, 0x00),
+            (0, 0xC, 0x10),
+        ],
+        [
+            (0, 0x0, 0x00),
+            (0, 0x0, 0x00),
+            (0, 0x0, 0x00),
+            (0, 0xD, 0x00),
+        ],
+    ];
+    let mut module = Vec::new();
+    module.extend_from_slice(b"golden test\0\0\0\0\0\0\0\0\0");
+    for sample in 0..31 {
+        let mut name = [0u8; 22];
+        if sample == 0 {
+            name[0..6].copy_from_slice(b"square");
+        }
+        module.extend_from_slice(&name);
+        // Length in words, finetune, volume, loop start and loop length
+        let (length, volume, repeat) = if sample == 0 { (32, 48, 32) } else { (0, 0, 1) };
+        module.extend_from_slice(&u16::to_be_bytes(length));
+        module.push(0);
+        module.push(volume);
+        module.extend_from_slice(&u16::to_be_bytes(0));
+        module.extend_from_slice(&u16::to_be_bytes(repeat));
+    }
+    // Song length, restart position, then the order table
+    module.push(1);
+    module.push(127);
+    module.extend_from_slice(&[0u8; 128]);
+    module.extend_from_slice(b"M.K.");
+    for row in 0..64 {
+        for channel in 0..4 {
+            let (period, effect, param) = rows.get(row).map_or((0, 0, 0), |r| r[channel]);
+            let sample: u8 = if period != 0 { 1 } else { 0 };
+            module.push((sample & 0xF0) | (period >> 8) as u8);
+            module.push(period as u8);
+            module.push((sample << 4) | effect);
+            module.push(param);
+        }
+    }
+    for idx in 0..64 {
+        module.push(if idx < 32 { 0x40 } else { 0xC0 });
+    }
+    module
+}
+
+/// Play the tiny module to the end, recording the sound
+#[test]
+fn mix_tiny_module() {
+    let output = Run::new(env!("CARGO_BIN_EXE_neoplay"))
+        .file("NEOPLAY.CFG", SETTINGS)
+        .file("TINY.MOD", tiny_module())
+        .arg("TINY.MOD")
+        .env("NEOTRON_AUDIO", "wav:TINY.WAV")
+        .env("NEOTRON_REALTIME", "0")
+        .run();
+    assert!(output.success);
+    assert_golden(golden!("mix_tiny_module.ansi"), &output.stdout);
+    assert_golden(golden!("mix_tiny_module.wav"), &output.file("TINY.WAV"));
+}
@@ -0,0 +1,149 @@
+//! Colours, and how text is styled.
+
+/// A colour from the 256-colour palette.
+///
+/// The first eight are the ANSI colours, and the next eight are their bright
+/// versions. After that come a 6x6x6 cube of colours, and then 24 greys.
+/// On a console with fewer colours we pick the nearest one it has.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Colour(pub u8);
+
+impl Colour {
+    /// Black
+    pub const BLACK: Colour = Colour(0);
+    /// Red
+    pub const RED: Colour = Colour(1);
+    /// Green
+    pub const GREEN: Colour = Colour(2);
+    /// Yellow, which is brown on some screens
+    pub const YELLOW: Colour = Colour(3);
+    /// Blue
+    pub const BLUE: Colour = Colour(4);
+    /// Magenta
+    pub const MAGENTA: Colour = Colour(5);
+    /// Cyan
+    pub const CYAN: Colour = Colour(6);
+    /// White, which is light grey on most screens
+    pub const WHITE: Colour = Colour(7);
+    /// Dark grey
+    pub const BRIGHT_BLACK: Colour = Colour(8);
+    /// Bright red
+    pub const BRIGHT_RED: Colour = Colour(9);
+    /// Bright green
+    pub const BRIGHT_GREEN: Colour = Colour(10);
+    /// Bright yellow
+    pub const BRIGHT_YELLOW: Colour = Colour(11);
+    /// Bright blue
+    pub const BRIGHT_BLUE: Colour = Colour(12);
+    /// Bright magenta
+    pub const BRIGHT_MAGENTA: Colour = Colour(13);
+    /// Bright cyan
+    pub const BRIGHT_CYAN: Colour = Colour(14);
+    /// Bright white
+    pub const BRIGHT_WHITE: Colour = Colour(15);
+
+    /// A colour from the cube, with red, green and blue each from 0 to 5
+    pub const fn rgb(red: u8, green: u8, blue: u8) -> Colour {
+        Colour(16 + 36 * min5(red) + 6 * min5(green) + min5(blue))
+    }
+
+    /// A grey, from 0 (nearly black) to 23 (nearly white)
+    pub const fn grey(level: u8) -> Colour {
+        Colour(232 + if level > 23 { 23 } else { level })
+    }
+
+    /// The nearest of the sixteen basic colours
+    pub const fn to_16(self) -> Colour {
+        match self.0 {
+            0..=15 => self,
+            16..=231 => {
+                let index = self.0 - 16;
+                let (red, green, blue) = (index / 36, index / 6 % 6, index % 6);
+                // Each part is on if it's more than half way, and the
+                // colour is bright if any part is nearly full
+                let base = (red >= 3) as u8 | ((green >= 3) as u8) << 1 | ((blue >= 3) as u8) << 2;
+                if red >= 4 || green >= 4 || blue >= 4 {
+                    Colour(base + 8)
+                } else {
+                    Colour(base)
+                }
+            }
+            232..=239 => Colour::BLACK,
+            240..=247 => Colour::BRIGHT_BLACK,
+            248..=251 => Colour::WHITE,
+            _ => Colour::BRIGHT_WHITE,
+        }
+    }
+}
+
+/// Keep a cube component in range
+const fn min5(value: u8) -> u8 {
+    if value > 5 {
+        5
+    } else {
+        value
+    }
+}
+
+/// How some text looks
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct Style {
+    /// The colour of the text, or `None` for the console's usual colour
+    pub fg: Option<Colour>,
+    /// The colour behind the text, or `None` for the console's usual colour
+    pub bg: Option<Colour>,
+    /// Bold, or bright
+    pub bold: bool,
+    /// Underlined
+    pub underline: bool,
+    /// Swap the text and background colours
+    pub reverse: bool,
+}
+
+impl Style {
+    /// Plain text, in the console's usual colours
+    pub const PLAIN: Style = Style {
+        fg: None,
+        bg: None,
+        bold: false,
+        underline: false,
+        reverse: false,
+    };
+
+    /// Text in this colour
+    pub const fn fg(colour: Colour) -> Style {
+        Style {
+            fg: Some(colour),
+            ..Style::PLAIN
+        }
+    }
+
+    /// The same, but on this background
+    pub const fn on(self, colour: Colour) -> Style {
+        Style {
+            bg: Some(colour),
+            ..self
+        }
+    }
+
+    /// The same, but bold
+    pub const fn bold(self) -> Style {
+        Style { bold: true, ..self }
+    }
+
+    /// The same, but underlined
+    pub const fn underline(self) -> Style {
+        Style {
+            underline: true,
+            ..self
+        }
+    }
+
+    /// The same, but in reverse video
+    pub const fn reverse(self) -> Style {
+        Style {
+            reverse: true,
+            ..self
+        }
+    }
+}
@@ -0,0 +1,239 @@
+//! Console output which suits what the terminal can do.
+//!
+//! Not every console understands the same escape sequences. A Neotron's own
+//! screen has sixteen colours, a serial terminal on a PC might have 256, and
+//! a teletype or a log file has none at all. A [`Writer`] is told which
+//! [`Level`] it is talking to, and turns colours and cursor movements into
+//! whatever that console understands - or leaves them out - so an
+//! application can draw the same way everywhere.
+//!
+//! Pick the level with [`detect`], which takes a setting from the user if
+//! there is one, and asks the console if not.
+
+#![no_std]
+#![deny(missing_docs)]
+#![deny(unsafe_code)]
+
+use core::fmt::Write;
+
+pub mod colour;
+
+pub use colour::{Colour, Style};
+
+/// How long we wait for the console to answer a probe
+const PROBE_MS: u32 = 200;
+
+/// How long we wait between looking for an answer
+const POLL_MS: u32 = 10;
+
+/// What a console can do
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    /// Plain text only. No colours, and no moving the cursor.
+    Dumb,
+    /// The sixteen ANSI colours, and cursor movement
+    Colour16,
+    /// The 256-colour palette, and cursor movement
+    Colour256,
+}
+
+impl Level {
+    /// What we assume if nobody tells us and the console doesn't say
+    pub const DEFAULT: Level = Level::Colour16;
+
+    /// Read a level from a setting, like `dumb`, `16` or `256`
+    pub fn parse(text: &str) -> Option<Level> {
+        let text = text.trim();
+        if text.eq_ignore_ascii_case("dumb") || text.eq_ignore_ascii_case("none") {
+            Some(Level::Dumb)
+        } else if text == "16" {
+            Some(Level::Colour16)
+        } else if text == "256" {
+            Some(Level::Colour256)
+        } else {
+            None
+        }
+    }
+
+    /// The name of the level, for a settings file
+    pub fn name(self) -> &'static str {
+        match self {
+            Level::Dumb => "dumb",
+            Level::Colour16 => "16",
+            Level::Colour256 => "256",
+        }
+    }
+
+    /// Can we move the cursor, and clear the screen?
+    pub fn can_move(self) -> bool {
+        self != Level::Dumb
+    }
+
+    /// Can we show colours?
+    pub fn has_colour(self) -> bool {
+        self != Level::Dumb
+    }
+}
+
+/// Work out what the console can do.
+///
+/// If the user has a setting, like `colours = 256` in a settings file, pass
+/// it in and we use that. Otherwise we [`probe`] the console, and if it
+/// doesn't answer we assume [`Level::DEFAULT`].
+pub fn detect(
+    setting: Option<&str>,
+    stdin: &neotron_sdk::File,
+    stdout: &mut neotron_sdk::File,
+) -> Level {
+    setting
+        .and_then(Level::parse)
+        .or_else(|| probe(stdin, stdout))
+        .unwrap_or(Level::DEFAULT)
+}
+
+/// Ask the console where its cursor is. Anything which answers understands
+/// escape sequences, so can do at least [`Level::Colour16`].
+///
+/// There's no standard way to ask how many colours a console has, so we
+/// never guess [`Level::Colour256`]. If the console doesn't answer, we can't
+/// tell whether it's dumb or just doesn't do this, so we return `None`.
+///
+/// This reads from stdin while it waits, so any keys pressed then are lost.
+/// A console which really is dumb shows the question as a bit of junk,
+/// which is one reason to let the user say what they've got.
+pub fn probe(stdin: &neotron_sdk::File, stdout: &mut neotron_sdk::File) -> Option<Level> {
+    stdout.write_str("\x1b[6n").ok()?;
+    // The answer looks like `ESC [ row ; col R`
+    let mut seen_escape = false;
+    let mut waited = 0;
+    while waited < PROBE_MS {
+        let mut buffer = [0u8; 1];
+        match stdin.read(&mut buffer) {
+            Ok(1) => match buffer[0] {
+                0x1B => seen_escape = true,
+                b'R' if seen_escape => return Some(Level::Colour16),
+                _ => {}
+            },
+            Ok(_) => {
+                neotron_sdk::delay(core::time::Duration::from_millis(u64::from(POLL_MS)));
+                waited += POLL_MS;
+            }
+            Err(_) => return None,
+        }
+    }
+    None
+}
+
+/// Writes text, with colours and cursor movements to suit the console.
+///
+/// Styles are only sent when they change, so it's cheap to set one before
+/// everything you write.
+pub struct Writer<W> {
+    out: W,
+    level: Level,
+    /// The style the console is using, if we know
+    style: Option<Style>,
+}
+
+impl<W: Write> Writer<W> {
+    /// Write to a console which can do this much
+    pub const fn new(out: W, level: Level) -> Writer<W> {
+        Writer {
+            out,
+            level,
+            style: None,
+        }
+    }
+
+    /// What the console can do
+    pub fn level(&self) -> Level {
+        self.level
+    }
+
+    /// Get back what we were writing to
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+
+    /// Use this style for whatever is written next
+    pub fn set_style(&mut self, style: Style) -> core::fmt::Result {
+        if self.level == Level::Dumb || self.style == Some(style) {
+            return Ok(());
+        }
+        self.style = Some(style);
+        // Start from the console's usual colours, so we don't need to know
+        // what was set before
+        self.out.write_str("\x1b[0")?;
+        let mut bold = style.bold;
+        if let Some(fg) = style.fg {
+            match self.level {
+                Level::Colour256 => write!(self.out, ";38;5;{}", fg.0)?,
+                _ => {
+                    // Bright colours are the normal ones in bold
+                    let fg = fg.to_16();
+                    bold |= fg.0 >= 8;
+                    write!(self.out, ";{}", 30 + fg.0 % 8)?;
+                }
+            }
+        }
+        if let Some(bg) = style.bg {
+            match self.level {
+                Level::Colour256 => write!(self.out, ";48;5;{}", bg.0)?,
+                _ => write!(self.out, ";{}", 40 + bg.to_16().0 % 8)?,
+            }
+        }
+        if bold {
+            self.out.write_str(";1")?;
+        }
+        if style.underline {
+            self.out.write_str(";4")?;
+        }
+        if style.reverse {
+            self.out.write_str(";7")?;
+        }
+        self.out.write_char('m')
+    }
+
+    /// Go back to the console's usual colours
+    pub fn reset(&mut self) -> core::fmt::Result {
+        self.set_style(Style::PLAIN)
+    }
+
+    /// Move the cursor, counting from zero. A dumb console can't, so this
+    /// does nothing - check [`Level::can_move`] first if it matters.
+    pub fn move_to(&mut self, row: u8, col: u8) -> core::fmt::Result {
+        if !self.level.can_move() {
+            return Ok(());
+        }
+        write!(
+            self.out,
+            "\x1b[{};{}H",
+            u16::from(row) + 1,
+            u16::from(col) + 1
+        )
+    }
+
+    /// Clear the screen and go to the top left. A dumb console just gets a
+    /// new line.
+    pub fn clear_screen(&mut self) -> core::fmt::Result {
+        if !self.level.can_move() {
+            return self.out.write_str("\r\n");
+        }
+        self.out.write_str("\x1b[2J\x1b[1;1H")
+    }
+
+    /// Show or hide the cursor
+    pub fn show_cursor(&mut self, visible: bool) -> core::fmt::Result {
+        if !self.level.can_move() {
+            return Ok(());
+        }
+        self.out
+            .write_str(if visible { "\x1b[?25h" } else { "\x1b[?25l" })
+    }
+}
+
+impl<W: Write> Write for Writer<W> {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        self.out.write_str(s)
+    }
+}
@@ -0,0 +1,150 @@
+//! The parts every full-screen Neotron application has.
+//!
+//! A [`Shell`] draws a title bar across the top of the screen and a row of
+//! key hints across the bottom, and pops up message boxes and questions in
+//! the middle. Everything in between is up to the application. Using the
+//! same shell means every utility looks and behaves the same way, without
+//! each one drawing its own.
+//!
+//! The bars are drawn with [`neotron_tui`], in the shell's [`Theme`], and
+//! the boxes read keys from a [`neotron_input::Keyboard`] until they are
+//! answered. [`preferred_theme`] gives the colours the user has picked for
+//! every application.
+
+#![no_std]
+#![deny(missing_docs)]
+#![deny(unsafe_code)]
+
+use core::fmt::Write;
+
+use neotron_sdk::console;
+use neotron_settings::shared::{self, ColourTheme};
+use neotron_tui::{Rect, Theme};
+
+pub mod modal;
+
+/// How many shared settings we read, looking for the theme
+const SETTINGS: usize = 16;
+
+/// The theme the user has picked in the shared settings file, or
+/// [`Theme::DEFAULT`] if they haven't picked one
+pub fn preferred_theme() -> Theme {
+    let mut settings: neotron_settings::Settings<SETTINGS> = neotron_settings::Settings::new();
+    // If the file's missing, or some of it doesn't fit, we use what we can
+    let _ = settings.load();
+    match settings.get(&shared::COLOUR_THEME) {
+        ColourTheme::Colour => Theme::DEFAULT,
+        ColourTheme::Mono => Theme::MONO,
+    }
+}
+
+/// One entry on the bottom row, like `F1 Help`
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Hint<'a> {
+    /// The key to press, like `F1` or `Q`
+    pub key: &'a str,
+    /// What it does, like `Help` or `Quit`
+    pub action: &'a str,
+}
+
+impl<'a> Hint<'a> {
+    /// Make a key hint
+    pub const fn new(key: &'a str, action: &'a str) -> Hint<'a> {
+        Hint { key, action }
+    }
+
+    /// How many columns it takes up, with the space between
+    fn width(&self) -> usize {
+        neotron_text::width(self.key) + 1 + neotron_text::width(self.action)
+    }
+}
+
+/// The title bar, key hints and message boxes for an application
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Shell<'a> {
+    /// The application's name, on the left of the title bar
+    pub title: &'a str,
+    /// How wide the screen is
+    pub width: u8,
+    /// How tall the screen is
+    pub height: u8,
+    /// What colours we draw in
+    pub theme: Theme,
+}
+
+impl<'a> Shell<'a> {
+    /// Make a shell for an application, on a screen of this size
+    pub const fn new(title: &'a str, width: u8, height: u8) -> Shell<'a> {
+        Shell {
+            title,
+            width,
+            height,
+            theme: Theme::DEFAULT,
+        }
+    }
+
+    /// Draw in these colours instead
+    pub const fn with_theme(self, theme: Theme) -> Shell<'a> {
+        Shell { theme, ..self }
+    }
+
+    /// The part of the screen between the title bar and the key hints,
+    /// which belongs to the application
+    pub const fn body(&self) -> Rect {
+        Rect::new(1, 0, self.width, self.height.saturating_sub(2))
+    }
+
+    /// Clear the screen, and draw the title bar and key hints
+    pub fn draw(&self, out: &mut neotron_sdk::File, detail: &str, hints: &[Hint]) {
+        self.theme.set_normal(out);
+        console::clear_screen(out);
+        self.draw_title(out, detail);
+        self.draw_hints(out, hints);
+    }
+
+    /// Draw the title bar, with the application's name on the left and some
+    /// detail, like the file that's open, on the right
+    pub fn draw_title(&self, out: &mut neotron_sdk::File, detail: &str) {
+        neotron_tui::StatusBar::new(0, self.width).draw(out, self.title, detail, &self.theme);
+    }
+
+    /// Draw the key hints along the bottom row.
+    ///
+    /// Hints which don't fit are left off, so put the important ones first.
+    /// We don't touch the last column, as writing there makes some consoles
+    /// scroll.
+    pub fn draw_hints(&self, out: &mut neotron_sdk::File, hints: &[Hint]) {
+        let width = usize::from(self.width).saturating_sub(1);
+        neotron_tui::move_to(out, self.height.saturating_sub(1), 0);
+        self.theme.set_selected(out);
+        let mut used = 0;
+        for hint in hints {
+            let gap = if used == 0 { 1 } else { 2 };
+            if used + gap + hint.width() > width {
+                break;
+            }
+            let _ = neotron_text::write_repeated(out, ' ', gap);
+            console::set_sgr(
+                out,
+                [
+                    console::SgrParam::Reset,
+                    self.theme.selected,
+                    console::SgrParam::Reverse,
+                    console::SgrParam::Bold,
+                ],
+            );
+            let _ = out.write_str(hint.key);
+            self.theme.set_selected(out);
+            let _ = write!(out, " {}", hint.action);
+            used += gap + hint.width();
+        }
+        let _ = neotron_text::write_repeated(out, ' ', width - used);
+        self.theme.set_normal(out);
+    }
+
+    /// Blank out the application's part of the screen
+    pub fn clear_body(&self, out: &mut neotron_sdk::File) {
+        self.theme.set_normal(out);
+        neotron_tui::clear_rect(out, self.body());
+    }
+}
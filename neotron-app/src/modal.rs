@@ -0,0 +1,67 @@
+//! Boxes which pop up over the application, and wait for an answer.
+//!
+//! Nothing else happens until the box is answered. The box is left on the
+//! screen afterwards, so redraw whatever was underneath it.
+
+use neotron_input::{KeyEvent, Keyboard};
+use neotron_tui::Dialog;
+
+use crate::Shell;
+
+impl Shell<'_> {
+    /// Tell the user something, and wait until they've seen it
+    pub fn message(
+        &self,
+        out: &mut neotron_sdk::File,
+        keyboard: &mut Keyboard,
+        title: &str,
+        lines: &[&str],
+    ) {
+        let _ = self.choose(out, keyboard, Dialog::new(title, lines, &["OK"]));
+    }
+
+    /// Ask a yes or no question. Escape means no.
+    ///
+    /// `No` is selected to start with, so a stray Enter doesn't do anything
+    /// drastic.
+    pub fn confirm(
+        &self,
+        out: &mut neotron_sdk::File,
+        keyboard: &mut Keyboard,
+        title: &str,
+        lines: &[&str],
+    ) -> bool {
+        let mut dialog = Dialog::new(title, lines, &["Yes", "No"]);
+        dialog.right();
+        self.choose(out, keyboard, dialog) == Some(0)
+    }
+
+    /// Show a dialog, and wait until a button is pressed. Returns which one,
+    /// or `None` if the user pressed Escape.
+    ///
+    /// The arrow keys and Tab move between the buttons, Enter presses the
+    /// selected one, and typing the first letter of a button presses that
+    /// one.
+    pub fn choose(
+        &self,
+        out: &mut neotron_sdk::File,
+        keyboard: &mut Keyboard,
+        mut dialog: Dialog,
+    ) -> Option<usize> {
+        loop {
+            dialog.draw(out, self.width, self.height, &self.theme);
+            match keyboard.wait() {
+                KeyEvent::Left | KeyEvent::BackTab => dialog.left(),
+                KeyEvent::Right | KeyEvent::Tab => dialog.right(),
+                KeyEvent::Enter => return Some(dialog.selected()),
+                KeyEvent::Escape => return None,
+                KeyEvent::Char(ch) => {
+                    if let Some(idx) = dialog.button_for(ch) {
+                        return Some(idx);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,277 @@
+//! Command-line arguments for Neotron applications.
+//!
+//! Describe your options in a [`Spec`], then pull the arguments out one at a
+//! time with a [`Parser`]:
+//!
+//! ```no_run
+//! use neotron_args::{Item, Opt, Parser, Spec};
+//!
+//! static SPEC: Spec = Spec {
+//!     name: "greet",
+//!     usage: "[options] <name>...",
+//!     about: "Says hello",
+//!     options: &[
+//!         Opt::flag('l', "loud", "Shout it"),
+//!         Opt::value('n', "times", "count", "Say it this many times"),
+//!     ],
+//! };
+//!
+//! fn real_main() -> Result<(), neotron_sdk::Error> {
+//!     let mut args = Parser::new(&SPEC);
+//!     let mut times = 1u32;
+//!     while let Some(item) = args.next_item()? {
+//!         match item {
+//!             Item::Flag("loud") => { /* ... */ }
+//!             Item::Value("times", value) => times = args.parse("times", &value)?,
+//!             Item::Positional(name) => { /* ... */ }
+//!             _ => {}
+//!         }
+//!     }
+//!     Ok(())
+//! }
+//! ```
+//!
+//! Short options can be grouped (`-lv`), and can have their value straight
+//! after them (`-n3`). Long options take a value either as the next argument
+//! or after an `=` (`--times=3`). Options and positional arguments can come
+//! in any order, and everything after `--` is positional. Everyone gets
+//! `-h` and `--help` for free.
+//!
+//! Problems are explained on standard output, so all the application has to
+//! do is give up - every [`Error`] turns into
+//! [`neotron_sdk::Error::InvalidArg`] with `?`.
+
+#![no_std]
+#![deny(missing_docs)]
+#![deny(unsafe_code)]
+
+use core::fmt::Write;
+
+mod parser;
+
+pub use parser::Parser;
+
+/// The longest argument we can handle, in bytes
+pub const MAX_ARG: usize = 128;
+
+/// An option a program understands
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Opt {
+    /// The single letter version, like `v` for `-v`
+    pub short: Option<char>,
+    /// The long version, like `verbose` for `--verbose`. This is also how the
+    /// option is named in an [`Item`].
+    pub long: &'static str,
+    /// What the value is called in the help, if the option takes one
+    pub value: Option<&'static str>,
+    /// One line explaining what the option does
+    pub help: &'static str,
+}
+
+impl Opt {
+    /// An option which is either there or not
+    pub const fn flag(short: char, long: &'static str, help: &'static str) -> Opt {
+        Opt {
+            short: Some(short),
+            long,
+            value: None,
+            help,
+        }
+    }
+
+    /// An option which only has a long version
+    pub const fn long_flag(long: &'static str, help: &'static str) -> Opt {
+        Opt {
+            short: None,
+            long,
+            value: None,
+            help,
+        }
+    }
+
+    /// An option which takes a value
+    pub const fn value(
+        short: char,
+        long: &'static str,
+        value: &'static str,
+        help: &'static str,
+    ) -> Opt {
+        Opt {
+            short: Some(short),
+            long,
+            value: Some(value),
+            help,
+        }
+    }
+
+    /// An option which takes a value, and only has a long version
+    pub const fn long_value(long: &'static str, value: &'static str, help: &'static str) -> Opt {
+        Opt {
+            short: None,
+            long,
+            value: Some(value),
+            help,
+        }
+    }
+}
+
+/// The help option everyone gets
+static HELP: Opt = Opt::flag('h', "help", "Show this help");
+
+/// Everything there is to know about a program's arguments
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Spec {
+    /// The program's name
+    pub name: &'static str,
+    /// What goes after the name on the usage line, like
+    /// `[options] <file>...`
+    pub usage: &'static str,
+    /// One line saying what the program does
+    pub about: &'static str,
+    /// The options the program understands
+    pub options: &'static [Opt],
+}
+
+impl Spec {
+    /// Find an option by its letter
+    fn find_short(&self, short: char) -> Option<&'static Opt> {
+        self.options
+            .iter()
+            .chain(core::iter::once(&HELP))
+            .find(|opt| opt.short == Some(short))
+    }
+
+    /// Find an option by its long name
+    fn find_long(&self, long: &str) -> Option<&'static Opt> {
+        self.options
+            .iter()
+            .chain(core::iter::once(&HELP))
+            .find(|opt| opt.long == long)
+    }
+
+    /// Write out the usage line, what the program does, and every option
+    pub fn write_help<W: Write>(&self, out: &mut W) -> core::fmt::Result {
+        self.write_usage(out)?;
+        writeln!(out, "{}", self.about)?;
+        writeln!(out)?;
+        writeln!(out, "Options:")?;
+        let options = || self.options.iter().chain(core::iter::once(&HELP));
+        let width = options().map(option_width).max().unwrap_or(0);
+        for opt in options() {
+            write!(out, "  ")?;
+            match opt.short {
+                Some(short) => write!(out, "-{}, ", short)?,
+                None => write!(out, "    ")?,
+            }
+            write!(out, "--{}", opt.long)?;
+            if let Some(value) = opt.value {
+                write!(out, " <{}>", value)?;
+            }
+            for _ in option_width(opt)..width {
+                out.write_char(' ')?;
+            }
+            writeln!(out, "  {}", opt.help)?;
+        }
+        Ok(())
+    }
+
+    /// Write out just the usage line
+    pub fn write_usage<W: Write>(&self, out: &mut W) -> core::fmt::Result {
+        writeln!(out, "Usage: {} {}", self.name, self.usage)
+    }
+}
+
+/// How wide an option is in the help, not counting the short version
+fn option_width(opt: &Opt) -> usize {
+    2 + opt.long.len() + opt.value.map_or(0, |value| value.len() + 3)
+}
+
+/// One argument, copied out of the OS
+#[derive(Clone, PartialEq, Eq)]
+pub struct Arg {
+    buffer: [u8; MAX_ARG],
+    len: usize,
+}
+
+impl Arg {
+    /// Copy some text. Gives `None` if it is longer than [`MAX_ARG`].
+    pub fn new(text: &str) -> Option<Arg> {
+        let mut arg = Arg {
+            buffer: [0; MAX_ARG],
+            len: text.len(),
+        };
+        arg.buffer
+            .get_mut(0..text.len())?
+            .copy_from_slice(text.as_bytes());
+        Some(arg)
+    }
+
+    /// Get the text
+    pub fn as_str(&self) -> &str {
+        // We only ever copy in whole strings, so this can't fail
+        core::str::from_utf8(&self.buffer[0..self.len]).unwrap_or("")
+    }
+}
+
+impl core::ops::Deref for Arg {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl core::fmt::Debug for Arg {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{:?}", self.as_str())
+    }
+}
+
+impl core::fmt::Display for Arg {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl PartialEq<&str> for Arg {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+/// Something found on the command line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Item {
+    /// An option without a value, named by its long version
+    Flag(&'static str),
+    /// An option and its value, named by its long version
+    Value(&'static str, Arg),
+    /// Something that isn't an option
+    Positional(Arg),
+}
+
+/// Something wrong with the arguments. By the time you see one of these, it
+/// has been explained on standard output.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// Help was asked for, and shown
+    Help,
+    /// An option we don't know about
+    Unknown,
+    /// An option that needs a value didn't get one
+    MissingValue,
+    /// An option that doesn't take a value was given one
+    UnexpectedValue,
+    /// A value didn't make sense
+    BadValue,
+    /// An argument was longer than [`MAX_ARG`]
+    TooLong,
+    /// The application didn't like the arguments, for its own reasons
+    Usage,
+}
+
+impl From<Error> for neotron_sdk::Error {
+    fn from(_error: Error) -> Self {
+        neotron_sdk::Error::InvalidArg
+    }
+}
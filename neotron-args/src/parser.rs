@@ -0,0 +1,158 @@
+//! Works through the arguments the OS gave us.
+
+use core::fmt::Write;
+
+use crate::{Arg, Error, Item, Opt, Spec, HELP};
+
+/// Pulls options and positional arguments off the command line, one at a
+/// time
+pub struct Parser {
+    spec: &'static Spec,
+    /// The next argument to ask the OS for
+    index: usize,
+    /// Part way through a group of short options, like `-abc`, and where we
+    /// are in it
+    group: Option<(Arg, usize)>,
+    /// We've seen `--`
+    only_positional: bool,
+}
+
+impl Parser {
+    /// Start at the first argument
+    pub const fn new(spec: &'static Spec) -> Parser {
+        Parser {
+            spec,
+            index: 0,
+            group: None,
+            only_positional: false,
+        }
+    }
+
+    /// The specification we are working from
+    pub fn spec(&self) -> &'static Spec {
+        self.spec
+    }
+
+    /// Get the next thing off the command line, or `None` at the end.
+    ///
+    /// If the user asked for help, it's shown and you get [`Error::Help`].
+    pub fn next_item(&mut self) -> Result<Option<Item>, Error> {
+        if let Some((group, offset)) = self.group.take() {
+            return self.short(group, offset).map(Some);
+        }
+        let Some(arg) = self.take()? else {
+            return Ok(None);
+        };
+        if self.only_positional || arg == "-" || !arg.starts_with('-') {
+            return Ok(Some(Item::Positional(arg)));
+        }
+        if arg == "--" {
+            self.only_positional = true;
+            return self.next_item();
+        }
+        if let Some(long) = arg.strip_prefix("--") {
+            let (name, value) = match long.split_once('=') {
+                Some((name, value)) => (name, Some(self.copy(value)?)),
+                None => (long, None),
+            };
+            let Some(opt) = self.spec.find_long(name) else {
+                return Err(self.report(Error::Unknown, format_args!("unknown option --{}", name)));
+            };
+            return self.found(opt, value).map(Some);
+        }
+        self.short(arg, 1).map(Some)
+    }
+
+    /// Turn a value into a number, or whatever else it should be. If it
+    /// doesn't make sense, we say so, naming the option by its long version.
+    pub fn parse<T: core::str::FromStr>(&self, long: &str, value: &str) -> Result<T, Error> {
+        value.parse().map_err(|_| {
+            self.report(
+                Error::BadValue,
+                format_args!("bad value for --{}: {}", long, value),
+            )
+        })
+    }
+
+    /// Complain about the arguments, for some reason the parser couldn't
+    /// spot - like a missing file name
+    pub fn usage_error(&self, message: &str) -> Error {
+        self.report(Error::Usage, format_args!("{}", message))
+    }
+
+    /// Show the help
+    pub fn print_help(&self) {
+        let _ = self.spec.write_help(&mut neotron_sdk::stdout());
+    }
+
+    /// Deal with the next letter in a group of short options
+    fn short(&mut self, group: Arg, offset: usize) -> Result<Item, Error> {
+        let Some(letter) = group[offset..].chars().next() else {
+            return Err(self.report(Error::Unknown, format_args!("unknown option -")));
+        };
+        let Some(opt) = self.spec.find_short(letter) else {
+            return Err(self.report(Error::Unknown, format_args!("unknown option -{}", letter)));
+        };
+        let after = offset + letter.len_utf8();
+        if opt.value.is_some() && after < group.len() {
+            // The value is stuck on the end, like -w40
+            let value = self.copy(&group[after..])?;
+            return self.found(opt, Some(value));
+        }
+        if after < group.len() {
+            self.group = Some((group, after));
+        }
+        self.found(opt, None)
+    }
+
+    /// We've found an option, and maybe a value for it already
+    fn found(&mut self, opt: &'static Opt, value: Option<Arg>) -> Result<Item, Error> {
+        if core::ptr::eq(opt, &HELP) {
+            self.print_help();
+            return Err(Error::Help);
+        }
+        match (opt.value, value) {
+            (None, None) => Ok(Item::Flag(opt.long)),
+            (None, Some(_)) => Err(self.report(
+                Error::UnexpectedValue,
+                format_args!("option --{} doesn't take a value", opt.long),
+            )),
+            (Some(_), Some(value)) => Ok(Item::Value(opt.long, value)),
+            (Some(_), None) => match self.take()? {
+                Some(value) => Ok(Item::Value(opt.long, value)),
+                None => Err(self.report(
+                    Error::MissingValue,
+                    format_args!("option --{} needs a value", opt.long),
+                )),
+            },
+        }
+    }
+
+    /// Get the next argument from the OS
+    fn take(&mut self) -> Result<Option<Arg>, Error> {
+        let Some(arg) = neotron_sdk::arg(self.index) else {
+            return Ok(None);
+        };
+        self.index += 1;
+        self.copy(&arg).map(Some)
+    }
+
+    /// Copy some text into an argument
+    fn copy(&self, text: &str) -> Result<Arg, Error> {
+        Arg::new(text).ok_or_else(|| {
+            self.report(
+                Error::TooLong,
+                format_args!("argument too long (the most is {})", crate::MAX_ARG),
+            )
+        })
+    }
+
+    /// Explain what went wrong, and how to get help
+    fn report(&self, error: Error, message: core::fmt::Arguments) -> Error {
+        let mut stdout = neotron_sdk::stdout();
+        let _ = writeln!(stdout, "{}: {}", self.spec.name, message);
+        let _ = self.spec.write_usage(&mut stdout);
+        let _ = writeln!(stdout, "Try '{} --help' for more.", self.spec.name);
+        error
+    }
+}
@@ -0,0 +1,152 @@
+//! Packing data, in a build script.
+//!
+//! Only available with the `build` feature, as it needs `std`.
+
+extern crate std;
+
+use std::{path::Path, vec, vec::Vec};
+
+use crate::{HEADER_LEN, MIN_MATCH};
+
+/// The longest run of bytes we copy in one go, which fits in four bits
+const MAX_MATCH: usize = MIN_MATCH + 15;
+
+/// How far back we can copy from, which fits in twelve bits
+const WINDOW: usize = 4096;
+
+/// How many earlier places we try, when looking for a match
+const MAX_TRIES: usize = 256;
+
+/// How many different hashes we sort earlier places by
+const HASH_SIZE: usize = 4096;
+
+/// Marks the end of a chain of earlier places
+const NONE: usize = usize::MAX;
+
+/// Pack a file into `OUT_DIR`, so the application can use it with
+/// [`include_asset!`](crate::include_asset).
+///
+/// The `source` path is relative to the crate being built. Cargo is told to
+/// run the build script again if the file changes.
+///
+/// # Panics
+///
+/// If the file can't be read or written - we're in a build script, so
+/// stopping the build is the best thing to do.
+pub fn embed(source: &str, name: &str) {
+    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").expect("CARGO_MANIFEST_DIR not set");
+    let path = Path::new(&manifest_dir).join(source);
+    std::println!("cargo:rerun-if-changed={}", path.display());
+    let data = std::fs::read(&path)
+        .unwrap_or_else(|e| std::panic!("Can't read {}: {}", path.display(), e));
+    let out_dir = std::env::var("OUT_DIR").expect("OUT_DIR not set");
+    let out_path = Path::new(&out_dir).join(name);
+    std::fs::write(&out_path, pack(&data))
+        .unwrap_or_else(|e| std::panic!("Can't write {}: {}", out_path.display(), e));
+}
+
+/// Pack some data, so [`unpack`](crate::unpack) can get it back.
+///
+/// # Panics
+///
+/// If there's more than 4 GiB of it.
+pub fn pack(data: &[u8]) -> Vec<u8> {
+    let len = u32::try_from(data.len()).expect("Too much data to pack");
+    let mut out = Vec::with_capacity(HEADER_LEN + data.len() + data.len() / 8 + 1);
+    out.extend_from_slice(&len.to_le_bytes());
+
+    let mut places = Places::new(data.len());
+    let mut pos = 0;
+    let mut flags_at = 0;
+    let mut item = 8;
+    while pos < data.len() {
+        if item == 8 {
+            flags_at = out.len();
+            out.push(0);
+            item = 0;
+        }
+        match places.longest_match(data, pos) {
+            Some((distance, count)) => {
+                let code = distance - 1;
+                out.push((code >> 4) as u8);
+                out.push(((code & 0x0F) << 4 | (count - MIN_MATCH)) as u8);
+                for skipped in pos..pos + count {
+                    places.add(data, skipped);
+                }
+                pos += count;
+            }
+            None => {
+                out[flags_at] |= 1 << item;
+                out.push(data[pos]);
+                places.add(data, pos);
+                pos += 1;
+            }
+        }
+        item += 1;
+    }
+    out
+}
+
+/// Remembers where each run of three bytes has been seen before
+struct Places {
+    /// The latest place for each hash
+    latest: Vec<usize>,
+    /// The place before each place with the same hash
+    previous: Vec<usize>,
+}
+
+impl Places {
+    /// Make an empty set of places, for this much data
+    fn new(len: usize) -> Places {
+        Places {
+            latest: vec![NONE; HASH_SIZE],
+            previous: vec![NONE; len],
+        }
+    }
+
+    /// Which chain the bytes at `pos` go on
+    fn hash(data: &[u8], pos: usize) -> usize {
+        let bytes = &data[pos..pos + MIN_MATCH];
+        (usize::from(bytes[0]) << 8 ^ usize::from(bytes[1]) << 4 ^ usize::from(bytes[2]))
+            % HASH_SIZE
+    }
+
+    /// Remember the bytes at `pos`
+    fn add(&mut self, data: &[u8], pos: usize) {
+        if pos + MIN_MATCH > data.len() {
+            return;
+        }
+        let hash = Places::hash(data, pos);
+        self.previous[pos] = self.latest[hash];
+        self.latest[hash] = pos;
+    }
+
+    /// Find the longest run earlier on which matches the bytes at `pos`, as
+    /// (distance back, length)
+    fn longest_match(&self, data: &[u8], pos: usize) -> Option<(usize, usize)> {
+        if pos + MIN_MATCH > data.len() {
+            return None;
+        }
+        let longest = MAX_MATCH.min(data.len() - pos);
+        let mut best: Option<(usize, usize)> = None;
+        let mut place = self.latest[Places::hash(data, pos)];
+        for _ in 0..MAX_TRIES {
+            if place == NONE || pos - place > WINDOW {
+                break;
+            }
+            let count = data[place..]
+                .iter()
+                .zip(&data[pos..pos + longest])
+                .take_while(|(a, b)| a == b)
+                .count();
+            if count >= MIN_MATCH && best.is_none_or(|(_, best_count)| count > best_count) {
+                best = Some((pos - place, count));
+                if count == longest {
+                    break;
+                }
+            }
+            place = self.previous[place];
+        }
+        best
+    }
+}
@@ -0,0 +1,135 @@
+//! Compressed data built into Neotron applications.
+//!
+//! Word lists, levels, fonts and samples can take up a lot of room in a
+//! binary, and there isn't much flash to go round. This crate packs them
+//! when the application is built, and unpacks them into a buffer when the
+//! application wants them.
+//!
+//! In the application's `build.rs`, with this crate as a build dependency
+//! with the `build` feature turned on:
+//!
+//! ```rust,ignore
+//! fn main() {
+//!     neotron_asset::build::embed("assets/words.txt", "words.lz");
+//! }
+//! ```
+//!
+//! And then in the application:
+//!
+//! ```rust,ignore
+//! const WORDS: neotron_asset::Asset = neotron_asset::include_asset!("words.lz");
+//!
+//! let mut buffer = [0u8; 4096];
+//! let len = WORDS.unpack(&mut buffer)?;
+//! ```
+//!
+//! The packing is a simple LZSS, which unpacks quickly with no memory
+//! beyond the buffer it unpacks into. Text usually packs to about half its
+//! size.
+
+#![no_std]
+#![deny(missing_docs)]
+#![deny(unsafe_code)]
+
+#[cfg(feature = "build")]
+pub mod build;
+
+/// The shortest run of bytes worth copying from earlier on
+const MIN_MATCH: usize = 3;
+
+/// The unpacked length goes at the start, as a little-endian `u32`
+const HEADER_LEN: usize = 4;
+
+/// Why some data couldn't be unpacked
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// The buffer is smaller than the unpacked data
+    TooSmall,
+    /// The packed data doesn't make sense
+    Corrupt,
+}
+
+/// Some packed data, built into the application
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Asset {
+    packed: &'static [u8],
+}
+
+impl Asset {
+    /// Wrap some packed data. See [`include_asset!`].
+    pub const fn new(packed: &'static [u8]) -> Asset {
+        Asset { packed }
+    }
+
+    /// How big a buffer the data needs, once unpacked
+    pub fn len(&self) -> usize {
+        unpacked_len(self.packed).unwrap_or(0)
+    }
+
+    /// Is there no data?
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Unpack the data into the start of a buffer. Returns how many bytes
+    /// it took up.
+    pub fn unpack(&self, buffer: &mut [u8]) -> Result<usize, Error> {
+        unpack(self.packed, buffer)
+    }
+}
+
+/// Build in some data packed by `build::embed` in the build script, giving
+/// an [`Asset`]
+#[macro_export]
+macro_rules! include_asset {
+    ($name:literal) => {
+        $crate::Asset::new(include_bytes!(concat!(env!("OUT_DIR"), "/", $name)))
+    };
+}
+
+/// How long some packed data is once it's unpacked
+pub fn unpacked_len(packed: &[u8]) -> Result<usize, Error> {
+    let header = packed.get(0..HEADER_LEN).ok_or(Error::Corrupt)?;
+    let len = u32::from_le_bytes([header[0], header[1], header[2], header[3]]);
+    usize::try_from(len).map_err(|_| Error::TooSmall)
+}
+
+/// Unpack some packed data into the start of a buffer. Returns how many
+/// bytes it took up.
+///
+/// The data comes in groups of up to eight items, after a byte of flags
+/// which say what each item is. A set flag means the item is one byte to
+/// copy out as it is. A clear flag means the item is two bytes, which say
+/// to copy 3 to 18 bytes from up to 4096 bytes back.
+pub fn unpack(packed: &[u8], buffer: &mut [u8]) -> Result<usize, Error> {
+    let len = unpacked_len(packed)?;
+    let buffer = buffer.get_mut(0..len).ok_or(Error::TooSmall)?;
+    let mut input = packed[HEADER_LEN..].iter().copied();
+    let mut next = || input.next().ok_or(Error::Corrupt);
+    let mut pos = 0;
+    // The top bits are a marker, so we know when we need more flags
+    let mut flags: u16 = 0;
+    while pos < len {
+        flags >>= 1;
+        if flags & 0x100 == 0 {
+            flags = u16::from(next()?) | 0xFF00;
+        }
+        if flags & 1 != 0 {
+            buffer[pos] = next()?;
+            pos += 1;
+        } else {
+            let (high, low) = (next()?, next()?);
+            let distance = (usize::from(high) << 4 | usize::from(low >> 4)) + 1;
+            let count = usize::from(low & 0x0F) + MIN_MATCH;
+            if distance > pos || pos + count > len {
+                return Err(Error::Corrupt);
+            }
+            // This can overlap what it's writing, which repeats a pattern
+            for _ in 0..count {
+                buffer[pos] = buffer[pos - distance];
+                pos += 1;
+            }
+        }
+    }
+    Ok(len)
+}
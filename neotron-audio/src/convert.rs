@@ -0,0 +1,57 @@
+//! Turning samples into the bytes the audio device wants, and back.
+//!
+//! We work in signed 16-bit samples. MOD files store signed 8-bit samples,
+//! WAV files use unsigned 8-bit ones, and the audio device takes 16-bit
+//! little-endian stereo, left then right.
+
+/// How many bytes one 16-bit stereo frame takes
+pub const FRAME_LEN: usize = 4;
+
+/// Widen a signed 8-bit sample, like a MOD file has
+pub const fn from_i8(sample: i8) -> i16 {
+    (sample as i16) << 8
+}
+
+/// Widen an unsigned 8-bit sample, like an 8-bit WAV file has
+pub const fn from_u8(sample: u8) -> i16 {
+    ((sample as i16) - 128) << 8
+}
+
+/// Narrow a sample to unsigned 8-bit, like an 8-bit WAV file has
+pub const fn to_u8(sample: i16) -> u8 {
+    ((sample >> 8) + 128) as u8
+}
+
+/// Narrow a sample to signed 8-bit
+pub const fn to_i8(sample: i16) -> i8 {
+    (sample >> 8) as i8
+}
+
+/// Put one stereo frame into the first [`FRAME_LEN`] bytes of `out`
+pub fn write_frame(out: &mut [u8], left: i16, right: i16) {
+    out[0..2].copy_from_slice(&left.to_le_bytes());
+    out[2..4].copy_from_slice(&right.to_le_bytes());
+}
+
+/// Fill a buffer with stereo frames, asking for each one in turn
+pub fn fill_stereo<F>(buffer: &mut [u8], mut next: F)
+where
+    F: FnMut() -> (i16, i16),
+{
+    for chunk in buffer.chunks_exact_mut(FRAME_LEN) {
+        let (left, right) = next();
+        write_frame(chunk, left, right);
+    }
+}
+
+/// Fill a buffer with stereo frames, asking for a mono sample for each one
+/// and sending it to both sides
+pub fn fill_mono<F>(buffer: &mut [u8], mut next: F)
+where
+    F: FnMut() -> i16,
+{
+    for chunk in buffer.chunks_exact_mut(FRAME_LEN) {
+        let sample = next();
+        write_frame(chunk, sample, sample);
+    }
+}
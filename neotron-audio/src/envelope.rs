@@ -0,0 +1,160 @@
+//! Envelopes, which shape how loud a note is over time.
+
+/// The loudest an envelope gets
+pub const FULL: u32 = 256;
+
+/// The shape of an envelope.
+///
+/// Times are in samples, so they depend on the sample rate - use
+/// [`Adsr::from_ms`] to give them in milliseconds instead.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Adsr {
+    /// How long it takes to get to full volume
+    pub attack: u32,
+    /// How long it takes to fall from full volume to the sustain level
+    pub decay: u32,
+    /// The level we hold the note at, out of [`FULL`]
+    pub sustain: u32,
+    /// How long it takes to fade to nothing once the note is released
+    pub release: u32,
+}
+
+impl Adsr {
+    /// An envelope that is full volume for as long as the note is held
+    pub const ORGAN: Adsr = Adsr {
+        attack: 0,
+        decay: 0,
+        sustain: FULL,
+        release: 0,
+    };
+
+    /// Give the times in milliseconds
+    pub const fn from_ms(
+        attack_ms: u32,
+        decay_ms: u32,
+        sustain: u32,
+        release_ms: u32,
+        sample_rate: u32,
+    ) -> Adsr {
+        Adsr {
+            attack: attack_ms * (sample_rate / 1000),
+            decay: decay_ms * (sample_rate / 1000),
+            sustain: if sustain > FULL { FULL } else { sustain },
+            release: release_ms * (sample_rate / 1000),
+        }
+    }
+}
+
+/// Where an envelope has got to
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Stage {
+    Idle,
+    Attack,
+    Decay,
+    Sustain,
+    Release,
+}
+
+/// Follows an [`Adsr`] shape, one sample at a time.
+///
+/// Call [`Envelope::trigger`] when the note starts and
+/// [`Envelope::release`] when it stops, and multiply each sample by what
+/// [`Envelope::next_level`] gives you.
+#[derive(Debug, Copy, Clone)]
+pub struct Envelope {
+    /// The shape we follow
+    pub adsr: Adsr,
+    stage: Stage,
+    /// How many samples we've been in this stage
+    age: u32,
+    /// The level we were at when we moved to this stage
+    from: u32,
+    /// The level we gave last
+    level: u32,
+}
+
+impl Envelope {
+    /// Make a silent envelope, with the given shape
+    pub const fn new(adsr: Adsr) -> Envelope {
+        Envelope {
+            adsr,
+            stage: Stage::Idle,
+            age: 0,
+            from: 0,
+            level: 0,
+        }
+    }
+
+    /// Start the note. If it was already playing, we go up from wherever
+    /// it had got to, so there's no click.
+    pub fn trigger(&mut self) {
+        self.enter(Stage::Attack);
+    }
+
+    /// Let go of the note, so it fades away
+    pub fn release(&mut self) {
+        if self.stage != Stage::Idle {
+            self.enter(Stage::Release);
+        }
+    }
+
+    /// Stop straight away
+    pub fn silence(&mut self) {
+        self.enter(Stage::Idle);
+        self.level = 0;
+    }
+
+    /// Is the note still making a sound?
+    pub fn is_active(&self) -> bool {
+        self.stage != Stage::Idle
+    }
+
+    /// Has the note been let go of, or finished?
+    pub fn is_released(&self) -> bool {
+        matches!(self.stage, Stage::Release | Stage::Idle)
+    }
+
+    /// Work out the level for the next sample, out of [`FULL`]
+    pub fn next_level(&mut self) -> u32 {
+        let adsr = self.adsr;
+        self.level = match self.stage {
+            Stage::Idle => 0,
+            Stage::Attack if self.age < adsr.attack => {
+                self.from + (FULL - self.from) * self.age / adsr.attack
+            }
+            Stage::Attack => {
+                self.enter(Stage::Decay);
+                FULL
+            }
+            Stage::Decay if self.age < adsr.decay => {
+                FULL - (FULL - adsr.sustain) * self.age / adsr.decay
+            }
+            Stage::Decay => {
+                self.enter(Stage::Sustain);
+                adsr.sustain
+            }
+            Stage::Sustain => adsr.sustain,
+            Stage::Release if self.age < adsr.release => {
+                self.from * (adsr.release - self.age) / adsr.release
+            }
+            Stage::Release => {
+                self.enter(Stage::Idle);
+                0
+            }
+        };
+        self.age += 1;
+        self.level
+    }
+
+    /// Apply the next level to a sample
+    pub fn apply(&mut self, sample: i16) -> i16 {
+        (i32::from(sample) * self.next_level() as i32 / FULL as i32) as i16
+    }
+
+    /// Move on to another stage, starting from the level we're at now
+    fn enter(&mut self, stage: Stage) {
+        self.stage = stage;
+        self.age = 0;
+        self.from = self.level;
+    }
+}
@@ -0,0 +1,151 @@
+//! Sound for Neotron applications.
+//!
+//! The audio device takes 16-bit stereo samples, and plays them at the rate
+//! you ask for. This crate has the pieces for making those samples:
+//!
+//! * [`Oscillator`] makes a square, triangle, sawtooth or noise wave at a
+//!   given pitch.
+//! * [`Envelope`] shapes how loud a note is, from an [`Adsr`].
+//! * [`Mixer`] adds channels together without wrapping around.
+//! * [`Resampler`] changes the sample rate, for when the device can't run
+//!   at the rate you mix at.
+//! * [`Limiter`] keeps the sound under a ceiling, so nothing comes out
+//!   louder than you expect.
+//! * [`convert`] turns samples into the bytes the device wants, and widens
+//!   8-bit samples from files.
+//! * [`open`] gets you the [`Device`] to send them to. On a PC that is the
+//!   sound card, through `neotron-host`.
+//!
+//! Everything works in integers, one sample at a time, so it is cheap
+//! enough to run alongside a game.
+
+#![no_std]
+#![deny(missing_docs)]
+#![deny(unsafe_code)]
+
+pub mod convert;
+mod envelope;
+mod limiter;
+mod mixer;
+mod oscillator;
+mod resample;
+
+pub use envelope::{Adsr, Envelope, FULL};
+pub use limiter::{Limiter, MAX_CEILING_DB};
+pub use mixer::{clip, Mixer, CENTRE};
+pub use oscillator::{Oscillator, Waveform};
+pub use resample::Resampler;
+
+/// The sample rate most of our applications ask for
+pub const SAMPLE_RATE: u32 = 44100;
+
+/// The rates we try, best first, when the device won't do the one we asked
+/// for
+#[cfg(target_os = "none")]
+const COMMON_RATES: [u32; 5] = [44100, 48000, 22050, 32000, 11025];
+
+/// The ioctl which sets the format and sample rate
+#[cfg(target_os = "none")]
+const IOCTL_SET_CONFIG: u64 = 1;
+
+/// The ioctl which reports the format and sample rate
+#[cfg(target_os = "none")]
+const IOCTL_GET_CONFIG: u64 = 0;
+
+/// The format part of a config, for 16-bit stereo
+#[cfg(target_os = "none")]
+const FORMAT_16_BIT_STEREO: u64 = 3 << 60;
+
+/// The audio device, ready for 16-bit stereo
+pub struct Device {
+    #[cfg(target_os = "none")]
+    file: neotron_sdk::File,
+    /// When we're running on a PC, the sound goes to its sound card instead
+    #[cfg(not(target_os = "none"))]
+    sink: neotron_host::audio::Sink,
+    sample_rate: u32,
+}
+
+impl Device {
+    /// The rate the device is playing at
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Send some frames to be played, waiting until there is room for them
+    pub fn write(&self, data: &[u8]) -> neotron_sdk::Result<()> {
+        #[cfg(target_os = "none")]
+        {
+            self.file.write(data)
+        }
+        #[cfg(not(target_os = "none"))]
+        {
+            self.sink
+                .write(data)
+                .map_err(|_| neotron_sdk::Error::DeviceSpecific)
+        }
+    }
+}
+
+/// Open the audio device, and set it for 16-bit stereo at the given rate
+#[cfg(target_os = "none")]
+pub fn open(sample_rate: u32) -> neotron_sdk::Result<Device> {
+    let path = neotron_sdk::path::Path::new("AUDIO:")?;
+    let file = neotron_sdk::File::open(path, neotron_sdk::Flags::empty())?;
+    if file
+        .ioctl(
+            IOCTL_SET_CONFIG,
+            FORMAT_16_BIT_STEREO | u64::from(sample_rate),
+        )
+        .is_err()
+    {
+        return Err(neotron_sdk::Error::DeviceSpecific);
+    }
+    Ok(Device { file, sample_rate })
+}
+
+/// Open the audio device for 16-bit stereo, at the given rate if it can do
+/// it, or else at the first of the usual rates that it can.
+///
+/// Check [`Device::sample_rate`] to see which rate you got.
+#[cfg(target_os = "none")]
+pub fn open_nearest(sample_rate: u32) -> neotron_sdk::Result<Device> {
+    let path = neotron_sdk::path::Path::new("AUDIO:")?;
+    let file = neotron_sdk::File::open(path, neotron_sdk::Flags::empty())?;
+    for rate in core::iter::once(sample_rate).chain(COMMON_RATES) {
+        if file
+            .ioctl(IOCTL_SET_CONFIG, FORMAT_16_BIT_STEREO | u64::from(rate))
+            .is_ok()
+        {
+            // The device might have rounded it, so ask what we got
+            let sample_rate = file
+                .ioctl(IOCTL_GET_CONFIG, 0)
+                .map_or(rate, |config| config as u32);
+            return Ok(Device { file, sample_rate });
+        }
+    }
+    Err(neotron_sdk::Error::DeviceSpecific)
+}
+
+/// Open the computer's sound card, for 16-bit stereo at the given rate
+#[cfg(not(target_os = "none"))]
+pub fn open(sample_rate: u32) -> neotron_sdk::Result<Device> {
+    let device = open_nearest(sample_rate)?;
+    if device.sample_rate != sample_rate {
+        return Err(neotron_sdk::Error::DeviceSpecific);
+    }
+    Ok(device)
+}
+
+/// Open the computer's sound card for 16-bit stereo, at the given rate
+/// unless `NEOTRON_AUDIO_RATE` says otherwise.
+///
+/// Check [`Device::sample_rate`] to see which rate you got.
+#[cfg(not(target_os = "none"))]
+pub fn open_nearest(sample_rate: u32) -> neotron_sdk::Result<Device> {
+    let sink = neotron_host::audio::Sink::open(sample_rate);
+    Ok(Device {
+        sample_rate: sink.sample_rate(),
+        sink,
+    })
+}
@@ -0,0 +1,119 @@
+//! Keeping the sound below a ceiling.
+
+/// The fixed-point one, for the gain
+const ONE: u32 = 1 << 16;
+
+/// How loud a sample can be at each number of dB below full volume
+const DB_LEVELS: [u16; 25] = [
+    32767, 29204, 26028, 23197, 20675, 18426, 16422, 14636, 13045, 11626, 10362, 9235, 8231, 7336,
+    6538, 5827, 5193, 4628, 4125, 3677, 3277, 2920, 2603, 2320, 2067,
+];
+
+/// The furthest below full volume the ceiling can go, in dB
+pub const MAX_CEILING_DB: u8 = DB_LEVELS.len() as u8 - 1;
+
+/// How long the gain takes to come back up after something loud, in
+/// milliseconds, roughly
+const RELEASE_MS: u32 = 200;
+
+/// A gain this far below one counts as limiting, so tiny changes don't
+/// flicker an indicator on and off
+const LIMITING: u32 = ONE - ONE / 32;
+
+/// Keeps stereo frames below a ceiling, for the sake of anyone's ears.
+///
+/// Quieter sounds go through untouched. From 6 dB below the ceiling we
+/// start turning things down, a little at first and more the louder they
+/// get, so the sound never gets past the ceiling - that's the soft knee.
+/// The gain drops within a few samples when something loud comes along,
+/// and comes back up slowly afterwards, so it doesn't pump.
+#[derive(Debug, Copy, Clone)]
+pub struct Limiter {
+    ceiling_db: u8,
+    /// The loudest a sample can be
+    ceiling: i32,
+    /// Where we start turning things down
+    knee: i32,
+    /// How much we're turning things down, out of [`ONE`]
+    gain: u32,
+    /// The gain moves this fraction of the way back up each sample, as a
+    /// shift
+    release_shift: u32,
+    /// The lowest the gain has been since [`Limiter::was_limiting`] was
+    /// last asked
+    lowest: u32,
+}
+
+impl Limiter {
+    /// Make a limiter with its ceiling this many dB below full volume, up
+    /// to [`MAX_CEILING_DB`], for sound at this sample rate
+    pub const fn new(ceiling_db: u8, sample_rate: u32) -> Limiter {
+        let ceiling_db = if ceiling_db > MAX_CEILING_DB {
+            MAX_CEILING_DB
+        } else {
+            ceiling_db
+        };
+        let ceiling = DB_LEVELS[ceiling_db as usize] as i32;
+        let release_samples = sample_rate / 1000 * RELEASE_MS;
+        Limiter {
+            ceiling_db,
+            ceiling,
+            knee: ceiling / 2,
+            gain: ONE,
+            release_shift: (u32::BITS - 1).saturating_sub(release_samples.leading_zeros()),
+            lowest: ONE,
+        }
+    }
+
+    /// How far below full volume the ceiling is, in dB
+    pub fn ceiling_db(&self) -> u8 {
+        self.ceiling_db
+    }
+
+    /// Turn a frame down, if it needs it
+    pub fn next(&mut self, (left, right): (i16, i16)) -> (i16, i16) {
+        let peak = i32::from(left).abs().max(i32::from(right).abs());
+        let target = self.target_gain(peak);
+        if target < self.gain {
+            // Fast attack - half way there every sample
+            self.gain -= (self.gain - target).div_ceil(2);
+        } else {
+            // Slow release
+            self.gain += ((target - self.gain) >> self.release_shift).max(1);
+            self.gain = self.gain.min(target);
+        }
+        self.lowest = self.lowest.min(self.gain);
+        (self.scale(left), self.scale(right))
+    }
+
+    /// Is the limiter turning things down right now?
+    pub fn is_limiting(&self) -> bool {
+        self.gain < LIMITING
+    }
+
+    /// Has the limiter turned anything down since we last asked?
+    pub fn was_limiting(&mut self) -> bool {
+        let result = self.lowest < LIMITING;
+        self.lowest = self.gain;
+        result
+    }
+
+    /// The gain that keeps a sample this loud under the ceiling
+    fn target_gain(&self, peak: i32) -> u32 {
+        if peak <= self.knee {
+            return ONE;
+        }
+        // Over the knee, the output creeps towards the ceiling but never
+        // gets there
+        let over = i64::from(peak - self.knee);
+        let room = i64::from(self.ceiling - self.knee);
+        let out = i64::from(self.knee) + over * room / (over + room);
+        ((out << 16) / i64::from(peak)) as u32
+    }
+
+    /// Apply the gain to one sample, and make sure it's under the ceiling
+    fn scale(&self, sample: i16) -> i16 {
+        let scaled = (i64::from(sample) * i64::from(self.gain)) >> 16;
+        scaled.clamp(-i64::from(self.ceiling), i64::from(self.ceiling)) as i16
+    }
+}
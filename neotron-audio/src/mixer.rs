@@ -0,0 +1,67 @@
+//! Adding channels together.
+
+/// The middle of the stereo image, for [`Mixer::add_panned`]
+pub const CENTRE: u8 = 128;
+
+/// Adds up samples from several channels into one stereo frame.
+///
+/// Add each channel's sample for this moment, then call [`Mixer::finish`].
+/// We add with plenty of headroom and only clip at the end, so a few loud
+/// channels at once don't wrap around into a horrible crackle.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct Mixer {
+    left: i32,
+    right: i32,
+}
+
+impl Mixer {
+    /// Start an empty frame
+    pub const fn new() -> Mixer {
+        Mixer { left: 0, right: 0 }
+    }
+
+    /// Add a sample to both sides, at a volume out of 256
+    pub fn add(&mut self, sample: i16, volume: u16) {
+        let scaled = i32::from(sample) * i32::from(volume) / 256;
+        self.left += scaled;
+        self.right += scaled;
+    }
+
+    /// Add a sample at a volume out of 256, placed somewhere between the
+    /// left (0) and the right (255)
+    pub fn add_panned(&mut self, sample: i16, volume: u16, pan: u8) {
+        let scaled = i32::from(sample) * i32::from(volume) / 256;
+        self.left += scaled * (255 - i32::from(pan)) / 255;
+        self.right += scaled * i32::from(pan) / 255;
+    }
+
+    /// Add a stereo pair, as it is
+    pub fn add_stereo(&mut self, left: i16, right: i16) {
+        self.left += i32::from(left);
+        self.right += i32::from(right);
+    }
+
+    /// Is the frame too loud, so that finishing it will clip it?
+    pub fn is_clipping(&self) -> bool {
+        clip(self.left) as i32 != self.left || clip(self.right) as i32 != self.right
+    }
+
+    /// Get the frame, clipped to fit, and start again with an empty one
+    pub fn finish(&mut self) -> (i16, i16) {
+        let frame = (clip(self.left), clip(self.right));
+        *self = Mixer::new();
+        frame
+    }
+
+    /// Get the frame as one mono sample, clipped to fit, and start again
+    pub fn finish_mono(&mut self) -> i16 {
+        let mono = clip((self.left + self.right) / 2);
+        *self = Mixer::new();
+        mono
+    }
+}
+
+/// Squash a sum of samples into the range a sample can hold
+pub fn clip(value: i32) -> i16 {
+    value.clamp(-32768, 32767) as i16
+}
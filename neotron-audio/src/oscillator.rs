@@ -0,0 +1,141 @@
+//! Oscillators, which make a steady tone.
+
+/// What the noise generator starts from. Anything but zero would do.
+const NOISE_SEED: u16 = 0xACE1;
+
+/// The shapes of wave we can make
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Waveform {
+    /// Hollow and buzzy
+    Square,
+    /// Soft, like a flute
+    Triangle,
+    /// Bright and brassy
+    Sawtooth,
+    /// A hiss, for drums and explosions. The pitch sets how often it
+    /// changes, so low pitches rumble and high ones hiss.
+    Noise,
+}
+
+impl Waveform {
+    /// The next waveform, going round in a loop
+    pub fn next(self) -> Waveform {
+        match self {
+            Waveform::Square => Waveform::Triangle,
+            Waveform::Triangle => Waveform::Sawtooth,
+            Waveform::Sawtooth => Waveform::Noise,
+            Waveform::Noise => Waveform::Square,
+        }
+    }
+
+    /// The name of this waveform
+    pub fn name(self) -> &'static str {
+        match self {
+            Waveform::Square => "Square",
+            Waveform::Triangle => "Triangle",
+            Waveform::Sawtooth => "Sawtooth",
+            Waveform::Noise => "Noise",
+        }
+    }
+}
+
+/// Makes a wave at a given pitch.
+///
+/// The phase is a 32-bit accumulator that goes once round per cycle of the
+/// wave, so the step we add each sample is `frequency * 2^32 / sample_rate`.
+#[derive(Debug, Copy, Clone)]
+pub struct Oscillator {
+    /// What shape of wave we make
+    pub waveform: Waveform,
+    phase: u32,
+    step: u32,
+    /// A linear-feedback shift register, for the noise
+    noise: u16,
+}
+
+impl Oscillator {
+    /// Make a silent oscillator, with the given wave shape
+    pub const fn new(waveform: Waveform) -> Oscillator {
+        Oscillator {
+            waveform,
+            phase: 0,
+            step: 0,
+            noise: NOISE_SEED,
+        }
+    }
+
+    /// How far the phase moves each sample, for a frequency in Hz
+    pub const fn step_for(frequency: u32, sample_rate: u32) -> u32 {
+        match ((frequency as u64) << 32).checked_div(sample_rate as u64) {
+            Some(step) => step as u32,
+            None => 0,
+        }
+    }
+
+    /// Play at this frequency, in Hz. Zero is silence.
+    pub fn set_frequency(&mut self, frequency: u32, sample_rate: u32) {
+        self.step = Self::step_for(frequency, sample_rate);
+    }
+
+    /// Play with this step, from [`Oscillator::step_for`] or a table of
+    /// your own
+    pub fn set_step(&mut self, step: u32) {
+        self.step = step;
+    }
+
+    /// The step we are playing with
+    pub fn step(&self) -> u32 {
+        self.step
+    }
+
+    /// Go back to the start of the wave, so a new note starts cleanly
+    pub fn reset(&mut self) {
+        self.phase = 0;
+    }
+
+    /// Work out the next sample, at full volume
+    pub fn next_sample(&mut self) -> i16 {
+        if self.step == 0 {
+            return 0;
+        }
+        let phase = self.phase;
+        let (next, wrapped) = phase.overflowing_add(self.step);
+        self.phase = next;
+        match self.waveform {
+            Waveform::Square => {
+                if phase < 0x8000_0000 {
+                    32767
+                } else {
+                    -32767
+                }
+            }
+            Waveform::Triangle => {
+                let position = (phase >> 15) as i32;
+                let value = if position < 0x10000 {
+                    position - 0x8000
+                } else {
+                    0x17FFF - position
+                };
+                value as i16
+            }
+            Waveform::Sawtooth => ((phase >> 16) as i32 - 0x8000) as i16,
+            Waveform::Noise => {
+                if wrapped {
+                    // Taps at 16, 14, 13 and 11 give the longest run
+                    // before it repeats
+                    let bit =
+                        (self.noise ^ (self.noise >> 2) ^ (self.noise >> 3) ^ (self.noise >> 5))
+                            & 1;
+                    self.noise = (self.noise >> 1) | (bit << 15);
+                }
+                self.noise as i16
+            }
+        }
+    }
+}
+
+impl Default for Oscillator {
+    fn default() -> Self {
+        Oscillator::new(Waveform::Square)
+    }
+}
@@ -0,0 +1,62 @@
+//! Changing the sample rate.
+
+/// The fixed-point one, for positions between two input frames
+const ONE: u32 = 1 << 16;
+
+/// Turns stereo frames at one rate into frames at another.
+///
+/// We draw a straight line between each pair of input frames, and pick
+/// each output frame off the line. That's cheap, and good enough for
+/// music which was sampled at 8-bit on an Amiga.
+#[derive(Debug, Copy, Clone)]
+pub struct Resampler {
+    /// How far through the input we move for each output frame
+    step: u32,
+    /// How far we are from `before` to `after`, out of [`ONE`]
+    position: u32,
+    before: (i16, i16),
+    after: (i16, i16),
+}
+
+impl Resampler {
+    /// Convert from one rate to another
+    pub const fn new(from_rate: u32, to_rate: u32) -> Resampler {
+        let step = match ((from_rate as u64) << 16).checked_div(to_rate as u64) {
+            Some(step) => step as u32,
+            None => ONE,
+        };
+        Resampler {
+            step,
+            // Fetch the first frame straight away
+            position: ONE,
+            before: (0, 0),
+            after: (0, 0),
+        }
+    }
+
+    /// Work out the next output frame, taking as many input frames as
+    /// we need from `source`
+    pub fn next<F>(&mut self, mut source: F) -> (i16, i16)
+    where
+        F: FnMut() -> (i16, i16),
+    {
+        while self.position >= ONE {
+            self.before = self.after;
+            self.after = source();
+            self.position -= ONE;
+        }
+        let frame = (
+            blend(self.before.0, self.after.0, self.position),
+            blend(self.before.1, self.after.1, self.position),
+        );
+        self.position += self.step;
+        frame
+    }
+}
+
+/// Go part of the way from one sample to another
+fn blend(from: i16, to: i16, position: u32) -> i16 {
+    let from = i64::from(from);
+    let to = i64::from(to);
+    (from + (to - from) * i64::from(position) / i64::from(ONE)) as i16
+}
@@ -0,0 +1,245 @@
+//! Configuration files for Neotron applications.
+//!
+//! Settings live in INI-style text files, like `SNAKE.CFG`, which anyone can
+//! change with a text editor:
+//!
+//! ```text
+//! ; Lines starting with a semicolon or a hash are comments
+//! [game]
+//! start_tick_ms = 100
+//! sound = on
+//!
+//! [keys]
+//! up = w
+//! ```
+//!
+//! Section and key names don't care about case. Values can be put in double
+//! quotes if they need spaces at either end, or a `;` or `#` in them.
+//! Anything after a `;` or `#` that follows some whitespace is a comment. If
+//! a key appears twice in a section, the last one wins.
+//!
+//! [`Config`] reads a file in place, without copying it, so it needs a buffer
+//! to load the file into. [`Writer`] writes a new file.
+
+#![no_std]
+#![deny(missing_docs)]
+#![deny(unsafe_code)]
+
+pub mod writer;
+
+pub use writer::Writer;
+
+/// Something that went wrong reading a configuration file
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// The OS couldn't open, read or write the file
+    Io(neotron_sdk::Error),
+    /// The file didn't fit in the buffer
+    TooLarge,
+    /// The file isn't UTF-8 text
+    NotText,
+    /// This line (counting from one) doesn't make sense
+    Syntax(usize),
+}
+
+impl From<neotron_sdk::Error> for Error {
+    fn from(error: neotron_sdk::Error) -> Self {
+        Error::Io(error)
+    }
+}
+
+/// One line of a configuration file
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Line<'a> {
+    /// Nothing, or just a comment
+    Blank,
+    /// The start of a section
+    Section(&'a str),
+    /// A setting
+    Entry(&'a str, &'a str),
+}
+
+impl<'a> Line<'a> {
+    /// Work out what a line is. Gives `None` if it doesn't make sense.
+    fn parse(line: &'a str) -> Option<Line<'a>> {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with([';', '#']) {
+            return Some(Line::Blank);
+        }
+        if let Some(rest) = line.strip_prefix('[') {
+            let (name, after) = rest.split_once(']')?;
+            let after = after.trim_start();
+            if !after.is_empty() && !after.starts_with([';', '#']) {
+                return None;
+            }
+            return Some(Line::Section(name.trim()));
+        }
+        let (key, value) = line.split_once('=')?;
+        let key = key.trim();
+        if key.is_empty() {
+            return None;
+        }
+        Some(Line::Entry(key, parse_value(value.trim())?))
+    }
+}
+
+/// Take the quotes off a value, or the comment off the end of it
+fn parse_value(value: &str) -> Option<&str> {
+    if let Some(rest) = value.strip_prefix('"') {
+        let (quoted, after) = rest.split_once('"')?;
+        let after = after.trim_start();
+        return if after.is_empty() || after.starts_with([';', '#']) {
+            Some(quoted)
+        } else {
+            None
+        };
+    }
+    // A comment has to have a space before it, so `#` can go in a value
+    let mut end = value.len();
+    for (index, _) in value.match_indices([';', '#']) {
+        if value[0..index].ends_with([' ', '\t']) {
+            end = index;
+            break;
+        }
+    }
+    Some(value[0..end].trim_end())
+}
+
+/// A configuration file, read in place
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Config<'a> {
+    text: &'a str,
+}
+
+impl<'a> Config<'a> {
+    /// Check every line of some text makes sense
+    pub fn parse(text: &'a str) -> Result<Config<'a>, Error> {
+        for (index, line) in text.lines().enumerate() {
+            if Line::parse(line).is_none() {
+                return Err(Error::Syntax(index + 1));
+            }
+        }
+        Ok(Config { text })
+    }
+
+    /// An empty configuration, where every lookup gives `None`
+    pub const fn empty() -> Config<'static> {
+        Config { text: "" }
+    }
+
+    /// Every setting, with the section it is in. Settings before the first
+    /// section are in a section called `""`.
+    pub fn iter(&self) -> impl Iterator<Item = (&'a str, &'a str, &'a str)> {
+        let mut section = "";
+        self.text
+            .lines()
+            .filter_map(move |line| match Line::parse(line)? {
+                Line::Section(name) => {
+                    section = name;
+                    None
+                }
+                Line::Entry(key, value) => Some((section, key, value)),
+                Line::Blank => None,
+            })
+    }
+
+    /// The names of all the sections, in the order they appear
+    pub fn sections(&self) -> impl Iterator<Item = &'a str> {
+        self.text
+            .lines()
+            .filter_map(|line| match Line::parse(line)? {
+                Line::Section(name) => Some(name),
+                _ => None,
+            })
+    }
+
+    /// All the settings in one section
+    pub fn entries<'s>(&self, section: &'s str) -> impl Iterator<Item = (&'a str, &'a str)> + 's
+    where
+        'a: 's,
+    {
+        self.iter()
+            .filter(move |(name, _, _)| name.eq_ignore_ascii_case(section))
+            .map(|(_, key, value)| (key, value))
+    }
+
+    /// Get a setting as text
+    pub fn get(&self, section: &str, key: &str) -> Option<&'a str> {
+        self.entries(section)
+            .filter(|(name, _)| name.eq_ignore_ascii_case(key))
+            .last()
+            .map(|(_, value)| value)
+    }
+
+    /// Get a setting as a whole number. Gives `None` if it isn't one.
+    pub fn get_u32(&self, section: &str, key: &str) -> Option<u32> {
+        self.get(section, key)?.parse().ok()
+    }
+
+    /// Get a setting as a whole number, which might be negative
+    pub fn get_i32(&self, section: &str, key: &str) -> Option<i32> {
+        self.get(section, key)?.parse().ok()
+    }
+
+    /// Get a setting as on or off. We understand `on`, `yes`, `true` and `1`,
+    /// and `off`, `no`, `false` and `0`.
+    pub fn get_bool(&self, section: &str, key: &str) -> Option<bool> {
+        let value = self.get(section, key)?;
+        let is = |word: &str| value.eq_ignore_ascii_case(word);
+        if is("on") || is("yes") || is("true") || is("1") {
+            Some(true)
+        } else if is("off") || is("no") || is("false") || is("0") {
+            Some(false)
+        } else {
+            None
+        }
+    }
+
+    /// Get a setting which should be a single character
+    pub fn get_char(&self, section: &str, key: &str) -> Option<char> {
+        let mut chars = self.get(section, key)?.chars();
+        match (chars.next(), chars.next()) {
+            (Some(ch), None) => Some(ch),
+            _ => None,
+        }
+    }
+}
+
+impl Default for Config<'_> {
+    fn default() -> Self {
+        Config::empty()
+    }
+}
+
+/// Load a configuration file into `buffer`, and check it.
+pub fn load<'a>(filename: &str, buffer: &'a mut [u8]) -> Result<Config<'a>, Error> {
+    let path = neotron_sdk::path::Path::new(filename)?;
+    let file = neotron_sdk::File::open(path, neotron_sdk::Flags::empty())?;
+    let mut len = 0;
+    while len < buffer.len() {
+        match file.read(&mut buffer[len..])? {
+            0 => break,
+            n => len += n,
+        }
+    }
+    if len == buffer.len() {
+        // Full up - check there's nothing more to come
+        let mut spare = [0u8; 1];
+        if file.read(&mut spare)? != 0 {
+            return Err(Error::TooLarge);
+        }
+    }
+    let text = core::str::from_utf8(&buffer[0..len]).map_err(|_| Error::NotText)?;
+    // Some editors put a byte order mark at the start
+    Config::parse(text.trim_start_matches('\u{feff}'))
+}
+
+/// Create (or replace) a configuration file, ready to write settings into
+pub fn create(filename: &str) -> Result<Writer<neotron_sdk::File>, Error> {
+    let path = neotron_sdk::path::Path::new(filename)?;
+    let file = neotron_sdk::File::open(
+        path,
+        neotron_sdk::Flags::WRITE | neotron_sdk::Flags::CREATE | neotron_sdk::Flags::TRUNCATE,
+    )?;
+    Ok(Writer::new(file))
+}
@@ -0,0 +1,67 @@
+//! Writes configuration files that [`Config`](crate::Config) can read back.
+
+use core::fmt::Write;
+
+/// Writes sections, settings and comments
+pub struct Writer<W: Write> {
+    out: W,
+    /// Nothing has been written yet, so a section doesn't need a blank line
+    /// before it
+    first: bool,
+}
+
+impl<W: Write> Writer<W> {
+    /// Write to anything that takes text
+    pub fn new(out: W) -> Writer<W> {
+        Writer { out, first: true }
+    }
+
+    /// Add a comment line
+    pub fn comment(&mut self, text: &str) -> core::fmt::Result {
+        self.first = false;
+        writeln!(self.out, "; {}", text)
+    }
+
+    /// Start a new section. There's a blank line before it, unless it's the
+    /// first thing in the file.
+    pub fn section(&mut self, name: &str) -> core::fmt::Result {
+        if !core::mem::replace(&mut self.first, false) {
+            writeln!(self.out)?;
+        }
+        writeln!(self.out, "[{}]", name)
+    }
+
+    /// Add a setting, written out as it is formatted.
+    ///
+    /// Use [`Writer::set_str`] for text which might need quotes.
+    pub fn set<T: core::fmt::Display>(&mut self, key: &str, value: T) -> core::fmt::Result {
+        self.first = false;
+        writeln!(self.out, "{} = {}", key, value)
+    }
+
+    /// Add a setting which is on or off
+    pub fn set_bool(&mut self, key: &str, value: bool) -> core::fmt::Result {
+        self.set(key, if value { "on" } else { "off" })
+    }
+
+    /// Add a setting which is some text. It gets quotes if it would read back
+    /// differently without them.
+    ///
+    /// Text that needs quotes can't have a `"` in it, so that gives an error.
+    pub fn set_str(&mut self, key: &str, value: &str) -> core::fmt::Result {
+        let needs_quotes =
+            value.trim() != value || value.starts_with('"') || value.contains([';', '#']);
+        if needs_quotes && value.contains('"') {
+            Err(core::fmt::Error)
+        } else if needs_quotes {
+            self.set(key, format_args!("\"{}\"", value))
+        } else {
+            self.set(key, value)
+        }
+    }
+
+    /// Finish writing, and get back whatever we were writing to
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+}
@@ -0,0 +1,251 @@
+//! Errors explained in plain words, for Neotron applications.
+//!
+//! `Error: InvalidPath` doesn't tell anyone much. An [`Error`] says what
+//! kind of thing went wrong, and a [`Report`] adds what it went wrong with,
+//! so it can be shown as a box saying what happened and what to try:
+//!
+//! ```text
+//! ╔═══════════ File not found ═══════════╗
+//! ║                                      ║
+//! ║       Couldn't find ADDRESS.DB       ║
+//! ║                                      ║
+//! ║  Check the name, and which drive it  ║
+//! ║                is on.                ║
+//! ║                [ OK ]                ║
+//! ╚══════════════════════════════════════╝
+//! ```
+//!
+//! An application's `main` can hand its result to [`exit_code`], which
+//! shows the box if there was an error and gives back the exit code.
+//! Command-line tools, which don't take over the screen, can use
+//! [`exit_code_text`] to print the same thing instead.
+
+#![no_std]
+#![deny(missing_docs)]
+#![deny(unsafe_code)]
+
+use core::fmt::Write;
+
+use neotron_app::Shell;
+use neotron_input::Keyboard;
+use neotron_sdk::console;
+use neotron_tui::Line;
+
+/// The longest file or device name a [`Report`] keeps. Anything longer is
+/// cut short.
+pub const MAX_SUBJECT: usize = 64;
+
+/// How big we assume the screen is, when an application has finished with
+/// it
+const SCREEN: (u8, u8) = (80, 25);
+
+/// The kinds of thing that go wrong
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// A file or directory isn't there
+    NotFound,
+    /// A device isn't there, or isn't working
+    Device,
+    /// There isn't enough room for something
+    OutOfMemory,
+    /// The command line doesn't make sense
+    Usage,
+    /// A file has something in it we don't understand
+    BadData,
+    /// The OS said no, for some other reason
+    Os(neotron_sdk::Error),
+}
+
+impl Error {
+    /// A few words saying what went wrong, for a title
+    pub fn title(&self) -> &'static str {
+        match self {
+            Error::NotFound => "File not found",
+            Error::Device => "Device problem",
+            Error::OutOfMemory => "Out of memory",
+            Error::Usage => "Command line doesn't make sense",
+            Error::BadData => "Can't read file",
+            Error::Os(_) => "Something went wrong",
+        }
+    }
+
+    /// How to say what it went wrong with, before its name
+    fn problem(&self) -> &'static str {
+        match self {
+            Error::NotFound => "Couldn't find",
+            Error::Device => "Couldn't use",
+            Error::OutOfMemory => "Not enough room for",
+            Error::Usage => "Didn't understand",
+            Error::BadData => "Couldn't make sense of",
+            Error::Os(_) => "Couldn't use",
+        }
+    }
+
+    /// What the user could try, a line at a time
+    pub fn advice(&self) -> &'static [&'static str] {
+        match self {
+            Error::NotFound => &["Check the name, and which drive it", "is on."],
+            Error::Device => &["Check it is plugged in and switched", "on, and try again."],
+            Error::OutOfMemory => &["Try something smaller."],
+            Error::Usage => &["Check what the program expects."],
+            Error::BadData => &["It may be the wrong kind of file,", "or damaged."],
+            Error::Os(_) => &[
+                "Try again. If it keeps happening,",
+                "something may be broken.",
+            ],
+        }
+    }
+}
+
+/// Errors from the OS are sorted into the kinds we explain.
+///
+/// Our applications give `InvalidArg` when their command line doesn't make
+/// sense, so that's a [`Error::Usage`].
+impl From<neotron_sdk::Error> for Error {
+    fn from(error: neotron_sdk::Error) -> Self {
+        match error {
+            neotron_sdk::Error::InvalidPath => Error::NotFound,
+            neotron_sdk::Error::InvalidArg => Error::Usage,
+            neotron_sdk::Error::DeviceSpecific => Error::Device,
+            other => Error::Os(other),
+        }
+    }
+}
+
+impl core::fmt::Display for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Error::Os(error) => write!(f, "{} ({:?})", self.title(), error),
+            _ => f.write_str(self.title()),
+        }
+    }
+}
+
+/// An error, and what it went wrong with
+pub struct Report {
+    error: Error,
+    /// The file or device, or nothing
+    subject: [u8; MAX_SUBJECT],
+    subject_len: usize,
+}
+
+impl Report {
+    /// Report an error, without saying what it went wrong with
+    pub const fn new(error: Error) -> Report {
+        Report {
+            error,
+            subject: [0; MAX_SUBJECT],
+            subject_len: 0,
+        }
+    }
+
+    /// Say what it went wrong with, like a file name
+    pub fn about(mut self, subject: &str) -> Report {
+        let mut len = subject.len().min(MAX_SUBJECT);
+        // Don't cut a character in half
+        while !subject.is_char_boundary(len) {
+            len -= 1;
+        }
+        self.subject[0..len].copy_from_slice(&subject.as_bytes()[0..len]);
+        self.subject_len = len;
+        self
+    }
+
+    /// What it went wrong with, or an empty string if we don't know
+    pub fn subject(&self) -> &str {
+        core::str::from_utf8(&self.subject[0..self.subject_len]).unwrap_or("")
+    }
+
+    /// What kind of error it was
+    pub fn error(&self) -> Error {
+        self.error
+    }
+
+    /// Write the report out as text, ending with a new line
+    pub fn print<W: Write>(&self, out: &mut W) -> core::fmt::Result {
+        write!(out, "Error: ")?;
+        self.write_summary(out)?;
+        writeln!(out)?;
+        for line in self.error.advice() {
+            writeln!(out, "{}", line)?;
+        }
+        Ok(())
+    }
+
+    /// Show the report in a box, and wait until the user has seen it. The
+    /// box is left on the screen.
+    pub fn show(&self, shell: &Shell, out: &mut neotron_sdk::File, keyboard: &mut Keyboard) {
+        let mut summary = Line::new();
+        let _ = self.write_summary(&mut summary);
+        let mut lines = [""; 5];
+        lines[0] = summary.as_str();
+        let advice = self.error.advice();
+        let count = advice.len().min(lines.len() - 2);
+        lines[2..2 + count].copy_from_slice(&advice[0..count]);
+        shell.message(out, keyboard, self.error.title(), &lines[0..2 + count]);
+    }
+
+    /// Say what happened, in one line
+    fn write_summary<W: Write>(&self, out: &mut W) -> core::fmt::Result {
+        let subject = self.subject();
+        match self.error {
+            Error::Os(error) if subject.is_empty() => write!(out, "The OS said {:?}", error),
+            Error::Os(error) => write!(out, "{} {} ({:?})", self.error.problem(), subject, error),
+            _ if subject.is_empty() => out.write_str(self.error.title()),
+            _ => write!(out, "{} {}", self.error.problem(), subject),
+        }
+    }
+}
+
+impl From<Error> for Report {
+    fn from(error: Error) -> Self {
+        Report::new(error)
+    }
+}
+
+impl From<neotron_sdk::Error> for Report {
+    fn from(error: neotron_sdk::Error) -> Self {
+        Report::new(error.into())
+    }
+}
+
+/// Turn the result of an application into an exit code, showing the error
+/// in a box if there was one.
+///
+/// Once the box has been seen, the screen is cleared, ready for the shell.
+/// A [`Error::Usage`] is about the command line, so that's printed instead,
+/// under whatever the application said about how to use it.
+pub fn exit_code<E: Into<Report>>(result: Result<(), E>) -> i32 {
+    let Err(error) = result else {
+        return 0;
+    };
+    let report = error.into();
+    let mut stdout = neotron_sdk::stdout();
+    if report.error() == Error::Usage {
+        let _ = report.print(&mut stdout);
+        return 1;
+    }
+    let shell = Shell::new("", SCREEN.0, SCREEN.1).with_theme(neotron_app::preferred_theme());
+    let mut keyboard = Keyboard::new();
+    // Anything typed while the application was going wrong shouldn't
+    // clear the box before it's been read
+    keyboard.flush();
+    report.show(&shell, &mut stdout, &mut keyboard);
+    console::set_sgr(&mut stdout, [console::SgrParam::Reset]);
+    console::clear_screen(&mut stdout);
+    console::move_cursor(&mut stdout, console::Position::origin());
+    console::cursor_on(&mut stdout);
+    1
+}
+
+/// Turn the result of an application into an exit code, printing the error
+/// if there was one
+pub fn exit_code_text<E: Into<Report>>(result: Result<(), E>) -> i32 {
+    match result {
+        Ok(()) => 0,
+        Err(error) => {
+            let _ = error.into().print(&mut neotron_sdk::stdout());
+            1
+        }
+    }
+}
@@ -0,0 +1,250 @@
+//! A signed 16.16 fixed-point number.
+
+use crate::FRACTION_BITS;
+
+/// A signed number with 16 bits of whole part and 16 bits of fraction.
+///
+/// That gives a range of -32768 to just under 32768, in steps of 1/65536.
+/// Like the integer types, adding, subtracting and multiplying wrap around
+/// if they overflow, and dividing by zero panics - use
+/// [`Fixed::checked_div`] if you aren't sure.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Fixed(i32);
+
+impl Fixed {
+    /// Zero
+    pub const ZERO: Fixed = Fixed(0);
+    /// One
+    pub const ONE: Fixed = Fixed(1 << FRACTION_BITS);
+    /// One half
+    pub const HALF: Fixed = Fixed(1 << (FRACTION_BITS - 1));
+    /// The biggest number we can hold
+    pub const MAX: Fixed = Fixed(i32::MAX);
+    /// The most negative number we can hold
+    pub const MIN: Fixed = Fixed(i32::MIN);
+    /// Pi, as near as we can get
+    pub const PI: Fixed = Fixed(205_887);
+
+    /// A whole number
+    pub const fn from_int(value: i32) -> Fixed {
+        Fixed(value << FRACTION_BITS)
+    }
+
+    /// One number divided by another, like `from_ratio(1, 3)` for a third.
+    /// Dividing by zero gives zero.
+    pub const fn from_ratio(numerator: i32, denominator: i32) -> Fixed {
+        match ((numerator as i64) << FRACTION_BITS).checked_div(denominator as i64) {
+            Some(value) => Fixed(value as i32),
+            None => Fixed(0),
+        }
+    }
+
+    /// Make one from a raw 16.16 value
+    pub const fn from_bits(bits: i32) -> Fixed {
+        Fixed(bits)
+    }
+
+    /// The raw 16.16 value
+    pub const fn to_bits(self) -> i32 {
+        self.0
+    }
+
+    /// The whole part, rounded down (so -1.5 gives -2)
+    pub const fn to_int(self) -> i32 {
+        self.0 >> FRACTION_BITS
+    }
+
+    /// The nearest whole number, with halves rounded up
+    pub const fn round(self) -> i32 {
+        self.0.wrapping_add(Self::HALF.0) >> FRACTION_BITS
+    }
+
+    /// The part after the point. This is never negative, so that
+    /// `to_int` plus `frac` gives back what you started with.
+    pub const fn frac(self) -> Fixed {
+        Fixed(self.0 & ((1 << FRACTION_BITS) - 1))
+    }
+
+    /// Without the sign
+    pub const fn abs(self) -> Fixed {
+        Fixed(self.0.wrapping_abs())
+    }
+
+    /// Is this less than zero?
+    pub const fn is_negative(self) -> bool {
+        self.0 < 0
+    }
+
+    /// Multiply, giving `None` if the answer doesn't fit
+    pub const fn checked_mul(self, rhs: Fixed) -> Option<Fixed> {
+        let wide = (self.0 as i64 * rhs.0 as i64) >> FRACTION_BITS;
+        if wide > i32::MAX as i64 || wide < i32::MIN as i64 {
+            None
+        } else {
+            Some(Fixed(wide as i32))
+        }
+    }
+
+    /// Multiply, sticking at [`Fixed::MAX`] or [`Fixed::MIN`] if the answer
+    /// doesn't fit
+    pub const fn saturating_mul(self, rhs: Fixed) -> Fixed {
+        match self.checked_mul(rhs) {
+            Some(value) => value,
+            None if (self.0 < 0) == (rhs.0 < 0) => Fixed::MAX,
+            None => Fixed::MIN,
+        }
+    }
+
+    /// Divide, giving `None` for a divide by zero or an answer that doesn't
+    /// fit
+    pub const fn checked_div(self, rhs: Fixed) -> Option<Fixed> {
+        match ((self.0 as i64) << FRACTION_BITS).checked_div(rhs.0 as i64) {
+            Some(wide) if wide <= i32::MAX as i64 && wide >= i32::MIN as i64 => {
+                Some(Fixed(wide as i32))
+            }
+            _ => None,
+        }
+    }
+
+    /// Part way from `self` to `other`. A `t` of zero gives `self`, one gives
+    /// `other`, and a half gives the point in the middle.
+    pub const fn lerp(self, other: Fixed, t: Fixed) -> Fixed {
+        let span = other.0 as i64 - self.0 as i64;
+        Fixed((self.0 as i64 + ((span * t.0 as i64) >> FRACTION_BITS)) as i32)
+    }
+
+    /// The square root. Negative numbers give zero.
+    pub const fn sqrt(self) -> Fixed {
+        if self.0 <= 0 {
+            return Fixed::ZERO;
+        }
+        // The root of (x * 2^16) is root(x) * 2^8, so shift up another 16 to
+        // keep all the bits
+        let square = (self.0 as u64) << FRACTION_BITS;
+        // Newton's method, starting from something too big
+        let mut root = 1u64 << ((64 - square.leading_zeros()).div_ceil(2));
+        loop {
+            let next = (root + square / root) / 2;
+            if next >= root {
+                return Fixed(root as i32);
+            }
+            root = next;
+        }
+    }
+}
+
+impl From<i32> for Fixed {
+    fn from(value: i32) -> Self {
+        Fixed::from_int(value)
+    }
+}
+
+impl From<i16> for Fixed {
+    fn from(value: i16) -> Self {
+        Fixed::from_int(i32::from(value))
+    }
+}
+
+impl core::ops::Add for Fixed {
+    type Output = Fixed;
+
+    fn add(self, rhs: Fixed) -> Fixed {
+        Fixed(self.0.wrapping_add(rhs.0))
+    }
+}
+
+impl core::ops::Sub for Fixed {
+    type Output = Fixed;
+
+    fn sub(self, rhs: Fixed) -> Fixed {
+        Fixed(self.0.wrapping_sub(rhs.0))
+    }
+}
+
+impl core::ops::Mul for Fixed {
+    type Output = Fixed;
+
+    fn mul(self, rhs: Fixed) -> Fixed {
+        Fixed(((self.0 as i64 * rhs.0 as i64) >> FRACTION_BITS) as i32)
+    }
+}
+
+impl core::ops::Div for Fixed {
+    type Output = Fixed;
+
+    fn div(self, rhs: Fixed) -> Fixed {
+        Fixed((((self.0 as i64) << FRACTION_BITS) / rhs.0 as i64) as i32)
+    }
+}
+
+impl core::ops::Mul<i32> for Fixed {
+    type Output = Fixed;
+
+    fn mul(self, rhs: i32) -> Fixed {
+        Fixed(self.0.wrapping_mul(rhs))
+    }
+}
+
+impl core::ops::Div<i32> for Fixed {
+    type Output = Fixed;
+
+    fn div(self, rhs: i32) -> Fixed {
+        Fixed(self.0 / rhs)
+    }
+}
+
+impl core::ops::Neg for Fixed {
+    type Output = Fixed;
+
+    fn neg(self) -> Fixed {
+        Fixed(self.0.wrapping_neg())
+    }
+}
+
+impl core::ops::AddAssign for Fixed {
+    fn add_assign(&mut self, rhs: Fixed) {
+        *self = *self + rhs;
+    }
+}
+
+impl core::ops::SubAssign for Fixed {
+    fn sub_assign(&mut self, rhs: Fixed) {
+        *self = *self - rhs;
+    }
+}
+
+impl core::ops::MulAssign for Fixed {
+    fn mul_assign(&mut self, rhs: Fixed) {
+        *self = *self * rhs;
+    }
+}
+
+impl core::ops::DivAssign for Fixed {
+    fn div_assign(&mut self, rhs: Fixed) {
+        *self = *self / rhs;
+    }
+}
+
+impl core::fmt::Display for Fixed {
+    /// Shows four decimal places, unless you ask for a different number.
+    /// Extra places are cut off rather than rounded.
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let bits = i64::from(self.0);
+        let magnitude = bits.unsigned_abs();
+        if bits < 0 {
+            f.write_str("-")?;
+        }
+        write!(f, "{}", magnitude >> FRACTION_BITS)?;
+        let places = f.precision().unwrap_or(4);
+        if places > 0 {
+            f.write_str(".")?;
+        }
+        let mut fraction = magnitude & ((1 << FRACTION_BITS) - 1);
+        for _ in 0..places {
+            fraction *= 10;
+            write!(f, "{}", fraction >> FRACTION_BITS)?;
+            fraction &= (1 << FRACTION_BITS) - 1;
+        }
+        Ok(())
+    }
+}
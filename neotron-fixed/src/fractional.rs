@@ -0,0 +1,70 @@
+//! Positions within a sample, for playing it back at a different pitch.
+
+use crate::FRACTION_BITS;
+
+/// The clock the Amiga's sound chip counted note periods in, in Hz (for a
+/// PAL machine)
+pub const AMIGA_CLOCK: u32 = 3_546_895;
+
+/// An unsigned 16.16 fixed-point number, for stepping through a sample.
+///
+/// Each output sample we add the step to the position, and the whole part
+/// of the position says which byte of the sample to play.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Fractional(u32);
+
+impl Fractional {
+    /// A whole number
+    pub const fn new(value: u32) -> Fractional {
+        Fractional(value << FRACTION_BITS)
+    }
+
+    /// How many Amiga clock ticks go by for each sample we play, at the
+    /// given sample rate. A rate of zero gives zero.
+    pub const fn new_from_sample_rate(sample_rate: u32) -> Fractional {
+        match ((AMIGA_CLOCK as u64) << FRACTION_BITS).checked_div(sample_rate as u64) {
+            Some(ticks) => Fractional(ticks as u32),
+            None => Fractional(0),
+        }
+    }
+
+    /// Turn clock ticks per output sample into how far to step through a
+    /// sample, for a note with the given Amiga period.
+    ///
+    /// A period of zero means no note, so gives no step.
+    pub const fn apply_period(self, period: u16) -> Fractional {
+        match self.0.checked_div(period as u32) {
+            Some(step) => Fractional(step),
+            None => Fractional(0),
+        }
+    }
+
+    /// The whole part, as an index into a sample
+    pub const fn as_index(self) -> usize {
+        (self.0 >> FRACTION_BITS) as usize
+    }
+
+    /// The raw 16.16 value
+    pub const fn to_bits(self) -> u32 {
+        self.0
+    }
+
+    /// Make one from a raw 16.16 value
+    pub const fn from_bits(bits: u32) -> Fractional {
+        Fractional(bits)
+    }
+}
+
+impl core::ops::Add for Fractional {
+    type Output = Fractional;
+
+    fn add(self, rhs: Fractional) -> Fractional {
+        Fractional(self.0.wrapping_add(rhs.0))
+    }
+}
+
+impl core::ops::AddAssign for Fractional {
+    fn add_assign(&mut self, rhs: Fractional) {
+        self.0 = self.0.wrapping_add(rhs.0);
+    }
+}
@@ -0,0 +1,26 @@
+//! Fixed-point maths for Neotron applications.
+//!
+//! Neotron machines don't have floating-point hardware, and software floats
+//! are slow and large. These types keep a fraction in the bottom 16 bits of
+//! an integer instead, so adding is just adding, and multiplying is a
+//! multiply and a shift.
+//!
+//! * [`Fixed`] is a signed number, for positions, speeds and the like.
+//! * [`Fractional`] is an unsigned position in a sample, which is how the MOD
+//!   player steps through an instrument at the right pitch.
+//! * [`Angle`] goes with [`sin`] and [`cos`], which use a table.
+
+#![no_std]
+#![deny(missing_docs)]
+#![deny(unsafe_code)]
+
+mod fixed;
+mod fractional;
+mod trig;
+
+pub use fixed::Fixed;
+pub use fractional::{Fractional, AMIGA_CLOCK};
+pub use trig::{cos, sin, Angle};
+
+/// How many bits of fraction our types have
+pub const FRACTION_BITS: u32 = 16;
@@ -0,0 +1,102 @@
+//! Sine and cosine, from a table.
+
+use crate::Fixed;
+
+/// The sine of each step through a quarter turn, where a whole turn is 256
+/// steps, as 16.16 fixed point
+const QUARTER_SINE: [i32; 65] = [
+    0, 1608, 3216, 4821, 6424, 8022, 9616, 11204, //
+    12785, 14359, 15924, 17479, 19024, 20557, 22078, 23586, //
+    25080, 26558, 28020, 29466, 30893, 32303, 33692, 35062, //
+    36410, 37736, 39040, 40320, 41576, 42806, 44011, 45190, //
+    46341, 47464, 48559, 49624, 50660, 51665, 52639, 53581, //
+    54491, 55368, 56212, 57022, 57798, 58538, 59244, 59914, //
+    60547, 61145, 61705, 62228, 62714, 63162, 63572, 63944, //
+    64277, 64571, 64827, 65043, 65220, 65358, 65457, 65516, //
+    65536,
+];
+
+/// An angle, where 65536 is a whole turn.
+///
+/// Adding angles wraps around, so you can keep turning forever.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Angle(pub u16);
+
+impl Angle {
+    /// No turn at all
+    pub const ZERO: Angle = Angle(0);
+    /// A quarter turn, or 90 degrees
+    pub const QUARTER: Angle = Angle(0x4000);
+    /// A half turn, or 180 degrees
+    pub const HALF: Angle = Angle(0x8000);
+
+    /// Turn degrees into the nearest angle. Anything outside 0 to 359 wraps
+    /// around.
+    pub const fn from_degrees(degrees: i32) -> Angle {
+        let degrees = degrees.rem_euclid(360) as u32;
+        Angle(((degrees * 65536 + 180) / 360) as u16)
+    }
+
+    /// Turn this angle into the nearest whole degree, from 0 to 359
+    pub const fn to_degrees(self) -> u16 {
+        ((self.0 as u32 * 360 + 32768) / 65536 % 360) as u16
+    }
+}
+
+impl core::ops::Add for Angle {
+    type Output = Angle;
+
+    fn add(self, rhs: Angle) -> Angle {
+        Angle(self.0.wrapping_add(rhs.0))
+    }
+}
+
+impl core::ops::Sub for Angle {
+    type Output = Angle;
+
+    fn sub(self, rhs: Angle) -> Angle {
+        Angle(self.0.wrapping_sub(rhs.0))
+    }
+}
+
+impl core::ops::AddAssign for Angle {
+    fn add_assign(&mut self, rhs: Angle) {
+        *self = *self + rhs;
+    }
+}
+
+impl core::ops::SubAssign for Angle {
+    fn sub_assign(&mut self, rhs: Angle) {
+        *self = *self - rhs;
+    }
+}
+
+/// The sine of an angle, between -1 and 1.
+///
+/// We look up the two nearest entries in a table and go part way between
+/// them, which is good to about four decimal places.
+pub fn sin(angle: Angle) -> Fixed {
+    let quadrant = angle.0 >> 14;
+    // Where we are in this quarter turn, in 1/256ths of a table step
+    let within = u32::from(angle.0 & 0x3FFF);
+    let within = if quadrant & 1 == 1 {
+        // Going back down again
+        0x4000 - within
+    } else {
+        within
+    };
+    let index = (within >> 8) as usize;
+    let low = QUARTER_SINE[index];
+    let high = QUARTER_SINE.get(index + 1).copied().unwrap_or(low);
+    let value = low + (((high - low) * (within & 0xFF) as i32) >> 8);
+    if quadrant >= 2 {
+        Fixed::from_bits(-value)
+    } else {
+        Fixed::from_bits(value)
+    }
+}
+
+/// The cosine of an angle, between -1 and 1
+pub fn cos(angle: Angle) -> Fixed {
+    sin(angle + Angle::QUARTER)
+}
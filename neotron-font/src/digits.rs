@@ -0,0 +1,37 @@
+//! Small digits, for clocks and scores.
+//!
+//! Each glyph is five rows of three pixels, apart from the colon and the
+//! space, which are one pixel wide.
+
+use crate::{Glyph, MAX_HEIGHT};
+
+/// The digits 0 to 9. Each row is three bits, most significant on the left.
+const DIGITS: [[u8; MAX_HEIGHT]; 10] = [
+    [0b111, 0b101, 0b101, 0b101, 0b111, 0, 0, 0],
+    [0b010, 0b110, 0b010, 0b010, 0b111, 0, 0, 0],
+    [0b111, 0b001, 0b111, 0b100, 0b111, 0, 0, 0],
+    [0b111, 0b001, 0b111, 0b001, 0b111, 0, 0, 0],
+    [0b101, 0b101, 0b111, 0b001, 0b001, 0, 0, 0],
+    [0b111, 0b100, 0b111, 0b001, 0b111, 0, 0, 0],
+    [0b111, 0b100, 0b111, 0b101, 0b111, 0, 0, 0],
+    [0b111, 0b001, 0b001, 0b001, 0b001, 0, 0, 0],
+    [0b111, 0b101, 0b111, 0b101, 0b111, 0, 0, 0],
+    [0b111, 0b101, 0b111, 0b001, 0b111, 0, 0, 0],
+];
+
+/// The colon between the hours and minutes
+const COLON: [u8; MAX_HEIGHT] = [0b0, 0b1, 0b0, 0b1, 0b0, 0, 0, 0];
+
+/// A minus sign, for scores which can go below zero
+const MINUS: [u8; MAX_HEIGHT] = [0b000, 0b000, 0b111, 0b000, 0b000, 0, 0, 0];
+
+/// Get the pixels for a character. Anything we don't have a glyph for comes
+/// out as a space.
+pub(crate) fn glyph(ch: char) -> Glyph {
+    match ch {
+        '0'..='9' => Glyph::new(DIGITS[ch as usize - '0' as usize], 3),
+        ':' => Glyph::new(COLON, 1),
+        '-' => Glyph::new(MINUS, 3),
+        _ => Glyph::new([0; MAX_HEIGHT], 1),
+    }
+}
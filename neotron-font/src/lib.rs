@@ -0,0 +1,219 @@
+//! Big text from bitmap fonts, for Neotron applications.
+//!
+//! Clocks, banners, title screens and scores all want text bigger than one
+//! character. A [`Font`] says which pixels make up each character, and
+//! [`BigText`] draws a string in that font with block characters, one row of
+//! the screen at a time, so you can put each row wherever you like.
+//!
+//! ```rust,ignore
+//! let big = BigText::new(neotron_font::DIGITS, "12:34");
+//! for row in 0..big.height() {
+//!     move_to(top + row, (screen_width - big.width()) / 2);
+//!     big.write_row(&mut stdout, row)?;
+//! }
+//! ```
+
+#![no_std]
+#![deny(missing_docs)]
+#![deny(unsafe_code)]
+
+mod ascii;
+mod digits;
+
+/// The tallest a glyph can be, in pixels
+pub const MAX_HEIGHT: usize = 8;
+
+/// Printable ASCII, five pixels wide and eight tall
+pub const ASCII: Font = Font {
+    width: 5,
+    height: 8,
+    baseline: 7,
+    lookup: ascii::glyph,
+};
+
+/// Digits, a colon and a minus sign, three pixels wide and five tall
+pub const DIGITS: Font = Font {
+    width: 3,
+    height: 5,
+    baseline: 5,
+    lookup: digits::glyph,
+};
+
+/// The pixels for one character
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Glyph {
+    /// Each row of pixels, with the left-most in the highest bit in use
+    rows: [u8; MAX_HEIGHT],
+    width: u8,
+}
+
+impl Glyph {
+    /// Make a glyph from rows of up to eight pixels, the left-most pixel in
+    /// bit `width - 1`
+    pub const fn new(rows: [u8; MAX_HEIGHT], width: u8) -> Glyph {
+        Glyph { rows, width }
+    }
+
+    /// How many pixels wide it is
+    pub fn width(&self) -> usize {
+        usize::from(self.width)
+    }
+
+    /// Is this pixel set? Column 0 is on the left and row 0 is at the top.
+    /// Anything outside the glyph isn't set.
+    pub fn pixel(&self, col: usize, row: usize) -> bool {
+        col < self.width()
+            && row < MAX_HEIGHT
+            && self.rows[row] & (1 << (self.width() - 1 - col)) != 0
+    }
+}
+
+/// A set of glyphs, all the same height
+#[derive(Debug, Copy, Clone)]
+pub struct Font {
+    /// How wide the widest glyph is
+    width: usize,
+    height: usize,
+    /// How many rows the glyphs use without their tails
+    baseline: usize,
+    lookup: fn(char) -> Glyph,
+}
+
+impl Font {
+    /// How many pixels wide the widest glyph is
+    pub const fn width(&self) -> usize {
+        self.width
+    }
+
+    /// How many pixels tall the glyphs are
+    pub const fn height(&self) -> usize {
+        self.height
+    }
+
+    /// How many pixels tall the glyphs are, without the tails on letters
+    /// like `g` and `y`
+    pub const fn baseline(&self) -> usize {
+        self.baseline
+    }
+
+    /// Get the pixels for a character
+    pub fn glyph(&self, ch: char) -> Glyph {
+        (self.lookup)(ch)
+    }
+
+    /// Does this character hang down below the baseline?
+    pub fn has_tail(&self, ch: char) -> bool {
+        let glyph = self.glyph(ch);
+        (self.baseline..self.height).any(|row| glyph.rows[row] != 0)
+    }
+}
+
+/// How each pixel is drawn
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Style {
+    /// Two `█` characters per pixel, which comes out roughly square
+    Blocks,
+    /// Half-block characters, like `▀` and `▄`, with two rows of pixels in
+    /// each row of the screen. Half the height, and half the width.
+    HalfBlocks,
+}
+
+/// Some text, drawn big
+#[derive(Debug, Copy, Clone)]
+pub struct BigText<'a> {
+    font: Font,
+    text: &'a str,
+    style: Style,
+}
+
+impl<'a> BigText<'a> {
+    /// Draw some text in this font, with [`Style::Blocks`]
+    pub const fn new(font: Font, text: &'a str) -> BigText<'a> {
+        BigText {
+            font,
+            text,
+            style: Style::Blocks,
+        }
+    }
+
+    /// Draw the pixels a different way
+    pub const fn with_style(self, style: Style) -> BigText<'a> {
+        BigText { style, ..self }
+    }
+
+    /// How many columns each pixel takes up
+    fn pixel_width(&self) -> usize {
+        match self.style {
+            Style::Blocks => 2,
+            Style::HalfBlocks => 1,
+        }
+    }
+
+    /// How many columns it takes up, with a pixel's gap between each
+    /// character
+    pub fn width(&self) -> usize {
+        let pixels: usize = self
+            .text
+            .chars()
+            .map(|ch| self.font.glyph(ch).width() + 1)
+            .sum();
+        // No gap after the last character
+        pixels.saturating_sub(1) * self.pixel_width()
+    }
+
+    /// How many rows it takes up. Tails are only left room for if something
+    /// has one.
+    pub fn height(&self) -> usize {
+        let pixels = if self.text.chars().any(|ch| self.font.has_tail(ch)) {
+            self.font.height()
+        } else {
+            self.font.baseline()
+        };
+        match self.style {
+            Style::Blocks => pixels,
+            Style::HalfBlocks => pixels.div_ceil(2),
+        }
+    }
+
+    /// Write out one row, from 0 at the top to [`BigText::height`] at the
+    /// bottom
+    pub fn write_row<W: core::fmt::Write>(&self, out: &mut W, row: usize) -> core::fmt::Result {
+        for (idx, ch) in self.text.chars().enumerate() {
+            let glyph = self.font.glyph(ch);
+            if idx > 0 {
+                self.write_pixel(out, false, false)?;
+            }
+            for col in 0..glyph.width() {
+                match self.style {
+                    Style::Blocks => {
+                        let set = glyph.pixel(col, row);
+                        self.write_pixel(out, set, set)?;
+                    }
+                    Style::HalfBlocks => {
+                        let top = glyph.pixel(col, row * 2);
+                        let bottom = glyph.pixel(col, row * 2 + 1);
+                        self.write_pixel(out, top, bottom)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Write one pixel, given whether its top and bottom halves are set
+    fn write_pixel<W: core::fmt::Write>(
+        &self,
+        out: &mut W,
+        top: bool,
+        bottom: bool,
+    ) -> core::fmt::Result {
+        match (self.style, top, bottom) {
+            (Style::Blocks, true, _) => out.write_str("██"),
+            (Style::Blocks, false, _) => out.write_str("  "),
+            (Style::HalfBlocks, true, true) => out.write_char('█'),
+            (Style::HalfBlocks, true, false) => out.write_char('▀'),
+            (Style::HalfBlocks, false, true) => out.write_char('▄'),
+            (Style::HalfBlocks, false, false) => out.write_char(' '),
+        }
+    }
+}
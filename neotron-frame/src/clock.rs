@@ -0,0 +1,157 @@
+//! Keeping track of how much time has passed.
+//!
+//! The SDK doesn't give us a fine-grained timer, so on a Neotron we count the
+//! time we spend sleeping. That drifts, because the game takes time to run
+//! too. So if there is a real-time clock, we line our count up with it every
+//! time its seconds tick over, and never let the count run ahead of it. On a
+//! PC we have a proper clock, and use that.
+
+/// How many seconds there are in a day
+#[cfg(target_os = "none")]
+const SECONDS_PER_DAY: u32 = 24 * 60 * 60;
+
+/// Counts milliseconds since we started
+pub struct Clock {
+    /// The last reading of the real-time clock, in seconds since midnight,
+    /// if we have one
+    #[cfg(target_os = "none")]
+    rtc: Option<u32>,
+    /// Our count when we first saw the real-time clock's seconds tick over
+    #[cfg(target_os = "none")]
+    boundary_ms: Option<u64>,
+    /// Whole seconds counted by the real-time clock since then
+    #[cfg(target_os = "none")]
+    rtc_seconds: u64,
+    /// Milliseconds since we started
+    #[cfg(target_os = "none")]
+    now_ms: u64,
+    /// What the PC's clock said when we started
+    #[cfg(not(target_os = "none"))]
+    started_ms: u64,
+}
+
+impl Clock {
+    /// Make a new clock. Call [`Clock::start`] before using it.
+    pub const fn new() -> Clock {
+        Clock {
+            #[cfg(target_os = "none")]
+            rtc: None,
+            #[cfg(target_os = "none")]
+            boundary_ms: None,
+            #[cfg(target_os = "none")]
+            rtc_seconds: 0,
+            #[cfg(target_os = "none")]
+            now_ms: 0,
+            #[cfg(not(target_os = "none"))]
+            started_ms: 0,
+        }
+    }
+
+    /// Start counting from zero
+    pub fn start(&mut self) {
+        #[cfg(target_os = "none")]
+        {
+            self.rtc = read_rtc();
+            self.boundary_ms = None;
+            self.rtc_seconds = 0;
+            self.now_ms = 0;
+        }
+        #[cfg(not(target_os = "none"))]
+        {
+            self.started_ms = neotron_host::millis();
+        }
+    }
+
+    /// Is there something keeping the count right, or are we just counting
+    /// sleeps?
+    pub fn is_accurate(&self) -> bool {
+        #[cfg(target_os = "none")]
+        {
+            self.rtc.is_some()
+        }
+        #[cfg(not(target_os = "none"))]
+        {
+            true
+        }
+    }
+
+    /// Milliseconds since we started.
+    ///
+    /// On a Neotron this only moves on when we sleep or [`Clock::sync`].
+    pub fn now_ms(&self) -> u64 {
+        #[cfg(target_os = "none")]
+        {
+            self.now_ms
+        }
+        #[cfg(not(target_os = "none"))]
+        {
+            neotron_host::millis().saturating_sub(self.started_ms)
+        }
+    }
+
+    /// Sleep, and count the time we slept
+    pub fn sleep_ms(&mut self, ms: u32) {
+        neotron_sdk::delay(core::time::Duration::from_millis(u64::from(ms)));
+        #[cfg(target_os = "none")]
+        {
+            self.now_ms = self.now_ms.saturating_add(u64::from(ms));
+        }
+    }
+
+    /// Line our count up with the real-time clock.
+    ///
+    /// Reading the clock means opening a file, so don't do this too often -
+    /// once a frame is plenty.
+    pub fn sync(&mut self) {
+        #[cfg(target_os = "none")]
+        if let Some(last) = self.rtc {
+            match read_rtc() {
+                Some(now) if now != last => {
+                    self.rtc = Some(now);
+                    match self.boundary_ms {
+                        // We didn't know where in the second we started, but
+                        // now we do
+                        None => self.boundary_ms = Some(self.now_ms),
+                        Some(boundary) => {
+                            // Copes with the clock going past midnight
+                            let seconds = (now + SECONDS_PER_DAY - last) % SECONDS_PER_DAY;
+                            self.rtc_seconds += u64::from(seconds);
+                            self.now_ms = self.now_ms.max(boundary + self.rtc_seconds * 1000);
+                        }
+                    }
+                }
+                Some(_) => {
+                    if let Some(boundary) = self.boundary_ms {
+                        let next = boundary + (self.rtc_seconds + 1) * 1000;
+                        self.now_ms = self.now_ms.min(next - 1);
+                    }
+                }
+                None => self.rtc = None,
+            }
+        }
+    }
+}
+
+impl Default for Clock {
+    fn default() -> Self {
+        Clock::new()
+    }
+}
+
+/// Read the real-time clock, as the number of seconds since midnight.
+///
+/// The clock gives us text like `2024-01-31 12:34:56`.
+#[cfg(target_os = "none")]
+fn read_rtc() -> Option<u32> {
+    let path = neotron_sdk::path::Path::new("RTC:").ok()?;
+    let rtc = neotron_sdk::File::open(path, neotron_sdk::Flags::empty()).ok()?;
+    let mut buffer = [0u8; 32];
+    let n = rtc.read(&mut buffer).ok()?;
+    let text = core::str::from_utf8(&buffer[0..n]).ok()?;
+    let time = text.trim().rsplit(' ').next()?;
+    let mut parts = time.split(':').map(|part| part.parse::<u32>().ok());
+    let hours = parts.next()??;
+    let minutes = parts.next()??;
+    let seconds = parts.next()??;
+    Some(hours * 3600 + minutes * 60 + seconds)
+}
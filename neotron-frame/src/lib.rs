@@ -0,0 +1,179 @@
+//! Frame timing for Neotron games.
+//!
+//! Calling `delay()` once round the main loop makes a game run slower than
+//! it should, because drawing and thinking take time too - and how much
+//! depends on the machine. A [`Frames`] scheduler knows when each frame is
+//! due, and only sleeps for what's left. While it waits it can keep calling
+//! a function of yours, so keys are picked up as soon as they're pressed.
+//!
+//! ```ignore
+//! let mut frames = neotron_frame::Frames::new(20);
+//! frames.start();
+//! loop {
+//!     let steps = frames.wait_polling(|| read_keys(&mut queue));
+//!     for _ in 0..steps {
+//!         update(&mut game);
+//!     }
+//!     draw(&game);
+//! }
+//! ```
+//!
+//! If the game falls behind, [`Frames::wait`] says how many steps it owes,
+//! so the game runs at the same speed even when the frame rate drops.
+//! [`Frames::report`] says how well it's keeping up.
+//!
+//! On a Neotron, the time spent running the game is only noticed when the
+//! real-time clock ticks over, once a second. On a PC it's measured
+//! properly.
+
+#![no_std]
+#![deny(missing_docs)]
+#![deny(unsafe_code)]
+
+mod clock;
+mod report;
+
+pub use clock::Clock;
+pub use report::Report;
+
+/// How often we call the polling function while waiting, in milliseconds
+pub const POLL_MS: u32 = 5;
+
+/// The most steps we'll ask a game to catch up on at once
+pub const MAX_STEPS: u32 = 4;
+
+/// Runs a game loop at a steady rate
+pub struct Frames {
+    clock: Clock,
+    /// How long each step is, in milliseconds
+    period_ms: u32,
+    /// When the next step is due, by our clock
+    due_ms: u64,
+    /// When the report started, by our clock
+    report_ms: u64,
+    frames: u32,
+    idle_ms: u64,
+    late: u32,
+    skipped: u32,
+}
+
+impl Frames {
+    /// Make a scheduler which runs `fps` frames a second
+    pub const fn new(fps: u32) -> Frames {
+        let fps = if fps == 0 { 1 } else { fps };
+        Frames::with_period_ms(1000 / fps)
+    }
+
+    /// Make a scheduler which runs a frame every `ms` milliseconds
+    pub const fn with_period_ms(ms: u32) -> Frames {
+        Frames {
+            clock: Clock::new(),
+            period_ms: if ms == 0 { 1 } else { ms },
+            due_ms: 0,
+            report_ms: 0,
+            frames: 0,
+            idle_ms: 0,
+            late: 0,
+            skipped: 0,
+        }
+    }
+
+    /// How long each frame is, in milliseconds
+    pub fn period_ms(&self) -> u32 {
+        self.period_ms
+    }
+
+    /// Change how long each frame is.
+    ///
+    /// The frame we're waiting for stays where it is, and the new length
+    /// counts from the one after.
+    pub fn set_period_ms(&mut self, ms: u32) {
+        self.period_ms = ms.max(1);
+    }
+
+    /// Start the clock. The first frame is due one period from now.
+    pub fn start(&mut self) {
+        self.clock.start();
+        self.due_ms = u64::from(self.period_ms);
+        self.reset_report();
+    }
+
+    /// Start again after a pause, without owing the game for the time it
+    /// was paused
+    pub fn resume(&mut self) {
+        self.clock.sync();
+        self.due_ms = self.clock.now_ms() + u64::from(self.period_ms);
+    }
+
+    /// Milliseconds since [`Frames::start`]
+    pub fn elapsed_ms(&self) -> u64 {
+        self.clock.now_ms()
+    }
+
+    /// Is the time we measure right, or are we only counting sleeps because
+    /// there's no real-time clock?
+    pub fn is_accurate(&self) -> bool {
+        self.clock.is_accurate()
+    }
+
+    /// Wait for the next frame.
+    ///
+    /// Returns how many steps the game should take - usually one, but more
+    /// if it has fallen behind.
+    pub fn wait(&mut self) -> u32 {
+        self.wait_polling(|| {})
+    }
+
+    /// Wait for the next frame, calling `poll` every [`POLL_MS`] while we
+    /// wait, and at least once even if the frame is already due.
+    ///
+    /// Returns how many steps the game should take - usually one, but more
+    /// if it has fallen behind.
+    pub fn wait_polling(&mut self, mut poll: impl FnMut()) -> u32 {
+        self.clock.sync();
+        let mut now = self.clock.now_ms();
+        if now > self.due_ms {
+            self.late += 1;
+        }
+        loop {
+            poll();
+            if now >= self.due_ms {
+                break;
+            }
+            let ms = (self.due_ms - now).min(u64::from(POLL_MS)) as u32;
+            self.clock.sleep_ms(ms);
+            self.idle_ms += u64::from(ms);
+            now = self.clock.now_ms();
+        }
+        let period = u64::from(self.period_ms);
+        let owed = 1 + (now - self.due_ms) / period;
+        self.due_ms += owed * period;
+        let steps = owed.min(u64::from(MAX_STEPS)) as u32;
+        self.skipped = self
+            .skipped
+            .saturating_add((owed as u32).saturating_sub(steps));
+        self.frames = self.frames.saturating_add(1);
+        steps
+    }
+
+    /// How well we've kept up since the report was last reset
+    pub fn report(&self) -> Report {
+        Report {
+            period_ms: self.period_ms,
+            frames: self.frames,
+            elapsed_ms: self.clock.now_ms().saturating_sub(self.report_ms),
+            idle_ms: self.idle_ms,
+            late: self.late,
+            skipped: self.skipped,
+        }
+    }
+
+    /// Start a new report from now
+    pub fn reset_report(&mut self) {
+        self.report_ms = self.clock.now_ms();
+        self.frames = 0;
+        self.idle_ms = 0;
+        self.late = 0;
+        self.skipped = 0;
+    }
+}
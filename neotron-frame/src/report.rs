@@ -0,0 +1,60 @@
+//! How well a game is keeping up.
+
+/// How well a game kept to its frame rate, since the report started
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Report {
+    /// How long each frame should take, in milliseconds
+    pub period_ms: u32,
+    /// How many frames we've run
+    pub frames: u32,
+    /// How long the report covers, in milliseconds
+    pub elapsed_ms: u64,
+    /// How much of that was spent waiting for the next frame
+    pub idle_ms: u64,
+    /// Frames which started after they were due
+    pub late: u32,
+    /// Steps we dropped because we were too far behind to catch up
+    pub skipped: u32,
+}
+
+impl Report {
+    /// The frame rate we're aiming for, in tenths of a frame per second
+    pub fn target_fps_tenths(&self) -> u32 {
+        10_000 / self.period_ms.max(1)
+    }
+
+    /// The frame rate we got, in tenths of a frame per second
+    pub fn fps_tenths(&self) -> u32 {
+        if self.elapsed_ms == 0 {
+            return 0;
+        }
+        (u64::from(self.frames) * 10_000 / self.elapsed_ms) as u32
+    }
+
+    /// How much of the frame budget the game used, as a percentage
+    pub fn busy_percent(&self) -> u32 {
+        if self.elapsed_ms == 0 {
+            return 0;
+        }
+        let busy = self.elapsed_ms.saturating_sub(self.idle_ms);
+        (busy * 100 / self.elapsed_ms) as u32
+    }
+}
+
+impl core::fmt::Display for Report {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let fps = self.fps_tenths();
+        let target = self.target_fps_tenths();
+        write!(
+            f,
+            "{}.{} fps of {}.{}, {}% busy, {} late, {} skipped",
+            fps / 10,
+            fps % 10,
+            target / 10,
+            target % 10,
+            self.busy_percent(),
+            self.late,
+            self.skipped
+        )
+    }
+}
@@ -0,0 +1,53 @@
+//! Simple wildcard patterns, like `*.MOD`.
+//!
+//! A `*` matches any run of bytes (including none) and a `?` matches any one
+//! byte. Everything else must match exactly, or ignoring ASCII case if you ask.
+
+/// Does the pattern match the text?
+///
+/// If `anywhere` is set, the pattern can match any part of the text, like
+/// `grep` does. Otherwise it must match the whole text, like a file name.
+pub fn matches(pattern: &[u8], text: &[u8], ignore_case: bool, anywhere: bool) -> bool {
+    let same = |a: u8, b: u8| {
+        if ignore_case {
+            a.eq_ignore_ascii_case(&b)
+        } else {
+            a == b
+        }
+    };
+    let mut p = 0;
+    let mut t = 0;
+    // Where to go back to if we fail after a `*`. Matching anywhere is the
+    // same as having a `*` on the front.
+    let mut star = if anywhere { Some((0, 0)) } else { None };
+    loop {
+        if p == pattern.len() {
+            // Matching anywhere is also like having a `*` on the end
+            if anywhere || t == text.len() {
+                return true;
+            }
+        } else if pattern[p] == b'*' {
+            p += 1;
+            star = Some((p, t));
+            continue;
+        } else if t < text.len() && (pattern[p] == b'?' || same(pattern[p], text[t])) {
+            p += 1;
+            t += 1;
+            continue;
+        }
+        // No match here, so let the last `*` eat one more byte
+        match star {
+            Some((star_p, star_t)) if star_t < text.len() => {
+                p = star_p;
+                t = star_t + 1;
+                star = Some((star_p, t));
+            }
+            _ => return false,
+        }
+    }
+}
+
+/// Does this text have any wildcards in it?
+pub fn has_wildcards(text: &str) -> bool {
+    text.bytes().any(|b| b == b'*' || b == b'?')
+}
@@ -0,0 +1,424 @@
+//! Directories, wildcards and sorting, for Neotron applications.
+//!
+//! The SDK gives us a directory one raw entry at a time. This crate adds
+//! the things applications kept writing for themselves:
+//!
+//! * [`Dir`] reads a directory, with tidy names and without `.` and `..`
+//! * [`list`] fills a slice with the files matching a wildcard, like
+//!   `*.MOD`, and [`sort`] puts them in order
+//! * [`Walk`] goes through a directory and everything under it
+//! * [`PathBuf`] builds up a path without an allocator
+//!
+//! ```rust,ignore
+//! let mut songs = [Entry::EMPTY; 32];
+//! let count = neotron_fs::list("0:/MUSIC/", "*.MOD", &mut songs)?;
+//! let songs = &mut songs[0..count.min(songs.len())];
+//! neotron_fs::sort(songs, Order::Name);
+//! ```
+
+#![no_std]
+#![deny(missing_docs)]
+#![deny(unsafe_code)]
+
+use core::cmp::Ordering;
+
+pub mod glob;
+
+/// The longest path we can build
+pub const MAX_PATH: usize = 96;
+
+/// How many directories deep a [`Walk`] can go, counting the one it starts
+/// in. Each one is open while we're inside it.
+pub const MAX_DEPTH: usize = 8;
+
+/// The longest file name on a disk, which is an 8.3 name
+const MAX_NAME: usize = 12;
+
+/// The name of a file or directory
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Name {
+    bytes: [u8; MAX_NAME],
+    len: u8,
+}
+
+impl Name {
+    /// The name, like `SONG.MOD`
+    pub fn as_str(&self) -> &str {
+        core::str::from_utf8(self.as_bytes()).unwrap_or("?")
+    }
+
+    /// The name, as bytes
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes[0..usize::from(self.len)]
+    }
+
+    /// The part after the last `.`, or nothing if there isn't one
+    pub fn extension(&self) -> &str {
+        match self.as_str().rsplit_once('.') {
+            Some((_, extension)) => extension,
+            None => "",
+        }
+    }
+}
+
+/// Something in a directory
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Entry {
+    name: Name,
+    size: u64,
+    is_dir: bool,
+}
+
+impl Entry {
+    /// A blank entry, for filling arrays before calling [`list`]
+    pub const EMPTY: Entry = Entry {
+        name: Name {
+            bytes: [0; MAX_NAME],
+            len: 0,
+        },
+        size: 0,
+        is_dir: false,
+    };
+
+    /// What it's called
+    pub fn name(&self) -> &Name {
+        &self.name
+    }
+
+    /// How big it is, in bytes
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Is it a directory?
+    pub fn is_dir(&self) -> bool {
+        self.is_dir
+    }
+
+    /// Does the name match a wildcard pattern, like `*.MOD`? Names on disks
+    /// don't care about case, so neither does this.
+    pub fn matches(&self, pattern: &str) -> bool {
+        glob::matches(pattern.as_bytes(), self.name.as_bytes(), true, false)
+    }
+}
+
+impl From<neotron_api::dir::Entry> for Entry {
+    fn from(entry: neotron_api::dir::Entry) -> Self {
+        // Names are padded out with spaces or nuls
+        let len = entry
+            .name
+            .iter()
+            .position(|b| *b == 0 || *b == b' ')
+            .unwrap_or(entry.name.len())
+            .min(MAX_NAME);
+        let mut name = Name {
+            bytes: [0; MAX_NAME],
+            len: len as u8,
+        };
+        name.bytes[0..len].copy_from_slice(&entry.name[0..len]);
+        Entry {
+            name,
+            size: entry.properties.file_size,
+            is_dir: entry
+                .properties
+                .attr
+                .contains(neotron_api::file::Attributes::DIRECTORY),
+        }
+    }
+}
+
+/// Reads the entries in a directory, skipping `.` and `..`
+pub struct Dir {
+    inner: neotron_sdk::ReadDir,
+}
+
+impl Dir {
+    /// Open a directory, like `0:/MUSIC/`. An empty path is the current
+    /// directory.
+    pub fn open(path: &str) -> Result<Dir, neotron_sdk::Error> {
+        let path = neotron_sdk::path::Path::new(path)?;
+        Ok(Dir {
+            inner: neotron_sdk::ReadDir::open(path)?,
+        })
+    }
+}
+
+impl Iterator for Dir {
+    type Item = Result<Entry, neotron_sdk::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let entry = match self.inner.next()? {
+                Ok(entry) => Entry::from(entry),
+                Err(e) => return Some(Err(e)),
+            };
+            if !matches!(entry.name.as_bytes(), b"." | b"..") {
+                return Some(Ok(entry));
+            }
+        }
+    }
+}
+
+/// Put the files in `directory` whose names match `pattern` into `entries`,
+/// in the order the disk has them. Directories are left out.
+///
+/// Gives how many files matched, which is more than `entries` holds if
+/// some didn't fit.
+pub fn list(
+    directory: &str,
+    pattern: &str,
+    entries: &mut [Entry],
+) -> Result<usize, neotron_sdk::Error> {
+    let mut count = 0;
+    for entry in Dir::open(directory)? {
+        let entry = entry?;
+        if entry.is_dir || !entry.matches(pattern) {
+            continue;
+        }
+        if let Some(slot) = entries.get_mut(count) {
+            *slot = entry;
+        }
+        count += 1;
+    }
+    Ok(count)
+}
+
+/// How to [`sort`] entries
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Order {
+    /// A to Z, ignoring case
+    Name,
+    /// By extension, then by name, so the same kinds of file are together
+    Extension,
+    /// Smallest first, then by name
+    Size,
+}
+
+/// Sort some entries. Directories always come before files, like they do in
+/// a file manager.
+pub fn sort(entries: &mut [Entry], order: Order) {
+    entries.sort_unstable_by(|a, b| {
+        let by_name = || compare_ignoring_case(a.name.as_bytes(), b.name.as_bytes());
+        b.is_dir.cmp(&a.is_dir).then_with(|| match order {
+            Order::Name => by_name(),
+            Order::Extension => {
+                compare_ignoring_case(a.name.extension().as_bytes(), b.name.extension().as_bytes())
+                    .then_with(by_name)
+            }
+            Order::Size => a.size.cmp(&b.size).then_with(by_name),
+        })
+    });
+}
+
+/// Compare two names, as if they were both in upper case
+fn compare_ignoring_case(a: &[u8], b: &[u8]) -> Ordering {
+    a.iter()
+        .map(u8::to_ascii_uppercase)
+        .cmp(b.iter().map(u8::to_ascii_uppercase))
+}
+
+/// Split a path into the directory, up to and including the last `/` or
+/// `:`, and the name after it.
+///
+/// `0:/DOCS/*.TXT` gives `0:/DOCS/` and `*.TXT`.
+pub fn split(path: &str) -> (&str, &str) {
+    match path.rfind(['/', ':']) {
+        Some(idx) => path.split_at(idx + 1),
+        None => ("", path),
+    }
+}
+
+/// A path, built up a piece at a time
+#[derive(Debug, Clone)]
+pub struct PathBuf {
+    bytes: [u8; MAX_PATH],
+    len: usize,
+}
+
+impl PathBuf {
+    /// Make an empty path
+    pub const fn new() -> PathBuf {
+        PathBuf {
+            bytes: [0; MAX_PATH],
+            len: 0,
+        }
+    }
+
+    /// Make a path from some text
+    pub fn from_path(path: &str) -> Result<PathBuf, neotron_sdk::Error> {
+        let mut result = PathBuf::new();
+        result.push_str(path)?;
+        Ok(result)
+    }
+
+    /// The path
+    pub fn as_str(&self) -> &str {
+        core::str::from_utf8(&self.bytes[0..self.len]).unwrap_or("?")
+    }
+
+    /// How long the path is, in bytes
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Is the path empty?
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Add a name to the end, with a `/` before it if it needs one
+    pub fn push(&mut self, name: &str) -> Result<(), neotron_sdk::Error> {
+        if !self.is_empty() && !self.as_str().ends_with(['/', ':']) {
+            self.push_str("/")?;
+        }
+        self.push_str(name)
+    }
+
+    /// Take the last name off the end, leaving the directory it was in with
+    /// its `/`. Gives `false` if there's nothing left to take off.
+    pub fn pop(&mut self) -> bool {
+        let path = self.as_str().trim_end_matches('/');
+        let (directory, name) = split(path);
+        if name.is_empty() {
+            return false;
+        }
+        self.len = directory.len();
+        true
+    }
+
+    /// Add some text to the end, as it is. Fails, and leaves the path as it
+    /// was, if it doesn't fit.
+    pub fn push_str(&mut self, text: &str) -> Result<(), neotron_sdk::Error> {
+        let end = self.len + text.len();
+        if end > MAX_PATH {
+            return Err(neotron_sdk::Error::InvalidPath);
+        }
+        self.bytes[self.len..end].copy_from_slice(text.as_bytes());
+        self.len = end;
+        Ok(())
+    }
+
+    /// Cut the path back to `len` bytes
+    fn truncate(&mut self, len: usize) {
+        self.len = self.len.min(len);
+    }
+}
+
+impl Default for PathBuf {
+    fn default() -> Self {
+        PathBuf::new()
+    }
+}
+
+/// Goes through a directory and every directory under it.
+///
+/// Each directory is given before what's inside it. [`Walk::path`] says
+/// where the last entry was, and [`Walk::skip`] stops us going into a
+/// directory we've just been given.
+///
+/// ```rust,ignore
+/// let mut walk = Walk::new("0:/")?;
+/// while let Some(entry) = walk.next() {
+///     if entry?.matches("*.TXT") {
+///         search(walk.path());
+///     }
+/// }
+/// ```
+pub struct Walk {
+    /// The directories we're inside, outermost first
+    dirs: [Option<Dir>; MAX_DEPTH],
+    /// How long `path` is for each directory in `dirs`, with its `/`
+    bases: [usize; MAX_DEPTH],
+    /// How many entries of `dirs` are open
+    depth: usize,
+    /// Where the last entry was
+    path: PathBuf,
+    /// Should the next call go into the directory we last gave?
+    descend: bool,
+    /// How many directories deep we go, counting the first
+    max_depth: usize,
+}
+
+impl Walk {
+    /// Start walking from `root`, like `0:/`. An empty path is the current
+    /// directory.
+    pub fn new(root: &str) -> Result<Walk, neotron_sdk::Error> {
+        let mut path = PathBuf::from_path(root)?;
+        if !path.is_empty() && !root.ends_with('/') {
+            path.push_str("/")?;
+        }
+        let mut walk = Walk {
+            dirs: [const { None }; MAX_DEPTH],
+            bases: [0; MAX_DEPTH],
+            depth: 0,
+            path,
+            descend: false,
+            max_depth: MAX_DEPTH,
+        };
+        walk.open_path()?;
+        Ok(walk)
+    }
+
+    /// Don't go more than `levels` directories below the first. Zero means
+    /// only the first directory.
+    pub fn max_depth(mut self, levels: usize) -> Walk {
+        self.max_depth = levels.saturating_add(1).min(MAX_DEPTH);
+        self
+    }
+
+    /// Where the last entry was, like `0:/MUSIC/SONG.MOD`
+    pub fn path(&self) -> &str {
+        self.path.as_str()
+    }
+
+    /// How many directories below the first the last entry was
+    pub fn depth(&self) -> usize {
+        self.depth.saturating_sub(1)
+    }
+
+    /// Don't go into the directory we've just been given
+    pub fn skip(&mut self) {
+        self.descend = false;
+    }
+
+    /// Open the directory in `path`, which ends with a `/`, and go into it
+    fn open_path(&mut self) -> Result<(), neotron_sdk::Error> {
+        let dir = Dir::open(self.path.as_str())?;
+        self.dirs[self.depth] = Some(dir);
+        self.bases[self.depth] = self.path.len();
+        self.depth += 1;
+        Ok(())
+    }
+}
+
+impl Iterator for Walk {
+    type Item = Result<Entry, neotron_sdk::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if core::mem::take(&mut self.descend) {
+            if let Err(e) = self.path.push_str("/").and_then(|_| self.open_path()) {
+                return Some(Err(e));
+            }
+        }
+        while self.depth > 0 {
+            let next = self.dirs[self.depth - 1].as_mut().and_then(Dir::next);
+            match next {
+                None => {
+                    // Finished with this directory
+                    self.depth -= 1;
+                    self.dirs[self.depth] = None;
+                }
+                Some(Err(e)) => return Some(Err(e)),
+                Some(Ok(entry)) => {
+                    self.path.truncate(self.bases[self.depth - 1]);
+                    if let Err(e) = self.path.push_str(entry.name.as_str()) {
+                        return Some(Err(e));
+                    }
+                    self.descend = entry.is_dir && self.depth < self.max_depth;
+                    return Some(Ok(entry));
+                }
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,98 @@
+//! Comparing output with golden files.
+
+use std::path::Path;
+
+/// How many bytes either side of a difference we show
+const CONTEXT: usize = 40;
+
+/// Check some output against a golden file.
+///
+/// If they differ, we save what we got next to the golden file with `.new`
+/// on the end, and panic saying where they first differ. If there's no
+/// golden file, we panic too, unless `UPDATE_GOLDEN` is set - then we
+/// record one, or replace one that differs.
+pub fn assert_golden(path: impl AsRef<Path>, actual: &[u8]) {
+    let path = path.as_ref();
+    let update = std::env::var_os("UPDATE_GOLDEN").is_some_and(|v| v != "0");
+    let expected = match std::fs::read(path) {
+        Ok(expected) => expected,
+        Err(_) if update => {
+            record(path, actual);
+            eprintln!(
+                "Recorded a new golden file at {} - check it, then commit it",
+                path.display()
+            );
+            return;
+        }
+        Err(e) => {
+            panic!(
+                "Can't read the golden file at {} ({}). Run the tests with UPDATE_GOLDEN=1 to record one.",
+                path.display(),
+                e
+            );
+        }
+    };
+    if expected == actual {
+        return;
+    }
+    if update {
+        record(path, actual);
+        eprintln!("Updated the golden file at {}", path.display());
+        return;
+    }
+    let mut new_path = path.as_os_str().to_owned();
+    new_path.push(".new");
+    let _ = std::fs::write(&new_path, actual);
+    let at = expected
+        .iter()
+        .zip(actual)
+        .position(|(e, a)| e != a)
+        .unwrap_or(expected.len().min(actual.len()));
+    panic!(
+        "Output doesn't match {}\n\
+         It is {} bytes, and should be {}. The first difference is at byte {}:\n\
+         expected: {}\n     \
+         got: {}\n\
+         What we got is in {}. If it's right, run the tests with UPDATE_GOLDEN=1.",
+        path.display(),
+        actual.len(),
+        expected.len(),
+        at,
+        excerpt(&expected, at),
+        excerpt(actual, at),
+        Path::new(&new_path).display(),
+    );
+}
+
+/// Save a golden file, making its directory if we need to
+fn record(path: &Path, contents: &[u8]) {
+    if let Some(dir) = path.parent() {
+        let _ = std::fs::create_dir_all(dir);
+    }
+    std::fs::write(path, contents).unwrap_or_else(|e| panic!("writing {}: {}", path.display(), e));
+}
+
+/// The bytes around a position, with escape codes and the like made
+/// visible
+fn excerpt(bytes: &[u8], at: usize) -> String {
+    // Don't start or stop part way through a character
+    let is_continuation = |idx: usize| bytes.get(idx).is_some_and(|b| b & 0xC0 == 0x80);
+    let mut start = at.saturating_sub(CONTEXT).min(bytes.len());
+    while start < at && is_continuation(start) {
+        start += 1;
+    }
+    let mut end = (at + CONTEXT).min(bytes.len());
+    while end > at && is_continuation(end) {
+        end -= 1;
+    }
+    let mut text = String::new();
+    for chunk in bytes[start..end].utf8_chunks() {
+        for ch in chunk.valid().chars() {
+            text.extend(ch.escape_debug());
+        }
+        for byte in chunk.invalid() {
+            text.push_str(&format!("\\x{:02x}", byte));
+        }
+    }
+    format!("\"{}\"", text)
+}
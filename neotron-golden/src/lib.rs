@@ -0,0 +1,48 @@
+//! Golden-output tests for Neotron applications.
+//!
+//! A test builds the application for the PC, runs it with `neotron-host`
+//! standing in for the Neotron, and types at it from a script. Everything
+//! the application sends to the console - the ANSI escape codes and all -
+//! is captured, along with any files it leaves on its disk, such as a WAV
+//! recording of its sound. Those are compared with golden copies kept next
+//! to the test.
+//!
+//! ```ignore
+//! let output = Run::new(env!("CARGO_BIN_EXE_snake"))
+//!     .wait_ms(300)
+//!     .send(b"p")
+//!     .wait_ms(3000)
+//!     .send(b"q")
+//!     .run();
+//! assert_golden(golden!("snake_crash.ansi"), &output.stdout);
+//! ```
+//!
+//! A missing golden file fails the test, the same as a wrong one. To record
+//! a new one, or to record new output after changing it on purpose, run
+//! the tests with `UPDATE_GOLDEN=1`, then check what changed and commit it.
+//!
+//! Runs are repeatable because every random number generator gets the same
+//! seed, and the sound goes nowhere unless the test asks for it.
+
+#![cfg_attr(target_os = "none", no_std)]
+#![deny(missing_docs)]
+#![deny(unsafe_code)]
+
+#[cfg(not(target_os = "none"))]
+mod golden;
+#[cfg(not(target_os = "none"))]
+mod run;
+
+#[cfg(not(target_os = "none"))]
+pub use golden::assert_golden;
+#[cfg(not(target_os = "none"))]
+pub use run::{Output, Run};
+
+/// The path of a golden file, in the `tests/golden` directory of the crate
+/// being tested
+#[macro_export]
+macro_rules! golden {
+    ($name:literal) => {
+        concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden/", $name)
+    };
+}
@@ -0,0 +1,190 @@
+//! Running an application from a script.
+
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+/// How long an application gets, unless the test says otherwise
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The seed every run gets, unless the test says otherwise
+const DEFAULT_SEED: &str = "1";
+
+/// Counts runs, so each gets its own disk
+static RUNS: AtomicUsize = AtomicUsize::new(0);
+
+/// One thing the script does
+enum Step {
+    /// Do nothing for a while
+    Wait(Duration),
+    /// Type some bytes
+    Send(Vec<u8>),
+}
+
+/// An application to run, and what to do to it
+pub struct Run {
+    program: PathBuf,
+    args: Vec<String>,
+    env: Vec<(String, String)>,
+    files: Vec<(String, Vec<u8>)>,
+    steps: Vec<Step>,
+    timeout: Duration,
+}
+
+impl Run {
+    /// Get ready to run a program - usually `env!("CARGO_BIN_EXE_<name>")`
+    pub fn new(program: impl Into<PathBuf>) -> Run {
+        Run {
+            program: program.into(),
+            args: Vec::new(),
+            env: vec![
+                ("NEOTRON_SEED".into(), DEFAULT_SEED.into()),
+                ("NEOTRON_AUDIO".into(), "none".into()),
+            ],
+            files: Vec::new(),
+            steps: Vec::new(),
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+
+    /// Give the application an argument
+    pub fn arg(mut self, arg: &str) -> Run {
+        self.args.push(arg.into());
+        self
+    }
+
+    /// Set an environment variable, like `NEOTRON_AUDIO`
+    pub fn env(mut self, key: &str, value: &str) -> Run {
+        self.env.retain(|(k, _)| k != key);
+        self.env.push((key.into(), value.into()));
+        self
+    }
+
+    /// Put a file on the application's disk before it starts
+    pub fn file(mut self, name: &str, contents: impl Into<Vec<u8>>) -> Run {
+        self.files.push((name.into(), contents.into()));
+        self
+    }
+
+    /// Wait a while before the next step
+    pub fn wait_ms(mut self, ms: u64) -> Run {
+        self.steps.push(Step::Wait(Duration::from_millis(ms)));
+        self
+    }
+
+    /// Type some keys
+    pub fn send(mut self, bytes: &[u8]) -> Run {
+        self.steps.push(Step::Send(bytes.to_vec()));
+        self
+    }
+
+    /// Give up on the application if it runs longer than this
+    pub fn timeout(mut self, timeout: Duration) -> Run {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Run the application, work through the script, and wait for it to
+    /// finish.
+    ///
+    /// Once the script is done we close the application's input. If it is
+    /// still going when the time runs out, we stop it and panic.
+    pub fn run(self) -> Output {
+        let disk = std::env::temp_dir().join(format!(
+            "neotron-golden-{}-{}",
+            std::process::id(),
+            RUNS.fetch_add(1, Ordering::Relaxed)
+        ));
+        let _ = std::fs::remove_dir_all(&disk);
+        std::fs::create_dir_all(&disk).expect("making the disk directory");
+        for (name, contents) in &self.files {
+            std::fs::write(disk.join(name), contents).expect("putting a file on the disk");
+        }
+
+        let mut child = Command::new(&self.program)
+            .args(&self.args)
+            .envs(self.env.iter().map(|(k, v)| (k, v)))
+            .env("NEOTRON_DISK", &disk)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .unwrap_or_else(|e| panic!("starting {}: {}", self.program.display(), e));
+
+        // Read everything it says in the background, so it never blocks
+        let mut stdout = child.stdout.take().expect("application's output");
+        let reader = std::thread::spawn(move || {
+            let mut captured = Vec::new();
+            let _ = stdout.read_to_end(&mut captured);
+            captured
+        });
+
+        let started = Instant::now();
+        let mut stdin = child.stdin.take();
+        for step in &self.steps {
+            match step {
+                Step::Wait(duration) => std::thread::sleep(*duration),
+                Step::Send(bytes) => {
+                    if let Some(input) = stdin.as_mut() {
+                        // If it has already quit, that's for the golden
+                        // output to show
+                        let _ = input.write_all(bytes).and_then(|_| input.flush());
+                    }
+                }
+            }
+        }
+        drop(stdin);
+
+        let status = loop {
+            if let Some(status) = child.try_wait().expect("waiting for the application") {
+                break status;
+            }
+            if started.elapsed() > self.timeout {
+                let _ = child.kill();
+                let _ = child.wait();
+                panic!(
+                    "{} was still running after {:?}",
+                    self.program.display(),
+                    self.timeout
+                );
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        };
+
+        Output {
+            stdout: reader.join().expect("reading the application's output"),
+            success: status.success(),
+            disk,
+        }
+    }
+}
+
+/// What an application did
+pub struct Output {
+    /// Everything it sent to the console
+    pub stdout: Vec<u8>,
+    /// Did it exit with a zero status?
+    pub success: bool,
+    disk: PathBuf,
+}
+
+impl Output {
+    /// The directory that stood in for its disk
+    pub fn disk(&self) -> &Path {
+        &self.disk
+    }
+
+    /// A file it left on its disk
+    pub fn file(&self, name: &str) -> Vec<u8> {
+        std::fs::read(self.disk.join(name))
+            .unwrap_or_else(|e| panic!("reading {} from the disk: {}", name, e))
+    }
+}
+
+impl Drop for Output {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.disk);
+    }
+}
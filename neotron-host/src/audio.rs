@@ -0,0 +1,188 @@
+//! Sound, through the computer's sound card.
+//!
+//! A Neotron's audio device takes 16-bit little-endian stereo, and a write
+//! waits until there is room in its buffer - which is how applications keep
+//! in time with the music. We do the same: `aplay` holds us up when its
+//! buffer is full, and when recording or staying silent we keep to the
+//! clock ourselves.
+
+use std::cell::RefCell;
+use std::io::{Seek, SeekFrom, Write};
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
+
+/// How far ahead of the clock an application may get, like the buffer in a
+/// real audio device
+const BUFFERED: Duration = Duration::from_millis(100);
+
+/// How many bytes one stereo frame takes
+const FRAME_LEN: u64 = 4;
+
+/// How long a WAV file's header is
+const WAV_HEADER_LEN: u32 = 44;
+
+/// Where the sound goes
+enum Backend {
+    /// Piped into a program that plays it
+    Player(Child),
+    /// Recorded into a WAV file
+    Wav { file: std::fs::File, data_len: u32 },
+    /// Thrown away
+    Silent,
+}
+
+/// Everything that changes as we play
+struct Inner {
+    backend: Backend,
+    sample_rate: u32,
+    /// When the first sample went out
+    started: Option<Instant>,
+    /// How many frames we've been given
+    frames: u64,
+    /// Should we keep to the clock when nothing else holds us up?
+    realtime: bool,
+}
+
+/// Plays 16-bit stereo audio, standing in for the `AUDIO:` device
+pub struct Sink {
+    inner: RefCell<Inner>,
+}
+
+impl Sink {
+    /// Start playing at the given sample rate.
+    ///
+    /// `NEOTRON_AUDIO` picks where the sound goes, and `NEOTRON_REALTIME`
+    /// set to `0` lets recording run as fast as it can. If we can't do what it
+    /// says, we say why and stay silent, so the application still runs.
+    ///
+    /// `NEOTRON_AUDIO_RATE` makes us run at that rate instead, whatever we
+    /// were asked for, like a device that only has one rate. Check
+    /// [`Sink::sample_rate`] to see which rate you got.
+    pub fn open(sample_rate: u32) -> Sink {
+        let sample_rate = std::env::var("NEOTRON_AUDIO_RATE")
+            .ok()
+            .and_then(|rate| rate.parse().ok())
+            .unwrap_or(sample_rate);
+        let choice = std::env::var("NEOTRON_AUDIO").unwrap_or_default();
+        let backend = match choice.as_str() {
+            "none" => Ok(Backend::Silent),
+            "" | "aplay" => start_player(sample_rate),
+            other => match other.strip_prefix("wav:") {
+                Some(path) => start_wav(path, sample_rate),
+                None => Err(std::io::Error::other(format!(
+                    "NEOTRON_AUDIO should be aplay, none or wav:FILE, not {:?}",
+                    other
+                ))),
+            },
+        };
+        let backend = backend.unwrap_or_else(|e| {
+            eprintln!("No sound: {}", e);
+            Backend::Silent
+        });
+        Sink {
+            inner: RefCell::new(Inner {
+                backend,
+                sample_rate,
+                started: None,
+                frames: 0,
+                realtime: std::env::var("NEOTRON_REALTIME").map_or(true, |v| v != "0"),
+            }),
+        }
+    }
+
+    /// The rate we're playing at
+    pub fn sample_rate(&self) -> u32 {
+        self.inner.borrow().sample_rate
+    }
+
+    /// Play some frames, waiting if we're too far ahead
+    pub fn write(&self, data: &[u8]) -> std::io::Result<()> {
+        let mut inner = self.inner.borrow_mut();
+        let started = *inner.started.get_or_insert_with(Instant::now);
+        inner.frames += data.len() as u64 / FRAME_LEN;
+        match &mut inner.backend {
+            Backend::Player(child) => {
+                // The pipe fills up when the player is busy, which keeps us
+                // in time
+                return match child.stdin.as_mut() {
+                    Some(stdin) => stdin.write_all(data),
+                    None => Err(std::io::ErrorKind::BrokenPipe.into()),
+                };
+            }
+            Backend::Wav { file, data_len } => {
+                file.write_all(data)?;
+                *data_len = data_len.saturating_add(data.len() as u32);
+            }
+            Backend::Silent => {}
+        }
+        if !inner.realtime {
+            return Ok(());
+        }
+        let played =
+            Duration::from_micros(inner.frames * 1_000_000 / u64::from(inner.sample_rate.max(1)));
+        let due = started + played;
+        if let Some(ahead) = due.checked_duration_since(Instant::now() + BUFFERED) {
+            std::thread::sleep(ahead);
+        }
+        Ok(())
+    }
+}
+
+impl Drop for Sink {
+    fn drop(&mut self) {
+        match &mut self.inner.get_mut().backend {
+            Backend::Player(child) => {
+                // Closing its input lets the player finish what it has
+                drop(child.stdin.take());
+                let _ = child.wait();
+            }
+            Backend::Wav { file, data_len } => {
+                let _ = finish_wav(file, *data_len);
+            }
+            Backend::Silent => {}
+        }
+    }
+}
+
+/// Start `aplay`, reading raw samples from a pipe
+fn start_player(sample_rate: u32) -> std::io::Result<Backend> {
+    let child = Command::new("aplay")
+        .args(["-q", "-t", "raw", "-f", "S16_LE", "-c", "2", "-r"])
+        .arg(sample_rate.to_string())
+        .stdin(Stdio::piped())
+        .spawn()
+        .map_err(|e| std::io::Error::other(format!("can't start aplay ({})", e)))?;
+    Ok(Backend::Player(child))
+}
+
+/// Start a WAV file. The lengths in the header get filled in at the end.
+fn start_wav(path: &str, sample_rate: u32) -> std::io::Result<Backend> {
+    let mut file = std::fs::File::create(path)?;
+    let byte_rate = sample_rate * FRAME_LEN as u32;
+    let mut header = Vec::with_capacity(WAV_HEADER_LEN as usize);
+    header.extend_from_slice(b"RIFF");
+    header.extend_from_slice(&(WAV_HEADER_LEN - 8).to_le_bytes());
+    header.extend_from_slice(b"WAVEfmt ");
+    header.extend_from_slice(&16u32.to_le_bytes());
+    // PCM, two channels
+    header.extend_from_slice(&1u16.to_le_bytes());
+    header.extend_from_slice(&2u16.to_le_bytes());
+    header.extend_from_slice(&sample_rate.to_le_bytes());
+    header.extend_from_slice(&byte_rate.to_le_bytes());
+    // Bytes per frame, and bits per sample
+    header.extend_from_slice(&(FRAME_LEN as u16).to_le_bytes());
+    header.extend_from_slice(&16u16.to_le_bytes());
+    header.extend_from_slice(b"data");
+    header.extend_from_slice(&0u32.to_le_bytes());
+    file.write_all(&header)?;
+    Ok(Backend::Wav { file, data_len: 0 })
+}
+
+/// Fill in the lengths in a WAV file's header
+fn finish_wav(file: &mut std::fs::File, data_len: u32) -> std::io::Result<()> {
+    file.seek(SeekFrom::Start(4))?;
+    file.write_all(&(data_len.saturating_add(WAV_HEADER_LEN - 8)).to_le_bytes())?;
+    file.seek(SeekFrom::Start(u64::from(WAV_HEADER_LEN) - 4))?;
+    file.write_all(&data_len.to_le_bytes())?;
+    file.flush()
+}
@@ -0,0 +1,84 @@
+//! Runs Neotron applications on a desktop computer.
+//!
+//! Build an application for your PC rather than a Neotron, and `neotron-sdk`
+//! gives it a console in your terminal. This crate fills in the rest, so
+//! you can run, debug and profile an application with `cargo run`:
+//!
+//! * [`os::init`] starts the application in place of `neotron_sdk::init()`,
+//!   and lets it open files. It works with a pipe for a console, as well as
+//!   a terminal, so a test can drive it.
+//! * [`setup`] moves into a directory which stands in for the Neotron's
+//!   disk, so save files and settings don't end up all over your computer.
+//!   It's `neotron-disk` unless you set `NEOTRON_DISK`.
+//! * [`audio::Sink`] plays what an application sends to the audio device,
+//!   through `aplay`. Set `NEOTRON_AUDIO` to `none` for silence, or to
+//!   `wav:FILE` to record it instead. Recording or silence keep to the
+//!   clock, unless `NEOTRON_REALTIME` is `0`.
+//! * [`seed`] gives `neotron-rand` a fixed seed from `NEOTRON_SEED`, so a
+//!   run can be repeated exactly.
+//! * [`net::Stream`] connects to a server over TCP, where a Neotron would
+//!   use a network device.
+//! * [`millis`] is a clock for `neotron-frame`, which on a Neotron has to
+//!   make do with counting delays.
+//! * [`on_panic`] lets an application tidy up before a panic stops it.
+//!
+//! On a Neotron this crate is empty, so applications can depend on it
+//! without checking which target they are built for.
+
+#![cfg_attr(target_os = "none", no_std)]
+#![deny(missing_docs)]
+#![deny(unsafe_code)]
+
+#[cfg(not(target_os = "none"))]
+pub mod audio;
+#[cfg(not(target_os = "none"))]
+pub mod net;
+#[cfg(not(target_os = "none"))]
+pub mod os;
+
+/// Where the disk goes, if `NEOTRON_DISK` doesn't say
+#[cfg(not(target_os = "none"))]
+const DEFAULT_DISK: &str = "neotron-disk";
+
+/// Get ready to run an application. Call this first thing in `main`, before
+/// [`os::init`].
+///
+/// If we can't make the disk directory we say so, and stay where we are.
+#[cfg(not(target_os = "none"))]
+pub fn setup() {
+    let disk = std::env::var_os("NEOTRON_DISK").unwrap_or_else(|| DEFAULT_DISK.into());
+    let result = std::fs::create_dir_all(&disk).and_then(|_| std::env::set_current_dir(&disk));
+    if let Err(e) = result {
+        eprintln!(
+            "Can't use {} as the disk ({}), so using the current directory",
+            std::path::Path::new(&disk).display(),
+            e
+        );
+    }
+}
+
+/// The seed the user wants every random number generator to start from, if
+/// they've set `NEOTRON_SEED`
+#[cfg(not(target_os = "none"))]
+pub fn seed() -> Option<u64> {
+    std::env::var("NEOTRON_SEED").ok()?.trim().parse().ok()
+}
+
+/// Run `cleanup` if the application panics, before the panic message, so
+/// it can put the console back and quieten the sound
+#[cfg(not(target_os = "none"))]
+pub fn on_panic(cleanup: fn()) {
+    let default = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        cleanup();
+        default(info);
+    }));
+}
+
+/// Milliseconds since the application first asked
+#[cfg(not(target_os = "none"))]
+pub fn millis() -> u64 {
+    static START: std::sync::OnceLock<std::time::Instant> = std::sync::OnceLock::new();
+    let start = START.get_or_init(std::time::Instant::now);
+    start.elapsed().as_millis() as u64
+}
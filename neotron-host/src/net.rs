@@ -0,0 +1,98 @@
+//! Network connections, for applications that talk to a server.
+//!
+//! A Neotron reaches the network through a device, which an application
+//! opens like any other. On a PC we make a TCP connection instead, and
+//! read from it without waiting, the way a device read returns whatever
+//! has arrived so far. To be the other end of a connection, wait for one
+//! with a [`Listener`].
+
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+/// How long we'll wait for a server to answer the phone
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// A TCP connection
+pub struct Stream {
+    stream: TcpStream,
+}
+
+impl Stream {
+    /// Connect to `host:port`
+    pub fn connect(endpoint: &str) -> std::io::Result<Stream> {
+        let mut last_error = None;
+        for address in endpoint.to_socket_addrs()? {
+            match TcpStream::connect_timeout(&address, CONNECT_TIMEOUT) {
+                Ok(stream) => return Stream::new(stream),
+                Err(e) => last_error = Some(e),
+            }
+        }
+        Err(last_error.unwrap_or_else(|| {
+            std::io::Error::other(format!("{} doesn't have an address", endpoint))
+        }))
+    }
+
+    /// Get a connection ready to use
+    fn new(stream: TcpStream) -> std::io::Result<Stream> {
+        stream.set_nonblocking(true)?;
+        let _ = stream.set_nodelay(true);
+        Ok(Stream { stream })
+    }
+
+    /// Read whatever has arrived, which may be nothing. Returns `Ok(0)`
+    /// when there's nothing yet, like a device would.
+    pub fn read(&self, buffer: &mut [u8]) -> std::io::Result<usize> {
+        match (&self.stream).read(buffer) {
+            Ok(0) if !buffer.is_empty() => Err(std::io::ErrorKind::UnexpectedEof.into()),
+            Ok(n) => Ok(n),
+            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => Ok(0),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Send all of `data`
+    pub fn write(&self, data: &[u8]) -> std::io::Result<()> {
+        let mut data = data;
+        while !data.is_empty() {
+            match (&self.stream).write(data) {
+                Ok(0) => return Err(std::io::ErrorKind::WriteZero.into()),
+                Ok(n) => data = &data[n..],
+                Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                    std::thread::sleep(Duration::from_millis(1));
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Waits for someone to connect to us
+pub struct Listener {
+    listener: TcpListener,
+}
+
+impl Listener {
+    /// Listen on the port in `host:port`, on every address we have. The
+    /// host is whoever the other end connects to, which is us.
+    pub fn bind(endpoint: &str) -> std::io::Result<Listener> {
+        let port = endpoint
+            .rsplit_once(':')
+            .and_then(|(_, port)| port.parse::<u16>().ok())
+            .ok_or_else(|| std::io::Error::other(format!("{} doesn't have a port", endpoint)))?;
+        let listener = TcpListener::bind(("0.0.0.0", port))?;
+        listener.set_nonblocking(true)?;
+        Ok(Listener { listener })
+    }
+
+    /// Take a connection, if someone has made one. Returns `Ok(None)` if
+    /// nobody has yet.
+    pub fn accept(&self) -> std::io::Result<Option<Stream>> {
+        match self.listener.accept() {
+            Ok((stream, _)) => Stream::new(stream).map(Some),
+            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+}
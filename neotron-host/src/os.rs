@@ -0,0 +1,376 @@
+//! The operating system, as an application sees it.
+//!
+//! `neotron_sdk::init()` gives an application a console, but nothing else:
+//! it can't open files, and it needs a terminal to put into raw mode. Here
+//! we hand the SDK a table of calls backed by the standard library instead,
+//! so an application can load and save files on the disk directory, and
+//! can be driven from a pipe as well as a keyboard.
+//!
+//! Paths work like they do on a Neotron. A drive, like `0:/`, means the top
+//! of the disk directory, and a drive with nothing after it, like `AUDIO:`,
+//! is a device - which we don't have, so it isn't found. We do the calls
+//! the SDK makes, and say the rest aren't implemented, as the SDK does.
+//!
+//! When standard input runs out, reading it gets
+//! `neotron_api::Error::EndOfFile` rather than waiting forever.
+
+use std::io::{IsTerminal, Read, Seek, SeekFrom, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, Receiver, TryRecvError};
+use std::sync::Mutex;
+
+use neotron_api::{dir, file, Error, FfiBuffer, FfiByteSlice, FfiString, Result};
+
+/// Handles below this are standard input, output and error
+const FIRST_HANDLE: usize = 3;
+
+/// How many files and directories can be open at once, as handles are a
+/// byte
+const MAX_HANDLES: usize = 256 - FIRST_HANDLE;
+
+/// The keys that have been typed
+static STDIN: Mutex<Option<Receiver<u8>>> = Mutex::new(None);
+
+/// Is the console in raw mode? If so, it needs a carriage return before
+/// every new line.
+static RAW: AtomicBool = AtomicBool::new(false);
+
+/// The open files, by handle, less [`FIRST_HANDLE`]
+static FILES: Mutex<Vec<Option<Open>>> = Mutex::new(Vec::new());
+
+/// The open directories, by handle
+static DIRS: Mutex<Vec<Option<std::fs::ReadDir>>> = Mutex::new(Vec::new());
+
+/// A file the application has open
+struct Open {
+    file: std::fs::File,
+    /// Did they ask to write to it?
+    writable: bool,
+}
+
+/// The calls an application can make
+static API: neotron_api::Api = neotron_api::Api {
+    open,
+    close,
+    write,
+    read,
+    seek_set,
+    seek_cur,
+    seek_end,
+    rename,
+    ioctl,
+    opendir,
+    closedir,
+    readdir,
+    stat,
+    fstat,
+    deletefile,
+    deletedir,
+    chdir,
+    dchdir,
+    pwd,
+    malloc,
+    free,
+};
+
+/// Start the application, in place of `neotron_sdk::init()`, and exit with
+/// whatever it returns
+pub fn init() -> ! {
+    let (sender, receiver) = channel();
+    *STDIN.lock().unwrap() = Some(receiver);
+    // Dropping the sender when input runs out tells `read` there's no more
+    std::thread::spawn(move || {
+        for byte in std::io::stdin().lock().bytes() {
+            let Ok(byte) = byte else {
+                break;
+            };
+            if sender.send(byte).is_err() {
+                break;
+            }
+        }
+    });
+
+    let raw = std::io::stdin().is_terminal() && crossterm::terminal::enable_raw_mode().is_ok();
+    RAW.store(raw, Ordering::Relaxed);
+    // The SDK reads the arguments from `std::env::args` itself
+    let code = neotron_sdk::app_entry(&API, 0, core::ptr::NonNull::dangling().as_ptr());
+    if raw {
+        let _ = crossterm::terminal::disable_raw_mode();
+    }
+    std::process::exit(code)
+}
+
+/// Where a path is on the disk directory, or `None` if it's a device
+fn local_path(path: &str) -> Option<std::path::PathBuf> {
+    let path = match path.split_once(':') {
+        Some((_drive, "")) => return None,
+        Some((_drive, rest)) => rest.trim_start_matches(['/', '\\']),
+        None => path,
+    };
+    Some(path.replace('\\', "/").into())
+}
+
+/// The nearest thing a Neotron has to a standard library error
+fn error(e: std::io::Error) -> Error {
+    match e.kind() {
+        std::io::ErrorKind::NotFound => Error::NotFound,
+        std::io::ErrorKind::PermissionDenied => Error::FileReadOnly,
+        std::io::ErrorKind::InvalidInput => Error::InvalidPath,
+        std::io::ErrorKind::UnexpectedEof => Error::EndOfFile,
+        std::io::ErrorKind::OutOfMemory => Error::OutOfMemory,
+        _ => Error::DeviceSpecific,
+    }
+}
+
+/// Put something in the first free slot of a handle table
+fn allocate<T>(table: &mut Vec<Option<T>>, item: T) -> Option<usize> {
+    let slot = match table.iter().position(Option::is_none) {
+        Some(slot) => slot,
+        None if table.len() < MAX_HANDLES => {
+            table.push(None);
+            table.len() - 1
+        }
+        None => return None,
+    };
+    table[slot] = Some(item);
+    Some(slot)
+}
+
+/// Do something with an open file
+fn with_file<T>(fd: file::Handle, f: impl FnOnce(&mut Open) -> Result<T>) -> Result<T> {
+    let mut files = FILES.lock().unwrap();
+    let slot = usize::from(fd.value()).checked_sub(FIRST_HANDLE);
+    match slot.and_then(|slot| files.get_mut(slot)?.as_mut()) {
+        Some(open) => f(open),
+        None => Result::Err(Error::BadHandle),
+    }
+}
+
+/// What we tell an application about a file. We don't keep Neotron
+/// times, so they're all zero.
+fn stat_of(metadata: &std::fs::Metadata) -> file::Stat {
+    let time = file::Time {
+        year_since_1970: 0,
+        zero_indexed_month: 0,
+        zero_indexed_day: 0,
+        hours: 0,
+        minutes: 0,
+        seconds: 0,
+    };
+    let mut attr = file::Attributes::empty();
+    if metadata.is_dir() {
+        attr |= file::Attributes::DIRECTORY;
+    }
+    if metadata.permissions().readonly() {
+        attr |= file::Attributes::READ_ONLY;
+    }
+    file::Stat {
+        file_size: metadata.len(),
+        ctime: time,
+        mtime: time,
+        attr,
+    }
+}
+
+extern "C" fn open(path: FfiString, flags: file::Flags) -> Result<file::Handle> {
+    let Some(path) = local_path(path.as_str()) else {
+        return Result::Err(Error::NotFound);
+    };
+    let writable = flags.contains(file::Flags::WRITE);
+    let create = flags.contains(file::Flags::CREATE);
+    let truncate = flags.contains(file::Flags::TRUNCATE);
+    let result = std::fs::OpenOptions::new()
+        .read(true)
+        .write(writable || create || truncate)
+        .create(create)
+        .truncate(truncate)
+        .open(path);
+    let file = match result {
+        Ok(file) => file,
+        Err(e) => return Result::Err(error(e)),
+    };
+    match allocate(&mut FILES.lock().unwrap(), Open { file, writable }) {
+        Some(slot) => Result::Ok(file::Handle::new((slot + FIRST_HANDLE) as u8)),
+        None => Result::Err(Error::OutOfMemory),
+    }
+}
+
+extern "C" fn close(fd: file::Handle) -> Result<()> {
+    let mut files = FILES.lock().unwrap();
+    let slot = usize::from(fd.value()).checked_sub(FIRST_HANDLE);
+    match slot.and_then(|slot| files.get_mut(slot)?.take()) {
+        Some(_) => Result::Ok(()),
+        None => Result::Err(Error::BadHandle),
+    }
+}
+
+extern "C" fn write(fd: file::Handle, buffer: FfiByteSlice) -> Result<()> {
+    let buffer = buffer.as_slice();
+    let console = if fd == file::Handle::new_stdout() {
+        Some(Box::new(std::io::stdout().lock()) as Box<dyn Write>)
+    } else if fd == file::Handle::new_stderr() {
+        Some(Box::new(std::io::stderr().lock()) as Box<dyn Write>)
+    } else {
+        None
+    };
+    if let Some(mut console) = console {
+        let result = if RAW.load(Ordering::Relaxed) {
+            buffer
+                .split_inclusive(|&b| b == b'\n')
+                .try_for_each(|chunk| match chunk.strip_suffix(b"\n") {
+                    Some(line) => console.write_all(line).and(console.write_all(b"\r\n")),
+                    None => console.write_all(chunk),
+                })
+        } else {
+            console.write_all(buffer)
+        };
+        return result.and(console.flush()).map_err(error).into();
+    }
+    with_file(fd, |open| {
+        if !open.writable {
+            return Result::Err(Error::FileReadOnly);
+        }
+        open.file.write_all(buffer).map_err(error).into()
+    })
+}
+
+extern "C" fn read(fd: file::Handle, mut buffer: FfiBuffer) -> Result<usize> {
+    let Some(buffer) = buffer.as_mut_slice() else {
+        return Result::Err(Error::InvalidArg);
+    };
+    if fd == file::Handle::new_stdin() {
+        let stdin = STDIN.lock().unwrap();
+        let Some(keys) = stdin.as_ref() else {
+            return Result::Err(Error::BadHandle);
+        };
+        let mut count = 0;
+        while count < buffer.len() {
+            match keys.try_recv() {
+                Ok(byte) => {
+                    buffer[count] = byte;
+                    count += 1;
+                }
+                Err(TryRecvError::Disconnected) if count == 0 && !buffer.is_empty() => {
+                    return Result::Err(Error::EndOfFile);
+                }
+                Err(_) => break,
+            }
+        }
+        return Result::Ok(count);
+    }
+    with_file(fd, |open| open.file.read(buffer).map_err(error).into())
+}
+
+extern "C" fn seek_set(fd: file::Handle, position: u64) -> Result<()> {
+    with_file(fd, |open| {
+        open.file
+            .seek(SeekFrom::Start(position))
+            .map(|_| ())
+            .map_err(error)
+            .into()
+    })
+}
+
+extern "C" fn seek_cur(fd: file::Handle, offset: i64) -> Result<u64> {
+    with_file(fd, |open| {
+        open.file
+            .seek(SeekFrom::Current(offset))
+            .map_err(error)
+            .into()
+    })
+}
+
+extern "C" fn seek_end(fd: file::Handle) -> Result<u64> {
+    with_file(fd, |open| {
+        open.file.seek(SeekFrom::End(0)).map_err(error).into()
+    })
+}
+
+extern "C" fn rename(old_path: FfiString, new_path: FfiString) -> Result<()> {
+    let (Some(old_path), Some(new_path)) =
+        (local_path(old_path.as_str()), local_path(new_path.as_str()))
+    else {
+        return Result::Err(Error::InvalidPath);
+    };
+    // A Neotron won't rename over a file that's already there
+    if new_path.exists() {
+        return Result::Err(Error::InvalidPath);
+    }
+    std::fs::rename(old_path, new_path).map_err(error).into()
+}
+
+extern "C" fn ioctl(_fd: file::Handle, _command: u64, _value: u64) -> Result<u64> {
+    Result::Err(Error::Unimplemented)
+}
+
+extern "C" fn opendir(path: FfiString) -> Result<dir::Handle> {
+    let Some(path) = local_path(path.as_str()) else {
+        return Result::Err(Error::NotFound);
+    };
+    // An empty path is the directory we're in
+    let path = if path.as_os_str().is_empty() {
+        ".".into()
+    } else {
+        path
+    };
+    let entries = match std::fs::read_dir(path) {
+        Ok(entries) => entries,
+        Err(e) => return Result::Err(error(e)),
+    };
+    match allocate(&mut DIRS.lock().unwrap(), entries) {
+        Some(slot) => Result::Ok(dir::Handle::new(slot as u8)),
+        None => Result::Err(Error::OutOfMemory),
+    }
+}
+
+extern "C" fn closedir(dir: dir::Handle) -> Result<()> {
+    let mut dirs = DIRS.lock().unwrap();
+    match dirs
+        .get_mut(usize::from(dir.value()))
+        .and_then(Option::take)
+    {
+        Some(_) => Result::Ok(()),
+        None => Result::Err(Error::BadHandle),
+    }
+}
+
+extern "C" fn readdir(_dir: dir::Handle) -> Result<dir::Entry> {
+    Result::Err(Error::Unimplemented)
+}
+
+extern "C" fn stat(_path: FfiString) -> Result<file::Stat> {
+    Result::Err(Error::Unimplemented)
+}
+
+extern "C" fn fstat(fd: file::Handle) -> Result<file::Stat> {
+    with_file(fd, |open| match open.file.metadata() {
+        Ok(metadata) => Result::Ok(stat_of(&metadata)),
+        Err(e) => Result::Err(error(e)),
+    })
+}
+
+extern "C" fn deletefile(_path: FfiString) -> Result<()> {
+    Result::Err(Error::Unimplemented)
+}
+
+extern "C" fn deletedir(_path: FfiString) -> Result<()> {
+    Result::Err(Error::Unimplemented)
+}
+
+extern "C" fn chdir(_path: FfiString) -> Result<()> {
+    Result::Err(Error::Unimplemented)
+}
+
+extern "C" fn dchdir(_dir: dir::Handle) -> Result<()> {
+    Result::Err(Error::Unimplemented)
+}
+
+extern "C" fn pwd(_path: FfiBuffer) -> Result<usize> {
+    Result::Err(Error::Unimplemented)
+}
+
+extern "C" fn malloc(_size: usize, _alignment: usize) -> Result<*mut core::ffi::c_void> {
+    Result::Err(Error::Unimplemented)
+}
+
+extern "C" fn free(_ptr: *mut core::ffi::c_void, _size: usize, _alignment: usize) {}
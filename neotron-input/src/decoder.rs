@@ -0,0 +1,246 @@
+//! Turns bytes from the keyboard into key events.
+//!
+//! This doesn't read anything itself, so you can feed it bytes from
+//! anywhere - standard input, a serial port, or a test.
+
+use crate::KeyEvent;
+
+/// The most numeric parameters we keep from a `CSI` sequence
+const MAX_PARAMS: usize = 4;
+
+/// How far through a sequence we are
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum State {
+    /// Between keys
+    Idle,
+    /// Seen ESC
+    Escape,
+    /// Seen ESC [, and maybe some parameters
+    Csi,
+    /// Seen ESC [ [, which the Linux console uses for F1 to F5
+    CsiBracket,
+    /// Seen ESC O
+    Ss3,
+    /// Part way through a UTF-8 character, with this many bytes to go
+    Utf8(u8),
+}
+
+/// A state machine which decodes key presses
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Decoder {
+    state: State,
+    params: [u16; MAX_PARAMS],
+    param_count: usize,
+    /// The UTF-8 character we are building
+    code_point: u32,
+    /// The last byte was a carriage return, so a line feed straight after it
+    /// is part of the same Enter
+    after_cr: bool,
+}
+
+impl Decoder {
+    /// Make a decoder, waiting for a key
+    pub const fn new() -> Decoder {
+        Decoder {
+            state: State::Idle,
+            params: [0; MAX_PARAMS],
+            param_count: 0,
+            code_point: 0,
+            after_cr: false,
+        }
+    }
+
+    /// Are we part way through a sequence?
+    ///
+    /// If we are, and no more bytes turn up for a while, call
+    /// [`Decoder::timeout`].
+    pub fn is_pending(&self) -> bool {
+        self.state != State::Idle
+    }
+
+    /// Process one byte. Returns a key if that byte finished one off.
+    pub fn feed(&mut self, byte: u8) -> Option<KeyEvent> {
+        let after_cr = core::mem::replace(&mut self.after_cr, false);
+        match self.state {
+            State::Idle => self.idle(byte, after_cr),
+            State::Escape => match byte {
+                b'[' => {
+                    self.state = State::Csi;
+                    self.params = [0; MAX_PARAMS];
+                    self.param_count = 0;
+                    None
+                }
+                b'O' => {
+                    self.state = State::Ss3;
+                    None
+                }
+                // Two escapes means the first one was on its own
+                0x1B => Some(KeyEvent::Escape),
+                0x20..=0x7E => {
+                    self.state = State::Idle;
+                    Some(KeyEvent::Alt(char::from(byte)))
+                }
+                _ => {
+                    // Alt with a control key, like ESC CR for Alt+Enter. We
+                    // can only return one key, so the escape is dropped.
+                    self.state = State::Idle;
+                    self.idle(byte, false).or(Some(KeyEvent::Escape))
+                }
+            },
+            State::Csi => self.csi(byte),
+            State::CsiBracket => {
+                self.state = State::Idle;
+                match byte {
+                    b'A'..=b'E' => Some(KeyEvent::F(byte - b'A' + 1)),
+                    _ => None,
+                }
+            }
+            State::Ss3 => {
+                self.state = State::Idle;
+                match byte {
+                    b'A' => Some(KeyEvent::Up),
+                    b'B' => Some(KeyEvent::Down),
+                    b'C' => Some(KeyEvent::Right),
+                    b'D' => Some(KeyEvent::Left),
+                    b'H' => Some(KeyEvent::Home),
+                    b'F' => Some(KeyEvent::End),
+                    b'P'..=b'S' => Some(KeyEvent::F(byte - b'P' + 1)),
+                    // The keypad Enter, in application mode
+                    b'M' => Some(KeyEvent::Enter),
+                    _ => None,
+                }
+            }
+            State::Utf8(left) => {
+                if byte & 0xC0 != 0x80 {
+                    // Broken character, so drop it and start again
+                    self.state = State::Idle;
+                    return self.idle(byte, after_cr);
+                }
+                self.code_point = self.code_point << 6 | u32::from(byte & 0x3F);
+                if left > 1 {
+                    self.state = State::Utf8(left - 1);
+                    None
+                } else {
+                    self.state = State::Idle;
+                    char::from_u32(self.code_point).map(KeyEvent::Char)
+                }
+            }
+        }
+    }
+
+    /// Nothing else turned up, so finish off whatever we were decoding.
+    ///
+    /// A lone escape is the Escape key. Anything else half-finished is
+    /// dropped.
+    pub fn timeout(&mut self) -> Option<KeyEvent> {
+        let state = core::mem::replace(&mut self.state, State::Idle);
+        match state {
+            State::Escape => Some(KeyEvent::Escape),
+            _ => None,
+        }
+    }
+
+    /// Handle a byte which isn't part of a sequence
+    fn idle(&mut self, byte: u8, after_cr: bool) -> Option<KeyEvent> {
+        match byte {
+            0x1B => {
+                self.state = State::Escape;
+                None
+            }
+            b'\r' => {
+                self.after_cr = true;
+                Some(KeyEvent::Enter)
+            }
+            b'\n' if after_cr => None,
+            b'\n' => Some(KeyEvent::Enter),
+            b'\t' => Some(KeyEvent::Tab),
+            0x08 | 0x7F => Some(KeyEvent::Backspace),
+            0x01..=0x1A => Some(KeyEvent::Ctrl(char::from(byte - 1 + b'a'))),
+            0x00 | 0x1C..=0x1F => None,
+            0x20..=0x7E => Some(KeyEvent::Char(char::from(byte))),
+            0xC0..=0xDF => self.start_utf8(byte & 0x1F, 1),
+            0xE0..=0xEF => self.start_utf8(byte & 0x0F, 2),
+            0xF0..=0xF7 => self.start_utf8(byte & 0x07, 3),
+            // A continuation byte on its own, or something which is never
+            // valid in UTF-8
+            _ => None,
+        }
+    }
+
+    /// Start collecting a multi-byte UTF-8 character
+    fn start_utf8(&mut self, bits: u8, more: u8) -> Option<KeyEvent> {
+        self.code_point = u32::from(bits);
+        self.state = State::Utf8(more);
+        None
+    }
+
+    /// Handle a byte in a `CSI` sequence
+    fn csi(&mut self, byte: u8) -> Option<KeyEvent> {
+        match byte {
+            b'0'..=b'9' => {
+                if self.param_count == 0 {
+                    self.param_count = 1;
+                }
+                if let Some(param) = self.params.get_mut(self.param_count - 1) {
+                    *param = param
+                        .saturating_mul(10)
+                        .saturating_add(u16::from(byte - b'0'));
+                }
+                None
+            }
+            b';' => {
+                self.param_count = (self.param_count.max(1) + 1).min(MAX_PARAMS + 1);
+                None
+            }
+            b'[' if self.param_count == 0 => {
+                self.state = State::CsiBracket;
+                None
+            }
+            0x40..=0x7E => {
+                self.state = State::Idle;
+                // Any modifiers (like Shift or Ctrl) come in the second
+                // parameter, and we ignore them
+                match byte {
+                    b'A' => Some(KeyEvent::Up),
+                    b'B' => Some(KeyEvent::Down),
+                    b'C' => Some(KeyEvent::Right),
+                    b'D' => Some(KeyEvent::Left),
+                    b'H' => Some(KeyEvent::Home),
+                    b'F' => Some(KeyEvent::End),
+                    b'Z' => Some(KeyEvent::BackTab),
+                    b'~' => tilde_key(self.params[0]),
+                    _ => None,
+                }
+            }
+            // Anything else in the middle of a sequence means it got
+            // mangled, so give up on it
+            0x00..=0x1F => {
+                self.state = State::Idle;
+                self.idle(byte, false)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Default for Decoder {
+    fn default() -> Self {
+        Decoder::new()
+    }
+}
+
+/// Work out which key sent `ESC [ <code> ~`
+fn tilde_key(code: u16) -> Option<KeyEvent> {
+    match code {
+        1 | 7 => Some(KeyEvent::Home),
+        2 => Some(KeyEvent::Insert),
+        3 => Some(KeyEvent::Delete),
+        4 | 8 => Some(KeyEvent::End),
+        5 => Some(KeyEvent::PageUp),
+        6 => Some(KeyEvent::PageDown),
+        11..=15 => Some(KeyEvent::F((code - 10) as u8)),
+        17..=21 => Some(KeyEvent::F((code - 11) as u8)),
+        23 | 24 => Some(KeyEvent::F((code - 12) as u8)),
+        _ => None,
+    }
+}
@@ -0,0 +1,166 @@
+//! Keyboard input for Neotron applications.
+//!
+//! Keys arrive on standard input as bytes. Most are just the character
+//! typed, but the arrow keys, function keys and so on send a sequence of
+//! bytes starting with Escape - and the Escape key sends just the Escape on
+//! its own. The [`Decoder`] turns those bytes into a [`KeyEvent`], and the
+//! [`Keyboard`] reads standard input and feeds the decoder, waiting a short
+//! while to see if an Escape is the start of a sequence or not.
+//!
+//! It understands the sequences sent by VT100 and xterm style terminals, and
+//! by the Linux console.
+//!
+//! If a keyboard repeats keys when it shouldn't, a [`RepeatFilter`] can keep
+//! the extras out. Give the [`Keyboard`] a window with
+//! [`Keyboard::set_repeat_ms`] and read keys with [`Keyboard::read_at`].
+
+#![no_std]
+#![deny(missing_docs)]
+#![deny(unsafe_code)]
+
+pub mod decoder;
+pub mod repeat;
+
+pub use decoder::Decoder;
+pub use repeat::RepeatFilter;
+
+/// How long to wait for the rest of an escape sequence, in milliseconds
+pub const ESCAPE_TIMEOUT_MS: u32 = 50;
+
+/// A key that was pressed
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum KeyEvent {
+    /// A printable character
+    Char(char),
+    /// The up arrow
+    Up,
+    /// The down arrow
+    Down,
+    /// The left arrow
+    Left,
+    /// The right arrow
+    Right,
+    /// Home
+    Home,
+    /// End
+    End,
+    /// Page Up
+    PageUp,
+    /// Page Down
+    PageDown,
+    /// Insert
+    Insert,
+    /// Delete
+    Delete,
+    /// A function key, from F1 to F12
+    F(u8),
+    /// Enter, or Return
+    Enter,
+    /// Tab
+    Tab,
+    /// Shift and Tab
+    BackTab,
+    /// Backspace
+    Backspace,
+    /// Escape
+    Escape,
+    /// Ctrl and a letter, given in lower case
+    Ctrl(char),
+    /// Alt and a printable character
+    Alt(char),
+}
+
+impl KeyEvent {
+    /// Is this the given character, ignoring case?
+    ///
+    /// Handy for menus, where `q` and `Q` both mean quit.
+    pub fn is_char(&self, ch: char) -> bool {
+        match self {
+            KeyEvent::Char(key) => key.eq_ignore_ascii_case(&ch),
+            _ => false,
+        }
+    }
+}
+
+/// Reads keys from standard input
+pub struct Keyboard {
+    stdin: neotron_sdk::File,
+    decoder: Decoder,
+    repeat: RepeatFilter,
+}
+
+impl Keyboard {
+    /// Make a new keyboard reader
+    pub const fn new() -> Keyboard {
+        Keyboard {
+            stdin: neotron_sdk::stdin(),
+            decoder: Decoder::new(),
+            repeat: RepeatFilter::new(0),
+        }
+    }
+
+    /// Get the next key, if one has been pressed.
+    ///
+    /// This doesn't wait for a key, but if we are part way through an escape
+    /// sequence it waits up to [`ESCAPE_TIMEOUT_MS`] for the rest of it.
+    pub fn read(&mut self) -> Option<KeyEvent> {
+        let mut waited = 0;
+        loop {
+            let mut buffer = [0u8; 1];
+            if let Ok(1) = self.stdin.read(&mut buffer) {
+                waited = 0;
+                if let Some(key) = self.decoder.feed(buffer[0]) {
+                    return Some(key);
+                }
+            } else if !self.decoder.is_pending() {
+                return None;
+            } else if waited >= ESCAPE_TIMEOUT_MS {
+                return self.decoder.timeout();
+            } else {
+                neotron_sdk::delay(core::time::Duration::from_millis(1));
+                waited += 1;
+            }
+        }
+    }
+
+    /// Get the next key, if one has been pressed, leaving out any that the
+    /// keyboard has repeated too soon.
+    ///
+    /// `now_ms` is the time in milliseconds, counted from whenever you like.
+    pub fn read_at(&mut self, now_ms: u64) -> Option<KeyEvent> {
+        loop {
+            let key = self.read()?;
+            if self.repeat.allow(key, now_ms) {
+                return Some(key);
+            }
+        }
+    }
+
+    /// Set how soon a key can repeat, in milliseconds, for
+    /// [`Keyboard::read_at`]. Zero lets every key through.
+    pub fn set_repeat_ms(&mut self, ms: u32) {
+        self.repeat.set_window_ms(ms);
+        self.repeat.clear();
+    }
+
+    /// Spin until a key is pressed
+    pub fn wait(&mut self) -> KeyEvent {
+        loop {
+            if let Some(key) = self.read() {
+                return key;
+            }
+            neotron_sdk::delay(core::time::Duration::from_millis(10));
+        }
+    }
+
+    /// Throw away any keys that have been pressed but not read yet
+    pub fn flush(&mut self) {
+        while self.read().is_some() {}
+    }
+}
+
+impl Default for Keyboard {
+    fn default() -> Self {
+        Keyboard::new()
+    }
+}
@@ -0,0 +1,89 @@
+//! Filters out keys the keyboard repeats by mistake.
+//!
+//! Some keyboards send a key twice for one press, or start repeating it
+//! sooner than anyone wants. In a game that can mean turning twice. A
+//! [`RepeatFilter`] lets each key through at most once in a window of time,
+//! but different keys pressed close together all get through.
+//!
+//! This doesn't know what time it is, so you tell it when you ask about a
+//! key. That can be any count of milliseconds, as long as it goes up.
+
+use crate::KeyEvent;
+
+/// How many different keys we remember at once
+const KEYS: usize = 8;
+
+/// Lets each key through at most once every so often
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepeatFilter {
+    window_ms: u32,
+    /// The keys we let through most recently, and when
+    recent: [Option<(KeyEvent, u64)>; KEYS],
+}
+
+impl RepeatFilter {
+    /// Make a filter which lets a key through at most once every
+    /// `window_ms` milliseconds. Zero lets every key through.
+    pub const fn new(window_ms: u32) -> RepeatFilter {
+        RepeatFilter {
+            window_ms,
+            recent: [None; KEYS],
+        }
+    }
+
+    /// How long a key has to wait before it can get through again
+    pub fn window_ms(&self) -> u32 {
+        self.window_ms
+    }
+
+    /// Change how long a key has to wait before it can get through again
+    pub fn set_window_ms(&mut self, window_ms: u32) {
+        self.window_ms = window_ms;
+    }
+
+    /// Should this key, pressed at `now_ms`, get through?
+    ///
+    /// A key that's kept out doesn't start the wait again, so a key that's
+    /// held down still gets through once every window.
+    pub fn allow(&mut self, key: KeyEvent, now_ms: u64) -> bool {
+        if self.window_ms == 0 {
+            return true;
+        }
+        let window = u64::from(self.window_ms);
+        let slot = match self
+            .recent
+            .iter()
+            .position(|r| r.is_some_and(|(k, _)| k == key))
+        {
+            Some(slot) => {
+                if let Some((_, when)) = self.recent[slot] {
+                    if now_ms.saturating_sub(when) < window {
+                        return false;
+                    }
+                }
+                slot
+            }
+            // A key we haven't seen lately goes in an empty slot, or in
+            // place of the one we saw longest ago
+            None => self
+                .recent
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, r)| r.map_or(0, |(_, when)| when.saturating_add(1)))
+                .map_or(0, |(slot, _)| slot),
+        };
+        self.recent[slot] = Some((key, now_ms));
+        true
+    }
+
+    /// Forget every key, so the next press of anything gets through
+    pub fn clear(&mut self) {
+        self.recent = [None; KEYS];
+    }
+}
+
+impl Default for RepeatFilter {
+    fn default() -> Self {
+        RepeatFilter::new(0)
+    }
+}
@@ -0,0 +1,150 @@
+//! A line of text being edited, with a cursor.
+
+/// Up to `N` bytes of UTF-8 text, and where the cursor is in it.
+///
+/// The cursor is always on a character boundary, so the text is always
+/// valid UTF-8.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Buffer<const N: usize> {
+    text: [u8; N],
+    len: usize,
+    /// The byte offset the next character goes in at
+    cursor: usize,
+}
+
+impl<const N: usize> Buffer<N> {
+    /// Make an empty line
+    pub const fn new() -> Buffer<N> {
+        Buffer {
+            text: [0; N],
+            len: 0,
+            cursor: 0,
+        }
+    }
+
+    /// Get the text
+    pub fn as_str(&self) -> &str {
+        // We only ever put whole characters in
+        core::str::from_utf8(&self.text[0..self.len]).unwrap_or("")
+    }
+
+    /// Is the line empty?
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// How many columns the text takes up
+    pub fn width(&self) -> usize {
+        self.as_str().chars().count()
+    }
+
+    /// How many columns along the cursor is
+    pub fn cursor_col(&self) -> usize {
+        self.as_str()[0..self.cursor].chars().count()
+    }
+
+    /// Is the cursor on the end of the line?
+    pub fn at_end(&self) -> bool {
+        self.cursor == self.len
+    }
+
+    /// Empty the line
+    pub fn clear(&mut self) {
+        self.len = 0;
+        self.cursor = 0;
+    }
+
+    /// Replace the text, with the cursor on the end. Anything which doesn't
+    /// fit is dropped.
+    pub fn set(&mut self, text: &str) {
+        self.clear();
+        self.insert_str(text);
+    }
+
+    /// Type a character at the cursor. Returns `false` if there's no room.
+    pub fn insert(&mut self, ch: char) -> bool {
+        let mut encoded = [0u8; 4];
+        let encoded = ch.encode_utf8(&mut encoded).as_bytes();
+        if self.len + encoded.len() > N {
+            return false;
+        }
+        self.text
+            .copy_within(self.cursor..self.len, self.cursor + encoded.len());
+        self.text[self.cursor..self.cursor + encoded.len()].copy_from_slice(encoded);
+        self.len += encoded.len();
+        self.cursor += encoded.len();
+        true
+    }
+
+    /// Type some text at the cursor, as much as fits. Returns `false` if it
+    /// didn't all fit.
+    pub fn insert_str(&mut self, text: &str) -> bool {
+        text.chars().all(|ch| self.insert(ch))
+    }
+
+    /// Take out the text between two byte offsets, which must be on
+    /// character boundaries, and move the cursor to where it was
+    pub fn remove(&mut self, start: usize, end: usize) {
+        self.text.copy_within(end..self.len, start);
+        self.len -= end - start;
+        self.cursor = start;
+    }
+
+    /// The byte offset of the cursor
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    /// The byte offset of the end of the text
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// The byte offset of the character before the cursor
+    pub fn prev_char(&self) -> usize {
+        self.as_str()[0..self.cursor]
+            .char_indices()
+            .next_back()
+            .map_or(0, |(idx, _)| idx)
+    }
+
+    /// The byte offset of the character after the cursor
+    pub fn next_char(&self) -> usize {
+        self.as_str()[self.cursor..]
+            .chars()
+            .next()
+            .map_or(self.cursor, |ch| self.cursor + ch.len_utf8())
+    }
+
+    /// The byte offset of the start of the word before the cursor
+    pub fn prev_word(&self) -> usize {
+        let before = self.as_str()[0..self.cursor].trim_end();
+        before
+            .char_indices()
+            .rev()
+            .find(|(_, ch)| ch.is_whitespace())
+            .map_or(0, |(idx, ch)| idx + ch.len_utf8())
+    }
+
+    /// The byte offset of the end of the word after the cursor
+    pub fn next_word(&self) -> usize {
+        let after = &self.as_str()[self.cursor..];
+        let word = after.trim_start();
+        let start = self.cursor + after.len() - word.len();
+        word.char_indices()
+            .find(|(_, ch)| ch.is_whitespace())
+            .map_or(self.len, |(idx, _)| start + idx)
+    }
+
+    /// Put the cursor at a byte offset, which must be on a character
+    /// boundary
+    pub fn move_to(&mut self, offset: usize) {
+        self.cursor = offset.min(self.len);
+    }
+}
+
+impl<const N: usize> Default for Buffer<N> {
+    fn default() -> Self {
+        Buffer::new()
+    }
+}
@@ -0,0 +1,79 @@
+//! The lines typed so far, so they can be brought back.
+
+/// The most recent lines, in up to `N` bytes.
+///
+/// When it fills up, the oldest lines are forgotten to make room.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct History<const N: usize> {
+    /// Each line, oldest first, with a new line after each
+    text: [u8; N],
+    len: usize,
+}
+
+impl<const N: usize> History<N> {
+    /// Make an empty history
+    pub const fn new() -> History<N> {
+        History {
+            text: [0; N],
+            len: 0,
+        }
+    }
+
+    /// Remember a line. Blank lines, and a line the same as the one before,
+    /// aren't worth remembering.
+    pub fn push(&mut self, line: &str) {
+        let line = line.trim();
+        if line.is_empty() || line.len() + 1 > N || self.get(0) == Some(line) {
+            return;
+        }
+        while self.len + line.len() + 1 > N {
+            self.forget_oldest();
+        }
+        self.text[self.len..self.len + line.len()].copy_from_slice(line.as_bytes());
+        self.text[self.len + line.len()] = b'\n';
+        self.len += line.len() + 1;
+    }
+
+    /// Get a line back, where 0 is the most recent
+    pub fn get(&self, age: usize) -> Option<&str> {
+        self.iter().rev().nth(age)
+    }
+
+    /// How many lines we remember
+    pub fn len(&self) -> usize {
+        self.iter().count()
+    }
+
+    /// Do we remember anything?
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Forget everything
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    /// Go through the lines, oldest first
+    pub fn iter(&self) -> core::str::Lines<'_> {
+        core::str::from_utf8(&self.text[0..self.len])
+            .unwrap_or("")
+            .lines()
+    }
+
+    /// Make room by dropping the oldest line
+    fn forget_oldest(&mut self) {
+        let end = self.text[0..self.len]
+            .iter()
+            .position(|b| *b == b'\n')
+            .map_or(self.len, |idx| idx + 1);
+        self.text.copy_within(end..self.len, 0);
+        self.len -= end;
+    }
+}
+
+impl<const N: usize> Default for History<N> {
+    fn default() -> Self {
+        History::new()
+    }
+}
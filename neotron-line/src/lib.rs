@@ -0,0 +1,292 @@
+//! A line editor for Neotron applications.
+//!
+//! Anything with a prompt - a BASIC, a Forth, a calculator, a chat client -
+//! wants the same thing: let the user type a line, fix their mistakes, and
+//! bring back what they typed before. An [`Editor`] does that, with the keys
+//! you'd expect from a Unix shell:
+//!
+//! | Key                  | Does                                   |
+//! |----------------------|----------------------------------------|
+//! | Left, Right, Ctrl+B/F| Move a character                       |
+//! | Alt+B, Alt+F         | Move a word                            |
+//! | Home, End, Ctrl+A/E  | Go to the start or end                 |
+//! | Backspace, Delete    | Delete a character                     |
+//! | Ctrl+W               | Cut the word before the cursor         |
+//! | Ctrl+U, Ctrl+K       | Cut to the start, or to the end        |
+//! | Ctrl+Y               | Paste what was cut                     |
+//! | Up, Down, Ctrl+P/N   | Go back and forward through the history|
+//! | Enter                | Finish the line                        |
+//! | Escape, Ctrl+C       | Give up on the line                    |
+//!
+//! Text is UTF-8, and the editor counts characters rather than bytes when
+//! it moves the cursor on screen. It only moves the cursor with backspaces,
+//! so it works on any console, but the line must fit on one row of the
+//! screen.
+
+#![no_std]
+#![deny(missing_docs)]
+#![deny(unsafe_code)]
+
+use core::fmt::Write;
+
+use neotron_input::{KeyEvent, Keyboard};
+
+pub mod buffer;
+pub mod history;
+
+pub use buffer::Buffer;
+pub use history::History;
+
+/// What happened when a key was handled
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    /// The line isn't finished yet
+    Editing,
+    /// Enter was pressed, and the line is ready
+    Done,
+    /// The user gave up on the line
+    Cancelled,
+}
+
+/// Edits a line of up to `N` bytes, remembering `H` bytes of history
+pub struct Editor<const N: usize, const H: usize> {
+    line: Buffer<N>,
+    history: History<H>,
+    /// The last thing cut, ready to paste back
+    cut: Buffer<N>,
+    /// How far back through the history we are, or zero for a new line
+    age: usize,
+    /// What was being typed before we went back through the history
+    draft: Buffer<N>,
+    /// How many columns of text are on screen
+    drawn: usize,
+    /// How many columns along the console's cursor is
+    shown: usize,
+}
+
+impl<const N: usize, const H: usize> Editor<N, H> {
+    /// Make an editor, with no history
+    pub const fn new() -> Editor<N, H> {
+        Editor {
+            line: Buffer::new(),
+            history: History::new(),
+            cut: Buffer::new(),
+            age: 0,
+            draft: Buffer::new(),
+            drawn: 0,
+            shown: 0,
+        }
+    }
+
+    /// The line as it stands
+    pub fn as_str(&self) -> &str {
+        self.line.as_str()
+    }
+
+    /// How many columns along the line the cursor is, for callers which
+    /// draw the line themselves
+    pub fn cursor_col(&self) -> usize {
+        self.line.cursor_col()
+    }
+
+    /// The lines typed so far
+    pub fn history(&self) -> &History<H> {
+        &self.history
+    }
+
+    /// Start a new line. Print the prompt first - the line goes wherever
+    /// the cursor is.
+    pub fn start(&mut self) {
+        self.line.clear();
+        self.age = 0;
+        self.drawn = 0;
+        self.shown = 0;
+    }
+
+    /// Start a new line with some text already in it, ready to change, and
+    /// draw it. Print the prompt first.
+    pub fn start_with<W: Write>(&mut self, out: &mut W, text: &str) {
+        self.start();
+        self.line.set(text);
+        self.redraw(out);
+    }
+
+    /// Read a whole line from the keyboard, echoing it as it's typed.
+    ///
+    /// Returns `None` if the user gave up. Either way the cursor is left on
+    /// the end of the line, so print a new line if you want one.
+    pub fn read_line<W: Write>(&mut self, out: &mut W, keyboard: &mut Keyboard) -> Option<&str> {
+        self.edit_line(out, keyboard, "")
+    }
+
+    /// Like [`Editor::read_line`], but the line starts with some text in
+    /// it, like the value being changed
+    pub fn edit_line<W: Write>(
+        &mut self,
+        out: &mut W,
+        keyboard: &mut Keyboard,
+        text: &str,
+    ) -> Option<&str> {
+        self.start_with(out, text);
+        loop {
+            match self.handle(out, keyboard.wait()) {
+                Outcome::Editing => {}
+                Outcome::Done => return Some(self.as_str()),
+                Outcome::Cancelled => return None,
+            }
+        }
+    }
+
+    /// Deal with a key, and update the screen to match.
+    ///
+    /// When a line is [`Outcome::Done`] it goes in the history.
+    pub fn handle<W: Write>(&mut self, out: &mut W, key: KeyEvent) -> Outcome {
+        // Moving about doesn't change the text, so there's less to draw
+        let target = match key {
+            KeyEvent::Left | KeyEvent::Ctrl('b') => Some(self.line.prev_char()),
+            KeyEvent::Right | KeyEvent::Ctrl('f') => Some(self.line.next_char()),
+            KeyEvent::Alt('b') => Some(self.line.prev_word()),
+            KeyEvent::Alt('f') => Some(self.line.next_word()),
+            KeyEvent::Home | KeyEvent::Ctrl('a') => Some(0),
+            KeyEvent::End | KeyEvent::Ctrl('e') => Some(self.line.len()),
+            _ => None,
+        };
+        if let Some(offset) = target {
+            self.move_to(out, offset);
+            return Outcome::Editing;
+        }
+        match key {
+            KeyEvent::Enter => {
+                self.end_of_line(out);
+                self.history.push(self.line.as_str());
+                return Outcome::Done;
+            }
+            KeyEvent::Escape | KeyEvent::Ctrl('c') => {
+                self.end_of_line(out);
+                return Outcome::Cancelled;
+            }
+            KeyEvent::Char(ch) if !ch.is_control() => {
+                let appending = self.line.at_end();
+                if !self.line.insert(ch) {
+                    return Outcome::Editing;
+                }
+                if appending {
+                    // The common case is easy, which matters on a slow line
+                    let _ = out.write_char(ch);
+                    self.drawn += 1;
+                    self.shown += 1;
+                    return Outcome::Editing;
+                }
+            }
+            KeyEvent::Backspace => {
+                let start = self.line.prev_char();
+                self.line.remove(start, self.line.cursor());
+            }
+            KeyEvent::Delete | KeyEvent::Ctrl('d') => {
+                let end = self.line.next_char();
+                self.line.remove(self.line.cursor(), end);
+            }
+            KeyEvent::Ctrl('w') => self.cut(self.line.prev_word(), self.line.cursor()),
+            KeyEvent::Ctrl('u') => self.cut(0, self.line.cursor()),
+            KeyEvent::Ctrl('k') => self.cut(self.line.cursor(), self.line.len()),
+            KeyEvent::Ctrl('y') => {
+                self.line.insert_str(self.cut.as_str());
+            }
+            KeyEvent::Up | KeyEvent::Ctrl('p') => self.older(),
+            KeyEvent::Down | KeyEvent::Ctrl('n') => self.newer(),
+            _ => return Outcome::Editing,
+        }
+        self.redraw(out);
+        Outcome::Editing
+    }
+
+    /// Move the cursor to a byte offset, without changing the text
+    fn move_to<W: Write>(&mut self, out: &mut W, offset: usize) {
+        self.line.move_to(offset);
+        let col = self.line.cursor_col();
+        if col < self.shown {
+            back(out, self.shown - col);
+        } else {
+            // Write over the text we're moving past
+            for ch in self
+                .line
+                .as_str()
+                .chars()
+                .skip(self.shown)
+                .take(col - self.shown)
+            {
+                let _ = out.write_char(ch);
+            }
+        }
+        self.shown = col;
+    }
+
+    /// Cut out some of the line, so it can be pasted back
+    fn cut(&mut self, start: usize, end: usize) {
+        if start == end {
+            return;
+        }
+        self.cut.set(&self.line.as_str()[start..end]);
+        self.line.remove(start, end);
+    }
+
+    /// Bring back the line before the one we're showing
+    fn older(&mut self) {
+        let Some(line) = self.history.get(self.age) else {
+            return;
+        };
+        if self.age == 0 {
+            self.draft = self.line;
+        }
+        self.line.set(line);
+        self.age += 1;
+    }
+
+    /// Bring back the line after the one we're showing, or what was being
+    /// typed if there isn't one
+    fn newer(&mut self) {
+        if self.age == 0 {
+            return;
+        }
+        self.age -= 1;
+        if self.age == 0 {
+            self.line = self.draft;
+            self.line.move_to(self.line.len());
+        } else if let Some(line) = self.history.get(self.age - 1) {
+            self.line.set(line);
+        }
+    }
+
+    /// Draw the line again, over what was there before
+    fn redraw<W: Write>(&mut self, out: &mut W) {
+        back(out, self.shown);
+        let _ = out.write_str(self.line.as_str());
+        let width = self.line.width();
+        // Blank out whatever was left over from a longer line
+        let spare = self.drawn.saturating_sub(width);
+        for _ in 0..spare {
+            let _ = out.write_char(' ');
+        }
+        self.shown = self.line.cursor_col();
+        back(out, width + spare - self.shown);
+        self.drawn = width;
+    }
+
+    /// Put the console's cursor on the end of the line
+    fn end_of_line<W: Write>(&mut self, out: &mut W) {
+        self.move_to(out, self.line.len());
+    }
+}
+
+impl<const N: usize, const H: usize> Default for Editor<N, H> {
+    fn default() -> Self {
+        Editor::new()
+    }
+}
+
+/// Move the console's cursor left
+fn back<W: Write>(out: &mut W, columns: usize) {
+    for _ in 0..columns {
+        let _ = out.write_char('\u{0008}');
+    }
+}
@@ -0,0 +1,154 @@
+//! Random numbers for Neotron applications.
+//!
+//! The OS's `rand()` gives 16 bits at a time, and taking the remainder of
+//! that makes some answers come up more often than others - which players
+//! notice when they are shuffling cards. [`Rng`] is a xoshiro128**
+//! generator: small, quick on a 32-bit CPU, and good enough that every card
+//! and every square of the board gets a fair chance.
+//!
+//! Each application keeps its own [`Rng`]. Seed it from the real-time clock
+//! with [`Rng::from_rtc`], and stir in something the player does, like how
+//! long they took to press a key, with [`Rng::mix`].
+
+#![no_std]
+#![deny(missing_docs)]
+#![deny(unsafe_code)]
+
+mod seed;
+
+pub use seed::rtc_seed;
+
+/// What we seed with if we're not told anything better
+const DEFAULT_SEED: u64 = 0x6E65_6F74_726F_6E21;
+
+/// A xoshiro128** random number generator
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: [u32; 4],
+}
+
+impl Rng {
+    /// Make a generator from a seed. The same seed always gives the same
+    /// numbers, which is handy for replays and daily puzzles.
+    pub const fn new(seed: u64) -> Rng {
+        // Spread the seed out with SplitMix64, as the xoshiro authors
+        // suggest, so similar seeds give very different numbers
+        let mut seed = seed;
+        let mut state = [0u32; 4];
+        let mut idx = 0;
+        while idx < 4 {
+            seed = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+            let mut z = seed;
+            z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+            z ^= z >> 31;
+            state[idx] = z as u32;
+            idx += 1;
+        }
+        Rng { state }
+    }
+
+    /// Make a generator seeded from the real-time clock, so each run is
+    /// different. Without a clock you get the same numbers each time, so
+    /// [`Rng::mix`] in some key presses too.
+    pub fn from_rtc() -> Rng {
+        Rng::new(rtc_seed().unwrap_or(DEFAULT_SEED))
+    }
+
+    /// Stir something unpredictable into the generator, like a key the
+    /// player pressed or how long they took to press it
+    pub fn mix(&mut self, value: u32) {
+        self.state[0] ^= value;
+        self.state[2] = self.state[2].rotate_left(value & 31) ^ value.rotate_left(16);
+        if self.state == [0; 4] {
+            *self = Rng::new(DEFAULT_SEED);
+        }
+        // Let the new bits spread through the whole state
+        for _ in 0..4 {
+            self.next_u32();
+        }
+    }
+
+    /// Get 32 random bits
+    pub fn next_u32(&mut self) -> u32 {
+        let result = self.state[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = self.state[1] << 9;
+        self.state[2] ^= self.state[0];
+        self.state[3] ^= self.state[1];
+        self.state[1] ^= self.state[2];
+        self.state[0] ^= self.state[3];
+        self.state[2] ^= t;
+        self.state[3] = self.state[3].rotate_left(11);
+        result
+    }
+
+    /// Get a number from zero up to, but not including, `limit`. Every
+    /// number is equally likely. A limit of zero gives zero.
+    pub fn below(&mut self, limit: u32) -> u32 {
+        if limit == 0 {
+            return 0;
+        }
+        // Lemire's method: scale up to 64 bits and take the top half,
+        // throwing away the few values that would make it uneven
+        let threshold = limit.wrapping_neg() % limit;
+        loop {
+            let wide = u64::from(self.next_u32()) * u64::from(limit);
+            if (wide as u32) >= threshold {
+                return (wide >> 32) as u32;
+            }
+        }
+    }
+
+    /// Get an index into something `len` long. A length of zero gives zero.
+    pub fn index(&mut self, len: usize) -> usize {
+        self.below(len as u32) as usize
+    }
+
+    /// Get a number from `low` up to and including `high`. If they are the
+    /// wrong way round, you get `low`.
+    pub fn range(&mut self, low: i32, high: i32) -> i32 {
+        if high <= low {
+            return low;
+        }
+        let span = high.wrapping_sub(low) as u32;
+        match span.checked_add(1) {
+            Some(count) => low.wrapping_add(self.below(count) as i32),
+            // The whole range of an i32
+            None => self.next_u32() as i32,
+        }
+    }
+
+    /// Is this a one-in-`n` moment? One in zero is never.
+    pub fn one_in(&mut self, n: u32) -> bool {
+        n != 0 && self.below(n) == 0
+    }
+
+    /// Heads or tails
+    pub fn coin(&mut self) -> bool {
+        self.next_u32() & 0x8000_0000 != 0
+    }
+
+    /// Put the items in a random order, with every order equally likely
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        // Fisher-Yates shuffle
+        for idx in (1..items.len()).rev() {
+            let other = self.index(idx + 1);
+            items.swap(idx, other);
+        }
+    }
+
+    /// Pick one of the items, or `None` if there aren't any
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> Option<&'a T> {
+        if items.is_empty() {
+            None
+        } else {
+            items.get(self.index(items.len()))
+        }
+    }
+}
+
+impl Default for Rng {
+    fn default() -> Self {
+        Rng::new(DEFAULT_SEED)
+    }
+}
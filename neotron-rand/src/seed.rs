@@ -0,0 +1,26 @@
+//! Finding a seed.
+
+/// Turn the real-time clock into a seed, or `None` if there isn't a clock.
+///
+/// The OS gives us the time as text, like `2024-03-09 17:45:02`, so we
+/// hash it rather than parse it. On a PC, `NEOTRON_SEED` wins if it is set.
+pub fn rtc_seed() -> Option<u64> {
+    #[cfg(not(target_os = "none"))]
+    if let Some(seed) = neotron_host::seed() {
+        return Some(seed);
+    }
+    let path = neotron_sdk::path::Path::new("RTC:").ok()?;
+    let rtc = neotron_sdk::File::open(path, neotron_sdk::Flags::empty()).ok()?;
+    let mut buffer = [0u8; 32];
+    let n = rtc.read(&mut buffer).ok()?;
+    if n == 0 {
+        return None;
+    }
+    // FNV-1a
+    let mut hash: u64 = 0xCBF2_9CE4_8422_2325;
+    for byte in &buffer[0..n] {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(0x0000_0100_0000_01B3);
+    }
+    Some(hash)
+}
@@ -0,0 +1,124 @@
+//! Packing values into bytes, and getting them back out again.
+//!
+//! Everything is little-endian, whatever machine wrote it, so save files can
+//! be copied between systems.
+
+use crate::Error;
+
+/// Packs values into a byte buffer
+pub struct Writer<'a> {
+    buffer: &'a mut [u8],
+    len: usize,
+}
+
+impl<'a> Writer<'a> {
+    /// Start writing at the beginning of a buffer
+    pub fn new(buffer: &'a mut [u8]) -> Writer<'a> {
+        Writer { buffer, len: 0 }
+    }
+
+    /// How many bytes have been written
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Has nothing been written yet?
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The bytes written so far
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.buffer[0..self.len]
+    }
+
+    /// Add some bytes
+    pub fn bytes(&mut self, bytes: &[u8]) -> Result<(), Error> {
+        let end = self.len + bytes.len();
+        let space = self.buffer.get_mut(self.len..end).ok_or(Error::TooLarge)?;
+        space.copy_from_slice(bytes);
+        self.len = end;
+        Ok(())
+    }
+
+    /// Add a byte
+    pub fn u8(&mut self, value: u8) -> Result<(), Error> {
+        self.bytes(&[value])
+    }
+
+    /// Add a 16-bit value
+    pub fn u16(&mut self, value: u16) -> Result<(), Error> {
+        self.bytes(&value.to_le_bytes())
+    }
+
+    /// Add a 32-bit value
+    pub fn u32(&mut self, value: u32) -> Result<(), Error> {
+        self.bytes(&value.to_le_bytes())
+    }
+
+    /// Add a flag, as one byte
+    pub fn bool(&mut self, value: bool) -> Result<(), Error> {
+        self.u8(u8::from(value))
+    }
+}
+
+/// Gets values back out of a byte buffer.
+///
+/// Running off the end means the data is damaged, so gives
+/// [`Error::Corrupt`].
+pub struct Reader<'a> {
+    buffer: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    /// Start reading at the beginning of some bytes
+    pub fn new(buffer: &'a [u8]) -> Reader<'a> {
+        Reader { buffer }
+    }
+
+    /// How many bytes are left to read
+    pub fn remaining(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Take some bytes
+    pub fn bytes(&mut self, count: usize) -> Result<&'a [u8], Error> {
+        if count > self.buffer.len() {
+            return Err(Error::Corrupt);
+        }
+        let (bytes, rest) = self.buffer.split_at(count);
+        self.buffer = rest;
+        Ok(bytes)
+    }
+
+    /// Take a fixed number of bytes
+    pub fn array<const N: usize>(&mut self) -> Result<[u8; N], Error> {
+        let mut result = [0u8; N];
+        result.copy_from_slice(self.bytes(N)?);
+        Ok(result)
+    }
+
+    /// Take a byte
+    pub fn u8(&mut self) -> Result<u8, Error> {
+        Ok(self.array::<1>()?[0])
+    }
+
+    /// Take a 16-bit value
+    pub fn u16(&mut self) -> Result<u16, Error> {
+        Ok(u16::from_le_bytes(self.array()?))
+    }
+
+    /// Take a 32-bit value
+    pub fn u32(&mut self) -> Result<u32, Error> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+
+    /// Take a flag. Anything other than 0 or 1 means the data is damaged.
+    pub fn bool(&mut self) -> Result<bool, Error> {
+        match self.u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(Error::Corrupt),
+        }
+    }
+}
@@ -0,0 +1,57 @@
+//! The CRC-32 we use to spot damaged files.
+//!
+//! This is the same CRC-32 as Zip and PNG use, worked out four bits at a time
+//! so the table stays small.
+
+/// The CRC of every four bit value
+const TABLE: [u32; 16] = [
+    0x0000_0000,
+    0x1DB7_1064,
+    0x3B6E_20C8,
+    0x26D9_30AC,
+    0x76DC_4190,
+    0x6B6B_51F4,
+    0x4DB2_6158,
+    0x5005_713C,
+    0xEDB8_8320,
+    0xF00F_9344,
+    0xD6D6_A3E8,
+    0xCB61_B38C,
+    0x9B64_C2B0,
+    0x86D3_D2D4,
+    0xA00A_E278,
+    0xBDBD_F21C,
+];
+
+/// A CRC-32 which can be fed a bit at a time
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Crc32(u32);
+
+impl Crc32 {
+    /// Start a new CRC
+    pub const fn new() -> Crc32 {
+        Crc32(0xFFFF_FFFF)
+    }
+
+    /// Add some bytes
+    pub fn update(&mut self, bytes: &[u8]) {
+        let mut crc = self.0;
+        for byte in bytes {
+            crc ^= u32::from(*byte);
+            crc = (crc >> 4) ^ TABLE[(crc & 0x0F) as usize];
+            crc = (crc >> 4) ^ TABLE[(crc & 0x0F) as usize];
+        }
+        self.0 = crc;
+    }
+
+    /// Get the CRC of everything added so far
+    pub const fn finish(&self) -> u32 {
+        !self.0
+    }
+}
+
+impl Default for Crc32 {
+    fn default() -> Self {
+        Crc32::new()
+    }
+}
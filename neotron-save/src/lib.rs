@@ -0,0 +1,356 @@
+//! Save files for Neotron applications.
+//!
+//! High score tables, settings and saved games all go in the same kind of
+//! file. Each one starts with a small header giving the layout version the
+//! application used and a CRC-32 of the contents, so damaged or half-written
+//! files are spotted rather than loaded.
+//!
+//! Saving never leaves you without a good copy. The new data goes into a
+//! temporary file first, and is checked. Then the old file is copied to a
+//! backup, and only then is the new data written over it. If the power goes
+//! off part way through, [`load`] falls back to whichever of those files is
+//! good. The temporary file ends its extension with `$` and the backup with
+//! `~`, so saving `SNAKE.DAT` leaves `SNAKE.DA$` and `SNAKE.DA~` behind.
+//!
+//! We copy rather than rename, because the SDK can't delete a file, and a
+//! rename can't replace one that is already there.
+//!
+//! The header looks like this, with everything little-endian:
+//!
+//! | Offset | Size | Contents                                     |
+//! |--------|------|----------------------------------------------|
+//! | 0      | 4    | `NSAV`                                       |
+//! | 4      | 1    | The header layout version, which is 1        |
+//! | 5      | 1    | Zero                                         |
+//! | 6      | 2    | The application's own layout version         |
+//! | 8      | 4    | How many bytes of data follow the header     |
+//! | 12     | 4    | CRC-32 of the first 12 bytes, then the data  |
+
+#![no_std]
+#![deny(missing_docs)]
+#![deny(unsafe_code)]
+
+pub mod codec;
+mod crc;
+pub mod scores;
+
+pub use codec::{Reader, Writer};
+pub use scores::{HighScores, Score};
+
+use crc::Crc32;
+
+/// What every save file starts with
+const MAGIC: [u8; 4] = *b"NSAV";
+
+/// Which layout of header we write
+const FORMAT: u8 = 1;
+
+/// How long the header is
+pub const HEADER_LEN: usize = 16;
+
+/// The longest file name we can handle
+const MAX_PATH: usize = 64;
+
+/// Ends the extension of the file we write a save into
+const TEMP_MARKER: char = '$';
+
+/// Ends the extension of the file we keep the last save in
+const BACKUP_MARKER: char = '~';
+
+/// Something that went wrong loading or saving
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// The OS couldn't open, read or write a file
+    Io(neotron_sdk::Error),
+    /// The file isn't a save file, or it has been damaged
+    Corrupt,
+    /// The data doesn't fit in the buffer
+    TooLarge,
+    /// The file was written by a version of the application that laid its
+    /// data out differently
+    Version(u16),
+    /// The file name is too long
+    BadPath,
+}
+
+impl From<neotron_sdk::Error> for Error {
+    fn from(error: neotron_sdk::Error) -> Self {
+        Error::Io(error)
+    }
+}
+
+/// Which file some loaded data came from
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Source {
+    /// The file itself, which is the usual case
+    Main,
+    /// A save that was written, but never made it into the file itself
+    Unfinished,
+    /// The copy from the save before last
+    Backup,
+}
+
+/// What [`load`] found
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Loaded {
+    /// The application's layout version, as given to [`save`]
+    pub version: u16,
+    /// How many bytes of data were loaded into the buffer
+    pub len: usize,
+    /// Which file the data came from
+    pub source: Source,
+}
+
+impl Loaded {
+    /// Was the file missing or damaged, so we had to use another copy?
+    ///
+    /// The next [`save`] puts things right.
+    pub fn recovered(&self) -> bool {
+        self.source != Source::Main
+    }
+}
+
+/// Load a save file into `buffer`.
+///
+/// If the file is missing or damaged, we try the unfinished temporary copy
+/// and then the backup. If none of them are any good you get the error from
+/// the file itself.
+pub fn load(filename: &str, buffer: &mut [u8]) -> Result<Loaded, Error> {
+    let main = SavePath::new(filename)?;
+    let error = match read_file(&main, buffer) {
+        Ok((version, len)) => {
+            return Ok(Loaded {
+                version,
+                len,
+                source: Source::Main,
+            })
+        }
+        Err(error) => error,
+    };
+    for (marker, source) in [
+        (TEMP_MARKER, Source::Unfinished),
+        (BACKUP_MARKER, Source::Backup),
+    ] {
+        let other = main.with_marker(marker)?;
+        if let Ok((version, len)) = read_file(&other, buffer) {
+            return Ok(Loaded {
+                version,
+                len,
+                source,
+            });
+        }
+    }
+    Err(error)
+}
+
+/// Save some data, tagged with your own layout version number.
+///
+/// If this fails, the last good save is still there for [`load`] to find.
+pub fn save(filename: &str, version: u16, data: &[u8]) -> Result<(), Error> {
+    let main = SavePath::new(filename)?;
+    let temp = main.with_marker(TEMP_MARKER)?;
+    let backup = main.with_marker(BACKUP_MARKER)?;
+    let length = u32::try_from(data.len()).map_err(|_| Error::TooLarge)?;
+
+    let header = Header { version, length }.encode(data);
+    write_file(&temp, &header, data)?;
+    // Make sure it all made it to the disk before we rely on it
+    if check_file(&temp)? != version {
+        return Err(Error::Corrupt);
+    }
+
+    // Keep the old file as the backup, unless it's damaged and the backup we
+    // already have is better. There might not be an old file yet, so
+    // failures here are fine.
+    if check_file(&main).is_ok() {
+        let _ = copy_file(&main, &backup);
+    }
+    write_file(&main, &header, data)?;
+    if check_file(&main)? != version {
+        return Err(Error::Corrupt);
+    }
+    Ok(())
+}
+
+/// Write a header and some data to a file, replacing whatever was in it
+fn write_file(path: &SavePath, header: &[u8; HEADER_LEN], data: &[u8]) -> Result<(), Error> {
+    let file = create(path)?;
+    file.write(header)?;
+    file.write(data)?;
+    Ok(())
+}
+
+/// Copy one file over another
+fn copy_file(from: &SavePath, to: &SavePath) -> Result<(), Error> {
+    let source = neotron_sdk::File::open(from.as_path()?, neotron_sdk::Flags::empty())?;
+    let dest = create(to)?;
+    let mut chunk = [0u8; 64];
+    loop {
+        match source.read(&mut chunk)? {
+            0 => return Ok(()),
+            n => dest.write(&chunk[0..n])?,
+        }
+    }
+}
+
+/// Open a file for writing, emptying it or making it if need be
+fn create(path: &SavePath) -> Result<neotron_sdk::File, Error> {
+    let file = neotron_sdk::File::open(
+        path.as_path()?,
+        neotron_sdk::Flags::WRITE | neotron_sdk::Flags::CREATE | neotron_sdk::Flags::TRUNCATE,
+    )?;
+    Ok(file)
+}
+
+/// The header at the front of a save file
+struct Header {
+    /// The application's layout version
+    version: u16,
+    /// How much data follows
+    length: u32,
+}
+
+impl Header {
+    /// Make the header bytes for this data
+    fn encode(&self, data: &[u8]) -> [u8; HEADER_LEN] {
+        let mut header = [0u8; HEADER_LEN];
+        header[0..4].copy_from_slice(&MAGIC);
+        header[4] = FORMAT;
+        header[6..8].copy_from_slice(&self.version.to_le_bytes());
+        header[8..12].copy_from_slice(&self.length.to_le_bytes());
+        let mut crc = Crc32::new();
+        crc.update(&header[0..12]);
+        crc.update(data);
+        header[12..16].copy_from_slice(&crc.finish().to_le_bytes());
+        header
+    }
+
+    /// Check the header bytes, and start off the CRC for the data. Also
+    /// gives the CRC the file says we should end up with.
+    fn decode(header: &[u8; HEADER_LEN]) -> Result<(Header, Crc32, u32), Error> {
+        if header[0..4] != MAGIC || header[4] != FORMAT {
+            return Err(Error::Corrupt);
+        }
+        let mut crc = Crc32::new();
+        crc.update(&header[0..12]);
+        let [_, _, _, _, _, _, v0, v1, l0, l1, l2, l3, c0, c1, c2, c3] = *header;
+        let header = Header {
+            version: u16::from_le_bytes([v0, v1]),
+            length: u32::from_le_bytes([l0, l1, l2, l3]),
+        };
+        Ok((header, crc, u32::from_le_bytes([c0, c1, c2, c3])))
+    }
+}
+
+/// Read a whole save file into a buffer, giving its version and length
+fn read_file(path: &SavePath, buffer: &mut [u8]) -> Result<(u16, usize), Error> {
+    let file = neotron_sdk::File::open(path.as_path()?, neotron_sdk::Flags::empty())?;
+    let mut header = [0u8; HEADER_LEN];
+    read_exact(&file, &mut header)?;
+    let (header, mut crc, expected) = Header::decode(&header)?;
+    let len = usize::try_from(header.length).map_err(|_| Error::TooLarge)?;
+    let data = buffer.get_mut(0..len).ok_or(Error::TooLarge)?;
+    read_exact(&file, data)?;
+    crc.update(data);
+    check_end(&file, crc, expected)?;
+    Ok((header.version, len))
+}
+
+/// Check a save file is in one piece, without keeping its contents.
+/// Gives its version.
+fn check_file(path: &SavePath) -> Result<u16, Error> {
+    let file = neotron_sdk::File::open(path.as_path()?, neotron_sdk::Flags::empty())?;
+    let mut header = [0u8; HEADER_LEN];
+    read_exact(&file, &mut header)?;
+    let (header, mut crc, expected) = Header::decode(&header)?;
+    let mut left = header.length as usize;
+    let mut chunk = [0u8; 64];
+    while left > 0 {
+        let piece = &mut chunk[0..left.min(64)];
+        read_exact(&file, piece)?;
+        crc.update(piece);
+        left -= piece.len();
+    }
+    check_end(&file, crc, expected)?;
+    Ok(header.version)
+}
+
+/// Make sure the CRC matches and there's nothing after the data
+fn check_end(file: &neotron_sdk::File, crc: Crc32, expected: u32) -> Result<(), Error> {
+    let mut spare = [0u8; 1];
+    if crc.finish() != expected || file.read(&mut spare)? != 0 {
+        return Err(Error::Corrupt);
+    }
+    Ok(())
+}
+
+/// Fill a buffer from a file. Running out of file means it was cut short.
+fn read_exact(file: &neotron_sdk::File, buffer: &mut [u8]) -> Result<(), Error> {
+    let mut done = 0;
+    while done < buffer.len() {
+        match file.read(&mut buffer[done..])? {
+            0 => return Err(Error::Corrupt),
+            n => done += n,
+        }
+    }
+    Ok(())
+}
+
+/// A file name, which we can change the extension of
+struct SavePath {
+    buffer: [u8; MAX_PATH],
+    len: usize,
+}
+
+impl SavePath {
+    /// Copy a file name
+    fn new(filename: &str) -> Result<SavePath, Error> {
+        let mut path = SavePath {
+            buffer: [0; MAX_PATH],
+            len: 0,
+        };
+        path.push(filename)?;
+        Ok(path)
+    }
+
+    /// The same file name, with the last character of the extension changed
+    /// to `marker`. Extensions shorter than three characters get it added.
+    fn with_marker(&self, marker: char) -> Result<SavePath, Error> {
+        let name = self.as_str();
+        // Only look for the dot in the last part of the path
+        let start = name.rfind(['/', '\\', ':']).map_or(0, |i| i + 1);
+        let (stem, extension) = match name[start..].rfind('.') {
+            Some(dot) => (&name[0..start + dot], &name[start + dot + 1..]),
+            None => (name, ""),
+        };
+        let kept = extension
+            .char_indices()
+            .nth(2)
+            .map_or(extension.len(), |(i, _)| i);
+        let mut path = SavePath::new(stem)?;
+        path.push(".")?;
+        path.push(&extension[0..kept])?;
+        path.push(marker.encode_utf8(&mut [0u8; 4]))?;
+        Ok(path)
+    }
+
+    /// Add some text to the end
+    fn push(&mut self, text: &str) -> Result<(), Error> {
+        let end = self.len + text.len();
+        let space = self.buffer.get_mut(self.len..end).ok_or(Error::BadPath)?;
+        space.copy_from_slice(text.as_bytes());
+        self.len = end;
+        Ok(())
+    }
+
+    /// The file name, as text
+    fn as_str(&self) -> &str {
+        // We only ever copy in whole strings, so this can't fail
+        core::str::from_utf8(&self.buffer[0..self.len]).unwrap_or("")
+    }
+
+    /// The file name, as the OS wants it
+    fn as_path(&self) -> Result<neotron_sdk::path::Path<'_>, Error> {
+        Ok(neotron_sdk::path::Path::new(self.as_str())?)
+    }
+}
@@ -0,0 +1,171 @@
+//! A table of the best scores, with a name against each one.
+
+use crate::{Error, Reader, Writer};
+
+/// The longest name we keep against a score
+pub const NAME_LEN: usize = 8;
+
+/// The most entries a table saved with [`HighScores::save`] can have
+pub const MAX_ENTRIES: usize = 32;
+
+/// How many bytes each entry takes in a file
+const ENTRY_LEN: usize = NAME_LEN + 4;
+
+/// One entry in a high score table
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Score {
+    name: [u8; NAME_LEN],
+    /// The score
+    pub score: u32,
+}
+
+impl Score {
+    /// An empty entry
+    const EMPTY: Score = Score {
+        name: [0; NAME_LEN],
+        score: 0,
+    };
+
+    /// Make an entry. Names longer than [`NAME_LEN`] are cut short, and
+    /// anything that isn't printable ASCII becomes `?`.
+    pub fn new(name: &str, score: u32) -> Score {
+        let mut entry = Score {
+            name: [0; NAME_LEN],
+            score,
+        };
+        for (slot, ch) in entry.name.iter_mut().zip(name.chars()) {
+            *slot = if ch.is_ascii_graphic() || ch == ' ' {
+                ch as u8
+            } else {
+                b'?'
+            };
+        }
+        entry
+    }
+
+    /// The name against the score
+    pub fn name(&self) -> &str {
+        let len = self.name.iter().position(|b| *b == 0).unwrap_or(NAME_LEN);
+        core::str::from_utf8(&self.name[0..len]).unwrap_or("")
+    }
+}
+
+/// The best `N` scores, highest first
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HighScores<const N: usize> {
+    entries: [Score; N],
+    count: usize,
+}
+
+impl<const N: usize> HighScores<N> {
+    /// The layout version we save the table with
+    pub const VERSION: u16 = 1;
+
+    /// Make an empty table
+    pub const fn new() -> HighScores<N> {
+        HighScores {
+            entries: [Score::EMPTY; N],
+            count: 0,
+        }
+    }
+
+    /// The scores so far, highest first
+    pub fn entries(&self) -> &[Score] {
+        &self.entries[0..self.count]
+    }
+
+    /// The highest score, if there is one
+    pub fn best(&self) -> Option<u32> {
+        self.entries().first().map(|entry| entry.score)
+    }
+
+    /// Would this score get into the table?
+    pub fn qualifies(&self, score: u32) -> bool {
+        if score == 0 || N == 0 {
+            return false;
+        }
+        self.count < N || score > self.entries[N - 1].score
+    }
+
+    /// Put a score in the table, if it's good enough. Gives the place it
+    /// went in at, counting from zero.
+    ///
+    /// A score that ties with one already in the table goes below it.
+    pub fn insert(&mut self, name: &str, score: u32) -> Option<usize> {
+        if !self.qualifies(score) {
+            return None;
+        }
+        let place = self
+            .entries()
+            .iter()
+            .position(|entry| entry.score < score)
+            .unwrap_or(self.count);
+        self.count = (self.count + 1).min(N);
+        self.entries[place..self.count].rotate_right(1);
+        self.entries[place] = Score::new(name, score);
+        Some(place)
+    }
+
+    /// Empty the table
+    pub fn clear(&mut self) {
+        self.count = 0;
+    }
+
+    /// Pack the table into bytes. Only the first [`MAX_ENTRIES`] are kept.
+    pub fn encode(&self, writer: &mut Writer) -> Result<(), Error> {
+        let entries = &self.entries()[0..self.count.min(MAX_ENTRIES)];
+        writer.u8(entries.len() as u8)?;
+        for entry in entries {
+            writer.bytes(&entry.name)?;
+            writer.u32(entry.score)?;
+        }
+        Ok(())
+    }
+
+    /// Unpack a table from bytes. Extra entries are dropped, so you can load
+    /// a bigger table into a smaller one.
+    pub fn decode(reader: &mut Reader) -> Result<HighScores<N>, Error> {
+        let mut table = HighScores::new();
+        let count = usize::from(reader.u8()?);
+        let mut last = u32::MAX;
+        for index in 0..count {
+            let name = reader.array::<NAME_LEN>()?;
+            let score = reader.u32()?;
+            // A table that's out of order has been tampered with
+            if score > last {
+                return Err(Error::Corrupt);
+            }
+            last = score;
+            if let Some(slot) = table.entries.get_mut(index) {
+                *slot = Score { name, score };
+                table.count += 1;
+            }
+        }
+        Ok(table)
+    }
+
+    /// Load a table from a save file
+    pub fn load(filename: &str) -> Result<HighScores<N>, Error> {
+        let mut buffer = [0u8; 1 + MAX_ENTRIES * ENTRY_LEN];
+        let loaded = crate::load(filename, &mut buffer)?;
+        if loaded.version != Self::VERSION {
+            return Err(Error::Version(loaded.version));
+        }
+        HighScores::decode(&mut Reader::new(&buffer[0..loaded.len]))
+    }
+
+    /// Write the table to a save file. Only the first [`MAX_ENTRIES`] are
+    /// kept.
+    pub fn save(&self, filename: &str) -> Result<(), Error> {
+        let mut buffer = [0u8; 1 + MAX_ENTRIES * ENTRY_LEN];
+        let mut writer = Writer::new(&mut buffer);
+        self.encode(&mut writer)?;
+        crate::save(filename, Self::VERSION, writer.as_bytes())
+    }
+}
+
+impl<const N: usize> Default for HighScores<N> {
+    fn default() -> Self {
+        HighScores::new()
+    }
+}
@@ -0,0 +1,353 @@
+//! Scenes, for Neotron games.
+//!
+//! Most games have a title screen, a menu or two, the game itself, a pause
+//! screen and a game over screen. Each of those is a [`Scene`], and a
+//! [`Stack`] runs them: the scene on top gets the keys and the ticks, and
+//! says which scene comes next with a [`Transition`].
+//!
+//! ```rust,ignore
+//! enum Screen {
+//!     Title,
+//!     Playing,
+//!     Paused,
+//! }
+//!
+//! impl Scene<Game> for Screen {
+//!     fn draw(&mut self, game: &mut Game) { ... }
+//!
+//!     fn handle_input(&mut self, game: &mut Game, key: KeyEvent) -> Transition<Screen> {
+//!         match self {
+//!             Screen::Title if key == KeyEvent::Enter => Transition::Push(Screen::Playing),
+//!             Screen::Playing if key == KeyEvent::Escape => Transition::Push(Screen::Paused),
+//!             Screen::Paused => Transition::Pop,
+//!             _ => Transition::Stay,
+//!         }
+//!     }
+//! }
+//!
+//! let mut scenes: Stack<Screen, 4> = Stack::new();
+//! scenes.run(Screen::Title, &mut game, &mut keyboard);
+//! ```
+//!
+//! Scenes are usually an `enum`, with the things each one needs to remember
+//! inside it. Everything they share, like the score, lives in the context
+//! they're all given.
+//!
+//! A whole game can also be a [`Game`], which [`play`] runs. That lets a
+//! launcher with several games in it run each of them a step at a time.
+
+#![no_std]
+#![deny(missing_docs)]
+#![deny(unsafe_code)]
+
+use neotron_frame::Frames;
+use neotron_input::{KeyEvent, Keyboard};
+
+/// What to do after a scene has handled a key or a tick
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Transition<S> {
+    /// Carry on with this scene
+    Stay,
+    /// Put a new scene on top of this one, which carries on when the new
+    /// one is popped
+    Push(S),
+    /// Go back to the scene underneath. Popping the last scene stops the
+    /// stack.
+    Pop,
+    /// Swap this scene for a new one
+    Replace(S),
+    /// Stop running, whatever is on the stack
+    Quit,
+}
+
+/// One screen of a game, like a menu or the game itself
+pub trait Scene<C>: Sized {
+    /// The scene has just been put on the stack. By default this draws it.
+    fn enter(&mut self, ctx: &mut C) {
+        self.draw(ctx);
+    }
+
+    /// Draw the whole scene, over whatever was on the screen. This is done
+    /// when the scene above it on the stack goes away.
+    fn draw(&mut self, ctx: &mut C);
+
+    /// How many milliseconds between each [`Scene::update`], or `None` if
+    /// the scene only does anything when a key is pressed
+    fn tick_ms(&self, _ctx: &C) -> Option<u32> {
+        None
+    }
+
+    /// Move the scene on by one tick. Keys pressed since the last tick have
+    /// already been handled.
+    fn update(&mut self, _ctx: &mut C) -> Transition<Self> {
+        Transition::Stay
+    }
+
+    /// Deal with a key
+    fn handle_input(&mut self, ctx: &mut C, key: KeyEvent) -> Transition<Self>;
+}
+
+/// Runs up to `N` scenes, one on top of the other.
+///
+/// Pushing a scene onto a full stack replaces the one on top.
+pub struct Stack<S, const N: usize> {
+    scenes: [Option<S>; N],
+    len: usize,
+}
+
+impl<S, const N: usize> Stack<S, N> {
+    /// Make an empty stack
+    pub const fn new() -> Stack<S, N> {
+        Stack {
+            scenes: [const { None }; N],
+            len: 0,
+        }
+    }
+
+    /// How many scenes are on the stack
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Is the stack empty?
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Run scenes, starting with `first`, until the last one is popped or
+    /// one of them quits.
+    ///
+    /// Anything already on the stack is thrown away first. While a scene is
+    /// ticking, keys the keyboard repeats too soon are left out - see
+    /// [`Keyboard::set_repeat_ms`].
+    pub fn run<C>(&mut self, first: S, ctx: &mut C, keyboard: &mut Keyboard)
+    where
+        S: Scene<C>,
+    {
+        self.start(first, ctx);
+        let mut frames = Frames::new(50);
+        // Whether `frames` is running, for the scene on top
+        let mut ticking = false;
+        while !self.is_empty() {
+            let changed = match self.tick_ms(ctx) {
+                None => {
+                    ticking = false;
+                    self.handle_input(ctx, keyboard.wait())
+                }
+                Some(ms) => {
+                    frames.set_period_ms(ms);
+                    if !ticking {
+                        frames.start();
+                        ticking = true;
+                    }
+                    // If we've fallen behind we just carry on from here
+                    frames.wait();
+                    let mut changed = false;
+                    while let Some(key) = keyboard.read_at(frames.elapsed_ms()) {
+                        changed = self.handle_input(ctx, key);
+                        if changed {
+                            break;
+                        }
+                    }
+                    changed || self.update(ctx)
+                }
+            };
+            if changed {
+                ticking = false;
+            }
+        }
+    }
+
+    /// Throw away anything on the stack, and put `first` on it.
+    ///
+    /// Use this, [`Stack::tick_ms`], [`Stack::handle_input`] and
+    /// [`Stack::update`] instead of [`Stack::run`] when something else is
+    /// reading the keyboard and keeping time.
+    pub fn start<C>(&mut self, first: S, ctx: &mut C)
+    where
+        S: Scene<C>,
+    {
+        self.clear();
+        self.apply(Transition::Push(first), ctx);
+    }
+
+    /// How many milliseconds between each [`Stack::update`], for the scene
+    /// on top, or `None` if it only wants keys
+    pub fn tick_ms<C>(&self, ctx: &C) -> Option<u32>
+    where
+        S: Scene<C>,
+    {
+        self.scenes[..self.len].last()?.as_ref()?.tick_ms(ctx)
+    }
+
+    /// Give a key to the scene on top.
+    ///
+    /// Returns `true` if the scene changed the stack.
+    pub fn handle_input<C>(&mut self, ctx: &mut C, key: KeyEvent) -> bool
+    where
+        S: Scene<C>,
+    {
+        let Some(scene) = self.top() else {
+            return false;
+        };
+        let transition = scene.handle_input(ctx, key);
+        let changed = !matches!(transition, Transition::Stay);
+        self.apply(transition, ctx);
+        changed
+    }
+
+    /// Move the scene on top on by one tick.
+    ///
+    /// Returns `true` if the scene changed the stack.
+    pub fn update<C>(&mut self, ctx: &mut C) -> bool
+    where
+        S: Scene<C>,
+    {
+        let Some(scene) = self.top() else {
+            return false;
+        };
+        let transition = scene.update(ctx);
+        let changed = !matches!(transition, Transition::Stay);
+        self.apply(transition, ctx);
+        changed
+    }
+
+    /// Throw away every scene
+    fn clear(&mut self) {
+        while self.len > 0 {
+            self.len -= 1;
+            self.scenes[self.len] = None;
+        }
+    }
+
+    /// The scene on top, if there is one
+    fn top(&mut self) -> Option<&mut S> {
+        self.scenes[..self.len].last_mut()?.as_mut()
+    }
+
+    /// Change the stack, and let whichever scene ends up on top know
+    fn apply<C>(&mut self, transition: Transition<S>, ctx: &mut C)
+    where
+        S: Scene<C>,
+    {
+        match transition {
+            Transition::Stay => {}
+            Transition::Push(scene) => {
+                if self.len < N {
+                    self.len += 1;
+                }
+                self.enter(scene, ctx);
+            }
+            Transition::Replace(scene) => {
+                if self.len == 0 {
+                    self.len = 1;
+                }
+                self.enter(scene, ctx);
+            }
+            Transition::Pop => {
+                if self.len > 0 {
+                    self.len -= 1;
+                    self.scenes[self.len] = None;
+                }
+                if let Some(scene) = self.top() {
+                    scene.draw(ctx);
+                }
+            }
+            Transition::Quit => self.clear(),
+        }
+    }
+
+    /// Put a scene on top of the stack, in place of whatever was there
+    fn enter<C>(&mut self, scene: S, ctx: &mut C)
+    where
+        S: Scene<C>,
+    {
+        let top = &mut self.scenes[self.len - 1];
+        *top = Some(scene);
+        if let Some(scene) = top {
+            scene.enter(ctx);
+        }
+    }
+}
+
+impl<S, const N: usize> Default for Stack<S, N> {
+    fn default() -> Self {
+        Stack::new()
+    }
+}
+
+/// A whole game, which can be run a step at a time.
+///
+/// [`play`] runs a game on its own. Something with several games in it can
+/// instead start whichever one is picked, and feed it keys and ticks from
+/// its own loop.
+pub trait Game {
+    /// Get ready to play, and draw the first screen
+    fn start(&mut self);
+
+    /// How soon a key can repeat, in milliseconds - see
+    /// [`Keyboard::set_repeat_ms`]
+    fn repeat_ms(&self) -> u32 {
+        0
+    }
+
+    /// How many milliseconds between each [`Game::tick`], or `None` if the
+    /// game only does anything when a key is pressed
+    fn tick_ms(&self) -> Option<u32>;
+
+    /// Deal with a key. Returns `false` once the player has quit.
+    fn handle_key(&mut self, key: KeyEvent) -> bool;
+
+    /// Move the game on by one tick. Returns `false` once the game is over.
+    fn tick(&mut self) -> bool {
+        true
+    }
+
+    /// Put the screen back how we found it
+    fn finish(&mut self);
+}
+
+/// Play a game until the player quits
+pub fn play<G>(game: &mut G, keyboard: &mut Keyboard)
+where
+    G: Game,
+{
+    game.start();
+    keyboard.set_repeat_ms(game.repeat_ms());
+    let mut scenes: Stack<Playing, 1> = Stack::new();
+    scenes.run(Playing, game, keyboard);
+    keyboard.set_repeat_ms(0);
+    game.finish();
+}
+
+/// The one scene [`play`] needs - the game, which does its own drawing
+struct Playing;
+
+impl<G> Scene<G> for Playing
+where
+    G: Game,
+{
+    fn enter(&mut self, _game: &mut G) {}
+
+    fn draw(&mut self, _game: &mut G) {}
+
+    fn tick_ms(&self, game: &G) -> Option<u32> {
+        game.tick_ms()
+    }
+
+    fn update(&mut self, game: &mut G) -> Transition<Self> {
+        if game.tick() {
+            Transition::Stay
+        } else {
+            Transition::Pop
+        }
+    }
+
+    fn handle_input(&mut self, game: &mut G, key: KeyEvent) -> Transition<Self> {
+        if game.handle_key(key) {
+            Transition::Stay
+        } else {
+            Transition::Pop
+        }
+    }
+}
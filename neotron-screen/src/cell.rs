@@ -0,0 +1,147 @@
+//! What goes in each character cell - a character, and its colours.
+
+/// One of the eight ANSI colours
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Colour {
+    /// Black
+    Black = 0,
+    /// Red
+    Red = 1,
+    /// Green
+    Green = 2,
+    /// Yellow, which is brown on some screens unless it is bold
+    Yellow = 3,
+    /// Blue
+    Blue = 4,
+    /// Magenta
+    Magenta = 5,
+    /// Cyan
+    Cyan = 6,
+    /// White, which is light grey on some screens unless it is bold
+    White = 7,
+}
+
+impl Colour {
+    /// Turn three bits back into a colour
+    const fn from_bits(bits: u32) -> Colour {
+        match bits & 7 {
+            0 => Colour::Black,
+            1 => Colour::Red,
+            2 => Colour::Green,
+            3 => Colour::Yellow,
+            4 => Colour::Blue,
+            5 => Colour::Magenta,
+            6 => Colour::Cyan,
+            _ => Colour::White,
+        }
+    }
+}
+
+/// How a character is drawn
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Attr {
+    /// The colour of the character
+    pub fg: Colour,
+    /// The colour behind the character
+    pub bg: Colour,
+    /// Bold, or bright
+    pub bold: bool,
+    /// Swap the foreground and background
+    pub reverse: bool,
+}
+
+impl Attr {
+    /// White on black, which is what the screen is reset to
+    pub const DEFAULT: Attr = Attr::new(Colour::White, Colour::Black);
+
+    /// Make a plain attribute with these colours
+    pub const fn new(fg: Colour, bg: Colour) -> Attr {
+        Attr {
+            fg,
+            bg,
+            bold: false,
+            reverse: false,
+        }
+    }
+
+    /// The same, but bold
+    pub const fn bold(self) -> Attr {
+        Attr { bold: true, ..self }
+    }
+
+    /// The same, but in reverse video
+    pub const fn reverse(self) -> Attr {
+        Attr {
+            reverse: true,
+            ..self
+        }
+    }
+
+    /// Pack the attribute into eight bits
+    const fn to_bits(self) -> u32 {
+        (self.fg as u32)
+            | (self.bg as u32) << 3
+            | (self.bold as u32) << 6
+            | (self.reverse as u32) << 7
+    }
+
+    /// Unpack an attribute
+    const fn from_bits(bits: u32) -> Attr {
+        Attr {
+            fg: Colour::from_bits(bits),
+            bg: Colour::from_bits(bits >> 3),
+            bold: bits & 1 << 6 != 0,
+            reverse: bits & 1 << 7 != 0,
+        }
+    }
+}
+
+impl Default for Attr {
+    fn default() -> Self {
+        Attr::DEFAULT
+    }
+}
+
+/// A character and its attribute, packed into 32 bits.
+///
+/// A `char` only needs 21 bits, so the attribute goes in the bits above it.
+/// That keeps a whole 80x25 screen down to 8 KiB.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Cell(u32);
+
+/// Where the attribute bits start
+const ATTR_SHIFT: u32 = 21;
+
+impl Cell {
+    /// A space, in the default colours
+    pub const BLANK: Cell = Cell::new(' ', Attr::DEFAULT);
+
+    /// A value no real cell can have, for when we don't know what's on the
+    /// screen
+    pub(crate) const UNKNOWN: Cell = Cell(u32::MAX);
+
+    /// Make a cell
+    pub const fn new(ch: char, attr: Attr) -> Cell {
+        Cell(ch as u32 | attr.to_bits() << ATTR_SHIFT)
+    }
+
+    /// The character in the cell
+    pub const fn ch(self) -> char {
+        match char::from_u32(self.0 & ((1 << ATTR_SHIFT) - 1)) {
+            Some(ch) => ch,
+            None => ' ',
+        }
+    }
+
+    /// How the character is drawn
+    pub const fn attr(self) -> Attr {
+        Attr::from_bits(self.0 >> ATTR_SHIFT)
+    }
+}
+
+impl Default for Cell {
+    fn default() -> Self {
+        Cell::BLANK
+    }
+}
@@ -0,0 +1,427 @@
+//! A flicker-free screen buffer for Neotron applications.
+//!
+//! You draw into a [`Screen`] in memory, as often and as untidily as you
+//! like, then call [`Screen::flush`]. That compares what you drew with what
+//! the console is already showing, and sends only the characters which
+//! changed - so on a slow serial console, or a slow video driver, the screen
+//! doesn't flicker and a busy frame costs a few bytes instead of a few
+//! thousand.
+
+#![no_std]
+#![deny(missing_docs)]
+#![deny(unsafe_code)]
+
+use core::fmt::Write;
+
+pub mod cell;
+
+pub use cell::{Attr, Cell, Colour};
+
+/// If the next change on a row is this close, we write out the unchanged
+/// characters in between rather than moving the cursor, as it's shorter
+const MAX_SKIP: usize = 4;
+
+/// A rectangle of character cells, and what we think the console is showing.
+///
+/// `W` can be up to 255 columns.
+pub struct Screen<const W: usize, const H: usize> {
+    /// What the application has drawn
+    cells: [[Cell; W]; H],
+    /// What we last sent to the console
+    shown: [[Cell; W]; H],
+    /// The first and last-plus-one column on each row which might have
+    /// changed since the last flush
+    damage: [(u8, u8); H],
+    /// Where the next character goes, as (row, column)
+    cursor: (usize, usize),
+    /// The attribute the next character gets
+    attr: Attr,
+    /// The rows which scroll when you write past the bottom, as (top,
+    /// bottom-plus-one)
+    region: (usize, usize),
+    /// A scroll we have done which the console hasn't, as (top,
+    /// bottom-plus-one, lines), where positive lines is up. Only used if the
+    /// console can scroll.
+    pending_scroll: Option<(usize, usize, isize)>,
+    /// Does the console understand scroll regions?
+    hardware_scroll: bool,
+    /// Where we left the console's cursor, if we know
+    console_cursor: Option<(usize, usize)>,
+}
+
+impl<const W: usize, const H: usize> Screen<W, H> {
+    /// Make a blank screen.
+    ///
+    /// We don't know what the console is showing yet, so the first flush
+    /// sends everything.
+    pub const fn new() -> Screen<W, H> {
+        Screen {
+            cells: [[Cell::BLANK; W]; H],
+            shown: [[Cell::UNKNOWN; W]; H],
+            damage: [(0, W as u8); H],
+            cursor: (0, 0),
+            attr: Attr::DEFAULT,
+            region: (0, H),
+            pending_scroll: None,
+            hardware_scroll: false,
+            console_cursor: None,
+        }
+    }
+
+    /// How many columns there are
+    pub const fn width(&self) -> usize {
+        W
+    }
+
+    /// How many rows there are
+    pub const fn height(&self) -> usize {
+        H
+    }
+
+    /// Forget what the console is showing, so the next flush sends
+    /// everything. Call this if something else has written to the console.
+    pub fn invalidate(&mut self) {
+        self.shown = [[Cell::UNKNOWN; W]; H];
+        self.damage = [(0, W as u8); H];
+        self.pending_scroll = None;
+        self.console_cursor = None;
+    }
+
+    /// Say whether the console understands ANSI scroll regions (`ESC [ r`)
+    /// and scroll commands (`ESC [ S` and `ESC [ T`).
+    ///
+    /// If it does, scrolling part of the screen costs a few bytes instead of
+    /// redrawing every row. It's off to start with.
+    pub fn set_hardware_scroll(&mut self, enabled: bool) {
+        self.hardware_scroll = enabled;
+    }
+
+    /// Set the attribute for the characters written after this
+    pub fn set_attr(&mut self, attr: Attr) {
+        self.attr = attr;
+    }
+
+    /// Get the attribute the next character gets
+    pub fn attr(&self) -> Attr {
+        self.attr
+    }
+
+    /// Move the cursor. Anything off the screen is moved on to it.
+    pub fn move_to(&mut self, row: usize, col: usize) {
+        self.cursor = (row.min(H - 1), col.min(W - 1));
+    }
+
+    /// Where the cursor is, as (row, column)
+    pub fn cursor(&self) -> (usize, usize) {
+        self.cursor
+    }
+
+    /// Get a cell. Anything off the screen is blank.
+    pub fn get(&self, row: usize, col: usize) -> Cell {
+        if row < H && col < W {
+            self.cells[row][col]
+        } else {
+            Cell::BLANK
+        }
+    }
+
+    /// Put a character somewhere, without moving the cursor. Anything off
+    /// the screen is ignored.
+    pub fn set(&mut self, row: usize, col: usize, ch: char, attr: Attr) {
+        if row < H && col < W {
+            self.cells[row][col] = Cell::new(ch, attr);
+            self.damage_cells(row, col, col + 1);
+        }
+    }
+
+    /// Fill a rectangle with one character. Anything off the screen is
+    /// ignored.
+    pub fn fill(
+        &mut self,
+        row: usize,
+        col: usize,
+        width: usize,
+        height: usize,
+        ch: char,
+        attr: Attr,
+    ) {
+        let cell = Cell::new(ch, attr);
+        let right = (col + width).min(W);
+        for r in row..(row + height).min(H) {
+            if col < right {
+                self.cells[r][col..right].fill(cell);
+                self.damage_cells(r, col, right);
+            }
+        }
+    }
+
+    /// Blank the whole screen in the current attribute, and put the cursor
+    /// in the top left
+    pub fn clear(&mut self) {
+        self.fill(0, 0, W, H, ' ', self.attr);
+        self.cursor = (0, 0);
+    }
+
+    /// Write a character at the cursor, and move the cursor on.
+    ///
+    /// `\n` goes to the start of the next line and `\r` to the start of this
+    /// one. Going past the end of a line wraps, and going past the bottom of
+    /// the scroll region scrolls it.
+    pub fn put_char(&mut self, ch: char) {
+        match ch {
+            '\n' => self.new_line(),
+            '\r' => self.cursor.1 = 0,
+            _ => {
+                let (row, col) = self.cursor;
+                self.set(row, col, ch, self.attr);
+                if col + 1 < W {
+                    self.cursor.1 += 1;
+                } else {
+                    self.new_line();
+                }
+            }
+        }
+    }
+
+    /// Write some text at the cursor
+    pub fn put_str(&mut self, text: &str) {
+        for ch in text.chars() {
+            self.put_char(ch);
+        }
+    }
+
+    /// Move to the start of the next line, scrolling if we're at the bottom
+    /// of the scroll region
+    fn new_line(&mut self) {
+        self.cursor.1 = 0;
+        if self.cursor.0 + 1 == self.region.1 {
+            self.scroll_up(self.region.0, self.region.1, 1);
+        } else if self.cursor.0 + 1 < H {
+            self.cursor.0 += 1;
+        }
+    }
+
+    /// Set which rows scroll when you write past the bottom, from `top` up
+    /// to but not including `bottom`
+    pub fn set_scroll_region(&mut self, top: usize, bottom: usize) {
+        let bottom = bottom.min(H);
+        self.region = (top.min(bottom), bottom);
+    }
+
+    /// Make every row scroll when you write past the bottom
+    pub fn reset_scroll_region(&mut self) {
+        self.region = (0, H);
+    }
+
+    /// Move rows `top` up to but not including `bottom` up by `lines`,
+    /// blanking the rows which appear at the bottom
+    pub fn scroll_up(&mut self, top: usize, bottom: usize, lines: usize) {
+        let bottom = bottom.min(H);
+        if top >= bottom || lines == 0 {
+            return;
+        }
+        let lines = lines.min(bottom - top);
+        self.cells.copy_within(top + lines..bottom, top);
+        for row in bottom - lines..bottom {
+            self.cells[row] = [Cell::new(' ', self.attr); W];
+        }
+        self.record_scroll(top, bottom, lines as isize);
+    }
+
+    /// Move rows `top` up to but not including `bottom` down by `lines`,
+    /// blanking the rows which appear at the top
+    pub fn scroll_down(&mut self, top: usize, bottom: usize, lines: usize) {
+        let bottom = bottom.min(H);
+        if top >= bottom || lines == 0 {
+            return;
+        }
+        let lines = lines.min(bottom - top);
+        self.cells.copy_within(top..bottom - lines, top + lines);
+        for row in top..top + lines {
+            self.cells[row] = [Cell::new(' ', self.attr); W];
+        }
+        self.record_scroll(top, bottom, -(lines as isize));
+    }
+
+    /// Note that some rows have moved, either so the console can scroll them
+    /// too, or so we redraw them. Positive `lines` is up.
+    fn record_scroll(&mut self, top: usize, bottom: usize, lines: isize) {
+        if self.hardware_scroll && self.pending_scroll.is_none() {
+            // Shift our idea of what's on the console the same way, and the
+            // flush will tell the console to do the same. The console fills
+            // the gap with blanks, in the default colours as that's what we
+            // set before scrolling.
+            if lines > 0 {
+                let lines = lines as usize;
+                self.shown.copy_within(top + lines..bottom, top);
+                self.shown[bottom - lines..bottom].fill([Cell::BLANK; W]);
+            } else {
+                let lines = (-lines) as usize;
+                self.shown.copy_within(top..bottom - lines, top + lines);
+                self.shown[top..top + lines].fill([Cell::BLANK; W]);
+            }
+            self.pending_scroll = Some((top, bottom, lines));
+        }
+        for row in top..bottom {
+            self.damage_cells(row, 0, W);
+        }
+    }
+
+    /// Note that some cells on a row might have changed
+    fn damage_cells(&mut self, row: usize, start: usize, end: usize) {
+        let (old_start, old_end) = self.damage[row];
+        if old_start < old_end {
+            self.damage[row] = (old_start.min(start as u8), old_end.max(end as u8));
+        } else {
+            self.damage[row] = (start as u8, end as u8);
+        }
+    }
+
+    /// Send whatever has changed to the console, and leave the console's
+    /// cursor where our cursor is
+    pub fn flush(&mut self, out: &mut neotron_sdk::File) {
+        let mut batch = Batch::new(out);
+        let scrolled = self.pending_scroll.is_some();
+        if let Some((top, bottom, lines)) = self.pending_scroll.take() {
+            // Set the region, scroll it, then put the region back
+            let _ = write!(
+                batch,
+                "\x1b[0m\x1b[{};{}r\x1b[{}{}\x1b[r",
+                top + 1,
+                bottom,
+                lines.unsigned_abs(),
+                if lines > 0 { 'S' } else { 'T' }
+            );
+        }
+        // What the console's cursor and pen are set to, if we know. Setting
+        // a scroll region moves the cursor.
+        let mut at = if scrolled { None } else { self.console_cursor };
+        let mut pen: Option<Attr> = None;
+        for row in 0..H {
+            let (start, end) = self.damage[row];
+            self.damage[row] = (0, 0);
+            let mut col = usize::from(start);
+            while col < usize::from(end) {
+                let cell = self.cells[row][col];
+                if cell == self.shown[row][col] {
+                    col += 1;
+                    continue;
+                }
+                if at != Some((row, col)) {
+                    match at {
+                        Some((at_row, at_col))
+                            if at_row == row
+                                && at_col < col
+                                && col - at_col <= MAX_SKIP
+                                && self.cells[row][at_col..col]
+                                    .iter()
+                                    .all(|c| Some(c.attr()) == pen) =>
+                        {
+                            // Cheaper to write what's already there
+                            for c in &self.cells[row][at_col..col] {
+                                let _ = batch.write_char(c.ch());
+                            }
+                        }
+                        _ => {
+                            let _ = write!(batch, "\x1b[{};{}H", row + 1, col + 1);
+                        }
+                    }
+                }
+                let attr = cell.attr();
+                if pen != Some(attr) {
+                    write_sgr(&mut batch, attr);
+                    pen = Some(attr);
+                }
+                let _ = batch.write_char(cell.ch());
+                self.shown[row][col] = cell;
+                // Some consoles wrap after the last column and some don't, so
+                // don't guess
+                at = if col + 1 < W {
+                    Some((row, col + 1))
+                } else {
+                    None
+                };
+                col += 1;
+            }
+        }
+        if at != Some(self.cursor) {
+            let _ = write!(batch, "\x1b[{};{}H", self.cursor.0 + 1, self.cursor.1 + 1);
+        }
+        self.console_cursor = Some(self.cursor);
+        batch.send();
+    }
+}
+
+impl<const W: usize, const H: usize> Default for Screen<W, H> {
+    fn default() -> Self {
+        Screen::new()
+    }
+}
+
+impl<const W: usize, const H: usize> core::fmt::Write for Screen<W, H> {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        self.put_str(s);
+        Ok(())
+    }
+}
+
+/// Send the SGR sequence for an attribute. We always start from a reset, so
+/// it doesn't matter what was set before.
+fn write_sgr(batch: &mut Batch, attr: Attr) {
+    let _ = write!(
+        batch,
+        "\x1b[0;{};{}",
+        30 + attr.fg as u8,
+        40 + attr.bg as u8
+    );
+    if attr.bold {
+        let _ = batch.write_str(";1");
+    }
+    if attr.reverse {
+        let _ = batch.write_str(";7");
+    }
+    let _ = batch.write_char('m');
+}
+
+/// Collects output so we can send it to the console in big pieces, not one
+/// character at a time
+struct Batch<'a> {
+    out: &'a mut neotron_sdk::File,
+    buffer: [u8; 256],
+    len: usize,
+}
+
+impl<'a> Batch<'a> {
+    /// Make an empty batch
+    fn new(out: &'a mut neotron_sdk::File) -> Batch<'a> {
+        Batch {
+            out,
+            buffer: [0; 256],
+            len: 0,
+        }
+    }
+
+    /// Send what we've collected
+    fn send(&mut self) {
+        if self.len > 0 {
+            let _ = self.out.write(&self.buffer[0..self.len]);
+            self.len = 0;
+        }
+    }
+}
+
+impl core::fmt::Write for Batch<'_> {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        let bytes = s.as_bytes();
+        if self.len + bytes.len() > self.buffer.len() {
+            self.send();
+        }
+        if bytes.len() > self.buffer.len() {
+            let _ = self.out.write(bytes);
+        } else {
+            self.buffer[self.len..self.len + bytes.len()].copy_from_slice(bytes);
+            self.len += bytes.len();
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,329 @@
+//! Settings shared between Neotron applications.
+//!
+//! Some settings, like which colours to use or how keys repeat, aren't
+//! really up to one application - the user wants them the same everywhere.
+//! They live in one file, [`FILE`], which every application can read and
+//! change.
+//!
+//! Each setting has a [`Key`], which knows where it goes in the file, what
+//! type it is and what it is if nobody has set it:
+//!
+//! ```rust,ignore
+//! const SOUND: Key<bool> = Key::new("sound", "enabled", true);
+//!
+//! let mut settings: Settings<32> = Settings::new();
+//! let _ = settings.load();
+//! if settings.get(&SOUND) {
+//!     beep();
+//! }
+//! ```
+//!
+//! The settings everyone should understand are in [`shared`]. Anything in
+//! the file we don't know about is kept, and written back when we save, so
+//! applications don't lose each other's settings.
+//!
+//! A [`Watcher`] says which settings have changed since it last looked, so
+//! an options screen can change something and whatever uses it can notice.
+
+#![no_std]
+#![deny(missing_docs)]
+#![deny(unsafe_code)]
+
+use core::fmt::Write;
+
+pub mod shared;
+pub mod value;
+
+pub use value::Value;
+
+use value::Text;
+
+/// Where the shared settings live
+pub const FILE: &str = "NEOTRON.CFG";
+
+/// The longest section or key name we keep
+pub const MAX_NAME: usize = 24;
+
+/// The longest value we keep
+pub const MAX_VALUE: usize = 40;
+
+/// The biggest settings file we can read
+pub const MAX_FILE: usize = 2048;
+
+/// Something that went wrong with the settings
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// The settings file couldn't be read or written
+    Config(neotron_config::Error),
+    /// There's no room for any more settings
+    Full,
+    /// A name or a value is too long to keep
+    TooLong,
+}
+
+impl From<neotron_config::Error> for Error {
+    fn from(error: neotron_config::Error) -> Self {
+        Error::Config(error)
+    }
+}
+
+/// Where a setting goes, what type it is, and what it is if nobody has set
+/// it
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Key<T> {
+    section: &'static str,
+    name: &'static str,
+    default: T,
+}
+
+impl<T: Value> Key<T> {
+    /// Make a key for the setting `name` in `section`
+    pub const fn new(section: &'static str, name: &'static str, default: T) -> Key<T> {
+        Key {
+            section,
+            name,
+            default,
+        }
+    }
+
+    /// Which section of the file the setting is in
+    pub const fn section(&self) -> &'static str {
+        self.section
+    }
+
+    /// What the setting is called
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// What the setting is if nobody has set it
+    pub fn default(&self) -> T {
+        self.default
+    }
+}
+
+/// One setting we're keeping
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct Entry {
+    section: Text<MAX_NAME>,
+    name: Text<MAX_NAME>,
+    value: Text<MAX_VALUE>,
+    /// The version of the settings when this last changed
+    changed: u32,
+}
+
+impl Entry {
+    /// An empty slot
+    const EMPTY: Entry = Entry {
+        section: Text::new(),
+        name: Text::new(),
+        value: Text::new(),
+        changed: 0,
+    };
+
+    /// Is this setting in `section`?
+    fn in_section(&self, section: &str) -> bool {
+        self.section.as_str().eq_ignore_ascii_case(section)
+    }
+
+    /// Is this the setting `name` in `section`?
+    fn is(&self, section: &str, name: &str) -> bool {
+        self.in_section(section) && self.name.as_str().eq_ignore_ascii_case(name)
+    }
+}
+
+/// Up to `N` settings, read from and saved to a file
+#[derive(Debug, Clone)]
+pub struct Settings<const N: usize> {
+    entries: [Entry; N],
+    len: usize,
+    /// Goes up by one every time a setting changes
+    version: u32,
+}
+
+impl<const N: usize> Settings<N> {
+    /// Make an empty set of settings, where everything is its default
+    pub const fn new() -> Settings<N> {
+        Settings {
+            entries: [Entry::EMPTY; N],
+            len: 0,
+            version: 0,
+        }
+    }
+
+    /// Read the shared settings file, [`FILE`]
+    pub fn load(&mut self) -> Result<(), Error> {
+        self.load_from(FILE)
+    }
+
+    /// Read a settings file. What's in it replaces what we had, and
+    /// anything it doesn't mention is left alone.
+    pub fn load_from(&mut self, filename: &str) -> Result<(), Error> {
+        let mut buffer = [0u8; MAX_FILE];
+        let config = neotron_config::load(filename, &mut buffer)?;
+        self.merge(&config)
+    }
+
+    /// Take every setting from a configuration file. Settings that don't
+    /// fit are skipped, and the first problem is reported once the rest
+    /// are in.
+    pub fn merge(&mut self, config: &neotron_config::Config) -> Result<(), Error> {
+        let mut result = Ok(());
+        for (section, name, value) in config.iter() {
+            if let Err(e) = self.store(section, name, value) {
+                if result.is_ok() {
+                    result = Err(e);
+                }
+            }
+        }
+        result
+    }
+
+    /// Write the settings out to the shared settings file, [`FILE`]
+    pub fn save(&self) -> Result<(), Error> {
+        self.save_to(FILE)
+    }
+
+    /// Write the settings out to a file. Each section is written in one
+    /// go, in the order they were first seen.
+    pub fn save_to(&self, filename: &str) -> Result<(), Error> {
+        let mut writer = neotron_config::create(filename)?;
+        self.write(&mut writer).map_err(|_| Error::TooLong)
+    }
+
+    /// Write the settings out as a configuration file
+    pub fn write<W: Write>(&self, writer: &mut neotron_config::Writer<W>) -> core::fmt::Result {
+        let entries = &self.entries[0..self.len];
+        for (idx, entry) in entries.iter().enumerate() {
+            let section = entry.section.as_str();
+            if entries[0..idx].iter().any(|e| e.in_section(section)) {
+                // Already written
+                continue;
+            }
+            // Settings before the first section don't get a heading
+            if !section.is_empty() {
+                writer.section(section)?;
+            }
+            for other in entries.iter().filter(|e| e.in_section(section)) {
+                writer.set_str(other.name.as_str(), other.value.as_str())?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Get a setting, or its default if it isn't set or doesn't make sense
+    pub fn get<T: Value>(&self, key: &Key<T>) -> T {
+        self.find(key.section, key.name)
+            .and_then(|idx| T::parse(self.entries[idx].value.as_str()))
+            .unwrap_or(key.default)
+    }
+
+    /// Get a setting as text, as it is in the file
+    pub fn get_str(&self, section: &str, name: &str) -> Option<&str> {
+        self.find(section, name)
+            .map(|idx| self.entries[idx].value.as_str())
+    }
+
+    /// Change a setting
+    pub fn set<T: Value>(&mut self, key: &Key<T>, value: T) -> Result<(), Error> {
+        let mut text: Text<MAX_VALUE> = Text::new();
+        value.write(&mut text).map_err(|_| Error::TooLong)?;
+        self.store(key.section, key.name, text.as_str())
+    }
+
+    /// Put a setting back to its default
+    pub fn reset<T: Value>(&mut self, key: &Key<T>) -> Result<(), Error> {
+        self.set(key, key.default)
+    }
+
+    /// Goes up every time a setting changes
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Has a setting changed since the settings were at `version`?
+    pub fn changed_since<T>(&self, key: &Key<T>, version: u32) -> bool {
+        self.find(key.section, key.name)
+            .is_some_and(|idx| self.entries[idx].changed > version)
+    }
+
+    /// Every setting changed since the settings were at `version`, as
+    /// (section, name)
+    pub fn changes_since(&self, version: u32) -> impl Iterator<Item = (&str, &str)> {
+        self.entries[0..self.len]
+            .iter()
+            .filter(move |entry| entry.changed > version)
+            .map(|entry| (entry.section.as_str(), entry.name.as_str()))
+    }
+
+    /// Where a setting is kept, if it is
+    fn find(&self, section: &str, name: &str) -> Option<usize> {
+        self.entries[0..self.len]
+            .iter()
+            .position(|entry| entry.is(section, name))
+    }
+
+    /// Keep a setting, as text, noting the change if it is one
+    fn store(&mut self, section: &str, name: &str, value: &str) -> Result<(), Error> {
+        let value: Text<MAX_VALUE> = Text::from_str(value).ok_or(Error::TooLong)?;
+        let idx = match self.find(section, name) {
+            Some(idx) => idx,
+            None => {
+                if self.len == N {
+                    return Err(Error::Full);
+                }
+                let entry = Entry {
+                    section: Text::from_str(section).ok_or(Error::TooLong)?,
+                    name: Text::from_str(name).ok_or(Error::TooLong)?,
+                    value: Text::new(),
+                    changed: 0,
+                };
+                self.entries[self.len] = entry;
+                self.len += 1;
+                self.len - 1
+            }
+        };
+        let entry = &mut self.entries[idx];
+        if entry.changed == 0 || entry.value != value {
+            self.version += 1;
+            entry.value = value;
+            entry.changed = self.version;
+        }
+        Ok(())
+    }
+}
+
+impl<const N: usize> Default for Settings<N> {
+    fn default() -> Self {
+        Settings::new()
+    }
+}
+
+/// Keeps track of which changes to some settings have been seen
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct Watcher {
+    seen: u32,
+}
+
+impl Watcher {
+    /// Make a watcher which hasn't seen anything, so every setting that has
+    /// been set counts as changed
+    pub const fn new() -> Watcher {
+        Watcher { seen: 0 }
+    }
+
+    /// Has anything changed since we last caught up?
+    pub fn any_changed<const N: usize>(&self, settings: &Settings<N>) -> bool {
+        settings.version() > self.seen
+    }
+
+    /// Has this setting changed since we last caught up?
+    pub fn changed<T, const N: usize>(&self, settings: &Settings<N>, key: &Key<T>) -> bool {
+        settings.changed_since(key, self.seen)
+    }
+
+    /// Count every change so far as seen
+    pub fn catch_up<const N: usize>(&mut self, settings: &Settings<N>) {
+        self.seen = settings.version();
+    }
+}
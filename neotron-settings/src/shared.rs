@@ -0,0 +1,42 @@
+//! Settings every application should understand.
+
+use core::fmt::Write;
+
+use crate::{Key, Value};
+
+/// Which colours applications should use
+pub const COLOUR_THEME: Key<ColourTheme> = Key::new("display", "theme", ColourTheme::Colour);
+
+/// How soon a key can repeat, in milliseconds. A key pressed again sooner
+/// than this is taken to be the keyboard repeating it by mistake. Zero lets
+/// every key through.
+pub const KEY_REPEAT_MS: Key<u32> = Key::new("keyboard", "repeat_ms", 0);
+
+/// Which colours applications should use
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ColourTheme {
+    /// Whatever colours the application likes
+    Colour,
+    /// Just white, for monochrome screens and anyone who finds colours hard
+    /// to tell apart
+    Mono,
+}
+
+impl Value for ColourTheme {
+    fn parse(text: &str) -> Option<ColourTheme> {
+        if text.eq_ignore_ascii_case("colour") || text.eq_ignore_ascii_case("color") {
+            Some(ColourTheme::Colour)
+        } else if text.eq_ignore_ascii_case("mono") {
+            Some(ColourTheme::Mono)
+        } else {
+            None
+        }
+    }
+
+    fn write<W: Write>(&self, out: &mut W) -> core::fmt::Result {
+        out.write_str(match self {
+            ColourTheme::Colour => "colour",
+            ColourTheme::Mono => "mono",
+        })
+    }
+}
@@ -0,0 +1,107 @@
+//! The types a setting can have, and how they're written down.
+
+use core::fmt::Write;
+
+/// Something that can be kept as a setting
+pub trait Value: Copy {
+    /// Read a value back from how it was written. Gives `None` if it
+    /// doesn't make sense.
+    fn parse(text: &str) -> Option<Self>;
+
+    /// Write the value down, so [`Value::parse`] can read it back
+    fn write<W: Write>(&self, out: &mut W) -> core::fmt::Result;
+}
+
+impl Value for bool {
+    /// We understand `on`, `yes`, `true` and `1`, and `off`, `no`, `false`
+    /// and `0`, like [`neotron_config::Config::get_bool`].
+    fn parse(text: &str) -> Option<bool> {
+        let is = |word: &str| text.eq_ignore_ascii_case(word);
+        if is("on") || is("yes") || is("true") || is("1") {
+            Some(true)
+        } else if is("off") || is("no") || is("false") || is("0") {
+            Some(false)
+        } else {
+            None
+        }
+    }
+
+    fn write<W: Write>(&self, out: &mut W) -> core::fmt::Result {
+        out.write_str(if *self { "on" } else { "off" })
+    }
+}
+
+impl Value for char {
+    fn parse(text: &str) -> Option<char> {
+        let mut chars = text.chars();
+        match (chars.next(), chars.next()) {
+            (Some(ch), None) => Some(ch),
+            _ => None,
+        }
+    }
+
+    fn write<W: Write>(&self, out: &mut W) -> core::fmt::Result {
+        out.write_char(*self)
+    }
+}
+
+/// Numbers are written out in decimal
+macro_rules! number_value {
+    ($($t:ty),*) => {
+        $(
+            impl Value for $t {
+                fn parse(text: &str) -> Option<$t> {
+                    text.parse().ok()
+                }
+
+                fn write<W: Write>(&self, out: &mut W) -> core::fmt::Result {
+                    write!(out, "{}", self)
+                }
+            }
+        )*
+    };
+}
+
+number_value!(u8, u16, u32, i8, i16, i32);
+
+/// A short piece of text, kept in place
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) struct Text<const N: usize> {
+    bytes: [u8; N],
+    len: usize,
+}
+
+impl<const N: usize> Text<N> {
+    /// Make some empty text
+    pub(crate) const fn new() -> Text<N> {
+        Text {
+            bytes: [0; N],
+            len: 0,
+        }
+    }
+
+    /// Make some text, if it fits
+    pub(crate) fn from_str(text: &str) -> Option<Text<N>> {
+        let mut result = Text::new();
+        result.write_str(text).ok()?;
+        Some(result)
+    }
+
+    /// The text
+    pub(crate) fn as_str(&self) -> &str {
+        core::str::from_utf8(&self.bytes[0..self.len]).unwrap_or("")
+    }
+}
+
+impl<const N: usize> Write for Text<N> {
+    /// Fails, and leaves the text as it was, if it doesn't fit
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        let end = self.len + s.len();
+        if end > N {
+            return Err(core::fmt::Error);
+        }
+        self.bytes[self.len..end].copy_from_slice(s.as_bytes());
+        self.len = end;
+        Ok(())
+    }
+}
@@ -0,0 +1,243 @@
+//! Makes `.sfx` files, on your PC.
+//!
+//! ```text
+//! sfxtool wav <input.wav> <output.sfx> [sample rate]
+//! sfxtool recipe <output.sfx> <step>...
+//! sfxtool info <file.sfx>
+//! ```
+//!
+//! A WAV file can be 8-bit or 16-bit, mono or stereo, at any rate. We mix
+//! it down to mono, resample it (to 11025 Hz unless you say otherwise), cut
+//! off any silence at either end, and store it as 8-bit.
+//!
+//! Each step of a recipe is `wave:from-to:ms:volume`, where the wave is
+//! `square`, `triangle`, `sawtooth`, `noise` or `rest`, the pitches are in
+//! Hz and the volume is out of 255. Leave out `-to` to hold the pitch, and
+//! `:volume` for full volume (or silence, for a rest).
+
+use neotron_audio::Waveform;
+use neotron_sfx::{Sfx, Step};
+
+/// The sample rate recordings get, if you don't say
+const DEFAULT_RATE: u32 = 11025;
+
+/// Samples quieter than this, at either end, get cut off
+const SILENCE: i16 = 256;
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let result = match args.first().map(String::as_str) {
+        Some("wav") if args.len() == 3 || args.len() == 4 => {
+            let rate = match args.get(3) {
+                Some(rate) => rate
+                    .parse()
+                    .ok()
+                    .filter(|rate| (1000..=65535).contains(rate))
+                    .ok_or_else(|| format!("{} isn't a sample rate I can use", rate)),
+                None => Ok(DEFAULT_RATE),
+            };
+            rate.and_then(|rate| convert_wav(&args[1], &args[2], rate))
+        }
+        Some("recipe") if args.len() >= 3 => make_recipe(&args[1], &args[2..]),
+        Some("info") if args.len() == 2 => info(&args[1]),
+        _ => Err("Usage:\n  \
+             sfxtool wav <input.wav> <output.sfx> [sample rate]\n  \
+             sfxtool recipe <output.sfx> <wave:from-to:ms:volume>...\n  \
+             sfxtool info <file.sfx>"
+            .into()),
+    };
+    if let Err(message) = result {
+        eprintln!("{}", message);
+        std::process::exit(1);
+    }
+}
+
+/// Turn a WAV file into a recording
+fn convert_wav(input: &str, output: &str, rate: u32) -> Result<(), String> {
+    let bytes = std::fs::read(input).map_err(|e| format!("Can't read {}: {}", input, e))?;
+    let (source_rate, samples) = read_wav(&bytes).map_err(|e| format!("{}: {}", input, e))?;
+    let samples = resample(&samples, source_rate, rate);
+    let first = samples.iter().position(|s| s.abs() >= SILENCE);
+    let last = samples.iter().rposition(|s| s.abs() >= SILENCE);
+    let samples = match (first, last) {
+        (Some(first), Some(last)) => &samples[first..=last],
+        _ => return Err(format!("{} is silent", input)),
+    };
+    let mut file = neotron_sfx::pcm_header(rate as u16).to_vec();
+    file.extend(
+        samples
+            .iter()
+            .map(|s| neotron_audio::convert::to_i8(*s) as u8),
+    );
+    std::fs::write(output, &file).map_err(|e| format!("Can't write {}: {}", output, e))?;
+    println!(
+        "{}: {} samples at {} Hz, {} ms, {} bytes",
+        output,
+        samples.len(),
+        rate,
+        samples.len() as u64 * 1000 / u64::from(rate),
+        file.len()
+    );
+    Ok(())
+}
+
+/// Get the sample rate, and the samples mixed down to mono, from a WAV file
+fn read_wav(bytes: &[u8]) -> Result<(u32, Vec<i16>), String> {
+    if bytes.len() < 12 || &bytes[0..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+        return Err("not a WAV file".into());
+    }
+    let u16_at = |at: usize| u16::from_le_bytes([bytes[at], bytes[at + 1]]);
+    let u32_at = |at: usize| u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap());
+    let mut format = None;
+    let mut at = 12;
+    while at + 8 <= bytes.len() {
+        let id = &bytes[at..at + 4];
+        let len = u32_at(at + 4) as usize;
+        let body = at + 8;
+        let end = (body + len).min(bytes.len());
+        if id == b"fmt " && len >= 16 {
+            format = Some((
+                u16_at(body),
+                u16_at(body + 2),
+                u32_at(body + 4),
+                u16_at(body + 14),
+            ));
+        } else if id == b"data" {
+            let Some((tag, channels, rate, bits)) = format else {
+                return Err("the data comes before the format".into());
+            };
+            if tag != 1 {
+                return Err("only plain PCM WAV files will do".into());
+            }
+            let width = match bits {
+                8 => 1,
+                16 => 2,
+                _ => return Err(format!("{}-bit samples aren't supported", bits)),
+            };
+            let channels = usize::from(channels.max(1));
+            let samples = bytes[body..end]
+                .chunks_exact(width * channels)
+                .map(|frame| {
+                    let sum: i32 = frame
+                        .chunks_exact(width)
+                        .map(|s| match width {
+                            1 => i32::from(neotron_audio::convert::from_u8(s[0])),
+                            _ => i32::from(i16::from_le_bytes([s[0], s[1]])),
+                        })
+                        .sum();
+                    (sum / channels as i32) as i16
+                })
+                .collect();
+            return Ok((rate, samples));
+        }
+        // Chunks are padded to an even length
+        at = body + len + (len & 1);
+    }
+    Err("there's no sound in it".into())
+}
+
+/// Change the sample rate, by drawing straight lines between the samples
+fn resample(samples: &[i16], from: u32, to: u32) -> Vec<i16> {
+    if from == to || samples.is_empty() {
+        return samples.to_vec();
+    }
+    let count = (samples.len() as u64 * u64::from(to) / u64::from(from)) as usize;
+    (0..count)
+        .map(|idx| {
+            let position = idx as u64 * u64::from(from);
+            let whole = (position / u64::from(to)) as usize;
+            let part = (position % u64::from(to)) as i64;
+            let a = i64::from(samples[whole.min(samples.len() - 1)]);
+            let b = i64::from(samples[(whole + 1).min(samples.len() - 1)]);
+            (a + (b - a) * part / i64::from(to)) as i16
+        })
+        .collect()
+}
+
+/// Write a recipe from the steps on the command line
+fn make_recipe(output: &str, steps: &[String]) -> Result<(), String> {
+    let steps = steps
+        .iter()
+        .map(|text| {
+            parse_step(text).ok_or_else(|| format!("I don't understand the step {:?}", text))
+        })
+        .collect::<Result<Vec<Step>, String>>()?;
+    let count = u16::try_from(steps.len()).map_err(|_| "That's too many steps")?;
+    let mut file = neotron_sfx::recipe_header(count).to_vec();
+    for step in &steps {
+        file.extend_from_slice(&step.to_bytes());
+    }
+    std::fs::write(output, &file).map_err(|e| format!("Can't write {}: {}", output, e))?;
+    println!("{}: {} steps, {} bytes", output, steps.len(), file.len());
+    Ok(())
+}
+
+/// Read a step like `square:440-880:60:200`
+fn parse_step(text: &str) -> Option<Step> {
+    let mut parts = text.split(':');
+    let waveform = match parts.next()? {
+        "square" => Some(Waveform::Square),
+        "triangle" => Some(Waveform::Triangle),
+        "sawtooth" => Some(Waveform::Sawtooth),
+        "noise" => Some(Waveform::Noise),
+        "rest" => None,
+        _ => return None,
+    };
+    let pitch = parts.next()?;
+    let (from_hz, to_hz) = match pitch.split_once('-') {
+        Some((from, to)) => (from.parse().ok()?, to.parse().ok()?),
+        None => {
+            let hz = pitch.parse().ok()?;
+            (hz, hz)
+        }
+    };
+    let ms = parts.next()?.parse().ok()?;
+    let volume = match parts.next() {
+        Some(volume) => volume.parse().ok()?,
+        // A rest is quiet, so whatever comes before it fades out
+        None if waveform.is_none() => 0,
+        None => 255,
+    };
+    if parts.next().is_some() {
+        return None;
+    }
+    Some(Step {
+        waveform,
+        volume,
+        from_hz,
+        to_hz,
+        ms,
+    })
+}
+
+/// Say what's in a file
+fn info(input: &str) -> Result<(), String> {
+    let bytes = std::fs::read(input).map_err(|e| format!("Can't read {}: {}", input, e))?;
+    let sfx = Sfx::parse(&bytes).map_err(|e| format!("{}: {:?}", input, e))?;
+    match sfx {
+        Sfx::Pcm {
+            sample_rate,
+            samples,
+        } => println!(
+            "{}: a recording, {} samples at {} Hz",
+            input,
+            samples.len(),
+            sample_rate
+        ),
+        Sfx::Recipe { .. } => {
+            println!("{}: a recipe, {} steps", input, sfx.step_count());
+            for step in (0..sfx.step_count()).filter_map(|idx| sfx.step(idx)) {
+                println!(
+                    "  {:8} {:5}-{:<5} Hz {:5} ms, volume {}",
+                    step.waveform.map_or("Rest", Waveform::name),
+                    step.from_hz,
+                    step.to_hz,
+                    step.ms,
+                    step.volume
+                );
+            }
+        }
+    }
+    println!("  {} ms long", sfx.duration_ms());
+    Ok(())
+}
@@ -0,0 +1,285 @@
+//! Sound effects for Neotron games.
+//!
+//! A sound effect is either a short recording, or a recipe of tones for
+//! [`neotron_audio`] to play. Either way it's a few bytes you can build
+//! into your game:
+//!
+//! ```ignore
+//! const EAT: Sfx = Sfx::from_bytes(include_bytes!("eat.sfx"));
+//!
+//! let mut sounds = Player::new(22050);
+//! sounds.open();
+//! // ... then in the game ...
+//! sounds.play(EAT);
+//! // ... and once a frame, to keep the sound coming ...
+//! sounds.pump(frame_ms);
+//! ```
+//!
+//! Make `.sfx` files from WAV files, or from a list of tones, with the
+//! converter that comes with this crate:
+//!
+//! ```text
+//! cargo run -p neotron-sfx --example sfxtool -- wav boom.wav boom.sfx 11025
+//! cargo run -p neotron-sfx --example sfxtool -- recipe eat.sfx square:440-880:60:200
+//! ```
+//!
+//! # The file format
+//!
+//! Every file starts with eight bytes:
+//!
+//! | Offset | Length | What                                                   |
+//! |--------|--------|--------------------------------------------------------|
+//! | 0      | 4      | `NSFX`                                                 |
+//! | 4      | 1      | Version, which is 1                                    |
+//! | 5      | 1      | 0 for a recording, 1 for a recipe                      |
+//! | 6      | 2      | A recording's sample rate, or how many steps a recipe has, little-endian |
+//!
+//! A recording carries on with signed 8-bit mono samples, up to the end of
+//! the file. A recipe carries on with eight bytes for each step:
+//!
+//! | Offset | Length | What                                                   |
+//! |--------|--------|--------------------------------------------------------|
+//! | 0      | 1      | 0 square, 1 triangle, 2 sawtooth, 3 noise, 4 rest      |
+//! | 1      | 1      | Volume, out of 255                                     |
+//! | 2      | 2      | Pitch at the start, in Hz, little-endian               |
+//! | 4      | 2      | Pitch at the end, in Hz, little-endian                 |
+//! | 6      | 2      | Length, in milliseconds, little-endian                 |
+//!
+//! The pitch slides from start to end over the step, and so does the
+//! volume, to the next step's volume - or to nothing, after the last step.
+
+#![no_std]
+#![deny(missing_docs)]
+#![deny(unsafe_code)]
+
+mod player;
+
+pub use player::{Player, VOICES};
+
+use neotron_audio::Waveform;
+
+/// What every sound effect file starts with
+pub const MAGIC: [u8; 4] = *b"NSFX";
+
+/// The version of the format we understand
+pub const VERSION: u8 = 1;
+
+/// How long the header is, in bytes
+pub const HEADER_LEN: usize = 8;
+
+/// How long each step of a recipe is, in bytes
+pub const STEP_LEN: usize = 8;
+
+/// Marks a recording, in the header
+const KIND_PCM: u8 = 0;
+
+/// Marks a recipe, in the header
+const KIND_RECIPE: u8 = 1;
+
+/// Something that went wrong loading a sound effect
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// The OS couldn't open or read the file
+    Io(neotron_sdk::Error),
+    /// The file didn't fit in the buffer
+    TooLarge,
+    /// The file isn't a sound effect
+    NotSfx,
+    /// The file is a newer version than we understand
+    Version(u8),
+    /// The file is a sound effect, but it's been cut short or damaged
+    Corrupt,
+}
+
+impl From<neotron_sdk::Error> for Error {
+    fn from(error: neotron_sdk::Error) -> Self {
+        Error::Io(error)
+    }
+}
+
+/// A sound effect
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Sfx<'a> {
+    /// A recording
+    Pcm {
+        /// How many samples a second it was recorded at
+        sample_rate: u32,
+        /// Signed 8-bit samples
+        samples: &'a [u8],
+    },
+    /// Some tones to play, one after the other
+    Recipe {
+        /// The steps, [`STEP_LEN`] bytes each
+        steps: &'a [u8],
+    },
+}
+
+impl<'a> Sfx<'a> {
+    /// Check a sound effect file, and get the sound out of it
+    pub const fn parse(bytes: &'a [u8]) -> Result<Sfx<'a>, Error> {
+        if bytes.len() < HEADER_LEN {
+            return Err(Error::NotSfx);
+        }
+        if bytes[0] != MAGIC[0]
+            || bytes[1] != MAGIC[1]
+            || bytes[2] != MAGIC[2]
+            || bytes[3] != MAGIC[3]
+        {
+            return Err(Error::NotSfx);
+        }
+        if bytes[4] != VERSION {
+            return Err(Error::Version(bytes[4]));
+        }
+        let value = u16::from_le_bytes([bytes[6], bytes[7]]);
+        let (_, body) = bytes.split_at(HEADER_LEN);
+        match bytes[5] {
+            KIND_PCM if value != 0 => Ok(Sfx::Pcm {
+                sample_rate: value as u32,
+                samples: body,
+            }),
+            KIND_RECIPE if body.len() == value as usize * STEP_LEN => {
+                Ok(Sfx::Recipe { steps: body })
+            }
+            _ => Err(Error::Corrupt),
+        }
+    }
+
+    /// Get the sound out of a sound effect file that's built into the
+    /// application. If the file is no good, it won't build.
+    pub const fn from_bytes(bytes: &'a [u8]) -> Sfx<'a> {
+        match Sfx::parse(bytes) {
+            Ok(sfx) => sfx,
+            Err(_) => panic!("That isn't a valid sound effect"),
+        }
+    }
+
+    /// How many steps there are in a recipe. A recording has none.
+    pub fn step_count(&self) -> usize {
+        match self {
+            Sfx::Pcm { .. } => 0,
+            Sfx::Recipe { steps } => steps.len() / STEP_LEN,
+        }
+    }
+
+    /// Get one step of a recipe
+    pub fn step(&self, index: usize) -> Option<Step> {
+        match self {
+            Sfx::Pcm { .. } => None,
+            Sfx::Recipe { steps } => {
+                let bytes = steps.get(index * STEP_LEN..(index + 1) * STEP_LEN)?;
+                Some(Step::from_bytes(bytes.try_into().ok()?))
+            }
+        }
+    }
+
+    /// How long it plays for, in milliseconds
+    pub fn duration_ms(&self) -> u32 {
+        match self {
+            Sfx::Pcm {
+                sample_rate,
+                samples,
+            } => (samples.len() as u64 * 1000 / u64::from(*sample_rate)) as u32,
+            Sfx::Recipe { .. } => (0..self.step_count())
+                .filter_map(|index| self.step(index))
+                .map(|step| u32::from(step.ms))
+                .sum(),
+        }
+    }
+}
+
+/// One step of a recipe
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Step {
+    /// What shape of wave to play, or `None` for a rest
+    pub waveform: Option<Waveform>,
+    /// How loud it starts, out of 255
+    pub volume: u8,
+    /// The pitch it starts at, in Hz
+    pub from_hz: u16,
+    /// The pitch it slides to, in Hz
+    pub to_hz: u16,
+    /// How long it lasts, in milliseconds
+    pub ms: u16,
+}
+
+impl Step {
+    /// Read a step from a recipe. A waveform we don't know is a rest.
+    pub fn from_bytes(bytes: [u8; STEP_LEN]) -> Step {
+        Step {
+            waveform: match bytes[0] {
+                0 => Some(Waveform::Square),
+                1 => Some(Waveform::Triangle),
+                2 => Some(Waveform::Sawtooth),
+                3 => Some(Waveform::Noise),
+                _ => None,
+            },
+            volume: bytes[1],
+            from_hz: u16::from_le_bytes([bytes[2], bytes[3]]),
+            to_hz: u16::from_le_bytes([bytes[4], bytes[5]]),
+            ms: u16::from_le_bytes([bytes[6], bytes[7]]),
+        }
+    }
+
+    /// Write a step for a recipe
+    pub fn to_bytes(&self) -> [u8; STEP_LEN] {
+        let waveform = match self.waveform {
+            Some(Waveform::Square) => 0,
+            Some(Waveform::Triangle) => 1,
+            Some(Waveform::Sawtooth) => 2,
+            Some(Waveform::Noise) => 3,
+            None => 4,
+        };
+        let [from_lo, from_hi] = self.from_hz.to_le_bytes();
+        let [to_lo, to_hi] = self.to_hz.to_le_bytes();
+        let [ms_lo, ms_hi] = self.ms.to_le_bytes();
+        [
+            waveform,
+            self.volume,
+            from_lo,
+            from_hi,
+            to_lo,
+            to_hi,
+            ms_lo,
+            ms_hi,
+        ]
+    }
+}
+
+/// The header for a recording at this sample rate
+pub const fn pcm_header(sample_rate: u16) -> [u8; HEADER_LEN] {
+    header(KIND_PCM, sample_rate)
+}
+
+/// The header for a recipe with this many steps
+pub const fn recipe_header(steps: u16) -> [u8; HEADER_LEN] {
+    header(KIND_RECIPE, steps)
+}
+
+/// Make a header
+const fn header(kind: u8, value: u16) -> [u8; HEADER_LEN] {
+    let [lo, hi] = value.to_le_bytes();
+    [
+        MAGIC[0], MAGIC[1], MAGIC[2], MAGIC[3], VERSION, kind, lo, hi,
+    ]
+}
+
+/// Load a sound effect file into `buffer`, and check it
+pub fn load<'a>(filename: &str, buffer: &'a mut [u8]) -> Result<Sfx<'a>, Error> {
+    let path = neotron_sdk::path::Path::new(filename)?;
+    let file = neotron_sdk::File::open(path, neotron_sdk::Flags::empty())?;
+    let mut len = 0;
+    while len < buffer.len() {
+        match file.read(&mut buffer[len..])? {
+            0 => break,
+            n => len += n,
+        }
+    }
+    if len == buffer.len() {
+        // Full up - check there's nothing more to come
+        let mut spare = [0u8; 1];
+        if file.read(&mut spare)? != 0 {
+            return Err(Error::TooLarge);
+        }
+    }
+    Sfx::parse(&buffer[0..len])
+}
@@ -0,0 +1,240 @@
+//! Playing sound effects.
+
+use neotron_audio::{convert, Mixer, Oscillator, Waveform};
+
+use crate::Sfx;
+
+/// How many sound effects can play at once
+pub const VOICES: usize = 4;
+
+/// How many frames we work out at a time, when sending them to the device
+const CHUNK_FRAMES: usize = 64;
+
+/// How often a recipe's pitch and volume move along its slide, in samples
+const SLIDE_SAMPLES: u32 = 32;
+
+/// One sound effect, part way through playing
+#[derive(Debug, Copy, Clone)]
+struct Voice<'a> {
+    sfx: Sfx<'a>,
+    /// When it started, so the oldest can make way for a new one
+    started: u32,
+    /// Where we are in a recording, in 16.16 fixed point
+    position: u32,
+    /// How far to move through a recording each sample, in 16.16 fixed
+    /// point
+    rate: u32,
+    /// Which step of a recipe we're on
+    index: usize,
+    /// How many samples of the step we've played
+    done: u32,
+    /// How many samples long the step is
+    length: u32,
+    /// How loud the step is right now, out of 256
+    level: u16,
+    oscillator: Oscillator,
+}
+
+impl<'a> Voice<'a> {
+    /// Get ready to play a sound effect
+    fn new(sfx: Sfx<'a>, sample_rate: u32, started: u32) -> Voice<'a> {
+        let rate = match sfx {
+            Sfx::Pcm {
+                sample_rate: recorded,
+                ..
+            } => ((u64::from(recorded) << 16) / u64::from(sample_rate.max(1))) as u32,
+            Sfx::Recipe { .. } => 0,
+        };
+        let mut voice = Voice {
+            sfx,
+            started,
+            position: 0,
+            rate,
+            index: 0,
+            done: 0,
+            length: 0,
+            level: 0,
+            oscillator: Oscillator::new(Waveform::Square),
+        };
+        voice.start_step(0, sample_rate);
+        voice
+    }
+
+    /// Move on to a step of the recipe
+    fn start_step(&mut self, index: usize, sample_rate: u32) {
+        self.index = index;
+        self.done = 0;
+        self.length = 0;
+        if let Some(step) = self.sfx.step(index) {
+            self.length = u32::from(step.ms) * sample_rate / 1000;
+            if let Some(waveform) = step.waveform {
+                self.oscillator.waveform = waveform;
+            }
+            self.oscillator.reset();
+            self.slide(sample_rate);
+        }
+    }
+
+    /// Set the pitch and volume for where we are in the step
+    fn slide(&mut self, sample_rate: u32) {
+        let Some(step) = self.sfx.step(self.index) else {
+            return;
+        };
+        let next_volume = self.sfx.step(self.index + 1).map_or(0, |next| next.volume);
+        let along = |from: u32, to: u32| -> u32 {
+            let from = i64::from(from);
+            let to = i64::from(to);
+            (from + (to - from) * i64::from(self.done) / i64::from(self.length.max(1))) as u32
+        };
+        if step.waveform.is_some() {
+            let hz = along(u32::from(step.from_hz), u32::from(step.to_hz));
+            self.oscillator.set_frequency(hz, sample_rate);
+            self.level = along(u32::from(step.volume), u32::from(next_volume)) as u16;
+        } else {
+            self.level = 0;
+        }
+    }
+
+    /// Work out the next sample, or `None` if we've finished
+    fn next_sample(&mut self, sample_rate: u32) -> Option<i16> {
+        match self.sfx {
+            Sfx::Pcm { samples, .. } => {
+                let sample = *samples.get((self.position >> 16) as usize)?;
+                self.position = self.position.wrapping_add(self.rate);
+                Some(convert::from_i8(sample as i8))
+            }
+            Sfx::Recipe { .. } => {
+                while self.done >= self.length {
+                    if self.index >= self.sfx.step_count() {
+                        return None;
+                    }
+                    self.start_step(self.index + 1, sample_rate);
+                }
+                if self.done.is_multiple_of(SLIDE_SAMPLES) {
+                    self.slide(sample_rate);
+                }
+                self.done += 1;
+                let sample = self.oscillator.next_sample();
+                Some((i32::from(sample) * i32::from(self.level) / 256) as i16)
+            }
+        }
+    }
+}
+
+/// Plays sound effects, several at once
+pub struct Player<'a> {
+    sample_rate: u32,
+    voices: [Option<Voice<'a>>; VOICES],
+    /// Counts the effects we've started
+    started: u32,
+    device: Option<neotron_audio::Device>,
+    /// How loud the effects are, out of 256
+    pub volume: u16,
+    /// Whether the user wants to hear anything
+    pub enabled: bool,
+}
+
+impl<'a> Player<'a> {
+    /// Make a player which works at this sample rate. It's silent until you
+    /// call [`Player::open`].
+    pub const fn new(sample_rate: u32) -> Player<'a> {
+        Player {
+            sample_rate,
+            voices: [None; VOICES],
+            started: 0,
+            device: None,
+            volume: 256,
+            enabled: true,
+        }
+    }
+
+    /// Try and open the audio device.
+    ///
+    /// If it isn't there, we just stay silent - the game doesn't need to
+    /// check.
+    pub fn open(&mut self) {
+        self.device = neotron_audio::open(self.sample_rate).ok();
+    }
+
+    /// Close the audio device
+    pub fn close(&mut self) {
+        self.device = None;
+    }
+
+    /// Did we find an audio device?
+    pub fn is_open(&self) -> bool {
+        self.device.is_some()
+    }
+
+    /// The sample rate we work at
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Start playing a sound effect.
+    ///
+    /// If all the voices are busy, the one that's been playing longest
+    /// stops to make way.
+    pub fn play(&mut self, sfx: Sfx<'a>) {
+        if !self.enabled {
+            return;
+        }
+        let slot = match self.voices.iter().position(|voice| voice.is_none()) {
+            Some(slot) => slot,
+            None => self
+                .voices
+                .iter()
+                .enumerate()
+                .max_by_key(|(_, voice)| voice.map_or(0, |v| self.started.wrapping_sub(v.started)))
+                .map_or(0, |(slot, _)| slot),
+        };
+        self.voices[slot] = Some(Voice::new(sfx, self.sample_rate, self.started));
+        self.started = self.started.wrapping_add(1);
+    }
+
+    /// Stop everything
+    pub fn stop(&mut self) {
+        self.voices = [None; VOICES];
+    }
+
+    /// Is anything still playing?
+    pub fn is_playing(&self) -> bool {
+        self.voices.iter().any(|voice| voice.is_some())
+    }
+
+    /// Work out the next sample, with all the effects mixed together
+    pub fn next_sample(&mut self) -> i16 {
+        let mut mixer = Mixer::new();
+        for slot in self.voices.iter_mut() {
+            if let Some(voice) = slot {
+                match voice.next_sample(self.sample_rate) {
+                    Some(sample) => mixer.add(sample, self.volume),
+                    None => *slot = None,
+                }
+            }
+        }
+        mixer.finish_mono()
+    }
+
+    /// Send this many milliseconds of sound to the audio device. Call this
+    /// once a frame with the length of the frame.
+    ///
+    /// When nothing is playing we send nothing, so this doesn't hold the
+    /// game up.
+    pub fn pump(&mut self, ms: u32) {
+        if self.device.is_none() || !self.is_playing() {
+            return;
+        }
+        let mut frames = (u64::from(ms) * u64::from(self.sample_rate) / 1000) as usize;
+        let mut buffer = [0u8; CHUNK_FRAMES * convert::FRAME_LEN];
+        while frames > 0 && self.is_playing() {
+            let chunk = frames.min(CHUNK_FRAMES);
+            let bytes = &mut buffer[0..chunk * convert::FRAME_LEN];
+            convert::fill_mono(bytes, || self.next_sample());
+            if let Some(device) = self.device.as_ref() {
+                let _ = device.write(bytes);
+            }
+            frames -= chunk;
+        }
+    }
+}
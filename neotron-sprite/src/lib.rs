@@ -0,0 +1,106 @@
+//! Sprites and tiles, drawn with character cells.
+//!
+//! Everything here draws into a [`neotron_screen::Screen`], which takes care
+//! of only sending what changed. There are three kinds of picture:
+//!
+//! * A [`Sprite`] is a little block of characters, like `/-\` for a ship.
+//!   Spaces are see-through.
+//! * A [`Bitmap`] is a block of pixels, drawn on a [`Pixels`] canvas. Each
+//!   character cell holds two pixels, one above the other, using the `▀`
+//!   and `▄` half blocks, so the pixels come out roughly square.
+//! * A [`TileMap`] is a grid of small numbers, each drawn as one character
+//!   from a [`Tileset`] - good for walls, bricks and mazes.
+//!
+//! Positions are signed, so things can slide in from off the edge of the
+//! screen. Anything that doesn't fit is clipped. [`Rect`] and the
+//! `collides` functions tell you when two things have hit each other.
+
+#![no_std]
+#![deny(missing_docs)]
+#![deny(unsafe_code)]
+
+mod pixels;
+mod sprite;
+mod tiles;
+
+pub use pixels::{Bitmap, Pixels};
+pub use sprite::Sprite;
+pub use tiles::{TileMap, Tileset};
+
+/// A rectangle, for checking whether things overlap. It can be in
+/// characters or in pixels, as long as you don't mix them.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Rect {
+    /// The left-most column
+    pub x: i16,
+    /// The top row
+    pub y: i16,
+    /// How many columns across
+    pub width: i16,
+    /// How many rows down
+    pub height: i16,
+}
+
+impl Rect {
+    /// Make a rectangle
+    pub const fn new(x: i16, y: i16, width: i16, height: i16) -> Rect {
+        Rect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// The column to the right of this rectangle
+    pub const fn right(&self) -> i16 {
+        self.x + self.width
+    }
+
+    /// The row below the bottom of this rectangle
+    pub const fn bottom(&self) -> i16 {
+        self.y + self.height
+    }
+
+    /// Is this point inside the rectangle?
+    pub const fn contains(&self, x: i16, y: i16) -> bool {
+        x >= self.x && x < self.right() && y >= self.y && y < self.bottom()
+    }
+
+    /// Do these two rectangles overlap at all?
+    pub const fn overlaps(&self, other: &Rect) -> bool {
+        self.x < other.right()
+            && other.x < self.right()
+            && self.y < other.bottom()
+            && other.y < self.bottom()
+    }
+
+    /// The part both rectangles cover, if there is one
+    pub fn intersection(&self, other: &Rect) -> Option<Rect> {
+        if !self.overlaps(other) {
+            return None;
+        }
+        let x = self.x.max(other.x);
+        let y = self.y.max(other.y);
+        Some(Rect {
+            x,
+            y,
+            width: self.right().min(other.right()) - x,
+            height: self.bottom().min(other.bottom()) - y,
+        })
+    }
+}
+
+/// Put a cell on the screen at a signed position, ignoring anything that
+/// is off the edge
+fn put<const W: usize, const H: usize>(
+    screen: &mut neotron_screen::Screen<W, H>,
+    x: i16,
+    y: i16,
+    ch: char,
+    attr: neotron_screen::Attr,
+) {
+    if x >= 0 && y >= 0 {
+        screen.set(y as usize, x as usize, ch, attr);
+    }
+}
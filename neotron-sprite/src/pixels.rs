@@ -0,0 +1,201 @@
+//! Pixels, two to a character cell.
+
+use neotron_screen::{Attr, Colour, Screen};
+
+use crate::Rect;
+
+/// A little picture made of pixels, one string per row.
+///
+/// Spaces and dots are see-through, and anything else is a pixel, so a
+/// ball might be `[".##.", "####", ".##."]`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Bitmap {
+    rows: &'static [&'static str],
+}
+
+impl Bitmap {
+    /// Make a bitmap from some rows of pixels
+    pub const fn new(rows: &'static [&'static str]) -> Bitmap {
+        Bitmap { rows }
+    }
+
+    /// How many pixels wide the bitmap is
+    pub fn width(&self) -> i16 {
+        self.rows
+            .iter()
+            .map(|row| row.chars().count())
+            .max()
+            .unwrap_or(0) as i16
+    }
+
+    /// How many pixels tall the bitmap is
+    pub fn height(&self) -> i16 {
+        self.rows.len() as i16
+    }
+
+    /// The rectangle the bitmap covers, with its top-left corner here
+    pub fn rect(&self, x: i16, y: i16) -> Rect {
+        Rect::new(x, y, self.width(), self.height())
+    }
+
+    /// Is this pixel set, counting from the top-left corner?
+    pub fn is_set(&self, x: i16, y: i16) -> bool {
+        if x < 0 || y < 0 {
+            return false;
+        }
+        self.rows
+            .get(y as usize)
+            .and_then(|row| row.chars().nth(x as usize))
+            .is_some_and(|ch| ch != ' ' && ch != '.')
+    }
+
+    /// Do two bitmaps touch? Only the pixels which are set count.
+    pub fn collides(&self, x: i16, y: i16, other: &Bitmap, other_x: i16, other_y: i16) -> bool {
+        let Some(both) = self.rect(x, y).intersection(&other.rect(other_x, other_y)) else {
+            return false;
+        };
+        for row in both.y..both.bottom() {
+            for col in both.x..both.right() {
+                if self.is_set(col - x, row - y) && other.is_set(col - other_x, row - other_y) {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+}
+
+/// A canvas `W` cells wide and `H` cells tall, which is `W` by `2 * H`
+/// pixels.
+///
+/// Draw into it with pixels, then [`Pixels::render`] it onto a screen. Each
+/// cell becomes a half block in the colour of one pixel, with the other
+/// pixel's colour behind it.
+pub struct Pixels<const W: usize, const H: usize> {
+    /// The top and bottom pixel in each cell. `None` shows the background.
+    cells: [[(Option<Colour>, Option<Colour>); W]; H],
+}
+
+impl<const W: usize, const H: usize> Pixels<W, H> {
+    /// Make an empty canvas
+    pub const fn new() -> Pixels<W, H> {
+        Pixels {
+            cells: [[(None, None); W]; H],
+        }
+    }
+
+    /// How many pixels across
+    pub const fn width(&self) -> i16 {
+        W as i16
+    }
+
+    /// How many pixels down
+    pub const fn height(&self) -> i16 {
+        (H * 2) as i16
+    }
+
+    /// Rub everything out
+    pub fn clear(&mut self) {
+        self.cells = [[(None, None); W]; H];
+    }
+
+    /// Get a pixel. Anything off the canvas is empty.
+    pub fn get(&self, x: i16, y: i16) -> Option<Colour> {
+        let cell = self.cell(x, y)?;
+        if y % 2 == 0 {
+            cell.0
+        } else {
+            cell.1
+        }
+    }
+
+    /// Set a pixel, or clear it with `None`. Anything off the canvas is
+    /// ignored.
+    pub fn set(&mut self, x: i16, y: i16, colour: Option<Colour>) {
+        if let Some(cell) = self.cell_mut(x, y) {
+            if y % 2 == 0 {
+                cell.0 = colour;
+            } else {
+                cell.1 = colour;
+            }
+        }
+    }
+
+    /// Fill a rectangle of pixels with one colour
+    pub fn fill(&mut self, rect: Rect, colour: Option<Colour>) {
+        for y in rect.y..rect.bottom() {
+            for x in rect.x..rect.right() {
+                self.set(x, y, colour);
+            }
+        }
+    }
+
+    /// Draw a bitmap with its top-left corner here. See-through pixels
+    /// leave what was there before.
+    pub fn draw(&mut self, bitmap: &Bitmap, x: i16, y: i16, colour: Colour) {
+        for dy in 0..bitmap.height() {
+            for dx in 0..bitmap.width() {
+                if bitmap.is_set(dx, dy) {
+                    self.set(x + dx, y + dy, Some(colour));
+                }
+            }
+        }
+    }
+
+    /// Does a bitmap, drawn here, land on any pixel already on the canvas?
+    pub fn hits(&self, bitmap: &Bitmap, x: i16, y: i16) -> bool {
+        for dy in 0..bitmap.height() {
+            for dx in 0..bitmap.width() {
+                if bitmap.is_set(dx, dy) && self.get(x + dx, y + dy).is_some() {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    /// Put the canvas on the screen, with its top-left corner in this cell.
+    /// Empty pixels show the background colour.
+    pub fn render<const SW: usize, const SH: usize>(
+        &self,
+        screen: &mut Screen<SW, SH>,
+        col: i16,
+        row: i16,
+        background: Colour,
+    ) {
+        for (dy, cells) in self.cells.iter().enumerate() {
+            for (dx, cell) in cells.iter().enumerate() {
+                let (ch, attr) = match *cell {
+                    (None, None) => (' ', Attr::new(Colour::White, background)),
+                    (Some(top), None) => ('▀', Attr::new(top, background)),
+                    (None, Some(bottom)) => ('▄', Attr::new(bottom, background)),
+                    (Some(top), Some(bottom)) if top == bottom => ('█', Attr::new(top, background)),
+                    (Some(top), Some(bottom)) => ('▀', Attr::new(top, bottom)),
+                };
+                crate::put(screen, col + dx as i16, row + dy as i16, ch, attr);
+            }
+        }
+    }
+
+    /// The cell holding a pixel
+    fn cell(&self, x: i16, y: i16) -> Option<&(Option<Colour>, Option<Colour>)> {
+        if x < 0 || y < 0 {
+            return None;
+        }
+        self.cells.get(y as usize / 2)?.get(x as usize)
+    }
+
+    /// The cell holding a pixel, so we can change it
+    fn cell_mut(&mut self, x: i16, y: i16) -> Option<&mut (Option<Colour>, Option<Colour>)> {
+        if x < 0 || y < 0 {
+            return None;
+        }
+        self.cells.get_mut(y as usize / 2)?.get_mut(x as usize)
+    }
+}
+
+impl<const W: usize, const H: usize> Default for Pixels<W, H> {
+    fn default() -> Self {
+        Pixels::new()
+    }
+}
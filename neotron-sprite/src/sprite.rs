@@ -0,0 +1,83 @@
+//! Sprites made of characters.
+
+use neotron_screen::{Attr, Screen};
+
+use crate::Rect;
+
+/// A little picture made of characters, one string per row.
+///
+/// Spaces are see-through, so whatever was on the screen shows through
+/// them. Rows can be different lengths; the sprite is as wide as the
+/// longest one.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Sprite {
+    rows: &'static [&'static str],
+    /// The colours to draw in
+    pub attr: Attr,
+}
+
+impl Sprite {
+    /// Make a sprite from some rows of characters
+    pub const fn new(rows: &'static [&'static str], attr: Attr) -> Sprite {
+        Sprite { rows, attr }
+    }
+
+    /// How many columns wide the sprite is
+    pub fn width(&self) -> i16 {
+        self.rows
+            .iter()
+            .map(|row| row.chars().count())
+            .max()
+            .unwrap_or(0) as i16
+    }
+
+    /// How many rows tall the sprite is
+    pub fn height(&self) -> i16 {
+        self.rows.len() as i16
+    }
+
+    /// The rectangle the sprite covers, with its top-left corner here
+    pub fn rect(&self, x: i16, y: i16) -> Rect {
+        Rect::new(x, y, self.width(), self.height())
+    }
+
+    /// Is there something solid at this spot in the sprite, counting from
+    /// its top-left corner?
+    pub fn is_solid(&self, x: i16, y: i16) -> bool {
+        if x < 0 || y < 0 {
+            return false;
+        }
+        self.rows
+            .get(y as usize)
+            .and_then(|row| row.chars().nth(x as usize))
+            .is_some_and(|ch| ch != ' ')
+    }
+
+    /// Draw the sprite with its top-left corner here
+    pub fn draw<const W: usize, const H: usize>(&self, screen: &mut Screen<W, H>, x: i16, y: i16) {
+        for (dy, row) in self.rows.iter().enumerate() {
+            for (dx, ch) in row.chars().enumerate() {
+                if ch != ' ' {
+                    crate::put(screen, x + dx as i16, y + dy as i16, ch, self.attr);
+                }
+            }
+        }
+    }
+
+    /// Do two sprites touch? Only the solid characters count, so two
+    /// sprites whose corners overlap only where they are see-through
+    /// haven't hit each other.
+    pub fn collides(&self, x: i16, y: i16, other: &Sprite, other_x: i16, other_y: i16) -> bool {
+        let Some(both) = self.rect(x, y).intersection(&other.rect(other_x, other_y)) else {
+            return false;
+        };
+        for row in both.y..both.bottom() {
+            for col in both.x..both.right() {
+                if self.is_solid(col - x, row - y) && other.is_solid(col - other_x, row - other_y) {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+}
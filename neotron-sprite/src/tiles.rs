@@ -0,0 +1,133 @@
+//! Grids of tiles, for walls, bricks and mazes.
+
+use neotron_screen::{Cell, Screen};
+
+use crate::Rect;
+
+/// What each tile number looks like. Tile 0 is the first cell, and so on.
+/// Numbers past the end are drawn as blanks.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Tileset {
+    cells: &'static [Cell],
+}
+
+impl Tileset {
+    /// Make a tileset
+    pub const fn new(cells: &'static [Cell]) -> Tileset {
+        Tileset { cells }
+    }
+
+    /// What a tile looks like
+    pub fn cell(&self, tile: u8) -> Cell {
+        self.cells
+            .get(usize::from(tile))
+            .copied()
+            .unwrap_or(Cell::BLANK)
+    }
+}
+
+/// A grid of tiles, `W` across and `H` down. Tile 0 is empty - it isn't
+/// drawn, and doesn't count when checking for collisions.
+pub struct TileMap<const W: usize, const H: usize> {
+    tiles: [[u8; W]; H],
+}
+
+impl<const W: usize, const H: usize> TileMap<W, H> {
+    /// Make an empty map
+    pub const fn new() -> TileMap<W, H> {
+        TileMap { tiles: [[0; W]; H] }
+    }
+
+    /// Fill the map from rows of text, turning each character into a tile
+    /// number. Anything past the end of the text is empty.
+    pub fn load<F>(&mut self, rows: &[&str], mut tile_for: F)
+    where
+        F: FnMut(char) -> u8,
+    {
+        self.clear();
+        for (tiles, text) in self.tiles.iter_mut().zip(rows) {
+            for (tile, ch) in tiles.iter_mut().zip(text.chars()) {
+                *tile = tile_for(ch);
+            }
+        }
+    }
+
+    /// Empty the whole map
+    pub fn clear(&mut self) {
+        self.tiles = [[0; W]; H];
+    }
+
+    /// Get a tile. Anything off the map is empty.
+    pub fn get(&self, x: i16, y: i16) -> u8 {
+        if x < 0 || y < 0 {
+            return 0;
+        }
+        self.tiles
+            .get(y as usize)
+            .and_then(|row| row.get(x as usize))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Change a tile. Anything off the map is ignored.
+    pub fn set(&mut self, x: i16, y: i16, tile: u8) {
+        if x < 0 || y < 0 {
+            return;
+        }
+        if let Some(slot) = self
+            .tiles
+            .get_mut(y as usize)
+            .and_then(|row| row.get_mut(x as usize))
+        {
+            *slot = tile;
+        }
+    }
+
+    /// Find the first non-empty tile under a rectangle, as (x, y, tile)
+    pub fn hit(&self, rect: Rect) -> Option<(i16, i16, u8)> {
+        for y in rect.y..rect.bottom() {
+            for x in rect.x..rect.right() {
+                let tile = self.get(x, y);
+                if tile != 0 {
+                    return Some((x, y, tile));
+                }
+            }
+        }
+        None
+    }
+
+    /// How many tiles of this kind are left, like the bricks in a wall
+    pub fn count(&self, tile: u8) -> usize {
+        self.tiles.iter().flatten().filter(|t| **t == tile).count()
+    }
+
+    /// Draw the map with its top-left corner in this cell
+    pub fn draw<const SW: usize, const SH: usize>(
+        &self,
+        screen: &mut Screen<SW, SH>,
+        tileset: &Tileset,
+        col: i16,
+        row: i16,
+    ) {
+        for (dy, tiles) in self.tiles.iter().enumerate() {
+            for (dx, tile) in tiles.iter().enumerate() {
+                if *tile != 0 {
+                    let cell = tileset.cell(*tile);
+                    crate::put(
+                        screen,
+                        col + dx as i16,
+                        row + dy as i16,
+                        cell.ch(),
+                        cell.attr(),
+                    );
+                }
+            }
+        }
+    }
+}
+
+impl<const W: usize, const H: usize> Default for TileMap<W, H> {
+    fn default() -> Self {
+        TileMap::new()
+    }
+}
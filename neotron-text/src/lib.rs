@@ -0,0 +1,124 @@
+//! Text layout helpers for fixed-width consoles.
+//!
+//! Everything here counts characters, not bytes, so text with accents or
+//! box-drawing characters in it lines up properly. We assume every
+//! character takes one column, as it does on a Neotron's text screen.
+//!
+//! The helpers which lay out a piece of text, like [`fit`] and [`centred`],
+//! return something you can pass to `write!`, so nothing needs a buffer.
+
+#![no_std]
+#![deny(missing_docs)]
+#![deny(unsafe_code)]
+
+use core::fmt::Write;
+
+pub mod table;
+pub mod wrap;
+
+pub use table::{Column, Table};
+pub use wrap::{wrap, Wrap};
+
+/// What we put on the end of text which has been cut short
+pub const ELLIPSIS: &str = "...";
+
+/// Which side of a space some text sits on
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Align {
+    /// Against the left edge
+    Left,
+    /// In the middle, with any odd space on the right
+    Centre,
+    /// Against the right edge
+    Right,
+}
+
+/// How many columns some text takes up
+pub fn width(text: &str) -> usize {
+    text.chars().count()
+}
+
+/// The start of some text, cut on a character boundary so it's no more
+/// than `width` columns wide
+pub fn truncate(text: &str, width: usize) -> &str {
+    match text.char_indices().nth(width) {
+        Some((idx, _)) => &text[0..idx],
+        None => text,
+    }
+}
+
+/// How far along `width` columns some text should start, to sit in the
+/// middle. Text which doesn't fit starts at zero.
+pub fn centre_offset(text: &str, width: usize) -> usize {
+    width.saturating_sub(self::width(text)) / 2
+}
+
+/// Some text, laid out in a space of a fixed width.
+///
+/// Made by [`fit`], [`centred`] or [`ellipsis`].
+#[derive(Debug, Copy, Clone)]
+pub struct Fit<'a> {
+    text: &'a str,
+    width: usize,
+    align: Align,
+    pad: bool,
+}
+
+impl core::fmt::Display for Fit<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let text_width = width(self.text);
+        if text_width > self.width {
+            // Cut it short, and say so, with as much of the ellipsis as fits
+            let dots = width(ELLIPSIS).min(self.width);
+            f.write_str(truncate(self.text, self.width - dots))?;
+            return f.write_str(truncate(ELLIPSIS, dots));
+        }
+        let space = self.width - text_width;
+        let before = match self.align {
+            Align::Left => 0,
+            Align::Centre => space / 2,
+            Align::Right => space,
+        };
+        write_repeated(f, ' ', before)?;
+        f.write_str(self.text)?;
+        if self.pad {
+            write_repeated(f, ' ', space - before)?;
+        }
+        Ok(())
+    }
+}
+
+/// Lay out text in exactly `width` columns, padded with spaces, and cut
+/// short with an [`ELLIPSIS`] if it doesn't fit
+pub fn fit(text: &str, width: usize, align: Align) -> Fit<'_> {
+    Fit {
+        text,
+        width,
+        align,
+        pad: true,
+    }
+}
+
+/// Put text in the middle of `width` columns, padded on both sides
+pub fn centred(text: &str, width: usize) -> Fit<'_> {
+    fit(text, width, Align::Centre)
+}
+
+/// Cut text short with an [`ELLIPSIS`] if it's wider than `width` columns,
+/// without padding it
+pub fn ellipsis(text: &str, width: usize) -> Fit<'_> {
+    Fit {
+        text,
+        width,
+        align: Align::Left,
+        pad: false,
+    }
+}
+
+/// Write the same character several times
+pub fn write_repeated<W: Write>(out: &mut W, ch: char, count: usize) -> core::fmt::Result {
+    for _ in 0..count {
+        out.write_char(ch)?;
+    }
+    Ok(())
+}
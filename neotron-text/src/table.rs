@@ -0,0 +1,93 @@
+//! Tables of text, in columns of a fixed width.
+
+use core::fmt::Write;
+
+use crate::{fit, write_repeated, Align};
+
+/// One column of a [`Table`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Column {
+    /// How many columns of text it takes up
+    pub width: usize,
+    /// Where the text sits in the column
+    pub align: Align,
+}
+
+impl Column {
+    /// A column with the text on the left, for words
+    pub const fn left(width: usize) -> Column {
+        Column {
+            width,
+            align: Align::Left,
+        }
+    }
+
+    /// A column with the text on the right, for numbers
+    pub const fn right(width: usize) -> Column {
+        Column {
+            width,
+            align: Align::Right,
+        }
+    }
+
+    /// A column with the text in the middle
+    pub const fn centre(width: usize) -> Column {
+        Column {
+            width,
+            align: Align::Centre,
+        }
+    }
+}
+
+/// Lays out rows of text in columns.
+///
+/// Every cell is padded to the width of its column, and anything too wide
+/// is cut short with an ellipsis, so the columns always line up.
+#[derive(Debug, Copy, Clone)]
+pub struct Table<'a> {
+    columns: &'a [Column],
+    gap: usize,
+}
+
+impl<'a> Table<'a> {
+    /// Make a table with these columns, and one space between each
+    pub const fn new(columns: &'a [Column]) -> Table<'a> {
+        Table { columns, gap: 1 }
+    }
+
+    /// Put this many spaces between the columns
+    pub const fn with_gap(self, gap: usize) -> Table<'a> {
+        Table { gap, ..self }
+    }
+
+    /// How wide a whole row is
+    pub fn width(&self) -> usize {
+        let text: usize = self.columns.iter().map(|column| column.width).sum();
+        text + self.gap * self.columns.len().saturating_sub(1)
+    }
+
+    /// Write one row, without a line ending.
+    ///
+    /// Missing cells are left blank, and extra ones are ignored.
+    pub fn write_row<W: Write>(&self, out: &mut W, cells: &[&str]) -> core::fmt::Result {
+        for (idx, column) in self.columns.iter().enumerate() {
+            if idx > 0 {
+                write_repeated(out, ' ', self.gap)?;
+            }
+            let cell = cells.get(idx).copied().unwrap_or("");
+            write!(out, "{}", fit(cell, column.width, column.align))?;
+        }
+        Ok(())
+    }
+
+    /// Write a line under the headings, made of `ch`, without a line ending
+    pub fn write_rule<W: Write>(&self, out: &mut W, ch: char) -> core::fmt::Result {
+        for (idx, column) in self.columns.iter().enumerate() {
+            if idx > 0 {
+                write_repeated(out, ' ', self.gap)?;
+            }
+            write_repeated(out, ch, column.width)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,72 @@
+//! Word-wrapping.
+//!
+//! Lines are broken at spaces where we can, and in the middle of a word
+//! only when the word is too long to fit on a line by itself. Line breaks
+//! already in the text are kept.
+
+use crate::{truncate, width};
+
+/// Breaks text up into lines which fit in a given width.
+///
+/// Made by [`wrap`]. Each line is a slice of the original text, without the
+/// spaces we broke it at or any line endings.
+#[derive(Debug, Clone)]
+pub struct Wrap<'a> {
+    /// What we haven't wrapped yet, or `None` if we've finished
+    rest: Option<&'a str>,
+    width: usize,
+}
+
+/// Wrap some text so no line is wider than `width` columns.
+///
+/// Like [`str::lines`], a line ending at the very end doesn't make an extra
+/// empty line.
+pub fn wrap(text: &str, width: usize) -> Wrap<'_> {
+    Wrap {
+        rest: if text.is_empty() { None } else { Some(text) },
+        // A line has to hold something, or we'd never finish
+        width: width.max(1),
+    }
+}
+
+impl<'a> Iterator for Wrap<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str> {
+        let rest = self.rest?;
+        let (line, after) = match rest.find('\n') {
+            Some(idx) => (&rest[0..idx], Some(&rest[idx + 1..])),
+            None => (rest, None),
+        };
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        let after = after.filter(|text| !text.is_empty());
+
+        if width(line) <= self.width {
+            self.rest = after;
+            return Some(line);
+        }
+
+        let head = truncate(line, self.width);
+        // Break at the last space that fits, which might be just after the
+        // text that fits
+        let space = if line[head.len()..].starts_with(' ') {
+            Some(head.len())
+        } else {
+            head.rfind(' ')
+        };
+        let (shown, next) = match space {
+            Some(idx) if !head[0..idx].trim_end().is_empty() => {
+                let skipped = line[idx..].len() - line[idx..].trim_start_matches(' ').len();
+                (head[0..idx].trim_end(), idx + skipped)
+            }
+            // No space, or only spaces before it, so the word has to be cut
+            _ => (head, head.len()),
+        };
+        self.rest = if next >= line.len() {
+            after
+        } else {
+            Some(&rest[next..])
+        };
+        Some(shown)
+    }
+}
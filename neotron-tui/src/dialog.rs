@@ -0,0 +1,116 @@
+//! Boxes which pop up in the middle of the screen, with a message and some
+//! buttons.
+
+use core::fmt::Write;
+
+use crate::{move_to, write_centred, Border, Frame, Rect, Theme};
+
+/// A message in a box, with a row of buttons underneath
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Dialog<'a> {
+    title: &'a str,
+    lines: &'a [&'a str],
+    buttons: &'a [&'a str],
+    selected: usize,
+}
+
+impl<'a> Dialog<'a> {
+    /// Make a dialog. The first button is selected.
+    ///
+    /// You can have no buttons, if the dialog is just there to tell the user
+    /// something while they wait.
+    pub const fn new(title: &'a str, lines: &'a [&'a str], buttons: &'a [&'a str]) -> Dialog<'a> {
+        Dialog {
+            title,
+            lines,
+            buttons,
+            selected: 0,
+        }
+    }
+
+    /// Which button is selected
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    /// Select the button on the left
+    pub fn left(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    /// Select the button on the right
+    pub fn right(&mut self) {
+        if self.selected + 1 < self.buttons.len() {
+            self.selected += 1;
+        }
+    }
+
+    /// Which button starts with this letter, ignoring case
+    pub fn button_for(&self, letter: char) -> Option<usize> {
+        self.buttons.iter().position(|button| {
+            let first = button.chars().next();
+            first.is_some_and(|ch| ch.eq_ignore_ascii_case(&letter))
+        })
+    }
+
+    /// How many columns the buttons take up, with two spaces between each
+    fn buttons_width(&self) -> usize {
+        let labels: usize = self.buttons.iter().map(|b| b.chars().count() + 4).sum();
+        labels + self.buttons.len().saturating_sub(1) * 2
+    }
+
+    /// How big a box the dialog needs, as (width, height)
+    pub fn size(&self) -> (u8, u8) {
+        let widest = self
+            .lines
+            .iter()
+            .map(|line| line.chars().count())
+            .chain([self.title.chars().count() + 2, self.buttons_width()])
+            .max()
+            .unwrap_or(0);
+        let button_rows = if self.buttons.is_empty() { 0 } else { 2 };
+        // A border and a space either side, and a blank line above and below
+        // the message
+        let width = (widest + 4).min(usize::from(u8::MAX)) as u8;
+        let height = (self.lines.len() + 4 + button_rows).min(usize::from(u8::MAX)) as u8;
+        (width, height)
+    }
+
+    /// Draw the dialog in the middle of a screen of the given size. Returns
+    /// where it went, so you can redraw whatever was underneath afterwards.
+    pub fn draw(
+        &self,
+        out: &mut neotron_sdk::File,
+        screen_width: u8,
+        screen_height: u8,
+        theme: &Theme,
+    ) -> Rect {
+        let (width, height) = self.size();
+        let rect = Rect::centred(width, height, screen_width, screen_height);
+        Frame::new(self.title)
+            .with_border(Border::Double)
+            .draw(out, rect, theme);
+        let inner = rect.inner();
+        let text_width = usize::from(inner.width.saturating_sub(2));
+        for (row, line) in (inner.row + 1..inner.bottom()).zip(self.lines.iter()) {
+            move_to(out, row, inner.col + 1);
+            write_centred(out, line, text_width);
+        }
+        if !self.buttons.is_empty() {
+            let width = self.buttons_width().min(usize::from(inner.width));
+            let col = inner.col + ((usize::from(inner.width) - width) / 2) as u8;
+            move_to(out, inner.bottom() - 2, col);
+            for (idx, button) in self.buttons.iter().enumerate() {
+                if idx > 0 {
+                    let _ = out.write_str("  ");
+                }
+                if idx == self.selected {
+                    theme.set_selected(out);
+                }
+                let _ = write!(out, "[ {} ]", button);
+                theme.set_normal(out);
+            }
+        }
+        rect
+    }
+}
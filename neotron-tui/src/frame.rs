@@ -0,0 +1,95 @@
+//! Boxes drawn with line-drawing characters, with an optional title.
+
+use core::fmt::Write;
+
+use crate::{move_to, write_repeated, Rect, Theme};
+
+/// What sort of lines to draw a box with
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Border {
+    /// `┌─┐`
+    Single,
+    /// `╔═╗`
+    Double,
+}
+
+impl Border {
+    /// The corners (top left, top right, bottom left, bottom right), then the
+    /// horizontal and vertical lines
+    fn chars(self) -> [char; 6] {
+        match self {
+            Border::Single => ['┌', '┐', '└', '┘', '─', '│'],
+            Border::Double => ['╔', '╗', '╚', '╝', '═', '║'],
+        }
+    }
+}
+
+/// A box around part of the screen
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Frame<'a> {
+    /// What sort of lines to draw
+    pub border: Border,
+    /// Goes in the middle of the top line. Can be empty.
+    pub title: &'a str,
+}
+
+impl<'a> Frame<'a> {
+    /// Make a single-line frame with a title
+    pub const fn new(title: &'a str) -> Frame<'a> {
+        Frame {
+            border: Border::Single,
+            title,
+        }
+    }
+
+    /// Change what sort of lines we draw
+    pub const fn with_border(self, border: Border) -> Frame<'a> {
+        Frame { border, ..self }
+    }
+
+    /// Draw the frame around the edge of a rectangle, and blank out the
+    /// inside
+    pub fn draw(&self, out: &mut neotron_sdk::File, rect: Rect, theme: &Theme) {
+        if rect.width < 2 || rect.height < 2 {
+            return;
+        }
+        let [top_left, top_right, bottom_left, bottom_right, across, down] = self.border.chars();
+        let inside = usize::from(rect.width - 2);
+
+        theme.set_border(out);
+        move_to(out, rect.row, rect.col);
+        let _ = out.write_char(top_left);
+        let title_len = self.title.chars().count().min(inside.saturating_sub(2));
+        if title_len > 0 {
+            // Leave a space either side of the title
+            let before = (inside - title_len - 2) / 2;
+            write_repeated(out, across, before);
+            let _ = out.write_char(' ');
+            theme.set_title(out);
+            for ch in self.title.chars().take(title_len) {
+                let _ = out.write_char(ch);
+            }
+            theme.set_border(out);
+            let _ = out.write_char(' ');
+            write_repeated(out, across, inside - before - title_len - 2);
+        } else {
+            write_repeated(out, across, inside);
+        }
+        let _ = out.write_char(top_right);
+
+        for row in rect.row + 1..rect.bottom() - 1 {
+            move_to(out, row, rect.col);
+            let _ = out.write_char(down);
+            theme.set_normal(out);
+            write_repeated(out, ' ', inside);
+            theme.set_border(out);
+            let _ = out.write_char(down);
+        }
+
+        move_to(out, rect.bottom() - 1, rect.col);
+        let _ = out.write_char(bottom_left);
+        write_repeated(out, across, inside);
+        let _ = out.write_char(bottom_right);
+        theme.set_normal(out);
+    }
+}
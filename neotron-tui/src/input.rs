@@ -0,0 +1,158 @@
+//! A one-line field you can type text into.
+
+use core::fmt::Write;
+
+use crate::{move_to, Theme};
+
+/// Some text being typed in, of up to `N` characters.
+///
+/// Only printable ASCII is accepted, so every character is one byte and one
+/// column on screen.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct TextInput<const N: usize> {
+    buffer: [u8; N],
+    len: usize,
+    /// Where the next character goes
+    cursor: usize,
+    /// The first character on screen, if the text is wider than the field
+    scroll: usize,
+}
+
+impl<const N: usize> TextInput<N> {
+    /// Make an empty field
+    pub const fn new() -> TextInput<N> {
+        TextInput {
+            buffer: [0; N],
+            len: 0,
+            cursor: 0,
+            scroll: 0,
+        }
+    }
+
+    /// Get the text
+    pub fn as_str(&self) -> &str {
+        core::str::from_utf8(&self.buffer[0..self.len]).unwrap_or("")
+    }
+
+    /// Is the field empty?
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Empty the field
+    pub fn clear(&mut self) {
+        self.len = 0;
+        self.cursor = 0;
+        self.scroll = 0;
+    }
+
+    /// Replace the text, with the cursor on the end. Anything which isn't
+    /// printable ASCII, or doesn't fit, is dropped.
+    pub fn set(&mut self, text: &str) {
+        self.clear();
+        for ch in text.chars() {
+            self.insert(ch);
+        }
+    }
+
+    /// Type a character at the cursor. Returns `false` if it isn't printable
+    /// ASCII, or the field is full.
+    pub fn insert(&mut self, ch: char) -> bool {
+        if !(ch.is_ascii_graphic() || ch == ' ') || self.len == N {
+            return false;
+        }
+        self.buffer
+            .copy_within(self.cursor..self.len, self.cursor + 1);
+        self.buffer[self.cursor] = ch as u8;
+        self.len += 1;
+        self.cursor += 1;
+        true
+    }
+
+    /// Delete the character before the cursor
+    pub fn backspace(&mut self) {
+        if self.cursor > 0 {
+            self.cursor -= 1;
+            self.delete();
+        }
+    }
+
+    /// Delete the character under the cursor
+    pub fn delete(&mut self) {
+        if self.cursor < self.len {
+            self.buffer
+                .copy_within(self.cursor + 1..self.len, self.cursor);
+            self.len -= 1;
+        }
+    }
+
+    /// Move the cursor left
+    pub fn left(&mut self) {
+        self.cursor = self.cursor.saturating_sub(1);
+    }
+
+    /// Move the cursor right
+    pub fn right(&mut self) {
+        self.cursor = (self.cursor + 1).min(self.len);
+    }
+
+    /// Move the cursor to the start
+    pub fn home(&mut self) {
+        self.cursor = 0;
+    }
+
+    /// Move the cursor to the end
+    pub fn end(&mut self) {
+        self.cursor = self.len;
+    }
+
+    /// Draw the field, `width` columns wide. If it has the focus, the cursor
+    /// is shown in reverse video.
+    pub fn draw(
+        &mut self,
+        out: &mut neotron_sdk::File,
+        row: u8,
+        col: u8,
+        width: u8,
+        theme: &Theme,
+        focused: bool,
+    ) {
+        let width = usize::from(width);
+        if width == 0 {
+            return;
+        }
+        // Keep the cursor on screen, leaving room for it past the end
+        if self.cursor < self.scroll {
+            self.scroll = self.cursor;
+        } else if self.cursor >= self.scroll + width {
+            self.scroll = self.cursor + 1 - width;
+        }
+        move_to(out, row, col);
+        theme.set_normal(out);
+        let end = self.len.min(self.scroll + width);
+        for idx in self.scroll..self.scroll + width {
+            let ch = if idx < end {
+                char::from(self.buffer[idx])
+            } else {
+                ' '
+            };
+            if focused && idx == self.cursor {
+                theme.set_selected(out);
+                let _ = out.write_char(ch);
+                theme.set_normal(out);
+            } else {
+                let _ = out.write_char(ch);
+            }
+        }
+        if focused {
+            // Put the terminal's own cursor where the typing goes, too
+            move_to(out, row, col + (self.cursor - self.scroll) as u8);
+        }
+    }
+}
+
+impl<const N: usize> Default for TextInput<N> {
+    fn default() -> Self {
+        TextInput::new()
+    }
+}
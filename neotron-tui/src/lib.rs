@@ -0,0 +1,262 @@
+//! Text user interface widgets for Neotron applications.
+//!
+//! Menus, list boxes, dialogs, progress bars, text input fields and status
+//! bars, all drawn with [`neotron_sdk::console`].
+//!
+//! The widgets don't read the keyboard themselves. They keep track of what
+//! is selected, or what has been typed, and your application calls methods
+//! like [`ListBox::down`] or [`TextInput::insert`] when it sees a key it
+//! wants to pass on. That way each application stays in charge of its own
+//! keys, and widgets can be drawn wherever they are wanted.
+
+#![no_std]
+#![deny(missing_docs)]
+#![deny(unsafe_code)]
+
+use core::fmt::Write;
+
+use neotron_sdk::console;
+
+pub mod dialog;
+pub mod frame;
+pub mod input;
+pub mod list;
+pub mod progress;
+pub mod status;
+
+pub use dialog::Dialog;
+pub use frame::{Border, Frame};
+pub use input::TextInput;
+pub use list::{ListBox, Menu};
+pub use progress::ProgressBar;
+pub use status::StatusBar;
+
+/// The widest line any widget will draw
+pub const MAX_LINE: usize = 132;
+
+/// A rectangle on the screen, in characters
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Rect {
+    /// The top row
+    pub row: u8,
+    /// The left-most column
+    pub col: u8,
+    /// How many columns across
+    pub width: u8,
+    /// How many rows down
+    pub height: u8,
+}
+
+impl Rect {
+    /// Make a rectangle
+    pub const fn new(row: u8, col: u8, width: u8, height: u8) -> Rect {
+        Rect {
+            row,
+            col,
+            width,
+            height,
+        }
+    }
+
+    /// Make a rectangle of the given size, in the middle of a screen of the
+    /// given size
+    pub const fn centred(width: u8, height: u8, screen_width: u8, screen_height: u8) -> Rect {
+        let width = if width > screen_width {
+            screen_width
+        } else {
+            width
+        };
+        let height = if height > screen_height {
+            screen_height
+        } else {
+            height
+        };
+        Rect {
+            row: (screen_height - height) / 2,
+            col: (screen_width - width) / 2,
+            width,
+            height,
+        }
+    }
+
+    /// The space inside a one-character border around this rectangle
+    pub const fn inner(&self) -> Rect {
+        Rect {
+            row: self.row + 1,
+            col: self.col + 1,
+            width: self.width.saturating_sub(2),
+            height: self.height.saturating_sub(2),
+        }
+    }
+
+    /// The row below the bottom of this rectangle
+    pub const fn bottom(&self) -> u8 {
+        self.row + self.height
+    }
+
+    /// The column to the right of this rectangle
+    pub const fn right(&self) -> u8 {
+        self.col + self.width
+    }
+}
+
+/// What colours a widget is drawn in
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Theme {
+    /// Ordinary text
+    pub normal: console::SgrParam,
+    /// Borders
+    pub border: console::SgrParam,
+    /// Titles, and anything else we want to stand out
+    pub title: console::SgrParam,
+    /// Whatever is selected is drawn in reverse video, in this colour
+    pub selected: console::SgrParam,
+}
+
+impl Theme {
+    /// White text in a cyan frame, with yellow titles
+    pub const DEFAULT: Theme = Theme {
+        normal: console::SgrParam::FgWhite,
+        border: console::SgrParam::FgCyan,
+        title: console::SgrParam::FgYellow,
+        selected: console::SgrParam::FgCyan,
+    };
+
+    /// Everything in white, for monochrome screens
+    pub const MONO: Theme = Theme {
+        normal: console::SgrParam::FgWhite,
+        border: console::SgrParam::FgWhite,
+        title: console::SgrParam::Bold,
+        selected: console::SgrParam::FgWhite,
+    };
+
+    /// Switch to the ordinary text colour
+    pub fn set_normal(&self, out: &mut neotron_sdk::File) {
+        console::set_sgr(out, [console::SgrParam::Reset, self.normal]);
+    }
+
+    /// Switch to the border colour
+    pub fn set_border(&self, out: &mut neotron_sdk::File) {
+        console::set_sgr(out, [console::SgrParam::Reset, self.border]);
+    }
+
+    /// Switch to the title colour
+    pub fn set_title(&self, out: &mut neotron_sdk::File) {
+        console::set_sgr(
+            out,
+            [
+                console::SgrParam::Reset,
+                console::SgrParam::Bold,
+                self.title,
+            ],
+        );
+    }
+
+    /// Switch to the colour for whatever is selected
+    pub fn set_selected(&self, out: &mut neotron_sdk::File) {
+        console::set_sgr(
+            out,
+            [
+                console::SgrParam::Reset,
+                self.selected,
+                console::SgrParam::Reverse,
+            ],
+        );
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::DEFAULT
+    }
+}
+
+/// Holds a line of formatted text, without an allocator.
+///
+/// Anything past [`MAX_LINE`] bytes is dropped.
+#[derive(Clone)]
+pub struct Line {
+    buffer: [u8; MAX_LINE],
+    len: usize,
+}
+
+impl Line {
+    /// Make an empty line
+    pub const fn new() -> Line {
+        Line {
+            buffer: [0; MAX_LINE],
+            len: 0,
+        }
+    }
+
+    /// Get the text
+    pub fn as_str(&self) -> &str {
+        match core::str::from_utf8(&self.buffer[0..self.len]) {
+            Ok(text) => text,
+            // We cut a character in half when the line filled up
+            Err(e) => core::str::from_utf8(&self.buffer[0..e.valid_up_to()]).unwrap_or(""),
+        }
+    }
+
+    /// Empty the line
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+}
+
+impl Default for Line {
+    fn default() -> Self {
+        Line::new()
+    }
+}
+
+impl core::fmt::Write for Line {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        let bytes = s.as_bytes();
+        let space = &mut self.buffer[self.len..];
+        let count = bytes.len().min(space.len());
+        space[0..count].copy_from_slice(&bytes[0..count]);
+        self.len += count;
+        Ok(())
+    }
+}
+
+/// Move the cursor
+pub fn move_to(out: &mut neotron_sdk::File, row: u8, col: u8) {
+    console::move_cursor(out, console::Position { row, col });
+}
+
+/// Write some text, cut or padded with spaces so it takes up exactly `width`
+/// columns
+pub fn write_padded(out: &mut neotron_sdk::File, text: &str, width: usize) {
+    let mut used = 0;
+    for ch in text.chars().take(width) {
+        let _ = out.write_char(ch);
+        used += 1;
+    }
+    write_repeated(out, ' ', width - used);
+}
+
+/// Write some text in the middle of `width` columns, padded with spaces on
+/// both sides
+pub fn write_centred(out: &mut neotron_sdk::File, text: &str, width: usize) {
+    let len = text.chars().count().min(width);
+    let before = (width - len) / 2;
+    write_repeated(out, ' ', before);
+    write_padded(out, text, width - before);
+}
+
+/// Write the same character several times
+pub fn write_repeated(out: &mut neotron_sdk::File, ch: char, count: usize) {
+    for _ in 0..count {
+        let _ = out.write_char(ch);
+    }
+}
+
+/// Fill a rectangle with spaces, in the current colours
+pub fn clear_rect(out: &mut neotron_sdk::File, rect: Rect) {
+    for row in rect.row..rect.bottom() {
+        move_to(out, row, rect.col);
+        write_repeated(out, ' ', usize::from(rect.width));
+    }
+}
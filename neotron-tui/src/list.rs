@@ -0,0 +1,193 @@
+//! Scrolling lists you can pick an item from, and menus built on them.
+
+use crate::{move_to, write_padded, Frame, Line, Rect, Theme};
+
+/// A scrolling list with one item selected.
+///
+/// The list doesn't hold the items. You tell it how many there are, and
+/// when it is drawn it asks you for the text of each item it shows.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct ListBox {
+    count: usize,
+    selected: usize,
+    /// The first item on screen
+    top: usize,
+}
+
+impl ListBox {
+    /// Make a list with this many items, with the first one selected
+    pub const fn new(count: usize) -> ListBox {
+        ListBox {
+            count,
+            selected: 0,
+            top: 0,
+        }
+    }
+
+    /// How many items there are
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Change how many items there are, keeping the selection in range
+    pub fn set_count(&mut self, count: usize) {
+        self.count = count;
+        self.selected = self.selected.min(count.saturating_sub(1));
+    }
+
+    /// Which item is selected. This is zero if the list is empty.
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    /// Select an item
+    pub fn select(&mut self, idx: usize) {
+        self.selected = idx.min(self.count.saturating_sub(1));
+    }
+
+    /// Select the item above
+    pub fn up(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    /// Select the item below
+    pub fn down(&mut self) {
+        self.select(self.selected + 1);
+    }
+
+    /// Go up a page
+    pub fn page_up(&mut self, rows: usize) {
+        self.selected = self.selected.saturating_sub(rows.max(1));
+    }
+
+    /// Go down a page
+    pub fn page_down(&mut self, rows: usize) {
+        self.select(self.selected + rows.max(1));
+    }
+
+    /// Select the first item
+    pub fn home(&mut self) {
+        self.selected = 0;
+    }
+
+    /// Select the last item
+    pub fn end(&mut self) {
+        self.select(usize::MAX);
+    }
+
+    /// Draw the items which fit in the rectangle, scrolling if we need to so
+    /// the selected item is on screen.
+    ///
+    /// `item` is called to get the text for each item shown.
+    pub fn draw<F>(&mut self, out: &mut neotron_sdk::File, rect: Rect, theme: &Theme, mut item: F)
+    where
+        F: FnMut(usize, &mut Line),
+    {
+        let rows = usize::from(rect.height);
+        if rows == 0 {
+            return;
+        }
+        if self.selected < self.top {
+            self.top = self.selected;
+        } else if self.selected >= self.top + rows {
+            self.top = self.selected + 1 - rows;
+        }
+        // Don't leave a gap at the bottom if the list got shorter
+        self.top = self.top.min(self.count.saturating_sub(rows));
+
+        let mut line = Line::new();
+        for (row, idx) in (rect.row..rect.bottom()).zip(self.top..) {
+            move_to(out, row, rect.col);
+            line.clear();
+            if idx < self.count {
+                item(idx, &mut line);
+            }
+            if idx == self.selected && idx < self.count {
+                theme.set_selected(out);
+            } else {
+                theme.set_normal(out);
+            }
+            write_padded(out, line.as_str(), usize::from(rect.width));
+        }
+        theme.set_normal(out);
+    }
+}
+
+/// A list of fixed choices in a box
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Menu<'a> {
+    title: &'a str,
+    items: &'a [&'a str],
+    list: ListBox,
+}
+
+impl<'a> Menu<'a> {
+    /// Make a menu, with the first item selected
+    pub const fn new(title: &'a str, items: &'a [&'a str]) -> Menu<'a> {
+        Menu {
+            title,
+            items,
+            list: ListBox::new(items.len()),
+        }
+    }
+
+    /// Which item is selected
+    pub fn selected(&self) -> usize {
+        self.list.selected()
+    }
+
+    /// Select the item above
+    pub fn up(&mut self) {
+        self.list.up();
+    }
+
+    /// Select the item below
+    pub fn down(&mut self) {
+        self.list.down();
+    }
+
+    /// Select the next item after the selected one which starts with this
+    /// letter, ignoring case. Returns `true` if there was one.
+    pub fn jump_to(&mut self, letter: char) -> bool {
+        let count = self.items.len();
+        let start = self.list.selected() + 1;
+        for idx in (start..start + count).map(|idx| idx % count) {
+            let first = self.items[idx].chars().next();
+            if first.is_some_and(|ch| ch.eq_ignore_ascii_case(&letter)) {
+                self.list.select(idx);
+                return true;
+            }
+        }
+        false
+    }
+
+    /// How big a box the menu needs, as (width, height)
+    pub fn size(&self) -> (u8, u8) {
+        let widest = self
+            .items
+            .iter()
+            .map(|item| item.chars().count())
+            .chain(core::iter::once(self.title.chars().count() + 2))
+            .max()
+            .unwrap_or(0);
+        // A border and a space either side
+        let width = (widest + 4).min(usize::from(u8::MAX)) as u8;
+        let height = (self.items.len() + 2).min(usize::from(u8::MAX)) as u8;
+        (width, height)
+    }
+
+    /// Draw the menu in a box
+    pub fn draw(&mut self, out: &mut neotron_sdk::File, rect: Rect, theme: &Theme) {
+        Frame::new(self.title).draw(out, rect, theme);
+        let inner = rect.inner();
+        let inner = Rect {
+            col: inner.col + 1,
+            width: inner.width.saturating_sub(2),
+            ..inner
+        };
+        let items = self.items;
+        self.list.draw(out, inner, theme, |idx, line| {
+            let _ = core::fmt::Write::write_str(line, items[idx]);
+        });
+    }
+}
@@ -0,0 +1,60 @@
+//! Bars which fill up as something gets done.
+
+use core::fmt::Write;
+
+use crate::{move_to, write_repeated, Theme};
+
+/// How far through something we are
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct ProgressBar {
+    done: u32,
+    total: u32,
+}
+
+impl ProgressBar {
+    /// Make a bar for a job with this many steps
+    pub const fn new(total: u32) -> ProgressBar {
+        ProgressBar { done: 0, total }
+    }
+
+    /// Say how many steps are done. Anything past the end counts as the end.
+    pub fn set(&mut self, done: u32) {
+        self.done = done.min(self.total);
+    }
+
+    /// Change how many steps there are
+    pub fn set_total(&mut self, total: u32) {
+        self.total = total;
+        self.done = self.done.min(total);
+    }
+
+    /// How far through we are, in percent. A job with no steps is always
+    /// finished.
+    pub fn percent(&self) -> u32 {
+        if self.total == 0 {
+            100
+        } else {
+            (u64::from(self.done) * 100 / u64::from(self.total)) as u32
+        }
+    }
+
+    /// Draw the bar, followed by the percentage, taking up `width` columns
+    pub fn draw(&self, out: &mut neotron_sdk::File, row: u8, col: u8, width: u8, theme: &Theme) {
+        // Room for " 100%"
+        let bar = usize::from(width.saturating_sub(5));
+        let filled = if self.total == 0 {
+            bar
+        } else {
+            (u64::from(self.done) * bar as u64 / u64::from(self.total)) as usize
+        };
+        move_to(out, row, col);
+        theme.set_title(out);
+        write_repeated(out, '█', filled);
+        theme.set_border(out);
+        write_repeated(out, '░', bar - filled);
+        theme.set_normal(out);
+        if width >= 5 {
+            let _ = write!(out, " {:>3}%", self.percent());
+        }
+    }
+}
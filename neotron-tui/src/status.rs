@@ -0,0 +1,43 @@
+//! A bar across the screen, for showing what's going on and which keys do
+//! what.
+
+use core::fmt::Write;
+
+use crate::{move_to, write_padded, Theme};
+
+/// A reverse video bar, with text on the left and right
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct StatusBar {
+    /// Which row it goes on
+    pub row: u8,
+    /// How wide the screen is
+    pub width: u8,
+}
+
+impl StatusBar {
+    /// Make a status bar for this row of the screen
+    pub const fn new(row: u8, width: u8) -> StatusBar {
+        StatusBar { row, width }
+    }
+
+    /// Draw the bar. If the two pieces of text don't both fit, the one on
+    /// the right gets cut.
+    pub fn draw(&self, out: &mut neotron_sdk::File, left: &str, right: &str, theme: &Theme) {
+        let width = usize::from(self.width);
+        // Keep a space at each end
+        let left_len = left.chars().count().min(width.saturating_sub(2));
+        let right_len = right
+            .chars()
+            .count()
+            .min(width.saturating_sub(left_len + 4));
+        move_to(out, self.row, 0);
+        theme.set_selected(out);
+        let _ = out.write_char(' ');
+        write_padded(out, left, width.saturating_sub(right_len + 2));
+        write_padded(out, right, right_len);
+        if width > 1 {
+            let _ = out.write_char(' ');
+        }
+        theme.set_normal(out);
+    }
+}
@@ -0,0 +1,389 @@
+//! A game of Nibbles.
+//!
+//! Steer your worm to the numbers, one to nine. Each number makes the worm
+//! longer, and eating the nine clears the level. Running into a wall, or a
+//! worm, costs a life and starts the level again.
+
+use core::fmt::Write;
+
+use neotron_input::{KeyEvent, Keyboard};
+use neotron_screen::Colour;
+use neotron_tui::Line;
+
+use crate::levels::{Arena, Direction, Start, ARENA_HEIGHT, ARENA_WIDTH, LEVEL_COUNT};
+use crate::{put, reset, show_message, text, Screen, SCREEN_HEIGHT, SCREEN_WIDTH};
+
+/// The longest a worm can get
+const MAX_LEN: usize = 1024;
+
+/// How many lives each player starts with
+const LIVES: u8 = 5;
+
+/// How many segments a worm grows by, for each point on the number it eats
+const GROWTH: usize = 4;
+
+/// The arena starts on this screen row, below the status line
+const ARENA_TOP: i16 = 1;
+
+/// The names of the players' worms
+const NAMES: [&str; 2] = ["SAMMY", "JAKE"];
+
+/// The colours of the players' worms
+const COLOURS: [Colour; 2] = [Colour::Yellow, Colour::Magenta];
+
+/// One player's worm
+struct Worm {
+    /// A ring buffer of the places the worm's body is
+    body: [(u8, u8); MAX_LEN],
+    /// Where the head is in `body`
+    head: usize,
+    len: usize,
+    /// How many more segments the worm will grow by
+    grow: usize,
+    direction: Direction,
+    /// Turns the player has asked for, which happen one per step. This stops
+    /// two quick turns from folding the worm back on itself.
+    turns: [Option<Direction>; 2],
+    lives: u8,
+    score: u32,
+}
+
+impl Worm {
+    /// Make a new worm
+    const fn new() -> Worm {
+        Worm {
+            body: [(0, 0); MAX_LEN],
+            head: 0,
+            len: 0,
+            grow: 0,
+            direction: Direction::Up,
+            turns: [None; 2],
+            lives: LIVES,
+            score: 0,
+        }
+    }
+
+    /// Put the worm back at its starting place, at its starting length
+    fn reset(&mut self, start: Start) {
+        let (col, row, direction) = start;
+        self.body[0] = (col, row);
+        self.head = 0;
+        self.len = 1;
+        self.grow = 2;
+        self.direction = direction;
+        self.turns = [None; 2];
+    }
+
+    /// The places the worm's body is, from head to tail
+    fn segments(&self) -> impl Iterator<Item = (u8, u8)> + '_ {
+        (0..self.len).map(move |idx| self.body[(self.head + MAX_LEN - idx) % MAX_LEN])
+    }
+
+    /// Is any part of the worm here?
+    fn occupies(&self, col: i16, row: i16) -> bool {
+        self.segments()
+            .any(|(c, r)| i16::from(c) == col && i16::from(r) == row)
+    }
+
+    /// Ask the worm to turn
+    fn turn(&mut self, direction: Direction) {
+        let last = self.turns.iter().rev().flatten().next().copied();
+        let current = last.unwrap_or(self.direction);
+        if direction == current || direction == current.opposite() {
+            return;
+        }
+        if let Some(slot) = self.turns.iter_mut().find(|t| t.is_none()) {
+            *slot = Some(direction);
+        }
+    }
+
+    /// Take the next turn, if there is one, and work out where the head will
+    /// go next
+    fn next_head(&mut self) -> (i16, i16) {
+        if let Some(direction) = self.turns[0] {
+            self.direction = direction;
+            self.turns = [self.turns[1], None];
+        }
+        let (col, row) = self.body[self.head];
+        let (dx, dy) = self.direction.delta();
+        (i16::from(col) + dx, i16::from(row) + dy)
+    }
+
+    /// Move the head to a new place. The tail follows, unless we're growing.
+    fn advance(&mut self, (col, row): (i16, i16)) {
+        if self.grow > 0 && self.len < MAX_LEN {
+            self.grow -= 1;
+            self.len += 1;
+        }
+        self.head = (self.head + 1) % MAX_LEN;
+        self.body[self.head] = (col as u8, row as u8);
+    }
+}
+
+/// How a game ended
+pub enum Ending {
+    /// The player quit part way through
+    Quit,
+    /// Somebody ran out of lives. Gives the scores.
+    GameOver([u32; 2]),
+}
+
+/// The game of Nibbles
+pub struct Nibbles {
+    arena: Arena,
+    worms: [Worm; 2],
+    /// How many people are playing
+    players: usize,
+    level: usize,
+    /// The number to eat next, from 1 to 9
+    number: u8,
+    /// Where the number is
+    number_place: (i16, i16),
+}
+
+impl Nibbles {
+    /// Make a new game
+    pub const fn new() -> Nibbles {
+        Nibbles {
+            arena: Arena::new(),
+            worms: [Worm::new(), Worm::new()],
+            players: 1,
+            level: 0,
+            number: 1,
+            number_place: (0, 0),
+        }
+    }
+
+    /// Play a game, with one or two players.
+    ///
+    /// The speed goes from 1 (slow) to 9 (fast).
+    pub fn play(
+        &mut self,
+        screen: &mut Screen,
+        keyboard: &mut Keyboard,
+        stdout: &mut neotron_sdk::File,
+        players: usize,
+        speed: u8,
+    ) -> Ending {
+        self.players = players.clamp(1, 2);
+        for worm in self.worms.iter_mut() {
+            worm.lives = LIVES;
+            worm.score = 0;
+        }
+        self.level = 0;
+        reset(screen);
+        loop {
+            self.arena.build(self.level);
+            self.number = 1;
+            self.restart_level();
+            self.draw(screen);
+            let mut message = Line::new();
+            let _ = write!(message, " Level {}, push any key ", self.level + 1);
+            show_message(screen, keyboard, stdout, message.as_str());
+
+            // Each time round all the levels, things speed up a bit
+            let laps = (self.level / LEVEL_COUNT) as u64;
+            let frame_ms = (200 - u64::from(speed.clamp(1, 9)) * 20) * 4 / (4 + laps);
+            loop {
+                while let Some(key) = keyboard.read() {
+                    let second = self.players - 1;
+                    match key {
+                        KeyEvent::Up => self.worms[0].turn(Direction::Up),
+                        KeyEvent::Down => self.worms[0].turn(Direction::Down),
+                        KeyEvent::Left => self.worms[0].turn(Direction::Left),
+                        KeyEvent::Right => self.worms[0].turn(Direction::Right),
+                        KeyEvent::Char('w' | 'W') => self.worms[second].turn(Direction::Up),
+                        KeyEvent::Char('s' | 'S') => self.worms[second].turn(Direction::Down),
+                        KeyEvent::Char('a' | 'A') => self.worms[second].turn(Direction::Left),
+                        KeyEvent::Char('d' | 'D') => self.worms[second].turn(Direction::Right),
+                        KeyEvent::Char('p' | 'P') => {
+                            show_message(screen, keyboard, stdout, " PAUSED ");
+                        }
+                        KeyEvent::Char('q' | 'Q') => return Ending::Quit,
+                        _ => {}
+                    }
+                }
+
+                match self.step() {
+                    Step::Moved => {}
+                    Step::Crashed(crashed) => {
+                        self.draw(screen);
+                        let mut message = Line::new();
+                        match crashed {
+                            [true, true] => {
+                                let _ = write!(message, " You both crashed! ");
+                            }
+                            [true, false] => {
+                                let _ = write!(message, " {} crashed! ", NAMES[0]);
+                            }
+                            _ => {
+                                let _ = write!(message, " {} crashed! ", NAMES[1]);
+                            }
+                        }
+                        show_message(screen, keyboard, stdout, message.as_str());
+                        if self.worms[0..self.players].iter().any(|w| w.lives == 0) {
+                            self.draw(screen);
+                            show_message(screen, keyboard, stdout, " GAME OVER ");
+                            return Ending::GameOver([self.worms[0].score, self.worms[1].score]);
+                        }
+                        self.restart_level();
+                    }
+                    Step::Cleared => {
+                        self.draw(screen);
+                        let mut message = Line::new();
+                        let _ = write!(message, " Level {} cleared! ", self.level + 1);
+                        show_message(screen, keyboard, stdout, message.as_str());
+                        wipe(screen, stdout);
+                        self.level += 1;
+                        break;
+                    }
+                }
+
+                self.draw(screen);
+                screen.flush(stdout);
+                neotron_sdk::delay(core::time::Duration::from_millis(frame_ms));
+            }
+        }
+    }
+
+    /// Put the worms back where they start, and put out the current number
+    fn restart_level(&mut self) {
+        for (worm, start) in self.worms.iter_mut().zip(self.arena.starts) {
+            worm.reset(start);
+        }
+        self.place_number();
+    }
+
+    /// Move every worm one step
+    fn step(&mut self) -> Step {
+        let mut heads = [(0, 0); 2];
+        for (worm, head) in self.worms[0..self.players].iter_mut().zip(heads.iter_mut()) {
+            *head = worm.next_head();
+        }
+        let mut crashed = [false; 2];
+        for (idx, &(col, row)) in heads[0..self.players].iter().enumerate() {
+            let other = heads[1 - idx];
+            crashed[idx] = self.arena.is_wall(col, row)
+                || self.worms[0..self.players]
+                    .iter()
+                    .any(|worm| worm.occupies(col, row))
+                || (self.players == 2 && other == (col, row));
+        }
+        if crashed.contains(&true) {
+            for (worm, crashed) in self.worms.iter_mut().zip(crashed) {
+                if crashed {
+                    worm.lives -= 1;
+                }
+            }
+            return Step::Crashed(crashed);
+        }
+
+        for (idx, head) in heads[0..self.players].iter().enumerate() {
+            let worm = &mut self.worms[idx];
+            worm.advance(*head);
+            if *head == self.number_place {
+                worm.score += u32::from(self.number) * 10;
+                worm.grow += usize::from(self.number) * GROWTH;
+                self.number += 1;
+                if self.number > 9 {
+                    return Step::Cleared;
+                }
+                self.place_number();
+            }
+        }
+        Step::Moved
+    }
+
+    /// Put the number somewhere empty
+    fn place_number(&mut self) {
+        loop {
+            let col = (neotron_sdk::rand() as usize % ARENA_WIDTH) as i16;
+            let row = (neotron_sdk::rand() as usize % ARENA_HEIGHT) as i16;
+            let taken = self.arena.is_wall(col, row)
+                || self.worms[0..self.players]
+                    .iter()
+                    .any(|worm| worm.occupies(col, row));
+            if !taken {
+                self.number_place = (col, row);
+                return;
+            }
+        }
+    }
+
+    /// Draw everything into the back buffer
+    fn draw(&self, screen: &mut Screen) {
+        screen.clear();
+        for row in 0..ARENA_HEIGHT as i16 {
+            for col in 0..ARENA_WIDTH as i16 {
+                if self.arena.is_wall(col, row) {
+                    put(screen, col, row + ARENA_TOP, '█', Colour::Red);
+                }
+            }
+        }
+        for (idx, worm) in self.worms[0..self.players].iter().enumerate() {
+            for (col, row) in worm.segments() {
+                put(
+                    screen,
+                    i16::from(col),
+                    i16::from(row) + ARENA_TOP,
+                    '█',
+                    COLOURS[idx],
+                );
+            }
+        }
+        let (col, row) = self.number_place;
+        put(
+            screen,
+            col,
+            row + ARENA_TOP,
+            char::from(b'0' + self.number),
+            Colour::White,
+        );
+
+        for (idx, worm) in self.worms[0..self.players].iter().enumerate() {
+            let mut line = Line::new();
+            let _ = write!(
+                line,
+                "{}  Score: {:<6} Lives: {}",
+                NAMES[idx], worm.score, worm.lives
+            );
+            let col = if idx == 0 { 1 } else { 50 };
+            text(screen, col, 0, line.as_str(), COLOURS[idx]);
+        }
+        let mut line = Line::new();
+        let _ = write!(line, "Level {}", self.level + 1);
+        text(screen, 35, 0, line.as_str(), Colour::Cyan);
+    }
+}
+
+impl Default for Nibbles {
+    fn default() -> Self {
+        Nibbles::new()
+    }
+}
+
+/// What happened when the worms moved
+enum Step {
+    /// Nothing much
+    Moved,
+    /// These players crashed
+    Crashed([bool; 2]),
+    /// The last number was eaten
+    Cleared,
+}
+
+/// Sweep across the screen between levels
+fn wipe(screen: &mut Screen, stdout: &mut neotron_sdk::File) {
+    let width = SCREEN_WIDTH as i16;
+    let height = SCREEN_HEIGHT as i16;
+    for ch in ['▒', ' '] {
+        for col in (0..width).step_by(2) {
+            for row in 0..height {
+                put(screen, col, row, ch, Colour::Blue);
+                put(screen, col + 1, row, ch, Colour::Blue);
+            }
+            screen.flush(stdout);
+            neotron_sdk::delay(core::time::Duration::from_millis(10));
+        }
+    }
+}
@@ -0,0 +1,176 @@
+//! The walls for each level.
+//!
+//! The arena fills the screen below the status line. Every level has a wall
+//! all the way round, plus its own obstacles in the middle.
+
+/// How wide the arena is
+pub const ARENA_WIDTH: usize = 80;
+
+/// How tall the arena is
+pub const ARENA_HEIGHT: usize = 24;
+
+/// How many different levels there are. After the last one we start again,
+/// but faster.
+pub const LEVEL_COUNT: usize = 9;
+
+/// Which way a worm is heading
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Direction {
+    /// Towards the top of the screen
+    Up,
+    /// Towards the bottom of the screen
+    Down,
+    /// Towards the left of the screen
+    Left,
+    /// Towards the right of the screen
+    Right,
+}
+
+impl Direction {
+    /// The direction that goes straight back the way we came
+    pub fn opposite(self) -> Direction {
+        match self {
+            Direction::Up => Direction::Down,
+            Direction::Down => Direction::Up,
+            Direction::Left => Direction::Right,
+            Direction::Right => Direction::Left,
+        }
+    }
+
+    /// How far one step in this direction moves, as (columns, rows)
+    pub fn delta(self) -> (i16, i16) {
+        match self {
+            Direction::Up => (0, -1),
+            Direction::Down => (0, 1),
+            Direction::Left => (-1, 0),
+            Direction::Right => (1, 0),
+        }
+    }
+}
+
+/// Where a worm starts: the column, the row, and the way it's heading
+pub type Start = (u8, u8, Direction);
+
+/// The walls of a level
+pub struct Arena {
+    walls: [[bool; ARENA_WIDTH]; ARENA_HEIGHT],
+    /// Where each player's worm starts
+    pub starts: [Start; 2],
+}
+
+impl Arena {
+    /// Make an arena with no walls at all
+    pub const fn new() -> Arena {
+        Arena {
+            walls: [[false; ARENA_WIDTH]; ARENA_HEIGHT],
+            starts: [(50, 12, Direction::Up), (30, 12, Direction::Down)],
+        }
+    }
+
+    /// Is there a wall at this spot? Anywhere outside the arena counts as a
+    /// wall.
+    pub fn is_wall(&self, col: i16, row: i16) -> bool {
+        if col < 0 || row < 0 || col >= ARENA_WIDTH as i16 || row >= ARENA_HEIGHT as i16 {
+            return true;
+        }
+        self.walls[row as usize][col as usize]
+    }
+
+    /// Build the walls for a level. Levels count from zero, and go round
+    /// again after the last one.
+    pub fn build(&mut self, level: usize) {
+        self.walls = [[false; ARENA_WIDTH]; ARENA_HEIGHT];
+        let right = ARENA_WIDTH - 1;
+        let bottom = ARENA_HEIGHT - 1;
+        self.hline(0, 0, right);
+        self.hline(bottom, 0, right);
+        self.vline(0, 0, bottom);
+        self.vline(right, 0, bottom);
+        self.starts = [(50, 12, Direction::Up), (30, 12, Direction::Down)];
+        match level % LEVEL_COUNT {
+            0 => {
+                // Just the outside wall
+            }
+            1 => {
+                // A bar across the middle
+                self.hline(12, 20, 60);
+                self.starts = [(60, 7, Direction::Left), (20, 17, Direction::Right)];
+            }
+            2 => {
+                // Two posts
+                self.vline(20, 4, 19);
+                self.vline(60, 4, 19);
+            }
+            3 => {
+                // A pinwheel
+                self.vline(20, 1, 13);
+                self.vline(60, 10, 22);
+                self.hline(17, 1, 40);
+                self.hline(6, 40, 78);
+                self.starts = [(60, 3, Direction::Left), (20, 20, Direction::Right)];
+            }
+            4 => {
+                // A box with the corners missing
+                self.hline(6, 27, 53);
+                self.hline(18, 27, 53);
+                self.vline(24, 8, 16);
+                self.vline(56, 8, 16);
+                self.starts = [(45, 10, Direction::Left), (35, 14, Direction::Right)];
+            }
+            5 => {
+                // Posts with a gap in the middle
+                for col in (10..=70).step_by(10) {
+                    self.vline(col, 1, 9);
+                    self.vline(col, 15, 22);
+                }
+                self.starts = [(65, 18, Direction::Up), (15, 6, Direction::Down)];
+            }
+            6 => {
+                // A dotted line down the middle
+                for row in (1..bottom).step_by(2) {
+                    self.walls[row][40] = true;
+                }
+                self.starts = [(60, 12, Direction::Up), (20, 12, Direction::Down)];
+            }
+            7 => {
+                // A zig-zag, with the posts hanging from the top and bottom in turn
+                for (idx, col) in (10..=70).step_by(10).enumerate() {
+                    if idx % 2 == 0 {
+                        self.vline(col, 1, 16);
+                    } else {
+                        self.vline(col, 7, 22);
+                    }
+                }
+                self.starts = [(65, 20, Direction::Up), (15, 3, Direction::Down)];
+            }
+            _ => {
+                // A big V
+                for step in 0..16 {
+                    self.walls[3 + step][20 + step] = true;
+                    self.walls[3 + step][60 - step] = true;
+                }
+                self.starts = [(50, 21, Direction::Right), (30, 21, Direction::Left)];
+            }
+        }
+    }
+
+    /// Put in a line of wall going across
+    fn hline(&mut self, row: usize, first_col: usize, last_col: usize) {
+        for cell in self.walls[row][first_col..=last_col].iter_mut() {
+            *cell = true;
+        }
+    }
+
+    /// Put in a line of wall going down
+    fn vline(&mut self, col: usize, first_row: usize, last_row: usize) {
+        for row in self.walls[first_row..=last_row].iter_mut() {
+            row[col] = true;
+        }
+    }
+}
+
+impl Default for Arena {
+    fn default() -> Self {
+        Arena::new()
+    }
+}
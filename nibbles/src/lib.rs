@@ -0,0 +1,200 @@
+//! Game logic for Nibbles, the worm game
+
+#![no_std]
+#![deny(missing_docs)]
+#![deny(unsafe_code)]
+
+use core::fmt::Write;
+
+use neotron_input::{KeyEvent, Keyboard};
+use neotron_screen::{Attr, Colour};
+use neotron_sdk::console;
+use neotron_tui::Line;
+
+pub mod game;
+pub mod levels;
+
+use game::{Ending, Nibbles};
+
+/// How big the screen is
+const SCREEN_WIDTH: usize = 80;
+const SCREEN_HEIGHT: usize = 25;
+
+/// What the game is drawn on
+pub type Screen = neotron_screen::Screen<SCREEN_WIDTH, SCREEN_HEIGHT>;
+
+/// Represents the Nibbles application
+pub struct App {
+    stdout: neotron_sdk::File,
+    screen: Screen,
+    keyboard: Keyboard,
+    game: Nibbles,
+    /// How fast the worms go, from 1 to 9
+    speed: u8,
+    /// The best one-player score this session
+    high_score: u32,
+    /// How the last game ended
+    last_scores: Option<([u32; 2], usize)>,
+}
+
+impl App {
+    /// Make a new Nibbles application.
+    ///
+    /// The arenas are laid out for an 80x25 screen, so the size is ignored.
+    pub const fn new(_width: u8, _height: u8) -> App {
+        App {
+            stdout: neotron_sdk::stdout(),
+            screen: Screen::new(),
+            keyboard: Keyboard::new(),
+            game: Nibbles::new(),
+            speed: 5,
+            high_score: 0,
+            last_scores: None,
+        }
+    }
+
+    /// Show the menu and play games until the user quits
+    pub fn play(&mut self) {
+        console::cursor_off(&mut self.stdout);
+        let mut seed: u16 = 0x4e1b;
+
+        loop {
+            reset(&mut self.screen);
+            self.menu();
+            let key = loop {
+                seed = seed.wrapping_add(1);
+                if let Some(key) = self.keyboard.read() {
+                    break key;
+                }
+                neotron_sdk::delay(core::time::Duration::from_millis(10));
+            };
+            neotron_sdk::srand(seed);
+            let players = match key {
+                KeyEvent::Char('1') => 1,
+                KeyEvent::Char('2') => 2,
+                KeyEvent::Char('+' | '=') => {
+                    self.speed = (self.speed + 1).min(9);
+                    continue;
+                }
+                KeyEvent::Char('-' | '_') => {
+                    self.speed = (self.speed - 1).max(1);
+                    continue;
+                }
+                KeyEvent::Char('q' | 'Q') => break,
+                _ => continue,
+            };
+            let ending = self.game.play(
+                &mut self.screen,
+                &mut self.keyboard,
+                &mut self.stdout,
+                players,
+                self.speed,
+            );
+            if let Ending::GameOver(scores) = ending {
+                if players == 1 {
+                    self.high_score = self.high_score.max(scores[0]);
+                }
+                self.last_scores = Some((scores, players));
+            }
+        }
+
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        console::clear_screen(&mut self.stdout);
+        console::move_cursor(&mut self.stdout, console::Position::origin());
+        console::cursor_on(&mut self.stdout);
+    }
+
+    /// Draw the game menu
+    fn menu(&mut self) {
+        let row = SCREEN_HEIGHT as i16 / 2 - 8;
+        self.screen.clear();
+        centre(&mut self.screen, row, "N I B B L E S", Colour::Yellow);
+        centre(&mut self.screen, row + 3, "1 - One player  ", Colour::White);
+        centre(&mut self.screen, row + 4, "2 - Two players ", Colour::White);
+        let mut line = Line::new();
+        let _ = write!(line, "+/- Speed: {}    ", self.speed);
+        centre(&mut self.screen, row + 5, line.as_str(), Colour::White);
+        centre(&mut self.screen, row + 7, "Q - Quit        ", Colour::White);
+        centre(
+            &mut self.screen,
+            row + 10,
+            "Sammy (yellow) steers with the arrow keys. In a two player",
+            Colour::Cyan,
+        );
+        centre(
+            &mut self.screen,
+            row + 11,
+            "game, Jake (magenta) steers with W, A, S and D.",
+            Colour::Cyan,
+        );
+        centre(
+            &mut self.screen,
+            row + 12,
+            "Eat the numbers to grow. Eat the 9 to clear the level.",
+            Colour::Cyan,
+        );
+        centre(
+            &mut self.screen,
+            row + 13,
+            "P pauses, Q returns to this menu",
+            Colour::Cyan,
+        );
+        if let Some((scores, players)) = self.last_scores {
+            let mut line = Line::new();
+            if players == 1 {
+                let _ = write!(
+                    line,
+                    "Last score: {}    Best: {}",
+                    scores[0], self.high_score
+                );
+            } else {
+                let _ = write!(line, "Last game: Sammy {}, Jake {}", scores[0], scores[1]);
+            }
+            centre(&mut self.screen, row + 16, line.as_str(), Colour::Green);
+        }
+        self.screen.flush(&mut self.stdout);
+    }
+}
+
+/// Show a message in the middle of the screen and wait for a key
+fn show_message(
+    screen: &mut Screen,
+    keyboard: &mut Keyboard,
+    stdout: &mut neotron_sdk::File,
+    message: &str,
+) {
+    let row = SCREEN_HEIGHT as i16 / 2;
+    centre(screen, row, message, Colour::Yellow);
+    screen.flush(stdout);
+    keyboard.wait();
+}
+
+/// Blank the screen and make sure the next flush redraws all of it
+fn reset(screen: &mut Screen) {
+    screen.clear();
+    screen.invalidate();
+}
+
+/// Put a character on the screen. Anything off the screen is ignored.
+fn put(screen: &mut Screen, col: i16, row: i16, ch: char, colour: Colour) {
+    // Negative positions turn into huge ones, which are off the screen too
+    screen.set(
+        row as usize,
+        col as usize,
+        ch,
+        Attr::new(colour, Colour::Black),
+    );
+}
+
+/// Put some text on the screen, starting at the given position
+fn text(screen: &mut Screen, col: i16, row: i16, text: &str, colour: Colour) {
+    for (idx, ch) in text.chars().enumerate() {
+        put(screen, col + idx as i16, row, ch, colour);
+    }
+}
+
+/// Put some text on the screen, centred on the given row
+fn centre(screen: &mut Screen, row: i16, message: &str, colour: Colour) {
+    let col = neotron_text::centre_offset(message, SCREEN_WIDTH) as i16;
+    text(screen, col, row, message, colour);
+}
@@ -0,0 +1,205 @@
+//! The text being viewed, and how it is laid out on screen.
+//!
+//! Positions in the text are byte offsets. A "row" is one line on the
+//! screen - a long line of text takes up several rows when we are wrapping,
+//! or one row (with the excess cut off) when we are not.
+
+/// How far apart the tab stops are
+const TAB_WIDTH: usize = 8;
+
+/// The text being viewed
+pub struct Document {
+    data: [u8; Self::MAX_SIZE],
+    len: usize,
+    truncated: bool,
+}
+
+impl Document {
+    /// The largest file we can hold
+    pub const MAX_SIZE: usize = 128 * 1024;
+
+    /// Make an empty document
+    pub const fn new() -> Document {
+        Document {
+            data: [0; Self::MAX_SIZE],
+            len: 0,
+            truncated: false,
+        }
+    }
+
+    /// Throw away the contents
+    pub fn clear(&mut self) {
+        self.len = 0;
+        self.truncated = false;
+    }
+
+    /// The space after the text, which can be filled in and then added with
+    /// [`Document::commit`].
+    pub fn spare(&mut self) -> &mut [u8] {
+        &mut self.data[self.len..]
+    }
+
+    /// Add bytes which have been written into [`Document::spare`]
+    pub fn commit(&mut self, count: usize) {
+        self.len = (self.len + count).min(Self::MAX_SIZE);
+    }
+
+    /// Add a single byte. Returns false if the document is full.
+    pub fn push(&mut self, byte: u8) -> bool {
+        if self.len == Self::MAX_SIZE {
+            self.truncated = true;
+            return false;
+        }
+        self.data[self.len] = byte;
+        self.len += 1;
+        true
+    }
+
+    /// Record that there was more text than we could hold
+    pub fn set_truncated(&mut self) {
+        self.truncated = true;
+    }
+
+    /// Was there more text than we could hold?
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+
+    /// The length of the text, in bytes
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Is there no text at all?
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The text itself
+    pub fn bytes(&self) -> &[u8] {
+        &self.data[0..self.len]
+    }
+
+    /// How many columns does this byte take up, if it appears at `col`?
+    pub fn byte_width(byte: u8, col: usize) -> usize {
+        match byte {
+            b'\t' => TAB_WIDTH - (col % TAB_WIDTH),
+            // Carriage returns from DOS line endings are invisible
+            b'\r' => 0,
+            // Other control characters are shown like ^C
+            0x00..=0x1F | 0x7F => 2,
+            // UTF-8 continuation bytes belong to the previous character
+            0x80..=0xBF => 0,
+            _ => 1,
+        }
+    }
+
+    /// Find the start of the line containing `offset`
+    pub fn line_start(&self, offset: usize) -> usize {
+        let offset = offset.min(self.len);
+        match self.data[0..offset].iter().rposition(|b| *b == b'\n') {
+            Some(pos) => pos + 1,
+            None => 0,
+        }
+    }
+
+    /// Find the start of the row after the one starting at `offset`.
+    ///
+    /// If `width` is `None`, long lines are not wrapped.
+    pub fn next_row(&self, offset: usize, width: Option<usize>) -> usize {
+        let mut col = 0;
+        for (idx, byte) in self.bytes().iter().enumerate().skip(offset) {
+            if *byte == b'\n' {
+                return idx + 1;
+            }
+            let byte_width = Self::byte_width(*byte, col);
+            if let Some(width) = width {
+                // Always put at least one character on a row
+                if col + byte_width > width && idx > offset {
+                    return idx;
+                }
+            }
+            col += byte_width;
+        }
+        self.len
+    }
+
+    /// Find the start of the row before the one starting at `offset`
+    pub fn prev_row(&self, offset: usize, width: Option<usize>) -> usize {
+        if offset == 0 {
+            return 0;
+        }
+        let mut row = self.line_start(offset - 1);
+        loop {
+            let next = self.next_row(row, width);
+            if next >= offset {
+                return row;
+            }
+            row = next;
+        }
+    }
+
+    /// Find the start of the row which contains `offset`
+    pub fn row_containing(&self, offset: usize, width: Option<usize>) -> usize {
+        let mut row = self.line_start(offset);
+        loop {
+            let next = self.next_row(row, width);
+            if next > offset || next >= self.len {
+                return row;
+            }
+            row = next;
+        }
+    }
+
+    /// Which line is `offset` on? The first line is line 1.
+    pub fn line_number(&self, offset: usize) -> usize {
+        let offset = offset.min(self.len);
+        1 + self.data[0..offset].iter().filter(|b| **b == b'\n').count()
+    }
+
+    /// Find the start of the given line. The first line is line 1.
+    pub fn find_line(&self, line: usize) -> usize {
+        let mut remaining = line.saturating_sub(1);
+        if remaining == 0 {
+            return 0;
+        }
+        for (idx, byte) in self.bytes().iter().enumerate() {
+            if *byte == b'\n' {
+                remaining -= 1;
+                if remaining == 0 {
+                    return idx + 1;
+                }
+            }
+        }
+        self.len
+    }
+
+    /// Does `pattern` appear at `offset`? Letters are compared without
+    /// regard to case.
+    pub fn matches_at(&self, offset: usize, pattern: &[u8]) -> bool {
+        if pattern.is_empty() {
+            return false;
+        }
+        match self.bytes().get(offset..offset + pattern.len()) {
+            Some(text) => text.eq_ignore_ascii_case(pattern),
+            None => false,
+        }
+    }
+
+    /// Search for `pattern`, starting at `from`
+    pub fn find(&self, from: usize, pattern: &[u8], forwards: bool) -> Option<usize> {
+        if forwards {
+            (from..self.len).find(|offset| self.matches_at(*offset, pattern))
+        } else {
+            (0..from.min(self.len))
+                .rev()
+                .find(|offset| self.matches_at(*offset, pattern))
+        }
+    }
+}
+
+impl Default for Document {
+    fn default() -> Self {
+        Document::new()
+    }
+}
@@ -0,0 +1,530 @@
+//! Application logic for the pager
+
+#![no_std]
+#![deny(missing_docs)]
+#![deny(unsafe_code)]
+
+use core::fmt::Write;
+
+use neotron_input::{KeyEvent, Keyboard};
+use neotron_sdk::console;
+use neotron_text::Align;
+
+pub mod document;
+
+use document::Document;
+
+/// What the help screen says
+const HELP: &[&str] = &[
+    "Moving:",
+    "  Space, f, PgDn    Forward one screen",
+    "  b, PgUp           Back one screen",
+    "  Enter, j, Down    Forward one line",
+    "  k, Up             Back one line",
+    "  d / u             Forward / back half a screen",
+    "  g, <, Home        Go to the start",
+    "  G, >, End         Go to the end",
+    "  :                 Go to a line number",
+    "",
+    "Searching:",
+    "  /                 Search forwards",
+    "  ?                 Search backwards",
+    "  n / N             Repeat the search, the same / the other way",
+    "",
+    "Long lines:",
+    "  S                 Switch between wrapping and cutting off",
+    "  Left, Right       Scroll sideways, when cutting off",
+    "",
+    "  q                 Quit",
+];
+
+/// Represents the pager application
+pub struct Pager {
+    width: u8,
+    height: u8,
+    stdout: neotron_sdk::File,
+    stdin: neotron_sdk::File,
+    keyboard: Keyboard,
+    document: Document,
+    /// What we call the document in the status bar
+    name: [u8; Self::MAX_NAME],
+    name_len: usize,
+    /// The offset of the first row on the screen
+    top: usize,
+    /// How many columns we have scrolled sideways
+    left: usize,
+    /// Do we wrap long lines?
+    wrap: bool,
+    /// The last thing searched for
+    pattern: [u8; Self::MAX_PATTERN],
+    pattern_len: usize,
+    /// Which way the last search went
+    search_forwards: bool,
+    /// A message to show in the status bar, instead of the usual info
+    message: Option<&'static str>,
+}
+
+impl Pager {
+    /// The longest name we show in the status bar
+    const MAX_NAME: usize = 32;
+    /// The longest search we can do
+    const MAX_PATTERN: usize = 64;
+
+    /// Make a new pager.
+    ///
+    /// You can give the screen size in characters.
+    pub const fn new(width: u8, height: u8) -> Pager {
+        Pager {
+            width,
+            height,
+            stdout: neotron_sdk::stdout(),
+            stdin: neotron_sdk::stdin(),
+            keyboard: Keyboard::new(),
+            document: Document::new(),
+            name: [0; Self::MAX_NAME],
+            name_len: 0,
+            top: 0,
+            left: 0,
+            wrap: true,
+            pattern: [0; Self::MAX_PATTERN],
+            pattern_len: 0,
+            search_forwards: true,
+            message: None,
+        }
+    }
+
+    /// Load the contents of a file
+    pub fn load_file(&mut self, filename: &str) -> Result<(), neotron_sdk::Error> {
+        self.document.clear();
+        self.set_name(filename);
+        let path = neotron_sdk::path::Path::new(filename)?;
+        let f = neotron_sdk::File::open(path, neotron_sdk::Flags::empty())?;
+        loop {
+            let spare = self.document.spare();
+            if spare.is_empty() {
+                // See if there was more we couldn't fit in
+                let mut buffer = [0u8; 1];
+                if let Ok(1) = f.read(&mut buffer) {
+                    self.document.set_truncated();
+                }
+                break;
+            }
+            let n = f.read(spare)?;
+            if n == 0 {
+                break;
+            }
+            self.document.commit(n);
+        }
+        Ok(())
+    }
+
+    /// Load text from standard input, until Ctrl+D is pressed
+    pub fn load_stdin(&mut self) {
+        self.document.clear();
+        self.set_name("(stdin)");
+        let mut last = 0;
+        loop {
+            let mut buffer = [0u8; 1];
+            let Ok(1) = self.stdin.read(&mut buffer) else {
+                neotron_sdk::delay(core::time::Duration::from_millis(10));
+                continue;
+            };
+            match buffer[0] {
+                0x04 => break,
+                // Terminals send CR for Enter, but files use LF (or CR LF)
+                b'\r' => {
+                    self.document.push(b'\n');
+                }
+                b'\n' if last == b'\r' => {}
+                byte => {
+                    self.document.push(byte);
+                }
+            }
+            last = buffer[0];
+        }
+    }
+
+    /// Remember the name to show in the status bar
+    fn set_name(&mut self, name: &str) {
+        let len = name.len().min(Self::MAX_NAME);
+        self.name[0..len].copy_from_slice(&name.as_bytes()[0..len]);
+        self.name_len = len;
+    }
+
+    /// Run the pager, until the user quits
+    pub fn run(&mut self) {
+        self.top = 0;
+        self.left = 0;
+        if self.document.is_truncated() {
+            self.message = Some("File too big - only showing the start");
+        }
+        console::cursor_off(&mut self.stdout);
+        loop {
+            self.draw();
+            let key = self.keyboard.wait();
+            self.message = None;
+            let page = self.text_rows();
+            match key {
+                KeyEvent::Char('q' | 'Q') => break,
+                KeyEvent::Char(' ' | 'f')
+                | KeyEvent::Ctrl('f')
+                | KeyEvent::Ctrl('v')
+                | KeyEvent::PageDown => self.scroll_down(page - 1),
+                KeyEvent::Char('b') | KeyEvent::Ctrl('b') | KeyEvent::PageUp => {
+                    self.scroll_up(page - 1)
+                }
+                KeyEvent::Char('j' | 'e')
+                | KeyEvent::Enter
+                | KeyEvent::Ctrl('n')
+                | KeyEvent::Down => self.scroll_down(1),
+                KeyEvent::Char('k' | 'y') | KeyEvent::Ctrl('p') | KeyEvent::Up => self.scroll_up(1),
+                KeyEvent::Char('d') | KeyEvent::Ctrl('d') => self.scroll_down(page / 2),
+                KeyEvent::Char('u') | KeyEvent::Ctrl('u') => self.scroll_up(page / 2),
+                KeyEvent::Char('g' | '<') | KeyEvent::Home => self.top = 0,
+                KeyEvent::Char('G' | '>') | KeyEvent::End => self.top = self.last_top(),
+                KeyEvent::Char(':') => self.go_to_line(),
+                KeyEvent::Char('/') => self.search_prompt(true),
+                KeyEvent::Char('?') => self.search_prompt(false),
+                KeyEvent::Char('n') => self.search(self.search_forwards),
+                KeyEvent::Char('N') => self.search(!self.search_forwards),
+                KeyEvent::Char('S' | 's') => {
+                    self.wrap = !self.wrap;
+                    self.left = 0;
+                    self.top = self.document.row_containing(self.top, self.row_width());
+                    self.message = Some(if self.wrap {
+                        "Wrapping long lines"
+                    } else {
+                        "Cutting off long lines"
+                    });
+                }
+                KeyEvent::Left if !self.wrap => {
+                    self.left = self.left.saturating_sub(usize::from(self.width) / 2);
+                }
+                KeyEvent::Right if !self.wrap => self.left += usize::from(self.width) / 2,
+                KeyEvent::Char('h' | 'H') => self.show_help(),
+                _ => {}
+            }
+        }
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        console::clear_screen(&mut self.stdout);
+        console::move_cursor(&mut self.stdout, console::Position::origin());
+        console::cursor_on(&mut self.stdout);
+    }
+
+    /// How many rows of text fit on the screen (leaving room for the status bar)
+    fn text_rows(&self) -> usize {
+        usize::from(self.height).saturating_sub(1).max(1)
+    }
+
+    /// The width we wrap lines at, or `None` if we don't wrap them
+    fn row_width(&self) -> Option<usize> {
+        if self.wrap {
+            Some(usize::from(self.width))
+        } else {
+            None
+        }
+    }
+
+    /// The furthest we can scroll, with the end of the text at the bottom
+    /// of the screen
+    fn last_top(&self) -> usize {
+        let mut offset = self.document.len();
+        for _ in 0..self.text_rows() {
+            offset = self.document.prev_row(offset, self.row_width());
+        }
+        offset
+    }
+
+    /// Move down through the text
+    fn scroll_down(&mut self, rows: usize) {
+        let last_top = self.last_top();
+        for _ in 0..rows.max(1) {
+            if self.top >= last_top {
+                break;
+            }
+            self.top = self.document.next_row(self.top, self.row_width());
+        }
+    }
+
+    /// Move up through the text
+    fn scroll_up(&mut self, rows: usize) {
+        for _ in 0..rows.max(1) {
+            self.top = self.document.prev_row(self.top, self.row_width());
+        }
+    }
+
+    /// Draw the text and the status bar
+    fn draw(&mut self) {
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        let mut offset = self.top;
+        for row in 0..self.text_rows() {
+            console::move_cursor(
+                &mut self.stdout,
+                console::Position {
+                    row: row as u8,
+                    col: 0,
+                },
+            );
+            if offset < self.document.len() {
+                self.draw_row(offset);
+                offset = self.document.next_row(offset, self.row_width());
+            } else {
+                // Past the end of the text
+                let _ = self.stdout.write_char('~');
+                self.pad(1);
+            }
+        }
+        self.draw_status(offset);
+    }
+
+    /// Draw one row of text, starting at `offset`
+    fn draw_row(&mut self, offset: usize) {
+        let width = usize::from(self.width);
+        let end = self.document.next_row(offset, self.row_width());
+        let pattern_len = self.pattern_len;
+        let mut col = 0;
+        let mut written = 0;
+        let mut highlight_until = offset;
+        let mut highlighted = false;
+        for idx in offset..end {
+            let byte = self.document.bytes()[idx];
+            if byte == b'\n' {
+                break;
+            }
+            if self.document.matches_at(idx, &self.pattern[0..pattern_len]) {
+                highlight_until = idx + pattern_len;
+            }
+            let byte_width = Document::byte_width(byte, col);
+            let visible = col >= self.left && col + byte_width <= self.left + width;
+            col += byte_width;
+            if !visible {
+                continue;
+            }
+            let highlight = idx < highlight_until;
+            if highlight != highlighted {
+                let sgr = if highlight {
+                    console::SgrParam::Reverse
+                } else {
+                    console::SgrParam::Reset
+                };
+                console::set_sgr(&mut self.stdout, [sgr]);
+                highlighted = highlight;
+            }
+            match byte {
+                b'\t' => {
+                    for _ in 0..byte_width {
+                        let _ = self.stdout.write_char(' ');
+                    }
+                }
+                b'\r' => {}
+                0x00..=0x1F | 0x7F => {
+                    let _ = self.stdout.write_char('^');
+                    let _ = self.stdout.write_char(char::from(byte ^ 0x40));
+                }
+                _ => {
+                    let _ = self.stdout.write(&[byte]);
+                }
+            }
+            written += byte_width;
+        }
+        if highlighted {
+            console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        }
+        self.pad(written);
+    }
+
+    /// Blank out the rest of the row, having written `written` columns
+    fn pad(&mut self, written: usize) {
+        for _ in written..usize::from(self.width) {
+            let _ = self.stdout.write_char(' ');
+        }
+    }
+
+    /// Draw the status bar, given the offset just past the bottom row
+    fn draw_status(&mut self, bottom: usize) {
+        console::move_cursor(
+            &mut self.stdout,
+            console::Position {
+                row: self.height - 1,
+                col: 0,
+            },
+        );
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reverse]);
+        let mut status = StatusLine::new();
+        if let Some(message) = self.message {
+            let _ = status.write_str(message);
+        } else {
+            let name = core::str::from_utf8(&self.name[0..self.name_len]).unwrap_or("?");
+            let percent = if self.document.is_empty() {
+                100
+            } else {
+                bottom.min(self.document.len()) * 100 / self.document.len()
+            };
+            let _ = write!(
+                status,
+                "{}  line {}  {}%",
+                name,
+                self.document.line_number(self.top),
+                percent
+            );
+            if bottom >= self.document.len() {
+                let _ = status.write_str("  (END)");
+            }
+            let _ = status.write_str("  h: help, q: quit");
+        }
+        let _ = write!(
+            self.stdout,
+            "{}",
+            neotron_text::fit(status.as_str(), usize::from(self.width) - 1, Align::Left)
+        );
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+    }
+
+    /// Ask for something to search for, and look for it
+    fn search_prompt(&mut self, forwards: bool) {
+        let prompt = if forwards { "/" } else { "?" };
+        let mut input = [0u8; Self::MAX_PATTERN];
+        let Some(len) = self.prompt(prompt, &mut input) else {
+            return;
+        };
+        if len > 0 {
+            self.pattern[0..len].copy_from_slice(&input[0..len]);
+            self.pattern_len = len;
+        }
+        self.search_forwards = forwards;
+        self.search(forwards);
+    }
+
+    /// Look for the last search pattern, and move to it
+    fn search(&mut self, forwards: bool) {
+        if self.pattern_len == 0 {
+            self.message = Some("Nothing to search for");
+            return;
+        }
+        let pattern = &self.pattern[0..self.pattern_len];
+        let found = if forwards {
+            let from = self.document.next_row(self.top, self.row_width());
+            self.document.find(from, pattern, true)
+        } else {
+            self.document.find(self.top, pattern, false)
+        };
+        match found {
+            Some(offset) => {
+                let row = self.document.row_containing(offset, self.row_width());
+                self.top = row.min(self.last_top());
+            }
+            None => self.message = Some("Pattern not found"),
+        }
+    }
+
+    /// Ask for a line number, and go there
+    fn go_to_line(&mut self) {
+        let mut input = [0u8; 8];
+        let Some(len) = self.prompt(":", &mut input) else {
+            return;
+        };
+        let line = core::str::from_utf8(&input[0..len])
+            .ok()
+            .and_then(|s| s.trim().parse::<usize>().ok());
+        match line {
+            Some(line) => {
+                let offset = self.document.find_line(line);
+                self.top = offset.min(self.last_top());
+            }
+            None => self.message = Some("That's not a line number"),
+        }
+    }
+
+    /// Read a line of text on the status bar.
+    ///
+    /// Returns `None` if the user pressed Escape.
+    fn prompt(&mut self, prompt: &str, input: &mut [u8]) -> Option<usize> {
+        console::move_cursor(
+            &mut self.stdout,
+            console::Position {
+                row: self.height - 1,
+                col: 0,
+            },
+        );
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        let _ = self.stdout.write_str(prompt);
+        self.pad(prompt.len() + 1);
+        console::move_cursor(
+            &mut self.stdout,
+            console::Position {
+                row: self.height - 1,
+                col: prompt.len() as u8,
+            },
+        );
+        console::cursor_on(&mut self.stdout);
+        let mut len = 0;
+        let result = loop {
+            match self.keyboard.wait() {
+                KeyEvent::Enter => break Some(len),
+                KeyEvent::Escape => break None,
+                KeyEvent::Backspace if len > 0 => {
+                    len -= 1;
+                    let _ = self.stdout.write_str("\u{0008} \u{0008}");
+                }
+                KeyEvent::Char(key) if key.is_ascii() && len < input.len() => {
+                    input[len] = key as u8;
+                    len += 1;
+                    let _ = self.stdout.write_char(key);
+                }
+                _ => {}
+            }
+        };
+        console::cursor_off(&mut self.stdout);
+        result
+    }
+
+    /// Show the list of keys
+    fn show_help(&mut self) {
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        console::clear_screen(&mut self.stdout);
+        console::move_cursor(&mut self.stdout, console::Position::origin());
+        for line in HELP {
+            let _ = writeln!(self.stdout, "{}", line);
+        }
+        let _ = writeln!(self.stdout);
+        let _ = write!(self.stdout, "Press any key to go back.");
+        self.keyboard.wait();
+    }
+}
+
+/// Builds the status bar text, without an allocator
+struct StatusLine {
+    buffer: [u8; 128],
+    len: usize,
+}
+
+impl StatusLine {
+    /// Make an empty status line
+    fn new() -> StatusLine {
+        StatusLine {
+            buffer: [0; 128],
+            len: 0,
+        }
+    }
+
+    /// Get the text
+    fn as_str(&self) -> &str {
+        core::str::from_utf8(&self.buffer[0..self.len]).unwrap_or("?")
+    }
+}
+
+impl core::fmt::Write for StatusLine {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        // Silently cut off anything which doesn't fit
+        for ch in s.chars() {
+            let mut encoded = [0u8; 4];
+            let encoded = ch.encode_utf8(&mut encoded);
+            if self.len + encoded.len() > self.buffer.len() {
+                break;
+            }
+            self.buffer[self.len..self.len + encoded.len()].copy_from_slice(encoded.as_bytes());
+            self.len += encoded.len();
+        }
+        Ok(())
+    }
+}
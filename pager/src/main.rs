@@ -0,0 +1,35 @@
+#![cfg_attr(target_os = "none", no_std)]
+#![cfg_attr(target_os = "none", no_main)]
+
+use core::{fmt::Write, ptr::addr_of_mut};
+
+#[cfg(not(target_os = "none"))]
+fn main() {
+    neotron_sdk::init();
+}
+
+static mut PAGER: pager::Pager = pager::Pager::new(80, 25);
+
+#[no_mangle]
+extern "C" fn neotron_main() -> i32 {
+    neotron_error::exit_code(real_main())
+}
+
+fn real_main() -> Result<(), neotron_error::Report> {
+    let pager = unsafe { &mut *addr_of_mut!(PAGER) };
+    match neotron_sdk::arg(0) {
+        Some(filename) => pager
+            .load_file(&filename)
+            .map_err(|e| neotron_error::Report::from(e).about(&filename))?,
+        None => {
+            let mut stdout = neotron_sdk::stdout();
+            let _ = writeln!(
+                stdout,
+                "Reading from standard input. Press Ctrl+D to finish."
+            );
+            pager.load_stdin();
+        }
+    }
+    pager.run();
+    Ok(())
+}
@@ -0,0 +1,274 @@
+//! The grid of pipes, and working out which of them the source reaches.
+//!
+//! Each tile is a set of the sides it has a pipe out of, as bits. A level is
+//! made by growing a random tree out from the source, so there's a way to
+//! connect everything, and then turning every tile a random amount.
+
+/// A pipe out of the top of a tile
+pub const NORTH: u8 = 1 << 0;
+/// A pipe out of the right of a tile
+pub const EAST: u8 = 1 << 1;
+/// A pipe out of the bottom of a tile
+pub const SOUTH: u8 = 1 << 2;
+/// A pipe out of the left of a tile
+pub const WEST: u8 = 1 << 3;
+
+/// The four sides, clockwise from the top
+const SIDES: [u8; 4] = [NORTH, EAST, SOUTH, WEST];
+
+/// The widest board we can hold
+pub const MAX_WIDTH: u8 = 21;
+/// The tallest board we can hold
+pub const MAX_HEIGHT: u8 = 17;
+
+/// How many tiles the biggest board has
+const MAX_TILES: usize = MAX_WIDTH as usize * MAX_HEIGHT as usize;
+
+/// How many times we try for a level without any four-way crossings
+/// before we allow them
+const TIDY_ATTEMPTS: u32 = 8;
+
+/// Turn a tile a quarter turn clockwise
+pub const fn clockwise(tile: u8) -> u8 {
+    ((tile << 1) | (tile >> 3)) & 0x0F
+}
+
+/// Turn a tile a quarter turn anticlockwise
+pub const fn anticlockwise(tile: u8) -> u8 {
+    ((tile >> 1) | (tile << 3)) & 0x0F
+}
+
+/// The side a pipe comes in at, if it goes out of `side`
+const fn opposite(side: u8) -> u8 {
+    clockwise(clockwise(side))
+}
+
+/// Is this tile the end of a line?
+pub const fn is_end(tile: u8) -> bool {
+    tile.count_ones() == 1
+}
+
+/// A board of pipes
+pub struct Board {
+    width: u8,
+    height: u8,
+    tiles: [u8; MAX_TILES],
+    /// Tiles the player has marked as done, so they don't turn
+    locked: [bool; MAX_TILES],
+    /// Tiles the source reaches
+    lit: [bool; MAX_TILES],
+    /// The tile the network is fed from
+    source: usize,
+}
+
+impl Board {
+    /// Make an empty board
+    pub const fn new() -> Board {
+        Board {
+            width: 0,
+            height: 0,
+            tiles: [0; MAX_TILES],
+            locked: [false; MAX_TILES],
+            lit: [false; MAX_TILES],
+            source: 0,
+        }
+    }
+
+    /// How many tiles across
+    pub fn width(&self) -> u8 {
+        self.width
+    }
+
+    /// How many tiles down
+    pub fn height(&self) -> u8 {
+        self.height
+    }
+
+    /// Make a new level, and scramble it.
+    ///
+    /// The size is clamped to [`MAX_WIDTH`] and [`MAX_HEIGHT`].
+    pub fn generate(&mut self, width: u8, height: u8, rng: &mut neotron_rand::Rng) {
+        self.width = width.clamp(2, MAX_WIDTH);
+        self.height = height.clamp(2, MAX_HEIGHT);
+        self.source = self.index(self.width / 2, self.height / 2);
+        let mut attempts = 0;
+        while !self.grow(rng, attempts < TIDY_ATTEMPTS) {
+            attempts += 1;
+        }
+        self.locked = [false; MAX_TILES];
+        // Turning everything the right way up by chance would be no fun
+        loop {
+            for idx in 0..self.len() {
+                for _ in 0..rng.below(4) {
+                    self.tiles[idx] = clockwise(self.tiles[idx]);
+                }
+            }
+            self.flood();
+            if !self.is_solved() {
+                break;
+            }
+        }
+    }
+
+    /// Grow a random tree out from the source, over every tile. Gives
+    /// false if we wanted it tidy and it couldn't be.
+    fn grow(&mut self, rng: &mut neotron_rand::Rng, tidy: bool) -> bool {
+        let len = self.len();
+        self.tiles = [0; MAX_TILES];
+        let mut visited = [false; MAX_TILES];
+        // Tiles in the tree that might still have a neighbour to grow into
+        let mut growing = [0u16; MAX_TILES];
+        let mut count = 1;
+        growing[0] = self.source as u16;
+        visited[self.source] = true;
+        let mut placed = 1;
+        while count > 0 {
+            let slot = rng.index(count);
+            let idx = usize::from(growing[slot]);
+            let mut choices = [(0u8, 0usize); 4];
+            let mut found = 0;
+            if !tidy || self.tiles[idx].count_ones() < 3 {
+                for side in SIDES {
+                    if let Some(next) = self.neighbour(idx, side) {
+                        if !visited[next] {
+                            choices[found] = (side, next);
+                            found += 1;
+                        }
+                    }
+                }
+            }
+            if found == 0 {
+                count -= 1;
+                growing[slot] = growing[count];
+                continue;
+            }
+            let (side, next) = choices[rng.index(found)];
+            self.tiles[idx] |= side;
+            self.tiles[next] |= opposite(side);
+            visited[next] = true;
+            growing[count] = next as u16;
+            count += 1;
+            placed += 1;
+        }
+        placed == len
+    }
+
+    /// Which way a tile's pipes point
+    pub fn tile(&self, x: u8, y: u8) -> u8 {
+        self.tiles[self.index(x, y)]
+    }
+
+    /// Is this the tile the network is fed from?
+    pub fn is_source(&self, x: u8, y: u8) -> bool {
+        self.index(x, y) == self.source
+    }
+
+    /// Does the source reach this tile?
+    pub fn is_lit(&self, x: u8, y: u8) -> bool {
+        self.lit[self.index(x, y)]
+    }
+
+    /// Has the player marked this tile as done?
+    pub fn is_locked(&self, x: u8, y: u8) -> bool {
+        self.locked[self.index(x, y)]
+    }
+
+    /// Mark a tile as done, or not
+    pub fn toggle_lock(&mut self, x: u8, y: u8) {
+        let idx = self.index(x, y);
+        self.locked[idx] = !self.locked[idx];
+    }
+
+    /// Turn a tile a quarter turn. Gives false if it's locked, and so
+    /// didn't turn.
+    pub fn rotate(&mut self, x: u8, y: u8, clockwise_turn: bool) -> bool {
+        let idx = self.index(x, y);
+        if self.locked[idx] {
+            return false;
+        }
+        self.tiles[idx] = if clockwise_turn {
+            clockwise(self.tiles[idx])
+        } else {
+            anticlockwise(self.tiles[idx])
+        };
+        self.flood();
+        true
+    }
+
+    /// How many ends there are to reach, and how many the source reaches
+    pub fn terminals(&self) -> (usize, usize) {
+        let mut total = 0;
+        let mut lit = 0;
+        for idx in 0..self.len() {
+            if idx != self.source && is_end(self.tiles[idx]) {
+                total += 1;
+                if self.lit[idx] {
+                    lit += 1;
+                }
+            }
+        }
+        (total, lit)
+    }
+
+    /// Does the source reach every end?
+    pub fn is_solved(&self) -> bool {
+        let (total, lit) = self.terminals();
+        total == lit
+    }
+
+    /// Work out which tiles the source reaches. A pipe only joins two tiles
+    /// if both of them have a pipe on the side they share.
+    fn flood(&mut self) {
+        self.lit = [false; MAX_TILES];
+        let mut stack = [0u16; MAX_TILES];
+        let mut count = 1;
+        stack[0] = self.source as u16;
+        self.lit[self.source] = true;
+        while count > 0 {
+            count -= 1;
+            let idx = usize::from(stack[count]);
+            for side in SIDES {
+                if self.tiles[idx] & side == 0 {
+                    continue;
+                }
+                let Some(next) = self.neighbour(idx, side) else {
+                    continue;
+                };
+                if !self.lit[next] && self.tiles[next] & opposite(side) != 0 {
+                    self.lit[next] = true;
+                    stack[count] = next as u16;
+                    count += 1;
+                }
+            }
+        }
+    }
+
+    /// The tile next to this one, on the given side, if it's on the board
+    fn neighbour(&self, idx: usize, side: u8) -> Option<usize> {
+        let width = usize::from(self.width);
+        let (x, y) = (idx % width, idx / width);
+        match side {
+            NORTH if y > 0 => Some(idx - width),
+            EAST if x + 1 < width => Some(idx + 1),
+            SOUTH if y + 1 < usize::from(self.height) => Some(idx + width),
+            WEST if x > 0 => Some(idx - 1),
+            _ => None,
+        }
+    }
+
+    /// How many tiles are in use
+    fn len(&self) -> usize {
+        usize::from(self.width) * usize::from(self.height)
+    }
+
+    /// Where a tile is kept
+    fn index(&self, x: u8, y: u8) -> usize {
+        usize::from(y) * usize::from(self.width) + usize::from(x)
+    }
+}
+
+impl Default for Board {
+    fn default() -> Self {
+        Board::new()
+    }
+}
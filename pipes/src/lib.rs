@@ -0,0 +1,539 @@
+//! Game logic for Pipes
+//!
+//! The board is a network of pipes, all turned the wrong way. Turn the tiles
+//! until the source in the middle is joined up to every terminal - the
+//! dead ends. Joined-up pipes light up as you go. Every level is made up
+//! fresh, and we count your moves and time you.
+
+#![no_std]
+#![deny(missing_docs)]
+#![deny(unsafe_code)]
+
+use core::fmt::Write;
+
+use neotron_input::{KeyEvent, Keyboard};
+use neotron_sdk::console;
+use neotron_tui::Line;
+
+pub mod board;
+
+use board::{Board, EAST, NORTH, SOUTH, WEST};
+
+/// A board size
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct Size {
+    /// What we call this size
+    name: &'static str,
+    /// Tiles across
+    width: u8,
+    /// Tiles down
+    height: u8,
+}
+
+/// The board sizes. We keep best scores for each of these.
+const PRESETS: [Size; 3] = [
+    Size {
+        name: "Small",
+        width: 7,
+        height: 7,
+    },
+    Size {
+        name: "Medium",
+        width: 13,
+        height: 11,
+    },
+    Size {
+        name: "Large",
+        width: board::MAX_WIDTH,
+        height: board::MAX_HEIGHT,
+    },
+];
+
+/// Where we keep the best scores
+const BEST_FILE: &str = "PIPES.DAT";
+
+/// The layout of [`BEST_FILE`]
+const BEST_VERSION: u16 = 1;
+
+/// How many bytes the best scores take up in [`BEST_FILE`]
+const BEST_LEN: usize = PRESETS.len() * 10;
+
+/// How long we wait between checking for keys, in milliseconds
+const POLL_MS: u32 = 20;
+
+/// How often we line the clock up with the real-time clock, in polls
+const SYNC_POLLS: u32 = 50;
+
+/// How wide a tile is on the screen
+const TILE_COLS: u8 = 3;
+
+/// The row the board's frame starts on, when it's as tall as it gets
+const BOARD_ROW: u8 = 3;
+
+/// How wide the menu's lines are, so they line up
+const MENU_WIDTH: usize = 44;
+
+/// The best anyone has done on one board size
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct Best {
+    /// The fewest moves
+    moves: Option<u32>,
+    /// The fastest time, in seconds
+    seconds: Option<u32>,
+}
+
+/// How a level ended
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Outcome {
+    /// Everything was connected, with this many moves in this many seconds
+    Won { moves: u32, seconds: u32 },
+    /// Gave up
+    Quit,
+}
+
+/// Represents the Pipes application
+///
+/// You pick a board size from the menu, and can play as many levels as you
+/// like.
+pub struct App {
+    stdout: neotron_sdk::File,
+    keyboard: Keyboard,
+    width: u8,
+    height: u8,
+    board: Board,
+    /// Makes the levels
+    rng: neotron_rand::Rng,
+    /// Times each level
+    clock: neotron_frame::Clock,
+    /// Which tile the player is on
+    cursor: (u8, u8),
+    /// How many times a tile has been turned this level
+    moves: u32,
+    /// Best scores for each of the `PRESETS`
+    best: [Best; PRESETS.len()],
+}
+
+impl App {
+    /// Make a new Pipes application.
+    ///
+    /// You can give the screen size in characters.
+    pub const fn new(width: u8, height: u8) -> App {
+        App {
+            stdout: neotron_sdk::stdout(),
+            keyboard: Keyboard::new(),
+            width,
+            height,
+            board: Board::new(),
+            rng: neotron_rand::Rng::new(0),
+            clock: neotron_frame::Clock::new(),
+            cursor: (0, 0),
+            moves: 0,
+            best: [Best {
+                moves: None,
+                seconds: None,
+            }; PRESETS.len()],
+        }
+    }
+
+    /// Show the menu and play levels until the user quits
+    pub fn play(&mut self) {
+        console::cursor_off(&mut self.stdout);
+        self.rng = neotron_rand::Rng::from_rtc();
+        self.load_best();
+        loop {
+            self.menu();
+            let key = self.keyboard.wait();
+            if let KeyEvent::Char(ch) = key {
+                self.rng.mix(u32::from(ch));
+            }
+            match key {
+                KeyEvent::Char(ch @ '1'..='3') => {
+                    self.play_size(usize::from(ch as u8 - b'1'));
+                }
+                KeyEvent::Escape => break,
+                key if key.is_char('q') => break,
+                _ => {}
+            }
+        }
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        console::clear_screen(&mut self.stdout);
+        console::move_cursor(&mut self.stdout, console::Position::origin());
+        console::cursor_on(&mut self.stdout);
+    }
+
+    /// Draw the menu
+    fn menu(&mut self) {
+        self.clear();
+        self.centre(4, "PIPES", true);
+        self.centre(
+            6,
+            "Turn the pipes to join every terminal up to the source",
+            false,
+        );
+        for (idx, (size, best)) in PRESETS.iter().zip(self.best).enumerate() {
+            let mut line = Line::new();
+            let _ = write!(
+                line,
+                "{} - {:<6} {:>2} x {:<2}   Best: ",
+                idx + 1,
+                size.name,
+                size.width,
+                size.height
+            );
+            let _ = match best.moves {
+                Some(moves) => write!(line, "{:>4} moves, ", moves),
+                None => write!(line, "{:>4} moves, ", "-"),
+            };
+            let mut time = Line::new();
+            let _ = match best.seconds {
+                Some(seconds) => write_time(&mut time, seconds),
+                None => time.write_str("-"),
+            };
+            let _ = write!(line, "{:>5}", time.as_str());
+            self.centre(9 + idx as u8, line.as_str(), false);
+        }
+        let mut line = Line::new();
+        let _ = write!(line, "{:<width$}", "Q - Quit", width = MENU_WIDTH);
+        self.centre(13, line.as_str(), false);
+        self.centre(
+            16,
+            "Arrows move, Space turns clockwise, Z turns anticlockwise,",
+            false,
+        );
+        self.centre(
+            17,
+            "L locks a tile so it won't turn, and Q gives up the level.",
+            false,
+        );
+    }
+
+    /// Play one level after another, until the user goes back to the menu
+    fn play_size(&mut self, preset: usize) {
+        let size = PRESETS[preset];
+        loop {
+            self.board.generate(size.width, size.height, &mut self.rng);
+            let Outcome::Won { moves, seconds } = self.play_level() else {
+                return;
+            };
+            let best = &mut self.best[preset];
+            let fewer = best.moves.is_none_or(|best| moves < best);
+            let faster = best.seconds.is_none_or(|best| seconds < best);
+            if fewer {
+                best.moves = Some(moves);
+            }
+            if faster {
+                best.seconds = Some(seconds);
+            }
+            if fewer || faster {
+                self.save_best();
+            }
+            let mut message = Line::new();
+            let _ = write!(message, "All joined up in {} moves and ", moves);
+            let _ = write_time(&mut message, seconds);
+            let _ = match (fewer, faster) {
+                (true, true) => message.write_str(" - both new bests!"),
+                (true, false) => message.write_str(" - the fewest moves yet!"),
+                (false, true) => message.write_str(" - the fastest yet!"),
+                (false, false) => message.write_str("."),
+            };
+            self.show_status(self.height - 2, message.as_str());
+            self.show_status(self.height - 1, "N for a new level, or Q for the menu.");
+            loop {
+                match self.keyboard.wait() {
+                    key if key.is_char('n') => break,
+                    KeyEvent::Escape => return,
+                    key if key.is_char('q') => return,
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    /// Play the level on the board, until it's solved or the player gives
+    /// up
+    fn play_level(&mut self) -> Outcome {
+        self.cursor = (self.board.width() / 2, self.board.height() / 2);
+        self.moves = 0;
+        self.keyboard.flush();
+        self.draw_screen();
+        self.clock.start();
+        let mut shown_seconds = 0;
+        let mut polls = 0;
+        loop {
+            let Some(key) = self.keyboard.read() else {
+                self.clock.sleep_ms(POLL_MS);
+                polls += 1;
+                if polls % SYNC_POLLS == 0 {
+                    self.clock.sync();
+                }
+                if self.seconds() != shown_seconds {
+                    shown_seconds = self.seconds();
+                    self.draw_counters();
+                }
+                continue;
+            };
+            let (x, y) = self.cursor;
+            let (width, height) = (self.board.width(), self.board.height());
+            let moved = match key {
+                KeyEvent::Up => (x, (y + height - 1) % height),
+                KeyEvent::Down => (x, (y + 1) % height),
+                KeyEvent::Left => ((x + width - 1) % width, y),
+                KeyEvent::Right => ((x + 1) % width, y),
+                KeyEvent::Char(' ') | KeyEvent::Enter => {
+                    self.turn(true);
+                    (x, y)
+                }
+                KeyEvent::Backspace => {
+                    self.turn(false);
+                    (x, y)
+                }
+                key if key.is_char('z') => {
+                    self.turn(false);
+                    (x, y)
+                }
+                key if key.is_char('l') => {
+                    self.board.toggle_lock(x, y);
+                    (x, y)
+                }
+                KeyEvent::Escape => return Outcome::Quit,
+                key if key.is_char('q') => return Outcome::Quit,
+                _ => (x, y),
+            };
+            self.cursor = moved;
+            self.draw_tile(x, y);
+            self.draw_tile(moved.0, moved.1);
+            if self.board.is_solved() {
+                self.clock.sync();
+                // Take the cursor off the board, so the whole network shows
+                self.cursor = (width, height);
+                self.draw_tile(moved.0, moved.1);
+                self.draw_counters();
+                return Outcome::Won {
+                    moves: self.moves,
+                    seconds: self.seconds(),
+                };
+            }
+        }
+    }
+
+    /// Turn the tile under the cursor, and redraw whatever lit up or went
+    /// dark
+    fn turn(&mut self, clockwise: bool) {
+        let (x, y) = self.cursor;
+        if self.board.rotate(x, y, clockwise) {
+            self.moves += 1;
+            self.draw_board();
+            self.draw_counters();
+        }
+    }
+
+    /// How long this level has taken so far, in seconds
+    fn seconds(&self) -> u32 {
+        (self.clock.now_ms() / 1000) as u32
+    }
+
+    /// Draw the whole game screen
+    fn draw_screen(&mut self) {
+        self.clear();
+        self.centre(0, "PIPES", true);
+        let (left, top) = self.origin();
+        let inner = usize::from(self.board.width() * TILE_COLS);
+        let bottom = top + self.board.height();
+        for (row, start, end) in [(top - 1, '┌', '┐'), (bottom, '└', '┘')] {
+            self.move_to(row, left - 1);
+            let _ = self.stdout.write_char(start);
+            for _ in 0..inner {
+                let _ = self.stdout.write_char('─');
+            }
+            let _ = self.stdout.write_char(end);
+        }
+        for row in top..bottom {
+            self.move_to(row, left - 1);
+            let _ = self.stdout.write_char('│');
+            self.move_to(row, left + self.board.width() * TILE_COLS);
+            let _ = self.stdout.write_char('│');
+        }
+        self.draw_board();
+        self.draw_counters();
+        self.show_status(
+            self.height - 1,
+            "Arrows move  Space turns  Z turns back  L locks  Q gives up",
+        );
+    }
+
+    /// Draw every tile
+    fn draw_board(&mut self) {
+        for y in 0..self.board.height() {
+            for x in 0..self.board.width() {
+                self.draw_tile(x, y);
+            }
+        }
+    }
+
+    /// Draw one tile, in colours that say if it's lit, locked, or under
+    /// the cursor
+    fn draw_tile(&mut self, x: u8, y: u8) {
+        let (left, top) = self.origin();
+        self.move_to(top + y, left + x * TILE_COLS);
+        let tile = self.board.tile(x, y);
+        let colour = if self.board.is_source(x, y) {
+            console::SgrParam::FgYellow
+        } else if self.board.is_lit(x, y) {
+            console::SgrParam::FgGreen
+        } else if board::is_end(tile) {
+            console::SgrParam::FgRed
+        } else {
+            console::SgrParam::FgWhite
+        };
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset, colour]);
+        if self.board.is_lit(x, y) {
+            console::set_sgr(&mut self.stdout, [console::SgrParam::Bold]);
+        }
+        if self.board.is_locked(x, y) {
+            console::set_sgr(&mut self.stdout, [console::SgrParam::BgBlue]);
+        }
+        if self.cursor == (x, y) {
+            console::set_sgr(&mut self.stdout, [console::SgrParam::Reverse]);
+        }
+        let _ = self
+            .stdout
+            .write_char(if tile & WEST != 0 { '─' } else { ' ' });
+        let _ = self.stdout.write_char(glyph(tile));
+        let _ = self
+            .stdout
+            .write_char(if tile & EAST != 0 { '─' } else { ' ' });
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+    }
+
+    /// Draw the move counter, the timer, and how many terminals are lit
+    fn draw_counters(&mut self) {
+        let (total, lit) = self.board.terminals();
+        let mut line = Line::new();
+        let _ = write!(line, "Moves: {:<5} Time: ", self.moves);
+        let _ = write_time(&mut line, self.seconds());
+        let _ = write!(line, "   Terminals: {:>3} of {:<3}", lit, total);
+        self.show_status(1, line.as_str());
+    }
+
+    /// Where the top left tile goes on the screen, as (column, row)
+    fn origin(&self) -> (u8, u8) {
+        let cols = self.board.width() * TILE_COLS;
+        let left = (self.width.saturating_sub(cols)) / 2;
+        let top = BOARD_ROW + 1 + (board::MAX_HEIGHT - self.board.height()) / 2;
+        (left, top)
+    }
+
+    /// Load the best scores, if there are any
+    fn load_best(&mut self) {
+        let mut buffer = [0u8; BEST_LEN];
+        let Ok(loaded) = neotron_save::load(BEST_FILE, &mut buffer) else {
+            return;
+        };
+        if loaded.version != BEST_VERSION {
+            return;
+        }
+        let mut reader = neotron_save::Reader::new(&buffer[0..loaded.len]);
+        for best in self.best.iter_mut() {
+            let (Ok(has_moves), Ok(moves), Ok(has_seconds), Ok(seconds)) =
+                (reader.bool(), reader.u32(), reader.bool(), reader.u32())
+            else {
+                return;
+            };
+            best.moves = has_moves.then_some(moves);
+            best.seconds = has_seconds.then_some(seconds);
+        }
+    }
+
+    /// Save the best scores. If we can't, they're still kept until the
+    /// user quits.
+    fn save_best(&mut self) {
+        let mut buffer = [0u8; BEST_LEN];
+        let mut writer = neotron_save::Writer::new(&mut buffer);
+        for best in self.best {
+            let _ = writer.bool(best.moves.is_some());
+            let _ = writer.u32(best.moves.unwrap_or(0));
+            let _ = writer.bool(best.seconds.is_some());
+            let _ = writer.u32(best.seconds.unwrap_or(0));
+        }
+        let _ = neotron_save::save(BEST_FILE, BEST_VERSION, writer.as_bytes());
+    }
+
+    /// Clear the screen
+    fn clear(&mut self) {
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        console::clear_screen(&mut self.stdout);
+    }
+
+    /// Move the cursor
+    fn move_to(&mut self, row: u8, col: u8) {
+        console::move_cursor(&mut self.stdout, console::Position { row, col });
+    }
+
+    /// Write some text in the middle of a row
+    fn centre(&mut self, row: u8, text: &str, title: bool) {
+        let col = (usize::from(self.width).saturating_sub(text.chars().count())) / 2;
+        self.move_to(row, col as u8);
+        if title {
+            console::set_sgr(
+                &mut self.stdout,
+                [console::SgrParam::Bold, console::SgrParam::FgYellow],
+            );
+        }
+        let _ = self.stdout.write_str(text);
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+    }
+
+    /// Write a line of text in the middle of the screen, clearing whatever
+    /// was there before
+    fn show_status(&mut self, row: u8, text: &str) {
+        self.move_to(row, 0);
+        let _ = write!(
+            self.stdout,
+            "{:^width$}",
+            text,
+            width = usize::from(self.width)
+        );
+    }
+}
+
+/// The character in the middle of a tile. The pipes out of the sides are
+/// drawn either side of it.
+///
+/// There are no line drawing characters for a line that stops in the
+/// middle, so a terminal is drawn as a line to a double bar.
+fn glyph(tile: u8) -> char {
+    const NORTH_EAST: u8 = NORTH | EAST;
+    const EAST_SOUTH: u8 = EAST | SOUTH;
+    const SOUTH_WEST: u8 = SOUTH | WEST;
+    const WEST_NORTH: u8 = WEST | NORTH;
+    const NORTH_SOUTH: u8 = NORTH | SOUTH;
+    const EAST_WEST: u8 = EAST | WEST;
+    const NOT_NORTH: u8 = EAST | SOUTH | WEST;
+    const NOT_EAST: u8 = SOUTH | WEST | NORTH;
+    const NOT_SOUTH: u8 = WEST | NORTH | EAST;
+    const NOT_WEST: u8 = NORTH | EAST | SOUTH;
+    const ALL: u8 = NORTH | EAST | SOUTH | WEST;
+    match tile {
+        NORTH => '╨',
+        EAST => '╞',
+        SOUTH => '╥',
+        WEST => '╡',
+        NORTH_EAST => '└',
+        EAST_SOUTH => '┌',
+        SOUTH_WEST => '┐',
+        WEST_NORTH => '┘',
+        NORTH_SOUTH => '│',
+        EAST_WEST => '─',
+        NOT_NORTH => '┬',
+        NOT_EAST => '┤',
+        NOT_SOUTH => '┴',
+        NOT_WEST => '├',
+        ALL => '┼',
+        _ => ' ',
+    }
+}
+
+/// Write a time in seconds as minutes and seconds, like `2:05`
+fn write_time<W: Write>(out: &mut W, seconds: u32) -> core::fmt::Result {
+    write!(out, "{}:{:02}", seconds / 60, seconds % 60)
+}
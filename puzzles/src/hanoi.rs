@@ -0,0 +1,265 @@
+//! The Tower of Hanoi
+//!
+//! Move every disk from the first peg to the last, one at a time, never
+//! putting a disk on a smaller one.
+
+use core::fmt::Write;
+
+use neotron_input::KeyEvent;
+use neotron_sdk::console::SgrParam;
+use neotron_tui::Line;
+
+use crate::{Action, Hint, Puzzle, Terminal};
+
+/// The fewest disks we play with
+const MIN_DISKS: u8 = 3;
+
+/// The most disks we play with, and the most a peg can hold
+const MAX_DISKS: u8 = 8;
+
+/// The peg every disk has to end up on
+const TARGET: u8 = 2;
+
+/// The row the pegs stand on
+const BASE_ROW: u8 = 16;
+
+/// The row a disk is lifted to while it's held
+const HELD_ROW: u8 = 5;
+
+/// The colour of each disk, smallest first
+const COLOURS: [SgrParam; MAX_DISKS as usize] = [
+    SgrParam::FgRed,
+    SgrParam::FgYellow,
+    SgrParam::FgGreen,
+    SgrParam::FgCyan,
+    SgrParam::FgBlue,
+    SgrParam::FgMagenta,
+    SgrParam::FgWhite,
+    SgrParam::FgRed,
+];
+
+/// Moving the top disk from one peg to another, counting pegs from zero
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Move {
+    /// The peg the disk comes off
+    pub from: u8,
+    /// The peg the disk goes on
+    pub to: u8,
+}
+
+/// A Tower of Hanoi puzzle
+#[derive(Debug, Clone)]
+pub struct Hanoi {
+    /// How many disks there are
+    disks: u8,
+    /// Which peg each disk is on, smallest first
+    on: [u8; MAX_DISKS as usize],
+    /// The peg the cursor is under
+    cursor: u8,
+    /// The peg whose top disk has been picked up
+    held: Option<u8>,
+}
+
+impl Hanoi {
+    /// Make a puzzle with four disks, all on the first peg
+    pub const fn new() -> Hanoi {
+        Hanoi {
+            disks: 4,
+            on: [0; MAX_DISKS as usize],
+            cursor: 0,
+            held: None,
+        }
+    }
+
+    /// The smallest disk on a peg, if it has any
+    fn top(&self, peg: u8) -> Option<u8> {
+        (0..self.disks).find(|&disk| self.on[usize::from(disk)] == peg)
+    }
+
+    /// Pick up the top disk from a peg, or put the held disk down on it
+    fn pick(&mut self, peg: u8) -> Action<Move> {
+        self.cursor = peg;
+        let Some(from) = self.held.take() else {
+            if self.top(peg).is_none() {
+                return Action::Refuse("There are no disks on that peg.");
+            }
+            self.held = Some(peg);
+            return Action::Redraw;
+        };
+        if from == peg {
+            return Action::Redraw;
+        }
+        match (self.top(from), self.top(peg)) {
+            (Some(disk), Some(under)) if under < disk => {
+                Action::Refuse("A disk can't go on top of a smaller one.")
+            }
+            _ => Action::Move(Move { from, to: peg }),
+        }
+    }
+
+    /// How many moves it takes to get disks `0..count` onto a peg
+    ///
+    /// The biggest of them has to move at most once, and only after all
+    /// the smaller ones are out of the way on the other peg.
+    fn moves_to(&self, count: u8, peg: u8) -> u32 {
+        let mut moves = 0;
+        let mut peg = peg;
+        for disk in (0..count).rev() {
+            let on = self.on[usize::from(disk)];
+            if on != peg {
+                moves += 1 << disk;
+                peg = 3 - on - peg;
+            }
+        }
+        moves
+    }
+
+    /// The first move of the shortest way to get every disk onto the target
+    fn next_move(&self) -> Option<Move> {
+        let mut peg = TARGET;
+        for disk in (0..self.disks).rev() {
+            let on = self.on[usize::from(disk)];
+            if on != peg {
+                let spare = 3 - on - peg;
+                if self.moves_to(disk, spare) == 0 {
+                    return Some(Move { from: on, to: peg });
+                }
+                peg = spare;
+            }
+        }
+        None
+    }
+
+    /// The column in the middle of a peg
+    fn peg_col(term: &Terminal, peg: u8) -> u8 {
+        let spacing = term.width() / 3;
+        spacing / 2 + spacing * peg
+    }
+
+    /// Draw a disk, centred on a column
+    fn draw_disk(term: &mut Terminal, disk: u8, row: u8, centre: u8) {
+        let half = disk + 1;
+        term.move_to(row, centre.saturating_sub(half));
+        term.colour([COLOURS[usize::from(disk)]]);
+        term.repeat('█', usize::from(half) * 2 + 1);
+    }
+}
+
+impl Default for Hanoi {
+    fn default() -> Self {
+        Hanoi::new()
+    }
+}
+
+impl Puzzle for Hanoi {
+    type Move = Move;
+
+    const NAME: &'static str = "Tower of Hanoi";
+
+    const KEYS: &'static str = "1 2 3, or arrows and Space, pick up and drop  + - change the disks";
+
+    fn start(&mut self, _rng: &mut neotron_rand::Rng) {
+        self.on = [0; MAX_DISKS as usize];
+        self.cursor = 0;
+        self.held = None;
+    }
+
+    fn draw(&self, term: &mut Terminal) {
+        let mut line = Line::new();
+        let _ = write!(line, "Move all {} disks to peg 3", self.disks);
+        term.centre(3, line.as_str());
+        for peg in 0..3 {
+            let centre = Self::peg_col(term, peg);
+            let mut row = BASE_ROW - 1;
+            for disk in (0..self.disks).rev() {
+                if self.on[usize::from(disk)] != peg {
+                    continue;
+                }
+                if self.held == Some(peg) && self.top(peg) == Some(disk) {
+                    Self::draw_disk(term, disk, HELD_ROW, centre);
+                } else {
+                    Self::draw_disk(term, disk, row, centre);
+                    row -= 1;
+                }
+            }
+            term.colour([]);
+            for row in (BASE_ROW - 1 - MAX_DISKS)..=row {
+                term.move_to(row, centre);
+                term.write("│");
+            }
+            term.move_to(BASE_ROW, centre - MAX_DISKS - 2);
+            term.repeat('▀', usize::from(MAX_DISKS) * 2 + 5);
+            term.move_to(BASE_ROW + 1, centre);
+            term.write(["1", "2", "3"][usize::from(peg)]);
+            if peg == self.cursor {
+                term.colour([SgrParam::Bold, SgrParam::FgYellow]);
+                term.move_to(BASE_ROW + 2, centre);
+                term.write("^");
+            }
+        }
+        term.colour([]);
+    }
+
+    fn key(&mut self, key: KeyEvent) -> Action<Move> {
+        match key {
+            KeyEvent::Char(ch @ '1'..='3') => self.pick(ch as u8 - b'1'),
+            KeyEvent::Char(' ') | KeyEvent::Enter => self.pick(self.cursor),
+            KeyEvent::Left => {
+                self.cursor = (self.cursor + 2) % 3;
+                Action::Redraw
+            }
+            KeyEvent::Right => {
+                self.cursor = (self.cursor + 1) % 3;
+                Action::Redraw
+            }
+            KeyEvent::Char('+' | '=') if self.disks < MAX_DISKS => {
+                self.disks += 1;
+                Action::NewPuzzle
+            }
+            KeyEvent::Char('-') if self.disks > MIN_DISKS => {
+                self.disks -= 1;
+                Action::NewPuzzle
+            }
+            KeyEvent::Char('+' | '=' | '-') => Action::Refuse("Between 3 and 8 disks, please."),
+            _ => Action::Nothing,
+        }
+    }
+
+    fn apply(&mut self, mv: Move) {
+        if let Some(disk) = self.top(mv.from) {
+            self.on[usize::from(disk)] = mv.to;
+        }
+        self.held = None;
+    }
+
+    fn undo(&mut self, mv: Move) {
+        self.apply(Move {
+            from: mv.to,
+            to: mv.from,
+        });
+    }
+
+    fn is_solved(&self) -> bool {
+        self.moves_to(self.disks, TARGET) == 0
+    }
+
+    fn hint(&self) -> Option<Hint<Move>> {
+        self.next_move().map(|next| Hint {
+            next,
+            moves_left: Some(self.moves_to(self.disks, TARGET)),
+        })
+    }
+
+    fn describe<W: Write>(&self, mv: Move, out: &mut W) -> core::fmt::Result {
+        match self.top(mv.from) {
+            Some(disk) => write!(
+                out,
+                "move disk {} from peg {} to peg {}",
+                disk + 1,
+                mv.from + 1,
+                mv.to + 1
+            ),
+            None => write!(out, "move from peg {} to peg {}", mv.from + 1, mv.to + 1),
+        }
+    }
+}
@@ -0,0 +1,66 @@
+//! The moves made so far, so they can be taken back.
+
+/// The last `N` moves, oldest first. When it's full, the oldest move is
+/// forgotten to make room.
+pub struct History<M, const N: usize> {
+    moves: [Option<M>; N],
+    /// Where the oldest move is
+    start: usize,
+    len: usize,
+}
+
+impl<M: Copy, const N: usize> History<M, N> {
+    /// Make an empty history
+    pub const fn new() -> History<M, N> {
+        History {
+            moves: [None; N],
+            start: 0,
+            len: 0,
+        }
+    }
+
+    /// How many moves can be taken back
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Are there no moves to take back?
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Remember a move
+    pub fn push(&mut self, mv: M) {
+        if N == 0 {
+            return;
+        }
+        if self.len == N {
+            self.start = (self.start + 1) % N;
+            self.len -= 1;
+        }
+        self.moves[(self.start + self.len) % N] = Some(mv);
+        self.len += 1;
+    }
+
+    /// Take back the last move
+    pub fn pop(&mut self) -> Option<M> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        self.moves[(self.start + self.len) % N].take()
+    }
+
+    /// Forget every move
+    pub fn clear(&mut self) {
+        self.moves = [None; N];
+        self.start = 0;
+        self.len = 0;
+    }
+}
+
+impl<M: Copy, const N: usize> Default for History<M, N> {
+    fn default() -> Self {
+        History::new()
+    }
+}
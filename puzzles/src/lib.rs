@@ -0,0 +1,393 @@
+//! Game logic for the logic puzzle pack - Tower of Hanoi, Lights Out and
+//! the 15 puzzle
+//!
+//! Each puzzle implements [`Puzzle`], and [`App`] does the rest: the menu,
+//! counting moves, taking them back, starting again, and asking the puzzle
+//! for a hint. Every puzzle can work out the best next move, and how many
+//! moves are left if you make it, so the hint is never a guess - except for
+//! a badly muddled 15 puzzle, which takes too long to work out, and says
+//! so.
+
+#![no_std]
+#![deny(missing_docs)]
+#![deny(unsafe_code)]
+
+use core::fmt::Write;
+
+use neotron_input::{KeyEvent, Keyboard};
+use neotron_sdk::console;
+use neotron_tui::Line;
+
+pub mod hanoi;
+pub mod history;
+pub mod lights;
+pub mod slider;
+
+use history::History;
+
+/// How many moves we can take back
+const MAX_UNDO: usize = 256;
+
+/// The row for the move counter
+const COUNTER_ROW: u8 = 2;
+
+/// The row for messages, like hints
+const MESSAGE_ROW: u8 = 20;
+
+/// The rows for the keys the player can press
+const HELP_ROW: u8 = 22;
+
+/// The keys every puzzle understands
+const SHARED_KEYS: &str = "U undoes  H hints  R restarts  N new puzzle  Q menu";
+
+/// What a key means to a puzzle
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Action<M> {
+    /// Make this move, which is allowed
+    Move(M),
+    /// Something changed that isn't a move, like the cursor moving, so
+    /// draw the puzzle again
+    Redraw,
+    /// The key wasn't allowed, and this says why
+    Refuse(&'static str),
+    /// Set up a new puzzle, as a setting changed
+    NewPuzzle,
+    /// The key means nothing to the puzzle
+    Nothing,
+}
+
+/// The best next move
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Hint<M> {
+    /// The move to make
+    pub next: M,
+    /// How many moves it takes to solve the puzzle from here, counting
+    /// this one, if we know for sure
+    pub moves_left: Option<u32>,
+}
+
+/// A puzzle the pack can play
+pub trait Puzzle: Clone {
+    /// One move. It must be possible to take it back.
+    type Move: Copy;
+
+    /// What the puzzle is called
+    const NAME: &'static str;
+
+    /// The keys the puzzle understands, for the bottom of the screen
+    const KEYS: &'static str;
+
+    /// Set up a new puzzle to solve
+    fn start(&mut self, rng: &mut neotron_rand::Rng);
+
+    /// Draw the puzzle, between the move counter and the messages
+    fn draw(&self, term: &mut Terminal);
+
+    /// Work out what a key means
+    fn key(&mut self, key: KeyEvent) -> Action<Self::Move>;
+
+    /// Make a move
+    fn apply(&mut self, mv: Self::Move);
+
+    /// Take a move back
+    fn undo(&mut self, mv: Self::Move);
+
+    /// Is it done?
+    fn is_solved(&self) -> bool;
+
+    /// The best next move, or `None` if it's already solved
+    fn hint(&self) -> Option<Hint<Self::Move>>;
+
+    /// Say what a move is, like "Move the top disk from 1 to 3"
+    fn describe<W: Write>(&self, mv: Self::Move, out: &mut W) -> core::fmt::Result;
+}
+
+/// How a puzzle went
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+struct Record {
+    /// How many were solved
+    solved: u32,
+    /// How many of those took the fewest moves they could have
+    perfect: u32,
+}
+
+/// Represents the puzzle pack application
+///
+/// You pick a puzzle from the menu, and can solve as many as you like. We
+/// remember how you did until you quit.
+pub struct App {
+    terminal: Terminal,
+    keyboard: Keyboard,
+    rng: neotron_rand::Rng,
+    hanoi: hanoi::Hanoi,
+    lights: lights::Lights,
+    slider: slider::Slider,
+    /// How each puzzle has gone, in menu order
+    records: [Record; 3],
+}
+
+impl App {
+    /// Make a new puzzle pack application.
+    ///
+    /// You can give the screen size in characters.
+    pub const fn new(width: u8, _height: u8) -> App {
+        App {
+            terminal: Terminal::new(width),
+            keyboard: Keyboard::new(),
+            rng: neotron_rand::Rng::new(0),
+            hanoi: hanoi::Hanoi::new(),
+            lights: lights::Lights::new(),
+            slider: slider::Slider::new(),
+            records: [Record {
+                solved: 0,
+                perfect: 0,
+            }; 3],
+        }
+    }
+
+    /// Show the menu and play puzzles until the user quits
+    pub fn play(&mut self) {
+        console::cursor_off(&mut self.terminal.stdout);
+        self.rng = neotron_rand::Rng::from_rtc();
+        loop {
+            self.menu();
+            let key = self.keyboard.wait();
+            if let KeyEvent::Char(ch) = key {
+                self.rng.mix(u32::from(ch));
+            }
+            match key {
+                KeyEvent::Char('1') => {
+                    let mut puzzle = self.hanoi.clone();
+                    self.run(&mut puzzle, 0);
+                    self.hanoi = puzzle;
+                }
+                KeyEvent::Char('2') => {
+                    let mut puzzle = self.lights.clone();
+                    self.run(&mut puzzle, 1);
+                    self.lights = puzzle;
+                }
+                KeyEvent::Char('3') => {
+                    let mut puzzle = self.slider.clone();
+                    self.run(&mut puzzle, 2);
+                    self.slider = puzzle;
+                }
+                KeyEvent::Escape => break,
+                key if key.is_char('q') => break,
+                _ => {}
+            }
+        }
+        let stdout = &mut self.terminal.stdout;
+        console::set_sgr(stdout, [console::SgrParam::Reset]);
+        console::clear_screen(stdout);
+        console::move_cursor(stdout, console::Position::origin());
+        console::cursor_on(stdout);
+    }
+
+    /// Draw the menu
+    fn menu(&mut self) {
+        let terminal = &mut self.terminal;
+        terminal.clear("NEOTRON LOGIC PUZZLES");
+        terminal.centre(6, "1 - Tower of Hanoi");
+        terminal.centre(7, "2 - Lights Out    ");
+        terminal.centre(8, "3 - 15 Puzzle     ");
+        terminal.centre(10, "Q - Quit          ");
+        for (idx, (name, record)) in [
+            hanoi::Hanoi::NAME,
+            lights::Lights::NAME,
+            slider::Slider::NAME,
+        ]
+        .iter()
+        .zip(self.records)
+        .enumerate()
+        {
+            let mut line = Line::new();
+            let _ = write!(
+                line,
+                "{:<15} solved {:>3}, {:>3} in the fewest moves",
+                name, record.solved, record.perfect
+            );
+            terminal.centre(13 + idx as u8, line.as_str());
+        }
+    }
+
+    /// Play one puzzle after another, until the user goes back to the menu
+    fn run<P: Puzzle>(&mut self, puzzle: &mut P, record: usize) {
+        let mut history: History<P::Move, MAX_UNDO> = History::new();
+        'puzzle: loop {
+            puzzle.start(&mut self.rng);
+            let start = puzzle.clone();
+            let fewest = puzzle.hint().and_then(|hint| hint.moves_left);
+            history.clear();
+            let mut moves: u32 = 0;
+            let mut message = Line::new();
+            loop {
+                self.draw(puzzle, moves, fewest, message.as_str());
+                message.clear();
+                let key = self.keyboard.wait();
+                match key {
+                    key if key.is_char('u') || key == KeyEvent::Backspace => {
+                        match history.pop() {
+                            Some(mv) => {
+                                puzzle.undo(mv);
+                                moves = moves.saturating_sub(1);
+                            }
+                            None => {
+                                let _ = message.write_str("There's nothing to undo.");
+                            }
+                        }
+                        continue;
+                    }
+                    key if key.is_char('h') => {
+                        if let Some(hint) = puzzle.hint() {
+                            let _ = message.write_str("Hint: ");
+                            let _ = puzzle.describe(hint.next, &mut message);
+                            let _ = match hint.moves_left {
+                                Some(left) => write!(message, " - {} moves to go", left),
+                                None => message.write_str(" - it's too muddled to be sure"),
+                            };
+                        }
+                        continue;
+                    }
+                    key if key.is_char('r') => {
+                        *puzzle = start.clone();
+                        history.clear();
+                        moves = 0;
+                        continue;
+                    }
+                    key if key.is_char('n') => continue 'puzzle,
+                    KeyEvent::Escape => return,
+                    key if key.is_char('q') => return,
+                    _ => {}
+                }
+                match puzzle.key(key) {
+                    Action::Move(mv) => {
+                        puzzle.apply(mv);
+                        history.push(mv);
+                        moves += 1;
+                    }
+                    Action::Refuse(why) => {
+                        let _ = message.write_str(why);
+                    }
+                    Action::NewPuzzle => continue 'puzzle,
+                    Action::Redraw | Action::Nothing => {}
+                }
+                if puzzle.is_solved() {
+                    break;
+                }
+            }
+
+            let perfect = fewest.is_some_and(|fewest| moves <= fewest);
+            self.records[record].solved += 1;
+            if perfect {
+                self.records[record].perfect += 1;
+            }
+            let mut message = Line::new();
+            let _ = write!(message, "Solved in {} moves", moves);
+            let _ = match fewest {
+                _ if perfect => message.write_str(" - the fewest it can be done in!"),
+                Some(fewest) => write!(message, " - it can be done in {}.", fewest),
+                None => message.write_str("."),
+            };
+            self.draw(puzzle, moves, fewest, message.as_str());
+            self.terminal
+                .status(HELP_ROW + 1, "N for a new puzzle, or Q for the menu.");
+            loop {
+                match self.keyboard.wait() {
+                    key if key.is_char('n') => break,
+                    KeyEvent::Escape => return,
+                    key if key.is_char('q') => return,
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    /// Draw a puzzle, with everything round it
+    fn draw<P: Puzzle>(&mut self, puzzle: &P, moves: u32, fewest: Option<u32>, message: &str) {
+        let terminal = &mut self.terminal;
+        terminal.clear(P::NAME);
+        let mut line = Line::new();
+        let _ = write!(line, "Moves: {}", moves);
+        if let Some(fewest) = fewest {
+            let _ = write!(line, "    Fewest possible: {}", fewest);
+        }
+        terminal.centre(COUNTER_ROW, line.as_str());
+        puzzle.draw(terminal);
+        terminal.status(MESSAGE_ROW, message);
+        terminal.status(HELP_ROW, P::KEYS);
+        terminal.status(HELP_ROW + 1, SHARED_KEYS);
+    }
+}
+
+/// Draws on the screen, for the menu and the puzzles
+pub struct Terminal {
+    stdout: neotron_sdk::File,
+    width: u8,
+}
+
+impl Terminal {
+    /// Make a terminal for a screen this many characters wide
+    const fn new(width: u8) -> Terminal {
+        Terminal {
+            stdout: neotron_sdk::stdout(),
+            width,
+        }
+    }
+
+    /// How many characters wide the screen is
+    pub fn width(&self) -> u8 {
+        self.width
+    }
+
+    /// Clear the screen and put a title at the top
+    fn clear(&mut self, title: &str) {
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        console::clear_screen(&mut self.stdout);
+        self.colour([console::SgrParam::Bold, console::SgrParam::FgYellow]);
+        self.centre(0, title);
+    }
+
+    /// Move the cursor
+    pub fn move_to(&mut self, row: u8, col: u8) {
+        console::move_cursor(&mut self.stdout, console::Position { row, col });
+    }
+
+    /// Change the colours, after going back to plain white on black
+    pub fn colour<const N: usize>(&mut self, params: [console::SgrParam; N]) {
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        console::set_sgr(&mut self.stdout, params);
+    }
+
+    /// Write some text at the cursor
+    pub fn write(&mut self, text: &str) {
+        let _ = self.stdout.write_str(text);
+    }
+
+    /// Write the same character several times
+    pub fn repeat(&mut self, ch: char, count: usize) {
+        for _ in 0..count {
+            let _ = self.stdout.write_char(ch);
+        }
+    }
+
+    /// Write some text in the middle of a row, in the current colours, then
+    /// go back to plain colours
+    pub fn centre(&mut self, row: u8, text: &str) {
+        let col = (usize::from(self.width).saturating_sub(text.chars().count())) / 2;
+        self.move_to(row, col as u8);
+        self.write(text);
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+    }
+
+    /// Write a line of text in the middle of the screen, clearing whatever
+    /// was there before
+    fn status(&mut self, row: u8, text: &str) {
+        self.move_to(row, 0);
+        let _ = write!(
+            self.stdout,
+            "{:^width$}",
+            text,
+            width = usize::from(self.width)
+        );
+    }
+}
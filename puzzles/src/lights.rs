@@ -0,0 +1,247 @@
+//! Lights Out
+//!
+//! Pressing a light switches it and the lights next to it. Switch them all
+//! off.
+//!
+//! Pressing a light twice undoes it, and the order doesn't matter, so a
+//! solution is just a set of lights to press. We find the smallest set by
+//! solving the puzzle as simultaneous equations, where one plus one is
+//! zero.
+
+use core::fmt::Write;
+
+use neotron_input::KeyEvent;
+use neotron_sdk::console::SgrParam;
+
+use crate::{Action, Hint, Puzzle, Terminal};
+
+/// How many lights across and down
+const SIZE: u8 = 5;
+
+/// How many lights there are
+const CELLS: u8 = SIZE * SIZE;
+
+/// Every light
+const ALL: u32 = (1 << CELLS) - 1;
+
+/// The row the top of the grid is drawn on
+const TOP_ROW: u8 = 5;
+
+/// How many columns each light takes, including the gap round it
+const CELL_WIDTH: u8 = 8;
+
+/// How many rows each light takes, including the gap under it
+const CELL_HEIGHT: u8 = 3;
+
+/// A Lights Out puzzle
+#[derive(Debug, Clone)]
+pub struct Lights {
+    /// The lights that are on, one bit each, row by row
+    lit: u32,
+    /// The column the cursor is in
+    x: u8,
+    /// The row the cursor is in
+    y: u8,
+}
+
+impl Lights {
+    /// Make a puzzle with every light off
+    pub const fn new() -> Lights {
+        Lights {
+            lit: 0,
+            x: SIZE / 2,
+            y: SIZE / 2,
+        }
+    }
+
+    /// The lights pressing this one switches
+    fn pattern(cell: u8) -> u32 {
+        let (x, y) = (cell % SIZE, cell / SIZE);
+        let mut bits = 1 << cell;
+        if x > 0 {
+            bits |= 1 << (cell - 1);
+        }
+        if x < SIZE - 1 {
+            bits |= 1 << (cell + 1);
+        }
+        if y > 0 {
+            bits |= 1 << (cell - SIZE);
+        }
+        if y < SIZE - 1 {
+            bits |= 1 << (cell + SIZE);
+        }
+        bits
+    }
+
+    /// The smallest set of lights to press to switch everything off
+    fn solve(lit: u32) -> Option<u32> {
+        // One equation per light: the presses next to it, and whether it
+        // is on now. As every pattern is symmetric, the presses that
+        // switch a light are its own pattern. The answer goes in the top
+        // bit.
+        const ANSWER: u32 = 1 << CELLS;
+        let mut rows = [0u32; CELLS as usize];
+        for cell in 0..CELLS {
+            let on = if lit & (1 << cell) != 0 { ANSWER } else { 0 };
+            rows[usize::from(cell)] = Self::pattern(cell) | on;
+        }
+
+        // Gauss-Jordan elimination, remembering which press each row pins
+        // down
+        let mut pivots = [None; CELLS as usize];
+        let mut free = 0u32;
+        let mut next = 0;
+        for press in 0..CELLS {
+            let bit = 1 << press;
+            let Some(found) = (next..usize::from(CELLS)).find(|&r| rows[r] & bit != 0) else {
+                free |= bit;
+                continue;
+            };
+            rows.swap(next, found);
+            let pivot = rows[next];
+            for (idx, row) in rows.iter_mut().enumerate() {
+                if idx != next && *row & bit != 0 {
+                    *row ^= pivot;
+                }
+            }
+            pivots[usize::from(press)] = Some(next);
+            next += 1;
+        }
+        if rows[next..].iter().any(|&row| row != 0) {
+            return None;
+        }
+
+        // Each way of pressing the free lights gives one solution. Try
+        // them all, and keep the shortest.
+        let mut best: Option<u32> = None;
+        let mut choice = 0u32;
+        loop {
+            let mut presses = choice;
+            for press in 0..CELLS {
+                if let Some(row) = pivots[usize::from(press)] {
+                    let row = rows[row];
+                    let wanted =
+                        u32::from(row & ANSWER != 0) ^ ((row & choice & ALL).count_ones() & 1);
+                    if wanted != 0 {
+                        presses |= 1 << press;
+                    }
+                }
+            }
+            if best.is_none_or(|best| presses.count_ones() < best.count_ones()) {
+                best = Some(presses);
+            }
+            // Step to the next subset of the free lights
+            choice = choice.wrapping_sub(free) & free;
+            if choice == 0 {
+                break;
+            }
+        }
+        best
+    }
+}
+
+impl Default for Lights {
+    fn default() -> Self {
+        Lights::new()
+    }
+}
+
+impl Puzzle for Lights {
+    type Move = u8;
+
+    const NAME: &'static str = "Lights Out";
+
+    const KEYS: &'static str = "Arrows move  Space or Enter presses a light";
+
+    fn start(&mut self, rng: &mut neotron_rand::Rng) {
+        // Pressing lights at random always makes a puzzle that can be
+        // solved, which most random patterns can't
+        self.lit = 0;
+        while self.lit == 0 {
+            for cell in 0..CELLS {
+                if rng.coin() {
+                    self.lit ^= Self::pattern(cell);
+                }
+            }
+        }
+        self.x = SIZE / 2;
+        self.y = SIZE / 2;
+    }
+
+    fn draw(&self, term: &mut Terminal) {
+        let left = (term.width() - SIZE * CELL_WIDTH) / 2;
+        for cell in 0..CELLS {
+            let (x, y) = (cell % SIZE, cell / SIZE);
+            let col = left + x * CELL_WIDTH;
+            let row = TOP_ROW + y * CELL_HEIGHT;
+            let (glyph, colour) = if self.lit & (1 << cell) != 0 {
+                ('█', SgrParam::FgYellow)
+            } else {
+                ('░', SgrParam::FgBlue)
+            };
+            for line in 0..CELL_HEIGHT - 1 {
+                term.colour([SgrParam::Bold, SgrParam::FgWhite]);
+                term.move_to(row + line, col);
+                let cursor = (x, y) == (self.x, self.y);
+                term.write(if cursor { ">" } else { " " });
+                term.colour([colour]);
+                term.repeat(glyph, usize::from(CELL_WIDTH) - 2);
+                term.colour([SgrParam::Bold, SgrParam::FgWhite]);
+                term.write(if cursor { "<" } else { " " });
+            }
+        }
+        term.colour([]);
+    }
+
+    fn key(&mut self, key: KeyEvent) -> Action<u8> {
+        match key {
+            KeyEvent::Up if self.y > 0 => self.y -= 1,
+            KeyEvent::Down if self.y < SIZE - 1 => self.y += 1,
+            KeyEvent::Left if self.x > 0 => self.x -= 1,
+            KeyEvent::Right if self.x < SIZE - 1 => self.x += 1,
+            KeyEvent::Char(' ') | KeyEvent::Enter => {
+                return Action::Move(self.y * SIZE + self.x);
+            }
+            _ => return Action::Nothing,
+        }
+        Action::Redraw
+    }
+
+    fn apply(&mut self, cell: u8) {
+        self.lit ^= Self::pattern(cell);
+    }
+
+    fn undo(&mut self, cell: u8) {
+        self.apply(cell);
+    }
+
+    fn is_solved(&self) -> bool {
+        self.lit == 0
+    }
+
+    fn hint(&self) -> Option<Hint<u8>> {
+        let presses = Self::solve(self.lit)?;
+        if presses == 0 {
+            return None;
+        }
+        // Suggest the press nearest the cursor, so following the hints
+        // doesn't send the cursor back and forth
+        let distance = |cell: u8| (cell % SIZE).abs_diff(self.x) + (cell / SIZE).abs_diff(self.y);
+        let next = (0..CELLS)
+            .filter(|&cell| presses & (1 << cell) != 0)
+            .min_by_key(|&cell| distance(cell))?;
+        Some(Hint {
+            next,
+            moves_left: Some(presses.count_ones()),
+        })
+    }
+
+    fn describe<W: Write>(&self, cell: u8, out: &mut W) -> core::fmt::Result {
+        write!(
+            out,
+            "press the light in row {}, column {}",
+            cell / SIZE + 1,
+            cell % SIZE + 1
+        )
+    }
+}
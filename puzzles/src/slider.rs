@@ -0,0 +1,385 @@
+//! The 15 puzzle
+//!
+//! Slide the tiles round the gap until they're in order, with the gap at
+//! the bottom right.
+//!
+//! The hint searches for the shortest solution with IDA*, guided by how
+//! far each tile is from home, plus two for each tile that has to get out
+//! of the way of another in its own row or column. A badly muddled board
+//! can take longer than we're prepared to wait, and then the hint just
+//! moves the tile that looks best, and says it isn't sure.
+
+use core::fmt::Write;
+
+use neotron_input::KeyEvent;
+use neotron_sdk::console::SgrParam;
+use neotron_tui::Line;
+
+use crate::{Action, Hint, Puzzle, Terminal};
+
+/// How many tiles across and down
+const SIZE: usize = 4;
+
+/// How many squares there are, including the gap
+const CELLS: usize = SIZE * SIZE;
+
+/// How many positions the hint can look at before giving up
+const SEARCH_LIMIT: u32 = 100_000;
+
+/// How many random slides each muddle setting makes
+const SHUFFLES: [u8; 4] = [20, 40, 60, 100];
+
+/// The row the top of the board is drawn on
+const TOP_ROW: u8 = 6;
+
+/// How many columns each tile takes, including the gap after it
+const TILE_WIDTH: u8 = 7;
+
+/// How many rows each tile takes
+const TILE_HEIGHT: u8 = 3;
+
+/// Which way a tile slides into the gap
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Slide {
+    /// The tile under the gap goes up
+    Up,
+    /// The tile over the gap goes down
+    Down,
+    /// The tile right of the gap goes left
+    Left,
+    /// The tile left of the gap goes right
+    Right,
+}
+
+impl Slide {
+    /// Every way there is, in the order the search tries them
+    const ALL: [Slide; 4] = [Slide::Up, Slide::Down, Slide::Left, Slide::Right];
+
+    /// The slide that puts it back
+    fn opposite(self) -> Slide {
+        match self {
+            Slide::Up => Slide::Down,
+            Slide::Down => Slide::Up,
+            Slide::Left => Slide::Right,
+            Slide::Right => Slide::Left,
+        }
+    }
+
+    /// What we call it
+    fn name(self) -> &'static str {
+        match self {
+            Slide::Up => "up",
+            Slide::Down => "down",
+            Slide::Left => "left",
+            Slide::Right => "right",
+        }
+    }
+}
+
+/// How a search went
+enum Search {
+    /// Found a solution this many moves long
+    Found(u32),
+    /// Nothing within the bound. This is the smallest estimate that went
+    /// over it.
+    Over(u32),
+    /// Ran out of time
+    GaveUp,
+}
+
+/// A 15 puzzle
+#[derive(Debug, Clone)]
+pub struct Slider {
+    /// The tile in each square, row by row, with zero for the gap
+    tiles: [u8; CELLS],
+    /// The square the gap is in
+    gap: usize,
+    /// Which of [`SHUFFLES`] to use
+    muddle: usize,
+}
+
+impl Slider {
+    /// Make a solved puzzle
+    pub const fn new() -> Slider {
+        let mut tiles = [0; CELLS];
+        let mut idx = 0;
+        while idx < CELLS - 1 {
+            tiles[idx] = idx as u8 + 1;
+            idx += 1;
+        }
+        Slider {
+            tiles,
+            gap: CELLS - 1,
+            muddle: 1,
+        }
+    }
+
+    /// The square holding the tile that would slide this way, if there is
+    /// one
+    fn source(&self, slide: Slide) -> Option<usize> {
+        let (x, y) = (self.gap % SIZE, self.gap / SIZE);
+        match slide {
+            Slide::Up if y < SIZE - 1 => Some(self.gap + SIZE),
+            Slide::Down if y > 0 => Some(self.gap - SIZE),
+            Slide::Left if x < SIZE - 1 => Some(self.gap + 1),
+            Slide::Right if x > 0 => Some(self.gap - 1),
+            _ => None,
+        }
+    }
+
+    /// Slide a tile into the gap, if it can go that way
+    fn slide(&mut self, slide: Slide) -> bool {
+        let Some(from) = self.source(slide) else {
+            return false;
+        };
+        self.tiles.swap(from, self.gap);
+        self.gap = from;
+        true
+    }
+
+    /// The fewest moves it could possibly take to solve the puzzle
+    fn estimate(&self) -> u32 {
+        let mut total = 0;
+        for (square, &tile) in self.tiles.iter().enumerate() {
+            if tile != 0 {
+                let home = usize::from(tile - 1);
+                total +=
+                    (square % SIZE).abs_diff(home % SIZE) + (square / SIZE).abs_diff(home / SIZE);
+            }
+        }
+        for line in 0..SIZE {
+            total += 2 * self.conflicts(|idx| line * SIZE + idx, |home| home / SIZE == line);
+            total += 2 * self.conflicts(|idx| idx * SIZE + line, |home| home % SIZE == line);
+        }
+        total as u32
+    }
+
+    /// How many tiles in one row or column, which all belong in it, have to
+    /// step out of it so the rest can pass each other
+    ///
+    /// That's the tiles left over from the longest run already in order.
+    fn conflicts(&self, square: impl Fn(usize) -> usize, belongs: impl Fn(usize) -> bool) -> usize {
+        let mut homes = [0; SIZE];
+        let mut count = 0;
+        for idx in 0..SIZE {
+            let tile = self.tiles[square(idx)];
+            if tile != 0 && belongs(usize::from(tile - 1)) {
+                homes[count] = usize::from(tile - 1);
+                count += 1;
+            }
+        }
+        // The longest increasing run, the slow way, as there are at most
+        // four of them
+        let mut longest = [1; SIZE];
+        for idx in 0..count {
+            for before in 0..idx {
+                if homes[before] < homes[idx] {
+                    longest[idx] = longest[idx].max(longest[before] + 1);
+                }
+            }
+        }
+        count - longest[..count].iter().copied().max().unwrap_or(0)
+    }
+
+    /// Look for a solution no longer than `bound`, carrying on from `moves`
+    /// moves in
+    fn search(&mut self, moves: u32, bound: u32, last: Option<Slide>, budget: &mut u32) -> Search {
+        let estimate = self.estimate();
+        if moves + estimate > bound {
+            return Search::Over(moves + estimate);
+        }
+        if estimate == 0 {
+            return Search::Found(moves);
+        }
+        if *budget == 0 {
+            return Search::GaveUp;
+        }
+        *budget -= 1;
+        let mut smallest = u32::MAX;
+        for slide in Slide::ALL {
+            if Some(slide.opposite()) == last || !self.slide(slide) {
+                continue;
+            }
+            let result = self.search(moves + 1, bound, Some(slide), budget);
+            self.slide(slide.opposite());
+            match result {
+                Search::Over(over) => smallest = smallest.min(over),
+                found_or_gave_up => return found_or_gave_up,
+            }
+        }
+        Search::Over(smallest)
+    }
+
+    /// The first move of the shortest solution, and how long it is, or
+    /// `None` if that takes too long to find
+    fn shortest(&self) -> Option<(Slide, u32)> {
+        let mut board = self.clone();
+        let mut budget = SEARCH_LIMIT;
+        let mut bound = self.estimate();
+        loop {
+            let mut smallest = u32::MAX;
+            for slide in Slide::ALL {
+                if !board.slide(slide) {
+                    continue;
+                }
+                let result = board.search(1, bound, Some(slide), &mut budget);
+                board.slide(slide.opposite());
+                match result {
+                    Search::Found(moves) => return Some((slide, moves)),
+                    Search::Over(over) => smallest = smallest.min(over),
+                    Search::GaveUp => return None,
+                }
+            }
+            bound = smallest;
+        }
+    }
+
+    /// The move that leaves the board looking closest to solved
+    fn greedy(&self) -> Option<Slide> {
+        let mut board = self.clone();
+        let mut best = None;
+        for slide in Slide::ALL {
+            if board.slide(slide) {
+                let estimate = board.estimate();
+                board.slide(slide.opposite());
+                if best.is_none_or(|(smallest, _)| estimate < smallest) {
+                    best = Some((estimate, slide));
+                }
+            }
+        }
+        best.map(|(_, slide)| slide)
+    }
+}
+
+impl Default for Slider {
+    fn default() -> Self {
+        Slider::new()
+    }
+}
+
+impl Puzzle for Slider {
+    type Move = Slide;
+
+    const NAME: &'static str = "15 Puzzle";
+
+    const KEYS: &'static str = "Arrows slide a tile into the gap  + - change how muddled it gets";
+
+    fn start(&mut self, rng: &mut neotron_rand::Rng) {
+        // Random slides from the solved board, never straight back, so it
+        // can always be solved
+        *self = Slider {
+            muddle: self.muddle,
+            ..Slider::new()
+        };
+        while self.is_solved() {
+            let mut last = None;
+            for _ in 0..SHUFFLES[self.muddle] {
+                let slide = loop {
+                    let slide = Slide::ALL[rng.index(4)];
+                    if Some(slide.opposite()) != last && self.source(slide).is_some() {
+                        break slide;
+                    }
+                };
+                self.slide(slide);
+                last = Some(slide);
+            }
+        }
+    }
+
+    fn draw(&self, term: &mut Terminal) {
+        let mut line = Line::new();
+        let _ = write!(line, "Shuffled with {} slides", SHUFFLES[self.muddle]);
+        term.centre(4, line.as_str());
+        let left = (term.width() - SIZE as u8 * TILE_WIDTH) / 2;
+        for (square, &tile) in self.tiles.iter().enumerate() {
+            let col = left + (square % SIZE) as u8 * TILE_WIDTH;
+            let row = TOP_ROW + (square / SIZE) as u8 * TILE_HEIGHT;
+            if tile == 0 {
+                continue;
+            }
+            let background = if usize::from(tile) == square + 1 {
+                SgrParam::BgGreen
+            } else {
+                SgrParam::BgBlue
+            };
+            term.colour([SgrParam::Bold, SgrParam::FgWhite, background]);
+            for line in 0..TILE_HEIGHT {
+                term.move_to(row + line, col);
+                if line == TILE_HEIGHT / 2 {
+                    let mut label = Line::new();
+                    let _ = write!(
+                        label,
+                        "{:^width$}",
+                        tile,
+                        width = usize::from(TILE_WIDTH) - 1
+                    );
+                    term.write(label.as_str());
+                } else {
+                    term.repeat(' ', usize::from(TILE_WIDTH) - 1);
+                }
+            }
+        }
+        term.colour([]);
+    }
+
+    fn key(&mut self, key: KeyEvent) -> Action<Slide> {
+        let slide = match key {
+            KeyEvent::Up => Slide::Up,
+            KeyEvent::Down => Slide::Down,
+            KeyEvent::Left => Slide::Left,
+            KeyEvent::Right => Slide::Right,
+            KeyEvent::Char('+' | '=') if self.muddle < SHUFFLES.len() - 1 => {
+                self.muddle += 1;
+                return Action::NewPuzzle;
+            }
+            KeyEvent::Char('-') if self.muddle > 0 => {
+                self.muddle -= 1;
+                return Action::NewPuzzle;
+            }
+            KeyEvent::Char('+' | '=' | '-') => {
+                return Action::Refuse("It doesn't go any further that way.");
+            }
+            _ => return Action::Nothing,
+        };
+        if self.source(slide).is_some() {
+            Action::Move(slide)
+        } else {
+            Action::Refuse("There's no tile to slide that way.")
+        }
+    }
+
+    fn apply(&mut self, slide: Slide) {
+        self.slide(slide);
+    }
+
+    fn undo(&mut self, slide: Slide) {
+        self.slide(slide.opposite());
+    }
+
+    fn is_solved(&self) -> bool {
+        self.estimate() == 0
+    }
+
+    fn hint(&self) -> Option<Hint<Slide>> {
+        if self.is_solved() {
+            return None;
+        }
+        match self.shortest() {
+            Some((next, moves)) => Some(Hint {
+                next,
+                moves_left: Some(moves),
+            }),
+            None => self.greedy().map(|next| Hint {
+                next,
+                moves_left: None,
+            }),
+        }
+    }
+
+    fn describe<W: Write>(&self, slide: Slide, out: &mut W) -> core::fmt::Result {
+        match self.source(slide) {
+            Some(square) => write!(out, "slide the {} {}", self.tiles[square], slide.name()),
+            None => write!(out, "slide {}", slide.name()),
+        }
+    }
+}
@@ -0,0 +1,123 @@
+//! The ASCII table
+
+use core::fmt::Write;
+
+use charmap::{LONG_NAMES, SHORT_NAMES};
+use neotron_input::KeyEvent;
+use neotron_sdk::console;
+use neotron_tui::Line;
+
+use crate::Terminal;
+
+/// The row the table starts on
+const TOP_ROW: u8 = 2;
+
+/// How many codes go in each column
+const ROWS: u8 = 16;
+
+/// How wide each column is
+const COLUMN_WIDTH: u8 = 10;
+
+/// The row we describe the selected code on
+const DETAIL_ROW: u8 = TOP_ROW + ROWS + 1;
+
+/// Show the table, until the user goes back to the menu.
+///
+/// `selected` is the code the cursor is on, and is kept for next time.
+pub fn browse(terminal: &mut Terminal, selected: &mut u8) {
+    terminal.clear(
+        "ASCII TABLE",
+        "Arrows: Move  Type a character to jump to it  Esc: Back",
+    );
+    for code in 0..128 {
+        draw_code(terminal, code, false);
+    }
+    loop {
+        draw_code(terminal, *selected, true);
+        draw_detail(terminal, *selected);
+        let key = terminal.wait_for_key();
+        draw_code(terminal, *selected, false);
+        *selected = match key {
+            KeyEvent::Up => selected.wrapping_sub(1) & 0x7F,
+            KeyEvent::Down => (*selected + 1) & 0x7F,
+            KeyEvent::Left => selected.wrapping_sub(ROWS) & 0x7F,
+            KeyEvent::Right => (*selected + ROWS) & 0x7F,
+            KeyEvent::Home => 0,
+            KeyEvent::End => 0x7F,
+            KeyEvent::Escape => return,
+            KeyEvent::Char(ch @ ' '..='~') => ch as u8,
+            _ => *selected,
+        };
+    }
+}
+
+/// Draw one entry in the table
+fn draw_code(terminal: &mut Terminal, code: u8, highlight: bool) {
+    terminal.move_to(TOP_ROW + code % ROWS, code / ROWS * COLUMN_WIDTH);
+    if highlight {
+        terminal.set_sgr([console::SgrParam::Reverse]);
+    } else if code < 0x20 || code == 0x7F {
+        terminal.set_sgr([console::SgrParam::FgCyan]);
+    }
+    let mut name = [0u8; 4];
+    terminal.write_fmt(format_args!(
+        "{:3} {:02X} {:<3}",
+        code,
+        code,
+        short_name(code, &mut name)
+    ));
+    terminal.set_sgr([console::SgrParam::Reset]);
+}
+
+/// Describe the selected code in detail
+fn draw_detail(terminal: &mut Terminal, code: u8) {
+    let mut line = Line::new();
+    let _ = write!(
+        line,
+        "Decimal {}   Hex 0x{:02X}   Octal 0o{:03o}   Binary 0b{:04b}_{:04b}",
+        code,
+        code,
+        code,
+        code >> 4,
+        code & 0x0F
+    );
+    terminal.centre(DETAIL_ROW, line.as_str());
+    let mut line = Line::new();
+    let ch = char::from(code);
+    let _ = match code {
+        0x00..=0x1F => write!(
+            line,
+            "{} - {} - type Ctrl+{}",
+            SHORT_NAMES[usize::from(code)],
+            LONG_NAMES[usize::from(code)],
+            char::from(code + 0x40)
+        ),
+        b' ' => line.write_str("Space"),
+        0x7F => line.write_str("DEL - Delete"),
+        b'0'..=b'9' => write!(line, "'{}' - the digit {}", ch, code - b'0'),
+        b'A'..=b'Z' => write!(
+            line,
+            "'{}' - capital letter, add 32 for '{}'",
+            ch,
+            char::from(code + 32)
+        ),
+        b'a'..=b'z' => write!(
+            line,
+            "'{}' - small letter, take away 32 for '{}'",
+            ch,
+            char::from(code - 32)
+        ),
+        _ => write!(line, "'{}'", ch),
+    };
+    terminal.centre(DETAIL_ROW + 2, line.as_str());
+}
+
+/// What we show for a code in the table
+fn short_name(code: u8, buffer: &mut [u8; 4]) -> &str {
+    match code {
+        0x00..=0x1F => SHORT_NAMES[usize::from(code)],
+        b' ' => "SP",
+        0x7F => "DEL",
+        _ => char::from(code).encode_utf8(buffer),
+    }
+}
@@ -0,0 +1,164 @@
+//! A chart of the colours the console can show
+
+use core::fmt::Write;
+
+use neotron_input::KeyEvent;
+use neotron_sdk::console;
+use neotron_tui::Line;
+
+use crate::Terminal;
+
+/// The row the headings go on
+const HEADER_ROW: u8 = 2;
+
+/// The row for the black background
+const CHART_ROW: u8 = HEADER_ROW + 2;
+
+/// The column for the first sample
+const CHART_COL: u8 = 12;
+
+/// How wide each sample is
+const CELL_WIDTH: u8 = 4;
+
+/// The row we describe the selected colours on
+const DETAIL_ROW: u8 = CHART_ROW + 9;
+
+/// What we call the colours, in SGR order
+const NAMES: [&str; 8] = [
+    "Black", "Red", "Green", "Yellow", "Blue", "Magenta", "Cyan", "White",
+];
+
+/// What the SDK calls the foreground colours
+const FOREGROUNDS: [console::SgrParam; 8] = [
+    console::SgrParam::FgBlack,
+    console::SgrParam::FgRed,
+    console::SgrParam::FgGreen,
+    console::SgrParam::FgYellow,
+    console::SgrParam::FgBlue,
+    console::SgrParam::FgMagenta,
+    console::SgrParam::FgCyan,
+    console::SgrParam::FgWhite,
+];
+
+/// What the SDK calls the background colours
+const BACKGROUNDS: [console::SgrParam; 8] = [
+    console::SgrParam::BgBlack,
+    console::SgrParam::BgRed,
+    console::SgrParam::BgGreen,
+    console::SgrParam::BgYellow,
+    console::SgrParam::BgBlue,
+    console::SgrParam::BgMagenta,
+    console::SgrParam::BgCyan,
+    console::SgrParam::BgWhite,
+];
+
+/// Show the chart, until the user goes back to the menu.
+///
+/// `selected` is the foreground (0 to 15, with 8 and up being bold) and
+/// background (0 to 7) the cursor is on, and is kept for next time.
+pub fn browse(terminal: &mut Terminal, selected: &mut (u8, u8)) {
+    terminal.clear("ANSI COLOUR CHART", "Arrows: Move  Esc: Back");
+    draw_chart(terminal);
+    draw_codes(terminal);
+    loop {
+        draw_sample(terminal, selected.0, selected.1, true);
+        draw_detail(terminal, selected.0, selected.1);
+        let key = terminal.wait_for_key();
+        draw_sample(terminal, selected.0, selected.1, false);
+        match key {
+            KeyEvent::Up => selected.1 = selected.1.saturating_sub(1),
+            KeyEvent::Down => selected.1 = (selected.1 + 1).min(7),
+            KeyEvent::Left => selected.0 = selected.0.saturating_sub(1),
+            KeyEvent::Right => selected.0 = (selected.0 + 1).min(15),
+            KeyEvent::Home => *selected = (7, 0),
+            KeyEvent::Escape => return,
+            _ => {}
+        }
+    }
+}
+
+/// Draw the headings and every combination of colours
+fn draw_chart(terminal: &mut Terminal) {
+    terminal.move_to(HEADER_ROW, CHART_COL);
+    terminal.write_fmt(format_args!(
+        "{:^2$}{:^2$}",
+        "Normal",
+        "Bold",
+        usize::from(CELL_WIDTH) * 8
+    ));
+    for fg in 0..16 {
+        terminal.move_to(HEADER_ROW + 1, CHART_COL + fg * CELL_WIDTH);
+        terminal.write_fmt(format_args!("{:^4}", 30 + fg % 8));
+    }
+    for bg in 0..8 {
+        terminal.move_to(CHART_ROW + bg, 1);
+        terminal.write_fmt(format_args!("{} {}", 40 + bg, NAMES[usize::from(bg)]));
+        for fg in 0..16 {
+            draw_sample(terminal, fg, bg, false);
+        }
+    }
+}
+
+/// Draw one pair of colours. The selected one has brackets round it.
+fn draw_sample(terminal: &mut Terminal, fg: u8, bg: u8, highlight: bool) {
+    terminal.move_to(CHART_ROW + bg, CHART_COL + fg * CELL_WIDTH);
+    set_colours(terminal, fg, bg);
+    terminal.write_str(if highlight { "[Aa]" } else { " Aa " });
+    terminal.set_sgr([console::SgrParam::Reset]);
+}
+
+/// Explain how to get the selected colours
+fn draw_detail(terminal: &mut Terminal, fg: u8, bg: u8) {
+    let bold = fg >= 8;
+    let fg = fg % 8;
+    let mut line = Line::new();
+    let _ = write!(
+        line,
+        "{}{} text on {}",
+        if bold { "Bright " } else { "" },
+        NAMES[usize::from(fg)],
+        NAMES[usize::from(bg)]
+    );
+    terminal.centre(DETAIL_ROW, line.as_str());
+
+    let mut line = Line::new();
+    let _ = line.write_str("Escape sequence:  ESC [ ");
+    if bold {
+        let _ = line.write_str("1 ; ");
+    }
+    let _ = write!(line, "{} ; {} m", 30 + fg, 40 + bg);
+    terminal.centre(DETAIL_ROW + 2, line.as_str());
+
+    let mut line = Line::new();
+    let _ = line.write_str("SDK:  console::set_sgr(out, [");
+    if bold {
+        let _ = line.write_str("Bold, ");
+    }
+    let _ = write!(
+        line,
+        "Fg{}, Bg{}])",
+        NAMES[usize::from(fg)],
+        NAMES[usize::from(bg)]
+    );
+    terminal.centre(DETAIL_ROW + 3, line.as_str());
+}
+
+/// List the other attributes
+fn draw_codes(terminal: &mut Terminal) {
+    terminal.centre(
+        DETAIL_ROW + 6,
+        "Other codes:  0 Reset   1 Bold   7 Reverse   39 Default text   49 Default back",
+    );
+}
+
+/// Switch to a pair of colours from the chart
+fn set_colours(terminal: &mut Terminal, fg: u8, bg: u8) {
+    terminal.set_sgr([
+        console::SgrParam::Reset,
+        FOREGROUNDS[usize::from(fg % 8)],
+        BACKGROUNDS[usize::from(bg)],
+    ]);
+    if fg >= 8 {
+        terminal.set_sgr([console::SgrParam::Bold]);
+    }
+}
@@ -0,0 +1,186 @@
+//! Application logic for the reference tables
+
+#![no_std]
+#![deny(missing_docs)]
+#![deny(unsafe_code)]
+
+use core::fmt::Write;
+
+use neotron_input::{KeyEvent, Keyboard};
+use neotron_sdk::console;
+
+pub mod ascii;
+pub mod colours;
+pub mod menu;
+pub mod periodic;
+
+use menu::Menu;
+
+/// The tables on the main menu
+const TABLES: &[&str] = &["ASCII table", "Periodic table", "ANSI colour chart", "Quit"];
+
+/// Represents the reference tables application
+pub struct App {
+    terminal: Terminal,
+    /// Where we were in each table, so we go back to the same place
+    menu: usize,
+    ascii: u8,
+    element: usize,
+    colour: (u8, u8),
+}
+
+impl App {
+    /// Make a new reference tables application.
+    ///
+    /// You can give the screen size in characters.
+    pub const fn new(width: u8, height: u8) -> App {
+        App {
+            terminal: Terminal::new(width, height),
+            menu: 0,
+            ascii: b'A',
+            element: 0,
+            colour: (7, 0),
+        }
+    }
+
+    /// Show the menu, and the tables, until the user quits
+    pub fn run(&mut self) {
+        console::cursor_off(&mut self.terminal.stdout);
+        loop {
+            let mut menu = Menu::new("NEOTRON REFERENCE TABLES", TABLES);
+            menu.select(self.menu);
+            let Some(choice) = menu.run(&mut self.terminal) else {
+                break;
+            };
+            self.menu = choice;
+            match choice {
+                0 => ascii::browse(&mut self.terminal, &mut self.ascii),
+                1 => periodic::browse(&mut self.terminal, &mut self.element),
+                2 => colours::browse(&mut self.terminal, &mut self.colour),
+                _ => break,
+            }
+        }
+        let stdout = &mut self.terminal.stdout;
+        console::set_sgr(stdout, [console::SgrParam::Reset]);
+        console::clear_screen(stdout);
+        console::move_cursor(stdout, console::Position::origin());
+        console::cursor_on(stdout);
+    }
+}
+
+/// The screen and keyboard, shared by the tables
+pub struct Terminal {
+    stdout: neotron_sdk::File,
+    keyboard: Keyboard,
+    width: u8,
+    height: u8,
+}
+
+impl Terminal {
+    /// Make a new terminal
+    const fn new(width: u8, height: u8) -> Terminal {
+        Terminal {
+            stdout: neotron_sdk::stdout(),
+            keyboard: Keyboard::new(),
+            width,
+            height,
+        }
+    }
+
+    /// Clear the screen, and put a title bar at the top and a list of keys
+    /// at the bottom
+    pub fn clear(&mut self, title: &str, help: &str) {
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        console::clear_screen(&mut self.stdout);
+        console::set_sgr(
+            &mut self.stdout,
+            [
+                console::SgrParam::Bold,
+                console::SgrParam::FgWhite,
+                console::SgrParam::BgBlue,
+            ],
+        );
+        self.centre(0, title);
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        self.centre(self.height - 2, help);
+    }
+
+    /// Write some text in the middle of a row, clearing whatever was there
+    pub fn centre(&mut self, row: u8, text: &str) {
+        self.move_to(row, 0);
+        let _ = write!(
+            self.stdout,
+            "{}",
+            neotron_text::centred(text, usize::from(self.width))
+        );
+    }
+
+    /// Move the cursor
+    pub fn move_to(&mut self, row: u8, col: u8) {
+        console::move_cursor(&mut self.stdout, console::Position { row, col });
+    }
+
+    /// Blank out a row
+    pub fn clear_row(&mut self, row: u8) {
+        self.move_to(row, 0);
+        let _ = neotron_text::write_repeated(&mut self.stdout, ' ', usize::from(self.width));
+    }
+
+    /// Change the colours
+    pub fn set_sgr<T>(&mut self, params: T)
+    where
+        T: IntoIterator<Item = console::SgrParam>,
+    {
+        console::set_sgr(&mut self.stdout, params);
+    }
+
+    /// Write some text where the cursor is
+    pub fn write_str(&mut self, text: &str) {
+        let _ = self.stdout.write_str(text);
+    }
+
+    /// Write some formatted text where the cursor is
+    pub fn write_fmt(&mut self, args: core::fmt::Arguments) {
+        let _ = self.stdout.write_fmt(args);
+    }
+
+    /// Read a line of text on the row above the list of keys.
+    ///
+    /// Returns `None` if the user pressed Escape.
+    pub fn prompt<'a>(&mut self, prompt: &str, input: &'a mut [u8]) -> Option<&'a str> {
+        let row = self.height - 3;
+        self.clear_row(row);
+        self.move_to(row, 2);
+        self.write_str(prompt);
+        console::cursor_on(&mut self.stdout);
+        let mut len = 0;
+        let done = loop {
+            match self.keyboard.wait() {
+                KeyEvent::Enter => break true,
+                KeyEvent::Escape => break false,
+                KeyEvent::Backspace if len > 0 => {
+                    len -= 1;
+                    self.write_str("\u{0008} \u{0008}");
+                }
+                KeyEvent::Char(key) if key.is_ascii() && len < input.len() => {
+                    input[len] = key as u8;
+                    len += 1;
+                    let _ = self.stdout.write_char(key);
+                }
+                _ => {}
+            }
+        };
+        console::cursor_off(&mut self.stdout);
+        self.clear_row(row);
+        if done {
+            core::str::from_utf8(&input[0..len]).ok()
+        } else {
+            None
+        }
+    }
+
+    /// Spin until a key is pressed
+    pub fn wait_for_key(&mut self) -> KeyEvent {
+        self.keyboard.wait()
+    }
+}
@@ -0,0 +1,90 @@
+//! A menu you can move around with the arrow keys
+
+use neotron_input::KeyEvent;
+use neotron_sdk::console;
+
+use crate::Terminal;
+
+/// The row the first item goes on
+const FIRST_ROW: u8 = 6;
+
+/// A list of things to pick from
+pub struct Menu<'a> {
+    title: &'a str,
+    items: &'a [&'a str],
+    selected: usize,
+}
+
+impl<'a> Menu<'a> {
+    /// Make a new menu, with the first item selected
+    pub fn new(title: &'a str, items: &'a [&'a str]) -> Menu<'a> {
+        Menu {
+            title,
+            items,
+            selected: 0,
+        }
+    }
+
+    /// Choose which item is selected
+    pub fn select(&mut self, item: usize) {
+        self.selected = item.min(self.items.len().saturating_sub(1));
+    }
+
+    /// Show the menu until something is picked.
+    ///
+    /// Returns `None` if the user pressed Escape.
+    pub fn run(&mut self, terminal: &mut Terminal) -> Option<usize> {
+        terminal.clear(
+            self.title,
+            "Up/Down: Choose  Enter: Open  1-9: Open that one  Esc: Quit",
+        );
+        loop {
+            self.draw(terminal);
+            match terminal.wait_for_key() {
+                KeyEvent::Up => self.selected = self.selected.saturating_sub(1),
+                KeyEvent::Down => self.select(self.selected + 1),
+                KeyEvent::Home => self.selected = 0,
+                KeyEvent::End => self.select(self.items.len()),
+                KeyEvent::Char(' ') | KeyEvent::Enter => return Some(self.selected),
+                KeyEvent::Char(digit @ '1'..='9') => {
+                    let item = digit as usize - '1' as usize;
+                    if item < self.items.len() {
+                        return Some(item);
+                    }
+                }
+                KeyEvent::Escape | KeyEvent::Char('q' | 'Q') => return None,
+                _ => {}
+            }
+        }
+    }
+
+    /// Draw the items, with a box round them
+    fn draw(&self, terminal: &mut Terminal) {
+        let inner = self.items.iter().map(|item| item.len()).max().unwrap_or(0) + 6;
+        let col = (usize::from(terminal.width).saturating_sub(inner + 2) / 2) as u8;
+        terminal.move_to(FIRST_ROW - 1, col);
+        write_edge(terminal, '┌', '┐', inner);
+        for (idx, item) in self.items.iter().enumerate() {
+            terminal.move_to(FIRST_ROW + idx as u8, col);
+            terminal.write_str("│");
+            if idx == self.selected {
+                terminal.set_sgr([console::SgrParam::Reverse]);
+            }
+            terminal.write_fmt(format_args!(" {}. {:<2$} ", idx + 1, item, inner - 5));
+            terminal.set_sgr([console::SgrParam::Reset]);
+            terminal.write_str("│");
+        }
+        terminal.move_to(FIRST_ROW + self.items.len() as u8, col);
+        write_edge(terminal, '└', '┘', inner);
+    }
+}
+
+/// Draw the top or bottom of a box
+fn write_edge(terminal: &mut Terminal, left: char, right: char, inner: usize) {
+    let mut buffer = [0u8; 4];
+    terminal.write_str(left.encode_utf8(&mut buffer));
+    for _ in 0..inner {
+        terminal.write_str("─");
+    }
+    terminal.write_str(right.encode_utf8(&mut buffer));
+}
@@ -0,0 +1,473 @@
+//! The periodic table of the elements
+
+use core::fmt::Write;
+
+use neotron_input::KeyEvent;
+use neotron_sdk::console;
+use neotron_tui::Line;
+
+use crate::Terminal;
+
+/// The row the group numbers go on
+const HEADER_ROW: u8 = 1;
+
+/// The row period 1 goes on
+const TABLE_ROW: u8 = HEADER_ROW + 1;
+
+/// The column group 1 goes in
+const TABLE_COL: u8 = 4;
+
+/// How wide each element is in the table
+const CELL_WIDTH: u8 = 4;
+
+/// The row we describe the selected element on
+const DETAIL_ROW: u8 = TABLE_ROW + 11;
+
+/// The row the key to the colours starts on
+const LEGEND_ROW: u8 = DETAIL_ROW + 4;
+
+/// How many categories go on each row of the key
+const LEGEND_COLUMNS: usize = 3;
+
+/// The grid row for the lanthanides, which sit under the main table
+const LANTHANIDE_ROW: u8 = 8;
+
+/// The grid row for the actinides
+const ACTINIDE_ROW: u8 = 9;
+
+/// The kinds of element, which we colour the table by
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Category {
+    /// Group 1, apart from hydrogen
+    AlkaliMetal,
+    /// Group 2
+    AlkalineEarthMetal,
+    /// Groups 3 to 12
+    TransitionMetal,
+    /// The metals to the right of the transition metals
+    PostTransitionMetal,
+    /// The elements between the metals and the non-metals
+    Metalloid,
+    /// The reactive non-metals, apart from the halogens
+    Nonmetal,
+    /// Group 17
+    Halogen,
+    /// Group 18
+    NobleGas,
+    /// Lanthanum to lutetium
+    Lanthanide,
+    /// Actinium to lawrencium
+    Actinide,
+    /// Made a few atoms at a time, so nobody is sure
+    Unknown,
+}
+
+impl Category {
+    /// Every category, in the order we show the key
+    const ALL: [Category; 11] = [
+        Category::AlkaliMetal,
+        Category::AlkalineEarthMetal,
+        Category::TransitionMetal,
+        Category::PostTransitionMetal,
+        Category::Metalloid,
+        Category::Nonmetal,
+        Category::Halogen,
+        Category::NobleGas,
+        Category::Lanthanide,
+        Category::Actinide,
+        Category::Unknown,
+    ];
+
+    /// What we call this category
+    pub fn name(self) -> &'static str {
+        match self {
+            Category::AlkaliMetal => "Alkali metal",
+            Category::AlkalineEarthMetal => "Alkaline earth metal",
+            Category::TransitionMetal => "Transition metal",
+            Category::PostTransitionMetal => "Post-transition metal",
+            Category::Metalloid => "Metalloid",
+            Category::Nonmetal => "Non-metal",
+            Category::Halogen => "Halogen",
+            Category::NobleGas => "Noble gas",
+            Category::Lanthanide => "Lanthanide",
+            Category::Actinide => "Actinide",
+            Category::Unknown => "Unknown properties",
+        }
+    }
+
+    /// The colour we draw this category in, and whether it is bright
+    fn colour(self) -> (console::SgrParam, bool) {
+        match self {
+            Category::AlkaliMetal => (console::SgrParam::FgRed, true),
+            Category::AlkalineEarthMetal => (console::SgrParam::FgYellow, false),
+            Category::TransitionMetal => (console::SgrParam::FgCyan, false),
+            Category::PostTransitionMetal => (console::SgrParam::FgGreen, false),
+            Category::Metalloid => (console::SgrParam::FgYellow, true),
+            Category::Nonmetal => (console::SgrParam::FgGreen, true),
+            Category::Halogen => (console::SgrParam::FgCyan, true),
+            Category::NobleGas => (console::SgrParam::FgMagenta, true),
+            Category::Lanthanide => (console::SgrParam::FgBlue, true),
+            Category::Actinide => (console::SgrParam::FgMagenta, false),
+            Category::Unknown => (console::SgrParam::FgWhite, false),
+        }
+    }
+}
+
+/// One chemical element
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Element {
+    /// The chemical symbol
+    pub symbol: &'static str,
+    /// The English name
+    pub name: &'static str,
+    /// The standard atomic weight. For elements with no stable isotopes, this
+    /// is the mass number of the longest-lived one, in brackets.
+    pub mass: &'static str,
+    /// What kind of element it is
+    pub category: Category,
+}
+
+impl Element {
+    /// Make a new element
+    const fn new(
+        symbol: &'static str,
+        name: &'static str,
+        mass: &'static str,
+        category: Category,
+    ) -> Element {
+        Element {
+            symbol,
+            name,
+            mass,
+            category,
+        }
+    }
+}
+
+/// All the elements, in order of atomic number
+pub static ELEMENTS: [Element; 118] = [
+    Element::new("H", "Hydrogen", "1.008", Category::Nonmetal),
+    Element::new("He", "Helium", "4.0026", Category::NobleGas),
+    Element::new("Li", "Lithium", "6.94", Category::AlkaliMetal),
+    Element::new("Be", "Beryllium", "9.0122", Category::AlkalineEarthMetal),
+    Element::new("B", "Boron", "10.81", Category::Metalloid),
+    Element::new("C", "Carbon", "12.011", Category::Nonmetal),
+    Element::new("N", "Nitrogen", "14.007", Category::Nonmetal),
+    Element::new("O", "Oxygen", "15.999", Category::Nonmetal),
+    Element::new("F", "Fluorine", "18.998", Category::Halogen),
+    Element::new("Ne", "Neon", "20.180", Category::NobleGas),
+    Element::new("Na", "Sodium", "22.990", Category::AlkaliMetal),
+    Element::new("Mg", "Magnesium", "24.305", Category::AlkalineEarthMetal),
+    Element::new("Al", "Aluminium", "26.982", Category::PostTransitionMetal),
+    Element::new("Si", "Silicon", "28.085", Category::Metalloid),
+    Element::new("P", "Phosphorus", "30.974", Category::Nonmetal),
+    Element::new("S", "Sulfur", "32.06", Category::Nonmetal),
+    Element::new("Cl", "Chlorine", "35.45", Category::Halogen),
+    Element::new("Ar", "Argon", "39.95", Category::NobleGas),
+    Element::new("K", "Potassium", "39.098", Category::AlkaliMetal),
+    Element::new("Ca", "Calcium", "40.078", Category::AlkalineEarthMetal),
+    Element::new("Sc", "Scandium", "44.956", Category::TransitionMetal),
+    Element::new("Ti", "Titanium", "47.867", Category::TransitionMetal),
+    Element::new("V", "Vanadium", "50.942", Category::TransitionMetal),
+    Element::new("Cr", "Chromium", "51.996", Category::TransitionMetal),
+    Element::new("Mn", "Manganese", "54.938", Category::TransitionMetal),
+    Element::new("Fe", "Iron", "55.845", Category::TransitionMetal),
+    Element::new("Co", "Cobalt", "58.933", Category::TransitionMetal),
+    Element::new("Ni", "Nickel", "58.693", Category::TransitionMetal),
+    Element::new("Cu", "Copper", "63.546", Category::TransitionMetal),
+    Element::new("Zn", "Zinc", "65.38", Category::TransitionMetal),
+    Element::new("Ga", "Gallium", "69.723", Category::PostTransitionMetal),
+    Element::new("Ge", "Germanium", "72.630", Category::Metalloid),
+    Element::new("As", "Arsenic", "74.922", Category::Metalloid),
+    Element::new("Se", "Selenium", "78.971", Category::Nonmetal),
+    Element::new("Br", "Bromine", "79.904", Category::Halogen),
+    Element::new("Kr", "Krypton", "83.798", Category::NobleGas),
+    Element::new("Rb", "Rubidium", "85.468", Category::AlkaliMetal),
+    Element::new("Sr", "Strontium", "87.62", Category::AlkalineEarthMetal),
+    Element::new("Y", "Yttrium", "88.906", Category::TransitionMetal),
+    Element::new("Zr", "Zirconium", "91.224", Category::TransitionMetal),
+    Element::new("Nb", "Niobium", "92.906", Category::TransitionMetal),
+    Element::new("Mo", "Molybdenum", "95.95", Category::TransitionMetal),
+    Element::new("Tc", "Technetium", "[98]", Category::TransitionMetal),
+    Element::new("Ru", "Ruthenium", "101.07", Category::TransitionMetal),
+    Element::new("Rh", "Rhodium", "102.91", Category::TransitionMetal),
+    Element::new("Pd", "Palladium", "106.42", Category::TransitionMetal),
+    Element::new("Ag", "Silver", "107.87", Category::TransitionMetal),
+    Element::new("Cd", "Cadmium", "112.41", Category::TransitionMetal),
+    Element::new("In", "Indium", "114.82", Category::PostTransitionMetal),
+    Element::new("Sn", "Tin", "118.71", Category::PostTransitionMetal),
+    Element::new("Sb", "Antimony", "121.76", Category::Metalloid),
+    Element::new("Te", "Tellurium", "127.60", Category::Metalloid),
+    Element::new("I", "Iodine", "126.90", Category::Halogen),
+    Element::new("Xe", "Xenon", "131.29", Category::NobleGas),
+    Element::new("Cs", "Caesium", "132.91", Category::AlkaliMetal),
+    Element::new("Ba", "Barium", "137.33", Category::AlkalineEarthMetal),
+    Element::new("La", "Lanthanum", "138.91", Category::Lanthanide),
+    Element::new("Ce", "Cerium", "140.12", Category::Lanthanide),
+    Element::new("Pr", "Praseodymium", "140.91", Category::Lanthanide),
+    Element::new("Nd", "Neodymium", "144.24", Category::Lanthanide),
+    Element::new("Pm", "Promethium", "[145]", Category::Lanthanide),
+    Element::new("Sm", "Samarium", "150.36", Category::Lanthanide),
+    Element::new("Eu", "Europium", "151.96", Category::Lanthanide),
+    Element::new("Gd", "Gadolinium", "157.25", Category::Lanthanide),
+    Element::new("Tb", "Terbium", "158.93", Category::Lanthanide),
+    Element::new("Dy", "Dysprosium", "162.50", Category::Lanthanide),
+    Element::new("Ho", "Holmium", "164.93", Category::Lanthanide),
+    Element::new("Er", "Erbium", "167.26", Category::Lanthanide),
+    Element::new("Tm", "Thulium", "168.93", Category::Lanthanide),
+    Element::new("Yb", "Ytterbium", "173.05", Category::Lanthanide),
+    Element::new("Lu", "Lutetium", "174.97", Category::Lanthanide),
+    Element::new("Hf", "Hafnium", "178.49", Category::TransitionMetal),
+    Element::new("Ta", "Tantalum", "180.95", Category::TransitionMetal),
+    Element::new("W", "Tungsten", "183.84", Category::TransitionMetal),
+    Element::new("Re", "Rhenium", "186.21", Category::TransitionMetal),
+    Element::new("Os", "Osmium", "190.23", Category::TransitionMetal),
+    Element::new("Ir", "Iridium", "192.22", Category::TransitionMetal),
+    Element::new("Pt", "Platinum", "195.08", Category::TransitionMetal),
+    Element::new("Au", "Gold", "196.97", Category::TransitionMetal),
+    Element::new("Hg", "Mercury", "200.59", Category::TransitionMetal),
+    Element::new("Tl", "Thallium", "204.38", Category::PostTransitionMetal),
+    Element::new("Pb", "Lead", "207.2", Category::PostTransitionMetal),
+    Element::new("Bi", "Bismuth", "208.98", Category::PostTransitionMetal),
+    Element::new("Po", "Polonium", "[209]", Category::PostTransitionMetal),
+    Element::new("At", "Astatine", "[210]", Category::Halogen),
+    Element::new("Rn", "Radon", "[222]", Category::NobleGas),
+    Element::new("Fr", "Francium", "[223]", Category::AlkaliMetal),
+    Element::new("Ra", "Radium", "[226]", Category::AlkalineEarthMetal),
+    Element::new("Ac", "Actinium", "[227]", Category::Actinide),
+    Element::new("Th", "Thorium", "232.04", Category::Actinide),
+    Element::new("Pa", "Protactinium", "231.04", Category::Actinide),
+    Element::new("U", "Uranium", "238.03", Category::Actinide),
+    Element::new("Np", "Neptunium", "[237]", Category::Actinide),
+    Element::new("Pu", "Plutonium", "[244]", Category::Actinide),
+    Element::new("Am", "Americium", "[243]", Category::Actinide),
+    Element::new("Cm", "Curium", "[247]", Category::Actinide),
+    Element::new("Bk", "Berkelium", "[247]", Category::Actinide),
+    Element::new("Cf", "Californium", "[251]", Category::Actinide),
+    Element::new("Es", "Einsteinium", "[252]", Category::Actinide),
+    Element::new("Fm", "Fermium", "[257]", Category::Actinide),
+    Element::new("Md", "Mendelevium", "[258]", Category::Actinide),
+    Element::new("No", "Nobelium", "[259]", Category::Actinide),
+    Element::new("Lr", "Lawrencium", "[266]", Category::Actinide),
+    Element::new("Rf", "Rutherfordium", "[267]", Category::TransitionMetal),
+    Element::new("Db", "Dubnium", "[268]", Category::TransitionMetal),
+    Element::new("Sg", "Seaborgium", "[269]", Category::TransitionMetal),
+    Element::new("Bh", "Bohrium", "[270]", Category::TransitionMetal),
+    Element::new("Hs", "Hassium", "[269]", Category::TransitionMetal),
+    Element::new("Mt", "Meitnerium", "[278]", Category::Unknown),
+    Element::new("Ds", "Darmstadtium", "[281]", Category::Unknown),
+    Element::new("Rg", "Roentgenium", "[282]", Category::Unknown),
+    Element::new("Cn", "Copernicium", "[285]", Category::Unknown),
+    Element::new("Nh", "Nihonium", "[286]", Category::Unknown),
+    Element::new("Fl", "Flerovium", "[289]", Category::Unknown),
+    Element::new("Mc", "Moscovium", "[290]", Category::Unknown),
+    Element::new("Lv", "Livermorium", "[293]", Category::Unknown),
+    Element::new("Ts", "Tennessine", "[294]", Category::Unknown),
+    Element::new("Og", "Oganesson", "[294]", Category::Unknown),
+];
+
+/// Show the table, until the user goes back to the menu.
+///
+/// `selected` is the index of the element the cursor is on, and is kept for
+/// next time.
+pub fn browse(terminal: &mut Terminal, selected: &mut usize) {
+    terminal.clear(
+        "PERIODIC TABLE OF THE ELEMENTS",
+        "Arrows: Move  /: Find by name, symbol or number  Esc: Back",
+    );
+    draw_table(terminal);
+    draw_legend(terminal);
+    loop {
+        draw_element(terminal, *selected, true);
+        draw_detail(terminal, *selected);
+        let key = terminal.wait_for_key();
+        draw_element(terminal, *selected, false);
+        match key {
+            KeyEvent::Up => *selected = step(*selected, -1, 0),
+            KeyEvent::Down => *selected = step(*selected, 1, 0),
+            KeyEvent::Left => *selected = step(*selected, 0, -1),
+            KeyEvent::Right => *selected = step(*selected, 0, 1),
+            KeyEvent::Home => *selected = 0,
+            KeyEvent::End => *selected = ELEMENTS.len() - 1,
+            KeyEvent::Char('/') => {
+                let mut input = [0u8; 16];
+                let Some(text) = terminal.prompt("Find: ", &mut input) else {
+                    continue;
+                };
+                match find(text) {
+                    Some(index) => *selected = index,
+                    None => terminal.centre(terminal.height - 3, "No element like that"),
+                }
+            }
+            KeyEvent::Escape => return,
+            _ => {}
+        }
+    }
+}
+
+/// Where an element goes in the table, as (grid row, group - 1)
+fn position(index: usize) -> (u8, u8) {
+    let number = index as u8 + 1;
+    match number {
+        1 => (0, 0),
+        2 => (0, 17),
+        3..=4 => (1, number - 3),
+        5..=10 => (1, number - 5 + 12),
+        11..=12 => (2, number - 11),
+        13..=18 => (2, number - 13 + 12),
+        19..=36 => (3, number - 19),
+        37..=54 => (4, number - 37),
+        55..=56 => (5, number - 55),
+        57..=71 => (LANTHANIDE_ROW, number - 57 + 2),
+        72..=86 => (5, number - 72 + 3),
+        87..=88 => (6, number - 87),
+        89..=103 => (ACTINIDE_ROW, number - 89 + 2),
+        _ => (6, number - 104 + 3),
+    }
+}
+
+/// Find the element at a place in the grid
+fn element_at(row: u8, col: u8) -> Option<usize> {
+    (0..ELEMENTS.len()).find(|&index| position(index) == (row, col))
+}
+
+/// Move from an element, in a direction, to the next element we find
+fn step(index: usize, rows: i8, cols: i8) -> usize {
+    let (mut row, mut col) = position(index);
+    loop {
+        let (Some(next_row), Some(next_col)) = (
+            row.checked_add_signed(rows)
+                .filter(|&row| row <= ACTINIDE_ROW),
+            col.checked_add_signed(cols).filter(|&col| col < 18),
+        ) else {
+            // Fell off the edge, so stay put
+            return index;
+        };
+        if let Some(found) = element_at(next_row, next_col) {
+            return found;
+        }
+        row = next_row;
+        col = next_col;
+    }
+}
+
+/// Look up an element by atomic number, symbol or the start of its name
+fn find(text: &str) -> Option<usize> {
+    let text = text.trim();
+    if let Ok(number) = text.parse::<usize>() {
+        return (1..=ELEMENTS.len()).contains(&number).then(|| number - 1);
+    }
+    if text.is_empty() {
+        return None;
+    }
+    ELEMENTS
+        .iter()
+        .position(|element| element.symbol.eq_ignore_ascii_case(text))
+        .or_else(|| {
+            ELEMENTS.iter().position(|element| {
+                element
+                    .name
+                    .get(0..text.len())
+                    .is_some_and(|start| start.eq_ignore_ascii_case(text))
+            })
+        })
+}
+
+/// The screen row for a grid row. Grid row 7 is the gap above the
+/// lanthanides.
+fn screen_row(row: u8) -> u8 {
+    TABLE_ROW + row
+}
+
+/// Draw the whole table, with the group and period numbers
+fn draw_table(terminal: &mut Terminal) {
+    for group in 0..18 {
+        terminal.move_to(HEADER_ROW, TABLE_COL + group * CELL_WIDTH);
+        terminal.write_fmt(format_args!("{:^4}", group + 1));
+    }
+    for period in 0..7 {
+        terminal.move_to(screen_row(period), 1);
+        terminal.write_fmt(format_args!("{}", period + 1));
+    }
+    for index in 0..ELEMENTS.len() {
+        draw_element(terminal, index, false);
+    }
+    // Show where the lanthanides and actinides belong
+    for (row, marker) in [
+        (5, "*"),
+        (6, "**"),
+        (LANTHANIDE_ROW, "*"),
+        (ACTINIDE_ROW, "**"),
+    ] {
+        let col = if row >= LANTHANIDE_ROW { 1 } else { 2 };
+        terminal.move_to(screen_row(row), TABLE_COL + col * CELL_WIDTH);
+        terminal.write_fmt(format_args!(" {:<3}", marker));
+    }
+}
+
+/// Draw one element's symbol in the table
+fn draw_element(terminal: &mut Terminal, index: usize, highlight: bool) {
+    let element = &ELEMENTS[index];
+    let (row, col) = position(index);
+    terminal.move_to(screen_row(row), TABLE_COL + col * CELL_WIDTH);
+    set_colour(terminal, element.category);
+    if highlight {
+        terminal.set_sgr([console::SgrParam::Reverse]);
+    }
+    terminal.write_fmt(format_args!(" {:<3}", element.symbol));
+    terminal.set_sgr([console::SgrParam::Reset]);
+}
+
+/// Describe the selected element
+fn draw_detail(terminal: &mut Terminal, index: usize) {
+    let element = &ELEMENTS[index];
+    let (row, col) = position(index);
+    for line in 0..3 {
+        terminal.clear_row(DETAIL_ROW + line);
+    }
+    terminal.move_to(DETAIL_ROW, TABLE_COL);
+    set_colour(terminal, element.category);
+    terminal.write_fmt(format_args!(
+        "{}  {}  {}",
+        index + 1,
+        element.symbol,
+        element.name
+    ));
+    terminal.set_sgr([console::SgrParam::Reset]);
+    let mut line = Line::new();
+    let _ = write!(line, "Atomic mass {}   ", element.mass);
+    let _ = if row >= LANTHANIDE_ROW {
+        write!(line, "f-block, period {}", row - LANTHANIDE_ROW + 6)
+    } else {
+        write!(line, "Group {}, period {}", col + 1, row + 1)
+    };
+    terminal.move_to(DETAIL_ROW + 1, TABLE_COL);
+    terminal.write_str(line.as_str());
+    terminal.move_to(DETAIL_ROW + 2, TABLE_COL);
+    terminal.write_str(element.category.name());
+    if element.mass.starts_with('[') {
+        terminal.write_str(" - mass of the longest-lived isotope");
+    }
+}
+
+/// Draw the key to the colours
+fn draw_legend(terminal: &mut Terminal) {
+    for (idx, category) in Category::ALL.iter().enumerate() {
+        let row = LEGEND_ROW + (idx / LEGEND_COLUMNS) as u8;
+        let col = TABLE_COL + (idx % LEGEND_COLUMNS) as u8 * 25;
+        terminal.move_to(row, col);
+        set_colour(terminal, *category);
+        terminal.write_str("■ ");
+        terminal.write_str(category.name());
+        terminal.set_sgr([console::SgrParam::Reset]);
+    }
+}
+
+/// Switch to the colour for a category
+fn set_colour(terminal: &mut Terminal, category: Category) {
+    let (colour, bright) = category.colour();
+    terminal.set_sgr([colour]);
+    if bright {
+        terminal.set_sgr([console::SgrParam::Bold]);
+    }
+}
@@ -0,0 +1,111 @@
+//! A logo, bouncing around the screen.
+//!
+//! It changes colour every time it hits the edge.
+
+use neotron_screen::{Attr, Colour};
+
+use crate::Screen;
+
+/// The logo
+const LOGO: [&str; 4] = [
+    r" _  _ ___ ___ _____ ___  ___  _  _ ",
+    r"| \| | __/ _ \_   _| _ \/ _ \| \| |",
+    r"| .` | _| (_) || | |   / (_) | .` |",
+    r"|_|\_|___\___/ |_| |_|_\\___/|_|\_|",
+];
+
+/// How wide the logo is
+const LOGO_WIDTH: i16 = LOGO[0].len() as i16;
+
+/// How tall the logo is
+const LOGO_HEIGHT: i16 = LOGO.len() as i16;
+
+/// The colours the logo goes through
+const COLOURS: [Colour; 6] = [
+    Colour::Cyan,
+    Colour::Green,
+    Colour::Yellow,
+    Colour::Red,
+    Colour::Magenta,
+    Colour::Blue,
+];
+
+/// The bouncing logo effect
+pub struct Bounce {
+    col: i16,
+    row: i16,
+    dx: i16,
+    dy: i16,
+    colour: usize,
+    /// Counts frames, so we can move up and down more slowly than side to
+    /// side. Characters are about twice as tall as they are wide.
+    tick: u8,
+}
+
+impl Bounce {
+    /// Make a new bouncing logo
+    pub const fn new() -> Bounce {
+        Bounce {
+            col: 0,
+            row: 0,
+            dx: 1,
+            dy: 1,
+            colour: 0,
+            tick: 0,
+        }
+    }
+
+    /// Get ready to start, with the logo somewhere random
+    pub fn start(&mut self, screen: &Screen) {
+        let (max_col, max_row) = Self::limits(screen);
+        self.col = crate::random(max_col as u16 + 1) as i16;
+        self.row = crate::random(max_row as u16 + 1) as i16;
+        self.dx = if crate::random(2) == 0 { 1 } else { -1 };
+        self.dy = if crate::random(2) == 0 { 1 } else { -1 };
+        self.colour = usize::from(crate::random(COLOURS.len() as u16));
+    }
+
+    /// Move the logo on, and draw it
+    pub fn frame(&mut self, screen: &mut Screen) {
+        let (max_col, max_row) = Self::limits(screen);
+        self.col += self.dx;
+        if self.col <= 0 || self.col >= max_col {
+            self.col = self.col.clamp(0, max_col);
+            self.dx = -self.dx;
+            self.colour = (self.colour + 1) % COLOURS.len();
+        }
+        self.tick = self.tick.wrapping_add(1);
+        if self.tick.is_multiple_of(2) {
+            self.row += self.dy;
+            if self.row <= 0 || self.row >= max_row {
+                self.row = self.row.clamp(0, max_row);
+                self.dy = -self.dy;
+                self.colour = (self.colour + 1) % COLOURS.len();
+            }
+        }
+        let attr = Attr::new(COLOURS[self.colour], Colour::Black).bold();
+        for (row, line) in LOGO.iter().enumerate() {
+            for (col, ch) in line.chars().enumerate() {
+                // Let the background show through the gaps
+                if ch != ' ' {
+                    let row = self.row as usize + row;
+                    screen.set(row, self.col as usize + col, ch, attr);
+                }
+            }
+        }
+    }
+
+    /// The furthest right and down the logo can go
+    fn limits(screen: &Screen) -> (i16, i16) {
+        (
+            (screen.width() as i16 - LOGO_WIDTH).max(0),
+            (screen.height() as i16 - LOGO_HEIGHT).max(0),
+        )
+    }
+}
+
+impl Default for Bounce {
+    fn default() -> Self {
+        Bounce::new()
+    }
+}
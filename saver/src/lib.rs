@@ -0,0 +1,165 @@
+//! Application logic for the screensavers
+
+#![no_std]
+#![deny(missing_docs)]
+#![deny(unsafe_code)]
+
+use neotron_sdk::console;
+
+pub mod bounce;
+pub mod matrix;
+pub mod plasma;
+pub mod starfield;
+
+/// How big the screen is
+const SCREEN_WIDTH: usize = 80;
+const SCREEN_HEIGHT: usize = 25;
+
+/// What the effects are drawn on
+pub type Screen = neotron_screen::Screen<SCREEN_WIDTH, SCREEN_HEIGHT>;
+
+/// The slowest speed
+pub const MIN_SPEED: u8 = 1;
+
+/// The fastest speed
+pub const MAX_SPEED: u8 = 9;
+
+/// The speed, if you don't pick one
+pub const DEFAULT_SPEED: u8 = 5;
+
+/// How many frames we show of each effect, when going through them all
+const CYCLE_FRAMES: u32 = 600;
+
+/// The effects we can show
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Effect {
+    /// Digital rain
+    Matrix,
+    /// Flying through the stars
+    Starfield,
+    /// A logo bouncing around
+    Bounce,
+    /// A plasma made of block characters
+    Plasma,
+}
+
+impl Effect {
+    /// All the effects, in the order we go through them
+    pub const ALL: [Effect; 4] = [
+        Effect::Matrix,
+        Effect::Starfield,
+        Effect::Bounce,
+        Effect::Plasma,
+    ];
+
+    /// Get the name you pick this effect with
+    pub fn name(self) -> &'static str {
+        match self {
+            Effect::Matrix => "matrix",
+            Effect::Starfield => "stars",
+            Effect::Bounce => "bounce",
+            Effect::Plasma => "plasma",
+        }
+    }
+
+    /// Find an effect by name, ignoring case
+    pub fn from_name(name: &str) -> Option<Effect> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|effect| effect.name().eq_ignore_ascii_case(name))
+    }
+
+    /// The effect after this one
+    fn next(self) -> Effect {
+        let idx = Self::ALL.iter().position(|e| *e == self).unwrap_or(0);
+        Self::ALL[(idx + 1) % Self::ALL.len()]
+    }
+}
+
+/// Represents the screensaver application
+pub struct App {
+    stdout: neotron_sdk::File,
+    stdin: neotron_sdk::File,
+    screen: Screen,
+    matrix: matrix::Matrix,
+    starfield: starfield::Starfield,
+    bounce: bounce::Bounce,
+    plasma: plasma::Plasma,
+}
+
+impl App {
+    /// Make a new screensaver application.
+    ///
+    /// The effects fill an 80x25 screen, so the size is ignored.
+    pub const fn new(_width: u8, _height: u8) -> App {
+        App {
+            stdout: neotron_sdk::stdout(),
+            stdin: neotron_sdk::stdin(),
+            screen: Screen::new(),
+            matrix: matrix::Matrix::new(),
+            starfield: starfield::Starfield::new(),
+            bounce: bounce::Bounce::new(),
+            plasma: plasma::Plasma::new(),
+        }
+    }
+
+    /// Show an effect until a key is pressed.
+    ///
+    /// If you don't pick an effect, we go through them all in turn. The speed
+    /// goes from [`MIN_SPEED`] to [`MAX_SPEED`].
+    pub fn play(&mut self, effect: Option<Effect>, speed: u8) {
+        let speed = speed.clamp(MIN_SPEED, MAX_SPEED);
+        let delay = core::time::Duration::from_millis(u64::from(MAX_SPEED + 1 - speed) * 15);
+        console::cursor_off(&mut self.stdout);
+        let mut current = effect.unwrap_or(Effect::ALL[0]);
+        self.start(current);
+        let mut frames = 0;
+        loop {
+            self.screen.clear();
+            match current {
+                Effect::Matrix => self.matrix.frame(&mut self.screen),
+                Effect::Starfield => self.starfield.frame(&mut self.screen),
+                Effect::Bounce => self.bounce.frame(&mut self.screen),
+                Effect::Plasma => self.plasma.frame(&mut self.screen),
+            }
+            self.screen.flush(&mut self.stdout);
+            let mut buffer = [0u8; 1];
+            if let Ok(1) = self.stdin.read(&mut buffer) {
+                break;
+            }
+            neotron_sdk::delay(delay);
+            frames += 1;
+            if effect.is_none() && frames == CYCLE_FRAMES {
+                current = current.next();
+                self.start(current);
+                frames = 0;
+            }
+        }
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        console::clear_screen(&mut self.stdout);
+        console::move_cursor(&mut self.stdout, console::Position::origin());
+        console::cursor_on(&mut self.stdout);
+    }
+
+    /// Clear the screen and get an effect ready to go
+    fn start(&mut self, effect: Effect) {
+        self.screen.clear();
+        self.screen.invalidate();
+        match effect {
+            Effect::Matrix => self.matrix.start(&self.screen),
+            Effect::Starfield => self.starfield.start(),
+            Effect::Bounce => self.bounce.start(&self.screen),
+            Effect::Plasma => self.plasma.start(),
+        }
+    }
+}
+
+/// Get a random number from zero up to (but not including) `limit`
+fn random(limit: u16) -> u16 {
+    if limit == 0 {
+        0
+    } else {
+        neotron_sdk::rand() % limit
+    }
+}
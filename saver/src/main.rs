@@ -0,0 +1,52 @@
+#![cfg_attr(target_os = "none", no_std)]
+#![cfg_attr(target_os = "none", no_main)]
+
+use core::{fmt::Write, ptr::addr_of_mut};
+
+use saver::Effect;
+
+#[cfg(not(target_os = "none"))]
+fn main() {
+    neotron_sdk::init();
+}
+
+static mut APP: saver::App = saver::App::new(80, 25);
+
+#[no_mangle]
+extern "C" fn neotron_main() -> i32 {
+    neotron_error::exit_code(real_main())
+}
+
+fn real_main() -> Result<(), neotron_sdk::Error> {
+    let app = unsafe { &mut *addr_of_mut!(APP) };
+    let mut effect = None;
+    let mut speed = saver::DEFAULT_SPEED;
+    for arg in (0..).map_while(neotron_sdk::arg) {
+        if let Ok(number) = arg.parse::<u8>() {
+            if !(saver::MIN_SPEED..=saver::MAX_SPEED).contains(&number) {
+                return usage();
+            }
+            speed = number;
+        } else if arg.eq_ignore_ascii_case("all") {
+            effect = None;
+        } else if let Some(chosen) = Effect::from_name(&arg) {
+            effect = Some(chosen);
+        } else {
+            return usage();
+        }
+    }
+    app.play(effect, speed);
+    Ok(())
+}
+
+/// Explain how to use the program
+fn usage() -> Result<(), neotron_sdk::Error> {
+    let mut stdout = neotron_sdk::stdout();
+    let _ = write!(stdout, "Usage: saver [all");
+    for effect in Effect::ALL {
+        let _ = write!(stdout, "|{}", effect.name());
+    }
+    let _ = writeln!(stdout, "] [{}-{}]", saver::MIN_SPEED, saver::MAX_SPEED);
+    let _ = writeln!(stdout, "Press any key to stop.");
+    Err(neotron_sdk::Error::InvalidArg)
+}
@@ -0,0 +1,132 @@
+//! Digital rain, falling down the screen.
+//!
+//! Each column has one drop. The head of the drop is bright white, and it
+//! leaves a green trail behind it. Now and then a character in a trail
+//! changes, which makes the rain shimmer.
+
+use neotron_screen::{Attr, Colour};
+
+use crate::{Screen, SCREEN_HEIGHT, SCREEN_WIDTH};
+
+/// The characters the rain is made of
+const GLYPHS: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ$+-*/=%#&<>?!:;@^";
+
+/// How many trail characters change each frame
+const FLICKER: usize = 10;
+
+/// How the head of a drop looks
+const HEAD: Attr = Attr::new(Colour::White, Colour::Black).bold();
+
+/// How the trail just behind the head looks
+const TRAIL: Attr = Attr::new(Colour::Green, Colour::Black).bold();
+
+/// How the rest of the trail looks
+const FADED: Attr = Attr::new(Colour::Green, Colour::Black);
+
+/// One drop of rain
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct Drop {
+    /// Which row the head is on. Starts off the top of the screen.
+    head: i16,
+    /// How long the trail is
+    length: i16,
+    /// How many frames to wait between steps
+    delay: u8,
+    /// How many frames until the next step
+    wait: u8,
+}
+
+impl Drop {
+    /// A drop which hasn't started yet
+    const IDLE: Drop = Drop {
+        head: 0,
+        length: 0,
+        delay: 0,
+        wait: 0,
+    };
+
+    /// Send the drop back to the top of the screen, somewhere above the top
+    fn restart(&mut self, height: i16) {
+        self.head = -(crate::random(height as u16) as i16);
+        self.length = 4 + crate::random(height as u16 / 2) as i16;
+        self.delay = crate::random(3) as u8;
+        self.wait = self.delay;
+    }
+}
+
+/// The digital rain effect
+pub struct Matrix {
+    drops: [Drop; SCREEN_WIDTH],
+    glyphs: [[u8; SCREEN_WIDTH]; SCREEN_HEIGHT],
+}
+
+impl Matrix {
+    /// Make a new rain effect
+    pub const fn new() -> Matrix {
+        Matrix {
+            drops: [Drop::IDLE; SCREEN_WIDTH],
+            glyphs: [[b' '; SCREEN_WIDTH]; SCREEN_HEIGHT],
+        }
+    }
+
+    /// Get ready to start
+    pub fn start(&mut self, screen: &Screen) {
+        for row in self.glyphs.iter_mut() {
+            for glyph in row.iter_mut() {
+                *glyph = random_glyph();
+            }
+        }
+        for drop in self.drops.iter_mut() {
+            drop.restart(screen.height() as i16);
+        }
+    }
+
+    /// Move the rain on, and draw it
+    pub fn frame(&mut self, screen: &mut Screen) {
+        let height = screen.height() as i16;
+        for col in 0..screen.width() {
+            let drop = &mut self.drops[col];
+            if drop.wait == 0 {
+                drop.head += 1;
+                drop.wait = drop.delay;
+                if (0..height).contains(&drop.head) {
+                    self.glyphs[drop.head as usize][col] = random_glyph();
+                }
+            } else {
+                drop.wait -= 1;
+            }
+            if drop.head - drop.length >= height {
+                drop.restart(height);
+            }
+            let tail = (drop.head - drop.length).max(0);
+            for row in tail..=drop.head.min(height - 1) {
+                let age = drop.head - row;
+                let attr = if age == 0 {
+                    HEAD
+                } else if age < drop.length / 3 {
+                    TRAIL
+                } else {
+                    FADED
+                };
+                let glyph = char::from(self.glyphs[row as usize][col]);
+                screen.set(row as usize, col, glyph, attr);
+            }
+        }
+        for _ in 0..FLICKER {
+            let row = crate::random(screen.height() as u16);
+            let col = crate::random(screen.width() as u16);
+            self.glyphs[usize::from(row)][usize::from(col)] = random_glyph();
+        }
+    }
+}
+
+impl Default for Matrix {
+    fn default() -> Self {
+        Matrix::new()
+    }
+}
+
+/// Pick a character for the rain
+fn random_glyph() -> u8 {
+    GLYPHS[usize::from(crate::random(GLYPHS.len() as u16))]
+}
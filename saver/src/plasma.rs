@@ -0,0 +1,87 @@
+//! A plasma, drawn with block characters.
+//!
+//! Each cell's value is the sum of a few sine waves running across the
+//! screen. The waves move a little each frame, and the value picks a shade
+//! from a palette which slowly rotates.
+
+use neotron_screen::{Attr, Colour};
+
+use crate::Screen;
+
+/// One cycle of a sine wave, from -127 to 127
+const SINE: [i8; 64] = [
+    0, 12, 25, 37, 49, 60, 71, 81, 90, 98, 106, 112, 117, 122, 125, 126, 127, 126, 125, 122, 117,
+    112, 106, 98, 90, 81, 71, 60, 49, 37, 25, 12, 0, -12, -25, -37, -49, -60, -71, -81, -90, -98,
+    -106, -112, -117, -122, -125, -126, -127, -126, -125, -122, -117, -112, -106, -98, -90, -81,
+    -71, -60, -49, -37, -25, -12,
+];
+
+/// The shades the plasma is drawn in. The ends match up, so it can wrap
+/// around.
+const PALETTE: [(char, Attr); 16] = [
+    ('░', Attr::new(Colour::Blue, Colour::Black)),
+    ('▒', Attr::new(Colour::Blue, Colour::Black)),
+    ('▓', Attr::new(Colour::Blue, Colour::Black)),
+    ('█', Attr::new(Colour::Blue, Colour::Black)),
+    ('▓', Attr::new(Colour::Cyan, Colour::Black)),
+    ('█', Attr::new(Colour::Cyan, Colour::Black)),
+    ('█', Attr::new(Colour::Cyan, Colour::Black).bold()),
+    ('▓', Attr::new(Colour::White, Colour::Black).bold()),
+    ('█', Attr::new(Colour::White, Colour::Black).bold()),
+    ('▓', Attr::new(Colour::Magenta, Colour::Black).bold()),
+    ('█', Attr::new(Colour::Magenta, Colour::Black)),
+    ('▓', Attr::new(Colour::Magenta, Colour::Black)),
+    ('▓', Attr::new(Colour::Red, Colour::Black)),
+    ('▒', Attr::new(Colour::Red, Colour::Black)),
+    ('░', Attr::new(Colour::Red, Colour::Black)),
+    ('░', Attr::new(Colour::Magenta, Colour::Black)),
+];
+
+/// The plasma effect
+pub struct Plasma {
+    /// Counts frames, to move the waves along
+    time: u16,
+}
+
+impl Plasma {
+    /// Make a new plasma
+    pub const fn new() -> Plasma {
+        Plasma { time: 0 }
+    }
+
+    /// Get ready to start
+    pub fn start(&mut self) {
+        self.time = crate::random(1024);
+    }
+
+    /// Move the waves on, and draw the plasma
+    pub fn frame(&mut self, screen: &mut Screen) {
+        self.time = self.time.wrapping_add(1);
+        let t = i32::from(self.time);
+        for row in 0..screen.height() as i32 {
+            // Characters are about twice as tall as they are wide
+            let y = row * 2;
+            for col in 0..screen.width() as i32 {
+                let value = sine(col * 3 + t)
+                    + sine(y * 2 - t * 2)
+                    + sine((col + y) * 2 + t)
+                    + sine(col - y * 3 + t * 3);
+                // Four waves, so -508..=508
+                let shade = (value + 512) as usize * PALETTE.len() / 1024;
+                let (ch, attr) = PALETTE[(shade + usize::from(self.time / 8)) % PALETTE.len()];
+                screen.set(row as usize, col as usize, ch, attr);
+            }
+        }
+    }
+}
+
+impl Default for Plasma {
+    fn default() -> Self {
+        Plasma::new()
+    }
+}
+
+/// Look up a sine wave. A whole cycle is 64 steps.
+fn sine(angle: i32) -> i32 {
+    i32::from(SINE[angle.rem_euclid(64) as usize])
+}
@@ -0,0 +1,110 @@
+//! Flying through a field of stars.
+//!
+//! Each star has a position in 3D space, and we move them all towards the
+//! viewer. Stars further away are dimmer and smaller. When a star goes past
+//! the viewer, or off the edge of the screen, it goes back into the distance.
+
+use neotron_screen::{Attr, Colour};
+
+use crate::Screen;
+
+/// How many stars there are
+const STARS: usize = 80;
+
+/// How far away new stars appear
+const FAR: i32 = 1024;
+
+/// How close a star can get before we send it back into the distance
+const NEAR: i32 = 32;
+
+/// How far the stars move towards us each frame
+const STEP: i32 = 12;
+
+/// How far from the middle of the view a star can be, at the start
+const SPREAD: i32 = 640;
+
+/// Scales a star's position into screen columns. Rows are half as tall, to
+/// make up for characters being taller than they are wide.
+const ZOOM: i32 = 64;
+
+/// How the furthest stars look - only just there
+const FAINT: Attr = Attr::new(Colour::Black, Colour::Black).bold();
+
+/// How most stars look
+const DIM: Attr = Attr::new(Colour::White, Colour::Black);
+
+/// How the nearest stars look
+const BRIGHT: Attr = Attr::new(Colour::White, Colour::Black).bold();
+
+/// One star
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct Star {
+    x: i32,
+    y: i32,
+    z: i32,
+}
+
+impl Star {
+    /// A star right in front of us, which will be moved straight away
+    const NONE: Star = Star { x: 0, y: 0, z: 0 };
+
+    /// Put the star somewhere random, at the given distance
+    fn place(&mut self, z: i32) {
+        self.x = i32::from(crate::random(SPREAD as u16 * 2)) - SPREAD;
+        self.y = i32::from(crate::random(SPREAD as u16 * 2)) - SPREAD;
+        self.z = z;
+    }
+}
+
+/// The star field effect
+pub struct Starfield {
+    stars: [Star; STARS],
+}
+
+impl Starfield {
+    /// Make a new star field
+    pub const fn new() -> Starfield {
+        Starfield {
+            stars: [Star::NONE; STARS],
+        }
+    }
+
+    /// Get ready to start, with the stars spread out at all distances
+    pub fn start(&mut self) {
+        for star in self.stars.iter_mut() {
+            star.place(NEAR + i32::from(crate::random((FAR - NEAR) as u16)));
+        }
+    }
+
+    /// Move the stars on, and draw them
+    pub fn frame(&mut self, screen: &mut Screen) {
+        let mid_col = screen.width() as i32 / 2;
+        let mid_row = screen.height() as i32 / 2;
+        for star in self.stars.iter_mut() {
+            star.z -= STEP;
+            if star.z < NEAR {
+                star.place(FAR);
+            }
+            let col = mid_col + star.x * ZOOM / star.z;
+            let row = mid_row + star.y * ZOOM / 2 / star.z;
+            if col < 0 || row < 0 || col >= screen.width() as i32 || row >= screen.height() as i32 {
+                star.place(FAR);
+                continue;
+            }
+            let (ch, attr) = match star.z {
+                768.. => ('.', FAINT),
+                512.. => ('.', DIM),
+                256.. => ('+', DIM),
+                128.. => ('*', DIM),
+                _ => ('*', BRIGHT),
+            };
+            screen.set(row as usize, col as usize, ch, attr);
+        }
+    }
+}
+
+impl Default for Starfield {
+    fn default() -> Self {
+        Starfield::new()
+    }
+}
@@ -0,0 +1,1042 @@
+//! Playing Snake with a friend, on another computer.
+//!
+//! Both computers play the same game in lockstep: a tick only happens once
+//! we know which keys both players pressed for it. The keys pressed during
+//! a tick are sent [`DELAY`] ticks ahead of when they take effect, so a
+//! link that takes a few ticks to get them across - like a serial port at
+//! 9600 baud - doesn't hold the game up.
+//!
+//! The joiner says hello, a line at a time, until the host answers with the
+//! game they're going to play:
+//!
+//! * `HELLO 1 <width> <height>` - the version of all this, and how many
+//!   squares of board the joiner can show
+//! * `START <seed> <width> <height> <tick_ms>` - the random number seed,
+//!   the size of the board and how fast it starts
+//!
+//! After that everything is a frame of bytes. The last byte of each one is
+//! all the others XORed together and with 0x5A, so junk on the line is
+//! spotted and skipped.
+//!
+//! * `I <tick> <keys> <hash lo> <hash hi>` - the keys for a tick, and a
+//!   hash of the game [`DELAY`] ticks before it
+//! * `R <tick> 0 0 0` - please send the keys for that tick again
+//! * `S <length lo> <length hi> <snapshot>` - the whole game, from the
+//!   host, when the hashes say the two games have drifted apart
+//!
+//! Only the bottom eight bits of a tick are sent. Neither end gets more
+//! than [`DELAY`] ticks ahead of the other, so that's plenty.
+
+use core::fmt::Write;
+
+use neotron_input::KeyEvent;
+use neotron_sdk::console;
+
+use crate::{link::Link, Direction, Game, Text};
+
+/// How many ticks ahead the keys are sent
+const DELAY: u32 = 3;
+
+/// How many ticks of keys and hashes we remember
+const HISTORY: usize = 32;
+
+/// The protocol version we speak
+const VERSION: u32 = 1;
+
+/// The fastest a co-op game gets, so the keys can keep up
+pub const MIN_TICK_MS: u16 = 50;
+
+/// How often we look for anything from the other end
+pub const POLL_MS: u32 = 10;
+
+/// How often the joiner says hello
+const HELLO_MS: u32 = 500;
+
+/// How long we wait for keys before asking for them again
+const RESEND_MS: u32 = 300;
+
+/// How long we wait for keys before saying so
+const STALL_MS: u32 = 500;
+
+/// How long we wait for keys before giving up
+const GIVE_UP_MS: u32 = 5000;
+
+/// The longest snapshot we can send
+const MAX_SNAPSHOT: usize = 512;
+
+/// The longest hello or start line
+const MAX_LINE: usize = 48;
+
+/// The longest endpoint we remember
+const MAX_ENDPOINT: usize = 64;
+
+/// The keys byte bit which says the player quit
+const QUIT: u8 = 0x40;
+
+/// How many turns fit in a keys byte
+const MAX_TURNS: u8 = 2;
+
+/// How a co-op game ended
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Ending {
+    /// Our snake crashed
+    YouCrashed,
+    /// The other snake crashed
+    PartnerCrashed,
+    /// We quit
+    YouQuit,
+    /// The other player quit
+    PartnerQuit,
+    /// The other end stopped answering
+    LostTouch,
+    /// The two games drifted apart, and couldn't be put back together
+    OutOfStep,
+    /// We couldn't open the link at all
+    NoLink,
+}
+
+impl Ending {
+    /// What we say about it
+    pub fn message(self) -> &'static str {
+        match self {
+            Ending::YouCrashed => "You crashed!",
+            Ending::PartnerCrashed => "Your partner crashed!",
+            Ending::YouQuit => "You quit",
+            Ending::PartnerQuit => "Your partner quit",
+            Ending::LostTouch => "We lost touch with your partner",
+            Ending::OutOfStep => "Your games got out of step",
+            Ending::NoLink => "Couldn't open the link",
+        }
+    }
+
+    /// Did we get far enough to have a score?
+    pub fn has_score(self) -> bool {
+        self != Ending::NoLink
+    }
+}
+
+/// What happened when a game was polled
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Event {
+    /// Nothing the screen needs to know about
+    Nothing,
+    /// We've been waiting a while for the other end
+    Stalled,
+    /// Everything needs drawing again
+    Redraw,
+    /// The game is over
+    Over(Ending),
+}
+
+/// Where to find the other player
+pub struct Settings {
+    endpoint: Text<MAX_ENDPOINT>,
+    /// The serial port speed, if the endpoint is a serial port
+    pub baud: u32,
+}
+
+impl Settings {
+    /// The settings we start with
+    pub const fn new() -> Settings {
+        Settings {
+            endpoint: Text::from_static("SER0:"),
+            baud: 9600,
+        }
+    }
+
+    /// Take any settings we understand from a configuration file
+    pub fn configure(&mut self, config: &neotron_config::Config) {
+        if let Some(endpoint) = config.get("coop", "endpoint") {
+            self.endpoint = Text::from_str(endpoint.trim());
+        }
+        if let Some(baud) = config.get_u32("coop", "baud") {
+            self.baud = baud;
+        }
+    }
+
+    /// Write our settings out
+    pub fn save_settings<W: Write>(&self, writer: &mut neotron_config::Writer<W>) {
+        let _ = writer.section("coop");
+        let _ = writer.comment("Where to find the other player: a serial port like SER0:, or");
+        let _ = writer.comment("on a PC, host:port. The host listens on the port.");
+        let _ = writer.set_str("endpoint", self.endpoint());
+        let _ = writer.set("baud", self.baud);
+    }
+
+    /// Where to find the other player
+    pub fn endpoint(&self) -> &str {
+        self.endpoint.as_str()
+    }
+}
+
+/// Waits for the other player, before a co-op game starts
+pub struct Handshake {
+    /// Are we the one who picks the game?
+    host: bool,
+    link: Option<Link>,
+    /// Waits for the joiner to connect, on a PC
+    #[cfg(not(target_os = "none"))]
+    listener: Option<neotron_host::net::Listener>,
+    /// The line we're part way through reading
+    line: [u8; MAX_LINE],
+    len: usize,
+    /// How long we've been waiting
+    waited_ms: u32,
+}
+
+impl Handshake {
+    /// Get ready to host or join a game. Returns `None` if there's no way
+    /// to reach the other end.
+    pub fn open(host: bool, settings: &Settings) -> Option<Handshake> {
+        let endpoint = settings.endpoint();
+        let mut handshake = Handshake {
+            host,
+            link: None,
+            #[cfg(not(target_os = "none"))]
+            listener: None,
+            line: [0; MAX_LINE],
+            len: 0,
+            waited_ms: 0,
+        };
+        // On a PC, the host waits for a TCP connection, and the joiner
+        // keeps trying to make one
+        #[cfg(not(target_os = "none"))]
+        if !endpoint.ends_with(':') {
+            if host {
+                handshake.listener = neotron_host::net::Listener::bind(endpoint).ok();
+                handshake.listener.as_ref()?;
+            }
+            return Some(handshake);
+        }
+        let link = Link::open(endpoint)?;
+        link.set_baud(settings.baud);
+        handshake.link = Some(link);
+        Some(handshake)
+    }
+
+    /// Carry on waiting for the other end, which we do every [`POLL_MS`].
+    /// Returns the game, set up in `game`, once it can start.
+    pub fn poll(&mut self, game: &mut Game, settings: &Settings) -> Option<Session> {
+        let hello_due = self.waited_ms.is_multiple_of(HELLO_MS);
+        self.waited_ms = self.waited_ms.wrapping_add(POLL_MS);
+        #[cfg(not(target_os = "none"))]
+        if self.link.is_none() {
+            if let Some(listener) = &self.listener {
+                self.link = listener.accept().ok().flatten().map(Link::Tcp);
+            } else if hello_due {
+                self.link = Link::open(settings.endpoint());
+            }
+        }
+        #[cfg(target_os = "none")]
+        let _ = settings;
+
+        if !self.host && hello_due {
+            let link = self.link.as_mut()?;
+            let _ = writeln!(
+                link,
+                "HELLO {} {} {}",
+                VERSION, game.view_width, game.view_height
+            );
+        }
+
+        let len = self.read_line()?;
+        let line = core::str::from_utf8(&self.line[..len]).ok()?;
+        let mut words = line.split_whitespace();
+        let saved = Saved::keep(game);
+        if self.host {
+            let (Some("HELLO"), Some(version), Some(width), Some(height)) =
+                (words.next(), words.next(), words.next(), words.next())
+            else {
+                return None;
+            };
+            if version.parse() != Ok(VERSION) {
+                return None;
+            }
+            // The board has to fill both screens
+            let width = game.width.max(width.parse().ok()?);
+            let height = game.height.max(height.parse().ok()?);
+            let start = Start {
+                seed: game.rng.next_u32(),
+                width,
+                height,
+                tick_ms: game.starting_tick.max(MIN_TICK_MS),
+            };
+            let link = self.link.take()?;
+            start.send(&link);
+            start.apply(game, 0);
+            Some(Session::new(link, Some(start), saved))
+        } else {
+            let (Some("START"), Some(seed), Some(width), Some(height), Some(tick_ms)) = (
+                words.next(),
+                words.next(),
+                words.next(),
+                words.next(),
+                words.next(),
+            ) else {
+                return None;
+            };
+            let start = Start {
+                seed: seed.parse().ok()?,
+                width: width.parse().ok()?,
+                height: height.parse().ok()?,
+                tick_ms: tick_ms.parse().ok()?,
+            };
+            start.apply(game, 1);
+            Some(Session::new(self.link.take()?, None, saved))
+        }
+    }
+
+    /// Read what's arrived, up to the end of a line. Returns how long the
+    /// line is, once there's a whole one.
+    fn read_line(&mut self) -> Option<usize> {
+        loop {
+            let mut byte = [0u8; 1];
+            match self.link.as_ref()?.read(&mut byte) {
+                Some(0) => return None,
+                Some(_) => {}
+                None => {
+                    // They went away. They can come back.
+                    self.link = None;
+                    return None;
+                }
+            }
+            match byte[0] {
+                b'\n' => {
+                    let len = self.len;
+                    self.len = 0;
+                    return Some(len);
+                }
+                b'\r' => {}
+                byte if self.len < MAX_LINE => {
+                    self.line[self.len] = byte;
+                    self.len += 1;
+                }
+                // Too long to be anything of ours
+                _ => self.len = 0,
+            }
+        }
+    }
+}
+
+/// The game the host picked
+#[derive(Debug, Copy, Clone)]
+struct Start {
+    seed: u32,
+    width: u8,
+    height: u8,
+    tick_ms: u16,
+}
+
+impl Start {
+    /// Tell the joiner
+    fn send(&self, link: &Link) {
+        let mut line = neotron_tui::Line::new();
+        let _ = writeln!(
+            line,
+            "START {} {} {} {}",
+            self.seed, self.width, self.height, self.tick_ms
+        );
+        link.send(line.as_str().as_bytes());
+    }
+
+    /// Set the game up to play it, as player `me`
+    fn apply(&self, game: &mut Game, me: usize) {
+        game.players = 2;
+        game.me = me;
+        game.width = self.width.clamp(game.view_width, Game::MAX_WIDTH as u8);
+        game.height = self.height.clamp(game.view_height, Game::MAX_HEIGHT as u8);
+        game.starting_tick = self.tick_ms.max(MIN_TICK_MS);
+        game.min_tick_ms = MIN_TICK_MS;
+        game.rng = neotron_rand::Rng::new(u64::from(self.seed));
+    }
+}
+
+/// The settings a co-op game changes, to put back afterwards
+#[derive(Debug, Copy, Clone)]
+struct Saved {
+    width: u8,
+    height: u8,
+    starting_tick: u16,
+}
+
+impl Saved {
+    /// Remember the settings in a game
+    fn keep(game: &Game) -> Saved {
+        Saved {
+            width: game.width,
+            height: game.height,
+            starting_tick: game.starting_tick,
+        }
+    }
+}
+
+/// What we know about one tick
+#[derive(Debug, Copy, Clone)]
+struct Slot {
+    /// Which tick this is about
+    tick: u32,
+    /// The keys we pressed
+    mine: Option<u8>,
+    /// The keys the other player pressed
+    theirs: Option<u8>,
+    /// A hash of our game after the tick
+    my_hash: Option<u16>,
+    /// A hash of their game after the tick
+    their_hash: Option<u16>,
+}
+
+impl Slot {
+    /// Nothing known about a tick yet
+    const fn new(tick: u32) -> Slot {
+        Slot {
+            tick,
+            mine: None,
+            theirs: None,
+            my_hash: None,
+            their_hash: None,
+        }
+    }
+}
+
+/// A frame from the other end
+#[derive(Debug, Copy, Clone)]
+enum Frame {
+    /// The keys for a tick, and a hash of the game [`DELAY`] ticks before
+    Keys { tick: u8, keys: u8, hash: u16 },
+    /// Please send the keys for a tick again
+    Resend { tick: u8 },
+    /// The whole game, this many bytes of it
+    Snapshot(usize),
+}
+
+/// Bytes that have arrived, but aren't a whole frame yet
+struct Inbox {
+    bytes: [u8; MAX_SNAPSHOT + 4],
+    len: usize,
+}
+
+impl Inbox {
+    /// Nothing here
+    const fn new() -> Inbox {
+        Inbox {
+            bytes: [0; MAX_SNAPSHOT + 4],
+            len: 0,
+        }
+    }
+
+    /// Read whatever has arrived. Returns `false` if the link has gone.
+    fn fill(&mut self, link: &Link) -> bool {
+        while self.len < self.bytes.len() {
+            match link.read(&mut self.bytes[self.len..]) {
+                Some(0) => break,
+                Some(count) => self.len += count,
+                None => return false,
+            }
+        }
+        true
+    }
+
+    /// Take the next whole frame, skipping any junk before it. A snapshot
+    /// is copied into `snapshot`.
+    fn next_frame(&mut self, snapshot: &mut [u8; MAX_SNAPSHOT]) -> Option<Frame> {
+        loop {
+            let bytes = &self.bytes[..self.len];
+            let size = match bytes.first()? {
+                b'I' | b'R' => 6,
+                b'S' if bytes.len() < 3 => return None,
+                b'S' => match usize::from(u16::from_le_bytes([bytes[1], bytes[2]])) {
+                    len if len <= MAX_SNAPSHOT => len + 4,
+                    _ => 0,
+                },
+                _ => 0,
+            };
+            if size > bytes.len() {
+                return None;
+            }
+            let frame = if size > 0 && check(&bytes[..size - 1]) == bytes[size - 1] {
+                match bytes[0] {
+                    b'I' => Some(Frame::Keys {
+                        tick: bytes[1],
+                        keys: bytes[2],
+                        hash: u16::from_le_bytes([bytes[3], bytes[4]]),
+                    }),
+                    b'R' if bytes[2..5] == [0, 0, 0] => Some(Frame::Resend { tick: bytes[1] }),
+                    b'S' => {
+                        snapshot[..size - 4].copy_from_slice(&bytes[3..size - 1]);
+                        Some(Frame::Snapshot(size - 4))
+                    }
+                    _ => None,
+                }
+            } else {
+                None
+            };
+            // Drop the frame, or one byte of junk, and try again
+            let used = if frame.is_some() { size } else { 1 };
+            self.bytes.copy_within(used..self.len, 0);
+            self.len -= used;
+            if frame.is_some() {
+                return frame;
+            }
+        }
+    }
+}
+
+/// A co-op game in progress
+pub struct Session {
+    link: Link,
+    /// The game the host picked, which it sends again until it hears back.
+    /// The joiner doesn't have one.
+    start: Option<Start>,
+    /// Have we heard anything from the other end yet?
+    heard: bool,
+    /// The settings to put back afterwards
+    saved: Saved,
+    /// The next tick to play
+    tick: u32,
+    /// The keys pressed since we last sent some
+    keys: u8,
+    history: [Slot; HISTORY],
+    inbox: Inbox,
+    /// How long since the last tick
+    since_tick_ms: u32,
+    /// How long we've been waiting for the other player's keys
+    waited_ms: u32,
+    /// When we last asked for them again
+    asked_ms: u32,
+    /// Have we said we're waiting?
+    stalled: bool,
+    /// Hashes of ticks before this one were made before the last snapshot,
+    /// so they're bound to be different
+    settled: u32,
+    /// Has a snapshot changed the game under us since the screen was drawn?
+    resynced: bool,
+}
+
+impl Session {
+    /// Start a game over a link
+    fn new(link: Link, start: Option<Start>, saved: Saved) -> Session {
+        let mut session = Session {
+            link,
+            start,
+            heard: false,
+            saved,
+            tick: 0,
+            keys: 0,
+            history: [Slot::new(u32::MAX); HISTORY],
+            inbox: Inbox::new(),
+            since_tick_ms: 0,
+            waited_ms: 0,
+            asked_ms: 0,
+            stalled: false,
+            settled: 0,
+            resynced: false,
+        };
+        // Nobody pressed anything before the game started
+        for tick in 0..DELAY {
+            let slot = session.slot(tick);
+            slot.mine = Some(0);
+            slot.theirs = Some(0);
+        }
+        session
+    }
+
+    /// Put back the settings the game changed
+    pub fn finish(&self, game: &mut Game) {
+        game.players = 1;
+        game.me = 0;
+        game.width = self.saved.width;
+        game.height = self.saved.height;
+        game.starting_tick = self.saved.starting_tick;
+        game.min_tick_ms = Game::MIN_TICK;
+    }
+
+    /// What we know about a tick, forgetting whichever tick was there
+    /// before
+    fn slot(&mut self, tick: u32) -> &mut Slot {
+        let slot = &mut self.history[tick as usize % HISTORY];
+        if slot.tick != tick {
+            *slot = Slot::new(tick);
+        }
+        slot
+    }
+
+    /// What we know about a tick, if we still remember it
+    fn find(&self, tick: u32) -> Option<&Slot> {
+        let slot = &self.history[tick as usize % HISTORY];
+        (slot.tick == tick).then_some(slot)
+    }
+
+    /// Work out which tick the bottom eight bits of one are from, as it
+    /// can't be far from the one we're on. Anything too far off to still
+    /// be in the history is no use to us.
+    fn unwrap_tick(&self, tick: u8) -> Option<u32> {
+        let ahead = i32::from(tick.wrapping_sub(self.tick as u8) as i8);
+        if ahead.unsigned_abs() >= HISTORY as u32 / 2 {
+            return None;
+        }
+        self.tick.checked_add_signed(ahead)
+    }
+
+    /// Deal with a key. Turns are sent with the next tick, and quitting
+    /// waits for that too, unless we're stuck waiting for the other end.
+    pub fn key(&mut self, game: &Game, key: KeyEvent) -> Option<Ending> {
+        if key == KeyEvent::Escape || key.is_char('q') {
+            if self.stalled {
+                return Some(Ending::YouQuit);
+            }
+            self.keys |= QUIT;
+        } else if let Some(turn) = game.turn_for(key) {
+            let turns = u8::from(self.keys & 0x07 != 0) + u8::from(self.keys & 0x38 != 0);
+            if turns < MAX_TURNS {
+                self.keys |= turn_bits(turn) << (3 * turns);
+            }
+        }
+        None
+    }
+
+    /// See what the other end has sent, and play the next tick when it's
+    /// due and we have everyone's keys for it. This is done every
+    /// [`POLL_MS`].
+    pub fn update(&mut self, game: &mut Game, stdout: &mut neotron_sdk::File) -> Event {
+        if let Err(ending) = self.receive(game, stdout) {
+            return Event::Over(ending);
+        }
+        let mut event = if core::mem::take(&mut self.resynced) {
+            Event::Redraw
+        } else {
+            Event::Nothing
+        };
+        self.since_tick_ms += POLL_MS;
+        if self.since_tick_ms < u32::from(game.tick_interval_ms) {
+            return event;
+        }
+
+        let slot = self.slot(self.tick);
+        let (Some(mine), Some(theirs)) = (slot.mine, slot.theirs) else {
+            return self.wait();
+        };
+        if core::mem::take(&mut self.stalled) {
+            event = Event::Redraw;
+        }
+        self.since_tick_ms = 0;
+        self.waited_ms = 0;
+        self.asked_ms = 0;
+        match self.play(game, stdout, mine, theirs) {
+            Ok(()) => event,
+            Err(ending) => Event::Over(ending),
+        }
+    }
+
+    /// Wait a bit longer for the other player's keys
+    fn wait(&mut self) -> Event {
+        self.waited_ms += POLL_MS;
+        if self.waited_ms >= GIVE_UP_MS {
+            return Event::Over(Ending::LostTouch);
+        }
+        if self.waited_ms - self.asked_ms >= RESEND_MS {
+            self.asked_ms = self.waited_ms;
+            self.send_frame([b'R', self.tick as u8, 0, 0, 0]);
+            // If the joiner never heard the game we picked, it's still
+            // saying hello, and we need to say it again
+            if let (Some(start), false) = (self.start, self.heard) {
+                start.send(&self.link);
+            }
+        }
+        if self.waited_ms >= STALL_MS && !self.stalled {
+            self.stalled = true;
+            return Event::Stalled;
+        }
+        Event::Nothing
+    }
+
+    /// Play one tick, with both players' keys, and send our keys for the
+    /// tick [`DELAY`] ticks on
+    fn play(
+        &mut self,
+        game: &mut Game,
+        stdout: &mut neotron_sdk::File,
+        mine: u8,
+        theirs: u8,
+    ) -> Result<(), Ending> {
+        // Both ends see a quit on the same tick, so both games stop there
+        if mine & QUIT != 0 {
+            return Err(Ending::YouQuit);
+        }
+        if theirs & QUIT != 0 {
+            return Err(Ending::PartnerQuit);
+        }
+        let alive = step(game, stdout, self.keys_by_snake(mine, theirs));
+        let hash = hash(game);
+        let tick = self.tick;
+        self.slot(tick).my_hash = Some(hash);
+        self.tick += 1;
+        self.check_hash(game, tick)?;
+
+        let ahead = tick + DELAY;
+        let keys = core::mem::take(&mut self.keys);
+        self.slot(ahead).mine = Some(keys);
+        self.send_keys(ahead);
+        if alive {
+            Ok(())
+        } else if game.crashed == Some(game.me) {
+            Err(Ending::YouCrashed)
+        } else {
+            Err(Ending::PartnerCrashed)
+        }
+    }
+
+    /// Put two players' keys in snake order - the host's snake first
+    fn keys_by_snake(&self, mine: u8, theirs: u8) -> [u8; 2] {
+        if self.start.is_some() {
+            [mine, theirs]
+        } else {
+            [theirs, mine]
+        }
+    }
+
+    /// Send our keys for a tick, with the hash from [`DELAY`] ticks before
+    fn send_keys(&mut self, tick: u32) {
+        let Some(keys) = self.find(tick).and_then(|slot| slot.mine) else {
+            return;
+        };
+        let hash = tick
+            .checked_sub(DELAY)
+            .and_then(|before| self.find(before))
+            .and_then(|slot| slot.my_hash)
+            .unwrap_or(0);
+        let [lo, hi] = hash.to_le_bytes();
+        self.send_frame([b'I', tick as u8, keys, lo, hi]);
+    }
+
+    /// Send a frame, with the check byte on the end
+    fn send_frame(&self, bytes: [u8; 5]) {
+        let mut frame = [0u8; 6];
+        frame[..5].copy_from_slice(&bytes);
+        frame[5] = check(&bytes);
+        self.link.send(&frame);
+    }
+
+    /// Deal with everything the other end has sent
+    fn receive(&mut self, game: &mut Game, stdout: &mut neotron_sdk::File) -> Result<(), Ending> {
+        if !self.inbox.fill(&self.link) {
+            return Err(Ending::LostTouch);
+        }
+        let mut snapshot = [0u8; MAX_SNAPSHOT];
+        while let Some(frame) = self.inbox.next_frame(&mut snapshot) {
+            self.heard = true;
+            match frame {
+                Frame::Keys { tick, keys, hash } => {
+                    let Some(tick) = self.unwrap_tick(tick) else {
+                        continue;
+                    };
+                    self.slot(tick).theirs = Some(keys);
+                    // The hash can get here before we've played the tick
+                    // it's for, and then it's checked when we have
+                    if let Some(before) = tick.checked_sub(DELAY) {
+                        self.slot(before).their_hash = Some(hash);
+                        self.check_hash(game, before)?;
+                    }
+                }
+                Frame::Resend { tick } => {
+                    if let Some(tick) = self.unwrap_tick(tick) {
+                        self.send_keys(tick);
+                    }
+                }
+                Frame::Snapshot(len) if self.start.is_none() => {
+                    self.restore(game, stdout, &snapshot[..len])?;
+                }
+                // Only the host sends them
+                Frame::Snapshot(_) => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Answer the other end's requests for keys, after our game has
+    /// finished, in case theirs hasn't yet
+    pub fn serve(&mut self) {
+        if !self.inbox.fill(&self.link) {
+            return;
+        }
+        let mut snapshot = [0u8; MAX_SNAPSHOT];
+        while let Some(frame) = self.inbox.next_frame(&mut snapshot) {
+            if let Frame::Resend { tick } = frame {
+                if let Some(tick) = self.unwrap_tick(tick) {
+                    self.send_keys(tick);
+                }
+            }
+        }
+    }
+
+    /// If we're the host and we have both hashes for a tick, make sure they
+    /// match, and send the joiner the whole game if they don't
+    fn check_hash(&mut self, game: &mut Game, tick: u32) -> Result<(), Ending> {
+        if self.start.is_none() || tick < self.settled {
+            return Ok(());
+        }
+        let Some(slot) = self.find(tick) else {
+            return Ok(());
+        };
+        match (slot.my_hash, slot.their_hash) {
+            (Some(mine), Some(theirs)) if mine != theirs => self.resync(game),
+            _ => Ok(()),
+        }
+    }
+
+    /// Send the joiner our game, to replace theirs
+    fn resync(&mut self, game: &mut Game) -> Result<(), Ending> {
+        let seed = game.rng.next_u32();
+        let mut frame = [0u8; MAX_SNAPSHOT + 4];
+        let len = encode(game, self.tick, seed, &mut frame[3..MAX_SNAPSHOT + 3])
+            .ok_or(Ending::OutOfStep)?;
+        frame[0] = b'S';
+        frame[1..3].copy_from_slice(&(len as u16).to_le_bytes());
+        frame[len + 3] = check(&frame[..len + 3]);
+        self.link.send(&frame[..len + 4]);
+        // Both games take their food from here on
+        game.rng = neotron_rand::Rng::new(u64::from(seed));
+        // The joiner might have played a few ticks past the snapshot
+        // before it gets it, and their hashes will still be wrong
+        self.settled = self.tick + DELAY;
+        Ok(())
+    }
+
+    /// Swap our game for the host's, and catch back up to where we were
+    fn restore(
+        &mut self,
+        game: &mut Game,
+        stdout: &mut neotron_sdk::File,
+        snapshot: &[u8],
+    ) -> Result<(), Ending> {
+        let (tick, seed) = decode(game, snapshot).ok_or(Ending::OutOfStep)?;
+        game.rng = neotron_rand::Rng::new(u64::from(seed));
+        game.rebuild_minimap(stdout);
+        if tick > self.tick {
+            // We're behind, so skip ahead. The host needs our keys for the
+            // ticks we skipped, and nobody pressed any.
+            for skipped in self.tick + DELAY..tick + DELAY {
+                self.slot(skipped).mine = Some(0);
+                self.send_keys(skipped);
+            }
+            self.keys = 0;
+            self.tick = tick;
+        } else {
+            // We're ahead, so play the ticks again, with the keys we used
+            for replay in tick..self.tick {
+                let slot = self.find(replay).ok_or(Ending::OutOfStep)?;
+                let (Some(mine), Some(theirs)) = (slot.mine, slot.theirs) else {
+                    return Err(Ending::OutOfStep);
+                };
+                if !step(game, stdout, self.keys_by_snake(mine, theirs)) {
+                    return Err(Ending::OutOfStep);
+                }
+                let hash = hash(game);
+                self.slot(replay).my_hash = Some(hash);
+            }
+        }
+        self.resynced = true;
+        Ok(())
+    }
+}
+
+/// Give each snake its turns, and move them all on. Returns `false` if one
+/// crashed.
+fn step(game: &mut Game, stdout: &mut neotron_sdk::File, keys: [u8; 2]) -> bool {
+    for (snake, keys) in game.snakes.iter_mut().zip(keys) {
+        for turn in [keys & 0x07, (keys >> 3) & 0x07] {
+            if let Some(turn) = turn_from_bits(turn) {
+                snake.turns.push(turn, snake.direction);
+            }
+        }
+    }
+    game.step(stdout)
+}
+
+/// The check byte for a frame
+fn check(bytes: &[u8]) -> u8 {
+    bytes.iter().fold(0x5A, |check, byte| check ^ byte)
+}
+
+/// A turn, as it's sent in a keys byte. Zero means no turn.
+fn turn_bits(turn: Direction) -> u8 {
+    direction_bits(turn) + 1
+}
+
+/// A turn from a keys byte
+fn turn_from_bits(bits: u8) -> Option<Direction> {
+    bits.checked_sub(1).and_then(direction_from_bits)
+}
+
+/// A direction, in two bits
+fn direction_bits(direction: Direction) -> u8 {
+    match direction {
+        Direction::Up => 0,
+        Direction::Down => 1,
+        Direction::Left => 2,
+        Direction::Right => 3,
+    }
+}
+
+/// A direction from two bits
+fn direction_from_bits(bits: u8) -> Option<Direction> {
+    match bits {
+        0 => Some(Direction::Up),
+        1 => Some(Direction::Down),
+        2 => Some(Direction::Left),
+        3 => Some(Direction::Right),
+        _ => None,
+    }
+}
+
+/// A hash of everything that matters in a game, so two of them can be
+/// compared without sending the whole thing
+fn hash(game: &Game) -> u16 {
+    // 32-bit FNV-1a, folded in half
+    let mut hash: u32 = 0x811c_9dc5;
+    let mut add = |bytes: &[u8]| {
+        for &byte in bytes {
+            hash ^= u32::from(byte);
+            hash = hash.wrapping_mul(0x0100_0193);
+        }
+    };
+    for snake in &game.snakes[..game.players] {
+        add(&[
+            snake.head.row,
+            snake.head.col,
+            snake.tail.row,
+            snake.tail.col,
+            direction_bits(snake.direction),
+            snake.digesting as u8,
+        ]);
+    }
+    add(&[game.food.row, game.food.col]);
+    add(&game.score.to_le_bytes());
+    add(&game.tick_interval_ms.to_le_bytes());
+    (hash ^ (hash >> 16)) as u16
+}
+
+/// Writes a snapshot into a buffer
+struct Writer<'a> {
+    out: &'a mut [u8],
+    len: usize,
+}
+
+impl Writer<'_> {
+    /// Add some bytes, if there's room
+    fn push(&mut self, bytes: &[u8]) -> Option<()> {
+        self.out
+            .get_mut(self.len..self.len + bytes.len())?
+            .copy_from_slice(bytes);
+        self.len += bytes.len();
+        Some(())
+    }
+}
+
+/// Reads a snapshot back
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl Reader<'_> {
+    /// Take some bytes, if there are enough left
+    fn take<const N: usize>(&mut self) -> Option<[u8; N]> {
+        let (first, rest) = self.bytes.split_first_chunk()?;
+        self.bytes = rest;
+        Some(*first)
+    }
+
+    /// Take a place on the board, if it's on the board
+    fn position(&mut self, game: &Game) -> Option<console::Position> {
+        let [row, col] = self.take()?;
+        (row < game.height && col < game.width).then_some(console::Position { row, col })
+    }
+}
+
+/// Write down the game as it is before `tick`, and the seed both ends
+/// carry on with. Returns how long it is, or `None` if it doesn't fit.
+///
+/// Each snake is its tail, and which way each square of it goes from
+/// there, two bits a square.
+fn encode(game: &Game, tick: u32, seed: u32, out: &mut [u8]) -> Option<usize> {
+    let mut writer = Writer { out, len: 0 };
+    writer.push(&tick.to_le_bytes())?;
+    writer.push(&seed.to_le_bytes())?;
+    writer.push(&game.score.to_le_bytes())?;
+    writer.push(&game.tick_interval_ms.to_le_bytes())?;
+    writer.push(&[game.food.row, game.food.col])?;
+    for snake in &game.snakes[..game.players] {
+        let mut turns = 0;
+        for (idx, turn) in snake.turns.turns[..snake.turns.len].iter().enumerate() {
+            turns |= turn_bits(*turn) << (3 * idx);
+        }
+        writer.push(&[
+            snake.tail.row,
+            snake.tail.col,
+            direction_bits(snake.direction),
+            snake.digesting as u8,
+            turns,
+        ])?;
+        // Leave room for the length, and fill it in afterwards
+        let length_at = writer.len;
+        writer.push(&[0, 0])?;
+        let mut length = 0u16;
+        let mut position = snake.tail;
+        while position.row != snake.head.row || position.col != snake.head.col {
+            let direction = game.board.direction_at(position)?;
+            if length.is_multiple_of(4) {
+                writer.push(&[0])?;
+            }
+            writer.out[writer.len - 1] |= direction_bits(direction) << (2 * (length % 4));
+            length += 1;
+            position = game.neighbour(position, direction)?;
+        }
+        writer.out[length_at..length_at + 2].copy_from_slice(&length.to_le_bytes());
+    }
+    Some(writer.len)
+}
+
+/// Swap the game for one from a snapshot. Returns the tick it's from, and
+/// the seed to carry on with, or `None` if it doesn't make sense.
+fn decode(game: &mut Game, snapshot: &[u8]) -> Option<(u32, u32)> {
+    let mut reader = Reader { bytes: snapshot };
+    let tick = u32::from_le_bytes(reader.take()?);
+    let seed = u32::from_le_bytes(reader.take()?);
+    let score = u32::from_le_bytes(reader.take()?);
+    let tick_interval_ms = u16::from_le_bytes(reader.take()?);
+    let food = reader.position(game)?;
+    game.board.reset();
+    game.board.store_food(food);
+    for idx in 0..game.players {
+        let tail = reader.position(game)?;
+        let [direction, digesting, turns] = reader.take()?;
+        let direction = direction_from_bits(direction)?;
+        let length = u16::from_le_bytes(reader.take()?);
+        let mut position = tail;
+        let mut bits = 0;
+        for segment in 0..length {
+            if segment.is_multiple_of(4) {
+                [bits] = reader.take()?;
+            }
+            let way = direction_from_bits((bits >> (2 * (segment % 4))) & 0x03)?;
+            game.board.store_body(position, way);
+            position = game.neighbour(position, way)?;
+        }
+        game.board.store_body(position, direction);
+        let snake = &mut game.snakes[idx];
+        snake.tail = tail;
+        snake.head = position;
+        snake.direction = direction;
+        snake.digesting = u32::from(digesting);
+        snake.turns = crate::Turns::new();
+        for turn in [turns & 0x07, (turns >> 3) & 0x07] {
+            if let Some(turn) = turn_from_bits(turn) {
+                snake.turns.push(turn, direction);
+            }
+        }
+    }
+    game.food = food;
+    game.score = score;
+    game.tick_interval_ms = tick_interval_ms;
+    game.crashed = None;
+    Some((tick, seed))
+}
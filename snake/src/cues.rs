@@ -0,0 +1,184 @@
+//! Sounds that say what's near the snake's head, so the game can be played
+//! without seeing the board well.
+//!
+//! Each kind of thing has its own sound: a soft chirp for food, a plain
+//! tone for the walls and a buzz for the snake's own body. The pitch says
+//! which way it is - highest for up, then right, then left, and lowest for
+//! down - and the closer it is, the louder.
+
+use neotron_sfx::{Player, Sfx};
+
+use crate::Direction;
+
+/// The sample rate we make the sounds at
+const SAMPLE_RATE: u32 = 11025;
+
+/// How far ahead and to the side we look for walls and body, in squares
+pub(crate) const DANGER_RANGE: u8 = 3;
+
+/// How many moves between telling the player where the food is, when
+/// nothing is in the way
+const FOOD_EVERY: u32 = 4;
+
+/// How long each sound lasts, in milliseconds
+const CUE_MS: u16 = 50;
+
+/// Something near the snake's head
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum Thing {
+    /// Something to eat
+    Food,
+    /// The edge of the board
+    Wall,
+    /// The snake itself
+    Body,
+}
+
+/// How loud a sound is, from far away to right next to us
+const VOLUMES: [u8; 3] = [80, 160, 255];
+
+/// The pitch for each way, in Hz, in the same order as [`DIRECTIONS`]
+const PITCHES: [u16; 4] = [880, 440, 587, 698];
+
+/// Every way, in the order we keep their sounds
+const DIRECTIONS: [Direction; 4] = [
+    Direction::Up,
+    Direction::Down,
+    Direction::Left,
+    Direction::Right,
+];
+
+/// How long one sound is, as a recipe with one step
+const CUE_LEN: usize = neotron_sfx::HEADER_LEN + neotron_sfx::STEP_LEN;
+
+/// A sound for each thing, each way and each volume
+static CUES: [[u8; CUE_LEN]; 36] = make_cues();
+
+/// Write out every sound
+const fn make_cues() -> [[u8; CUE_LEN]; 36] {
+    let mut cues = [[0; CUE_LEN]; 36];
+    let mut thing = 0;
+    while thing < 3 {
+        let mut way = 0;
+        while way < 4 {
+            let mut level = 0;
+            while level < 3 {
+                cues[cue_index(thing, way, level)] = make_cue(thing, PITCHES[way], VOLUMES[level]);
+                level += 1;
+            }
+            way += 1;
+        }
+        thing += 1;
+    }
+    cues
+}
+
+/// Where a sound is kept in [`CUES`]
+const fn cue_index(thing: usize, way: usize, level: usize) -> usize {
+    (thing * 4 + way) * 3 + level
+}
+
+/// Write out one sound. Food is a triangle wave that slides up, walls are
+/// a square wave and body is a sawtooth.
+const fn make_cue(thing: usize, hz: u16, volume: u8) -> [u8; CUE_LEN] {
+    let (waveform, to_hz) = match thing {
+        0 => (1, hz + hz / 4),
+        1 => (0, hz),
+        _ => (2, hz),
+    };
+    let header = neotron_sfx::recipe_header(1);
+    let [from_lo, from_hi] = hz.to_le_bytes();
+    let [to_lo, to_hi] = to_hz.to_le_bytes();
+    let [ms_lo, ms_hi] = CUE_MS.to_le_bytes();
+    [
+        header[0], header[1], header[2], header[3], header[4], header[5], header[6], header[7],
+        waveform, volume, from_lo, from_hi, to_lo, to_hi, ms_lo, ms_hi,
+    ]
+}
+
+/// Plays the sounds
+pub(crate) struct Cues {
+    player: Player<'static>,
+    /// Does the player want the sounds?
+    enabled: bool,
+    /// Counts moves, so we only mention the food every so often
+    moves: u32,
+}
+
+impl Cues {
+    /// Make something to play the sounds, which starts off quiet
+    pub(crate) const fn new() -> Cues {
+        Cues {
+            player: Player::new(SAMPLE_RATE),
+            enabled: false,
+            moves: 0,
+        }
+    }
+
+    /// Does the player want the sounds?
+    pub(crate) fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Turn the sounds on or off. The audio device is only open while
+    /// they're on.
+    pub(crate) fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        if enabled && !self.player.is_open() {
+            self.player.open();
+        } else if !enabled {
+            self.player.stop();
+            self.player.close();
+        }
+    }
+
+    /// Say what's near the head, after a move that took `ms` milliseconds.
+    ///
+    /// `danger` is the nearest wall or body, if there's one within
+    /// [`DANGER_RANGE`], and `food` is which way the food is and how far.
+    /// Danger matters more, so we only mention the food if nothing's in the
+    /// way. We don't start a sound until the last one has finished.
+    pub(crate) fn after_move(
+        &mut self,
+        danger: Option<(Thing, Direction, u8)>,
+        food: (Direction, u16),
+        ms: u32,
+    ) {
+        if !self.enabled {
+            return;
+        }
+        self.moves = self.moves.wrapping_add(1);
+        if !self.player.is_playing() {
+            match danger {
+                Some((thing, way, distance)) => {
+                    // Right next to us is loudest
+                    let level = usize::from(DANGER_RANGE.saturating_sub(distance).min(2));
+                    self.play(thing, way, level);
+                }
+                None if self.moves.is_multiple_of(FOOD_EVERY) => {
+                    let (way, distance) = food;
+                    let level = match distance {
+                        0..=5 => 2,
+                        6..=15 => 1,
+                        _ => 0,
+                    };
+                    self.play(Thing::Food, way, level);
+                }
+                None => {}
+            }
+        }
+        self.player.pump(ms);
+    }
+
+    /// Start one of the sounds
+    fn play(&mut self, thing: Thing, way: Direction, level: usize) {
+        let thing = match thing {
+            Thing::Food => 0,
+            Thing::Wall => 1,
+            Thing::Body => 2,
+        };
+        let way = DIRECTIONS.iter().position(|d| *d == way).unwrap_or(0);
+        self.player
+            .play(Sfx::from_bytes(&CUES[cue_index(thing, way, level)]));
+    }
+}
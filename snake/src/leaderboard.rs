@@ -0,0 +1,166 @@
+//! The world leaderboard, kept on a server.
+//!
+//! We talk to the server one line at a time, in plain text:
+//!
+//! * `SCORE <name> <score>` sends a score. The server answers `OK <rank>`,
+//!   or `ERR <reason>`.
+//! * `TOP` asks for the best scores. The server answers with up to ten
+//!   lines of `<name> <score>`, best first, and then `END`.
+//!
+//! On a Neotron the endpoint is a device, like `NET0:` or a serial port
+//! with something on the other end that passes the lines on. On a PC it
+//! can also be `host:port`, and we make a TCP connection.
+
+use core::fmt::Write;
+
+use crate::{link::Link, Text};
+
+/// How many scores the server sends us
+pub const TOP: usize = 10;
+
+/// The longest name we send
+const MAX_NAME: usize = 12;
+
+/// The longest endpoint we remember
+const MAX_ENDPOINT: usize = 64;
+
+/// The longest line we expect from the server
+const MAX_LINE: usize = 64;
+
+/// How long we wait for the server to answer
+const TIMEOUT_MS: u32 = 2000;
+
+/// One line of the leaderboard
+#[derive(Debug, Copy, Clone)]
+pub struct Entry {
+    name: Text<MAX_NAME>,
+    /// How many points they got
+    pub score: u32,
+}
+
+impl Entry {
+    /// Who got the score
+    pub fn name(&self) -> &str {
+        self.name.as_str()
+    }
+}
+
+/// Sends scores to the server, and remembers the best ones it told us
+/// about
+pub struct Leaderboard {
+    /// Does the user want to use the server?
+    pub enabled: bool,
+    endpoint: Text<MAX_ENDPOINT>,
+    /// The name we send scores under
+    name: Text<MAX_NAME>,
+    entries: [Option<Entry>; TOP],
+}
+
+impl Leaderboard {
+    /// Make a leaderboard which is turned off
+    pub const fn new() -> Leaderboard {
+        Leaderboard {
+            enabled: false,
+            endpoint: Text::new(),
+            name: Text::new(),
+            entries: [None; TOP],
+        }
+    }
+
+    /// Take any settings we understand from a configuration file
+    pub fn configure(&mut self, config: &neotron_config::Config) {
+        if let Some(enabled) = config.get_bool("leaderboard", "enabled") {
+            self.enabled = enabled;
+        }
+        if let Some(endpoint) = config.get("leaderboard", "endpoint") {
+            self.endpoint = Text::from_str(endpoint.trim());
+        }
+        if let Some(name) = config.get("leaderboard", "name") {
+            // Names go in a line with spaces between the parts, so they
+            // can't have spaces in
+            let name = name.trim();
+            if !name.is_empty() && name.chars().all(|ch| ch.is_ascii_graphic()) {
+                self.name = Text::from_str(name);
+            }
+        }
+    }
+
+    /// Write our settings out
+    pub fn save_settings<W: Write>(&self, writer: &mut neotron_config::Writer<W>) {
+        let _ = writer.section("leaderboard");
+        let _ = writer.comment("Send scores to a server, and show the best on the title screen.");
+        let _ = writer.set_bool("enabled", self.enabled);
+        let _ = writer.comment("A device like NET0:, or on a PC, host:port");
+        let _ = writer.set_str("endpoint", self.endpoint.as_str());
+        let _ = writer.set_str("name", self.name());
+    }
+
+    /// The name we send scores under
+    pub fn name(&self) -> &str {
+        match self.name.as_str() {
+            "" => "NEOTRON",
+            name => name,
+        }
+    }
+
+    /// The best scores, from the last time we asked
+    pub fn entries(&self) -> impl Iterator<Item = &Entry> {
+        self.entries.iter().flatten()
+    }
+
+    /// Connect, if we're meant to
+    fn connect(&self) -> Option<Link> {
+        if !self.enabled || self.endpoint.len == 0 {
+            return None;
+        }
+        Link::open(self.endpoint.as_str())
+    }
+
+    /// Ask the server for the best scores. Returns `false` if we couldn't,
+    /// and keeps the ones we had.
+    pub fn fetch(&mut self) -> bool {
+        let Some(mut link) = self.connect() else {
+            return false;
+        };
+        if writeln!(link, "TOP").is_err() {
+            return false;
+        }
+        let mut entries = [None; TOP];
+        let mut count = 0;
+        let mut buffer = [0u8; MAX_LINE];
+        loop {
+            let Some(line) = link.read_line(&mut buffer, TIMEOUT_MS) else {
+                return false;
+            };
+            if line == "END" {
+                break;
+            }
+            let mut parts = line.split_whitespace();
+            let (Some(name), Some(score), None) = (parts.next(), parts.next(), parts.next()) else {
+                return false;
+            };
+            let Ok(score) = score.parse() else {
+                return false;
+            };
+            if count < TOP {
+                entries[count] = Some(Entry {
+                    name: Text::from_str(name),
+                    score,
+                });
+                count += 1;
+            }
+        }
+        self.entries = entries;
+        true
+    }
+
+    /// Send a score. Returns where it came in the world, if the server
+    /// took it.
+    pub fn submit(&mut self, score: u32) -> Option<u32> {
+        let mut link = self.connect()?;
+        writeln!(link, "SCORE {} {}", self.name(), score).ok()?;
+        let mut buffer = [0u8; MAX_LINE];
+        let line = link.read_line(&mut buffer, TIMEOUT_MS)?;
+        line.strip_prefix("OK ")?.trim().parse().ok()
+    }
+}
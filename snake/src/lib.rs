@@ -6,7 +6,147 @@
 
 use core::fmt::Write;
 
+use neotron_input::{KeyEvent, Keyboard};
 use neotron_sdk::console;
+use neotron_tui::{Frame, ListBox, Menu, Rect, Theme};
+
+mod coop;
+mod cues;
+mod leaderboard;
+mod link;
+mod minimap;
+mod options;
+mod scenes;
+
+use scenes::Screen;
+
+/// Where we keep the high scores
+const SCORE_FILE: &str = "SNAKE.DAT";
+
+/// How many of our own high scores we keep
+const HIGH_SCORES: usize = 5;
+
+/// The best score you need to unlock the rainbow skin
+const RAINBOW_SCORE: u32 = 500;
+
+/// What's on the title screen menu
+const MENU_ITEMS: [&str; 6] = ["Play", "Co-op", "Options", "High Scores", "Help", "Quit"];
+
+/// What's on the co-op menu
+const COOP_ITEMS: [&str; 2] = ["Host a game", "Join a game"];
+
+/// How the menus and boxes look
+const THEME: Theme = Theme {
+    normal: console::SgrParam::FgWhite,
+    border: console::SgrParam::FgYellow,
+    title: console::SgrParam::FgYellow,
+    selected: console::SgrParam::FgMagenta,
+};
+
+/// What the help screen says
+const HELP: [&str; 9] = [
+    "Steer the snake to the food, and eat it.",
+    "Each bite makes you longer, and faster.",
+    "Don't hit the walls, or yourself!",
+    "",
+    "You get a point for every move, and ten",
+    "for every bite.",
+    "",
+    "P pauses a game and Q quits it. Steer",
+    "with the arrows or the keys in SNAKE.CFG.",
+];
+
+/// Where we keep the settings
+const CONFIG_FILE: &str = "SNAKE.CFG";
+
+/// The biggest settings file we can read. The one we write with the
+/// defaults in is about a quarter of this, leaving plenty of room for more
+/// settings and the user's own comments.
+const CONFIG_SIZE: usize = 4096;
+
+/// How many steps a long loop takes between letting the OS do its
+/// housekeeping
+const YIELD_EVERY: u32 = 64;
+
+/// A short piece of text, kept without allocating
+#[derive(Debug, Copy, Clone)]
+struct Text<const N: usize> {
+    bytes: [u8; N],
+    len: usize,
+}
+
+impl<const N: usize> Text<N> {
+    /// Some empty text
+    const fn new() -> Text<N> {
+        Text {
+            bytes: [0; N],
+            len: 0,
+        }
+    }
+
+    /// Copy some text which is known to fit
+    const fn from_static(text: &'static str) -> Text<N> {
+        let mut result = Text::new();
+        let bytes = text.as_bytes();
+        while result.len < bytes.len() {
+            result.bytes[result.len] = bytes[result.len];
+            result.len += 1;
+        }
+        result
+    }
+
+    /// Copy as much of some text as fits
+    fn from_str(text: &str) -> Text<N> {
+        let mut result = Text::new();
+        for ch in text.chars() {
+            let mut buffer = [0u8; 4];
+            let encoded = ch.encode_utf8(&mut buffer).as_bytes();
+            if result.len + encoded.len() > N {
+                break;
+            }
+            result.bytes[result.len..result.len + encoded.len()].copy_from_slice(encoded);
+            result.len += encoded.len();
+        }
+        result
+    }
+
+    /// Get the text
+    fn as_str(&self) -> &str {
+        core::str::from_utf8(&self.bytes[0..self.len]).unwrap_or("")
+    }
+}
+
+/// Space kept free around the board, in characters, for things like the
+/// score
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+struct Margins {
+    top: u8,
+    bottom: u8,
+    left: u8,
+    right: u8,
+}
+
+impl Margins {
+    /// The smallest board we'll squeeze the margins down to, including its
+    /// border
+    const MIN_BOARD: (u8, u8) = (22, 12);
+
+    /// Read the margins from a configuration file, keeping enough room for
+    /// a board on a screen this big
+    fn configure(&mut self, config: &neotron_config::Config, width: u8, height: u8) {
+        let get = |key, default: u8| {
+            config
+                .get_u32("layout", key)
+                .map_or(default, |value| value.min(u32::from(u8::MAX)) as u8)
+        };
+        let spare_rows = height.saturating_sub(Self::MIN_BOARD.1);
+        let spare_cols = width.saturating_sub(Self::MIN_BOARD.0);
+        self.top = get("top", self.top).min(spare_rows);
+        self.bottom = get("bottom", self.bottom).min(spare_rows - self.top);
+        self.left = get("left", self.left).min(spare_cols);
+        self.right = get("right", self.right).min(spare_cols - self.left);
+    }
+}
 
 /// Represents the Snake application
 ///
@@ -16,7 +156,21 @@ pub struct App {
     width: u8,
     height: u8,
     stdout: neotron_sdk::File,
-    stdin: neotron_sdk::File,
+    margins: Margins,
+    menu: Menu<'static>,
+    /// Changes with every key pressed on the title screen, to mix into the
+    /// random numbers
+    seed: u16,
+    high_score: neotron_save::HighScores<HIGH_SCORES>,
+    leaderboard: leaderboard::Leaderboard,
+    /// Where to find the other player in a co-op game
+    coop: coop::Settings,
+    /// The co-op game in progress, if there is one
+    session: Option<coop::Session>,
+    /// Why we couldn't use the settings file, to say on the title screen
+    settings_error: Option<neotron_config::Error>,
+    /// The screens we're showing, one on top of the other
+    scenes: neotron_scene::Stack<Screen, 4>,
 }
 
 impl App {
@@ -30,44 +184,317 @@ impl App {
             width,
             height,
             stdout: neotron_sdk::stdout(),
-            stdin: neotron_sdk::stdin(),
+            margins: Margins {
+                top: 0,
+                bottom: 0,
+                left: 0,
+                right: 0,
+            },
+            menu: Menu::new("Snake", &MENU_ITEMS),
+            seed: 0x4f34,
+            high_score: neotron_save::HighScores::new(),
+            leaderboard: leaderboard::Leaderboard::new(),
+            coop: coop::Settings::new(),
+            session: None,
+            settings_error: None,
+            scenes: neotron_scene::Stack::new(),
         }
     }
 
     /// Play multiple games of snake.
     ///
-    /// Loops playing games and printing scores.
+    /// Shows the title screen menu, and does what's picked from it until
+    /// the user quits.
     pub fn play(&mut self) {
-        console::cursor_off(&mut self.stdout);
+        neotron_scene::play(self, &mut Keyboard::new());
+    }
+
+    /// Give the scenes to `f`, along with the rest of the app they work on
+    fn with_scenes<T, F>(&mut self, f: F) -> T
+    where
+        F: FnOnce(&mut neotron_scene::Stack<Screen, 4>, &mut App) -> T,
+    {
+        let mut scenes = core::mem::take(&mut self.scenes);
+        let result = f(&mut scenes, self);
+        self.scenes = scenes;
+        result
+    }
+
+    /// Start a game
+    fn start_game(&mut self) {
         self.clear_screen();
-        self.title_screen();
+        self.game.best = self.high_score.best().unwrap_or(0);
+        self.game.start(&mut self.stdout);
+    }
 
-        let mut seed: u16 = 0x4f34;
+    /// Draw the game again, after something has been drawn over it
+    fn redraw_game(&mut self) {
+        self.clear_screen();
+        self.game.redraw(&mut self.stdout);
+    }
 
-        'outer: loop {
-            'inner: loop {
-                let key = self.wait_for_key();
-                seed = seed.wrapping_add(1);
-                if key == b'q' || key == b'Q' {
-                    break 'outer;
-                }
-                if key == b'p' || key == b'P' {
-                    break 'inner;
-                }
-            }
+    /// Keep the score from a game. Returns whether it beat our best, and
+    /// where it came in the world.
+    fn finish_game(&mut self, score: u32) -> (bool, Option<u32>) {
+        let place = self.high_score.insert(self.leaderboard.name(), score);
+        if place.is_some() {
+            let _ = self.high_score.save(SCORE_FILE);
+        }
+        self.unlock_skins();
+
+        let world_rank = if score > 0 {
+            self.leaderboard.submit(score)
+        } else {
+            None
+        };
+        (place == Some(0), world_rank)
+    }
 
-            self.clear_screen();
+    /// Let the player have the skins their best score has earned
+    fn unlock_skins(&mut self) {
+        self.game.rainbow_unlocked = self.high_score.best().unwrap_or(0) >= RAINBOW_SCORE;
+    }
 
-            neotron_sdk::srand(seed);
+    /// Where the settings go
+    fn options_rect(&self) -> Rect {
+        Rect::centred(
+            40,
+            options::SETTINGS.len() as u8 + 2,
+            self.width,
+            self.height,
+        )
+    }
 
-            let score = self.game.play(&mut self.stdin, &mut self.stdout);
+    /// Draw the options screen
+    fn draw_options(&mut self, list: &mut ListBox) {
+        let rect = self.options_rect();
+        self.clear_screen();
+        Frame::new("Options").draw(&mut self.stdout, rect, &THEME);
+        self.centre(
+            rect.bottom() + 1,
+            "Up and Down to choose, Left and Right to change",
+        );
+        self.centre(rect.bottom() + 2, "Esc when you're done");
+        self.draw_settings(list);
+    }
 
-            self.winning_message(score);
+    /// Draw each setting and its value
+    fn draw_settings(&mut self, list: &mut ListBox) {
+        let inner = self.options_rect().inner();
+        let inner = Rect {
+            col: inner.col + 1,
+            width: inner.width - 2,
+            ..inner
+        };
+        let (game, leaderboard) = (&self.game, &self.leaderboard);
+        list.draw(&mut self.stdout, inner, &THEME, |idx, line| {
+            let setting = options::SETTINGS[idx];
+            let _ = write!(line, "{:<16}", setting.name());
+            setting.value(line, game, leaderboard);
+        });
+    }
+
+    /// Save the settings, now the user has finished changing them
+    fn finish_options(&mut self) {
+        self.save_settings();
+        self.leaderboard.fetch();
+    }
+
+    /// Show our best scores, and the world's
+    fn high_scores(&mut self) {
+        self.clear_screen();
+        let world = self.leaderboard.entries().count() as u8;
+        let height = (HIGH_SCORES as u8 + 2).max(world + 2);
+        let ours = Rect::new(4, (self.width / 2).saturating_sub(32), 28, height);
+        let theirs = Rect::new(4, self.width / 2 + 4, 28, height);
+        Frame::new("Your Best").draw(&mut self.stdout, ours, &THEME);
+        if self.high_score.entries().is_empty() {
+            neotron_tui::move_to(&mut self.stdout, ours.row + 1, ours.col + 2);
+            let _ = self.stdout.write_str("No scores yet");
+        }
+        for (idx, entry) in self.high_score.entries().iter().enumerate() {
+            neotron_tui::move_to(&mut self.stdout, ours.row + 1 + idx as u8, ours.col + 2);
+            let _ = write!(
+                self.stdout,
+                "{:>2}. {:<12} {:06}",
+                idx + 1,
+                entry.name(),
+                entry.score
+            );
         }
+        if world > 0 {
+            Frame::new("World Top Ten").draw(&mut self.stdout, theirs, &THEME);
+            for (idx, entry) in self.leaderboard.entries().enumerate() {
+                neotron_tui::move_to(&mut self.stdout, theirs.row + 1 + idx as u8, theirs.col + 2);
+                let _ = write!(
+                    self.stdout,
+                    "{:>2}. {:<12} {:06}",
+                    idx + 1,
+                    entry.name(),
+                    entry.score
+                );
+            }
+        }
+        THEME.set_normal(&mut self.stdout);
+        self.centre(self.height - 3, "Press any key");
+    }
 
-        // show cursor
-        console::cursor_on(&mut self.stdout);
+    /// Say how to play
+    fn help(&mut self) {
         self.clear_screen();
+        let rect = Rect::centred(48, HELP.len() as u8 + 4, self.width, self.height);
+        Frame::new("How to Play").draw(&mut self.stdout, rect, &THEME);
+        for (idx, line) in HELP.iter().enumerate() {
+            neotron_tui::move_to(&mut self.stdout, rect.row + 2 + idx as u8, rect.col + 4);
+            let _ = self.stdout.write_str(line);
+        }
+        THEME.set_normal(&mut self.stdout);
+        self.centre(rect.bottom() + 1, "Press any key");
+    }
+
+    /// Read the settings file, or write one out with the defaults in if
+    /// there isn't one yet
+    fn load_settings(&mut self) {
+        let mut buffer = [0u8; CONFIG_SIZE];
+        self.settings_error = None;
+        match neotron_config::load(CONFIG_FILE, &mut buffer) {
+            Ok(config) => {
+                self.margins.configure(&config, self.width, self.height);
+                self.apply_layout();
+                self.game.configure(&config);
+                self.leaderboard.configure(&config);
+                self.coop.configure(&config);
+            }
+            Err(neotron_config::Error::Io(_)) => self.save_settings(),
+            // Leave a broken file alone, so it can be fixed, but say why
+            // none of it is being used
+            Err(error) => self.settings_error = Some(error),
+        }
+    }
+
+    /// Say why the settings file isn't being used, if it isn't
+    fn settings_warning(&mut self) {
+        let Some(error) = self.settings_error else {
+            return;
+        };
+        let mut line = neotron_tui::Line::new();
+        let _ = match error {
+            neotron_config::Error::TooLarge => write!(
+                line,
+                "{} is over {} bytes, so we can't use it",
+                CONFIG_FILE, CONFIG_SIZE
+            ),
+            neotron_config::Error::NotText => {
+                write!(line, "{} isn't text, so we can't use it", CONFIG_FILE)
+            }
+            neotron_config::Error::Syntax(number) => write!(
+                line,
+                "Line {} of {} doesn't make sense, so we can't use it",
+                number, CONFIG_FILE
+            ),
+            neotron_config::Error::Io(_) => {
+                write!(line, "Couldn't read {}, so we can't use it", CONFIG_FILE)
+            }
+        };
+        console::set_sgr(
+            &mut self.stdout,
+            [console::SgrParam::Bold, console::SgrParam::FgRed],
+        );
+        self.centre(self.height - 2, line.as_str());
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+    }
+
+    /// Where the board and its border go, inside the margins
+    fn playfield(&self) -> Rect {
+        Rect::new(
+            self.margins.top,
+            self.margins.left,
+            self.width - self.margins.left - self.margins.right,
+            self.height - self.margins.top - self.margins.bottom,
+        )
+    }
+
+    /// Tell the game where the board is on screen, and what it can put in
+    /// the margins
+    fn apply_layout(&mut self) {
+        let field = self.playfield();
+        let inner = field.inner();
+        self.game.resize_view(
+            inner.width,
+            inner.height,
+            console::Position {
+                row: inner.row,
+                col: inner.col,
+            },
+        );
+        self.game.margin_right = self.margins.right;
+        // The score goes just above the board, or just below it
+        self.game.hud = if self.margins.top > 0 {
+            Some(console::Position {
+                row: field.row - 1,
+                col: inner.col,
+            })
+        } else if self.margins.bottom > 0 {
+            Some(console::Position {
+                row: field.bottom(),
+                col: inner.col,
+            })
+        } else {
+            None
+        };
+    }
+
+    /// Write the current settings out
+    fn save_settings(&mut self) {
+        let Ok(mut writer) = neotron_config::create(CONFIG_FILE) else {
+            return;
+        };
+        let _ = writer.comment("Neotron Snake settings");
+        let _ = writer.section("game");
+        let _ = writer.comment("Milliseconds per move at the start. Smaller is faster.");
+        let _ = writer.set("start_tick_ms", self.game.starting_tick);
+        let _ = writer.comment("How big the board is. It scrolls if it's bigger than the screen.");
+        let _ = writer.set("board_width", self.game.width);
+        let _ = writer.set("board_height", self.game.height);
+        let _ = writer.section("keys");
+        let keys = self.game.keys;
+        let _ = writer.set("up", char::from(keys.up));
+        let _ = writer.set("down", char::from(keys.down));
+        let _ = writer.set("left", char::from(keys.left));
+        let _ = writer.set("right", char::from(keys.right));
+        let _ = writer.comment("Ignore a key pressed again sooner than this many milliseconds,");
+        let _ = writer.comment("for keyboards that repeat too soon. If this isn't set, we use");
+        let _ = writer.comment("repeat_ms from the [keyboard] section of NEOTRON.CFG.");
+        match self.game.repeat_ms {
+            Some(ms) => {
+                let _ = writer.set("repeat_ms", ms);
+            }
+            None => {
+                let _ = writer.comment("repeat_ms = 100");
+            }
+        }
+        let _ = writer.section("display");
+        let _ = writer.comment("Show a map of the board: auto (when it doesn't fit), on or off.");
+        let _ = writer.set("minimap", self.game.minimap_mode.name());
+        let _ = writer.comment("How a big board scrolls: centre (always) or jump (less often).");
+        let _ = writer.set("camera", self.game.camera_mode.name());
+        let _ = writer.comment("Draw each square two columns wide, so it looks square.");
+        let _ = writer.set_bool("square_cells", self.game.cell_width == 2);
+        let _ = writer.comment("How the snake looks: classic, or rainbow once you've earned it.");
+        let _ = writer.set("skin", self.game.skin.name());
+        let _ = writer.section("sound");
+        let _ = writer.comment("Sounds that say which way the food, walls and body are.");
+        let _ = writer.set_bool("cues", self.game.cues.enabled());
+        let _ = writer.section("layout");
+        let _ =
+            writer.comment("Space to leave around the board. The score goes above or below it,");
+        let _ = writer.comment("and the map goes on the right if there's room.");
+        let _ = writer.set("top", self.margins.top);
+        let _ = writer.set("bottom", self.margins.bottom);
+        let _ = writer.set("left", self.margins.left);
+        let _ = writer.set("right", self.margins.right);
+        self.leaderboard.save_settings(&mut writer);
+        self.coop.save_settings(&mut writer);
     }
 
     /// Clear the screen and draw the board.
@@ -82,85 +509,226 @@ impl App {
                 console::SgrParam::BgBlack,
             ],
         );
-        console::move_cursor(&mut self.stdout, console::Position::origin());
+        let field = self.playfield();
+        let across = usize::from(field.width - 2);
+        neotron_tui::move_to(&mut self.stdout, field.row, field.col);
         let _ = self.stdout.write_char('╔');
-        for _ in 1..self.width - 1 {
-            let _ = self.stdout.write_char('═');
-        }
+        neotron_tui::write_repeated(&mut self.stdout, '═', across);
         let _ = self.stdout.write_char('╗');
-        console::move_cursor(
-            &mut self.stdout,
-            console::Position {
-                row: self.height - 1,
-                col: 0,
-            },
-        );
+        neotron_tui::move_to(&mut self.stdout, field.bottom() - 1, field.col);
         let _ = self.stdout.write_char('╚');
-        for _ in 1..self.width - 1 {
-            let _ = self.stdout.write_char('═');
-        }
+        neotron_tui::write_repeated(&mut self.stdout, '═', across);
         let _ = self.stdout.write_char('╝');
-        for row in 1..self.height - 1 {
-            console::move_cursor(&mut self.stdout, console::Position { row, col: 0 });
+        for row in field.row + 1..field.bottom() - 1 {
+            neotron_tui::move_to(&mut self.stdout, row, field.col);
             let _ = self.stdout.write_char('║');
-            console::move_cursor(
-                &mut self.stdout,
-                console::Position {
-                    row,
-                    col: self.width - 1,
-                },
-            );
+            neotron_tui::move_to(&mut self.stdout, row, field.right() - 1);
             let _ = self.stdout.write_char('║');
         }
         console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
     }
 
-    /// Show the title screen
+    /// Show the title screen, with the menu, and the world's best scores
+    /// beside it if the leaderboard server gave us any
     fn title_screen(&mut self) {
         console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
-        let message = "Neotron Snake by theJPster";
-        let pos = console::Position {
-            row: self.height / 2,
-            col: (self.width - message.chars().count() as u8) / 2,
+        self.centre(3, "Neotron Snake by theJPster");
+        self.draw_menu();
+        let entries = self.leaderboard.entries().count() as u8;
+        if entries > 0 {
+            let rect = Rect::new(6, self.width / 2 + 4, 28, entries + 2);
+            Frame::new("World Top Ten").draw(&mut self.stdout, rect, &THEME);
+            for (idx, entry) in self.leaderboard.entries().enumerate() {
+                neotron_tui::move_to(&mut self.stdout, rect.row + 1 + idx as u8, rect.col + 2);
+                let _ = write!(
+                    self.stdout,
+                    "{:>2}. {:<12} {:06}",
+                    idx + 1,
+                    entry.name(),
+                    entry.score
+                );
+            }
+            THEME.set_normal(&mut self.stdout);
+        }
+        self.centre(self.height - 3, "Arrows and Enter to choose");
+        self.settings_warning();
+    }
+
+    /// Draw the title screen menu - in the middle, or on the left if the
+    /// world's best scores are showing
+    fn draw_menu(&mut self) {
+        let (width, height) = self.menu.size();
+        let col = if self.leaderboard.entries().next().is_some() {
+            (self.width / 2).saturating_sub(width + 8)
+        } else {
+            (self.width - width) / 2
         };
-        console::move_cursor(&mut self.stdout, pos);
-        let _ = self.stdout.write_str(message);
-        let message = "Q to Quit | 'P' to Play";
-        let pos = console::Position {
-            row: pos.row + 1,
-            col: (self.width - message.chars().count() as u8) / 2,
+        self.menu
+            .draw(&mut self.stdout, Rect::new(6, col, width, height), &THEME);
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+    }
+
+    /// Say the game is paused, in the middle of the board
+    fn paused_message(&mut self) {
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        let field = self.playfield();
+        let row = field.row + field.height / 2;
+        self.centre_in(field, row, "Paused");
+        self.centre_in(field, row + 1, "Press any key");
+    }
+
+    /// Show the co-op menu
+    fn coop_screen(&mut self, menu: &mut Menu) {
+        self.clear_screen();
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        self.centre(3, "Play together, over the link in SNAKE.CFG");
+        self.draw_coop_menu(menu);
+        self.centre(
+            self.height - 3,
+            "Arrows and Enter to choose, Esc to go back",
+        );
+    }
+
+    /// Draw the co-op menu, in the middle
+    fn draw_coop_menu(&mut self, menu: &mut Menu) {
+        let (width, height) = menu.size();
+        let rect = Rect::new(6, (self.width - width) / 2, width, height);
+        menu.draw(&mut self.stdout, rect, &THEME);
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+    }
+
+    /// Say we're waiting for the other player to turn up
+    fn connecting_message(&mut self, host: bool) {
+        self.clear_screen();
+        let mut line = neotron_tui::Line::new();
+        let _ = if host {
+            write!(line, "Waiting for a partner on {}", self.coop.endpoint())
+        } else {
+            write!(line, "Looking for a game on {}", self.coop.endpoint())
         };
-        console::move_cursor(&mut self.stdout, pos);
-        let _ = self.stdout.write_str(message);
+        let field = self.playfield();
+        let row = field.row + field.height / 2;
+        self.centre_in(field, row, line.as_str());
+        self.centre_in(field, row + 1, "Esc to give up");
     }
 
-    /// Spin until a key is pressed
-    fn wait_for_key(&mut self) -> u8 {
-        loop {
-            let mut buffer = [0u8; 1];
-            if let Ok(1) = self.stdin.read(&mut buffer) {
-                return buffer[0];
-            }
-            neotron_sdk::delay(core::time::Duration::from_millis(10));
+    /// Say we're waiting for the other player's keys, in the middle of the
+    /// board
+    fn stalled_message(&mut self) {
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        let field = self.playfield();
+        let row = field.row + field.height / 2;
+        self.centre_in(field, row, "Waiting for your partner");
+        self.centre_in(field, row + 1, "Q to give up");
+    }
+
+    /// Say how a co-op game ended
+    fn coop_message(&mut self, ending: coop::Ending, score: u32) {
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        let field = self.playfield();
+        let mut row = field.row + field.height / 2;
+        self.centre_in(field, row, ending.message());
+        row += 1;
+        let mut line = neotron_tui::Line::new();
+        if ending.has_score() {
+            let _ = write!(line, "Score: {:06}", score);
+        } else {
+            let _ = write!(line, "Is {} right?", self.coop.endpoint());
         }
+        self.centre_in(field, row, line.as_str());
+        self.centre_in(field, row + 1, "Press any key");
     }
 
-    /// Print the game over message with the given score
-    fn winning_message(&mut self, score: u32) {
+    /// Write some text in the middle of a row
+    fn centre(&mut self, row: u8, text: &str) {
+        self.centre_in(Rect::new(0, 0, self.width, self.height), row, text);
+    }
+
+    /// Write some text in the middle of a row of `rect`, cutting it short
+    /// if it doesn't fit
+    fn centre_in(&mut self, rect: Rect, row: u8, text: &str) {
+        let width = usize::from(rect.width);
+        let col = rect.col + neotron_text::centre_offset(text, width) as u8;
+        neotron_tui::move_to(&mut self.stdout, row, col);
+        let _ = write!(self.stdout, "{}", neotron_text::ellipsis(text, width));
+    }
+
+    /// Print the game over message with the given score, whether it beat
+    /// the high score, and where it came in the world
+    fn winning_message(&mut self, score: u32, new_best: bool, world_rank: Option<u32>) {
         console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
-        let pos = console::Position {
-            row: self.height / 2,
-            col: (self.width - 13u8) / 2,
-        };
-        console::move_cursor(&mut self.stdout, pos);
-        let _ = writeln!(self.stdout, "Score: {:06}", score);
-        let message = "Q to Quit | 'P' to Play";
-        let pos = console::Position {
-            row: pos.row + 1,
-            col: (self.width - message.chars().count() as u8) / 2,
-        };
-        console::move_cursor(&mut self.stdout, pos);
-        let _ = self.stdout.write_str(message);
+        let field = self.playfield();
+        let mut row = field.row + field.height / 2;
+        let mut line = neotron_tui::Line::new();
+        let _ = write!(line, "Score: {:06}", score);
+        self.centre_in(field, row, line.as_str());
+        row += 1;
+        line.clear();
+        if new_best {
+            let _ = line.write_str("New high score!");
+        } else {
+            let best = self.high_score.best().unwrap_or(0);
+            let _ = write!(line, "Best: {:06}", best);
+        }
+        self.centre_in(field, row, line.as_str());
+        row += 1;
+        if let Some(rank) = world_rank {
+            line.clear();
+            let _ = write!(line, "World rank: {}", rank);
+            self.centre_in(field, row, line.as_str());
+            row += 1;
+        }
+        self.centre_in(field, row, "Press any key");
+    }
+}
+
+impl neotron_scene::Game for App {
+    fn start(&mut self) {
+        console::cursor_off(&mut self.stdout);
+        // No file, or a damaged one, just means starting from scratch
+        self.high_score = neotron_save::HighScores::load(SCORE_FILE).unwrap_or_default();
+        self.unlock_skins();
+        self.load_settings();
+        // If the server's not there, we just don't show the world's best
+        self.leaderboard.fetch();
+        self.game.rng = neotron_rand::Rng::from_rtc();
+        self.with_scenes(|scenes, app| scenes.start(Screen::Title, app));
+    }
+
+    /// How soon a key can repeat, from our settings file if it says, or
+    /// from the settings every application shares
+    fn repeat_ms(&self) -> u32 {
+        self.game.repeat_ms.unwrap_or_else(|| {
+            let mut shared: neotron_settings::Settings<16> = neotron_settings::Settings::new();
+            // No shared settings just means the defaults
+            let _ = shared.load();
+            shared.get(&neotron_settings::shared::KEY_REPEAT_MS)
+        })
+    }
+
+    fn tick_ms(&self) -> Option<u32> {
+        self.scenes.tick_ms(self)
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) -> bool {
+        self.with_scenes(|scenes, app| {
+            scenes.handle_input(app, key);
+            !scenes.is_empty()
+        })
+    }
+
+    fn tick(&mut self) -> bool {
+        self.with_scenes(|scenes, app| {
+            scenes.update(app);
+            !scenes.is_empty()
+        })
+    }
+
+    fn finish(&mut self) {
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        console::clear_screen(&mut self.stdout);
+        console::move_cursor(&mut self.stdout, console::Position::origin());
+        console::cursor_on(&mut self.stdout);
     }
 }
 
@@ -168,6 +736,8 @@ impl App {
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 enum Piece {
     Head,
+    /// The other player's head, in a co-op game
+    Partner,
     Food,
     Body,
 }
@@ -177,250 +747,842 @@ impl Piece {
     fn get_char(self) -> char {
         match self {
             Piece::Body => '▓',
-            Piece::Head => '█',
+            Piece::Head | Piece::Partner => '█',
             Piece::Food => '▲',
         }
     }
 
+    /// Get the Unicode chars for this piece, when a square is two columns
+    /// wide
+    fn get_wide_chars(self) -> [char; 2] {
+        match self {
+            Piece::Body => ['▓', '▓'],
+            Piece::Head | Piece::Partner => ['█', '█'],
+            Piece::Food => ['◄', '►'],
+        }
+    }
+
+    /// Get the Unicode char for one column of this piece
+    fn get_char_at(self, column: u8, cell_width: u8) -> char {
+        if cell_width == 1 {
+            self.get_char()
+        } else {
+            self.get_wide_chars()[usize::from(column.min(1))]
+        }
+    }
+
     /// Get the ANSI colour for this piece
     fn get_colour(self) -> console::SgrParam {
         match self {
             Piece::Body => console::SgrParam::FgMagenta,
             Piece::Head => console::SgrParam::FgYellow,
+            Piece::Partner => console::SgrParam::FgCyan,
             Piece::Food => console::SgrParam::FgGreen,
         }
     }
 }
 
+/// How the snake looks
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Skin {
+    /// Magenta, with a yellow head
+    Classic,
+    /// Every colour, moving down the snake as it goes. You have to earn
+    /// this one.
+    Rainbow,
+}
+
+impl Skin {
+    /// The colours the rainbow skin goes through
+    const RAINBOW: [console::SgrParam; 6] = [
+        console::SgrParam::FgRed,
+        console::SgrParam::FgYellow,
+        console::SgrParam::FgGreen,
+        console::SgrParam::FgCyan,
+        console::SgrParam::FgBlue,
+        console::SgrParam::FgMagenta,
+    ];
+
+    /// Read the skin from a setting
+    fn parse(text: &str) -> Option<Skin> {
+        if text.eq_ignore_ascii_case("classic") {
+            Some(Skin::Classic)
+        } else if text.eq_ignore_ascii_case("rainbow") {
+            Some(Skin::Rainbow)
+        } else {
+            None
+        }
+    }
+
+    /// The name of the skin, for the settings file
+    fn name(self) -> &'static str {
+        match self {
+            Skin::Classic => "classic",
+            Skin::Rainbow => "rainbow",
+        }
+    }
+}
+
+/// How the view follows the snake round a board that's bigger than the
+/// screen
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum CameraMode {
+    /// Keep the head in the middle of the screen
+    Centre,
+    /// Leave the view where it is until the head gets near its edge, then
+    /// put the head back in the middle. This redraws the screen much less
+    /// often, which helps on a slow terminal.
+    Jump,
+}
+
+impl CameraMode {
+    /// Read the mode from a setting
+    fn parse(text: &str) -> Option<CameraMode> {
+        if text.eq_ignore_ascii_case("centre") || text.eq_ignore_ascii_case("center") {
+            Some(CameraMode::Centre)
+        } else if text.eq_ignore_ascii_case("jump") {
+            Some(CameraMode::Jump)
+        } else {
+            None
+        }
+    }
+
+    /// The name of the mode, for the settings file
+    fn name(self) -> &'static str {
+        match self {
+            CameraMode::Centre => "centre",
+            CameraMode::Jump => "jump",
+        }
+    }
+
+    /// Where the view should start along one axis, given where it starts
+    /// now, where the head is, and how big the view and the board are
+    fn follow(self, start: u8, head: u8, view: u8, board: u8) -> u8 {
+        let centred = head
+            .saturating_sub(view / 2)
+            .min(board.saturating_sub(view));
+        match self {
+            CameraMode::Centre => centred,
+            CameraMode::Jump => {
+                let margin = view / 4;
+                if head < start.saturating_add(margin)
+                    || head >= start.saturating_add(view).saturating_sub(margin)
+                {
+                    centred
+                } else {
+                    start
+                }
+            }
+        }
+    }
+}
+
+/// The keys that steer the snake
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct Keys {
+    up: u8,
+    down: u8,
+    left: u8,
+    right: u8,
+}
+
+impl Keys {
+    /// W, A, S and D
+    const DEFAULT: Keys = Keys {
+        up: b'w',
+        down: b's',
+        left: b'a',
+        right: b'd',
+    };
+}
+
 /// Represents one game of Snake
 struct Game {
     board: Board<{ Self::MAX_WIDTH }, { Self::MAX_HEIGHT }>,
     width: u8,
     height: u8,
     offset: console::Position,
-    head: console::Position,
-    tail: console::Position,
-    direction: Direction,
+    /// The snakes on the board. A game for one only uses the first.
+    snakes: [Snake; 2],
+    /// How many snakes are playing
+    players: usize,
+    /// Which snake is ours. The view follows it and the keys steer it.
+    me: usize,
+    /// Which snake crashed, when a game has ended
+    crashed: Option<usize>,
     score: u32,
-    digesting: u32,
     tick_interval_ms: u16,
+    starting_tick: u16,
+    /// The fastest the game gets
+    min_tick_ms: u16,
+    keys: Keys,
+    /// How soon a key can repeat, if we've been told
+    repeat_ms: Option<u32>,
+    /// Decides where the food goes
+    rng: neotron_rand::Rng,
+    /// How many columns of the screen the board can use
+    screen_width: u8,
+    /// How many columns each square of the board takes up. Character
+    /// cells are about twice as tall as they are wide, so two columns
+    /// makes the squares look square.
+    cell_width: u8,
+    /// How much of the board fits on the screen, in squares
+    view_width: u8,
+    view_height: u8,
+    /// Which square of the board is in the top left of the screen
+    camera: console::Position,
+    camera_mode: CameraMode,
+    /// Shows the whole board in the corner, if it doesn't fit
+    minimap: minimap::Minimap,
+    minimap_mode: minimap::Mode,
+    /// How the snake looks
+    skin: Skin,
+    /// Has the player earned the rainbow skin yet?
+    rainbow_unlocked: bool,
+    /// Counts moves, to step the rainbow along
+    ticks: u32,
+    /// Where to show the score, if there's room outside the board
+    hud: Option<console::Position>,
+    /// The high score, to show next to the score
+    best: u32,
+    /// How many columns there are to the right of the board's border
+    margin_right: u8,
+    /// Where the food is
+    food: console::Position,
+    /// Sounds that say what's near the head
+    cues: cues::Cues,
 }
 
 impl Game {
     /// The maximum width board we can handle
-    pub const MAX_WIDTH: usize = 78;
+    pub const MAX_WIDTH: usize = 200;
     /// The maximum height board we can handle
-    pub const MAX_HEIGHT: usize = 23;
+    pub const MAX_HEIGHT: usize = 100;
     /// How many ms per tick do we start at?
     const STARTING_TICK: u16 = 100;
+    /// How many ms per tick we speed up to, at most
+    const MIN_TICK: u16 = 5;
 
     /// Make a new game.
     ///
-    /// Give the width and the height of the game board, and where on the screen
-    /// the board should be located.
+    /// Give the width and the height of the space on screen for the board,
+    /// and where on the screen it is. The board starts off that size, but
+    /// the settings can make it bigger.
     const fn new(width: u8, height: u8, offset: console::Position) -> Game {
         Game {
             board: Board::new(),
             width,
             height,
             offset,
-            head: console::Position { row: 0, col: 0 },
-            tail: console::Position { row: 0, col: 0 },
-            direction: Direction::Up,
+            snakes: [Snake::new(console::Position { row: 0, col: 0 }); 2],
+            players: 1,
+            me: 0,
+            crashed: None,
             score: 0,
-            digesting: 3,
             tick_interval_ms: Self::STARTING_TICK,
+            starting_tick: Self::STARTING_TICK,
+            min_tick_ms: Self::MIN_TICK,
+            keys: Keys::DEFAULT,
+            repeat_ms: None,
+            rng: neotron_rand::Rng::new(0),
+            screen_width: width,
+            cell_width: 1,
+            view_width: width,
+            view_height: height,
+            camera: console::Position { row: 0, col: 0 },
+            camera_mode: CameraMode::Centre,
+            minimap: minimap::Minimap::new(),
+            minimap_mode: minimap::Mode::Auto,
+            skin: Skin::Classic,
+            rainbow_unlocked: false,
+            ticks: 0,
+            hud: None,
+            best: 0,
+            margin_right: 0,
+            food: console::Position { row: 0, col: 0 },
+            cues: cues::Cues::new(),
+        }
+    }
+
+    /// Take any settings we understand from a configuration file
+    fn configure(&mut self, config: &neotron_config::Config) {
+        if let Some(ms) = config.get_u32("game", "start_tick_ms") {
+            self.starting_tick = ms.clamp(5, 1000) as u16;
+        }
+        if let Some(square) = config.get_bool("display", "square_cells") {
+            self.set_square_cells(square);
+        }
+        // The board can't be smaller than the screen, or there'd be gaps
+        if let Some(width) = config.get_u32("game", "board_width") {
+            self.width = width.clamp(u32::from(self.view_width), Self::MAX_WIDTH as u32) as u8;
+        }
+        if let Some(height) = config.get_u32("game", "board_height") {
+            self.height = height.clamp(u32::from(self.view_height), Self::MAX_HEIGHT as u32) as u8;
+        }
+        if let Some(mode) = config.get("display", "camera").and_then(CameraMode::parse) {
+            self.camera_mode = mode;
+        }
+        if let Some(skin) = config.get("display", "skin").and_then(Skin::parse) {
+            self.skin = skin;
+        }
+        for (name, key) in [
+            ("up", &mut self.keys.up),
+            ("down", &mut self.keys.down),
+            ("left", &mut self.keys.left),
+            ("right", &mut self.keys.right),
+        ] {
+            if let Some(ch) = config
+                .get_char("keys", name)
+                .filter(|ch| ch.is_ascii_graphic())
+            {
+                *key = ch.to_ascii_lowercase() as u8;
+            }
+        }
+        if let Some(mode) = config
+            .get("display", "minimap")
+            .and_then(minimap::Mode::parse)
+        {
+            self.minimap_mode = mode;
+        }
+        if let Some(ms) = config.get_u32("keys", "repeat_ms") {
+            self.repeat_ms = Some(ms.min(1000));
+        }
+        if let Some(cues) = config.get_bool("sound", "cues") {
+            self.cues.set_enabled(cues);
         }
     }
 
-    /// Play a game
-    fn play(&mut self, stdin: &mut neotron_sdk::File, stdout: &mut neotron_sdk::File) -> u32 {
+    /// Draw each square one or two columns wide. A board that fitted the
+    /// screen before still does.
+    fn set_square_cells(&mut self, square: bool) {
+        self.cell_width = if square { 2 } else { 1 };
+        self.resize_view(self.screen_width, self.view_height, self.offset);
+    }
+
+    /// Change the space on screen for the board, and where it is. A board
+    /// that fitted the screen before still does, and one that was bigger
+    /// is at least as big as the screen.
+    fn resize_view(&mut self, width: u8, height: u8, offset: console::Position) {
+        let fitted_width = self.width == self.view_width;
+        let fitted_height = self.height == self.view_height;
+        self.screen_width = width;
+        self.view_width = width / self.cell_width;
+        self.view_height = height;
+        self.offset = offset;
+        self.width = if fitted_width {
+            self.view_width
+        } else {
+            self.width.max(self.view_width)
+        };
+        self.height = if fitted_height {
+            self.view_height
+        } else {
+            self.height.max(self.view_height)
+        };
+    }
+
+    /// Start a game, and draw the first bit of each snake and the food
+    fn start(&mut self, stdout: &mut neotron_sdk::File) {
         // Reset score and speed, and start with a bit of snake
         self.score = 0;
-        self.tick_interval_ms = Self::STARTING_TICK;
-        self.digesting = 2;
+        self.tick_interval_ms = self.starting_tick;
+        self.crashed = None;
         // Wipe board
         self.board.reset();
-        // Add offset snake
-        self.head = console::Position {
-            row: self.height / 4,
-            col: self.width / 4,
-        };
-        self.tail = self.head;
-        self.board.store_body(self.head, self.direction);
-        self.write_at(stdout, self.head, Some(Piece::Head));
+        self.reset_minimap();
+        self.minimap.draw(stdout);
+        // Add offset snakes, a quarter of the way in from opposite corners
+        self.snakes = [
+            Snake::new(console::Position {
+                row: self.height / 4,
+                col: self.width / 4,
+            }),
+            Snake::new(console::Position {
+                row: self.height - 1 - self.height / 4,
+                col: self.width - 1 - self.width / 4,
+            }),
+        ];
+        self.camera = console::Position { row: 0, col: 0 };
+        self.follow_head();
+        for idx in 0..self.players {
+            let snake = self.snakes[idx];
+            self.board.store_body(snake.head, snake.direction);
+            self.write_at(stdout, snake.head, Some(self.head_piece(idx)));
+            self.minimap.add_snake(stdout, snake.head);
+        }
+        self.minimap.move_head(stdout, self.snakes[self.me].head);
         // Add random food
-        let pos = self.random_empty_position();
-        self.board.store_food(pos);
-        self.write_at(stdout, pos, Some(Piece::Food));
+        self.add_food(stdout);
+    }
 
-        'game: loop {
-            // Wait for frame tick
-            neotron_sdk::delay(core::time::Duration::from_millis(
-                self.tick_interval_ms as u64,
-            ));
-
-            // 1 point for not being dead
-            self.score += 1;
-
-            // Read input
-            'input: loop {
-                let mut buffer = [0u8; 1];
-                if let Ok(1) = stdin.read(&mut buffer) {
-                    match buffer[0] {
-                        b'w' | b'W' => {
-                            // Going up
-                            if self.direction.is_horizontal() {
-                                self.direction = Direction::Up;
-                            }
-                        }
-                        b's' | b'S' => {
-                            // Going down
-                            if self.direction.is_horizontal() {
-                                self.direction = Direction::Down;
-                            }
-                        }
-                        b'a' | b'A' => {
-                            // Going left
-                            if self.direction.is_vertical() {
-                                self.direction = Direction::Left;
-                            }
-                        }
-                        b'd' | b'D' => {
-                            // Going right
-                            if self.direction.is_vertical() {
-                                self.direction = Direction::Right;
-                            }
-                        }
-                        b'q' | b'Q' => {
-                            // Quit game
-                            break 'game;
-                        }
-                        _ => {
-                            // ignore
-                        }
-                    }
-                } else {
-                    break 'input;
+    /// Empty the map, and work out where it goes
+    fn reset_minimap(&mut self) {
+        let show_map = match self.minimap_mode {
+            minimap::Mode::On => true,
+            minimap::Mode::Off => false,
+            minimap::Mode::Auto => self.width > self.view_width || self.height > self.view_height,
+        };
+        // The map goes to the right of the board if there's room for it
+        // and its frame, or in the corner of the board if not
+        let (map_cols, _) = minimap::Minimap::size(self.width, self.height);
+        let map_col = if self.margin_right > map_cols + 1 {
+            self.offset.col + self.screen_width + 1 + self.margin_right
+        } else {
+            self.offset.col + self.view_width * self.cell_width
+        };
+        self.minimap.reset(
+            show_map,
+            self.width,
+            self.height,
+            console::Position {
+                row: self.offset.row + self.view_height,
+                col: map_col,
+            },
+        );
+    }
+
+    /// Put everything on the board back on the map, after the board has
+    /// been changed under it
+    fn rebuild_minimap(&mut self, stdout: &mut neotron_sdk::File) {
+        self.reset_minimap();
+        for snake in &self.snakes[..self.players] {
+            let mut position = snake.tail;
+            loop {
+                self.minimap.add_snake(stdout, position);
+                if position.row == snake.head.row && position.col == snake.head.col {
+                    break;
                 }
+                let Some(direction) = self.board.direction_at(position) else {
+                    break;
+                };
+                position = direction.step(position);
             }
+        }
+        self.minimap.add_food(stdout, self.food);
+        self.minimap.move_head(stdout, self.snakes[self.me].head);
+    }
 
-            // Mark which way we're going in the old head position
-            self.board.store_body(self.head, self.direction);
-            self.write_at(stdout, self.head, Some(Piece::Body));
+    /// Move every snake on one square. Returns `false` if one crashed, and
+    /// then `crashed` says which.
+    fn step(&mut self, stdout: &mut neotron_sdk::File) -> bool {
+        // 1 point for not being dead
+        self.score += 1;
+        self.draw_hud(stdout);
 
-            // Update head position
-            match self.direction {
-                Direction::Up => {
-                    if self.head.row == 0 {
-                        break 'game;
-                    }
-                    self.head.row -= 1;
+        // The snakes always move in the same order, so both ends of a
+        // co-op game agree who got somewhere first
+        let mut scrolled = false;
+        for idx in 0..self.players {
+            if !self.move_snake(stdout, idx, &mut scrolled) {
+                self.crashed = Some(idx);
+                return false;
+            }
+        }
+
+        if scrolled {
+            self.draw_view(stdout);
+        }
+
+        self.ticks = self.ticks.wrapping_add(1);
+        if self.skin == Skin::Rainbow && self.rainbow_unlocked {
+            self.draw_rainbow(stdout);
+        }
+        if self.cues.enabled() {
+            let danger = self.nearest_danger();
+            let food = self.way_to_food();
+            self.cues
+                .after_move(danger, food, u32::from(self.tick_interval_ms));
+        }
+        true
+    }
+
+    /// Move one snake on one square. Returns `false` if it crashed. If the
+    /// view has to move to keep up with our snake, `scrolled` is set.
+    fn move_snake(
+        &mut self,
+        stdout: &mut neotron_sdk::File,
+        idx: usize,
+        scrolled: &mut bool,
+    ) -> bool {
+        let snake = &mut self.snakes[idx];
+        if let Some(turn) = snake.turns.pop() {
+            snake.direction = turn;
+        }
+        let (old_head, direction) = (snake.head, snake.direction);
+
+        // Mark which way we're going in the old head position
+        self.board.store_body(old_head, direction);
+        self.write_at(stdout, old_head, Some(Piece::Body));
+
+        // Update head position, unless that's into a wall
+        let Some(head) = self.neighbour(old_head, direction) else {
+            return false;
+        };
+        self.snakes[idx].head = head;
+
+        // Keep our head on screen. Everything we draw from here on goes
+        // where the view is now, and then we redraw anything else.
+        if idx == self.me {
+            *scrolled |= self.follow_head();
+        }
+
+        // Check what we just ate
+        //   - Food => get longer
+        //   - A snake => die
+        if self.board.is_food(head) {
+            // yum
+            self.minimap.remove_food(stdout, head);
+            self.score += 10;
+            self.snakes[idx].digesting = 2;
+            // Drop 10% on the tick interval
+            self.tick_interval_ms *= 9;
+            self.tick_interval_ms /= 10;
+            if self.tick_interval_ms < self.min_tick_ms {
+                // Maximum speed
+                self.tick_interval_ms = self.min_tick_ms;
+            }
+            // Add random food
+            self.add_food(stdout);
+        } else if self.board.is_body(head) {
+            // oh no
+            return false;
+        }
+
+        // Write the new head
+        self.board.store_body(head, direction);
+        self.write_at(stdout, head, Some(self.head_piece(idx)));
+        self.minimap.add_snake(stdout, head);
+        if idx == self.me {
+            self.minimap.move_head(stdout, head);
+        }
+
+        let snake = &mut self.snakes[idx];
+        if snake.digesting == 0 {
+            let old_tail = snake.tail;
+            match self.board.remove_piece(old_tail) {
+                Some(way) => snake.tail = way.step(old_tail),
+                None => {
+                    panic!("Bad game state");
                 }
-                Direction::Down => {
-                    if self.head.row == self.height - 1 {
-                        break 'game;
+            }
+            self.write_at(stdout, old_tail, None);
+            self.minimap.remove_snake(stdout, old_tail);
+        } else {
+            snake.digesting -= 1;
+        }
+        true
+    }
+
+    /// How a snake's head is drawn - ours, or the other player's
+    fn head_piece(&self, idx: usize) -> Piece {
+        if idx == self.me {
+            Piece::Head
+        } else {
+            Piece::Partner
+        }
+    }
+
+    /// Put some food somewhere empty
+    fn add_food(&mut self, stdout: &mut neotron_sdk::File) {
+        let pos = self.random_empty_position();
+        self.food = pos;
+        self.board.store_food(pos);
+        self.write_at(stdout, pos, Some(Piece::Food));
+        self.minimap.add_food(stdout, pos);
+    }
+
+    /// The nearest wall or body straight ahead of the head or to either
+    /// side of it, if there's one close enough to worry about. Gives what
+    /// it is, which way it is and how many squares away.
+    fn nearest_danger(&self) -> Option<(cues::Thing, Direction, u8)> {
+        let Snake {
+            head, direction, ..
+        } = self.snakes[self.me];
+        let sides = if direction.is_vertical() {
+            [Direction::Left, Direction::Right]
+        } else {
+            [Direction::Up, Direction::Down]
+        };
+        let mut nearest: Option<(cues::Thing, Direction, u8)> = None;
+        // Straight ahead goes first, so it wins a tie
+        for way in [direction, sides[0], sides[1]] {
+            let mut position = head;
+            for distance in 1..=cues::DANGER_RANGE {
+                let thing = match self.neighbour(position, way) {
+                    None => Some(cues::Thing::Wall),
+                    Some(next) if self.board.piece_at(next) == Some(Piece::Body) => {
+                        Some(cues::Thing::Body)
                     }
-                    self.head.row += 1;
-                }
-                Direction::Left => {
-                    if self.head.col == 0 {
-                        break 'game;
+                    Some(next) => {
+                        position = next;
+                        None
                     }
-                    self.head.col -= 1;
-                }
-                Direction::Right => {
-                    if self.head.col == self.width - 1 {
-                        break 'game;
+                };
+                if let Some(thing) = thing {
+                    if nearest.is_none_or(|(_, _, nearest)| distance < nearest) {
+                        nearest = Some((thing, way, distance));
                     }
-                    self.head.col += 1;
+                    break;
                 }
             }
+        }
+        nearest
+    }
 
-            // Check what we just ate
-            //   - Food => get longer
-            //   - Ourselves => die
-            if self.board.is_food(self.head) {
-                // yum
-                self.score += 10;
-                self.digesting = 2;
-                // Drop 10% on the tick interval
-                self.tick_interval_ms *= 9;
-                self.tick_interval_ms /= 10;
-                if self.tick_interval_ms < 5 {
-                    // Maximum speed
-                    self.tick_interval_ms = 5;
-                }
-                // Add random food
-                let pos = self.random_empty_position();
-                self.board.store_food(pos);
-                self.write_at(stdout, pos, Some(Piece::Food));
-            } else if self.board.is_body(self.head) {
-                // oh no
-                break 'game;
+    /// Which way the food is from the head, mostly, and how many squares
+    /// it is away
+    fn way_to_food(&self) -> (Direction, u16) {
+        let head = self.snakes[self.me].head;
+        let rows = i16::from(self.food.row) - i16::from(head.row);
+        let cols = i16::from(self.food.col) - i16::from(head.col);
+        let way = if rows.abs() >= cols.abs() {
+            if rows < 0 {
+                Direction::Up
+            } else {
+                Direction::Down
             }
+        } else if cols < 0 {
+            Direction::Left
+        } else {
+            Direction::Right
+        };
+        (way, rows.unsigned_abs() + cols.unsigned_abs())
+    }
 
-            // Write the new head
-            self.board.store_body(self.head, self.direction);
-            self.write_at(stdout, self.head, Some(Piece::Head));
+    /// The square next to this one, this way, if it's on the board
+    fn neighbour(&self, position: console::Position, way: Direction) -> Option<console::Position> {
+        let on_board = match way {
+            Direction::Up => position.row > 0,
+            Direction::Down => position.row + 1 < self.height,
+            Direction::Left => position.col > 0,
+            Direction::Right => position.col + 1 < self.width,
+        };
+        on_board.then(|| way.step(position))
+    }
 
-            if self.digesting == 0 {
-                let old_tail = self.tail;
-                match self.board.remove_piece(self.tail) {
-                    Some(Direction::Up) => {
-                        self.tail.row -= 1;
-                    }
-                    Some(Direction::Down) => {
-                        self.tail.row += 1;
-                    }
-                    Some(Direction::Left) => {
-                        self.tail.col -= 1;
-                    }
-                    Some(Direction::Right) => {
-                        self.tail.col += 1;
+    /// Turn, if this is one of the keys to steer with. Each key is a turn,
+    /// and we make one turn a tick, so two quick presses become two turns
+    /// rather than the last one winning.
+    fn steer(&mut self, key: KeyEvent) -> bool {
+        let Some(turn) = self.turn_for(key) else {
+            return false;
+        };
+        let snake = &mut self.snakes[self.me];
+        snake.turns.push(turn, snake.direction);
+        true
+    }
+
+    /// Which way a key steers, if it's one of the keys to steer with
+    fn turn_for(&self, key: KeyEvent) -> Option<Direction> {
+        let turn = match key {
+            KeyEvent::Up => Direction::Up,
+            KeyEvent::Down => Direction::Down,
+            KeyEvent::Left => Direction::Left,
+            KeyEvent::Right => Direction::Right,
+            KeyEvent::Char(ch) if ch.is_ascii() => match ch as u8 {
+                key if key.eq_ignore_ascii_case(&self.keys.up) => Direction::Up,
+                key if key.eq_ignore_ascii_case(&self.keys.down) => Direction::Down,
+                key if key.eq_ignore_ascii_case(&self.keys.left) => Direction::Left,
+                key if key.eq_ignore_ascii_case(&self.keys.right) => Direction::Right,
+                _ => return None,
+            },
+            _ => return None,
+        };
+        Some(turn)
+    }
+
+    /// Draw everything again - the board, the map and the score
+    fn redraw(&self, stdout: &mut neotron_sdk::File) {
+        self.draw_view(stdout);
+        if self.skin == Skin::Rainbow && self.rainbow_unlocked {
+            self.draw_rainbow(stdout);
+        }
+        self.minimap.draw(stdout);
+        self.draw_hud(stdout);
+    }
+
+    /// Move the view so the head is where the camera wants it. Returns
+    /// `true` if the view moved.
+    fn follow_head(&mut self) -> bool {
+        let old = self.camera;
+        let head = self.snakes[self.me].head;
+        self.camera = console::Position {
+            row: self
+                .camera_mode
+                .follow(self.camera.row, head.row, self.view_height, self.height),
+            col: self
+                .camera_mode
+                .follow(self.camera.col, head.col, self.view_width, self.width),
+        };
+        self.camera.row != old.row || self.camera.col != old.col
+    }
+
+    /// Where a square of the board is on the screen, if it's in view
+    fn to_screen(&self, position: console::Position) -> Option<console::Position> {
+        let row = position.row.checked_sub(self.camera.row)?;
+        let col = position.col.checked_sub(self.camera.col)?;
+        if row >= self.view_height || col >= self.view_width {
+            return None;
+        }
+        Some(console::Position {
+            row: row + self.offset.row,
+            col: col * self.cell_width + self.offset.col,
+        })
+    }
+
+    /// What's on a square of the board
+    fn piece_at(&self, position: console::Position) -> Option<Piece> {
+        let head = self.snakes[..self.players]
+            .iter()
+            .position(|snake| position.row == snake.head.row && position.col == snake.head.col);
+        match head {
+            Some(idx) => Some(self.head_piece(idx)),
+            None => self.board.piece_at(position),
+        }
+    }
+
+    /// Draw everything in view, after it has scrolled
+    fn draw_view(&self, console: &mut neotron_sdk::File) {
+        for row in 0..self.view_height {
+            yield_now();
+            let start = console::Position {
+                row: row + self.offset.row,
+                col: self.offset.col,
+            };
+            console::move_cursor(console, start);
+            let mut colour = None;
+            'row: for col in 0..self.view_width {
+                let piece = self.piece_at(console::Position {
+                    row: self.camera.row + row,
+                    col: self.camera.col + col,
+                });
+                for column in 0..self.cell_width {
+                    let screen = console::Position {
+                        row: start.row,
+                        col: start.col + col * self.cell_width + column,
+                    };
+                    // The map is in the bottom right, so the rest of the row
+                    // is under it
+                    if self.minimap.covers(screen) {
+                        break 'row;
                     }
-                    None => {
-                        panic!("Bad game state");
+                    match piece {
+                        Some(piece) => {
+                            if colour != Some(piece) {
+                                console::set_sgr(console, [piece.get_colour()]);
+                                colour = Some(piece);
+                            }
+                            let _ = console.write_char(piece.get_char_at(column, self.cell_width));
+                        }
+                        None => {
+                            let _ = console.write_char(' ');
+                        }
                     }
                 }
-                self.write_at(stdout, old_tail, None);
-            } else {
-                self.digesting -= 1;
             }
         }
+    }
 
-        self.score
+    /// Show the score outside the board, if there's room
+    fn draw_hud(&self, console: &mut neotron_sdk::File) {
+        let Some(position) = self.hud else {
+            return;
+        };
+        console::move_cursor(console, position);
+        console::set_sgr(console, [console::SgrParam::Reset]);
+        let _ = write!(
+            console,
+            "Score: {:06}  Best: {:06}",
+            self.score,
+            self.best.max(self.score)
+        );
     }
 
-    /// Draw a piece on the ANSI console at the given location
+    /// Colour in each body, from the tail up to the head. Each square is
+    /// one step along the rainbow from the one before, and they all move on
+    /// a step each tick.
+    fn draw_rainbow(&self, console: &mut neotron_sdk::File) {
+        for snake in &self.snakes[..self.players] {
+            self.draw_rainbow_snake(console, snake);
+        }
+    }
+
+    /// Colour in one body with the rainbow
+    fn draw_rainbow_snake(&self, console: &mut neotron_sdk::File, snake: &Snake) {
+        let mut position = snake.tail;
+        let mut colour = self.ticks as usize;
+        let mut steps = 0u32;
+        while position.row != snake.head.row || position.col != snake.head.col {
+            steps += 1;
+            if steps.is_multiple_of(YIELD_EVERY) {
+                yield_now();
+            }
+            let Some(direction) = self.board.direction_at(position) else {
+                break;
+            };
+            let tint = Skin::RAINBOW[colour % Skin::RAINBOW.len()];
+            self.write_coloured(console, position, Some(Piece::Body), tint);
+            colour = colour.wrapping_add(Skin::RAINBOW.len() - 1);
+            position = direction.step(position);
+        }
+    }
+
+    /// Draw a piece on the ANSI console at the given location on the board
     fn write_at(
         &self,
         console: &mut neotron_sdk::File,
         position: console::Position,
         piece: Option<Piece>,
     ) {
-        let adjusted_position = console::Position {
-            row: position.row + self.offset.row,
-            col: position.col + self.offset.col,
+        let colour = piece.map_or(console::SgrParam::Reset, Piece::get_colour);
+        self.write_coloured(console, position, piece, colour);
+    }
+
+    /// Draw a piece, in a colour of our choosing
+    fn write_coloured(
+        &self,
+        console: &mut neotron_sdk::File,
+        position: console::Position,
+        piece: Option<Piece>,
+        colour: console::SgrParam,
+    ) {
+        let Some(adjusted_position) = self.to_screen(position) else {
+            // Not in view
+            return;
         };
+        if self.minimap.covers(adjusted_position) {
+            // Hidden under the map
+            return;
+        }
         console::move_cursor(console, adjusted_position);
-        if let Some(piece) = piece {
-            let colour = piece.get_colour();
-            let ch = piece.get_char();
+        if piece.is_some() {
             console::set_sgr(console, [colour]);
+        }
+        for column in 0..self.cell_width {
+            let screen = console::Position {
+                row: adjusted_position.row,
+                col: adjusted_position.col + column,
+            };
+            if self.minimap.covers(screen) {
+                break;
+            }
+            let ch = piece.map_or(' ', |piece| piece.get_char_at(column, self.cell_width));
             let _ = console.write_char(ch);
-        } else {
-            let _ = console.write_char(' ');
         }
     }
 
     /// Find a spot on the board that is empty
     fn random_empty_position(&mut self) -> console::Position {
+        // When the board is nearly full, this can take a lot of goes
+        let mut tries = 0u32;
         loop {
-            // This isn't equally distributed. I don't really care.
+            tries = tries.wrapping_add(1);
+            if tries.is_multiple_of(YIELD_EVERY) {
+                yield_now();
+            }
             let pos = console::Position {
-                row: (neotron_sdk::rand() % self.height as u16) as u8,
-                col: (neotron_sdk::rand() % self.width as u16) as u8,
+                row: self.rng.below(u32::from(self.height)) as u8,
+                col: self.rng.below(u32::from(self.width)) as u8,
             };
             if self.board.is_empty(pos) {
                 return pos;
@@ -429,6 +1591,14 @@ impl Game {
     }
 }
 
+/// Let the OS get on with anything it needs to do in the background.
+///
+/// Neotron OS runs one thing at a time, so a loop which never waits holds
+/// everything else up. Long loops call this every so often.
+fn yield_now() {
+    neotron_sdk::delay(core::time::Duration::ZERO);
+}
+
 /// A direction in which a body piece can face
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 enum Direction {
@@ -452,6 +1622,101 @@ impl Direction {
     fn is_vertical(self) -> bool {
         self == Direction::Up || self == Direction::Down
     }
+
+    /// The square next to this one, this way. The snake never goes off the
+    /// board, so there always is one.
+    fn step(self, position: console::Position) -> console::Position {
+        match self {
+            Direction::Up => console::Position {
+                row: position.row - 1,
+                ..position
+            },
+            Direction::Down => console::Position {
+                row: position.row + 1,
+                ..position
+            },
+            Direction::Left => console::Position {
+                col: position.col - 1,
+                ..position
+            },
+            Direction::Right => console::Position {
+                col: position.col + 1,
+                ..position
+            },
+        }
+    }
+}
+
+/// One snake on the board
+#[derive(Debug, Copy, Clone)]
+struct Snake {
+    head: console::Position,
+    tail: console::Position,
+    direction: Direction,
+    /// Turns the player has asked for, which we haven't made yet
+    turns: Turns,
+    /// How many more moves the tail stays put for
+    digesting: u32,
+}
+
+impl Snake {
+    /// A snake one square long, going up, which is about to grow
+    const fn new(head: console::Position) -> Snake {
+        Snake {
+            head,
+            tail: head,
+            direction: Direction::Up,
+            turns: Turns::new(),
+            digesting: 2,
+        }
+    }
+}
+
+/// The turns waiting to be made, oldest first
+#[derive(Debug, Copy, Clone)]
+struct Turns {
+    turns: [Direction; Self::MAX],
+    len: usize,
+}
+
+impl Turns {
+    /// How many turns we remember. Any more keys than this in a tick are
+    /// probably a mistake.
+    const MAX: usize = 2;
+
+    /// No turns waiting
+    const fn new() -> Turns {
+        Turns {
+            turns: [Direction::Up; Self::MAX],
+            len: 0,
+        }
+    }
+
+    /// Remember a turn, if it makes sense after the turns already waiting.
+    ///
+    /// The snake can only turn to the side - it can't carry on the way it
+    /// was going, or double back on itself.
+    fn push(&mut self, turn: Direction, heading: Direction) {
+        let heading = match self.len {
+            0 => heading,
+            len => self.turns[len - 1],
+        };
+        if self.len < Self::MAX && turn.is_horizontal() == heading.is_vertical() {
+            self.turns[self.len] = turn;
+            self.len += 1;
+        }
+    }
+
+    /// Take the oldest turn
+    fn pop(&mut self) -> Option<Direction> {
+        if self.len == 0 {
+            return None;
+        }
+        let turn = self.turns[0];
+        self.turns.copy_within(1.., 0);
+        self.len -= 1;
+        Some(turn)
+    }
 }
 
 /// Something we can put on a board.
@@ -532,15 +1797,29 @@ impl<const WIDTH: usize, const HEIGHT: usize> Board<WIDTH, HEIGHT> {
         self.cells[usize::from(position.row)][usize::from(position.col)] == BoardPiece::Empty
     }
 
-    /// Remove a piece from the board
-    fn remove_piece(&mut self, position: console::Position) -> Option<Direction> {
-        let old = match self.cells[usize::from(position.row)][usize::from(position.col)] {
+    /// What's on the board here, drawn as body or food
+    fn piece_at(&self, position: console::Position) -> Option<Piece> {
+        match self.cells[usize::from(position.row)][usize::from(position.col)] {
+            BoardPiece::Empty => None,
+            BoardPiece::Food => Some(Piece::Food),
+            _ => Some(Piece::Body),
+        }
+    }
+
+    /// Which way the next piece of body is from here, if this is body
+    fn direction_at(&self, position: console::Position) -> Option<Direction> {
+        match self.cells[usize::from(position.row)][usize::from(position.col)] {
             BoardPiece::Up => Some(Direction::Up),
             BoardPiece::Down => Some(Direction::Down),
             BoardPiece::Left => Some(Direction::Left),
             BoardPiece::Right => Some(Direction::Right),
             _ => None,
-        };
+        }
+    }
+
+    /// Remove a piece from the board
+    fn remove_piece(&mut self, position: console::Position) -> Option<Direction> {
+        let old = self.direction_at(position);
         self.cells[usize::from(position.row)][usize::from(position.col)] = BoardPiece::Empty;
         old
     }
@@ -0,0 +1,105 @@
+//! A connection to another computer, for the leaderboard server or the
+//! other player in a co-op game.
+//!
+//! On a Neotron the endpoint is a device, like `NET0:` or `SER0:`, and
+//! anything on the other end of it. On a PC it can also be `host:port`,
+//! and we make a TCP connection.
+
+use core::fmt::Write;
+
+/// The I/O control command that sets a serial port's baud rate
+const IOCTL_SET_BAUD: u64 = 1;
+
+/// How long we wait between looking for more to read
+const POLL_MS: u32 = 10;
+
+/// How we reach the other end
+pub enum Link {
+    /// Through a device
+    Device(neotron_sdk::File),
+    /// Through a TCP connection, on a PC
+    #[cfg(not(target_os = "none"))]
+    Tcp(neotron_host::net::Stream),
+}
+
+impl Link {
+    /// Connect to the other end
+    pub fn open(endpoint: &str) -> Option<Link> {
+        // Devices end with a colon, and addresses end with a port number
+        #[cfg(not(target_os = "none"))]
+        if !endpoint.ends_with(':') {
+            return neotron_host::net::Stream::connect(endpoint)
+                .ok()
+                .map(Link::Tcp);
+        }
+        let path = neotron_sdk::path::Path::new(endpoint).ok()?;
+        // We send and then read the answer. Anything that's open can be
+        // read, so asking for writing gets us both.
+        let flags = neotron_sdk::Flags::WRITE;
+        neotron_sdk::File::open(path, flags).ok().map(Link::Device)
+    }
+
+    /// Set the baud rate, if this is a serial port. Anything else doesn't
+    /// have one, so that's fine too.
+    pub fn set_baud(&self, baud: u32) {
+        if let Link::Device(file) = self {
+            let _ = file.ioctl(IOCTL_SET_BAUD, u64::from(baud));
+        }
+    }
+
+    /// Read whatever has arrived, or `None` if the connection has gone
+    pub fn read(&self, buffer: &mut [u8]) -> Option<usize> {
+        match self {
+            Link::Device(file) => file.read(buffer).ok(),
+            #[cfg(not(target_os = "none"))]
+            Link::Tcp(stream) => stream.read(buffer).ok(),
+        }
+    }
+
+    /// Send some bytes. Returns `false` if the connection has gone.
+    pub fn send(&self, bytes: &[u8]) -> bool {
+        match self {
+            Link::Device(file) => file.write(bytes).is_ok(),
+            #[cfg(not(target_os = "none"))]
+            Link::Tcp(stream) => stream.write(bytes).is_ok(),
+        }
+    }
+
+    /// Read a line, without the line ending, waiting up to `timeout_ms`
+    /// for it
+    pub fn read_line<'a>(&self, buffer: &'a mut [u8], timeout_ms: u32) -> Option<&'a str> {
+        let mut len = 0;
+        let mut waited = 0;
+        loop {
+            let mut byte = [0u8; 1];
+            match self.read(&mut byte)? {
+                0 if waited >= timeout_ms => return None,
+                0 => {
+                    neotron_sdk::delay(core::time::Duration::from_millis(u64::from(POLL_MS)));
+                    waited += POLL_MS;
+                }
+                _ if byte[0] == b'\n' => break,
+                _ if byte[0] == b'\r' => {}
+                _ => {
+                    // Anything too long is no good to us, but keep reading
+                    // to the end of it
+                    if len < buffer.len() {
+                        buffer[len] = byte[0];
+                    }
+                    len += 1;
+                }
+            }
+        }
+        core::str::from_utf8(buffer.get(0..len)?).ok()
+    }
+}
+
+impl Write for Link {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        if self.send(s.as_bytes()) {
+            Ok(())
+        } else {
+            Err(core::fmt::Error)
+        }
+    }
+}
@@ -1,15 +1,18 @@
 #![cfg_attr(target_os = "none", no_std)]
 #![cfg_attr(target_os = "none", no_main)]
 
+use core::ptr::addr_of_mut;
+
 #[cfg(not(target_os = "none"))]
 fn main() {
-    neotron_sdk::init();
+    neotron_host::setup();
+    neotron_host::os::init();
 }
 
 static mut APP: snake::App = snake::App::new(80, 25);
 
 #[no_mangle]
 extern "C" fn neotron_main() -> i32 {
-    unsafe { APP.play() }
+    unsafe { (*addr_of_mut!(APP)).play() }
     0
 }
@@ -0,0 +1,296 @@
+//! A small map of the whole board, for when it doesn't all fit on screen.
+//!
+//! Each character of the map has four quadrants, and each quadrant stands
+//! for a block of board squares. A quadrant is lit if there is any snake or
+//! food in its block. We keep a count of what's in each block, so when the
+//! board changes we only redraw the one character that changed.
+
+use core::fmt::Write;
+
+use neotron_sdk::console;
+
+/// The most characters wide the map can be
+const MAX_COLS: usize = 16;
+
+/// The most characters high the map can be
+const MAX_ROWS: usize = 6;
+
+/// The quadrant characters, indexed by which quadrants are lit: top left
+/// is 1, top right 2, bottom left 4 and bottom right 8
+const QUADRANTS: [char; 16] = [
+    ' ', '▘', '▝', '▀', '▖', '▌', '▞', '▛', '▗', '▚', '▐', '▜', '▄', '▙', '▟', '█',
+];
+
+/// When to show the map
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Mode {
+    /// Only when the board is bigger than the screen
+    Auto,
+    /// Always
+    On,
+    /// Never
+    Off,
+}
+
+impl Mode {
+    /// Read the mode from a setting
+    pub fn parse(text: &str) -> Option<Mode> {
+        if text.eq_ignore_ascii_case("auto") {
+            Some(Mode::Auto)
+        } else if text.eq_ignore_ascii_case("on") {
+            Some(Mode::On)
+        } else if text.eq_ignore_ascii_case("off") {
+            Some(Mode::Off)
+        } else {
+            None
+        }
+    }
+
+    /// The name of the mode, for the settings file
+    pub fn name(self) -> &'static str {
+        match self {
+            Mode::Auto => "auto",
+            Mode::On => "on",
+            Mode::Off => "off",
+        }
+    }
+}
+
+/// What colour a character of the map is
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Tint {
+    /// Just snake, or nothing
+    Snake,
+    /// There's food in it
+    Food,
+    /// The head is in it
+    Head,
+}
+
+/// A map of where the snake and the food are
+pub struct Minimap {
+    /// Are we drawing the map at all?
+    enabled: bool,
+    /// Where the top left character of the map goes on screen
+    origin: console::Position,
+    /// How many characters wide and high the map is
+    cols: u8,
+    rows: u8,
+    /// How many board squares each quadrant covers, across and down
+    scale_x: u8,
+    scale_y: u8,
+    /// How many squares of snake there are in each quadrant
+    snake: [[u16; MAX_COLS * 2]; MAX_ROWS * 2],
+    /// How much food there is in each quadrant
+    food: [[u8; MAX_COLS * 2]; MAX_ROWS * 2],
+    /// Which quadrant the head is in
+    head: Option<(usize, usize)>,
+}
+
+impl Minimap {
+    /// Make a map that isn't shown
+    pub const fn new() -> Minimap {
+        Minimap {
+            enabled: false,
+            origin: console::Position { row: 0, col: 0 },
+            cols: 0,
+            rows: 0,
+            scale_x: 1,
+            scale_y: 1,
+            snake: [[0; MAX_COLS * 2]; MAX_ROWS * 2],
+            food: [[0; MAX_COLS * 2]; MAX_ROWS * 2],
+            head: None,
+        }
+    }
+
+    /// How many board squares each quadrant covers, across and down, for a
+    /// board of this size
+    fn scale(board_width: u8, board_height: u8) -> (u8, u8) {
+        (
+            board_width.div_ceil(MAX_COLS as u8 * 2).max(1),
+            board_height.div_ceil(MAX_ROWS as u8 * 2).max(1),
+        )
+    }
+
+    /// How many characters wide and high the map is, for a board of this
+    /// size. It has a frame along its top and left too.
+    pub fn size(board_width: u8, board_height: u8) -> (u8, u8) {
+        let (scale_x, scale_y) = Self::scale(board_width, board_height);
+        (
+            board_width.div_ceil(scale_x).div_ceil(2),
+            board_height.div_ceil(scale_y).div_ceil(2),
+        )
+    }
+
+    /// Empty the map, and work out how big it is for a board of this size.
+    ///
+    /// The map sits in the bottom right corner of the screen area given by
+    /// `corner`, which is just past the last row and column it can use.
+    pub fn reset(
+        &mut self,
+        enabled: bool,
+        board_width: u8,
+        board_height: u8,
+        corner: console::Position,
+    ) {
+        self.snake = [[0; MAX_COLS * 2]; MAX_ROWS * 2];
+        self.food = [[0; MAX_COLS * 2]; MAX_ROWS * 2];
+        self.head = None;
+        (self.scale_x, self.scale_y) = Self::scale(board_width, board_height);
+        (self.cols, self.rows) = Self::size(board_width, board_height);
+        // Leave room for the frame round the top and left
+        self.enabled = enabled && corner.col > self.cols && corner.row > self.rows;
+        self.origin = console::Position {
+            row: corner.row.saturating_sub(self.rows),
+            col: corner.col.saturating_sub(self.cols),
+        };
+    }
+
+    /// Is this spot on the screen covered by the map, or its frame?
+    pub fn covers(&self, position: console::Position) -> bool {
+        self.enabled
+            && position.row + 1 >= self.origin.row
+            && position.row < self.origin.row + self.rows
+            && position.col + 1 >= self.origin.col
+            && position.col < self.origin.col + self.cols
+    }
+
+    /// Draw the whole map, with a frame along its top and left
+    pub fn draw(&self, console: &mut neotron_sdk::File) {
+        if !self.enabled {
+            return;
+        }
+        console::set_sgr(console, [console::SgrParam::Reset]);
+        console::move_cursor(
+            console,
+            console::Position {
+                row: self.origin.row - 1,
+                col: self.origin.col - 1,
+            },
+        );
+        let _ = console.write_char('┌');
+        for _ in 0..self.cols {
+            let _ = console.write_char('─');
+        }
+        for row in 0..self.rows {
+            console::move_cursor(
+                console,
+                console::Position {
+                    row: self.origin.row + row,
+                    col: self.origin.col - 1,
+                },
+            );
+            let _ = console.write_char('│');
+            for col in 0..self.cols {
+                self.draw_char(console, usize::from(col), usize::from(row));
+            }
+        }
+        console::set_sgr(console, [console::SgrParam::Reset]);
+    }
+
+    /// Some snake has arrived on the board here
+    pub fn add_snake(&mut self, console: &mut neotron_sdk::File, position: console::Position) {
+        self.change(console, position, |map, x, y| {
+            map.snake[y][x] = map.snake[y][x].saturating_add(1)
+        });
+    }
+
+    /// Some snake has left the board here
+    pub fn remove_snake(&mut self, console: &mut neotron_sdk::File, position: console::Position) {
+        self.change(console, position, |map, x, y| {
+            map.snake[y][x] = map.snake[y][x].saturating_sub(1)
+        });
+    }
+
+    /// Some food has appeared here
+    pub fn add_food(&mut self, console: &mut neotron_sdk::File, position: console::Position) {
+        self.change(console, position, |map, x, y| {
+            map.food[y][x] = map.food[y][x].saturating_add(1)
+        });
+    }
+
+    /// Some food has been eaten here
+    pub fn remove_food(&mut self, console: &mut neotron_sdk::File, position: console::Position) {
+        self.change(console, position, |map, x, y| {
+            map.food[y][x] = map.food[y][x].saturating_sub(1)
+        });
+    }
+
+    /// The head has moved here
+    pub fn move_head(&mut self, console: &mut neotron_sdk::File, position: console::Position) {
+        let old = self.head;
+        self.change(console, position, |map, x, y| map.head = Some((x, y)));
+        // The character it left needs its colour back
+        if let Some((x, y)) = old {
+            if self.enabled && Some((x / 2, y / 2)) != self.head.map(|(x, y)| (x / 2, y / 2)) {
+                self.draw_char(console, x / 2, y / 2);
+                console::set_sgr(console, [console::SgrParam::Reset]);
+            }
+        }
+    }
+
+    /// Update the quadrant for a board square, and redraw its character if
+    /// that changes how it looks
+    fn change<F>(&mut self, console: &mut neotron_sdk::File, position: console::Position, update: F)
+    where
+        F: FnOnce(&mut Minimap, usize, usize),
+    {
+        if !self.enabled {
+            return;
+        }
+        let x = usize::from(position.col / self.scale_x);
+        let y = usize::from(position.row / self.scale_y);
+        if x >= MAX_COLS * 2 || y >= MAX_ROWS * 2 {
+            return;
+        }
+        let before = self.look(x / 2, y / 2);
+        update(self, x, y);
+        if self.look(x / 2, y / 2) != before {
+            self.draw_char(console, x / 2, y / 2);
+            console::set_sgr(console, [console::SgrParam::Reset]);
+        }
+    }
+
+    /// What a character of the map looks like - which quadrants are lit,
+    /// and what colour it is
+    fn look(&self, col: usize, row: usize) -> (usize, Tint) {
+        let mut mask = 0;
+        let mut has_food = false;
+        let mut has_head = false;
+        for (bit, (dx, dy)) in [(0, 0), (1, 0), (0, 1), (1, 1)].into_iter().enumerate() {
+            let (x, y) = (col * 2 + dx, row * 2 + dy);
+            if self.snake[y][x] != 0 || self.food[y][x] != 0 {
+                mask |= 1 << bit;
+            }
+            has_food |= self.food[y][x] != 0;
+            has_head |= self.head == Some((x, y));
+        }
+        let tint = if has_head {
+            Tint::Head
+        } else if has_food {
+            Tint::Food
+        } else {
+            Tint::Snake
+        };
+        (mask, tint)
+    }
+
+    /// Draw one character of the map
+    fn draw_char(&self, console: &mut neotron_sdk::File, col: usize, row: usize) {
+        let (mask, tint) = self.look(col, row);
+        let colour = match tint {
+            Tint::Snake => console::SgrParam::FgMagenta,
+            Tint::Food => console::SgrParam::FgGreen,
+            Tint::Head => console::SgrParam::FgYellow,
+        };
+        console::move_cursor(
+            console,
+            console::Position {
+                row: self.origin.row + row as u8,
+                col: self.origin.col + col as u8,
+            },
+        );
+        console::set_sgr(console, [colour, console::SgrParam::BgBlack]);
+        let _ = console.write_char(QUADRANTS[mask]);
+    }
+}
@@ -0,0 +1,143 @@
+//! The settings you can change from the options screen.
+//!
+//! Everything here is also in the settings file, which is where it's kept
+//! when you leave the options screen.
+
+use core::fmt::Write;
+
+use crate::{leaderboard::Leaderboard, minimap, CameraMode, Game, Skin};
+
+/// The settings, in the order they appear on screen
+pub const SETTINGS: [Setting; 9] = [
+    Setting::Speed,
+    Setting::BoardWidth,
+    Setting::BoardHeight,
+    Setting::SquareCells,
+    Setting::Minimap,
+    Setting::Camera,
+    Setting::Skin,
+    Setting::SoundCues,
+    Setting::Leaderboard,
+];
+
+/// Something you can change on the options screen
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Setting {
+    /// How long each move takes at the start
+    Speed,
+    /// How many squares across the board is
+    BoardWidth,
+    /// How many squares down the board is
+    BoardHeight,
+    /// Whether squares are drawn two columns wide
+    SquareCells,
+    /// When to show the map of the board
+    Minimap,
+    /// How the view follows the snake
+    Camera,
+    /// How the snake looks
+    Skin,
+    /// Whether sounds say what's near the snake
+    SoundCues,
+    /// Whether we talk to the leaderboard server
+    Leaderboard,
+}
+
+impl Setting {
+    /// What the setting is called
+    pub fn name(self) -> &'static str {
+        match self {
+            Setting::Speed => "Move every",
+            Setting::BoardWidth => "Board width",
+            Setting::BoardHeight => "Board height",
+            Setting::SquareCells => "Square cells",
+            Setting::Minimap => "Map",
+            Setting::Camera => "Scrolling",
+            Setting::Skin => "Skin",
+            Setting::SoundCues => "Sound cues",
+            Setting::Leaderboard => "World scores",
+        }
+    }
+
+    /// Write out what the setting is set to
+    pub fn value<W: Write>(self, out: &mut W, game: &Game, leaderboard: &Leaderboard) {
+        let _ = match self {
+            Setting::Speed => write!(out, "{} ms", game.starting_tick),
+            Setting::BoardWidth => write!(out, "{}", game.width),
+            Setting::BoardHeight => write!(out, "{}", game.height),
+            Setting::SquareCells => out.write_str(on_off(game.cell_width == 2)),
+            Setting::Minimap => out.write_str(game.minimap_mode.name()),
+            Setting::Camera => out.write_str(game.camera_mode.name()),
+            Setting::Skin if !game.rainbow_unlocked => {
+                write!(out, "locked until {}", crate::RAINBOW_SCORE)
+            }
+            Setting::Skin => out.write_str(game.skin.name()),
+            Setting::SoundCues => out.write_str(on_off(game.cues.enabled())),
+            Setting::Leaderboard => out.write_str(on_off(leaderboard.enabled)),
+        };
+    }
+
+    /// Change the setting one step up, or down
+    pub fn change(self, up: bool, game: &mut Game, leaderboard: &mut Leaderboard) {
+        match self {
+            Setting::Speed => {
+                let ms = if up {
+                    game.starting_tick.saturating_add(10)
+                } else {
+                    game.starting_tick.saturating_sub(10)
+                };
+                game.starting_tick = ms.clamp(5, 1000);
+            }
+            Setting::BoardWidth => {
+                game.width = step(game.width, up, game.view_width, Game::MAX_WIDTH);
+            }
+            Setting::BoardHeight => {
+                game.height = step(game.height, up, game.view_height, Game::MAX_HEIGHT);
+            }
+            Setting::SquareCells => game.set_square_cells(game.cell_width == 1),
+            Setting::Minimap => {
+                game.minimap_mode = match (game.minimap_mode, up) {
+                    (minimap::Mode::Auto, true) | (minimap::Mode::Off, false) => minimap::Mode::On,
+                    (minimap::Mode::On, true) | (minimap::Mode::Auto, false) => minimap::Mode::Off,
+                    (minimap::Mode::Off, true) | (minimap::Mode::On, false) => minimap::Mode::Auto,
+                };
+            }
+            Setting::Camera => {
+                game.camera_mode = match game.camera_mode {
+                    CameraMode::Centre => CameraMode::Jump,
+                    CameraMode::Jump => CameraMode::Centre,
+                };
+            }
+            // There's only one other skin, so both ways go to it
+            Setting::Skin if game.rainbow_unlocked => {
+                game.skin = match game.skin {
+                    Skin::Classic => Skin::Rainbow,
+                    Skin::Rainbow => Skin::Classic,
+                };
+            }
+            Setting::Skin => {}
+            Setting::SoundCues => game.cues.set_enabled(!game.cues.enabled()),
+            Setting::Leaderboard => leaderboard.enabled = !leaderboard.enabled,
+        }
+    }
+}
+
+/// Change a board size by ten squares, keeping it between the size of the
+/// screen and the biggest board we can handle
+fn step(size: u8, up: bool, min: u8, max: usize) -> u8 {
+    let size = if up {
+        size.saturating_add(10)
+    } else {
+        size.saturating_sub(10)
+    };
+    size.clamp(min, max.min(usize::from(u8::MAX)) as u8)
+}
+
+/// How a setting which is on or off looks
+fn on_off(value: bool) -> &'static str {
+    if value {
+        "on"
+    } else {
+        "off"
+    }
+}
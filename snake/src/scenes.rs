@@ -0,0 +1,341 @@
+//! The screens Snake shows, and how it gets from one to the next
+
+use neotron_input::KeyEvent;
+use neotron_scene::{Scene, Transition};
+use neotron_tui::{ListBox, Menu};
+
+use crate::{coop, options, App, COOP_ITEMS, MENU_ITEMS};
+
+/// Each screen, and anything it needs to remember
+pub(crate) enum Screen {
+    /// The title screen, with the menu
+    Title,
+    /// A game in progress
+    Playing,
+    /// A game, stopped until a key is pressed
+    Paused,
+    /// A game has finished, and we're saying how it went
+    GameOver {
+        score: u32,
+        new_best: bool,
+        world_rank: Option<u32>,
+        /// Can a key take us back to the title screen yet?
+        ready: bool,
+    },
+    /// Choosing whether to host or join a co-op game
+    CoopMenu(Menu<'static>),
+    /// Waiting for the other player to turn up
+    Connecting {
+        host: bool,
+        handshake: coop::Handshake,
+    },
+    /// A co-op game in progress
+    Coop {
+        /// Are we saying we're waiting for the other player?
+        stalled: bool,
+    },
+    /// A co-op game has finished
+    CoopOver {
+        ending: coop::Ending,
+        score: u32,
+        /// How long the message has been up
+        shown_ms: u32,
+    },
+    /// Changing the settings
+    Options(ListBox),
+    /// Our best scores, and the world's
+    HighScores,
+    /// How to play
+    Help,
+}
+
+impl Screen {
+    /// How long the game over message stays up before a key can clear it,
+    /// so steering keys pressed as the snake crashed don't skip it
+    const GAME_OVER_MS: u32 = 500;
+
+    /// The game over screen, for a game with this score
+    fn game_over(score: u32) -> Screen {
+        Screen::GameOver {
+            score,
+            new_best: false,
+            world_rank: None,
+            ready: false,
+        }
+    }
+
+    /// The screen for the end of a co-op game
+    fn coop_over(ending: coop::Ending, score: u32) -> Screen {
+        Screen::CoopOver {
+            ending,
+            score,
+            shown_ms: 0,
+        }
+    }
+}
+
+impl Scene<App> for Screen {
+    fn enter(&mut self, app: &mut App) {
+        match self {
+            Screen::Playing | Screen::Coop { .. } => app.start_game(),
+            Screen::CoopOver { .. } => {
+                // Co-op scores don't go in the high scores, as they're
+                // half someone else's
+                if let Some(session) = &app.session {
+                    session.finish(&mut app.game);
+                }
+                self.draw(app);
+            }
+            Screen::GameOver {
+                score,
+                new_best,
+                world_rank,
+                ..
+            } => {
+                (*new_best, *world_rank) = app.finish_game(*score);
+                app.winning_message(*score, *new_best, *world_rank);
+            }
+            _ => self.draw(app),
+        }
+    }
+
+    fn draw(&mut self, app: &mut App) {
+        match self {
+            Screen::Title => {
+                app.clear_screen();
+                app.title_screen();
+            }
+            Screen::Playing => app.redraw_game(),
+            Screen::Paused => app.paused_message(),
+            Screen::CoopMenu(menu) => app.coop_screen(menu),
+            Screen::Connecting { host, .. } => app.connecting_message(*host),
+            Screen::Coop { stalled } => {
+                app.redraw_game();
+                if *stalled {
+                    app.stalled_message();
+                }
+            }
+            Screen::CoopOver { ending, score, .. } => {
+                app.clear_screen();
+                app.coop_message(*ending, *score);
+            }
+            Screen::GameOver {
+                score,
+                new_best,
+                world_rank,
+                ..
+            } => {
+                app.clear_screen();
+                app.winning_message(*score, *new_best, *world_rank);
+            }
+            Screen::Options(list) => app.draw_options(list),
+            Screen::HighScores => app.high_scores(),
+            Screen::Help => app.help(),
+        }
+    }
+
+    fn tick_ms(&self, app: &App) -> Option<u32> {
+        match self {
+            Screen::Playing => Some(u32::from(app.game.tick_interval_ms)),
+            Screen::GameOver { ready: false, .. } => Some(Self::GAME_OVER_MS),
+            // These keep an eye on the link
+            Screen::Connecting { .. } | Screen::Coop { .. } | Screen::CoopOver { .. } => {
+                Some(coop::POLL_MS)
+            }
+            _ => None,
+        }
+    }
+
+    fn update(&mut self, app: &mut App) -> Transition<Screen> {
+        match self {
+            Screen::Playing => {
+                if app.game.step(&mut app.stdout) {
+                    Transition::Stay
+                } else {
+                    Transition::Replace(Screen::game_over(app.game.score))
+                }
+            }
+            Screen::GameOver { ready, .. } => {
+                *ready = true;
+                Transition::Stay
+            }
+            Screen::Connecting { handshake, .. } => {
+                match handshake.poll(&mut app.game, &app.coop) {
+                    Some(session) => {
+                        app.session = Some(session);
+                        Transition::Replace(Screen::Coop { stalled: false })
+                    }
+                    None => Transition::Stay,
+                }
+            }
+            Screen::Coop { stalled } => {
+                let Some(session) = &mut app.session else {
+                    return Transition::Pop;
+                };
+                match session.update(&mut app.game, &mut app.stdout) {
+                    coop::Event::Nothing => {}
+                    coop::Event::Stalled => {
+                        *stalled = true;
+                        app.stalled_message();
+                    }
+                    coop::Event::Redraw => {
+                        *stalled = false;
+                        app.redraw_game();
+                    }
+                    coop::Event::Over(ending) => {
+                        return Transition::Replace(Screen::coop_over(ending, app.game.score));
+                    }
+                }
+                Transition::Stay
+            }
+            Screen::CoopOver { shown_ms, .. } => {
+                *shown_ms = shown_ms.saturating_add(coop::POLL_MS);
+                // The other game might still need our last few keys
+                if let Some(session) = &mut app.session {
+                    session.serve();
+                }
+                Transition::Stay
+            }
+            _ => Transition::Stay,
+        }
+    }
+
+    fn handle_input(&mut self, app: &mut App, key: KeyEvent) -> Transition<Screen> {
+        match self {
+            Screen::Title => title_key(app, key),
+            Screen::Playing => {
+                if app.game.steer(key) {
+                    Transition::Stay
+                } else if key.is_char('q') {
+                    Transition::Replace(Screen::game_over(app.game.score))
+                } else if key == KeyEvent::Escape || key.is_char('p') {
+                    Transition::Push(Screen::Paused)
+                } else {
+                    Transition::Stay
+                }
+            }
+            Screen::GameOver { ready: false, .. } => Transition::Stay,
+            Screen::CoopMenu(menu) => coop_menu_key(app, menu, key),
+            Screen::Connecting { .. } => {
+                if key == KeyEvent::Escape || key.is_char('q') {
+                    Transition::Pop
+                } else {
+                    Transition::Stay
+                }
+            }
+            Screen::Coop { .. } => {
+                let Some(session) = &mut app.session else {
+                    return Transition::Pop;
+                };
+                match session.key(&app.game, key) {
+                    Some(ending) => Transition::Replace(Screen::coop_over(ending, app.game.score)),
+                    None => Transition::Stay,
+                }
+            }
+            Screen::CoopOver { shown_ms, .. } if *shown_ms < Self::GAME_OVER_MS => Transition::Stay,
+            Screen::CoopOver { .. } => {
+                app.session = None;
+                Transition::Pop
+            }
+            Screen::Options(list) => options_key(app, list, key),
+            Screen::Paused | Screen::GameOver { .. } | Screen::HighScores | Screen::Help => {
+                Transition::Pop
+            }
+        }
+    }
+}
+
+/// Move about the title screen menu, and go wherever is picked
+fn title_key(app: &mut App, key: KeyEvent) -> Transition<Screen> {
+    app.seed = app.seed.wrapping_add(1);
+    let choice = match key {
+        KeyEvent::Up => {
+            app.menu.up();
+            None
+        }
+        KeyEvent::Down => {
+            app.menu.down();
+            None
+        }
+        KeyEvent::Enter | KeyEvent::Char(' ') => Some(app.menu.selected()),
+        KeyEvent::Escape => return Transition::Quit,
+        // The keys from before there was a menu still work
+        key if key.is_char('p') => Some(0),
+        key if key.is_char('q') => return Transition::Quit,
+        KeyEvent::Char(ch) => {
+            app.menu.jump_to(ch);
+            None
+        }
+        _ => None,
+    };
+    let Some(choice) = choice else {
+        app.draw_menu();
+        return Transition::Stay;
+    };
+    match MENU_ITEMS[choice] {
+        "Play" => {
+            app.game.rng.mix(u32::from(app.seed));
+            Transition::Push(Screen::Playing)
+        }
+        "Co-op" => Transition::Push(Screen::CoopMenu(Menu::new("Co-op", &COOP_ITEMS))),
+        "Options" => Transition::Push(Screen::Options(ListBox::new(options::SETTINGS.len()))),
+        "High Scores" => Transition::Push(Screen::HighScores),
+        "Help" => Transition::Push(Screen::Help),
+        _ => Transition::Quit,
+    }
+}
+
+/// Change the settings, and save them when the user is done
+fn options_key(app: &mut App, list: &mut ListBox, key: KeyEvent) -> Transition<Screen> {
+    let setting = options::SETTINGS[list.selected()];
+    match key {
+        KeyEvent::Up => list.up(),
+        KeyEvent::Down => list.down(),
+        KeyEvent::Left => setting.change(false, &mut app.game, &mut app.leaderboard),
+        KeyEvent::Right | KeyEvent::Enter | KeyEvent::Char(' ') => {
+            setting.change(true, &mut app.game, &mut app.leaderboard)
+        }
+        KeyEvent::Escape => {
+            app.finish_options();
+            return Transition::Pop;
+        }
+        key if key.is_char('q') => {
+            app.finish_options();
+            return Transition::Pop;
+        }
+        _ => {}
+    }
+    app.draw_settings(list);
+    Transition::Stay
+}
+
+/// Pick whether to host or join a co-op game, and start looking for the
+/// other player
+fn coop_menu_key(app: &mut App, menu: &mut Menu<'static>, key: KeyEvent) -> Transition<Screen> {
+    let host = match key {
+        KeyEvent::Up => {
+            menu.up();
+            None
+        }
+        KeyEvent::Down => {
+            menu.down();
+            None
+        }
+        KeyEvent::Enter | KeyEvent::Char(' ') => Some(menu.selected() == 0),
+        KeyEvent::Escape => return Transition::Pop,
+        key if key.is_char('q') => return Transition::Pop,
+        KeyEvent::Char(ch) => {
+            menu.jump_to(ch);
+            None
+        }
+        _ => None,
+    };
+    let Some(host) = host else {
+        app.draw_coop_menu(menu);
+        return Transition::Stay;
+    };
+    match coop::Handshake::open(host, &app.coop) {
+        Some(handshake) => Transition::Replace(Screen::Connecting { host, handshake }),
+        None => Transition::Replace(Screen::coop_over(coop::Ending::NoLink, 0)),
+    }
+}
@@ -0,0 +1,24 @@
+//! Check what Snake draws, against recordings we know are right
+
+use neotron_golden::{assert_golden, golden, Run};
+
+/// Start a game and leave the snake to run into the top wall, then go back
+/// to the title screen and quit from there.
+///
+/// Nothing is typed while the snake moves, so the output doesn't depend on
+/// which tick a key happens to arrive in.
+#[test]
+fn crash_into_wall() {
+    let output = Run::new(env!("CARGO_BIN_EXE_snake"))
+        .wait_ms(500)
+        .send(b"p")
+        .wait_ms(2000)
+        // One to clear the game over message, and one to quit
+        .send(b"q")
+        .wait_ms(300)
+        .send(b"q")
+        .run();
+    assert!(output.success);
+    assert_golden(golden!("crash_into_wall.ansi"), &output.stdout);
+    assert!(output.disk().join("SNAKE.DAT").exists());
+}
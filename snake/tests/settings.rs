@@ -0,0 +1,49 @@
+//! Check Snake can read back the settings file it writes
+
+use neotron_golden::Run;
+
+/// With no settings file, Snake writes one with the defaults in. Change a
+/// setting in it, then open and close the options screen, which writes the
+/// settings out again - if the file was read, the change is still there.
+#[test]
+fn settings_read_back() {
+    let first = Run::new(env!("CARGO_BIN_EXE_snake"))
+        .wait_ms(500)
+        .send(b"q")
+        .run();
+    assert!(first.success);
+    let written = String::from_utf8(first.file("SNAKE.CFG")).expect("settings are text");
+
+    let edited: String = written
+        .lines()
+        .map(|line| {
+            if line.starts_with("start_tick_ms = ") {
+                "start_tick_ms = 123\n".to_owned()
+            } else {
+                format!("{}\n", line)
+            }
+        })
+        .collect();
+    assert_ne!(edited, written, "no start_tick_ms setting to change");
+
+    let second = Run::new(env!("CARGO_BIN_EXE_snake"))
+        .file("SNAKE.CFG", edited.clone())
+        .wait_ms(500)
+        // Pick Options from the menu, then leave it, then quit
+        .send(b"o\r")
+        .wait_ms(300)
+        .send(b"q")
+        .wait_ms(300)
+        .send(b"q")
+        .run();
+    assert!(second.success);
+    let stdout = String::from_utf8_lossy(&second.stdout);
+    assert!(
+        !stdout.contains("SNAKE.CFG"),
+        "Snake couldn't use its own settings file"
+    );
+    assert_eq!(
+        String::from_utf8(second.file("SNAKE.CFG")).expect("settings are text"),
+        edited
+    );
+}
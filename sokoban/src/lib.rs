@@ -0,0 +1,633 @@
+//! Game logic for Sokoban
+//!
+//! Levels are read from the standard `.xsb` text format:
+//!
+//! * `#` is a wall
+//! * ` `, `-` or `_` is floor
+//! * `.` is a goal
+//! * `$` is a box, and `*` is a box on a goal
+//! * `@` is the player, and `+` is the player on a goal
+//!
+//! Levels are separated by lines which are not part of a map. A line starting
+//! with `;`, or a `Title:` line, gives the level a name.
+
+#![no_std]
+#![deny(missing_docs)]
+#![deny(unsafe_code)]
+
+use core::fmt::Write;
+
+use neotron_input::{KeyEvent, Keyboard};
+use neotron_sdk::console;
+
+/// Some levels to play when no level pack is given
+pub const BUILTIN_LEVELS: &str = "\
+; First Steps
+#####
+#@$.#
+#####
+
+; Round the Corner
+  #####
+###   #
+#.@$  #
+### $.#
+#.##$ #
+# # . ##
+#$ *$$.#
+#   .  #
+########
+
+; The Warehouse
+########
+#      #
+# .$$. #
+# $..$ #
+#  @   #
+########
+";
+
+/// Where the solved-level records go if there is no level pack file
+const BUILTIN_SOLVED_FILE: &str = "SOKOBAN.SOL";
+
+/// A direction the player can move in
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Direction {
+    /// How far does this move us, in columns and rows
+    fn delta(self) -> (i16, i16) {
+        match self {
+            Direction::Up => (0, -1),
+            Direction::Down => (0, 1),
+            Direction::Left => (-1, 0),
+            Direction::Right => (1, 0),
+        }
+    }
+}
+
+/// What the player wants to do
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Action {
+    Move(Direction),
+    Undo,
+    Restart,
+    NextLevel,
+    PreviousLevel,
+    Quit,
+}
+
+/// Represents the Sokoban application
+pub struct App {
+    width: u8,
+    height: u8,
+    stdout: neotron_sdk::File,
+    keyboard: Keyboard,
+    level: Level,
+    /// Where each level starts and ends in the level pack text
+    level_spans: [(usize, usize); Self::MAX_LEVELS],
+    num_levels: usize,
+    /// The fewest moves each level has been solved in (0 if unsolved)
+    solved: [u16; Self::MAX_LEVELS],
+    /// Every move made this level, so it can be undone
+    history: [Step; Self::MAX_HISTORY],
+    history_len: usize,
+    /// Holds the name of the solved-levels file
+    solved_path: [u8; Self::MAX_PATH],
+    solved_path_len: usize,
+}
+
+/// A move made by the player, recorded so it can be undone
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct Step {
+    direction: Direction,
+    pushed: bool,
+}
+
+impl App {
+    /// The most levels we can load from one pack
+    const MAX_LEVELS: usize = 256;
+    /// The most moves we can undo
+    const MAX_HISTORY: usize = 2000;
+    /// The longest path to the solved-levels file
+    const MAX_PATH: usize = 64;
+
+    /// Make a new Sokoban application.
+    ///
+    /// You can give the screen size in characters.
+    pub const fn new(width: u8, height: u8) -> App {
+        App {
+            width,
+            height,
+            stdout: neotron_sdk::stdout(),
+            keyboard: Keyboard::new(),
+            level: Level::new(),
+            level_spans: [(0, 0); Self::MAX_LEVELS],
+            num_levels: 0,
+            solved: [0; Self::MAX_LEVELS],
+            history: [Step {
+                direction: Direction::Up,
+                pushed: false,
+            }; Self::MAX_HISTORY],
+            history_len: 0,
+            solved_path: [0; Self::MAX_PATH],
+            solved_path_len: 0,
+        }
+    }
+
+    /// Play the levels in the given level pack.
+    ///
+    /// If you give the name of the level pack file, we record solved levels
+    /// in a file with the same name and a `.SOL` extension.
+    pub fn play(&mut self, levels: &str, filename: Option<&str>) {
+        self.index_levels(levels);
+        if self.num_levels == 0 {
+            let _ = writeln!(self.stdout, "No levels found!");
+            return;
+        }
+        self.set_solved_path(filename);
+        self.load_solved();
+
+        console::cursor_off(&mut self.stdout);
+
+        // Start at the first level not yet solved
+        let mut level_idx = self.solved[0..self.num_levels]
+            .iter()
+            .position(|&moves| moves == 0)
+            .unwrap_or(0);
+
+        'levels: loop {
+            let (start, end) = self.level_spans[level_idx];
+            self.level.load(&levels[start..end]);
+            self.history_len = 0;
+            self.draw_screen(levels, level_idx);
+
+            loop {
+                if self.level.is_solved() {
+                    let moves = self.history_len as u16;
+                    let best = &mut self.solved[level_idx];
+                    if *best == 0 || moves < *best {
+                        *best = moves;
+                        self.save_solved();
+                    }
+                    self.draw_message("Solved! Press any key for the next level.");
+                    self.keyboard.wait();
+                    level_idx = (level_idx + 1) % self.num_levels;
+                    continue 'levels;
+                }
+                match self.wait_for_action() {
+                    Action::Move(direction) => {
+                        if let Some(pushed) = self.level.try_move(direction) {
+                            if self.history_len < Self::MAX_HISTORY {
+                                self.history[self.history_len] = Step { direction, pushed };
+                                self.history_len += 1;
+                            }
+                            self.draw_board();
+                            self.draw_counters();
+                        }
+                    }
+                    Action::Undo => {
+                        if self.history_len > 0 {
+                            self.history_len -= 1;
+                            let step = self.history[self.history_len];
+                            self.level.undo(step.direction, step.pushed);
+                            self.draw_board();
+                            self.draw_counters();
+                        }
+                    }
+                    Action::Restart => {
+                        continue 'levels;
+                    }
+                    Action::NextLevel => {
+                        level_idx = (level_idx + 1) % self.num_levels;
+                        continue 'levels;
+                    }
+                    Action::PreviousLevel => {
+                        level_idx = (level_idx + self.num_levels - 1) % self.num_levels;
+                        continue 'levels;
+                    }
+                    Action::Quit => {
+                        break 'levels;
+                    }
+                }
+            }
+        }
+
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        console::clear_screen(&mut self.stdout);
+        console::move_cursor(&mut self.stdout, console::Position::origin());
+        console::cursor_on(&mut self.stdout);
+    }
+
+    /// Find where each level is in the level pack.
+    ///
+    /// A level is a run of map lines. The span includes any comment lines
+    /// just before the map, so we can find the title.
+    fn index_levels(&mut self, levels: &str) {
+        self.num_levels = 0;
+        let mut level_start = None;
+        let mut title_start = None;
+        let mut offset = 0;
+        for line in levels.split_inclusive('\n') {
+            let line_start = offset;
+            offset += line.len();
+            if Level::is_map_line(line) {
+                if level_start.is_none() {
+                    level_start = Some(title_start.unwrap_or(line_start));
+                }
+                continue;
+            }
+            if let Some(start) = level_start.take() {
+                self.push_level(start, line_start);
+                title_start = None;
+            }
+            if title_start.is_none() && !line.trim().is_empty() {
+                title_start = Some(line_start);
+            } else if line.trim().is_empty() {
+                title_start = None;
+            }
+        }
+        if let Some(start) = level_start {
+            self.push_level(start, levels.len());
+        }
+    }
+
+    /// Record where a level is in the level pack
+    fn push_level(&mut self, start: usize, end: usize) {
+        if self.num_levels < Self::MAX_LEVELS {
+            self.level_spans[self.num_levels] = (start, end);
+            self.num_levels += 1;
+        }
+    }
+
+    /// Work out the name of the solved-levels file
+    fn set_solved_path(&mut self, filename: Option<&str>) {
+        let filename = filename.unwrap_or(BUILTIN_SOLVED_FILE);
+        let stem = match filename.rfind('.') {
+            Some(dot) if !filename[dot..].contains(['/', '\\', ':']) => &filename[0..dot],
+            _ => filename,
+        };
+        self.solved_path_len = 0;
+        for part in [stem, ".SOL"] {
+            for byte in part.bytes() {
+                if self.solved_path_len < Self::MAX_PATH {
+                    self.solved_path[self.solved_path_len] = byte;
+                    self.solved_path_len += 1;
+                }
+            }
+        }
+    }
+
+    /// Get the name of the solved-levels file
+    fn solved_path(&self) -> &str {
+        core::str::from_utf8(&self.solved_path[0..self.solved_path_len]).unwrap_or("")
+    }
+
+    /// Load the solved-levels file.
+    ///
+    /// Each line holds a level number (starting at 1) and the fewest moves
+    /// it was solved in.
+    fn load_solved(&mut self) {
+        self.solved = [0; Self::MAX_LEVELS];
+        let Ok(path) = neotron_sdk::path::Path::new(self.solved_path()) else {
+            return;
+        };
+        let Ok(file) = neotron_sdk::File::open(path, neotron_sdk::Flags::empty()) else {
+            return;
+        };
+        let mut buffer = [0u8; 2048];
+        let Ok(n) = file.read(&mut buffer) else {
+            return;
+        };
+        let Ok(text) = core::str::from_utf8(&buffer[0..n]) else {
+            return;
+        };
+        for line in text.lines() {
+            let mut fields = line.split_whitespace();
+            let (Some(Ok(level)), Some(Ok(moves))) = (
+                fields.next().map(str::parse::<usize>),
+                fields.next().map(str::parse::<u16>),
+            ) else {
+                continue;
+            };
+            if (1..=Self::MAX_LEVELS).contains(&level) {
+                self.solved[level - 1] = moves;
+            }
+        }
+    }
+
+    /// Save the solved-levels file
+    fn save_solved(&mut self) {
+        let Ok(path) = neotron_sdk::path::Path::new(self.solved_path()) else {
+            return;
+        };
+        let Ok(mut file) = neotron_sdk::File::open(
+            path,
+            neotron_sdk::Flags::WRITE | neotron_sdk::Flags::CREATE | neotron_sdk::Flags::TRUNCATE,
+        ) else {
+            return;
+        };
+        for (index, moves) in self.solved.iter().enumerate() {
+            if *moves != 0 {
+                let _ = writeln!(file, "{} {}", index + 1, moves);
+            }
+        }
+    }
+
+    /// Draw everything
+    fn draw_screen(&mut self, levels: &str, level_idx: usize) {
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        console::clear_screen(&mut self.stdout);
+        console::move_cursor(&mut self.stdout, console::Position::origin());
+        let (start, end) = self.level_spans[level_idx];
+        let title = Level::title(&levels[start..end]);
+        let _ = write!(
+            self.stdout,
+            "Neotron Sokoban - Level {}/{}",
+            level_idx + 1,
+            self.num_levels
+        );
+        if !title.is_empty() {
+            let _ = write!(self.stdout, ": {}", title);
+        }
+        if self.solved[level_idx] != 0 {
+            console::set_sgr(&mut self.stdout, [console::SgrParam::FgGreen]);
+            let _ = write!(self.stdout, " (solved in {})", self.solved[level_idx]);
+            console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        }
+        self.draw_message("WASD/arrows move, U undo, R restart, N/P next/prev level, Q/Esc quit");
+        self.draw_board();
+        self.draw_counters();
+    }
+
+    /// How many screen columns each board cell uses
+    fn cell_width(&self) -> u8 {
+        if self.level.width * 2 < self.width {
+            2
+        } else {
+            1
+        }
+    }
+
+    /// Draw the level
+    fn draw_board(&mut self) {
+        let cell_width = self.cell_width();
+        let origin_col = (self.width - self.level.width * cell_width) / 2;
+        let origin_row = 2 + (self.height - 4).saturating_sub(self.level.height) / 2;
+        for y in 0..self.level.height {
+            console::move_cursor(
+                &mut self.stdout,
+                console::Position {
+                    row: origin_row + y,
+                    col: origin_col,
+                },
+            );
+            for x in 0..self.level.width {
+                let tile = self.level.tile(x, y);
+                let (colour, text) = if (x, y) == self.level.player {
+                    (console::SgrParam::FgYellow, "@@")
+                } else if tile & Level::WALL != 0 {
+                    (console::SgrParam::FgBlue, "▓▓")
+                } else if tile & Level::BOX != 0 {
+                    if tile & Level::GOAL != 0 {
+                        (console::SgrParam::FgGreen, "[]")
+                    } else {
+                        (console::SgrParam::FgRed, "[]")
+                    }
+                } else if tile & Level::GOAL != 0 {
+                    (console::SgrParam::FgCyan, "::")
+                } else {
+                    (console::SgrParam::FgWhite, "  ")
+                };
+                console::set_sgr(&mut self.stdout, [console::SgrParam::Bold, colour]);
+                let _ = self.stdout.write_str(&text[0..usize::from(cell_width)]);
+            }
+        }
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+    }
+
+    /// Draw the move and push counters
+    fn draw_counters(&mut self) {
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        console::move_cursor(&mut self.stdout, console::Position { row: 1, col: 0 });
+        let pushes = self.history[0..self.history_len]
+            .iter()
+            .filter(|step| step.pushed)
+            .count();
+        let _ = write!(
+            self.stdout,
+            "Moves: {:<5} Pushes: {:<5}",
+            self.history_len, pushes
+        );
+    }
+
+    /// Show a message on the bottom line
+    fn draw_message(&mut self, message: &str) {
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        console::move_cursor(
+            &mut self.stdout,
+            console::Position {
+                row: self.height - 1,
+                col: 0,
+            },
+        );
+        let _ = write!(self.stdout, "{:<79}", message);
+    }
+
+    /// Wait for a key, and work out what the player wants to do.
+    ///
+    /// Handles WASD and the arrow keys.
+    fn wait_for_action(&mut self) -> Action {
+        loop {
+            return match self.keyboard.wait() {
+                KeyEvent::Up => Action::Move(Direction::Up),
+                KeyEvent::Down => Action::Move(Direction::Down),
+                KeyEvent::Left => Action::Move(Direction::Left),
+                KeyEvent::Right => Action::Move(Direction::Right),
+                KeyEvent::Backspace => Action::Undo,
+                KeyEvent::Escape => Action::Quit,
+                KeyEvent::Char(ch) => match ch.to_ascii_lowercase() {
+                    'w' => Action::Move(Direction::Up),
+                    's' => Action::Move(Direction::Down),
+                    'a' => Action::Move(Direction::Left),
+                    'd' => Action::Move(Direction::Right),
+                    'u' => Action::Undo,
+                    'r' => Action::Restart,
+                    'n' => Action::NextLevel,
+                    'p' => Action::PreviousLevel,
+                    'q' => Action::Quit,
+                    _ => continue,
+                },
+                _ => continue,
+            };
+        }
+    }
+}
+
+/// The level currently being played
+struct Level {
+    tiles: [[u8; Self::MAX_WIDTH]; Self::MAX_HEIGHT],
+    width: u8,
+    height: u8,
+    player: (u8, u8),
+}
+
+impl Level {
+    /// The widest level we can show
+    const MAX_WIDTH: usize = 78;
+    /// The tallest level we can show
+    const MAX_HEIGHT: usize = 21;
+
+    /// Tile flag - this is a wall
+    const WALL: u8 = 1 << 0;
+    /// Tile flag - this is a goal
+    const GOAL: u8 = 1 << 1;
+    /// Tile flag - there is a box here
+    const BOX: u8 = 1 << 2;
+
+    /// Make an empty level
+    const fn new() -> Level {
+        Level {
+            tiles: [[0; Self::MAX_WIDTH]; Self::MAX_HEIGHT],
+            width: 0,
+            height: 0,
+            player: (0, 0),
+        }
+    }
+
+    /// Is this line part of a level map?
+    fn is_map_line(line: &str) -> bool {
+        let line = line.trim_end();
+        line.contains('#')
+            && line
+                .chars()
+                .all(|ch| matches!(ch, '#' | ' ' | '-' | '_' | '.' | '$' | '*' | '@' | '+'))
+    }
+
+    /// Find the title of a level, from the text before the map
+    fn title(text: &str) -> &str {
+        for line in text.lines() {
+            if Level::is_map_line(line) {
+                break;
+            }
+            let line = line.trim();
+            if let Some(title) = line.strip_prefix("Title:") {
+                return title.trim();
+            }
+            if let Some(title) = line.strip_prefix(';') {
+                return title.trim();
+            }
+        }
+        ""
+    }
+
+    /// Load a level from its text.
+    ///
+    /// Anything beyond the maximum size is ignored.
+    fn load(&mut self, text: &str) {
+        self.tiles = [[0; Self::MAX_WIDTH]; Self::MAX_HEIGHT];
+        self.width = 0;
+        self.height = 0;
+        self.player = (0, 0);
+        for line in text.lines().filter(|line| Level::is_map_line(line)) {
+            let y = self.height;
+            if usize::from(y) == Self::MAX_HEIGHT {
+                break;
+            }
+            for (x, ch) in line.trim_end().chars().take(Self::MAX_WIDTH).enumerate() {
+                let x = x as u8;
+                let tile = match ch {
+                    '#' => Self::WALL,
+                    '.' => Self::GOAL,
+                    '$' => Self::BOX,
+                    '*' => Self::BOX | Self::GOAL,
+                    '@' => {
+                        self.player = (x, y);
+                        0
+                    }
+                    '+' => {
+                        self.player = (x, y);
+                        Self::GOAL
+                    }
+                    _ => 0,
+                };
+                self.tiles[usize::from(y)][usize::from(x)] = tile;
+                self.width = self.width.max(x + 1);
+            }
+            self.height += 1;
+        }
+    }
+
+    /// What's at this position?
+    fn tile(&self, x: u8, y: u8) -> u8 {
+        self.tiles[usize::from(y)][usize::from(x)]
+    }
+
+    /// Step from a position in a direction, if that stays on the board
+    fn step(&self, (x, y): (u8, u8), direction: Direction) -> Option<(u8, u8)> {
+        let (dx, dy) = direction.delta();
+        let nx = i16::from(x) + dx;
+        let ny = i16::from(y) + dy;
+        if nx < 0 || ny < 0 || nx >= i16::from(self.width) || ny >= i16::from(self.height) {
+            None
+        } else {
+            Some((nx as u8, ny as u8))
+        }
+    }
+
+    /// Try and move the player.
+    ///
+    /// Returns `None` if the move is blocked, or `Some(pushed)` if it worked.
+    fn try_move(&mut self, direction: Direction) -> Option<bool> {
+        let next = self.step(self.player, direction)?;
+        let next_tile = self.tile(next.0, next.1);
+        if next_tile & Self::WALL != 0 {
+            return None;
+        }
+        let mut pushed = false;
+        if next_tile & Self::BOX != 0 {
+            let beyond = self.step(next, direction)?;
+            if self.tile(beyond.0, beyond.1) & (Self::WALL | Self::BOX) != 0 {
+                return None;
+            }
+            self.tiles[usize::from(next.1)][usize::from(next.0)] &= !Self::BOX;
+            self.tiles[usize::from(beyond.1)][usize::from(beyond.0)] |= Self::BOX;
+            pushed = true;
+        }
+        self.player = next;
+        Some(pushed)
+    }
+
+    /// Reverse a move
+    fn undo(&mut self, direction: Direction, pushed: bool) {
+        let reverse = match direction {
+            Direction::Up => Direction::Down,
+            Direction::Down => Direction::Up,
+            Direction::Left => Direction::Right,
+            Direction::Right => Direction::Left,
+        };
+        let Some(previous) = self.step(self.player, reverse) else {
+            return;
+        };
+        if pushed {
+            if let Some(pushed_box) = self.step(self.player, direction) {
+                self.tiles[usize::from(pushed_box.1)][usize::from(pushed_box.0)] &= !Self::BOX;
+                self.tiles[usize::from(self.player.1)][usize::from(self.player.0)] |= Self::BOX;
+            }
+        }
+        self.player = previous;
+    }
+
+    /// Are all the boxes on goals?
+    fn is_solved(&self) -> bool {
+        self.tiles[0..usize::from(self.height)].iter().all(|row| {
+            row.iter()
+                .all(|&tile| tile & Self::BOX == 0 || tile & Self::GOAL != 0)
+        })
+    }
+}
@@ -0,0 +1,41 @@
+#![cfg_attr(target_os = "none", no_std)]
+#![cfg_attr(target_os = "none", no_main)]
+
+use core::ptr::addr_of_mut;
+
+const FILE_BUFFER_LEN: usize = 64 * 1024;
+static mut FILE_BUFFER: [u8; FILE_BUFFER_LEN] = [0u8; FILE_BUFFER_LEN];
+
+static mut APP: sokoban::App = sokoban::App::new(80, 25);
+
+#[cfg(not(target_os = "none"))]
+fn main() {
+    neotron_sdk::init();
+}
+
+#[no_mangle]
+extern "C" fn neotron_main() -> i32 {
+    neotron_error::exit_code(real_main())
+}
+
+fn real_main() -> Result<(), neotron_sdk::Error> {
+    let app = unsafe { &mut *addr_of_mut!(APP) };
+    let Some(filename) = neotron_sdk::arg(0) else {
+        // No level pack given, so play the built-in levels
+        app.play(sokoban::BUILTIN_LEVELS, None);
+        return Ok(());
+    };
+    let path = neotron_sdk::path::Path::new(&filename)?;
+    let f = neotron_sdk::File::open(path, neotron_sdk::Flags::empty())?;
+    let file_buffer = unsafe {
+        let file_buffer = &mut *addr_of_mut!(FILE_BUFFER);
+        let n = f.read(file_buffer)?;
+        &file_buffer[0..n]
+    };
+    drop(f);
+    let Ok(levels) = core::str::from_utf8(file_buffer) else {
+        return Err(neotron_sdk::Error::InvalidArg);
+    };
+    app.play(levels, Some(&filename));
+    Ok(())
+}
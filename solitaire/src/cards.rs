@@ -0,0 +1,177 @@
+//! Playing cards, and piles of them.
+
+/// The four suits
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Suit {
+    /// Clubs (black)
+    Clubs,
+    /// Diamonds (red)
+    Diamonds,
+    /// Hearts (red)
+    Hearts,
+    /// Spades (black)
+    Spades,
+}
+
+impl Suit {
+    /// All the suits, in the order their foundations are laid out
+    pub const ALL: [Suit; 4] = [Suit::Clubs, Suit::Diamonds, Suit::Hearts, Suit::Spades];
+
+    /// The symbol for this suit
+    pub fn symbol(self) -> char {
+        match self {
+            Suit::Clubs => '♣',
+            Suit::Diamonds => '♦',
+            Suit::Hearts => '♥',
+            Suit::Spades => '♠',
+        }
+    }
+
+    /// Is this a red suit?
+    pub fn is_red(self) -> bool {
+        matches!(self, Suit::Diamonds | Suit::Hearts)
+    }
+}
+
+/// A playing card.
+///
+/// Stored as a number from 0 to 51 - the suit times 13, plus the rank less one.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Card(u8);
+
+impl Card {
+    /// Make a card from its position in a new deck, 0 to 51
+    pub const fn from_index(index: u8) -> Card {
+        Card(index % 52)
+    }
+
+    /// The rank, from 1 (Ace) to 13 (King)
+    pub fn rank(self) -> u8 {
+        self.0 % 13 + 1
+    }
+
+    /// The suit
+    pub fn suit(self) -> Suit {
+        Suit::ALL[usize::from(self.0 / 13)]
+    }
+
+    /// Is this a red card?
+    pub fn is_red(self) -> bool {
+        self.suit().is_red()
+    }
+
+    /// The rank as text, like `A` or `10`
+    pub fn rank_str(self) -> &'static str {
+        const RANKS: [&str; 13] = [
+            "A", "2", "3", "4", "5", "6", "7", "8", "9", "10", "J", "Q", "K",
+        ];
+        RANKS[usize::from(self.rank() - 1)]
+    }
+
+    /// Can this card go on top of `other` in the tableau?
+    ///
+    /// It must be one rank lower, and the other colour.
+    pub fn fits_on(self, other: Card) -> bool {
+        self.rank() + 1 == other.rank() && self.is_red() != other.is_red()
+    }
+}
+
+/// A pile of cards, some of which may be face down
+#[derive(Debug, Copy, Clone)]
+pub struct Pile {
+    cards: [Card; 52],
+    len: u8,
+    /// How many cards, from the bottom, are face down
+    face_down: u8,
+}
+
+impl Pile {
+    /// Make an empty pile
+    pub const fn new() -> Pile {
+        Pile {
+            cards: [Card(0); 52],
+            len: 0,
+            face_down: 0,
+        }
+    }
+
+    /// How many cards are there?
+    pub fn len(&self) -> usize {
+        usize::from(self.len)
+    }
+
+    /// Are there no cards at all?
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// How many cards are face down?
+    pub fn face_down(&self) -> usize {
+        usize::from(self.face_down)
+    }
+
+    /// The cards, from the bottom of the pile to the top
+    pub fn cards(&self) -> &[Card] {
+        &self.cards[0..self.len()]
+    }
+
+    /// The card on top, if any
+    pub fn top(&self) -> Option<Card> {
+        self.cards().last().copied()
+    }
+
+    /// The face-up cards, from the bottom of the pile to the top
+    pub fn face_up(&self) -> &[Card] {
+        &self.cards[self.face_down()..self.len()]
+    }
+
+    /// Put a card on top
+    pub fn push(&mut self, card: Card) {
+        if self.len() < self.cards.len() {
+            self.cards[self.len()] = card;
+            self.len += 1;
+        }
+    }
+
+    /// Take the top card off
+    pub fn pop(&mut self) -> Option<Card> {
+        let card = self.top()?;
+        self.len -= 1;
+        self.face_down = self.face_down.min(self.len);
+        Some(card)
+    }
+
+    /// Empty the pile
+    pub fn clear(&mut self) {
+        self.len = 0;
+        self.face_down = 0;
+    }
+
+    /// Turn the top card face up. Returns true if it was face down.
+    pub fn flip_top(&mut self) -> bool {
+        if self.len > 0 && self.face_down == self.len {
+            self.face_down -= 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Turn the top card face down again (to undo [`Pile::flip_top`])
+    pub fn unflip_top(&mut self) {
+        if self.face_down < self.len {
+            self.face_down += 1;
+        }
+    }
+
+    /// Set how many cards are face down
+    pub fn set_face_down(&mut self, count: usize) {
+        self.face_down = count.min(self.len()) as u8;
+    }
+}
+
+impl Default for Pile {
+    fn default() -> Self {
+        Pile::new()
+    }
+}
@@ -0,0 +1,374 @@
+//! The rules of Klondike.
+//!
+//! We keep a history of everything that happens, so any move can be undone.
+//! Moves to the foundations which the game makes on its own are marked as
+//! automatic, and undone along with the move which caused them.
+
+use crate::cards::{Card, Pile};
+
+/// Somewhere cards can be moved from, or to
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Place {
+    /// The face-up cards next to the stock
+    Waste,
+    /// One of the four foundations, numbered in [`crate::cards::Suit::ALL`] order
+    Foundation(u8),
+    /// One of the seven tableau piles
+    Tableau(u8),
+}
+
+/// Something that happened, which we can undo
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Action {
+    /// Some cards were turned from the stock onto the waste
+    Draw(u8),
+    /// The waste was turned back over to make a new stock
+    Recycle(u8),
+    /// Some cards were moved
+    Move {
+        from: Place,
+        to: Place,
+        count: u8,
+        /// Did we turn over the card underneath?
+        flipped: bool,
+        /// Did the game make this move by itself?
+        automatic: bool,
+    },
+}
+
+/// A game of Klondike
+pub struct Game {
+    stock: Pile,
+    waste: Pile,
+    foundations: [Pile; 4],
+    tableau: [Pile; 7],
+    /// How many cards we turn over from the stock at a time
+    draw_count: u8,
+    history: [Action; Self::MAX_HISTORY],
+    history_len: usize,
+    /// How many moves the player has made
+    moves: u32,
+    /// Shuffles the cards
+    pub rng: neotron_rand::Rng,
+}
+
+impl Game {
+    /// How many actions we can undo
+    const MAX_HISTORY: usize = 1024;
+
+    /// Make an empty game. Call [`Game::deal`] to start playing.
+    pub const fn new() -> Game {
+        Game {
+            stock: Pile::new(),
+            waste: Pile::new(),
+            foundations: [Pile::new(); 4],
+            tableau: [Pile::new(); 7],
+            draw_count: 1,
+            history: [Action::Draw(0); Self::MAX_HISTORY],
+            history_len: 0,
+            moves: 0,
+            rng: neotron_rand::Rng::new(0),
+        }
+    }
+
+    /// Shuffle the cards and deal a new game.
+    ///
+    /// We turn over `draw_count` cards (1 or 3) from the stock at a time.
+    pub fn deal(&mut self, draw_count: u8) {
+        let mut deck = [Card::from_index(0); 52];
+        for (idx, card) in deck.iter_mut().enumerate() {
+            *card = Card::from_index(idx as u8);
+        }
+        self.rng.shuffle(&mut deck);
+
+        self.stock.clear();
+        self.waste.clear();
+        for pile in self.foundations.iter_mut().chain(self.tableau.iter_mut()) {
+            pile.clear();
+        }
+        let mut cards = deck.iter();
+        for (idx, pile) in self.tableau.iter_mut().enumerate() {
+            for card in cards.by_ref().take(idx + 1) {
+                pile.push(*card);
+            }
+            pile.set_face_down(idx);
+        }
+        for card in cards {
+            self.stock.push(*card);
+        }
+        self.draw_count = draw_count;
+        self.history_len = 0;
+        self.moves = 0;
+    }
+
+    /// The stock, which is all face down
+    pub fn stock(&self) -> &Pile {
+        &self.stock
+    }
+
+    /// The waste
+    pub fn waste(&self) -> &Pile {
+        &self.waste
+    }
+
+    /// One of the foundations
+    pub fn foundation(&self, idx: usize) -> &Pile {
+        &self.foundations[idx]
+    }
+
+    /// One of the tableau piles
+    pub fn tableau(&self, idx: usize) -> &Pile {
+        &self.tableau[idx]
+    }
+
+    /// How many cards we turn over from the stock at a time
+    pub fn draw_count(&self) -> u8 {
+        self.draw_count
+    }
+
+    /// How many moves the player has made
+    pub fn moves(&self) -> u32 {
+        self.moves
+    }
+
+    /// Are all the cards on the foundations?
+    pub fn is_won(&self) -> bool {
+        self.foundations.iter().all(|pile| pile.len() == 13)
+    }
+
+    /// Turn over cards from the stock, or turn the waste back over if the
+    /// stock is empty. Returns false if there was nothing to do.
+    pub fn draw(&mut self) -> bool {
+        if self.stock.is_empty() {
+            let count = self.waste.len();
+            if count == 0 {
+                return false;
+            }
+            while let Some(card) = self.waste.pop() {
+                self.stock.push(card);
+            }
+            self.record(Action::Recycle(count as u8));
+        } else {
+            let count = usize::from(self.draw_count).min(self.stock.len());
+            for _ in 0..count {
+                if let Some(card) = self.stock.pop() {
+                    self.waste.push(card);
+                }
+            }
+            self.record(Action::Draw(count as u8));
+        }
+        self.moves += 1;
+        true
+    }
+
+    /// Move cards from one place to another, if the rules allow it.
+    ///
+    /// When moving between tableau piles, we work out how many cards to
+    /// move. Returns false if the move isn't allowed.
+    pub fn move_cards(&mut self, from: Place, to: Place) -> bool {
+        let Some(count) = self.legal_count(from, to) else {
+            return false;
+        };
+        self.apply(from, to, count, false);
+        self.moves += 1;
+        true
+    }
+
+    /// Move the top card from somewhere to its foundation, if the rules allow
+    pub fn to_foundation(&mut self, from: Place) -> bool {
+        let Some(card) = self.pile(from).top() else {
+            return false;
+        };
+        let to = Place::Foundation(card.suit() as u8);
+        self.move_cards(from, to)
+    }
+
+    /// Move cards to the foundations, until no more will go.
+    ///
+    /// If `safe_only` is set, we only move cards which can't be needed in
+    /// the tableau any more. Returns how many cards were moved.
+    pub fn auto_move(&mut self, safe_only: bool) -> usize {
+        let mut moved = 0;
+        loop {
+            let mut progress = false;
+            let sources = core::iter::once(Place::Waste).chain((0..7).map(Place::Tableau));
+            for from in sources {
+                let Some(card) = self.pile(from).top() else {
+                    continue;
+                };
+                if safe_only && !self.is_safe(card) {
+                    continue;
+                }
+                let to = Place::Foundation(card.suit() as u8);
+                if self.legal_count(from, to).is_some() {
+                    self.apply(from, to, 1, true);
+                    moved += 1;
+                    progress = true;
+                }
+            }
+            if !progress {
+                return moved;
+            }
+        }
+    }
+
+    /// Undo the last move the player made, and anything the game did
+    /// automatically afterwards. Returns false if there is nothing to undo.
+    pub fn undo(&mut self) -> bool {
+        if self.history_len == 0 {
+            return false;
+        }
+        while self.history_len > 0 {
+            self.history_len -= 1;
+            let action = self.history[self.history_len];
+            match action {
+                Action::Draw(count) => {
+                    for _ in 0..count {
+                        if let Some(card) = self.waste.pop() {
+                            self.stock.push(card);
+                        }
+                    }
+                }
+                Action::Recycle(count) => {
+                    for _ in 0..count {
+                        if let Some(card) = self.stock.pop() {
+                            self.waste.push(card);
+                        }
+                    }
+                }
+                Action::Move {
+                    from,
+                    to,
+                    count,
+                    flipped,
+                    automatic,
+                } => {
+                    if flipped {
+                        self.pile_mut(from).unflip_top();
+                    }
+                    self.transfer(to, from, usize::from(count));
+                    if automatic {
+                        // Keep going until we undo the player's move
+                        continue;
+                    }
+                }
+            }
+            break;
+        }
+        self.moves = self.moves.saturating_sub(1);
+        true
+    }
+
+    /// Work out how many cards would move, if this move is legal
+    fn legal_count(&self, from: Place, to: Place) -> Option<usize> {
+        if from == to {
+            return None;
+        }
+        let source = self.pile(from);
+        let moving = match from {
+            Place::Tableau(_) => source.face_up(),
+            _ => &source.cards()[source.len().saturating_sub(1)..],
+        };
+        if moving.is_empty() {
+            return None;
+        }
+        match to {
+            Place::Waste => None,
+            Place::Foundation(idx) => {
+                let card = *moving.last()?;
+                let foundation = &self.foundations[usize::from(idx)];
+                let fits =
+                    card.suit() as u8 == idx && usize::from(card.rank()) == foundation.len() + 1;
+                fits.then_some(1)
+            }
+            Place::Tableau(idx) => {
+                // Find the card in the run which will go on the destination
+                let position = match self.tableau[usize::from(idx)].top() {
+                    Some(top) => moving.iter().position(|card| card.fits_on(top))?,
+                    None => moving.iter().position(|card| card.rank() == 13)?,
+                };
+                Some(moving.len() - position)
+            }
+        }
+    }
+
+    /// Can this card go to the foundation without the player ever wanting it
+    /// back? That's when the cards which could go on it are already up.
+    fn is_safe(&self, card: Card) -> bool {
+        if card.rank() <= 2 {
+            return true;
+        }
+        self.foundations
+            .iter()
+            .filter_map(|pile| pile.top())
+            .filter(|top| top.is_red() != card.is_red())
+            .filter(|top| top.rank() + 1 >= card.rank())
+            .count()
+            == 2
+    }
+
+    /// Move the cards and record the move
+    fn apply(&mut self, from: Place, to: Place, count: usize, automatic: bool) {
+        self.transfer(from, to, count);
+        let flipped = match from {
+            Place::Tableau(_) => self.pile_mut(from).flip_top(),
+            _ => false,
+        };
+        self.record(Action::Move {
+            from,
+            to,
+            count: count as u8,
+            flipped,
+            automatic,
+        });
+    }
+
+    /// Move cards from the top of one pile to another, keeping their order
+    fn transfer(&mut self, from: Place, to: Place, count: usize) {
+        let mut moving = [Card::from_index(0); 13];
+        let count = count.min(moving.len());
+        for slot in moving[0..count].iter_mut().rev() {
+            if let Some(card) = self.pile_mut(from).pop() {
+                *slot = card;
+            }
+        }
+        for card in &moving[0..count] {
+            self.pile_mut(to).push(*card);
+        }
+    }
+
+    /// Remember an action, so it can be undone
+    fn record(&mut self, action: Action) {
+        if self.history_len == Self::MAX_HISTORY {
+            // Forget the oldest thing we did
+            self.history.copy_within(1.., 0);
+            self.history_len -= 1;
+        }
+        self.history[self.history_len] = action;
+        self.history_len += 1;
+    }
+
+    /// Get a pile
+    fn pile(&self, place: Place) -> &Pile {
+        match place {
+            Place::Waste => &self.waste,
+            Place::Foundation(idx) => &self.foundations[usize::from(idx)],
+            Place::Tableau(idx) => &self.tableau[usize::from(idx)],
+        }
+    }
+
+    /// Get a pile, to change it
+    fn pile_mut(&mut self, place: Place) -> &mut Pile {
+        match place {
+            Place::Waste => &mut self.waste,
+            Place::Foundation(idx) => &mut self.foundations[usize::from(idx)],
+            Place::Tableau(idx) => &mut self.tableau[usize::from(idx)],
+        }
+    }
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Game::new()
+    }
+}
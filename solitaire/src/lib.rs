@@ -0,0 +1,443 @@
+//! Game logic for Klondike Solitaire
+
+#![no_std]
+#![deny(missing_docs)]
+#![deny(unsafe_code)]
+
+use core::fmt::Write;
+
+use neotron_input::{KeyEvent, Keyboard};
+use neotron_sdk::console;
+use neotron_tui::Line;
+
+pub mod cards;
+pub mod game;
+
+use cards::{Card, Suit};
+use game::{Game, Place};
+
+/// Where we keep the win statistics
+const STATS_FILE: &str = "SOLITAIR.DAT";
+
+/// The column of the first tableau pile. The stock lines up with it.
+const FIRST_COL: u8 = 4;
+
+/// How far apart the piles are
+const PILE_SPACING: u8 = 10;
+
+/// The row with the stock, waste and foundations on
+const TOP_ROW: u8 = 2;
+
+/// The row with the first card of each tableau pile on
+const TABLEAU_ROW: u8 = 6;
+
+/// How many games have been played and won
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+struct Stats {
+    played: u32,
+    won: u32,
+    streak: u32,
+    best_streak: u32,
+    /// The fewest moves anyone has won in
+    best_moves: Option<u32>,
+}
+
+/// Represents the Solitaire application
+pub struct App {
+    width: u8,
+    height: u8,
+    stdout: neotron_sdk::File,
+    keyboard: Keyboard,
+    game: Game,
+    stats: Stats,
+    /// Has the current game been counted in the statistics?
+    counted: bool,
+    /// The pile the player has picked cards up from
+    selected: Option<Place>,
+    /// How many cards we turn over at a time in the next game
+    draw_count: u8,
+    message: Option<&'static str>,
+}
+
+impl App {
+    /// Make a new Solitaire application.
+    ///
+    /// You can give the screen size in characters.
+    pub const fn new(width: u8, height: u8) -> App {
+        App {
+            width,
+            height,
+            stdout: neotron_sdk::stdout(),
+            keyboard: Keyboard::new(),
+            game: Game::new(),
+            stats: Stats {
+                played: 0,
+                won: 0,
+                streak: 0,
+                best_streak: 0,
+                best_moves: None,
+            },
+            counted: false,
+            selected: None,
+            draw_count: 1,
+            message: None,
+        }
+    }
+
+    /// Play Solitaire, until the user quits
+    pub fn play(&mut self) {
+        self.load_stats();
+        console::cursor_off(&mut self.stdout);
+        self.game.rng = neotron_rand::Rng::from_rtc();
+        self.new_game();
+        loop {
+            self.draw();
+            let key = self.keyboard.wait();
+            self.message = None;
+            let key = match key {
+                KeyEvent::Char(ch) => ch.to_ascii_lowercase(),
+                KeyEvent::Escape => {
+                    self.selected = None;
+                    continue;
+                }
+                _ => continue,
+            };
+            match key {
+                'q' => break,
+                'n' => self.new_game(),
+                'm' => {
+                    self.draw_count = if self.draw_count == 1 { 3 } else { 1 };
+                    self.message = Some(if self.draw_count == 1 {
+                        "The next game will turn over one card at a time"
+                    } else {
+                        "The next game will turn over three cards at a time"
+                    });
+                }
+                ' ' | 'd' => {
+                    self.selected = None;
+                    if self.game.draw() {
+                        self.after_move();
+                    }
+                }
+                'u' => {
+                    self.selected = None;
+                    if !self.game.undo() {
+                        self.message = Some("Nothing to undo");
+                    }
+                }
+                'a' => {
+                    self.selected = None;
+                    if self.game.auto_move(false) == 0 {
+                        self.message = Some("Nothing can go to the foundations");
+                    } else {
+                        self.after_move();
+                    }
+                }
+                'w' => self.choose(Place::Waste),
+                'f' => self.choose(Place::Foundation(0)),
+                digit @ '1'..='7' => self.choose(Place::Tableau(digit as u8 - b'1')),
+                _ => {}
+            }
+        }
+        self.forfeit();
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        console::clear_screen(&mut self.stdout);
+        console::move_cursor(&mut self.stdout, console::Position::origin());
+        console::cursor_on(&mut self.stdout);
+    }
+
+    /// Start a new game
+    fn new_game(&mut self) {
+        self.forfeit();
+        self.game.deal(self.draw_count);
+        self.counted = false;
+        self.selected = None;
+    }
+
+    /// If the current game was started but not won, count it as a loss
+    fn forfeit(&mut self) {
+        if self.counted && !self.game.is_won() {
+            self.stats.streak = 0;
+            self.save_stats();
+        }
+        self.counted = false;
+    }
+
+    /// The player pressed the key for a pile
+    fn choose(&mut self, place: Place) {
+        let Some(from) = self.selected else {
+            // Pick up from here. You can't pick up from an empty pile.
+            let empty = match place {
+                Place::Waste => self.game.waste().is_empty(),
+                Place::Foundation(_) => (0..4).all(|idx| self.game.foundation(idx).is_empty()),
+                Place::Tableau(idx) => self.game.tableau(usize::from(idx)).is_empty(),
+            };
+            if !empty {
+                self.selected = Some(place);
+            }
+            return;
+        };
+        self.selected = None;
+        let moved = match (from, place) {
+            // Picking the same pile twice sends its top card to the foundations
+            _ if from == place => self.game.to_foundation(from),
+            (_, Place::Foundation(_)) => self.game.to_foundation(from),
+            (Place::Foundation(_), _) => {
+                // Find a foundation card which fits
+                (0..4).any(|idx| self.game.move_cards(Place::Foundation(idx), place))
+            }
+            _ => self.game.move_cards(from, place),
+        };
+        if moved {
+            self.after_move();
+        } else {
+            self.message = Some("You can't move that there");
+        }
+    }
+
+    /// Tidy up after a successful move
+    fn after_move(&mut self) {
+        if !self.counted {
+            self.counted = true;
+            self.stats.played += 1;
+            self.save_stats();
+        }
+        self.game.auto_move(true);
+        if self.game.is_won() {
+            self.win();
+        }
+    }
+
+    /// The player has won!
+    fn win(&mut self) {
+        self.stats.won += 1;
+        self.stats.streak += 1;
+        self.stats.best_streak = self.stats.best_streak.max(self.stats.streak);
+        let moves = self.game.moves();
+        self.stats.best_moves = Some(self.stats.best_moves.map_or(moves, |best| best.min(moves)));
+        self.counted = false;
+        self.save_stats();
+        self.draw();
+        let mut text = Line::new();
+        let _ = write!(
+            text,
+            " You won in {} moves! Press any key for a new game. ",
+            moves
+        );
+        let row = self.height / 2;
+        let col = (usize::from(self.width).saturating_sub(text.as_str().len()) / 2) as u8;
+        console::move_cursor(&mut self.stdout, console::Position { row, col });
+        console::set_sgr(
+            &mut self.stdout,
+            [
+                console::SgrParam::Bold,
+                console::SgrParam::FgYellow,
+                console::SgrParam::BgBlue,
+            ],
+        );
+        let _ = self.stdout.write_str(text.as_str());
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        self.keyboard.wait();
+        self.new_game();
+    }
+
+    /// Draw the whole table
+    fn draw(&mut self) {
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        console::clear_screen(&mut self.stdout);
+
+        // Title and statistics
+        self.move_to(0, 1);
+        let _ = write!(
+            self.stdout,
+            "Klondike (draw {})   Moves: {}   Played: {}  Won: {}",
+            self.game.draw_count(),
+            self.game.moves(),
+            self.stats.played,
+            self.stats.won
+        );
+        if let Some(percent) = (self.stats.won * 100).checked_div(self.stats.played) {
+            let _ = write!(self.stdout, " ({}%)", percent);
+        }
+        let _ = write!(
+            self.stdout,
+            "  Streak: {}  Best: {}",
+            self.stats.streak, self.stats.best_streak
+        );
+
+        // Labels for the keys
+        self.move_to(TOP_ROW - 1, FIRST_COL);
+        let _ = self.stdout.write_str("Space");
+        self.move_to(TOP_ROW - 1, FIRST_COL + PILE_SPACING);
+        let _ = self.stdout.write_str("  W");
+        self.move_to(TOP_ROW - 1, FIRST_COL + 3 * PILE_SPACING);
+        let _ = self.stdout.write_str("  F");
+
+        // The stock
+        self.move_to(TOP_ROW, FIRST_COL);
+        if self.game.stock().is_empty() {
+            self.draw_space();
+        } else {
+            self.draw_back();
+        }
+        self.move_to(TOP_ROW + 1, FIRST_COL);
+        let _ = write!(self.stdout, " {:2}", self.game.stock().len());
+
+        // The waste - in draw three, we fan out the top three cards
+        let waste = *self.game.waste();
+        let shown = if self.game.draw_count() == 3 { 3 } else { 1 };
+        let start = waste.len().saturating_sub(shown);
+        if waste.is_empty() {
+            self.move_to(TOP_ROW, FIRST_COL + PILE_SPACING);
+            self.draw_space();
+        }
+        for (idx, card) in waste.cards()[start..].iter().enumerate() {
+            self.move_to(TOP_ROW, FIRST_COL + PILE_SPACING + idx as u8 * 4);
+            let is_top = start + idx + 1 == waste.len();
+            let selected = is_top && self.selected == Some(Place::Waste);
+            self.draw_card(*card, selected);
+        }
+
+        // The foundations
+        for (idx, suit) in Suit::ALL.iter().enumerate() {
+            self.move_to(TOP_ROW, FIRST_COL + (3 + idx as u8) * PILE_SPACING);
+            match self.game.foundation(idx).top() {
+                Some(card) => {
+                    let selected = matches!(self.selected, Some(Place::Foundation(_)));
+                    self.draw_card(card, selected);
+                }
+                None => {
+                    let _ = write!(self.stdout, "[ {} ]", suit.symbol());
+                }
+            }
+        }
+
+        // The tableau
+        for idx in 0..7 {
+            let col = FIRST_COL + idx as u8 * PILE_SPACING;
+            self.move_to(TABLEAU_ROW - 1, col);
+            let _ = write!(self.stdout, "  {}", idx + 1);
+            let pile = *self.game.tableau(idx);
+            if pile.is_empty() {
+                self.move_to(TABLEAU_ROW, col);
+                self.draw_space();
+            }
+            let selected = self.selected == Some(Place::Tableau(idx as u8));
+            for (depth, card) in pile.cards().iter().enumerate() {
+                self.move_to(TABLEAU_ROW + depth as u8, col);
+                if depth < pile.face_down() {
+                    self.draw_back();
+                } else {
+                    self.draw_card(*card, selected);
+                }
+            }
+        }
+
+        // Help, or a message
+        self.move_to(self.height - 1, 0);
+        match self.message {
+            Some(message) => {
+                let _ = self.stdout.write_str(message);
+            }
+            None if self.selected.is_some() => {
+                let _ = self
+                    .stdout
+                    .write_str("Move to which pile? 1-7: tableau, F: foundation, Esc: cancel");
+            }
+            None => {
+                let _ = self.stdout.write_str(
+                    "1-7/W: pick up  Space: draw  U: undo  A: auto  N: new  M: draw 1/3  Q: quit",
+                );
+            }
+        }
+    }
+
+    /// Draw a face-up card at the cursor
+    fn draw_card(&mut self, card: Card, selected: bool) {
+        let fg = if card.is_red() {
+            console::SgrParam::FgRed
+        } else {
+            console::SgrParam::FgBlack
+        };
+        let bg = if selected {
+            console::SgrParam::BgYellow
+        } else {
+            console::SgrParam::BgWhite
+        };
+        console::set_sgr(&mut self.stdout, [fg, bg]);
+        let _ = write!(
+            self.stdout,
+            "[{:>2}{}]",
+            card.rank_str(),
+            card.suit().symbol()
+        );
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+    }
+
+    /// Draw a face-down card at the cursor
+    fn draw_back(&mut self) {
+        console::set_sgr(
+            &mut self.stdout,
+            [console::SgrParam::FgCyan, console::SgrParam::BgBlue],
+        );
+        let _ = self.stdout.write_str("[▒▒▒]");
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+    }
+
+    /// Draw an empty space where a card could go
+    fn draw_space(&mut self) {
+        let _ = self.stdout.write_str("[   ]");
+    }
+
+    /// Move the cursor
+    fn move_to(&mut self, row: u8, col: u8) {
+        console::move_cursor(&mut self.stdout, console::Position { row, col });
+    }
+
+    /// Read the statistics from disk, if we have any
+    fn load_stats(&mut self) {
+        let Ok(path) = neotron_sdk::path::Path::new(STATS_FILE) else {
+            return;
+        };
+        let Ok(file) = neotron_sdk::File::open(path, neotron_sdk::Flags::empty()) else {
+            return;
+        };
+        let mut buffer = [0u8; 64];
+        let Ok(n) = file.read(&mut buffer) else {
+            return;
+        };
+        let Ok(text) = core::str::from_utf8(&buffer[0..n]) else {
+            return;
+        };
+        let mut lines = text.lines().map(|line| line.trim().parse::<u32>().ok());
+        self.stats.played = lines.next().flatten().unwrap_or(0);
+        self.stats.won = lines.next().flatten().unwrap_or(0);
+        self.stats.streak = lines.next().flatten().unwrap_or(0);
+        self.stats.best_streak = lines.next().flatten().unwrap_or(0);
+        self.stats.best_moves = lines.next().flatten();
+    }
+
+    /// Write the statistics to disk.
+    ///
+    /// One number per line, with `-` for the best moves if nobody has won yet.
+    fn save_stats(&mut self) {
+        let Ok(path) = neotron_sdk::path::Path::new(STATS_FILE) else {
+            return;
+        };
+        let Ok(mut file) = neotron_sdk::File::open(
+            path,
+            neotron_sdk::Flags::WRITE | neotron_sdk::Flags::CREATE | neotron_sdk::Flags::TRUNCATE,
+        ) else {
+            return;
+        };
+        let stats = self.stats;
+        let _ = writeln!(file, "{}", stats.played);
+        let _ = writeln!(file, "{}", stats.won);
+        let _ = writeln!(file, "{}", stats.streak);
+        let _ = writeln!(file, "{}", stats.best_streak);
+        let _ = match stats.best_moves {
+            Some(moves) => writeln!(file, "{}", moves),
+            None => writeln!(file, "-"),
+        };
+    }
+}
@@ -0,0 +1,358 @@
+//! Gomoku, or five in a row.
+//!
+//! Players take turns to put a stone on a 15 by 15 board, and the first to
+//! make an unbroken line of five (or more) wins. The computer scores every
+//! empty point by the lines it would make for itself and the lines it would
+//! block for you, and plays the best one.
+
+use core::fmt::Write;
+
+use neotron_input::{KeyEvent, Keyboard};
+use neotron_sdk::console;
+use neotron_tui::Line;
+
+use crate::{show_status, Difficulty, Finish, Player};
+
+/// How many points along each side
+const SIZE: usize = 15;
+
+/// What the two sides are called. Black moves first.
+pub const SIDES: [&str; 2] = ["Black", "White"];
+
+/// The screen row of the top of the board
+const TOP: u8 = 3;
+
+/// The four directions a line can go in: across, down, and the two diagonals
+const DIRECTIONS: [(i8, i8); 4] = [(1, 0), (0, 1), (1, 1), (1, -1)];
+
+/// The score for making five
+const FIVE: i32 = 1_000_000;
+
+/// How many of the best moves the computer looks at more closely on Hard
+const CANDIDATES: usize = 8;
+
+/// What is on a point
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Stone {
+    Empty,
+    Black,
+    White,
+}
+
+impl Stone {
+    /// The other player's stone
+    fn other(self) -> Stone {
+        match self {
+            Stone::Black => Stone::White,
+            Stone::White => Stone::Black,
+            Stone::Empty => Stone::Empty,
+        }
+    }
+
+    /// Which side this stone belongs to
+    fn side(self) -> usize {
+        match self {
+            Stone::White => 1,
+            _ => 0,
+        }
+    }
+}
+
+/// A game of Gomoku
+pub struct Gomoku {
+    points: [[Stone; SIZE]; SIZE],
+    /// Which point the cursor is on, as (column, row)
+    cursor: (usize, usize),
+    /// Where the last stone went
+    last: Option<(usize, usize)>,
+    /// The line of five which won the game, if there is one
+    winning_line: Option<((usize, usize), (i8, i8))>,
+}
+
+impl Gomoku {
+    /// Make a new game
+    pub const fn new() -> Gomoku {
+        Gomoku {
+            points: [[Stone::Empty; SIZE]; SIZE],
+            cursor: (SIZE / 2, SIZE / 2),
+            last: None,
+            winning_line: None,
+        }
+    }
+
+    /// Play one game.
+    ///
+    /// Returns `None` if the player gave up part way through.
+    pub fn play(
+        &mut self,
+        keyboard: &mut Keyboard,
+        stdout: &mut neotron_sdk::File,
+        width: u8,
+        players: [Player; 2],
+        difficulty: Difficulty,
+    ) -> Option<Finish> {
+        *self = Gomoku::new();
+        let left = (width - SIZE as u8 * 2) / 2;
+        let mut turn = Stone::Black;
+        let mut moves = 0;
+        loop {
+            let human = players[turn.side()] == Player::Human;
+            self.draw(stdout, left, turn, human);
+            let (col, row) = if human {
+                loop {
+                    match keyboard.wait() {
+                        KeyEvent::Left if self.cursor.0 > 0 => self.cursor.0 -= 1,
+                        KeyEvent::Right if self.cursor.0 < SIZE - 1 => self.cursor.0 += 1,
+                        KeyEvent::Up if self.cursor.1 > 0 => self.cursor.1 -= 1,
+                        KeyEvent::Down if self.cursor.1 < SIZE - 1 => self.cursor.1 += 1,
+                        KeyEvent::Char(' ') | KeyEvent::Enter
+                            if self.points[self.cursor.1][self.cursor.0] == Stone::Empty =>
+                        {
+                            break self.cursor;
+                        }
+                        KeyEvent::Char('q' | 'Q') | KeyEvent::Escape => return None,
+                        _ => {}
+                    }
+                    self.draw(stdout, left, turn, true);
+                }
+            } else {
+                self.best_move(turn, difficulty)
+            };
+            self.points[row][col] = turn;
+            self.last = Some((col, row));
+            moves += 1;
+            if let Some(direction) = self.five_at(col, row) {
+                self.winning_line = Some(((col, row), direction));
+                self.draw(stdout, left, turn, false);
+                return Some(Finish::Won(turn.side()));
+            }
+            if moves == SIZE * SIZE {
+                self.draw(stdout, left, turn, false);
+                return Some(Finish::Drawn);
+            }
+            turn = turn.other();
+        }
+    }
+
+    /// Draw the board, and whose turn it is
+    fn draw(&self, stdout: &mut neotron_sdk::File, left: u8, turn: Stone, choosing: bool) {
+        console::set_sgr(stdout, [console::SgrParam::Reset]);
+        console::move_cursor(
+            stdout,
+            console::Position {
+                row: TOP - 1,
+                col: left,
+            },
+        );
+        for col in 0..SIZE {
+            let _ = write!(stdout, "{} ", char::from(b'A' + col as u8));
+        }
+        for row in 0..SIZE {
+            console::move_cursor(
+                stdout,
+                console::Position {
+                    row: TOP + row as u8,
+                    col: left - 3,
+                },
+            );
+            console::set_sgr(stdout, [console::SgrParam::Reset]);
+            let _ = write!(stdout, "{:>2} ", SIZE - row);
+            for col in 0..SIZE {
+                let stone = self.points[row][col];
+                let (text, colour) = match stone {
+                    Stone::Empty => ('·', console::SgrParam::FgBlue),
+                    Stone::Black => ('X', console::SgrParam::FgRed),
+                    Stone::White => ('O', console::SgrParam::FgCyan),
+                };
+                if self.in_winning_line(col, row) {
+                    console::set_sgr(
+                        stdout,
+                        [console::SgrParam::Bold, console::SgrParam::FgYellow],
+                    );
+                } else if stone == Stone::Empty {
+                    console::set_sgr(stdout, [colour]);
+                } else {
+                    console::set_sgr(stdout, [console::SgrParam::Bold, colour]);
+                }
+                if choosing && self.cursor == (col, row) {
+                    console::set_sgr(stdout, [console::SgrParam::Reverse]);
+                } else if self.winning_line.is_none() && self.last == Some((col, row)) {
+                    // So you can see where the last stone went
+                    console::set_sgr(stdout, [console::SgrParam::BgBlue]);
+                }
+                let _ = stdout.write_char(text);
+                console::set_sgr(stdout, [console::SgrParam::Reset]);
+                let _ = stdout.write_char(' ');
+            }
+        }
+        let mut line = Line::new();
+        if choosing {
+            let _ = write!(
+                line,
+                "{} ({}) to play. Use the arrows, then Enter.",
+                SIDES[turn.side()],
+                if turn == Stone::Black { 'X' } else { 'O' }
+            );
+        } else if self.winning_line.is_none() {
+            let _ = line.write_str("Thinking...");
+        }
+        show_status(stdout, TOP + SIZE as u8 + 1, line.as_str());
+    }
+
+    /// Is this point part of the winning line?
+    fn in_winning_line(&self, col: usize, row: usize) -> bool {
+        let Some(((c, r), (dx, dy))) = self.winning_line else {
+            return false;
+        };
+        let stone = self.points[r][c];
+        if self.points[row][col] != stone {
+            return false;
+        }
+        // Walk from the winning stone in both directions
+        for sign in [1, -1] {
+            let (mut x, mut y) = (c as i8, r as i8);
+            while self.get(x, y) == Some(stone) {
+                if (x as usize, y as usize) == (col, row) {
+                    return true;
+                }
+                x += dx * sign;
+                y += dy * sign;
+            }
+        }
+        false
+    }
+
+    /// Did the stone here make five in a row? Returns the direction of the
+    /// line if so.
+    fn five_at(&self, col: usize, row: usize) -> Option<(i8, i8)> {
+        let stone = self.points[row][col];
+        DIRECTIONS
+            .into_iter()
+            .find(|&dir| self.run(col, row, stone, dir).0 >= 5)
+    }
+
+    /// Get a point, if it is on the board
+    fn get(&self, col: i8, row: i8) -> Option<Stone> {
+        if (0..SIZE as i8).contains(&col) && (0..SIZE as i8).contains(&row) {
+            Some(self.points[row as usize][col as usize])
+        } else {
+            None
+        }
+    }
+
+    /// How long a line this stone would make through this point, going both
+    /// ways along a direction, and how many of its ends are open
+    fn run(&self, col: usize, row: usize, stone: Stone, (dx, dy): (i8, i8)) -> (usize, usize) {
+        let mut length = 1;
+        let mut open = 0;
+        for sign in [1, -1] {
+            let (mut x, mut y) = (col as i8 + dx * sign, row as i8 + dy * sign);
+            while self.get(x, y) == Some(stone) {
+                length += 1;
+                x += dx * sign;
+                y += dy * sign;
+            }
+            if self.get(x, y) == Some(Stone::Empty) {
+                open += 1;
+            }
+        }
+        (length, open)
+    }
+
+    /// How good this empty point would be for this stone, counting the lines
+    /// it would make
+    fn line_score(&self, col: usize, row: usize, stone: Stone) -> i32 {
+        let mut score = 0;
+        for dir in DIRECTIONS {
+            score += match self.run(col, row, stone, dir) {
+                (5.., _) => FIVE,
+                (4, 2) => 100_000,
+                (4, 1) => 10_000,
+                (3, 2) => 5_000,
+                (3, 1) => 500,
+                (2, 2) => 200,
+                (2, 1) => 50,
+                (1, 2) => 10,
+                (1, 1) => 2,
+                _ => 0,
+            };
+        }
+        score
+    }
+
+    /// How good this empty point would be for this stone, counting the lines
+    /// it would make and the lines it would block
+    fn score(&self, col: usize, row: usize, stone: Stone, difficulty: Difficulty) -> i32 {
+        let attack = self.line_score(col, row, stone);
+        let defence = self.line_score(col, row, stone.other());
+        // Blocking matters nearly as much as attacking, unless we're being
+        // kind
+        let weight = match difficulty {
+            Difficulty::Easy => 4,
+            _ => 9,
+        };
+        attack.saturating_add(defence / 10 * weight)
+    }
+
+    /// Is there a stone within two points of this one? Moves anywhere else
+    /// aren't worth thinking about.
+    fn is_near_stone(&self, col: usize, row: usize) -> bool {
+        for dy in -2..=2 {
+            for dx in -2..=2 {
+                if let Some(Stone::Black | Stone::White) = self.get(col as i8 + dx, row as i8 + dy)
+                {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    /// Pick the best point for the computer
+    fn best_move(&mut self, stone: Stone, difficulty: Difficulty) -> (usize, usize) {
+        // Keep the best few moves, best first
+        let mut best = [(i32::MIN, (SIZE / 2, SIZE / 2)); CANDIDATES];
+        for row in 0..SIZE {
+            for col in 0..SIZE {
+                if self.points[row][col] != Stone::Empty || !self.is_near_stone(col, row) {
+                    continue;
+                }
+                let score = self.score(col, row, stone, difficulty);
+                if let Some(pos) = best.iter().position(|&(s, _)| score > s) {
+                    best.copy_within(pos..CANDIDATES - 1, pos + 1);
+                    best[pos] = (score, (col, row));
+                }
+            }
+        }
+        if difficulty != Difficulty::Hard || best[0].0 >= FIVE {
+            return best[0].1;
+        }
+
+        // Look at what the other player could do after each of our best
+        // moves, and pick the one which leaves them worst off
+        let mut choice = (i32::MIN, best[0].1);
+        for &(score, (col, row)) in best.iter().filter(|(s, _)| *s > i32::MIN) {
+            self.points[row][col] = stone;
+            let mut reply = 0;
+            for r in 0..SIZE {
+                for c in 0..SIZE {
+                    if self.points[r][c] == Stone::Empty && self.is_near_stone(c, r) {
+                        reply = reply.max(self.score(c, r, stone.other(), difficulty));
+                    }
+                }
+            }
+            self.points[row][col] = Stone::Empty;
+            let value = score.saturating_sub(reply / 2);
+            if value > choice.0 {
+                choice = (value, (col, row));
+            }
+        }
+        choice.1
+    }
+}
+
+impl Default for Gomoku {
+    fn default() -> Self {
+        Gomoku::new()
+    }
+}
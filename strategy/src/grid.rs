@@ -0,0 +1,137 @@
+//! Draws a board as a grid of boxes, for all the games.
+//!
+//! Each cell is three characters wide and one row tall, with a line drawn
+//! around it. The cells can have a coloured background, like the green baize
+//! of a Reversi board.
+
+use core::fmt::Write;
+
+use neotron_sdk::console;
+
+/// What to show in one cell of the grid
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Glyph {
+    /// Three characters of text
+    pub text: &'static str,
+    /// What colour to draw the text in
+    pub colour: console::SgrParam,
+    /// Should the text be bold?
+    pub bold: bool,
+}
+
+impl Glyph {
+    /// An empty cell
+    pub const EMPTY: Glyph = Glyph {
+        text: "   ",
+        colour: console::SgrParam::FgWhite,
+        bold: false,
+    };
+}
+
+/// A grid of cells on the screen
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Grid {
+    cols: u8,
+    rows: u8,
+    /// The screen row of the top edge
+    top: u8,
+    /// The screen column of the left edge
+    left: u8,
+    /// The colour behind the cells
+    background: console::SgrParam,
+}
+
+impl Grid {
+    /// Make a grid with the given number of cells, centred on a screen of the
+    /// given width, with its top edge on the given row.
+    pub const fn new(
+        cols: u8,
+        rows: u8,
+        screen_width: u8,
+        top: u8,
+        background: console::SgrParam,
+    ) -> Grid {
+        let width = cols * 4 + 1;
+        Grid {
+            cols,
+            rows,
+            top,
+            left: if screen_width > width {
+                (screen_width - width) / 2
+            } else {
+                0
+            },
+            background,
+        }
+    }
+
+    /// The screen row just below the grid
+    pub fn bottom(&self) -> u8 {
+        self.top + self.rows * 2 + 1
+    }
+
+    /// Draw the whole grid.
+    ///
+    /// We call `glyph` to find out what is in each cell. The cell under the
+    /// cursor, if there is one, is drawn in reverse video.
+    pub fn draw<F>(&self, stdout: &mut neotron_sdk::File, cursor: Option<(u8, u8)>, glyph: F)
+    where
+        F: Fn(u8, u8) -> Glyph,
+    {
+        console::set_sgr(stdout, [console::SgrParam::Reset]);
+        self.draw_edge(stdout, self.top, ['┌', '┬', '┐']);
+        for row in 0..self.rows {
+            let screen_row = self.top + 1 + row * 2;
+            console::move_cursor(
+                stdout,
+                console::Position {
+                    row: screen_row,
+                    col: self.left,
+                },
+            );
+            for col in 0..self.cols {
+                let _ = stdout.write_str("│");
+                let cell = glyph(col, row);
+                if cell.bold {
+                    console::set_sgr(
+                        stdout,
+                        [console::SgrParam::Bold, cell.colour, self.background],
+                    );
+                } else {
+                    console::set_sgr(stdout, [cell.colour, self.background]);
+                }
+                if cursor == Some((col, row)) {
+                    console::set_sgr(stdout, [console::SgrParam::Reverse]);
+                }
+                let _ = stdout.write_str(cell.text);
+                console::set_sgr(stdout, [console::SgrParam::Reset]);
+            }
+            let _ = stdout.write_str("│");
+            if row + 1 < self.rows {
+                self.draw_edge(stdout, screen_row + 1, ['├', '┼', '┤']);
+            }
+        }
+        self.draw_edge(stdout, self.bottom() - 1, ['└', '┴', '┘']);
+    }
+
+    /// Draw a horizontal line across the grid, with the given corner pieces
+    /// for the left end, the joins, and the right end
+    fn draw_edge(&self, stdout: &mut neotron_sdk::File, row: u8, pieces: [char; 3]) {
+        console::move_cursor(
+            stdout,
+            console::Position {
+                row,
+                col: self.left,
+            },
+        );
+        let _ = stdout.write_char(pieces[0]);
+        for col in 0..self.cols {
+            let _ = stdout.write_str("───");
+            let _ = stdout.write_char(if col + 1 < self.cols {
+                pieces[1]
+            } else {
+                pieces[2]
+            });
+        }
+    }
+}
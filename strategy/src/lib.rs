@@ -0,0 +1,313 @@
+//! Game logic for the strategy games - Gomoku and Reversi
+
+#![no_std]
+#![deny(missing_docs)]
+#![deny(unsafe_code)]
+
+use core::fmt::Write;
+
+use neotron_input::{KeyEvent, Keyboard};
+use neotron_sdk::console;
+use neotron_tui::Line;
+
+pub mod gomoku;
+pub mod grid;
+pub mod reversi;
+
+/// Who is playing one side of the board
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Player {
+    /// Someone at the keyboard
+    Human,
+    /// The computer
+    Computer,
+}
+
+/// How a game ended
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Finish {
+    /// One side won. Side 0 moves first.
+    Won(usize),
+    /// Nobody won
+    Drawn,
+}
+
+/// How a game ended, from the human player's point of view
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    /// The human won
+    Won,
+    /// The computer won
+    Lost,
+    /// Nobody won
+    Drawn,
+}
+
+/// How hard the computer tries
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Difficulty {
+    /// Makes mistakes
+    Easy,
+    /// Plays sensibly
+    Normal,
+    /// Looks further ahead, which can take a few seconds
+    Hard,
+}
+
+impl Difficulty {
+    /// The next difficulty, going round in a loop
+    fn next(self) -> Difficulty {
+        match self {
+            Difficulty::Easy => Difficulty::Normal,
+            Difficulty::Normal => Difficulty::Hard,
+            Difficulty::Hard => Difficulty::Easy,
+        }
+    }
+
+    /// The name of this difficulty
+    fn name(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Normal => "Normal",
+            Difficulty::Hard => "Hard",
+        }
+    }
+}
+
+/// Which game we are playing
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Game {
+    Gomoku,
+    Reversi,
+}
+
+impl Game {
+    /// The name of the game
+    fn name(self) -> &'static str {
+        match self {
+            Game::Gomoku => "GOMOKU",
+            Game::Reversi => "REVERSI",
+        }
+    }
+
+    /// What the two sides are called
+    fn sides(self) -> [&'static str; 2] {
+        match self {
+            Game::Gomoku => gomoku::SIDES,
+            Game::Reversi => reversi::SIDES,
+        }
+    }
+}
+
+/// Wins, losses and draws for one game
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct Tally {
+    won: u16,
+    lost: u16,
+    drawn: u16,
+}
+
+impl Tally {
+    /// No games played yet
+    const NEW: Tally = Tally {
+        won: 0,
+        lost: 0,
+        drawn: 0,
+    };
+
+    /// Count a game
+    fn add(&mut self, outcome: Outcome) {
+        let count = match outcome {
+            Outcome::Won => &mut self.won,
+            Outcome::Lost => &mut self.lost,
+            Outcome::Drawn => &mut self.drawn,
+        };
+        *count = count.saturating_add(1);
+    }
+}
+
+/// Represents the strategy games application
+///
+/// You pick a game from the menu, and can play as many games as you like,
+/// either against the computer or against someone sitting next to you.
+pub struct App {
+    stdout: neotron_sdk::File,
+    keyboard: Keyboard,
+    width: u8,
+    gomoku: gomoku::Gomoku,
+    reversi: reversi::Reversi,
+    difficulty: Difficulty,
+    two_players: bool,
+    /// Scores against the computer for Gomoku, then Reversi
+    tallies: [Tally; 2],
+}
+
+impl App {
+    /// Make a new strategy games application.
+    ///
+    /// You can give the screen size in characters.
+    pub const fn new(width: u8, _height: u8) -> App {
+        App {
+            stdout: neotron_sdk::stdout(),
+            keyboard: Keyboard::new(),
+            width,
+            gomoku: gomoku::Gomoku::new(),
+            reversi: reversi::Reversi::new(),
+            difficulty: Difficulty::Normal,
+            two_players: false,
+            tallies: [Tally::NEW; 2],
+        }
+    }
+
+    /// Show the menu and play games until the user quits
+    pub fn play(&mut self) {
+        console::cursor_off(&mut self.stdout);
+        loop {
+            self.menu();
+            match self.keyboard.wait() {
+                KeyEvent::Char('1') => self.play_game(Game::Gomoku),
+                KeyEvent::Char('2') => self.play_game(Game::Reversi),
+                KeyEvent::Char('d' | 'D') => self.difficulty = self.difficulty.next(),
+                KeyEvent::Char('p' | 'P') => self.two_players = !self.two_players,
+                KeyEvent::Char('q' | 'Q') => break,
+                _ => {}
+            }
+        }
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        console::clear_screen(&mut self.stdout);
+        console::move_cursor(&mut self.stdout, console::Position::origin());
+        console::cursor_on(&mut self.stdout);
+    }
+
+    /// Draw the game menu
+    fn menu(&mut self) {
+        self.clear();
+        let mut line = Line::new();
+        self.centre(4, "NEOTRON STRATEGY GAMES", true);
+        self.centre(7, "1 - Gomoku (five in a row)    ", false);
+        self.centre(8, "2 - Reversi                   ", false);
+        let players = if self.two_players {
+            "Two players"
+        } else {
+            "You vs computer"
+        };
+        let _ = write!(line, "P - Players: {:<17}", players);
+        self.centre(10, line.as_str(), false);
+        let mut line = Line::new();
+        let _ = write!(line, "D - Difficulty: {:<14}", self.difficulty.name());
+        self.centre(11, line.as_str(), false);
+        self.centre(13, "Q - Quit                      ", false);
+        for (idx, game) in [Game::Gomoku, Game::Reversi].iter().enumerate() {
+            let tally = self.tallies[idx];
+            let mut line = Line::new();
+            let _ = write!(
+                line,
+                "{:<8} won {:>3}  lost {:>3}  drawn {:>3}",
+                game.name(),
+                tally.won,
+                tally.lost,
+                tally.drawn
+            );
+            self.centre(16 + idx as u8, line.as_str(), false);
+        }
+        self.centre(20, "In a game, Q goes back to this menu", false);
+    }
+
+    /// Play one game after another, until the user goes back to the menu
+    fn play_game(&mut self, game: Game) {
+        let mut human_first = true;
+        loop {
+            self.clear();
+            self.centre(0, game.name(), true);
+            let players = if self.two_players {
+                [Player::Human, Player::Human]
+            } else if human_first {
+                [Player::Human, Player::Computer]
+            } else {
+                [Player::Computer, Player::Human]
+            };
+            let finish = match game {
+                Game::Gomoku => self.gomoku.play(
+                    &mut self.keyboard,
+                    &mut self.stdout,
+                    self.width,
+                    players,
+                    self.difficulty,
+                ),
+                Game::Reversi => self.reversi.play(
+                    &mut self.keyboard,
+                    &mut self.stdout,
+                    self.width,
+                    players,
+                    self.difficulty,
+                ),
+            };
+            let Some(finish) = finish else {
+                return;
+            };
+            let mut message = Line::new();
+            if self.two_players {
+                let _ = match finish {
+                    Finish::Won(side) => write!(message, "{} wins!", game.sides()[side]),
+                    Finish::Drawn => write!(message, "It's a draw."),
+                };
+            } else {
+                let outcome = match finish {
+                    Finish::Won(side) if players[side] == Player::Human => Outcome::Won,
+                    Finish::Won(_) => Outcome::Lost,
+                    Finish::Drawn => Outcome::Drawn,
+                };
+                self.tallies[game as usize].add(outcome);
+                let _ = message.write_str(match outcome {
+                    Outcome::Won => "You win!",
+                    Outcome::Lost => "I win!",
+                    Outcome::Drawn => "It's a draw.",
+                });
+            }
+            let _ = message.write_str(" Press N for a new game, or Q for the menu.");
+            show_status(&mut self.stdout, 23, message.as_str());
+            loop {
+                match self.keyboard.wait() {
+                    KeyEvent::Char('n' | 'N') => break,
+                    KeyEvent::Char('q' | 'Q') | KeyEvent::Escape => return,
+                    _ => {}
+                }
+            }
+            // Take turns to go first
+            human_first = !human_first;
+        }
+    }
+
+    /// Clear the screen
+    fn clear(&mut self) {
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        console::clear_screen(&mut self.stdout);
+    }
+
+    /// Write some text in the middle of a row
+    fn centre(&mut self, row: u8, text: &str, title: bool) {
+        let col = (usize::from(self.width).saturating_sub(text.chars().count())) / 2;
+        console::move_cursor(
+            &mut self.stdout,
+            console::Position {
+                row,
+                col: col as u8,
+            },
+        );
+        if title {
+            console::set_sgr(
+                &mut self.stdout,
+                [console::SgrParam::Bold, console::SgrParam::FgYellow],
+            );
+        }
+        let _ = self.stdout.write_str(text);
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+    }
+}
+
+/// Write a line of text in the middle of the screen, clearing whatever was
+/// there before
+fn show_status(stdout: &mut neotron_sdk::File, row: u8, text: &str) {
+    console::move_cursor(stdout, console::Position { row, col: 0 });
+    let _ = write!(stdout, "{:^80}", text);
+}
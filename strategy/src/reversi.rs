@@ -0,0 +1,416 @@
+//! Reversi (also known as Othello).
+//!
+//! Each move must trap a line of the other player's discs between the new
+//! disc and one of your own, and the trapped discs are turned over. The
+//! computer searches a few moves ahead, scoring each board by which squares
+//! each side holds - corners are good, the squares next to them are bad - and
+//! by how many moves each side has left.
+
+use core::fmt::Write;
+
+use neotron_input::{KeyEvent, Keyboard};
+use neotron_sdk::console;
+use neotron_tui::Line;
+
+use crate::grid::{Glyph, Grid};
+use crate::{show_status, Difficulty, Finish, Player};
+
+/// How many squares along each side
+const SIZE: usize = 8;
+
+/// What the two sides are called. Black moves first.
+pub const SIDES: [&str; 2] = ["Black", "White"];
+
+/// The eight directions a line can go in
+const DIRECTIONS: [(i8, i8); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
+/// How much each square is worth. Corners can never be taken back, and the
+/// squares next to them tend to give the corner away.
+const WEIGHTS: [[i32; SIZE]; SIZE] = [
+    [100, -20, 10, 5, 5, 10, -20, 100],
+    [-20, -50, -2, -2, -2, -2, -50, -20],
+    [10, -2, 1, 1, 1, 1, -2, 10],
+    [5, -2, 1, 0, 0, 1, -2, 5],
+    [5, -2, 1, 0, 0, 1, -2, 5],
+    [10, -2, 1, 1, 1, 1, -2, 10],
+    [-20, -50, -2, -2, -2, -2, -50, -20],
+    [100, -20, 10, 5, 5, 10, -20, 100],
+];
+
+/// The score for a board where the game is over, per disc ahead
+const WIN: i32 = 10_000;
+
+/// What is on a square
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Disc {
+    Empty,
+    Black,
+    White,
+}
+
+impl Disc {
+    /// The other player's disc
+    fn other(self) -> Disc {
+        match self {
+            Disc::Black => Disc::White,
+            Disc::White => Disc::Black,
+            Disc::Empty => Disc::Empty,
+        }
+    }
+
+    /// Which side this disc belongs to
+    fn side(self) -> usize {
+        match self {
+            Disc::White => 1,
+            _ => 0,
+        }
+    }
+}
+
+/// The squares of the board
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct Board {
+    squares: [[Disc; SIZE]; SIZE],
+}
+
+impl Board {
+    /// A board with the four discs in the middle
+    const START: Board = {
+        let mut squares = [[Disc::Empty; SIZE]; SIZE];
+        squares[3][3] = Disc::White;
+        squares[4][4] = Disc::White;
+        squares[3][4] = Disc::Black;
+        squares[4][3] = Disc::Black;
+        Board { squares }
+    };
+
+    /// Get a square, if it is on the board
+    fn get(&self, col: i8, row: i8) -> Option<Disc> {
+        if (0..SIZE as i8).contains(&col) && (0..SIZE as i8).contains(&row) {
+            Some(self.squares[row as usize][col as usize])
+        } else {
+            None
+        }
+    }
+
+    /// How many discs a move here would turn over in one direction
+    fn flips_in(&self, col: usize, row: usize, disc: Disc, (dx, dy): (i8, i8)) -> usize {
+        let (mut c, mut r) = (col as i8 + dx, row as i8 + dy);
+        let mut count = 0;
+        loop {
+            match self.get(c, r) {
+                Some(d) if d == disc.other() => count += 1,
+                Some(d) if d == disc => return count,
+                _ => return 0,
+            }
+            c += dx;
+            r += dy;
+        }
+    }
+
+    /// Can this disc go here?
+    fn is_legal(&self, col: usize, row: usize, disc: Disc) -> bool {
+        self.squares[row][col] == Disc::Empty
+            && DIRECTIONS
+                .iter()
+                .any(|&dir| self.flips_in(col, row, disc, dir) > 0)
+    }
+
+    /// Can this disc go anywhere?
+    fn has_move(&self, disc: Disc) -> bool {
+        self.mobility(disc) > 0
+    }
+
+    /// How many places this disc could go
+    fn mobility(&self, disc: Disc) -> i32 {
+        let mut count = 0;
+        for row in 0..SIZE {
+            for col in 0..SIZE {
+                if self.is_legal(col, row, disc) {
+                    count += 1;
+                }
+            }
+        }
+        count
+    }
+
+    /// Put a disc down and turn over the discs it traps. The move must be
+    /// legal.
+    fn play(&mut self, col: usize, row: usize, disc: Disc) {
+        for dir in DIRECTIONS {
+            let flips = self.flips_in(col, row, disc, dir);
+            let (mut c, mut r) = (col as i8, row as i8);
+            for _ in 0..flips {
+                c += dir.0;
+                r += dir.1;
+                self.squares[r as usize][c as usize] = disc;
+            }
+        }
+        self.squares[row][col] = disc;
+    }
+
+    /// How many discs of each colour there are
+    fn count(&self) -> [usize; 2] {
+        let mut counts = [0; 2];
+        for disc in self.squares.iter().flatten() {
+            if *disc != Disc::Empty {
+                counts[disc.side()] += 1;
+            }
+        }
+        counts
+    }
+
+    /// Score the board from the point of view of `disc`, without looking
+    /// ahead
+    fn evaluate(&self, disc: Disc) -> i32 {
+        let mut score = 0;
+        for (row, weights) in self.squares.iter().zip(WEIGHTS.iter()) {
+            for (square, weight) in row.iter().zip(weights.iter()) {
+                if *square == disc {
+                    score += weight;
+                } else if *square == disc.other() {
+                    score -= weight;
+                }
+            }
+        }
+        // Having more moves to choose from than the other player is good
+        score + 5 * (self.mobility(disc) - self.mobility(disc.other()))
+    }
+
+    /// The score for a finished game, from the point of view of `disc`
+    fn final_score(&self, disc: Disc) -> i32 {
+        let counts = self.count();
+        let mine = counts[disc.side()] as i32;
+        let theirs = counts[disc.other().side()] as i32;
+        (mine - theirs) * WIN
+    }
+}
+
+/// A game of Reversi
+pub struct Reversi {
+    board: Board,
+    /// Which square the cursor is on, as (column, row)
+    cursor: (usize, usize),
+}
+
+impl Reversi {
+    /// Make a new game
+    pub const fn new() -> Reversi {
+        Reversi {
+            board: Board::START,
+            cursor: (3, 2),
+        }
+    }
+
+    /// Play one game.
+    ///
+    /// Returns `None` if the player gave up part way through.
+    pub fn play(
+        &mut self,
+        keyboard: &mut Keyboard,
+        stdout: &mut neotron_sdk::File,
+        width: u8,
+        players: [Player; 2],
+        difficulty: Difficulty,
+    ) -> Option<Finish> {
+        *self = Reversi::new();
+        let grid = Grid::new(SIZE as u8, SIZE as u8, width, 2, console::SgrParam::BgGreen);
+        let mut turn = Disc::Black;
+        loop {
+            if !self.board.has_move(turn) {
+                if !self.board.has_move(turn.other()) {
+                    // Nobody can go, so the game is over
+                    self.draw(stdout, &grid, turn, false);
+                    let counts = self.board.count();
+                    return Some(if counts[0] > counts[1] {
+                        Finish::Won(0)
+                    } else if counts[1] > counts[0] {
+                        Finish::Won(1)
+                    } else {
+                        Finish::Drawn
+                    });
+                }
+                self.draw(stdout, &grid, turn, false);
+                let mut message = Line::new();
+                let _ = write!(
+                    message,
+                    "{} can't go, so has to pass. Press any key.",
+                    SIDES[turn.side()]
+                );
+                show_status(stdout, grid.bottom() + 1, message.as_str());
+                if let KeyEvent::Char('q' | 'Q') | KeyEvent::Escape = keyboard.wait() {
+                    return None;
+                }
+                turn = turn.other();
+                continue;
+            }
+
+            let human = players[turn.side()] == Player::Human;
+            self.draw(stdout, &grid, turn, human);
+            let (col, row) = if human {
+                loop {
+                    match keyboard.wait() {
+                        KeyEvent::Left if self.cursor.0 > 0 => self.cursor.0 -= 1,
+                        KeyEvent::Right if self.cursor.0 < SIZE - 1 => self.cursor.0 += 1,
+                        KeyEvent::Up if self.cursor.1 > 0 => self.cursor.1 -= 1,
+                        KeyEvent::Down if self.cursor.1 < SIZE - 1 => self.cursor.1 += 1,
+                        KeyEvent::Char(' ') | KeyEvent::Enter
+                            if self.board.is_legal(self.cursor.0, self.cursor.1, turn) =>
+                        {
+                            break self.cursor;
+                        }
+                        KeyEvent::Char('q' | 'Q') | KeyEvent::Escape => return None,
+                        _ => {}
+                    }
+                    self.draw(stdout, &grid, turn, true);
+                }
+            } else {
+                self.best_move(turn, difficulty)
+            };
+            self.board.play(col, row, turn);
+            turn = turn.other();
+        }
+    }
+
+    /// Draw the board, the score, and whose turn it is. If a human is
+    /// choosing a move, we show the cursor and where they can go.
+    fn draw(&self, stdout: &mut neotron_sdk::File, grid: &Grid, turn: Disc, choosing: bool) {
+        let cursor = choosing.then_some((self.cursor.0 as u8, self.cursor.1 as u8));
+        grid.draw(stdout, cursor, |col, row| {
+            let (col, row) = (usize::from(col), usize::from(row));
+            match self.board.squares[row][col] {
+                Disc::Empty if choosing && self.board.is_legal(col, row, turn) => Glyph {
+                    text: " · ",
+                    colour: console::SgrParam::FgYellow,
+                    bold: true,
+                },
+                Disc::Empty => Glyph {
+                    colour: console::SgrParam::FgGreen,
+                    ..Glyph::EMPTY
+                },
+                Disc::Black => Glyph {
+                    text: "▐█▌",
+                    colour: console::SgrParam::FgBlack,
+                    bold: false,
+                },
+                Disc::White => Glyph {
+                    text: "▐█▌",
+                    colour: console::SgrParam::FgWhite,
+                    bold: true,
+                },
+            }
+        });
+        let counts = self.board.count();
+        let mut line = Line::new();
+        let _ = write!(
+            line,
+            "{} {:>2}     {} {:>2}",
+            SIDES[0], counts[0], SIDES[1], counts[1]
+        );
+        show_status(stdout, grid.bottom(), line.as_str());
+        let mut line = Line::new();
+        if choosing {
+            let _ = write!(
+                line,
+                "{} to play. Use the arrows, then Enter on a yellow dot.",
+                SIDES[turn.side()]
+            );
+        } else if self.board.has_move(turn) {
+            let _ = line.write_str("Thinking...");
+        }
+        show_status(stdout, grid.bottom() + 1, line.as_str());
+    }
+
+    /// Pick the best move for the computer
+    fn best_move(&self, disc: Disc, difficulty: Difficulty) -> (usize, usize) {
+        let depth = match difficulty {
+            Difficulty::Easy => 0,
+            Difficulty::Normal => 2,
+            Difficulty::Hard => 4,
+        };
+        let mut best = (i32::MIN, (0, 0));
+        for (col, row) in moves_in_order() {
+            if !self.board.is_legal(col, row, disc) {
+                continue;
+            }
+            let mut board = self.board;
+            board.play(col, row, disc);
+            let score = if difficulty == Difficulty::Easy {
+                // Just grab as many discs as we can
+                board.count()[disc.side()] as i32
+            } else {
+                -negamax(&board, disc.other(), depth, -i32::MAX, i32::MAX)
+            };
+            if score > best.0 {
+                best = (score, (col, row));
+            }
+        }
+        best.1
+    }
+}
+
+impl Default for Reversi {
+    fn default() -> Self {
+        Reversi::new()
+    }
+}
+
+/// Score the board from the point of view of `disc`, who is about to move,
+/// looking `depth` moves ahead
+fn negamax(board: &Board, disc: Disc, depth: u8, mut alpha: i32, beta: i32) -> i32 {
+    if depth == 0 {
+        return board.evaluate(disc);
+    }
+    let mut best = None;
+    for (col, row) in moves_in_order() {
+        if !board.is_legal(col, row, disc) {
+            continue;
+        }
+        let mut next = *board;
+        next.play(col, row, disc);
+        let score = -negamax(&next, disc.other(), depth - 1, -beta, -alpha);
+        best = Some(best.map_or(score, |best: i32| best.max(score)));
+        alpha = alpha.max(score);
+        if alpha >= beta {
+            break;
+        }
+    }
+    match best {
+        Some(score) => score,
+        // We have to pass. If they have to as well, the game is over.
+        None if board.has_move(disc.other()) => {
+            -negamax(board, disc.other(), depth - 1, -beta, -alpha)
+        }
+        None => board.final_score(disc),
+    }
+}
+
+/// Every square, best first. Trying the best moves first lets us skip more
+/// of the search.
+fn moves_in_order() -> impl Iterator<Item = (usize, usize)> {
+    (0..=4).flat_map(|rank| {
+        (0..SIZE * SIZE)
+            .map(|idx| (idx % SIZE, idx / SIZE))
+            .filter(move |&(col, row)| square_rank(WEIGHTS[row][col]) == rank)
+    })
+}
+
+/// Group the squares by how good they are, best first
+fn square_rank(weight: i32) -> usize {
+    match weight {
+        100 => 0,
+        10 | 5 => 1,
+        0 | 1 => 2,
+        -2 => 3,
+        _ => 4,
+    }
+}
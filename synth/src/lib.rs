@@ -0,0 +1,276 @@
+//! Application logic for the music keyboard
+
+#![no_std]
+#![deny(missing_docs)]
+#![deny(unsafe_code)]
+
+use core::fmt::Write;
+
+use neotron_input::{KeyEvent, Keyboard};
+use neotron_sdk::console;
+
+pub mod voice;
+
+use voice::Synth;
+
+/// The computer keys we play, and the note each one plays.
+///
+/// The bottom row of letters is the lower octave, with the black notes on
+/// the row above, like a piano. The top row of letters is the upper octave,
+/// with its black notes on the number keys.
+const KEYS: [(u8, u8); 30] = [
+    (b'z', 0),
+    (b's', 1),
+    (b'x', 2),
+    (b'd', 3),
+    (b'c', 4),
+    (b'v', 5),
+    (b'g', 6),
+    (b'b', 7),
+    (b'h', 8),
+    (b'n', 9),
+    (b'j', 10),
+    (b'm', 11),
+    (b',', 12),
+    (b'q', 12),
+    (b'2', 13),
+    (b'w', 14),
+    (b'3', 15),
+    (b'e', 16),
+    (b'r', 17),
+    (b'5', 18),
+    (b't', 19),
+    (b'6', 20),
+    (b'y', 21),
+    (b'7', 22),
+    (b'u', 23),
+    (b'i', 24),
+    (b'9', 25),
+    (b'o', 26),
+    (b'0', 27),
+    (b'p', 28),
+];
+
+/// The names of the notes in an octave
+const NOTE_NAMES: [&str; 12] = [
+    "C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B",
+];
+
+/// The lowest octave the bottom row can play
+const MIN_OCTAVE: u8 = 1;
+
+/// The highest octave the bottom row can play
+const MAX_OCTAVE: u8 = 6;
+
+/// The shortest a note can be held for, in milliseconds
+const MIN_HOLD_MS: u32 = 50;
+
+/// The longest a note can be held for, in milliseconds
+const MAX_HOLD_MS: u32 = 2000;
+
+/// Where the left edge of the keyboards go
+const LEFT_COL: u8 = 4;
+
+/// Where the upper keyboard goes. The black keys are on the row above.
+const UPPER_ROW: u8 = 5;
+
+/// Where the lower keyboard goes. The black keys are on the row above.
+const LOWER_ROW: u8 = 10;
+
+/// Where the settings go
+const SETTINGS_ROW: u8 = 14;
+
+/// How many audio frames we make each time round the main loop
+const FRAMES_PER_LOOP: usize = 256;
+
+/// Represents the music keyboard application
+pub struct App {
+    width: u8,
+    height: u8,
+    stdout: neotron_sdk::File,
+    keyboard: Keyboard,
+    synth: Synth,
+    /// Which octave the bottom row plays
+    octave: u8,
+    /// Which notes are lit up on the screen
+    shown: u32,
+}
+
+impl App {
+    /// Make a new music keyboard application.
+    ///
+    /// You can give the screen size in characters.
+    pub const fn new(width: u8, height: u8) -> App {
+        App {
+            width,
+            height,
+            stdout: neotron_sdk::stdout(),
+            keyboard: Keyboard::new(),
+            synth: Synth::new(),
+            octave: 3,
+            shown: 0,
+        }
+    }
+
+    /// Play the keyboard, until the user quits
+    pub fn play(&mut self) {
+        let Ok(dsp) = neotron_audio::open(voice::SAMPLE_RATE) else {
+            let _ = writeln!(self.stdout, "Could not open the audio device");
+            return;
+        };
+        console::cursor_off(&mut self.stdout);
+        self.draw_frame();
+        let mut buffer = [0u8; FRAMES_PER_LOOP * neotron_audio::convert::FRAME_LEN];
+        // Some silence to give us a head-start
+        let _ = dsp.write(&buffer);
+        loop {
+            if !self.handle_keys() {
+                break;
+            }
+            neotron_audio::convert::fill_mono(&mut buffer, || self.synth.next_sample());
+            // This waits until the audio device has room, which keeps us in
+            // time with the music.
+            let _ = dsp.write(&buffer);
+            let sounding = self.synth.sounding();
+            if sounding != self.shown {
+                self.shown = sounding;
+                self.draw_keyboards();
+            }
+        }
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        console::clear_screen(&mut self.stdout);
+        console::move_cursor(&mut self.stdout, console::Position::origin());
+        console::cursor_on(&mut self.stdout);
+    }
+
+    /// Deal with any keys that have been pressed.
+    ///
+    /// Returns `false` if the user wants to quit.
+    fn handle_keys(&mut self) -> bool {
+        let Some(key) = self.keyboard.read() else {
+            return true;
+        };
+        match key {
+            KeyEvent::Escape => return false,
+            KeyEvent::Up => {
+                self.synth.hold_ms = (self.synth.hold_ms + MIN_HOLD_MS).min(MAX_HOLD_MS);
+                self.draw_settings();
+            }
+            KeyEvent::Down => {
+                self.synth.hold_ms = (self.synth.hold_ms - MIN_HOLD_MS).max(MIN_HOLD_MS);
+                self.draw_settings();
+            }
+            KeyEvent::Right => {
+                self.octave = (self.octave + 1).min(MAX_OCTAVE);
+                self.draw_frame();
+            }
+            KeyEvent::Left => {
+                self.octave = (self.octave - 1).max(MIN_OCTAVE);
+                self.draw_frame();
+            }
+            KeyEvent::Tab => {
+                self.synth.waveform = self.synth.waveform.next();
+                self.draw_settings();
+            }
+            KeyEvent::Char(' ') => self.synth.all_off(),
+            KeyEvent::Char(ch) if ch.is_ascii() => {
+                let key = (ch as u8).to_ascii_lowercase();
+                if let Some((_, note)) = KEYS.iter().find(|(k, _)| *k == key) {
+                    self.synth.note_on(*note, self.octave);
+                }
+            }
+            _ => {
+                // Some other function key
+            }
+        }
+        true
+    }
+
+    /// Draw the whole screen
+    fn draw_frame(&mut self) {
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        console::clear_screen(&mut self.stdout);
+        self.move_to(1, LEFT_COL);
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Bold]);
+        let _ = self.stdout.write_str("Neotron Mini Synth");
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        self.draw_keyboards();
+        self.draw_settings();
+        self.move_to(self.height - 2, 0);
+        let _ = writeln!(
+            self.stdout,
+            "Play with the letter and number keys.  Tab: waveform  Space: silence"
+        );
+        let _ = write!(
+            self.stdout,
+            "Left/Right: octave  Up/Down: note length  Esc: quit"
+        );
+    }
+
+    /// Draw both keyboards, lighting up the notes which are playing
+    fn draw_keyboards(&mut self) {
+        for (idx, (key, note)) in KEYS.iter().enumerate() {
+            // The comma and the Q play the same note, on different keyboards
+            let (row, first) = if idx < 13 {
+                (LOWER_ROW, 0)
+            } else {
+                (UPPER_ROW, 12)
+            };
+            let whites = (first..*note).filter(|n| !is_black(*n)).count() as u8;
+            let lit = self.shown & 1 << note != 0;
+            let (row, col, colours) = if is_black(*note) {
+                let colours = if lit {
+                    [console::SgrParam::FgBlack, console::SgrParam::BgYellow]
+                } else {
+                    [console::SgrParam::FgWhite, console::SgrParam::BgBlue]
+                };
+                (row - 1, LEFT_COL + whites * 4 - 2, colours)
+            } else {
+                let colours = if lit {
+                    [console::SgrParam::FgBlack, console::SgrParam::BgYellow]
+                } else {
+                    [console::SgrParam::FgBlack, console::SgrParam::BgWhite]
+                };
+                (row, LEFT_COL + whites * 4, colours)
+            };
+            self.move_to(row, col);
+            console::set_sgr(&mut self.stdout, colours);
+            let _ = write!(self.stdout, " {} ", key.to_ascii_uppercase() as char);
+            console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+            if !is_black(*note) {
+                // Name the note underneath
+                self.move_to(row + 1, col);
+                let octave = self.octave + note / 12;
+                let _ = write!(
+                    self.stdout,
+                    "{}{} ",
+                    NOTE_NAMES[usize::from(note % 12)],
+                    octave
+                );
+            }
+        }
+    }
+
+    /// Draw the current settings
+    fn draw_settings(&mut self) {
+        self.move_to(SETTINGS_ROW, LEFT_COL);
+        let _ = write!(
+            self.stdout,
+            "Waveform: {:<8}   Octave: {}   Note length: {:4} ms",
+            self.synth.waveform.name(),
+            self.octave,
+            self.synth.hold_ms
+        );
+    }
+
+    /// Move the cursor
+    fn move_to(&mut self, row: u8, col: u8) {
+        let col = col.min(self.width - 1);
+        console::move_cursor(&mut self.stdout, console::Position { row, col });
+    }
+}
+
+/// Is this one of the black notes on a piano?
+fn is_black(note: u8) -> bool {
+    matches!(note % 12, 1 | 3 | 6 | 8 | 10)
+}
@@ -0,0 +1,150 @@
+//! The sound generator.
+//!
+//! We have a handful of voices, each playing one note through an oscillator
+//! and an envelope. The console only tells us when a key goes down, not when
+//! it comes back up, so every note is held for a fixed time and then released.
+
+use neotron_audio::{Adsr, Envelope, Oscillator, Waveform};
+
+/// The sample rate we ask the audio device for
+pub const SAMPLE_RATE: u32 = neotron_audio::SAMPLE_RATE;
+
+/// How many notes can sound at once
+const NUM_VOICES: usize = 6;
+
+/// The oscillator step for each note in octave 3, from C3 up to B3.
+///
+/// The phase accumulator is 32 bits wide, so the step is
+/// `frequency * 2^32 / SAMPLE_RATE`.
+const STEPS: [u32; 12] = [
+    12739788, 13497495, 14300001, 15150229, 16051101, 17005538, 18017437, 19088744, 20223355,
+    21426141, 22700022, 24049869,
+];
+
+/// How every note rises and falls: quick to start, then settling to a
+/// steady level, then a slow fade
+const ADSR: Adsr = Adsr {
+    attack: SAMPLE_RATE / 200,
+    decay: SAMPLE_RATE / 10,
+    sustain: 160,
+    release: SAMPLE_RATE / 4,
+};
+
+/// One note being played
+#[derive(Debug, Copy, Clone)]
+struct Voice {
+    /// Which note, counting semitones up from the bottom of the keyboard
+    note: u8,
+    oscillator: Oscillator,
+    envelope: Envelope,
+    /// How many samples since the note started
+    age: u32,
+    /// How many samples we hold the note for before releasing it
+    hold: u32,
+}
+
+/// Plays notes
+pub struct Synth {
+    voices: [Option<Voice>; NUM_VOICES],
+    /// What new notes sound like
+    pub waveform: Waveform,
+    /// How long new notes are held for, in milliseconds
+    pub hold_ms: u32,
+}
+
+impl Synth {
+    /// Make a silent synthesiser
+    pub const fn new() -> Synth {
+        Synth {
+            voices: [None; NUM_VOICES],
+            waveform: Waveform::Square,
+            hold_ms: 300,
+        }
+    }
+
+    /// Start playing a note.
+    ///
+    /// The note counts in semitones from C, in the given octave. If every
+    /// voice is busy, the oldest note makes way.
+    pub fn note_on(&mut self, note: u8, octave: u8) {
+        let semitones = u32::from(note) + u32::from(octave) * 12;
+        let base = STEPS[(semitones % 12) as usize];
+        let octaves_up = semitones / 12;
+        let step = if octaves_up >= 3 {
+            base << (octaves_up - 3)
+        } else {
+            base >> (3 - octaves_up)
+        };
+        let mut oscillator = Oscillator::new(self.waveform);
+        oscillator.set_step(step);
+        let mut envelope = Envelope::new(ADSR);
+        envelope.trigger();
+        let voice = Voice {
+            note,
+            oscillator,
+            envelope,
+            age: 0,
+            hold: (ADSR.attack + ADSR.decay) + self.hold_ms * SAMPLE_RATE / 1000,
+        };
+        // Play the same note again, or use a free voice, or the oldest one
+        let slot = self
+            .voices
+            .iter()
+            .position(|v| v.map(|v| v.note) == Some(note))
+            .or_else(|| self.voices.iter().position(|v| v.is_none()))
+            .unwrap_or_else(|| {
+                let mut oldest = 0;
+                for (idx, v) in self.voices.iter().enumerate() {
+                    if let (Some(v), Some(old)) = (v, self.voices[oldest]) {
+                        if v.age > old.age {
+                            oldest = idx;
+                        }
+                    }
+                }
+                oldest
+            });
+        self.voices[slot] = Some(voice);
+    }
+
+    /// Stop all the notes, straight away
+    pub fn all_off(&mut self) {
+        self.voices = [None; NUM_VOICES];
+    }
+
+    /// Which notes are playing, as a bit-mask of the note numbers given to
+    /// [`Synth::note_on`]
+    pub fn sounding(&self) -> u32 {
+        self.voices
+            .iter()
+            .flatten()
+            .fold(0, |mask, voice| mask | 1 << voice.note)
+    }
+
+    /// Work out the next (mono) sample
+    pub fn next_sample(&mut self) -> i16 {
+        let mut total = 0;
+        for slot in self.voices.iter_mut() {
+            let Some(voice) = slot else {
+                continue;
+            };
+            if voice.age == voice.hold {
+                voice.envelope.release();
+            }
+            voice.age += 1;
+            let wave = voice.envelope.apply(voice.oscillator.next_sample());
+            if !voice.envelope.is_active() {
+                *slot = None;
+                continue;
+            }
+            // Leave room for all the voices at once
+            total += i32::from(wave) / NUM_VOICES as i32;
+        }
+        neotron_audio::clip(total)
+    }
+}
+
+impl Default for Synth {
+    fn default() -> Self {
+        Synth::new()
+    }
+}
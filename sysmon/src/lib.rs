@@ -0,0 +1,212 @@
+//! Application logic for the system monitor
+
+#![no_std]
+#![deny(missing_docs)]
+#![deny(unsafe_code)]
+
+use core::fmt::Write;
+
+use neotron_input::{KeyEvent, Keyboard};
+use neotron_sdk::console;
+use neotron_tui::Line;
+
+pub mod probe;
+
+use probe::{Device, Drive};
+
+/// How long we wait each time round the main loop
+const TICK_MS: u32 = 50;
+
+/// How many ticks make a second
+const TICKS_PER_SECOND: u32 = 1000 / TICK_MS;
+
+/// Where the values go, on each line of the dashboard
+const VALUE_COL: u8 = 18;
+
+/// Where the memory section goes
+const MEMORY_ROW: u8 = 2;
+
+/// Where the time section goes
+const TIME_ROW: u8 = 6;
+
+/// Where the devices section goes
+const DEVICE_ROW: u8 = 10;
+
+/// Where the drives section goes
+const DRIVE_ROW: u8 = DEVICE_ROW + Device::ALL.len() as u8 + 2;
+
+/// Represents the system monitor application
+pub struct App {
+    width: u8,
+    height: u8,
+    stdout: neotron_sdk::File,
+    keyboard: Keyboard,
+    /// What we found on each drive, the last time we looked
+    drives: [Option<Drive>; probe::NUM_DRIVES],
+    /// How long we have been running, in seconds
+    running: u32,
+}
+
+impl App {
+    /// Make a new system monitor.
+    ///
+    /// You can give the screen size in characters.
+    pub const fn new(width: u8, height: u8) -> App {
+        App {
+            width,
+            height,
+            stdout: neotron_sdk::stdout(),
+            keyboard: Keyboard::new(),
+            drives: [None; probe::NUM_DRIVES],
+            running: 0,
+        }
+    }
+
+    /// Show the dashboard, until the user quits
+    pub fn play(&mut self) {
+        console::cursor_off(&mut self.stdout);
+        self.scan_drives();
+        self.draw_frame();
+        let mut ticks = 0;
+        'outer: loop {
+            self.draw_values();
+            // Wait a second, but keep an eye on the keyboard
+            while ticks < TICKS_PER_SECOND {
+                if let Some(key) = self.keyboard.read() {
+                    match key {
+                        KeyEvent::Char('q' | 'Q') | KeyEvent::Escape => break 'outer,
+                        KeyEvent::Char('r' | 'R') => {
+                            self.scan_drives();
+                            self.draw_frame();
+                            self.draw_values();
+                        }
+                        _ => {}
+                    }
+                }
+                neotron_sdk::delay(core::time::Duration::from_millis(u64::from(TICK_MS)));
+                ticks += 1;
+            }
+            ticks = 0;
+            self.running += 1;
+        }
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        console::clear_screen(&mut self.stdout);
+        console::move_cursor(&mut self.stdout, console::Position::origin());
+        console::cursor_on(&mut self.stdout);
+    }
+
+    /// Look at all the drives again. This can be slow, so we only do it
+    /// when asked.
+    fn scan_drives(&mut self) {
+        self.move_to(self.height - 1, 0);
+        let _ = self.stdout.write_str("Scanning drives...");
+        for (index, drive) in self.drives.iter_mut().enumerate() {
+            *drive = probe::scan_drive(index);
+        }
+    }
+
+    /// Draw everything which doesn't change every second
+    fn draw_frame(&mut self) {
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        console::clear_screen(&mut self.stdout);
+
+        // Title bar
+        self.move_to(0, 0);
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reverse]);
+        let title = " Neotron System Monitor";
+        let _ = write!(
+            self.stdout,
+            "{:<width$}",
+            title,
+            width = usize::from(self.width)
+        );
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+
+        self.heading(MEMORY_ROW, "Memory");
+        self.label(MEMORY_ROW + 1, "Free");
+        let _ = self
+            .stdout
+            .write_str("not reported - the OS has no call for this yet");
+
+        self.heading(TIME_ROW, "Time");
+        self.label(TIME_ROW + 1, "System uptime");
+        let _ = self
+            .stdout
+            .write_str("not reported - the OS has no call for this yet");
+        self.label(TIME_ROW + 2, "Monitor running");
+
+        self.heading(DEVICE_ROW, "Devices");
+        for (index, device) in Device::ALL.iter().enumerate() {
+            self.label(DEVICE_ROW + 1 + index as u8, device.path());
+        }
+
+        self.heading(DRIVE_ROW, "Drives (root directory only)");
+        for index in 0..probe::NUM_DRIVES {
+            self.label(DRIVE_ROW + 1 + index as u8, probe::drive_name(index));
+            match self.drives[index] {
+                Some(drive) => {
+                    let _ = write!(
+                        self.stdout,
+                        "{} files, {} directories, {} KiB used",
+                        drive.files,
+                        drive.directories,
+                        drive.bytes.div_ceil(1024)
+                    );
+                }
+                None => {
+                    let _ = self.stdout.write_str("not found");
+                }
+            }
+        }
+
+        self.move_to(self.height - 1, 0);
+        let _ = self
+            .stdout
+            .write_str("R: rescan drives  Q: quit                  ");
+    }
+
+    /// Draw everything which we refresh every second
+    fn draw_values(&mut self) {
+        self.move_to(TIME_ROW + 2, VALUE_COL);
+        let _ = write!(
+            self.stdout,
+            "{:02}:{:02}:{:02}",
+            self.running / 3600,
+            self.running / 60 % 60,
+            self.running % 60
+        );
+
+        let space = usize::from(self.width.saturating_sub(VALUE_COL + 1));
+        for (index, device) in Device::ALL.iter().enumerate() {
+            let mut text = Line::new();
+            if !device.describe(&mut text) {
+                let _ = text.write_str("not found");
+            }
+            self.move_to(DEVICE_ROW + 1 + index as u8, VALUE_COL);
+            let _ = write!(self.stdout, "{:<width$}", text.as_str(), width = space);
+        }
+    }
+
+    /// Draw a section heading
+    fn heading(&mut self, row: u8, text: &str) {
+        self.move_to(row, 1);
+        console::set_sgr(
+            &mut self.stdout,
+            [console::SgrParam::Bold, console::SgrParam::FgYellow],
+        );
+        let _ = self.stdout.write_str(text);
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+    }
+
+    /// Draw the label for a line, and leave the cursor where the value goes
+    fn label(&mut self, row: u8, text: &str) {
+        self.move_to(row, 3);
+        let _ = self.stdout.write_str(text);
+        self.move_to(row, VALUE_COL);
+    }
+
+    /// Move the cursor
+    fn move_to(&mut self, row: u8, col: u8) {
+        console::move_cursor(&mut self.stdout, console::Position { row, col });
+    }
+}
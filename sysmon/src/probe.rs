@@ -0,0 +1,149 @@
+//! Finding out what the system has got.
+//!
+//! The SDK has no call that lists the devices, so we try opening each device
+//! we know about, and ask the ones we find to describe themselves.
+
+use core::fmt::Write;
+
+/// The ioctl which reports how a device is set up. Both the audio and video
+/// devices support it.
+const IOCTL_GET_CONFIG: u64 = 0;
+
+/// The drives we look for
+const DRIVES: [&str; 4] = ["0:/", "1:/", "2:/", "3:/"];
+
+/// How many drives we look for
+pub const NUM_DRIVES: usize = DRIVES.len();
+
+/// The devices we know how to ask about
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Device {
+    /// The sound output
+    Audio,
+    /// The framebuffer
+    Video,
+    /// The battery-backed real-time clock
+    Clock,
+}
+
+impl Device {
+    /// All the devices, in the order we show them
+    pub const ALL: [Device; 3] = [Device::Audio, Device::Video, Device::Clock];
+
+    /// The name of the device file
+    pub fn path(self) -> &'static str {
+        match self {
+            Device::Audio => "AUDIO:",
+            Device::Video => "VIDEO:",
+            Device::Clock => "RTC:",
+        }
+    }
+
+    /// See if this device is there, and write a description of it.
+    ///
+    /// Returns `false` if we couldn't open the device.
+    pub fn describe<W>(self, out: &mut W) -> bool
+    where
+        W: Write,
+    {
+        let Ok(path) = neotron_sdk::path::Path::new(self.path()) else {
+            return false;
+        };
+        let Ok(file) = neotron_sdk::File::open(path, neotron_sdk::Flags::empty()) else {
+            return false;
+        };
+        match self {
+            Device::Audio => match file.ioctl(IOCTL_GET_CONFIG, 0) {
+                // The format is in the top four bits, and the rate at the bottom
+                Ok(config) => {
+                    let format = match config >> 60 {
+                        0 => "8-bit mono",
+                        1 => "8-bit stereo",
+                        2 => "16-bit mono",
+                        _ => "16-bit stereo",
+                    };
+                    let _ = write!(out, "{} Hz, {}", config as u32, format);
+                }
+                Err(_) => {
+                    let _ = out.write_str("present");
+                }
+            },
+            Device::Video => match file.ioctl(IOCTL_GET_CONFIG, 0) {
+                // Packed as `width | height << 16 | bits_per_pixel << 32`
+                Ok(mode) => {
+                    let width = mode as u16;
+                    let height = (mode >> 16) as u16;
+                    match (mode >> 32) as u8 {
+                        0 => {
+                            let _ = write!(out, "{}x{} text", width, height);
+                        }
+                        bpp => {
+                            let _ = write!(out, "{}x{}, {} colours", width, height, 1u32 << bpp);
+                        }
+                    }
+                }
+                Err(_) => {
+                    let _ = out.write_str("present");
+                }
+            },
+            Device::Clock => {
+                let mut buffer = [0u8; 32];
+                let text = file
+                    .read(&mut buffer)
+                    .ok()
+                    .and_then(|n| core::str::from_utf8(&buffer[0..n]).ok());
+                match text {
+                    Some(text) => {
+                        let _ = out.write_str(text.trim());
+                    }
+                    None => {
+                        let _ = out.write_str("present, but unreadable");
+                    }
+                }
+            }
+        }
+        true
+    }
+}
+
+/// What we found on a drive
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct Drive {
+    /// How many files are in the root directory
+    pub files: u32,
+    /// How many directories are in the root directory
+    pub directories: u32,
+    /// How big the files in the root directory are, added up
+    pub bytes: u64,
+}
+
+/// Get the name of a drive, like `0:`
+pub fn drive_name(index: usize) -> &'static str {
+    DRIVES[index].trim_end_matches('/')
+}
+
+/// Look at the root directory of a drive, if it is there.
+///
+/// We don't go into sub-directories, because that can take a long time on a
+/// big SD card.
+pub fn scan_drive(index: usize) -> Option<Drive> {
+    let path = neotron_sdk::path::Path::new(DRIVES[index]).ok()?;
+    let dir = neotron_sdk::ReadDir::open(path).ok()?;
+    let mut drive = Drive::default();
+    for entry in dir {
+        let Ok(entry) = entry else {
+            break;
+        };
+        if entry
+            .properties
+            .attr
+            .contains(neotron_api::file::Attributes::DIRECTORY)
+        {
+            drive.directories += 1;
+        } else {
+            drive.files += 1;
+            drive.bytes += entry.properties.file_size;
+        }
+    }
+    Some(drive)
+}
@@ -0,0 +1,246 @@
+//! The header block that comes before each file in an archive.
+//!
+//! We use the POSIX "ustar" layout, so archives can be made or opened with
+//! `tar` on a PC too. Numbers are written in octal, as text.
+
+/// How big the header is, and what the file data is padded out to
+pub const BLOCK_LEN: usize = 512;
+
+/// The longest name a header holds, which is the 155 byte prefix, a `/`
+/// and the 100 byte name
+pub const MAX_NAME: usize = 256;
+
+/// Where each field starts, and how long it is
+const NAME: (usize, usize) = (0, 100);
+const MODE: (usize, usize) = (100, 8);
+const UID: (usize, usize) = (108, 8);
+const GID: (usize, usize) = (116, 8);
+const SIZE: (usize, usize) = (124, 12);
+const MTIME: (usize, usize) = (136, 12);
+const CHECKSUM: (usize, usize) = (148, 8);
+const TYPE: usize = 156;
+const MAGIC: (usize, usize) = (257, 8);
+const PREFIX: (usize, usize) = (345, 155);
+
+/// What an entry is
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Kind {
+    /// An ordinary file
+    File,
+    /// A directory, which has no data
+    Dir,
+    /// Something we don't make, like a link. Its data is skipped.
+    Other,
+}
+
+/// A block that isn't a header, or has a bad checksum
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct BadHeader;
+
+/// What a header says
+#[derive(Debug, Clone)]
+pub struct Header {
+    name: [u8; MAX_NAME],
+    name_len: usize,
+    size: u64,
+    kind: Kind,
+}
+
+impl Header {
+    /// A header for a file, or `None` if the name won't fit
+    pub fn file(name: &str, size: u64) -> Option<Header> {
+        Self::new(name, size, Kind::File)
+    }
+
+    /// A header for a directory, whose name should end with a `/`, or
+    /// `None` if the name won't fit
+    pub fn dir(name: &str) -> Option<Header> {
+        Self::new(name, 0, Kind::Dir)
+    }
+
+    fn new(name: &str, size: u64, kind: Kind) -> Option<Header> {
+        if name.len() > MAX_NAME {
+            return None;
+        }
+        let mut header = Header {
+            name: [0; MAX_NAME],
+            name_len: name.len(),
+            size,
+            kind,
+        };
+        header.name[0..name.len()].copy_from_slice(name.as_bytes());
+        // Check now that it splits, so `to_block` can't fail
+        header.split_name()?;
+        Some(header)
+    }
+
+    /// Read a header. An empty block, which is how an archive ends, gives
+    /// `None`.
+    pub fn parse(block: &[u8; BLOCK_LEN]) -> Result<Option<Header>, BadHeader> {
+        if block.iter().all(|b| *b == 0) {
+            return Ok(None);
+        }
+        let expected = parse_octal(field(block, CHECKSUM)).ok_or(BadHeader)?;
+        // Some old versions of tar summed signed bytes
+        let (unsigned, signed) = checksum(block);
+        if expected != unsigned && expected as i64 != signed {
+            return Err(BadHeader);
+        }
+        let mut header = Header {
+            name: [0; MAX_NAME],
+            name_len: 0,
+            size: parse_octal(field(block, SIZE)).ok_or(BadHeader)?,
+            kind: match block[TYPE] {
+                b'0' | 0 | b'7' => Kind::File,
+                b'5' => Kind::Dir,
+                _ => Kind::Other,
+            },
+        };
+        // Only ustar archives have a prefix
+        if field(block, MAGIC).starts_with(b"ustar") {
+            header.push_name(trim_nul(field(block, PREFIX)));
+            if header.name_len > 0 {
+                header.push_name(b"/");
+            }
+        }
+        header.push_name(trim_nul(field(block, NAME)));
+        // Old archives mark directories with just a `/`
+        if header.kind == Kind::File && header.name().ends_with('/') {
+            header.kind = Kind::Dir;
+        }
+        if header.name_len == 0 {
+            return Err(BadHeader);
+        }
+        Ok(Some(header))
+    }
+
+    /// Write the header out as a block
+    pub fn to_block(&self) -> [u8; BLOCK_LEN] {
+        let mut block = [0u8; BLOCK_LEN];
+        let (prefix, name) = self.split_name().unwrap_or((&[], &[]));
+        block[PREFIX.0..PREFIX.0 + prefix.len()].copy_from_slice(prefix);
+        block[NAME.0..NAME.0 + name.len()].copy_from_slice(name);
+        let (mode, kind) = match self.kind {
+            Kind::Dir => (0o755, b'5'),
+            _ => (0o644, b'0'),
+        };
+        write_octal(field_mut(&mut block, MODE), mode);
+        write_octal(field_mut(&mut block, UID), 0);
+        write_octal(field_mut(&mut block, GID), 0);
+        write_octal(field_mut(&mut block, SIZE), self.size);
+        // We've no clock to ask, so files are dated 1970
+        write_octal(field_mut(&mut block, MTIME), 0);
+        block[TYPE] = kind;
+        field_mut(&mut block, MAGIC).copy_from_slice(b"ustar\x0000");
+        let (sum, _) = checksum(&block);
+        // Six digits, a nul and a space, which is how tar writes it
+        let sum_field = field_mut(&mut block, CHECKSUM);
+        write_octal(&mut sum_field[0..7], sum);
+        sum_field[7] = b' ';
+        block
+    }
+
+    /// The name, like `MUSIC/SONG.MOD`, or `MUSIC/` for a directory
+    pub fn name(&self) -> &str {
+        // Names from other machines might not be UTF-8
+        match core::str::from_utf8(&self.name[0..self.name_len]) {
+            Ok(name) => name,
+            Err(e) => core::str::from_utf8(&self.name[0..e.valid_up_to()]).unwrap_or_default(),
+        }
+    }
+
+    /// How many bytes of data follow the header
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// What the entry is
+    pub fn kind(&self) -> Kind {
+        self.kind
+    }
+
+    /// How many blocks the data takes up, after padding
+    pub fn data_blocks(&self) -> u64 {
+        self.size.div_ceil(BLOCK_LEN as u64)
+    }
+
+    /// Add some bytes to the name, dropping any that don't fit
+    fn push_name(&mut self, bytes: &[u8]) {
+        let n = bytes.len().min(MAX_NAME - self.name_len);
+        self.name[self.name_len..self.name_len + n].copy_from_slice(&bytes[0..n]);
+        self.name_len += n;
+    }
+
+    /// Split the name into the prefix and name fields. A long name has to
+    /// be split at a `/`.
+    fn split_name(&self) -> Option<(&[u8], &[u8])> {
+        let name = &self.name[0..self.name_len];
+        if name.len() <= NAME.1 {
+            return Some((&[], name));
+        }
+        // Don't split at the `/` on the end of a directory
+        let last = name.len() - 1;
+        name[0..last]
+            .iter()
+            .enumerate()
+            .filter(|(idx, b)| **b == b'/' && *idx <= PREFIX.1 && last - idx <= NAME.1)
+            .map(|(idx, _)| (&name[0..idx], &name[idx + 1..]))
+            .next()
+    }
+}
+
+/// Get a field from a block
+fn field(block: &[u8; BLOCK_LEN], (start, len): (usize, usize)) -> &[u8] {
+    &block[start..start + len]
+}
+
+/// Get a field from a block, to write to
+fn field_mut(block: &mut [u8; BLOCK_LEN], (start, len): (usize, usize)) -> &mut [u8] {
+    &mut block[start..start + len]
+}
+
+/// Everything up to the first nul
+fn trim_nul(bytes: &[u8]) -> &[u8] {
+    let len = bytes.iter().position(|b| *b == 0).unwrap_or(bytes.len());
+    &bytes[0..len]
+}
+
+/// Sum the bytes of a header, counting the checksum field as spaces, both
+/// unsigned and signed
+fn checksum(block: &[u8; BLOCK_LEN]) -> (u64, i64) {
+    let mut unsigned = 0u64;
+    let mut signed = 0i64;
+    for (idx, &byte) in block.iter().enumerate() {
+        let byte = if (CHECKSUM.0..CHECKSUM.0 + CHECKSUM.1).contains(&idx) {
+            b' '
+        } else {
+            byte
+        };
+        unsigned += u64::from(byte);
+        signed += i64::from(byte as i8);
+    }
+    (unsigned, signed)
+}
+
+/// Fill a field with a number in octal, with leading zeros and a nul on the
+/// end
+fn write_octal(field: &mut [u8], mut value: u64) {
+    let Some((nul, digits)) = field.split_last_mut() else {
+        return;
+    };
+    *nul = 0;
+    for digit in digits.iter_mut().rev() {
+        *digit = b'0' + (value & 7) as u8;
+        value >>= 3;
+    }
+}
+
+/// Read a number in octal, which might have spaces around it and a nul on
+/// the end
+fn parse_octal(field: &[u8]) -> Option<u64> {
+    let text = core::str::from_utf8(trim_nul(field)).ok()?.trim();
+    if text.is_empty() {
+        return Some(0);
+    }
+    u64::from_str_radix(text, 8).ok()
+}
@@ -0,0 +1,322 @@
+//! Application logic for the archiver
+//!
+//! An archive is a `tar` file, with no compression. Each file is a header
+//! block giving its path and size, then its data padded out to a whole
+//! block, and two empty blocks mark the end. Paths are kept relative to the
+//! directory that was packed, so an archive can be unpacked anywhere:
+//!
+//! ```text
+//! GAMES/
+//! GAMES/SNAKE.ELF
+//! GAMES/SAVES/
+//! GAMES/SAVES/SNAKE.SAV
+//! ```
+
+#![no_std]
+#![deny(missing_docs)]
+#![deny(unsafe_code)]
+
+use core::fmt::Write;
+
+use neotron_fs::PathBuf;
+
+pub mod header;
+
+use header::{Header, Kind, BLOCK_LEN};
+
+/// The ways packing and unpacking go wrong
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// The OS couldn't do something with the archive
+    Io(neotron_sdk::Error),
+    /// The archive has something in it that isn't a header, or it stops
+    /// part-way through
+    BadArchive,
+}
+
+impl From<neotron_sdk::Error> for Error {
+    fn from(e: neotron_sdk::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+/// What we packed or unpacked
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct Summary {
+    /// How many files
+    pub files: u32,
+    /// How many directories
+    pub dirs: u32,
+    /// How many bytes of file data
+    pub bytes: u64,
+    /// How many files or directories we had to leave out
+    pub failed: u32,
+}
+
+/// Pack `root`, and everything under it, into a new archive. Each path
+/// goes on `out` as we pack it.
+///
+/// If the archive is inside `root`, it's left out.
+pub fn create<W: Write>(archive: &str, root: &str, out: &mut W) -> Result<Summary, Error> {
+    let mut walk = neotron_fs::Walk::new(root)?;
+    // The walk puts a `/` on the end of the root, and we don't keep it
+    let base_len = match root {
+        "" => 0,
+        _ if root.ends_with('/') => root.len(),
+        _ => root.len() + 1,
+    };
+    let path = neotron_sdk::path::Path::new(archive)?;
+    let file = neotron_sdk::File::open(
+        path,
+        neotron_sdk::Flags::WRITE | neotron_sdk::Flags::CREATE | neotron_sdk::Flags::TRUNCATE,
+    )?;
+    let mut summary = Summary::default();
+    while let Some(entry) = walk.next() {
+        let entry = entry?;
+        if walk.path().eq_ignore_ascii_case(archive) {
+            continue;
+        }
+        let name = walk.path().get(base_len..).unwrap_or_default();
+        if entry.is_dir() {
+            let mut dir_name = PathBuf::from_path(name)?;
+            dir_name.push_str("/")?;
+            let Some(header) = Header::dir(dir_name.as_str()) else {
+                summary.failed += 1;
+                let _ = writeln!(out, "{}: name too long", dir_name.as_str());
+                neotron_fs::Walk::skip(&mut walk);
+                continue;
+            };
+            file.write(&header.to_block())?;
+            summary.dirs += 1;
+            let _ = writeln!(out, "{}", dir_name.as_str());
+            continue;
+        }
+        let source = match open(walk.path()) {
+            Ok(source) => source,
+            Err(e) => {
+                summary.failed += 1;
+                let _ = writeln!(out, "{}: can't read ({:?})", name, e);
+                continue;
+            }
+        };
+        let Some(header) = Header::file(name, entry.size()) else {
+            summary.failed += 1;
+            let _ = writeln!(out, "{}: name too long", name);
+            continue;
+        };
+        file.write(&header.to_block())?;
+        let copied = copy_in(&source, &file, entry.size())?;
+        summary.files += 1;
+        summary.bytes += entry.size();
+        if copied == entry.size() {
+            let _ = writeln!(out, "{}", name);
+        } else {
+            // The header's already written, so it keeps the size it says
+            // and we padded it with zeros
+            summary.failed += 1;
+            let _ = writeln!(
+                out,
+                "{}: only read {} bytes of {}",
+                name,
+                copied,
+                entry.size()
+            );
+        }
+    }
+    // Two empty blocks mark the end
+    let end = [0u8; BLOCK_LEN];
+    file.write(&end)?;
+    file.write(&end)?;
+    Ok(summary)
+}
+
+/// Show what's in an archive on `out`, with the size of each file
+pub fn list<W: Write>(archive: &str, out: &mut W) -> Result<Summary, Error> {
+    let file = open(archive)?;
+    let mut summary = Summary::default();
+    while let Some(header) = next_header(&file)? {
+        match header.kind() {
+            Kind::File => {
+                summary.files += 1;
+                summary.bytes += header.size();
+            }
+            Kind::Dir => summary.dirs += 1,
+            Kind::Other => summary.failed += 1,
+        }
+        let _ = writeln!(out, "{:>10} {}", header.size(), header.name());
+        skip_data(&file, &header)?;
+    }
+    Ok(summary)
+}
+
+/// Unpack an archive into `dest`. Each path goes on `out` as we unpack it.
+///
+/// The OS can't make directories yet, so any the archive has must be there
+/// already. Files in ones that aren't are left out. If `flat` is set, we
+/// ignore the directories and put every file straight into `dest`.
+pub fn extract<W: Write>(
+    archive: &str,
+    dest: &str,
+    flat: bool,
+    out: &mut W,
+) -> Result<Summary, Error> {
+    let file = open(archive)?;
+    let mut summary = Summary::default();
+    while let Some(header) = next_header(&file)? {
+        // Archives made on a PC often start every path with `./`, and have
+        // an entry for `./` itself
+        let name = header.name().trim_start_matches("./");
+        if name.is_empty() {
+            skip_data(&file, &header)?;
+            continue;
+        }
+        if !is_safe(name) {
+            summary.failed += 1;
+            let _ = writeln!(out, "{}: skipped, as it's outside the archive", name);
+            skip_data(&file, &header)?;
+            continue;
+        }
+        match header.kind() {
+            Kind::Dir if flat => {}
+            Kind::Dir => {
+                let target = join(dest, name.trim_end_matches('/'))?;
+                // If we can open it as a directory, it's there
+                let found = neotron_sdk::path::Path::new(target.as_str())
+                    .and_then(neotron_sdk::ReadDir::open)
+                    .is_ok();
+                if found {
+                    summary.dirs += 1;
+                    let _ = writeln!(out, "{}", name);
+                } else {
+                    summary.failed += 1;
+                    let _ = writeln!(out, "{}: isn't there, so make it first", name);
+                }
+            }
+            Kind::Other => {
+                summary.failed += 1;
+                let _ = writeln!(out, "{}: skipped, as it isn't a file", name);
+                skip_data(&file, &header)?;
+            }
+            Kind::File => {
+                let target = if flat {
+                    join(dest, neotron_fs::split(name).1)?
+                } else {
+                    join(dest, name)?
+                };
+                let path = neotron_sdk::path::Path::new(target.as_str())?;
+                let opened = neotron_sdk::File::open(
+                    path,
+                    neotron_sdk::Flags::WRITE
+                        | neotron_sdk::Flags::CREATE
+                        | neotron_sdk::Flags::TRUNCATE,
+                );
+                match opened {
+                    Ok(output) => {
+                        copy_out(&file, &output, header.size())?;
+                        summary.files += 1;
+                        summary.bytes += header.size();
+                        let _ = writeln!(out, "{}", target.as_str());
+                    }
+                    Err(e) => {
+                        summary.failed += 1;
+                        let _ = writeln!(out, "{}: can't write ({:?})", target.as_str(), e);
+                        skip_data(&file, &header)?;
+                    }
+                }
+            }
+        }
+    }
+    Ok(summary)
+}
+
+/// Open a file to read
+fn open(name: &str) -> Result<neotron_sdk::File, neotron_sdk::Error> {
+    let path = neotron_sdk::path::Path::new(name)?;
+    neotron_sdk::File::open(path, neotron_sdk::Flags::empty())
+}
+
+/// Put a path from an archive on the end of a directory
+fn join(dest: &str, name: &str) -> Result<PathBuf, neotron_sdk::Error> {
+    let mut path = PathBuf::from_path(dest)?;
+    path.push(name)?;
+    Ok(path)
+}
+
+/// Does a path stay inside the directory we're unpacking into? Archives
+/// from elsewhere might have absolute paths, drives or `..` in them.
+fn is_safe(name: &str) -> bool {
+    !name.starts_with('/') && !name.contains(':') && !name.split('/').any(|part| part == "..")
+}
+
+/// Read as much of `buffer` as the file has left
+fn read_full(file: &neotron_sdk::File, buffer: &mut [u8]) -> Result<usize, neotron_sdk::Error> {
+    let mut filled = 0;
+    while filled < buffer.len() {
+        let n = file.read(&mut buffer[filled..])?;
+        if n == 0 {
+            break;
+        }
+        filled += n;
+    }
+    Ok(filled)
+}
+
+/// Read the next header. An archive that just stops, without the empty
+/// blocks, is fine as long as it stops between files.
+fn next_header(file: &neotron_sdk::File) -> Result<Option<Header>, Error> {
+    let mut block = [0u8; BLOCK_LEN];
+    match read_full(file, &mut block)? {
+        0 => Ok(None),
+        BLOCK_LEN => Header::parse(&block).map_err(|_| Error::BadArchive),
+        _ => Err(Error::BadArchive),
+    }
+}
+
+/// Skip over the data after a header
+fn skip_data(file: &neotron_sdk::File, header: &Header) -> Result<(), neotron_sdk::Error> {
+    let len = header.data_blocks() * BLOCK_LEN as u64;
+    if len > 0 {
+        file.seek_cur(len as i64)?;
+    }
+    Ok(())
+}
+
+/// Copy `size` bytes of a file into the archive, padded out to a whole
+/// block. If the file is shorter than it should be, we pad that with zeros
+/// too, so the archive still makes sense. Gives how much we really read.
+fn copy_in(
+    source: &neotron_sdk::File,
+    archive: &neotron_sdk::File,
+    size: u64,
+) -> Result<u64, neotron_sdk::Error> {
+    let mut block = [0u8; BLOCK_LEN];
+    let mut left = size;
+    let mut copied = 0;
+    while left > 0 {
+        let want = left.min(BLOCK_LEN as u64) as usize;
+        block.fill(0);
+        copied += read_full(source, &mut block[0..want])? as u64;
+        archive.write(&block)?;
+        left -= want as u64;
+    }
+    Ok(copied)
+}
+
+/// Copy `size` bytes of data out of the archive, and skip the padding
+fn copy_out(
+    archive: &neotron_sdk::File,
+    output: &neotron_sdk::File,
+    size: u64,
+) -> Result<(), Error> {
+    let mut block = [0u8; BLOCK_LEN];
+    let mut left = size;
+    while left > 0 {
+        if read_full(archive, &mut block)? != BLOCK_LEN {
+            return Err(Error::BadArchive);
+        }
+        let want = left.min(BLOCK_LEN as u64) as usize;
+        output.write(&block[0..want])?;
+        left -= want as u64;
+    }
+    Ok(())
+}
@@ -0,0 +1,130 @@
+#![cfg_attr(target_os = "none", no_std)]
+#![cfg_attr(target_os = "none", no_main)]
+
+use core::fmt::Write;
+
+use neotron_args::{Item, Opt};
+
+#[cfg(not(target_os = "none"))]
+fn main() {
+    neotron_sdk::init();
+}
+
+#[no_mangle]
+extern "C" fn neotron_main() -> i32 {
+    match real_main() {
+        Ok(true) => 0,
+        // Something had to be left out
+        Ok(false) => 1,
+        Err(report) => {
+            let _ = report.print(&mut neotron_sdk::stdout());
+            2
+        }
+    }
+}
+
+/// The options we understand
+static SPEC: neotron_args::Spec = neotron_args::Spec {
+    name: "tar",
+    usage: "-c|-t|-x [options] <archive> [<directory>]",
+    about: "Packs a directory, and everything in it, into one archive file, \
+            and unpacks it again. Archives are tar files, so a PC can open \
+            them too.",
+    options: &[
+        Opt::flag('c', "create", "Pack <directory> into a new archive"),
+        Opt::flag('t', "list", "Show what's in an archive"),
+        Opt::flag('x', "extract", "Unpack an archive into <directory>"),
+        Opt::flag(
+            'f',
+            "flat",
+            "When unpacking, put every file in <directory>, ignoring paths",
+        ),
+    ],
+};
+
+/// What we've been asked to do
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Mode {
+    Create,
+    List,
+    Extract,
+}
+
+/// Problems with the command line, for reporting
+fn usage(error: neotron_args::Error) -> neotron_error::Report {
+    neotron_sdk::Error::from(error).into()
+}
+
+fn real_main() -> Result<bool, neotron_error::Report> {
+    let mut stdout = neotron_sdk::stdout();
+    let mut mode = None;
+    let mut flat = false;
+    let mut archive = None;
+    let mut directory = None;
+    let mut args = neotron_args::Parser::new(&SPEC);
+    loop {
+        let item = match args.next_item() {
+            Ok(Some(item)) => item,
+            Ok(None) => break,
+            Err(neotron_args::Error::Help) => return Ok(true),
+            Err(e) => return Err(usage(e)),
+        };
+        let new_mode = match item {
+            Item::Flag("create") => Mode::Create,
+            Item::Flag("list") => Mode::List,
+            Item::Flag("extract") => Mode::Extract,
+            Item::Flag("flat") => {
+                flat = true;
+                continue;
+            }
+            Item::Positional(value) if archive.is_none() => {
+                archive = Some(value);
+                continue;
+            }
+            Item::Positional(value) if directory.is_none() => {
+                directory = Some(value);
+                continue;
+            }
+            Item::Positional(_) => return Err(usage(args.usage_error("too many names"))),
+            _ => continue,
+        };
+        if mode.is_some_and(|mode| mode != new_mode) {
+            return Err(usage(args.usage_error("pick one of -c, -t and -x")));
+        }
+        mode = Some(new_mode);
+    }
+
+    let Some(mode) = mode else {
+        return Err(usage(args.usage_error("pick one of -c, -t and -x")));
+    };
+    let Some(archive) = archive else {
+        return Err(usage(args.usage_error("no archive given")));
+    };
+    if mode == Mode::List && directory.is_some() {
+        return Err(usage(args.usage_error("--list only takes an archive")));
+    }
+    let archive = archive.as_str();
+    // The current directory, unless we're told otherwise
+    let directory = directory.as_ref().map_or("", |d| d.as_str());
+    let result = match mode {
+        Mode::Create => tar::create(archive, directory, &mut stdout),
+        Mode::List => tar::list(archive, &mut stdout),
+        Mode::Extract => tar::extract(archive, directory, flat, &mut stdout),
+    };
+    let summary = result.map_err(|e| match e {
+        tar::Error::Io(e) => neotron_error::Report::from(e).about(archive),
+        tar::Error::BadArchive => {
+            neotron_error::Report::from(neotron_error::Error::BadData).about(archive)
+        }
+    })?;
+    let _ = write!(
+        stdout,
+        "{} files ({} bytes), {} directories",
+        summary.files, summary.bytes, summary.dirs
+    );
+    if summary.failed > 0 {
+        let _ = write!(stdout, ", {} left out", summary.failed);
+    }
+    let _ = writeln!(stdout);
+    Ok(summary.failed == 0)
+}
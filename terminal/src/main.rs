@@ -0,0 +1,150 @@
+#![cfg_attr(target_os = "none", no_std)]
+#![cfg_attr(target_os = "none", no_main)]
+
+use core::fmt::Write;
+
+mod vt100;
+
+/// The device we open if you don't give one
+const DEFAULT_DEVICE: &str = "SER0:";
+
+/// The baud rate we use if you don't give one
+const DEFAULT_BAUD: u32 = 115200;
+
+/// The baud rates we cycle through with the `b` command
+const BAUD_RATES: [u32; 6] = [9600, 19200, 38400, 57600, 115200, 230400];
+
+/// The ioctl which sets the baud rate of a serial device
+const IOCTL_SET_BAUD: u64 = 1;
+
+/// Press this (Ctrl+]) to get the command prompt
+const COMMAND_KEY: u8 = 0x1D;
+
+#[cfg(not(target_os = "none"))]
+fn main() {
+    neotron_sdk::init();
+}
+
+#[no_mangle]
+extern "C" fn neotron_main() -> i32 {
+    neotron_error::exit_code(real_main())
+}
+
+fn real_main() -> Result<(), neotron_sdk::Error> {
+    let mut stdout = neotron_sdk::stdout();
+    let stdin = neotron_sdk::stdin();
+    let device_name = neotron_sdk::arg(0);
+    let device_name: &str = match &device_name {
+        Some(name) => name,
+        None => DEFAULT_DEVICE,
+    };
+    let mut baud = match neotron_sdk::arg(1) {
+        Some(baud) => baud.parse().map_err(|_| neotron_sdk::Error::InvalidArg)?,
+        None => DEFAULT_BAUD,
+    };
+
+    let path = neotron_sdk::path::Path::new(device_name)?;
+    let serial = neotron_sdk::File::open(path, neotron_sdk::Flags::empty())?;
+    if serial.ioctl(IOCTL_SET_BAUD, u64::from(baud)).is_err() {
+        let _ = writeln!(stdout, "Failed to set baud rate {}", baud);
+        return Err(neotron_sdk::Error::DeviceSpecific);
+    }
+
+    let _ = writeln!(
+        stdout,
+        "Connected to {} at {} baud. Press Ctrl+] for commands.",
+        device_name, baud
+    );
+
+    let mut parser = vt100::Parser::new();
+    let mut local_echo = false;
+    let mut rx_buffer = [0u8; 64];
+
+    loop {
+        let mut idle = true;
+
+        // Show anything the remote end sent us
+        if let Ok(n) = serial.read(&mut rx_buffer) {
+            for byte in &rx_buffer[0..n] {
+                parser.feed(*byte, &stdout);
+            }
+            if n > 0 {
+                idle = false;
+            }
+        }
+
+        // Send anything the user typed
+        let mut in_buf = [0u8; 1];
+        if let Ok(1) = stdin.read(&mut in_buf) {
+            idle = false;
+            if in_buf[0] == COMMAND_KEY {
+                let _ = write!(
+                    stdout,
+                    "\r\n[q=quit e=echo({}) b=baud({}) ^]=send ^]] ",
+                    if local_echo { "on" } else { "off" },
+                    baud
+                );
+                match wait_for_key(&stdin) {
+                    b'q' | b'Q' => {
+                        let _ = writeln!(stdout, "\r\nBye!");
+                        break;
+                    }
+                    b'e' | b'E' => {
+                        local_echo = !local_echo;
+                        let _ = write!(
+                            stdout,
+                            "\r\n[Local echo {}]\r\n",
+                            if local_echo { "on" } else { "off" }
+                        );
+                    }
+                    b'b' | b'B' => {
+                        baud = next_baud_rate(baud);
+                        if serial.ioctl(IOCTL_SET_BAUD, u64::from(baud)).is_err() {
+                            let _ = write!(stdout, "\r\n[Failed to set {} baud]\r\n", baud);
+                        } else {
+                            let _ = write!(stdout, "\r\n[{} baud]\r\n", baud);
+                        }
+                    }
+                    COMMAND_KEY => {
+                        let _ = serial.write(&[COMMAND_KEY]);
+                        let _ = write!(stdout, "\r\n");
+                    }
+                    _ => {
+                        let _ = write!(stdout, "\r\n");
+                    }
+                }
+            } else {
+                let _ = serial.write(&in_buf);
+                if local_echo {
+                    parser.feed(in_buf[0], &stdout);
+                }
+            }
+        }
+
+        if idle {
+            neotron_sdk::delay(core::time::Duration::from_millis(5));
+        }
+    }
+
+    Ok(())
+}
+
+/// Pick the baud rate after this one, wrapping around at the end of the list
+fn next_baud_rate(baud: u32) -> u32 {
+    BAUD_RATES
+        .iter()
+        .find(|&&rate| rate > baud)
+        .cloned()
+        .unwrap_or(BAUD_RATES[0])
+}
+
+/// Spin until a key is pressed
+fn wait_for_key(stdin: &neotron_sdk::File) -> u8 {
+    loop {
+        let mut buffer = [0u8; 1];
+        if let Ok(1) = stdin.read(&mut buffer) {
+            return buffer[0];
+        }
+        neotron_sdk::delay(core::time::Duration::from_millis(10));
+    }
+}
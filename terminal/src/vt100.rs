@@ -0,0 +1,106 @@
+//! A basic VT100 escape sequence filter.
+//!
+//! Bytes from the remote end are fed in one at a time. Plain text, and the
+//! escape sequences our console understands, are passed through to the output.
+//! Anything else is quietly dropped so it doesn't mess up the screen.
+
+/// The longest CSI sequence we will buffer up
+const MAX_SEQUENCE: usize = 16;
+
+/// What the parser is currently doing
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum State {
+    /// Passing through plain text
+    Ground,
+    /// We've seen an ESC
+    Escape,
+    /// We've seen `ESC [` and are collecting parameters
+    Csi,
+}
+
+/// Decodes a stream of bytes from a VT100 compatible host
+pub struct Parser {
+    state: State,
+    buffer: [u8; MAX_SEQUENCE],
+    buffer_len: usize,
+}
+
+impl Parser {
+    /// Make a new parser, in the ground state
+    pub const fn new() -> Parser {
+        Parser {
+            state: State::Ground,
+            buffer: [0; MAX_SEQUENCE],
+            buffer_len: 0,
+        }
+    }
+
+    /// Process one received byte.
+    ///
+    /// Anything which should be shown on screen is written to `out`.
+    pub fn feed(&mut self, byte: u8, out: &neotron_sdk::File) {
+        match self.state {
+            State::Ground => match byte {
+                0x1B => {
+                    self.state = State::Escape;
+                }
+                b'\r' | b'\n' | 0x08 | b'\t' | 0x07 | 0x20.. => {
+                    let _ = out.write(&[byte]);
+                }
+                _ => {
+                    // Drop other control characters
+                }
+            },
+            State::Escape => match byte {
+                b'[' => {
+                    self.buffer[0] = 0x1B;
+                    self.buffer[1] = b'[';
+                    self.buffer_len = 2;
+                    self.state = State::Csi;
+                }
+                b'c' => {
+                    // Reset to Initial State - clear the screen and home
+                    let _ = out.write(b"\x1b[0m\x1b[2J\x1b[1;1H");
+                    self.state = State::Ground;
+                }
+                _ => {
+                    // Character sets, keypad modes, etc. We don't do those.
+                    self.state = State::Ground;
+                }
+            },
+            State::Csi => {
+                if self.buffer_len == MAX_SEQUENCE {
+                    // Runaway sequence - give up on it
+                    self.state = State::Ground;
+                    return;
+                }
+                self.buffer[self.buffer_len] = byte;
+                self.buffer_len += 1;
+                match byte {
+                    b'0'..=b'9' | b';' | b'?' => {
+                        // Keep collecting parameters
+                    }
+                    b'A' | b'B' | b'C' | b'D' | b'H' | b'f' | b'J' | b'K' | b'm' => {
+                        // Cursor movement, erase, and colours
+                        if self.buffer[2] != b'?' {
+                            let _ = out.write(&self.buffer[0..self.buffer_len]);
+                        }
+                        self.state = State::Ground;
+                    }
+                    b'h' | b'l'
+                        if self.buffer[2..self.buffer_len] == *b"?25h"
+                            || self.buffer[2..self.buffer_len] == *b"?25l" =>
+                    {
+                        // Cursor show/hide
+                        let _ = out.write(&self.buffer[0..self.buffer_len]);
+                        self.state = State::Ground;
+                    }
+                    _ => {
+                        // Something we don't support
+                        self.state = State::Ground;
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,66 @@
+//! Big digits, drawn with block characters.
+//!
+//! Each glyph is five rows of up to three "pixels", and each pixel is drawn
+//! as two characters so it comes out roughly square.
+
+/// How tall the glyphs are, in rows
+pub const HEIGHT: usize = 5;
+
+/// The digits 0 to 9. Each row is three bits, most significant on the left.
+const DIGITS: [[u8; HEIGHT]; 10] = [
+    [0b111, 0b101, 0b101, 0b101, 0b111],
+    [0b010, 0b110, 0b010, 0b010, 0b111],
+    [0b111, 0b001, 0b111, 0b100, 0b111],
+    [0b111, 0b001, 0b111, 0b001, 0b111],
+    [0b101, 0b101, 0b111, 0b001, 0b001],
+    [0b111, 0b100, 0b111, 0b001, 0b111],
+    [0b111, 0b100, 0b111, 0b101, 0b111],
+    [0b111, 0b001, 0b001, 0b001, 0b001],
+    [0b111, 0b101, 0b111, 0b101, 0b111],
+    [0b111, 0b101, 0b111, 0b001, 0b111],
+];
+
+/// The colon between the hours and minutes. This one is one pixel wide.
+const COLON: [u8; HEIGHT] = [0b0, 0b1, 0b0, 0b1, 0b0];
+
+/// The point before the tenths of a second. Also one pixel wide.
+const POINT: [u8; HEIGHT] = [0b0, 0b0, 0b0, 0b0, 0b1];
+
+/// Get the pixels and width (in pixels) of a character we can draw big.
+///
+/// Anything we don't have a glyph for comes out as a space.
+fn glyph(ch: char) -> ([u8; HEIGHT], usize) {
+    match ch {
+        '0'..='9' => (DIGITS[ch as usize - '0' as usize], 3),
+        ':' => (COLON, 1),
+        '.' => (POINT, 1),
+        _ => ([0; HEIGHT], 1),
+    }
+}
+
+/// How many columns will this text take up when drawn big?
+pub fn width(text: &str) -> usize {
+    let pixels: usize = text.chars().map(|ch| glyph(ch).1 + 1).sum();
+    // Two columns per pixel, with no gap after the last character
+    pixels.saturating_sub(1) * 2
+}
+
+/// Write out one row of some big text
+pub fn write_row<W>(out: &mut W, text: &str, row: usize)
+where
+    W: core::fmt::Write,
+{
+    for (idx, ch) in text.chars().enumerate() {
+        if idx > 0 {
+            let _ = out.write_str("  ");
+        }
+        let (pixels, width) = glyph(ch);
+        for bit in (0..width).rev() {
+            let _ = out.write_str(if pixels[row] & (1 << bit) != 0 {
+                "██"
+            } else {
+                "  "
+            });
+        }
+    }
+}
@@ -0,0 +1,621 @@
+//! Application logic for the stopwatch and kitchen timers
+
+#![no_std]
+#![deny(missing_docs)]
+#![deny(unsafe_code)]
+
+use core::fmt::Write;
+
+use neotron_input::{KeyEvent, Keyboard};
+use neotron_sdk::console;
+use neotron_tui::Line;
+
+mod beeper;
+mod font;
+mod ticker;
+
+use beeper::Beeper;
+use ticker::Ticker;
+
+/// How long we wait each time round the main loop
+const TICK_MS: u32 = 50;
+
+/// How often we redraw the timers while they are running, in ticks
+const LIST_REDRAW_TICKS: u32 = 4;
+
+/// Where the big digits go
+const DIGITS_ROW: u8 = 3;
+
+/// Where the "time's up" message goes
+const RINGING_ROW: u8 = DIGITS_ROW + font::HEIGHT as u8 + 1;
+
+/// Where the list of laps or timers goes
+const LIST_ROW: u8 = RINGING_ROW + 2;
+
+/// How many rows the list can have
+const LIST_ROWS: usize = 8;
+
+/// The most timers we can have
+const MAX_TIMERS: usize = 8;
+
+/// The most laps we remember
+const MAX_LAPS: usize = 99;
+
+/// The longest name a timer can have
+const MAX_NAME: usize = 20;
+
+/// Which screen we are showing
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum View {
+    Stopwatch,
+    Timers,
+}
+
+/// A stopwatch which can record laps
+struct Stopwatch {
+    running: bool,
+    ms: u32,
+    /// The stopwatch reading at the end of each lap
+    laps: [u32; MAX_LAPS],
+    lap_count: usize,
+}
+
+impl Stopwatch {
+    /// How long a lap took
+    fn lap_time(&self, lap: usize) -> u32 {
+        let start = if lap == 0 { 0 } else { self.laps[lap - 1] };
+        self.laps[lap] - start
+    }
+}
+
+/// A named countdown timer
+#[derive(Debug, Copy, Clone)]
+struct Timer {
+    name: [u8; MAX_NAME],
+    name_len: usize,
+    /// How long the timer was set for
+    length_ms: u32,
+    remaining_ms: u32,
+    running: bool,
+}
+
+impl Timer {
+    /// An unused timer
+    const EMPTY: Timer = Timer {
+        name: [0; MAX_NAME],
+        name_len: 0,
+        length_ms: 0,
+        remaining_ms: 0,
+        running: false,
+    };
+
+    /// The timer's name
+    fn name(&self) -> &str {
+        core::str::from_utf8(&self.name[0..self.name_len]).unwrap_or("?")
+    }
+}
+
+/// Represents the timers application
+pub struct App {
+    width: u8,
+    height: u8,
+    stdout: neotron_sdk::File,
+    keyboard: Keyboard,
+    beeper: Beeper,
+    ticker: Ticker,
+    view: View,
+    stopwatch: Stopwatch,
+    timers: [Timer; MAX_TIMERS],
+    num_timers: usize,
+    /// The timer the keys work on
+    selected: usize,
+    /// The timer which has just run out
+    ringing: Option<usize>,
+    /// The big digits currently on screen
+    drawn_digits: Line,
+    /// Does the list need drawing again?
+    list_dirty: bool,
+    /// Counts trips around the main loop
+    ticks: u32,
+}
+
+impl App {
+    /// Make a new timers application.
+    ///
+    /// You can give the screen size in characters.
+    pub const fn new(width: u8, height: u8) -> App {
+        App {
+            width,
+            height,
+            stdout: neotron_sdk::stdout(),
+            keyboard: Keyboard::new(),
+            beeper: Beeper::new(),
+            ticker: Ticker::new(),
+            view: View::Stopwatch,
+            stopwatch: Stopwatch {
+                running: false,
+                ms: 0,
+                laps: [0; MAX_LAPS],
+                lap_count: 0,
+            },
+            timers: [Timer::EMPTY; MAX_TIMERS],
+            num_timers: 0,
+            selected: 0,
+            ringing: None,
+            drawn_digits: Line::new(),
+            list_dirty: true,
+            ticks: 0,
+        }
+    }
+
+    /// Run the stopwatch and timers, until the user quits
+    pub fn play(&mut self) {
+        self.beeper.open();
+        self.ticker.start();
+        console::cursor_off(&mut self.stdout);
+        self.redraw();
+        if !self.ticker.has_rtc() {
+            self.show_message("No real-time clock found, so the times may drift a little.");
+        }
+
+        loop {
+            if let Some(key) = self.keyboard.read() {
+                if self.ringing.is_some() {
+                    self.dismiss();
+                } else if !self.handle_key(key) {
+                    break;
+                }
+            }
+            let elapsed = self.ticker.tick(TICK_MS);
+            self.update(elapsed);
+            self.draw_digits();
+            if self.list_dirty {
+                self.draw_list();
+            } else if self.view == View::Timers
+                && self.is_running()
+                && self.ticks.is_multiple_of(LIST_REDRAW_TICKS)
+            {
+                // The lines stay the same width, so we can just write over them
+                self.draw_timers();
+            }
+            self.draw_ringing();
+            self.beeper.tick(TICK_MS);
+            neotron_sdk::delay(core::time::Duration::from_millis(u64::from(TICK_MS)));
+            self.ticks = self.ticks.wrapping_add(1);
+        }
+
+        self.beeper.close();
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        console::clear_screen(&mut self.stdout);
+        console::move_cursor(&mut self.stdout, console::Position::origin());
+        console::cursor_on(&mut self.stdout);
+    }
+
+    /// Deal with a key press. Returns false if the user wants to quit.
+    fn handle_key(&mut self, key: KeyEvent) -> bool {
+        let key = match key {
+            KeyEvent::Char(ch) => KeyEvent::Char(ch.to_ascii_lowercase()),
+            other => other,
+        };
+        match (self.view, key) {
+            (_, KeyEvent::Char('q')) => return false,
+            (_, KeyEvent::Tab) => {
+                self.view = match self.view {
+                    View::Stopwatch => View::Timers,
+                    View::Timers => View::Stopwatch,
+                };
+                self.redraw();
+            }
+            (View::Stopwatch, KeyEvent::Char(' ')) => {
+                self.stopwatch.running = !self.stopwatch.running
+            }
+            (View::Stopwatch, KeyEvent::Char('l')) => {
+                let stopwatch = &mut self.stopwatch;
+                if stopwatch.running && stopwatch.lap_count < MAX_LAPS {
+                    stopwatch.laps[stopwatch.lap_count] = stopwatch.ms;
+                    stopwatch.lap_count += 1;
+                    self.list_dirty = true;
+                }
+            }
+            (View::Stopwatch, KeyEvent::Char('r')) => {
+                self.stopwatch.running = false;
+                self.stopwatch.ms = 0;
+                self.stopwatch.lap_count = 0;
+                self.list_dirty = true;
+            }
+            (View::Timers, KeyEvent::Char('a')) => self.add_timer(),
+            (View::Timers, KeyEvent::Char('d')) if self.num_timers > 0 => {
+                self.timers
+                    .copy_within(self.selected + 1..self.num_timers, self.selected);
+                self.num_timers -= 1;
+                self.selected = self.selected.min(self.num_timers.saturating_sub(1));
+                self.list_dirty = true;
+            }
+            (View::Timers, KeyEvent::Char(digit @ '1'..='8')) => {
+                let idx = digit as usize - '1' as usize;
+                if idx < self.num_timers {
+                    self.selected = idx;
+                    self.list_dirty = true;
+                }
+            }
+            (View::Timers, KeyEvent::Char(' ')) if self.num_timers > 0 => {
+                let timer = &mut self.timers[self.selected];
+                timer.running = !timer.running;
+                self.list_dirty = true;
+            }
+            (View::Timers, KeyEvent::Char('r')) if self.num_timers > 0 => {
+                let timer = &mut self.timers[self.selected];
+                timer.running = false;
+                timer.remaining_ms = timer.length_ms;
+                self.list_dirty = true;
+            }
+            _ => {}
+        }
+        true
+    }
+
+    /// Stop the alarm ringing
+    fn dismiss(&mut self) {
+        self.ringing = None;
+        self.beeper.stop();
+        self.clear_row(RINGING_ROW);
+    }
+
+    /// Is the stopwatch or any timer running?
+    fn is_running(&self) -> bool {
+        self.stopwatch.running || self.timers[0..self.num_timers].iter().any(|t| t.running)
+    }
+
+    /// Move everything on by some milliseconds
+    fn update(&mut self, elapsed: u32) {
+        if self.stopwatch.running {
+            self.stopwatch.ms = self.stopwatch.ms.saturating_add(elapsed);
+        }
+        for (idx, timer) in self.timers[0..self.num_timers].iter_mut().enumerate() {
+            if !timer.running {
+                continue;
+            }
+            timer.remaining_ms = timer.remaining_ms.saturating_sub(elapsed);
+            if timer.remaining_ms == 0 {
+                // Wind it back, ready to use again
+                timer.running = false;
+                timer.remaining_ms = timer.length_ms;
+                self.ringing = Some(idx);
+                self.beeper.start();
+                self.list_dirty = true;
+            }
+        }
+    }
+
+    /// Draw everything from scratch
+    fn redraw(&mut self) {
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        console::clear_screen(&mut self.stdout);
+        self.drawn_digits = Line::new();
+        self.list_dirty = true;
+        self.draw_tabs();
+        self.draw_help();
+    }
+
+    /// Draw the names of the two views across the top
+    fn draw_tabs(&mut self) {
+        self.move_to(0, 2);
+        for (view, name) in [(View::Stopwatch, " Stopwatch "), (View::Timers, " Timers ")] {
+            if view == self.view {
+                console::set_sgr(&mut self.stdout, [console::SgrParam::Reverse]);
+            }
+            let _ = self.stdout.write_str(name);
+            console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+            let _ = self.stdout.write_str("  ");
+        }
+    }
+
+    /// Draw the big digits, if they have changed
+    fn draw_digits(&mut self) {
+        let mut text = Line::new();
+        match self.view {
+            View::Stopwatch => write_time(&mut text, self.stopwatch.ms, true),
+            View::Timers if self.num_timers > 0 => {
+                // Round up, so we never show zero while still running
+                let ms = self.timers[self.selected].remaining_ms.div_ceil(1000) * 1000;
+                write_time(&mut text, ms, false)
+            }
+            View::Timers => {}
+        }
+        if text.as_str() == self.drawn_digits.as_str() {
+            return;
+        }
+        // The new text might be narrower, so wipe the old
+        for row in 0..font::HEIGHT {
+            self.clear_row(DIGITS_ROW + row as u8);
+        }
+        let width = font::width(text.as_str());
+        let col = usize::from(self.width).saturating_sub(width) / 2;
+        console::set_sgr(
+            &mut self.stdout,
+            [console::SgrParam::Bold, console::SgrParam::FgCyan],
+        );
+        for row in 0..font::HEIGHT {
+            self.move_to(DIGITS_ROW + row as u8, col as u8);
+            font::write_row(&mut self.stdout, text.as_str(), row);
+        }
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        self.drawn_digits = text;
+    }
+
+    /// Draw the laps or the timers
+    fn draw_list(&mut self) {
+        self.list_dirty = false;
+        for row in 0..LIST_ROWS {
+            self.clear_row(LIST_ROW + row as u8);
+        }
+        match self.view {
+            View::Stopwatch => self.draw_laps(),
+            View::Timers => self.draw_timers(),
+        }
+    }
+
+    /// Draw the most recent laps, with the fastest in green and the slowest
+    /// in red
+    fn draw_laps(&mut self) {
+        let count = self.stopwatch.lap_count;
+        if count == 0 {
+            self.move_to(LIST_ROW, 4);
+            let _ = self
+                .stdout
+                .write_str("Press L while the stopwatch runs to record a lap.");
+            return;
+        }
+        let times = (0..count).map(|lap| self.stopwatch.lap_time(lap));
+        let fastest = times.clone().min().unwrap_or(0);
+        let slowest = times.max().unwrap_or(0);
+        let first = count.saturating_sub(LIST_ROWS);
+        for (row, lap) in (first..count).enumerate() {
+            let lap_time = self.stopwatch.lap_time(lap);
+            let mut line = Line::new();
+            let _ = write!(line, "Lap {:>2}   ", lap + 1);
+            write_time(&mut line, lap_time, true);
+            let _ = line.write_str("   total ");
+            write_time(&mut line, self.stopwatch.laps[lap], true);
+            if count >= 3 && lap_time == fastest {
+                console::set_sgr(&mut self.stdout, [console::SgrParam::FgGreen]);
+            } else if count >= 3 && lap_time == slowest {
+                console::set_sgr(&mut self.stdout, [console::SgrParam::FgRed]);
+            }
+            self.move_to(LIST_ROW + row as u8, 4);
+            let _ = self.stdout.write_str(line.as_str());
+            console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        }
+    }
+
+    /// Draw the list of timers, with the selected one highlighted
+    fn draw_timers(&mut self) {
+        if self.num_timers == 0 {
+            self.move_to(LIST_ROW, 4);
+            let _ = self.stdout.write_str("No timers yet. Press A to add one.");
+            return;
+        }
+        for idx in 0..self.num_timers {
+            let timer = self.timers[idx];
+            let mut line = Line::new();
+            let _ = write!(line, "{}) {:<20}  ", idx + 1, timer.name());
+            write_time(&mut line, timer.remaining_ms.div_ceil(1000) * 1000, false);
+            let _ = line.write_str(" of ");
+            write_time(&mut line, timer.length_ms, false);
+            let _ = line.write_str(if timer.running {
+                "  running"
+            } else if timer.remaining_ms == timer.length_ms {
+                "  ready"
+            } else {
+                "  paused"
+            });
+            if idx == self.selected {
+                console::set_sgr(&mut self.stdout, [console::SgrParam::Reverse]);
+            }
+            if timer.running {
+                console::set_sgr(&mut self.stdout, [console::SgrParam::FgGreen]);
+            }
+            self.move_to(LIST_ROW + idx as u8, 4);
+            let _ = self.stdout.write_str(line.as_str());
+            console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        }
+    }
+
+    /// Flash a message while a timer is ringing
+    fn draw_ringing(&mut self) {
+        let Some(idx) = self.ringing else {
+            return;
+        };
+        let mut text = Line::new();
+        let _ = write!(
+            text,
+            " {} - TIME'S UP - press any key ",
+            self.timers[idx].name()
+        );
+        // Flash on and off every half a second
+        let on = (self.ticks / (500 / TICK_MS)).is_multiple_of(2);
+        if on {
+            console::set_sgr(
+                &mut self.stdout,
+                [console::SgrParam::Bold, console::SgrParam::Reverse],
+            );
+        }
+        self.centre(RINGING_ROW, text.as_str());
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+    }
+
+    /// Draw the list of keys at the bottom
+    fn draw_help(&mut self) {
+        self.clear_row(self.height - 3);
+        self.move_to(self.height - 3, 0);
+        let _ = self.stdout.write_str(match self.view {
+            View::Stopwatch => "Space: Start/stop  L: Lap  R: Reset",
+            View::Timers => {
+                "A: Add timer  1-8: Pick a timer  Space: Start/pause  R: Reset  D: Delete"
+            }
+        });
+        self.move_to(self.height - 2, 0);
+        let _ = self
+            .stdout
+            .write_str("Tab: Switch between stopwatch and timers  Q: Quit");
+    }
+
+    /// Ask for a name and a length, and add a timer
+    fn add_timer(&mut self) {
+        if self.num_timers == MAX_TIMERS {
+            self.show_message("You can't have any more timers.");
+            return;
+        }
+        let mut name = [0u8; MAX_NAME];
+        let Some(name) = self.prompt("Timer name: ", &mut name) else {
+            return;
+        };
+        let mut timer = Timer::EMPTY;
+        timer.name_len = name.len();
+        timer.name[0..name.len()].copy_from_slice(name.as_bytes());
+        let mut input = [0u8; 8];
+        let Some(text) = self.prompt("How long (MM:SS, H:MM:SS or minutes): ", &mut input) else {
+            return;
+        };
+        let Some(ms) = parse_duration(text) else {
+            self.show_message("Please give a time like 05:00 or 1:30:00.");
+            return;
+        };
+        timer.length_ms = ms;
+        timer.remaining_ms = ms;
+        timer.running = true;
+        self.timers[self.num_timers] = timer;
+        self.selected = self.num_timers;
+        self.num_timers += 1;
+        self.list_dirty = true;
+    }
+
+    /// Show a message just above the list of keys
+    fn show_message(&mut self, message: &str) {
+        let row = self.prompt_row();
+        self.clear_row(row);
+        self.move_to(row, 0);
+        let _ = self.stdout.write_str(message);
+    }
+
+    /// The row where we ask questions
+    fn prompt_row(&self) -> u8 {
+        self.height - 5
+    }
+
+    /// Read some text from the user.
+    ///
+    /// Returns `None` if they pressed Escape or typed nothing. Everything
+    /// carries on running while we wait, but the alarm can't ring until we're
+    /// done.
+    fn prompt<'a>(&mut self, prompt: &str, input: &'a mut [u8]) -> Option<&'a str> {
+        let row = self.prompt_row();
+        self.clear_row(row);
+        self.move_to(row, 0);
+        let _ = self.stdout.write_str(prompt);
+        console::cursor_on(&mut self.stdout);
+        let mut len = 0;
+        let done = loop {
+            let Some(key) = self.keyboard.read() else {
+                neotron_sdk::delay(core::time::Duration::from_millis(u64::from(TICK_MS)));
+                let elapsed = self.ticker.tick(TICK_MS);
+                self.update(elapsed);
+                continue;
+            };
+            match key {
+                KeyEvent::Enter => break len > 0,
+                KeyEvent::Escape => break false,
+                KeyEvent::Backspace if len > 0 => {
+                    len -= 1;
+                    let _ = self.stdout.write_str("\u{0008} \u{0008}");
+                }
+                KeyEvent::Char(key @ ' '..='~') if len < input.len() => {
+                    input[len] = key as u8;
+                    len += 1;
+                    let _ = self.stdout.write_char(key);
+                }
+                _ => {}
+            }
+        };
+        console::cursor_off(&mut self.stdout);
+        self.clear_row(row);
+        if done {
+            core::str::from_utf8(&input[0..len]).ok()
+        } else {
+            None
+        }
+    }
+
+    /// Write some text in the middle of a row
+    fn centre(&mut self, row: u8, text: &str) {
+        let col = usize::from(self.width).saturating_sub(text.len()) / 2;
+        self.move_to(row, col as u8);
+        let _ = self.stdout.write_str(text);
+    }
+
+    /// Blank out a row
+    fn clear_row(&mut self, row: u8) {
+        self.move_to(row, 0);
+        for _ in 0..self.width - 1 {
+            let _ = self.stdout.write_char(' ');
+        }
+    }
+
+    /// Move the cursor
+    fn move_to(&mut self, row: u8, col: u8) {
+        console::move_cursor(&mut self.stdout, console::Position { row, col });
+    }
+}
+
+/// Write out a time.
+///
+/// Under an hour, we give minutes and seconds, plus tenths if asked for.
+/// After that, hours, minutes and seconds.
+fn write_time(out: &mut Line, ms: u32, tenths: bool) {
+    let seconds = ms / 1000;
+    let _ = if seconds >= 3600 {
+        write!(
+            out,
+            "{}:{:02}:{:02}",
+            seconds / 3600,
+            (seconds / 60) % 60,
+            seconds % 60
+        )
+    } else if tenths {
+        write!(
+            out,
+            "{:02}:{:02}.{}",
+            seconds / 60,
+            seconds % 60,
+            (ms / 100) % 10
+        )
+    } else {
+        write!(out, "{:02}:{:02}", seconds / 60, seconds % 60)
+    };
+}
+
+/// Parse a length of time, like `05:00`, `1:30:00` or just `5` for five
+/// minutes. Returns milliseconds.
+fn parse_duration(text: &str) -> Option<u32> {
+    let mut seconds: u32 = 0;
+    let mut parts = 0;
+    for part in text.trim().split(':') {
+        let value: u32 = part.parse().ok()?;
+        if parts > 0 && value > 59 {
+            return None;
+        }
+        seconds = seconds.checked_mul(60)?.checked_add(value)?;
+        parts += 1;
+    }
+    if parts > 3 {
+        return None;
+    }
+    if parts == 1 {
+        // A single number is minutes
+        seconds = seconds.checked_mul(60)?;
+    }
+    if seconds == 0 || seconds > 100 * 3600 {
+        return None;
+    }
+    Some(seconds * 1000)
+}
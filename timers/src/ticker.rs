@@ -0,0 +1,102 @@
+//! Keeping track of how much time has passed.
+//!
+//! The SDK doesn't give us a fine-grained timer, so we count trips round the
+//! main loop. That drifts, because drawing the screen takes time too. So if
+//! there is a real-time clock, we line our count up with it every time its
+//! seconds tick over, and never let the count run ahead of it.
+
+/// How many seconds there are in a day
+const SECONDS_PER_DAY: u32 = 24 * 60 * 60;
+
+/// Counts milliseconds since we started
+pub struct Ticker {
+    /// The last reading of the real-time clock, in seconds since midnight,
+    /// if we have one
+    rtc: Option<u32>,
+    /// Our count when we first saw the real-time clock's seconds tick over
+    boundary_ms: Option<u32>,
+    /// Whole seconds counted by the real-time clock since then
+    rtc_seconds: u32,
+    /// Milliseconds since we started
+    now_ms: u32,
+}
+
+impl Ticker {
+    /// Make a new ticker
+    pub const fn new() -> Ticker {
+        Ticker {
+            rtc: None,
+            boundary_ms: None,
+            rtc_seconds: 0,
+            now_ms: 0,
+        }
+    }
+
+    /// Start counting from zero
+    pub fn start(&mut self) {
+        self.rtc = read_rtc();
+        self.boundary_ms = None;
+        self.rtc_seconds = 0;
+        self.now_ms = 0;
+    }
+
+    /// Do we have a real-time clock to keep us right?
+    pub fn has_rtc(&self) -> bool {
+        self.rtc.is_some()
+    }
+
+    /// Count another trip round the main loop, which should have taken `ms`
+    /// milliseconds. Returns how many milliseconds have really passed.
+    pub fn tick(&mut self, ms: u32) -> u32 {
+        let before = self.now_ms;
+        self.now_ms = self.now_ms.saturating_add(ms);
+        if let Some(last) = self.rtc {
+            match read_rtc() {
+                Some(now) if now != last => {
+                    self.rtc = Some(now);
+                    match self.boundary_ms {
+                        // We didn't know where in the second we started, but
+                        // now we do
+                        None => self.boundary_ms = Some(self.now_ms),
+                        Some(boundary) => {
+                            // Copes with the clock going past midnight
+                            self.rtc_seconds += (now + SECONDS_PER_DAY - last) % SECONDS_PER_DAY;
+                            self.now_ms = self.now_ms.max(boundary + self.rtc_seconds * 1000);
+                        }
+                    }
+                }
+                Some(_) => {
+                    if let Some(boundary) = self.boundary_ms {
+                        let next = boundary + (self.rtc_seconds + 1) * 1000;
+                        self.now_ms = self.now_ms.min(next - 1);
+                    }
+                }
+                None => self.rtc = None,
+            }
+        }
+        self.now_ms.saturating_sub(before)
+    }
+}
+
+impl Default for Ticker {
+    fn default() -> Self {
+        Ticker::new()
+    }
+}
+
+/// Read the real-time clock, as the number of seconds since midnight.
+///
+/// The clock gives us text like `2024-01-31 12:34:56`.
+fn read_rtc() -> Option<u32> {
+    let path = neotron_sdk::path::Path::new("RTC:").ok()?;
+    let rtc = neotron_sdk::File::open(path, neotron_sdk::Flags::empty()).ok()?;
+    let mut buffer = [0u8; 32];
+    let n = rtc.read(&mut buffer).ok()?;
+    let text = core::str::from_utf8(&buffer[0..n]).ok()?;
+    let time = text.trim().rsplit(' ').next()?;
+    let mut parts = time.split(':').map(|part| part.parse::<u32>().ok());
+    let hours = parts.next()??;
+    let minutes = parts.next()??;
+    let seconds = parts.next()??;
+    Some(hours * 3600 + minutes * 60 + seconds)
+}
@@ -0,0 +1,538 @@
+//! Application logic for the tracker editor
+
+#![no_std]
+#![deny(missing_docs)]
+#![deny(unsafe_code)]
+
+use neoplay::player::Player;
+use neotron_input::{KeyEvent, Keyboard};
+use neotron_sdk::console;
+
+pub mod module;
+pub mod preview;
+mod view;
+pub mod wav;
+
+use module::{Cell, Error, Module};
+use preview::Preview;
+use view::View;
+
+/// The sample rate we ask the audio device for
+const SAMPLE_RATE: u32 = 44100;
+
+/// How many audio frames we make each time round the main loop
+const FRAMES_PER_LOOP: usize = 256;
+
+/// How far Page Up and Page Down move
+const PAGE_ROWS: u8 = 16;
+
+/// The furthest the cursor can jump after entering a note
+const MAX_STEP: u8 = 16;
+
+/// The computer keys we play notes with, and how many semitones up from the
+/// current octave each one is.
+///
+/// The bottom row of letters is one octave, with the black notes on the row
+/// above, like a piano. The top row of letters is the next octave up, with
+/// its black notes on the number keys.
+const NOTE_KEYS: [(u8, u8); 30] = [
+    (b'z', 0),
+    (b's', 1),
+    (b'x', 2),
+    (b'd', 3),
+    (b'c', 4),
+    (b'v', 5),
+    (b'g', 6),
+    (b'b', 7),
+    (b'h', 8),
+    (b'n', 9),
+    (b'j', 10),
+    (b'm', 11),
+    (b',', 12),
+    (b'q', 12),
+    (b'2', 13),
+    (b'w', 14),
+    (b'3', 15),
+    (b'e', 16),
+    (b'r', 17),
+    (b'5', 18),
+    (b't', 19),
+    (b'6', 20),
+    (b'y', 21),
+    (b'7', 22),
+    (b'u', 23),
+    (b'i', 24),
+    (b'9', 25),
+    (b'o', 26),
+    (b'0', 27),
+    (b'p', 28),
+];
+
+/// What the help screen says
+const HELP: &[&str] = &[
+    "Tracker editor keys",
+    "",
+    "  Arrow keys       Move around the pattern",
+    "  Tab              Next channel",
+    "  PgUp/PgDn        Move up or down 16 rows",
+    "  Home/End         Go to the first or last row",
+    "",
+    "  Z S X D C ...    Enter notes, on the note column (two octaves,",
+    "  Q 2 W 3 E ...      laid out like a piano)",
+    "  0-9 A-F          Enter hex digits, on the sample and effect columns",
+    "  . or Delete      Clear the field under the cursor",
+    "  Enter            Listen to the note under the cursor",
+    "",
+    "  - =              Octave down / up",
+    "  [ ]              Previous / next sample",
+    "  { }              Smaller / bigger step after each entry",
+    "  < >              Previous / next position in the song",
+    "  ( )              Previous / next pattern at this position",
+    "  + _              Make the song one position longer / shorter",
+    "",
+    "  Space            Play the song from the start",
+    "  Ctrl+S           Save             Ctrl+T   Set the song title",
+    "  Ctrl+L           Load a sample    Ctrl+N   Name the sample",
+    "  Ctrl+V           Sample volume    Ctrl+Q   Quit",
+    "",
+    "Press any key to go back.",
+];
+
+/// Represents the tracker editor
+pub struct App {
+    keyboard: Keyboard,
+    module: Module,
+    preview: Preview,
+    view: View,
+}
+
+impl App {
+    /// Make a new tracker editor.
+    ///
+    /// You can give the screen size in characters.
+    pub const fn new(width: u8, height: u8) -> App {
+        App {
+            keyboard: Keyboard::new(),
+            module: Module::new(),
+            preview: Preview::new(),
+            view: View::new(width, height),
+        }
+    }
+
+    /// Open a module to edit.
+    ///
+    /// If the file doesn't exist, we start a new song, which will be saved
+    /// with this name.
+    pub fn open(&mut self, filename: &str) -> Result<(), Error> {
+        self.view.filename.set(filename);
+        self.module.clear();
+        let path = neotron_sdk::path::Path::new(filename)?;
+        match neotron_sdk::File::open(path, neotron_sdk::Flags::empty()) {
+            Ok(file) => self.module.load(&file),
+            Err(_) => {
+                self.view.message = Some("New song");
+                Ok(())
+            }
+        }
+    }
+
+    /// Run the editor, until the user quits
+    pub fn run(&mut self) {
+        let dsp = Self::open_audio();
+        if dsp.is_none() {
+            self.view.message = Some("No audio device - you won't hear anything");
+        }
+        console::cursor_off(&mut self.view.stdout);
+        self.view.clear();
+        self.view.draw(&self.module);
+        let mut buffer = [0u8; FRAMES_PER_LOOP * 4];
+        loop {
+            if let Some(key) = self.keyboard.read() {
+                let message = self.view.message.take();
+                match key {
+                    KeyEvent::Ctrl('q') => {
+                        if !self.view.modified || message == Some(UNSAVED) {
+                            break;
+                        }
+                        self.view.message = Some(UNSAVED);
+                    }
+                    KeyEvent::Char(' ') => {
+                        if let Some(dsp) = dsp.as_ref() {
+                            self.play_song(dsp);
+                        }
+                    }
+                    KeyEvent::Char('?') => {
+                        self.view.draw_help(HELP);
+                        self.keyboard.wait();
+                        self.view.clear();
+                    }
+                    key => self.handle_key(key),
+                }
+                self.view.draw(&self.module);
+            }
+            match dsp.as_ref() {
+                Some(dsp) => {
+                    for chunk in buffer.chunks_exact_mut(4) {
+                        let sample = self.preview.next_sample(&self.module).to_le_bytes();
+                        chunk[0] = sample[0];
+                        chunk[1] = sample[1];
+                        chunk[2] = sample[0];
+                        chunk[3] = sample[1];
+                    }
+                    // This waits until the audio device has room
+                    let _ = dsp.write(&buffer);
+                }
+                None => {
+                    neotron_sdk::delay(core::time::Duration::from_millis(10));
+                }
+            }
+        }
+        console::set_sgr(&mut self.view.stdout, [console::SgrParam::Reset]);
+        console::clear_screen(&mut self.view.stdout);
+        console::move_cursor(&mut self.view.stdout, console::Position::origin());
+        console::cursor_on(&mut self.view.stdout);
+    }
+
+    /// Open the audio device, for 16-bit stereo at our sample rate
+    fn open_audio() -> Option<neotron_sdk::File> {
+        let path = neotron_sdk::path::Path::new("AUDIO:").ok()?;
+        let dsp = neotron_sdk::File::open(path, neotron_sdk::Flags::empty()).ok()?;
+        dsp.ioctl(1, 3 << 60 | u64::from(SAMPLE_RATE)).ok()?;
+        Some(dsp)
+    }
+
+    /// Deal with a key press
+    fn handle_key(&mut self, key: KeyEvent) {
+        let pattern = self.view.pattern(&self.module);
+        let last_row = module::NUM_ROWS - 1;
+        match key {
+            KeyEvent::Up => self.view.row = self.view.row.checked_sub(1).unwrap_or(last_row),
+            KeyEvent::Down => self.view.row = (self.view.row + 1) % module::NUM_ROWS,
+            KeyEvent::PageUp => self.view.row = self.view.row.saturating_sub(PAGE_ROWS),
+            KeyEvent::PageDown => self.view.row = (self.view.row + PAGE_ROWS).min(last_row),
+            KeyEvent::Home => self.view.row = 0,
+            KeyEvent::End => self.view.row = last_row,
+            KeyEvent::Left => {
+                if self.view.field > 0 {
+                    self.view.field -= 1;
+                } else {
+                    self.view.field = view::NUM_FIELDS - 1;
+                    self.view.channel =
+                        (self.view.channel + module::NUM_CHANNELS - 1) % module::NUM_CHANNELS;
+                }
+            }
+            KeyEvent::Right => {
+                if self.view.field + 1 < view::NUM_FIELDS {
+                    self.view.field += 1;
+                } else {
+                    self.view.field = 0;
+                    self.view.channel = (self.view.channel + 1) % module::NUM_CHANNELS;
+                }
+            }
+            KeyEvent::Tab => {
+                self.view.field = 0;
+                self.view.channel = (self.view.channel + 1) % module::NUM_CHANNELS;
+            }
+            KeyEvent::Delete | KeyEvent::Char('.') | KeyEvent::Backspace => {
+                let mut cell = self.current_cell();
+                match self.view.field {
+                    0 => {
+                        cell.period = 0;
+                        cell.sample = 0;
+                    }
+                    1 | 2 => cell.sample = 0,
+                    _ => cell.effect = 0,
+                }
+                self.set_current_cell(cell);
+            }
+            KeyEvent::Enter => {
+                let cell = self.current_cell();
+                let sample = if cell.sample == 0 {
+                    self.view.sample
+                } else {
+                    cell.sample
+                };
+                self.preview
+                    .play(&self.module, sample, cell.period, SAMPLE_RATE);
+            }
+            KeyEvent::Char('-') => self.view.octave = (self.view.octave - 1).max(1),
+            KeyEvent::Char('=') => self.view.octave = (self.view.octave + 1).min(3),
+            KeyEvent::Char('[') => self.view.sample = (self.view.sample - 1).max(1),
+            KeyEvent::Char(']') => {
+                self.view.sample = (self.view.sample + 1).min(module::NUM_SAMPLES)
+            }
+            KeyEvent::Char('{') => self.view.step = self.view.step.saturating_sub(1),
+            KeyEvent::Char('}') => self.view.step = (self.view.step + 1).min(MAX_STEP),
+            KeyEvent::Char('<') => self.view.position = self.view.position.saturating_sub(1),
+            KeyEvent::Char('>') => {
+                self.view.position = (self.view.position + 1).min(self.module.song_length() - 1)
+            }
+            KeyEvent::Char('(') => self.change_order(pattern.checked_sub(1)),
+            KeyEvent::Char(')') => self.change_order(Some(pattern + 1)),
+            KeyEvent::Char('+') => {
+                self.preview.stop();
+                self.module.set_song_length(self.module.song_length() + 1);
+                self.view.modified = true;
+            }
+            KeyEvent::Char('_') => {
+                self.preview.stop();
+                self.module.set_song_length(self.module.song_length() - 1);
+                self.view.position = self.view.position.min(self.module.song_length() - 1);
+                self.view.modified = true;
+            }
+            KeyEvent::Ctrl('s') => self.save(),
+            KeyEvent::Ctrl('l') => self.load_sample(),
+            KeyEvent::Ctrl('t') => {
+                let mut title = [0u8; 20];
+                if let Some(len) = self.prompt("Song title: ", &mut title) {
+                    if let Ok(title) = core::str::from_utf8(&title[0..len]) {
+                        self.module.set_title(title);
+                        self.view.modified = true;
+                    }
+                }
+            }
+            KeyEvent::Ctrl('n') => {
+                let mut name = [0u8; module::SAMPLE_NAME_LEN];
+                if let Some(len) = self.prompt("Sample name: ", &mut name) {
+                    if let Ok(name) = core::str::from_utf8(&name[0..len]) {
+                        self.module.set_sample_name(self.view.sample, name);
+                        self.view.modified = true;
+                    }
+                }
+            }
+            KeyEvent::Ctrl('v') => {
+                let mut volume = [0u8; 2];
+                if let Some(len) = self.prompt("Sample volume (0-64): ", &mut volume) {
+                    match core::str::from_utf8(&volume[0..len]).map(|v| v.parse::<u8>()) {
+                        Ok(Ok(volume)) if volume <= 64 => {
+                            self.module.set_sample_volume(self.view.sample, volume);
+                            self.view.modified = true;
+                        }
+                        _ => self.view.message = Some("The volume must be from 0 to 64"),
+                    }
+                }
+            }
+            KeyEvent::Char(key) if key.is_ascii() && self.view.field == 0 => {
+                self.enter_note(key as u8)
+            }
+            KeyEvent::Char(key) if key.is_ascii() => self.enter_digit(key as u8),
+            _ => {}
+        }
+    }
+
+    /// Put a note in the cell under the cursor, if this is a note key
+    fn enter_note(&mut self, key: u8) {
+        let key = key.to_ascii_lowercase();
+        let Some((_, offset)) = NOTE_KEYS.iter().find(|(k, _)| *k == key) else {
+            return;
+        };
+        let note = (self.view.octave - 1) * 12 + offset;
+        let Some(period) = module::note_period(note) else {
+            self.view.message = Some("That note is too high");
+            return;
+        };
+        let mut cell = self.current_cell();
+        cell.period = period;
+        cell.sample = self.view.sample;
+        self.set_current_cell(cell);
+        self.preview
+            .play(&self.module, self.view.sample, period, SAMPLE_RATE);
+    }
+
+    /// Put a hex digit in the cell under the cursor, if this is a hex key
+    fn enter_digit(&mut self, key: u8) {
+        let Some(digit) = (key as char).to_digit(16) else {
+            return;
+        };
+        let digit = digit as u16;
+        let mut cell = self.current_cell();
+        match self.view.field {
+            1 => cell.sample = (cell.sample & 0x0F) | (digit as u8) << 4,
+            2 => cell.sample = (cell.sample & 0xF0) | digit as u8,
+            3 => cell.effect = (cell.effect & 0x0FF) | digit << 8,
+            4 => cell.effect = (cell.effect & 0xF0F) | digit << 4,
+            _ => cell.effect = (cell.effect & 0xFF0) | digit,
+        }
+        if cell.sample > module::NUM_SAMPLES {
+            self.view.message = Some("There are only 31 samples (1F in hex)");
+            return;
+        }
+        self.set_current_cell(cell);
+    }
+
+    /// Get the cell under the cursor
+    fn current_cell(&self) -> Cell {
+        self.module.cell(
+            self.view.pattern(&self.module),
+            self.view.row,
+            self.view.channel,
+        )
+    }
+
+    /// Change the cell under the cursor, and move down
+    fn set_current_cell(&mut self, cell: Cell) {
+        self.module.set_cell(
+            self.view.pattern(&self.module),
+            self.view.row,
+            self.view.channel,
+            cell,
+        );
+        self.view.modified = true;
+        self.view.row = (self.view.row + self.view.step) % module::NUM_ROWS;
+    }
+
+    /// Play a different pattern at this position in the song
+    fn change_order(&mut self, pattern: Option<u8>) {
+        let Some(pattern) = pattern else {
+            return;
+        };
+        self.preview.stop();
+        match self.module.set_order(self.view.position, pattern) {
+            Ok(()) => self.view.modified = true,
+            Err(e) => self.view.message = Some(e.describe()),
+        }
+    }
+
+    /// Save the song, asking for a file name
+    fn save(&mut self) {
+        let mut name = [0u8; 64];
+        let current = self.view.filename.as_str().as_bytes();
+        name[0..current.len()].copy_from_slice(current);
+        let Some(len) = self.prompt_with("Save as: ", &mut name, current.len()) else {
+            return;
+        };
+        let Ok(filename) = core::str::from_utf8(&name[0..len]) else {
+            return;
+        };
+        let result = neotron_sdk::path::Path::new(filename)
+            .and_then(|path| {
+                neotron_sdk::File::open(
+                    path,
+                    neotron_sdk::Flags::WRITE
+                        | neotron_sdk::Flags::CREATE
+                        | neotron_sdk::Flags::TRUNCATE,
+                )
+            })
+            .map_err(Error::from)
+            .and_then(|file| self.module.save(&file));
+        match result {
+            Ok(()) => {
+                self.view.filename.set(filename);
+                self.view.modified = false;
+                self.view.message = Some("Saved");
+            }
+            Err(e) => self.view.message = Some(e.describe()),
+        }
+    }
+
+    /// Load a sample file into the current sample
+    fn load_sample(&mut self) {
+        let mut name = [0u8; 64];
+        let Some(len) = self.prompt("Load sample from: ", &mut name) else {
+            return;
+        };
+        let Ok(filename) = core::str::from_utf8(&name[0..len]) else {
+            return;
+        };
+        self.preview.stop();
+        let result = neotron_sdk::path::Path::new(filename)
+            .and_then(|path| neotron_sdk::File::open(path, neotron_sdk::Flags::empty()))
+            .map_err(Error::from)
+            .and_then(|file| wav::load(&mut self.module, self.view.sample, &file));
+        match result {
+            Ok(()) => {
+                // Name the sample after the file, without any directories
+                let name = filename.rsplit(['/', '\\', ':']).next().unwrap_or(filename);
+                self.module.set_sample_name(self.view.sample, name);
+                self.view.message = Some("Sample loaded");
+            }
+            Err(e) => self.view.message = Some(e.describe()),
+        }
+        self.view.modified = true;
+    }
+
+    /// Play the song from the start, until it ends or a key is pressed
+    fn play_song(&mut self, dsp: &neotron_sdk::File) {
+        self.preview.stop();
+        let mut player = match Player::new(self.module.as_bytes(), SAMPLE_RATE) {
+            Ok(player) => player,
+            Err(_) => {
+                self.view.message = Some("The player couldn't understand this song");
+                return;
+            }
+        };
+        let (position, row) = (self.view.position, self.view.row);
+        self.view.playing = true;
+        self.view.draw_footer();
+        let mut buffer = [0u8; FRAMES_PER_LOOP * 4];
+        let mut shown = None;
+        loop {
+            for chunk in buffer.chunks_exact_mut(4) {
+                let (left, right) = player.next_sample(&mut Discard);
+                let left = left.to_le_bytes();
+                let right = right.to_le_bytes();
+                chunk[0] = left[0];
+                chunk[1] = left[1];
+                chunk[2] = right[0];
+                chunk[3] = right[1];
+            }
+            let _ = dsp.write(&buffer);
+            if player.is_finished() {
+                break;
+            }
+            if self.keyboard.read().is_some() {
+                break;
+            }
+            // Follow the music
+            let now = (player.position(), player.line().saturating_sub(1));
+            if shown != Some(now) && now.0 < self.module.song_length() {
+                shown = Some(now);
+                self.view.position = now.0;
+                self.view.row = now.1;
+                self.view.draw_status(&self.module);
+                self.view.draw_grid(&self.module);
+            }
+        }
+        self.view.playing = false;
+        self.view.position = position;
+        self.view.row = row;
+    }
+
+    /// Ask the user for some text, on the message line.
+    ///
+    /// Returns how many bytes they typed, or `None` if they pressed Escape.
+    fn prompt(&mut self, question: &str, buffer: &mut [u8]) -> Option<usize> {
+        self.prompt_with(question, buffer, 0)
+    }
+
+    /// Ask the user for some text, starting with the first `len` bytes of
+    /// the buffer filled in
+    fn prompt_with(&mut self, question: &str, buffer: &mut [u8], len: usize) -> Option<usize> {
+        let mut len = len;
+        loop {
+            self.view.draw_prompt(question, &buffer[0..len]);
+            match self.keyboard.wait() {
+                KeyEvent::Enter => return Some(len),
+                KeyEvent::Escape => return None,
+                KeyEvent::Backspace => len = len.saturating_sub(1),
+                KeyEvent::Char(key) if key.is_ascii() && len < buffer.len() => {
+                    buffer[len] = key as u8;
+                    len += 1;
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// What we say when you try to quit without saving
+const UNSAVED: &str = "The song has not been saved. Press Ctrl+Q again to quit anyway.";
+
+/// Throws away the player's trace of each line
+struct Discard;
+
+impl core::fmt::Write for Discard {
+    fn write_str(&mut self, _s: &str) -> core::fmt::Result {
+        Ok(())
+    }
+}
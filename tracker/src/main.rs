@@ -0,0 +1,27 @@
+#![cfg_attr(target_os = "none", no_std)]
+#![cfg_attr(target_os = "none", no_main)]
+
+use core::{fmt::Write, ptr::addr_of_mut};
+
+#[cfg(not(target_os = "none"))]
+fn main() {
+    neotron_sdk::init();
+}
+
+static mut TRACKER: tracker::App = tracker::App::new(80, 25);
+
+/// What we call the song if we aren't given a file name
+const DEFAULT_FILENAME: &str = "SONG.MOD";
+
+#[no_mangle]
+extern "C" fn neotron_main() -> i32 {
+    let tracker = unsafe { &mut *addr_of_mut!(TRACKER) };
+    let filename = neotron_sdk::arg(0);
+    if let Err(e) = tracker.open(filename.as_deref().unwrap_or(DEFAULT_FILENAME)) {
+        let mut stdout = neotron_sdk::stdout();
+        let _ = writeln!(stdout, "Error: {}", e.describe());
+        return 1;
+    }
+    tracker.run();
+    0
+}
@@ -0,0 +1,520 @@
+//! A ProTracker module, held in memory exactly as it is stored on disk.
+//!
+//! Keeping the file format in memory means we can hand the bytes straight to
+//! the player, and save them without any conversion. The price is that
+//! adding a pattern, or changing a sample, means shuffling the sample data
+//! along.
+//!
+//! A 4-channel `M.K.` module is laid out as:
+//!
+//! * 20 bytes of song title
+//! * 31 sample headers, 30 bytes each
+//! * the song length, a restart byte, and the 128 byte order table
+//! * the `M.K.` tag
+//! * the patterns, 1024 bytes each
+//! * the sample data, one sample after another
+
+/// The most module data we can hold
+pub const CAPACITY: usize = 192 * 1024;
+
+/// How many samples a module has. They are numbered from 1.
+pub const NUM_SAMPLES: u8 = 31;
+
+/// How many rows in a pattern
+pub const NUM_ROWS: u8 = 64;
+
+/// How many channels in a pattern
+pub const NUM_CHANNELS: u8 = 4;
+
+/// How many patterns a module can have
+pub const MAX_PATTERNS: u8 = 64;
+
+/// How many entries in the order table
+pub const MAX_ORDERS: u8 = 128;
+
+/// The longest sample the format can describe, in bytes
+const MAX_SAMPLE_LEN: usize = 0xFFFF * 2;
+
+/// Where the song title lives
+const TITLE_LEN: usize = 20;
+
+/// How big each sample header is
+const SAMPLE_HEADER_LEN: usize = 30;
+
+/// How long a sample name can be
+pub const SAMPLE_NAME_LEN: usize = 22;
+
+/// Where the song length lives
+const SONG_LENGTH_OFFSET: usize = 950;
+
+/// Where the order table starts
+const ORDERS_OFFSET: usize = 952;
+
+/// Where the format tag lives
+const TAG_OFFSET: usize = 1080;
+
+/// The format tag for a 4-channel, 31-sample module
+const TAG: &[u8; 4] = b"M.K.";
+
+/// How big the header is, before the first pattern
+const HEADER_LEN: usize = 1084;
+
+/// How big each pattern is
+const PATTERN_LEN: usize = NUM_ROWS as usize * NUM_CHANNELS as usize * 4;
+
+/// The Amiga periods for each note, from C-1 to B-3, with no fine-tuning
+const PERIODS: [u16; 36] = [
+    856, 808, 762, 720, 678, 640, 604, 570, 538, 508, 480, 453, // Octave 1
+    428, 404, 381, 360, 339, 320, 302, 285, 269, 254, 240, 226, // Octave 2
+    214, 202, 190, 180, 170, 160, 151, 143, 135, 127, 120, 113, // Octave 3
+];
+
+/// The names of the notes in an octave
+const NOTE_NAMES: [&str; 12] = [
+    "C-", "C#", "D-", "D#", "E-", "F-", "F#", "G-", "G#", "A-", "A#", "B-",
+];
+
+/// Things that can go wrong with a module
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// The file isn't a 4-channel `M.K.` module
+    NotAModule,
+    /// The module won't fit in memory
+    TooBig,
+    /// There are no more patterns available
+    TooManyPatterns,
+    /// The sample file is in a format we can't read
+    BadSample,
+    /// The operating system reported an error
+    Io(neotron_sdk::Error),
+}
+
+impl Error {
+    /// Get a human-readable description of this error
+    pub fn describe(self) -> &'static str {
+        match self {
+            Error::NotAModule => "That isn't a 4-channel M.K. module",
+            Error::TooBig => "That won't fit in memory",
+            Error::TooManyPatterns => "There is no room for another pattern",
+            Error::BadSample => "That isn't a sample I can read",
+            Error::Io(_) => "Could not read or write the file",
+        }
+    }
+}
+
+impl From<neotron_sdk::Error> for Error {
+    fn from(error: neotron_sdk::Error) -> Error {
+        Error::Io(error)
+    }
+}
+
+/// One note, in one channel, on one row of a pattern
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct Cell {
+    /// The Amiga period, or zero for no note
+    pub period: u16,
+    /// The sample to play, or zero to keep the last one
+    pub sample: u8,
+    /// The effect, as three hex digits
+    pub effect: u16,
+}
+
+impl Cell {
+    /// Unpack a cell from its four bytes
+    fn from_bytes(bytes: &[u8]) -> Cell {
+        Cell {
+            period: u16::from(bytes[0] & 0x0F) << 8 | u16::from(bytes[1]),
+            sample: (bytes[0] & 0xF0) | (bytes[2] >> 4),
+            effect: u16::from(bytes[2] & 0x0F) << 8 | u16::from(bytes[3]),
+        }
+    }
+
+    /// Pack a cell into its four bytes
+    fn to_bytes(self) -> [u8; 4] {
+        [
+            (self.sample & 0xF0) | (self.period >> 8) as u8 & 0x0F,
+            self.period as u8,
+            (self.sample << 4) | (self.effect >> 8) as u8 & 0x0F,
+            self.effect as u8,
+        ]
+    }
+
+    /// Is there nothing in this cell?
+    pub fn is_empty(&self) -> bool {
+        *self == Cell::default()
+    }
+}
+
+/// Work out the period for a note, counting semitones up from C-1
+pub fn note_period(note: u8) -> Option<u16> {
+    PERIODS.get(usize::from(note)).cloned()
+}
+
+/// Write the name of the note with this period, like `C#2`.
+///
+/// Periods which aren't in our table are shown in hex.
+pub fn write_note<W>(out: &mut W, period: u16) -> core::fmt::Result
+where
+    W: core::fmt::Write,
+{
+    if period == 0 {
+        return out.write_str("---");
+    }
+    match PERIODS.iter().position(|p| *p == period) {
+        Some(idx) => write!(out, "{}{}", NOTE_NAMES[idx % 12], idx / 12 + 1),
+        None => write!(out, "{:03x}", period),
+    }
+}
+
+/// The settings for one sample
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct SampleInfo {
+    /// The sample's name, padded with zeroes
+    pub name: [u8; SAMPLE_NAME_LEN],
+    /// How long the sample is, in bytes
+    pub length: usize,
+    /// The volume, from 0 to 64
+    pub volume: u8,
+    /// Where the loop starts, in bytes
+    pub repeat_point: usize,
+    /// How long the loop is, in bytes. Two or less means no loop.
+    pub repeat_length: usize,
+}
+
+impl SampleInfo {
+    /// Get the name, without the padding
+    pub fn name(&self) -> &str {
+        let len = self
+            .name
+            .iter()
+            .position(|b| *b == 0)
+            .unwrap_or(self.name.len());
+        core::str::from_utf8(&self.name[0..len]).unwrap_or("?")
+    }
+
+    /// Does the sample loop?
+    pub fn loops(&self) -> bool {
+        self.repeat_length > 2
+    }
+}
+
+/// A ProTracker module
+pub struct Module {
+    data: [u8; CAPACITY],
+    len: usize,
+}
+
+impl Module {
+    /// Make an empty module. Call [`Module::clear`] before you use it.
+    pub const fn new() -> Module {
+        Module {
+            data: [0; CAPACITY],
+            len: 0,
+        }
+    }
+
+    /// Start a new song, with one empty pattern and no samples
+    pub fn clear(&mut self) {
+        self.data[0..HEADER_LEN + PATTERN_LEN].fill(0);
+        for sample in 1..=NUM_SAMPLES {
+            // A repeat length of one word means "doesn't loop"
+            let offset = Self::sample_header(sample) + 28;
+            self.data[offset..offset + 2].copy_from_slice(&1u16.to_be_bytes());
+        }
+        self.data[SONG_LENGTH_OFFSET] = 1;
+        self.data[SONG_LENGTH_OFFSET + 1] = 127;
+        self.data[TAG_OFFSET..HEADER_LEN].copy_from_slice(TAG);
+        self.len = HEADER_LEN + PATTERN_LEN;
+    }
+
+    /// Load a module from a file
+    pub fn load(&mut self, file: &neotron_sdk::File) -> Result<(), Error> {
+        let mut len = 0;
+        loop {
+            if len == CAPACITY {
+                // Is there more?
+                let mut extra = [0u8; 1];
+                if file.read(&mut extra)? != 0 {
+                    self.clear();
+                    return Err(Error::TooBig);
+                }
+                break;
+            }
+            let n = file.read(&mut self.data[len..])?;
+            if n == 0 {
+                break;
+            }
+            len += n;
+        }
+        self.len = len;
+        if len < HEADER_LEN
+            || &self.data[TAG_OFFSET..HEADER_LEN] != TAG
+            || self.num_patterns() > MAX_PATTERNS
+            || len < self.sample_data_start()
+        {
+            self.clear();
+            return Err(Error::NotAModule);
+        }
+        // Some modules have truncated sample data. Pad it with silence.
+        let needed = self.sample_data_start()
+            + (1..=NUM_SAMPLES)
+                .map(|n| self.sample(n).length)
+                .sum::<usize>();
+        if needed > CAPACITY {
+            self.clear();
+            return Err(Error::TooBig);
+        }
+        if needed > self.len {
+            self.data[self.len..needed].fill(0);
+            self.len = needed;
+        }
+        Ok(())
+    }
+
+    /// Save the module to a file
+    pub fn save(&self, file: &neotron_sdk::File) -> Result<(), Error> {
+        file.write(self.as_bytes())?;
+        Ok(())
+    }
+
+    /// Get the module, as it would be stored on disk
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data[0..self.len]
+    }
+
+    /// How much of our memory the module is using
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Is the module empty?
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Get the song title
+    pub fn title(&self) -> &str {
+        let title = &self.data[0..TITLE_LEN];
+        let len = title.iter().position(|b| *b == 0).unwrap_or(TITLE_LEN);
+        core::str::from_utf8(&title[0..len]).unwrap_or("?")
+    }
+
+    /// Change the song title. It is cut short if it is too long.
+    pub fn set_title(&mut self, title: &str) {
+        copy_padded(&mut self.data[0..TITLE_LEN], title);
+    }
+
+    /// How many entries of the order table get played
+    pub fn song_length(&self) -> u8 {
+        self.data[SONG_LENGTH_OFFSET].clamp(1, MAX_ORDERS)
+    }
+
+    /// Which pattern plays at this point in the song
+    pub fn order(&self, position: u8) -> u8 {
+        self.data[ORDERS_OFFSET + usize::from(position.min(MAX_ORDERS - 1))]
+    }
+
+    /// Change which pattern plays at this point in the song.
+    ///
+    /// New patterns are made as required.
+    pub fn set_order(&mut self, position: u8, pattern: u8) -> Result<(), Error> {
+        if pattern >= MAX_PATTERNS {
+            return Err(Error::TooManyPatterns);
+        }
+        let count = self.num_patterns();
+        if pattern >= count {
+            self.grow_patterns(count, pattern + 1)?;
+        }
+        self.data[ORDERS_OFFSET + usize::from(position.min(MAX_ORDERS - 1))] = pattern;
+        self.keep_patterns(count.max(pattern + 1));
+        Ok(())
+    }
+
+    /// Make the song longer or shorter.
+    ///
+    /// New positions play the same pattern as the last one.
+    pub fn set_song_length(&mut self, length: u8) {
+        let length = length.clamp(1, MAX_ORDERS);
+        let old = self.song_length();
+        let last = self.order(old - 1);
+        let keep = self.num_patterns();
+        for position in old..length {
+            self.data[ORDERS_OFFSET + usize::from(position)] = last;
+        }
+        self.data[SONG_LENGTH_OFFSET] = length;
+        self.keep_patterns(keep);
+    }
+
+    /// How many patterns are stored in the module.
+    ///
+    /// Like ProTracker, we work this out from the highest pattern in the
+    /// whole order table, not just the part which gets played.
+    pub fn num_patterns(&self) -> u8 {
+        self.data[ORDERS_OFFSET..ORDERS_OFFSET + usize::from(MAX_ORDERS)]
+            .iter()
+            .max()
+            .map_or(1, |max| max + 1)
+    }
+
+    /// Get one cell from a pattern
+    pub fn cell(&self, pattern: u8, row: u8, channel: u8) -> Cell {
+        let offset = Self::cell_offset(pattern, row, channel);
+        Cell::from_bytes(&self.data[offset..offset + 4])
+    }
+
+    /// Change one cell in a pattern
+    pub fn set_cell(&mut self, pattern: u8, row: u8, channel: u8, cell: Cell) {
+        if pattern >= self.num_patterns() {
+            return;
+        }
+        let offset = Self::cell_offset(pattern, row, channel);
+        self.data[offset..offset + 4].copy_from_slice(&cell.to_bytes());
+    }
+
+    /// Get the settings for a sample, numbered from 1
+    pub fn sample(&self, sample: u8) -> SampleInfo {
+        let offset = Self::sample_header(sample);
+        let header = &self.data[offset..offset + SAMPLE_HEADER_LEN];
+        let word = |idx: usize| usize::from(u16::from_be_bytes([header[idx], header[idx + 1]])) * 2;
+        let mut info = SampleInfo {
+            length: word(22),
+            volume: header[25].min(64),
+            repeat_point: word(26),
+            repeat_length: word(28),
+            ..Default::default()
+        };
+        info.name.copy_from_slice(&header[0..SAMPLE_NAME_LEN]);
+        info
+    }
+
+    /// Get the sample data for a sample, numbered from 1
+    pub fn sample_data(&self, sample: u8) -> &[u8] {
+        let start = self.sample_start(sample);
+        let end = (start + self.sample(sample).length).min(self.len);
+        &self.data[start.min(end)..end]
+    }
+
+    /// Change the name of a sample
+    pub fn set_sample_name(&mut self, sample: u8, name: &str) {
+        let offset = Self::sample_header(sample);
+        copy_padded(&mut self.data[offset..offset + SAMPLE_NAME_LEN], name);
+    }
+
+    /// Change the volume of a sample
+    pub fn set_sample_volume(&mut self, sample: u8, volume: u8) {
+        self.data[Self::sample_header(sample) + 25] = volume.min(64);
+    }
+
+    /// Replace the data for a sample.
+    ///
+    /// The `fill` function is given a buffer of `length` bytes to write the
+    /// new sample data into. The sample stops looping, and is set to full
+    /// volume.
+    pub fn replace_sample<F>(&mut self, sample: u8, length: usize, fill: F) -> Result<(), Error>
+    where
+        F: FnOnce(&mut [u8]) -> Result<(), Error>,
+    {
+        let length = length.min(MAX_SAMPLE_LEN) & !1;
+        let start = self.sample_start(sample);
+        let old_length = self.sample(sample).length;
+        if self.len - old_length + length > CAPACITY {
+            return Err(Error::TooBig);
+        }
+        // Shuffle the samples after this one along
+        self.data
+            .copy_within(start + old_length..self.len, start + length);
+        self.len = self.len - old_length + length;
+        let header = Self::sample_header(sample);
+        self.data[header + 22..header + 24].copy_from_slice(&((length / 2) as u16).to_be_bytes());
+        self.data[header + 24] = 0;
+        self.data[header + 25] = 64;
+        self.data[header + 26..header + 28].copy_from_slice(&0u16.to_be_bytes());
+        self.data[header + 28..header + 30].copy_from_slice(&1u16.to_be_bytes());
+        let result = fill(&mut self.data[start..start + length]);
+        if result.is_err() {
+            // Don't leave half a sample behind
+            self.data[start..start + length].fill(0);
+        }
+        result
+    }
+
+    /// Insert empty patterns after the last one, so we have `new` patterns
+    /// instead of `old`
+    fn grow_patterns(&mut self, old: u8, new: u8) -> Result<(), Error> {
+        let start = HEADER_LEN + usize::from(old) * PATTERN_LEN;
+        let extra = usize::from(new - old) * PATTERN_LEN;
+        if self.len + extra > CAPACITY {
+            return Err(Error::TooBig);
+        }
+        self.data.copy_within(start..self.len, start + extra);
+        self.data[start..start + extra].fill(0);
+        self.len += extra;
+        Ok(())
+    }
+
+    /// Make sure we still count the given number of patterns.
+    ///
+    /// If the played part of the order table no longer uses the highest
+    /// pattern, we note it in the unplayed part of the table. Otherwise the
+    /// pattern would vanish, and the sample data would appear to be in the
+    /// wrong place. If the whole table is played, there's nowhere to note
+    /// it, so the unused patterns are thrown away.
+    fn keep_patterns(&mut self, count: u8) {
+        let length = usize::from(self.song_length());
+        let orders = &mut self.data[ORDERS_OFFSET..ORDERS_OFFSET + usize::from(MAX_ORDERS)];
+        orders[length..].fill(0);
+        let played = orders[0..length].iter().max().map_or(1, |max| max + 1);
+        if played >= count {
+            return;
+        }
+        if length < orders.len() {
+            orders[orders.len() - 1] = count - 1;
+        } else {
+            let start = HEADER_LEN + usize::from(played) * PATTERN_LEN;
+            let end = HEADER_LEN + usize::from(count) * PATTERN_LEN;
+            self.data.copy_within(end..self.len, start);
+            self.len -= end - start;
+        }
+    }
+
+    /// Where the header for a sample lives
+    fn sample_header(sample: u8) -> usize {
+        let idx = usize::from(sample.clamp(1, NUM_SAMPLES) - 1);
+        TITLE_LEN + idx * SAMPLE_HEADER_LEN
+    }
+
+    /// Where a cell lives
+    fn cell_offset(pattern: u8, row: u8, channel: u8) -> usize {
+        HEADER_LEN
+            + usize::from(pattern) * PATTERN_LEN
+            + usize::from(row.min(NUM_ROWS - 1)) * usize::from(NUM_CHANNELS) * 4
+            + usize::from(channel.min(NUM_CHANNELS - 1)) * 4
+    }
+
+    /// Where the sample data starts
+    fn sample_data_start(&self) -> usize {
+        HEADER_LEN + usize::from(self.num_patterns()) * PATTERN_LEN
+    }
+
+    /// Where the data for a sample starts, in the bytes of the module
+    pub fn sample_start(&self, sample: u8) -> usize {
+        self.sample_data_start()
+            + (1..sample.clamp(1, NUM_SAMPLES))
+                .map(|n| self.sample(n).length)
+                .sum::<usize>()
+    }
+}
+
+impl Default for Module {
+    fn default() -> Self {
+        Module::new()
+    }
+}
+
+/// Copy text into a fixed-size field, padding it with zeroes
+fn copy_padded(field: &mut [u8], text: &str) {
+    field.fill(0);
+    for (dest, src) in field.iter_mut().zip(text.bytes()) {
+        *dest = src;
+    }
+}
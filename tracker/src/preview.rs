@@ -0,0 +1,78 @@
+//! Plays single notes, so you can hear what you are typing in.
+//!
+//! This works like one channel of the player, without any effects.
+
+use crate::module::{Module, SampleInfo};
+
+/// A note being played
+#[derive(Debug, Copy, Clone)]
+struct Note {
+    /// Where the sample data starts, in the module
+    start: usize,
+    info: SampleInfo,
+    volume: i32,
+    position: neotron_fixed::Fractional,
+    step: neotron_fixed::Fractional,
+}
+
+/// Plays one note at a time
+pub struct Preview {
+    note: Option<Note>,
+}
+
+impl Preview {
+    /// Make a silent previewer
+    pub const fn new() -> Preview {
+        Preview { note: None }
+    }
+
+    /// Start playing a sample, at the pitch given by an Amiga period
+    pub fn play(&mut self, module: &Module, sample: u8, period: u16, sample_rate: u32) {
+        let info = module.sample(sample);
+        if info.length == 0 || period == 0 {
+            return;
+        }
+        self.note = Some(Note {
+            start: module.sample_start(sample),
+            info,
+            volume: i32::from(info.volume),
+            position: neotron_fixed::Fractional::default(),
+            step: neotron_fixed::Fractional::new_from_sample_rate(sample_rate).apply_period(period),
+        });
+    }
+
+    /// Stop playing. Do this before changing the module.
+    pub fn stop(&mut self) {
+        self.note = None;
+    }
+
+    /// Work out the next (mono) sample
+    pub fn next_sample(&mut self, module: &Module) -> i16 {
+        let Some(note) = self.note.as_mut() else {
+            return 0;
+        };
+        let info = note.info;
+        let Some(byte) = module.as_bytes().get(note.start + note.position.as_index()) else {
+            self.note = None;
+            return 0;
+        };
+        // Sample range [-128, 127] scaled to [-32768, 32767], at volume 0 to 64
+        let value = i32::from(*byte as i8) * 256 * note.volume / 64;
+        note.position += note.step;
+        let index = note.position.as_index();
+        if info.loops() {
+            if index >= info.repeat_point + info.repeat_length {
+                note.position = neotron_fixed::Fractional::new(info.repeat_point as u32);
+            }
+        } else if index >= info.length {
+            self.note = None;
+        }
+        value as i16
+    }
+}
+
+impl Default for Preview {
+    fn default() -> Self {
+        Preview::new()
+    }
+}
@@ -0,0 +1,338 @@
+//! Drawing the editor screen.
+//!
+//! The top two lines show the song and the current settings, then comes the
+//! pattern grid, then a message line and a line of help at the bottom.
+
+use core::fmt::Write;
+
+use neotron_sdk::console;
+
+use crate::module::{self, Cell, Module};
+
+/// Where the column headings go
+const HEADING_ROW: u8 = 2;
+
+/// Where the pattern grid starts
+const GRID_ROW: u8 = 3;
+
+/// Where the first channel starts
+const GRID_COL: u8 = 5;
+
+/// How wide each channel is, including the gap
+const CHANNEL_WIDTH: u8 = 13;
+
+/// Where each field starts, within a channel
+const FIELD_COLS: [u8; NUM_FIELDS as usize] = [0, 4, 5, 7, 8, 9];
+
+/// How many fields each cell has: the note, two digits of sample number,
+/// and three digits of effect
+pub const NUM_FIELDS: u8 = 6;
+
+/// Holds the file name, without an allocator
+#[derive(Debug, Copy, Clone)]
+pub struct Name {
+    buffer: [u8; 64],
+    len: usize,
+}
+
+impl Name {
+    /// Make an empty name
+    pub const fn new() -> Name {
+        Name {
+            buffer: [0; 64],
+            len: 0,
+        }
+    }
+
+    /// Set the name. It is cut short if it is too long.
+    pub fn set(&mut self, text: &str) {
+        let mut len = text.len().min(self.buffer.len());
+        while !text.is_char_boundary(len) {
+            len -= 1;
+        }
+        self.buffer[0..len].copy_from_slice(&text.as_bytes()[0..len]);
+        self.len = len;
+    }
+
+    /// Get the name
+    pub fn as_str(&self) -> &str {
+        core::str::from_utf8(&self.buffer[0..self.len]).unwrap_or("?")
+    }
+}
+
+/// What we are showing, and where the cursor is
+pub struct View {
+    pub stdout: neotron_sdk::File,
+    pub width: u8,
+    pub height: u8,
+    /// Which entry in the order table we are editing
+    pub position: u8,
+    /// Which row of the pattern the cursor is on
+    pub row: u8,
+    /// Which channel the cursor is in
+    pub channel: u8,
+    /// Which part of the cell the cursor is on
+    pub field: u8,
+    /// Which octave the bottom row of the keyboard plays
+    pub octave: u8,
+    /// Which sample new notes use
+    pub sample: u8,
+    /// How many rows the cursor moves down after entering something
+    pub step: u8,
+    /// The file we load from and save to
+    pub filename: Name,
+    /// Has the song changed since it was saved?
+    pub modified: bool,
+    /// Something to tell the user
+    pub message: Option<&'static str>,
+    /// Are we playing the song? The cursor follows the music if so.
+    pub playing: bool,
+    /// The first row of the pattern on the screen
+    top: u8,
+}
+
+impl View {
+    /// Make a new view
+    pub const fn new(width: u8, height: u8) -> View {
+        View {
+            stdout: neotron_sdk::stdout(),
+            width,
+            height,
+            position: 0,
+            row: 0,
+            channel: 0,
+            field: 0,
+            octave: 2,
+            sample: 1,
+            step: 1,
+            filename: Name::new(),
+            modified: false,
+            message: None,
+            playing: false,
+            top: 0,
+        }
+    }
+
+    /// Which pattern the cursor is in
+    pub fn pattern(&self, module: &Module) -> u8 {
+        module.order(self.position)
+    }
+
+    /// Draw the whole screen
+    pub fn draw(&mut self, module: &Module) {
+        self.draw_status(module);
+        self.draw_grid(module);
+        self.draw_footer();
+    }
+
+    /// Draw the top two lines
+    pub fn draw_status(&mut self, module: &Module) {
+        let width = usize::from(self.width);
+        let mut line = Line::new();
+        let _ = write!(
+            line,
+            " {}{}  \"{}\"",
+            self.filename.as_str(),
+            if self.modified { "*" } else { "" },
+            module.title()
+        );
+        let _ = write!(
+            line,
+            "  {} patterns, {} KiB free",
+            module.num_patterns(),
+            (module::CAPACITY - module.len()) / 1024
+        );
+        self.move_to(0, 0);
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reverse]);
+        let _ = write!(self.stdout, "{:<width$.width$}", line.as_str());
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+
+        let info = module.sample(self.sample);
+        let mut line = Line::new();
+        let _ = write!(
+            line,
+            " Order {:02}/{:02} Pattern {:02}  Octave {}  Step {:2}  Sample {:02X} {:<22} vol {:2} len {}",
+            self.position,
+            module.song_length(),
+            self.pattern(module),
+            self.octave,
+            self.step,
+            self.sample,
+            info.name(),
+            info.volume,
+            info.length
+        );
+        self.move_to(1, 0);
+        let _ = write!(self.stdout, "{:<width$.width$}", line.as_str());
+    }
+
+    /// Draw the pattern, with the cursor's row in the middle where possible
+    pub fn draw_grid(&mut self, module: &Module) {
+        let rows = self.grid_rows();
+        self.top = self
+            .row
+            .saturating_sub(rows / 2)
+            .min(module::NUM_ROWS.saturating_sub(rows));
+
+        self.move_to(HEADING_ROW, 0);
+        let _ = self.stdout.write_str("Row");
+        for channel in 0..module::NUM_CHANNELS {
+            self.move_to(HEADING_ROW, GRID_COL + channel * CHANNEL_WIDTH);
+            let _ = write!(self.stdout, "Channel {}  ", channel + 1);
+        }
+
+        let pattern = self.pattern(module);
+        for screen_row in 0..rows {
+            let row = self.top + screen_row;
+            self.move_to(GRID_ROW + screen_row, 0);
+            if row >= module::NUM_ROWS {
+                let _ = write!(
+                    self.stdout,
+                    "{:width$}",
+                    "",
+                    width = usize::from(self.width)
+                );
+                continue;
+            }
+            let current = row == self.row;
+            if current {
+                console::set_sgr(&mut self.stdout, [console::SgrParam::Bold]);
+            }
+            let _ = write!(self.stdout, " {:02}", row);
+            for channel in 0..module::NUM_CHANNELS {
+                let cell = module.cell(pattern, row, channel);
+                let mut text = Line::new();
+                write_cell(&mut text, cell);
+                let col = GRID_COL + channel * CHANNEL_WIDTH;
+                self.move_to(GRID_ROW + screen_row, col - 2);
+                let _ = self.stdout.write_str("| ");
+                if current && channel == self.channel && !self.playing {
+                    // Draw the field under the cursor in reverse
+                    let bytes = text.as_str().as_bytes();
+                    let start = usize::from(FIELD_COLS[usize::from(self.field)]);
+                    let end = if self.field == 0 {
+                        start + 3
+                    } else {
+                        start + 1
+                    };
+                    let _ = self.stdout.write(&bytes[0..start]);
+                    console::set_sgr(&mut self.stdout, [console::SgrParam::Reverse]);
+                    let _ = self.stdout.write(&bytes[start..end]);
+                    console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+                    console::set_sgr(&mut self.stdout, [console::SgrParam::Bold]);
+                    let _ = self.stdout.write(&bytes[end..]);
+                } else {
+                    let _ = self.stdout.write_str(text.as_str());
+                }
+            }
+            console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        }
+    }
+
+    /// Draw the message and help lines
+    pub fn draw_footer(&mut self) {
+        let width = usize::from(self.width);
+        self.move_to(self.height - 2, 0);
+        let message = self.message.unwrap_or("");
+        let _ = write!(self.stdout, "{:<width$.width$}", message);
+        self.move_to(self.height - 1, 0);
+        let help = if self.playing {
+            "Playing - press any key to stop"
+        } else {
+            "?: help  Space: play  Ctrl+S: save  Ctrl+L: load sample  Ctrl+Q: quit"
+        };
+        let _ = write!(self.stdout, "{:<width$.width$}", help);
+    }
+
+    /// Ask the user a question, on the message line
+    pub fn draw_prompt(&mut self, question: &str, answer: &[u8]) {
+        self.move_to(self.height - 2, 0);
+        let _ = self.stdout.write_str(question);
+        let _ = self.stdout.write(answer);
+        let used = question.len() + answer.len();
+        let _ = write!(
+            self.stdout,
+            "_{:width$}",
+            "",
+            width = usize::from(self.width).saturating_sub(used + 2)
+        );
+    }
+
+    /// Show a page of help, and wait for a key
+    pub fn draw_help(&mut self, lines: &[&str]) {
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        console::clear_screen(&mut self.stdout);
+        for (row, line) in lines.iter().enumerate() {
+            self.move_to(row as u8, 0);
+            let _ = self.stdout.write_str(line);
+        }
+    }
+
+    /// Clear the screen, ready for a full redraw
+    pub fn clear(&mut self) {
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        console::clear_screen(&mut self.stdout);
+    }
+
+    /// How many rows of the pattern fit on the screen
+    pub fn grid_rows(&self) -> u8 {
+        self.height.saturating_sub(GRID_ROW + 2)
+    }
+
+    /// Move the cursor
+    fn move_to(&mut self, row: u8, col: u8) {
+        console::move_cursor(&mut self.stdout, console::Position { row, col });
+    }
+}
+
+/// Write a cell like `C-2 01 C40`, with dashes for empty parts
+fn write_cell<W>(out: &mut W, cell: Cell)
+where
+    W: Write,
+{
+    let _ = module::write_note(out, cell.period);
+    if cell.sample == 0 {
+        let _ = out.write_str(" --");
+    } else {
+        let _ = write!(out, " {:02X}", cell.sample);
+    }
+    if cell.effect == 0 {
+        let _ = out.write_str(" ---");
+    } else {
+        let _ = write!(out, " {:03X}", cell.effect);
+    }
+}
+
+/// Holds one line of text, without an allocator
+struct Line {
+    buffer: [u8; 128],
+    len: usize,
+}
+
+impl Line {
+    /// Make an empty line
+    fn new() -> Line {
+        Line {
+            buffer: [0; 128],
+            len: 0,
+        }
+    }
+
+    /// Get the text
+    fn as_str(&self) -> &str {
+        core::str::from_utf8(&self.buffer[0..self.len]).unwrap_or("?")
+    }
+}
+
+impl core::fmt::Write for Line {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        let bytes = s.as_bytes();
+        let space = &mut self.buffer[self.len..];
+        if bytes.len() > space.len() {
+            return Err(core::fmt::Error);
+        }
+        space[0..bytes.len()].copy_from_slice(bytes);
+        self.len += bytes.len();
+        Ok(())
+    }
+}
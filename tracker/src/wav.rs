@@ -0,0 +1,133 @@
+//! Reading samples from disk.
+//!
+//! We read uncompressed PCM WAV files of any bit depth, keeping only the
+//! first channel and the top eight bits. Any other file is loaded as raw
+//! 8-bit signed sample data, the way the Amiga stores it.
+
+use crate::module::{Error, Module};
+
+/// How the sample data is stored in the file
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct Format {
+    /// How many bytes make one frame, across all the channels
+    frame_len: usize,
+    /// Which byte of the frame is the top byte of the first channel
+    top_byte: usize,
+    /// Is the data signed? 8-bit WAV files are unsigned.
+    signed: bool,
+    /// How many frames there are
+    frames: usize,
+}
+
+/// Load a sample file into the module
+pub fn load(module: &mut Module, sample: u8, file: &neotron_sdk::File) -> Result<(), Error> {
+    let format = read_header(file)?;
+    module.replace_sample(sample, format.frames, |dest| {
+        read_frames(file, format, dest)
+    })
+}
+
+/// Work out what sort of file this is, and leave it ready to read the
+/// sample data
+fn read_header(file: &neotron_sdk::File) -> Result<Format, Error> {
+    let mut riff = [0u8; 12];
+    if read_fully(file, &mut riff)? != riff.len()
+        || &riff[0..4] != b"RIFF"
+        || &riff[8..12] != b"WAVE"
+    {
+        // Raw sample data
+        let frames = file.seek_end()? as usize;
+        file.seek_set(0)?;
+        return Ok(Format {
+            frame_len: 1,
+            top_byte: 0,
+            signed: true,
+            frames,
+        });
+    }
+    let mut format = None;
+    loop {
+        let mut header = [0u8; 8];
+        if read_fully(file, &mut header)? != header.len() {
+            // We ran out of file before we found the data
+            return Err(Error::BadSample);
+        }
+        let size = u32::from_le_bytes([header[4], header[5], header[6], header[7]]) as usize;
+        match &header[0..4] {
+            b"fmt " => {
+                let mut fmt = [0u8; 16];
+                if size < fmt.len() || read_fully(file, &mut fmt)? != fmt.len() {
+                    return Err(Error::BadSample);
+                }
+                let audio_format = u16::from_le_bytes([fmt[0], fmt[1]]);
+                let block_align = usize::from(u16::from_le_bytes([fmt[12], fmt[13]]));
+                let bits = usize::from(u16::from_le_bytes([fmt[14], fmt[15]]));
+                if audio_format != 1 || bits == 0 || !bits.is_multiple_of(8) || block_align == 0 {
+                    return Err(Error::BadSample);
+                }
+                format = Some(Format {
+                    frame_len: block_align,
+                    top_byte: bits / 8 - 1,
+                    signed: bits > 8,
+                    frames: 0,
+                });
+                skip(file, size - fmt.len())?;
+            }
+            b"data" => {
+                let Some(format) = format else {
+                    return Err(Error::BadSample);
+                };
+                return Ok(Format {
+                    frames: size / format.frame_len,
+                    ..format
+                });
+            }
+            _ => skip(file, size)?,
+        }
+    }
+}
+
+/// Read the sample data into the module
+fn read_frames(file: &neotron_sdk::File, format: Format, dest: &mut [u8]) -> Result<(), Error> {
+    let mut buffer = [0u8; 512];
+    let frames_per_read = buffer.len() / format.frame_len;
+    if frames_per_read == 0 {
+        return Err(Error::BadSample);
+    }
+    for chunk in dest.chunks_mut(frames_per_read) {
+        let wanted = chunk.len() * format.frame_len;
+        let n = read_fully(file, &mut buffer[0..wanted])?;
+        for (out, frame) in chunk
+            .iter_mut()
+            .zip(buffer[0..n].chunks_exact(format.frame_len))
+        {
+            let byte = frame[format.top_byte];
+            *out = if format.signed { byte } else { byte ^ 0x80 };
+        }
+        if n < wanted {
+            // The file is shorter than it said. The rest is silence.
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Skip over a chunk we don't need. Chunks are padded to an even length.
+fn skip(file: &neotron_sdk::File, size: usize) -> Result<(), Error> {
+    let size = size + (size & 1);
+    file.seek_cur(size as i64)?;
+    Ok(())
+}
+
+/// Read until the buffer is full, or the file ends
+fn read_fully(file: &neotron_sdk::File, buffer: &mut [u8]) -> Result<usize, Error> {
+    let mut len = 0;
+    while len < buffer.len() {
+        let n = file.read(&mut buffer[len..])?;
+        if n == 0 {
+            break;
+        }
+        len += n;
+    }
+    Ok(len)
+}
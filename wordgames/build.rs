@@ -0,0 +1,3 @@
+fn main() {
+    neotron_asset::build::embed("assets/words.txt", "words.lz");
+}
@@ -0,0 +1,120 @@
+//! Anagrams.
+//!
+//! We jumble up the letters of a word, and you work out what it was. Any
+//! word from the list which uses the same letters counts. Asking for a hint
+//! gives away the next letter, and costs you a point.
+
+use core::fmt::Write;
+use neotron_input::KeyEvent;
+use neotron_tui::Line;
+
+use crate::wordlist::{WordList, MAX_WORD_LEN};
+use crate::{Outcome, Terminal, HELP_ROW, STATUS_ROW};
+
+/// Where the jumbled letters go
+const JUMBLE_ROW: u8 = 7;
+
+/// Where the player's answer goes
+const ANSWER_ROW: u8 = 11;
+
+/// Where the hint goes
+const HINT_ROW: u8 = 14;
+
+/// Play one round with the given word.
+///
+/// Returns how it went, and the points scored.
+pub fn play(terminal: &mut Terminal, words: &WordList, word: &str) -> Option<(Outcome, u32)> {
+    let mut jumbled = [0u8; MAX_WORD_LEN];
+    let jumbled = &mut jumbled[0..word.len()];
+    jumbled.copy_from_slice(word.as_bytes());
+    jumbled.make_ascii_uppercase();
+    // Fisher-Yates shuffle, a few times over in case it comes out unjumbled
+    for _ in 0..4 {
+        for idx in (1..jumbled.len()).rev() {
+            let other = usize::from(neotron_sdk::rand()) % (idx + 1);
+            jumbled.swap(idx, other);
+        }
+        if !word.as_bytes().eq_ignore_ascii_case(jumbled) {
+            break;
+        }
+    }
+
+    let mut answer = [0u8; MAX_WORD_LEN];
+    let mut answer_len = 0;
+    let mut hints = 0;
+    terminal.clear("ANAGRAMS");
+    terminal.centre(
+        HELP_ROW,
+        "Type your answer and press Enter. ? for a hint, Esc gives up.",
+    );
+    let mut line = Line::new();
+    for letter in jumbled.iter() {
+        let _ = write!(line, "{} ", char::from(*letter));
+    }
+    terminal.centre(JUMBLE_ROW, line.as_str());
+    loop {
+        let mut line = Line::new();
+        for (idx, letter) in answer.iter().take(word.len()).enumerate() {
+            let letter = if idx < answer_len { *letter } else { b'_' };
+            let _ = write!(line, "{} ", char::from(letter));
+        }
+        terminal.centre(ANSWER_ROW, line.as_str());
+
+        let key = terminal.keyboard.wait();
+        terminal.centre(STATUS_ROW, "");
+        match key {
+            KeyEvent::Char(key @ ('a'..='z' | 'A'..='Z')) if answer_len < word.len() => {
+                answer[answer_len] = (key as u8).to_ascii_uppercase();
+                answer_len += 1;
+            }
+            KeyEvent::Backspace if answer_len > 0 => answer_len -= 1,
+            KeyEvent::Char('?') if hints < word.len() - 1 => {
+                hints += 1;
+                let mut line = Line::new();
+                let _ = write!(line, "It starts with ");
+                for letter in word.bytes().take(hints) {
+                    let _ = line.write_char(char::from(letter.to_ascii_uppercase()));
+                }
+                terminal.centre(HINT_ROW, line.as_str());
+            }
+            KeyEvent::Escape => {
+                let mut line = Line::new();
+                let _ = write!(line, "The word was ");
+                for letter in word.bytes() {
+                    let _ = line.write_char(char::from(letter.to_ascii_uppercase()));
+                }
+                terminal.centre(STATUS_ROW, line.as_str());
+                return Some((Outcome::Lost, 0));
+            }
+            KeyEvent::Enter if answer_len == word.len() => {
+                let guess = core::str::from_utf8(&answer[0..answer_len]).unwrap_or("");
+                if guess.eq_ignore_ascii_case(word)
+                    || (same_letters(guess, word) && words.contains(guess))
+                {
+                    let points = (word.len() - hints) as u32;
+                    let mut line = Line::new();
+                    let _ = write!(line, "Correct! That's {} points.", points);
+                    terminal.centre(STATUS_ROW, line.as_str());
+                    return Some((Outcome::Won, points));
+                } else if !same_letters(guess, word) {
+                    terminal.centre(STATUS_ROW, "That doesn't use the same letters");
+                } else {
+                    terminal.centre(STATUS_ROW, "That's not the word");
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Are these two words made of the same letters? Case doesn't matter.
+fn same_letters(a: &str, b: &str) -> bool {
+    let mut counts = [0i8; 26];
+    for letter in a.bytes() {
+        counts[usize::from(letter.to_ascii_lowercase() - b'a')] += 1;
+    }
+    for letter in b.bytes() {
+        counts[usize::from(letter.to_ascii_lowercase() - b'a')] -= 1;
+    }
+    counts.iter().all(|c| *c == 0)
+}
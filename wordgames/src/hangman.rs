@@ -0,0 +1,128 @@
+//! Hangman.
+//!
+//! Guess the word a letter at a time. Six wrong guesses and you're hanged.
+
+use core::fmt::Write;
+use neotron_input::KeyEvent;
+use neotron_tui::Line;
+
+use crate::{Outcome, Terminal, HELP_ROW, STATUS_ROW};
+
+/// How many wrong guesses you get
+const MAX_WRONG: usize = 6;
+
+/// Where the gallows go
+const GALLOWS_ROW: u8 = 3;
+
+/// Where the word goes
+const WORD_ROW: u8 = 12;
+
+/// Where the wrong guesses go
+const WRONG_ROW: u8 = 15;
+
+/// The gallows, before anyone is hanged on them
+const GALLOWS: [&str; 7] = [
+    "  +---+  ",
+    "  |   |  ",
+    "      |  ",
+    "      |  ",
+    "      |  ",
+    "      |  ",
+    "=========",
+];
+
+/// The parts of the body, in the order they appear: the row, the column,
+/// and what to draw
+const BODY: [(usize, usize, char); MAX_WRONG] = [
+    (2, 2, 'O'),
+    (3, 2, '|'),
+    (3, 1, '/'),
+    (3, 3, '\\'),
+    (4, 1, '/'),
+    (4, 3, '\\'),
+];
+
+/// Play one game with the given word
+pub fn play(terminal: &mut Terminal, word: &str) -> Option<Outcome> {
+    // One bit per letter of the alphabet
+    let mut guessed: u32 = 0;
+    let mut wrong = 0;
+    let is_guessed = |guessed: u32, letter: u8| guessed & 1 << (letter - b'a') != 0;
+    terminal.clear("HANGMAN");
+    terminal.centre(HELP_ROW, "Type a letter to guess it. Esc gives up.");
+    loop {
+        draw_gallows(terminal, wrong);
+        let mut line = Line::new();
+        for letter in word.bytes().map(|b| b.to_ascii_lowercase()) {
+            let shown = if is_guessed(guessed, letter) {
+                letter.to_ascii_uppercase()
+            } else {
+                b'_'
+            };
+            let _ = write!(line, "{} ", char::from(shown));
+        }
+        terminal.centre(WORD_ROW, line.as_str());
+        let mut line = Line::new();
+        let _ = write!(line, "Wrong guesses: ");
+        for letter in b'a'..=b'z' {
+            if is_guessed(guessed, letter) && !word.bytes().any(|b| b.eq_ignore_ascii_case(&letter))
+            {
+                let _ = write!(line, "{} ", char::from(letter.to_ascii_uppercase()));
+            }
+        }
+        terminal.centre(WRONG_ROW, line.as_str());
+
+        if word
+            .bytes()
+            .all(|b| is_guessed(guessed, b.to_ascii_lowercase()))
+        {
+            terminal.centre(STATUS_ROW, "You got it!");
+            return Some(Outcome::Won);
+        }
+        if wrong == MAX_WRONG {
+            let mut line = Line::new();
+            let _ = write!(line, "You're hanged! The word was ");
+            for letter in word.bytes() {
+                let _ = line.write_char(char::from(letter.to_ascii_uppercase()));
+            }
+            terminal.centre(STATUS_ROW, line.as_str());
+            return Some(Outcome::Lost);
+        }
+
+        let key = terminal.keyboard.wait();
+        terminal.centre(STATUS_ROW, "");
+        match key {
+            KeyEvent::Escape => return None,
+            KeyEvent::Char(key @ ('a'..='z' | 'A'..='Z')) => {
+                let letter = (key as u8).to_ascii_lowercase();
+                if is_guessed(guessed, letter) {
+                    terminal.centre(STATUS_ROW, "You've already tried that letter");
+                    continue;
+                }
+                guessed |= 1 << (letter - b'a');
+                if !word.bytes().any(|b| b.eq_ignore_ascii_case(&letter)) {
+                    wrong += 1;
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Draw the gallows, with as much of the body as the wrong guesses have
+/// earned
+fn draw_gallows(terminal: &mut Terminal, wrong: usize) {
+    for (row, text) in GALLOWS.iter().enumerate() {
+        let mut line = [0u8; 9];
+        line.copy_from_slice(text.as_bytes());
+        for (body_row, col, part) in BODY.iter().take(wrong) {
+            if *body_row == row {
+                line[*col] = *part as u8;
+            }
+        }
+        terminal.centre(
+            GALLOWS_ROW + row as u8,
+            core::str::from_utf8(&line).unwrap_or(""),
+        );
+    }
+}
@@ -0,0 +1,227 @@
+//! Game logic for the word games - Hangman and Anagrams
+
+#![no_std]
+#![deny(missing_docs)]
+#![deny(unsafe_code)]
+
+use core::fmt::Write;
+
+use neotron_input::{KeyEvent, Keyboard};
+use neotron_sdk::console;
+use neotron_tui::Line;
+
+pub mod anagram;
+pub mod hangman;
+pub mod wordlist;
+
+use wordlist::WordList;
+
+/// The row for messages about the game
+const STATUS_ROW: u8 = 19;
+
+/// The row for the keys the player can press
+const HELP_ROW: u8 = 22;
+
+/// How a game ended
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    /// The player got the word
+    Won,
+    /// The player didn't
+    Lost,
+}
+
+/// Represents the word games application
+pub struct App {
+    terminal: Terminal,
+    words: WordList,
+    /// Name of the word list file, if we loaded one
+    filename: Line,
+    /// Hangman games won and lost
+    hangman: (u32, u32),
+    /// Anagrams solved and points scored
+    anagrams: (u32, u32),
+}
+
+impl App {
+    /// Make a new word games application.
+    ///
+    /// You can give the screen size in characters.
+    pub const fn new(width: u8, _height: u8) -> App {
+        App {
+            terminal: Terminal::new(width),
+            words: WordList::new(),
+            filename: Line::new(),
+            hangman: (0, 0),
+            anagrams: (0, 0),
+        }
+    }
+
+    /// Use the words from the given file instead of the built-in ones
+    pub fn load_words(&mut self, filename: &str) -> Result<(), neotron_sdk::Error> {
+        self.words.load(filename)?;
+        self.filename = Line::new();
+        let _ = self.filename.write_str(filename);
+        Ok(())
+    }
+
+    /// Show the menu and play games until the user quits
+    pub fn play(&mut self) {
+        if self.words.is_empty() {
+            self.words.use_builtin();
+        }
+        console::cursor_off(&mut self.terminal.stdout);
+        let mut seed: u16 = 0x71e3;
+        loop {
+            self.menu();
+            let KeyEvent::Char(key) = self.terminal.keyboard.wait() else {
+                continue;
+            };
+            seed = seed.wrapping_add(key as u16);
+            neotron_sdk::srand(seed);
+            match key {
+                '1' => self.play_hangman(),
+                '2' => self.play_anagrams(),
+                'q' | 'Q' => break,
+                _ => {}
+            }
+        }
+        let stdout = &mut self.terminal.stdout;
+        console::set_sgr(stdout, [console::SgrParam::Reset]);
+        console::clear_screen(stdout);
+        console::move_cursor(stdout, console::Position::origin());
+        console::cursor_on(stdout);
+    }
+
+    /// Draw the menu
+    fn menu(&mut self) {
+        let terminal = &mut self.terminal;
+        terminal.clear("NEOTRON WORD GAMES");
+        terminal.centre(6, "1 - Hangman ");
+        terminal.centre(7, "2 - Anagrams");
+        terminal.centre(9, "Q - Quit    ");
+        let mut line = Line::new();
+        let _ = write!(
+            line,
+            "Hangman: won {}, lost {}",
+            self.hangman.0, self.hangman.1
+        );
+        terminal.centre(12, line.as_str());
+        let mut line = Line::new();
+        let _ = write!(
+            line,
+            "Anagrams: {} solved, {} points",
+            self.anagrams.0, self.anagrams.1
+        );
+        terminal.centre(13, line.as_str());
+        let mut line = Line::new();
+        if self.words.is_builtin() {
+            let _ = write!(line, "Using the built-in words ({})", self.words.count());
+        } else {
+            let _ = write!(
+                line,
+                "Using words from {} ({})",
+                self.filename.as_str(),
+                self.words.count()
+            );
+        }
+        terminal.centre(16, line.as_str());
+    }
+
+    /// Play games of hangman until the player has had enough
+    fn play_hangman(&mut self) {
+        loop {
+            let Some(word) = self.words.random(5..=10) else {
+                self.no_words();
+                return;
+            };
+            match hangman::play(&mut self.terminal, word) {
+                None => return,
+                Some(Outcome::Won) => self.hangman.0 += 1,
+                Some(Outcome::Lost) => self.hangman.1 += 1,
+            }
+            if !self.terminal.again() {
+                return;
+            }
+        }
+    }
+
+    /// Play rounds of anagrams until the player has had enough
+    fn play_anagrams(&mut self) {
+        loop {
+            let Some(word) = self.words.random(5..=8) else {
+                self.no_words();
+                return;
+            };
+            match anagram::play(&mut self.terminal, &self.words, word) {
+                None => return,
+                Some((Outcome::Won, points)) => {
+                    self.anagrams.0 += 1;
+                    self.anagrams.1 += points;
+                }
+                Some((Outcome::Lost, _)) => {}
+            }
+            if !self.terminal.again() {
+                return;
+            }
+        }
+    }
+
+    /// Tell the player the word list doesn't suit this game
+    fn no_words(&mut self) {
+        self.terminal.centre(
+            STATUS_ROW,
+            "There are no words of the right length in the list",
+        );
+        self.terminal.keyboard.wait();
+    }
+}
+
+/// The screen and keyboard, shared by the games
+pub struct Terminal {
+    stdout: neotron_sdk::File,
+    /// Where the player's keys come from
+    pub keyboard: Keyboard,
+    width: u8,
+}
+
+impl Terminal {
+    /// Make a new terminal
+    const fn new(width: u8) -> Terminal {
+        Terminal {
+            stdout: neotron_sdk::stdout(),
+            keyboard: Keyboard::new(),
+            width,
+        }
+    }
+
+    /// Clear the screen and put a title at the top
+    pub fn clear(&mut self, title: &str) {
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        console::clear_screen(&mut self.stdout);
+        console::set_sgr(
+            &mut self.stdout,
+            [console::SgrParam::Bold, console::SgrParam::FgYellow],
+        );
+        self.centre(1, title);
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+    }
+
+    /// Write some text in the middle of a row, clearing whatever was there
+    pub fn centre(&mut self, row: u8, text: &str) {
+        console::move_cursor(&mut self.stdout, console::Position { row, col: 0 });
+        let _ = write!(self.stdout, "{:^1$}", text, usize::from(self.width));
+    }
+
+    /// Ask if the player wants another go
+    pub fn again(&mut self) -> bool {
+        self.centre(HELP_ROW, "Press N for another go, or Q for the menu");
+        loop {
+            match self.keyboard.wait() {
+                KeyEvent::Char('n' | 'N' | ' ') | KeyEvent::Enter => return true,
+                KeyEvent::Char('q' | 'Q') | KeyEvent::Escape => return false,
+                _ => {}
+            }
+        }
+    }
+}
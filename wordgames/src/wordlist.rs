@@ -0,0 +1,132 @@
+//! Word lists - the built-in one, or one loaded from disk.
+//!
+//! A word list file is plain text, with words separated by spaces or new
+//! lines. Anything which isn't a word made only of letters, or which is too
+//! short or too long, is skipped, so an ordinary dictionary file works fine.
+//!
+//! The built-in list is packed when we're built, and unpacked into the same
+//! buffer a file would be loaded into.
+
+use neotron_asset::Asset;
+
+/// The longest word we use
+pub const MAX_WORD_LEN: usize = 12;
+
+/// The shortest word we use
+pub const MIN_WORD_LEN: usize = 3;
+
+/// How much of a word list file we read
+const BUFFER_LEN: usize = 16 * 1024;
+
+/// The words we know if we aren't given any, from `assets/words.txt`
+const BUILTIN: Asset = neotron_asset::include_asset!("words.lz");
+
+/// A list of words
+pub struct WordList {
+    buffer: [u8; BUFFER_LEN],
+    len: usize,
+    /// Are we using the built-in list?
+    builtin: bool,
+}
+
+impl WordList {
+    /// Make an empty word list. Fill it with [`WordList::use_builtin`] or
+    /// [`WordList::load`].
+    pub const fn new() -> WordList {
+        WordList {
+            buffer: [0; BUFFER_LEN],
+            len: 0,
+            builtin: false,
+        }
+    }
+
+    /// Use the built-in words
+    pub fn use_builtin(&mut self) {
+        // They're built in, so we know they fit
+        self.len = BUILTIN.unpack(&mut self.buffer).unwrap_or(0);
+        self.builtin = true;
+    }
+
+    /// Load a word list from a file.
+    ///
+    /// If the file is too big, we use as much as fits. If there are no usable
+    /// words in it, we go back to the built-in words.
+    pub fn load(&mut self, filename: &str) -> Result<(), neotron_sdk::Error> {
+        let path = neotron_sdk::path::Path::new(filename)?;
+        let file = neotron_sdk::File::open(path, neotron_sdk::Flags::empty())?;
+        let mut len = 0;
+        while len < self.buffer.len() {
+            let n = file.read(&mut self.buffer[len..])?;
+            if n == 0 {
+                break;
+            }
+            len += n;
+        }
+        if len == self.buffer.len() {
+            // Drop the last word, which has probably been cut short
+            len = self
+                .buffer
+                .iter()
+                .rposition(|b| b.is_ascii_whitespace())
+                .unwrap_or(0);
+        }
+        self.len = len;
+        self.builtin = false;
+        if self.count() == 0 {
+            // The file has gone over whatever we had before
+            self.use_builtin();
+            return Err(neotron_sdk::Error::InvalidArg);
+        }
+        Ok(())
+    }
+
+    /// Are we using the built-in list?
+    pub fn is_builtin(&self) -> bool {
+        self.builtin
+    }
+
+    /// Is the list empty?
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// How many words there are
+    pub fn count(&self) -> usize {
+        self.words().count()
+    }
+
+    /// Go through all the usable words
+    pub fn words(&self) -> impl Iterator<Item = &str> {
+        let text = core::str::from_utf8(&self.buffer[0..self.len]).unwrap_or("");
+        text.split_ascii_whitespace().filter(|word| {
+            (MIN_WORD_LEN..=MAX_WORD_LEN).contains(&word.len())
+                && word.bytes().all(|b| b.is_ascii_alphabetic())
+        })
+    }
+
+    /// Pick a word at random, with a length in the given range.
+    ///
+    /// Returns `None` if there are no words that long.
+    pub fn random(&self, lengths: core::ops::RangeInclusive<usize>) -> Option<&str> {
+        let matching = self.words().filter(|w| lengths.contains(&w.len())).count();
+        if matching == 0 {
+            return None;
+        }
+        // Two random numbers, so big lists don't just use their first 64K words
+        let roll = usize::from(neotron_sdk::rand()) << 16 | usize::from(neotron_sdk::rand());
+        self.words()
+            .filter(|w| lengths.contains(&w.len()))
+            .nth(roll % matching)
+    }
+
+    /// Is this word in the list? Case doesn't matter.
+    pub fn contains(&self, word: &str) -> bool {
+        self.words().any(|w| w.eq_ignore_ascii_case(word))
+    }
+}
+
+impl Default for WordList {
+    fn default() -> Self {
+        WordList::new()
+    }
+}
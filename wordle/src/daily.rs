@@ -0,0 +1,49 @@
+//! Picking the daily word, using the date from the real-time clock.
+//!
+//! The clock gives us text like `2024-01-31 12:34:56`. We turn the date into
+//! a day number, and every machine with the right date gets the same word.
+
+use crate::words::WORD_COUNT;
+
+/// Stepping through the list this many words at a time visits every word
+/// before any comes round again, because it shares no factors with
+/// [`WORD_COUNT`].
+const STRIDE: usize = 7919;
+
+/// Read today's date from the real-time clock, as (year, month, day)
+pub fn today() -> Option<(u16, u8, u8)> {
+    let path = neotron_sdk::path::Path::new("RTC:").ok()?;
+    let rtc = neotron_sdk::File::open(path, neotron_sdk::Flags::empty()).ok()?;
+    let mut buffer = [0u8; 32];
+    let n = rtc.read(&mut buffer).ok()?;
+    let text = core::str::from_utf8(&buffer[0..n]).ok()?;
+    let date = text.trim().split([' ', 'T']).next()?;
+    let mut parts = date.split('-');
+    let year = parts.next()?.parse().ok()?;
+    let month = parts.next()?.parse().ok()?;
+    let day = parts.next()?.parse().ok()?;
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    Some((year, month, day))
+}
+
+/// Count the days from 1 March 2000 to the given date.
+///
+/// Counting from March puts the leap day at the end of the year, which makes
+/// the sums easier.
+pub fn day_number(year: u16, month: u8, day: u8) -> u32 {
+    let (year, month) = if month < 3 {
+        (u32::from(year) - 1, u32::from(month) + 9)
+    } else {
+        (u32::from(year), u32::from(month) - 3)
+    };
+    let years = year.saturating_sub(2000);
+    let days_before_month = (153 * month + 2) / 5;
+    years * 365 + years / 4 - years / 100 + years / 400 + days_before_month + u32::from(day) - 1
+}
+
+/// Which word in the list is the daily word on the given day
+pub fn word_index(day: u32) -> usize {
+    (day as usize).wrapping_mul(STRIDE) % WORD_COUNT
+}
@@ -0,0 +1,450 @@
+//! Game logic for Wordle, the five-letter word-guessing game
+
+#![no_std]
+#![deny(missing_docs)]
+#![deny(unsafe_code)]
+
+use core::fmt::Write;
+
+use neotron_input::{KeyEvent, Keyboard};
+use neotron_sdk::console;
+use neotron_tui::Line;
+
+pub mod daily;
+pub mod stats;
+pub mod words;
+
+use stats::{Stats, MAX_GUESSES};
+use words::{Word, WORD_COUNT, WORD_LEN};
+
+/// The row with the first guess on
+const BOARD_ROW: u8 = 3;
+
+/// The rows of the on-screen keyboard
+const KEYBOARD: [&str; 3] = ["qwertyuiop", "asdfghjkl", "zxcvbnm"];
+
+/// The row with the top of the on-screen keyboard on
+const KEYBOARD_ROW: u8 = 16;
+
+/// How long the longest bar is on the chart of guesses
+const BAR_WIDTH: u32 = 30;
+
+/// The row for messages
+const STATUS_ROW: u8 = 20;
+
+/// The row for the keys the player can press
+const HELP_ROW: u8 = 22;
+
+/// How a letter in a guess scored.
+///
+/// They are in order, so the best thing we know about a letter is the largest.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum Score {
+    /// Not guessed yet
+    Unknown,
+    /// Not in the word
+    Absent,
+    /// In the word, but somewhere else
+    Present,
+    /// In the right place
+    Correct,
+}
+
+impl Score {
+    /// The colours for a tile with this score
+    fn colours(self) -> [console::SgrParam; 2] {
+        match self {
+            Score::Unknown => [console::SgrParam::FgWhite, console::SgrParam::BgBlue],
+            Score::Absent => [console::SgrParam::FgWhite, console::SgrParam::BgBlack],
+            Score::Present => [console::SgrParam::FgBlack, console::SgrParam::BgYellow],
+            Score::Correct => [console::SgrParam::FgBlack, console::SgrParam::BgGreen],
+        }
+    }
+}
+
+/// Score a guess against the secret word.
+///
+/// Letters in the right place are marked first. Then each other letter is
+/// marked present only if the secret has a copy of it which hasn't been
+/// used up, so guessing `geese` for `those` only gets one yellow `e`.
+fn score(guess: &Word, secret: &Word) -> [Score; WORD_LEN] {
+    let mut scores = [Score::Absent; WORD_LEN];
+    let mut unused = [0u8; 26];
+    for idx in 0..WORD_LEN {
+        if guess[idx] == secret[idx] {
+            scores[idx] = Score::Correct;
+        } else {
+            unused[usize::from(secret[idx] - b'a')] += 1;
+        }
+    }
+    for idx in 0..WORD_LEN {
+        let count = &mut unused[usize::from(guess[idx] - b'a')];
+        if scores[idx] != Score::Correct && *count > 0 {
+            *count -= 1;
+            scores[idx] = Score::Present;
+        }
+    }
+    scores
+}
+
+/// How a game ended
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Outcome {
+    /// Guessed in this many goes
+    Won(usize),
+    /// Ran out of guesses, or gave up
+    Lost,
+}
+
+/// One game
+struct Game {
+    secret: Word,
+    guesses: [Word; MAX_GUESSES],
+    scores: [[Score; WORD_LEN]; MAX_GUESSES],
+    /// How many guesses have been made
+    count: usize,
+    /// The guess being typed in
+    input: Word,
+    input_len: usize,
+}
+
+impl Game {
+    /// Start a game with the given secret word
+    const fn new(secret: Word) -> Game {
+        Game {
+            secret,
+            guesses: [[b' '; WORD_LEN]; MAX_GUESSES],
+            scores: [[Score::Unknown; WORD_LEN]; MAX_GUESSES],
+            count: 0,
+            input: [b' '; WORD_LEN],
+            input_len: 0,
+        }
+    }
+
+    /// The best we know about a letter, from all the guesses so far
+    fn letter_score(&self, letter: u8) -> Score {
+        let mut best = Score::Unknown;
+        for (guess, scores) in self.guesses[0..self.count].iter().zip(self.scores.iter()) {
+            for (&guessed, &score) in guess.iter().zip(scores.iter()) {
+                if guessed == letter {
+                    best = best.max(score);
+                }
+            }
+        }
+        best
+    }
+
+    /// In hard mode, every hint so far must be used. Says what's wrong with
+    /// the guess, if anything.
+    fn hard_mode_problem(&self, guess: &Word) -> Option<Line> {
+        let mut message = Line::new();
+        for (previous, scores) in self.guesses[0..self.count].iter().zip(self.scores.iter()) {
+            for idx in 0..WORD_LEN {
+                if scores[idx] == Score::Correct && guess[idx] != previous[idx] {
+                    let _ = write!(
+                        message,
+                        "Letter {} must be {}",
+                        idx + 1,
+                        char::from(previous[idx].to_ascii_uppercase())
+                    );
+                    return Some(message);
+                }
+            }
+            // Each yellow or green letter must appear at least as many times
+            for idx in 0..WORD_LEN {
+                let letter = previous[idx];
+                let needed = (0..WORD_LEN)
+                    .filter(|&i| previous[i] == letter && scores[i] != Score::Absent)
+                    .count();
+                let got = guess.iter().filter(|&&g| g == letter).count();
+                if got < needed {
+                    let _ = write!(
+                        message,
+                        "The guess must contain {}",
+                        char::from(letter.to_ascii_uppercase())
+                    );
+                    return Some(message);
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Represents the Wordle application
+pub struct App {
+    width: u8,
+    stdout: neotron_sdk::File,
+    keyboard: Keyboard,
+    stats: Stats,
+    hard_mode: bool,
+    game: Game,
+}
+
+impl App {
+    /// Make a new Wordle application.
+    ///
+    /// You can give the screen size in characters.
+    pub const fn new(width: u8, _height: u8) -> App {
+        App {
+            width,
+            stdout: neotron_sdk::stdout(),
+            keyboard: Keyboard::new(),
+            stats: Stats::new(),
+            hard_mode: false,
+            game: Game::new([b'a'; WORD_LEN]),
+        }
+    }
+
+    /// Play Wordle, until the user quits
+    pub fn play(&mut self) {
+        self.stats.load();
+        console::cursor_off(&mut self.stdout);
+        let mut seed: u16 = 0x3c91;
+        let mut message = "";
+        loop {
+            self.title_screen(message);
+            message = "";
+            let KeyEvent::Char(key) = self.keyboard.wait() else {
+                continue;
+            };
+            seed = seed.wrapping_add(key as u16);
+            match key {
+                '1' => {
+                    let Some((year, month, day)) = daily::today() else {
+                        message = "There's no clock to tell the date, so no daily word";
+                        continue;
+                    };
+                    let day = daily::day_number(year, month, day);
+                    let counted = self.stats.last_daily != Some(day);
+                    if counted {
+                        self.stats.last_daily = Some(day);
+                    }
+                    self.play_game(daily::word_index(day), counted);
+                    if !counted {
+                        message = "You'd already played today's word, so that didn't count";
+                    }
+                }
+                '2' => {
+                    neotron_sdk::srand(seed);
+                    let index = usize::from(neotron_sdk::rand()) % WORD_COUNT;
+                    self.play_game(index, true);
+                }
+                'h' | 'H' => self.hard_mode = !self.hard_mode,
+                'q' | 'Q' => break,
+                _ => {}
+            }
+        }
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        console::clear_screen(&mut self.stdout);
+        console::move_cursor(&mut self.stdout, console::Position::origin());
+        console::cursor_on(&mut self.stdout);
+    }
+
+    /// Draw the title screen, with the statistics
+    fn title_screen(&mut self, message: &str) {
+        self.clear();
+        self.centre(3, "W O R D L E", true);
+        self.centre(6, "1 - Today's word      ", false);
+        self.centre(7, "2 - A random word     ", false);
+        let mut line = Line::new();
+        let _ = write!(
+            line,
+            "H - Hard mode: {:<8}",
+            if self.hard_mode { "On" } else { "Off" }
+        );
+        self.centre(8, line.as_str(), false);
+        self.centre(9, "Q - Quit              ", false);
+
+        let stats = self.stats;
+        let percent = (stats.won * 100).checked_div(stats.played).unwrap_or(0);
+        let mut line = Line::new();
+        let _ = write!(
+            line,
+            "Played {}  Won {}%  Streak {}  Best streak {}",
+            stats.played, percent, stats.streak, stats.best_streak
+        );
+        self.centre(12, line.as_str(), false);
+        // A bar chart of how many guesses each win took
+        let most = stats.guesses.iter().copied().max().unwrap_or(0).max(1);
+        let col = self.width.saturating_sub(2 + BAR_WIDTH as u8 + 5) / 2;
+        for (idx, count) in stats.guesses.iter().enumerate() {
+            let bar = (count * BAR_WIDTH).div_ceil(most) as usize;
+            self.move_to(14 + idx as u8, col);
+            let _ = write!(self.stdout, "{} ", idx + 1);
+            console::set_sgr(
+                &mut self.stdout,
+                [console::SgrParam::FgBlack, console::SgrParam::BgGreen],
+            );
+            let _ = write!(self.stdout, "{:1$}", "", bar);
+            console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+            let _ = write!(
+                self.stdout,
+                "{:1$} {2:>4}",
+                "",
+                BAR_WIDTH as usize - bar,
+                count
+            );
+        }
+        self.centre(STATUS_ROW + 1, message, false);
+    }
+
+    /// Play one game, with the word at the given position in the list
+    fn play_game(&mut self, index: usize, counted: bool) {
+        let Some(secret) = words::get(index) else {
+            return;
+        };
+        self.game = Game::new(secret);
+        self.clear();
+        self.centre(
+            1,
+            if self.hard_mode {
+                "WORDLE - Hard mode"
+            } else {
+                "WORDLE"
+            },
+            true,
+        );
+        self.centre(
+            HELP_ROW,
+            "Type a word and press Enter. Backspace deletes, Esc gives up.",
+            false,
+        );
+        let outcome = self.guess_loop();
+        if counted {
+            self.stats.record(match outcome {
+                Outcome::Won(guesses) => Some(guesses),
+                Outcome::Lost => None,
+            });
+            self.stats.save();
+        }
+        let mut line = Line::new();
+        match outcome {
+            Outcome::Won(1) => {
+                let _ = write!(line, "Incredible - first time!");
+            }
+            Outcome::Won(guesses) => {
+                let _ = write!(line, "Well done - you got it in {} guesses.", guesses);
+            }
+            Outcome::Lost => {
+                let _ = write!(line, "The word was ");
+                for letter in self.game.secret {
+                    let _ = line.write_char(char::from(letter.to_ascii_uppercase()));
+                }
+            }
+        }
+        self.centre(STATUS_ROW, line.as_str(), false);
+        self.centre(HELP_ROW, "Press any key", false);
+        self.keyboard.wait();
+    }
+
+    /// Take guesses until the game is over
+    fn guess_loop(&mut self) -> Outcome {
+        loop {
+            self.draw();
+            let key = self.keyboard.wait();
+            self.centre(STATUS_ROW, "", false);
+            let game = &mut self.game;
+            match key {
+                KeyEvent::Char(key @ ('a'..='z' | 'A'..='Z')) if game.input_len < WORD_LEN => {
+                    game.input[game.input_len] = (key as u8).to_ascii_lowercase();
+                    game.input_len += 1;
+                }
+                KeyEvent::Backspace if game.input_len > 0 => {
+                    game.input_len -= 1;
+                    game.input[game.input_len] = b' ';
+                }
+                KeyEvent::Escape => return Outcome::Lost,
+                KeyEvent::Enter if game.input_len == WORD_LEN => {
+                    let guess = game.input;
+                    if !words::contains(&guess) {
+                        self.centre(STATUS_ROW, "That's not in the word list", false);
+                        continue;
+                    }
+                    if self.hard_mode {
+                        if let Some(problem) = game.hard_mode_problem(&guess) {
+                            self.centre(STATUS_ROW, problem.as_str(), false);
+                            continue;
+                        }
+                    }
+                    game.guesses[game.count] = guess;
+                    game.scores[game.count] = score(&guess, &game.secret);
+                    game.count += 1;
+                    game.input = [b' '; WORD_LEN];
+                    game.input_len = 0;
+                    if guess == game.secret {
+                        let count = game.count;
+                        self.draw();
+                        return Outcome::Won(count);
+                    }
+                    if game.count == MAX_GUESSES {
+                        self.draw();
+                        return Outcome::Lost;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Draw the guesses and the on-screen keyboard
+    fn draw(&mut self) {
+        let board_col = (self.width.saturating_sub(WORD_LEN as u8 * 4 - 1)) / 2;
+        for row in 0..MAX_GUESSES {
+            let (letters, scores) = if row < self.game.count {
+                (self.game.guesses[row], self.game.scores[row])
+            } else if row == self.game.count {
+                (self.game.input, [Score::Unknown; WORD_LEN])
+            } else {
+                ([b' '; WORD_LEN], [Score::Unknown; WORD_LEN])
+            };
+            for idx in 0..WORD_LEN {
+                self.move_to(BOARD_ROW + row as u8 * 2, board_col + idx as u8 * 4);
+                self.tile(letters[idx], scores[idx]);
+            }
+        }
+        for (row, keys) in KEYBOARD.iter().enumerate() {
+            let col = (self.width.saturating_sub(keys.len() as u8 * 4 - 1)) / 2;
+            self.move_to(KEYBOARD_ROW + row as u8, col);
+            for letter in keys.bytes() {
+                let score = self.game.letter_score(letter);
+                self.tile(letter, score);
+                let _ = self.stdout.write_str(" ");
+            }
+        }
+    }
+
+    /// Draw a letter tile at the cursor
+    fn tile(&mut self, letter: u8, score: Score) {
+        console::set_sgr(&mut self.stdout, score.colours());
+        if score != Score::Absent {
+            console::set_sgr(&mut self.stdout, [console::SgrParam::Bold]);
+        }
+        let _ = write!(self.stdout, " {} ", char::from(letter.to_ascii_uppercase()));
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+    }
+
+    /// Clear the screen
+    fn clear(&mut self) {
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        console::clear_screen(&mut self.stdout);
+    }
+
+    /// Write some text in the middle of a row, clearing whatever was there
+    fn centre(&mut self, row: u8, text: &str, title: bool) {
+        self.move_to(row, 0);
+        if title {
+            console::set_sgr(
+                &mut self.stdout,
+                [console::SgrParam::Bold, console::SgrParam::FgYellow],
+            );
+        }
+        let _ = write!(self.stdout, "{:^1$}", text, usize::from(self.width));
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+    }
+
+    /// Move the cursor
+    fn move_to(&mut self, row: u8, col: u8) {
+        console::move_cursor(&mut self.stdout, console::Position { row, col });
+    }
+}
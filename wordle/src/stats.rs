@@ -0,0 +1,110 @@
+//! How many games have been played and won, kept on disk between sessions.
+
+use core::fmt::Write;
+
+/// Where we keep the statistics
+const STATS_FILE: &str = "WORDLE.DAT";
+
+/// How many guesses you get
+pub const MAX_GUESSES: usize = 6;
+
+/// How many games have been played and won
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct Stats {
+    /// Games played
+    pub played: u32,
+    /// Games won
+    pub won: u32,
+    /// Games won in a row, up to now
+    pub streak: u32,
+    /// The longest streak there has been
+    pub best_streak: u32,
+    /// How many games were won in one guess, two guesses, and so on
+    pub guesses: [u32; MAX_GUESSES],
+    /// The day of the last daily word that was played, so each daily word is
+    /// only counted once
+    pub last_daily: Option<u32>,
+}
+
+impl Stats {
+    /// Make empty statistics
+    pub const fn new() -> Stats {
+        Stats {
+            played: 0,
+            won: 0,
+            streak: 0,
+            best_streak: 0,
+            guesses: [0; MAX_GUESSES],
+            last_daily: None,
+        }
+    }
+
+    /// Count a finished game. `guesses` is `None` if it was lost.
+    pub fn record(&mut self, guesses: Option<usize>) {
+        self.played += 1;
+        match guesses {
+            Some(count) => {
+                self.won += 1;
+                self.streak += 1;
+                self.best_streak = self.best_streak.max(self.streak);
+                if let Some(slot) = self.guesses.get_mut(count.saturating_sub(1)) {
+                    *slot += 1;
+                }
+            }
+            None => self.streak = 0,
+        }
+    }
+
+    /// Read the statistics from disk, if we have any
+    pub fn load(&mut self) {
+        let Ok(path) = neotron_sdk::path::Path::new(STATS_FILE) else {
+            return;
+        };
+        let Ok(file) = neotron_sdk::File::open(path, neotron_sdk::Flags::empty()) else {
+            return;
+        };
+        let mut buffer = [0u8; 128];
+        let Ok(n) = file.read(&mut buffer) else {
+            return;
+        };
+        let Ok(text) = core::str::from_utf8(&buffer[0..n]) else {
+            return;
+        };
+        let mut lines = text.lines().map(|line| line.trim().parse::<u32>().ok());
+        self.played = lines.next().flatten().unwrap_or(0);
+        self.won = lines.next().flatten().unwrap_or(0);
+        self.streak = lines.next().flatten().unwrap_or(0);
+        self.best_streak = lines.next().flatten().unwrap_or(0);
+        for slot in self.guesses.iter_mut() {
+            *slot = lines.next().flatten().unwrap_or(0);
+        }
+        self.last_daily = lines.next().flatten();
+    }
+
+    /// Write the statistics to disk.
+    ///
+    /// One number per line, with `-` for the last daily word if there hasn't
+    /// been one.
+    pub fn save(&self) {
+        let Ok(path) = neotron_sdk::path::Path::new(STATS_FILE) else {
+            return;
+        };
+        let Ok(mut file) = neotron_sdk::File::open(
+            path,
+            neotron_sdk::Flags::WRITE | neotron_sdk::Flags::CREATE | neotron_sdk::Flags::TRUNCATE,
+        ) else {
+            return;
+        };
+        let _ = writeln!(file, "{}", self.played);
+        let _ = writeln!(file, "{}", self.won);
+        let _ = writeln!(file, "{}", self.streak);
+        let _ = writeln!(file, "{}", self.best_streak);
+        for count in self.guesses.iter() {
+            let _ = writeln!(file, "{}", count);
+        }
+        let _ = match self.last_daily {
+            Some(day) => writeln!(file, "{}", day),
+            None => writeln!(file, "-"),
+        };
+    }
+}
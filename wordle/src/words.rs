@@ -0,0 +1,127 @@
+//! The built-in word list.
+//!
+//! The words are kept in alphabetical order and front-coded to save space:
+//! each word is a digit saying how many letters it shares with the word
+//! before, followed by the letters that are different. So `about` then
+//! `above` is stored as `0about3ve`. That brings the list down to less than
+//! four bytes a word.
+
+/// How many words there are in the list
+pub const WORD_COUNT: usize = 1085;
+
+/// How long every word is
+pub const WORD_LEN: usize = 5;
+
+/// One word, in lower case
+pub type Word = [u8; WORD_LEN];
+
+/// All the words we know, front-coded
+const WORDS: &str = "\
+    0about3ve2use1corn2tor2ute1dmit2opt3re2ult1fter1gain2ent2ile2ree1head\
+    1isle1larm2bum2ert2ien3ke3ve2ley3ow2one4g2ter1mber2ong2ple1ngel4r3le3ry\
+    2kle1part2ple4y2ron1rena2gue2ise2oma2ray3ow1side2set1ttic1udio4t1vert\
+    2oid1wake3rd4e0badge3ly2gel2ker2ron2sic4l4s2tch1each3st2gan3in3un2ige3ng\
+    2lly3ow2nch2rry1ible2rth2son1lack3de3me3nk3st3ze2eak3ss2ind3ss2ock3od4m\
+    2ush1oard3st2nus2ost3th3ze2und2xer1rain3nd3ve4o2ead4k3ed2ick3de3ef3ng4k\
+    3sk2oad3ke3om3wn2ush1uddy2ild4t2nch3ny2rst2yer0cabin3le2mel2ndy3oe2rgo\
+    3ol3ry2tch2use1edar1hain4r3lk3rm4t3se2eap3ck3ek4r3ss4t2ick3ef3ld4i4l3me\
+    3rp2oir3rd3se2unk1ider2gar2nch2vic4l1laim3mp3sh4p4s2ean4r2ick3ff3mb2oak\
+    3ck3se3th3ud3ve3wn1oach3st2ral2uch3gh3ld3nt3rt2ver1raft3ne3sh3te3wl3zy\
+    2eam3ek2ime3sp2oss3wd4n2umb3sh4t1ubic2pid2rly3ve1ycle0daily3ry3sy2nce\
+    1ealt3th2but2coy2lay3ta2mon2nim2pth1iary2git2ner3gy2tch2zzy1odge2ing2ubt\
+    3gh2zen1raft3in3ke3ma3wn2ead4m3ss2ied3ft3ll3nk3ve2oop3ve1uchy2sty1warf\
+    1ying0eager3le2rly3th2sel1bony1erie1ight1lbow2der2ite1mail2ber2pty1nemy\
+    2joy2ter3ry1poch1qual3ip1rase2ror1ssay1thic1vent3ry2oke1xact2ile3st2tra\
+    0fable2iry3th2lse2ncy2ult1east2nce2rry2tch2ver1iber2eld3ry2fth4y2ght2nal\
+    2rst2xed1lair3ke3me3nk3sh4k2eet2int2oat3ck3or3ra3ur2uid3te1ocal3us2ggy\
+    2rce3ge4o3th4y3um2und1rame3nk3ud2esh2ont3st3ze2uit1udge2lly2ngi3ny0gauze\
+    2vel1ecko1host1iant2ddy2ven2zmo1lade3nd3re3ss2eam2ide3nt2obe3om3ry3ve\
+    1nome1oing2ose2rge2urd1race3de3nd4t3pe4h3sp4s3te3vy3ze2eat3ed4n2ief3ll\
+    3nd2oan3om3ss3up3ve3wl4n2uel1uard2ess4t2ide2sto0habit2iry2lve2ndy2ppy\
+    2rdy3sh2tch2unt2ven2zel1eard4t3vy2dge2fty2ist2llo2nce2ron1inge2ppo1obby\
+    2ist2ney3or2rde3se2tel2use2ver2wdy1uman3id3or2nch2sky1yena0icing1deal\
+    1gloo1mage1ndex2let2ner2put1rony1ssue1vory0jazzy1elly2wel1oint2lly1udge\
+    2ice4y2mbo0kayak1ebab1haki1iosk2tty1nack2ead3el3lt2ife2ock3wn1oala0label\
+    2nce2pel2rge2ser2tch3er3he2ugh2yer1earn3se4t3ve2gal2mon2vel4r1ight2lac\
+    2mit2nen2ver1lama1ocal2dge2fty2gic2ose2rry2tus2ver2wer1ucky2nar3ch1ying\
+    2ric0macho2gic2jor2ker2ngo3or2ple2rch3sh2son2tch2ybe3or1eant2dal3ia2lon\
+    2rcy3ry2tal1idst2ght2mic2nce3or3us2rth2xed1odel2ist2lar2ney3th2ose2ral\
+    2ssy2tel3or3to2und4t3rn3se3th2vie1ulch2ral3ky2shy3ic0naive2sty2val1erve\
+    2ver2wly1ifty2ght2nja1oble2ise2mad2rth2tch3ed2vel1udge2rse1ylon0oasis\
+    1ccur2ean1ffer2ten1live1nion1pera1rbit2der1ther2ter1ught2nce2tdo1xide\
+    1zone0paddy2int2nel3sy2pal3er2rka3ty2sta4e2tch2use1eace3rl2can2dal2nny\
+    2rch3il2tal1hase2one3to1iano2cky2ece2lot2nch2tch2xel2zza1lace3id4n3ne4k\
+    4t3te3za2ead4t2umb4e4p3sh1oach2int2lar3ka2ppy2rch2uch3nd2wer1rank3wn2ess\
+    2ice3de3me3nt3or3sm3ze2one3of3ud3ve2une1ulse2nch2pil3py2rse0quail3lm3rt\
+    2een3ry3st3ue2ick3et3ll3rk3te2ota4e0rabbi2dar3io2iny3se2lly2nch3ge2pid\
+    2tio2ven2zor1each3dy2bel2fer2lax4y2mit2new1hino2yme1ider3ge2fle2ght2nse\
+    2pen2sky2val3er1oast2bin3ot2cky2deo2gue2ost2ugh3nd3te2wdy2yal1uler2ral\
+    2sty0salad3on3sa3ty2ndy2uce3na2vor1cald4e3rf4y2ene4t2oop3pe3re3ut2rap3ew\
+    3ub1eize2nse2rve2ven1hade3ky3ll3pe3re4k4p3wl2eep4t3lf4l2ift3ne4y3rt2ock\
+    3ot3re4t3ut3wn2rub4g1iege3ve2ght2lky3ly2nce2ren2xth4y1kate2ier3ll3mp3rt\
+    2ull1late2eep2ice3de2ope3th2ump1mall3rt3sh2ear2ile2oke1nack3il3ke3re2eak\
+    2iff2ore3ut3wy1ober2lar3id3ve2nic2rry2und3th1pace3de3re4k3sm3wn2eak4r3ed\
+    3nd4t2ice4y3ke3ll3ne4y2lit2oke3on3re4t3ut2ray3ee3ig1quad3id1tack3ff3ge\
+    3in4r3ke3lk4l3mp3nd3rt3te2eak4m3el3rn2ick3ll3nt2ock3mp3ne3od4l3re4k4m4y\
+    3ve2rap4w4y3ip2uck3dy3ff3nt2yle1ugar2ite2nny2per2rge1wamp3rm2ear4t3ep4t\
+    3pt2ift3rl2ord1yrup0tabby3le2ffy2ken2lly3on2ngo4y2pir2rdy2ste4y2wny1each\
+    3se2eth2mpo2nor3se2pid1hank2eft3ir3me3re3se2ick3ef3ng4k3rd2orn3se2ree4w\
+    3ow2umb4p1iara2dal2ger3ht2mid2red2tle1oast2day2ken2nic2oth2pic2rch2tal\
+    3em2uch3gh2wel4r2xic1race4k3de3il4n3mp3wl2ead4t3nd2ial3be3ck3ed2out2uce\
+    4k3ly3st3th1ulip2mor2ner3ic1weed2ice3rl0udder1lcer1mbra1ncle2der3ue2ion\
+    3ty2til2zip1pper2set1rban1sage2her2ual1tter0vague2lid3or3ue3ve2por2ult\
+    1egan2nom2rse1ideo2gor2nyl2ola2per2rus2sit2tal2vid1ocal2dka2gue2ice2wel\
+    0wafer2ger3on2ltz2ste2tch3er1eary3ve2dge2ird1hale2eat3el3re2ich3ff3le3sk\
+    3te2ole3se1idow3th2eld2ndy2tch3ty1oken2man3en2ody3zy2rld3ry3se4t3th2uld\
+    3nd1rath2eck2ing3st3te2ong3te0yacht1earn3st1ield1oung3th0zebra2sty";
+
+/// Goes through the word list in order
+pub struct Words {
+    data: &'static [u8],
+    word: Word,
+}
+
+impl Iterator for Words {
+    type Item = Word;
+
+    fn next(&mut self) -> Option<Word> {
+        let (&shared, rest) = self.data.split_first()?;
+        let mut pos = usize::from(shared.wrapping_sub(b'0')).min(WORD_LEN);
+        let mut used = 0;
+        for &letter in rest.iter().take_while(|b| b.is_ascii_lowercase()) {
+            if pos < WORD_LEN {
+                self.word[pos] = letter;
+                pos += 1;
+            }
+            used += 1;
+        }
+        self.data = &rest[used..];
+        Some(self.word)
+    }
+}
+
+/// Go through all the words
+pub fn iter() -> Words {
+    Words {
+        data: WORDS.as_bytes(),
+        word: [b'a'; WORD_LEN],
+    }
+}
+
+/// Get a word by its position in the list
+pub fn get(index: usize) -> Option<Word> {
+    iter().nth(index)
+}
+
+/// Is this one of our words? It must be in lower case.
+pub fn contains(word: &Word) -> bool {
+    // The list is sorted, so we can stop once we've gone past it
+    for candidate in iter() {
+        match candidate.cmp(word) {
+            core::cmp::Ordering::Less => {}
+            core::cmp::Ordering::Equal => return true,
+            core::cmp::Ordering::Greater => return false,
+        }
+    }
+    false
+}
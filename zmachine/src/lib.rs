@@ -0,0 +1,344 @@
+//! A Z-machine interpreter, for playing Infocom-style text adventures
+//!
+//! The [`machine`] module runs the story. The [`Console`] here gives it a
+//! screen to print on, with a status line at the top, and files to save to.
+
+#![no_std]
+#![deny(missing_docs)]
+#![deny(unsafe_code)]
+
+use core::fmt::Write;
+
+use neotron_input::{KeyEvent, Keyboard};
+use neotron_sdk::console;
+use neotron_tui::Line;
+
+pub mod machine;
+mod text;
+
+/// The screen and keyboard, as seen by the Z-machine
+pub struct Console {
+    width: u8,
+    height: u8,
+    stdout: neotron_sdk::File,
+    keyboard: Keyboard,
+    /// Where the cursor is
+    row: u8,
+    col: u8,
+    /// How many lines have scrolled past since the player last typed
+    /// something, so we know when to say `[MORE]`
+    lines_since_input: u8,
+    /// The word being printed, so we can wrap lines between words
+    word: [u8; Self::MAX_WORD],
+    word_len: usize,
+    /// The status line, so we can redraw it when the screen scrolls
+    status: [u8; Self::MAX_WIDTH],
+    /// The default file name for saved games
+    save_name: [u8; Self::MAX_FILENAME],
+    save_name_len: usize,
+}
+
+impl Console {
+    /// The longest word we wrap as a unit
+    const MAX_WORD: usize = 40;
+    /// The widest screen we support
+    const MAX_WIDTH: usize = 80;
+    /// The longest file name you can type
+    const MAX_FILENAME: usize = 32;
+
+    /// Make a new console of the given size, for the given story file.
+    ///
+    /// The story file name is used to make up a name for saved games.
+    pub fn new(width: u8, height: u8, story_filename: &str) -> Console {
+        let mut console = Console {
+            width: width.min(Self::MAX_WIDTH as u8),
+            height,
+            stdout: neotron_sdk::stdout(),
+            keyboard: Keyboard::new(),
+            row: 1,
+            col: 0,
+            lines_since_input: 0,
+            word: [0; Self::MAX_WORD],
+            word_len: 0,
+            status: [b' '; Self::MAX_WIDTH],
+            save_name: [0; Self::MAX_FILENAME],
+            save_name_len: 0,
+        };
+        // `ZORK1.Z3` saves to `ZORK1.SAV`
+        let stem = story_filename
+            .rsplit_once('.')
+            .map(|(stem, _ext)| stem)
+            .unwrap_or(story_filename);
+        for byte in stem.bytes().chain(b".SAV".iter().copied()) {
+            if console.save_name_len < Self::MAX_FILENAME {
+                console.save_name[console.save_name_len] = byte;
+                console.save_name_len += 1;
+            }
+        }
+        console
+    }
+
+    /// Clear the screen, and wait for a key so we can seed the random number
+    /// generator.
+    ///
+    /// Returns the seed.
+    pub fn start(&mut self) -> u16 {
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        console::clear_screen(&mut self.stdout);
+        self.status = [b' '; Self::MAX_WIDTH];
+        self.draw_status();
+        self.row = 1;
+        self.col = 0;
+        self.lines_since_input = 0;
+        console::move_cursor(&mut self.stdout, console::Position { row: 1, col: 0 });
+        let _ = self
+            .stdout
+            .write_str("Neotron Z-machine. Press a key to begin.");
+        let mut seed: u16 = 0x5eed;
+        loop {
+            if self.keyboard.read().is_some() {
+                break;
+            }
+            seed = seed.wrapping_add(1);
+            neotron_sdk::delay(core::time::Duration::from_millis(10));
+        }
+        console::move_cursor(&mut self.stdout, console::Position { row: 1, col: 0 });
+        let _ = write!(self.stdout, "{:1$}", "", usize::from(self.width - 1));
+        console::move_cursor(&mut self.stdout, console::Position { row: 1, col: 0 });
+        seed
+    }
+
+    /// Finish off any output and put the console back to normal
+    pub fn finish(&mut self) {
+        self.flush_word();
+        self.newline();
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        console::cursor_on(&mut self.stdout);
+    }
+
+    /// Print the word we've been collecting, wrapping first if needed
+    fn flush_word(&mut self) {
+        if self.word_len == 0 {
+            return;
+        }
+        if usize::from(self.col) + self.word_len >= usize::from(self.width) {
+            self.newline();
+        }
+        let _ = self.stdout.write(&self.word[0..self.word_len]);
+        self.col += self.word_len as u8;
+        self.word_len = 0;
+    }
+
+    /// Move to the start of the next line, scrolling if needed
+    fn newline(&mut self) {
+        let _ = self.stdout.write_str("\n");
+        self.col = 0;
+        if self.row < self.height - 1 {
+            self.row += 1;
+        } else {
+            // The status line has scrolled off the top
+            self.draw_status();
+        }
+        self.lines_since_input += 1;
+        if self.lines_since_input >= self.height - 3 {
+            self.more();
+        }
+    }
+
+    /// Wait for the player to read the screen before scrolling any more
+    fn more(&mut self) {
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reverse]);
+        let _ = self.stdout.write_str("[MORE]");
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        self.keyboard.wait();
+        let _ = self.stdout.write_str("\r      \r");
+        self.lines_since_input = 0;
+    }
+
+    /// Draw the status line at the top of the screen
+    fn draw_status(&mut self) {
+        console::move_cursor(&mut self.stdout, console::Position::origin());
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reverse]);
+        let _ = self.stdout.write(&self.status[0..usize::from(self.width)]);
+        console::set_sgr(&mut self.stdout, [console::SgrParam::Reset]);
+        console::move_cursor(
+            &mut self.stdout,
+            console::Position {
+                row: self.row,
+                col: self.col,
+            },
+        );
+    }
+
+    /// Ask the player for a file name, offering the default save name.
+    ///
+    /// Returns `None` if they pressed Escape.
+    fn ask_filename<'a>(&mut self, prompt: &str, buffer: &'a mut [u8]) -> Option<&'a str> {
+        self.flush_word();
+        let _ = write!(self.stdout, "{} [", prompt);
+        let _ = self.stdout.write(&self.save_name[0..self.save_name_len]);
+        let _ = self.stdout.write_str("]: ");
+        let mut len = machine::Io::read_line(self, buffer);
+        if buffer
+            .get(..len)
+            .map(|b| b.contains(&0x1B))
+            .unwrap_or(false)
+        {
+            return None;
+        }
+        if len == 0 {
+            len = self.save_name_len.min(buffer.len());
+            buffer[0..len].copy_from_slice(&self.save_name[0..len]);
+        }
+        core::str::from_utf8(&buffer[0..len]).ok()
+    }
+
+    /// Open a file for saving or restoring
+    fn open(
+        filename: &str,
+        flags: neotron_sdk::Flags,
+    ) -> Result<neotron_sdk::File, neotron_sdk::Error> {
+        let path = neotron_sdk::path::Path::new(filename)?;
+        neotron_sdk::File::open(path, flags)
+    }
+}
+
+impl machine::Io for Console {
+    fn print_char(&mut self, ch: u8) {
+        match ch {
+            b'\n' | 13 => {
+                self.flush_word();
+                self.newline();
+            }
+            b' ' => {
+                self.flush_word();
+                if self.col + 1 >= self.width {
+                    self.newline();
+                } else {
+                    let _ = self.stdout.write_str(" ");
+                    self.col += 1;
+                }
+            }
+            33..=126 => {
+                if self.word_len == Self::MAX_WORD {
+                    self.flush_word();
+                }
+                self.word[self.word_len] = ch;
+                self.word_len += 1;
+            }
+            _ => {
+                // Not something we can show
+            }
+        }
+    }
+
+    fn show_status(&mut self, location: &[u8], status: machine::Status) {
+        let width = usize::from(self.width);
+        self.status = [b' '; Self::MAX_WIDTH];
+        let mut right = Line::new();
+        let _ = match status {
+            machine::Status::Score(score, turns) => {
+                write!(right, "Score: {}  Moves: {} ", score, turns)
+            }
+            machine::Status::Time(hours, minutes) => {
+                let (hours_12, suffix) = match hours {
+                    0 => (12, "am"),
+                    1..=11 => (hours, "am"),
+                    12 => (12, "pm"),
+                    _ => (hours - 12, "pm"),
+                };
+                write!(right, "Time: {}:{:02} {} ", hours_12, minutes, suffix)
+            }
+        };
+        let right = right.as_str().as_bytes();
+        let right_start = width.saturating_sub(right.len());
+        for (dest, src) in self.status[1..right_start.saturating_sub(1)]
+            .iter_mut()
+            .zip(location.iter())
+        {
+            *dest = *src;
+        }
+        self.status[right_start..width].copy_from_slice(&right[0..width - right_start]);
+        self.draw_status();
+    }
+
+    fn read_line(&mut self, buffer: &mut [u8]) -> usize {
+        self.flush_word();
+        console::cursor_on(&mut self.stdout);
+        // Leave room for the cursor at the end of the line
+        let max_len = buffer
+            .len()
+            .min(usize::from(self.width.saturating_sub(self.col + 1)));
+        let mut len = 0;
+        loop {
+            match self.keyboard.wait() {
+                KeyEvent::Enter => break,
+                KeyEvent::Escape => {
+                    // Escape - only used at the file name prompts
+                    if len < buffer.len() {
+                        buffer[len] = 0x1B;
+                        len += 1;
+                    }
+                    break;
+                }
+                KeyEvent::Backspace if len > 0 => {
+                    len -= 1;
+                    self.col -= 1;
+                    let _ = self.stdout.write_str("\u{0008} \u{0008}");
+                }
+                KeyEvent::Char(key @ ' '..='~') if len < max_len => {
+                    buffer[len] = key as u8;
+                    len += 1;
+                    self.col += 1;
+                    let _ = self.stdout.write_char(key);
+                }
+                _ => {}
+            }
+        }
+        console::cursor_off(&mut self.stdout);
+        self.newline();
+        self.lines_since_input = 0;
+        len
+    }
+
+    fn save(&mut self, chunks: &[&[u8]]) -> bool {
+        let mut buffer = [0u8; Self::MAX_FILENAME];
+        let Some(filename) = self.ask_filename("Save to file", &mut buffer) else {
+            return false;
+        };
+        let flags =
+            neotron_sdk::Flags::WRITE | neotron_sdk::Flags::CREATE | neotron_sdk::Flags::TRUNCATE;
+        let Ok(file) = Self::open(filename, flags) else {
+            return false;
+        };
+        chunks.iter().all(|chunk| file.write(chunk).is_ok())
+    }
+
+    fn restore(&mut self, chunks: &mut [&mut [u8]]) -> bool {
+        let mut buffer = [0u8; Self::MAX_FILENAME];
+        let Some(filename) = self.ask_filename("Restore from file", &mut buffer) else {
+            return false;
+        };
+        let Ok(file) = Self::open(filename, neotron_sdk::Flags::empty()) else {
+            return false;
+        };
+        let expected: usize = chunks.iter().map(|c| c.len()).sum();
+        match file.seek_end() {
+            Ok(len) if len == expected as u64 => {}
+            _ => return false,
+        }
+        if file.seek_set(0).is_err() {
+            return false;
+        }
+        for chunk in chunks.iter_mut() {
+            let mut filled = 0;
+            while filled < chunk.len() {
+                match file.read(&mut chunk[filled..]) {
+                    Ok(0) | Err(_) => return false,
+                    Ok(n) => filled += n,
+                }
+            }
+        }
+        true
+    }
+}
@@ -0,0 +1,1229 @@
+//! The Z-machine itself.
+//!
+//! This is a version 3 Z-machine, as described in the Z-Machine Standards
+//! Document 1.1. It runs the story file held in memory, talking to the user
+//! through something that implements the [`Io`] trait.
+
+use crate::text;
+
+/// Things that can go wrong running a story
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// We only support version 3 story files
+    UnsupportedVersion(u8),
+    /// The story file is too big for our memory
+    StoryTooBig,
+    /// We found an instruction we don't understand, at the given address
+    BadOpcode(u8, u32),
+    /// Too many nested routine calls
+    StackOverflow,
+    /// Tried to pop from an empty stack
+    StackUnderflow,
+    /// Tried to read or write somewhere outside memory
+    BadAddress(u32),
+    /// Tried to use object zero, or an object that doesn't exist
+    BadObject(u16),
+    /// Tried to divide by zero
+    DivideByZero,
+    /// Restored a save file from a different story
+    BadSaveFile,
+}
+
+/// Why the story stopped running
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Exit {
+    /// The story executed `quit`
+    Quit,
+    /// The story executed `restart`, so the story file needs loading again
+    Restart,
+}
+
+/// What the status line should show on the right-hand side
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Status {
+    /// A score and a count of turns
+    Score(i16, u16),
+    /// A time of day, in hours and minutes
+    Time(u16, u16),
+}
+
+/// How the Z-machine talks to the outside world
+pub trait Io {
+    /// Print one ZSCII character
+    fn print_char(&mut self, ch: u8);
+
+    /// Update the status line.
+    ///
+    /// `location` is the name of the current location, in ZSCII.
+    fn show_status(&mut self, location: &[u8], status: Status);
+
+    /// Read a line of input into `buffer`, in lower case.
+    ///
+    /// Returns how many bytes were read.
+    fn read_line(&mut self, buffer: &mut [u8]) -> usize;
+
+    /// Save the game. Each of the `chunks` should be written out in order.
+    ///
+    /// Returns `true` on success.
+    fn save(&mut self, chunks: &[&[u8]]) -> bool;
+
+    /// Restore a game. Each of the `chunks` should be filled in order, with
+    /// the data written by [`Io::save`].
+    ///
+    /// Returns `true` on success. If the saved data is not exactly the right
+    /// length, return `false` without touching `chunks`.
+    fn restore(&mut self, chunks: &mut [&mut [u8]]) -> bool;
+}
+
+/// The operand types
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum OperandType {
+    Large,
+    Small,
+    Variable,
+    Omitted,
+}
+
+impl OperandType {
+    /// Decode a two-bit operand type
+    fn from_bits(bits: u8) -> OperandType {
+        match bits & 3 {
+            0 => OperandType::Large,
+            1 => OperandType::Small,
+            2 => OperandType::Variable,
+            _ => OperandType::Omitted,
+        }
+    }
+}
+
+/// The kinds of instruction
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Form {
+    Op0,
+    Op1,
+    Op2,
+    Var,
+}
+
+/// One routine call on the call stack
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct Frame {
+    /// Where to carry on when this routine returns
+    return_pc: u32,
+    /// How deep the evaluation stack was when this routine was called
+    stack_base: u16,
+    /// The routine's local variables
+    locals: [u16; 15],
+    /// How many local variables the routine has
+    num_locals: u8,
+    /// Which variable to store the result in, if any
+    store: Option<u8>,
+}
+
+impl Frame {
+    /// How many bytes each frame takes up in a save file
+    const SAVED_LEN: usize = 4 + 2 + 30 + 1 + 2;
+
+    /// An unused frame
+    const EMPTY: Frame = Frame {
+        return_pc: 0,
+        stack_base: 0,
+        locals: [0; 15],
+        num_locals: 0,
+        store: None,
+    };
+
+    /// Write this frame into a save buffer
+    fn save(&self, out: &mut [u8]) {
+        out[0..4].copy_from_slice(&self.return_pc.to_be_bytes());
+        out[4..6].copy_from_slice(&self.stack_base.to_be_bytes());
+        for (idx, local) in self.locals.iter().enumerate() {
+            out[6 + idx * 2..8 + idx * 2].copy_from_slice(&local.to_be_bytes());
+        }
+        out[36] = self.num_locals;
+        out[37] = u8::from(self.store.is_some());
+        out[38] = self.store.unwrap_or(0);
+    }
+
+    /// Read a frame from a save buffer
+    fn restore(data: &[u8]) -> Frame {
+        let mut frame = Frame::EMPTY;
+        frame.return_pc = u32::from_be_bytes([data[0], data[1], data[2], data[3]]);
+        frame.stack_base = u16::from_be_bytes([data[4], data[5]]);
+        for (idx, local) in frame.locals.iter_mut().enumerate() {
+            *local = u16::from_be_bytes([data[6 + idx * 2], data[7 + idx * 2]]);
+        }
+        frame.num_locals = data[36].min(15);
+        frame.store = if data[37] != 0 { Some(data[38]) } else { None };
+        frame
+    }
+}
+
+/// A Z-machine, with its memory and stacks
+pub struct Machine {
+    /// The story file, which the game modifies as it runs
+    memory: [u8; Self::MAX_STORY],
+    /// How many bytes of `memory` were loaded
+    story_len: usize,
+    pc: u32,
+    stack: [u16; Self::STACK_SIZE],
+    sp: usize,
+    frames: [Frame; Self::MAX_FRAMES],
+    frame_count: usize,
+    /// State for our random number generator
+    random_state: u32,
+    /// When `true`, the random number generator counts up (for testing)
+    random_predictable: Option<(u16, u16)>,
+    /// Where output stream 3 is writing to, if it's selected
+    stream3: Option<usize>,
+    /// Scratch space for saving and restoring the stacks
+    scratch: [u8; Self::SCRATCH_LEN],
+}
+
+impl Machine {
+    /// Version 3 story files are at most 128 KiB
+    const MAX_STORY: usize = 128 * 1024;
+    /// How many words the evaluation stack can hold
+    const STACK_SIZE: usize = 1024;
+    /// How deeply routine calls can be nested
+    const MAX_FRAMES: usize = 64;
+    /// Save header (magic, release, checksum, pc, sp, frame count) length
+    const SAVE_HEADER_LEN: usize = 4 + 2 + 2 + 4 + 2 + 2;
+    /// How big the stack part of a save file can be
+    const SCRATCH_LEN: usize =
+        Self::SAVE_HEADER_LEN + Self::MAX_FRAMES * Frame::SAVED_LEN + Self::STACK_SIZE * 2;
+    /// Identifies our save files
+    const SAVE_MAGIC: [u8; 4] = *b"NZS1";
+
+    /// Make a new, empty, Z-machine
+    pub const fn new() -> Machine {
+        Machine {
+            memory: [0; Self::MAX_STORY],
+            story_len: 0,
+            pc: 0,
+            stack: [0; Self::STACK_SIZE],
+            sp: 0,
+            frames: [Frame::EMPTY; Self::MAX_FRAMES],
+            frame_count: 0,
+            random_state: 1,
+            random_predictable: None,
+            stream3: None,
+            scratch: [0; Self::SCRATCH_LEN],
+        }
+    }
+
+    /// Get the buffer the story file should be loaded into
+    pub fn story_buffer(&mut self) -> &mut [u8] {
+        &mut self.memory
+    }
+
+    /// Get ready to run a story file, which has been loaded into the
+    /// [`Machine::story_buffer`].
+    pub fn start(&mut self, story_len: usize, seed: u16) -> Result<(), Error> {
+        if story_len >= Self::MAX_STORY {
+            return Err(Error::StoryTooBig);
+        }
+        let version = self.memory[0];
+        if version != 3 {
+            return Err(Error::UnsupportedVersion(version));
+        }
+        self.story_len = story_len;
+        // Status line is available, screen splitting isn't, and the font is
+        // fixed pitch
+        self.memory[1] &= !0x70;
+        // Interpreter number (IBM PC), version, and standard revision 1.1
+        self.memory[0x1E] = 6;
+        self.memory[0x1F] = b'N';
+        self.memory[0x32] = 1;
+        self.memory[0x33] = 1;
+        self.pc = u32::from(self.header_word(0x06));
+        self.sp = 0;
+        self.frame_count = 0;
+        self.random_state = u32::from(seed) | 1;
+        self.random_predictable = None;
+        self.stream3 = None;
+        Ok(())
+    }
+
+    /// Run the story until it quits or restarts
+    pub fn run(&mut self, io: &mut dyn Io) -> Result<Exit, Error> {
+        loop {
+            if let Some(exit) = self.step(io)? {
+                return Ok(exit);
+            }
+        }
+    }
+
+    // ----------------------------------------------------------------------
+    // Memory access
+    // ----------------------------------------------------------------------
+
+    /// Read a word from the header
+    fn header_word(&self, addr: usize) -> u16 {
+        u16::from_be_bytes([self.memory[addr], self.memory[addr + 1]])
+    }
+
+    /// Read a byte from memory
+    fn read_byte(&self, addr: u32) -> Result<u8, Error> {
+        self.memory
+            .get(addr as usize)
+            .copied()
+            .ok_or(Error::BadAddress(addr))
+    }
+
+    /// Read a word from memory
+    fn read_word(&self, addr: u32) -> Result<u16, Error> {
+        Ok(u16::from_be_bytes([
+            self.read_byte(addr)?,
+            self.read_byte(addr + 1)?,
+        ]))
+    }
+
+    /// Write a byte to dynamic memory
+    fn write_byte(&mut self, addr: u32, value: u8) -> Result<(), Error> {
+        if addr >= u32::from(self.header_word(0x0E)) {
+            return Err(Error::BadAddress(addr));
+        }
+        self.memory[addr as usize] = value;
+        Ok(())
+    }
+
+    /// Write a word to dynamic memory
+    fn write_word(&mut self, addr: u32, value: u16) -> Result<(), Error> {
+        let bytes = value.to_be_bytes();
+        self.write_byte(addr, bytes[0])?;
+        self.write_byte(addr + 1, bytes[1])
+    }
+
+    /// Read the next byte of the instruction stream
+    fn next_byte(&mut self) -> Result<u8, Error> {
+        let value = self.read_byte(self.pc)?;
+        self.pc += 1;
+        Ok(value)
+    }
+
+    /// Read the next word of the instruction stream
+    fn next_word(&mut self) -> Result<u16, Error> {
+        let value = self.read_word(self.pc)?;
+        self.pc += 2;
+        Ok(value)
+    }
+
+    // ----------------------------------------------------------------------
+    // Variables and the stack
+    // ----------------------------------------------------------------------
+
+    /// Push a value on to the evaluation stack
+    fn push(&mut self, value: u16) -> Result<(), Error> {
+        if self.sp == Self::STACK_SIZE {
+            return Err(Error::StackOverflow);
+        }
+        self.stack[self.sp] = value;
+        self.sp += 1;
+        Ok(())
+    }
+
+    /// Pop a value from the evaluation stack
+    fn pop(&mut self) -> Result<u16, Error> {
+        let base = self
+            .current_frame()
+            .map(|f| usize::from(f.stack_base))
+            .unwrap_or(0);
+        if self.sp <= base {
+            return Err(Error::StackUnderflow);
+        }
+        self.sp -= 1;
+        Ok(self.stack[self.sp])
+    }
+
+    /// Get the frame for the currently running routine, if any
+    fn current_frame(&self) -> Option<&Frame> {
+        self.frame_count.checked_sub(1).map(|idx| &self.frames[idx])
+    }
+
+    /// Address of a global variable
+    fn global_addr(&self, var: u8) -> u32 {
+        u32::from(self.header_word(0x0C)) + 2 * u32::from(var - 16)
+    }
+
+    /// Read a variable. Variable 0 pops the stack.
+    fn read_var(&mut self, var: u8) -> Result<u16, Error> {
+        match var {
+            0 => self.pop(),
+            1..=15 => Ok(self
+                .current_frame()
+                .map(|f| f.locals[usize::from(var - 1)])
+                .unwrap_or(0)),
+            _ => self.read_word(self.global_addr(var)),
+        }
+    }
+
+    /// Write a variable. Variable 0 pushes on to the stack.
+    fn write_var(&mut self, var: u8, value: u16) -> Result<(), Error> {
+        match var {
+            0 => self.push(value),
+            1..=15 => {
+                if let Some(idx) = self.frame_count.checked_sub(1) {
+                    self.frames[idx].locals[usize::from(var - 1)] = value;
+                }
+                Ok(())
+            }
+            _ => self.write_word(self.global_addr(var), value),
+        }
+    }
+
+    /// Read a variable by reference. Variable 0 reads the top of the stack
+    /// without popping it.
+    fn peek_var(&mut self, var: u8) -> Result<u16, Error> {
+        if var == 0 {
+            let value = self.pop()?;
+            self.push(value)?;
+            Ok(value)
+        } else {
+            self.read_var(var)
+        }
+    }
+
+    /// Write a variable by reference. Variable 0 replaces the top of the
+    /// stack, rather than pushing.
+    fn poke_var(&mut self, var: u8, value: u16) -> Result<(), Error> {
+        if var == 0 {
+            self.pop()?;
+        }
+        self.write_var(var, value)
+    }
+
+    // ----------------------------------------------------------------------
+    // Routines and branches
+    // ----------------------------------------------------------------------
+
+    /// Call the routine at the packed address, with the given arguments
+    fn call(&mut self, packed: u16, args: &[u16], store: Option<u8>) -> Result<(), Error> {
+        if packed == 0 {
+            // Calling address zero does nothing and returns false
+            if let Some(var) = store {
+                self.write_var(var, 0)?;
+            }
+            return Ok(());
+        }
+        if self.frame_count == Self::MAX_FRAMES {
+            return Err(Error::StackOverflow);
+        }
+        let addr = u32::from(packed) * 2;
+        let num_locals = self.read_byte(addr)?.min(15);
+        let mut frame = Frame {
+            return_pc: self.pc,
+            stack_base: self.sp as u16,
+            locals: [0; 15],
+            num_locals,
+            store,
+        };
+        for idx in 0..usize::from(num_locals) {
+            frame.locals[idx] = self.read_word(addr + 1 + 2 * idx as u32)?;
+        }
+        for (local, arg) in frame
+            .locals
+            .iter_mut()
+            .zip(args.iter())
+            .take(usize::from(num_locals))
+        {
+            *local = *arg;
+        }
+        self.frames[self.frame_count] = frame;
+        self.frame_count += 1;
+        self.pc = addr + 1 + 2 * u32::from(num_locals);
+        Ok(())
+    }
+
+    /// Return from the current routine with the given value
+    fn ret(&mut self, value: u16) -> Result<(), Error> {
+        let Some(idx) = self.frame_count.checked_sub(1) else {
+            return Err(Error::StackUnderflow);
+        };
+        let frame = self.frames[idx];
+        self.frame_count = idx;
+        self.sp = usize::from(frame.stack_base);
+        self.pc = frame.return_pc;
+        if let Some(var) = frame.store {
+            self.write_var(var, value)?;
+        }
+        Ok(())
+    }
+
+    /// Read the branch data and branch if `condition` matches it
+    fn branch(&mut self, condition: bool) -> Result<(), Error> {
+        let first = self.next_byte()?;
+        let on_true = first & 0x80 != 0;
+        let offset: i16 = if first & 0x40 != 0 {
+            i16::from(first & 0x3F)
+        } else {
+            let second = self.next_byte()?;
+            // Sign-extend the 14-bit offset
+            let raw = (u16::from(first & 0x3F) << 8) | u16::from(second);
+            ((raw << 2) as i16) >> 2
+        };
+        if condition == on_true {
+            match offset {
+                0 => self.ret(0)?,
+                1 => self.ret(1)?,
+                _ => {
+                    self.pc = (self.pc as i32 + i32::from(offset) - 2) as u32;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Read the variable number to store a result in, and store it
+    fn store(&mut self, value: u16) -> Result<(), Error> {
+        let var = self.next_byte()?;
+        self.write_var(var, value)
+    }
+
+    // ----------------------------------------------------------------------
+    // Objects
+    // ----------------------------------------------------------------------
+
+    /// Address of an object's entry in the object table
+    fn object_addr(&self, object: u16) -> Result<u32, Error> {
+        if object == 0 || object > 255 {
+            return Err(Error::BadObject(object));
+        }
+        Ok(u32::from(self.header_word(0x0A)) + 31 * 2 + (u32::from(object) - 1) * 9)
+    }
+
+    /// Get an object's parent, sibling or child
+    fn relative(&self, object: u16, offset: u32) -> Result<u16, Error> {
+        Ok(u16::from(
+            self.read_byte(self.object_addr(object)? + offset)?,
+        ))
+    }
+
+    /// Set an object's parent, sibling or child
+    fn set_relative(&mut self, object: u16, offset: u32, value: u16) -> Result<(), Error> {
+        let addr = self.object_addr(object)? + offset;
+        self.write_byte(addr, value as u8)
+    }
+
+    /// Offset of the parent in an object entry
+    const PARENT: u32 = 4;
+    /// Offset of the sibling in an object entry
+    const SIBLING: u32 = 5;
+    /// Offset of the child in an object entry
+    const CHILD: u32 = 6;
+
+    /// Find the byte and mask for an attribute
+    fn attr_location(&self, object: u16, attr: u16) -> Result<(u32, u8), Error> {
+        let attr = attr.min(31);
+        Ok((
+            self.object_addr(object)? + u32::from(attr / 8),
+            0x80 >> (attr % 8),
+        ))
+    }
+
+    /// Take an object out of the tree
+    fn remove_object(&mut self, object: u16) -> Result<(), Error> {
+        let parent = self.relative(object, Self::PARENT)?;
+        if parent == 0 {
+            return Ok(());
+        }
+        let sibling = self.relative(object, Self::SIBLING)?;
+        let first = self.relative(parent, Self::CHILD)?;
+        if first == object {
+            self.set_relative(parent, Self::CHILD, sibling)?;
+        } else {
+            let mut previous = first;
+            while previous != 0 {
+                let next = self.relative(previous, Self::SIBLING)?;
+                if next == object {
+                    self.set_relative(previous, Self::SIBLING, sibling)?;
+                    break;
+                }
+                previous = next;
+            }
+        }
+        self.set_relative(object, Self::PARENT, 0)?;
+        self.set_relative(object, Self::SIBLING, 0)
+    }
+
+    /// Address of the object's property table
+    fn property_table(&self, object: u16) -> Result<u32, Error> {
+        Ok(u32::from(self.read_word(self.object_addr(object)? + 7)?))
+    }
+
+    /// Address of the first property in an object's property table
+    fn first_property(&self, object: u16) -> Result<u32, Error> {
+        let table = self.property_table(object)?;
+        let name_words = u32::from(self.read_byte(table)?);
+        Ok(table + 1 + name_words * 2)
+    }
+
+    /// Find a property. Returns the address of its size byte, and its length.
+    fn find_property(&self, object: u16, property: u16) -> Result<Option<(u32, u16)>, Error> {
+        let mut addr = self.first_property(object)?;
+        loop {
+            let size = self.read_byte(addr)?;
+            if size == 0 {
+                return Ok(None);
+            }
+            let number = u16::from(size & 0x1F);
+            let len = u16::from(size >> 5) + 1;
+            if number == property {
+                return Ok(Some((addr, len)));
+            }
+            if number < property {
+                // Properties are stored in descending order
+                return Ok(None);
+            }
+            addr += 1 + u32::from(len);
+        }
+    }
+
+    /// Print the short name of an object
+    fn print_object(&mut self, object: u16, io: &mut dyn Io) -> Result<(), Error> {
+        let table = self.property_table(object)?;
+        // Objects can have no name at all
+        if self.read_byte(table)? != 0 {
+            self.print_string(table + 1, io)?;
+        }
+        Ok(())
+    }
+
+    // ----------------------------------------------------------------------
+    // Text
+    // ----------------------------------------------------------------------
+
+    /// Print one character, to the screen or to memory
+    fn print_char(&mut self, ch: u8, io: &mut dyn Io) {
+        if let Some(table) = self.stream3 {
+            let count = usize::from(self.header_word_at(table));
+            let addr = table + 2 + count;
+            if addr < self.story_len {
+                self.memory[addr] = if ch == b'\n' { 13 } else { ch };
+                let bytes = (count as u16 + 1).to_be_bytes();
+                self.memory[table] = bytes[0];
+                self.memory[table + 1] = bytes[1];
+            }
+        } else {
+            io.print_char(ch);
+        }
+    }
+
+    /// Read a word from anywhere in memory, without bounds errors
+    fn header_word_at(&self, addr: usize) -> u16 {
+        match self.memory.get(addr..addr + 2) {
+            Some(b) => u16::from_be_bytes([b[0], b[1]]),
+            None => 0,
+        }
+    }
+
+    /// Print the Z-string at the given address, returning the address after it
+    fn print_string(&mut self, addr: u32, io: &mut dyn Io) -> Result<u32, Error> {
+        if addr as usize >= self.story_len {
+            return Err(Error::BadAddress(addr));
+        }
+        // Decode into a small buffer first, as we can't borrow memory while
+        // writing to it (for output stream 3)
+        let mut buffer = [0u8; 64];
+        let mut len = 0;
+        let mut overflow = false;
+        let end = text::decode(&self.memory[0..self.story_len], addr as usize, &mut |ch| {
+            if len < buffer.len() {
+                buffer[len] = ch;
+                len += 1;
+            } else {
+                overflow = true;
+            }
+        });
+        if !overflow {
+            for &ch in &buffer[0..len] {
+                self.print_char(ch, io);
+            }
+        } else if self.stream3.is_none() {
+            // Long strings go straight to the screen
+            text::decode(&self.memory[0..self.story_len], addr as usize, &mut |ch| {
+                io.print_char(ch)
+            });
+        } else {
+            // Long strings to memory are done in two passes
+            let mut skip = 0;
+            loop {
+                let mut idx = 0;
+                len = 0;
+                text::decode(&self.memory[0..self.story_len], addr as usize, &mut |ch| {
+                    if idx >= skip && len < buffer.len() {
+                        buffer[len] = ch;
+                        len += 1;
+                    }
+                    idx += 1;
+                });
+                for &ch in &buffer[0..len] {
+                    self.print_char(ch, io);
+                }
+                skip += len;
+                if len < buffer.len() {
+                    break;
+                }
+            }
+        }
+        Ok(end as u32)
+    }
+
+    /// Print a signed number
+    fn print_number(&mut self, value: i16, io: &mut dyn Io) {
+        let mut digits = [0u8; 6];
+        let mut len = 0;
+        let mut magnitude = i32::from(value).unsigned_abs();
+        loop {
+            digits[len] = b'0' + (magnitude % 10) as u8;
+            len += 1;
+            magnitude /= 10;
+            if magnitude == 0 {
+                break;
+            }
+        }
+        if value < 0 {
+            self.print_char(b'-', io);
+        }
+        for idx in (0..len).rev() {
+            self.print_char(digits[idx], io);
+        }
+    }
+
+    /// Update the status line
+    fn show_status(&mut self, io: &mut dyn Io) -> Result<(), Error> {
+        let location = self.read_word(self.global_addr(16))?;
+        let first = self.read_word(self.global_addr(17))?;
+        let second = self.read_word(self.global_addr(18))?;
+        let mut name = [0u8; 64];
+        let mut len = 0;
+        if location != 0 && self.read_byte(self.property_table(location)?)? != 0 {
+            let table = self.property_table(location)? as usize;
+            text::decode(&self.memory[0..self.story_len], table + 1, &mut |ch| {
+                if len < name.len() {
+                    name[len] = ch;
+                    len += 1;
+                }
+            });
+        }
+        let status = if self.memory[1] & 0x02 != 0 {
+            Status::Time(first, second)
+        } else {
+            Status::Score(first as i16, second)
+        };
+        io.show_status(&name[0..len], status);
+        Ok(())
+    }
+
+    // ----------------------------------------------------------------------
+    // Input
+    // ----------------------------------------------------------------------
+
+    /// Read a line of text and split it into words
+    fn read(&mut self, text_addr: u16, parse_addr: u16, io: &mut dyn Io) -> Result<(), Error> {
+        self.show_status(io)?;
+        let text_addr = u32::from(text_addr);
+        let parse_addr = u32::from(parse_addr);
+        let max_len = usize::from(self.read_byte(text_addr)?).saturating_sub(1);
+        let mut line = [0u8; 256];
+        let len = io.read_line(&mut line[0..max_len.min(255)]);
+        for (idx, ch) in line[0..len].iter().enumerate() {
+            self.write_byte(text_addr + 1 + idx as u32, ch.to_ascii_lowercase())?;
+        }
+        self.write_byte(text_addr + 1 + len as u32, 0)?;
+        if parse_addr != 0 {
+            self.tokenise(&line[0..len], parse_addr)?;
+        }
+        Ok(())
+    }
+
+    /// Split a line into words and look them up in the dictionary
+    fn tokenise(&mut self, line: &[u8], parse_addr: u32) -> Result<(), Error> {
+        let dictionary = u32::from(self.header_word(0x08));
+        let num_separators = self.read_byte(dictionary)?;
+        let separators_addr = dictionary + 1;
+        let is_separator = |machine: &Machine, ch: u8| -> Result<bool, Error> {
+            for idx in 0..u32::from(num_separators) {
+                if machine.read_byte(separators_addr + idx)? == ch {
+                    return Ok(true);
+                }
+            }
+            Ok(false)
+        };
+        let max_words = self.read_byte(parse_addr)?;
+        let mut count = 0u8;
+        let mut start = 0;
+        while start < line.len() && count < max_words {
+            let ch = line[start].to_ascii_lowercase();
+            if ch == b' ' {
+                start += 1;
+                continue;
+            }
+            let mut end = start + 1;
+            if !is_separator(self, ch)? {
+                while end < line.len()
+                    && line[end] != b' '
+                    && !is_separator(self, line[end].to_ascii_lowercase())?
+                {
+                    end += 1;
+                }
+            }
+            let mut word = [0u8; 16];
+            let word_len = (end - start).min(word.len());
+            for (dest, src) in word.iter_mut().zip(line[start..start + word_len].iter()) {
+                *dest = src.to_ascii_lowercase();
+            }
+            let entry = self.lookup(&word[0..word_len])?;
+            let block = parse_addr + 2 + u32::from(count) * 4;
+            self.write_word(block, entry)?;
+            self.write_byte(block + 2, (end - start) as u8)?;
+            // Position is counted from the start of the text buffer
+            self.write_byte(block + 3, (start + 1) as u8)?;
+            count += 1;
+            start = end;
+        }
+        self.write_byte(parse_addr + 1, count)
+    }
+
+    /// Find a word in the dictionary, returning its address or zero
+    fn lookup(&self, word: &[u8]) -> Result<u16, Error> {
+        let encoded = text::encode(word);
+        let dictionary = u32::from(self.header_word(0x08));
+        let num_separators = u32::from(self.read_byte(dictionary)?);
+        let entry_len = u32::from(self.read_byte(dictionary + 1 + num_separators)?);
+        let num_entries = self.read_word(dictionary + 2 + num_separators)? as i16;
+        let first = dictionary + 4 + num_separators;
+        for idx in 0..u32::from(num_entries.unsigned_abs()) {
+            let addr = first + idx * entry_len;
+            let mut matches = true;
+            for (offset, byte) in encoded.iter().enumerate() {
+                if self.read_byte(addr + offset as u32)? != *byte {
+                    matches = false;
+                    break;
+                }
+            }
+            if matches {
+                return Ok(addr as u16);
+            }
+        }
+        Ok(0)
+    }
+
+    // ----------------------------------------------------------------------
+    // Random numbers
+    // ----------------------------------------------------------------------
+
+    /// The `random` opcode
+    fn random(&mut self, range: i16) -> u16 {
+        if range < 0 {
+            // Predictable mode, for testing
+            self.random_predictable = Some((range.unsigned_abs(), 0));
+            0
+        } else if range == 0 {
+            self.random_predictable = None;
+            0
+        } else if let Some((limit, counter)) = self.random_predictable {
+            let next = if counter >= limit.max(1) {
+                1
+            } else {
+                counter + 1
+            };
+            self.random_predictable = Some((limit, next));
+            (next - 1) % range as u16 + 1
+        } else {
+            // xorshift32
+            let mut x = self.random_state;
+            x ^= x << 13;
+            x ^= x >> 17;
+            x ^= x << 5;
+            self.random_state = x;
+            (x % range as u32) as u16 + 1
+        }
+    }
+
+    // ----------------------------------------------------------------------
+    // Save and restore
+    // ----------------------------------------------------------------------
+
+    /// Save the game. `self.pc` must point at the save instruction's branch.
+    fn save(&mut self, io: &mut dyn Io) -> bool {
+        let dynamic_len = usize::from(self.header_word(0x0E));
+        let scratch = &mut self.scratch;
+        scratch[0..4].copy_from_slice(&Self::SAVE_MAGIC);
+        scratch[4..6].copy_from_slice(&self.memory[0x02..0x04]);
+        scratch[6..8].copy_from_slice(&self.memory[0x1C..0x1E]);
+        scratch[8..12].copy_from_slice(&self.pc.to_be_bytes());
+        scratch[12..14].copy_from_slice(&(self.sp as u16).to_be_bytes());
+        scratch[14..16].copy_from_slice(&(self.frame_count as u16).to_be_bytes());
+        let mut pos = Self::SAVE_HEADER_LEN;
+        for frame in &self.frames[0..self.frame_count] {
+            frame.save(&mut scratch[pos..pos + Frame::SAVED_LEN]);
+            pos += Frame::SAVED_LEN;
+        }
+        for value in &self.stack[0..self.sp] {
+            scratch[pos..pos + 2].copy_from_slice(&value.to_be_bytes());
+            pos += 2;
+        }
+        // Always write the whole scratch area, so save files for a given
+        // story are all the same length
+        scratch[pos..].fill(0);
+        io.save(&[&self.scratch, &self.memory[0..dynamic_len]])
+    }
+
+    /// Restore a saved game.
+    ///
+    /// Returns `Ok(true)` on success, and `Ok(false)` if nothing could be
+    /// loaded. If the file was for some other story, it's too late to carry
+    /// on, and we return an error.
+    fn restore(&mut self, io: &mut dyn Io) -> Result<bool, Error> {
+        let dynamic_len = usize::from(self.header_word(0x0E));
+        // The interpreter controls these, so keep them
+        let release = [self.memory[0x02], self.memory[0x03]];
+        let checksum = [self.memory[0x1C], self.memory[0x1D]];
+        let flags = [self.memory[0x10], self.memory[0x11]];
+        if !io.restore(&mut [&mut self.scratch, &mut self.memory[0..dynamic_len]]) {
+            return Ok(false);
+        }
+        self.memory[0x10..0x12].copy_from_slice(&flags);
+        let header = &self.scratch[0..Self::SAVE_HEADER_LEN];
+        let pc = u32::from_be_bytes([header[8], header[9], header[10], header[11]]);
+        let sp = usize::from(u16::from_be_bytes([header[12], header[13]]));
+        let frame_count = usize::from(u16::from_be_bytes([header[14], header[15]]));
+        if header[0..4] != Self::SAVE_MAGIC
+            || header[4..6] != release
+            || header[6..8] != checksum
+            || sp > Self::STACK_SIZE
+            || frame_count > Self::MAX_FRAMES
+        {
+            // Not one of ours, and we've scribbled on memory, so we can't
+            // carry on.
+            return Err(Error::BadSaveFile);
+        }
+        let mut pos = Self::SAVE_HEADER_LEN;
+        for idx in 0..frame_count {
+            self.frames[idx] = Frame::restore(&self.scratch[pos..pos + Frame::SAVED_LEN]);
+            pos += Frame::SAVED_LEN;
+        }
+        for idx in 0..sp {
+            self.stack[idx] = u16::from_be_bytes([self.scratch[pos], self.scratch[pos + 1]]);
+            pos += 2;
+        }
+        self.pc = pc;
+        self.sp = sp;
+        self.frame_count = frame_count;
+        Ok(true)
+    }
+
+    // ----------------------------------------------------------------------
+    // Instruction execution
+    // ----------------------------------------------------------------------
+
+    /// Fetch the value of an operand
+    fn operand(&mut self, kind: OperandType) -> Result<Option<u16>, Error> {
+        Ok(match kind {
+            OperandType::Large => Some(self.next_word()?),
+            OperandType::Small => Some(u16::from(self.next_byte()?)),
+            OperandType::Variable => {
+                let var = self.next_byte()?;
+                Some(self.read_var(var)?)
+            }
+            OperandType::Omitted => None,
+        })
+    }
+
+    /// Execute one instruction
+    fn step(&mut self, io: &mut dyn Io) -> Result<Option<Exit>, Error> {
+        let start_pc = self.pc;
+        let opcode_byte = self.next_byte()?;
+        let mut operands = [0u16; 4];
+        let mut count = 0;
+
+        let (form, opcode) = match opcode_byte {
+            0x00..=0x7F => {
+                // Long form, always two operands
+                for (idx, bit) in [0x40, 0x20].iter().enumerate() {
+                    let kind = if opcode_byte & bit != 0 {
+                        OperandType::Variable
+                    } else {
+                        OperandType::Small
+                    };
+                    operands[idx] = self.operand(kind)?.unwrap_or(0);
+                }
+                count = 2;
+                (Form::Op2, opcode_byte & 0x1F)
+            }
+            0x80..=0xBF => {
+                let kind = OperandType::from_bits(opcode_byte >> 4);
+                if kind == OperandType::Omitted {
+                    (Form::Op0, opcode_byte & 0x0F)
+                } else {
+                    operands[0] = self.operand(kind)?.unwrap_or(0);
+                    count = 1;
+                    (Form::Op1, opcode_byte & 0x0F)
+                }
+            }
+            0xC0..=0xFF => {
+                let types = self.next_byte()?;
+                for shift in [6, 4, 2, 0] {
+                    match self.operand(OperandType::from_bits(types >> shift))? {
+                        Some(value) => {
+                            operands[count] = value;
+                            count += 1;
+                        }
+                        None => break,
+                    }
+                }
+                let form = if opcode_byte & 0x20 == 0 {
+                    Form::Op2
+                } else {
+                    Form::Var
+                };
+                (form, opcode_byte & 0x1F)
+            }
+        };
+        let bad = Error::BadOpcode(opcode_byte, start_pc);
+        let [a, b, c, _] = operands;
+
+        match (form, opcode) {
+            // 2OP
+            (Form::Op2, 0x01) => {
+                // je can take up to four operands
+                let result = operands[1..count].contains(&a);
+                self.branch(result)?;
+            }
+            (Form::Op2, 0x02) => self.branch((a as i16) < (b as i16))?,
+            (Form::Op2, 0x03) => self.branch((a as i16) > (b as i16))?,
+            (Form::Op2, 0x04) => {
+                let value = (self.peek_var(a as u8)? as i16).wrapping_sub(1);
+                self.poke_var(a as u8, value as u16)?;
+                self.branch(value < b as i16)?;
+            }
+            (Form::Op2, 0x05) => {
+                let value = (self.peek_var(a as u8)? as i16).wrapping_add(1);
+                self.poke_var(a as u8, value as u16)?;
+                self.branch(value > b as i16)?;
+            }
+            (Form::Op2, 0x06) => {
+                let parent = self.relative(a, Self::PARENT)?;
+                self.branch(parent == b)?;
+            }
+            (Form::Op2, 0x07) => self.branch(a & b == b)?,
+            (Form::Op2, 0x08) => self.store(a | b)?,
+            (Form::Op2, 0x09) => self.store(a & b)?,
+            (Form::Op2, 0x0A) => {
+                let (addr, mask) = self.attr_location(a, b)?;
+                let set = self.read_byte(addr)? & mask != 0;
+                self.branch(set)?;
+            }
+            (Form::Op2, 0x0B) => {
+                let (addr, mask) = self.attr_location(a, b)?;
+                let value = self.read_byte(addr)? | mask;
+                self.write_byte(addr, value)?;
+            }
+            (Form::Op2, 0x0C) => {
+                let (addr, mask) = self.attr_location(a, b)?;
+                let value = self.read_byte(addr)? & !mask;
+                self.write_byte(addr, value)?;
+            }
+            (Form::Op2, 0x0D) => self.poke_var(a as u8, b)?,
+            (Form::Op2, 0x0E) => {
+                self.remove_object(a)?;
+                let first = self.relative(b, Self::CHILD)?;
+                self.set_relative(a, Self::SIBLING, first)?;
+                self.set_relative(a, Self::PARENT, b)?;
+                self.set_relative(b, Self::CHILD, a)?;
+            }
+            (Form::Op2, 0x0F) => {
+                let value = self.read_word(u32::from(a.wrapping_add(b.wrapping_mul(2))))?;
+                self.store(value)?;
+            }
+            (Form::Op2, 0x10) => {
+                let value = self.read_byte(u32::from(a.wrapping_add(b)))?;
+                self.store(u16::from(value))?;
+            }
+            (Form::Op2, 0x11) => {
+                let value = match self.find_property(a, b)? {
+                    Some((addr, 1)) => u16::from(self.read_byte(addr + 1)?),
+                    Some((addr, _)) => self.read_word(addr + 1)?,
+                    None => {
+                        let defaults = u32::from(self.header_word(0x0A));
+                        self.read_word(defaults + 2 * u32::from(b.saturating_sub(1)))?
+                    }
+                };
+                self.store(value)?;
+            }
+            (Form::Op2, 0x12) => {
+                let value = match self.find_property(a, b)? {
+                    Some((addr, _)) => (addr + 1) as u16,
+                    None => 0,
+                };
+                self.store(value)?;
+            }
+            (Form::Op2, 0x13) => {
+                let next_addr = if b == 0 {
+                    Some(self.first_property(a)?)
+                } else {
+                    self.find_property(a, b)?
+                        .map(|(addr, len)| addr + 1 + u32::from(len))
+                };
+                let value = match next_addr {
+                    Some(addr) => u16::from(self.read_byte(addr)? & 0x1F),
+                    None => 0,
+                };
+                self.store(value)?;
+            }
+            (Form::Op2, 0x14) => self.store((a as i16).wrapping_add(b as i16) as u16)?,
+            (Form::Op2, 0x15) => self.store((a as i16).wrapping_sub(b as i16) as u16)?,
+            (Form::Op2, 0x16) => self.store((a as i16).wrapping_mul(b as i16) as u16)?,
+            (Form::Op2, 0x17) => {
+                if b == 0 {
+                    return Err(Error::DivideByZero);
+                }
+                self.store((a as i16).wrapping_div(b as i16) as u16)?;
+            }
+            (Form::Op2, 0x18) => {
+                if b == 0 {
+                    return Err(Error::DivideByZero);
+                }
+                self.store((a as i16).wrapping_rem(b as i16) as u16)?;
+            }
+
+            // 1OP
+            (Form::Op1, 0x00) => self.branch(a == 0)?,
+            (Form::Op1, 0x01) => {
+                let value = self.relative(a, Self::SIBLING)?;
+                self.store(value)?;
+                self.branch(value != 0)?;
+            }
+            (Form::Op1, 0x02) => {
+                let value = self.relative(a, Self::CHILD)?;
+                self.store(value)?;
+                self.branch(value != 0)?;
+            }
+            (Form::Op1, 0x03) => {
+                let value = self.relative(a, Self::PARENT)?;
+                self.store(value)?;
+            }
+            (Form::Op1, 0x04) => {
+                let value = if a == 0 {
+                    0
+                } else {
+                    u16::from(self.read_byte(u32::from(a) - 1)? >> 5) + 1
+                };
+                self.store(value)?;
+            }
+            (Form::Op1, 0x05) => {
+                let value = self.peek_var(a as u8)?.wrapping_add(1);
+                self.poke_var(a as u8, value)?;
+            }
+            (Form::Op1, 0x06) => {
+                let value = self.peek_var(a as u8)?.wrapping_sub(1);
+                self.poke_var(a as u8, value)?;
+            }
+            (Form::Op1, 0x07) => {
+                self.print_string(u32::from(a), io)?;
+            }
+            (Form::Op1, 0x09) => self.remove_object(a)?,
+            (Form::Op1, 0x0A) => self.print_object(a, io)?,
+            (Form::Op1, 0x0B) => self.ret(a)?,
+            (Form::Op1, 0x0C) => {
+                self.pc = (self.pc as i32 + i32::from(a as i16) - 2) as u32;
+            }
+            (Form::Op1, 0x0D) => {
+                self.print_string(u32::from(a) * 2, io)?;
+            }
+            (Form::Op1, 0x0E) => {
+                let value = self.peek_var(a as u8)?;
+                self.store(value)?;
+            }
+            (Form::Op1, 0x0F) => self.store(!a)?,
+
+            // 0OP
+            (Form::Op0, 0x00) => self.ret(1)?,
+            (Form::Op0, 0x01) => self.ret(0)?,
+            (Form::Op0, 0x02) => {
+                self.pc = self.print_string(self.pc, io)?;
+            }
+            (Form::Op0, 0x03) => {
+                self.pc = self.print_string(self.pc, io)?;
+                self.print_char(b'\n', io);
+                self.ret(1)?;
+            }
+            (Form::Op0, 0x04) => {}
+            (Form::Op0, 0x05) => {
+                let ok = self.save(io);
+                self.branch(ok)?;
+            }
+            (Form::Op0, 0x06) => {
+                // If this works, we carry on from where the save was made,
+                // which is at a branch instruction
+                let ok = self.restore(io)?;
+                self.branch(ok)?;
+            }
+            (Form::Op0, 0x07) => return Ok(Some(Exit::Restart)),
+            (Form::Op0, 0x08) => {
+                let value = self.pop()?;
+                self.ret(value)?;
+            }
+            (Form::Op0, 0x09) => {
+                self.pop()?;
+            }
+            (Form::Op0, 0x0A) => return Ok(Some(Exit::Quit)),
+            (Form::Op0, 0x0B) => self.print_char(b'\n', io),
+            (Form::Op0, 0x0C) => self.show_status(io)?,
+            (Form::Op0, 0x0D) => {
+                // We don't keep a pristine copy of the story to check the
+                // dynamic memory against, so we just say it's fine.
+                self.branch(true)?;
+            }
+
+            // VAR
+            (Form::Var, 0x00) => {
+                let store = self.next_byte()?;
+                self.call(a, &operands[1..count.max(1)], Some(store))?;
+            }
+            (Form::Var, 0x01) => {
+                self.write_word(u32::from(a.wrapping_add(b.wrapping_mul(2))), c)?
+            }
+            (Form::Var, 0x02) => self.write_byte(u32::from(a.wrapping_add(b)), c as u8)?,
+            (Form::Var, 0x03) => match self.find_property(a, b)? {
+                Some((addr, 1)) => self.write_byte(addr + 1, c as u8)?,
+                Some((addr, _)) => self.write_word(addr + 1, c)?,
+                None => return Err(bad),
+            },
+            (Form::Var, 0x04) => self.read(a, b, io)?,
+            (Form::Var, 0x05) => self.print_char(a as u8, io),
+            (Form::Var, 0x06) => self.print_number(a as i16, io),
+            (Form::Var, 0x07) => {
+                let value = self.random(a as i16);
+                self.store(value)?;
+            }
+            (Form::Var, 0x08) => self.push(a)?,
+            (Form::Var, 0x09) => {
+                let value = self.pop()?;
+                self.poke_var(a as u8, value)?;
+            }
+            (Form::Var, 0x0A) | (Form::Var, 0x0B) => {
+                // split_window and set_window - we only have the one window
+            }
+            (Form::Var, 0x13) => match a as i16 {
+                3 => self.stream3 = Some(usize::from(b)),
+                -3 => self.stream3 = None,
+                _ => {
+                    // Transcripts are not supported
+                }
+            },
+            (Form::Var, 0x14) | (Form::Var, 0x15) => {
+                // input_stream and sound_effect - not supported
+            }
+            _ => return Err(bad),
+        }
+        Ok(None)
+    }
+}
+
+impl Default for Machine {
+    fn default() -> Self {
+        Machine::new()
+    }
+}
@@ -0,0 +1,56 @@
+#![cfg_attr(target_os = "none", no_std)]
+#![cfg_attr(target_os = "none", no_main)]
+
+use core::{fmt::Write, ptr::addr_of_mut};
+
+use zmachine::machine::{Exit, Machine};
+
+static mut MACHINE: Machine = Machine::new();
+
+#[cfg(not(target_os = "none"))]
+fn main() {
+    neotron_sdk::init();
+}
+
+#[no_mangle]
+extern "C" fn neotron_main() -> i32 {
+    neotron_error::exit_code(real_main())
+}
+
+fn real_main() -> Result<(), neotron_sdk::Error> {
+    let mut stdout = neotron_sdk::stdout();
+    let Some(filename) = neotron_sdk::arg(0) else {
+        let _ = writeln!(stdout, "Usage: zmachine <story.z3>");
+        return Err(neotron_sdk::Error::InvalidArg);
+    };
+    let machine = unsafe { &mut *addr_of_mut!(MACHINE) };
+    let mut console = zmachine::Console::new(80, 25, &filename);
+
+    loop {
+        // We (re)load the story every time, as the game changes its memory
+        let _ = writeln!(stdout, "Loading {:?}...", filename);
+        let path = neotron_sdk::path::Path::new(&filename)?;
+        let f = neotron_sdk::File::open(path, neotron_sdk::Flags::empty())?;
+        let n = f.read(machine.story_buffer())?;
+        drop(f);
+
+        let seed = console.start();
+        if let Err(e) = machine.start(n, seed) {
+            console.finish();
+            let _ = writeln!(stdout, "Can't play {:?}: {:?}", filename, e);
+            return Err(neotron_sdk::Error::InvalidArg);
+        }
+        match machine.run(&mut console) {
+            Ok(Exit::Quit) => break,
+            Ok(Exit::Restart) => continue,
+            Err(e) => {
+                console.finish();
+                let _ = writeln!(stdout, "Story stopped: {:?}", e);
+                return Err(neotron_sdk::Error::DeviceSpecific);
+            }
+        }
+    }
+
+    console.finish();
+    Ok(())
+}
@@ -0,0 +1,133 @@
+//! Z-machine text encoding and decoding.
+//!
+//! Text is stored as 5-bit Z-characters, packed three to a 16-bit word. The
+//! top bit of the last word is set. Z-characters 6 to 31 select a letter from
+//! one of three alphabets, 4 and 5 shift to the next alphabet for one
+//! character, and 1 to 3 insert one of the 96 abbreviations.
+
+/// Alphabet A2, for version 3. Z-character 6 in this alphabet is the escape
+/// for a 10-bit ZSCII code, and 7 is a newline.
+const ALPHABET_2: &[u8; 26] = b" \n0123456789.,!?_#'\"/\\-:()";
+
+/// How many Z-characters are in each dictionary word, in version 3
+pub const DICT_ZCHARS: usize = 6;
+
+/// How many bytes each encoded dictionary word takes, in version 3
+pub const DICT_BYTES: usize = DICT_ZCHARS / 3 * 2;
+
+/// Read a big-endian word from memory, or zero if it's off the end
+fn word_at(mem: &[u8], addr: usize) -> u16 {
+    match mem.get(addr..addr + 2) {
+        Some(bytes) => u16::from_be_bytes([bytes[0], bytes[1]]),
+        None => 0x8000,
+    }
+}
+
+/// Decode the string at `addr`, passing each ZSCII character to `out`.
+///
+/// Returns the address just past the end of the string.
+pub fn decode(mem: &[u8], addr: usize, out: &mut dyn FnMut(u8)) -> usize {
+    decode_inner(mem, addr, out, true)
+}
+
+/// Decode a string, optionally allowing abbreviations (which can't nest)
+fn decode_inner(mem: &[u8], mut addr: usize, out: &mut dyn FnMut(u8), allow_abbrev: bool) -> usize {
+    let abbrev_table = usize::from(word_at(mem, 0x18));
+    let mut alphabet = 0;
+    // Set when we're part way through an abbreviation or a 10-bit code
+    let mut abbrev: Option<u8> = None;
+    let mut escape: Option<Option<u8>> = None;
+    loop {
+        let word = word_at(mem, addr);
+        addr += 2;
+        for shift in [10, 5, 0] {
+            let zchar = ((word >> shift) & 0x1F) as u8;
+            if let Some(bank) = abbrev.take() {
+                let idx = usize::from(32 * (bank - 1) + zchar);
+                let string = usize::from(word_at(mem, abbrev_table + idx * 2)) * 2;
+                decode_inner(mem, string, out, false);
+                alphabet = 0;
+                continue;
+            }
+            match escape {
+                Some(None) => {
+                    escape = Some(Some(zchar));
+                    continue;
+                }
+                Some(Some(high)) => {
+                    out((high << 5) | zchar);
+                    escape = None;
+                    continue;
+                }
+                None => {}
+            }
+            match (zchar, alphabet) {
+                (0, _) => {
+                    out(b' ');
+                    alphabet = 0;
+                }
+                (1..=3, _) => {
+                    if allow_abbrev {
+                        abbrev = Some(zchar);
+                    }
+                    alphabet = 0;
+                }
+                (4, _) => alphabet = 1,
+                (5, _) => alphabet = 2,
+                (6, 2) => {
+                    escape = Some(None);
+                    alphabet = 0;
+                }
+                (_, 0) => out(b'a' + zchar - 6),
+                (_, 1) => {
+                    out(b'A' + zchar - 6);
+                    alphabet = 0;
+                }
+                (_, _) => {
+                    out(ALPHABET_2[usize::from(zchar - 6)]);
+                    alphabet = 0;
+                }
+            }
+        }
+        if word & 0x8000 != 0 {
+            return addr;
+        }
+    }
+}
+
+/// Encode a word (already in lower case) for looking up in the dictionary
+pub fn encode(word: &[u8]) -> [u8; DICT_BYTES] {
+    let mut zchars = [5u8; DICT_ZCHARS];
+    let mut len = 0;
+    for &ch in word {
+        let mut push = |z: u8| {
+            if len < DICT_ZCHARS {
+                zchars[len] = z;
+                len += 1;
+            }
+        };
+        if ch.is_ascii_lowercase() {
+            push(ch - b'a' + 6);
+        } else if let Some(idx) = ALPHABET_2.iter().skip(2).position(|&c| c == ch) {
+            push(5);
+            push(idx as u8 + 8);
+        } else {
+            push(5);
+            push(6);
+            push(ch >> 5);
+            push(ch & 0x1F);
+        }
+        if len == DICT_ZCHARS {
+            break;
+        }
+    }
+    let mut result = [0u8; DICT_BYTES];
+    for (idx, chunk) in zchars.chunks_exact(3).enumerate() {
+        let mut word = u16::from(chunk[0]) << 10 | u16::from(chunk[1]) << 5 | u16::from(chunk[2]);
+        if idx == DICT_ZCHARS / 3 - 1 {
+            word |= 0x8000;
+        }
+        result[idx * 2..idx * 2 + 2].copy_from_slice(&word.to_be_bytes());
+    }
+    result
+}